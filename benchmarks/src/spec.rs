@@ -268,8 +268,10 @@ impl WorkloadSpec {
 
                 let create_cache_query = nom_sql::CreateCacheStatement {
                     name: None,
+                    options: vec![],
                     inner: Ok(nom_sql::CacheInner::Statement(Box::new(stmt))),
                     always: false,
+                    warmup: None,
                 };
 
                 let _ = conn
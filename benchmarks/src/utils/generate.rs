@@ -226,6 +226,7 @@ fn query_for_prepared_insert(
                 .collect(),
         },
         ignore: false,
+        on_conflict: None,
         on_duplicate: None,
     }
     .display(dialect)
@@ -383,6 +384,7 @@ pub async fn load_to_backend(db: &mut Backend, mut spec: DatabaseGenerationSpec)
                 })
                 .collect(),
             ignore: false,
+            on_conflict: None,
             on_duplicate: None,
         };
 
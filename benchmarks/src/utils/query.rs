@@ -211,8 +211,10 @@ impl ArbitraryQueryParameters {
 
         let create_cache_query = nom_sql::CreateCacheStatement {
             name: Some("q".into()),
+            options: vec![],
             inner: Ok(nom_sql::CacheInner::Statement(Box::new(stmt))),
             always: false,
+            warmup: None,
         };
 
         conn.query_drop(create_cache_query.display(conn.dialect()).to_string())
@@ -241,6 +241,7 @@ impl MultithreadBenchmark for WriteBenchmark {
                         })
                         .collect(),
                     ignore: false,
+                    on_conflict: None,
                     on_duplicate: None,
                 }
             };
@@ -11,7 +11,7 @@ use rand::distributions::Standard;
 use rand::prelude::Distribution;
 use rand::seq::SliceRandom;
 use rand::{thread_rng, Rng, RngCore};
-use readyset_data::{DfType, DfValue, Dialect};
+use readyset_data::{DfType, DfValue, Dialect, Interval};
 use rust_decimal::Decimal;
 use zipf::ZipfDistribution;
 
@@ -456,7 +456,7 @@ pub fn value_of_type(typ: &SqlType) -> DfValue {
         SqlType::Time => NaiveTime::from_hms(12, 30, 45).into(),
         SqlType::Date => NaiveDate::from_ymd(2020, 1, 1).into(),
         SqlType::Bool => 1i32.into(),
-        SqlType::Enum(_) => unimplemented!(),
+        SqlType::Enum(variants) => variants.first().cloned().unwrap_or_default().into(),
         SqlType::Json | SqlType::Jsonb => "{}".into(),
         SqlType::MacAddr => "01:23:45:67:89:AF".into(),
         SqlType::Inet => "::beef".into(),
@@ -465,8 +465,10 @@ pub fn value_of_type(typ: &SqlType) -> DfValue {
             DfValue::from(BitVec::with_capacity(size_opt.unwrap_or(1) as usize))
         }
         SqlType::VarBit(_) => DfValue::from(BitVec::new()),
+        SqlType::Interval => Interval::new(0, 1, 0).into(),
         SqlType::Array(_) => unimplemented!(),
         SqlType::Other(_) => unimplemented!(),
+        SqlType::Spatial(_) => unimplemented!(),
     }
 }
 
@@ -556,7 +558,11 @@ where
             NaiveDate::from_ymd(2020, rng.gen_range(1..12), rng.gen_range(1..28)).into()
         }
         SqlType::Bool => DfValue::from(rng.gen_bool(0.5)),
-        SqlType::Enum(_) => unimplemented!(),
+        SqlType::Enum(variants) => variants
+            .choose(&mut rng)
+            .cloned()
+            .unwrap_or_default()
+            .into(),
         SqlType::Json | SqlType::Jsonb => DfValue::from(format!(
             "{{\"k\":\"{}\"}}",
             "a".repeat(rng.gen_range(1..255))
@@ -596,8 +602,15 @@ where
         }
         SqlType::Serial => ((rng.gen::<u32>() + 1) as i32).into(),
         SqlType::BigSerial => ((rng.gen::<u64>() + 1) as i64).into(),
+        SqlType::Interval => Interval::new(
+            rng.gen_range(0..12),
+            rng.gen_range(0..31),
+            rng.gen_range(0..86_400_000_000),
+        )
+        .into(),
         SqlType::Array(_) => unimplemented!(),
         SqlType::Other(_) => unimplemented!(),
+        SqlType::Spatial(_) => unimplemented!(),
     }
 }
 
@@ -720,7 +733,10 @@ pub fn unique_value_of_type(typ: &SqlType, idx: u32) -> DfValue {
         }
         SqlType::Serial => ((idx + 1) as i32).into(),
         SqlType::BigSerial => ((idx + 1) as i64).into(),
+        // Intervals compare by their total length, so only vary one component
+        SqlType::Interval => Interval::new(0, 0, i64::from(idx) * 1_000_000).into(),
         SqlType::Array(_) => unimplemented!(),
         SqlType::Other(_) => unimplemented!(),
+        SqlType::Spatial(_) => unimplemented!(),
     }
 }
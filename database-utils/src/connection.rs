@@ -346,6 +346,8 @@ impl DatabaseStatement {
                         &Type::NUMERIC => SqlType::Numeric(None),
                         &Type::UUID => SqlType::Uuid,
                         &Type::UUID_ARRAY => SqlType::Array(Box::new(SqlType::Uuid)),
+                        &Type::INTERVAL => SqlType::Interval,
+                        &Type::INTERVAL_ARRAY => SqlType::Array(Box::new(SqlType::Interval)),
                         &Type::JSONB => SqlType::Jsonb,
                         &Type::JSONB_ARRAY => SqlType::Array(Box::new(SqlType::Jsonb)),
                         t => unimplemented!("Unsupported type: {:?}", t),
@@ -74,3 +74,15 @@ pub struct DatabaseTypeParseError {
     /// The value that was originally being parsed
     pub value: String,
 }
+
+/// Error type for the [`FromStr`] implementation for [`SecretSource`]
+///
+/// [`SecretSource`]: crate::secrets::SecretSource
+#[derive(Debug, Error)]
+#[error("Invalid secret source `{value}`: {reason}")]
+pub struct SecretSourceParseError {
+    /// The value that was originally being parsed
+    pub value: String,
+    /// Why the value could not be parsed
+    pub reason: String,
+}
@@ -11,15 +11,18 @@ use derive_more::From;
 use error::DatabaseTypeParseError;
 use mysql_async::OptsBuilder;
 use native_tls::TlsConnectorBuilder;
+use readyset_client::consensus::AuthorityType;
 use readyset_errors::{ReadySetError, ReadySetResult};
 use readyset_util::redacted::RedactedString;
 use serde::{Deserialize, Serialize};
 use {mysql_async as mysql, tokio_postgres as pgsql};
 
 use crate::error::DatabaseURLParseError;
+use crate::secrets::{SecretSource, WatchedSecret};
 
 mod connection;
 pub mod error;
+pub mod secrets;
 
 pub use connection::{
     DatabaseConnection, DatabaseStatement, QueryResults, QueryableConnection, Transaction,
@@ -35,6 +38,25 @@ pub struct UpstreamConfig {
     #[serde(default)]
     pub upstream_db_url: Option<RedactedString>,
 
+    /// Read the URL for the upstream database from a secret, rather than passing it with
+    /// `--upstream-db-url`. One of `file:<path>`, `vault:<path>#<field>` (using `VAULT_ADDR` and
+    /// `VAULT_TOKEN`), or `aws-secretsmanager:<secret id>[#<field>]`. The secret is re-read every
+    /// `--secret-refresh-interval` seconds, and replication reconnects to the upstream database if
+    /// it changes.
+    #[clap(
+        long,
+        env = "UPSTREAM_DB_URL_SECRET",
+        conflicts_with = "upstream_db_url"
+    )]
+    #[serde(default)]
+    pub upstream_db_url_secret: Option<SecretSource>,
+
+    /// How often (in seconds) to re-read secrets passed with `--upstream-db-url-secret`, to
+    /// detect when they've been rotated.
+    #[clap(long, env = "SECRET_REFRESH_INTERVAL", default_value = "60", value_parser = duration_from_seconds)]
+    #[serde(default = "default_secret_refresh_interval")]
+    pub secret_refresh_interval: Duration,
+
     /// Disable verification of SSL certificates supplied by the upstream database (postgres
     /// only, ignored for mysql). Ignored if `--upstream-db-url` is not passed.
     ///
@@ -73,6 +95,20 @@ pub struct UpstreamConfig {
     #[serde(default = "default_replicator_restart_timeout")]
     pub replicator_restart_timeout: Duration,
 
+    /// How often (in seconds) to probe the upstream database to detect failovers - the upstream
+    /// database URL pointing at a different server, for example because its DNS name was
+    /// repointed at a new writer, or the server becoming read-only. When a failover is detected,
+    /// replication reconnects once the URL points at a writable server. A value of 0 disables
+    /// probing.
+    #[clap(
+        long,
+        env = "UPSTREAM_FAILOVER_PROBE_INTERVAL",
+        default_value = "5",
+        value_parser = duration_from_seconds
+    )]
+    #[serde(default = "default_failover_probe_interval")]
+    pub failover_probe_interval: Duration,
+
     #[clap(long, env = "REPLICATION_TABLES")]
     #[serde(default)]
     pub replication_tables: Option<RedactedString>,
@@ -87,6 +123,37 @@ pub struct UpstreamConfig {
     #[clap(long, default_value = "50")]
     #[serde(default)]
     pub replication_pool_size: usize,
+
+    /// Address of the authority of a standby ReadySet deployment to ship all writes to base tables
+    /// to, to maintain a warm standby of this deployment (eg in another region) for disaster
+    /// recovery. The standby must be run with `--standby`, and with the same `--http-auth-token`
+    /// and `--internal-tls-*` options as this deployment.
+    #[clap(
+        long,
+        env = "STANDBY_AUTHORITY_ADDRESS",
+        requires = "standby_deployment"
+    )]
+    #[serde(default)]
+    pub standby_authority_address: Option<String>,
+
+    /// Deployment name of the standby ReadySet deployment to ship writes to. Ignored if
+    /// `--standby-authority-address` is not passed.
+    #[clap(long, env = "STANDBY_DEPLOYMENT")]
+    #[serde(default)]
+    pub standby_deployment: Option<String>,
+
+    /// The type of authority used by the standby ReadySet deployment to ship writes to.
+    #[clap(long, env = "STANDBY_AUTHORITY", value_enum, default_value = "consul")]
+    #[serde(default = "default_standby_authority")]
+    pub standby_authority: AuthorityType,
+
+    /// Run this deployment as a warm standby of another deployment, which ships writes to base
+    /// tables to this deployment. If this deployment has not yet been snapshotted, it first takes
+    /// a snapshot of the upstream database, after which it stops replicating from the upstream
+    /// database until it is promoted.
+    #[clap(long, env = "STANDBY")]
+    #[serde(default)]
+    pub standby: bool,
 }
 
 impl UpstreamConfig {
@@ -108,6 +175,26 @@ impl UpstreamConfig {
         }
     }
 
+    /// If [`Self::upstream_db_url_secret`] is set, read the upstream database URL from it into
+    /// [`Self::upstream_db_url`]
+    pub async fn resolve_secrets(&mut self) -> ReadySetResult<()> {
+        if let Some(secret) = &self.upstream_db_url_secret {
+            self.upstream_db_url = Some(secret.fetch().await?);
+        }
+        Ok(())
+    }
+
+    /// If [`Self::upstream_db_url_secret`] is set, start periodically re-reading the upstream
+    /// database URL from it, and set [`Self::upstream_db_url`] to its current value
+    pub async fn watch_upstream_db_url(&mut self) -> ReadySetResult<Option<WatchedSecret>> {
+        let Some(secret) = &self.upstream_db_url_secret else {
+            return Ok(None);
+        };
+        let watched = WatchedSecret::new(secret.clone(), self.secret_refresh_interval).await?;
+        self.upstream_db_url = Some(watched.current());
+        Ok(Some(watched))
+    }
+
     pub fn from_url<S: AsRef<str>>(url: S) -> Self {
         UpstreamConfig {
             upstream_db_url: Some(url.as_ref().to_string().into()),
@@ -120,10 +207,22 @@ fn default_replicator_restart_timeout() -> Duration {
     UpstreamConfig::default().replicator_restart_timeout
 }
 
+fn default_failover_probe_interval() -> Duration {
+    UpstreamConfig::default().failover_probe_interval
+}
+
+fn default_secret_refresh_interval() -> Duration {
+    UpstreamConfig::default().secret_refresh_interval
+}
+
 fn default_snapshot_report_interval_secs() -> u16 {
     UpstreamConfig::default().snapshot_report_interval_secs
 }
 
+fn default_standby_authority() -> AuthorityType {
+    UpstreamConfig::default().standby_authority
+}
+
 fn duration_from_seconds(i: &str) -> Result<Duration, ParseIntError> {
     i.parse::<u64>().map(Duration::from_secs)
 }
@@ -132,14 +231,21 @@ impl Default for UpstreamConfig {
     fn default() -> Self {
         Self {
             upstream_db_url: Default::default(),
+            upstream_db_url_secret: None,
+            secret_refresh_interval: Duration::from_secs(60),
             disable_upstream_ssl_verification: false,
             disable_setup_ddl_replication: false,
             replication_server_id: Default::default(),
             replicator_restart_timeout: Duration::from_secs(1),
+            failover_probe_interval: Duration::from_secs(5),
             replication_tables: Default::default(),
             snapshot_report_interval_secs: 30,
             ssl_root_cert: None,
             replication_pool_size: 50,
+            standby_authority_address: None,
+            standby_deployment: None,
+            standby_authority: AuthorityType::Consul,
+            standby: false,
         }
     }
 }
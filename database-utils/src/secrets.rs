@@ -0,0 +1,558 @@
+//! Reading credentials from secret stores rather than from command-line flags or environment
+//! variables, where they would be visible in process listings.
+//!
+//! A [`SecretSource`] is written as `<scheme>:<location>`, with the following schemes supported:
+//!
+//! * `file:<path>` reads the secret from a file, such as a Docker or Kubernetes secret mount. A
+//!   single trailing newline is stripped from the contents of the file.
+//! * `vault:<path>#<field>` reads the given field of a secret from HashiCorp Vault, using the
+//!   address in `VAULT_ADDR` and the token in `VAULT_TOKEN`. Both the v1 and v2 key/value secrets
+//!   engines are supported - for v2 the path should include `data/`, eg
+//!   `vault:secret/data/readyset#upstream_db_url`.
+//! * `aws-secretsmanager:<secret id>[#<field>]` reads a secret from AWS Secrets Manager, in the
+//!   region given by `AWS_REGION` or `AWS_DEFAULT_REGION`. If a field is given, the secret string
+//!   is parsed as a JSON object and that field is read from it. Credentials are read from
+//!   `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN`, falling back to the
+//!   instance profile of the EC2 instance we're running on.
+//!
+//! Since secrets can be rotated out from under a running process, a [`WatchedSecret`] periodically
+//! re-reads a secret in the background so that new connections can use its latest value.
+
+use std::fmt::{self, Display};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use readyset_errors::{ReadySetError, ReadySetResult};
+use readyset_util::redacted::RedactedString;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::error::SecretSourceParseError;
+
+/// How long to wait for a response from a remote secret store
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Address of the EC2 instance metadata service, used to look up instance profile credentials
+const IMDS_ADDRESS: &str = "http://169.254.169.254";
+
+/// A location to read a secret from. See the [module documentation](self) for the syntax.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum SecretSource {
+    /// The contents of a file
+    File(PathBuf),
+    /// A field of a secret stored in HashiCorp Vault
+    Vault {
+        /// The path of the secret, not including the `/v1/` API prefix
+        path: String,
+        /// The field within the secret to read
+        field: String,
+    },
+    /// A secret stored in AWS Secrets Manager
+    AwsSecretsManager {
+        /// The name or ARN of the secret
+        secret_id: String,
+        /// The field to read, if the secret string is a JSON object
+        field: Option<String>,
+    },
+}
+
+impl FromStr for SecretSource {
+    type Err = SecretSourceParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| SecretSourceParseError {
+            value: s.to_owned(),
+            reason: reason.to_owned(),
+        };
+        let (scheme, location) = s
+            .split_once(':')
+            .ok_or_else(|| invalid("expected <scheme>:<location>"))?;
+        if location.is_empty() {
+            return Err(invalid("location must not be empty"));
+        }
+
+        match scheme {
+            "file" => Ok(Self::File(location.into())),
+            "vault" => {
+                let (path, field) = location
+                    .rsplit_once('#')
+                    .filter(|(path, field)| !path.is_empty() && !field.is_empty())
+                    .ok_or_else(|| {
+                        invalid("vault secrets must be written as vault:<path>#<field>")
+                    })?;
+                Ok(Self::Vault {
+                    path: path.trim_matches('/').to_owned(),
+                    field: field.to_owned(),
+                })
+            }
+            "aws-secretsmanager" => {
+                let (secret_id, field) = match location.rsplit_once('#') {
+                    Some((secret_id, field)) if !secret_id.is_empty() && !field.is_empty() => {
+                        (secret_id, Some(field.to_owned()))
+                    }
+                    Some(_) => return Err(invalid("secret ID and field must not be empty")),
+                    None => (location, None),
+                };
+                Ok(Self::AwsSecretsManager {
+                    secret_id: secret_id.to_owned(),
+                    field,
+                })
+            }
+            _ => Err(invalid(
+                "unknown scheme, expected one of `file`, `vault`, or `aws-secretsmanager`",
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for SecretSource {
+    type Error = SecretSourceParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<SecretSource> for String {
+    fn from(source: SecretSource) -> Self {
+        source.to_string()
+    }
+}
+
+impl Display for SecretSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretSource::File(path) => write!(f, "file:{}", path.display()),
+            SecretSource::Vault { path, field } => write!(f, "vault:{path}#{field}"),
+            SecretSource::AwsSecretsManager { secret_id, field } => {
+                write!(f, "aws-secretsmanager:{secret_id}")?;
+                if let Some(field) = field {
+                    write!(f, "#{field}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl SecretSource {
+    /// Read the current value of the secret
+    pub async fn fetch(&self) -> ReadySetResult<RedactedString> {
+        let value = match self {
+            SecretSource::File(path) => {
+                let mut contents = tokio::fs::read_to_string(path).await.map_err(|e| {
+                    ReadySetError::IOError(format!(
+                        "Could not read secret from {}: {e}",
+                        path.display()
+                    ))
+                })?;
+                if contents.ends_with('\n') {
+                    contents.pop();
+                    if contents.ends_with('\r') {
+                        contents.pop();
+                    }
+                }
+                contents
+            }
+            SecretSource::Vault { path, field } => self.fetch_vault(path, field).await?,
+            SecretSource::AwsSecretsManager { secret_id, field } => {
+                self.fetch_aws(secret_id, field.as_deref()).await?
+            }
+        };
+        Ok(value.into())
+    }
+
+    fn request_failed<E: Display>(&self, message: E) -> ReadySetError {
+        ReadySetError::HttpRequestFailed {
+            request: self.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    async fn fetch_vault(&self, path: &str, field: &str) -> ReadySetResult<String> {
+        let addr = std::env::var("VAULT_ADDR").map_err(|_| {
+            self.request_failed("VAULT_ADDR must be set to read secrets from Vault")
+        })?;
+        let token = std::env::var("VAULT_TOKEN").map_err(|_| {
+            self.request_failed("VAULT_TOKEN must be set to read secrets from Vault")
+        })?;
+
+        let response: serde_json::Value = reqwest::Client::new()
+            .get(format!("{}/v1/{path}", addr.trim_end_matches('/')))
+            .header("X-Vault-Token", token)
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| self.request_failed(e))?
+            .json()
+            .await
+            .map_err(|e| self.request_failed(e))?;
+
+        // The v2 key/value engine nests the secret's fields one level deeper than v1 does
+        let data = &response["data"];
+        data["data"][field]
+            .as_str()
+            .or_else(|| data[field].as_str())
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| self.request_failed(format!("secret has no string field `{field}`")))
+    }
+
+    async fn fetch_aws(&self, secret_id: &str, field: Option<&str>) -> ReadySetResult<String> {
+        let region = std::env::var("AWS_REGION")
+            .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+            .map_err(|_| {
+                self.request_failed(
+                    "AWS_REGION must be set to read secrets from AWS Secrets Manager",
+                )
+            })?;
+        let credentials = AwsCredentials::load()
+            .await
+            .map_err(|e| self.request_failed(format!("could not load AWS credentials: {e}")))?;
+
+        let request = GetSecretValue::new(&region, secret_id);
+        let response: serde_json::Value = reqwest::Client::new()
+            .post(format!("https://{}/", request.host))
+            .headers(request.signed_headers(
+                &credentials,
+                &Utc::now().format("%Y%m%dT%H%M%SZ").to_string(),
+            ))
+            .body(request.body.clone())
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| self.request_failed(e))?
+            .json()
+            .await
+            .map_err(|e| self.request_failed(e))?;
+
+        let secret = response["SecretString"]
+            .as_str()
+            .ok_or_else(|| self.request_failed("secret has no SecretString"))?;
+        match field {
+            None => Ok(secret.to_owned()),
+            Some(field) => serde_json::from_str::<serde_json::Value>(secret)
+                .ok()
+                .and_then(|v| v[field].as_str().map(ToOwned::to_owned))
+                .ok_or_else(|| {
+                    self.request_failed(format!("secret has no string field `{field}`"))
+                }),
+        }
+    }
+}
+
+/// Credentials used to sign requests to AWS
+struct AwsCredentials {
+    access_key_id: String,
+    secret_access_key: RedactedString,
+    session_token: Option<RedactedString>,
+}
+
+impl AwsCredentials {
+    /// Load credentials from the environment, or failing that from the instance profile of the EC2
+    /// instance we're running on
+    async fn load() -> Result<Self, reqwest::Error> {
+        if let (Ok(access_key_id), Ok(secret_access_key)) = (
+            std::env::var("AWS_ACCESS_KEY_ID"),
+            std::env::var("AWS_SECRET_ACCESS_KEY"),
+        ) {
+            return Ok(Self {
+                access_key_id,
+                secret_access_key: secret_access_key.into(),
+                session_token: std::env::var("AWS_SESSION_TOKEN").ok().map(Into::into),
+            });
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        let imds_token = client
+            .put(format!("{IMDS_ADDRESS}/latest/api/token"))
+            .header("X-aws-ec2-metadata-token-ttl-seconds", "60")
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        let credentials_url = format!("{IMDS_ADDRESS}/latest/meta-data/iam/security-credentials/");
+        let role = client
+            .get(&credentials_url)
+            .header("X-aws-ec2-metadata-token", &imds_token)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        let credentials: serde_json::Value = client
+            .get(format!("{credentials_url}{}", role.trim()))
+            .header("X-aws-ec2-metadata-token", &imds_token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let field = |name: &str| credentials[name].as_str().unwrap_or_default().to_owned();
+        Ok(Self {
+            access_key_id: field("AccessKeyId"),
+            secret_access_key: field("SecretAccessKey").into(),
+            session_token: Some(field("Token").into()),
+        })
+    }
+}
+
+/// A `GetSecretValue` request to AWS Secrets Manager, signed with [AWS Signature Version 4][sigv4]
+///
+/// [sigv4]: https://docs.aws.amazon.com/general/latest/gr/sigv4_signing.html
+struct GetSecretValue<'a> {
+    region: &'a str,
+    host: String,
+    body: String,
+}
+
+impl<'a> GetSecretValue<'a> {
+    const SERVICE: &'static str = "secretsmanager";
+    const TARGET: &'static str = "secretsmanager.GetSecretValue";
+    const CONTENT_TYPE: &'static str = "application/x-amz-json-1.1";
+
+    fn new(region: &'a str, secret_id: &str) -> Self {
+        Self {
+            region,
+            host: format!("{}.{region}.amazonaws.com", Self::SERVICE),
+            body: serde_json::json!({ "SecretId": secret_id }).to_string(),
+        }
+    }
+
+    /// Compute the signature for this request, given the time formatted as `YYYYMMDDTHHMMSSZ`.
+    /// Returns the `Authorization` header value
+    fn authorization(&self, credentials: &AwsCredentials, amz_date: &str) -> String {
+        let date = &amz_date[..8];
+
+        let mut headers = vec![
+            ("content-type", Self::CONTENT_TYPE),
+            ("host", self.host.as_str()),
+            ("x-amz-date", amz_date),
+            ("x-amz-target", Self::TARGET),
+        ];
+        if let Some(token) = &credentials.session_token {
+            headers.push(("x-amz-security-token", token.as_str()));
+        }
+        headers.sort_unstable();
+
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+            .collect();
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_request = format!(
+            "POST\n/\n\n{canonical_headers}\n{signed_headers}\n{}",
+            hex::encode(Sha256::digest(&self.body))
+        );
+
+        let scope = format!("{date}/{}/{}/aws4_request", self.region, Self::SERVICE);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(&canonical_request))
+        );
+
+        let key = [date, self.region, Self::SERVICE, "aws4_request"]
+            .iter()
+            .fold(
+                format!("AWS4{}", credentials.secret_access_key.as_str()).into_bytes(),
+                |key, part| hmac_sha256(&key, part),
+            );
+        let signature = hex::encode(hmac_sha256(&key, &string_to_sign));
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, \
+             Signature={signature}",
+            credentials.access_key_id
+        )
+    }
+
+    fn signed_headers(
+        &self,
+        credentials: &AwsCredentials,
+        amz_date: &str,
+    ) -> reqwest::header::HeaderMap {
+        let mut headers = reqwest::header::HeaderMap::new();
+        let mut insert = |name: &'static str, value: &str| {
+            if let Ok(value) = value.parse() {
+                headers.insert(name, value);
+            }
+        };
+        insert("content-type", Self::CONTENT_TYPE);
+        insert("x-amz-date", amz_date);
+        insert("x-amz-target", Self::TARGET);
+        if let Some(token) = &credentials.session_token {
+            insert("x-amz-security-token", token.as_str());
+        }
+        insert("authorization", &self.authorization(credentials, amz_date));
+        headers
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    #[allow(clippy::unwrap_used)] // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// A secret which is periodically re-read in the background, to pick up rotated credentials.
+///
+/// If re-reading the secret fails, its previous value continues to be used.
+pub struct WatchedSecret {
+    value: watch::Receiver<RedactedString>,
+    refresh_task: JoinHandle<()>,
+}
+
+impl WatchedSecret {
+    /// Read the initial value of the secret from `source`, and then start re-reading it every
+    /// `refresh_interval`
+    pub async fn new(source: SecretSource, refresh_interval: Duration) -> ReadySetResult<Self> {
+        let (tx, value) = watch::channel(source.fetch().await?);
+        let refresh_task = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(refresh_interval);
+            // The first tick completes immediately, and we've just read the secret
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                match source.fetch().await {
+                    Ok(new_value) => {
+                        tx.send_if_modified(|value| {
+                            if *value == new_value {
+                                return false;
+                            }
+                            info!(%source, "Secret was rotated");
+                            *value = new_value;
+                            true
+                        });
+                    }
+                    Err(error) => {
+                        warn!(%source, %error, "Could not re-read secret, using its previous value")
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            value,
+            refresh_task,
+        })
+    }
+
+    /// Returns the most recently read value of the secret
+    pub fn current(&self) -> RedactedString {
+        self.value.borrow().clone()
+    }
+
+    /// Wait for the secret to be rotated, and return its new value
+    pub async fn changed(&mut self) -> RedactedString {
+        if self.value.changed().await.is_err() {
+            // The refresh task never exits on its own, so this is only reachable if it panicked
+            futures::future::pending::<()>().await;
+        }
+        self.value.borrow_and_update().clone()
+    }
+}
+
+impl Drop for WatchedSecret {
+    fn drop(&mut self) {
+        self.refresh_task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        for (s, source) in [
+            (
+                "file:/run/secrets/db_url",
+                SecretSource::File("/run/secrets/db_url".into()),
+            ),
+            (
+                "vault:secret/data/readyset#upstream_db_url",
+                SecretSource::Vault {
+                    path: "secret/data/readyset".into(),
+                    field: "upstream_db_url".into(),
+                },
+            ),
+            (
+                "aws-secretsmanager:prod/readyset",
+                SecretSource::AwsSecretsManager {
+                    secret_id: "prod/readyset".into(),
+                    field: None,
+                },
+            ),
+            (
+                "aws-secretsmanager:prod/readyset#password",
+                SecretSource::AwsSecretsManager {
+                    secret_id: "prod/readyset".into(),
+                    field: Some("password".into()),
+                },
+            ),
+        ] {
+            assert_eq!(s.parse::<SecretSource>().unwrap(), source);
+            assert_eq!(source.to_string(), s);
+        }
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for s in [
+            "/run/secrets/db_url",
+            "file:",
+            "vault:secret/data/readyset",
+            "vault:#field",
+            "aws-secretsmanager:prod#",
+            "env:DB_URL",
+        ] {
+            assert!(s.parse::<SecretSource>().is_err(), "{s} should not parse");
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_file_strips_trailing_newline() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "mysql://root:password@db/readyset").unwrap();
+        let source = SecretSource::File(file.path().to_owned());
+        assert_eq!(
+            source.fetch().await.unwrap().as_str(),
+            "mysql://root:password@db/readyset"
+        );
+    }
+
+    #[test]
+    fn sigv4_signature() {
+        let credentials = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".into(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_owned().into(),
+            session_token: None,
+        };
+        let request = GetSecretValue::new("us-east-1", "prod/readyset");
+        assert_eq!(
+            request.authorization(&credentials, "20150830T123600Z"),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/secretsmanager/aws4_request, \
+             SignedHeaders=content-type;host;x-amz-date;x-amz-target, \
+             Signature=ff81733685189ec8b4c0cd85d1334e44441f2e4b4d57d3a41508802057e761f6"
+        );
+    }
+}
@@ -68,15 +68,13 @@ pub enum BinaryOperator {
     /// `||`
     JsonConcat,
 
-    /// (Unimplemented) [MySQL `->`](https://dev.mysql.com/doc/refman/5.7/en/json-search-functions.html#operator_json-column-path)
+    /// [MySQL `->`](https://dev.mysql.com/doc/refman/5.7/en/json-search-functions.html#operator_json-column-path)
     /// operator to extract JSON values via a path: `json -> jsonpath` to `json`.
-    // TODO(ENG-1517)
     JsonPathExtract,
 
-    /// (Unimplemented) [MySQL `->>`](https://dev.mysql.com/doc/refman/5.7/en/json-search-functions.html#operator_json-inline-path)
+    /// [MySQL `->>`](https://dev.mysql.com/doc/refman/5.7/en/json-search-functions.html#operator_json-inline-path)
     /// operator to extract JSON values and apply [`json_unquote`](https://dev.mysql.com/doc/refman/5.7/en/json-modification-functions.html#function_json-unquote):
     /// `json ->> jsonpath` to unquoted `text`.
-    // TODO(ENG-1518)
     JsonPathExtractUnquote,
 
     /// PostgreSQL `->` operator to extract JSON values as JSON via a key:
@@ -176,6 +174,7 @@ impl BinaryOperator {
             HashArrow2 => Ok((Self::JsonKeyPathExtractText, false)),
             AtArrowRight => Ok((Self::JsonContains, false)),
             AtArrowLeft => Ok((Self::JsonContainedIn, false)),
+            AtAt => unsupported!("Full-text search with '@@' is not supported"),
         }
     }
 
@@ -212,17 +211,33 @@ impl BinaryOperator {
             }
         };
 
+        // When comparing strings with different collations, compare them using whichever
+        // collation isn't the default, so that eg comparing a case-insensitive column against a
+        // literal is case-insensitive regardless of which side of the operator the column is on
+        let collation_coercions = || match (left_type.collation(), right_type.collation()) {
+            (Some(left), Some(right)) if left != right && left.is_utf8() => {
+                Some((Some(DfType::Text(right)), None))
+            }
+            (Some(left), Some(right)) if left != right && right.is_utf8() => {
+                Some((None, Some(DfType::Text(left))))
+            }
+            _ => None,
+        };
+
         use BinaryOperator::*;
         match self {
-            Add | Subtract | Multiply | Divide | And | Or | Greater | GreaterOrEqual | Less
-            | LessOrEqual | Is => Ok((None, None)),
+            Add | Subtract | Multiply | Divide | And | Or | Is => Ok((None, None)),
+
+            Greater | GreaterOrEqual | Less | LessOrEqual => {
+                Ok(collation_coercions().unwrap_or((None, None)))
+            }
 
             Like | ILike => Ok((
                 coerce_to_text_type(left_type),
                 coerce_to_text_type(right_type),
             )),
 
-            Equal => Ok((None, Some(left_type.clone()))),
+            Equal => Ok(collation_coercions().unwrap_or((None, Some(left_type.clone())))),
 
             JsonExists => {
                 if left_type.is_known() && !left_type.is_jsonb() {
@@ -261,6 +276,9 @@ impl BinaryOperator {
                 Ok((Some(DfType::DEFAULT_TEXT), Some(DfType::DEFAULT_TEXT)))
             }
             JsonKeyExtract | JsonKeyExtractText => Ok((Some(DfType::DEFAULT_TEXT), None)),
+            JsonPathExtract | JsonPathExtractUnquote => {
+                Ok((Some(DfType::DEFAULT_TEXT), Some(DfType::DEFAULT_TEXT)))
+            }
 
             JsonSubtract => {
                 if left_type.is_known() && !left_type.is_jsonb() {
@@ -269,10 +287,6 @@ impl BinaryOperator {
 
                 Ok((None, None))
             }
-
-            JsonPathExtract | JsonPathExtractUnquote => {
-                unsupported!("'{self}' operator not implemented yet for MySQL")
-            }
         }
     }
 
@@ -301,6 +315,8 @@ impl BinaryOperator {
             | Self::JsonContains
             | Self::JsonContainedIn => Ok(DfType::Bool),
 
+            Self::JsonPathExtract => Ok(DfType::Json),
+
             Self::JsonPathExtractUnquote
             | Self::JsonKeyExtractText
             | Self::JsonKeyPathExtractText => Ok(DfType::DEFAULT_TEXT),
@@ -361,6 +377,45 @@ mod tests {
         );
     }
 
+    mod argument_type_coercions {
+        use readyset_data::Collation;
+
+        use super::*;
+
+        #[test]
+        fn compare_with_case_insensitive_text() {
+            let ci = DfType::VarChar(10, Collation::Citext);
+            for op in [BinaryOperator::Equal, BinaryOperator::Less] {
+                assert_eq!(
+                    op.argument_type_coercions(&DfType::DEFAULT_TEXT, &ci)
+                        .unwrap(),
+                    (Some(DfType::Text(Collation::Citext)), None)
+                );
+                assert_eq!(
+                    op.argument_type_coercions(&ci, &DfType::DEFAULT_TEXT)
+                        .unwrap(),
+                    (None, Some(DfType::Text(Collation::Citext)))
+                );
+            }
+        }
+
+        #[test]
+        fn compare_with_same_collation() {
+            assert_eq!(
+                BinaryOperator::Equal
+                    .argument_type_coercions(&DfType::DEFAULT_TEXT, &DfType::DEFAULT_TEXT)
+                    .unwrap(),
+                (None, Some(DfType::DEFAULT_TEXT))
+            );
+            assert_eq!(
+                BinaryOperator::Greater
+                    .argument_type_coercions(&DfType::Int, &DfType::Int)
+                    .unwrap(),
+                (None, None)
+            );
+        }
+    }
+
     mod output_type {
         use super::*;
 
@@ -1,7 +1,7 @@
 use std::borrow::Borrow;
 
 use readyset_data::{Array, ArrayD, DfValue, IxDyn};
-use readyset_errors::{invalid_err, unsupported, ReadySetError, ReadySetResult};
+use readyset_errors::{invalid_err, ReadySetError, ReadySetResult};
 use serde_json::Value as JsonValue;
 
 use crate::like::{CaseInsensitive, CaseSensitive, LikePattern};
@@ -84,12 +84,17 @@ fn eval_binary_op(op: BinaryOperator, left: &DfValue, right: &DfValue) -> ReadyS
             };
             Ok(result.into())
         }
-        // TODO(ENG-1517)
-        // TODO(ENG-1518)
         JsonPathExtract | JsonPathExtractUnquote => {
-            // TODO: Perform `JSON_EXTRACT` conditionally followed by `JSON_UNQUOTE` for
-            // `->>`.
-            unsupported!("'{op}' operator not implemented yet for MySQL")
+            // `json -> path` is equivalent to `json_extract(json, path)`, and `json ->> path` is
+            // equivalent to `json_unquote(json_extract(json, path))`.
+            let json = non_null!(left).to_json()?;
+            let path = <&str>::try_from(non_null!(right))?.parse::<json::JsonPath>()?;
+
+            Ok(match json::json_extract(&json, &[path]) {
+                Some(JsonValue::String(s)) if op == JsonPathExtractUnquote => s.into(),
+                Some(value) => value.to_string().into(),
+                None => DfValue::None,
+            })
         }
 
         JsonKeyExtract | JsonKeyExtractText => {
@@ -1020,6 +1025,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn eval_simple_case() {
+        let expr = Expr::lower(
+            parse_expr(
+                nom_sql::Dialect::MySQL,
+                "CASE a WHEN 1 THEN NULL WHEN 2 THEN 'two' ELSE 'other' END",
+            )
+            .unwrap(),
+            Dialect::DEFAULT_MYSQL,
+            resolve_columns(|c| {
+                if c == "a".into() {
+                    Ok((0, DfType::Int))
+                } else {
+                    internal!("what's this column!?")
+                }
+            }),
+        )
+        .unwrap();
+
+        assert!(expr.ty().is_any_text());
+        assert_eq!(expr.eval::<DfValue>(&[1.into()]), Ok(DfValue::None));
+        assert_eq!(expr.eval::<DfValue>(&[2.into()]), Ok(DfValue::from("two")));
+        assert_eq!(
+            expr.eval::<DfValue>(&[DfValue::from(8)]),
+            Ok(DfValue::from("other"))
+        );
+    }
+
     #[test]
     fn like_expr() {
         let expr = Expr::Op {
@@ -1138,6 +1171,32 @@ mod tests {
         test(object, "'abc'::char(3)", "123");
     }
 
+    /// Tests evaluation of `JsonPathExtract` and `JsonPathExtractUnquote` binary ops.
+    #[test]
+    fn eval_json_path_extract() {
+        #[track_caller]
+        fn test(path: &str, extracted: Option<&str>, unquoted: Option<&str>) {
+            let json = r#"{"a": {"b": "c"}, "d": [1, 2]}"#;
+            for (op, expected) in [("->", extracted), ("->>", unquoted)] {
+                let expr = format!("'{json}' {op} '{path}'");
+                assert_eq!(
+                    eval_expr(&expr, MySQL),
+                    expected.into(),
+                    "incorrect result for `{expr}`"
+                );
+            }
+        }
+
+        test("$.a.b", Some("\"c\""), Some("c"));
+        test("$.a", Some(r#"{"b":"c"}"#), Some(r#"{"b":"c"}"#));
+        test("$.d[last]", Some("2"), Some("2"));
+        test("$.d[*]", Some("[1,2]"), Some("[1,2]"));
+        test("$.x", None, None);
+
+        assert_eq!(eval_expr("null -> '$.a'", MySQL), DfValue::None);
+        try_eval_expr("'{}' -> 'a'", MySQL).unwrap_err();
+    }
+
     /// Tests evaluation of `JsonKeyPathExtract` and `JsonKeyPathExtractText` binary ops.
     #[test]
     fn eval_json_key_path_extract() {
@@ -3,10 +3,13 @@ use std::cmp::Ordering;
 use std::fmt::Write;
 use std::ops::{Add, Div, Mul, Sub};
 
-use chrono::{Datelike, LocalResult, Month, NaiveDate, NaiveDateTime, TimeZone, Timelike, Weekday};
+use chrono::{
+    Datelike, Duration, LocalResult, Month, NaiveDate, NaiveDateTime, TimeZone, Timelike, Weekday,
+};
 use chrono_tz::Tz;
 use itertools::Either;
 use mysql_time::MySqlTime;
+use nom_sql::{IntervalUnit, TrimSide};
 use readyset_data::{DfType, DfValue};
 use readyset_errors::{invalid_err, ReadySetError, ReadySetResult};
 use readyset_util::math::integer_rnd;
@@ -108,6 +111,87 @@ fn addtime_times(time1: &MySqlTime, time2: &MySqlTime) -> MySqlTime {
     time1.add(*time2)
 }
 
+/// Add the given number of months to the given datetime. If the day of the month doesn't exist in
+/// the resulting month, it's clamped to the last day of that month, so that (as in MySQL) adding
+/// one month to January 31st results in the last day of February. Returns `None` if the result is
+/// out of range.
+fn add_months(datetime: NaiveDateTime, months: i64) -> Option<NaiveDateTime> {
+    let total_months = i64::from(datetime.year())
+        .checked_mul(12)?
+        .checked_add(i64::from(datetime.month0()))?
+        .checked_add(months)?;
+    let year = i32::try_from(total_months.div_euclid(12)).ok()?;
+    let month = total_months.rem_euclid(12) as u32 + 1;
+    let date = (1..=datetime.day())
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))?;
+    Some(date.and_time(datetime.time()))
+}
+
+/// Add `amount` of the given `unit` to the given datetime, as in MySQL's `DATE_ADD`. Returns `None`
+/// if the result is out of range.
+fn date_add(datetime: NaiveDateTime, amount: i64, unit: IntervalUnit) -> Option<NaiveDateTime> {
+    let microseconds_per_unit: i64 = match unit {
+        IntervalUnit::Microsecond => 1,
+        IntervalUnit::Second => 1_000_000,
+        IntervalUnit::Minute => 60 * 1_000_000,
+        IntervalUnit::Hour => 60 * 60 * 1_000_000,
+        IntervalUnit::Day => 24 * 60 * 60 * 1_000_000,
+        IntervalUnit::Week => 7 * 24 * 60 * 60 * 1_000_000,
+        IntervalUnit::Month => return add_months(datetime, amount),
+        IntervalUnit::Quarter => return add_months(datetime, amount.checked_mul(3)?),
+        IntervalUnit::Year => return add_months(datetime, amount.checked_mul(12)?),
+    };
+    datetime.checked_add_signed(Duration::microseconds(
+        amount.checked_mul(microseconds_per_unit)?,
+    ))
+}
+
+/// Extract the given field from the given datetime, as in MySQL's `EXTRACT`
+fn extract_from_datetime(field: IntervalUnit, datetime: &NaiveDateTime) -> i64 {
+    match field {
+        IntervalUnit::Microsecond => i64::from(datetime.nanosecond() / 1_000),
+        IntervalUnit::Second => i64::from(datetime.second()),
+        IntervalUnit::Minute => i64::from(datetime.minute()),
+        IntervalUnit::Hour => i64::from(datetime.hour()),
+        IntervalUnit::Day => i64::from(datetime.day()),
+        // MySQL's `EXTRACT(WEEK ...)` uses the default mode of `WEEK()`, in which weeks start on
+        // Sunday and the days before the first Sunday of the year are in week 0
+        IntervalUnit::Week => {
+            i64::from((datetime.ordinal() + 6 - datetime.weekday().num_days_from_sunday()) / 7)
+        }
+        IntervalUnit::Month => i64::from(datetime.month()),
+        IntervalUnit::Quarter => i64::from(datetime.month0() / 3 + 1),
+        IntervalUnit::Year => i64::from(datetime.year()),
+    }
+}
+
+/// Remove `remove` from the given `side` of `s`. If `remove_chars` is true, every character in
+/// `remove` is removed, otherwise `remove` is removed as a whole string, as many times as it
+/// occurs.
+fn trim<'a>(s: &'a str, remove: &str, side: TrimSide, remove_chars: bool) -> &'a str {
+    let leading = matches!(side, TrimSide::Both | TrimSide::Leading);
+    let trailing = matches!(side, TrimSide::Both | TrimSide::Trailing);
+    let mut s = s;
+    if remove_chars {
+        let is_removed = |c: char| remove.contains(c);
+        if leading {
+            s = s.trim_start_matches(is_removed);
+        }
+        if trailing {
+            s = s.trim_end_matches(is_removed);
+        }
+    } else if !remove.is_empty() {
+        if leading {
+            s = s.trim_start_matches(remove);
+        }
+        if trailing {
+            s = s.trim_end_matches(remove);
+        }
+    }
+    s
+}
+
 /// Format the given time value according to the given `format_string`, using the [MySQL date
 /// formatting rules][mysql-docs]. Since these rules don't match up well with anything available in
 /// the Rust crate ecosystem, this is done manually.
@@ -443,6 +527,42 @@ impl BuiltinFunction {
                     Ok(DfValue::None)
                 }
             }
+            BuiltinFunction::DateAdd(date, interval, unit) => {
+                let date = get_time_or_default(&non_null!(date.eval(record)?), date.ty());
+                let amount = try_cast_or_none!(
+                    non_null!(interval.eval(record)?),
+                    &DfType::BigInt,
+                    interval.ty()
+                );
+                let Ok(date) = NaiveDateTime::try_from(&date) else {
+                    return Ok(DfValue::None);
+                };
+                match date_add(date, i64::try_from(amount)?, *unit) {
+                    Some(res) if *ty == DfType::Date => Ok(DfValue::TimestampTz(res.date().into())),
+                    Some(res) => Ok(DfValue::TimestampTz(res.into())),
+                    None => Ok(DfValue::None),
+                }
+            }
+            BuiltinFunction::Extract(field, arg) => {
+                let value = get_time_or_default(&non_null!(arg.eval(record)?), arg.ty());
+                if let Ok(datetime) = NaiveDateTime::try_from(&value) {
+                    return Ok(DfValue::Int(extract_from_datetime(*field, &datetime)));
+                }
+                let Ok(time) = MySqlTime::try_from(&value) else {
+                    return Ok(DfValue::None);
+                };
+                let sign = if time.is_positive() { 1 } else { -1 };
+                Ok(match field {
+                    IntervalUnit::Microsecond => {
+                        DfValue::Int(sign * i64::from(time.microseconds()))
+                    }
+                    IntervalUnit::Second => DfValue::Int(sign * i64::from(time.seconds())),
+                    IntervalUnit::Minute => DfValue::Int(sign * i64::from(time.minutes())),
+                    IntervalUnit::Hour => DfValue::Int(sign * i64::from(time.hour())),
+                    // Times don't have any date fields
+                    _ => DfValue::None,
+                })
+            }
             BuiltinFunction::Round(arg1, arg2) => {
                 let expr = arg1.eval(record)?;
                 let param2 = arg2.eval(record)?;
@@ -562,6 +682,21 @@ impl BuiltinFunction {
                 let json = non_null!(expr.eval(record)?);
                 Ok(crate::eval::json::json_quote(<&str>::try_from(&json)?).into())
             }
+            BuiltinFunction::JsonUnquote(expr) => {
+                let json = non_null!(expr.eval(record)?);
+                Ok(crate::eval::json::json_unquote(<&str>::try_from(&json)?)?.into())
+            }
+            BuiltinFunction::JsonExtract { json, paths } => {
+                let json = non_null!(json.eval(record)?).to_json()?;
+                let mut parsed_paths = Vec::with_capacity(paths.len());
+                for path in paths {
+                    let path = non_null!(path.eval(record)?);
+                    parsed_paths.push(<&str>::try_from(&path)?.parse()?);
+                }
+                Ok(crate::eval::json::json_extract(&json, &parsed_paths)
+                    .map(|value| value.to_string().into())
+                    .unwrap_or_default())
+            }
             BuiltinFunction::JsonOverlaps(expr1, expr2) => Ok(crate::eval::json::json_overlaps(
                 &non_null!(expr1.eval(record)?).to_json()?,
                 &non_null!(expr2.eval(record)?).to_json()?,
@@ -793,6 +928,34 @@ impl BuiltinFunction {
                     }
                 }
             }
+            BuiltinFunction::Lower(arg) => {
+                let string = non_null!(arg.eval(record)?);
+                Ok(<&str>::try_from(&string)?.to_lowercase().into())
+            }
+            BuiltinFunction::Upper(arg) => {
+                let string = non_null!(arg.eval(record)?);
+                Ok(<&str>::try_from(&string)?.to_uppercase().into())
+            }
+            BuiltinFunction::Trim {
+                string,
+                remove,
+                side,
+                remove_chars,
+            } => {
+                let string = non_null!(string.eval(record)?);
+                let remove = match remove {
+                    Some(remove) => Some(non_null!(remove.eval(record)?)),
+                    None => None,
+                };
+                let remove = remove.as_ref().map(<&str>::try_from).transpose()?;
+                Ok(trim(
+                    <&str>::try_from(&string)?,
+                    remove.unwrap_or(" "),
+                    *side,
+                    *remove_chars,
+                )
+                .into())
+            }
             BuiltinFunction::Greatest { args, compare_as } => {
                 greatest_or_least(args, record, compare_as, ty, |v1, v2| v1 > v2)
             }
@@ -1578,6 +1741,57 @@ mod tests {
         assert_eq!(res, "abc".into());
     }
 
+    #[test]
+    fn lower_and_upper() {
+        assert_eq!(eval_expr("lower('MySQL')", MySQL), "mysql".into());
+        assert_eq!(eval_expr("lcase('MySQL')", MySQL), "mysql".into());
+        assert_eq!(eval_expr("upper('MySQL')", PostgreSQL), "MYSQL".into());
+        assert_eq!(eval_expr("upper(null)", PostgreSQL), DfValue::None);
+    }
+
+    #[test]
+    fn trim_mysql() {
+        assert_eq!(eval_expr("trim('  abc  ')", MySQL), "abc".into());
+        assert_eq!(eval_expr("ltrim('  abc  ')", MySQL), "abc  ".into());
+        assert_eq!(eval_expr("rtrim('  abc  ')", MySQL), "  abc".into());
+        assert_eq!(
+            eval_expr("trim(leading 'xy' from 'xyxyabcxy')", MySQL),
+            "abcxy".into()
+        );
+        assert_eq!(
+            eval_expr("trim(trailing 'xy' from 'xyabcxyxy')", MySQL),
+            "xyabc".into()
+        );
+        // MySQL removes the whole string, not individual characters
+        assert_eq!(
+            eval_expr("trim('xy' from 'yxabcx')", MySQL),
+            "yxabcx".into()
+        );
+        assert_eq!(eval_expr("trim(null from 'abc')", MySQL), DfValue::None);
+    }
+
+    #[test]
+    fn trim_postgres() {
+        assert_eq!(
+            eval_expr("trim(both from '  abc  ')", PostgreSQL),
+            "abc".into()
+        );
+        // PostgreSQL removes any of the given characters
+        assert_eq!(
+            eval_expr("trim('xy' from 'yxabcx')", PostgreSQL),
+            "abc".into()
+        );
+        assert_eq!(
+            eval_expr("ltrim('yxabcx', 'xy')", PostgreSQL),
+            "abcx".into()
+        );
+        assert_eq!(
+            eval_expr("rtrim('yxabcx', 'xy')", PostgreSQL),
+            "yxabc".into()
+        );
+        assert_eq!(eval_expr("btrim('yxabcx', 'xy')", PostgreSQL), "abc".into());
+    }
+
     #[test]
     fn greatest_mysql() {
         assert_eq!(eval_expr("greatest(1, 2, 3)", MySQL), 3.into());
@@ -1646,6 +1860,103 @@ mod tests {
         );
     }
 
+    #[test]
+    fn eval_call_date_add() {
+        #[track_caller]
+        fn datetime(expr: &str) -> NaiveDateTime {
+            NaiveDateTime::try_from(&eval_expr(expr, MySQL)).unwrap()
+        }
+        let expected = |y, m, d, h, mi, s| NaiveDate::from_ymd(y, m, d).and_hms(h, mi, s);
+
+        assert_eq!(
+            datetime("date_add('2022-01-31 10:00:00', INTERVAL 1 DAY)"),
+            expected(2022, 2, 1, 10, 0, 0)
+        );
+        assert_eq!(
+            datetime("date_sub('2022-01-01 00:00:00', INTERVAL 90 MINUTE)"),
+            expected(2021, 12, 31, 22, 30, 0)
+        );
+        assert_eq!(
+            datetime("'2022-01-01 00:00:00' - INTERVAL 1 SECOND"),
+            expected(2021, 12, 31, 23, 59, 59)
+        );
+        assert_eq!(
+            datetime("INTERVAL 2 WEEK + '2022-01-01 00:00:00'"),
+            expected(2022, 1, 15, 0, 0, 0)
+        );
+        assert_eq!(
+            datetime("adddate('2022-01-01 00:00:00', 3)"),
+            expected(2022, 1, 4, 0, 0, 0)
+        );
+
+        // Adding months clamps the day to the end of the resulting month
+        assert_eq!(
+            datetime("date_add('2020-01-31 12:00:00', INTERVAL 1 MONTH)"),
+            expected(2020, 2, 29, 12, 0, 0)
+        );
+        assert_eq!(
+            datetime("date_sub('2020-05-31 12:00:00', INTERVAL 1 QUARTER)"),
+            expected(2020, 2, 29, 12, 0, 0)
+        );
+        assert_eq!(
+            datetime("date_add('2020-02-29 12:00:00', INTERVAL 1 YEAR)"),
+            expected(2021, 2, 28, 12, 0, 0)
+        );
+
+        assert_eq!(
+            eval_expr("date_add(null, INTERVAL 1 DAY)", MySQL),
+            DfValue::None
+        );
+    }
+
+    #[test]
+    fn date_add_to_date_column() {
+        let expr = Expr::lower(
+            parse_expr(MySQL, "date_add(d, INTERVAL c DAY)").unwrap(),
+            Dialect::DEFAULT_MYSQL,
+            resolve_columns(|c| match c.name.as_str() {
+                "d" => Ok((0, DfType::Date)),
+                "c" => Ok((1, DfType::Int)),
+                _ => internal!(),
+            }),
+        )
+        .unwrap();
+        assert_eq!(*expr.ty(), DfType::Date);
+
+        let res = expr
+            .eval(&[
+                DfValue::from(NaiveDate::from_ymd(2022, 12, 31)),
+                DfValue::from(1),
+            ])
+            .unwrap();
+        assert_eq!(res, DfValue::from(NaiveDate::from_ymd(2023, 1, 1)));
+    }
+
+    #[test]
+    fn eval_call_extract() {
+        let extract = |field: &str| {
+            eval_expr(
+                &format!("extract({field} from '2022-03-06 13:14:15.123456')"),
+                MySQL,
+            )
+        };
+        assert_eq!(extract("microsecond"), 123456.into());
+        assert_eq!(extract("second"), 15.into());
+        assert_eq!(extract("minute"), 14.into());
+        assert_eq!(extract("hour"), 13.into());
+        assert_eq!(extract("day"), 6.into());
+        assert_eq!(extract("week"), 10.into());
+        assert_eq!(extract("month"), 3.into());
+        assert_eq!(extract("quarter"), 1.into());
+        assert_eq!(extract("year"), 2022.into());
+
+        assert_eq!(
+            eval_expr("extract(week from '2022-01-01 00:00:00')", MySQL),
+            0.into()
+        );
+        assert_eq!(eval_expr("extract(year from null)", MySQL), DfValue::None);
+    }
+
     #[track_caller]
     fn date_format(time: &str, fmt: &str) -> DfValue {
         lazy_static! {
@@ -1839,6 +2150,49 @@ mod tests {
             test(r#"wo"r\\ld"#, r#""wo\"r\\ld""#);
         }
 
+        // JSON paths are more thoroughly tested in `eval::json::tests::json_extract`.
+        #[test]
+        fn json_extract() {
+            #[track_caller]
+            fn test(args: &str, expected: Option<&str>) {
+                let expr = format!("json_extract({args})");
+                assert_eq!(
+                    eval_expr(&expr, MySQL),
+                    expected.map(normalize_json).into(),
+                    "incorrect result for `{expr}`"
+                );
+            }
+
+            let json = r#"'{"a": [1, {"b": "c"}], "d": null}'"#;
+            test(&format!("{json}, '$.a[1].b'"), Some(r#""c""#));
+            test(&format!("{json}, '$.a[last]'"), Some(r#"{"b": "c"}"#));
+            test(&format!("{json}, '$.d'"), Some("null"));
+            test(&format!("{json}, '$.x'"), None);
+            test(
+                &format!("{json}, '$.a[0]', '$.a[1].b'"),
+                Some(r#"[1, "c"]"#),
+            );
+            test(&format!("{json}, '$.a[*]'"), Some(r#"[1, {"b": "c"}]"#));
+            test(&format!("{json}, null"), None);
+            test("null, '$.a'", None);
+
+            try_eval_expr(&format!("json_extract({json}, 'a')"), MySQL).unwrap_err();
+        }
+
+        #[test]
+        fn json_unquote() {
+            assert_eq!(
+                eval_expr(r#"json_unquote('"hello"')"#, MySQL),
+                "hello".into()
+            );
+            assert_eq!(eval_expr("json_unquote('[1]')", MySQL), "[1]".into());
+            assert_eq!(eval_expr("json_unquote(null)", MySQL), DfValue::None);
+            assert_eq!(
+                eval_expr(r#"json_unquote(json_extract('{"a": "b"}', '$.a'))"#, MySQL),
+                "b".into()
+            );
+        }
+
         #[test]
         fn json_array_length() {
             #[track_caller]
@@ -150,6 +150,231 @@ pub(crate) fn json_quote(json: &str) -> String {
     result
 }
 
+/// Unquotes a JSON string literal using the semantics of MySQL's
+/// [`json_unquote`](https://dev.mysql.com/doc/refman/8.0/en/json-modification-functions.html#function_json-unquote).
+///
+/// Values which are not quoted strings are returned unchanged.
+pub(crate) fn json_unquote(json: &str) -> ReadySetResult<String> {
+    if json.len() >= 2 && json.starts_with('"') && json.ends_with('"') {
+        serde_json::from_str::<String>(json)
+            .map_err(|_| invalid_err!("Invalid JSON text in argument 1 to function json_unquote"))
+    } else {
+        Ok(json.to_owned())
+    }
+}
+
+/// A single leg of a [MySQL JSON path](https://dev.mysql.com/doc/refman/8.0/en/json.html#json-path-syntax).
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum JsonPathLeg {
+    /// `.key` or `."key"`
+    Member(String),
+    /// `.*`
+    AnyMember,
+    /// `[N]` or `[last - N]`
+    Index(JsonPathIndex),
+    /// `[*]`
+    AnyIndex,
+    /// `**`, which matches any (possibly empty) sequence of legs
+    AnyDescendant,
+}
+
+/// An array index within a [`JsonPathLeg::Index`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JsonPathIndex {
+    /// `N`, counting from the start of the array.
+    FromStart(usize),
+    /// `last - N`, counting from the end of the array.
+    FromEnd(usize),
+}
+
+impl JsonPathIndex {
+    /// Returns the position of this index within an array of `len` elements, if in bounds.
+    fn resolve(self, len: usize) -> Option<usize> {
+        match self {
+            Self::FromStart(index) => (index < len).then_some(index),
+            Self::FromEnd(offset) => len.checked_sub(offset)?.checked_sub(1),
+        }
+    }
+}
+
+/// A parsed [MySQL JSON path](https://dev.mysql.com/doc/refman/8.0/en/json.html#json-path-syntax),
+/// such as `$.a[0]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct JsonPath {
+    legs: Vec<JsonPathLeg>,
+}
+
+impl JsonPath {
+    /// Returns `true` if this path may match more than one value.
+    fn has_wildcard(&self) -> bool {
+        self.legs.iter().any(|leg| {
+            matches!(
+                leg,
+                JsonPathLeg::AnyMember | JsonPathLeg::AnyIndex | JsonPathLeg::AnyDescendant
+            )
+        })
+    }
+
+    /// Returns the values within `json` matched by this path, in document order.
+    fn find<'j>(&self, json: &'j JsonValue) -> Vec<&'j JsonValue> {
+        fn find_legs<'j>(
+            json: &'j JsonValue,
+            legs: &[JsonPathLeg],
+            matches: &mut Vec<&'j JsonValue>,
+        ) {
+            let (leg, rest) = match legs.split_first() {
+                Some(split) => split,
+                None => {
+                    // `**` can reach the same value through multiple paths.
+                    if !matches.iter().any(|m| std::ptr::eq(*m, json)) {
+                        matches.push(json);
+                    }
+                    return;
+                }
+            };
+
+            match (leg, json) {
+                (JsonPathLeg::Member(key), JsonValue::Object(object)) => {
+                    if let Some(value) = object.get(key) {
+                        find_legs(value, rest, matches);
+                    }
+                }
+                (JsonPathLeg::AnyMember, JsonValue::Object(object)) => {
+                    for value in object.values() {
+                        find_legs(value, rest, matches);
+                    }
+                }
+                (JsonPathLeg::Index(index), JsonValue::Array(array)) => {
+                    if let Some(value) = index.resolve(array.len()).and_then(|i| array.get(i)) {
+                        find_legs(value, rest, matches);
+                    }
+                }
+                // MySQL treats non-array values as single-element arrays when indexing.
+                (JsonPathLeg::Index(index), _) => {
+                    if index.resolve(1) == Some(0) {
+                        find_legs(json, rest, matches);
+                    }
+                }
+                (JsonPathLeg::AnyIndex, JsonValue::Array(array)) => {
+                    for value in array {
+                        find_legs(value, rest, matches);
+                    }
+                }
+                (JsonPathLeg::AnyDescendant, _) => {
+                    find_legs(json, rest, matches);
+                    match json {
+                        JsonValue::Array(array) => {
+                            for value in array {
+                                find_legs(value, legs, matches);
+                            }
+                        }
+                        JsonValue::Object(object) => {
+                            for value in object.values() {
+                                find_legs(value, legs, matches);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut matches = Vec::new();
+        find_legs(json, &self.legs, &mut matches);
+        matches
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = ReadySetError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let invalid = || invalid_err!("Invalid JSON path expression '{path}'");
+
+        let mut rest = path.trim_start().strip_prefix('$').ok_or_else(invalid)?;
+        let mut legs = Vec::new();
+
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+
+            if let Some(after) = rest.strip_prefix("**") {
+                legs.push(JsonPathLeg::AnyDescendant);
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix('.') {
+                let after = after.trim_start();
+                if let Some(after) = after.strip_prefix('*') {
+                    legs.push(JsonPathLeg::AnyMember);
+                    rest = after;
+                } else if after.starts_with('"') {
+                    let mut keys = serde_json::Deserializer::from_str(after).into_iter::<String>();
+                    let key = keys.next().and_then(Result::ok).ok_or_else(invalid)?;
+                    legs.push(JsonPathLeg::Member(key));
+                    rest = &after[keys.byte_offset()..];
+                } else {
+                    let end = after
+                        .find(|c: char| c.is_whitespace() || matches!(c, '.' | '[' | '*'))
+                        .unwrap_or(after.len());
+                    if end == 0 {
+                        return Err(invalid());
+                    }
+                    legs.push(JsonPathLeg::Member(after[..end].to_owned()));
+                    rest = &after[end..];
+                }
+            } else if let Some(after) = rest.strip_prefix('[') {
+                let (index, after) = after.split_once(']').ok_or_else(invalid)?;
+                let index = index.trim();
+                let parse_index =
+                    |index: &str| index.trim().parse::<usize>().map_err(|_| invalid());
+
+                legs.push(if index == "*" {
+                    JsonPathLeg::AnyIndex
+                } else if let Some(offset) = index.strip_prefix("last") {
+                    let offset = match offset.trim_start() {
+                        "" => 0,
+                        offset => parse_index(offset.strip_prefix('-').ok_or_else(invalid)?)?,
+                    };
+                    JsonPathLeg::Index(JsonPathIndex::FromEnd(offset))
+                } else {
+                    JsonPathLeg::Index(JsonPathIndex::FromStart(parse_index(index)?))
+                });
+                rest = after;
+            } else {
+                return Err(invalid());
+            }
+        }
+
+        // `**` must be followed by another leg.
+        if legs.last() == Some(&JsonPathLeg::AnyDescendant) {
+            return Err(invalid());
+        }
+
+        Ok(Self { legs })
+    }
+}
+
+/// Extracts values from JSON using the semantics of MySQL's
+/// [`json_extract`](https://dev.mysql.com/doc/refman/8.0/en/json-search-functions.html#function_json-extract).
+///
+/// If given a single path without wildcards, the value it matches is returned as-is. Otherwise,
+/// the values matched by every path are wrapped in an array. Returns `None` if nothing matched.
+pub(crate) fn json_extract(json: &JsonValue, paths: &[JsonPath]) -> Option<JsonValue> {
+    let matches = paths
+        .iter()
+        .flat_map(|path| path.find(json))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    match paths {
+        [path] if !path.has_wildcard() => matches.into_iter().next(),
+        _ if matches.is_empty() => None,
+        _ => Some(JsonValue::Array(matches)),
+    }
+}
+
 /// Removes a value from JSON using PostgreSQL `#-` semantics.
 pub(crate) fn json_remove_path<'k>(
     json: &mut JsonValue,
@@ -830,6 +1055,103 @@ mod tests {
         }
     }
 
+    mod json_extract {
+        use super::*;
+
+        #[track_caller]
+        fn extract(json: &str, paths: &[&str]) -> Option<String> {
+            let json = json.parse::<JsonValue>().unwrap();
+            let paths = paths
+                .iter()
+                .map(|path| path.parse::<JsonPath>().unwrap())
+                .collect::<Vec<_>>();
+            json_extract(&json, &paths).map(|value| value.to_string())
+        }
+
+        #[test]
+        fn parse_paths() {
+            use JsonPathIndex::*;
+            use JsonPathLeg::*;
+
+            #[track_caller]
+            fn test(path: &str, legs: Vec<JsonPathLeg>) {
+                assert_eq!(path.parse::<JsonPath>().unwrap(), JsonPath { legs });
+            }
+
+            test("$", vec![]);
+            test(" $ . a ", vec![Member("a".into())]);
+            test(
+                r#"$."a b".c"#,
+                vec![Member("a b".into()), Member("c".into())],
+            );
+            test(r#"$."a\"b""#, vec![Member("a\"b".into())]);
+            test(
+                "$[1][last][ last - 2 ][*]",
+                vec![
+                    Index(FromStart(1)),
+                    Index(FromEnd(0)),
+                    Index(FromEnd(2)),
+                    AnyIndex,
+                ],
+            );
+            test(
+                "$.***.a",
+                vec![AnyMember, AnyDescendant, Member("a".into())],
+            );
+
+            for path in ["", "a", "$.", "$**", "$[", "$[a]", "$[-1]", "$[last 1]"] {
+                assert!(
+                    path.parse::<JsonPath>().is_err(),
+                    "parsed invalid path {path:?}"
+                );
+            }
+        }
+
+        #[test]
+        fn single_path() {
+            let json = r#"{"a": {"b": [10, 20, 30]}, "c": "d"}"#;
+            assert_eq!(extract(json, &["$"]), Some(normalize(json)));
+            assert_eq!(extract(json, &["$.c"]), Some(r#""d""#.into()));
+            assert_eq!(extract(json, &["$.a.b[1]"]), Some("20".into()));
+            assert_eq!(extract(json, &["$.a.b[last]"]), Some("30".into()));
+            assert_eq!(extract(json, &["$.a.b[last-2]"]), Some("10".into()));
+            assert_eq!(extract(json, &["$.a.b[3]"]), None);
+            assert_eq!(extract(json, &["$.a.b[last-3]"]), None);
+            assert_eq!(extract(json, &["$.x"]), None);
+
+            // Scalars behave like single-element arrays.
+            assert_eq!(extract(json, &["$.c[0]"]), Some(r#""d""#.into()));
+            assert_eq!(extract(json, &["$.c[1]"]), None);
+        }
+
+        #[test]
+        fn wildcards_and_multiple_paths() {
+            let json = r#"{"a": {"b": 1}, "c": {"b": 2}, "d": [3, {"b": 4}]}"#;
+            assert_eq!(extract(json, &["$.*.b"]), Some("[1,2]".into()));
+            assert_eq!(extract(json, &["$.d[*]"]), Some(r#"[3,{"b":4}]"#.into()));
+            assert_eq!(extract(json, &["$**.b"]), Some("[1,2,4]".into()));
+            assert_eq!(extract(json, &["$.a.b"]), Some("1".into()));
+            assert_eq!(extract(json, &["$.a.b", "$.d[0]"]), Some("[1,3]".into()));
+            assert_eq!(extract(json, &["$.a.b", "$.x"]), Some("[1]".into()));
+            assert_eq!(extract(json, &["$.x", "$.y"]), None);
+            assert_eq!(extract(json, &["$.*.x"]), None);
+        }
+
+        fn normalize(json: &str) -> String {
+            json.parse::<JsonValue>().unwrap().to_string()
+        }
+    }
+
+    #[test]
+    fn json_unquote() {
+        assert_eq!(super::json_unquote(r#""abc""#).unwrap(), "abc");
+        assert_eq!(super::json_unquote(r#""a\"b\u00e9""#).unwrap(), "a\"bé");
+        assert_eq!(super::json_unquote("abc").unwrap(), "abc");
+        assert_eq!(super::json_unquote("[1, 2]").unwrap(), "[1, 2]");
+        assert_eq!(super::json_unquote("\"").unwrap(), "\"");
+        super::json_unquote(r#""\x""#).unwrap_err();
+    }
+
     mod json_scalar {
         use proptest::prelude::*;
 
@@ -10,6 +10,7 @@ pub mod utils;
 use std::fmt::{self, Display, Formatter};
 
 use itertools::Itertools;
+use nom_sql::{IntervalUnit, TrimSide};
 pub use readyset_data::Dialect;
 use readyset_data::{DfType, DfValue};
 use serde::{Deserialize, Serialize};
@@ -38,6 +39,15 @@ pub enum BuiltinFunction {
     Addtime(Expr, Expr),
     /// [`date_format`](https://dev.mysql.com/doc/refman/8.0/en/date-and-time-functions.html#function_date-format)
     DateFormat(Expr, Expr),
+    /// [`date_add`](https://dev.mysql.com/doc/refman/8.0/en/date-and-time-functions.html#function_date-add),
+    /// which is also used for `date_sub` (with a negated interval) and for adding an `INTERVAL` to
+    /// (or subtracting one from) a date with the `+` and `-` operators
+    DateAdd(Expr, Expr, IntervalUnit),
+    /// `extract`:
+    ///
+    /// * [MySQL](https://dev.mysql.com/doc/refman/8.0/en/date-and-time-functions.html#function_extract)
+    /// * [PostgreSQL](https://www.postgresql.org/docs/current/functions-datetime.html#FUNCTIONS-DATETIME-EXTRACT)
+    Extract(IntervalUnit, Expr),
     /// [`round`](https://dev.mysql.com/doc/refman/8.0/en/mathematical-functions.html#function_round)
     Round(Expr, Expr),
     /// [`json_depth`](https://dev.mysql.com/doc/refman/8.0/en/json-attribute-functions.html#function_json-depth)
@@ -46,6 +56,10 @@ pub enum BuiltinFunction {
     JsonValid(Expr),
     /// [`json_quote`](https://dev.mysql.com/doc/refman/8.0/en/json-creation-functions.html#function_json-quote)
     JsonQuote(Expr),
+    /// [`json_unquote`](https://dev.mysql.com/doc/refman/8.0/en/json-modification-functions.html#function_json-unquote)
+    JsonUnquote(Expr),
+    /// [`json_extract`](https://dev.mysql.com/doc/refman/8.0/en/json-search-functions.html#function_json-extract)
+    JsonExtract { json: Expr, paths: Vec1<Expr> },
     /// [`json_overlaps`](https://dev.mysql.com/doc/refman/8.0/en/json-search-functions.html#function_json-overlaps)
     JsonOverlaps(Expr, Expr),
     /// [`json[b]_typeof`](https://www.postgresql.org/docs/current/functions-json.html)
@@ -84,6 +98,33 @@ pub enum BuiltinFunction {
     /// [`split_part`](https://www.postgresql.org/docs/current/functions-string.html)
     SplitPart(Expr, Expr, Expr),
 
+    /// `lower`:
+    ///
+    /// * [MySQL](https://dev.mysql.com/doc/refman/8.0/en/string-functions.html#function_lower)
+    /// * [PostgreSQL](https://www.postgresql.org/docs/current/functions-string.html)
+    Lower(Expr),
+
+    /// `upper`:
+    ///
+    /// * [MySQL](https://dev.mysql.com/doc/refman/8.0/en/string-functions.html#function_upper)
+    /// * [PostgreSQL](https://www.postgresql.org/docs/current/functions-string.html)
+    Upper(Expr),
+
+    /// `trim`, which is also used for `ltrim`, `rtrim` and `btrim`:
+    ///
+    /// * [MySQL](https://dev.mysql.com/doc/refman/8.0/en/string-functions.html#function_trim)
+    /// * [PostgreSQL](https://www.postgresql.org/docs/current/functions-string.html)
+    Trim {
+        string: Expr,
+        /// What to remove from the string, which defaults to a single space
+        remove: Option<Expr>,
+        side: TrimSide,
+        /// If true, `remove` is a set of characters, any of which are removed (as in PostgreSQL).
+        /// Otherwise, `remove` is a string which is removed as many times as it occurs (as in
+        /// MySQL)
+        remove_chars: bool,
+    },
+
     /// `greatest`:
     ///
     /// * [MySQL](https://dev.mysql.com/doc/refman/8.0/en/comparison-operators.html#function_greatest)
@@ -121,10 +162,14 @@ impl BuiltinFunction {
             Timediff { .. } => "timediff",
             Addtime { .. } => "addtime",
             DateFormat { .. } => "date_format",
+            DateAdd { .. } => "date_add",
+            Extract { .. } => "extract",
             Round { .. } => "round",
             JsonDepth { .. } => "json_depth",
             JsonValid { .. } => "json_valid",
             JsonQuote { .. } => "json_quote",
+            JsonUnquote { .. } => "json_unquote",
+            JsonExtract { .. } => "json_extract",
             JsonOverlaps { .. } => "json_overlaps",
             JsonTypeof { .. } => "json_typeof",
             JsonObject { .. } => "json_object",
@@ -138,6 +183,9 @@ impl BuiltinFunction {
             Concat { .. } => "concat",
             Substring { .. } => "substring",
             SplitPart { .. } => "split_part",
+            Lower { .. } => "lower",
+            Upper { .. } => "upper",
+            Trim { .. } => "trim",
             Greatest { .. } => "greatest",
             Least { .. } => "least",
             ArrayToString { .. } => "array_to_string",
@@ -173,11 +221,17 @@ impl Display for BuiltinFunction {
             DateFormat(arg1, arg2) => {
                 write!(f, "({}, {})", arg1, arg2)
             }
+            DateAdd(date, interval, unit) => {
+                write!(f, "({date}, INTERVAL {interval} {unit})")
+            }
+            Extract(field, arg) => {
+                write!(f, "({field} from {arg})")
+            }
             Round(arg1, precision) => {
                 write!(f, "({}, {})", arg1, precision)
             }
-            JsonDepth(arg) | JsonValid(arg) | JsonQuote(arg) | JsonTypeof(arg)
-            | JsonArrayLength(arg) | JsonStripNulls(arg) | JsonbPretty(arg) => {
+            JsonDepth(arg) | JsonValid(arg) | JsonQuote(arg) | JsonUnquote(arg)
+            | JsonTypeof(arg) | JsonArrayLength(arg) | JsonStripNulls(arg) | JsonbPretty(arg) => {
                 write!(f, "({})", arg)
             }
             JsonOverlaps(arg1, arg2) => {
@@ -190,6 +244,9 @@ impl Display for BuiltinFunction {
                 }
                 write!(f, ")")
             }
+            JsonExtract { json, paths } => {
+                write!(f, "({}, {})", json, paths.iter().join(", "))
+            }
             JsonExtractPath { json, keys } => {
                 write!(f, "({}, {})", json, keys.iter().join(", "))
             }
@@ -226,6 +283,19 @@ impl Display for BuiltinFunction {
                 write!(f, ")")
             }
             SplitPart(string, delimiter, field) => write!(f, "({string}, {delimiter}, {field})"),
+            Lower(arg) | Upper(arg) => write!(f, "({arg})"),
+            Trim {
+                string,
+                remove,
+                side,
+                ..
+            } => {
+                write!(f, "({side} ")?;
+                if let Some(remove) = remove {
+                    write!(f, "{remove} ")?;
+                }
+                write!(f, "from {string})")
+            }
             Greatest { args, .. } | Least { args, .. } => {
                 write!(f, "({})", args.iter().join(", "))
             }
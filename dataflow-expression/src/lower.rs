@@ -1,14 +1,12 @@
 use std::iter;
 
 use nom_sql::{
-    BinaryOperator as SqlBinaryOperator, Column, Expr as AstExpr, FunctionExpr, InValue, Relation,
-    UnaryOperator,
+    BinaryOperator as SqlBinaryOperator, Column, Expr as AstExpr, FunctionExpr, InValue,
+    IntervalUnit, Relation, TrimSide, UnaryOperator,
 };
 use readyset_data::dialect::SqlEngine;
 use readyset_data::{DfType, DfValue};
-use readyset_errors::{
-    internal, internal_err, invalid, invalid_err, unsupported, ReadySetError, ReadySetResult,
-};
+use readyset_errors::{internal, invalid, invalid_err, unsupported, ReadySetError, ReadySetResult};
 use readyset_util::redacted::Sensitive;
 use vec1::Vec1;
 
@@ -126,6 +124,42 @@ fn mysql_least_greatest_compare_as(arg_types: Vec<&DfType>) -> DfType {
 }
 
 impl BuiltinFunction {
+    /// Construct a call to `trim` which removes `remove` (or spaces, if not given) from the given
+    /// `side` of `string`, returning the function along with its return type
+    fn trim(
+        string: Expr,
+        remove: Option<Expr>,
+        side: TrimSide,
+        dialect: Dialect,
+    ) -> (Self, DfType) {
+        let ty = if string.ty().is_any_text() {
+            string.ty().clone()
+        } else {
+            DfType::DEFAULT_TEXT
+        };
+        let cast_to_text = |expr: Expr, ty: &DfType| {
+            if expr.ty().is_any_text() {
+                expr
+            } else {
+                Expr::Cast {
+                    expr: Box::new(expr),
+                    ty: ty.clone(),
+                    null_on_failure: false,
+                }
+            }
+        };
+
+        (
+            Self::Trim {
+                string: cast_to_text(string, &ty),
+                remove: remove.map(|remove| cast_to_text(remove, &DfType::DEFAULT_TEXT)),
+                side,
+                remove_chars: dialect.engine() == SqlEngine::PostgreSQL,
+            },
+            ty,
+        )
+    }
+
     pub(crate) fn from_name_and_args<A>(
         name: &str,
         args: A,
@@ -245,6 +279,15 @@ impl BuiltinFunction {
             "json_valid" => (Self::JsonValid(next_arg()?), DfType::BigInt),
             "json_overlaps" => (Self::JsonOverlaps(next_arg()?, next_arg()?), DfType::BigInt),
             "json_quote" => (Self::JsonQuote(next_arg()?), DfType::DEFAULT_TEXT),
+            "json_unquote" => (Self::JsonUnquote(next_arg()?), DfType::DEFAULT_TEXT),
+            "json_extract" => (
+                Self::JsonExtract {
+                    json: next_arg()?,
+                    paths: Vec1::try_from_vec(args.by_ref().collect())
+                        .map_err(|_| arity_error())?,
+                },
+                DfType::Json,
+            ),
             "json_typeof" | "jsonb_typeof" => (
                 Self::JsonTypeof(next_arg()?),
                 // Always returns text containing the JSON type.
@@ -365,6 +408,37 @@ impl BuiltinFunction {
                     ty,
                 )
             }
+            "lower" | "lcase" | "upper" | "ucase" => {
+                let string = next_arg()?;
+                let ty = if string.ty().is_any_text() {
+                    string.ty().clone()
+                } else {
+                    DfType::DEFAULT_TEXT
+                };
+                let string = cast(string, ty.clone());
+                (
+                    if matches!(name, "lower" | "lcase") {
+                        Self::Lower(string)
+                    } else {
+                        Self::Upper(string)
+                    },
+                    ty,
+                )
+            }
+            "ltrim" | "rtrim" | "btrim" => {
+                let string = next_arg()?;
+                // Only PostgreSQL accepts the characters to remove as a second argument
+                let remove = match dialect.engine() {
+                    SqlEngine::PostgreSQL => args.next(),
+                    SqlEngine::MySQL => None,
+                };
+                let side = match name {
+                    "ltrim" => TrimSide::Leading,
+                    "rtrim" => TrimSide::Trailing,
+                    _ => TrimSide::Both,
+                };
+                Self::trim(string, remove, side, dialect)
+            }
             "split_part" => (
                 Self::SplitPart(
                     cast(next_arg()?, DfType::DEFAULT_TEXT),
@@ -458,6 +532,28 @@ impl Expr {
         C: LowerContext,
     {
         match expr {
+            AstExpr::Call(FunctionExpr::Call {
+                name: fname,
+                arguments,
+            }) if matches!(
+                fname.as_str(),
+                "date_add" | "adddate" | "date_sub" | "subdate"
+            ) =>
+            {
+                let [date, interval]: [AstExpr; 2] = arguments
+                    .try_into()
+                    .map_err(|_| ReadySetError::ArityError(fname.to_string()))?;
+                let (interval, unit) = match interval {
+                    AstExpr::Interval { value, unit } => (*value, unit),
+                    // `ADDDATE(expr, days)` and `SUBDATE(expr, days)`
+                    days if matches!(fname.as_str(), "adddate" | "subdate") => {
+                        (days, IntervalUnit::Day)
+                    }
+                    _ => invalid!("The second argument to {fname} must be an INTERVAL"),
+                };
+                let subtract = matches!(fname.as_str(), "date_sub" | "subdate");
+                Self::lower_date_add(date, interval, unit, subtract, dialect, context)
+            }
             AstExpr::Call(FunctionExpr::Call {
                 name: fname,
                 arguments,
@@ -489,6 +585,39 @@ impl Expr {
 
                 Ok(Self::Call { func, ty })
             }
+            AstExpr::Call(FunctionExpr::Extract { field, expr }) => {
+                let func = Box::new(BuiltinFunction::Extract(
+                    field,
+                    Self::lower(*expr, dialect, context)?,
+                ));
+                Ok(Self::Call {
+                    func,
+                    ty: DfType::BigInt,
+                })
+            }
+            AstExpr::Call(FunctionExpr::Trim {
+                side,
+                remove,
+                string,
+            }) => {
+                let string = Self::lower(*string, dialect, context.clone())?;
+                let remove = remove
+                    .map(|remove| Self::lower(*remove, dialect, context))
+                    .transpose()?;
+                let (func, ty) = BuiltinFunction::trim(string, remove, side, dialect);
+                Ok(Self::Call {
+                    func: Box::new(func),
+                    ty,
+                })
+            }
+            // Window functions projected directly by a query are computed by window nodes during
+            // planning, so any window function that makes it here is nested in another expression
+            AstExpr::Call(FunctionExpr::Window { function, .. }) => {
+                unsupported!("Window function {function}() is only supported as a projected field")
+            }
+            AstExpr::Call(FunctionExpr::Match { .. }) => {
+                unsupported!("Full-text search with MATCH ... AGAINST is not supported")
+            }
             AstExpr::Call(call) => internal!(
                 "Unexpected (aggregate?) call node in project expression: {:?}",
                 Sensitive(&call)
@@ -509,19 +638,29 @@ impl Expr {
                 let (index, ty) = context.resolve_column(col)?;
                 Ok(Self::Column { index, ty })
             }
+            AstExpr::BinaryOp {
+                lhs,
+                op: op @ (SqlBinaryOperator::Add | SqlBinaryOperator::Subtract),
+                rhs: box AstExpr::Interval { value, unit },
+            } => Self::lower_date_add(
+                *lhs,
+                *value,
+                unit,
+                op == SqlBinaryOperator::Subtract,
+                dialect,
+                context,
+            ),
+            AstExpr::BinaryOp {
+                lhs: box AstExpr::Interval { value, unit },
+                op: SqlBinaryOperator::Add,
+                rhs,
+            } => Self::lower_date_add(*rhs, *value, unit, false, dialect, context),
             AstExpr::BinaryOp { lhs, op, rhs } => {
                 let mut left = Box::new(Self::lower(*lhs, dialect, context.clone())?);
                 let mut right = Box::new(Self::lower(*rhs, dialect, context)?);
                 let (op, negated) =
                     BinaryOperator::from_sql_op(op, dialect, left.ty(), right.ty())?;
 
-                if matches!(
-                    op,
-                    BinaryOperator::JsonPathExtract | BinaryOperator::JsonPathExtractUnquote
-                ) {
-                    unsupported!("'{op}' operator not implemented yet for MySQL");
-                }
-
                 let ty = op.output_type(left.ty(), right.ty())?;
                 let (left_coerce_target, right_coerce_target) =
                     op.argument_type_coercions(left.ty(), right.ty())?;
@@ -609,22 +748,35 @@ impl Expr {
                         Ok(CaseWhenBranch { condition, body })
                     })
                     .collect::<ReadySetResult<Vec<_>>>()?;
-                // TODO: Do case arm types have to match? See if type is inferred at runtime
-                let ty = branches
-                    .first()
-                    .ok_or_else(|| internal_err!("CASE expression cannot have zero branches"))?
-                    .body
-                    .ty()
-                    .clone();
+                if branches.is_empty() {
+                    internal!("CASE expression cannot have zero branches");
+                }
+                let else_expr = match else_expr {
+                    Some(else_expr) => Box::new(Self::lower(*else_expr, dialect, context)?),
+                    None => Box::new(Self::Literal {
+                        val: DfValue::None,
+                        ty: DfType::Unknown,
+                    }),
+                };
+                let arm_tys = branches
+                    .iter()
+                    .map(|branch| branch.body.ty())
+                    .chain(iter::once(else_expr.ty()))
+                    .collect::<Vec<_>>();
+                let ty = match dialect.engine() {
+                    SqlEngine::PostgreSQL => unify_postgres_types(arm_tys)?,
+                    // TODO: What are the rules for MySQL's return type inference? For now, use
+                    // the type of the first arm whose type is known, so that (for example)
+                    // `CASE WHEN x THEN NULL ELSE 1 END` still gets a useful type
+                    SqlEngine::MySQL => arm_tys
+                        .into_iter()
+                        .find(|ty| ty.is_known())
+                        .cloned()
+                        .unwrap_or_default(),
+                };
                 Ok(Self::CaseWhen {
                     branches,
-                    else_expr: match else_expr {
-                        Some(else_expr) => Box::new(Self::lower(*else_expr, dialect, context)?),
-                        None => Box::new(Self::Literal {
-                            val: DfValue::None,
-                            ty: DfType::Unknown,
-                        }),
-                    },
+                    else_expr,
                     ty,
                 })
             }
@@ -731,6 +883,9 @@ impl Expr {
                 })
             }
             AstExpr::Exists(_) => unsupported!("EXISTS not currently supported"),
+            AstExpr::Interval { .. } => {
+                unsupported!("INTERVAL is only supported when added to or subtracted from a date")
+            }
             AstExpr::Variable(_) => unsupported!("Variables not currently supported"),
             AstExpr::Between { .. } | AstExpr::NestedSelect(_) | AstExpr::In { .. } => {
                 internal!(
@@ -741,6 +896,52 @@ impl Expr {
         }
     }
 
+    /// Lower the addition of `interval` `unit`s to `date` (or their subtraction, if `subtract` is
+    /// true) to a call to [`BuiltinFunction::DateAdd`]
+    fn lower_date_add<C>(
+        date: AstExpr,
+        interval: AstExpr,
+        unit: IntervalUnit,
+        subtract: bool,
+        dialect: Dialect,
+        context: C,
+    ) -> ReadySetResult<Self>
+    where
+        C: LowerContext,
+    {
+        let date = Self::lower(date, dialect, context.clone())?;
+        let mut interval = Self::lower(interval, dialect, context)?;
+        if subtract {
+            let ty = interval.ty().clone();
+            interval = Self::Op {
+                op: BinaryOperator::Multiply,
+                left: Box::new(interval),
+                right: Box::new(Self::Literal {
+                    val: DfValue::Int(-1),
+                    ty: DfType::Int,
+                }),
+                ty,
+            };
+        }
+
+        // Adding days (or any longer unit) to a date results in a date, but adding a shorter unit
+        // (or anything to a value which isn't a date) results in a datetime
+        let ty = match date.ty() {
+            DfType::Date if unit >= IntervalUnit::Day => DfType::Date,
+            ty @ (DfType::DateTime { .. }
+            | DfType::Timestamp { .. }
+            | DfType::TimestampTz { .. }) => ty.clone(),
+            _ => DfType::DateTime {
+                subsecond_digits: dialect.default_subsecond_digits(),
+            },
+        };
+
+        Ok(Self::Call {
+            func: Box::new(BuiltinFunction::DateAdd(date, interval, unit)),
+            ty,
+        })
+    }
+
     fn lower_op_any_or_all<C>(
         lhs: AstExpr,
         op: SqlBinaryOperator,
@@ -168,6 +168,9 @@ pub struct PostLookup {
     /// If an empty `Vec` is specified, rows are sorted in lexicographic order.
     pub order_by: Option<Vec<(usize, OrderType)>>,
     /// Maximum number of records to return
+    ///
+    /// This is `None` for queries with a parametrized `LIMIT`, the value of which is instead
+    /// provided with each read (after the result set has been ordered by `order_by`)
     pub limit: Option<usize>,
     /// Indices of the columns requested in the query. Reader will filter out all other projected
     /// columns
@@ -0,0 +1,266 @@
+//! Compressed storage for cold keys in partially materialized [`MemoryState`].
+//!
+//! When [state compression](StateCompression) is enabled for a [`MemoryState`], once the
+//! uncompressed rows in that state grow past a configured size, randomly selected keys are moved
+//! out of the (uncompressed) index and into a [`CompressedRows`], which stores all the rows for
+//! each key as a single zstd-compressed block of bincode-serialized rows. Lookups that miss in the
+//! uncompressed index fall back to the compressed blocks, decompressing them on demand and keeping
+//! a small LRU cache of the most recently decompressed keys so that repeated reads of the same cold
+//! key don't pay for decompression every time. Writes to a compressed key move that key back into
+//! the uncompressed index before being applied.
+//!
+//! [`MemoryState`]: crate::MemoryState
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+
+use bincode::Options;
+use common::SizeOf;
+use lru::LruCache;
+use readyset_data::DfValue;
+use serde::{Deserialize, Serialize};
+
+/// Configuration for compressing cold keys in partially materialized in-memory state.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StateCompression {
+    /// Once the uncompressed rows stored in a node's partial state grow past this many bytes,
+    /// keys are compressed until the state is back under this limit.
+    pub max_uncompressed_bytes: u64,
+    /// The number of decompressed keys to cache for reads, per node.
+    pub hot_keys: usize,
+    /// The zstd compression level to use when compressing keys.
+    pub level: i32,
+}
+
+impl Default for StateCompression {
+    fn default() -> Self {
+        Self {
+            max_uncompressed_bytes: 64 * 1024 * 1024,
+            hot_keys: 1024,
+            level: zstd::DEFAULT_COMPRESSION_LEVEL,
+        }
+    }
+}
+
+/// Statistics about the compressed keys in a [`MemoryState`](crate::MemoryState)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CompressionStats {
+    /// The number of keys currently stored compressed
+    pub compressed_keys: usize,
+    /// The total size in bytes of all compressed blocks
+    pub compressed_bytes: u64,
+    /// The number of lookups of compressed keys which were served from the cache of decompressed
+    /// keys
+    pub cache_hits: u64,
+    /// The number of lookups of compressed keys which required decompressing the key
+    pub cache_misses: u64,
+}
+
+/// The rows for a single key, serialized and compressed
+struct CompressedBlock {
+    data: Box<[u8]>,
+    rows: usize,
+}
+
+/// The compressed tier of a [`MemoryState`](crate::MemoryState).
+///
+/// See [the module documentation](self) for more information.
+pub(crate) struct CompressedRows {
+    params: StateCompression,
+    blocks: HashMap<Vec<DfValue>, CompressedBlock>,
+    compressed_bytes: u64,
+    row_count: usize,
+    /// Cache of recently decompressed keys. This is in a [`RefCell`] since lookups only take a
+    /// shared reference to the state.
+    cache: RefCell<LruCache<Vec<DfValue>, Vec<Vec<DfValue>>>>,
+    cache_hits: Cell<u64>,
+    cache_misses: Cell<u64>,
+}
+
+impl CompressedRows {
+    pub(crate) fn new(params: StateCompression) -> Self {
+        let cache_size = NonZeroUsize::new(params.hot_keys.max(1)).unwrap();
+        Self {
+            params,
+            blocks: HashMap::new(),
+            compressed_bytes: 0,
+            row_count: 0,
+            cache: RefCell::new(LruCache::new(cache_size)),
+            cache_hits: Cell::new(0),
+            cache_misses: Cell::new(0),
+        }
+    }
+
+    /// Returns the configured maximum size of the uncompressed part of the state
+    pub(crate) fn max_uncompressed_bytes(&self) -> u64 {
+        self.params.max_uncompressed_bytes
+    }
+
+    /// Compress and store the given `rows` for `key`, which must not already be compressed
+    pub(crate) fn insert(&mut self, key: Vec<DfValue>, rows: Vec<Vec<DfValue>>) {
+        let serialized = bincode::options().serialize(&rows).unwrap();
+        let data = zstd::bulk::compress(&serialized, self.params.level)
+            .unwrap()
+            .into_boxed_slice();
+
+        self.compressed_bytes += block_bytes(&key, &data);
+        self.row_count += rows.len();
+        let block = CompressedBlock {
+            data,
+            rows: rows.len(),
+        };
+        if let Some(old) = self.blocks.insert(key.clone(), block) {
+            self.forget(&key, &old);
+        }
+    }
+
+    /// Look up the rows for `key`, decompressing them if they aren't in the cache of hot keys.
+    /// Returns `None` if `key` is not compressed.
+    pub(crate) fn lookup(&self, key: &[DfValue]) -> Option<Vec<Vec<DfValue>>> {
+        let block = self.blocks.get(key)?;
+        let mut cache = self.cache.borrow_mut();
+        if let Some(rows) = cache.get(key) {
+            self.cache_hits.set(self.cache_hits.get() + 1);
+            return Some(rows.clone());
+        }
+
+        self.cache_misses.set(self.cache_misses.get() + 1);
+        let rows = decompress(block);
+        cache.put(key.to_vec(), rows.clone());
+        Some(rows)
+    }
+
+    /// Remove `key`, returning its decompressed rows if it was compressed
+    pub(crate) fn take(&mut self, key: &[DfValue]) -> Option<Vec<Vec<DfValue>>> {
+        let (key, block) = self.blocks.remove_entry(key)?;
+        self.forget(&key, &block);
+        Some(
+            self.cache
+                .get_mut()
+                .pop(&key)
+                .unwrap_or_else(|| decompress(&block)),
+        )
+    }
+
+    /// Remove `key` without decompressing it, returning the number of bytes freed
+    pub(crate) fn remove(&mut self, key: &[DfValue]) -> u64 {
+        match self.blocks.remove_entry(key) {
+            Some((key, block)) => {
+                self.cache.get_mut().pop(&key);
+                self.forget(&key, &block)
+            }
+            None => 0,
+        }
+    }
+
+    /// Remove an arbitrary key, returning the key and the number of bytes freed
+    pub(crate) fn remove_any(&mut self) -> Option<(Vec<DfValue>, u64)> {
+        let key = self.blocks.keys().next()?.clone();
+        let bytes_freed = self.remove(&key);
+        Some((key, bytes_freed))
+    }
+
+    /// Remove all compressed keys
+    pub(crate) fn clear(&mut self) {
+        self.blocks.clear();
+        self.cache.get_mut().clear();
+        self.compressed_bytes = 0;
+        self.row_count = 0;
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    pub(crate) fn key_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub(crate) fn row_count(&self) -> usize {
+        self.row_count
+    }
+
+    /// Returns the total size in bytes of the compressed keys, plus the decompressed keys in the
+    /// cache
+    pub(crate) fn deep_size_of(&self) -> u64 {
+        let cache = self.cache.borrow();
+        let cached_bytes: u64 = cache
+            .iter()
+            .map(|(_, rows)| rows.iter().map(|r| r.deep_size_of()).sum::<u64>())
+            .sum();
+        self.compressed_bytes + cached_bytes
+    }
+
+    pub(crate) fn stats(&self) -> CompressionStats {
+        CompressionStats {
+            compressed_keys: self.blocks.len(),
+            compressed_bytes: self.compressed_bytes,
+            cache_hits: self.cache_hits.get(),
+            cache_misses: self.cache_misses.get(),
+        }
+    }
+
+    /// Update bookkeeping for a block which has been removed from `self.blocks`, returning its
+    /// size
+    fn forget(&mut self, key: &[DfValue], block: &CompressedBlock) -> u64 {
+        let bytes = block_bytes(key, &block.data);
+        self.compressed_bytes = self.compressed_bytes.saturating_sub(bytes);
+        self.row_count = self.row_count.saturating_sub(block.rows);
+        bytes
+    }
+}
+
+fn block_bytes(key: &[DfValue], data: &[u8]) -> u64 {
+    key.iter().map(SizeOf::deep_size_of).sum::<u64>() + data.len() as u64
+}
+
+fn decompress(block: &CompressedBlock) -> Vec<Vec<DfValue>> {
+    let serialized = zstd::stream::decode_all(&*block.data).unwrap();
+    bincode::options().deserialize(&serialized).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(n: i32) -> Vec<Vec<DfValue>> {
+        (0..n)
+            .map(|i| vec![DfValue::from(i), DfValue::from(format!("row {i}"))])
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut compressed = CompressedRows::new(StateCompression::default());
+        compressed.insert(vec![1.into()], rows(10));
+        assert_eq!(compressed.key_count(), 1);
+        assert_eq!(compressed.row_count(), 10);
+        assert_eq!(compressed.lookup(&[1.into()]), Some(rows(10)));
+        assert_eq!(compressed.lookup(&[2.into()]), None);
+        assert_eq!(compressed.take(&[1.into()]), Some(rows(10)));
+        assert!(compressed.is_empty());
+        assert_eq!(compressed.row_count(), 0);
+        assert_eq!(compressed.stats().compressed_bytes, 0);
+    }
+
+    #[test]
+    fn cache_hits_and_misses() {
+        let mut compressed = CompressedRows::new(StateCompression {
+            hot_keys: 1,
+            ..Default::default()
+        });
+        compressed.insert(vec![1.into()], rows(3));
+        compressed.insert(vec![2.into()], rows(4));
+
+        compressed.lookup(&[1.into()]);
+        compressed.lookup(&[1.into()]);
+        compressed.lookup(&[2.into()]);
+        compressed.lookup(&[1.into()]);
+
+        let stats = compressed.stats();
+        assert_eq!(stats.cache_hits, 1);
+        assert_eq!(stats.cache_misses, 3);
+        assert_eq!(stats.compressed_keys, 2);
+    }
+}
@@ -1,10 +1,12 @@
 #![feature(stmt_expr_attributes, bound_map, iter_order_by, bound_as_ref)]
 
+mod compressed_rows;
 mod key;
 mod keyed_state;
 mod memory_state;
 mod mk_key;
 mod persistent_state;
+mod remote_storage;
 mod single_state;
 
 use std::borrow::Cow;
@@ -25,11 +27,14 @@ use readyset_client::{KeyComparison, KeyCount};
 use readyset_data::DfValue;
 use readyset_errors::ReadySetResult;
 
+pub use crate::compressed_rows::{CompressionStats, StateCompression};
 pub use crate::key::{PointKey, RangeKey};
 pub use crate::memory_state::MemoryState;
 pub use crate::persistent_state::{
-    DurabilityMode, PersistenceParameters, PersistentState, PersistentStateHandle, SnapshotMode,
+    BaseTableCompression, DurabilityMode, PersistenceParameters, PersistentState,
+    PersistentStateHandle, RocksDbTuning, SnapshotMode, TombstoneStats,
 };
+pub use crate::remote_storage::RemoteStorage;
 
 /// Information about state evicted via a call to [`State::evict_bytes`]
 pub struct EvictBytesResult<'a> {
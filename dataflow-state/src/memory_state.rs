@@ -9,7 +9,9 @@ use readyset_client::{KeyComparison, KeyCount};
 use readyset_data::DfValue;
 use readyset_errors::ReadySetResult;
 use tracing::trace;
+use vec1::Vec1;
 
+use crate::compressed_rows::{CompressedRows, CompressionStats, StateCompression};
 use crate::keyed_state::KeyedState;
 use crate::single_state::SingleState;
 use crate::{
@@ -26,6 +28,9 @@ pub struct MemoryState {
     /// The latest replication offset that has been written to the base table backed by this
     /// [`MemoryState`], it is only used when [`LocalAuthority`] is the ReadySet authority.
     replication_offset: Option<ReplicationOffset>,
+    /// Compressed storage for cold keys, if [state compression](StateCompression) is enabled for
+    /// this state
+    compressed: Option<CompressedRows>,
 }
 
 impl SizeOf for MemoryState {
@@ -37,10 +42,14 @@ impl SizeOf for MemoryState {
 
     fn deep_size_of(&self) -> u64 {
         self.mem_size
+            + self
+                .compressed
+                .as_ref()
+                .map_or(0, CompressedRows::deep_size_of)
     }
 
     fn is_empty(&self) -> bool {
-        self.state[0].is_empty()
+        self.state[0].is_empty() && self.compressed.as_ref().map_or(true, |c| c.is_empty())
     }
 }
 
@@ -108,6 +117,8 @@ impl State for MemoryState {
         partial_tag: Option<Tag>,
         replication_offset: Option<ReplicationOffset>,
     ) -> ReadySetResult<()> {
+        self.decompress_keys_for(records);
+
         if self.is_partial() {
             records.retain(|r| {
                 // we need to check that we're not erroneously filling any holes
@@ -148,16 +159,26 @@ impl State for MemoryState {
             self.replication_offset = Some(replication_offset);
         }
 
+        self.compress_cold_keys();
+
         Ok(())
     }
 
     fn key_count(&self) -> KeyCount {
-        let count = self.state.iter().map(SingleState::key_count).sum();
+        let count = self.state.iter().map(SingleState::key_count).sum::<usize>()
+            + self
+                .compressed
+                .as_ref()
+                .map_or(0, CompressedRows::key_count);
         KeyCount::ExactKeyCount(count)
     }
 
     fn row_count(&self) -> usize {
-        self.state.iter().map(SingleState::row_count).sum()
+        self.state.iter().map(SingleState::row_count).sum::<usize>()
+            + self
+                .compressed
+                .as_ref()
+                .map_or(0, CompressedRows::row_count)
     }
 
     fn mark_filled(&mut self, key: KeyComparison, tag: Tag) {
@@ -171,6 +192,9 @@ impl State for MemoryState {
         debug_assert!(!self.state.is_empty(), "filling uninitialized index");
         let index = self.by_tag[&tag];
         let freed_bytes = self.state[index].mark_hole(key);
+        if let (Some(compressed), KeyComparison::Equal(key)) = (&mut self.compressed, key) {
+            compressed.remove(key);
+        }
         self.mem_size = self
             .mem_size
             .saturating_sub(freed_bytes + base_row_bytes_from_comparison(key));
@@ -187,6 +211,21 @@ impl State for MemoryState {
             return ret;
         }
 
+        if let Some(rows) = self
+            .compressed
+            .as_ref()
+            .filter(|compressed| !compressed.is_empty())
+            .and_then(|compressed| {
+                compressed.lookup(
+                    &(0..key.len())
+                        .map(|i| key.get(i).unwrap().clone())
+                        .collect::<Vec<_>>(),
+                )
+            })
+        {
+            return LookupResult::Some(RecordResult::Owned(rows));
+        }
+
         trace!(?columns, ?key, "lookup missed; trying other indexes");
 
         // We missed in the index we tried to look up on, but we might have the rows
@@ -319,12 +358,25 @@ impl State for MemoryState {
             bytes_freed += base_row_bytes(&keys);
             keys_evicted.push(keys);
         }
+        self.mem_size = self.mem_size.saturating_sub(bytes_freed);
+
+        // Once the uncompressed keys are exhausted, move on to evicting compressed keys
+        if let Some(compressed) = &mut self.compressed {
+            while bytes_freed < bytes as u64 {
+                match compressed.remove_any() {
+                    Some((keys, freed)) => {
+                        bytes_freed += freed;
+                        keys_evicted.push(keys);
+                    }
+                    None => break,
+                }
+            }
+        }
 
         if bytes_freed == 0 {
             return None;
         }
 
-        self.mem_size = self.mem_size.saturating_sub(bytes_freed);
         return Some(EvictBytesResult {
             index: self.state[state_index].index(),
             keys_evicted,
@@ -349,6 +401,14 @@ impl State for MemoryState {
 
             self.mem_size = self.mem_size.saturating_sub(bytes_freed + key_bytes);
 
+            if let Some(compressed) = &mut self.compressed {
+                for key in keys {
+                    if let KeyComparison::Equal(key) = key {
+                        bytes_freed += compressed.remove(key);
+                    }
+                }
+            }
+
             EvictKeysResult {
                 index: self.state[state_index].index(),
                 bytes_freed,
@@ -359,9 +419,8 @@ impl State for MemoryState {
     /// Randomly evict a single key from the state associated with the target of the given `tag`
     fn evict_random<R: rand::Rng>(&mut self, tag: Tag, rng: &mut R) -> Option<EvictRandomResult> {
         self.by_tag.get(&tag).cloned().and_then(move |state_index| {
-            self.state[state_index]
-                .evict_random(rng)
-                .map(|(key, rows)| {
+            match self.state[state_index].evict_random(rng) {
+                Some((key, rows)) => {
                     let mut bytes_freed = 0;
                     rows.iter()
                         .for_each(|row| bytes_freed += self.handle_evicted_row(row));
@@ -369,12 +428,21 @@ impl State for MemoryState {
                     bytes_freed += key_bytes;
                     self.mem_size = self.mem_size.saturating_sub(bytes_freed);
 
-                    EvictRandomResult {
+                    Some(EvictRandomResult {
                         index: self.state[state_index].index(),
                         key_evicted: key,
                         bytes_freed,
-                    }
-                })
+                    })
+                }
+                None => {
+                    let (key, bytes_freed) = self.compressed.as_mut()?.remove_any()?;
+                    Some(EvictRandomResult {
+                        index: self.state[state_index].index(),
+                        key_evicted: key,
+                        bytes_freed,
+                    })
+                }
+            }
         })
     }
 
@@ -382,6 +450,9 @@ impl State for MemoryState {
         for state in &mut self.state {
             state.clear();
         }
+        if let Some(compressed) = &mut self.compressed {
+            compressed.clear();
+        }
         self.mem_size = 0;
     }
 
@@ -404,6 +475,85 @@ impl State for MemoryState {
 }
 
 impl MemoryState {
+    /// Construct a new, empty [`MemoryState`] which compresses cold keys according to the given
+    /// [`StateCompression`] parameters once it grows too large.
+    ///
+    /// Compression only takes effect for partial states with a single hash map index and no weak
+    /// indices; all other states are left uncompressed.
+    pub fn with_compression(params: StateCompression) -> Self {
+        Self {
+            compressed: Some(CompressedRows::new(params)),
+            ..Default::default()
+        }
+    }
+
+    /// Returns statistics about the compressed keys in this state, if compression is enabled
+    pub fn compression_stats(&self) -> Option<CompressionStats> {
+        self.compressed.as_ref().map(CompressedRows::stats)
+    }
+
+    /// Returns true if cold keys in this state can be compressed
+    fn compressible(&self) -> bool {
+        self.compressed.is_some()
+            && self.weak_indices.is_empty()
+            && self.state.len() == 1
+            && self.state[0].partial()
+            && self.state[0].index_type() == IndexType::HashMap
+            && !self.state[0].columns().is_empty()
+    }
+
+    /// Move random keys out of the uncompressed index and into compressed storage until the size
+    /// of the uncompressed index is under the configured limit
+    fn compress_cold_keys(&mut self) {
+        if !self.compressible() {
+            return;
+        }
+        let Some(compressed) = &mut self.compressed else {
+            return;
+        };
+
+        let mut rng = rand::thread_rng();
+        while self.mem_size > compressed.max_uncompressed_bytes() {
+            let Some((key, rows)) = self.state[0].evict_random(&mut rng) else {
+                break;
+            };
+            let freed = rows.iter().map(|r| r.deep_size_of()).sum::<u64>() + base_row_bytes(&key);
+            self.mem_size = self.mem_size.saturating_sub(freed);
+            compressed.insert(key, rows.iter().map(|r| Vec::clone(r)).collect());
+        }
+    }
+
+    /// Move any compressed keys which will be written to by the given `records` back into the
+    /// uncompressed index, so that the writes can be applied to them
+    fn decompress_keys_for(&mut self, records: &Records) {
+        let Some(compressed) = &mut self.compressed else {
+            return;
+        };
+        if compressed.is_empty() || self.state.len() != 1 {
+            return;
+        }
+
+        let columns = self.state[0].columns().to_vec();
+        for record in records.iter() {
+            let key = columns
+                .iter()
+                .map(|&c| record[c].clone())
+                .collect::<Vec<_>>();
+            let Some(rows) = compressed.take(&key) else {
+                continue;
+            };
+
+            self.mem_size += base_row_bytes(&key);
+            // Keys are only ever compressed in states with at least one column
+            self.state[0].mark_filled(KeyComparison::Equal(Vec1::try_from_vec(key).unwrap()));
+            for row in rows {
+                let row = Row::from(row);
+                self.mem_size += row.deep_size_of();
+                self.state[0].insert_row(row);
+            }
+        }
+    }
+
     /// Returns the index in `self.state` of the index keyed on `cols` and with the given
     /// `index_type`, or None if no such index exists.
     fn state_for(&self, cols: &[usize], index_type: IndexType) -> Option<usize> {
@@ -678,6 +828,62 @@ mod tests {
         assert_eq!(res, rows);
     }
 
+    #[test]
+    fn compressed_cold_keys() {
+        let mut state = MemoryState::with_compression(StateCompression {
+            max_uncompressed_bytes: 0,
+            hot_keys: 1,
+            ..Default::default()
+        });
+        let tag = Tag::new(0);
+        state.add_key(Index::hash_map(vec![0]), Some(vec![tag]));
+        for key in 0..10 {
+            state.mark_filled(KeyComparison::Equal(vec1![key.into()]), tag);
+            state
+                .process_records(
+                    &mut vec![vec![DfValue::from(key), DfValue::from(key * 10)]].into(),
+                    Some(tag),
+                    None,
+                )
+                .unwrap();
+        }
+
+        let stats = state.compression_stats().unwrap();
+        assert_eq!(stats.compressed_keys, 10);
+        assert_eq!(state.row_count(), 10);
+        assert_eq!(
+            state.lookup(&[0], &PointKey::Single(3.into())).unwrap(),
+            RecordResult::Owned(vec![vec![3.into(), 30.into()]])
+        );
+        state.lookup(&[0], &PointKey::Single(3.into()));
+        let stats = state.compression_stats().unwrap();
+        assert_eq!(stats.cache_misses, 1);
+        assert_eq!(stats.cache_hits, 1);
+
+        // Writes to a compressed key are applied to its existing rows
+        state
+            .process_records(
+                &mut vec![vec![DfValue::from(3), DfValue::from(31)]].into(),
+                None,
+                None,
+            )
+            .unwrap();
+        let mut res = state
+            .lookup(&[0], &PointKey::Single(3.into()))
+            .unwrap()
+            .into_iter()
+            .map(|r| r.into_owned())
+            .collect::<Vec<_>>();
+        res.sort();
+        assert_eq!(
+            res,
+            vec![vec![3.into(), 30.into()], vec![3.into(), 31.into()]]
+        );
+
+        state.mark_hole(&KeyComparison::Equal(vec1![3.into()]), tag);
+        assert!(state.lookup(&[0], &PointKey::Single(3.into())).is_missing());
+    }
+
     mod lookup_range {
         use std::ops::{Bound, RangeBounds};
 
@@ -60,33 +60,44 @@
 //! that replication log of the last record that we have successfully applied. To maintain
 //! atomicity, these offsets are stored inside of rocksdb as part of the persisted
 //! [`PersistentMeta`], and updated as part of every write.
+//!
+//! # Per-table tuning
+//!
+//! Each base table (or shard of a base table) is stored in its own RocksDB database, with its own
+//! set of column families, so dropping a table drops its column families along with the rest of
+//! its database, and compactions can be targeted at a single table. The block cache size,
+//! compression algorithm, and write buffer size used for a table can be configured with
+//! [`RocksDbTuning`], either for all tables or per-table via [`PersistenceParameters`], and can be
+//! changed at runtime with [`PersistentState::set_tuning`].
 
 use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::io::{self, Read};
 use std::ops::Bound;
-use std::path::PathBuf;
-use std::str::FromStr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 use std::{fmt, fs};
 
 use bincode::Options;
-use clap::ValueEnum;
 use common::{IndexType, Record, Records, SizeOf, Tag};
+use nom_sql::Relation;
 use parking_lot::{
     MappedRwLockReadGuard, MappedRwLockWriteGuard, RwLock, RwLockReadGuard, RwLockWriteGuard,
 };
 use readyset_client::internal::Index;
 use readyset_client::replication::ReplicationOffset;
+pub use readyset_client::{BaseTableCompression, DurabilityMode, RocksDbTuning};
 use readyset_client::{KeyComparison, KeyCount, SqlIdentifier};
 use readyset_data::DfValue;
 use readyset_errors::{internal_err, invariant, ReadySetError, ReadySetResult};
 use readyset_util::intervals::BoundPair;
+use rocksdb::checkpoint::Checkpoint;
 use rocksdb::{
-    self, ColumnFamilyDescriptor, CompactOptions, EncodingType, IteratorMode,
-    PlainTableFactoryOptions, SliceTransform, WriteBatch, DB,
+    self, BlockBasedOptions, Cache, ColumnFamilyDescriptor, CompactOptions, EncodingType,
+    IteratorMode, PlainTableFactoryOptions, SliceTransform, WriteBatch, DB,
 };
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
@@ -95,9 +106,10 @@ use test_strategy::Arbitrary;
 use thiserror::Error;
 use tracing::{debug, error, info, info_span, instrument, warn};
 
+use crate::remote_storage::{self, RemoteStorage, UploaderHandle};
 use crate::{
     EvictKeysResult, EvictRandomResult, LookupResult, PointKey, RangeKey, RangeLookupResult,
-    RecordResult, State,
+    RecordResult, State, StateCompression,
 };
 
 // Incremented on each PersistentState initialization so that IndexSeq
@@ -199,37 +211,6 @@ impl SnapshotMode {
     }
 }
 
-/// Indicates to what degree updates should be persisted.
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, ValueEnum)]
-pub enum DurabilityMode {
-    /// Don't do any durability
-    #[value(alias("memory"))]
-    MemoryOnly,
-    /// Delete any log files on exit. Useful mainly for tests.
-    #[value(alias("ephemeral"))]
-    DeleteOnExit,
-    /// Persist updates to disk, and don't delete them later.
-    #[value(alias("persistent"))]
-    Permanent,
-}
-
-#[derive(Debug, Error)]
-#[error("Invalid durability mode; expected one of persistent, ephemeral, or memory")]
-pub struct InvalidDurabilityMode;
-
-impl FromStr for DurabilityMode {
-    type Err = InvalidDurabilityMode;
-
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        match s {
-            "persistent" => Ok(Self::Permanent),
-            "ephemeral" => Ok(Self::DeleteOnExit),
-            "memory" => Ok(Self::MemoryOnly),
-            _ => Err(InvalidDurabilityMode),
-        }
-    }
-}
-
 /// Parameters to control the operation of GroupCommitQueue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PersistenceParameters {
@@ -242,6 +223,26 @@ pub struct PersistenceParameters {
     /// An optional path to a directory where to store the DB files, if None will be stored in the
     /// current working directory
     pub db_dir: Option<PathBuf>,
+    /// Per-table overrides for [`mode`](Self::mode). A table name without a schema applies to
+    /// tables with that name in any schema.
+    #[serde(default)]
+    pub table_modes: HashMap<Relation, DurabilityMode>,
+    /// If set, compress cold keys in partially materialized in-memory state according to these
+    /// parameters. See [`MemoryState::with_compression`](crate::MemoryState::with_compression).
+    #[serde(default)]
+    pub state_compression: Option<StateCompression>,
+    /// RocksDB tuning parameters for the persisted state of all base tables
+    #[serde(default)]
+    pub tuning: RocksDbTuning,
+    /// Per-table overrides for [`tuning`](Self::tuning). A table name without a schema applies to
+    /// tables with that name in any schema.
+    #[serde(default)]
+    pub table_tuning: HashMap<Relation, RocksDbTuning>,
+    /// If set, periodically upload checkpoints of the persisted state of base tables to a remote
+    /// object store, and restore base tables from there if they don't exist locally. Only takes
+    /// effect for tables in [`DurabilityMode::Permanent`].
+    #[serde(default)]
+    pub remote_storage: Option<RemoteStorage>,
 }
 
 impl Default for PersistenceParameters {
@@ -251,6 +252,11 @@ impl Default for PersistenceParameters {
             db_filename_prefix: String::from("readyset"),
             persistence_threads: 1,
             db_dir: None,
+            table_modes: HashMap::new(),
+            state_compression: None,
+            tuning: RocksDbTuning::default(),
+            table_tuning: HashMap::new(),
+            remote_storage: None,
         }
     }
 }
@@ -283,10 +289,82 @@ impl PersistenceParameters {
             db_filename_prefix,
             persistence_threads,
             db_dir,
+            table_modes: HashMap::new(),
+            state_compression: None,
+            tuning: RocksDbTuning::default(),
+            table_tuning: HashMap::new(),
+            remote_storage: None,
+        }
+    }
+
+    /// Override the durability mode for the base table with the given name
+    pub fn set_table_mode(&mut self, table: Relation, mode: DurabilityMode) {
+        self.table_modes.insert(table, mode);
+    }
+
+    /// Store checkpoints of the persisted state of base tables in a remote object store according
+    /// to the given configuration
+    pub fn set_remote_storage(&mut self, config: Option<RemoteStorage>) {
+        self.remote_storage = config;
+    }
+
+    /// Compress cold keys in partially materialized in-memory state according to the given
+    /// parameters
+    pub fn set_state_compression(&mut self, params: Option<StateCompression>) {
+        self.state_compression = params;
+    }
+
+    /// Returns the durability mode to use for the base table with the given name, taking into
+    /// account any per-table overrides
+    pub fn mode_for_table(&self, table: &Relation) -> &DurabilityMode {
+        table_override(&self.table_modes, table).unwrap_or(&self.mode)
+    }
+
+    /// Override the RocksDB tuning parameters for the base table with the given name. Any
+    /// parameters left unset in `tuning` fall back to those in [`tuning`](Self::tuning)
+    pub fn set_table_tuning(&mut self, table: Relation, tuning: RocksDbTuning) {
+        self.table_tuning.insert(table, tuning);
+    }
+
+    /// Returns the RocksDB tuning parameters to use for the base table with the given name, taking
+    /// into account any per-table overrides
+    pub fn tuning_for_table(&self, table: &Relation) -> RocksDbTuning {
+        match table_override(&self.table_tuning, table) {
+            Some(tuning) => tuning.or(self.tuning),
+            None => self.tuning,
         }
     }
 }
 
+/// Look up the per-table override for the given table in `overrides`, falling back to an override
+/// for the unqualified table name if the table is schema-qualified
+fn table_override<'a, T>(overrides: &'a HashMap<Relation, T>, table: &Relation) -> Option<&'a T> {
+    overrides.get(table).or_else(|| {
+        table.schema.as_ref()?;
+        overrides.get(&Relation {
+            schema: None,
+            name: table.name.clone(),
+        })
+    })
+}
+
+/// Returns the RocksDB compression type to use for the given [`BaseTableCompression`]
+fn rocksdb_compression_type(compression: BaseTableCompression) -> rocksdb::DBCompressionType {
+    match compression {
+        BaseTableCompression::None => rocksdb::DBCompressionType::None,
+        BaseTableCompression::Lz4 => rocksdb::DBCompressionType::Lz4,
+    }
+}
+
+/// Returns the value of the `compression` option to pass to RocksDB to use the given
+/// [`BaseTableCompression`]
+fn rocksdb_compression_option(compression: BaseTableCompression) -> &'static str {
+    match compression {
+        BaseTableCompression::None => "kNoCompression",
+        BaseTableCompression::Lz4 => "kLZ4Compression",
+    }
+}
+
 /// Errors that can occur when creating a new persistent state or opening an existing one.
 ///
 /// This is a distinct enum from [`ReadySetError`] so we can make it include [`rocksdb::Error`]
@@ -312,6 +390,9 @@ pub enum Error {
 
     #[error(transparent)]
     Io(#[from] io::Error),
+
+    #[error("Remote storage error: {0}")]
+    RemoteStorage(String),
 }
 
 impl From<Error> for ReadySetError {
@@ -331,6 +412,8 @@ impl Error {
             // Could *maybe* try to slice up the IO errors here, but for now it's simpler to just
             // assume all IO errors are permanent
             Error::Io(_) => true,
+            // Deleting the local DB file won't make the remote storage any more reachable
+            Error::RemoteStorage(_) => true,
             Error::BadDbFormat | Error::SerdeVersionMismatch { .. } => false,
         }
     }
@@ -401,10 +484,58 @@ impl Drop for CompactionThreadHandle {
     }
 }
 
+/// Estimated counts of entries and deletion tombstones in the primary index of a
+/// [`PersistentState`].
+///
+/// Deletes in RocksDB are written as tombstones, which are only dropped when the SST files
+/// containing them are compacted, so a high ratio of tombstones to entries indicates a table that
+/// would benefit from a manual compaction.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TombstoneStats {
+    /// The estimated total number of entries, including deletion tombstones
+    pub entries: u64,
+    /// The estimated number of deletion tombstones
+    pub deletions: u64,
+}
+
+impl TombstoneStats {
+    /// Returns the fraction of all entries which are deletion tombstones, or 0 if there are no
+    /// entries
+    pub fn ratio(&self) -> f64 {
+        if self.entries == 0 {
+            0.0
+        } else {
+            self.deletions as f64 / self.entries as f64
+        }
+    }
+
+    /// Parse the `# entries` and `# deletions` fields out of the value of the
+    /// `rocksdb.aggregated-table-properties` property, which is formatted as a list of
+    /// `key=value` pairs separated by semicolons.
+    fn from_table_properties(props: &str) -> Self {
+        let mut stats = Self::default();
+        for (key, value) in props.split(';').filter_map(|prop| prop.split_once('=')) {
+            let value = match value.trim().parse() {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+            match key.trim() {
+                "# entries" => stats.entries = value,
+                "# deletions" => stats.deletions = value,
+                _ => {}
+            }
+        }
+        stats
+    }
+}
+
 /// PersistentState stores data in RocksDB.
 pub struct PersistentState {
     name: SqlIdentifier,
     default_options: rocksdb::Options,
+    /// The block cache dedicated to this table, if one was configured via
+    /// [`RocksDbTuning::block_cache_bytes`]
+    block_cache: Option<Cache>,
     db: PersistentStateHandle,
     // The list of all the indices that are defined as unique in the schema for this table
     unique_keys: Vec<Box<[usize]>>,
@@ -417,6 +548,9 @@ pub struct PersistentState {
     /// writes will bypass WAL and fsync
     snapshot_mode: SnapshotMode,
     compaction_threads: Vec<CompactionThreadHandle>,
+    /// Handle to the thread uploading checkpoints of this table to remote storage, if
+    /// [`PersistenceParameters::remote_storage`] is set
+    remote_uploader: Option<UploaderHandle>,
 }
 
 /// Things that are shared between read handles and the state itself, that can be locked under a
@@ -472,6 +606,20 @@ impl PersistentStateHandle {
         RwLockReadGuard::map(self.inner.read(), |i| &i.db)
     }
 
+    /// Create a RocksDB checkpoint of the database in a subdirectory of `dir` with the same name
+    /// as the database's own directory, and return the path to that subdirectory. See
+    /// [`PersistentState::checkpoint`].
+    pub(crate) fn checkpoint(&self, dir: &Path) -> Result<PathBuf> {
+        let inner = self.inner();
+        let name = inner.db.path().file_name().ok_or(Error::BadDbFormat)?;
+        let path = dir.join(name);
+        if !dir.is_dir() {
+            fs::create_dir_all(dir)?;
+        }
+        Checkpoint::new(&inner.db)?.create_checkpoint(&path)?;
+        Ok(path)
+    }
+
     fn handle_mut(&self) -> MappedRwLockWriteGuard<'_, DB> {
         RwLockWriteGuard::map(self.inner.write(), |i| &mut i.db)
     }
@@ -1103,9 +1251,14 @@ fn deserialize_row<T: AsRef<[u8]>>(bytes: T) -> Vec<DfValue> {
 ///
 /// This will construct the set of options that *all* column families should have regardless of
 /// index type.
-fn base_options(params: &PersistenceParameters) -> rocksdb::Options {
+fn base_options(params: &PersistenceParameters, block_cache: Option<&Cache>) -> rocksdb::Options {
     let mut opts = rocksdb::Options::default();
-    opts.set_compression_type(rocksdb::DBCompressionType::Lz4);
+    opts.set_compression_type(
+        params
+            .tuning
+            .compression
+            .map_or(rocksdb::DBCompressionType::Lz4, rocksdb_compression_type),
+    );
     opts.create_if_missing(true);
     opts.create_missing_column_families(true);
     opts.set_allow_concurrent_memtable_write(false);
@@ -1126,6 +1279,16 @@ fn base_options(params: &PersistenceParameters) -> rocksdb::Options {
     // Keep up to 4 parallel memtables:
     opts.set_max_write_buffer_number(4);
 
+    if let Some(write_buffer_bytes) = params.tuning.write_buffer_bytes {
+        opts.set_write_buffer_size(write_buffer_bytes);
+    }
+
+    if let Some(cache) = block_cache {
+        let mut block_opts = BlockBasedOptions::default();
+        block_opts.set_block_cache(cache);
+        opts.set_block_based_table_factory(&block_opts);
+    }
+
     opts
 }
 
@@ -1401,14 +1564,30 @@ impl PersistentState {
             }
         };
 
+        // Remote storage is only useful if the local database outlives the process
+        let remote_storage = params
+            .remote_storage
+            .as_ref()
+            .filter(|_| params.mode == DurabilityMode::Permanent);
+        if let Some(remote_storage) = remote_storage {
+            if !full_path.exists() {
+                remote_storage::restore(remote_storage, &full_path)?;
+            }
+        }
+
         let name = SqlIdentifier::from(name);
 
-        match Self::new_inner(name.clone(), full_path.clone(), unique_keys.clone(), params) {
-            Ok(ps) => Ok(Self {
+        let mut state = match Self::new_inner(
+            name.clone(),
+            full_path.clone(),
+            unique_keys.clone(),
+            params,
+        ) {
+            Ok(ps) => Self {
                 _tmpdir: tmpdir,
                 ..ps
-            }),
-            Err(e) if e.is_permanent() => Err(e),
+            },
+            Err(e) if e.is_permanent() => return Err(e),
             Err(error) => {
                 warn!(
                     %error,
@@ -1417,9 +1596,19 @@ impl PersistentState {
                 if full_path.is_dir() {
                     fs::remove_dir_all(&full_path)?;
                 }
-                Self::new_inner(name, full_path, unique_keys, params)
+                Self::new_inner(name, full_path.clone(), unique_keys, params)?
             }
+        };
+
+        if let Some(remote_storage) = remote_storage {
+            state.remote_uploader = Some(remote_storage::spawn_uploader(
+                remote_storage,
+                &full_path,
+                state.read_handle(),
+            )?);
         }
+
+        Ok(state)
     }
 
     fn new_inner(
@@ -1428,7 +1617,8 @@ impl PersistentState {
         unique_keys: Vec<Box<[usize]>>,
         params: &PersistenceParameters,
     ) -> Result<Self> {
-        let default_options = base_options(params);
+        let block_cache = params.tuning.block_cache_bytes.map(Cache::new_lru_cache);
+        let default_options = base_options(params, block_cache.as_ref());
         // We use a column family for each index, and one for metadata.
         // When opening the DB the exact same column families needs to be used,
         // so we'll have to retrieve the existing ones first:
@@ -1536,6 +1726,7 @@ impl PersistentState {
         let mut state = Self {
             name,
             default_options,
+            block_cache,
             seq: 0,
             unique_keys,
             epoch: meta.epoch,
@@ -1543,6 +1734,7 @@ impl PersistentState {
             _tmpdir: None,
             snapshot_mode: SnapshotMode::SnapshotModeDisabled,
             compaction_threads: vec![],
+            remote_uploader: None,
         };
 
         if let Some(pk) = state.unique_keys.first().cloned() {
@@ -1731,6 +1923,119 @@ impl PersistentState {
         self.compaction_threads.is_empty()
     }
 
+    /// Trigger a manual compaction of all the indices of this table in the background, to drop
+    /// any accumulated deletion tombstones. Does nothing if a compaction is already in progress.
+    ///
+    /// Use [`Self::compaction_finished`] to check whether the compaction has completed.
+    pub fn compact(&mut self) {
+        if !self.compaction_finished() {
+            debug!(table = %self.name, "Compaction already in progress, not starting another");
+            return;
+        }
+
+        info!(table = %self.name, "Starting manual compaction");
+        self.spawn_compaction_threads();
+    }
+
+    /// Returns estimated counts of entries and deletion tombstones in the primary index of this
+    /// table, including both SST files and memtables
+    pub fn tombstone_stats(&self) -> TombstoneStats {
+        let inner = self.db.inner();
+        let db = &inner.db;
+        let cf = match inner
+            .indices
+            .first()
+            .and_then(|index| db.cf_handle(&index.column_family))
+        {
+            Some(cf) => cf,
+            None => return TombstoneStats::default(),
+        };
+
+        let mut stats = db
+            .property_value_cf(cf, "rocksdb.aggregated-table-properties")
+            .ok()
+            .flatten()
+            .map(|props| TombstoneStats::from_table_properties(&props))
+            .unwrap_or_default();
+        let int_property = |name: &str| {
+            db.property_int_value_cf(cf, name)
+                .ok()
+                .flatten()
+                .unwrap_or_default()
+        };
+        stats.entries += int_property("rocksdb.num-entries-active-mem-table")
+            + int_property("rocksdb.num-entries-imm-mem-tables");
+        stats.deletions += int_property("rocksdb.num-deletes-active-mem-table")
+            + int_property("rocksdb.num-deletes-imm-mem-tables");
+        stats
+    }
+
+    /// Apply the given RocksDB tuning parameters to all the indices of this table. Parameters left
+    /// unset in `tuning` are left unchanged.
+    ///
+    /// A new block cache size only takes effect immediately if the table was created with a
+    /// dedicated block cache; otherwise, it will take effect the next time the table is opened.
+    pub fn set_tuning(&mut self, tuning: RocksDbTuning) -> Result<()> {
+        let mut options = vec![];
+        if let Some(compression) = tuning.compression {
+            self.default_options
+                .set_compression_type(rocksdb_compression_type(compression));
+            options.push((
+                "compression",
+                rocksdb_compression_option(compression).to_owned(),
+            ));
+        }
+        if let Some(write_buffer_bytes) = tuning.write_buffer_bytes {
+            self.default_options
+                .set_write_buffer_size(write_buffer_bytes);
+            options.push(("write_buffer_size", write_buffer_bytes.to_string()));
+        }
+
+        if let Some(block_cache_bytes) = tuning.block_cache_bytes {
+            match &mut self.block_cache {
+                Some(cache) => cache.set_capacity(block_cache_bytes),
+                None => warn!(
+                    table = %self.name,
+                    "Table has no dedicated block cache; new block cache size will take effect \
+                     the next time the table is opened"
+                ),
+            }
+        }
+
+        if options.is_empty() {
+            return Ok(());
+        }
+
+        let options = options
+            .iter()
+            .map(|(k, v)| (*k, v.as_str()))
+            .collect::<Vec<_>>();
+        let inner = self.db.inner();
+        for index in &inner.indices {
+            if let Some(cf) = inner.db.cf_handle(&index.column_family) {
+                inner.db.set_options_cf(cf, &options)?;
+            }
+        }
+        info!(table = %self.name, ?tuning, "Updated RocksDB tuning");
+
+        Ok(())
+    }
+
+    /// Create a consistent, point-in-time copy of this table's RocksDB database in a subdirectory
+    /// of `dir` with the same name as the database's own directory, using a [RocksDB
+    /// checkpoint][checkpoint], and return the path to that subdirectory.
+    ///
+    /// Since checkpoints hard-link the database's immutable SST files where possible, this is cheap
+    /// if `dir` is on the same filesystem as the database. The resulting directory can be restored
+    /// by copying it into the [`db_dir`](PersistenceParameters::db_dir) of a new deployment.
+    ///
+    /// [checkpoint]: https://github.com/facebook/rocksdb/wiki/Checkpoints
+    pub fn checkpoint(&self, dir: &Path) -> Result<PathBuf> {
+        let path = self.db.checkpoint(dir)?;
+        info!(table = %self.name, path = %path.display(), "Created checkpoint of base table");
+        Ok(path)
+    }
+
     fn enable_snapshot_mode(&mut self) {
         self.db.replication_offset = None; // Remove any replication offset first (although it should be None already)
         let meta = self.meta();
@@ -1757,9 +2062,12 @@ impl PersistentState {
     }
 
     fn disable_snapshot_mode(&mut self) {
-        for index in self.db.inner().indices.iter().cloned() {
-            // Perform a manual compaction for each column family
+        self.spawn_compaction_threads();
+    }
 
+    /// Spawn a background thread performing a manual compaction for each column family
+    fn spawn_compaction_threads(&mut self) {
+        for index in self.db.inner().indices.iter().cloned() {
             let mut opts = CompactOptions::default();
             opts.set_exclusive_manual_compaction(false);
             opts.create_cancel_flag();
@@ -2544,6 +2852,179 @@ mod tests {
         }
     }
 
+    #[test]
+    fn persistence_parameters_table_modes() {
+        let mut params = PersistenceParameters {
+            mode: DurabilityMode::Permanent,
+            ..Default::default()
+        };
+        params.set_table_mode("scratch".into(), DurabilityMode::MemoryOnly);
+        params.set_table_mode(
+            Relation {
+                schema: Some("s".into()),
+                name: "t".into(),
+            },
+            DurabilityMode::DeleteOnExit,
+        );
+
+        assert_eq!(
+            params.mode_for_table(&"scratch".into()),
+            &DurabilityMode::MemoryOnly
+        );
+        assert_eq!(
+            params.mode_for_table(&Relation {
+                schema: Some("public".into()),
+                name: "scratch".into(),
+            }),
+            &DurabilityMode::MemoryOnly
+        );
+        assert_eq!(
+            params.mode_for_table(&Relation {
+                schema: Some("s".into()),
+                name: "t".into(),
+            }),
+            &DurabilityMode::DeleteOnExit
+        );
+        assert_eq!(
+            params.mode_for_table(&"t".into()),
+            &DurabilityMode::Permanent
+        );
+        assert_eq!(
+            params.mode_for_table(&"other".into()),
+            &DurabilityMode::Permanent
+        );
+    }
+
+    #[test]
+    fn persistence_parameters_table_tuning() {
+        let mut params = PersistenceParameters {
+            tuning: RocksDbTuning {
+                compression: Some(BaseTableCompression::Lz4),
+                write_buffer_bytes: Some(64 << 20),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        params.set_table_tuning(
+            "t".into(),
+            RocksDbTuning {
+                block_cache_bytes: Some(1 << 20),
+                compression: Some(BaseTableCompression::None),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            params.tuning_for_table(&Relation {
+                schema: Some("s".into()),
+                name: "t".into(),
+            }),
+            RocksDbTuning {
+                block_cache_bytes: Some(1 << 20),
+                compression: Some(BaseTableCompression::None),
+                write_buffer_bytes: Some(64 << 20),
+            }
+        );
+        assert_eq!(params.tuning_for_table(&"other".into()), params.tuning);
+    }
+
+    #[test]
+    fn set_tuning() {
+        let params = PersistenceParameters {
+            tuning: RocksDbTuning {
+                block_cache_bytes: Some(8 << 20),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut state =
+            PersistentState::new(String::from("set_tuning"), None::<&[usize]>, &params).unwrap();
+        state.add_key(Index::new(IndexType::BTreeMap, vec![0]), None);
+        state
+            .set_tuning(RocksDbTuning {
+                compression: Some(BaseTableCompression::None),
+                write_buffer_bytes: Some(1 << 20),
+                block_cache_bytes: Some(1 << 20),
+            })
+            .unwrap();
+        insert(&mut state, vec![1.into(), 2.into()]);
+        assert_eq!(
+            state
+                .lookup(&[0], &PointKey::Single(1.into()))
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn checkpoint() {
+        let (_dir, name) = get_tmp_path();
+        let backup_dir = tempdir().unwrap();
+        let params = PersistenceParameters {
+            mode: DurabilityMode::Permanent,
+            ..Default::default()
+        };
+        let row: Vec<DfValue> = vec![10.into(), "Cat".into()];
+        let checkpoint = {
+            let mut state =
+                PersistentState::new(name, Vec::<Box<[usize]>>::new(), &params).unwrap();
+            state.add_key(Index::new(IndexType::HashMap, vec![0]), None);
+            insert(&mut state, row.clone());
+            state.checkpoint(backup_dir.path()).unwrap()
+        };
+
+        let state = PersistentState::new(
+            checkpoint.to_string_lossy().into(),
+            Vec::<Box<[usize]>>::new(),
+            &params,
+        )
+        .unwrap();
+        match state.lookup(&[0], &PointKey::Single(10.into())) {
+            LookupResult::Some(RecordResult::Owned(rows)) => assert_eq!(rows, vec![row]),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn restore_from_remote_storage() {
+        let db_dir = tempdir().unwrap();
+        let remote_dir = tempdir().unwrap();
+        let params = PersistenceParameters {
+            mode: DurabilityMode::Permanent,
+            db_dir: Some(db_dir.path().to_owned()),
+            remote_storage: Some(RemoteStorage {
+                url: format!("file://{}", remote_dir.path().display()),
+                upload_interval: Duration::from_millis(10),
+            }),
+            ..Default::default()
+        };
+        let row: Vec<DfValue> = vec![10.into(), "Cat".into()];
+        {
+            let mut state =
+                PersistentState::new("t".into(), Vec::<Box<[usize]>>::new(), &params).unwrap();
+            state.add_key(Index::new(IndexType::HashMap, vec![0]), None);
+            insert(&mut state, row.clone());
+
+            let snapshot = remote_dir
+                .path()
+                .join("t.db")
+                .join(remote_storage::SNAPSHOT_FILE);
+            let start = Instant::now();
+            while !snapshot.exists() {
+                assert!(start.elapsed() < Duration::from_secs(10));
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+
+        fs::remove_dir_all(db_dir.path().join("t.db")).unwrap();
+        let state = PersistentState::new("t".into(), Vec::<Box<[usize]>>::new(), &params).unwrap();
+        match state.lookup(&[0], &PointKey::Single(10.into())) {
+            LookupResult::Some(RecordResult::Owned(rows)) => assert_eq!(rows, vec![row]),
+            _ => unreachable!(),
+        }
+    }
+
     #[test]
     fn persistent_state_recover() {
         let (_dir, name) = get_tmp_path();
@@ -2617,6 +3098,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn tombstone_stats_from_table_properties() {
+        let stats = TombstoneStats::from_table_properties(
+            "# data blocks=2; # entries=10; # deletions=4; # merge operands=0; raw key size=120",
+        );
+        assert_eq!(
+            stats,
+            TombstoneStats {
+                entries: 10,
+                deletions: 4
+            }
+        );
+        assert_eq!(stats.ratio(), 0.4);
+        assert_eq!(TombstoneStats::default().ratio(), 0.0);
+    }
+
+    #[test]
+    fn persistent_state_tombstone_stats() {
+        let mut state = setup_single_key("persistent_state_tombstone_stats");
+        let rows: Vec<Vec<DfValue>> = (0..10).map(|i| vec![i.into(), "Cat".into()]).collect();
+        state
+            .process_records(&mut rows.clone().into(), None, None)
+            .unwrap();
+        assert_eq!(state.tombstone_stats().deletions, 0);
+
+        state
+            .process_records(
+                &mut rows
+                    .into_iter()
+                    .take(5)
+                    .map(|r| (r, false))
+                    .collect::<Vec<_>>()
+                    .into(),
+                None,
+                None,
+            )
+            .unwrap();
+        let stats = state.tombstone_stats();
+        assert_eq!(stats.deletions, 5);
+        assert!(stats.ratio() > 0.0);
+    }
+
     #[test]
     fn persistent_state_remove() {
         let mut state = setup_persistent("persistent_state_remove", None);
@@ -0,0 +1,398 @@
+//! Durable storage of the persisted state of base tables in a remote object store.
+//!
+//! By default the persisted state of a base table only lives on the local disk of the worker
+//! running the table's domain, so if that disk is lost (eg because workers run on ephemeral
+//! storage) the table has to be re-snapshotted from the upstream database. If
+//! [`PersistenceParameters::remote_storage`](crate::PersistenceParameters::remote_storage) is set,
+//! every [`PersistentState`] in [`DurabilityMode::Permanent`] periodically creates a RocksDB
+//! checkpoint of its database and uploads it to an object store - S3, GCS, or a local directory
+//! (useful for testing).
+//!
+//! The objects for each table are stored under a prefix named after the table's database
+//! directory, laid out as:
+//!
+//! - `sst/<file>`: the table's SST files. Since SST files are immutable, each is only uploaded
+//!   once, and shared between all checkpoints which include it.
+//! - `<generation>/<file>`: every other file in the checkpoint (the manifest, options, and any
+//!   write-ahead log segments), which can change between checkpoints.
+//! - [`SNAPSHOT_FILE`]: a list of the files in the most recent complete checkpoint. This is only
+//!   written once all of a checkpoint's files have been uploaded, so that a checkpoint which was
+//!   only partially uploaded is never restored. Once it's written, the files of the previous
+//!   checkpoint which aren't part of the new checkpoint are deleted.
+//!
+//! When a [`PersistentState`] is opened and its database doesn't exist on the local disk, the most
+//! recent checkpoint is downloaded from the object store, if there is one.
+//!
+//! [`PersistentState`]: crate::PersistentState
+//! [`DurabilityMode::Permanent`]: crate::DurabilityMode::Permanent
+
+use std::collections::HashSet;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use object_store::aws::AmazonS3Builder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::local::LocalFileSystem;
+use object_store::path::Path as ObjectPath;
+use object_store::ObjectStore;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+use url::Url;
+
+use crate::persistent_state::{Error, PersistentStateHandle, Result};
+
+/// The name of the object listing the files in the most recent complete checkpoint of a table
+pub const SNAPSHOT_FILE: &str = "snapshot.json";
+
+/// Configuration for storing the persisted state of base tables in a remote object store.
+///
+/// See [the module documentation](self) for more information.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RemoteStorage {
+    /// The URL of the object store to upload to, eg `s3://bucket/prefix`, `gs://bucket/prefix`,
+    /// or `file:///path/to/dir`. Credentials for S3 and GCS are read from the standard
+    /// environment variables for each (`AWS_ACCESS_KEY_ID`, `GOOGLE_SERVICE_ACCOUNT`, etc).
+    pub url: String,
+    /// How often to upload a new checkpoint of each base table
+    pub upload_interval: Duration,
+}
+
+/// The contents of [`SNAPSHOT_FILE`]
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Snapshot {
+    /// The generation of the checkpoint, used to namespace its non-SST files
+    generation: u64,
+    /// The SST files in the checkpoint, stored under `sst/`
+    sst_files: Vec<String>,
+    /// All other files in the checkpoint, stored under `<generation>/`
+    files: Vec<String>,
+}
+
+fn remote_err<E: std::fmt::Display>(error: E) -> Error {
+    Error::RemoteStorage(error.to_string())
+}
+
+/// A connection to the objects for a single table in an object store
+struct TableStore {
+    store: Arc<dyn ObjectStore>,
+    prefix: ObjectPath,
+}
+
+impl TableStore {
+    /// Connect to the object store at the given URL, for the table whose database is stored in
+    /// the directory with the given name
+    fn connect(config: &RemoteStorage, db_name: &str) -> Result<Self> {
+        let url = Url::parse(&config.url).map_err(remote_err)?;
+        let (store, prefix): (Arc<dyn ObjectStore>, _) = match url.scheme() {
+            "s3" | "s3a" => (
+                Arc::new(
+                    AmazonS3Builder::from_env()
+                        .with_url(url.as_str())
+                        .build()
+                        .map_err(remote_err)?,
+                ),
+                ObjectPath::from(url.path()),
+            ),
+            "gs" => (
+                Arc::new(
+                    GoogleCloudStorageBuilder::from_env()
+                        .with_url(url.as_str())
+                        .build()
+                        .map_err(remote_err)?,
+                ),
+                ObjectPath::from(url.path()),
+            ),
+            "file" => {
+                fs::create_dir_all(url.path())?;
+                (
+                    Arc::new(LocalFileSystem::new_with_prefix(url.path()).map_err(remote_err)?),
+                    ObjectPath::default(),
+                )
+            }
+            scheme => {
+                return Err(Error::RemoteStorage(format!(
+                    "Unsupported remote storage URL scheme: {scheme}"
+                )))
+            }
+        };
+
+        Ok(Self {
+            store,
+            prefix: prefix.child(db_name),
+        })
+    }
+
+    fn sst_path(&self, file: &str) -> ObjectPath {
+        self.prefix.child("sst").child(file)
+    }
+
+    fn file_path(&self, generation: u64, file: &str) -> ObjectPath {
+        self.prefix.child(generation.to_string()).child(file)
+    }
+
+    /// Read the [`SNAPSHOT_FILE`] for the table, or return `None` if there isn't one
+    async fn snapshot(&self) -> Result<Option<Snapshot>> {
+        match self.store.get(&self.prefix.child(SNAPSHOT_FILE)).await {
+            Ok(res) => {
+                let bytes = res.bytes().await.map_err(remote_err)?;
+                Ok(Some(serde_json::from_slice(&bytes).map_err(remote_err)?))
+            }
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(error) => Err(remote_err(error)),
+        }
+    }
+
+    async fn download(&self, from: &ObjectPath, to: &Path) -> Result<()> {
+        let res = self.store.get(from).await.map_err(remote_err)?;
+        fs::write(to, res.bytes().await.map_err(remote_err)?)?;
+        Ok(())
+    }
+
+    async fn upload(&self, from: &Path, to: &ObjectPath) -> Result<()> {
+        let data = fs::read(from)?;
+        self.store.put(to, data.into()).await.map_err(remote_err)
+    }
+}
+
+/// Run the given future to completion on a new single-threaded runtime, in a new thread (so that
+/// this can be called from within the runtime running the domain)
+fn block_on<F>(fut: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    std::thread::scope(|s| {
+        s.spawn(|| {
+            #[allow(clippy::expect_used)] // only fails if we can't create threads or an epoll fd
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to build runtime for remote storage")
+                .block_on(fut)
+        })
+        .join()
+        .unwrap_or_else(|e| std::panic::resume_unwind(e))
+    })
+}
+
+/// If the most recent checkpoint of the table whose database should be at `path` exists in remote
+/// storage, download it to `path`. Returns `true` if a checkpoint was restored.
+///
+/// `path` must not already exist.
+pub(crate) fn restore(config: &RemoteStorage, path: &Path) -> Result<bool> {
+    let db_name = path
+        .file_name()
+        .ok_or(Error::BadDbFormat)?
+        .to_string_lossy()
+        .into_owned();
+    let store = TableStore::connect(config, &db_name)?;
+
+    block_on(async {
+        let snapshot = match store.snapshot().await? {
+            Some(snapshot) => snapshot,
+            None => {
+                debug!(%db_name, "No checkpoint of table in remote storage");
+                return Ok(false);
+            }
+        };
+
+        info!(
+            %db_name,
+            generation = snapshot.generation,
+            "Restoring table from remote storage"
+        );
+
+        // Download to a temporary directory first, so that an interrupted download doesn't leave a
+        // partial database behind
+        let parent = path.parent().unwrap_or_else(|| Path::new("."));
+        fs::create_dir_all(parent)?;
+        let tmp = tempfile::tempdir_in(parent)?;
+        for file in &snapshot.sst_files {
+            store
+                .download(&store.sst_path(file), &tmp.path().join(file))
+                .await?;
+        }
+        for file in &snapshot.files {
+            store
+                .download(
+                    &store.file_path(snapshot.generation, file),
+                    &tmp.path().join(file),
+                )
+                .await?;
+        }
+        fs::rename(tmp.into_path(), path)?;
+
+        Ok(true)
+    })
+}
+
+/// Periodically uploads checkpoints of a table to remote storage.
+///
+/// See [the module documentation](self) for more information.
+struct Uploader {
+    store: TableStore,
+    handle: PersistentStateHandle,
+    /// The directory to create checkpoints in before uploading them
+    checkpoint_dir: PathBuf,
+    /// The most recent checkpoint that was uploaded, or `None` if we haven't read the snapshot
+    /// from remote storage yet
+    last_snapshot: Option<Snapshot>,
+}
+
+impl Uploader {
+    async fn upload_checkpoint(&mut self) -> Result<()> {
+        let last = match self.last_snapshot.take() {
+            Some(snapshot) => snapshot,
+            None => self.store.snapshot().await?.unwrap_or_default(),
+        };
+
+        let checkpoint = tempfile::tempdir_in(&self.checkpoint_dir)?;
+        let path = self.handle.checkpoint(checkpoint.path())?;
+
+        let generation = last.generation + 1;
+        let uploaded_sst_files = last.sst_files.iter().collect::<HashSet<_>>();
+        let mut snapshot = Snapshot {
+            generation,
+            ..Default::default()
+        };
+        for entry in fs::read_dir(&path)? {
+            let entry = entry?;
+            let file = entry.file_name().to_string_lossy().into_owned();
+            if file.ends_with(".sst") {
+                if !uploaded_sst_files.contains(&file) {
+                    self.store
+                        .upload(&entry.path(), &self.store.sst_path(&file))
+                        .await?;
+                }
+                snapshot.sst_files.push(file);
+            } else {
+                self.store
+                    .upload(&entry.path(), &self.store.file_path(generation, &file))
+                    .await?;
+                snapshot.files.push(file);
+            }
+        }
+
+        self.store
+            .store
+            .put(
+                &self.store.prefix.child(SNAPSHOT_FILE),
+                serde_json::to_vec(&snapshot).map_err(remote_err)?.into(),
+            )
+            .await
+            .map_err(remote_err)?;
+        debug!(generation, "Uploaded checkpoint to remote storage");
+
+        // Now that the new checkpoint is complete, clean up the files from the old one
+        let sst_files = snapshot.sst_files.iter().collect::<HashSet<_>>();
+        for file in uploaded_sst_files.difference(&sst_files) {
+            self.store
+                .store
+                .delete(&self.store.sst_path(file))
+                .await
+                .map_err(remote_err)?;
+        }
+        for file in &last.files {
+            self.store
+                .store
+                .delete(&self.store.file_path(last.generation, file))
+                .await
+                .map_err(remote_err)?;
+        }
+
+        self.last_snapshot = Some(snapshot);
+        Ok(())
+    }
+}
+
+/// A handle to a background thread periodically uploading checkpoints of a table to remote
+/// storage. Dropping the handle stops the thread.
+pub(crate) struct UploaderHandle {
+    stop_tx: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for UploaderHandle {
+    fn drop(&mut self) {
+        // Dropping the sender wakes up the thread
+        self.stop_tx.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Spawn a background thread which uploads a checkpoint of the table whose database is at
+/// `db_path` every [`upload_interval`](RemoteStorage::upload_interval)
+pub(crate) fn spawn_uploader(
+    config: &RemoteStorage,
+    db_path: &Path,
+    handle: PersistentStateHandle,
+) -> Result<UploaderHandle> {
+    let db_name = db_path
+        .file_name()
+        .ok_or(Error::BadDbFormat)?
+        .to_string_lossy()
+        .into_owned();
+    let mut uploader = Uploader {
+        store: TableStore::connect(config, &db_name)?,
+        handle,
+        checkpoint_dir: db_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .to_owned(),
+        last_snapshot: None,
+    };
+    let interval = config.upload_interval;
+
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+    let thread = std::thread::Builder::new()
+        .name(format!("Upload {db_name}"))
+        .spawn(move || {
+            #[allow(clippy::expect_used)] // only fails if we can't create an epoll fd
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to build runtime for remote storage");
+            while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+                if let Err(error) = rt.block_on(uploader.upload_checkpoint()) {
+                    warn!(%error, %db_name, "Failed to upload checkpoint to remote storage");
+                }
+            }
+        })?;
+
+    Ok(UploaderHandle {
+        stop_tx: Some(stop_tx),
+        thread: Some(thread),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_missing_snapshot() {
+        let remote = tempfile::tempdir().unwrap();
+        let local = tempfile::tempdir().unwrap();
+        let config = RemoteStorage {
+            url: format!("file://{}", remote.path().display()),
+            upload_interval: Duration::from_secs(1),
+        };
+        assert!(!restore(&config, &local.path().join("t.db")).unwrap());
+    }
+
+    #[test]
+    fn unsupported_scheme() {
+        let config = RemoteStorage {
+            url: "ftp://example.com/backups".into(),
+            upload_interval: Duration::from_secs(1),
+        };
+        assert!(TableStore::connect(&config, "t.db").is_err());
+    }
+}
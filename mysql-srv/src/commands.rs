@@ -2,10 +2,12 @@ use nom::branch::alt;
 use nom::bytes::complete::{tag, take, take_until};
 use nom::combinator::{map, map_res, opt, rest};
 use nom::error::FromExternalError;
+use nom::multi::many0;
 use nom::number::complete::{le_i16, le_i24, le_i64, le_u16, le_u32, le_u8};
-use nom::sequence::preceded;
+use nom::sequence::{pair, preceded};
 use nom::IResult;
 
+use crate::constants::ZSTD_COMPRESSION_ALGORITHM;
 use crate::myc::constants::{CapabilityFlags, Command as CommandByte};
 
 #[derive(Debug)]
@@ -17,6 +19,12 @@ pub struct ClientHandshake<'a> {
     pub password: &'a [u8],
     pub database: Option<&'a str>,
     pub auth_plugin_name: Option<&'a str>,
+    /// The connection attributes sent by the client (eg `program_name`), if the client supports
+    /// `CLIENT_CONNECT_ATTRS`
+    pub connect_attrs: Vec<(&'a str, &'a str)>,
+    /// The zstd compression level requested by the client, if the client supports
+    /// `CLIENT_ZSTD_COMPRESSION_ALGORITHM`
+    pub zstd_compression_level: Option<u8>,
 }
 
 /// Parse a "length-encoded integer" as specified by the [mysql binary protocol documentation][docs]
@@ -46,6 +54,14 @@ fn parse_bytes_to_string(i: &[u8]) -> Result<&str, nom::Err<nom::error::Error<&[
     })
 }
 
+/// Parse a "length-encoded string" as specified by the [mysql binary protocol documentation][docs]
+///
+/// [docs]: https://dev.mysql.com/doc/internals/en/string.html#packet-Protocol::LengthEncodedString
+fn lenenc_str(i: &[u8]) -> IResult<&[u8], &str> {
+    let (i, len) = lenenc_int(i)?;
+    map_res(take(len as usize), parse_bytes_to_string)(i)
+}
+
 fn null_terminated_string(i: &[u8]) -> IResult<&[u8], &str> {
     let (i, res) = map_res(take_until(&b"\0"[..]), parse_bytes_to_string)(i)?;
     let (i, _) = take(1u8)(i)?;
@@ -54,7 +70,8 @@ fn null_terminated_string(i: &[u8]) -> IResult<&[u8], &str> {
 
 /// <https://dev.mysql.com/doc/internals/en/connection-phase-packets.html#packet-Protocol::HandshakeResponse41>
 pub fn client_handshake(i: &[u8]) -> IResult<&[u8], ClientHandshake<'_>> {
-    let (i, capabilities) = map(le_u32, CapabilityFlags::from_bits_truncate)(i)?;
+    let (i, raw_capabilities) = le_u32(i)?;
+    let capabilities = CapabilityFlags::from_bits_truncate(raw_capabilities);
     let (i, maxps) = le_u32(i)?;
     let (i, charset) = le_u8(i)?;
     let (i, _) = take(23u8)(i)?;
@@ -82,6 +99,22 @@ pub fn client_handshake(i: &[u8]) -> IResult<&[u8], ClientHandshake<'_>> {
         (i, None)
     };
 
+    let (i, connect_attrs) =
+        if capabilities.contains(CapabilityFlags::CLIENT_CONNECT_ATTRS) && !i.is_empty() {
+            let (i, attrs_length) = lenenc_int(i)?;
+            let (i, attrs) = take(attrs_length as usize)(i)?;
+            let (_, attrs) = many0(pair(lenenc_str, lenenc_str))(attrs)?;
+            (i, attrs)
+        } else {
+            (i, vec![])
+        };
+
+    let (i, zstd_compression_level) = if raw_capabilities & ZSTD_COMPRESSION_ALGORITHM != 0 {
+        opt(le_u8)(i)?
+    } else {
+        (i, None)
+    };
+
     Ok((
         i,
         ClientHandshake {
@@ -92,6 +125,8 @@ pub fn client_handshake(i: &[u8]) -> IResult<&[u8], ClientHandshake<'_>> {
             password,
             database,
             auth_plugin_name,
+            connect_attrs,
+            zstd_compression_level,
         },
     ))
 }
@@ -213,6 +248,58 @@ mod tests {
         assert_eq!(handshake.charset, UTF8_GENERAL_CI);
         assert_eq!(handshake.username, "jon");
         assert_eq!(handshake.maxps, 16777216);
+        assert!(handshake.connect_attrs.is_empty());
+    }
+
+    #[test]
+    fn it_parses_handshake_with_connect_attrs() {
+        let capabilities = CapabilityFlags::CLIENT_PROTOCOL_41
+            | CapabilityFlags::CLIENT_SECURE_CONNECTION
+            | CapabilityFlags::CLIENT_PLUGIN_AUTH
+            | CapabilityFlags::CLIENT_CONNECT_ATTRS;
+        let mut data = capabilities.bits().to_le_bytes().to_vec();
+        data.extend_from_slice(&16777216u32.to_le_bytes());
+        data.push(0x21);
+        data.extend_from_slice(&[0; 23]);
+        data.extend_from_slice(b"jon\0");
+        data.push(0); // empty password
+        data.extend_from_slice(b"mysql_native_password\0");
+        let attrs = b"\x0cprogram_name\x05mysql\x04_pid\x0212";
+        data.push(attrs.len() as u8);
+        data.extend_from_slice(attrs);
+
+        let (rest, handshake) = client_handshake(&data).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(handshake.auth_plugin_name, Some("mysql_native_password"));
+        assert_eq!(
+            handshake.connect_attrs,
+            vec![("program_name", "mysql"), ("_pid", "12")]
+        );
+    }
+
+    #[test]
+    fn it_parses_handshake_with_zstd_compression_level() {
+        let capabilities = CapabilityFlags::CLIENT_PROTOCOL_41
+            | CapabilityFlags::CLIENT_SECURE_CONNECTION
+            | CapabilityFlags::CLIENT_PLUGIN_AUTH
+            | CapabilityFlags::CLIENT_COMPRESS;
+        let mut data = (capabilities.bits() | ZSTD_COMPRESSION_ALGORITHM)
+            .to_le_bytes()
+            .to_vec();
+        data.extend_from_slice(&16777216u32.to_le_bytes());
+        data.push(0x21);
+        data.extend_from_slice(&[0; 23]);
+        data.extend_from_slice(b"jon\0");
+        data.push(0); // empty password
+        data.extend_from_slice(b"mysql_native_password\0");
+        data.push(7);
+
+        let (rest, handshake) = client_handshake(&data).unwrap();
+        assert!(rest.is_empty());
+        assert!(handshake
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_COMPRESS));
+        assert_eq!(handshake.zstd_compression_level, Some(7));
     }
 
     #[tokio::test]
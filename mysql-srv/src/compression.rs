@@ -0,0 +1,217 @@
+//! Framing for the [compressed MySQL protocol][docs].
+//!
+//! Once a client negotiates `CLIENT_COMPRESS` (or `CLIENT_ZSTD_COMPRESSION_ALGORITHM`), every
+//! byte sent in either direction after the handshake is wrapped in compressed packets, each of
+//! which holds part of the stream of regular MySQL packets:
+//!
+//! | Bytes | Description |
+//! | ----- | ----------- |
+//! | 3     | length of the (possibly compressed) payload |
+//! | 1     | compressed sequence id |
+//! | 3     | length of the payload before compression, or 0 if it isn't compressed |
+//! | n     | payload |
+//!
+//! The compressed sequence id is independent of the sequence ids of the packets inside the
+//! payload, but is reset at the start of each command in the same way.
+//!
+//! [docs]: https://dev.mysql.com/doc/dev/mysql-server/latest/page_protocol_basic_compression.html
+
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+
+const U24_MAX: usize = 16_777_215;
+
+/// The length of the header of each compressed packet
+const HEADER_LEN: usize = 7;
+
+/// Payloads shorter than this are sent uncompressed, since compressing them wouldn't save
+/// anything. This matches the threshold used by MySQL itself.
+const MIN_COMPRESS_LEN: usize = 50;
+
+/// The compression algorithm negotiated for a connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// zlib, negotiated with `CLIENT_COMPRESS`
+    Zlib,
+    /// zstd at the given level, negotiated with `CLIENT_ZSTD_COMPRESSION_ALGORITHM`
+    Zstd { level: i32 },
+}
+
+impl Compression {
+    fn compress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Compression::Zlib => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            Compression::Zstd { level } => zstd::bulk::compress(data, level),
+        }
+    }
+
+    fn decompress(self, data: &[u8], len: usize) -> io::Result<Vec<u8>> {
+        let decompressed = match self {
+            Compression::Zlib => {
+                let mut decompressed = Vec::with_capacity(len);
+                ZlibDecoder::new(data).read_to_end(&mut decompressed)?;
+                decompressed
+            }
+            Compression::Zstd { .. } => zstd::bulk::decompress(data, len)?,
+        };
+        if decompressed.len() != len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "compressed packet decompressed to {} bytes, expected {}",
+                    decompressed.len(),
+                    len
+                ),
+            ));
+        }
+        Ok(decompressed)
+    }
+}
+
+/// The state of the compressed protocol for a connection.
+///
+/// Clones share the same compressed sequence id, so that the packet writer continues the sequence
+/// from the last compressed packet read by the packet reader.
+#[derive(Debug, Clone)]
+pub(crate) struct CompressionState {
+    compression: Compression,
+    /// The compressed sequence id of the next compressed packet
+    seq: Arc<AtomicU8>,
+}
+
+impl CompressionState {
+    pub(crate) fn new(compression: Compression) -> Self {
+        Self {
+            compression,
+            seq: Default::default(),
+        }
+    }
+
+    #[cfg(test)]
+    fn seq(&self) -> u8 {
+        self.seq.load(Ordering::Relaxed)
+    }
+
+    /// Wrap `data` in as many compressed packets as needed to hold it, returning the bytes to
+    /// write to the wire
+    pub(crate) fn encode(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(data.len() + HEADER_LEN);
+        for chunk in data.chunks(U24_MAX) {
+            let (payload, uncompressed_len) = if chunk.len() < MIN_COMPRESS_LEN {
+                (None, 0)
+            } else {
+                match self.compression.compress(chunk)? {
+                    compressed if compressed.len() < chunk.len() => (Some(compressed), chunk.len()),
+                    // Incompressible data is sent as-is
+                    _ => (None, 0),
+                }
+            };
+            let payload = payload.as_deref().unwrap_or(chunk);
+            out.extend_from_slice(&(payload.len() as u32).to_le_bytes()[..3]);
+            out.push(self.seq.fetch_add(1, Ordering::Relaxed));
+            out.extend_from_slice(&(uncompressed_len as u32).to_le_bytes()[..3]);
+            out.extend_from_slice(payload);
+        }
+        Ok(out)
+    }
+
+    /// If `buf` starts with a complete compressed packet, returns its decompressed payload and
+    /// the number of bytes of `buf` it took up
+    pub(crate) fn decode(&self, buf: &[u8]) -> io::Result<Option<(Vec<u8>, usize)>> {
+        let Some(header) = buf.get(..HEADER_LEN) else {
+            return Ok(None);
+        };
+        let le_u24 = |b: &[u8]| u32::from_le_bytes([b[0], b[1], b[2], 0]) as usize;
+        let len = le_u24(&header[..3]);
+        let seq = header[3];
+        let uncompressed_len = le_u24(&header[4..]);
+        let Some(payload) = buf.get(HEADER_LEN..HEADER_LEN + len) else {
+            return Ok(None);
+        };
+
+        self.seq.store(seq.wrapping_add(1), Ordering::Relaxed);
+        let payload = if uncompressed_len == 0 {
+            payload.to_vec()
+        } else {
+            self.compression.decompress(payload, uncompressed_len)?
+        };
+        Ok(Some((payload, HEADER_LEN + len)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_utils::slow;
+
+    use super::*;
+
+    fn round_trip(compression: Compression, data: &[u8]) {
+        let writer = CompressionState::new(compression);
+        let encoded = writer.encode(data).unwrap();
+
+        let reader = CompressionState::new(compression);
+        let mut decoded = vec![];
+        let mut rest = &encoded[..];
+        while let Some((payload, len)) = reader.decode(rest).unwrap() {
+            decoded.extend(payload);
+            rest = &rest[len..];
+        }
+        assert!(rest.is_empty());
+        assert_eq!(decoded, data);
+        assert_eq!(reader.seq(), writer.seq());
+    }
+
+    #[test]
+    fn small_payloads_are_not_compressed() {
+        let state = CompressionState::new(Compression::Zlib);
+        let encoded = state.encode(&[1, 0, 0, 0, 0x0e]).unwrap();
+        assert_eq!(encoded, [5, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0x0e]);
+        assert_eq!(state.seq(), 1);
+    }
+
+    #[test]
+    fn zlib_round_trip() {
+        round_trip(
+            Compression::Zlib,
+            &b"SELECT * FROM t WHERE x = 1;".repeat(100),
+        );
+    }
+
+    #[test]
+    fn zstd_round_trip() {
+        round_trip(
+            Compression::Zstd { level: 3 },
+            &b"SELECT * FROM t WHERE x = 1;".repeat(100),
+        );
+    }
+
+    #[test]
+    fn incomplete_packets() {
+        let writer = CompressionState::new(Compression::Zlib);
+        let encoded = writer.encode(&[7; 1000]).unwrap();
+        let reader = CompressionState::new(Compression::Zlib);
+        assert!(reader.decode(&encoded[..3]).unwrap().is_none());
+        assert!(reader
+            .decode(&encoded[..encoded.len() - 1])
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            reader.decode(&encoded).unwrap(),
+            Some((vec![7; 1000], encoded.len()))
+        );
+    }
+
+    #[test]
+    #[slow]
+    fn large_payloads_are_split() {
+        round_trip(Compression::Zlib, &vec![1; U24_MAX + 100]);
+    }
+}
@@ -55,6 +55,8 @@ pub const SESSION_TRACK: u32 = 0x00800000;
 pub const DEPRECATE_EOF: u32 = 0x01000000;
 /// Client supports plugin authentication
 pub const CLIENT_PLUGIN_AUTH: u32 = 0x00080000;
+/// Can use the zstd compression algorithm for the compression protocol
+pub const ZSTD_COMPRESSION_ALGORITHM: u32 = 0x04000000;
 
 pub const SSL_VERIFY_SERVER_CERT: u32 = 0x40000000;
 pub const REMEMBER_OPTIONS: u32 = 0x80000000;
@@ -164,7 +164,11 @@ use std::io;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use constants::{CLIENT_PLUGIN_AUTH, PROTOCOL_41, RESERVED, SECURE_CONNECTION};
+use compression::{Compression, CompressionState};
+use constants::{
+    CLIENT_PLUGIN_AUTH, COMPRESS, CONNECT_ATTRS, PROTOCOL_41, RESERVED, SECURE_CONNECTION,
+    ZSTD_COMPRESSION_ALGORITHM,
+};
 use error::{other_error, OtherErrorKind};
 use mysql_common::constants::CapabilityFlags;
 use readyset_data::DfType;
@@ -179,6 +183,7 @@ pub use crate::writers::prepare_column_definitions;
 
 mod authentication;
 mod commands;
+mod compression;
 mod constants;
 pub mod error;
 mod errorcodes;
@@ -284,6 +289,23 @@ pub trait MySqlShim<W: AsyncWrite + Unpin + Send> {
     fn require_authentication(&self) -> bool {
         true
     }
+
+    /// Returns the id of this connection, which is sent to the client in the initial handshake and
+    /// identifies the connection in `KILL QUERY` statements
+    fn connection_id(&self) -> u32 {
+        8
+    }
+
+    /// Called with the connection attributes (eg `program_name`) sent by the client in its
+    /// handshake, before the client is authenticated
+    fn on_connection_attributes(&mut self, _attributes: &[(&str, &str)]) {}
+
+    /// Called once the client has successfully authenticated as the given user, before the client
+    /// is told that authentication succeeded. If this returns an error, the client is sent an
+    /// access denied error with the error's message, and the connection is closed.
+    async fn on_authenticated(&mut self, _username: &str) -> io::Result<()> {
+        Ok(())
+    }
 }
 
 /// Stores a preencoded result schema for a prepared MySQL statement
@@ -356,7 +378,13 @@ struct StatementData {
     params: u16,
 }
 
-const CAPABILITIES: u32 = PROTOCOL_41 | SECURE_CONNECTION | RESERVED | CLIENT_PLUGIN_AUTH;
+const CAPABILITIES: u32 = PROTOCOL_41
+    | SECURE_CONNECTION
+    | RESERVED
+    | CLIENT_PLUGIN_AUTH
+    | CONNECT_ATTRS
+    | COMPRESS
+    | ZSTD_COMPRESSION_ALGORITHM;
 
 impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
     MySqlIntermediary<B, R, W>
@@ -406,7 +434,7 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
         );
         init_packet.extend_from_slice(&[10]); // protocol 10
         init_packet.extend_from_slice(self.shim.version().as_bytes());
-        init_packet.extend_from_slice(&[0x08, 0x00, 0x00, 0x00]); // TODO: connection ID
+        init_packet.extend_from_slice(&self.shim.connection_id().to_le_bytes());
         init_packet.extend_from_slice(&auth_data[..8]);
         init_packet.push(0);
         init_packet.extend_from_slice(&CAPABILITIES.to_le_bytes()[..2]);
@@ -458,6 +486,19 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
         let password = handshake.password.to_vec();
         let database = handshake.database.map(String::from);
         let client_auth_plugin = handshake.auth_plugin_name.map(|s| s.to_owned());
+        let compression = match handshake.zstd_compression_level {
+            Some(level) => Some(Compression::Zstd {
+                level: level.into(),
+            }),
+            None if handshake
+                .capabilities
+                .contains(CapabilityFlags::CLIENT_COMPRESS) =>
+            {
+                Some(Compression::Zlib)
+            }
+            None => None,
+        };
+        self.shim.on_connection_attributes(&handshake.connect_attrs);
 
         let handshake_password = if client_auth_plugin.iter().all(|apn| apn != AUTH_PLUGIN_NAME)
             // Some clients (at the very least certain versions of PHP's MySQL PDO library) send an
@@ -512,7 +553,7 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
             password
         };
 
-        let auth_success = !self.shim.require_authentication()
+        let mut auth_success = !self.shim.require_authentication()
             || self
                 .shim
                 .password_for_username(&username)
@@ -523,7 +564,22 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
                     expected == actual
                 });
 
-        if auth_success {
+        let auth_error = if auth_success {
+            self.shim.on_authenticated(&username).await.err()
+        } else {
+            None
+        };
+
+        if let Some(error) = auth_error {
+            debug!(%username, %error, "Failed to set up connection for authenticated client");
+            auth_success = false;
+            writers::write_err(
+                ErrorKind::ER_ACCESS_DENIED_ERROR,
+                format!("Access denied for user {}: {}", username, error).as_bytes(),
+                &mut self.writer,
+            )
+            .await?;
+        } else if auth_success {
             debug!(%username, "Successfully authenticated client");
             writers::write_ok_packet(&mut self.writer, 0, 0, StatusFlags::empty()).await?;
         } else {
@@ -537,6 +593,14 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
         }
         self.writer.flush().await?;
 
+        // The compressed protocol starts with the first packet after the OK packet
+        if let Some(compression) = compression.filter(|_| auth_success) {
+            debug!(?compression, "Using compressed protocol");
+            let state = CompressionState::new(compression);
+            self.reader.enable_compression(state.clone());
+            self.writer.enable_compression(state);
+        }
+
         Ok((auth_success, database))
     }
 
@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
+use crate::compression::CompressionState;
 use crate::error::{other_error, OtherErrorKind};
 use crate::resultset::{MAX_POOL_ROWS, MAX_POOL_ROW_CAPACITY};
 
@@ -15,6 +16,9 @@ pub struct PacketWriter<W> {
 
     /// Reusable packets
     preallocated: Vec<QueuedPacket>,
+
+    /// Set once the client and server have agreed to use the compressed protocol
+    compression: Option<CompressionState>,
 }
 
 /// Type for packets being enqueued in the packet writer.
@@ -55,6 +59,25 @@ async fn write_all_vectored<'a, W: AsyncWrite + Unpin>(
     }
 }
 
+/// Write all of `slices`, wrapping them in compressed packets if the connection uses the
+/// compressed protocol
+async fn write_all<'a, W: AsyncWrite + Unpin>(
+    w: &'a mut W,
+    compression: Option<&CompressionState>,
+    slices: &'a mut [IoSlice<'a>],
+) -> io::Result<()> {
+    match compression {
+        Some(compression) => {
+            let mut data = Vec::with_capacity(slices.iter().map(|s| s.len()).sum());
+            for slice in slices.iter() {
+                data.extend_from_slice(slice);
+            }
+            w.write_all(&compression.encode(&data)?).await
+        }
+        None => write_all_vectored(w, slices).await,
+    }
+}
+
 // Gets an IoSlice to each of the packets currently enqueued in `queue`.
 fn queued_packet_slices(queue: &[QueuedPacket]) -> Vec<IoSlice<'_>> {
     if queue.is_empty() {
@@ -82,9 +105,15 @@ impl<W: AsyncWrite + Unpin> PacketWriter<W> {
             w,
             queue: Vec::new(),
             preallocated: Vec::new(),
+            compression: None,
         }
     }
 
+    /// Wrap all packets written from now on in compressed packets
+    pub(crate) fn enable_compression(&mut self, compression: CompressionState) {
+        self.compression = Some(compression);
+    }
+
     pub fn set_seq(&mut self, seq: u8) {
         self.seq = seq;
     }
@@ -131,7 +160,7 @@ impl<W: AsyncWrite + Unpin> PacketWriter<W> {
     pub async fn write_queued_packets(&mut self) -> Result<(), tokio::io::Error> {
         let mut slices = queued_packet_slices(&self.queue);
         if !slices.is_empty() {
-            write_all_vectored(&mut self.w, &mut slices).await?;
+            write_all(&mut self.w, self.compression.as_ref(), &mut slices).await?;
             self.return_queued_to_pool();
         }
 
@@ -172,7 +201,7 @@ impl<W: AsyncWrite + Unpin> PacketWriter<W> {
             }
         }
 
-        write_all_vectored(&mut self.w, &mut slices).await?;
+        write_all(&mut self.w, self.compression.as_ref(), &mut slices).await?;
         self.return_queued_to_pool();
 
         Ok(())
@@ -207,7 +236,7 @@ impl<W: AsyncWrite + Unpin> PacketWriter<W> {
             IoSlice::new(packet),
         ]);
 
-        write_all_vectored(&mut self.w, &mut slices).await?;
+        write_all(&mut self.w, self.compression.as_ref(), &mut slices).await?;
 
         self.seq = self.seq.wrapping_add(1);
         Ok(())
@@ -232,6 +261,44 @@ pub struct PacketReader<R> {
     start: usize,
     remaining: usize,
     r: R,
+    compressed: Option<CompressedReader>,
+}
+
+/// Buffers compressed packets read off the wire until they can be decompressed
+struct CompressedReader {
+    state: CompressionState,
+    bytes: Vec<u8>,
+}
+
+impl CompressedReader {
+    /// Read and decompress the next non-empty compressed packet, appending its payload to `out`
+    /// and returning its length, or 0 at EOF
+    async fn read<R: AsyncRead + Unpin>(
+        &mut self,
+        r: &mut R,
+        out: &mut Vec<u8>,
+    ) -> io::Result<usize> {
+        loop {
+            if let Some((payload, len)) = self.state.decode(&self.bytes)? {
+                self.bytes.drain(..len);
+                if payload.is_empty() {
+                    continue;
+                }
+                out.extend_from_slice(&payload);
+                return Ok(payload.len());
+            }
+
+            if r.read_buf(&mut self.bytes).await? == 0 {
+                if self.bytes.is_empty() {
+                    return Ok(0);
+                }
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("{} bytes of incomplete compressed packet", self.bytes.len()),
+                ));
+            }
+        }
+    }
 }
 
 impl<R> PacketReader<R> {
@@ -241,8 +308,20 @@ impl<R> PacketReader<R> {
             start: 0,
             remaining: 0,
             r,
+            compressed: None,
         }
     }
+
+    /// Read all packets from now on out of compressed packets
+    pub(crate) fn enable_compression(&mut self, compression: CompressionState) {
+        self.compressed = Some(CompressedReader {
+            state: compression,
+            // Anything left over in our buffer was read off the wire before compression was
+            // enabled, so must already be compressed
+            bytes: self.bytes.split_off(self.bytes.len() - self.remaining),
+        });
+        self.remaining = 0;
+    }
 }
 
 impl<R: AsyncRead + Unpin> PacketReader<R> {
@@ -283,20 +362,25 @@ impl<R: AsyncRead + Unpin> PacketReader<R> {
             // we need to read some more
             self.bytes.drain(0..self.start);
             self.start = 0;
-            let end = self.bytes.len();
-            let new_len = std::cmp::max(4096, end * 2);
-            self.bytes.resize(new_len, 0);
-            let read = {
-                let buf = self.bytes.get_mut(end..).ok_or_else(|| {
-                    other_error(OtherErrorKind::IndexErr {
-                        data: "self.bytes".to_string(),
-                        index: end,
-                        length: new_len,
-                    })
-                })?;
-                self.r.read(buf).await?
+            let read = if let Some(compressed) = &mut self.compressed {
+                compressed.read(&mut self.r, &mut self.bytes).await?
+            } else {
+                let end = self.bytes.len();
+                let new_len = std::cmp::max(4096, end * 2);
+                self.bytes.resize(new_len, 0);
+                let read = {
+                    let buf = self.bytes.get_mut(end..).ok_or_else(|| {
+                        other_error(OtherErrorKind::IndexErr {
+                            data: "self.bytes".to_string(),
+                            index: end,
+                            length: new_len,
+                        })
+                    })?;
+                    self.r.read(buf).await?
+                };
+                self.bytes.truncate(end + read);
+                read
             };
-            self.bytes.truncate(end + read);
             self.remaining = self.bytes.len();
 
             if read == 0 {
@@ -412,6 +496,7 @@ mod tests {
     use test_utils::slow;
 
     use super::*;
+    use crate::compression::Compression;
 
     #[test]
     fn test_one_ping() {
@@ -502,4 +587,40 @@ mod tests {
 
         assert!(reader.next().await.unwrap().is_none());
     }
+
+    #[tokio::test]
+    async fn test_compressed_packets() {
+        let (u_out, u_in) = tokio::net::UnixStream::pair().unwrap();
+
+        let packets = vec![vec![0u8; 10], vec![1u8; 10_000], b"SELECT 1".to_vec()];
+
+        let p = packets.clone();
+        tokio::spawn(async move {
+            let mut writer = PacketWriter::new(u_out);
+            writer.enable_compression(CompressionState::new(Compression::Zlib));
+
+            for packet in &p {
+                writer.enqueue_packet(packet.clone());
+            }
+            writer.write_queued_packets().await.unwrap();
+
+            for packet in &p {
+                writer.write_packet(&packet[..]).await.unwrap();
+            }
+            writer.flush().await.unwrap();
+        });
+
+        let mut reader = PacketReader::new(u_in);
+        reader.enable_compression(CompressionState::new(Compression::Zlib));
+
+        for _ in 0..2 {
+            for (seq, encoded) in packets.iter().enumerate() {
+                let decoded = reader.next().await.unwrap().unwrap();
+                assert_eq!(decoded.0 as usize % packets.len(), seq);
+                assert_eq!(&decoded.1[..], encoded);
+            }
+        }
+
+        assert!(reader.next().await.unwrap().is_none());
+    }
 }
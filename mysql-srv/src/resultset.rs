@@ -424,6 +424,17 @@ where
         Ok(())
     }
 
+    /// Returns the size, in bytes, of the column data written so far for the current row
+    pub fn row_len(&self) -> usize {
+        self.row_data.as_ref().map_or(0, |row_data| row_data.len())
+    }
+
+    /// Send all rows which have been ended so far to the client, rather than waiting for more rows
+    /// to be buffered.
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.result.writer.flush().await
+    }
+
     /// Write a single row as a part of this resultset.
     ///
     /// Note that the row *must* conform to the column specification provided to
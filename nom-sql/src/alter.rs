@@ -23,7 +23,7 @@ use crate::common::{
 use crate::create::key_specification;
 use crate::literal::literal;
 use crate::table::{relation, Relation};
-use crate::whitespace::whitespace1;
+use crate::whitespace::{whitespace0, whitespace1};
 use crate::{Dialect, Literal, NomSqlResult, SqlIdentifier};
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
@@ -77,9 +77,31 @@ impl Display for ReplicaIdentity {
     }
 }
 
+/// The position of a column added or changed by `ALTER TABLE`, relative to the other columns in
+/// the table. If not specified, added columns go at the end of the table.
+///
+/// See <https://dev.mysql.com/doc/refman/8.0/en/alter-table.html#alter-table-redefine-column>
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum ColumnPosition {
+    First,
+    After(SqlIdentifier),
+}
+
+impl ColumnPosition {
+    pub fn display(&self, dialect: Dialect) -> impl fmt::Display + Copy + '_ {
+        fmt_with(move |f| match self {
+            Self::First => write!(f, "FIRST"),
+            Self::After(column) => write!(f, "AFTER {}", dialect.quote_identifier(column)),
+        })
+    }
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub enum AlterTableDefinition {
-    AddColumn(ColumnSpecification),
+    AddColumn {
+        spec: ColumnSpecification,
+        position: Option<ColumnPosition>,
+    },
     AddKey(TableKey),
     AlterColumn {
         name: SqlIdentifier,
@@ -92,6 +114,7 @@ pub enum AlterTableDefinition {
     ChangeColumn {
         name: SqlIdentifier,
         spec: ColumnSpecification,
+        position: Option<ColumnPosition>,
     },
     RenameColumn {
         name: SqlIdentifier,
@@ -111,8 +134,12 @@ pub enum AlterTableDefinition {
 impl AlterTableDefinition {
     pub fn display(&self, dialect: Dialect) -> impl fmt::Display + Copy + '_ {
         fmt_with(move |f| match self {
-            Self::AddColumn(col) => {
-                write!(f, "ADD COLUMN {}", col.display(dialect))
+            Self::AddColumn { spec, position } => {
+                write!(f, "ADD COLUMN {}", spec.display(dialect))?;
+                if let Some(position) = position {
+                    write!(f, " {}", position.display(dialect))?;
+                }
+                Ok(())
             }
             Self::AddKey(index) => {
                 write!(f, "ADD {}", index.display(dialect))
@@ -132,18 +159,26 @@ impl AlterTableDefinition {
                 }
                 Ok(())
             }
-            Self::ChangeColumn { name, spec } => {
+            Self::ChangeColumn {
+                name,
+                spec,
+                position,
+            } => {
                 write!(
                     f,
                     "CHANGE COLUMN {} {}",
                     dialect.quote_identifier(name),
                     spec.display(dialect)
-                )
+                )?;
+                if let Some(position) = position {
+                    write!(f, " {}", position.display(dialect))?;
+                }
+                Ok(())
             }
             Self::RenameColumn { name, new_name } => {
                 write!(
                     f,
-                    "RENAME COLUMN {} {}",
+                    "RENAME COLUMN {} TO {}",
                     dialect.quote_identifier(name),
                     dialect.quote_identifier(new_name)
                 )
@@ -199,6 +234,23 @@ impl AlterTableStatement {
     }
 }
 
+fn column_position(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], ColumnPosition> {
+    move |i| {
+        alt((
+            value(ColumnPosition::First, tag_no_case("first")),
+            map(
+                preceded(
+                    terminated(tag_no_case("after"), whitespace1),
+                    dialect.identifier(),
+                ),
+                ColumnPosition::After,
+            ),
+        ))(i)
+    }
+}
+
 fn add_column(
     dialect: Dialect,
 ) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], AlterTableDefinition> {
@@ -207,9 +259,10 @@ fn add_column(
         let (i, _) = opt(preceded(whitespace1, tag_no_case("column")))(i)?;
         let (i, _) = whitespace1(i)?;
 
-        map(column_specification(dialect), |c| {
-            AlterTableDefinition::AddColumn(c)
-        })(i)
+        let (i, spec) = column_specification(dialect)(i)?;
+        let (i, position) = opt(preceded(whitespace0, column_position(dialect)))(i)?;
+
+        Ok((i, AlterTableDefinition::AddColumn { spec, position }))
     }
 }
 
@@ -308,26 +361,38 @@ fn change_column(
         let (i, name) = dialect.identifier()(i)?;
         let (i, _) = whitespace1(i)?;
         let (i, spec) = column_specification(dialect)(i)?;
+        let (i, position) = opt(preceded(whitespace0, column_position(dialect)))(i)?;
 
-        Ok((i, AlterTableDefinition::ChangeColumn { name, spec }))
+        Ok((
+            i,
+            AlterTableDefinition::ChangeColumn {
+                name,
+                spec,
+                position,
+            },
+        ))
     }
 }
 
 fn modify_column(
     dialect: Dialect,
 ) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], AlterTableDefinition> {
-    // TODO: FIRST, AFTER col_name
     move |i| {
         let (i, _) = tag_no_case("modify")(i)?;
         let (i, _) = opt(preceded(whitespace1, tag_no_case("column")))(i)?;
         let (i, _) = whitespace1(i)?;
 
-        map(column_specification(dialect), |spec| {
+        let (i, spec) = column_specification(dialect)(i)?;
+        let (i, position) = opt(preceded(whitespace0, column_position(dialect)))(i)?;
+
+        Ok((
+            i,
             AlterTableDefinition::ChangeColumn {
                 name: spec.column.name.clone(),
                 spec,
-            }
-        })(i)
+                position,
+            },
+        ))
     }
 }
 
@@ -341,15 +406,8 @@ fn rename_column(
         let (i, _) = whitespace1(i)?;
 
         let (i, name) = dialect.identifier()(i)?;
-
-        let i = if dialect == Dialect::PostgreSQL {
-            let (i, _) = whitespace1(i)?;
-            let (i, _) = tag_no_case("to")(i)?;
-            i
-        } else {
-            i
-        };
-
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("to")(i)?;
         let (i, _) = whitespace1(i)?;
         let (i, new_name) = dialect.identifier()(i)?;
 
@@ -476,24 +534,30 @@ mod tests {
                 schema: None,
             },
             definitions: Ok(vec![
-                AlterTableDefinition::AddColumn(ColumnSpecification {
-                    column: Column {
-                        name: "Email".into(),
-                        table: None,
+                AlterTableDefinition::AddColumn {
+                    spec: ColumnSpecification {
+                        column: Column {
+                            name: "Email".into(),
+                            table: None,
+                        },
+                        sql_type: SqlType::VarChar(Some(255)),
+                        constraints: vec![],
+                        comment: None,
                     },
-                    sql_type: SqlType::VarChar(Some(255)),
-                    constraints: vec![],
-                    comment: None,
-                }),
-                AlterTableDefinition::AddColumn(ColumnSpecification {
-                    column: Column {
-                        name: "snailmail".into(),
-                        table: None,
+                    position: None,
+                },
+                AlterTableDefinition::AddColumn {
+                    spec: ColumnSpecification {
+                        column: Column {
+                            name: "snailmail".into(),
+                            table: None,
+                        },
+                        sql_type: SqlType::Text,
+                        constraints: vec![],
+                        comment: None,
                     },
-                    sql_type: SqlType::Text,
-                    constraints: vec![],
-                    comment: None,
-                }),
+                    position: None,
+                },
             ]),
             only: false,
         };
@@ -510,15 +574,18 @@ mod tests {
         fn display_add_column() {
             let stmt = AlterTableStatement {
                 table: "t".into(),
-                definitions: Ok(vec![AlterTableDefinition::AddColumn(ColumnSpecification {
-                    column: Column {
-                        name: "c".into(),
-                        table: None,
+                definitions: Ok(vec![AlterTableDefinition::AddColumn {
+                    spec: ColumnSpecification {
+                        column: Column {
+                            name: "c".into(),
+                            table: None,
+                        },
+                        sql_type: SqlType::Int(Some(32)),
+                        comment: None,
+                        constraints: vec![],
                     },
-                    sql_type: SqlType::Int(Some(32)),
-                    comment: None,
-                    constraints: vec![],
-                })]),
+                    position: None,
+                }]),
                 only: false,
             };
 
@@ -577,15 +644,18 @@ mod tests {
                     name: "t".into(),
                     schema: None,
                 },
-                definitions: Ok(vec![AlterTableDefinition::AddColumn(ColumnSpecification {
-                    column: Column {
-                        name: "c".into(),
-                        table: None,
+                definitions: Ok(vec![AlterTableDefinition::AddColumn {
+                    spec: ColumnSpecification {
+                        column: Column {
+                            name: "c".into(),
+                            table: None,
+                        },
+                        sql_type: SqlType::Int(None),
+                        constraints: vec![],
+                        comment: None,
                     },
-                    sql_type: SqlType::Int(None),
-                    constraints: vec![],
-                    comment: None,
-                })]),
+                    position: None,
+                }]),
                 only: false,
             };
             let result =
@@ -602,24 +672,30 @@ mod tests {
                     schema: None,
                 },
                 definitions: Ok(vec![
-                    AlterTableDefinition::AddColumn(ColumnSpecification {
-                        column: Column {
-                            name: "c".into(),
-                            table: None,
+                    AlterTableDefinition::AddColumn {
+                        spec: ColumnSpecification {
+                            column: Column {
+                                name: "c".into(),
+                                table: None,
+                            },
+                            sql_type: SqlType::Int(None),
+                            constraints: vec![],
+                            comment: None,
                         },
-                        sql_type: SqlType::Int(None),
-                        constraints: vec![],
-                        comment: None,
-                    }),
-                    AlterTableDefinition::AddColumn(ColumnSpecification {
-                        column: Column {
-                            name: "d".into(),
-                            table: None,
+                        position: None,
+                    },
+                    AlterTableDefinition::AddColumn {
+                        spec: ColumnSpecification {
+                            column: Column {
+                                name: "d".into(),
+                                table: None,
+                            },
+                            sql_type: SqlType::Text,
+                            constraints: vec![],
+                            comment: None,
                         },
-                        sql_type: SqlType::Text,
-                        constraints: vec![],
-                        comment: None,
-                    }),
+                        position: None,
+                    },
                 ]),
                 only: false,
             };
@@ -721,7 +797,8 @@ mod tests {
                             sql_type: SqlType::DateTime(None),
                             constraints: vec![ColumnConstraint::NotNull],
                             comment: None,
-                        }
+                        },
+                        position: None
                     }]),
                     only: false,
                 }
@@ -746,7 +823,8 @@ mod tests {
                                 ColumnConstraint::PrimaryKey
                             ],
                             comment: None,
-                        }
+                        },
+                        position: None
                     }]),
                     only: false,
                 }
@@ -768,7 +846,8 @@ mod tests {
                             sql_type: SqlType::DateTime(None),
                             constraints: vec![],
                             comment: None,
-                        }
+                        },
+                        position: None
                     }]),
                     only: false,
                 }
@@ -848,12 +927,18 @@ mod tests {
                 res,
                 AlterTableStatement {
                     table: Relation::from("discussion_user"),
-                    definitions: Ok(vec![AlterTableDefinition::AddColumn(ColumnSpecification {
-                        column: Column::from("subscription"),
-                        sql_type: SqlType::from_enum_variants(["follow".into(), "ignore".into(),]),
-                        constraints: vec![ColumnConstraint::Null],
-                        comment: None,
-                    })]),
+                    definitions: Ok(vec![AlterTableDefinition::AddColumn {
+                        spec: ColumnSpecification {
+                            column: Column::from("subscription"),
+                            sql_type: SqlType::from_enum_variants([
+                                "follow".into(),
+                                "ignore".into(),
+                            ]),
+                            constraints: vec![ColumnConstraint::Null],
+                            comment: None,
+                        },
+                        position: None,
+                    }]),
                     only: false,
                 }
             );
@@ -862,6 +947,75 @@ mod tests {
                 "ALTER TABLE `discussion_user` ADD COLUMN `subscription` ENUM('follow', 'ignore') NULL"
             );
         }
+
+        #[test]
+        fn add_column_with_position() {
+            let res = test_parse!(
+                alter_table_statement(Dialect::MySQL),
+                b"ALTER TABLE t ADD COLUMN a INT FIRST, ADD b TEXT NOT NULL AFTER `a`, ADD c INT"
+            );
+            assert_eq!(
+                res.definitions
+                    .as_ref()
+                    .unwrap()
+                    .iter()
+                    .map(|def| match def {
+                        AlterTableDefinition::AddColumn { position, .. } => position.clone(),
+                        _ => panic!("expected ADD COLUMN, got {def:?}"),
+                    })
+                    .collect::<Vec<_>>(),
+                vec![
+                    Some(ColumnPosition::First),
+                    Some(ColumnPosition::After("a".into())),
+                    None
+                ]
+            );
+            assert_eq!(
+                res.display(Dialect::MySQL).to_string(),
+                "ALTER TABLE `t` ADD COLUMN `a` INT FIRST, ADD COLUMN `b` TEXT NOT NULL AFTER `a`, \
+                 ADD COLUMN `c` INT"
+            );
+        }
+
+        #[test]
+        fn modify_column_with_position() {
+            let res = test_parse!(
+                alter_table_statement(Dialect::MySQL),
+                b"ALTER TABLE t MODIFY COLUMN x BIGINT AFTER y"
+            );
+            assert_eq!(
+                res.definitions.unwrap(),
+                vec![AlterTableDefinition::ChangeColumn {
+                    name: "x".into(),
+                    spec: ColumnSpecification {
+                        column: Column::from("x"),
+                        sql_type: SqlType::BigInt(None),
+                        constraints: vec![],
+                        comment: None,
+                    },
+                    position: Some(ColumnPosition::After("y".into())),
+                }]
+            );
+        }
+
+        #[test]
+        fn rename_column() {
+            let res = test_parse!(
+                alter_table_statement(Dialect::MySQL),
+                b"ALTER TABLE t RENAME COLUMN x TO y"
+            );
+            assert_eq!(
+                res.definitions.as_ref().unwrap(),
+                &vec![AlterTableDefinition::RenameColumn {
+                    name: "x".into(),
+                    new_name: "y".into()
+                }]
+            );
+            assert_eq!(
+                res.display(Dialect::MySQL).to_string(),
+                "ALTER TABLE `t` RENAME COLUMN `x` TO `y`"
+            );
+        }
     }
 
     mod postgres {
@@ -872,15 +1026,18 @@ mod tests {
         fn display_add_column() {
             let stmt = AlterTableStatement {
                 table: "t".into(),
-                definitions: Ok(vec![AlterTableDefinition::AddColumn(ColumnSpecification {
-                    column: Column {
-                        name: "c".into(),
-                        table: None,
+                definitions: Ok(vec![AlterTableDefinition::AddColumn {
+                    spec: ColumnSpecification {
+                        column: Column {
+                            name: "c".into(),
+                            table: None,
+                        },
+                        sql_type: SqlType::Int(Some(32)),
+                        comment: None,
+                        constraints: vec![],
                     },
-                    sql_type: SqlType::Int(Some(32)),
-                    comment: None,
-                    constraints: vec![],
-                })]),
+                    position: None,
+                }]),
                 only: false,
             };
 
@@ -939,15 +1096,18 @@ mod tests {
                     name: "t".into(),
                     schema: None,
                 },
-                definitions: Ok(vec![AlterTableDefinition::AddColumn(ColumnSpecification {
-                    column: Column {
-                        name: "c".into(),
-                        table: None,
+                definitions: Ok(vec![AlterTableDefinition::AddColumn {
+                    spec: ColumnSpecification {
+                        column: Column {
+                            name: "c".into(),
+                            table: None,
+                        },
+                        sql_type: SqlType::Int(None),
+                        constraints: vec![],
+                        comment: None,
                     },
-                    sql_type: SqlType::Int(None),
-                    constraints: vec![],
-                    comment: None,
-                })]),
+                    position: None,
+                }]),
                 only: false,
             };
             let result =
@@ -964,24 +1124,30 @@ mod tests {
                     schema: None,
                 },
                 definitions: Ok(vec![
-                    AlterTableDefinition::AddColumn(ColumnSpecification {
-                        column: Column {
-                            name: "c".into(),
-                            table: None,
+                    AlterTableDefinition::AddColumn {
+                        spec: ColumnSpecification {
+                            column: Column {
+                                name: "c".into(),
+                                table: None,
+                            },
+                            sql_type: SqlType::Int(None),
+                            constraints: vec![],
+                            comment: None,
                         },
-                        sql_type: SqlType::Int(None),
-                        constraints: vec![],
-                        comment: None,
-                    }),
-                    AlterTableDefinition::AddColumn(ColumnSpecification {
-                        column: Column {
-                            name: "d".into(),
-                            table: None,
+                        position: None,
+                    },
+                    AlterTableDefinition::AddColumn {
+                        spec: ColumnSpecification {
+                            column: Column {
+                                name: "d".into(),
+                                table: None,
+                            },
+                            sql_type: SqlType::Text,
+                            constraints: vec![],
+                            comment: None,
                         },
-                        sql_type: SqlType::Text,
-                        constraints: vec![],
-                        comment: None,
-                    }),
+                        position: None,
+                    },
                 ]),
                 only: false,
             };
@@ -1297,5 +1463,17 @@ mod tests {
                 }]
             );
         }
+
+        #[test]
+        fn display_rename_column() {
+            let res = test_parse!(
+                alter_table_statement(Dialect::PostgreSQL),
+                b"ALTER TABLE t RENAME COLUMN x TO y"
+            );
+            assert_eq!(
+                res.display(Dialect::PostgreSQL).to_string(),
+                r#"ALTER TABLE "t" RENAME COLUMN "x" TO "y""#
+            );
+        }
     }
 }
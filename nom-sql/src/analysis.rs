@@ -54,7 +54,9 @@ impl<'a> ReferredColumnsIter<'a> {
                 self.exprs_to_visit.push(lhs);
                 self.visit_expr(rhs)
             }
-            Expr::UnaryOp { rhs: expr, .. } | Expr::Cast { expr, .. } => self.visit_expr(expr),
+            Expr::UnaryOp { rhs: expr, .. }
+            | Expr::Cast { expr, .. }
+            | Expr::Interval { value: expr, .. } => self.visit_expr(expr),
             Expr::Exists { .. } => None,
             Expr::Between {
                 operand, min, max, ..
@@ -97,17 +99,47 @@ impl<'a> ReferredColumnsIter<'a> {
             Max(arg) => self.visit_expr(arg),
             Min(arg) => self.visit_expr(arg),
             GroupConcat { expr, .. } => self.visit_expr(expr),
+            Median(expr) => self.visit_expr(expr),
+            PercentileCont { expr, .. } => self.visit_expr(expr),
+            ApproxCountDistinct(expr) => self.visit_expr(expr),
+            ApproxPercentile { expr, .. } => self.visit_expr(expr),
+            Extract { expr, .. } => self.visit_expr(expr),
+            Match { columns, .. } => {
+                self.columns_to_visit.extend(columns.iter());
+                None
+            }
             Call { arguments, .. } => arguments.first().and_then(|first_arg| {
                 if arguments.len() >= 2 {
                     self.exprs_to_visit.extend(arguments.iter().skip(1));
                 }
                 self.visit_expr(first_arg)
             }),
+            Window {
+                partition_by,
+                order,
+                ..
+            } => {
+                self.exprs_to_visit.extend(partition_by);
+                self.exprs_to_visit.extend(order.iter().flat_map(|order| {
+                    order.order_by.iter().filter_map(|(field, _)| match field {
+                        FieldReference::Expr(expr) => Some(expr),
+                        FieldReference::Numeric(_) => None,
+                    })
+                }));
+                self.exprs_to_visit
+                    .pop()
+                    .and_then(|expr| self.visit_expr(expr))
+            }
             Substring { string, pos, len } => {
                 self.exprs_to_visit.extend(pos.iter().map(|e| e.as_ref()));
                 self.exprs_to_visit.extend(len.iter().map(|e| e.as_ref()));
                 self.visit_expr(string)
             }
+            Trim { remove, string, .. } => {
+                self.exprs_to_visit
+                    .extend(remove.iter().map(|e| e.as_ref()));
+                self.visit_expr(string)
+            }
         }
     }
 
@@ -167,7 +199,9 @@ impl<'a> ReferredColumnsMut<'a> {
                 self.exprs_to_visit.push(lhs);
                 self.visit_expr(rhs)
             }
-            Expr::UnaryOp { rhs: expr, .. } | Expr::Cast { expr, .. } => self.visit_expr(expr),
+            Expr::UnaryOp { rhs: expr, .. }
+            | Expr::Cast { expr, .. }
+            | Expr::Interval { value: expr, .. } => self.visit_expr(expr),
             Expr::Exists { .. } => None,
             Expr::Between {
                 operand, min, max, ..
@@ -206,10 +240,39 @@ impl<'a> ReferredColumnsMut<'a> {
             Max(arg) => self.visit_expr(arg),
             Min(arg) => self.visit_expr(arg),
             GroupConcat { expr, .. } => self.visit_expr(expr),
+            Median(expr) => self.visit_expr(expr),
+            PercentileCont { expr, .. } => self.visit_expr(expr),
+            ApproxCountDistinct(expr) => self.visit_expr(expr),
+            ApproxPercentile { expr, .. } => self.visit_expr(expr),
+            Extract { expr, .. } => self.visit_expr(expr),
+            Match { columns, .. } => {
+                self.columns_to_visit.extend(columns.iter_mut());
+                None
+            }
             Call { arguments, .. } => arguments.split_first_mut().and_then(|(first_arg, args)| {
                 self.exprs_to_visit.extend(args);
                 self.visit_expr(first_arg)
             }),
+            Window {
+                partition_by,
+                order,
+                ..
+            } => {
+                self.exprs_to_visit.extend(partition_by);
+                self.exprs_to_visit
+                    .extend(order.iter_mut().flat_map(|order| {
+                        order
+                            .order_by
+                            .iter_mut()
+                            .filter_map(|(field, _)| match field {
+                                FieldReference::Expr(expr) => Some(expr),
+                                FieldReference::Numeric(_) => None,
+                            })
+                    }));
+                self.exprs_to_visit
+                    .pop()
+                    .and_then(|expr| self.visit_expr(expr))
+            }
             Substring { string, pos, len } => {
                 self.exprs_to_visit
                     .extend(pos.iter_mut().map(|e| e.as_mut()));
@@ -217,6 +280,11 @@ impl<'a> ReferredColumnsMut<'a> {
                     .extend(len.iter_mut().map(|e| e.as_mut()));
                 self.visit_expr(string)
             }
+            Trim { remove, string, .. } => {
+                self.exprs_to_visit
+                    .extend(remove.iter_mut().map(|e| e.as_mut()));
+                self.visit_expr(string)
+            }
         }
     }
 
@@ -338,8 +406,16 @@ pub fn is_aggregate(function: &FunctionExpr) -> bool {
         | FunctionExpr::Sum { .. }
         | FunctionExpr::Max(_)
         | FunctionExpr::Min(_)
-        | FunctionExpr::GroupConcat { .. } => true,
+        | FunctionExpr::GroupConcat { .. }
+        | FunctionExpr::Median(_)
+        | FunctionExpr::PercentileCont { .. }
+        | FunctionExpr::ApproxCountDistinct(_)
+        | FunctionExpr::ApproxPercentile { .. } => true,
         FunctionExpr::Substring { .. }
+        | FunctionExpr::Extract { .. }
+        | FunctionExpr::Trim { .. }
+        | FunctionExpr::Window { .. }
+        | FunctionExpr::Match { .. }
         // For now, assume all "generic" function calls are not aggregates
         | FunctionExpr::Call { .. } => false,
     }
@@ -367,7 +443,9 @@ pub fn contains_aggregate(expr: &Expr) -> bool {
         | Expr::OpAny { lhs, rhs, .. }
         | Expr::OpSome { lhs, rhs, .. }
         | Expr::OpAll { lhs, rhs, .. } => contains_aggregate(lhs) || contains_aggregate(rhs),
-        Expr::UnaryOp { rhs: expr, .. } | Expr::Cast { expr, .. } => contains_aggregate(expr),
+        Expr::UnaryOp { rhs: expr, .. }
+        | Expr::Cast { expr, .. }
+        | Expr::Interval { value: expr, .. } => contains_aggregate(expr),
         Expr::Exists(_) => false,
         Expr::Between {
             operand, min, max, ..
@@ -441,9 +519,9 @@ impl Expr {
             | Expr::OpAll { lhs, rhs, .. } => {
                 Box::new(vec![lhs, rhs].into_iter().map(AsRef::as_ref)) as _
             }
-            Expr::UnaryOp { rhs: expr, .. } | Expr::Cast { expr, .. } => {
-                Box::new(iter::once(expr.as_ref())) as _
-            }
+            Expr::UnaryOp { rhs: expr, .. }
+            | Expr::Cast { expr, .. }
+            | Expr::Interval { value: expr, .. } => Box::new(iter::once(expr.as_ref())) as _,
             Expr::CaseWhen {
                 branches,
                 else_expr,
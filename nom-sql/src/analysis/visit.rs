@@ -15,14 +15,16 @@ use crate::rename::{RenameTableOperation, RenameTableStatement};
 use crate::select::LimitClause;
 use crate::set::Variable;
 use crate::transaction::{CommitStatement, RollbackStatement, StartTransactionStatement};
+use crate::truncate::{TruncateStatement, TruncateTable};
 use crate::{
-    AlterColumnOperation, AlterTableDefinition, AlterTableStatement, CacheInner, CaseWhenBranch,
-    Column, ColumnConstraint, ColumnSpecification, CommonTableExpr, CompoundSelectStatement,
-    CreateCacheStatement, CreateTableStatement, CreateViewStatement, DeleteStatement,
-    DropAllCachesStatement, DropCacheStatement, DropTableStatement, DropViewStatement,
-    ExplainStatement, Expr, FieldDefinitionExpr, FieldReference, FunctionExpr, GroupByClause,
-    InValue, InsertStatement, JoinClause, JoinConstraint, JoinRightSide, Literal, OrderClause,
-    Relation, SelectSpecification, SelectStatement, SetNames, SetPostgresParameter, SetStatement,
+    AlterColumnOperation, AlterTableDefinition, AlterTableStatement, CacheInner, CacheWarmup,
+    CaseWhenBranch, Column, ColumnConstraint, ColumnSpecification, CommonTableExpr,
+    CompoundSelectStatement, CreateCacheStatement, CreateTableStatement, CreateViewStatement,
+    DeleteStatement, DropAllCachesStatement, DropCacheStatement, DropTableStatement,
+    DropViewStatement, ExperimentStatement, ExplainStatement, Expr, FieldDefinitionExpr,
+    FieldReference, FlushCacheStatement, FunctionExpr, GroupByClause, InValue, InsertStatement,
+    JoinClause, JoinConstraint, JoinRightSide, KillStatement, Literal, OrderClause, Relation,
+    SelectSpecification, SelectStatement, SetNames, SetPostgresParameter, SetStatement,
     SetVariables, ShowStatement, SqlIdentifier, SqlQuery, SqlType, TableExpr, TableExprInner,
     TableKey, UpdateStatement, UseStatement,
 };
@@ -396,6 +398,41 @@ pub trait Visitor<'ast>: Sized {
         Ok(())
     }
 
+    fn visit_kill_statement(
+        &mut self,
+        _kill_statement: &'ast KillStatement,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn visit_experiment_statement(
+        &mut self,
+        experiment_statement: &'ast ExperimentStatement,
+    ) -> Result<(), Self::Error> {
+        walk_experiment_statement(self, experiment_statement)
+    }
+
+    fn visit_flush_cache_statement(
+        &mut self,
+        flush_cache_statement: &'ast FlushCacheStatement,
+    ) -> Result<(), Self::Error> {
+        walk_flush_cache_statement(self, flush_cache_statement)
+    }
+
+    fn visit_truncate_statement(
+        &mut self,
+        truncate_statement: &'ast TruncateStatement,
+    ) -> Result<(), Self::Error> {
+        walk_truncate_statement(self, truncate_statement)
+    }
+
+    fn visit_truncate_table(
+        &mut self,
+        truncate_table: &'ast TruncateTable,
+    ) -> Result<(), Self::Error> {
+        walk_truncate_table(self, truncate_table)
+    }
+
     fn visit_sql_query(&mut self, sql_query: &'ast SqlQuery) -> Result<(), Self::Error> {
         walk_sql_query(self, sql_query)
     }
@@ -452,6 +489,7 @@ pub fn walk_expr<'ast, V: Visitor<'ast>>(
             }
             Ok(())
         }
+        Expr::Interval { value, .. } => visitor.visit_expr(value.as_ref()),
         Expr::Variable(var) => visitor.visit_variable(var),
     }
 }
@@ -476,12 +514,44 @@ pub fn walk_function_expr<'ast, V: Visitor<'ast>>(
         FunctionExpr::Max(expr) => visitor.visit_expr(expr.as_ref()),
         FunctionExpr::Min(expr) => visitor.visit_expr(expr.as_ref()),
         FunctionExpr::GroupConcat { expr, .. } => visitor.visit_expr(expr.as_ref()),
+        FunctionExpr::Median(expr) => visitor.visit_expr(expr.as_ref()),
+        FunctionExpr::Extract { expr, .. } => visitor.visit_expr(expr.as_ref()),
+        FunctionExpr::PercentileCont { percentile, expr } => {
+            visitor.visit_literal(percentile)?;
+            visitor.visit_expr(expr.as_ref())
+        }
+        FunctionExpr::ApproxCountDistinct(expr) => visitor.visit_expr(expr.as_ref()),
+        FunctionExpr::ApproxPercentile { expr, percentile } => {
+            visitor.visit_expr(expr.as_ref())?;
+            visitor.visit_literal(percentile)
+        }
         FunctionExpr::Call { arguments, .. } => {
             for arg in arguments {
                 visitor.visit_expr(arg)?;
             }
             Ok(())
         }
+        FunctionExpr::Match {
+            columns, against, ..
+        } => {
+            for column in columns {
+                visitor.visit_column(column)?;
+            }
+            visitor.visit_literal(against)
+        }
+        FunctionExpr::Window {
+            partition_by,
+            order,
+            ..
+        } => {
+            for expr in partition_by {
+                visitor.visit_expr(expr)?;
+            }
+            if let Some(order) = order {
+                visitor.visit_order_clause(order)?;
+            }
+            Ok(())
+        }
         FunctionExpr::Substring { string, pos, len } => {
             visitor.visit_expr(string.as_ref())?;
             if let Some(pos) = pos {
@@ -492,6 +562,12 @@ pub fn walk_function_expr<'ast, V: Visitor<'ast>>(
             }
             Ok(())
         }
+        FunctionExpr::Trim { remove, string, .. } => {
+            if let Some(remove) = remove {
+                visitor.visit_expr(remove.as_ref())?;
+            }
+            visitor.visit_expr(string.as_ref())
+        }
     }
 }
 
@@ -784,6 +860,10 @@ pub fn walk_table_key<'a, V: Visitor<'a>>(
         TableKey::FulltextKey {
             index_name,
             columns,
+        }
+        | TableKey::SpatialKey {
+            index_name,
+            columns,
         } => {
             if let Some(index_name) = index_name {
                 visitor.visit_sql_identifier(index_name)?;
@@ -858,7 +938,8 @@ pub fn walk_column_constraint<'a, V: Visitor<'a>>(
         | ColumnConstraint::AutoIncrement
         | ColumnConstraint::PrimaryKey
         | ColumnConstraint::Unique
-        | ColumnConstraint::OnUpdateCurrentTimestamp => Ok(()),
+        | ColumnConstraint::OnUpdateCurrentTimestamp
+        | ColumnConstraint::Srid(_) => Ok(()),
     }
 }
 
@@ -900,14 +981,18 @@ pub fn walk_alter_table_definition<'a, V: Visitor<'a>>(
     alter_table_definition: &'a AlterTableDefinition,
 ) -> Result<(), V::Error> {
     match alter_table_definition {
-        AlterTableDefinition::AddColumn(spec) => visitor.visit_column_specification(spec),
+        AlterTableDefinition::AddColumn { spec, position: _ } => {
+            visitor.visit_column_specification(spec)
+        }
         AlterTableDefinition::AddKey(key) => visitor.visit_table_key(key),
         AlterTableDefinition::AlterColumn { name: _, operation } => {
             visitor.visit_alter_column_operation(operation)
         }
-        AlterTableDefinition::ChangeColumn { name: _, spec } => {
-            visitor.visit_column_specification(spec)
-        }
+        AlterTableDefinition::ChangeColumn {
+            name: _,
+            spec,
+            position: _,
+        } => visitor.visit_column_specification(spec),
         AlterTableDefinition::DropColumn {
             name: _,
             behavior: _,
@@ -958,6 +1043,12 @@ pub fn walk_insert_statement<'a, V: Visitor<'a>>(
         }
     }
 
+    if let Some(on_conflict) = &insert_statement.on_conflict {
+        for column in on_conflict {
+            visitor.visit_column(column)?;
+        }
+    }
+
     Ok(())
 }
 
@@ -1068,10 +1159,67 @@ pub fn walk_create_cache_statement<'a, V: Visitor<'a>>(
         Ok(CacheInner::Id(_)) => {}
         Err(_) => {}
     }
+    match &create_cache_statement.warmup {
+        Some(CacheWarmup::Keys(keys)) => {
+            for literal in keys.iter().flatten() {
+                visitor.visit_literal(literal)?;
+            }
+        }
+        Some(CacheWarmup::Query(stmt)) => visitor.visit_select_statement(stmt)?,
+        None => {}
+    }
 
     Ok(())
 }
 
+pub fn walk_experiment_statement<'a, V: Visitor<'a>>(
+    visitor: &mut V,
+    experiment_statement: &'a ExperimentStatement,
+) -> Result<(), V::Error> {
+    match experiment_statement {
+        ExperimentStatement::Create(create) => {
+            walk_relation(visitor, &create.cache)?;
+            visitor.visit_select_statement(&create.statement)
+        }
+        ExperimentStatement::Promote { cache } | ExperimentStatement::Drop { cache } => {
+            walk_relation(visitor, cache)
+        }
+    }
+}
+
+pub fn walk_flush_cache_statement<'a, V: Visitor<'a>>(
+    visitor: &mut V,
+    flush_cache_statement: &'a FlushCacheStatement,
+) -> Result<(), V::Error> {
+    if let Some(view) = &flush_cache_statement.view {
+        walk_relation(visitor, view)?;
+    }
+    for key in &flush_cache_statement.keys {
+        for literal in key {
+            visitor.visit_literal(literal)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn walk_truncate_statement<'a, V: Visitor<'a>>(
+    visitor: &mut V,
+    truncate_statement: &'a TruncateStatement,
+) -> Result<(), V::Error> {
+    for table in &truncate_statement.tables {
+        visitor.visit_truncate_table(table)?;
+    }
+
+    Ok(())
+}
+
+pub fn walk_truncate_table<'a, V: Visitor<'a>>(
+    visitor: &mut V,
+    truncate_table: &'a TruncateTable,
+) -> Result<(), V::Error> {
+    visitor.visit_table(&truncate_table.relation)
+}
+
 pub fn walk_drop_view_statement<'a, V: Visitor<'a>>(
     visitor: &mut V,
     drop_view_statement: &'a DropViewStatement,
@@ -1111,6 +1259,10 @@ pub fn walk_sql_query<'a, V: Visitor<'a>>(
         SqlQuery::Use(statement) => visitor.visit_use_statement(statement),
         SqlQuery::Show(statement) => visitor.visit_show_statement(statement),
         SqlQuery::Explain(statement) => visitor.visit_explain_statement(statement),
+        SqlQuery::Kill(statement) => visitor.visit_kill_statement(statement),
+        SqlQuery::Experiment(statement) => visitor.visit_experiment_statement(statement),
+        SqlQuery::FlushCache(statement) => visitor.visit_flush_cache_statement(statement),
+        SqlQuery::Truncate(statement) => visitor.visit_truncate_statement(statement),
     }
 }
 
@@ -1,9 +1,11 @@
 use std::cmp::Ordering;
+use std::str::FromStr;
 use std::{fmt, str};
 
 use nom::branch::alt;
 use nom::bytes::complete::{tag, tag_no_case};
-use nom::combinator::{map, opt};
+use nom::character::complete::digit1;
+use nom::combinator::{map, map_res, opt};
 use nom::multi::many0;
 use nom::sequence::{delimited, preceded, tuple};
 use nom_locate::LocatedSpan;
@@ -89,6 +91,9 @@ pub enum ColumnConstraint {
     /// NOTE(grfn): Yes, this really is its own special thing, not just an expression - see
     /// <https://dev.mysql.com/doc/refman/8.0/en/timestamp-initialization.html>
     OnUpdateCurrentTimestamp,
+    /// The spatial reference system identifier of a column with a spatial type - see
+    /// <https://dev.mysql.com/doc/refman/8.0/en/spatial-type-overview.html>
+    Srid(u32),
 }
 
 impl ColumnConstraint {
@@ -103,6 +108,7 @@ impl ColumnConstraint {
             Self::PrimaryKey => write!(f, "PRIMARY KEY"),
             Self::Unique => write!(f, "UNIQUE"),
             Self::OnUpdateCurrentTimestamp => write!(f, "ON UPDATE CURRENT_TIMESTAMP"),
+            Self::Srid(srid) => write!(f, "SRID {}", srid),
         })
     }
 }
@@ -244,6 +250,16 @@ pub fn column_constraint(
                 ColumnConstraint::Collation(collation)
             },
         );
+        let srid = map(
+            preceded(
+                delimited(whitespace0, tag_no_case("srid"), whitespace1),
+                map_res(
+                    map_res(digit1, |i: LocatedSpan<&[u8]>| str::from_utf8(&i)),
+                    u32::from_str,
+                ),
+            ),
+            ColumnConstraint::Srid,
+        );
 
         alt((
             not_null,
@@ -255,6 +271,7 @@ pub fn column_constraint(
             character_set,
             collate,
             on_update_current_timestamp,
+            srid,
         ))(i)
     }
 }
@@ -298,7 +315,7 @@ mod tests {
 
     mod mysql {
         use super::*;
-        use crate::FunctionExpr;
+        use crate::{FunctionExpr, SpatialType};
 
         #[test]
         fn multiple_constraints() {
@@ -358,6 +375,21 @@ mod tests {
                 ColumnConstraint::DefaultValue(Expr::Literal(Literal::Boolean(true)))
             ));
         }
+
+        #[test]
+        fn spatial_column_with_srid() {
+            let input = b"`location` POINT NOT NULL SRID 4326";
+            let cspec = column_specification(Dialect::MySQL)(LocatedSpan::new(input))
+                .unwrap()
+                .1;
+            assert_eq!(cspec.sql_type, SqlType::Spatial(SpatialType::Point));
+            assert_eq!(
+                cspec.constraints,
+                vec![ColumnConstraint::NotNull, ColumnConstraint::Srid(4326)]
+            );
+            let res = cspec.display(Dialect::MySQL).to_string();
+            assert_eq!(res, String::from_utf8(input.to_vec()).unwrap());
+        }
     }
 
     mod postgres {
@@ -8,7 +8,7 @@ use itertools::Itertools;
 use nom::branch::alt;
 use nom::bytes::complete::{tag, tag_no_case, take_until};
 use nom::character::complete::{digit1, line_ending};
-use nom::combinator::{map, map_res, opt};
+use nom::combinator::{map, map_res, opt, value};
 use nom::error::{ErrorKind, ParseError};
 use nom::multi::{separated_list0, separated_list1};
 use nom::sequence::{delimited, pair, preceded, separated_pair, terminated, tuple};
@@ -19,10 +19,15 @@ use serde::{Deserialize, Serialize};
 
 use crate::column::Column;
 use crate::dialect::Dialect;
-use crate::expression::expression;
+use crate::expression::{expression, interval_unit};
+use crate::literal::literal;
+use crate::order::order_clause;
 use crate::table::Relation;
 use crate::whitespace::{whitespace0, whitespace1};
-use crate::{Expr, FunctionExpr, Literal, NomSqlResult, SqlIdentifier};
+use crate::{
+    Expr, FunctionExpr, Literal, MatchSearchModifier, NomSqlResult, SqlIdentifier, TrimSide,
+    WindowFunction,
+};
 
 #[cfg(feature = "debug")]
 pub fn debug_print(tag: &str, i: &[u8]) {
@@ -110,6 +115,10 @@ pub enum TableKey {
         expr: Expr,
         enforced: Option<bool>,
     },
+    SpatialKey {
+        index_name: Option<SqlIdentifier>,
+        columns: Vec<Column>,
+    },
 }
 
 impl TableKey {
@@ -130,7 +139,7 @@ impl TableKey {
             | TableKey::CheckConstraint {
                 constraint_name, ..
             } => constraint_name,
-            TableKey::FulltextKey { .. } => &None,
+            TableKey::FulltextKey { .. } | TableKey::SpatialKey { .. } => &None,
         }
     }
 
@@ -194,6 +203,20 @@ impl TableKey {
                         columns.iter().map(|c| c.display(dialect)).join(", ")
                     )
                 }
+                TableKey::SpatialKey {
+                    index_name,
+                    columns,
+                } => {
+                    write!(f, "SPATIAL KEY ")?;
+                    if let Some(ref index_name) = *index_name {
+                        write!(f, "{} ", dialect.quote_identifier(index_name))?;
+                    }
+                    write!(
+                        f,
+                        "({})",
+                        columns.iter().map(|c| c.display(dialect)).join(", ")
+                    )
+                }
                 TableKey::Key {
                     index_name,
                     columns,
@@ -225,14 +248,14 @@ impl TableKey {
                 } => {
                     let index_name = fmt_with(|f| {
                         if let Some(index_name) = index_name {
-                            write!(f, "{}", dialect.quote_identifier(index_name))?;
+                            write!(f, "{} ", dialect.quote_identifier(index_name))?;
                         }
                         Ok(())
                     });
 
                     write!(
                         f,
-                        "FOREIGN KEY {} ({}) REFERENCES {} ({})",
+                        "FOREIGN KEY {}({}) REFERENCES {} ({})",
                         index_name,
                         columns.iter().map(|c| c.display(dialect)).join(", "),
                         target_table.display(dialect),
@@ -500,6 +523,277 @@ fn substring(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[
     }
 }
 
+fn extract(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
+    move |i| {
+        let (i, _) = tag_no_case("extract")(i)?;
+        let (i, _) = tuple((whitespace0, tag("("), whitespace0))(i)?;
+        let (i, field) = interval_unit(i)?;
+        let (i, _) = tuple((whitespace1, tag_no_case("from"), whitespace1))(i)?;
+        let (i, expr) = expression(dialect)(i)?;
+        let (i, _) = tuple((whitespace0, tag(")")))(i)?;
+
+        Ok((
+            i,
+            FunctionExpr::Extract {
+                field,
+                expr: Box::new(expr),
+            },
+        ))
+    }
+}
+
+fn trim_side(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], TrimSide> {
+    alt((
+        value(TrimSide::Both, tag_no_case("both")),
+        value(TrimSide::Leading, tag_no_case("leading")),
+        value(TrimSide::Trailing, tag_no_case("trailing")),
+    ))(i)
+}
+
+fn trim(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
+    move |i| {
+        let (i, _) = tag_no_case("trim")(i)?;
+        let (i, _) = tuple((whitespace0, tag("("), whitespace0))(i)?;
+        let (i, side) = opt(terminated(trim_side, whitespace1))(i)?;
+        let (i, remove) = opt(terminated(
+            expression(dialect),
+            tuple((whitespace1, tag_no_case("from"), whitespace1)),
+        ))(i)?;
+        // `TRIM(LEADING FROM string)` specifies the side but not the string to remove
+        let (i, _) = if side.is_some() && remove.is_none() {
+            map(tuple((tag_no_case("from"), whitespace1)), |_| ())(i)?
+        } else {
+            (i, ())
+        };
+        let (i, string) = expression(dialect)(i)?;
+        let (i, _) = tuple((whitespace0, tag(")")))(i)?;
+
+        Ok((
+            i,
+            FunctionExpr::Trim {
+                side: side.unwrap_or(TrimSide::Both),
+                remove: remove.map(Box::new),
+                string: Box::new(string),
+            },
+        ))
+    }
+}
+
+fn percentile_cont(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
+    move |i| {
+        let (i, _) = tag_no_case("percentile_cont")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, percentile) = delimited(
+            terminated(tag("("), whitespace0),
+            literal(dialect),
+            preceded(whitespace0, tag(")")),
+        )(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("within")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("group")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag("(")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag_no_case("order")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("by")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, expr) = expression(dialect)(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag(")")(i)?;
+
+        Ok((
+            i,
+            FunctionExpr::PercentileCont {
+                percentile,
+                expr: Box::new(expr),
+            },
+        ))
+    }
+}
+
+fn approx_percentile(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
+    move |i| {
+        let (i, _) = tag_no_case("approx_percentile")(i)?;
+        let (i, _) = tuple((whitespace0, tag("("), whitespace0))(i)?;
+        let (i, expr) = expression(dialect)(i)?;
+        let (i, _) = ws_sep_comma(i)?;
+        let (i, percentile) = literal(dialect)(i)?;
+        let (i, _) = tuple((whitespace0, tag(")")))(i)?;
+
+        Ok((
+            i,
+            FunctionExpr::ApproxPercentile {
+                expr: Box::new(expr),
+                percentile,
+            },
+        ))
+    }
+}
+
+fn query_expansion(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], ()> {
+    let (i, _) = tag_no_case("with")(i)?;
+    let (i, _) = whitespace1(i)?;
+    let (i, _) = tag_no_case("query")(i)?;
+    let (i, _) = whitespace1(i)?;
+    let (i, _) = tag_no_case("expansion")(i)?;
+    Ok((i, ()))
+}
+
+fn match_search_modifier(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], MatchSearchModifier> {
+    alt((
+        map(
+            tuple((
+                tag_no_case("in"),
+                whitespace1,
+                tag_no_case("natural"),
+                whitespace1,
+                tag_no_case("language"),
+                whitespace1,
+                tag_no_case("mode"),
+                whitespace1,
+                query_expansion,
+            )),
+            |_| MatchSearchModifier::NaturalLanguageWithQueryExpansion,
+        ),
+        map(
+            tuple((
+                tag_no_case("in"),
+                whitespace1,
+                tag_no_case("natural"),
+                whitespace1,
+                tag_no_case("language"),
+                whitespace1,
+                tag_no_case("mode"),
+            )),
+            |_| MatchSearchModifier::NaturalLanguage,
+        ),
+        map(
+            tuple((
+                tag_no_case("in"),
+                whitespace1,
+                tag_no_case("boolean"),
+                whitespace1,
+                tag_no_case("mode"),
+            )),
+            |_| MatchSearchModifier::Boolean,
+        ),
+        map(query_expansion, |_| MatchSearchModifier::QueryExpansion),
+    ))(i)
+}
+
+fn match_against(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
+    move |i| {
+        let (i, _) = tag_no_case("match")(i)?;
+        let (i, _) = tuple((whitespace0, tag("("), whitespace0))(i)?;
+        let (i, columns) = separated_list1(ws_sep_comma, column_identifier_no_alias(dialect))(i)?;
+        let (i, _) = tuple((whitespace0, tag(")"), whitespace0))(i)?;
+        let (i, _) = tag_no_case("against")(i)?;
+        let (i, _) = tuple((whitespace0, tag("("), whitespace0))(i)?;
+        let (i, against) = literal(dialect)(i)?;
+        let (i, modifier) = opt(preceded(whitespace1, match_search_modifier))(i)?;
+        let (i, _) = tuple((whitespace0, tag(")")))(i)?;
+
+        Ok((
+            i,
+            FunctionExpr::Match {
+                columns,
+                against,
+                modifier,
+            },
+        ))
+    }
+}
+
+fn window_function(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], WindowFunction> {
+    alt((
+        map(tag_no_case("row_number"), |_| WindowFunction::RowNumber),
+        map(tag_no_case("dense_rank"), |_| WindowFunction::DenseRank),
+        map(tag_no_case("rank"), |_| WindowFunction::Rank),
+    ))(i)
+}
+
+fn window_partition_by(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Vec<Expr>> {
+    move |i| {
+        let (i, _) = tag_no_case("partition")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("by")(i)?;
+        let (i, _) = whitespace1(i)?;
+        separated_list1(ws_sep_comma, expression(dialect))(i)
+    }
+}
+
+/// Parses a MySQL `VALUES(<column>)` function call, which can be used in the `ON DUPLICATE KEY
+/// UPDATE` clause of an `INSERT` statement to refer to the value that would have been inserted into
+/// the given column. `VALUES` is a keyword, so this can't be parsed as a regular function call.
+fn values_function_call(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
+    move |i| {
+        let (i, _) = tag_no_case("values")(i)?;
+        let (i, _) = tuple((whitespace0, tag("("), whitespace0))(i)?;
+        let (i, column) = column_identifier_no_alias(dialect)(i)?;
+        let (i, _) = tuple((whitespace0, tag(")")))(i)?;
+
+        Ok((
+            i,
+            FunctionExpr::Call {
+                name: "values".into(),
+                arguments: vec![Expr::Column(column)],
+            },
+        ))
+    }
+}
+
+fn window_function_call(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
+    move |i| {
+        let (i, function) = alt((
+            terminated(
+                window_function,
+                tuple((whitespace0, tag("("), whitespace0, tag(")"))),
+            ),
+            map(
+                tuple((
+                    tag_no_case("count"),
+                    whitespace0,
+                    tag("("),
+                    whitespace0,
+                    tag("*"),
+                    whitespace0,
+                    tag(")"),
+                )),
+                |_| WindowFunction::CountStar,
+            ),
+        ))(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag_no_case("over")(i)?;
+        let (i, _) = tuple((whitespace0, tag("("), whitespace0))(i)?;
+        let (i, partition_by) = opt(window_partition_by(dialect))(i)?;
+        let (i, order) = opt(order_clause(dialect))(i)?;
+        let (i, _) = tuple((whitespace0, tag(")")))(i)?;
+
+        Ok((
+            i,
+            FunctionExpr::Window {
+                function,
+                partition_by: partition_by.unwrap_or_default(),
+                order,
+            },
+        ))
+    }
+}
+
 fn function_call(
     dialect: Dialect,
 ) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
@@ -522,6 +816,9 @@ pub fn function_expr(
 ) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
     move |i| {
         alt((
+            // Window functions need to be tried before aggregates, since `COUNT(*) OVER (...)`
+            // would otherwise be parsed as a bare `COUNT(*)`
+            window_function_call(dialect),
             map(
                 tuple((
                     tag_no_case("count"),
@@ -576,7 +873,41 @@ pub fn function_expr(
                     separator,
                 },
             ),
+            map(
+                preceded(
+                    tag_no_case("median"),
+                    preceded(
+                        whitespace0,
+                        delimited(
+                            terminated(tag("("), whitespace0),
+                            expression(dialect),
+                            preceded(whitespace0, tag(")")),
+                        ),
+                    ),
+                ),
+                |expr| FunctionExpr::Median(Box::new(expr)),
+            ),
+            percentile_cont(dialect),
+            map(
+                preceded(
+                    tag_no_case("approx_count_distinct"),
+                    preceded(
+                        whitespace0,
+                        delimited(
+                            terminated(tag("("), whitespace0),
+                            expression(dialect),
+                            preceded(whitespace0, tag(")")),
+                        ),
+                    ),
+                ),
+                |expr| FunctionExpr::ApproxCountDistinct(Box::new(expr)),
+            ),
+            approx_percentile(dialect),
+            match_against(dialect),
             substring(dialect),
+            extract(dialect),
+            trim(dialect),
+            values_function_call(dialect),
             function_call(dialect),
             function_call_without_parens,
         ))(i)
@@ -843,7 +1174,9 @@ pub fn field_reference_list(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{to_nom_result, SqlType};
+    use crate::{
+        to_nom_result, Double, IntervalUnit, ItemPlaceholder, OrderClause, OrderType, SqlType,
+    };
 
     fn test_opt_delimited_fn_call(i: &str) -> IResult<&[u8], &[u8]> {
         opt_delimited(tag("("), tag("abc"), tag(")"))(i.as_bytes())
@@ -913,6 +1246,122 @@ mod tests {
         );
     }
 
+    #[test]
+    fn median() {
+        let res = test_parse!(function_expr(Dialect::MySQL), b"median( x )");
+        assert_eq!(
+            res,
+            FunctionExpr::Median(Box::new(Expr::Column("x".into())))
+        );
+        assert_eq!(res.display(Dialect::MySQL).to_string(), "median(`x`)");
+    }
+
+    #[test]
+    fn percentile_cont() {
+        let res = test_parse!(
+            function_expr(Dialect::PostgreSQL),
+            b"PERCENTILE_CONT(0.9) WITHIN GROUP (ORDER BY t.x)"
+        );
+        assert_eq!(
+            res,
+            FunctionExpr::PercentileCont {
+                percentile: Literal::Double(Double {
+                    value: 0.9,
+                    precision: 1
+                }),
+                expr: Box::new(Expr::Column("t.x".into())),
+            }
+        );
+        assert_eq!(
+            res.display(Dialect::PostgreSQL).to_string(),
+            "percentile_cont(0.9) within group (order by \"t\".\"x\")"
+        );
+    }
+
+    #[test]
+    fn approx_count_distinct() {
+        let res = test_parse!(function_expr(Dialect::MySQL), b"APPROX_COUNT_DISTINCT(x)");
+        assert_eq!(
+            res,
+            FunctionExpr::ApproxCountDistinct(Box::new(Expr::Column("x".into())))
+        );
+        assert_eq!(
+            res.display(Dialect::MySQL).to_string(),
+            "approx_count_distinct(`x`)"
+        );
+    }
+
+    #[test]
+    fn approx_percentile() {
+        let res = test_parse!(
+            function_expr(Dialect::PostgreSQL),
+            b"approx_percentile( t.x , 0.99 )"
+        );
+        assert_eq!(
+            res,
+            FunctionExpr::ApproxPercentile {
+                expr: Box::new(Expr::Column("t.x".into())),
+                percentile: Literal::Double(Double {
+                    value: 0.99,
+                    precision: 2
+                }),
+            }
+        );
+        assert_eq!(
+            res.display(Dialect::PostgreSQL).to_string(),
+            "approx_percentile(\"t\".\"x\", 0.99)"
+        );
+    }
+
+    #[test]
+    fn match_against() {
+        let res = test_parse!(
+            function_expr(Dialect::MySQL),
+            b"MATCH (title, t.body) AGAINST ('database' IN BOOLEAN MODE)"
+        );
+        assert_eq!(
+            res,
+            FunctionExpr::Match {
+                columns: vec!["title".into(), "t.body".into()],
+                against: Literal::String("database".into()),
+                modifier: Some(MatchSearchModifier::Boolean),
+            }
+        );
+        assert_eq!(
+            res.display(Dialect::MySQL).to_string(),
+            "MATCH (`title`, `t`.`body`) AGAINST ('database' IN BOOLEAN MODE)"
+        );
+
+        let res = test_parse!(function_expr(Dialect::MySQL), b"match(body) against(?)");
+        assert_eq!(
+            res,
+            FunctionExpr::Match {
+                columns: vec!["body".into()],
+                against: Literal::Placeholder(ItemPlaceholder::QuestionMark),
+                modifier: None,
+            }
+        );
+    }
+
+    #[test]
+    fn match_search_modifiers() {
+        for (modifier, expected) in [
+            (
+                "in natural language mode",
+                MatchSearchModifier::NaturalLanguage,
+            ),
+            (
+                "IN NATURAL LANGUAGE MODE WITH QUERY EXPANSION",
+                MatchSearchModifier::NaturalLanguageWithQueryExpansion,
+            ),
+            ("with query expansion", MatchSearchModifier::QueryExpansion),
+        ] {
+            let res = test_parse!(match_search_modifier, modifier.as_bytes());
+            assert_eq!(res, expected);
+            assert_eq!(res.to_string().to_lowercase(), modifier.to_lowercase());
+        }
+    }
+
     #[test]
     fn simple_generic_function() {
         let qlist = [
@@ -1001,6 +1450,79 @@ mod tests {
         assert_eq!(*rem, b"");
     }
 
+    #[test]
+    fn extract_from() {
+        let res = test_parse!(
+            function_expr(Dialect::PostgreSQL),
+            b"EXTRACT(hour FROM created_at)"
+        );
+        assert_eq!(
+            res,
+            FunctionExpr::Extract {
+                field: IntervalUnit::Hour,
+                expr: Box::new(Expr::Column("created_at".into())),
+            }
+        );
+        assert_eq!(
+            res.display(Dialect::PostgreSQL).to_string(),
+            "extract(HOUR from \"created_at\")"
+        );
+    }
+
+    #[test]
+    fn trim_from() {
+        let res = test_parse!(function_expr(Dialect::MySQL), b"TRIM(name)");
+        assert_eq!(
+            res,
+            FunctionExpr::Trim {
+                side: TrimSide::Both,
+                remove: None,
+                string: Box::new(Expr::Column("name".into())),
+            }
+        );
+        assert_eq!(res.display(Dialect::MySQL).to_string(), "trim(`name`)");
+
+        let res = test_parse!(
+            function_expr(Dialect::MySQL),
+            b"trim(leading 'x' from name)"
+        );
+        assert_eq!(
+            res,
+            FunctionExpr::Trim {
+                side: TrimSide::Leading,
+                remove: Some(Box::new(Expr::Literal("x".into()))),
+                string: Box::new(Expr::Column("name".into())),
+            }
+        );
+        assert_eq!(
+            res.display(Dialect::MySQL).to_string(),
+            "trim(LEADING 'x' from `name`)"
+        );
+
+        let res = test_parse!(
+            function_expr(Dialect::PostgreSQL),
+            b"trim(trailing from name)"
+        );
+        assert_eq!(
+            res,
+            FunctionExpr::Trim {
+                side: TrimSide::Trailing,
+                remove: None,
+                string: Box::new(Expr::Column("name".into())),
+            }
+        );
+
+        let res = test_parse!(function_expr(Dialect::PostgreSQL), b"trim('xy' FROM name)");
+        assert_eq!(
+            res,
+            FunctionExpr::Trim {
+                side: TrimSide::Both,
+                remove: Some(Box::new(Expr::Literal("xy".into()))),
+                string: Box::new(Expr::Column("name".into())),
+            }
+        );
+    }
+
     #[test]
     fn substr_from_for() {
         let res = test_parse!(function_expr(Dialect::MySQL), b"substr(a from 1 for 7)");
@@ -1085,6 +1607,78 @@ mod tests {
         );
     }
 
+    #[test]
+    fn window_functions() {
+        let res = test_parse!(
+            function_expr(Dialect::MySQL),
+            b"row_number() OVER (PARTITION BY a, b ORDER BY c DESC)"
+        );
+        assert_eq!(
+            res,
+            FunctionExpr::Window {
+                function: WindowFunction::RowNumber,
+                partition_by: vec![Expr::Column("a".into()), Expr::Column("b".into())],
+                order: Some(OrderClause {
+                    order_by: vec![(
+                        FieldReference::Expr(Expr::Column("c".into())),
+                        Some(OrderType::OrderDescending)
+                    )]
+                }),
+            }
+        );
+        assert_eq!(
+            res.display(Dialect::MySQL).to_string(),
+            "row_number() OVER (PARTITION BY `a`, `b` ORDER BY `c` DESC)"
+        );
+
+        assert_eq!(
+            test_parse!(function_expr(Dialect::MySQL), b"RANK ( ) over(order by x)"),
+            FunctionExpr::Window {
+                function: WindowFunction::Rank,
+                partition_by: vec![],
+                order: Some(OrderClause {
+                    order_by: vec![(FieldReference::Expr(Expr::Column("x".into())), None)]
+                }),
+            }
+        );
+        assert_eq!(
+            test_parse!(
+                function_expr(Dialect::MySQL),
+                b"dense_rank() OVER (PARTITION BY y)"
+            ),
+            FunctionExpr::Window {
+                function: WindowFunction::DenseRank,
+                partition_by: vec![Expr::Column("y".into())],
+                order: None,
+            }
+        );
+        assert_eq!(
+            test_parse!(
+                function_expr(Dialect::MySQL),
+                b"COUNT( * ) OVER (PARTITION BY y)"
+            ),
+            FunctionExpr::Window {
+                function: WindowFunction::CountStar,
+                partition_by: vec![Expr::Column("y".into())],
+                order: None,
+            }
+        );
+    }
+
+    #[test]
+    fn window_function_round_trip() {
+        for s in [
+            "rank() OVER ()",
+            "dense_rank() OVER (ORDER BY `x` ASC)",
+            "row_number() OVER (PARTITION BY `t`.`a` ORDER BY `b`)",
+            "count(*) OVER ()",
+            "count(*) OVER (PARTITION BY `a` ORDER BY `b` DESC)",
+        ] {
+            let res = test_parse!(function_expr(Dialect::MySQL), s.as_bytes());
+            assert_eq!(res.display(Dialect::MySQL).to_string(), s);
+        }
+    }
+
     mod mysql {
         use super::*;
 
@@ -26,7 +26,7 @@ pub enum CompoundSelectOperator {
 impl fmt::Display for CompoundSelectOperator {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            CompoundSelectOperator::Union => write!(f, "UNION"),
+            CompoundSelectOperator::Union => write!(f, "UNION ALL"),
             CompoundSelectOperator::DistinctUnion => write!(f, "UNION DISTINCT"),
             CompoundSelectOperator::Intersect => write!(f, "INTERSECT"),
             CompoundSelectOperator::Except => write!(f, "EXCEPT"),
@@ -44,18 +44,28 @@ pub struct CompoundSelectStatement {
 impl CompoundSelectStatement {
     pub fn display(&self, dialect: Dialect) -> impl fmt::Display + Copy + '_ {
         fmt_with(move |f| {
-            for (op, sel) in &self.selects {
+            for (i, (op, sel)) in self.selects.iter().enumerate() {
+                if i != 0 {
+                    write!(f, " ")?;
+                }
                 if let Some(o) = op {
-                    write!(f, " {}", o)?;
+                    write!(f, "{} ", o)?;
+                }
+
+                // A select with its own ORDER BY or LIMIT clause has to be parenthesized, so that
+                // those clauses aren't parsed as applying to the compound select as a whole
+                if sel.order.is_some() || !sel.limit_clause.is_empty() {
+                    write!(f, "({})", sel.display(dialect))?;
+                } else {
+                    write!(f, "{}", sel.display(dialect))?;
                 }
-                write!(f, " {}", sel.display(dialect))?;
             }
 
             if let Some(ord) = &self.order {
                 write!(f, " {}", ord.display(dialect))?;
             }
 
-            if self.limit_clause.is_empty() {
+            if !self.limit_clause.is_empty() {
                 write!(f, " {}", self.limit_clause)?;
             }
 
@@ -97,23 +107,32 @@ fn compound_op(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], CompoundSelectOpera
     ))(i)
 }
 
+/// A compound operator, the select statement following it, and whether or not that select statement
+/// was parenthesized
+type OtherSelect = (Option<CompoundSelectOperator>, SelectStatement, bool);
+
 fn other_selects(
     dialect: Dialect,
-) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], (Option<CompoundSelectOperator>, SelectStatement)>
-{
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], OtherSelect> {
     move |i| {
-        let (remaining_input, (_, op, _, select)) = tuple((
+        let (remaining_input, (_, op, _, (select, parenthesized))) = tuple((
             whitespace0,
             compound_op,
             whitespace1,
-            opt_delimited(
-                tag("("),
-                delimited(whitespace0, nested_selection(dialect), whitespace0),
-                tag(")"),
-            ),
+            alt((
+                map(nested_selection(dialect), |select| (select, false)),
+                map(
+                    delimited(
+                        tag("("),
+                        delimited(whitespace0, nested_selection(dialect), whitespace0),
+                        tag(")"),
+                    ),
+                    |select| (select, true),
+                ),
+            )),
         ))(i)?;
 
-        Ok((remaining_input, (Some(op), select)))
+        Ok((remaining_input, (Some(op), select, parenthesized)))
     }
 }
 
@@ -138,7 +157,21 @@ pub fn nested_compound_selection(
             ))(i)?;
 
         let mut selects = vec![(None, first_select)];
-        selects.extend(other_selects);
+        let mut last_parenthesized = false;
+        for (op, select, parenthesized) in other_selects {
+            selects.push((op, select));
+            last_parenthesized = parenthesized;
+        }
+
+        // An ORDER BY or LIMIT clause after the last, unparenthesized select statement applies to
+        // the compound select as a whole, rather than just to that last select statement
+        let (mut order, mut limit_clause) = (order, limit_clause);
+        if !last_parenthesized && order.is_none() && limit_clause.is_none() {
+            if let Some((_, last)) = selects.last_mut() {
+                order = last.order.take();
+                limit_clause = Some(std::mem::take(&mut last.limit_clause));
+            }
+        }
 
         Ok((
             remaining_input,
@@ -319,6 +352,33 @@ mod tests {
         assert_eq!(res.unwrap().1, expected);
     }
 
+    #[test]
+    fn display_union() {
+        let qstr = "SELECT id, 1 FROM Vote UNION ALL SELECT id, stars from Rating;";
+        let res = test_parse!(compound_selection(Dialect::MySQL), qstr.as_bytes());
+        assert_eq!(
+            res.display(Dialect::MySQL).to_string(),
+            "SELECT `id`, 1 FROM `Vote` UNION ALL SELECT `id`, `stars` FROM `Rating`"
+        );
+    }
+
+    #[test]
+    fn display_union_with_order_and_limit() {
+        let qstr = "(SELECT id FROM Vote ORDER BY id LIMIT 3) \
+                    UNION DISTINCT SELECT id FROM Rating \
+                    ORDER BY id DESC LIMIT 10";
+        let res = test_parse!(compound_selection(Dialect::MySQL), qstr.as_bytes());
+        let displayed = res.display(Dialect::MySQL).to_string();
+        assert_eq!(
+            displayed,
+            "(SELECT `id` FROM `Vote` ORDER BY `id` LIMIT 3) \
+             UNION DISTINCT SELECT `id` FROM `Rating` \
+             ORDER BY `id` DESC LIMIT 10"
+        );
+        let reparsed = test_parse!(compound_selection(Dialect::MySQL), displayed.as_bytes());
+        assert_eq!(reparsed, res);
+    }
+
     #[test]
     #[ignore]
     fn union_flarum_1() {
@@ -8,7 +8,7 @@ use nom::bytes::complete::{is_not, tag, tag_no_case};
 use nom::character::complete::digit1;
 use nom::combinator::{map, map_res, opt};
 use nom::multi::{separated_list0, separated_list1};
-use nom::sequence::{delimited, preceded, terminated, tuple};
+use nom::sequence::{delimited, pair, preceded, separated_pair, terminated, tuple};
 use nom_locate::LocatedSpan;
 use readyset_util::fmt::fmt_with;
 use serde::{Deserialize, Serialize};
@@ -16,16 +16,18 @@ use serde::{Deserialize, Serialize};
 use crate::column::{column_specification, Column, ColumnSpecification};
 use crate::common::{
     column_identifier_no_alias, debug_print, if_not_exists, parse_fallible, statement_terminator,
-    until_statement_terminator, ws_sep_comma, IndexType, ReferentialAction, TableKey,
+    until_statement_terminator, ws_sep_comma, ws_sep_equals, IndexType, ReferentialAction,
+    TableKey,
 };
 use crate::compound_select::{nested_compound_selection, CompoundSelectStatement};
 use crate::create_table_options::{table_options, CreateTableOption};
 use crate::expression::expression;
+use crate::literal::literal;
 use crate::order::{order_type, OrderType};
-use crate::select::{nested_selection, selection, SelectStatement};
+use crate::select::{nested_selection, SelectStatement};
 use crate::table::{relation, Relation};
 use crate::whitespace::{whitespace0, whitespace1};
-use crate::{Dialect, NomSqlResult, SqlIdentifier};
+use crate::{Dialect, Literal, NomSqlResult, SqlIdentifier};
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct CreateTableBody {
@@ -121,6 +123,18 @@ impl CreateTableStatement {
             })
     }
 
+    /// If the create statement contained a `DURABILITY` option, return it
+    pub fn get_durability(&self) -> Option<&str> {
+        self.options
+            .as_ref()
+            .ok()?
+            .iter()
+            .find_map(|opt| match opt {
+                CreateTableOption::Durability(s) => Some(s.as_str()),
+                _ => None,
+            })
+    }
+
     /// If the create statement contained AUTOINCREMENT, return it
     pub fn get_autoincrement(&self) -> Option<u64> {
         self.options
@@ -203,12 +217,48 @@ impl CacheInner {
     }
 }
 
-/// `CREATE CACHE [ALWAYS] [<name>] FROM ...`
+/// The keys to replay into a cache as soon as it's created, given in the `WITH WARMUP` clause of a
+/// [`CreateCacheStatement`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum CacheWarmup {
+    /// `WITH WARMUP (1, 2)` or `WITH WARMUP ((1, 'a'), (2, 'b'))` - an explicit list of keys, each
+    /// consisting of the values for the parameters of the cached query, in order
+    Keys(Vec<Vec<Literal>>),
+    /// `WITH WARMUP (SELECT ...)` - a query whose result rows are the keys
+    Query(Box<SelectStatement>),
+}
+
+impl CacheWarmup {
+    pub fn display(&self, dialect: Dialect) -> impl fmt::Display + Copy + '_ {
+        fmt_with(move |f| {
+            write!(f, "WITH WARMUP (")?;
+            match self {
+                Self::Keys(keys) => write!(
+                    f,
+                    "{}",
+                    keys.iter()
+                        .map(|key| match key.as_slice() {
+                            [value] => value.to_string(),
+                            values => format!("({})", values.iter().join(", ")),
+                        })
+                        .join(", ")
+                )?,
+                Self::Query(stmt) => write!(f, "{}", stmt.display(dialect))?,
+            }
+            write!(f, ")")
+        })
+    }
+}
+
+/// `CREATE CACHE [ALWAYS] [<name>] [WITH (<option> = <value>, ...)] FROM ... [WITH WARMUP (...)]`
 ///
 /// This is a non-standard ReadySet specific extension to SQL
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct CreateCacheStatement {
     pub name: Option<Relation>,
+    /// Options for how the cache should be created, given in the `WITH (...)` clause, as pairs of
+    /// option name and value. The names and values aren't validated by the parser.
+    pub options: Vec<(SqlIdentifier, Literal)>,
     /// The result of parsing the inner statement or query ID for the `CREATE CACHE` statement.
     ///
     /// If parsing succeeded, then this will be an `Ok` result with the definition of the
@@ -216,6 +266,8 @@ pub struct CreateCacheStatement {
     /// that could not be parsed.
     pub inner: Result<CacheInner, String>,
     pub always: bool,
+    /// The keys to replay into the cache once it's been created, if any
+    pub warmup: Option<CacheWarmup>,
 }
 
 impl CreateCacheStatement {
@@ -228,11 +280,25 @@ impl CreateCacheStatement {
             if let Some(name) = &self.name {
                 write!(f, "{} ", name.display(dialect))?;
             }
+            if !self.options.is_empty() {
+                write!(
+                    f,
+                    "WITH ({}) ",
+                    self.options
+                        .iter()
+                        .map(|(name, value)| format!("{name} = {value}"))
+                        .join(", ")
+                )?;
+            }
             write!(f, "FROM ")?;
             match &self.inner {
-                Ok(inner) => write!(f, "{}", inner.display(dialect)),
-                Err(unparsed) => write!(f, "{unparsed}"),
+                Ok(inner) => write!(f, "{}", inner.display(dialect))?,
+                Err(unparsed) => write!(f, "{unparsed}")?,
+            }
+            if let Some(warmup) = &self.warmup {
+                write!(f, " {}", warmup.display(dialect))?;
             }
+            Ok(())
         })
     }
 }
@@ -285,6 +351,7 @@ pub fn key_specification(
         let (i, table_key) = alt((
             check_constraint(dialect),
             full_text_key(dialect),
+            spatial_key(dialect),
             primary_key(dialect),
             unique(dialect),
             key_or_index(dialect),
@@ -323,6 +390,34 @@ fn full_text_key(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResul
     }
 }
 
+fn spatial_key(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], TableKey> {
+    move |i| {
+        debug_print("before spatial_key", &i);
+        let (remaining_input, (_, _, _, _, index_name, _, columns)) = tuple((
+            tag_no_case("spatial"),
+            whitespace1,
+            alt((tag_no_case("key"), tag_no_case("index"))),
+            whitespace1,
+            opt(dialect.identifier()),
+            whitespace0,
+            delimited(
+                tag("("),
+                delimited(whitespace0, index_col_list(dialect), whitespace0),
+                tag(")"),
+            ),
+        ))(i)?;
+
+        debug_print("after spatial_key", &remaining_input);
+        Ok((
+            remaining_input,
+            TableKey::SpatialKey {
+                index_name,
+                columns,
+            },
+        ))
+    }
+}
+
 fn primary_key(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], TableKey> {
     move |i| {
         debug_print("before primary_key", &i);
@@ -374,6 +469,21 @@ fn referential_action(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], ReferentialA
     ))(i)
 }
 
+/// Parses `ON <event> <action>`, eg `ON DELETE CASCADE`
+fn referential_trigger(
+    event: &'static str,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], ReferentialAction> {
+    move |i| {
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag_no_case("on")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case(event)(i)?;
+        let (i, _) = whitespace1(i)?;
+
+        referential_action(i)
+    }
+}
+
 fn constraint_identifier(
     dialect: Dialect,
 ) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Option<SqlIdentifier>> {
@@ -429,27 +539,20 @@ fn foreign_key(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<
             separated_list0(ws_sep_comma, column_identifier_no_alias(dialect))(i)?;
         let (i, _) = tag(")")(i)?;
 
-        // ON DELETE
-        let (i, on_delete) = opt(move |i| {
-            let (i, _) = whitespace0(i)?;
-            let (i, _) = tag_no_case("on")(i)?;
-            let (i, _) = whitespace1(i)?;
-            let (i, _) = tag_no_case("delete")(i)?;
-            let (i, _) = whitespace1(i)?;
-
-            referential_action(i)
-        })(i)?;
-
-        // ON UPDATE
-        let (i, on_update) = opt(move |i| {
-            let (i, _) = whitespace0(i)?;
-            let (i, _) = tag_no_case("on")(i)?;
-            let (i, _) = whitespace1(i)?;
-            let (i, _) = tag_no_case("update")(i)?;
-            let (i, _) = whitespace1(i)?;
-
-            referential_action(i)
-        })(i)?;
+        // ON DELETE and ON UPDATE, in either order
+        let (i, (on_delete, on_update)) = alt((
+            map(
+                pair(
+                    referential_trigger("update"),
+                    opt(referential_trigger("delete")),
+                ),
+                |(on_update, on_delete)| (on_delete, Some(on_update)),
+            ),
+            pair(
+                opt(referential_trigger("delete")),
+                opt(referential_trigger("update")),
+            ),
+        ))(i)?;
         debug_print("after foreign_key", &i);
 
         Ok((
@@ -762,12 +865,74 @@ pub fn cached_query_inner(
 ) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], CacheInner> {
     move |i| {
         alt((
-            map(map(selection(dialect), Box::new), CacheInner::from),
+            map(map(nested_selection(dialect), Box::new), CacheInner::from),
             map(dialect.identifier(), CacheInner::from),
         ))(i)
     }
 }
 
+/// Parse a single key in a [`CacheWarmup::Keys`] list, which is either a literal or a
+/// parenthesized list of literals
+fn cache_warmup_key(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Vec<Literal>> {
+    move |i| {
+        alt((
+            map(literal(dialect), |l| vec![l]),
+            delimited(
+                terminated(tag("("), whitespace0),
+                separated_list1(ws_sep_comma, literal(dialect)),
+                preceded(whitespace0, tag(")")),
+            ),
+        ))(i)
+    }
+}
+
+/// Parse the `WITH WARMUP (...)` clause of a [`CreateCacheStatement`]
+fn cache_warmup(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], CacheWarmup> {
+    move |i| {
+        let (i, _) = tag_no_case("with")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("warmup")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag("(")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, warmup) = alt((
+            map(nested_selection(dialect), |stmt| {
+                CacheWarmup::Query(Box::new(stmt))
+            }),
+            map(
+                separated_list1(ws_sep_comma, cache_warmup_key(dialect)),
+                CacheWarmup::Keys,
+            ),
+        ))(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag(")")(i)?;
+        Ok((i, warmup))
+    }
+}
+
+/// Parse the `WITH (<option> = <value>, ...)` clause of a [`CreateCacheStatement`]
+fn cache_options(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Vec<(SqlIdentifier, Literal)>> {
+    move |i| {
+        let (i, _) = tag_no_case("with")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag("(")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, options) = separated_list1(
+            ws_sep_comma,
+            separated_pair(dialect.identifier(), ws_sep_equals, literal(dialect)),
+        )(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag(")")(i)?;
+        Ok((i, options))
+    }
+}
+
 /// Parse a [`CreateCacheStatement`]
 pub fn create_cached_query(
     dialect: Dialect,
@@ -779,16 +944,31 @@ pub fn create_cached_query(
         let (i, _) = whitespace1(i)?;
         let (i, always) = opt(terminated(tag_no_case("always"), whitespace1))(i)?;
         let (i, name) = opt(terminated(relation(dialect), whitespace1))(i)?;
+        let (i, options) = opt(terminated(cache_options(dialect), whitespace1))(i)?;
         let (i, _) = tag_no_case("from")(i)?;
         let (i, _) = whitespace1(i)?;
-        let (i, inner) =
-            parse_fallible(cached_query_inner(dialect), until_statement_terminator)(i)?;
+        let (i, inner) = parse_fallible(
+            terminated(
+                pair(
+                    cached_query_inner(dialect),
+                    opt(preceded(whitespace0, cache_warmup(dialect))),
+                ),
+                statement_terminator,
+            ),
+            until_statement_terminator,
+        )(i)?;
+        let (inner, warmup) = match inner {
+            Ok((inner, warmup)) => (Ok(inner), warmup),
+            Err(unparsed) => (Err(unparsed), None),
+        };
         Ok((
             i,
             CreateCacheStatement {
                 name,
+                options: options.unwrap_or_default(),
                 inner,
                 always: always.is_some(),
+                warmup,
             },
         ))
     }
@@ -1585,6 +1765,89 @@ mod tests {
             );
         }
 
+        #[test]
+        fn create_cached_query_with_warmup_keys() {
+            let res = test_parse!(
+                create_cached_query(Dialect::MySQL),
+                b"CREATE CACHE foo FROM SELECT id FROM users WHERE name = ? AND age = ? \
+                  WITH WARMUP (('a', 1), ('b', 2))"
+            );
+            assert!(matches!(res.inner, Ok(CacheInner::Statement(_))));
+            assert_eq!(
+                res.warmup,
+                Some(CacheWarmup::Keys(vec![
+                    vec![Literal::from("a"), Literal::from(1u32)],
+                    vec![Literal::from("b"), Literal::from(2u32)],
+                ]))
+            );
+            assert_eq!(
+                res.display(Dialect::MySQL).to_string(),
+                "CREATE CACHE `foo` FROM SELECT `id` FROM `users` \
+                 WHERE ((`name` = ?) AND (`age` = ?)) WITH WARMUP (('a', 1), ('b', 2))"
+            );
+        }
+
+        #[test]
+        fn create_cached_query_with_warmup_query() {
+            let res = test_parse!(
+                create_cached_query(Dialect::MySQL),
+                b"CREATE CACHE FROM q_0123456789ABCDEF WITH WARMUP (SELECT name FROM top_users)"
+            );
+            assert!(matches!(res.inner, Ok(CacheInner::Id(_))));
+            let warmup = match res.warmup {
+                Some(CacheWarmup::Query(stmt)) => stmt,
+                _ => panic!(),
+            };
+            assert_eq!(
+                warmup.tables,
+                vec![TableExpr::from(Relation::from("top_users"))]
+            );
+        }
+
+        #[test]
+        fn create_cached_query_with_single_column_warmup_keys() {
+            let res = test_parse!(
+                create_cached_query(Dialect::MySQL),
+                b"CREATE CACHE FROM SELECT id FROM users WHERE name = ? WITH WARMUP ('a', 'b')"
+            );
+            assert_eq!(
+                res.warmup,
+                Some(CacheWarmup::Keys(vec![
+                    vec![Literal::from("a")],
+                    vec![Literal::from("b")]
+                ]))
+            );
+        }
+
+        #[test]
+        fn create_cached_query_with_options() {
+            let res = test_parse!(
+                create_cached_query(Dialect::MySQL),
+                b"CREATE CACHE foo WITH (full_materialization = true, replicas = 2, index_type = 'btree') \
+                  FROM SELECT id FROM users WHERE name = ?"
+            );
+            assert_eq!(res.name, Some("foo".into()));
+            assert_eq!(
+                res.options,
+                vec![
+                    ("full_materialization".into(), Literal::Boolean(true)),
+                    ("replicas".into(), Literal::from(2u32)),
+                    ("index_type".into(), Literal::from("btree")),
+                ]
+            );
+            assert!(matches!(res.inner, Ok(CacheInner::Statement(_))));
+
+            let res = test_parse!(
+                create_cached_query(Dialect::MySQL),
+                b"CREATE CACHE WITH (replicas = 2) FROM SELECT id FROM users WHERE name = ?"
+            );
+            assert_eq!(res.name, None);
+            assert_eq!(
+                res.display(Dialect::MySQL).to_string(),
+                "CREATE CACHE WITH (replicas = 2) FROM SELECT `id` FROM `users` WHERE (`name` = ?)"
+            );
+        }
+
         #[test]
         fn lobsters_indexes() {
             let qstring = "CREATE TABLE `comments` (
@@ -1806,6 +2069,45 @@ mod tests {
                   GROUP BY emp_no"
             );
         }
+
+        #[test]
+        fn spatial_columns() {
+            let res = test_parse!(
+                create_table(Dialect::MySQL),
+                b"CREATE TABLE places (
+                    id          INT             NOT NULL PRIMARY KEY,
+                    location    POINT           NOT NULL SRID 4326,
+                    area        GEOMETRY,
+                    SPATIAL KEY location_idx (location)
+                )"
+            );
+            let body = res.body.as_ref().unwrap();
+            assert_eq!(
+                body.fields[1].sql_type,
+                SqlType::Spatial(crate::SpatialType::Point)
+            );
+            assert_eq!(
+                body.fields[1].constraints,
+                vec![ColumnConstraint::NotNull, ColumnConstraint::Srid(4326)]
+            );
+            assert_eq!(
+                body.fields[2].sql_type,
+                SqlType::Spatial(crate::SpatialType::Geometry)
+            );
+            assert_eq!(
+                body.keys,
+                Some(vec![TableKey::SpatialKey {
+                    index_name: Some("location_idx".into()),
+                    columns: vec![Column::from("location")],
+                }])
+            );
+
+            let rt = res.display(Dialect::MySQL).to_string();
+            assert_eq!(
+                test_parse!(create_table(Dialect::MySQL), rt.as_bytes()),
+                res
+            );
+        }
     }
 
     mod postgres {
@@ -1814,6 +2116,28 @@ mod tests {
         use crate::table::Relation;
         use crate::{to_nom_result, ColumnConstraint, Literal, SqlType};
 
+        #[test]
+        fn foreign_key_on_update_before_on_delete() {
+            // As formatted by `pg_get_constraintdef`
+            let res = test_parse!(
+                key_specification(Dialect::PostgreSQL),
+                b"CONSTRAINT c_p_fkey FOREIGN KEY (p_id) REFERENCES p(id) \
+                  ON UPDATE RESTRICT ON DELETE CASCADE"
+            );
+            assert_eq!(
+                res,
+                TableKey::ForeignKey {
+                    constraint_name: Some("c_p_fkey".into()),
+                    index_name: None,
+                    columns: vec!["p_id".into()],
+                    target_table: "p".into(),
+                    target_columns: vec!["id".into()],
+                    on_delete: Some(ReferentialAction::Cascade),
+                    on_update: Some(ReferentialAction::Restrict),
+                }
+            );
+        }
+
         #[test]
         fn if_not_exists() {
             let res = test_parse!(
@@ -51,6 +51,9 @@ pub enum CreateTableOption {
     Charset(CharsetName),
     Collate(CollationName),
     Comment(String),
+    /// ReadySet-specific option overriding the durability mode of the table's base table state,
+    /// given as `DURABILITY = 'memory'`
+    Durability(String),
     /// Any currently uncotegorized option falls here
     /// TODO: implement other options
     Other,
@@ -66,6 +69,7 @@ impl fmt::Display for CreateTableOption {
             CreateTableOption::Charset(c) => write!(f, "DEFAULT CHARSET={}", c),
             CreateTableOption::Collate(c) => write!(f, "COLLATE={}", c),
             CreateTableOption::Comment(c) => write!(f, "COMMENT='{}'", c),
+            CreateTableOption::Durability(d) => write!(f, "DURABILITY='{}'", d),
             CreateTableOption::Other => Ok(()),
         }
     }
@@ -96,6 +100,7 @@ fn create_option(
             create_option_default_charset(dialect),
             create_option_collate(dialect),
             create_option_comment(dialect),
+            create_option_durability(dialect),
             map(create_option_max_rows, |_| CreateTableOption::Other),
             map(create_option_avg_row_length, |_| CreateTableOption::Other),
             map(create_option_row_format, |_| CreateTableOption::Other),
@@ -304,6 +309,20 @@ fn create_option_comment(
     }
 }
 
+fn create_option_durability(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], CreateTableOption> {
+    move |i| {
+        map(
+            map_res(
+                create_option_equals_pair(tag_no_case("durability"), dialect.string_literal()),
+                String::from_utf8,
+            ),
+            CreateTableOption::Durability,
+        )(i)
+    }
+}
+
 fn create_option_max_rows(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Literal> {
     create_option_equals_pair(tag_no_case("max_rows"), integer_literal)(i)
 }
@@ -473,4 +492,15 @@ mod tests {
             vec![CreateTableOption::Comment("foobar".to_string())],
         );
     }
+
+    #[test]
+    fn create_table_option_durability() {
+        should_parse_all(
+            "ENGINE=InnoDB DURABILITY='memory'",
+            vec![
+                CreateTableOption::Engine(Some("InnoDB".to_string())),
+                CreateTableOption::Durability("memory".to_string()),
+            ],
+        );
+    }
 }
@@ -27,7 +27,7 @@ fn if_exists(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], bool> {
     )(i)
 }
 
-fn restrict_cascade(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], (bool, bool)> {
+pub(crate) fn restrict_cascade(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], (bool, bool)> {
     let (i, restrict) = opt(preceded(whitespace1, tag_no_case("restrict")))(i)?;
     let (i, cascade) = opt(preceded(whitespace1, tag_no_case("cascade")))(i)?;
     Ok((i, (restrict.is_some(), cascade.is_some())))
@@ -80,11 +80,18 @@ pub fn drop_table(
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct DropCacheStatement {
     pub name: Relation,
+    pub if_exists: bool,
 }
 
 impl DropCacheStatement {
     pub fn display(&self, dialect: Dialect) -> impl Display + Copy + '_ {
-        fmt_with(move |f| write!(f, "DROP CACHE {}", self.name.display(dialect)))
+        fmt_with(move |f| {
+            write!(f, "DROP CACHE ")?;
+            if self.if_exists {
+                write!(f, "IF EXISTS ")?;
+            }
+            write!(f, "{}", self.name.display(dialect))
+        })
     }
 }
 
@@ -96,9 +103,10 @@ pub fn drop_cached_query(
         let (i, _) = whitespace1(i)?;
         let (i, _) = tag_no_case("cache")(i)?;
         let (i, _) = whitespace1(i)?;
+        let (i, if_exists) = if_exists(i)?;
         let (i, name) = relation(dialect)(i)?;
         let (i, _) = statement_terminator(i)?;
-        Ok((i, DropCacheStatement { name }))
+        Ok((i, DropCacheStatement { name, if_exists }))
     }
 }
 
@@ -204,16 +212,36 @@ mod tests {
     fn parse_drop_cached_query() {
         let res = test_parse!(drop_cached_query(Dialect::MySQL), b"DROP CACHE test");
         assert_eq!(res.name, "test".into());
+        assert!(!res.if_exists);
+    }
+
+    #[test]
+    fn parse_drop_cached_query_if_exists() {
+        let res = test_parse!(
+            drop_cached_query(Dialect::MySQL),
+            b"DROP CACHE IF EXISTS test"
+        );
+        assert_eq!(res.name, "test".into());
+        assert!(res.if_exists);
     }
 
     #[test]
     fn format_drop_cached_query() {
         let res = DropCacheStatement {
             name: "test".into(),
+            if_exists: false,
         }
         .display(Dialect::MySQL)
         .to_string();
         assert_eq!(res, "DROP CACHE `test`");
+
+        let res = DropCacheStatement {
+            name: "test".into(),
+            if_exists: true,
+        }
+        .display(Dialect::MySQL)
+        .to_string();
+        assert_eq!(res, "DROP CACHE IF EXISTS `test`");
     }
 
     #[test]
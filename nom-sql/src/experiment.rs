@@ -0,0 +1,224 @@
+use std::fmt;
+
+use nom::branch::alt;
+use nom::bytes::complete::tag_no_case;
+use nom::character::complete::{digit1, u8 as nom_u8};
+use nom::combinator::{map, map_parser, opt, verify};
+use nom::sequence::{preceded, tuple};
+use nom_locate::LocatedSpan;
+use readyset_util::fmt::fmt_with;
+use serde::{Deserialize, Serialize};
+
+use crate::common::statement_terminator;
+use crate::select::{selection, SelectStatement};
+use crate::table::{relation, Relation};
+use crate::whitespace::whitespace1;
+use crate::{Dialect, NomSqlResult};
+
+/// The percentage of reads routed to the candidate of a [`CreateExperimentStatement`] if no
+/// `PERCENT` is given
+pub const DEFAULT_EXPERIMENT_PERCENT: u8 = 10;
+
+/// `CREATE EXPERIMENT FOR <cache> [PERCENT <n>] [COMPARE RESULTS] FROM <select>`
+///
+/// Creates a candidate cache for the given SELECT statement alongside an existing cache, and
+/// routes `percent` percent of the reads of the existing cache to the candidate instead. If
+/// `COMPARE RESULTS` is given, reads routed to the candidate are also executed against the
+/// existing cache, and any differences in the results are reported.
+///
+/// This is a non-standard ReadySet specific extension to SQL
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct CreateExperimentStatement {
+    /// The name of the existing cache
+    pub cache: Relation,
+    /// The percentage of reads of the existing cache to route to the candidate, between 0 and 100
+    pub percent: u8,
+    pub compare_results: bool,
+    /// The query for the candidate cache
+    pub statement: Box<SelectStatement>,
+}
+
+/// One of the ReadySet-specific statements for managing cache experiments
+///
+/// This is a non-standard ReadySet specific extension to SQL
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum ExperimentStatement {
+    /// `CREATE EXPERIMENT FOR ...`
+    Create(CreateExperimentStatement),
+    /// `PROMOTE EXPERIMENT FOR <cache>`, which replaces the existing cache with the candidate for
+    /// all reads and ends the experiment
+    Promote { cache: Relation },
+    /// `DROP EXPERIMENT FOR <cache>`, which ends the experiment and removes the candidate cache,
+    /// leaving the existing cache in place
+    Drop { cache: Relation },
+}
+
+impl ExperimentStatement {
+    /// Returns the name of the existing cache the experiment is being run against
+    pub fn cache(&self) -> &Relation {
+        match self {
+            Self::Create(create) => &create.cache,
+            Self::Promote { cache } | Self::Drop { cache } => cache,
+        }
+    }
+
+    pub fn display(&self, dialect: Dialect) -> impl fmt::Display + Copy + '_ {
+        fmt_with(move |f| match self {
+            Self::Create(create) => {
+                write!(
+                    f,
+                    "CREATE EXPERIMENT FOR {} PERCENT {} ",
+                    create.cache.display(dialect),
+                    create.percent
+                )?;
+                if create.compare_results {
+                    write!(f, "COMPARE RESULTS ")?;
+                }
+                write!(f, "FROM {}", create.statement.display(dialect))
+            }
+            Self::Promote { cache } => {
+                write!(f, "PROMOTE EXPERIMENT FOR {}", cache.display(dialect))
+            }
+            Self::Drop { cache } => write!(f, "DROP EXPERIMENT FOR {}", cache.display(dialect)),
+        })
+    }
+}
+
+fn experiment_for(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Relation> {
+    move |i| {
+        let (i, _) = tag_no_case("experiment")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("for")(i)?;
+        let (i, _) = whitespace1(i)?;
+        relation(dialect)(i)
+    }
+}
+
+fn create_experiment(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], CreateExperimentStatement> {
+    move |i| {
+        let (i, _) = tag_no_case("create")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, cache) = experiment_for(dialect)(i)?;
+        let (i, percent) = opt(preceded(
+            tuple((whitespace1, tag_no_case("percent"), whitespace1)),
+            verify(map_parser(digit1, nom_u8), |p| *p <= 100),
+        ))(i)?;
+        let (i, compare_results) = opt(tuple((
+            whitespace1,
+            tag_no_case("compare"),
+            whitespace1,
+            tag_no_case("results"),
+        )))(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("from")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, statement) = selection(dialect)(i)?;
+        Ok((
+            i,
+            CreateExperimentStatement {
+                cache,
+                percent: percent.unwrap_or(DEFAULT_EXPERIMENT_PERCENT),
+                compare_results: compare_results.is_some(),
+                statement: Box::new(statement),
+            },
+        ))
+    }
+}
+
+/// Parse an [`ExperimentStatement`]
+pub fn experiment(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], ExperimentStatement> {
+    move |i| {
+        let (i, statement) = alt((
+            map(create_experiment(dialect), ExperimentStatement::Create),
+            map(
+                preceded(
+                    tuple((tag_no_case("promote"), whitespace1)),
+                    experiment_for(dialect),
+                ),
+                |cache| ExperimentStatement::Promote { cache },
+            ),
+            map(
+                preceded(
+                    tuple((tag_no_case("drop"), whitespace1)),
+                    experiment_for(dialect),
+                ),
+                |cache| ExperimentStatement::Drop { cache },
+            ),
+        ))(i)?;
+        let (i, _) = statement_terminator(i)?;
+        Ok((i, statement))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_experiment_all_options() {
+        let res = test_parse!(
+            experiment(Dialect::MySQL),
+            b"CREATE EXPERIMENT FOR q1 PERCENT 25 COMPARE RESULTS FROM SELECT id FROM t WHERE id = ?;"
+        );
+        let create = match &res {
+            ExperimentStatement::Create(create) => create,
+            _ => panic!("Expected CREATE EXPERIMENT, got {res:?}"),
+        };
+        assert_eq!(create.cache, Relation::from("q1"));
+        assert_eq!(create.percent, 25);
+        assert!(create.compare_results);
+        assert_eq!(
+            res.display(Dialect::MySQL).to_string(),
+            "CREATE EXPERIMENT FOR `q1` PERCENT 25 COMPARE RESULTS FROM SELECT `id` FROM `t` \
+             WHERE (`id` = ?)"
+        );
+    }
+
+    #[test]
+    fn create_experiment_defaults() {
+        let res = test_parse!(
+            experiment(Dialect::PostgreSQL),
+            b"create experiment for \"s\".\"q1\" from select id from t"
+        );
+        let create = match res {
+            ExperimentStatement::Create(create) => create,
+            _ => panic!("Expected CREATE EXPERIMENT"),
+        };
+        assert_eq!(
+            create.cache,
+            Relation {
+                schema: Some("s".into()),
+                name: "q1".into()
+            }
+        );
+        assert_eq!(create.percent, DEFAULT_EXPERIMENT_PERCENT);
+        assert!(!create.compare_results);
+    }
+
+    #[test]
+    fn create_experiment_invalid_percent() {
+        assert!(experiment(Dialect::MySQL)(LocatedSpan::new(
+            b"CREATE EXPERIMENT FOR q1 PERCENT 101 FROM SELECT id FROM t"
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn promote_and_drop_experiment() {
+        let res = test_parse!(experiment(Dialect::MySQL), b"PROMOTE EXPERIMENT FOR q1");
+        assert_eq!(res, ExperimentStatement::Promote { cache: "q1".into() });
+        assert_eq!(
+            res.display(Dialect::MySQL).to_string(),
+            "PROMOTE EXPERIMENT FOR `q1`"
+        );
+
+        let res = test_parse!(experiment(Dialect::MySQL), b"drop experiment for q1;");
+        assert_eq!(res, ExperimentStatement::Drop { cache: "q1".into() });
+    }
+}
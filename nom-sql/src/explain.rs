@@ -1,15 +1,17 @@
-use std::fmt::{self, Display};
+use std::fmt;
 
 use nom::branch::alt;
 use nom::bytes::complete::tag_no_case;
 use nom::combinator::{map, opt};
 use nom::sequence::{terminated, tuple};
 use nom_locate::LocatedSpan;
+use readyset_util::fmt::fmt_with;
 use serde::{Deserialize, Serialize};
 
 use crate::common::statement_terminator;
+use crate::select::nested_selection;
 use crate::whitespace::whitespace1;
-use crate::NomSqlResult;
+use crate::{Dialect, NomSqlResult, SelectStatement};
 
 /// EXPLAIN statements
 ///
@@ -20,20 +22,26 @@ pub enum ExplainStatement {
     Graphviz { simplified: bool },
     /// Provides metadata about the last statement that was executed.
     LastStatement,
+    /// Describes how ReadySet would execute the given query: whether it is cached, and if so the
+    /// chain of dataflow nodes and indexes it reads from
+    Plan(Box<SelectStatement>),
 }
 
-impl Display for ExplainStatement {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "EXPLAIN ")?;
-        match self {
-            ExplainStatement::Graphviz { simplified } => {
-                if *simplified {
-                    write!(f, "SIMPLIFIED ")?;
+impl ExplainStatement {
+    pub fn display(&self, dialect: Dialect) -> impl fmt::Display + Copy + '_ {
+        fmt_with(move |f| {
+            write!(f, "EXPLAIN ")?;
+            match self {
+                ExplainStatement::Graphviz { simplified } => {
+                    if *simplified {
+                        write!(f, "SIMPLIFIED ")?;
+                    }
+                    write!(f, "GRAPHVIZ;")
                 }
-                write!(f, "GRAPHVIZ;")
+                ExplainStatement::LastStatement => write!(f, "LAST STATEMENT;"),
+                ExplainStatement::Plan(stmt) => write!(f, "{}", stmt.display(dialect)),
             }
-            ExplainStatement::LastStatement => write!(f, "LAST STATEMENT;"),
-        }
+        })
     }
 }
 
@@ -48,18 +56,25 @@ fn explain_graphviz(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], ExplainStateme
     ))
 }
 
-pub(crate) fn explain_statement(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], ExplainStatement> {
-    let (i, _) = tag_no_case("explain")(i)?;
-    let (i, _) = whitespace1(i)?;
-    let (i, stmt) = alt((
-        explain_graphviz,
-        map(
-            tuple((tag_no_case("last"), whitespace1, tag_no_case("statement"))),
-            |_| ExplainStatement::LastStatement,
-        ),
-    ))(i)?;
-    let (i, _) = statement_terminator(i)?;
-    Ok((i, stmt))
+pub(crate) fn explain_statement(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], ExplainStatement> {
+    move |i| {
+        let (i, _) = tag_no_case("explain")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, stmt) = alt((
+            explain_graphviz,
+            map(
+                tuple((tag_no_case("last"), whitespace1, tag_no_case("statement"))),
+                |_| ExplainStatement::LastStatement,
+            ),
+            map(nested_selection(dialect), |stmt| {
+                ExplainStatement::Plan(Box::new(stmt))
+            }),
+        ))(i)?;
+        let (i, _) = statement_terminator(i)?;
+        Ok((i, stmt))
+    }
 }
 
 #[cfg(test)]
@@ -69,7 +84,7 @@ mod tests {
     #[test]
     fn explain_graphviz() {
         assert_eq!(
-            explain_statement(LocatedSpan::new(b"explain graphviz;"))
+            explain_statement(Dialect::MySQL)(LocatedSpan::new(b"explain graphviz;"))
                 .unwrap()
                 .1,
             ExplainStatement::Graphviz { simplified: false }
@@ -79,10 +94,30 @@ mod tests {
     #[test]
     fn explain_last_statement() {
         assert_eq!(
-            explain_statement(LocatedSpan::new(b"explain last statement;"))
+            explain_statement(Dialect::MySQL)(LocatedSpan::new(b"explain last statement;"))
                 .unwrap()
                 .1,
             ExplainStatement::LastStatement
         );
     }
+
+    #[test]
+    fn explain_plan() {
+        for dialect in [Dialect::MySQL, Dialect::PostgreSQL] {
+            let res = explain_statement(dialect)(LocatedSpan::new(
+                b"EXPLAIN SELECT id FROM users WHERE name = ?",
+            ))
+            .unwrap()
+            .1;
+            let stmt = match &res {
+                ExplainStatement::Plan(stmt) => stmt,
+                _ => panic!("Expected ExplainStatement::Plan, got {res:?}"),
+            };
+            assert_eq!(stmt.tables.len(), 1);
+            assert_eq!(
+                res.display(dialect).to_string(),
+                format!("EXPLAIN {}", stmt.display(dialect))
+            );
+        }
+    }
 }
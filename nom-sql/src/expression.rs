@@ -23,7 +23,10 @@ use crate::select::nested_selection;
 use crate::set::{variable_scope_prefix, Variable};
 use crate::sql_type::{mysql_int_cast_targets, type_identifier};
 use crate::whitespace::{whitespace0, whitespace1};
-use crate::{Column, Dialect, Literal, NomSqlResult, SelectStatement, SqlIdentifier, SqlType};
+use crate::{
+    Column, Dialect, FieldReference, Literal, NomSqlResult, OrderClause, SelectStatement,
+    SqlIdentifier, SqlType,
+};
 
 /// Function call expressions
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Hash, Serialize, Deserialize)]
@@ -52,6 +55,49 @@ pub enum FunctionExpr {
         separator: Option<String>,
     },
 
+    /// `MEDIAN` aggregation, equivalent to `PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY expr)`
+    Median(Box<Expr>),
+
+    /// `PERCENTILE_CONT` aggregation, which computes a percentile of the values of `expr`,
+    /// interpolating between adjacent values if needed.
+    ///
+    /// The supported syntax is:
+    ///
+    /// `PERCENTILE_CONT(percentile) WITHIN GROUP (ORDER BY expr)`
+    PercentileCont {
+        percentile: Literal,
+        expr: Box<Expr>,
+    },
+
+    /// `APPROX_COUNT_DISTINCT` aggregation, which estimates the number of distinct values of
+    /// `expr` using a HyperLogLog sketch
+    ApproxCountDistinct(Box<Expr>),
+
+    /// `APPROX_PERCENTILE` aggregation, which estimates a percentile of the values of `expr` using
+    /// a t-digest sketch.
+    ///
+    /// The supported syntax is:
+    ///
+    /// `APPROX_PERCENTILE(expr, percentile)`
+    ApproxPercentile {
+        expr: Box<Expr>,
+        percentile: Literal,
+    },
+
+    /// MySQL's `MATCH ... AGAINST` full-text search function.
+    ///
+    /// The supported syntax is:
+    ///
+    /// `MATCH (col, ...) AGAINST (search_string [modifier])`
+    ///
+    /// Full-text search can't be cached, so this is only parsed so that queries using it can be
+    /// proxied to the upstream database.
+    Match {
+        columns: Vec<Column>,
+        against: Literal,
+        modifier: Option<MatchSearchModifier>,
+    },
+
     /// The SQL `SUBSTRING`/`SUBSTR` function.
     ///
     /// The supported syntax is one of:
@@ -67,11 +113,94 @@ pub enum FunctionExpr {
         len: Option<Box<Expr>>,
     },
 
+    /// The SQL `EXTRACT` function.
+    ///
+    /// The supported syntax is:
+    ///
+    /// `EXTRACT(unit FROM expr)`
+    Extract {
+        field: IntervalUnit,
+        expr: Box<Expr>,
+    },
+
+    /// The SQL `TRIM` function.
+    ///
+    /// The supported syntax is:
+    ///
+    /// `TRIM([{BOTH | LEADING | TRAILING}] [remove] FROM string)`
+    /// `TRIM(string)`
+    Trim {
+        side: TrimSide,
+        remove: Option<Box<Expr>>,
+        string: Box<Expr>,
+    },
+
     /// Generic function call expression
     Call {
         name: SqlIdentifier,
         arguments: Vec<Expr>,
     },
+
+    /// Call to a ranking window function (or `COUNT(*)`), with an `OVER` clause.
+    ///
+    /// The supported syntax is:
+    ///
+    /// `<function>() OVER ([PARTITION BY expr, ...] [ORDER BY field [ASC|DESC], ...])`
+    Window {
+        function: WindowFunction,
+        partition_by: Vec<Expr>,
+        order: Option<OrderClause>,
+    },
+}
+
+/// The search modifiers for a [`FunctionExpr::Match`] full-text search
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum MatchSearchModifier {
+    /// `IN NATURAL LANGUAGE MODE`
+    NaturalLanguage,
+    /// `IN NATURAL LANGUAGE MODE WITH QUERY EXPANSION`
+    NaturalLanguageWithQueryExpansion,
+    /// `IN BOOLEAN MODE`
+    Boolean,
+    /// `WITH QUERY EXPANSION`
+    QueryExpansion,
+}
+
+impl Display for MatchSearchModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NaturalLanguage => write!(f, "IN NATURAL LANGUAGE MODE"),
+            Self::NaturalLanguageWithQueryExpansion => {
+                write!(f, "IN NATURAL LANGUAGE MODE WITH QUERY EXPANSION")
+            }
+            Self::Boolean => write!(f, "IN BOOLEAN MODE"),
+            Self::QueryExpansion => write!(f, "WITH QUERY EXPANSION"),
+        }
+    }
+}
+
+/// Window functions, which can be called with an `OVER` clause
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum WindowFunction {
+    /// `ROW_NUMBER()`
+    RowNumber,
+    /// `RANK()`
+    Rank,
+    /// `DENSE_RANK()`
+    DenseRank,
+    /// `COUNT(*)`
+    CountStar,
+}
+
+impl Display for WindowFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowFunction::RowNumber => write!(f, "row_number"),
+            WindowFunction::Rank => write!(f, "rank"),
+            WindowFunction::DenseRank => write!(f, "dense_rank"),
+            WindowFunction::CountStar => write!(f, "count"),
+        }
+    }
 }
 
 impl FunctionExpr {
@@ -85,16 +214,37 @@ impl FunctionExpr {
             | FunctionExpr::Sum { expr: arg, .. }
             | FunctionExpr::Max(arg)
             | FunctionExpr::Min(arg)
-            | FunctionExpr::GroupConcat { expr: arg, .. } => {
+            | FunctionExpr::GroupConcat { expr: arg, .. }
+            | FunctionExpr::Median(arg)
+            | FunctionExpr::PercentileCont { expr: arg, .. }
+            | FunctionExpr::ApproxCountDistinct(arg)
+            | FunctionExpr::ApproxPercentile { expr: arg, .. }
+            | FunctionExpr::Extract { expr: arg, .. } => {
                 concrete_iter!(iter::once(arg.as_ref()))
             }
-            FunctionExpr::CountStar => concrete_iter!(iter::empty()),
+            FunctionExpr::CountStar | FunctionExpr::Match { .. } => concrete_iter!(iter::empty()),
+            FunctionExpr::Window {
+                partition_by,
+                order,
+                ..
+            } => concrete_iter!(partition_by.iter().chain(order.iter().flat_map(|order| {
+                order.order_by.iter().filter_map(|(field, _)| match field {
+                    FieldReference::Expr(expr) => Some(expr),
+                    FieldReference::Numeric(_) => None,
+                })
+            }))),
             FunctionExpr::Call { arguments, .. } => concrete_iter!(arguments),
             FunctionExpr::Substring { string, pos, len } => {
                 concrete_iter!(iter::once(string.as_ref())
                     .chain(pos.iter().map(|p| p.as_ref()))
                     .chain(len.iter().map(|p| p.as_ref())))
             }
+            FunctionExpr::Trim { remove, string, .. } => {
+                concrete_iter!(remove
+                    .iter()
+                    .map(|r| r.as_ref())
+                    .chain(iter::once(string.as_ref())))
+            }
         }
     }
 }
@@ -131,6 +281,41 @@ impl FunctionExpr {
                 }
                 write!(f, ")")
             }
+            FunctionExpr::Median(expr) => write!(f, "median({})", expr.display(dialect)),
+            FunctionExpr::PercentileCont { percentile, expr } => write!(
+                f,
+                "percentile_cont({}) within group (order by {})",
+                percentile,
+                expr.display(dialect)
+            ),
+            FunctionExpr::ApproxCountDistinct(expr) => {
+                write!(f, "approx_count_distinct({})", expr.display(dialect))
+            }
+            FunctionExpr::ApproxPercentile { expr, percentile } => write!(
+                f,
+                "approx_percentile({}, {})",
+                expr.display(dialect),
+                percentile
+            ),
+            FunctionExpr::Extract { field, expr } => {
+                write!(f, "extract({} from {})", field, expr.display(dialect))
+            }
+            FunctionExpr::Match {
+                columns,
+                against,
+                modifier,
+            } => {
+                write!(
+                    f,
+                    "MATCH ({}) AGAINST ({}",
+                    columns.iter().map(|c| c.display(dialect)).join(", "),
+                    against
+                )?;
+                if let Some(modifier) = modifier {
+                    write!(f, " {}", modifier)?;
+                }
+                write!(f, ")")
+            }
             FunctionExpr::Call { name, arguments } => {
                 write!(
                     f,
@@ -152,6 +337,45 @@ impl FunctionExpr {
 
                 write!(f, ")")
             }
+            FunctionExpr::Trim {
+                side,
+                remove,
+                string,
+            } => {
+                write!(f, "trim(")?;
+                match (side, remove) {
+                    (TrimSide::Both, None) => {}
+                    (side, None) => write!(f, "{} from ", side)?,
+                    (side, Some(remove)) => {
+                        write!(f, "{} {} from ", side, remove.display(dialect))?
+                    }
+                }
+                write!(f, "{})", string.display(dialect))
+            }
+            FunctionExpr::Window {
+                function,
+                partition_by,
+                order,
+            } => {
+                match function {
+                    WindowFunction::CountStar => write!(f, "count(*) OVER (")?,
+                    _ => write!(f, "{}() OVER (", function)?,
+                }
+                if !partition_by.is_empty() {
+                    write!(
+                        f,
+                        "PARTITION BY {}",
+                        partition_by.iter().map(|e| e.display(dialect)).join(", ")
+                    )?;
+                    if order.is_some() {
+                        write!(f, " ")?;
+                    }
+                }
+                if let Some(order) = order {
+                    write!(f, "{}", order.display(dialect))?;
+                }
+                write!(f, ")")
+            }
         })
     }
 }
@@ -239,14 +463,14 @@ pub enum BinaryOperator {
     /// `->`
     ///
     /// This extracts JSON values as JSON:
-    /// - MySQL: `json -> jsonpath` to `json` (unimplemented)
+    /// - MySQL: `json -> jsonpath` to `json`
     /// - PostgreSQL: `json[b] -> {text,integer}` to `json[b]`
     Arrow1,
 
     /// `->>`
     ///
     /// This extracts JSON values and applies a transformation:
-    /// - MySQL: `json ->> jsonpath` to unquoted `text` (unimplemented)
+    /// - MySQL: `json ->> jsonpath` to unquoted `text`
     /// - PostgreSQL: `json[b] ->> {text,integer}` to `text`
     Arrow2,
 
@@ -271,6 +495,12 @@ pub enum BinaryOperator {
     /// Postgres-specific JSONB operator. Behaves like [`BinaryOperator::AtArrowRight`] with
     /// switched sides for the operands.
     AtArrowLeft,
+
+    /// `@@`
+    ///
+    /// Postgres-specific full-text search operator. Determines whether a `tsvector` matches a
+    /// `tsquery`.
+    AtAt,
 }
 
 impl BinaryOperator {
@@ -326,6 +556,7 @@ impl Display for BinaryOperator {
             Self::HashArrow2 => "#>>",
             Self::AtArrowRight => "@>",
             Self::AtArrowLeft => "<@",
+            Self::AtAt => "@@",
         };
         f.write_str(op)
     }
@@ -346,6 +577,59 @@ impl Display for UnaryOperator {
     }
 }
 
+/// Units of time, used both for `INTERVAL` expressions and for the fields which can be extracted
+/// from a date or time with `EXTRACT`
+#[derive(
+    Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Serialize, Deserialize, Arbitrary,
+)]
+pub enum IntervalUnit {
+    Microsecond,
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Display for IntervalUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntervalUnit::Microsecond => write!(f, "MICROSECOND"),
+            IntervalUnit::Second => write!(f, "SECOND"),
+            IntervalUnit::Minute => write!(f, "MINUTE"),
+            IntervalUnit::Hour => write!(f, "HOUR"),
+            IntervalUnit::Day => write!(f, "DAY"),
+            IntervalUnit::Week => write!(f, "WEEK"),
+            IntervalUnit::Month => write!(f, "MONTH"),
+            IntervalUnit::Quarter => write!(f, "QUARTER"),
+            IntervalUnit::Year => write!(f, "YEAR"),
+        }
+    }
+}
+
+/// Which end(s) of a string the SQL `TRIM` function removes characters from
+#[derive(
+    Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Serialize, Deserialize, Arbitrary,
+)]
+pub enum TrimSide {
+    Both,
+    Leading,
+    Trailing,
+}
+
+impl Display for TrimSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrimSide::Both => write!(f, "BOTH"),
+            TrimSide::Leading => write!(f, "LEADING"),
+            TrimSide::Trailing => write!(f, "TRAILING"),
+        }
+    }
+}
+
 /// Right-hand side of IN
 #[derive(Debug, PartialEq, Eq, PartialOrd, Hash, Clone, Serialize, Deserialize, From)]
 pub enum InValue {
@@ -481,6 +765,13 @@ pub enum Expr {
     /// `ARRAY[expr1, expr2, ...]`
     Array(Vec<Expr>),
 
+    /// `INTERVAL value unit`, which can be added to or subtracted from a date or time, either with
+    /// the `+` and `-` operators or with `DATE_ADD` and `DATE_SUB`
+    Interval {
+        value: Box<Expr>,
+        unit: IntervalUnit,
+    },
+
     /// A variable reference
     Variable(Variable),
 }
@@ -489,7 +780,7 @@ impl Expr {
     pub fn display(&self, dialect: Dialect) -> impl fmt::Display + Copy + '_ {
         fmt_with(move |f| match self {
             Expr::Call(fe) => write!(f, "{}", fe.display(dialect)),
-            Expr::Literal(l) => write!(f, "{}", l),
+            Expr::Literal(l) => write!(f, "{}", l.display(dialect)),
             Expr::Column(col) => write!(f, "{}", col.display(dialect)),
             Expr::CaseWhen {
                 branches,
@@ -565,12 +856,17 @@ impl Expr {
             } if *postgres_style => {
                 write!(f, "({}::{})", expr.display(dialect), ty.display(dialect))
             }
-            Expr::Cast { expr, ty, .. } => write!(
-                f,
-                "CAST({} as {})",
-                expr.display(dialect),
-                ty.display(dialect)
-            ),
+            Expr::Cast { expr, ty, .. } => {
+                write!(f, "CAST({} as ", expr.display(dialect))?;
+                match (dialect, ty) {
+                    // MySQL doesn't support its own integer type names as CAST targets (see
+                    // `mysql_int_cast_targets`)
+                    (Dialect::MySQL, SqlType::BigInt(None)) => write!(f, "SIGNED")?,
+                    (Dialect::MySQL, SqlType::UnsignedBigInt(None)) => write!(f, "UNSIGNED")?,
+                    _ => write!(f, "{}", ty.display(dialect))?,
+                }
+                write!(f, ")")
+            }
             Expr::Array(exprs) => {
                 fn write_value(
                     expr: &Expr,
@@ -601,6 +897,9 @@ impl Expr {
                 }
                 write!(f, "]")
             }
+            Expr::Interval { value, unit } => {
+                write!(f, "INTERVAL {} {}", value.display(dialect), unit)
+            }
             Expr::Variable(var) => write!(f, "{}", var),
         })
     }
@@ -745,6 +1044,7 @@ fn binary_operator_no_and_or(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Binar
             map(tag("#>"), |_| BinaryOperator::HashArrow1),
         )),
         map(tag("#-"), |_| BinaryOperator::HashSubtract),
+        map(tag("@@"), |_| BinaryOperator::AtAt),
     ))(i)
 }
 
@@ -984,6 +1284,7 @@ where
             Infix(HashArrow2) => Affix::Infix(Precedence(8), Associativity::Left),
             Infix(AtArrowRight) => Affix::Infix(Precedence(8), Associativity::Left),
             Infix(AtArrowLeft) => Affix::Infix(Precedence(8), Associativity::Left),
+            Infix(AtAt) => Affix::Infix(Precedence(8), Associativity::Left),
             Infix(HashSubtract) => Affix::Infix(Precedence(8), Associativity::Left),
         })
     }
@@ -1203,7 +1504,22 @@ fn case_when_expr(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResu
     move |i| {
         let (i, _) = tag_no_case("case")(i)?;
         let (i, _) = whitespace1(i)?;
-        let (i, branches) = many1(terminated(case_when_branch(dialect), whitespace1))(i)?;
+        let (i, operand) = opt(terminated(expression(dialect), whitespace1))(i)?;
+        let (i, mut branches) = many1(terminated(case_when_branch(dialect), whitespace1))(i)?;
+        if let Some(operand) = operand {
+            // Desugar the "simple" form of CASE (`CASE x WHEN 1 THEN ...`) into the searched form
+            // (`CASE WHEN x = 1 THEN ...`), so the rest of the system only has to handle one
+            for branch in &mut branches {
+                branch.condition = Expr::BinaryOp {
+                    lhs: Box::new(operand.clone()),
+                    op: BinaryOperator::Equal,
+                    rhs: Box::new(mem::replace(
+                        &mut branch.condition,
+                        Expr::Literal(Literal::Null),
+                    )),
+                };
+            }
+        }
         let (i, else_expr) = opt(map(case_when_else(dialect), Box::new))(i)?;
         let (i, _) = tag_no_case("end")(i)?;
         Ok((
@@ -1216,6 +1532,38 @@ fn case_when_expr(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResu
     }
 }
 
+pub(crate) fn interval_unit(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], IntervalUnit> {
+    alt((
+        value(IntervalUnit::Microsecond, tag_no_case("microsecond")),
+        value(IntervalUnit::Second, tag_no_case("second")),
+        value(IntervalUnit::Minute, tag_no_case("minute")),
+        value(IntervalUnit::Hour, tag_no_case("hour")),
+        value(IntervalUnit::Day, tag_no_case("day")),
+        value(IntervalUnit::Week, tag_no_case("week")),
+        value(IntervalUnit::Month, tag_no_case("month")),
+        value(IntervalUnit::Quarter, tag_no_case("quarter")),
+        value(IntervalUnit::Year, tag_no_case("year")),
+    ))(i)
+}
+
+fn interval_expr(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Expr> {
+    move |i| {
+        let (i, _) = tag_no_case("interval")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, value) = simple_expr(dialect)(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, unit) = interval_unit(i)?;
+
+        Ok((
+            i,
+            Expr::Interval {
+                value: Box::new(value),
+                unit,
+            },
+        ))
+    }
+}
+
 fn cast(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Expr> {
     move |i| {
         let (i, _) = tag_no_case("cast")(i)?;
@@ -1331,6 +1679,7 @@ pub(crate) fn simple_expr(
             map(function_expr(dialect), Expr::Call),
             map(literal(dialect), Expr::Literal),
             case_when_expr(dialect),
+            interval_expr(dialect),
             array_expr(dialect),
             map(column_identifier_no_alias(dialect), Expr::Column),
             cast(dialect),
@@ -1355,7 +1704,7 @@ pub(crate) fn expression(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::to_nom_result;
+    use crate::{to_nom_result, ItemPlaceholder};
 
     #[test]
     fn column_then_column() {
@@ -1365,6 +1714,56 @@ mod tests {
         assert_eq!(rem, b" y");
     }
 
+    #[test]
+    fn window_function_arguments() {
+        let (_, res) = to_nom_result(expression(Dialect::MySQL)(LocatedSpan::new(
+            b"rank() OVER (PARTITION BY x ORDER BY y DESC, 1)",
+        )))
+        .unwrap();
+        let Expr::Call(function) = res else {
+            panic!("Expected a function call, got {res:?}");
+        };
+        assert_eq!(
+            function.arguments().collect::<Vec<_>>(),
+            vec![&Expr::Column("x".into()), &Expr::Column("y".into())]
+        );
+    }
+
+    #[test]
+    fn simple_case() {
+        precedence::parses_same(
+            Dialect::MySQL,
+            "CASE x WHEN 1 THEN 'a' WHEN y + 1 THEN 'b' ELSE 'c' END",
+            "CASE WHEN x = 1 THEN 'a' WHEN x = (y + 1) THEN 'b' ELSE 'c' END",
+        );
+    }
+
+    #[test]
+    fn interval() {
+        let res = to_nom_result(expression(Dialect::MySQL)(LocatedSpan::new(
+            b"created_at - INTERVAL ? day",
+        )))
+        .unwrap()
+        .1;
+        assert_eq!(
+            res,
+            Expr::BinaryOp {
+                lhs: Box::new(Expr::Column("created_at".into())),
+                op: BinaryOperator::Subtract,
+                rhs: Box::new(Expr::Interval {
+                    value: Box::new(Expr::Literal(Literal::Placeholder(
+                        ItemPlaceholder::QuestionMark
+                    ))),
+                    unit: IntervalUnit::Day,
+                }),
+            }
+        );
+        assert_eq!(
+            res.display(Dialect::MySQL).to_string(),
+            "(`created_at` - INTERVAL ? DAY)"
+        );
+    }
+
     pub mod precedence {
         use super::*;
 
@@ -1480,6 +1879,18 @@ mod tests {
                 }
             );
         }
+
+        #[test]
+        fn display_mysql_int_cast_targets() {
+            for (input, expected) in [
+                ("CAST(x AS SIGNED)", "CAST(`x` as SIGNED)"),
+                ("CAST(x AS UNSIGNED INTEGER)", "CAST(`x` as UNSIGNED)"),
+                ("CAST(x AS CHAR)", "CAST(`x` as CHAR)"),
+            ] {
+                let res = test_parse!(expression(Dialect::MySQL), input.as_bytes());
+                assert_eq!(res.display(Dialect::MySQL).to_string(), expected);
+            }
+        }
     }
 
     mod conditions {
@@ -2757,6 +3168,26 @@ mod tests {
                 );
             }
 
+            #[test]
+            fn at_at_operator() {
+                let cond = b"to_tsvector(body) @@ to_tsquery('cat & dog')";
+                let res = test_parse!(expression(Dialect::PostgreSQL), cond);
+                assert_eq!(
+                    res,
+                    Expr::BinaryOp {
+                        lhs: Box::new(Expr::Call(FunctionExpr::Call {
+                            name: "to_tsvector".into(),
+                            arguments: vec![Expr::Column("body".into())],
+                        })),
+                        op: BinaryOperator::AtAt,
+                        rhs: Box::new(Expr::Call(FunctionExpr::Call {
+                            name: "to_tsquery".into(),
+                            arguments: vec![Expr::Literal("cat & dog".into())],
+                        })),
+                    }
+                );
+            }
+
             #[test]
             fn at_arrow_left_operator() {
                 let cond = b"'2' <@ '[1, 2, 2]'";
@@ -0,0 +1,166 @@
+use std::fmt;
+
+use itertools::Itertools;
+use nom::bytes::complete::{tag, tag_no_case};
+use nom::combinator::opt;
+use nom::multi::separated_list1;
+use nom::sequence::{delimited, preceded, tuple};
+use nom_locate::LocatedSpan;
+use readyset_util::fmt::fmt_with;
+use serde::{Deserialize, Serialize};
+
+use crate::common::{statement_terminator, ws_sep_comma};
+use crate::literal::literal;
+use crate::table::{relation, Relation};
+use crate::whitespace::{whitespace0, whitespace1};
+use crate::{Dialect, Literal, NomSqlResult};
+
+/// `FLUSH READYSET CACHE [<view> [KEYS (<value>, ...) [, (<value>, ...)]*]]`
+///
+/// Evicts all partially materialized state from the dataflow graph, or only the state feeding into
+/// the given cache if one is specified, so that subsequent reads are replayed from the base tables.
+/// If `KEYS` are given, only those keys are evicted from the reader of the cache, with the values
+/// of each key given in the same order as the parameters of the cached query.
+///
+/// This is a non-standard ReadySet specific extension to SQL
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct FlushCacheStatement {
+    /// The cache to flush the state of, or [`None`] to flush the state of all caches
+    pub view: Option<Relation>,
+    /// The keys to evict from the reader of `view`, or empty to evict all of its state
+    pub keys: Vec<Vec<Literal>>,
+}
+
+impl FlushCacheStatement {
+    pub fn display(&self, dialect: Dialect) -> impl fmt::Display + Copy + '_ {
+        fmt_with(move |f| {
+            write!(f, "FLUSH READYSET CACHE")?;
+            if let Some(view) = &self.view {
+                write!(f, " {}", view.display(dialect))?;
+            }
+            if !self.keys.is_empty() {
+                write!(
+                    f,
+                    " KEYS {}",
+                    self.keys
+                        .iter()
+                        .map(|key| format!("({})", key.iter().join(", ")))
+                        .join(", ")
+                )?;
+            }
+            Ok(())
+        })
+    }
+}
+
+fn key(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Vec<Literal>> {
+    move |i| {
+        delimited(
+            tuple((tag("("), whitespace0)),
+            separated_list1(ws_sep_comma, literal(dialect)),
+            tuple((whitespace0, tag(")"))),
+        )(i)
+    }
+}
+
+/// Parse a [`FlushCacheStatement`]
+pub fn flush_cache(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FlushCacheStatement> {
+    move |i| {
+        let (i, _) = tag_no_case("flush")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("readyset")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("cache")(i)?;
+        let (i, view_and_keys) = opt(tuple((
+            preceded(whitespace1, relation(dialect)),
+            opt(preceded(
+                tuple((whitespace1, tag_no_case("keys"), whitespace1)),
+                separated_list1(ws_sep_comma, key(dialect)),
+            )),
+        )))(i)?;
+        let (i, _) = statement_terminator(i)?;
+        let (view, keys) = match view_and_keys {
+            Some((view, keys)) => (Some(view), keys.unwrap_or_default()),
+            None => (None, vec![]),
+        };
+        Ok((i, FlushCacheStatement { view, keys }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flush_all() {
+        let res = test_parse!(flush_cache(Dialect::MySQL), b"FLUSH READYSET CACHE;");
+        assert_eq!(
+            res,
+            FlushCacheStatement {
+                view: None,
+                keys: vec![]
+            }
+        );
+        assert_eq!(
+            res.display(Dialect::MySQL).to_string(),
+            "FLUSH READYSET CACHE"
+        );
+    }
+
+    #[test]
+    fn flush_view() {
+        let res = test_parse!(
+            flush_cache(Dialect::PostgreSQL),
+            b"flush readyset cache \"s\".\"q1\""
+        );
+        assert_eq!(
+            res,
+            FlushCacheStatement {
+                view: Some(Relation {
+                    schema: Some("s".into()),
+                    name: "q1".into()
+                }),
+                keys: vec![]
+            }
+        );
+        assert_eq!(
+            res.display(Dialect::PostgreSQL).to_string(),
+            "FLUSH READYSET CACHE \"s\".\"q1\""
+        );
+    }
+
+    #[test]
+    fn flush_keys() {
+        let res = test_parse!(
+            flush_cache(Dialect::MySQL),
+            b"FLUSH READYSET CACHE q1 KEYS (1, 'a'),( 2 , 'b' );"
+        );
+        assert_eq!(res.view, Some("q1".into()));
+        assert_eq!(
+            res.keys,
+            vec![
+                vec![Literal::UnsignedInteger(1), Literal::String("a".into())],
+                vec![Literal::UnsignedInteger(2), Literal::String("b".into())],
+            ]
+        );
+        assert_eq!(
+            res.display(Dialect::MySQL).to_string(),
+            "FLUSH READYSET CACHE `q1` KEYS (1, 'a'), (2, 'b')"
+        );
+    }
+
+    #[test]
+    fn flush_keys_requires_view() {
+        assert!(
+            flush_cache(Dialect::MySQL)(LocatedSpan::new(b"FLUSH READYSET CACHE KEYS (1)"))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn flush_requires_readyset() {
+        assert!(flush_cache(Dialect::MySQL)(LocatedSpan::new(b"FLUSH CACHE")).is_err());
+    }
+}
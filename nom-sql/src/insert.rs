@@ -1,8 +1,9 @@
 use std::{fmt, str};
 
 use itertools::Itertools;
+use nom::branch::alt;
 use nom::bytes::complete::{tag, tag_no_case};
-use nom::combinator::opt;
+use nom::combinator::{map, opt};
 use nom::multi::separated_list1;
 use nom::sequence::{delimited, preceded, terminated, tuple};
 use nom_locate::LocatedSpan;
@@ -11,7 +12,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::column::Column;
 use crate::common::{
-    assignment_expr_list, field_list, statement_terminator, value_list, ws_sep_comma,
+    assignment_expr_list, column_identifier_no_alias, field_list, statement_terminator, value_list,
+    ws_sep_comma,
 };
 use crate::table::{relation, Relation};
 use crate::whitespace::{whitespace0, whitespace1};
@@ -23,7 +25,12 @@ pub struct InsertStatement {
     pub fields: Option<Vec<Column>>,
     pub data: Vec<Vec<Expr>>,
     pub ignore: bool,
+    /// The assignments of an upsert, from either a MySQL `ON DUPLICATE KEY UPDATE` clause or a
+    /// Postgres `ON CONFLICT ... DO UPDATE SET` clause. Empty for `ON CONFLICT ... DO NOTHING`.
     pub on_duplicate: Option<Vec<(Column, Expr)>>,
+    /// The conflict target of a Postgres `ON CONFLICT` clause, if the statement has one. Empty if
+    /// the clause has no conflict target (which is only valid with `DO NOTHING`).
+    pub on_conflict: Option<Vec<Column>>,
 }
 
 impl InsertStatement {
@@ -52,7 +59,48 @@ impl InsertStatement {
                         datas.iter().map(|l| l.display(dialect)).join(", ")
                     ))
                     .join(", ")
-            )
+            )?;
+
+            let assignments = |assignments: &[(Column, Expr)]| {
+                assignments
+                    .iter()
+                    .map(|(col, expr)| {
+                        format!(
+                            "{} = {}",
+                            dialect.quote_identifier(&col.name),
+                            expr.display(dialect)
+                        )
+                    })
+                    .join(", ")
+            };
+
+            match (&self.on_conflict, &self.on_duplicate) {
+                (Some(target), on_duplicate) => {
+                    write!(f, " ON CONFLICT")?;
+                    if !target.is_empty() {
+                        write!(
+                            f,
+                            " ({})",
+                            target
+                                .iter()
+                                .map(|col| dialect.quote_identifier(&col.name))
+                                .join(", ")
+                        )?;
+                    }
+                    match on_duplicate {
+                        Some(on_duplicate) if !on_duplicate.is_empty() => {
+                            write!(f, " DO UPDATE SET {}", assignments(on_duplicate))?
+                        }
+                        _ => write!(f, " DO NOTHING")?,
+                    }
+                }
+                (None, Some(on_duplicate)) => {
+                    write!(f, " ON DUPLICATE KEY UPDATE {}", assignments(on_duplicate))?
+                }
+                (None, None) => {}
+            }
+
+            Ok(())
         })
     }
 }
@@ -91,31 +139,104 @@ fn on_duplicate(
     }
 }
 
+fn conflict_target(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Vec<Column>> {
+    move |i| {
+        terminated(
+            delimited(
+                terminated(tag("("), whitespace0),
+                separated_list1(ws_sep_comma, column_identifier_no_alias(dialect)),
+                preceded(whitespace0, tag(")")),
+            ),
+            whitespace0,
+        )(i)
+    }
+}
+
+/// Parse a Postgres `ON CONFLICT [(<columns>)] DO NOTHING` or
+/// `ON CONFLICT (<columns>) DO UPDATE SET <assignments>` clause, returning the conflict target and
+/// the assignments (which are empty for `DO NOTHING`)
+fn on_conflict(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], (Vec<Column>, Vec<(Column, Expr)>)> {
+    move |i| {
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag_no_case("on")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("conflict")(i)?;
+        let (i, _) = whitespace0(i)?;
+        alt((
+            map(
+                terminated(
+                    opt(conflict_target(dialect)),
+                    tuple((tag_no_case("do"), whitespace1, tag_no_case("nothing"))),
+                ),
+                |target| (target.unwrap_or_default(), vec![]),
+            ),
+            // Postgres requires a conflict target for `DO UPDATE`
+            tuple((
+                conflict_target(dialect),
+                preceded(
+                    tuple((
+                        tag_no_case("do"),
+                        whitespace1,
+                        tag_no_case("update"),
+                        whitespace1,
+                        tag_no_case("set"),
+                        whitespace1,
+                    )),
+                    assignment_expr_list(dialect),
+                ),
+            )),
+        ))(i)
+    }
+}
+
+/// Parse either a MySQL `ON DUPLICATE KEY UPDATE` clause or a Postgres `ON CONFLICT` clause,
+/// returning the conflict target (for `ON CONFLICT`) and the assignments of the upsert
+#[allow(clippy::type_complexity)]
+fn upsert(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], (Option<Vec<Column>>, Vec<(Column, Expr)>)>
+{
+    move |i| {
+        alt((
+            map(on_duplicate(dialect), |assignments| (None, assignments)),
+            map(on_conflict(dialect), |(target, assignments)| {
+                (Some(target), assignments)
+            }),
+        ))(i)
+    }
+}
+
 // Parse rule for a SQL insert query.
 // TODO(malte): support REPLACE, nested selection, DEFAULT VALUES
 pub fn insertion(
     dialect: Dialect,
 ) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], InsertStatement> {
     move |i| {
-        let (
-            remaining_input,
-            (_, ignore_res, _, _, _, table, _, fields, _, _, data, on_duplicate, _),
-        ) = tuple((
-            tag_no_case("insert"),
-            opt(preceded(whitespace1, tag_no_case("ignore"))),
-            whitespace1,
-            tag_no_case("into"),
-            whitespace1,
-            relation(dialect),
-            whitespace0,
-            opt(fields(dialect)),
-            tag_no_case("values"),
-            whitespace0,
-            separated_list1(ws_sep_comma, data(dialect)),
-            opt(on_duplicate(dialect)),
-            statement_terminator,
-        ))(i)?;
+        let (remaining_input, (_, ignore_res, _, _, _, table, _, fields, _, _, data, upsert, _)) =
+            tuple((
+                tag_no_case("insert"),
+                opt(preceded(whitespace1, tag_no_case("ignore"))),
+                whitespace1,
+                tag_no_case("into"),
+                whitespace1,
+                relation(dialect),
+                whitespace0,
+                opt(fields(dialect)),
+                tag_no_case("values"),
+                whitespace0,
+                separated_list1(ws_sep_comma, data(dialect)),
+                opt(upsert(dialect)),
+                statement_terminator,
+            ))(i)?;
         let ignore = ignore_res.is_some();
+        let (on_conflict, on_duplicate) = match upsert {
+            Some((on_conflict, on_duplicate)) => (on_conflict, Some(on_duplicate)),
+            None => (None, None),
+        };
 
         Ok((
             remaining_input,
@@ -125,6 +246,7 @@ pub fn insertion(
                 data,
                 ignore,
                 on_duplicate,
+                on_conflict,
             },
         ))
     }
@@ -152,6 +274,7 @@ mod tests {
                     Expr::Literal(Literal::Placeholder(ItemPlaceholder::QuestionMark)),
                     Expr::Literal(Literal::Placeholder(ItemPlaceholder::QuestionMark))
                 ]],
+                on_conflict: None,
                 on_duplicate: None,
                 ignore: false
             }
@@ -179,6 +302,7 @@ mod tests {
                         Expr::Literal(42_u32.into()),
                         Expr::Literal("test".into())
                     ]],
+                    on_conflict: None,
                     on_duplicate: None,
                     ignore: false
                 }
@@ -206,6 +330,7 @@ mod tests {
                             arguments: vec![]
                         }),
                     ],],
+                    on_conflict: None,
                     on_duplicate: None,
                     ignore: false
                 }
@@ -226,6 +351,7 @@ mod tests {
                         Expr::Literal(42_u32.into()),
                         Expr::Literal("test".into())
                     ]],
+                    on_conflict: None,
                     on_duplicate: None,
                     ignore: false
                 }
@@ -247,6 +373,7 @@ mod tests {
                         Expr::Literal(42_u32.into()),
                         Expr::Literal("test".into())
                     ]],
+                    on_conflict: None,
                     on_duplicate: None,
                     ignore: false
                 }
@@ -270,6 +397,7 @@ mod tests {
                         Expr::Literal(42_u32.into()),
                         Expr::Literal("test".into())
                     ]],
+                    on_conflict: None,
                     on_duplicate: None,
                     ignore: false,
                 }
@@ -290,6 +418,7 @@ mod tests {
                         vec![Expr::Literal(42_u32.into()), Expr::Literal("test".into())],
                         vec![Expr::Literal(21_u32.into()), Expr::Literal("test2".into())],
                     ],
+                    on_conflict: None,
                     on_duplicate: None,
                     ignore: false,
                 }
@@ -311,6 +440,7 @@ mod tests {
                         Expr::Literal(Literal::Placeholder(ItemPlaceholder::DollarNumber(1))),
                         Expr::Literal(Literal::Placeholder(ItemPlaceholder::ColonNumber(2)))
                     ]],
+                    on_conflict: None,
                     on_duplicate: Some(vec![(
                         Column::from("value"),
                         Expr::BinaryOp {
@@ -324,6 +454,31 @@ mod tests {
             );
         }
 
+        #[test]
+        fn insert_with_on_dup_update_values() {
+            let qstring = "INSERT INTO keystores (`key`, `value`) VALUES (1, 2), (3, 4) \
+                       ON DUPLICATE KEY UPDATE `value` = VALUES(`value`)";
+
+            let res = insertion(Dialect::MySQL)(LocatedSpan::new(qstring.as_bytes()))
+                .unwrap()
+                .1;
+            assert_eq!(
+                res.on_duplicate,
+                Some(vec![(
+                    Column::from("value"),
+                    Expr::Call(FunctionExpr::Call {
+                        name: "values".into(),
+                        arguments: vec![Expr::Column(Column::from("value"))],
+                    }),
+                )])
+            );
+            assert_eq!(
+                res.display(Dialect::MySQL).to_string(),
+                "INSERT INTO `keystores` (`key`, `value`) VALUES (1, 2), (3, 4) \
+                 ON DUPLICATE KEY UPDATE `value` = values(`value`)"
+            );
+        }
+
         #[test]
         fn insert_with_leading_value_whitespace() {
             let qstring = "INSERT INTO users (id, name) VALUES ( 42, \"test\");";
@@ -338,6 +493,7 @@ mod tests {
                         Expr::Literal(42_u32.into()),
                         Expr::Literal("test".into())
                     ]],
+                    on_conflict: None,
                     on_duplicate: None,
                     ignore: false,
                 }
@@ -374,6 +530,7 @@ mod tests {
                         Expr::Literal(42_u32.into()),
                         Expr::Literal("test".into())
                     ]],
+                    on_conflict: None,
                     on_duplicate: None,
                     ignore: false,
                 }
@@ -399,6 +556,7 @@ mod tests {
                             arguments: vec![],
                         }),
                     ],],
+                    on_conflict: None,
                     on_duplicate: None,
                     ignore: false,
                 }
@@ -419,6 +577,7 @@ mod tests {
                         Expr::Literal(42_u32.into()),
                         Expr::Literal("test".into())
                     ]],
+                    on_conflict: None,
                     on_duplicate: None,
                     ignore: false,
                 }
@@ -440,6 +599,7 @@ mod tests {
                         Expr::Literal(42_u32.into()),
                         Expr::Literal("test".into())
                     ]],
+                    on_conflict: None,
                     on_duplicate: None,
                     ignore: false,
                 }
@@ -463,6 +623,7 @@ mod tests {
                         Expr::Literal(42_u32.into()),
                         Expr::Literal("test".into())
                     ]],
+                    on_conflict: None,
                     on_duplicate: None,
                     ignore: false,
                 }
@@ -484,6 +645,7 @@ mod tests {
                         vec![Expr::Literal(21_u32.into()), Expr::Literal("test2".into())],
                     ],
                     ignore: false,
+                    on_conflict: None,
                     on_duplicate: None
                 }
             );
@@ -504,6 +666,7 @@ mod tests {
                         Expr::Literal(Literal::Placeholder(ItemPlaceholder::DollarNumber(1))),
                         Expr::Literal(Literal::Placeholder(ItemPlaceholder::ColonNumber(2)))
                     ]],
+                    on_conflict: None,
                     on_duplicate: Some(vec![(
                         Column::from("value"),
                         Expr::BinaryOp {
@@ -517,6 +680,65 @@ mod tests {
             );
         }
 
+        #[test]
+        fn insert_on_conflict_do_update() {
+            let qstring = "INSERT INTO keystores (key, value) VALUES ($1, $2) \
+                           ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value";
+
+            let res = insertion(Dialect::PostgreSQL)(LocatedSpan::new(qstring.as_bytes()))
+                .unwrap()
+                .1;
+            assert_eq!(res.on_conflict, Some(vec![Column::from("key")]));
+            assert_eq!(
+                res.on_duplicate,
+                Some(vec![(
+                    Column::from("value"),
+                    Expr::Column(Column {
+                        name: "value".into(),
+                        table: Some("excluded".into()),
+                    }),
+                )])
+            );
+            assert_eq!(
+                res.display(Dialect::PostgreSQL).to_string(),
+                "INSERT INTO \"keystores\" (\"key\", \"value\") VALUES ($1, $2) \
+                 ON CONFLICT (\"key\") DO UPDATE SET \"value\" = \"excluded\".\"value\""
+            );
+        }
+
+        #[test]
+        fn insert_on_conflict_do_nothing() {
+            let res = insertion(Dialect::PostgreSQL)(LocatedSpan::new(
+                b"INSERT INTO t (a, b) VALUES (1, 2) ON CONFLICT DO NOTHING".as_slice(),
+            ))
+            .unwrap()
+            .1;
+            assert_eq!(res.on_conflict, Some(vec![]));
+            assert_eq!(res.on_duplicate, Some(vec![]));
+            assert_eq!(
+                res.display(Dialect::PostgreSQL).to_string(),
+                "INSERT INTO \"t\" (\"a\", \"b\") VALUES (1, 2) ON CONFLICT DO NOTHING"
+            );
+
+            let res = insertion(Dialect::PostgreSQL)(LocatedSpan::new(
+                b"INSERT INTO t (a, b) VALUES (1, 2) ON CONFLICT (a, b) DO NOTHING".as_slice(),
+            ))
+            .unwrap()
+            .1;
+            assert_eq!(
+                res.on_conflict,
+                Some(vec![Column::from("a"), Column::from("b")])
+            );
+        }
+
+        #[test]
+        fn insert_on_conflict_do_update_requires_target() {
+            assert!(insertion(Dialect::PostgreSQL)(LocatedSpan::new(
+                b"INSERT INTO t (a, b) VALUES (1, 2) ON CONFLICT DO UPDATE SET b = 3".as_slice(),
+            ))
+            .is_err());
+        }
+
         #[test]
         fn insert_with_leading_value_whitespace() {
             let qstring = "INSERT INTO users (id, name) VALUES ( 42, 'test');";
@@ -532,6 +754,7 @@ mod tests {
                         Expr::Literal("test".into())
                     ]],
                     ignore: false,
+                    on_conflict: None,
                     on_duplicate: None
                 }
             );
@@ -166,11 +166,7 @@ fn keyword_p_to_t(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], &[u8]> {
             terminated(tag_no_case("SAVEPOINT"), keyword_follow_char),
             terminated(tag_no_case("SELECT"), keyword_follow_char),
             terminated(tag_no_case("SET"), keyword_follow_char),
-            terminated(tag_no_case("TABLE"), keyword_follow_char),
-            terminated(tag_no_case("TEMP"), keyword_follow_char),
-            terminated(tag_no_case("TEMPORARY"), keyword_follow_char),
-            terminated(tag_no_case("THEN"), keyword_follow_char),
-            terminated(tag_no_case("TO"), keyword_follow_char),
+            terminated(tag_no_case("SPATIAL"), keyword_follow_char),
         )),
         |i| *i,
     )(i)
@@ -179,6 +175,11 @@ fn keyword_p_to_t(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], &[u8]> {
 fn keyword_t_to_z(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], &[u8]> {
     map(
         alt((
+            terminated(tag_no_case("TABLE"), keyword_follow_char),
+            terminated(tag_no_case("TEMP"), keyword_follow_char),
+            terminated(tag_no_case("TEMPORARY"), keyword_follow_char),
+            terminated(tag_no_case("THEN"), keyword_follow_char),
+            terminated(tag_no_case("TO"), keyword_follow_char),
             terminated(tag_no_case("TRANSACTION"), keyword_follow_char),
             terminated(tag_no_case("TRIGGER"), keyword_follow_char),
             terminated(tag_no_case("UNION"), keyword_follow_char),
@@ -485,6 +486,8 @@ lazy_static! {
         b"SIMPLE",
         b"SKIP",
         b"SNAPSHOT",
+        // Not a keyword at all in PostgreSQL, but reserved in MySQL
+        b"SPATIAL",
         b"SQL",
         b"STABLE",
         b"STANDALONE",
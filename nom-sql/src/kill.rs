@@ -0,0 +1,99 @@
+use std::fmt;
+
+use nom::branch::alt;
+use nom::bytes::complete::tag_no_case;
+use nom::character::complete::{digit1, u32 as nom_u32};
+use nom::combinator::{map_parser, opt, value};
+use nom::sequence::terminated;
+use nom_locate::LocatedSpan;
+use serde::{Deserialize, Serialize};
+
+use crate::common::statement_terminator;
+use crate::whitespace::whitespace1;
+use crate::NomSqlResult;
+
+/// What to kill in a [`KillStatement`]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum KillType {
+    /// Terminate the connection, along with any statement it is executing. This is the default if
+    /// neither `CONNECTION` nor `QUERY` is specified
+    Connection,
+    /// Terminate the statement the connection is currently executing, leaving the connection
+    /// itself intact
+    Query,
+}
+
+/// `KILL [CONNECTION | QUERY] <id>`, which terminates a statement (or connection) running on
+/// another connection, identified by its connection id
+///
+/// <https://dev.mysql.com/doc/refman/8.0/en/kill.html>
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct KillStatement {
+    pub kill_type: KillType,
+    pub id: u32,
+}
+
+impl fmt::Display for KillStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "KILL ")?;
+        match self.kill_type {
+            KillType::Connection => write!(f, "CONNECTION ")?,
+            KillType::Query => write!(f, "QUERY ")?,
+        }
+        write!(f, "{}", self.id)
+    }
+}
+
+pub fn kill_statement(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], KillStatement> {
+    let (i, _) = tag_no_case("kill")(i)?;
+    let (i, _) = whitespace1(i)?;
+    let (i, kill_type) = opt(terminated(
+        alt((
+            value(KillType::Connection, tag_no_case("connection")),
+            value(KillType::Query, tag_no_case("query")),
+        )),
+        whitespace1,
+    ))(i)?;
+    let (i, id) = map_parser(digit1, nom_u32)(i)?;
+    let (i, _) = statement_terminator(i)?;
+    Ok((
+        i,
+        KillStatement {
+            kill_type: kill_type.unwrap_or(KillType::Connection),
+            id,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> KillStatement {
+        kill_statement(LocatedSpan::new(s.as_bytes())).unwrap().1
+    }
+
+    #[test]
+    fn kill_query() {
+        let res = parse("KILL QUERY 42;");
+        assert_eq!(
+            res,
+            KillStatement {
+                kill_type: KillType::Query,
+                id: 42
+            }
+        );
+        assert_eq!(res.to_string(), "KILL QUERY 42");
+    }
+
+    #[test]
+    fn kill_connection() {
+        assert_eq!(parse("kill 7"), parse("KILL CONNECTION 7"));
+        assert_eq!(parse("kill 7").kill_type, KillType::Connection);
+    }
+
+    #[test]
+    fn kill_invalid_id() {
+        assert!(kill_statement(LocatedSpan::new(b"KILL QUERY abc")).is_err());
+    }
+}
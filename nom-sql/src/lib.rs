@@ -13,14 +13,17 @@ use nom::{AsBytes, Err, HexDisplay, IResult};
 use nom_locate::LocatedSpan;
 
 pub use self::alter::{
-    AlterColumnOperation, AlterTableDefinition, AlterTableStatement, ReplicaIdentity,
+    AlterColumnOperation, AlterTableDefinition, AlterTableStatement, ColumnPosition,
+    ReplicaIdentity,
 };
 pub use self::column::{Column, ColumnConstraint, ColumnSpecification};
-pub use self::common::{FieldDefinitionExpr, FieldReference, IndexType, TableKey};
+pub use self::common::{
+    FieldDefinitionExpr, FieldReference, IndexType, ReferentialAction, TableKey,
+};
 pub use self::compound_select::{CompoundSelectOperator, CompoundSelectStatement};
 pub use self::create::{
-    CacheInner, CreateCacheStatement, CreateTableBody, CreateTableStatement, CreateViewStatement,
-    SelectSpecification,
+    CacheInner, CacheWarmup, CreateCacheStatement, CreateTableBody, CreateTableStatement,
+    CreateViewStatement, SelectSpecification,
 };
 pub use self::create_table_options::CreateTableOption;
 pub use self::delete::DeleteStatement;
@@ -28,12 +31,18 @@ pub use self::dialect::Dialect;
 pub use self::drop::{
     DropAllCachesStatement, DropCacheStatement, DropTableStatement, DropViewStatement,
 };
+pub use self::experiment::{
+    CreateExperimentStatement, ExperimentStatement, DEFAULT_EXPERIMENT_PERCENT,
+};
 pub use self::explain::ExplainStatement;
 pub use self::expression::{
-    BinaryOperator, CaseWhenBranch, Expr, FunctionExpr, InValue, UnaryOperator,
+    BinaryOperator, CaseWhenBranch, Expr, FunctionExpr, InValue, IntervalUnit, MatchSearchModifier,
+    TrimSide, UnaryOperator, WindowFunction,
 };
+pub use self::flush::FlushCacheStatement;
 pub use self::insert::InsertStatement;
 pub use self::join::{JoinConstraint, JoinOperator, JoinRightSide};
+pub use self::kill::{KillStatement, KillType};
 pub use self::literal::{
     embedded_literal, literal, raw_string_literal, utf8_string_literal, Double, Float,
     ItemPlaceholder, Literal, QuotingStyle,
@@ -48,9 +57,10 @@ pub use self::set::{
 };
 pub use self::show::ShowStatement;
 pub use self::sql_identifier::SqlIdentifier;
-pub use self::sql_type::{EnumVariants, SqlType, SqlTypeArbitraryOptions};
+pub use self::sql_type::{EnumVariants, SpatialType, SqlType, SqlTypeArbitraryOptions};
 pub use self::table::{replicator_table_list, Relation, TableExpr, TableExprInner};
 pub use self::transaction::StartTransactionStatement;
+pub use self::truncate::{TruncateStatement, TruncateTable};
 pub use self::update::UpdateStatement;
 pub use self::use_statement::UseStatement;
 
@@ -69,11 +79,14 @@ mod create;
 mod create_table_options;
 mod delete;
 mod drop;
+mod experiment;
 mod explain;
 mod expression;
+mod flush;
 mod insert;
 mod join;
 mod keywords;
+mod kill;
 mod literal;
 mod order;
 mod rename;
@@ -84,6 +97,7 @@ mod sql_identifier;
 mod sql_type;
 mod table;
 mod transaction;
+mod truncate;
 mod update;
 mod use_statement;
 pub mod whitespace;
@@ -20,6 +20,7 @@ use readyset_util::arbitrary::{
     arbitrary_naive_time, arbitrary_positive_naive_date, arbitrary_timestamp_naive_date_time,
     arbitrary_uuid,
 };
+use readyset_util::fmt::fmt_with;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use test_strategy::Arbitrary;
@@ -207,7 +208,11 @@ impl Display for Literal {
                     .join(" ")
             ),
             Literal::ByteArray(b) => {
-                write!(f, "E'\\x{}'", b.iter().map(|v| format!("{:x}", v)).join(""))
+                write!(
+                    f,
+                    "E'\\\\x{}'::bytea",
+                    b.iter().map(|v| format!("{:02x}", v)).join("")
+                )
             }
             Literal::Placeholder(item) => write!(f, "{}", item.to_string()),
             Literal::BitVector(ref b) => {
@@ -225,6 +230,18 @@ impl Display for Literal {
 }
 
 impl Literal {
+    /// Display this literal as it would be written in the given dialect.
+    ///
+    /// This only differs from the [`Display`] impl for literals whose syntax depends on the dialect
+    pub fn display(&self, dialect: Dialect) -> impl fmt::Display + Copy + '_ {
+        fmt_with(move |f| match (self, dialect) {
+            (Literal::ByteArray(b), Dialect::MySQL) => {
+                write!(f, "X'{}'", b.iter().map(|v| format!("{:02x}", v)).join(""))
+            }
+            _ => write!(f, "{}", self),
+        })
+    }
+
     pub fn arbitrary_with_type(sql_type: &SqlType) -> impl Strategy<Value = Self> + 'static {
         use proptest::prelude::*;
 
@@ -286,7 +303,13 @@ impl Literal {
             SqlType::Time => arbitrary_naive_time()
                 .prop_map(|nt| Self::String(nt.format("%H:%M:%S").to_string()))
                 .boxed(),
-            SqlType::Enum(_) => unimplemented!("Enums aren't implemented yet"),
+            SqlType::Enum(variants) if !variants.is_empty() => {
+                proptest::sample::select(variants.iter().cloned().collect::<Vec<_>>())
+                    .prop_map(Self::String)
+                    .boxed()
+            }
+            // The only valid value of an enum with no variants is the empty string
+            SqlType::Enum(_) => Just(Self::String(String::new())).boxed(),
             SqlType::Json | SqlType::Jsonb => arbitrary_json()
                 .prop_map(|v| Self::String(v.to_string()))
                 .boxed(),
@@ -321,10 +344,14 @@ impl Literal {
             }
             SqlType::Serial => any::<i32>().prop_map(Self::from).boxed(),
             SqlType::BigSerial => any::<i64>().prop_map(Self::from).boxed(),
+            SqlType::Interval => (0..10_000i32, 0..86_400i64)
+                .prop_map(|(days, seconds)| Self::String(format!("{days} days {seconds} seconds")))
+                .boxed(),
             SqlType::Array(_) => unimplemented!("Arrays aren't implemented yet"),
             SqlType::Other(ty) => {
                 unimplemented!("Other({}) isn't implemented yet", ty.display_unquoted())
             }
+            SqlType::Spatial(_) => unimplemented!("Spatial types aren't implemented yet"),
         }
     }
 
@@ -642,6 +669,18 @@ mod tests {
         }
     }
 
+    #[proptest]
+    fn byte_array_display_parse_round_trip(bytes: Vec<u8>) {
+        let lit = Literal::ByteArray(bytes);
+        for &dialect in Dialect::ALL {
+            let s = lit.display(dialect).to_string();
+            assert_eq!(
+                literal(dialect)(LocatedSpan::new(s.as_bytes())).unwrap().1,
+                lit
+            )
+        }
+    }
+
     #[test]
     fn boolean_literals() {
         for &dialect in Dialect::ALL {
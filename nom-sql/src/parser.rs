@@ -18,18 +18,23 @@ use crate::drop::{
     drop_all_caches, drop_cached_query, drop_table, drop_view, DropCacheStatement,
     DropTableStatement, DropViewStatement,
 };
+use crate::experiment::{experiment, ExperimentStatement};
 use crate::explain::{explain_statement, ExplainStatement};
 use crate::expression::expression;
+use crate::flush::{flush_cache, FlushCacheStatement};
 use crate::insert::{insertion, InsertStatement};
+use crate::kill::{kill_statement, KillStatement};
 use crate::rename::{rename_table, RenameTableStatement};
 use crate::select::{selection, SelectStatement};
 use crate::set::{set, SetStatement};
 use crate::show::{show, ShowStatement};
 use crate::sql_type::type_identifier;
+use crate::table::{relation, Relation};
 use crate::transaction::{
     commit, rollback, start_transaction, CommitStatement, RollbackStatement,
     StartTransactionStatement,
 };
+use crate::truncate::{truncate, TruncateStatement};
 use crate::update::{updating, UpdateStatement};
 use crate::use_statement::{use_statement, UseStatement};
 use crate::whitespace::whitespace0;
@@ -59,6 +64,10 @@ pub enum SqlQuery {
     Use(UseStatement),
     Show(ShowStatement),
     Explain(ExplainStatement),
+    Kill(KillStatement),
+    Experiment(ExperimentStatement),
+    FlushCache(FlushCacheStatement),
+    Truncate(TruncateStatement),
 }
 
 impl SqlQuery {
@@ -84,7 +93,11 @@ impl SqlQuery {
             Self::RenameTable(rename) => write!(f, "{}", rename.display(dialect)),
             Self::Use(use_db) => write!(f, "{}", use_db),
             Self::Show(show) => write!(f, "{}", show.display(dialect)),
-            Self::Explain(explain) => write!(f, "{}", explain),
+            Self::Explain(explain) => write!(f, "{}", explain.display(dialect)),
+            Self::Kill(kill) => write!(f, "{}", kill),
+            Self::Experiment(experiment) => write!(f, "{}", experiment.display(dialect)),
+            Self::FlushCache(flush) => write!(f, "{}", flush.display(dialect)),
+            Self::Truncate(truncate) => write!(f, "{}", truncate.display(dialect)),
         })
     }
 }
@@ -122,6 +135,10 @@ impl SqlQuery {
             Self::Use(_) => "USE",
             Self::Show(_) => "SHOW",
             Self::Explain(_) => "EXPLAIN",
+            Self::Kill(_) => "KILL",
+            Self::Experiment(_) => "EXPERIMENT",
+            Self::FlushCache(_) => "FLUSH CACHE",
+            Self::Truncate(_) => "TRUNCATE",
         }
     }
 
@@ -154,9 +171,15 @@ pub fn sql_query(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResul
             map(commit(dialect), SqlQuery::Commit),
             map(rollback(dialect), SqlQuery::Rollback),
             map(rename_table(dialect), SqlQuery::RenameTable),
-            map(use_statement(dialect), SqlQuery::Use),
-            map(show(dialect), SqlQuery::Show),
-            map(explain_statement, SqlQuery::Explain),
+            alt((
+                map(use_statement(dialect), SqlQuery::Use),
+                map(show(dialect), SqlQuery::Show),
+                map(explain_statement(dialect), SqlQuery::Explain),
+                map(kill_statement, SqlQuery::Kill),
+                map(experiment(dialect), SqlQuery::Experiment),
+                map(flush_cache(dialect), SqlQuery::FlushCache),
+                map(truncate(dialect), SqlQuery::Truncate),
+            )),
         ))(i)
     }
 }
@@ -211,6 +234,7 @@ export_parser!(
     parse_sql_type_bytes,
     parse_sql_type
 );
+export_parser!(relation -> Relation, parse_relation_bytes, parse_relation);
 
 #[cfg(test)]
 mod tests {
@@ -264,6 +288,7 @@ mod tests {
                     Expr::Literal("test".into()),
                 ]],
                 ignore: false,
+                on_conflict: None,
                 on_duplicate: None,
             });
             let mut h0 = DefaultHasher::new();
@@ -474,6 +499,7 @@ mod tests {
                     Expr::Literal("test".into()),
                 ]],
                 ignore: false,
+                on_conflict: None,
                 on_duplicate: None,
             });
             let mut h0 = DefaultHasher::new();
@@ -171,6 +171,10 @@ impl fmt::Display for LimitClause {
 pub struct SelectStatement {
     pub ctes: Vec<CommonTableExpr>,
     pub distinct: bool,
+    /// Whether the MySQL-specific `SQL_CALC_FOUND_ROWS` modifier was given, asking the database
+    /// to compute the number of rows the query would return without its `LIMIT`, to be returned
+    /// by a subsequent call to `FOUND_ROWS()`
+    pub calc_found_rows: bool,
     pub fields: Vec<FieldDefinitionExpr>,
     pub tables: Vec<TableExpr>,
     pub join: Vec<JoinClause>,
@@ -194,6 +198,10 @@ impl SelectStatement {
                         | FunctionExpr::Max(_)
                         | FunctionExpr::Min(_)
                         | FunctionExpr::GroupConcat { .. }
+                        | FunctionExpr::Median(_)
+                        | FunctionExpr::PercentileCont { .. }
+                        | FunctionExpr::ApproxCountDistinct(_)
+                        | FunctionExpr::ApproxPercentile { .. }
                 ),
                 Expr::NestedSelect(select) => select.contains_aggregate_select(),
                 _ => false,
@@ -218,6 +226,9 @@ impl SelectStatement {
             if self.distinct {
                 write!(f, "DISTINCT ")?;
             }
+            if self.calc_found_rows {
+                write!(f, "SQL_CALC_FOUND_ROWS ")?;
+            }
 
             write!(
                 f,
@@ -565,8 +576,23 @@ pub fn nested_selection(
         let (i, ctes) = opt(ctes(dialect))(i)?;
         let (i, _) = tag_no_case("select")(i)?;
         let (i, _) = whitespace1(i)?;
-        let (i, distinct) = opt(tag_no_case("distinct"))(i)?;
+        let (i, mut distinct) = opt(tag_no_case("distinct"))(i)?;
         let (i, _) = whitespace0(i)?;
+        // MySQL allows `SQL_CALC_FOUND_ROWS` either before or after `DISTINCT`
+        let (i, calc_found_rows) = if dialect == Dialect::MySQL {
+            let (i, calc_found_rows) =
+                opt(terminated(tag_no_case("sql_calc_found_rows"), whitespace1))(i)?;
+            let i = if calc_found_rows.is_some() && distinct.is_none() {
+                let (i, d) = opt(terminated(tag_no_case("distinct"), whitespace1))(i)?;
+                distinct = d;
+                i
+            } else {
+                i
+            };
+            (i, calc_found_rows.is_some())
+        } else {
+            (i, false)
+        };
         let (i, fields) = field_definition_expr(dialect)(i)?;
 
         let (i, from_clause) = opt(move |i| {
@@ -595,6 +621,7 @@ pub fn nested_selection(
         let mut result = SelectStatement {
             ctes: ctes.unwrap_or_default(),
             distinct: distinct.is_some(),
+            calc_found_rows,
             fields,
             ..Default::default()
         };
@@ -1968,6 +1995,34 @@ mod tests {
                 "SELECT `x`, count(*) FROM `t` HAVING (count(*) > 1)"
             );
         }
+
+        #[test]
+        fn sql_calc_found_rows() {
+            let res = test_parse!(
+                selection(Dialect::MySQL),
+                b"SELECT SQL_CALC_FOUND_ROWS x FROM t LIMIT 10"
+            );
+            assert!(res.calc_found_rows);
+            assert!(!res.distinct);
+            assert_eq!(res.fields, columns(&["x"]));
+            assert_eq!(
+                res.display(Dialect::MySQL).to_string(),
+                "SELECT SQL_CALC_FOUND_ROWS `x` FROM `t` LIMIT 10"
+            );
+        }
+
+        #[test]
+        fn sql_calc_found_rows_with_distinct() {
+            for qstr in [
+                "SELECT DISTINCT SQL_CALC_FOUND_ROWS x FROM t",
+                "select sql_calc_found_rows distinct x from t",
+            ] {
+                let res = test_parse!(selection(Dialect::MySQL), qstr.as_bytes());
+                assert!(res.calc_found_rows, "{qstr}");
+                assert!(res.distinct, "{qstr}");
+                assert_eq!(res.fields, columns(&["x"]));
+            }
+        }
     }
 
     mod postgres {
@@ -23,6 +23,8 @@ pub enum ShowStatement {
     ReadySetStatus,
     ReadySetVersion,
     ReadySetTables,
+    ReadySetReplicationPosition,
+    Experiments,
 }
 
 impl ShowStatement {
@@ -49,6 +51,8 @@ impl ShowStatement {
                 Self::ReadySetStatus => write!(f, "READYSET STATUS"),
                 Self::ReadySetVersion => write!(f, "READYSET VERSION"),
                 Self::ReadySetTables => write!(f, "READYSET TABLES"),
+                Self::ReadySetReplicationPosition => write!(f, "READYSET REPLICATION POSITION"),
+                Self::Experiments => write!(f, "EXPERIMENTS"),
             }
         })
     }
@@ -109,8 +113,19 @@ pub fn show(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u
                 ShowStatement::ReadySetTables,
                 tuple((tag_no_case("readyset"), whitespace1, tag_no_case("tables"))),
             ),
+            value(
+                ShowStatement::ReadySetReplicationPosition,
+                tuple((
+                    tag_no_case("readyset"),
+                    whitespace1,
+                    tag_no_case("replication"),
+                    whitespace1,
+                    tag_no_case("position"),
+                )),
+            ),
             map(show_tables(dialect), ShowStatement::Tables),
             value(ShowStatement::Events, tag_no_case("events")),
+            value(ShowStatement::Experiments, tag_no_case("experiments")),
         ))(i)?;
         Ok((i, statement))
     }
@@ -289,6 +304,15 @@ mod tests {
         assert_eq!(res2, ShowStatement::Events);
     }
 
+    #[test]
+    fn show_experiments() {
+        let res = show(Dialect::MySQL)(LocatedSpan::new(b"SHOW EXPERIMENTS"))
+            .unwrap()
+            .1;
+        assert_eq!(res, ShowStatement::Experiments);
+        assert_eq!(res.display(Dialect::MySQL).to_string(), "SHOW EXPERIMENTS");
+    }
+
     #[test]
     fn show_caches() {
         let qstring1 = "SHOW CACHES";
@@ -373,4 +397,16 @@ mod tests {
         let res = test_parse!(show(Dialect::MySQL), b"SHOW READYSET TABLES");
         assert_eq!(res, ShowStatement::ReadySetTables);
     }
+
+    #[test]
+    fn show_readyset_replication_position() {
+        for &dialect in Dialect::ALL {
+            let res = test_parse!(show(dialect), b"SHOW READYSET REPLICATION POSITION");
+            assert_eq!(res, ShowStatement::ReadySetReplicationPosition);
+            assert_eq!(
+                res.display(dialect).to_string(),
+                "SHOW READYSET REPLICATION POSITION"
+            );
+        }
+    }
 }
@@ -78,6 +78,42 @@ pub enum SqlType {
 
     /// Any other named type
     Other(Relation),
+
+    Interval,
+
+    /// One of MySQL's spatial data types
+    Spatial(SpatialType),
+}
+
+/// The spatial data types supported by MySQL.
+///
+/// ReadySet doesn't support any spatial operations, so values of these types are stored and
+/// returned as opaque byte strings in MySQL's internal geometry format.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum SpatialType {
+    Geometry,
+    Point,
+    LineString,
+    Polygon,
+    MultiPoint,
+    MultiLineString,
+    MultiPolygon,
+    GeometryCollection,
+}
+
+impl fmt::Display for SpatialType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SpatialType::Geometry => "GEOMETRY",
+            SpatialType::Point => "POINT",
+            SpatialType::LineString => "LINESTRING",
+            SpatialType::Polygon => "POLYGON",
+            SpatialType::MultiPoint => "MULTIPOINT",
+            SpatialType::MultiLineString => "MULTILINESTRING",
+            SpatialType::MultiPolygon => "MULTIPOLYGON",
+            SpatialType::GeometryCollection => "GEOMETRYCOLLECTION",
+        })
+    }
 }
 
 /// Options for generating arbitrary [`SqlType`]s
@@ -168,6 +204,7 @@ impl Arbitrary for SqlType {
                 Just(TimestampTz).boxed(),
                 Just(Citext).boxed(),
                 Just(QuotedChar).boxed(),
+                Just(Interval).boxed(),
             ]);
 
             if args.generate_json {
@@ -352,6 +389,8 @@ impl SqlType {
                 SqlType::BigSerial => write!(f, "BIGSERIAL"),
                 SqlType::Array(ref t) => write!(f, "{}[]", t.display(dialect)),
                 SqlType::Other(ref t) => write!(f, "{}", t.display(dialect)),
+                SqlType::Interval => write!(f, "INTERVAL"),
+                SqlType::Spatial(ty) => write!(f, "{}", ty),
             }
         })
     }
@@ -597,6 +636,8 @@ fn type_identifier_part1(
             value(SqlType::Int2, tag_no_case("int2")),
             value(SqlType::Int4, tag_no_case("int4")),
             value(SqlType::Int8, tag_no_case("int8")),
+            // Must come before `int`, which is a prefix of it
+            value(SqlType::Interval, tag_no_case("interval")),
             |i| int_type("tinyint", SqlType::UnsignedTinyInt, SqlType::TinyInt, i),
             |i| int_type("smallint", SqlType::UnsignedSmallInt, SqlType::SmallInt, i),
             |i| int_type("integer", SqlType::UnsignedInt, SqlType::Int, i),
@@ -765,11 +806,43 @@ fn type_identifier_part3(
             map(tag_no_case("bigserial"), |_| SqlType::BigSerial),
             map(tag_no_case("citext"), |_| SqlType::Citext),
             map(tag("\"char\""), |_| SqlType::QuotedChar),
+            map(spatial_type(dialect), SqlType::Spatial),
             map(other_type(dialect), SqlType::Other),
         ))(i)
     }
 }
 
+fn spatial_type(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], SpatialType> {
+    move |i| match dialect {
+        // PostgreSQL's geometric types have entirely different semantics (and representations)
+        // from MySQL's spatial types, so we only parse these as spatial types in MySQL
+        Dialect::MySQL => alt((
+            map(
+                alt((
+                    tag_no_case("geometrycollection"),
+                    tag_no_case("geomcollection"),
+                )),
+                |_| SpatialType::GeometryCollection,
+            ),
+            map(tag_no_case("geometry"), |_| SpatialType::Geometry),
+            map(tag_no_case("multipoint"), |_| SpatialType::MultiPoint),
+            map(tag_no_case("multilinestring"), |_| {
+                SpatialType::MultiLineString
+            }),
+            map(tag_no_case("multipolygon"), |_| SpatialType::MultiPolygon),
+            map(tag_no_case("point"), |_| SpatialType::Point),
+            map(tag_no_case("linestring"), |_| SpatialType::LineString),
+            map(tag_no_case("polygon"), |_| SpatialType::Polygon),
+        ))(i),
+        Dialect::PostgreSQL => Err(nom::Err::Error(ParseError::from_error_kind(
+            i,
+            ErrorKind::IsNot,
+        ))),
+    }
+}
+
 fn other_type(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Relation> {
     move |i| match dialect {
         Dialect::PostgreSQL => relation(dialect)(i),
@@ -968,6 +1041,32 @@ mod tests {
             assert!(res.is_ok());
             assert_eq!(res.unwrap().1, SqlType::Double);
         }
+
+        #[test]
+        fn spatial_types() {
+            for (input, ty) in [
+                ("geometry", SpatialType::Geometry),
+                ("POINT", SpatialType::Point),
+                ("linestring", SpatialType::LineString),
+                ("polygon", SpatialType::Polygon),
+                ("multipoint", SpatialType::MultiPoint),
+                ("multilinestring", SpatialType::MultiLineString),
+                ("multipolygon", SpatialType::MultiPolygon),
+                ("geometrycollection", SpatialType::GeometryCollection),
+                ("geomcollection", SpatialType::GeometryCollection),
+            ] {
+                let res = test_parse!(type_identifier(Dialect::MySQL), input.as_bytes());
+                assert_eq!(res, SqlType::Spatial(ty));
+            }
+        }
+
+        #[test]
+        fn spatial_type_round_trip() {
+            let ty = SqlType::Spatial(SpatialType::MultiLineString);
+            let displayed = ty.display(Dialect::MySQL).to_string();
+            let res = test_parse!(type_identifier(Dialect::MySQL), displayed.as_bytes());
+            assert_eq!(res, ty);
+        }
     }
 
     mod postgres {
@@ -1021,6 +1120,12 @@ mod tests {
             assert_eq!(res, SqlType::Uuid);
         }
 
+        #[test]
+        fn interval_type() {
+            let res = test_parse!(type_identifier(Dialect::PostgreSQL), b"interval");
+            assert_eq!(res, SqlType::Interval);
+        }
+
         #[test]
         fn json_type() {
             let res = test_parse!(type_identifier(Dialect::PostgreSQL), b"json");
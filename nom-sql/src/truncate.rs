@@ -0,0 +1,203 @@
+use std::fmt::Display;
+use std::str;
+
+use itertools::Itertools;
+use nom::branch::alt;
+use nom::bytes::complete::{tag, tag_no_case};
+use nom::combinator::{map, opt};
+use nom::multi::separated_list1;
+use nom::sequence::{preceded, terminated};
+use nom_locate::LocatedSpan;
+use readyset_util::fmt::fmt_with;
+use serde::{Deserialize, Serialize};
+
+use crate::common::{statement_terminator, ws_sep_comma};
+use crate::drop::restrict_cascade;
+use crate::table::{relation, Relation};
+use crate::whitespace::{whitespace0, whitespace1};
+use crate::{Dialect, NomSqlResult};
+
+/// A single table in a [`TruncateStatement`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct TruncateTable {
+    pub relation: Relation,
+    /// Whether only this table, and not any of its descendant tables, should be truncated
+    /// (Postgres only)
+    pub only: bool,
+}
+
+impl TruncateTable {
+    pub fn display(&self, dialect: Dialect) -> impl Display + Copy + '_ {
+        fmt_with(move |f| {
+            if self.only {
+                write!(f, "ONLY ")?;
+            }
+            write!(f, "{}", self.relation.display(dialect))
+        })
+    }
+}
+
+/// A `TRUNCATE` statement, which deletes all rows from one or more tables
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct TruncateStatement {
+    pub tables: Vec<TruncateTable>,
+    /// Whether sequences owned by the truncated tables should be reset (Postgres only)
+    pub restart_identity: bool,
+    /// Whether tables with foreign keys referencing the truncated tables should also be truncated
+    /// (Postgres only)
+    pub cascade: bool,
+}
+
+impl TruncateStatement {
+    pub fn display(&self, dialect: Dialect) -> impl Display + Copy + '_ {
+        fmt_with(move |f| {
+            write!(
+                f,
+                "TRUNCATE {}",
+                self.tables.iter().map(|t| t.display(dialect)).join(", ")
+            )?;
+
+            if self.restart_identity {
+                write!(f, " RESTART IDENTITY")?;
+            }
+            if self.cascade {
+                write!(f, " CASCADE")?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+fn truncate_table(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], TruncateTable> {
+    move |i| {
+        let (i, only) = opt(terminated(tag_no_case("only"), whitespace1))(i)?;
+        let (i, relation) = relation(dialect)(i)?;
+        let (i, _) = opt(preceded(whitespace0, tag("*")))(i)?;
+        Ok((
+            i,
+            TruncateTable {
+                relation,
+                only: only.is_some(),
+            },
+        ))
+    }
+}
+
+fn restart_identity(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], bool> {
+    let (i, restart) = alt((
+        map(tag_no_case("restart"), |_| true),
+        map(tag_no_case("continue"), |_| false),
+    ))(i)?;
+    let (i, _) = whitespace1(i)?;
+    let (i, _) = tag_no_case("identity")(i)?;
+    Ok((i, restart))
+}
+
+pub fn truncate(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], TruncateStatement> {
+    move |i| {
+        let (i, _) = tag_no_case("truncate")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = opt(terminated(tag_no_case("table"), whitespace1))(i)?;
+        let (i, tables) = separated_list1(ws_sep_comma, truncate_table(dialect))(i)?;
+        let (i, restart_identity) = opt(preceded(whitespace1, restart_identity))(i)?;
+        let (i, (_, cascade)) = restrict_cascade(i)?;
+        let (i, _) = statement_terminator(i)?;
+
+        Ok((
+            i,
+            TruncateStatement {
+                tables,
+                restart_identity: restart_identity.unwrap_or(false),
+                cascade,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod mysql {
+        use super::*;
+
+        #[test]
+        fn truncate_table() {
+            let res = test_parse!(truncate(Dialect::MySQL), b"TRUNCATE TABLE `db`.`t`;");
+            assert_eq!(
+                res,
+                TruncateStatement {
+                    tables: vec![TruncateTable {
+                        relation: Relation {
+                            schema: Some("db".into()),
+                            name: "t".into()
+                        },
+                        only: false
+                    }],
+                    ..Default::default()
+                }
+            );
+            assert_eq!(res.display(Dialect::MySQL).to_string(), "TRUNCATE `db`.`t`");
+        }
+
+        #[test]
+        fn without_table_keyword() {
+            let res = test_parse!(truncate(Dialect::MySQL), b"truncate t");
+            assert_eq!(
+                res.tables,
+                vec![TruncateTable {
+                    relation: "t".into(),
+                    only: false
+                }]
+            );
+        }
+    }
+
+    mod postgres {
+        use super::*;
+
+        #[test]
+        fn truncate_multiple_tables() {
+            let res = test_parse!(
+                truncate(Dialect::PostgreSQL),
+                b"TRUNCATE TABLE ONLY t1, t2 * RESTART IDENTITY CASCADE"
+            );
+            assert_eq!(
+                res,
+                TruncateStatement {
+                    tables: vec![
+                        TruncateTable {
+                            relation: "t1".into(),
+                            only: true
+                        },
+                        TruncateTable {
+                            relation: "t2".into(),
+                            only: false
+                        }
+                    ],
+                    restart_identity: true,
+                    cascade: true
+                }
+            );
+            assert_eq!(
+                res.display(Dialect::PostgreSQL).to_string(),
+                r#"TRUNCATE ONLY "t1", "t2" RESTART IDENTITY CASCADE"#
+            );
+        }
+
+        #[test]
+        fn continue_identity_restrict() {
+            let res = test_parse!(
+                truncate(Dialect::PostgreSQL),
+                b"truncate t continue identity restrict"
+            );
+            assert!(!res.restart_identity);
+            assert!(!res.cascade);
+        }
+    }
+}
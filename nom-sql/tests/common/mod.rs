@@ -0,0 +1,50 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use nom_sql::Dialect;
+
+/// Returns the path to the corpus of statements used by the display tests for the given dialect
+pub fn corpus_path(dialect: Dialect) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join(format!("display-{}.sql", dialect_name(dialect)))
+}
+
+fn dialect_name(dialect: Dialect) -> &'static str {
+    match dialect {
+        Dialect::MySQL => "mysql",
+        Dialect::PostgreSQL => "postgresql",
+    }
+}
+
+/// Load the corpus of statements for the given dialect.
+///
+/// Lines starting with `--` and blank lines are skipped, and each statement runs until the next
+/// line ending in `;`.
+pub fn load_corpus(dialect: Dialect) -> Vec<String> {
+    let path = corpus_path(dialect);
+    let contents = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Could not read {}: {e}", path.display()));
+
+    let mut statements = vec![];
+    let mut statement = String::new();
+    for line in contents
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with("--"))
+    {
+        if !statement.is_empty() && !statement.ends_with('(') && !line.starts_with(')') {
+            statement.push(' ');
+        }
+        statement.push_str(line);
+        if line.ends_with(';') {
+            statements.push(std::mem::take(&mut statement));
+        }
+    }
+    assert!(
+        statement.is_empty(),
+        "Unterminated statement at the end of {}",
+        path.display()
+    );
+    statements
+}
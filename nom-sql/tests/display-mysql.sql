@@ -0,0 +1,106 @@
+-- Corpus for the MySQL display round-trip and snapshot tests in `tests/display.rs`.
+--
+-- Every statement must be accepted by MySQL itself, in order, against an empty database, since
+-- the `display_upstream` tests run our display of each of them against a real MySQL server.
+-- Statements end with `;` and may span multiple lines.
+
+CREATE TABLE users (
+  id INT NOT NULL AUTO_INCREMENT,
+  name VARCHAR(255) NOT NULL,
+  email VARCHAR(255) DEFAULT NULL,
+  age TINYINT UNSIGNED,
+  balance DECIMAL(10,2) NOT NULL DEFAULT 0,
+  created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+  bio TEXT,
+  avatar BLOB,
+  PRIMARY KEY (id),
+  UNIQUE KEY email_idx (email),
+  KEY name_idx (name)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+CREATE TABLE posts (
+  id BIGINT NOT NULL,
+  author_id INT NOT NULL,
+  title VARCHAR(255) NOT NULL,
+  body MEDIUMTEXT,
+  score DOUBLE,
+  published BOOL NOT NULL DEFAULT FALSE,
+  kind ENUM('draft', 'post', 'page') NOT NULL,
+  published_on DATE,
+  PRIMARY KEY (id),
+  FULLTEXT KEY body_idx (body),
+  CONSTRAINT posts_author FOREIGN KEY (author_id) REFERENCES users (id)
+);
+CREATE TABLE `select` (`from` INT, `where` VARCHAR(10));
+CREATE TABLE tags (post_id BIGINT NOT NULL, tag CHAR(32) NOT NULL, PRIMARY KEY (post_id, tag));
+CREATE VIEW published_posts AS SELECT id, title FROM posts WHERE published = TRUE;
+ALTER TABLE users ADD COLUMN nickname VARCHAR(64);
+ALTER TABLE users DROP COLUMN nickname;
+ALTER TABLE tags ADD COLUMN weight INT DEFAULT 1;
+ALTER TABLE tags MODIFY COLUMN weight BIGINT;
+ALTER TABLE tags CHANGE COLUMN weight tag_weight BIGINT;
+ALTER TABLE tags RENAME COLUMN tag_weight TO weight;
+INSERT INTO users (name, email, age) VALUES ('alice', 'alice@example.com', 30);
+INSERT INTO users (name, email) VALUES ('bob', NULL), ('carol', 'carol@example.com');
+INSERT INTO users (id, name) VALUES (10, 'dave') ON DUPLICATE KEY UPDATE name = 'dave';
+INSERT INTO `select` (`from`, `where`) VALUES (1, 'it''s'), (2, 'a\\b');
+INSERT INTO posts (id, author_id, title, score, kind) VALUES (1, 1, 'hello', -1.5, 'post');
+UPDATE users SET age = age + 1 WHERE id = 1;
+UPDATE users SET email = NULL, age = 3 WHERE name LIKE 'b%' AND age IS NULL;
+DELETE FROM tags WHERE post_id = 1;
+DELETE FROM users WHERE id IN (10, 11, 12);
+SELECT * FROM users;
+SELECT users.* FROM users;
+SELECT id, name AS n FROM users WHERE id = 1;
+SELECT DISTINCT name FROM users;
+SELECT `from`, `where` FROM `select` WHERE `from` = 1;
+SELECT id FROM users WHERE name = 'it''s' OR name = 'a\\b';
+SELECT id FROM users WHERE age BETWEEN 18 AND 65;
+SELECT id FROM users WHERE age NOT BETWEEN 18 AND 65;
+SELECT id FROM users WHERE name NOT LIKE '%a%' AND email IS NOT NULL;
+SELECT id FROM users WHERE id IN (1, 2, 3) AND age NOT IN (4, 5);
+SELECT id FROM users WHERE id IN (SELECT author_id FROM posts);
+SELECT id FROM users WHERE EXISTS (SELECT 1 FROM posts WHERE posts.author_id = users.id);
+SELECT id FROM users WHERE NOT EXISTS (SELECT 1 FROM posts WHERE posts.author_id = users.id);
+SELECT id FROM users WHERE NOT (age > 3 OR age < 1);
+SELECT -age, age * 2 + 1, (age - 1) / 3 FROM users;
+SELECT id FROM users WHERE age > 1 AND (age < 10 OR age > 20);
+SELECT count(*), count(id), count(DISTINCT name) FROM users;
+SELECT sum(age), avg(age), min(age), max(age) FROM users;
+SELECT author_id, count(*) AS c FROM posts GROUP BY author_id HAVING count(*) > 1;
+SELECT author_id, group_concat(title SEPARATOR ', ') FROM posts GROUP BY author_id;
+SELECT id FROM users ORDER BY name DESC, id ASC;
+SELECT id FROM users ORDER BY name LIMIT 10;
+SELECT id FROM users ORDER BY name LIMIT 10 OFFSET 20;
+SELECT id FROM users LIMIT 5, 10;
+SELECT id FROM users WHERE id = ?;
+SELECT id FROM users WHERE id = ? AND name = ?;
+SELECT users.name, posts.title FROM users JOIN posts ON users.id = posts.author_id;
+SELECT users.name, posts.title FROM users INNER JOIN posts ON users.id = posts.author_id;
+SELECT users.name, posts.title FROM users LEFT JOIN posts ON users.id = posts.author_id;
+SELECT users.name, posts.title FROM users LEFT OUTER JOIN posts ON users.id = posts.author_id;
+SELECT users.name, posts.title FROM users RIGHT JOIN posts ON users.id = posts.author_id;
+SELECT u.name, p.title FROM users AS u JOIN posts AS p ON u.id = p.author_id;
+SELECT u.name, p.title FROM users u, posts p WHERE u.id = p.author_id;
+SELECT t1.tag FROM tags AS t1 JOIN tags AS t2 USING (post_id);
+SELECT sub.n FROM (SELECT name AS n FROM users) AS sub;
+SELECT name FROM users WHERE id = (SELECT max(author_id) FROM posts);
+SELECT CASE WHEN age > 18 THEN 'adult' ELSE 'minor' END FROM users;
+SELECT CASE WHEN age > 65 THEN 'senior' WHEN age > 18 THEN 'adult' END AS bucket FROM users;
+SELECT CAST(age AS CHAR) FROM users;
+SELECT CAST(balance AS SIGNED) FROM users;
+SELECT coalesce(email, name), ifnull(age, 0) FROM users;
+SELECT concat(name, ' <', email, '>') FROM users;
+SELECT substring(name, 1, 3), length(name), lower(name), upper(name) FROM users;
+SELECT round(balance, 1), abs(balance) FROM users;
+SELECT date_format(created_at, '%Y-%m-%d') FROM users;
+SELECT id FROM users WHERE created_at > '2020-01-01 00:00:00';
+SELECT TRUE, FALSE, NULL, 1, -1, 1.5, 'str', X'0A0B';
+SELECT id FROM users UNION SELECT author_id FROM posts;
+SELECT id FROM users UNION ALL SELECT author_id FROM posts;
+SELECT id FROM users UNION SELECT author_id FROM posts ORDER BY id LIMIT 3;
+WITH adults AS (SELECT id FROM users WHERE age >= 18) SELECT id FROM adults;
+SELECT * FROM published_posts;
+SELECT id FROM `select`;
+DROP VIEW published_posts;
+DROP TABLE IF EXISTS tags;
+DROP TABLE `select`, posts;
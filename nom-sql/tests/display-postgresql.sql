@@ -0,0 +1,92 @@
+-- Corpus for the PostgreSQL display round-trip and snapshot tests in `tests/display.rs`.
+--
+-- Every statement must be accepted by PostgreSQL itself, in order, against an empty schema,
+-- since the `display_upstream` tests run our display of each of them against a real PostgreSQL
+-- server. Statements end with `;` and may span multiple lines.
+
+CREATE TABLE users (
+  id INT NOT NULL,
+  name VARCHAR(255) NOT NULL,
+  email TEXT DEFAULT NULL,
+  age SMALLINT,
+  balance NUMERIC(10, 2) NOT NULL DEFAULT 0,
+  created_at TIMESTAMP,
+  created_at_tz TIMESTAMP WITH TIME ZONE,
+  avatar BYTEA,
+  settings JSONB,
+  uid UUID,
+  tags TEXT[],
+  PRIMARY KEY (id)
+);
+CREATE TABLE posts (
+  id BIGINT NOT NULL,
+  author_id INT NOT NULL,
+  title VARCHAR(255) NOT NULL,
+  body TEXT,
+  score DOUBLE PRECISION,
+  rating REAL,
+  published BOOLEAN NOT NULL DEFAULT FALSE,
+  published_on DATE,
+  PRIMARY KEY (id),
+  CONSTRAINT posts_author FOREIGN KEY (author_id) REFERENCES users (id)
+);
+CREATE TABLE "select" ("from" INT, "Where" VARCHAR(10));
+CREATE TABLE tags (post_id BIGINT NOT NULL, tag CHAR(32) NOT NULL, PRIMARY KEY (post_id, tag));
+CREATE VIEW published_posts AS SELECT id, title FROM posts WHERE published = TRUE;
+ALTER TABLE users ADD COLUMN nickname VARCHAR(64);
+ALTER TABLE users DROP COLUMN nickname;
+ALTER TABLE tags ADD COLUMN weight INT DEFAULT 1;
+ALTER TABLE tags RENAME COLUMN weight TO tag_weight;
+INSERT INTO users (id, name, email, age) VALUES (1, 'alice', 'alice@example.com', 30);
+INSERT INTO users (id, name, email) VALUES (2, 'bob', NULL), (3, 'carol', 'carol@example.com');
+INSERT INTO "select" ("from", "Where") VALUES (1, 'it''s');
+INSERT INTO posts (id, author_id, title, score) VALUES (1, 1, 'hello', -1.5);
+UPDATE users SET age = age + 1 WHERE id = 1;
+UPDATE users SET email = NULL, age = 3 WHERE name LIKE 'b%' AND age IS NULL;
+DELETE FROM tags WHERE post_id = 1;
+DELETE FROM users WHERE id IN (10, 11, 12);
+SELECT * FROM users;
+SELECT users.* FROM users;
+SELECT id, name AS n FROM users WHERE id = 1;
+SELECT DISTINCT name FROM users;
+SELECT "from", "Where" FROM "select" WHERE "from" = 1;
+SELECT id FROM users WHERE name = 'it''s';
+SELECT id FROM users WHERE age BETWEEN 18 AND 65;
+SELECT id FROM users WHERE name NOT LIKE '%a%' AND email IS NOT NULL;
+SELECT id FROM users WHERE name ILIKE '%A%';
+SELECT id FROM users WHERE id IN (1, 2, 3) AND age NOT IN (4, 5);
+SELECT id FROM users WHERE id IN (SELECT author_id FROM posts);
+SELECT id FROM users WHERE EXISTS (SELECT 1 FROM posts WHERE posts.author_id = users.id);
+SELECT id FROM users WHERE NOT (age > 3 OR age < 1);
+SELECT -age, age * 2 + 1, (age - 1) / 3 FROM users;
+SELECT count(*), count(id), count(DISTINCT name) FROM users;
+SELECT sum(age), avg(age), min(age), max(age) FROM users;
+SELECT author_id, count(*) AS c FROM posts GROUP BY author_id HAVING count(*) > 1;
+SELECT id FROM users ORDER BY name DESC, id ASC;
+SELECT id FROM users ORDER BY name LIMIT 10 OFFSET 20;
+SELECT id FROM users WHERE id = $1;
+SELECT id FROM users WHERE id = $1 AND name = $2;
+SELECT users.name, posts.title FROM users JOIN posts ON users.id = posts.author_id;
+SELECT users.name, posts.title FROM users LEFT JOIN posts ON users.id = posts.author_id;
+SELECT u.name, p.title FROM users AS u JOIN posts AS p ON u.id = p.author_id;
+SELECT sub.n FROM (SELECT name AS n FROM users) AS sub;
+SELECT name FROM users WHERE id = (SELECT max(author_id) FROM posts);
+SELECT CASE WHEN age > 18 THEN 'adult' ELSE 'minor' END FROM users;
+SELECT CAST(age AS TEXT) FROM users;
+SELECT age::TEXT, balance::INT FROM users;
+SELECT coalesce(email, name) FROM users;
+SELECT concat(name, ' <', email, '>') FROM users;
+SELECT substring(name from 1 for 3), length(name), lower(name), upper(name) FROM users;
+SELECT round(balance, 1), abs(balance) FROM users;
+SELECT settings -> 'a', settings ->> 'b', settings @> '{"a": 1}' FROM users;
+SELECT id FROM users WHERE id = ANY (ARRAY[1, 2, 3]);
+SELECT id FROM users WHERE created_at > '2020-01-01 00:00:00';
+SELECT TRUE, FALSE, NULL, 1, -1, 1.5, 'str', E'it\'s';
+SELECT E'\\x0a0b'::bytea, B'01010000';
+SELECT id FROM users UNION SELECT author_id FROM posts;
+SELECT id FROM users UNION ALL SELECT author_id FROM posts;
+WITH adults AS (SELECT id FROM users WHERE age >= 18) SELECT id FROM adults;
+SELECT * FROM published_posts;
+DROP VIEW published_posts;
+DROP TABLE IF EXISTS tags;
+DROP TABLE "select", posts;
@@ -0,0 +1,88 @@
+//! Tests that the `Display` implementations for the AST round-trip through the parser, and that
+//! they don't change unexpectedly.
+//!
+//! Every statement in `tests/display-<dialect>.sql` is parsed, displayed, and parsed again, and
+//! must produce the same AST both times. The displayed statements are also compared against the
+//! snapshot in `tests/display-<dialect>.snap`; to update the snapshots after an intentional change
+//! to how statements are displayed, run:
+//!
+//! ```notrust
+//! UPDATE_SNAPSHOTS=1 cargo test -p nom-sql --test display
+//! ```
+
+use std::{env, fs};
+
+use nom_sql::{parse_query, Dialect};
+use pretty_assertions::assert_eq;
+
+mod common;
+
+fn display(dialect: Dialect, statement: &str) -> String {
+    parse_query(dialect, statement)
+        .unwrap_or_else(|e| panic!("Could not parse `{statement}`: {e}"))
+        .display(dialect)
+        .to_string()
+}
+
+fn display_round_trips(dialect: Dialect) {
+    for statement in common::load_corpus(dialect) {
+        let parsed = parse_query(dialect, &statement)
+            .unwrap_or_else(|e| panic!("Could not parse `{statement}`: {e}"));
+        let displayed = parsed.display(dialect).to_string();
+        let reparsed = parse_query(dialect, &displayed).unwrap_or_else(|e| {
+            panic!("Could not parse `{displayed}` (the display of `{statement}`): {e}")
+        });
+        assert_eq!(
+            reparsed, parsed,
+            "`{statement}` displayed as `{displayed}`, which parses differently"
+        );
+        assert_eq!(
+            reparsed.display(dialect).to_string(),
+            displayed,
+            "Display of `{statement}` is not stable"
+        );
+    }
+}
+
+fn display_matches_snapshot(dialect: Dialect) {
+    let mut displayed = String::new();
+    for statement in common::load_corpus(dialect) {
+        displayed.push_str(&display(dialect, &statement));
+        displayed.push('\n');
+    }
+
+    let path = common::corpus_path(dialect).with_extension("snap");
+    if env::var("UPDATE_SNAPSHOTS").is_ok() {
+        fs::write(&path, displayed).unwrap();
+        return;
+    }
+
+    let snapshot = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Could not read snapshot {}: {e}", path.display()));
+    assert_eq!(
+        snapshot,
+        displayed,
+        "Display output differs from {}; if this is intentional, re-run with UPDATE_SNAPSHOTS=1",
+        path.display()
+    );
+}
+
+#[test]
+fn mysql_display_round_trips() {
+    display_round_trips(Dialect::MySQL);
+}
+
+#[test]
+fn postgresql_display_round_trips() {
+    display_round_trips(Dialect::PostgreSQL);
+}
+
+#[test]
+fn mysql_display_matches_snapshot() {
+    display_matches_snapshot(Dialect::MySQL);
+}
+
+#[test]
+fn postgresql_display_matches_snapshot() {
+    display_matches_snapshot(Dialect::PostgreSQL);
+}
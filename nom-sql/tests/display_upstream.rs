@@ -0,0 +1,129 @@
+//! Tests that MySQL and PostgreSQL themselves accept our display of every statement in the corpus
+//! used by the display round-trip tests in `tests/display.rs`.
+//!
+//! Note that these tests are ignored by default, and conditionally de-ignored with the
+//! `upstream_display_tests` feature, since they need running MySQL and PostgreSQL databases; to run
+//! them locally run:
+//!
+//! ```notrust
+//! cargo test -p nom-sql --features upstream_display_tests --test display_upstream
+//! ```
+//!
+//! The databases can be configured with the same environment variables as the rest of our tests
+//! against upstream databases (`MYSQL_HOST`, `PGHOST`, etc.)
+
+use std::env;
+
+use mysql_async::prelude::Queryable;
+use nom_sql::{parse_query, Dialect};
+
+mod common;
+
+/// The database (in MySQL) or schema (in PostgreSQL) to run the corpus in, which is dropped and
+/// recreated at the start of each test
+const DATABASE: &str = "nom_sql_display";
+
+fn displayed_corpus(dialect: Dialect) -> Vec<String> {
+    common::load_corpus(dialect)
+        .into_iter()
+        .map(|statement| {
+            parse_query(dialect, &statement)
+                .unwrap_or_else(|e| panic!("Could not parse `{statement}`: {e}"))
+                .display(dialect)
+                .to_string()
+        })
+        .collect()
+}
+
+fn mysql_opts() -> mysql_async::Opts {
+    mysql_async::OptsBuilder::default()
+        .ip_or_hostname(env::var("MYSQL_HOST").unwrap_or_else(|_| "127.0.0.1".into()))
+        .tcp_port(
+            env::var("MYSQL_TCP_PORT")
+                .unwrap_or_else(|_| "3306".into())
+                .parse()
+                .unwrap(),
+        )
+        .user(Some(
+            env::var("MYSQL_USER").unwrap_or_else(|_| "root".into()),
+        ))
+        .pass(Some(
+            env::var("MYSQL_PASSWORD").unwrap_or_else(|_| "noria".into()),
+        ))
+        .into()
+}
+
+fn postgres_config() -> tokio_postgres::Config {
+    let mut config = tokio_postgres::Config::new();
+    config
+        .host(env::var("PGHOST").as_deref().unwrap_or("localhost"))
+        .port(
+            env::var("PGPORT")
+                .unwrap_or_else(|_| "5432".into())
+                .parse()
+                .unwrap(),
+        )
+        .user(env::var("PGUSER").as_deref().unwrap_or("postgres"))
+        .password(env::var("PGPASSWORD").unwrap_or_else(|_| "noria".into()))
+        .dbname(env::var("PGDATABASE").as_deref().unwrap_or("noria"));
+    config
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "upstream_display_tests"), ignore)]
+async fn mysql_accepts_display() {
+    let mut conn = mysql_async::Conn::new(mysql_opts()).await.unwrap();
+    conn.query_drop(format!("DROP DATABASE IF EXISTS {DATABASE}"))
+        .await
+        .unwrap();
+    conn.query_drop(format!("CREATE DATABASE {DATABASE}"))
+        .await
+        .unwrap();
+    conn.query_drop(format!("USE {DATABASE}")).await.unwrap();
+
+    for statement in displayed_corpus(Dialect::MySQL) {
+        // Preparing the statement checks that MySQL accepts it, even if it has placeholders; then
+        // we run it if we can, so that later statements can see its effects
+        let prepared = conn
+            .prep(&statement)
+            .await
+            .unwrap_or_else(|e| panic!("MySQL rejected `{statement}`: {e}"));
+        if prepared.num_params() == 0 {
+            conn.query_drop(&statement)
+                .await
+                .unwrap_or_else(|e| panic!("Error running `{statement}` in MySQL: {e}"));
+        }
+    }
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "upstream_display_tests"), ignore)]
+async fn postgresql_accepts_display() {
+    let (client, conn) = postgres_config()
+        .connect(tokio_postgres::NoTls)
+        .await
+        .unwrap();
+    tokio::spawn(conn);
+    client
+        .batch_execute(&format!(
+            "DROP SCHEMA IF EXISTS {DATABASE} CASCADE; CREATE SCHEMA {DATABASE}; \
+             SET search_path TO {DATABASE}"
+        ))
+        .await
+        .unwrap();
+
+    for statement in displayed_corpus(Dialect::PostgreSQL) {
+        // Preparing the statement checks that PostgreSQL accepts it, even if it has placeholders;
+        // then we run it if we can, so that later statements can see its effects
+        let prepared = client
+            .prepare(&statement)
+            .await
+            .unwrap_or_else(|e| panic!("PostgreSQL rejected `{statement}`: {e}"));
+        if prepared.params().is_empty() {
+            client
+                .batch_execute(&statement)
+                .await
+                .unwrap_or_else(|e| panic!("Error running `{statement}` in PostgreSQL: {e}"));
+        }
+    }
+}
@@ -8,7 +8,7 @@ use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
 use cidr::IpInet;
 use eui48::MacAddress;
 use postgres_types::{FromSql, Kind, Type};
-use readyset_data::{Array, Collation};
+use readyset_data::{Array, Collation, Interval};
 use rust_decimal::prelude::FromStr;
 use rust_decimal::Decimal;
 use tokio_util::codec::Decoder;
@@ -43,8 +43,10 @@ const DESCRIBE_TYPE_PORTAL: u8 = b'P';
 const DESCRIBE_TYPE_PREPARED_STATEMENT: u8 = b'S';
 
 const SSL_REQUEST_CODE: i32 = 80877103;
+const CANCEL_REQUEST_CODE: i32 = 80877102;
 
 const STARTUP_MESSAGE_DATABASE_PARAMETER: &str = "database";
+const STARTUP_MESSAGE_APPLICATION_NAME_PARAMETER: &str = "application_name";
 const STARTUP_MESSAGE_TERMINATOR: &str = "";
 const STARTUP_MESSAGE_USER_PARAMETER: &str = "user";
 
@@ -95,11 +97,16 @@ impl<R: IntoIterator<Item: TryInto<Value, Error = BackendError>>> Decoder for Co
             let token = get_i32(msg)?;
             let ret = match token {
                 SSL_REQUEST_CODE => Ok(Some(SSLRequest)),
+                CANCEL_REQUEST_CODE => Ok(Some(CancelRequest {
+                    process_id: get_i32(msg)?,
+                    secret_key: get_i32(msg)?,
+                })),
 
                 // Parse StartupMessage
                 protocol_version => {
                     let mut user: Option<BytesStr> = None;
                     let mut database: Option<BytesStr> = None;
+                    let mut application_name: Option<BytesStr> = None;
                     loop {
                         let key = get_str(msg)?;
                         if key.borrow() as &str == STARTUP_MESSAGE_TERMINATOR {
@@ -110,12 +117,16 @@ impl<R: IntoIterator<Item: TryInto<Value, Error = BackendError>>> Decoder for Co
                             user = Some(val);
                         } else if key.borrow() as &str == STARTUP_MESSAGE_DATABASE_PARAMETER {
                             database = Some(val);
+                        } else if key.borrow() as &str == STARTUP_MESSAGE_APPLICATION_NAME_PARAMETER
+                        {
+                            application_name = Some(val);
                         }
                     }
                     Ok(Some(StartupMessage {
                         protocol_version,
                         user,
                         database,
+                        application_name,
                     }))
                 }
             };
@@ -386,6 +397,7 @@ fn get_binary_value(src: &mut Bytes, t: &Type) -> Result<Value, Error> {
             Type::MACADDR => Ok(Value::MacAddress(MacAddress::from_sql(t, buf)?)),
             Type::INET => Ok(Value::Inet(IpInet::from_sql(t, buf)?)),
             Type::UUID => Ok(Value::Uuid(Uuid::from_sql(t, buf)?)),
+            Type::INTERVAL => Ok(Value::Interval(Interval::from_sql(t, buf)?)),
             Type::JSON => Ok(Value::Json(serde_json::Value::from_sql(t, buf)?)),
             Type::JSONB => Ok(Value::Jsonb(serde_json::Value::from_sql(t, buf)?)),
             Type::BIT => Ok(Value::Bit(BitVec::from_sql(t, buf)?)),
@@ -470,6 +482,10 @@ fn get_text_value(src: &mut Bytes, t: &Type) -> Result<Value, Error> {
         Type::UUID => Uuid::parse_str(text_str)
             .map_err(DecodeError::InvalidTextUuidValue)
             .map(Value::Uuid),
+        Type::INTERVAL => text_str
+            .parse::<Interval>()
+            .map_err(|e| DecodeError::InvalidTextIntervalValue(e.to_string()))
+            .map(Value::Interval),
         Type::JSON => serde_json::from_str::<serde_json::Value>(text_str)
             .map_err(DecodeError::InvalidTextJsonValue)
             .map(Value::Json),
@@ -527,21 +543,41 @@ mod tests {
         codec.decode(&mut buf).unwrap_err();
     }
 
+    #[test]
+    fn test_decode_cancel_request() {
+        let mut codec = Codec::<Vec<Value>>::new();
+        let mut buf = BytesMut::new();
+        buf.put_i32(16); // size
+        buf.put_i32(80877102); // cancel request code
+        buf.put_i32(42); // process id
+        buf.put_i32(1234); // secret key
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(CancelRequest {
+                process_id: 42,
+                secret_key: 1234
+            })
+        );
+    }
+
     #[test]
     fn test_decode_startup_message() {
         let mut codec = Codec::<Vec<Value>>::new();
         let mut buf = BytesMut::new();
-        buf.put_i32(4 + 4 + 5 + 10 + 9 + 14 + 1); // size
+        buf.put_i32(4 + 4 + 5 + 10 + 9 + 14 + 17 + 5 + 1); // size
         buf.put_i32(196608); // standard protocol version
         buf.extend_from_slice(b"user\0");
         buf.extend_from_slice(b"user_name\0");
         buf.extend_from_slice(b"database\0");
         buf.extend_from_slice(b"database_name\0");
+        buf.extend_from_slice(b"application_name\0");
+        buf.extend_from_slice(b"psql\0");
         buf.put_u8(b'\0');
         let expected = Some(StartupMessage {
             protocol_version: 196608,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: Some(bytes_str("psql")),
         });
         assert_eq!(codec.decode(&mut buf).unwrap(), expected);
     }
@@ -1188,6 +1224,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_decode_binary_interval() {
+        let interval = Interval::new(14, -3, 14_706_000_000);
+        let mut buf = BytesMut::new();
+        buf.put_i32(16);
+        interval.to_sql(&Type::INTERVAL, &mut buf).unwrap(); // add value
+        assert_eq!(
+            get_binary_value(&mut buf.freeze(), &Type::INTERVAL).unwrap(),
+            DataValue::Interval(interval)
+        );
+    }
+
     #[test]
     fn test_decode_binary_json() {
         let json = serde_json::from_str::<serde_json::Value>(
@@ -17,11 +17,13 @@ use crate::scram::{SCRAM_SHA_256_AUTHENTICATION_METHOD, SCRAM_SHA_256_SSL_AUTHEN
 use crate::value::Value;
 
 const ID_AUTHENTICATION_REQUEST: u8 = b'R';
+const ID_BACKEND_KEY_DATA: u8 = b'K';
 const ID_BIND_COMPLETE: u8 = b'2';
 const ID_CLOSE_COMPLETE: u8 = b'3';
 const ID_COMMAND_COMPLETE: u8 = b'C';
 const ID_DATA_ROW: u8 = b'D';
 const ID_ERROR_RESPONSE: u8 = b'E';
+const ID_NOTIFICATION_RESPONSE: u8 = b'A';
 const ID_PARAMETER_DESCRIPTION: u8 = b't';
 const ID_PARAMETER_STATUS: u8 = b'S';
 const ID_PARSE_COMPLETE: u8 = b'1';
@@ -149,6 +151,16 @@ where
             put_i32(AUTHENTICATION_OK_SUCCESS, dst);
         }
 
+        BackendKeyData {
+            process_id,
+            secret_key,
+        } => {
+            put_u8(ID_BACKEND_KEY_DATA, dst);
+            put_i32(LENGTH_PLACEHOLDER, dst);
+            put_i32(process_id, dst);
+            put_i32(secret_key, dst);
+        }
+
         BindComplete => {
             put_u8(ID_BIND_COMPLETE, dst);
             put_i32(LENGTH_PLACEHOLDER, dst);
@@ -331,6 +343,18 @@ where
             put_u8(ERROR_RESPONSE_TERMINATOR, dst);
         }
 
+        NotificationResponse {
+            process_id,
+            channel,
+            payload,
+        } => {
+            put_u8(ID_NOTIFICATION_RESPONSE, dst);
+            put_i32(LENGTH_PLACEHOLDER, dst);
+            put_i32(process_id, dst);
+            put_str(&channel, dst);
+            put_str(&payload, dst);
+        }
+
         ParameterDescription {
             parameter_data_types,
         } => {
@@ -539,6 +563,9 @@ fn put_binary_value(val: Value, dst: &mut BytesMut) -> Result<(), Error> {
         Value::Uuid(u) => {
             u.to_sql(&Type::UUID, dst)?;
         }
+        Value::Interval(i) => {
+            i.to_sql(&Type::INTERVAL, dst)?;
+        }
         Value::Json(v) => {
             v.to_sql(&Type::JSON, dst)?;
         }
@@ -648,6 +675,7 @@ fn put_text_value(val: Value, dst: &mut BytesMut) -> Result<(), Error> {
         Value::MacAddress(m) => write!(dst, "{}", m.to_string(MacAddressFormat::HexString))?,
         Value::Inet(ip) => write!(dst, "{}", ip)?,
         Value::Uuid(u) => write!(dst, "{}", u)?,
+        Value::Interval(i) => write!(dst, "{}", i)?,
         Value::Json(v) => write!(dst, "{}", v)?,
         Value::Jsonb(v) => write!(dst, "{}", v)?,
         Value::Bit(bits) | Value::VarBit(bits) => write!(
@@ -683,6 +711,7 @@ mod tests {
     use eui48::MacAddress;
     use postgres::SimpleQueryRow;
     use postgres_protocol::message::backend::DataRowBody;
+    use readyset_data::Interval;
     use rust_decimal::Decimal;
     use tokio_postgres::OwnedField;
     use uuid::Uuid;
@@ -739,6 +768,27 @@ mod tests {
         assert_eq!(buf, exp);
     }
 
+    #[test]
+    fn test_encode_backend_key_data() {
+        let mut codec = Codec::<Vec<Value>>::new();
+        let mut buf = BytesMut::new();
+        codec
+            .encode(
+                BackendKeyData {
+                    process_id: 42,
+                    secret_key: 1234,
+                },
+                &mut buf,
+            )
+            .unwrap();
+        let mut exp = BytesMut::new();
+        exp.put_u8(b'K'); // message id
+        exp.put_i32(12); // message length
+        exp.put_i32(42); // process id
+        exp.put_i32(1234); // secret key
+        assert_eq!(buf, exp);
+    }
+
     #[test]
     fn test_encode_bind_complete() {
         let mut codec = Codec::<Vec<Value>>::new();
@@ -1047,6 +1097,29 @@ mod tests {
         assert_eq!(buf, exp);
     }
 
+    #[test]
+    fn test_encode_notification_response() {
+        let mut codec = Codec::<Vec<Value>>::new();
+        let mut buf = BytesMut::new();
+        codec
+            .encode(
+                NotificationResponse {
+                    process_id: 42,
+                    channel: "chan".to_string(),
+                    payload: "hi".to_string(),
+                },
+                &mut buf,
+            )
+            .unwrap();
+        let mut exp = BytesMut::new();
+        exp.put_u8(b'A'); // message id
+        exp.put_i32(4 + 4 + 5 + 3); // message length
+        exp.put_i32(42); // process id
+        exp.extend_from_slice(b"chan\0");
+        exp.extend_from_slice(b"hi\0");
+        assert_eq!(buf, exp);
+    }
+
     #[test]
     fn test_encode_parameter_description() {
         let mut codec = Codec::<Vec<Value>>::new();
@@ -1655,6 +1728,20 @@ mod tests {
         assert_eq!(buf, exp);
     }
 
+    #[test]
+    fn test_encode_text_interval() {
+        let mut buf = BytesMut::new();
+        put_text_value(
+            DataValue::Interval(Interval::new(14, 3, 14_706_000_000)),
+            &mut buf,
+        )
+        .unwrap();
+        let mut exp = BytesMut::new();
+        exp.put_i32(29); // length (placeholder)
+        exp.extend_from_slice(b"1 year 2 mons 3 days 04:05:06");
+        assert_eq!(buf, exp);
+    }
+
     #[test]
     fn test_encode_text_json() {
         let mut buf = BytesMut::new();
@@ -28,6 +28,9 @@ pub enum Error {
     #[error("internal error: {0}")]
     InternalError(String),
 
+    #[error("permission denied: {0}")]
+    InsufficientPrivilege(String),
+
     #[error("invalid integer: {0}")]
     InvalidInteger(#[from] TryFromIntError),
 
@@ -43,6 +46,15 @@ pub enum Error {
     #[error("parse error: {0}")]
     ParseError(String),
 
+    #[error("query timeout: {0}")]
+    QueryTimeout(String),
+
+    #[error("canceling statement due to user request: {0}")]
+    QueryCancelled(String),
+
+    #[error("result limit exceeded: {0}")]
+    ResultLimitExceeded(String),
+
     #[error("unexpected message: {0}")]
     UnexpectedMessage(String),
 
@@ -77,11 +89,14 @@ impl<R> From<Error> for BackendMessage<R> {
             Error::EncodeError(_) => SqlState::IO_ERROR,
             Error::IncorrectFormatCount(_) => SqlState::IO_ERROR,
             Error::InternalError(_) => SqlState::INTERNAL_ERROR,
+            Error::InsufficientPrivilege(_) => SqlState::INSUFFICIENT_PRIVILEGE,
             Error::InvalidInteger(_) => SqlState::DATATYPE_MISMATCH,
             Error::IoError(_) => SqlState::IO_ERROR,
             Error::MissingPortal(_) => SqlState::UNDEFINED_PSTATEMENT,
             Error::MissingPreparedStatement(_) => SqlState::UNDEFINED_PSTATEMENT,
             Error::ParseError(_) => SqlState::INVALID_PSTATEMENT_DEFINITION,
+            Error::QueryTimeout(_) | Error::QueryCancelled(_) => SqlState::QUERY_CANCELED,
+            Error::ResultLimitExceeded(_) => SqlState::PROGRAM_LIMIT_EXCEEDED,
             Error::Unimplemented(_) => SqlState::FEATURE_NOT_SUPPORTED,
             Error::UnexpectedMessage(_) => SqlState::PROTOCOL_VIOLATION,
             Error::Unknown(_) => SqlState::INTERNAL_ERROR,
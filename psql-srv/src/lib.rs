@@ -88,6 +88,28 @@ pub trait Backend {
     /// Look up authentication credentials for the given user
     fn credentials_for_user(&self, user: &str) -> Option<Credentials>;
 
+    /// Called with the `application_name` sent by the client in its startup message, if any
+    fn on_application_name(&mut self, _application_name: &str) {}
+
+    /// Called once the client has successfully authenticated as the given user, before the client
+    /// is told that authentication succeeded. If this returns an error, authentication fails with
+    /// that error.
+    async fn on_authenticated(&mut self, _user: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Returns the process id and secret key which identify this connection in cancel requests
+    /// sent by the client on other connections, or [`None`] if queries on this connection can't be
+    /// cancelled
+    fn cancel_key(&self) -> Option<(i32, i32)> {
+        None
+    }
+
+    /// Called when a client connects to send a cancel request for the query running on the
+    /// connection identified by the given process id and secret key, instead of starting up a
+    /// session. The connection is closed afterwards.
+    async fn on_cancel_request(&mut self, _process_id: i32, _secret_key: i32) {}
+
     /// Performs the specified SQL query.
     ///
     /// * `query` - The sql query to perform.
@@ -122,6 +144,45 @@ pub trait Backend {
     ///
     /// * `statement_id` - The identifier of the prepared statement to close.
     async fn on_close(&mut self, statement_id: u32) -> Result<(), Error>;
+
+    /// Returns whether the connection is currently within a transaction, which is reported to the
+    /// client each time the backend becomes ready for a new query. By default, connections are
+    /// never reported to be within a transaction.
+    fn transaction_status(&self) -> TransactionStatus {
+        TransactionStatus::Idle
+    }
+
+    /// Waits for the next asynchronous notification (eg one received as a result of a `LISTEN`
+    /// statement) to relay to the client. This is only polled while the client is idle, between
+    /// requests, and must be cancellation safe, since it's cancelled whenever the client sends a
+    /// new request. By default, never returns.
+    async fn next_notification(&mut self) -> Notification {
+        futures::future::pending().await
+    }
+}
+
+/// The transaction status of a connection, as reported to the client in `ReadyForQuery` messages
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TransactionStatus {
+    /// Not within a transaction block
+    Idle,
+    /// Within a transaction block
+    InTransaction,
+    /// Within a failed transaction block, in which statements are rejected until the transaction
+    /// is ended
+    Failed,
+}
+
+/// An asynchronous notification to relay to the client, sent by the backend process identified by
+/// `process_id` on the given `channel` with a `NOTIFY` statement or the `pg_notify` function
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Notification {
+    /// The process id of the backend that sent the notification
+    pub process_id: i32,
+    /// The name of the channel the notification was sent on
+    pub channel: String,
+    /// The payload of the notification, which is empty if none was given
+    pub payload: String,
 }
 
 /// A description of a column, either in the parameters to a query or in a resultset
@@ -192,7 +253,9 @@ where
     B: Backend,
     C: AsyncRead + AsyncWrite + Unpin,
 {
-    let packet = Protocol::new().on_error::<B>(error).await?;
+    let packet = Protocol::new()
+        .on_error::<B>(error, TransactionStatus::Idle)
+        .await?;
     channel::Channel::new(channel).send(packet).await?;
     Ok(())
 }
@@ -11,8 +11,11 @@ use tokio_postgres::OwnedField;
 use crate::error::Error;
 use crate::message::TransferFormat;
 use crate::value::Value;
+use crate::TransactionStatus;
 
 const READY_FOR_QUERY_IDLE: u8 = b'I';
+const READY_FOR_QUERY_IN_TRANSACTION: u8 = b'T';
+const READY_FOR_QUERY_FAILED: u8 = b'E';
 const SSL_RESPONSE_UNWILLING: u8 = b'N';
 const SSL_RESPONSE_WILLING: u8 = b'S';
 
@@ -41,6 +44,10 @@ pub enum BackendMessage<R> {
         sasl_data: Bytes,
     },
     AuthenticationOk,
+    BackendKeyData {
+        process_id: i32,
+        secret_key: i32,
+    },
     BindComplete,
     CloseComplete,
     CommandComplete {
@@ -68,6 +75,11 @@ pub enum BackendMessage<R> {
         line: Option<u32>,
         routine: Option<String>,
     },
+    NotificationResponse {
+        process_id: i32,
+        channel: String,
+        payload: String,
+    },
     ParameterDescription {
         parameter_data_types: Vec<Type>,
     },
@@ -91,8 +103,16 @@ pub enum BackendMessage<R> {
 
 impl<R: IntoIterator<Item: TryInto<Value, Error = Error>>> BackendMessage<R> {
     pub fn ready_for_query_idle() -> BackendMessage<R> {
+        Self::ready_for_query(TransactionStatus::Idle)
+    }
+
+    pub fn ready_for_query(transaction_status: TransactionStatus) -> BackendMessage<R> {
         BackendMessage::ReadyForQuery {
-            status: READY_FOR_QUERY_IDLE,
+            status: match transaction_status {
+                TransactionStatus::Idle => READY_FOR_QUERY_IDLE,
+                TransactionStatus::InTransaction => READY_FOR_QUERY_IN_TRANSACTION,
+                TransactionStatus::Failed => READY_FOR_QUERY_FAILED,
+            },
         }
     }
 
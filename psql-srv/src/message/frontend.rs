@@ -52,10 +52,17 @@ pub enum FrontendMessage {
         query: BytesStr,
     },
     SSLRequest,
+    /// A request, sent on a new connection instead of a `StartupMessage`, to cancel the query
+    /// running on the connection identified by the given process id and secret key
+    CancelRequest {
+        process_id: i32,
+        secret_key: i32,
+    },
     StartupMessage {
         protocol_version: i32,
         user: Option<BytesStr>,
         database: Option<BytesStr>,
+        application_name: Option<BytesStr>,
     },
     SaslResponse {
         scram_data: Bytes,
@@ -82,6 +89,7 @@ impl fmt::Display for FrontendMessage {
             Self::Parse { .. } => write!(f, "Parse"),
             Self::Query { .. } => write!(f, "Query"),
             Self::SSLRequest => write!(f, "SSLRequest"),
+            Self::CancelRequest { .. } => write!(f, "CancelRequest"),
             Self::StartupMessage { .. } => write!(f, "StartupMessage"),
             Self::SaslResponse { .. } => write!(f, "SASLResponse"),
             Self::Sync => write!(f, "Sync"),
@@ -5,7 +5,7 @@ use std::sync::Arc;
 use postgres::SimpleQueryMessage;
 use postgres_protocol::Oid;
 use postgres_types::{Kind, Type};
-use smallvec::smallvec;
+use smallvec::{smallvec, SmallVec};
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_postgres::CommandCompleteContents;
 
@@ -25,7 +25,7 @@ use crate::scram::{
 };
 use crate::value::Value;
 use crate::QueryResponse::*;
-use crate::{Backend, Column, Credentials, PrepareResponse};
+use crate::{Backend, Column, Credentials, PrepareResponse, TransactionStatus};
 
 const ATTTYPMOD_NONE: i32 = -1;
 const TRANSFER_FORMAT_PLACEHOLDER: TransferFormat = TransferFormat::Text;
@@ -67,6 +67,7 @@ pub(crate) enum SaslState {
 ///
 /// * StartingUp -> SslHandshake
 /// * SslHandshake -> StartingUp
+/// * StartingUp -> Cancelled
 /// * StartingUp -> Ready
 /// * StartingUp -> AuthenticatingCleartext
 /// * StartingUp -> AuthenticatingSasl
@@ -81,6 +82,10 @@ pub(crate) enum State {
     /// The server is starting up
     StartingUp,
 
+    /// The client connected to send a cancel request for another connection, which has been
+    /// handled, and the connection should be closed
+    Cancelled,
+
     /// The client is performing authentication using the cleartext password protocol
     AuthenticatingCleartext { user: BytesStr },
 
@@ -199,8 +204,14 @@ impl Protocol {
         backend: &mut B,
         channel: &mut Channel<C, B::Row>,
     ) -> Result<Response<B::Row, B::Resultset>, Error> {
-        let get_ready_message = |version| {
-            smallvec![
+        let get_ready_message = |backend: &B| {
+            let cancel_key = backend.cancel_key().map(|(process_id, secret_key)| {
+                BackendMessage::BackendKeyData {
+                    process_id,
+                    secret_key,
+                }
+            });
+            let mut messages: SmallVec<[BackendMessage<B::Row>; 2]> = smallvec![
                 AuthenticationOk,
                 BackendMessage::ParameterStatus {
                     parameter_name: "client_encoding".to_owned(),
@@ -220,10 +231,14 @@ impl Protocol {
                 },
                 BackendMessage::ParameterStatus {
                     parameter_name: "server_version".to_owned(),
-                    parameter_value: version,
+                    parameter_value: backend.version(),
                 },
-                BackendMessage::ready_for_query_idle(),
-            ]
+            ];
+            messages.extend(cancel_key);
+            messages.push(BackendMessage::ready_for_query(
+                backend.transaction_status(),
+            ));
+            messages
         };
         match self.state {
             State::StartingUp => match message {
@@ -239,14 +254,33 @@ impl Protocol {
                     }
                 }
 
+                // A request to cancel the query running on another connection, sent on a new
+                // connection instead of a StartupMessage
+                CancelRequest {
+                    process_id,
+                    secret_key,
+                } => {
+                    backend.on_cancel_request(process_id, secret_key).await;
+                    self.state = State::Cancelled;
+                    Ok(Response::Empty)
+                }
+
                 // A request to start up a connection, with some metadata provided.
-                StartupMessage { database, user, .. } => {
+                StartupMessage {
+                    database,
+                    user,
+                    application_name,
+                    ..
+                } => {
                     let database = database
                         .ok_or_else(|| Error::Unsupported("database is required".to_string()))?;
+                    if let Some(application_name) = application_name {
+                        backend.on_application_name(application_name.borrow());
+                    }
                     let response = match backend.on_init(database.borrow()).await? {
                         crate::CredentialsNeeded::None => {
                             self.state = State::Ready;
-                            get_ready_message(backend.version())
+                            get_ready_message(&*backend)
                         }
                         crate::CredentialsNeeded::Cleartext => {
                             self.state = State::AuthenticatingCleartext {
@@ -289,10 +323,11 @@ impl Protocol {
                         .ok_or_else(|| Error::AuthenticationFailure {
                             username: user.to_string(),
                         })?;
+                    backend.on_authenticated(user).await?;
 
                     self.state = State::Ready;
 
-                    Ok(Response::Messages(get_ready_message(backend.version())))
+                    Ok(Response::Messages(get_ready_message(&*backend)))
                 }
 
                 m => Err(Error::UnsupportedMessage(m)),
@@ -311,7 +346,7 @@ impl Protocol {
                     }
                     Some(Credentials::Any) => {
                         self.state = State::Ready;
-                        return Ok(Response::Messages(get_ready_message(backend.version())));
+                        return Ok(Response::Messages(get_ready_message(&*backend)));
                     }
                     Some(Credentials::CleartextPassword(pw)) => pw,
                 };
@@ -403,11 +438,12 @@ impl Protocol {
                         .then_some(self.tls_server_end_point.as_deref())
                         .flatten(),
                 )? {
+                    backend.on_authenticated(user).await?;
                     self.state = State::Ready;
                     let mut messages = vec![BackendMessage::AuthenticationSaslFinal {
                         sasl_data: server_final_message.to_string().into(),
                     }];
-                    messages.extend(get_ready_message(backend.version()));
+                    messages.extend(get_ready_message(&*backend));
                     Ok(Response::Messages(messages.into()))
                 } else {
                     Err(Error::AuthenticationFailure {
@@ -419,7 +455,9 @@ impl Protocol {
             State::Error => match message {
                 Sync => {
                     self.state = State::Ready;
-                    Ok(Response::Message(BackendMessage::ready_for_query_idle()))
+                    Ok(Response::Message(BackendMessage::ready_for_query(
+                        backend.transaction_status(),
+                    )))
                 }
                 _ => Ok(Response::Empty),
             },
@@ -618,7 +656,9 @@ impl Protocol {
                             header: Some(RowDescription { field_descriptions }),
                             resultset,
                             result_transfer_formats: None,
-                            trailer: Some(BackendMessage::ready_for_query_idle()),
+                            trailer: Some(BackendMessage::ready_for_query(
+                                backend.transaction_status(),
+                            )),
                         })
                     } else if let SimpleQuery(resp) = response {
                         let mut messages = smallvec![];
@@ -658,7 +698,9 @@ impl Protocol {
                                 }
                             }
                         }
-                        messages.push(BackendMessage::ready_for_query_idle());
+                        messages.push(BackendMessage::ready_for_query(
+                            backend.transaction_status(),
+                        ));
                         Ok(Response::Messages(messages))
                     } else {
                         let tag = match response {
@@ -676,7 +718,7 @@ impl Protocol {
                         };
                         Ok(Response::Messages(smallvec![
                             CommandComplete { tag },
-                            BackendMessage::ready_for_query_idle(),
+                            BackendMessage::ready_for_query(backend.transaction_status()),
                         ]))
                     }
                 }
@@ -712,7 +754,9 @@ impl Protocol {
                 // sequence, or after an error has occurred.
                 Sync => {
                     self.state = State::Ready;
-                    Ok(Response::Message(BackendMessage::ready_for_query_idle()))
+                    Ok(Response::Message(BackendMessage::ready_for_query(
+                        backend.transaction_status(),
+                    )))
                 }
 
                 Flush => Ok(Response::Empty),
@@ -729,10 +773,13 @@ impl Protocol {
     ///
     /// * `error` - an `Error` that has occurred while communicating with the frontend or handling
     ///   one of the frontend's requests.
+    /// * `transaction_status` - the transaction status of the backend, reported to the frontend if
+    ///   it's ready for a new query after the error.
     /// * returns - A `Response` containing an `ErrorResponse` message to send to the frontend.
     pub async fn on_error<B: Backend>(
         &mut self,
         error: Error,
+        transaction_status: TransactionStatus,
     ) -> Result<Response<B::Row, B::Resultset>, Error> {
         match self.state {
             State::StartingUp | State::Extended => {
@@ -741,7 +788,7 @@ impl Protocol {
             }
             _ => Ok(Response::Messages(smallvec![
                 error.into(),
-                BackendMessage::ready_for_query_idle(),
+                BackendMessage::ready_for_query(transaction_status),
             ])),
         }
     }
@@ -752,6 +799,18 @@ impl Protocol {
         self.state == State::SslHandshake
     }
 
+    /// Whether the client is idle, having completed start up and not being in the middle of an
+    /// extended query, so asynchronous notifications can be sent to it.
+    pub fn is_ready_for_notifications(&self) -> bool {
+        self.state == State::Ready
+    }
+
+    /// Whether the client connected to send a cancel request, which has been handled, so the
+    /// connection should be closed.
+    pub fn is_cancelled(&self) -> bool {
+        self.state == State::Cancelled
+    }
+
     /// Informs the `Protocol` that we have initiated a TLS connection with the client, storing the
     /// TLS server endpoint for optional use in channel binding later.
     pub fn completed_ssl_handshake(&mut self, server_end_point: Option<Vec<u8>>) {
@@ -969,7 +1028,9 @@ mod tests {
         last_close: Option<u32>,
         last_execute_id: Option<u32>,
         last_execute_params: Option<Vec<DataValue>>,
+        last_cancel_request: Option<(i32, i32)>,
         needed_credentials: Option<Credentials<'static>>,
+        transaction_status: TransactionStatus,
     }
 
     impl Backend {
@@ -984,7 +1045,9 @@ mod tests {
                 last_close: None,
                 last_execute_id: None,
                 last_execute_params: None,
+                last_cancel_request: None,
                 needed_credentials: None,
+                transaction_status: TransactionStatus::Idle,
             }
         }
     }
@@ -1011,6 +1074,14 @@ mod tests {
             self.needed_credentials
         }
 
+        async fn on_cancel_request(&mut self, process_id: i32, secret_key: i32) {
+            self.last_cancel_request = Some((process_id, secret_key));
+        }
+
+        fn transaction_status(&self) -> TransactionStatus {
+            self.transaction_status
+        }
+
         async fn on_query(&mut self, query: &str) -> Result<QueryResponse<Self::Resultset>, Error> {
             self.last_query = Some(query.to_string());
             if self.is_query_err {
@@ -1143,6 +1214,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn cancel_request() {
+        let mut protocol = Protocol::new();
+        let request = FrontendMessage::CancelRequest {
+            process_id: 42,
+            secret_key: 1234,
+        };
+        let mut backend = Backend::new();
+        let mut channel = Channel::<NullBytestream, Vec<Value>>::new(NullBytestream);
+        assert!(matches!(
+            block_on(protocol.on_request(request, &mut backend, &mut channel)).unwrap(),
+            Response::Empty
+        ));
+        assert_eq!(backend.last_cancel_request, Some((42, 1234)));
+        assert!(protocol.is_cancelled());
+    }
+
     #[test]
     fn ssl_request() {
         let mut protocol = Protocol::new();
@@ -1175,6 +1263,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         let mut backend = Backend::new();
         let mut channel = Channel::<NullBytestream, Vec<Value>>::new(NullBytestream);
@@ -1225,6 +1314,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(expected_username.clone()),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         let mut backend = Backend::new();
         backend.needed_credentials = Some(Credentials::CleartextPassword(expected_password));
@@ -1290,6 +1380,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(expected_username.clone()),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         let mut backend = Backend::new();
         backend.needed_credentials = Some(Credentials::CleartextPassword(expected_password));
@@ -1331,6 +1422,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: None,
+            application_name: None,
         };
         let mut backend = Backend::new();
         let mut channel = Channel::<NullBytestream, Vec<Value>>::new(NullBytestream);
@@ -1358,6 +1450,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1366,6 +1459,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(request, &mut backend, &mut channel)).unwrap_err();
     }
@@ -1380,6 +1474,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1401,6 +1496,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1422,6 +1518,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         protocol
             .on_request(startup_request, &mut backend, &mut channel)
@@ -1494,6 +1591,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1515,6 +1613,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1537,6 +1636,50 @@ mod tests {
         assert_eq!(backend.last_query.unwrap(), "DELETE * FROM test;");
     }
 
+    #[test]
+    fn query_in_transaction() {
+        let mut protocol = Protocol::new();
+        let mut backend = Backend::new();
+        backend.is_query_read = false;
+        let mut channel = Channel::<NullBytestream, Vec<Value>>::new(NullBytestream);
+
+        let startup_request = FrontendMessage::StartupMessage {
+            protocol_version: 12345,
+            user: Some(bytes_str("user_name")),
+            database: Some(bytes_str("database_name")),
+            application_name: None,
+        };
+        block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
+
+        // The backend's transaction status is reported to the client once it's ready for the next
+        // query
+        backend.transaction_status = TransactionStatus::InTransaction;
+        let request = FrontendMessage::Query {
+            query: bytes_str("DELETE * FROM test;"),
+        };
+        match block_on(protocol.on_request(request, &mut backend, &mut channel)).unwrap() {
+            Response::Messages(ms) => assert_eq!(
+                ms.last(),
+                Some(&BackendMessage::ReadyForQuery { status: b'T' })
+            ),
+            _ => panic!(),
+        }
+
+        backend.transaction_status = TransactionStatus::Failed;
+        match block_on(protocol.on_error::<Backend>(
+            Error::InternalError("error requested".to_string()),
+            backend.transaction_status(),
+        ))
+        .unwrap()
+        {
+            Response::Messages(ms) => assert_eq!(
+                ms.last(),
+                Some(&BackendMessage::ReadyForQuery { status: b'E' })
+            ),
+            _ => panic!(),
+        }
+    }
+
     #[test]
     fn parse() {
         let mut protocol = Protocol::new();
@@ -1547,6 +1690,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1595,6 +1739,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1617,6 +1762,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1663,6 +1809,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1707,6 +1854,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1754,6 +1902,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1788,6 +1937,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1818,6 +1968,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1852,6 +2003,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1875,6 +2027,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1918,6 +2071,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -1941,6 +2095,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -2001,6 +2156,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -2021,6 +2177,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -2087,6 +2244,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -2107,6 +2265,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         protocol
             .on_request(startup_request, &mut backend, &mut channel)
@@ -2188,6 +2347,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -2229,6 +2389,7 @@ mod tests {
             protocol_version: 12345,
             user: Some(bytes_str("user_name")),
             database: Some(bytes_str("database_name")),
+            application_name: None,
         };
         block_on(protocol.on_request(startup_request, &mut backend, &mut channel)).unwrap();
 
@@ -2274,7 +2435,10 @@ mod tests {
         let mut protocol = Protocol::new();
         assert!(matches!(
             block_on(
-                protocol.on_error::<Backend>(Error::InternalError("error requested".to_string()))
+                protocol.on_error::<Backend>(
+                    Error::InternalError("error requested".to_string()),
+                    TransactionStatus::Idle
+                )
             )
             .unwrap(),
             Response::Message(ErrorResponse {
@@ -2290,9 +2454,10 @@ mod tests {
     fn on_error_after_starting_up() {
         let mut protocol = Protocol::new();
         protocol.state = State::Ready;
-        match block_on(
-            protocol.on_error::<Backend>(Error::InternalError("error requested".to_string())),
-        )
+        match block_on(protocol.on_error::<Backend>(
+            Error::InternalError("error requested".to_string()),
+            TransactionStatus::Idle,
+        ))
         .unwrap()
         {
             Response::Messages(ms) => assert_eq!(
@@ -2328,7 +2493,10 @@ mod tests {
         protocol.state = State::Extended;
         assert!(matches!(
             block_on(
-                protocol.on_error::<Backend>(Error::InternalError("error requested".to_string()))
+                protocol.on_error::<Backend>(
+                    Error::InternalError("error requested".to_string()),
+                    TransactionStatus::Idle
+                )
             )
             .unwrap(),
             Response::Message(ErrorResponse {
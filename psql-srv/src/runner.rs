@@ -1,14 +1,16 @@
 use std::sync::Arc;
 
+use readyset_util::redacted::log_parameters;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_native_tls::TlsAcceptor;
 use tracing::{error, info};
 
 use crate::channel::Channel;
 use crate::error::Error;
-use crate::message::FrontendMessage;
+use crate::message::{BackendMessage, FrontendMessage};
 use crate::protocol::Protocol;
-use crate::{codec, Backend};
+use crate::response::Response;
+use crate::{codec, Backend, Notification};
 
 /// A helper struct that can be used to run a `Protocol` on a `Backend` and `Channel`.
 pub struct Runner<B: Backend, C> {
@@ -101,7 +103,22 @@ impl<B: Backend, C: AsyncRead + AsyncWrite + Unpin> Runner<B, C> {
     ) -> Result<(), Error> {
         let request = request?;
         if self.enable_statement_logging {
-            info!(target: "client_statement", "{:?}", request);
+            match &request {
+                // The values of parameters are only logged if explicitly enabled
+                FrontendMessage::Bind {
+                    portal_name,
+                    prepared_statement_name,
+                    params,
+                    ..
+                } if !log_parameters() => info!(
+                    target: "client_statement",
+                    "Bind {{ portal_name: {:?}, prepared_statement_name: {:?}, num_params: {} }}",
+                    portal_name,
+                    prepared_statement_name,
+                    params.len()
+                ),
+                _ => info!(target: "client_statement", "{:?}", request),
+            }
         }
         if request == FrontendMessage::Flush {
             self.channel.flush().await?;
@@ -114,8 +131,29 @@ impl<B: Backend, C: AsyncRead + AsyncWrite + Unpin> Runner<B, C> {
         Ok(())
     }
 
+    async fn handle_notification(&mut self, notification: Notification) -> Result<(), Error> {
+        let Notification {
+            process_id,
+            channel,
+            payload,
+        } = notification;
+        self.channel
+            .send(Response::Message::<_, B::Resultset>(
+                BackendMessage::NotificationResponse {
+                    process_id,
+                    channel,
+                    payload,
+                },
+            ))
+            .await?;
+        Ok(())
+    }
+
     async fn handle_error(&mut self, error: Error) -> Result<(), Error> {
-        let response = self.protocol.on_error::<B>(error).await?;
+        let response = self
+            .protocol
+            .on_error::<B>(error, self.backend.transaction_status())
+            .await?;
         self.channel.send(response).await?;
         Ok(())
     }
@@ -123,7 +161,22 @@ impl<B: Backend, C: AsyncRead + AsyncWrite + Unpin> Runner<B, C> {
     /// Main loop for Protocol handling. When the client requests a TLS connection, we exit this
     /// loop so that we can construct a TLS capable `Channel` and restart.
     async fn main_loop(&mut self) -> MainLoopStatus {
-        while let Some(message) = self.channel.next().await {
+        loop {
+            let message = tokio::select! {
+                message = self.channel.next() => match message {
+                    Some(message) => message,
+                    None => break,
+                },
+                // Relay asynchronous notifications to the client while it's idle
+                notification = self.backend.next_notification(),
+                    if self.protocol.is_ready_for_notifications() =>
+                {
+                    if let Err(error) = self.handle_notification(notification).await {
+                        error!(%error, "Could not relay notification to client");
+                    }
+                    continue;
+                }
+            };
             match self.handle_request(message).await {
                 Ok(()) => {
                     // Client requests a TLS channel. We exit so that we can reconstruct a TLS
@@ -131,6 +184,11 @@ impl<B: Backend, C: AsyncRead + AsyncWrite + Unpin> Runner<B, C> {
                     if self.protocol.is_initiating_ssl_handshake() {
                         return MainLoopStatus::RestartWithTls;
                     }
+                    // The client only connected to cancel a query on another connection, and
+                    // expects the connection to be closed once it has been handled
+                    if self.protocol.is_cancelled() {
+                        return MainLoopStatus::Terminate;
+                    }
                 }
                 // Return an error message but do not exit the loop
                 Err(e) => {
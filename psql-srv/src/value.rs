@@ -2,7 +2,7 @@ use bit_vec::BitVec;
 use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
 use cidr::IpInet;
 use eui48::MacAddress;
-use readyset_data::{Array, Text};
+use readyset_data::{Array, Interval, Text};
 use rust_decimal::Decimal;
 use uuid::Uuid;
 
@@ -31,6 +31,7 @@ pub enum Value {
     MacAddress(MacAddress),
     Inet(IpInet),
     Uuid(Uuid),
+    Interval(Interval),
     Json(serde_json::Value),
     Jsonb(serde_json::Value),
     Bit(BitVec),
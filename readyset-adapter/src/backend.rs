@@ -76,36 +76,51 @@ use std::fmt::{self, Debug};
 use std::marker::PhantomData;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use std::{iter, mem};
 
-use futures::future::{self, OptionFuture};
+use futures::future::{self, BoxFuture, OptionFuture};
+use futures::Future;
 use mysql_common::row::convert::{FromRow, FromRowError};
 use nom_sql::{
-    CacheInner, CreateCacheStatement, DeleteStatement, Dialect, DropCacheStatement,
-    InsertStatement, Relation, SelectStatement, SetStatement, ShowStatement, SqlIdentifier,
-    SqlQuery, UpdateStatement, UseStatement,
+    CacheInner, CacheWarmup, CreateCacheStatement, CreateExperimentStatement, DeleteStatement,
+    Dialect, DropCacheStatement, ExperimentStatement, FlushCacheStatement, InsertStatement,
+    KillStatement, KillType, Literal, Relation, SelectStatement, SetStatement, ShowStatement,
+    SqlIdentifier, SqlQuery, UpdateStatement, UseStatement,
 };
 use readyset_client::consistency::Timestamp;
+use readyset_client::internal::MaterializationStatus;
 use readyset_client::query::*;
+use readyset_client::recipe::changelist::CacheOptions;
 use readyset_client::results::Results;
-use readyset_client::{ColumnSchema, PlaceholderIdx, ViewCreateRequest};
+use readyset_client::{ColumnSchema, PlaceholderIdx, ResultLimits, ViewCreateRequest};
 pub use readyset_client_metrics::QueryDestination;
 use readyset_client_metrics::{recorded, EventType, QueryExecutionEvent, SqlQueryType};
 use readyset_data::{DfType, DfValue};
 use readyset_errors::ReadySetError::{self, PreparedStatementMissing};
 use readyset_errors::{internal, internal_err, unsupported, unsupported_err, ReadySetResult};
+use readyset_sql_passes::anonymize::anonymize_query_literals;
 use readyset_telemetry_reporter::{TelemetryBuilder, TelemetryEvent, TelemetrySender};
-use readyset_util::redacted::Sensitive;
+use readyset_util::redacted::{log_parameters, Sensitive};
 use readyset_version::READYSET_VERSION;
 use timestamp_service::client::{TimestampClient, WriteId, WriteKey};
 use tokio::sync::mpsc::UnboundedSender;
-use tracing::{error, instrument, trace, warn};
+use tokio::sync::Notify;
+use tracing::{debug, error, info, instrument, trace, warn};
 use vec1::Vec1;
 
 use crate::backend::noria_connector::ExecuteSelectContext;
+use crate::cache_experiments::CacheExperiments;
+use crate::connection_registry::{ConnectionHandle, ConnectionRegistry};
+use crate::migration_queue::MigrationQueue;
+use crate::privileges::SharedSelectPrivileges;
 use crate::query_handler::SetBehavior;
-use crate::query_status_cache::QueryStatusCache;
+use crate::query_hints::QueryHint;
+use crate::query_status_cache::{QueryPin, QueryStatusCache, QueryStatusKey};
+use crate::result_streaming::{ResultStreaming, StreamedResultSize};
+use crate::transactions::TransactionTracker;
 pub use crate::upstream_database::UpstreamPrepare;
-use crate::{rewrite, QueryHandler, UpstreamDatabase, UpstreamDestination};
+use crate::upstream_retry::{ClassifyError, TransientErrorKind, UpstreamRetryPolicy};
+use crate::{rewrite, utils, QueryHandler, UpstreamDatabase, UpstreamDestination};
 
 pub mod noria_connector;
 
@@ -150,6 +165,21 @@ pub enum UnsupportedSetMode {
     Allow,
 }
 
+/// Whether a connection is within a transaction, as reported to clients by the protocol-specific
+/// backends (for example in Postgres's `ReadyForQuery` message)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TransactionStatus {
+    /// Not within a transaction
+    Idle,
+    /// Within a transaction, either opened explicitly or implicitly because autocommit is off. All
+    /// statements are proxied upstream until the transaction is committed or rolled back.
+    InTransaction,
+    /// Within a transaction which has been failed by an error in the upstream database (see
+    /// [`ClassifyError::fails_transaction`]), so that every statement other than a rollback is
+    /// rejected until the transaction is ended
+    Failed,
+}
+
 /// A state machine representing how statements are proxied upstream for a particular instance of a
 /// backend.
 ///
@@ -237,6 +267,12 @@ impl ProxyState {
         }
     }
 
+    /// Returns true if statements are being executed within a transaction, either an explicit one
+    /// or one formed by turning autocommit off
+    fn in_transaction(&self) -> bool {
+        matches!(self, Self::InTransaction | Self::AutocommitOff)
+    }
+
     /// Returns `true` if the proxy state is [`Fallback`].
     ///
     /// [`Fallback`]: ProxyState::Fallback
@@ -253,6 +289,7 @@ pub struct BackendBuilder {
     slowlog: bool,
     dialect: Dialect,
     users: HashMap<String, String>,
+    upstream_urls: HashMap<String, String>,
     require_authentication: bool,
     ticket: Option<Timestamp>,
     timestamp_client: Option<TimestampClient>,
@@ -264,6 +301,16 @@ pub struct BackendBuilder {
     fallback_recovery_seconds: u64,
     telemetry_sender: Option<TelemetrySender>,
     enable_experimental_placeholder_inlining: bool,
+    query_timeout: Option<Duration>,
+    idle_in_transaction_timeout: Option<Duration>,
+    result_limits: ResultLimits,
+    result_streaming: ResultStreaming,
+    connection_registry: Option<Arc<ConnectionRegistry>>,
+    cache_experiments: Option<Arc<CacheExperiments>>,
+    migration_queue: Option<Arc<MigrationQueue>>,
+    select_privileges: Option<SharedSelectPrivileges>,
+    upstream_retry_policy: UpstreamRetryPolicy,
+    write_through: bool,
 }
 
 impl Default for BackendBuilder {
@@ -272,6 +319,7 @@ impl Default for BackendBuilder {
             slowlog: false,
             dialect: Dialect::MySQL,
             users: Default::default(),
+            upstream_urls: Default::default(),
             require_authentication: true,
             ticket: None,
             timestamp_client: None,
@@ -283,6 +331,16 @@ impl Default for BackendBuilder {
             fallback_recovery_seconds: 0,
             telemetry_sender: None,
             enable_experimental_placeholder_inlining: false,
+            query_timeout: None,
+            idle_in_transaction_timeout: None,
+            result_limits: ResultLimits::default(),
+            result_streaming: ResultStreaming::default(),
+            connection_registry: None,
+            cache_experiments: None,
+            migration_queue: None,
+            select_privileges: None,
+            upstream_retry_policy: UpstreamRetryPolicy::default(),
+            write_through: false,
         }
     }
 }
@@ -294,7 +352,7 @@ impl BackendBuilder {
 
     pub fn build<DB: UpstreamDatabase, Handler>(
         self,
-        noria: NoriaConnector,
+        mut noria: NoriaConnector,
         upstream: Option<DB>,
         query_status_cache: &'static QueryStatusCache,
     ) -> Backend<DB, Handler> {
@@ -305,12 +363,23 @@ impl BackendBuilder {
         } else {
             ProxyState::Never
         };
+        noria.set_read_timeout(self.query_timeout);
+        noria.set_result_limits(self.result_limits);
+        noria.set_cache_experiments(self.cache_experiments);
+        noria.set_migration_queue(self.migration_queue);
+        if let Some(privileges) = self.select_privileges {
+            noria.enforce_select_privileges(privileges);
+        }
 
         Backend {
             noria,
             upstream,
+            read_upstream: None,
             users: self.users,
+            upstream_urls: self.upstream_urls,
             query_log_sender: self.query_log_sender,
+            authenticated_user: None,
+            application_name: None,
             last_query: None,
             state: BackendState {
                 proxy_state,
@@ -319,6 +388,10 @@ impl BackendBuilder {
                 query_status_cache,
                 ticket: self.ticket,
                 timestamp_client: self.timestamp_client,
+                transactions: TransactionTracker::default(),
+                pending_writes: Vec::new(),
+                transaction_failed: false,
+                reconnect_upstream: false,
             },
             settings: BackendSettings {
                 slowlog: self.slowlog,
@@ -331,8 +404,14 @@ impl BackendBuilder {
                 fallback_recovery_duration: Duration::new(self.fallback_recovery_seconds, 0),
                 enable_experimental_placeholder_inlining: self
                     .enable_experimental_placeholder_inlining,
+                query_timeout: self.query_timeout,
+                result_streaming: self.result_streaming,
+                idle_in_transaction_timeout: self.idle_in_transaction_timeout,
+                upstream_retry_policy: self.upstream_retry_policy,
+                write_through: self.write_through,
             },
             telemetry_sender: self.telemetry_sender,
+            connection: self.connection_registry.as_ref().map(|r| r.register()),
             _query_handler: PhantomData,
         }
     }
@@ -362,6 +441,14 @@ impl BackendBuilder {
         self
     }
 
+    /// Set the URLs of the upstream database to connect to for users which should connect to the
+    /// upstream database with their own credentials, rather than those in the configured upstream
+    /// URL
+    pub fn upstream_urls(mut self, upstream_urls: HashMap<String, String>) -> Self {
+        self.upstream_urls = upstream_urls;
+        self
+    }
+
     pub fn require_authentication(mut self, require_authentication: bool) -> Self {
         self.require_authentication = require_authentication;
         self
@@ -410,6 +497,90 @@ impl BackendBuilder {
         self.enable_experimental_placeholder_inlining = enable_experimental_placeholder_inlining;
         self
     }
+
+    /// Sets the maximum amount of time any single query or prepared statement execution may take,
+    /// including reads from ReadySet and queries proxied to the upstream database. Queries which
+    /// exceed this time are cancelled, and return [`ReadySetError::QueryTimeout`] to the client.
+    pub fn query_timeout(mut self, query_timeout: Option<Duration>) -> Self {
+        self.query_timeout = query_timeout;
+        self
+    }
+
+    /// Sets the maximum amount of time a connection may remain idle within an open transaction.
+    /// Connections which exceed this time are terminated by the caller of
+    /// [`Backend::idle_in_transaction_timeout`], rolling back the transaction.
+    pub fn idle_in_transaction_timeout(
+        mut self,
+        idle_in_transaction_timeout: Option<Duration>,
+    ) -> Self {
+        self.idle_in_transaction_timeout = idle_in_transaction_timeout;
+        self
+    }
+
+    /// Sets the maximum number of rows and bytes any single read from ReadySet may return. Reads
+    /// which exceed either limit return [`ReadySetError::ResultRowLimitExceeded`] or
+    /// [`ReadySetError::ResultSizeLimitExceeded`] to the client, and are not retried against the
+    /// upstream database.
+    pub fn result_limits(mut self, result_limits: ResultLimits) -> Self {
+        self.result_limits = result_limits;
+        self
+    }
+
+    /// Sets how result sets are buffered while they're written to clients, and the maximum size of
+    /// any single row or result set written to a client. See [`ResultStreaming`] for more
+    /// information.
+    pub fn result_streaming(mut self, result_streaming: ResultStreaming) -> Self {
+        self.result_streaming = result_streaming;
+        self
+    }
+
+    /// Sets which statements that fail in the upstream database with a transient error, such as a
+    /// deadlock, are retried. See [`Backend::upstream_retry_backoff`] for more information.
+    pub fn upstream_retry_policy(mut self, upstream_retry_policy: UpstreamRetryPolicy) -> Self {
+        self.upstream_retry_policy = upstream_retry_policy;
+        self
+    }
+
+    /// Sets whether INSERT and DELETE statements which succeed in the upstream database are also
+    /// applied directly to ReadySet's base tables, so that they can be read from caches
+    /// immediately rather than after they arrive via replication. Only writes to tables with a
+    /// primary key are written through; inserts which rely on auto-incremented or computed default
+    /// values, and upserts, are not. Writes executed within a transaction are buffered, and only
+    /// written through once the transaction commits.
+    pub fn write_through(mut self, write_through: bool) -> Self {
+        self.write_through = write_through;
+        self
+    }
+
+    /// Sets the registry of connections to the adapter which built backends are registered with,
+    /// allowing statements running on them to be cancelled from other connections
+    pub fn connection_registry(mut self, connection_registry: Arc<ConnectionRegistry>) -> Self {
+        self.connection_registry = Some(connection_registry);
+        self
+    }
+
+    /// Sets the cache experiments running in the adapter, which are shared between all built
+    /// backends. Experiment statements such as `CREATE EXPERIMENT` are unsupported if this is not
+    /// set.
+    pub fn cache_experiments(mut self, cache_experiments: Arc<CacheExperiments>) -> Self {
+        self.cache_experiments = Some(cache_experiments);
+        self
+    }
+
+    /// Sets the queue of migrations which are shared between all built backends. If set, queries
+    /// migrated in the request path are migrated via the queue, which deduplicates concurrent
+    /// migrations of the same query and limits the number of migrations run at once.
+    pub fn migration_queue(mut self, migration_queue: Arc<MigrationQueue>) -> Self {
+        self.migration_queue = Some(migration_queue);
+        self
+    }
+
+    /// Enforce the given `SELECT` privileges of the upstream database's users on queries served by
+    /// ReadySet, which are shared between all built backends. See [`crate::privileges`].
+    pub fn select_privileges(mut self, select_privileges: SharedSelectPrivileges) -> Self {
+        self.select_privileges = Some(select_privileges);
+        self
+    }
 }
 
 /// A [`CachedPreparedStatement`] stores the data needed for an immediate
@@ -485,10 +656,22 @@ where
     noria: NoriaConnector,
     /// Optional connector to the upstream DB. Used for fallback reads and all writes if it exists
     upstream: Option<DB>,
+    /// Optional connector to a read replica of the upstream DB. Used instead of `upstream` for
+    /// ad-hoc fallback reads outside of transactions if it exists. See
+    /// [`Backend::with_read_upstream`]
+    read_upstream: Option<DB>,
     /// Map from username to password for all users allowed to connect to the db
     pub users: HashMap<String, String>,
+    /// Map from username to the URL of the upstream database to connect to once a client has
+    /// authenticated as that user, for users with their own upstream credentials
+    upstream_urls: HashMap<String, String>,
 
     query_log_sender: Option<UnboundedSender<QueryExecutionEvent>>,
+    /// The user this connection is authenticated as, if any, recorded in query log events
+    authenticated_user: Option<Arc<str>>,
+    /// The name of the client application which opened this connection, if it was sent by the
+    /// client, recorded in query log events
+    application_name: Option<Arc<str>>,
 
     /// Information regarding the last query sent over this connection. If None, then no queries
     /// have been handled using this connection (Backend) yet.
@@ -502,6 +685,9 @@ where
     /// Provides the ability to send [`TelemetryEvent`]s to Segment
     telemetry_sender: Option<TelemetrySender>,
 
+    /// This connection's registration with the adapter's [`ConnectionRegistry`], if any
+    connection: Option<ConnectionHandle>,
+
     _query_handler: PhantomData<Handler>,
 }
 
@@ -526,6 +712,19 @@ where
     /// is responsible for creating accurate RYW timestamps/tickets based on writes made by the
     /// Backend client.
     timestamp_client: Option<TimestampClient>,
+    /// Tracks the explicit transaction open on this connection, if any
+    transactions: TransactionTracker,
+    /// Writes executed within the open transaction which succeeded upstream, to be written
+    /// through to ReadySet once the transaction commits. Empty unless write-through is enabled.
+    pending_writes: Vec<SqlQuery>,
+    /// Set when a statement fails within the open transaction with an error which fails the
+    /// transaction in the upstream database, and cleared once the transaction ends or a
+    /// subsequent statement succeeds upstream (such as `ROLLBACK TO SAVEPOINT`)
+    transaction_failed: bool,
+    /// Set when a statement fails with an error indicating the upstream database may have failed
+    /// over, in which case the connection to the upstream database is re-established before the
+    /// next statement is executed
+    reconnect_upstream: bool,
 }
 
 /// Settings that have no state and are constant for a given [`Backend`]
@@ -549,6 +748,16 @@ struct BackendSettings {
     /// Whether to automatically create inlined migrations for queries with unsupported
     /// placeholders.
     enable_experimental_placeholder_inlining: bool,
+    /// The maximum amount of time a single query or execution may take before it is cancelled
+    query_timeout: Option<Duration>,
+    /// How result sets are buffered while they're written to clients
+    result_streaming: ResultStreaming,
+    /// The maximum amount of time the connection may remain idle within an open transaction
+    idle_in_transaction_timeout: Option<Duration>,
+    /// Which statements that fail in the upstream database with a transient error are retried
+    upstream_retry_policy: UpstreamRetryPolicy,
+    /// Whether writes which succeed upstream are also applied directly to ReadySet
+    write_through: bool,
 }
 
 /// QueryInfo holds information regarding the last query that was sent along this connection
@@ -755,16 +964,14 @@ where
     /// Internally, this will set the schema search path to a single-element vector with the
     /// database, and send a `USE` command to the upstream, if any.
     pub async fn set_database(&mut self, db: &str) -> Result<(), DB::Error> {
+        let use_statement = UseStatement {
+            database: db.into(),
+        }
+        .to_string();
         if let Some(upstream) = &mut self.upstream {
-            upstream
-                .query(
-                    &UseStatement {
-                        database: db.into(),
-                    }
-                    .to_string(),
-                )
-                .await?;
+            upstream.query(&use_statement).await?;
         }
+        self.mirror_to_read_upstream(&use_statement).await;
         self.noria.set_schema_search_path(vec![db.into()]);
         Ok(())
     }
@@ -1017,6 +1224,13 @@ where
         }
 
         match self.parse_query(query) {
+            Ok(SqlQuery::Select(_))
+                if self.has_fallback()
+                    && QueryHint::parse(self.settings.dialect, query)
+                        == Some(QueryHint::Upstream) =>
+            {
+                PrepareMeta::Proxy
+            }
             Ok(SqlQuery::Select(stmt)) => self.plan_prepare_select(stmt),
             Ok(
                 query @ SqlQuery::Insert(_)
@@ -1096,7 +1310,9 @@ where
         data: DB::PrepareData<'_>,
     ) -> Result<&PrepareResult<DB>, DB::Error> {
         self.last_query = None;
-        let mut query_event = QueryExecutionEvent::new(EventType::Prepare);
+        self.reconnect_upstream_after_failover().await?;
+        let mut query_event = self.new_query_event(EventType::Prepare);
+        let _statement = self.state.transactions.statement_started();
 
         let meta = self.plan_prepare(query).await;
         let res = self
@@ -1244,6 +1460,11 @@ where
                 }
                 Ok(noria_ok)
             }
+            Err(noria_err) if noria_err.result_limit_exceeded_cause().is_some() => {
+                // Retrying a read whose results are too big against the upstream database would
+                // just move the problem there
+                Err(noria_err.into())
+            }
             Err(noria_err) => {
                 if let Some(info) = ex_info {
                     if noria_err.is_networking_related() {
@@ -1257,7 +1478,9 @@ where
                 }
                 if !matches!(
                     noria_err,
-                    ReadySetError::ReaderMissingKey | ReadySetError::NoCacheForQuery
+                    ReadySetError::ReaderMissingKey
+                        | ReadySetError::NoCacheForQuery
+                        | ReadySetError::SelectPrivilegeDenied { .. }
                 ) {
                     warn!(error = %noria_err,
                           "Error received from noria, sending query to fallback");
@@ -1364,22 +1587,48 @@ where
     /// `params`.
     /// A [`QueryExecutionEvent`], is used to track metrics and behavior scoped to the
     /// execute operation.
+    ///
+    /// If a [query timeout](BackendBuilder::query_timeout) is configured and the execution exceeds
+    /// it, the execution is cancelled and returns [`ReadySetError::QueryTimeout`]. If the execution
+    /// is cancelled from another connection, it returns [`ReadySetError::QueryCancelled`].
+    pub async fn execute(
+        &mut self,
+        id: u32,
+        params: &[DfValue],
+    ) -> Result<QueryResult<'_, DB>, DB::Error> {
+        self.reconnect_upstream_after_failover().await?;
+        let query_timeout = self.settings.query_timeout;
+        let cancel_signal = self
+            .connection
+            .as_ref()
+            .map(ConnectionHandle::cancel_signal);
+        let cancel_upstream = self.upstream_canceller();
+        let _statement = self.state.transactions.statement_started();
+        run_cancellable(
+            query_timeout,
+            cancel_signal.as_deref(),
+            cancel_upstream,
+            self.execute_inner(id, params),
+        )
+        .await
+    }
+
     // TODO(andrew, justin): add RYW support for executing prepared queries
     #[instrument(skip_all)]
     #[inline]
-    pub async fn execute(
+    async fn execute_inner(
         &mut self,
         id: u32,
         params: &[DfValue],
     ) -> Result<QueryResult<'_, DB>, DB::Error> {
         self.last_query = None;
+        let mut event = self.new_query_event(EventType::Execute);
         let cached_statement = self
             .state
             .prepared_statements
             .get_mut(id as usize)
             .ok_or(PreparedStatementMissing { statement_id: id })?;
 
-        let mut event = QueryExecutionEvent::new(EventType::Execute);
         event.query = cached_statement.parsed_query.clone();
         event.query_id = cached_statement.query_id;
 
@@ -1536,14 +1785,23 @@ where
         });
         log_query(self.query_log_sender.as_ref(), event, self.settings.slowlog);
 
+        if matches!(result, Ok(QueryResult::Upstream(_))) {
+            self.state.transaction_failed = false;
+        }
+
         result
     }
 
     /// Should only be called with a SqlQuery that is of type StartTransaction, Commit, or
     /// Rollback. Used to handle transaction boundary queries.
+    ///
+    /// Transaction boundaries are always executed upstream, and the connection's transaction state
+    /// is only updated once they succeed there. Writes buffered within the transaction are written
+    /// through to ReadySet once it commits, and discarded if it's rolled back.
     async fn handle_transaction_boundaries<'a>(
+        noria: &mut NoriaConnector,
         upstream: Option<&'a mut DB>,
-        proxy_state: &mut ProxyState,
+        state: &mut BackendState<DB>,
         query: &SqlQuery,
     ) -> Result<QueryResult<'a, DB>, DB::Error> {
         let upstream = upstream.ok_or_else(|| {
@@ -1553,17 +1811,27 @@ where
         match query {
             SqlQuery::StartTransaction(inner) => {
                 let result = QueryResult::Upstream(upstream.start_tx(inner).await?);
-                proxy_state.start_transaction();
+                state.transaction_failed = false;
+                state.proxy_state.start_transaction();
+                state.transactions.start_transaction();
                 Ok(result)
             }
             SqlQuery::Commit(_) => {
                 let result = QueryResult::Upstream(upstream.commit().await?);
-                proxy_state.end_transaction();
+                for write in mem::take(&mut state.pending_writes) {
+                    Self::write_through(noria, &write).await;
+                }
+                state.transaction_failed = false;
+                state.proxy_state.end_transaction();
+                state.transactions.end_transaction();
                 Ok(result)
             }
             SqlQuery::Rollback(_) => {
                 let result = QueryResult::Upstream(upstream.rollback().await?);
-                proxy_state.end_transaction();
+                state.pending_writes.clear();
+                state.transaction_failed = false;
+                state.proxy_state.end_transaction();
+                state.transactions.end_transaction();
                 Ok(result)
             }
             _ => {
@@ -1602,6 +1870,128 @@ where
         ]))
     }
 
+    /// Responds to an `EXPLAIN <select>` query with the plan ReadySet would use to execute the
+    /// query: whether it is cached, where it would be routed, and the chain of dataflow nodes and
+    /// index keys it would be read from.
+    ///
+    /// Returns one row per dataflow node in the plan, or a single row with no node information if
+    /// the query is not cached.
+    #[instrument(skip(self))]
+    async fn explain_plan(
+        &mut self,
+        stmt: &SelectStatement,
+    ) -> ReadySetResult<noria_connector::QueryResult<'static>> {
+        let create_dummy_column = |n: &str| ColumnSchema {
+            column: nom_sql::Column {
+                name: n.into(),
+                table: None,
+            },
+            column_type: DfType::DEFAULT_TEXT,
+            base: None,
+        };
+
+        let mut view_request =
+            ViewCreateRequest::new(stmt.clone(), self.noria.schema_search_path().to_owned());
+        rewrite::process_query(
+            &mut view_request.statement,
+            self.noria.server_supports_pagination(),
+        )?;
+        let query_id = QueryId::from_view_create_request(&view_request);
+        let status = view_request.with_status(&self.state.query_status_cache, |s| s.cloned());
+        let plan = self.noria.view_plan(view_request).await?;
+
+        let migration_state = status
+            .as_ref()
+            .map(|s| s.migration_state.clone())
+            .unwrap_or(MigrationState::Pending);
+        let always = status.map_or(false, |s| s.always);
+        // This mirrors the routing decisions made when executing an ad-hoc select
+        let destination = if !self.has_fallback() || always {
+            QueryDestination::Readyset
+        } else if self.state.proxy_state.should_proxy()
+            || migration_state == MigrationState::Unsupported
+            || (plan.is_none() && self.settings.migration_mode != MigrationMode::InRequestPath)
+        {
+            QueryDestination::Upstream
+        } else {
+            QueryDestination::Readyset
+        };
+
+        let select_schema = SelectSchema {
+            use_bogo: false,
+            schema: Cow::Owned(vec![
+                create_dummy_column("query id"),
+                create_dummy_column("cached"),
+                create_dummy_column("destination"),
+                create_dummy_column("node"),
+                create_dummy_column("operator"),
+                create_dummy_column("materialization"),
+                create_dummy_column("index keys"),
+            ]),
+            columns: Cow::Owned(vec![
+                "query id".into(),
+                "cached".into(),
+                "destination".into(),
+                "node".into(),
+                "operator".into(),
+                "materialization".into(),
+                "index keys".into(),
+            ]),
+        };
+
+        let summary = vec![
+            DfValue::from(query_id.to_string()),
+            DfValue::from(if plan.is_some() { "yes" } else { "no" }),
+            DfValue::from(destination.to_string()),
+        ];
+        let data = match plan {
+            Some(plan) => plan
+                .nodes
+                .into_iter()
+                .map(|node| {
+                    let materialization = match node.materialization {
+                        MaterializationStatus::Not => "none",
+                        MaterializationStatus::Full => "full",
+                        MaterializationStatus::Partial {
+                            beyond_materialization_frontier: false,
+                        } => "partial",
+                        MaterializationStatus::Partial {
+                            beyond_materialization_frontier: true,
+                        } => "partial (beyond materialization frontier)",
+                    };
+                    let index_keys = node
+                        .indices
+                        .iter()
+                        .map(|index| {
+                            format!("{:?}({})", index.index_type, index.columns.join(", "))
+                        })
+                        .collect::<Vec<_>>()
+                        .join("; ");
+
+                    summary
+                        .iter()
+                        .cloned()
+                        .chain([
+                            DfValue::from(format!("{} ({})", node.name, node.index.index())),
+                            DfValue::from(node.description),
+                            DfValue::from(materialization),
+                            DfValue::from(index_keys),
+                        ])
+                        .collect()
+                })
+                .collect(),
+            None => vec![summary
+                .into_iter()
+                .chain(iter::repeat(DfValue::None).take(4))
+                .collect()],
+        };
+
+        Ok(noria_connector::QueryResult::from_owned(
+            select_schema,
+            vec![Results::new(data)],
+        ))
+    }
+
     /// Forwards a `CREATE CACHE` request to noria
     #[instrument(skip(self))]
     async fn create_cached_query(
@@ -1610,7 +2000,10 @@ where
         mut stmt: SelectStatement,
         override_schema_search_path: Option<Vec<SqlIdentifier>>,
         always: bool,
+        options: &[(SqlIdentifier, Literal)],
+        warmup: Option<&CacheWarmup>,
     ) -> ReadySetResult<noria_connector::QueryResult<'static>> {
+        let options = CacheOptions::from_sql(options)?;
         // If we have another query with the same name, drop that query first
         if let Some(name) = name {
             if let Some(view_request) = self.noria.view_create_request_from_name(name) {
@@ -1620,14 +2013,17 @@ where
                     name = %name.display(nom_sql::Dialect::MySQL),
                     "Dropping previously cached query",
                 );
-                self.drop_cached_query(name).await?;
+                self.drop_cached_query(name, true).await?;
             }
         }
         // Now migrate the new query
         rewrite::process_query(&mut stmt, self.noria.server_supports_pagination())?;
+        if self.state.query_status_cache.pin(&stmt) == Some(QueryPin::Deny) {
+            unsupported!("Query is in the query deny list, so cannot be cached");
+        }
         let migration_state = match self
             .noria
-            .handle_create_cached_query(name, &stmt, override_schema_search_path, always)
+            .handle_create_cached_query(name, &stmt, override_schema_search_path, always, options)
             .await
         {
             Ok(()) => MigrationState::Successful,
@@ -1646,6 +2042,8 @@ where
                 return Err(e);
             }
         };
+        // Inlined queries aren't migrated until they're executed, so there's nothing to warm up yet
+        let migrated = migration_state == MigrationState::Successful;
         self.state.query_status_cache.update_query_migration_state(
             &ViewCreateRequest::new(stmt.clone(), self.noria.schema_search_path().to_owned()),
             migration_state,
@@ -1654,17 +2052,33 @@ where
             &ViewCreateRequest::new(stmt.clone(), self.noria.schema_search_path().to_owned()),
             always,
         );
+
+        if let Some(warmup) = warmup.filter(|_| migrated) {
+            let name = name.cloned().unwrap_or_else(|| {
+                utils::generate_query_name(&stmt, self.noria.schema_search_path()).into()
+            });
+            let num_keys = self.noria.warm_up_cache(&name, warmup).await?;
+            info!(cache = %name.display_unquoted(), num_keys, "Warmed up cache");
+        }
+
         Ok(noria_connector::QueryResult::Empty)
     }
 
-    /// Forwards a `DROP CACHE` request to noria
+    /// Forwards a `DROP CACHE` request to noria. If `if_exists` is true, dropping a cache that
+    /// doesn't exist is not an error.
     #[instrument(skip(self))]
     async fn drop_cached_query(
         &mut self,
         name: &Relation,
+        if_exists: bool,
     ) -> ReadySetResult<noria_connector::QueryResult<'static>> {
         let maybe_view_request = self.noria.view_create_request_from_name(name);
-        self.noria.drop_view(name).await?;
+        match self.noria.drop_view(name).await {
+            Err(e) if if_exists && e.caused_by_view_not_found() => {
+                debug!(name = %name.display_unquoted(), "Cache does not exist, not dropping");
+            }
+            res => res?,
+        }
         if let Some(view_request) = maybe_view_request {
             self.state
                 .query_status_cache
@@ -1697,6 +2111,78 @@ where
         Ok(noria_connector::QueryResult::Empty)
     }
 
+    /// Handles a `KILL QUERY` statement by cancelling the statement running on another connection
+    /// to this adapter
+    fn kill(&self, kill: &KillStatement) -> ReadySetResult<noria_connector::QueryResult<'static>> {
+        if kill.kill_type != KillType::Query {
+            unsupported!("KILL CONNECTION is not supported, use KILL QUERY instead");
+        }
+        let connection = match &self.connection {
+            Some(connection) => connection,
+            None => unsupported!("KILL QUERY is not supported on this connection"),
+        };
+        if !connection.registry().cancel(kill.id) {
+            return Err(ReadySetError::UnknownConnection { id: kill.id });
+        }
+        Ok(noria_connector::QueryResult::Empty)
+    }
+
+    /// Handles a `FLUSH READYSET CACHE` statement
+    #[instrument(skip(self))]
+    async fn flush_cache(
+        &mut self,
+        flush: &FlushCacheStatement,
+    ) -> ReadySetResult<noria_connector::QueryResult<'static>> {
+        match (&flush.view, flush.keys.is_empty()) {
+            (Some(view), false) => {
+                let keys = flush
+                    .keys
+                    .iter()
+                    .map(|key| {
+                        key.iter()
+                            .cloned()
+                            .map(DfValue::try_from)
+                            .collect::<ReadySetResult<Vec<_>>>()
+                    })
+                    .collect::<ReadySetResult<Vec<_>>>()?;
+                self.noria.invalidate_keys(view, keys).await?;
+                info!(view = %view.display_unquoted(), keys = flush.keys.len(), "Invalidated keys");
+            }
+            (view, _) => {
+                let bytes = self.noria.flush_cache(view.as_ref()).await?;
+                info!(?view, bytes, "Flushed cache state");
+            }
+        }
+        Ok(noria_connector::QueryResult::Empty)
+    }
+
+    /// Handles a `CREATE EXPERIMENT`, `PROMOTE EXPERIMENT` or `DROP EXPERIMENT` statement
+    async fn experiment(
+        &mut self,
+        experiment: &ExperimentStatement,
+    ) -> ReadySetResult<noria_connector::QueryResult<'static>> {
+        match experiment {
+            ExperimentStatement::Create(CreateExperimentStatement {
+                cache,
+                percent,
+                compare_results,
+                statement,
+            }) => {
+                let mut stmt = (**statement).clone();
+                rewrite::process_query(&mut stmt, self.noria.server_supports_pagination())?;
+                if self.state.query_status_cache.pin(&stmt) == Some(QueryPin::Deny) {
+                    unsupported!("Query is in the query deny list, so cannot be cached");
+                }
+                self.noria
+                    .create_experiment(cache, &stmt, *percent, *compare_results)
+                    .await?;
+            }
+            ExperimentStatement::Promote { cache } => self.noria.promote_experiment(cache).await?,
+            ExperimentStatement::Drop { cache } => self.noria.drop_experiment(cache).await?,
+        }
+        Ok(noria_connector::QueryResult::Empty)
+    }
+
     /// Responds to a `SHOW PROXIED QUERIES` query
     #[instrument(skip(self))]
     async fn show_proxied_queries(
@@ -1772,10 +2258,18 @@ where
             SqlQuery::Explain(nom_sql::ExplainStatement::Graphviz { simplified }) => {
                 self.noria.graphviz(*simplified).await
             }
+            SqlQuery::Explain(nom_sql::ExplainStatement::Plan(stmt)) => {
+                self.explain_plan(stmt).await
+            }
+            SqlQuery::Kill(kill) => self.kill(kill),
+            SqlQuery::Experiment(experiment) => self.experiment(experiment).await,
+            SqlQuery::FlushCache(flush) => self.flush_cache(flush).await,
             SqlQuery::CreateCache(CreateCacheStatement {
                 name,
+                options,
                 inner,
                 always,
+                warmup,
             }) => {
                 let (stmt, search_path) = match inner {
                     Ok(CacheInner::Statement(st)) => (*st.clone(), None),
@@ -1815,10 +2309,19 @@ where
                     trace!("No telemetry sender. not sending metric for CREATE CACHE");
                 }
 
-                self.create_cached_query(name.as_ref(), stmt, search_path, *always)
-                    .await
+                self.create_cached_query(
+                    name.as_ref(),
+                    stmt,
+                    search_path,
+                    *always,
+                    options,
+                    warmup.as_ref(),
+                )
+                .await
+            }
+            SqlQuery::DropCache(DropCacheStatement { name, if_exists }) => {
+                self.drop_cached_query(name, *if_exists).await
             }
-            SqlQuery::DropCache(DropCacheStatement { name }) => self.drop_cached_query(name).await,
             SqlQuery::DropAllCaches(_) => self.drop_all_caches().await,
             SqlQuery::Show(ShowStatement::CachedQueries(query_id)) => {
                 // Log a telemetry event
@@ -1835,6 +2338,10 @@ where
             SqlQuery::Show(ShowStatement::ReadySetStatus) => self.noria.readyset_status().await,
             SqlQuery::Show(ShowStatement::ReadySetVersion) => readyset_version(),
             SqlQuery::Show(ShowStatement::ReadySetTables) => self.noria.table_statuses().await,
+            SqlQuery::Show(ShowStatement::ReadySetReplicationPosition) => {
+                self.noria.replication_positions().await
+            }
+            SqlQuery::Show(ShowStatement::Experiments) => self.noria.experiment_statuses(),
             SqlQuery::Show(ShowStatement::ProxiedQueries(q_id)) => {
                 // Log a telemetry event
                 if let Some(ref telemetry_sender) = self.telemetry_sender {
@@ -1915,6 +2422,7 @@ where
             let ctx = ExecuteSelectContext::AdHoc {
                 statement: original_stmt,
                 create_if_missing: settings.migration_mode == MigrationMode::InRequestPath,
+                view: None,
             };
             let res = noria.execute_select(ctx, state.ticket.clone(), event).await;
             event.readyset_duration = Some(start.elapsed());
@@ -1970,6 +2478,7 @@ where
                 // query.
                 match (always, upstream) {
                     (true, _) | (_, None) => Err(noria_err.into()),
+                    _ if noria_err.result_limit_exceeded_cause().is_some() => Err(noria_err.into()),
                     (false, Some(fallback)) => {
                         event.destination = Some(QueryDestination::ReadysetThenUpstream);
                         let _t = event.start_upstream_timer();
@@ -2071,6 +2580,13 @@ where
                         return Err(e.into());
                     }
                     UnsupportedSetMode::Proxy => {
+                        if on {
+                            // Turning autocommit back on implicitly commits the open transaction.
+                            // Rather than writing through the writes buffered within it before
+                            // the SET statement has run upstream, leave them to replication.
+                            state.pending_writes.clear();
+                            state.transaction_failed = false;
+                        }
                         state.proxy_state.set_autocommit(on);
                     }
                     _ => {}
@@ -2085,6 +2601,18 @@ where
         Ok(())
     }
 
+    /// Applies a write which succeeded upstream directly to ReadySet's base tables. Failing to do
+    /// so is not an error, since the write will still arrive via replication.
+    async fn write_through(noria: &mut NoriaConnector, query: &SqlQuery) {
+        match noria.write_through(query).await {
+            Ok(()) => metrics::increment_counter!(recorded::WRITES_WRITTEN_THROUGH),
+            Err(error) => {
+                metrics::increment_counter!(recorded::WRITES_NOT_WRITTEN_THROUGH);
+                debug!(%error, "Not writing through to ReadySet");
+            }
+        }
+    }
+
     #[instrument(level = "trace", skip_all)]
     async fn query_adhoc_non_select<'a>(
         noria: &'a mut NoriaConnector,
@@ -2115,7 +2643,7 @@ where
             // Upstream reads are tried when noria reads produce an error. Upstream writes are done
             // by default when the upstream connector is present.
             if let Some(upstream) = upstream {
-                match query {
+                match &query {
                     SqlQuery::Select(_) => unreachable!("read path returns prior"),
                     SqlQuery::Insert(InsertStatement { table: t, .. })
                     | SqlQuery::Update(UpdateStatement { table: t, .. })
@@ -2154,9 +2682,25 @@ where
                             upstream.query(raw_query).await
                         };
 
+                        if query_result.is_ok() && settings.write_through {
+                            if state.proxy_state.is_fallback() {
+                                Self::write_through(noria, &query).await;
+                            } else if state.proxy_state.in_transaction() {
+                                state.pending_writes.push(query.clone());
+                            }
+                        }
+
                         query_result.map(QueryResult::Upstream)
                     }
 
+                    // Truncations are replicated back to ReadySet's base tables like any other
+                    // write
+                    SqlQuery::Truncate(_) => {
+                        event.sql_type = SqlQueryType::Write;
+                        let _t = event.start_upstream_timer();
+                        upstream.query(raw_query).await.map(QueryResult::Upstream)
+                    }
+
                     // Table Create / Drop (RYW not supported)
                     // TODO(andrew, justin): how are these types of writes handled w.r.t RYW?
                     SqlQuery::CreateView(_)
@@ -2177,17 +2721,16 @@ where
                     }
 
                     SqlQuery::StartTransaction(_) | SqlQuery::Commit(_) | SqlQuery::Rollback(_) => {
-                        Self::handle_transaction_boundaries(
-                            Some(upstream),
-                            &mut state.proxy_state,
-                            &query,
-                        )
-                        .await
+                        Self::handle_transaction_boundaries(noria, Some(upstream), state, &query)
+                            .await
                     }
                     SqlQuery::CreateCache(_)
                     | SqlQuery::DropCache(_)
                     | SqlQuery::DropAllCaches(_)
-                    | SqlQuery::Explain(_) => {
+                    | SqlQuery::Explain(_)
+                    | SqlQuery::Kill(_)
+                    | SqlQuery::Experiment(_)
+                    | SqlQuery::FlushCache(_) => {
                         unreachable!("path returns prior")
                     }
                 }
@@ -2211,6 +2754,7 @@ where
                     SqlQuery::Insert(q) => noria.handle_insert(q).await,
                     SqlQuery::Update(q) => noria.handle_update(q).await,
                     SqlQuery::Delete(q) => noria.handle_delete(q).await,
+                    SqlQuery::Truncate(q) => noria.handle_truncate(q).await,
                     // Return a empty result we are allowing unsupported set statements. Commit
                     // messages are dropped - we do not support transactions in noria standalone.
                     // We return an empty result set instead of an error to support test
@@ -2234,10 +2778,235 @@ where
     }
 
     /// Executes `query` using the reader/writer belonging to the calling `Backend` struct.
+    ///
+    /// If a [query timeout](BackendBuilder::query_timeout) is configured and the query exceeds it,
+    /// the query is cancelled and returns [`ReadySetError::QueryTimeout`]. If the query is
+    /// cancelled from another connection, it returns [`ReadySetError::QueryCancelled`].
+    pub async fn query<'a>(&'a mut self, query: &'a str) -> Result<QueryResult<'a, DB>, DB::Error> {
+        self.reconnect_upstream_after_failover().await?;
+        let query_timeout = self.settings.query_timeout;
+        let cancel_signal = self
+            .connection
+            .as_ref()
+            .map(ConnectionHandle::cancel_signal);
+        let cancel_upstream = self.upstream_canceller();
+        let _statement = self.state.transactions.statement_started();
+        run_cancellable(
+            query_timeout,
+            cancel_signal.as_deref(),
+            cancel_upstream,
+            self.query_inner(query),
+        )
+        .await
+    }
+
+    /// If queries on this connection can be cancelled (because a query timeout is configured, or
+    /// the connection is registered with a [`ConnectionRegistry`]), returns a future which cancels
+    /// whatever query is running on the upstream database connection at the time it's awaited
+    fn upstream_canceller(&self) -> Option<BoxFuture<'static, Result<(), DB::Error>>> {
+        if self.settings.query_timeout.is_none() && self.connection.is_none() {
+            return None;
+        }
+        let cancel_upstream = self.upstream.as_ref()?.query_canceller();
+        match &self.read_upstream {
+            // We don't know in advance which of the two connections the statement will run on, but
+            // cancelling a connection that isn't running a statement has no effect
+            Some(read_upstream) => {
+                let cancel_read_upstream = read_upstream.query_canceller();
+                Some(Box::pin(async move {
+                    let (res, read_res) = future::join(cancel_upstream, cancel_read_upstream).await;
+                    res.and(read_res)
+                }))
+            }
+            None => Some(cancel_upstream),
+        }
+    }
+
+    /// If an [idle-in-transaction timeout](BackendBuilder::idle_in_transaction_timeout) is
+    /// configured, returns a future which resolves once this connection has been idle within an
+    /// open transaction for longer than that timeout, at which point the connection should be
+    /// terminated
+    pub fn idle_in_transaction_timeout(&self) -> Option<impl Future<Output = ()> + 'static> {
+        self.settings
+            .idle_in_transaction_timeout
+            .map(|timeout| self.state.transactions.idle_timeout(timeout))
+    }
+
+    /// Returns whether this connection is currently within a transaction, for reporting to the
+    /// client
+    pub fn transaction_status(&self) -> TransactionStatus {
+        if !self.state.proxy_state.in_transaction() {
+            TransactionStatus::Idle
+        } else if self.state.transaction_failed {
+            TransactionStatus::Failed
+        } else {
+            TransactionStatus::InTransaction
+        }
+    }
+
+    /// Returns a future which resolves once this connection is idle outside of a transaction: no
+    /// statement is running on it, and it has no open transaction. Used to close connections
+    /// between statements while draining them before the adapter shuts down.
+    pub fn idle(&self) -> impl Future<Output = ()> + 'static {
+        self.state.transactions.idle()
+    }
+
+    /// Returns a tracker for the size of a single result set written to the client, which
+    /// enforces the configured [result streaming limits](BackendBuilder::result_streaming)
+    pub fn streamed_result_size(&self) -> StreamedResultSize {
+        StreamedResultSize::new(self.settings.result_streaming)
+    }
+
+    /// Called by the protocol-specific backends when a statement fails with an error which may
+    /// have been returned by the upstream database. If the error is transient, the statement was
+    /// executed outside of a transaction, and the configured [retry
+    /// policy](BackendBuilder::upstream_retry_policy) allows it, returns how long to wait before
+    /// executing the statement again, having already retried it `attempt` times.
+    ///
+    /// If the error indicates that the upstream database may have failed over - the connection to
+    /// it was lost, or it has become read-only - and the statement was executed outside of a
+    /// transaction, the connection to the upstream database is re-established before the next
+    /// statement is executed, so that it reaches the new writer once the upstream database's
+    /// address has been repointed at it.
+    ///
+    /// If the statement was executed within a transaction, any writes buffered to be written
+    /// through to ReadySet when the transaction commits are discarded, and if the error [fails the
+    /// transaction](ClassifyError::fails_transaction) the connection's [transaction
+    /// status](Backend::transaction_status) becomes [`TransactionStatus::Failed`].
+    pub fn upstream_retry_backoff(&mut self, error: &DB::Error, attempt: u32) -> Option<Duration> {
+        let in_transaction = self.state.proxy_state.in_transaction();
+        if in_transaction {
+            // Postgres aborts a transaction as soon as any statement in it fails, so we can't know
+            // whether the writes buffered so far will be committed. They'll still arrive via
+            // replication if they are.
+            self.state.pending_writes.clear();
+            if error.fails_transaction() {
+                self.state.transaction_failed = true;
+            }
+        }
+
+        let kind = error.transient_kind()?;
+        if !in_transaction
+            && matches!(
+                kind,
+                TransientErrorKind::ConnectionLost | TransientErrorKind::ReadOnly
+            )
+        {
+            self.state.reconnect_upstream = true;
+        }
+
+        let backoff = if in_transaction {
+            None
+        } else {
+            self.settings.upstream_retry_policy.backoff(kind, attempt)
+        };
+
+        if backoff.is_some() {
+            metrics::increment_counter!(recorded::UPSTREAM_RETRIES, "kind" => kind.as_str());
+        } else {
+            metrics::increment_counter!(
+                recorded::UPSTREAM_TRANSIENT_ERRORS_NOT_RETRIED,
+                "kind" => kind.as_str()
+            );
+        }
+        backoff
+    }
+
+    /// Re-establish the connection to the upstream database if a previous statement failed with an
+    /// error indicating that the upstream database may have failed over. See
+    /// [`Backend::upstream_retry_backoff`].
+    ///
+    /// Statements prepared in the upstream database before reconnecting can no longer be executed
+    /// there, and must be prepared again by the client.
+    async fn reconnect_upstream_after_failover(&mut self) -> Result<(), DB::Error> {
+        if !self.state.reconnect_upstream {
+            return Ok(());
+        }
+        if let Some(upstream) = &mut self.upstream {
+            warn!("Reconnecting to the upstream database, which may have failed over");
+            upstream.reset().await?;
+            metrics::increment_counter!(recorded::UPSTREAM_RECONNECTS);
+        }
+        if let Some(read_upstream) = &mut self.read_upstream {
+            if let Err(error) = read_upstream.reset().await {
+                warn!(%error, "Error reconnecting to read replica, proxying reads upstream");
+                self.read_upstream = None;
+            }
+        }
+        self.state.reconnect_upstream = false;
+        Ok(())
+    }
+
+    /// Send ad-hoc `SELECT` statements which would otherwise be proxied to the upstream database
+    /// outside of a transaction to the given connection to a read replica of the upstream database
+    /// instead, so that reads which miss in ReadySet don't add load to the primary.
+    ///
+    /// Statements which change session state, such as `SET` and `USE`, are also run against the
+    /// read replica. Prepared statements are always executed against the upstream database they
+    /// were prepared in.
+    pub fn with_read_upstream(mut self, read_upstream: Option<DB>) -> Self {
+        self.read_upstream = read_upstream;
+        self
+    }
+
+    /// Returns the connection to proxy ad-hoc reads to: the read replica if there is one and we're
+    /// not in a transaction, and the upstream database otherwise
+    fn fallback_read_upstream<'a>(
+        upstream: &'a mut Option<DB>,
+        read_upstream: &'a mut Option<DB>,
+        proxy_state: &ProxyState,
+    ) -> Option<&'a mut DB> {
+        match read_upstream {
+            Some(read_upstream) if proxy_state.is_fallback() => Some(read_upstream),
+            _ => upstream.as_mut(),
+        }
+    }
+
+    /// Run a statement which changes session state, such as `SET` or `USE`, against the read
+    /// replica, if any, so that reads proxied there see the same session state as the upstream
+    /// database. If the statement fails on the read replica, stops proxying reads to it.
+    async fn mirror_to_read_upstream(&mut self, query: &str) {
+        let Some(read_upstream) = &mut self.read_upstream else {
+            return;
+        };
+        let res = read_upstream.query(query).await.map(|_| ());
+        if let Err(error) = res {
+            warn!(%error, "Error running statement on read replica, proxying reads upstream");
+            self.read_upstream = None;
+        }
+    }
+
+    /// Returns the connection to the upstream database, if any
+    pub fn upstream_mut(&mut self) -> Option<&mut DB> {
+        self.upstream.as_mut()
+    }
+
+    /// Returns this connection's registration with the adapter's [`ConnectionRegistry`], if any
+    pub fn connection(&self) -> Option<&ConnectionHandle> {
+        self.connection.as_ref()
+    }
+
+    /// Record the name of the client application which opened this connection, as sent by the
+    /// client when the connection was established
+    pub fn set_application_name(&mut self, application_name: &str) {
+        self.application_name = Some(application_name.into());
+    }
+
+    /// Create a new query log event, labeled with the user and application of this connection
+    fn new_query_event(&self, event_type: EventType) -> QueryExecutionEvent {
+        let mut event = QueryExecutionEvent::new(event_type);
+        event.user = self.authenticated_user.clone();
+        event.application = self.application_name.clone();
+        event
+    }
+
     #[instrument(skip_all)]
     #[inline]
-    pub async fn query<'a>(&'a mut self, query: &'a str) -> Result<QueryResult<'a, DB>, DB::Error> {
-        let mut event = QueryExecutionEvent::new(EventType::Query);
+    async fn query_inner<'a>(
+        &'a mut self,
+        query: &'a str,
+    ) -> Result<QueryResult<'a, DB>, DB::Error> {
+        let mut event = self.new_query_event(EventType::Query);
         let query_log_sender = self.query_log_sender.clone();
         let slowlog = self.settings.slowlog;
 
@@ -2245,6 +3014,19 @@ where
             let _t = event.start_parse_timer();
             self.parse_query(query)
         };
+        let hint = QueryHint::parse(self.settings.dialect, query);
+
+        let changes_session_state = match &parse_result {
+            Ok(SqlQuery::Set(set)) => {
+                Handler::handle_set_statement(set) != SetBehavior::Unsupported
+                    || self.settings.unsupported_set_mode == UnsupportedSetMode::Allow
+            }
+            Ok(SqlQuery::Use(_)) => true,
+            _ => false,
+        };
+        if changes_session_state {
+            self.mirror_to_read_upstream(query).await;
+        }
 
         let result = match parse_result {
             // Parse error, but no fallback exists
@@ -2326,6 +3108,33 @@ where
                         .map_err(Into::into)
                 }
             }
+            Ok(SqlQuery::Select(_)) if hint == Some(QueryHint::Upstream) && self.has_fallback() => {
+                let upstream = Self::fallback_read_upstream(
+                    &mut self.upstream,
+                    &mut self.read_upstream,
+                    &self.state.proxy_state,
+                );
+                Self::query_fallback(upstream, query, &mut event).await
+            }
+            Ok(SqlQuery::Select(stmt)) if let Some(QueryHint::View(view)) = &hint => {
+                event.sql_type = SqlQueryType::Read;
+                event.destination = Some(QueryDestination::Readyset);
+                let start = Instant::now();
+                let ctx = ExecuteSelectContext::AdHoc {
+                    statement: stmt,
+                    create_if_missing: false,
+                    view: Some(view.clone()),
+                };
+                let res = self
+                    .noria
+                    .execute_select(ctx, self.state.ticket.clone(), &mut event)
+                    .await;
+                event.readyset_duration = Some(start.elapsed());
+                if let Err(e) = &res {
+                    event.set_noria_error(e);
+                }
+                res.map(Into::into).map_err(Into::into)
+            }
             Ok(SqlQuery::Select(stmt)) => {
                 let mut view_request = ViewCreateRequest::new(
                     stmt.clone(),
@@ -2340,7 +3149,11 @@ where
                     }
                     Self::query_adhoc_select(
                         &mut self.noria,
-                        self.upstream.as_mut(),
+                        Self::fallback_read_upstream(
+                            &mut self.upstream,
+                            &mut self.read_upstream,
+                            &self.state.proxy_state,
+                        ),
                         &self.settings,
                         &mut self.state,
                         query,
@@ -2351,9 +3164,30 @@ where
                     )
                     .await
                 } else {
-                    Self::query_fallback(self.upstream.as_mut(), query, &mut event).await
+                    let upstream = Self::fallback_read_upstream(
+                        &mut self.upstream,
+                        &mut self.read_upstream,
+                        &self.state.proxy_state,
+                    );
+                    Self::query_fallback(upstream, query, &mut event).await
                 }
             }
+            // Writes within a transaction are executed upstream like every other statement, but
+            // are also buffered to be written through to ReadySet once the transaction commits
+            Ok(
+                parsed_query @ (SqlQuery::Insert(_) | SqlQuery::Update(_) | SqlQuery::Delete(_)),
+            ) if self.settings.write_through && self.state.proxy_state.in_transaction() => {
+                Self::query_adhoc_non_select(
+                    &mut self.noria,
+                    self.upstream.as_mut(),
+                    query,
+                    &mut event,
+                    parsed_query,
+                    &self.settings,
+                    &mut self.state,
+                )
+                .await
+            }
             Ok(_) if self.state.proxy_state.should_proxy() => {
                 Self::query_fallback(self.upstream.as_mut(), query, &mut event).await
             }
@@ -2382,6 +3216,12 @@ where
 
         log_query(query_log_sender.as_ref(), event, slowlog);
 
+        // A statement can only succeed upstream within a failed transaction if it recovered the
+        // transaction, such as `ROLLBACK TO SAVEPOINT`
+        if matches!(result, Ok(QueryResult::Upstream(_))) {
+            self.state.transaction_failed = false;
+        }
+
         result
     }
 
@@ -2423,6 +3263,29 @@ where
     pub fn does_require_authentication(&self) -> bool {
         self.settings.require_authentication
     }
+
+    /// Called once a client has successfully authenticated as the given user. If the user has its
+    /// own upstream credentials, reconnects to the upstream database with those credentials, and
+    /// reloads the schema search path for the new connection. If upstream privileges are being
+    /// enforced, subsequent queries are checked against the user's privileges.
+    pub async fn on_authenticated(&mut self, username: &str) -> Result<(), DB::Error> {
+        self.noria.set_authenticated_user(username);
+        self.authenticated_user = Some(username.into());
+
+        let (upstream, url) = match (&mut self.upstream, self.upstream_urls.get(username)) {
+            (Some(upstream), Some(url)) if upstream.url() != url => (upstream, url),
+            _ => return Ok(()),
+        };
+
+        debug!(%username, "Reconnecting to upstream database with user's credentials");
+        upstream.reconnect_to(url.clone()).await?;
+        // The read replica is connected to with the configured credentials, which may be able to
+        // read data that this user can't
+        self.read_upstream = None;
+        let schema_search_path = upstream.schema_search_path().await?;
+        self.noria.set_schema_search_path(schema_search_path);
+        Ok(())
+    }
 }
 
 impl<DB, Handler> Drop for Backend<DB, Handler>
@@ -2448,9 +3311,18 @@ fn log_query(
             || event.readyset_duration.unwrap_or_default() > SLOW_DURATION)
     {
         if let Some(query) = &event.query {
-            warn!(
+            // Literals can contain sensitive data, so they're only included in the slow log if
+            // explicitly enabled
+            let query = if log_parameters() {
                 // FIXME(ENG-2499): Use correct dialect.
-                query = %Sensitive(&query.display(nom_sql::Dialect::MySQL)),
+                query.display(nom_sql::Dialect::MySQL).to_string()
+            } else {
+                let mut query = SqlQuery::clone(query);
+                anonymize_query_literals(&mut query);
+                query.display(nom_sql::Dialect::MySQL).to_string()
+            };
+            warn!(
+                %query,
                 readyset_time = ?event.readyset_duration,
                 upstream_time = ?event.upstream_duration,
                 "slow query"
@@ -2466,6 +3338,63 @@ fn log_query(
     }
 }
 
+/// Runs `query`, cancelling it if it takes longer than `timeout`. If the query is cancelled,
+/// `cancel_upstream` is awaited to cancel any work the query started on the upstream database.
+async fn run_cancellable<F, T, E>(
+    timeout: Option<Duration>,
+    cancel_signal: Option<&Notify>,
+    cancel_upstream: Option<BoxFuture<'static, Result<(), E>>>,
+    query: F,
+) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+    E: From<ReadySetError> + std::error::Error,
+{
+    if timeout.is_none() && cancel_signal.is_none() {
+        return query.await;
+    }
+
+    let deadline = async {
+        match timeout {
+            Some(timeout) => {
+                tokio::time::sleep(timeout).await;
+                timeout
+            }
+            None => future::pending().await,
+        }
+    };
+    let cancelled = async {
+        match cancel_signal {
+            Some(cancel_signal) => cancel_signal.notified().await,
+            None => future::pending().await,
+        }
+    };
+
+    let error = tokio::select! {
+        res = query => return res,
+        timeout = deadline => {
+            warn!(
+                ?timeout,
+                "Query exceeded maximum execution time, cancelling"
+            );
+            metrics::increment_counter!(recorded::QUERY_TIMEOUTS);
+            ReadySetError::QueryTimeout { timeout }
+        }
+        _ = cancelled => {
+            debug!("Query cancelled by request from another connection");
+            metrics::increment_counter!(recorded::QUERIES_CANCELLED);
+            ReadySetError::QueryCancelled
+        }
+    };
+
+    if let Some(cancel_upstream) = cancel_upstream {
+        if let Err(error) = cancel_upstream.await {
+            warn!(%error, "Failed to cancel query on upstream database");
+        }
+    }
+    Err(error.into())
+}
+
 fn readyset_version() -> ReadySetResult<noria_connector::QueryResult<'static>> {
     Ok(noria_connector::QueryResult::MetaWithHeader(
         <Vec<(String, String)>>::from(READYSET_VERSION.clone())
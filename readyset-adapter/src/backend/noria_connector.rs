@@ -3,33 +3,38 @@ use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::sync::{atomic, Arc, RwLock};
+use std::time::{Duration, Instant};
 
 use itertools::Itertools;
 use nom_sql::analysis::visit::Visitor;
 use nom_sql::{
-    self, ColumnConstraint, DeleteStatement, Expr, InsertStatement, Literal, Relation,
-    SelectStatement, SqlIdentifier, SqlQuery, UnaryOperator, UpdateStatement,
+    self, CacheWarmup, ColumnConstraint, CreateTableBody, DeleteStatement, Expr, InsertStatement,
+    Literal, Relation, SelectStatement, SqlIdentifier, SqlQuery, UnaryOperator, UpdateStatement,
 };
 use readyset_client::consistency::Timestamp;
+use readyset_client::debug::info::ViewPlan;
 use readyset_client::internal::LocalNodeIndex;
-use readyset_client::recipe::changelist::{Change, ChangeList, IntoChanges};
+use readyset_client::recipe::changelist::{CacheOptions, Change, ChangeList, IntoChanges};
 use readyset_client::results::{ResultIterator, Results};
 use readyset_client::{
-    ColumnSchema, ReadQuery, ReaderAddress, ReaderHandle, ReadySetHandle, SchemaType, Table,
-    TableOperation, View, ViewCreateRequest, ViewQuery,
+    ColumnSchema, ReadQuery, ReaderAddress, ReaderHandle, ReadySetHandle, ResultLimits, SchemaType,
+    Table, TableOperation, View, ViewCreateRequest, ViewQuery,
 };
 use readyset_data::{DfType, DfValue, Dialect};
 use readyset_errors::ReadySetError::{self, PreparedStatementMissing};
 use readyset_errors::{
-    internal, internal_err, invalid, invariant_eq, table_err, unsupported, unsupported_err,
+    bad_request_err, internal, internal_err, invalid, table_err, unsupported, unsupported_err,
     ReadySetResult,
 };
 use readyset_server::worker::readers::{CallResult, ReadRequestHandler};
 use readyset_sql_passes::anonymize::anonymize_literals;
-use readyset_util::redacted::{Sensitive, REDACT_SENSITIVE};
+use readyset_util::redacted::{redact_sensitive, Sensitive};
 use tracing::{error, info, instrument, trace, warn};
 
 use crate::backend::SelectSchema;
+use crate::cache_experiments::{CacheExperiments, Experiment};
+use crate::migration_queue::MigrationQueue;
+use crate::privileges::{tables_read_by, PrivilegeCheck, SharedSelectPrivileges};
 use crate::rewrite::{self, ProcessedQueryParams};
 use crate::utils;
 
@@ -50,6 +55,8 @@ pub(crate) enum PreparedStatement {
 pub(crate) struct PreparedSelectStatement {
     name: Relation,
     processed_query_params: ProcessedQueryParams,
+    /// The tables read by the query, for enforcing upstream privileges
+    tables: Vec<Relation>,
 }
 
 impl fmt::Debug for PreparedStatement {
@@ -87,6 +94,11 @@ pub struct NoriaBackendInner {
     noria: ReadySetHandle,
     tables: BTreeMap<Relation, Table>,
     views: BTreeMap<Relation, View>,
+    /// The foreign keys declared with `ON DELETE CASCADE` which reference each table that rows
+    /// have been deleted from, along with the schemas of their referencing tables. Like the
+    /// schemas of the tables in `tables`, these are cached for the lifetime of the connection, and
+    /// cleared when the connection changes the schema.
+    cascading_foreign_keys: HashMap<Relation, Vec<(utils::CascadingForeignKey, CreateTableBody)>>,
     /// The server can handle (non-parameterized) LIMITs and (parameterized) OFFSETs in the
     /// dataflow graph
     server_supports_pagination: bool,
@@ -106,6 +118,7 @@ impl NoriaBackendInner {
         NoriaBackendInner {
             tables: BTreeMap::new(),
             views: BTreeMap::new(),
+            cascading_foreign_keys: HashMap::new(),
             noria: ch,
             server_supports_pagination,
         }
@@ -391,15 +404,36 @@ pub struct NoriaConnector {
     /// Set of views that have failed on previous requests. Separate from the backend
     /// to allow returning references to schemas from views all the way to mysql-srv,
     /// but on subsequent requests, do not use a failed view.
+    ///
+    /// Views are also marked as failed if the reader shard addresses we have for them turn out to
+    /// be out of date (see [`ReadySetError::is_stale_reader_address`]), so that the next request
+    /// re-fetches the shard mapping for the view from the controller.
     failed_views: HashSet<Relation>,
 
     /// How to handle issuing reads against ReadySet. See [`ReadBehavior`].
     read_behavior: ReadBehavior,
 
+    /// The maximum amount of time to wait for the results of a blocking read. See
+    /// [`ViewQuery::timeout`].
+    read_timeout: Option<Duration>,
+
+    /// Limits on the number of rows and bytes a single read against ReadySet may return. See
+    /// [`ViewQuery::result_limits`].
+    result_limits: ResultLimits,
+
     /// A read request handler that may be used to service reads from readers
     /// on the same server.
     read_request_handler: request_handler::LocalReadHandler,
 
+    /// The cache experiments running in this adapter, if experiments are enabled. See
+    /// [`CacheExperiments`].
+    experiments: Option<Arc<CacheExperiments>>,
+
+    /// The queue of migrations shared between all connections to this adapter, if any. If set,
+    /// queries are migrated via the queue rather than directly by this connection. See
+    /// [`MigrationQueue`].
+    migration_queue: Option<Arc<MigrationQueue>>,
+
     /// SQL Dialect to pass to ReadySet as part of all migration requests
     dialect: Dialect,
 
@@ -412,6 +446,11 @@ pub struct NoriaConnector {
     /// supports a multi-element schema search path, the concept of "currently connected database"
     /// in MySQL can be thought of as a schema search path that only has one element.
     schema_search_path: Vec<SqlIdentifier>,
+
+    /// If upstream privileges are being enforced, checks that the user this connection has
+    /// authenticated as can read from the tables of the queries it executes. See
+    /// [`crate::privileges`].
+    privilege_check: Option<PrivilegeCheck>,
 }
 
 mod request_handler {
@@ -470,6 +509,8 @@ pub(crate) enum ExecuteSelectContext<'ctx> {
     AdHoc {
         statement: nom_sql::SelectStatement,
         create_if_missing: bool,
+        /// If set, read from the view with this name rather than the view for `statement`
+        view: Option<Relation>,
     },
 }
 
@@ -522,10 +563,59 @@ impl NoriaConnector {
             prepared_statement_cache: HashMap::new(),
             failed_views: HashSet::new(),
             read_behavior,
+            read_timeout: None,
+            result_limits: ResultLimits::default(),
             read_request_handler: request_handler::LocalReadHandler::new(read_request_handler),
+            experiments: None,
+            migration_queue: None,
             dialect,
             parse_dialect,
             schema_search_path,
+            privilege_check: None,
+        }
+    }
+
+    /// Set the maximum amount of time to wait for the results of blocking reads against ReadySet,
+    /// including any upqueries they trigger
+    pub(crate) fn set_read_timeout(&mut self, timeout: Option<Duration>) {
+        self.read_timeout = timeout;
+    }
+
+    /// Set the limits on the number of rows and bytes a single read against ReadySet may return
+    pub(crate) fn set_result_limits(&mut self, limits: ResultLimits) {
+        self.result_limits = limits;
+    }
+
+    /// Set the cache experiments running in this adapter, which are shared between all of its
+    /// connections
+    pub(crate) fn set_cache_experiments(&mut self, experiments: Option<Arc<CacheExperiments>>) {
+        self.experiments = experiments;
+    }
+
+    /// Set the queue of migrations shared between all connections to this adapter
+    pub(crate) fn set_migration_queue(&mut self, migration_queue: Option<Arc<MigrationQueue>>) {
+        self.migration_queue = migration_queue;
+    }
+
+    /// Enforce the given upstream `SELECT` privileges on all queries executed against ReadySet
+    pub(crate) fn enforce_select_privileges(&mut self, privileges: SharedSelectPrivileges) {
+        self.privilege_check = Some(PrivilegeCheck::new(privileges));
+    }
+
+    /// Set the user this connection has authenticated as, whose privileges are checked if upstream
+    /// privileges are being enforced
+    pub(crate) fn set_authenticated_user(&mut self, user: &str) {
+        if let Some(check) = &mut self.privilege_check {
+            check.set_user(user);
+        }
+    }
+
+    /// If upstream privileges are being enforced, check that the authenticated user can read from
+    /// all of the given tables
+    fn check_select_privileges(&self, tables: &[Relation]) -> ReadySetResult<()> {
+        match &self.privilege_check {
+            Some(check) => check.check(tables, &self.schema_search_path),
+            None => Ok(()),
         }
     }
 
@@ -544,6 +634,17 @@ impl NoriaConnector {
         Ok(QueryResult::Meta(vec![(label, graphviz).into()]))
     }
 
+    /// Returns the plan for reading from the cache for the given query, or `None` if the query is
+    /// not cached
+    pub(crate) async fn view_plan(
+        &mut self,
+        query: ViewCreateRequest,
+    ) -> ReadySetResult<Option<ViewPlan>> {
+        let dialect = self.dialect;
+        let noria = &mut self.inner.get_mut()?.noria;
+        noria.view_plan(query, dialect).await
+    }
+
     pub(crate) async fn verbose_views(
         &mut self,
         query_id: &Option<String>,
@@ -592,7 +693,7 @@ impl NoriaConnector {
         let data = views
             .into_iter()
             .map(|(n, (mut q, always))| {
-                if REDACT_SENSITIVE {
+                if redact_sensitive() {
                     anonymize_literals(&mut q);
                 }
                 vec![
@@ -673,7 +774,81 @@ impl NoriaConnector {
             })
             .collect::<Result<Vec<_>, _>>()?;
 
-        self.do_insert(&q, data).await
+        self.do_insert(&q, data, None).await
+    }
+
+    /// Deletes every row from each of the tables in the given `TRUNCATE` statement. The deleted
+    /// rows are propagated through the dataflow graph like any other deletion, so they're removed
+    /// from all caches which depend on those tables.
+    pub(crate) async fn handle_truncate(
+        &mut self,
+        q: &nom_sql::TruncateStatement,
+    ) -> ReadySetResult<QueryResult<'_>> {
+        for table in &q.tables {
+            trace!(table = %table.relation.name, "truncate::access mutator");
+            let mutator = self
+                .inner
+                .get_mut()?
+                .get_noria_table(&table.relation)
+                .await?;
+            mutator.truncate().await?;
+        }
+
+        Ok(QueryResult::Empty)
+    }
+
+    /// Apply an INSERT or DELETE statement which has already been executed successfully against
+    /// the upstream database directly to its base table, so that the write is visible to reads
+    /// before it arrives via replication.
+    ///
+    /// This relies on the base table having a primary key: base tables ignore inserts of rows whose
+    /// key already exists and deletes of rows which no longer exist, so when the same write later
+    /// arrives via replication it has no effect. Since the write is applied without a replication
+    /// offset, the replicated write is still applied, which corrects the row if ReadySet's copy
+    /// differs from the upstream database's. Writes whose effect on the base table can't be
+    /// determined from the statement alone - inserts which rely on an auto-incremented or
+    /// non-literal default value, upserts, and UPDATEs (whose modifications could be applied to a
+    /// row which is not yet up to date) - return an error and are not applied.
+    pub(crate) async fn write_through(&mut self, q: &SqlQuery) -> ReadySetResult<()> {
+        let table = match q {
+            SqlQuery::Insert(q) => &q.table,
+            SqlQuery::Delete(q) => &q.table,
+            _ => unsupported!("Only INSERT and DELETE statements can be written through"),
+        };
+        let putter = self.inner.get_mut()?.get_noria_table(table).await?;
+        let schema = putter
+            .schema()
+            .ok_or_else(|| internal_err!("no schema for table {}", table.display_unquoted()))?;
+        if utils::get_primary_key(schema).is_empty() {
+            unsupported!("Writes can only be written through to tables with a primary key");
+        }
+
+        match q {
+            SqlQuery::Insert(q) => {
+                if q.on_duplicate.is_some() {
+                    unsupported!("Upserts can't be written through");
+                }
+                if let Some(fields) = &q.fields {
+                    let auto_increment_unspecified = schema.fields.iter().any(|f| {
+                        f.constraints.contains(&ColumnConstraint::AutoIncrement)
+                            && !fields.iter().any(|c| c.name == f.column.name)
+                    });
+                    if auto_increment_unspecified {
+                        unsupported!(
+                            "Inserts which rely on an auto-incremented value can't be written \
+                             through"
+                        );
+                    }
+                }
+                self.handle_insert(q).await?;
+            }
+            SqlQuery::Delete(q) => {
+                self.handle_delete(q).await?;
+            }
+            _ => {}
+        }
+
+        Ok(())
     }
 
     pub async fn prepare_insert(
@@ -766,7 +941,24 @@ impl NoriaConnector {
                 )
                 .unwrap()
                 .unwrap();
-                self.do_insert(q, vec![coerced_params]).await
+
+                // Parameters for the inserted rows come first, followed by any parameters in the
+                // ON DUPLICATE KEY UPDATE clause
+                let num_data_params = q
+                    .data
+                    .iter()
+                    .flatten()
+                    .filter(|e| matches!(e, Expr::Literal(Literal::Placeholder(_))))
+                    .count();
+                if coerced_params.len() < num_data_params {
+                    return Err(bad_request_err(
+                        "Not enough parameter values given in EXECUTE",
+                    ));
+                }
+                let (data_params, update_params) = coerced_params.split_at(num_data_params);
+                let row_len = q.fields.as_ref().map_or(0, |f| f.len()).max(1);
+                let data = data_params.chunks(row_len).map(Vec::from).collect();
+                self.do_insert(q, data, Some(update_params)).await
             }
             _ => {
                 internal!(
@@ -791,6 +983,10 @@ impl NoriaConnector {
 
         trace!("delete::extract schema");
         let pkey = if let Some(cts) = mutator.schema() {
+            if utils::where_refers_to_non_pkey_columns(cond, cts) {
+                return self.do_delete(Cow::Borrowed(q), None).await;
+            }
+
             utils::get_primary_key(cts)
                 .into_iter()
                 .map(|(_, c)| c)
@@ -808,18 +1004,16 @@ impl NoriaConnector {
                 unsupported!("DELETE only supports WHERE-clauses on primary keys")
             }
             Some(flattened) => {
-                let count = flattened.len() as u64;
                 trace!("delete::execute");
-                for key in flattened {
-                    if let Err(e) = mutator.delete(key).await {
-                        error!(error = %e, "failed");
-                        return Err(e);
-                    };
-                }
+                let num_rows_deleted =
+                    self.delete_cascading(&q.table, flattened)
+                        .await
+                        .map_err(|e| {
+                            error!(error = %e, "failed");
+                            e
+                        })?;
                 trace!("delete::done");
-                Ok(QueryResult::Delete {
-                    num_rows_deleted: count,
-                })
+                Ok(QueryResult::Delete { num_rows_deleted })
             }
         }
     }
@@ -955,6 +1149,7 @@ impl NoriaConnector {
     {
         // TODO(malte): we should perhaps check our usual caches here, rather than just blindly
         // doing a migration on ReadySet ever time. On the other hand, CREATE TABLE is rare...
+        self.inner.get_mut()?.cascading_foreign_keys.clear();
         noria_await!(
             self.inner.get_mut()?,
             self.inner.get_mut()?.noria.extend_recipe(
@@ -1014,6 +1209,51 @@ impl NoriaConnector {
         Ok(QueryResult::from_owned(schema, vec![Results::new(data)]))
     }
 
+    /// Returns the position in the upstream database's replication log up to which each table has
+    /// been replicated, in the upstream database's own format, so that clients can compare it
+    /// against the position of their own writes. Tables which have not yet finished snapshotting
+    /// have a position of `NULL`.
+    pub(crate) async fn replication_positions(&mut self) -> ReadySetResult<QueryResult<'static>> {
+        let offsets = noria_await!(
+            self.inner.get_mut()?,
+            self.inner.get_mut()?.noria.replication_offsets()
+        )?;
+
+        let schema = SelectSchema {
+            use_bogo: false,
+            schema: Cow::Owned(
+                ["table", "replication position"]
+                    .iter()
+                    .map(|name| ColumnSchema {
+                        column: nom_sql::Column {
+                            name: name.into(),
+                            table: None,
+                        },
+                        column_type: DfType::DEFAULT_TEXT,
+                        base: None,
+                    })
+                    .collect(),
+            ),
+            columns: Cow::Owned(vec!["table".into(), "replication position".into()]),
+        };
+
+        let mut tables = offsets.tables.into_iter().collect::<Vec<_>>();
+        tables.sort_by(|(t1, _), (t2, _)| t1.cmp(t2));
+        let data = tables
+            .into_iter()
+            .map(|(tbl, offset)| {
+                vec![
+                    tbl.display(self.parse_dialect).to_string().into(),
+                    offset
+                        .map(|offset| offset.upstream_position().into())
+                        .unwrap_or(DfValue::None),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        Ok(QueryResult::from_owned(schema, vec![Results::new(data)]))
+    }
+
     /// Set the schema search path
     pub fn set_schema_search_path(&mut self, search_path: Vec<SqlIdentifier>) {
         self.schema_search_path = search_path;
@@ -1026,6 +1266,136 @@ impl NoriaConnector {
 }
 
 impl NoriaConnector {
+    /// Make a request to ReadySet to evict all partially materialized state, or only the state
+    /// read by the cache with the given name, so that subsequent reads are replayed from the base
+    /// tables. Returns the number of bytes evicted.
+    pub async fn flush_cache(&mut self, name: Option<&Relation>) -> ReadySetResult<u64> {
+        let name = match (name, &self.experiments) {
+            (Some(name), Some(experiments)) => Some(experiments.resolve(name)),
+            (name, _) => name.cloned(),
+        };
+        noria_await!(
+            self.inner.get_mut()?,
+            self.inner.get_mut()?.noria.flush_partial(name)
+        )
+    }
+
+    /// Make a request to ReadySet to evict the given keys from the reader of the cache with the
+    /// given name, so that subsequent reads of those keys are replayed. Each key consists of the
+    /// values for the parameters of the cached query, in order.
+    pub async fn invalidate_keys(
+        &mut self,
+        name: &Relation,
+        keys: Vec<Vec<DfValue>>,
+    ) -> ReadySetResult<()> {
+        let name = match &self.experiments {
+            Some(experiments) => experiments.resolve(name),
+            None => name.clone(),
+        };
+        let dialect = self.dialect;
+        let view = self.inner.get_mut()?.get_noria_view(&name, false).await?;
+        // Build a view query for the keys so they're mapped and coerced to the key columns of the
+        // reader exactly the same way as for reads
+        let raw_keys = keys.iter().map(|k| Cow::Borrowed(k.as_slice())).collect();
+        let (reader, key_comparisons) =
+            match view.build_view_query(raw_keys, None, None, None, false, dialect)? {
+                Some((reader, query)) => (reader.name().clone(), query.key_comparisons),
+                // The keys don't match the values inlined into any of the readers for the view,
+                // so there's nothing to evict
+                None => return Ok(()),
+            };
+        noria_await!(
+            self.inner.get_mut()?,
+            self.inner
+                .get_mut()?
+                .noria
+                .invalidate_keys(reader, key_comparisons)
+        )
+    }
+
+    /// Replay the keys given in the `WITH WARMUP` clause of a `CREATE CACHE` statement into the
+    /// reader of the cache with the given name, which must already exist. Returns the number of
+    /// keys replayed.
+    pub async fn warm_up_cache(
+        &mut self,
+        name: &Relation,
+        warmup: &CacheWarmup,
+    ) -> ReadySetResult<usize> {
+        let keys = match warmup {
+            CacheWarmup::Keys(keys) => keys
+                .iter()
+                .map(|key| key.iter().map(DfValue::try_from).collect())
+                .collect::<ReadySetResult<Vec<Vec<DfValue>>>>()?,
+            CacheWarmup::Query(stmt) => self.read_warm_up_keys((**stmt).clone()).await?,
+        };
+        if keys.is_empty() {
+            return Ok(0);
+        }
+
+        let dialect = self.dialect;
+        let view = self.inner.get_mut()?.get_noria_view(name, false).await?;
+        // Build a view query for the keys so they're mapped and coerced to the key columns of the
+        // reader exactly the same way as for reads
+        let raw_keys = keys.iter().map(|k| Cow::Borrowed(k.as_slice())).collect();
+        let (reader, key_comparisons) =
+            match view.build_view_query(raw_keys, None, None, None, true, dialect)? {
+                Some((reader, query)) => (reader.name().clone(), query.key_comparisons),
+                // The keys don't match the values inlined into any of the readers for the view
+                None => return Ok(0),
+            };
+        noria_await!(
+            self.inner.get_mut()?,
+            self.inner
+                .get_mut()?
+                .noria
+                .warm_up_view(reader, key_comparisons)
+        )
+    }
+
+    /// Returns the result rows of the query given in a `WITH WARMUP` clause, to use as keys to
+    /// warm up a cache with. If the query isn't cached yet, it's migrated to read from and then
+    /// dropped again.
+    async fn read_warm_up_keys(
+        &mut self,
+        mut statement: SelectStatement,
+    ) -> ReadySetResult<Vec<Vec<DfValue>>> {
+        verify_no_placeholders(&statement)?;
+        self.check_select_privileges(&tables_read_by(&statement))?;
+        let processed_query_params =
+            rewrite::process_query(&mut statement, self.server_supports_pagination())?;
+        let view_request =
+            ViewCreateRequest::new(statement.clone(), self.schema_search_path().to_vec());
+        let already_cached = self.view_cache.statement_name(&view_request).is_some();
+        let name = self.get_view(&statement, false, true, None).await?;
+
+        let mut event = readyset_client_metrics::QueryExecutionEvent::new(
+            readyset_client_metrics::EventType::Query,
+        );
+        let res = self
+            .read_owned(&name, &processed_query_params, &[], None, &mut event)
+            .await;
+        if !already_cached {
+            if let Err(error) = self.drop_view(&name).await {
+                warn!(
+                    %error,
+                    cache = %name.display_unquoted(),
+                    "Failed to drop cache created for warm-up query"
+                );
+            }
+        }
+
+        let (schema, rows) = res?;
+        let num_columns = schema.schema.len();
+        Ok(rows
+            .into_iter()
+            .map(|mut row| {
+                // Drop any columns the reader returns beyond the projected ones
+                row.truncate(num_columns);
+                row
+            })
+            .collect())
+    }
+
     /// This function handles CREATE CACHE statements. When explicit-migrations is enabled,
     /// this function is the only way to create a view in noria.
     pub async fn handle_create_cached_query(
@@ -1034,6 +1404,7 @@ impl NoriaConnector {
         statement: &nom_sql::SelectStatement,
         override_schema_search_path: Option<Vec<SqlIdentifier>>,
         always: bool,
+        options: CacheOptions,
     ) -> ReadySetResult<()> {
         let name = name.cloned().unwrap_or_else(|| {
             utils::generate_query_name(statement, self.schema_search_path()).into()
@@ -1041,7 +1412,12 @@ impl NoriaConnector {
         let schema_search_path =
             override_schema_search_path.unwrap_or_else(|| self.schema_search_path.clone());
         let changelist = ChangeList::from_change(
-            Change::create_cache(name.clone(), statement.clone(), always),
+            Change::CreateCache {
+                name: Some(name.clone()),
+                statement: Box::new(statement.clone()),
+                always,
+                options,
+            },
             self.dialect,
         )
         .with_schema_search_path(schema_search_path.clone());
@@ -1051,6 +1427,10 @@ impl NoriaConnector {
             self.inner.get_mut()?.noria.extend_recipe(changelist)
         )?;
 
+        if let Some(experiments) = &self.experiments {
+            experiments.cache_created(&name);
+        }
+
         // If the query is already in there with a different name, we don't need to make a new name
         // for it, as *lookups* only need one of the names for the query, and when we drop it we'll
         // be hitting noria anyway
@@ -1098,10 +1478,22 @@ impl NoriaConnector {
                     )
                     .with_schema_search_path(search_path);
 
-                    if let Err(error) = noria_await!(
-                        self.inner.get_mut()?,
-                        self.inner.get_mut()?.noria.extend_recipe(changelist)
-                    ) {
+                    let res = match &self.migration_queue {
+                        Some(queue) => {
+                            queue
+                                .migrate(
+                                    &self.inner.get_mut()?.noria,
+                                    view_request.clone(),
+                                    changelist,
+                                )
+                                .await
+                        }
+                        None => noria_await!(
+                            self.inner.get_mut()?,
+                            self.inner.get_mut()?.noria.extend_recipe(changelist)
+                        ),
+                    };
+                    if let Err(error) = res {
                         if error.caused_by_table_not_replicated() {
                             warn!(%error, "add query failed");
                         } else {
@@ -1135,13 +1527,24 @@ impl NoriaConnector {
 
     /// Make a request to ReadySet to drop the query with the given name, and remove it from all
     /// internal state.
+    ///
+    /// If the query doesn't exist in ReadySet, it's still removed from internal state, but an error
+    /// that was [caused by `ViewNotFound`](ReadySetError::caused_by_view_not_found) is returned.
     pub async fn drop_view(&mut self, name: &Relation) -> ReadySetResult<()> {
-        noria_await!(
+        let res = noria_await!(
             self.inner.get_mut()?,
             self.inner.get_mut()?.noria.remove_query(name)
-        )?;
+        );
+        if let Err(e) = &res {
+            if !e.caused_by_view_not_found() {
+                return res;
+            }
+        }
         self.view_cache.remove_statement(name);
-        Ok(())
+        if let Some(experiments) = &self.experiments {
+            experiments.cache_dropped(name);
+        }
+        res
     }
 
     /// Make a request to ReadySet to drop all cached queries, and empty all internal state
@@ -1151,6 +1554,9 @@ impl NoriaConnector {
             self.inner.get_mut()?.noria.remove_all_queries()
         )?;
         self.view_cache.clear();
+        if let Some(experiments) = &self.experiments {
+            experiments.clear();
+        }
         Ok(())
     }
 
@@ -1158,10 +1564,14 @@ impl NoriaConnector {
         self.view_cache.view_create_request_from_name(name)
     }
 
+    /// Insert the given rows into the table for the given INSERT statement. `update_params` are the
+    /// values for any placeholders in the statement's `ON DUPLICATE KEY UPDATE` or
+    /// `ON CONFLICT DO UPDATE` clause.
     async fn do_insert(
         &mut self,
         q: &InsertStatement,
         data: Vec<Vec<DfValue>>,
+        update_params: Option<&[DfValue]>,
     ) -> ReadySetResult<QueryResult<'_>> {
         let table = &q.table;
 
@@ -1305,27 +1715,69 @@ impl NoriaConnector {
             Ok(())
         })?;
 
+        if let Some(target) = &q.on_conflict {
+            // Base tables only enforce uniqueness of their primary key, so that's the only
+            // conflict we can detect
+            let primary_key = utils::get_primary_key(schema)
+                .into_iter()
+                .map(|(_, c)| &c.name)
+                .collect::<HashSet<_>>();
+            if !target.is_empty()
+                && target.iter().map(|c| &c.name).collect::<HashSet<_>>() != primary_key
+            {
+                unsupported!("ON CONFLICT is only supported for the primary key of a table");
+            }
+        }
+
         let result = if let Some(ref update_fields) = q.on_duplicate {
             trace!("insert::complex");
-            invariant_eq!(buf.len(), 1);
-
-            let updates = {
-                // fake out an update query
-                let mut uq = UpdateStatement {
-                    table: table.clone(),
-                    fields: update_fields.clone(),
-                    where_clause: None,
-                };
-                utils::extract_update_params_and_fields(
-                    &mut uq,
-                    &mut None::<std::iter::Empty<DfValue>>,
-                    schema,
-                    self.dialect,
-                )?
+            // Each row is inserted if no row with its primary key exists yet, and otherwise
+            // updates the existing row (which is left unchanged for `ON CONFLICT DO NOTHING`). The
+            // updates can differ per row, since they can refer to the row that would have been
+            // inserted with `VALUES(<column>)` or `EXCLUDED.<column>`
+            let mut upserts = buf
+                .into_iter()
+                .map(|row| {
+                    // fake out an update query
+                    let mut uq = UpdateStatement {
+                        table: table.clone(),
+                        fields: update_fields.clone(),
+                        where_clause: None,
+                    };
+                    let update = utils::extract_update_params_and_fields(
+                        &mut uq,
+                        &mut update_params.map(|p| p.iter().cloned()),
+                        Some(&row),
+                        schema,
+                        self.dialect,
+                    )?;
+                    Ok((row, update))
+                })
+                .collect::<ReadySetResult<Vec<_>>>()?;
+
+            // A single row is upserted by its primary key, which returns the number of affected
+            // rows counted per row: 1 if it was inserted, 2 if an existing row was changed, and 0
+            // if it was left unchanged. Several rows are upserted in a single batch instead, to
+            // avoid a round trip to the base table per row.
+            let single_key = match upserts.as_slice() {
+                [(row, _)] => putter
+                    .key()
+                    .iter()
+                    .map(|col| row.get(*col).cloned())
+                    .collect::<Option<Vec<_>>>(),
+                _ => None,
+            };
+            let r = if let Some(key) = single_key {
+                #[allow(clippy::unwrap_used)] // We just checked there's exactly one row
+                let (row, update) = upserts.pop().unwrap();
+                putter.upsert(key, row, update).await
+            } else {
+                let ops = upserts
+                    .into_iter()
+                    .map(|(row, update)| putter.insert_or_update_operation(row, update))
+                    .collect::<ReadySetResult<Vec<_>>>()?;
+                putter.perform_all(ops).await
             };
-
-            // TODO(malte): why can't I consume buf here?
-            let r = putter.insert_or_update(buf[0].clone(), updates).await;
             trace!("insert::complex::complete");
             r
         } else {
@@ -1335,9 +1787,8 @@ impl NoriaConnector {
             trace!("insert::simple::complete");
             r
         };
-        result?;
         Ok(QueryResult::Insert {
-            num_rows_inserted: data.len() as u64,
+            num_rows_inserted: result?,
             first_inserted_id: first_inserted_id.unwrap_or(0) as u64,
         })
     }
@@ -1351,31 +1802,36 @@ impl NoriaConnector {
         let mutator = self.inner.get_mut()?.get_noria_table(&q.table).await?;
 
         let q = q.into_owned();
-        let (key, updates) = {
-            trace!("update::extract schema");
-            let schema = if let Some(cts) = mutator.schema() {
-                cts
-            } else {
-                // no update on views
-                unsupported!();
-            };
-            let coerced_params =
-                utils::coerce_params(params, &SqlQuery::Update(q.clone()), schema, self.dialect)?;
-            utils::extract_update(
-                q,
-                coerced_params.map(|p| p.into_iter()),
-                schema,
-                self.dialect,
-            )?
+        let table = q.table.clone();
+        trace!("update::extract schema");
+        let schema = if let Some(cts) = mutator.schema() {
+            cts.clone()
+        } else {
+            // no update on views
+            unsupported!();
         };
+        let coerced_params =
+            utils::coerce_params(params, &SqlQuery::Update(q.clone()), &schema, self.dialect)?;
+        let (target, updates) = utils::extract_update(
+            q,
+            coerced_params.map(|p| p.into_iter()),
+            &schema,
+            self.dialect,
+        )?;
+        let keys = self
+            .resolve_mutation_target(&table, &schema, target)
+            .await?;
 
-        trace!("update::update");
-        mutator.update(key, updates).await?;
+        trace!(num_keys = keys.len(), "update::update");
+        let mutator = self.inner.get_mut()?.get_noria_table(&table).await?;
+        let mut num_rows_updated = 0;
+        for key in keys {
+            num_rows_updated += mutator.update(key, updates.clone()).await?;
+        }
         trace!("update::complete");
-        // TODO: return meaningful fields for (num_rows_updated, last_inserted_id) rather than
-        // hardcoded (1,0)
+        // Updates never insert rows, so there's no meaningful last inserted id to return
         Ok(QueryResult::Update {
-            num_rows_updated: 1,
+            num_rows_updated,
             last_inserted_id: 0,
         })
     }
@@ -1389,27 +1845,182 @@ impl NoriaConnector {
         let mutator = self.inner.get_mut()?.get_noria_table(&q.table).await?;
 
         let q = q.into_owned();
-        let key = {
-            trace!("delete::extract schema");
-            let schema = if let Some(cts) = mutator.schema() {
-                cts
-            } else {
-                // no delete on views
-                unsupported!();
-            };
-            let coerced_params =
-                utils::coerce_params(params, &SqlQuery::Delete(q.clone()), schema, self.dialect)?;
-            utils::extract_delete(q, coerced_params.map(|p| p.into_iter()), schema)?
+        let table = q.table.clone();
+        trace!("delete::extract schema");
+        let schema = if let Some(cts) = mutator.schema() {
+            cts.clone()
+        } else {
+            // no delete on views
+            unsupported!();
         };
+        let coerced_params =
+            utils::coerce_params(params, &SqlQuery::Delete(q.clone()), &schema, self.dialect)?;
+        let target = utils::extract_delete(q, coerced_params.map(|p| p.into_iter()), &schema)?;
+        let keys = self
+            .resolve_mutation_target(&table, &schema, target)
+            .await?;
 
-        trace!("delete::delete");
-        mutator.delete(key).await?;
+        trace!(num_keys = keys.len(), "delete::delete");
+        let num_rows_deleted = self.delete_cascading(&table, keys).await?;
         trace!("delete::complete");
-        // TODO: return meaningful fields for (num_rows_deleted, last_inserted_id) rather than
-        // hardcoded (1,0)
-        Ok(QueryResult::Delete {
-            num_rows_deleted: 1,
-        })
+        Ok(QueryResult::Delete { num_rows_deleted })
+    }
+
+    /// Deletes the rows of `table` with the given primary keys, along with (recursively) the rows
+    /// of any other tables which reference them through a foreign key declared with `ON DELETE
+    /// CASCADE`, to match the behavior of the upstream database. Returns the number of rows
+    /// deleted from `table` itself.
+    ///
+    /// Referencing rows are found with [`Self::resolve_mutation_target`], so rows written very
+    /// recently may not be deleted.
+    async fn delete_cascading(
+        &mut self,
+        table: &Relation,
+        keys: Vec<Vec<DfValue>>,
+    ) -> ReadySetResult<u64> {
+        let mut num_rows_deleted = None;
+        let mut pending = vec![(table.clone(), keys)];
+        while let Some((table, keys)) = pending.pop() {
+            if keys.is_empty() {
+                num_rows_deleted.get_or_insert(0);
+                continue;
+            }
+
+            // Find the foreign keys to cascade to before deleting anything, so that unsupported
+            // foreign keys don't leave the delete half-applied
+            let cascades = self.cascading_foreign_keys(&table).await?;
+
+            let mutator = self.inner.get_mut()?.get_noria_table(&table).await?;
+            let mut deleted = Vec::with_capacity(keys.len());
+            let mut n = 0;
+            for key in keys {
+                let deleted_rows = mutator.delete(key.clone()).await?;
+                if deleted_rows > 0 {
+                    n += deleted_rows;
+                    deleted.push(key);
+                }
+            }
+            num_rows_deleted.get_or_insert(n);
+
+            for (fk, referencing_schema) in cascades {
+                let mut referencing_keys = vec![];
+                for key in &deleted {
+                    let Some(where_clause) = fk.referencing_rows(key)? else {
+                        continue;
+                    };
+                    trace!(table = %fk.table.display_unquoted(), "delete::cascade");
+                    referencing_keys.extend(
+                        self.resolve_mutation_target(
+                            &fk.table,
+                            &referencing_schema,
+                            utils::MutationTarget::Lookup {
+                                where_clause,
+                                params: vec![],
+                            },
+                        )
+                        .await?,
+                    );
+                }
+                pending.push((fk.table, referencing_keys));
+            }
+        }
+
+        Ok(num_rows_deleted.unwrap_or(0))
+    }
+
+    /// Returns the foreign keys declared with `ON DELETE CASCADE` which reference `table`, along
+    /// with the schemas of their referencing tables, looking them up in the schemas of all tables
+    /// the first time rows are deleted from `table`.
+    async fn cascading_foreign_keys(
+        &mut self,
+        table: &Relation,
+    ) -> ReadySetResult<Vec<(utils::CascadingForeignKey, CreateTableBody)>> {
+        if let Some(cascades) = self.inner.get_mut()?.cascading_foreign_keys.get(table) {
+            return Ok(cascades.clone());
+        }
+
+        let schema = self
+            .inner
+            .get_mut()?
+            .get_noria_table(table)
+            .await?
+            .schema()
+            .cloned()
+            .ok_or_else(|| internal_err!("no schema for table {}", table.display_unquoted()))?;
+        let inner = self.inner.get_mut()?;
+        let tables = noria_await!(inner, inner.noria.tables())?;
+        let mut cascades = vec![];
+        for referencing in tables.into_keys() {
+            let Some(referencing_schema) = self
+                .inner
+                .get_mut()?
+                .get_noria_table(&referencing)
+                .await?
+                .schema()
+                .cloned()
+            else {
+                continue;
+            };
+            for fk in
+                utils::cascading_foreign_keys(table, &schema, &referencing, &referencing_schema)?
+            {
+                cascades.push((fk, referencing_schema.clone()));
+            }
+        }
+
+        self.inner
+            .get_mut()?
+            .cascading_foreign_keys
+            .insert(table.clone(), cascades.clone());
+        Ok(cascades)
+    }
+
+    /// Returns the primary keys of the rows of `table` targeted by an UPDATE or DELETE statement.
+    ///
+    /// If the statement's WHERE clause refers to columns other than the primary key, the keys are
+    /// resolved by reading from a parameterized view projecting the primary key of all the rows
+    /// matching the WHERE clause, which is created if it doesn't exist yet. Since reads are
+    /// eventually consistent, rows written very recently may not be matched.
+    async fn resolve_mutation_target(
+        &mut self,
+        table: &Relation,
+        schema: &CreateTableBody,
+        target: utils::MutationTarget,
+    ) -> ReadySetResult<Vec<Vec<DfValue>>> {
+        let (where_clause, params) = match target {
+            utils::MutationTarget::Key(key) => return Ok(vec![key]),
+            utils::MutationTarget::Lookup {
+                where_clause,
+                params,
+            } => (where_clause, params),
+        };
+
+        let mut statement = utils::pkey_lookup_query(table, schema, where_clause);
+        trace!(
+            query = %Sensitive(&statement.display(self.parse_dialect)),
+            "mutation::lookup primary keys"
+        );
+        let processed_query_params =
+            rewrite::process_query(&mut statement, self.server_supports_pagination())?;
+        let name = self.get_view(&statement, true, true, None).await?;
+
+        let mut event = readyset_client_metrics::QueryExecutionEvent::new(
+            readyset_client_metrics::EventType::Query,
+        );
+        let (_, rows) = self
+            .read_owned(&name, &processed_query_params, &params, None, &mut event)
+            .await?;
+
+        let pkey_len = utils::get_primary_key(schema).len();
+        Ok(rows
+            .into_iter()
+            .map(|mut row| {
+                // Drop any columns the reader returns beyond the projected primary key
+                row.truncate(pkey_len);
+                row
+            })
+            .unique()
+            .collect())
     }
 
     #[instrument(level = "info", skip(self, statement))]
@@ -1438,6 +2049,8 @@ impl NoriaConnector {
             })
             .collect();
 
+        let tables = tables_read_by(&statement);
+
         trace!("select::collapse where-in clauses");
         let processed_query_params =
             rewrite::process_query(&mut statement, self.server_supports_pagination())?;
@@ -1476,6 +2089,7 @@ impl NoriaConnector {
         let ps = PreparedSelectStatement {
             name: qname.clone(),
             processed_query_params,
+            tables,
         };
         self.prepared_statement_cache
             .insert(statement_id, PreparedStatement::Select(ps));
@@ -1513,11 +2127,14 @@ impl NoriaConnector {
         ticket: Option<Timestamp>,
         event: &mut readyset_client_metrics::QueryExecutionEvent,
     ) -> ReadySetResult<QueryResult<'_>> {
-        let (qname, processed_query_params, params) = match ctx {
+        // Reads pinned to a particular view aren't routed by experiments
+        let pinned = matches!(ctx, ExecuteSelectContext::AdHoc { view: Some(_), .. });
+        let (mut qname, processed_query_params, params) = match ctx {
             ExecuteSelectContext::Prepared { q_id, params } => {
                 let PreparedSelectStatement {
                     name,
                     processed_query_params,
+                    tables,
                 } = {
                     match self.prepared_statement_cache.get(&q_id) {
                         Some(PreparedStatement::Select(ps)) => ps,
@@ -1525,6 +2142,7 @@ impl NoriaConnector {
                         None => return Err(PreparedStatementMissing { statement_id: q_id }),
                     }
                 };
+                self.check_select_privileges(tables)?;
                 (
                     Cow::Borrowed(name),
                     Cow::Borrowed(processed_query_params),
@@ -1534,13 +2152,19 @@ impl NoriaConnector {
             ExecuteSelectContext::AdHoc {
                 mut statement,
                 create_if_missing,
+                view,
             } => {
                 verify_no_placeholders(&statement)?;
+                self.check_select_privileges(&tables_read_by(&statement))?;
                 let processed_query_params =
                     rewrite::process_query(&mut statement, self.server_supports_pagination())?;
-                let name = self
-                    .get_view(&statement, false, create_if_missing, None)
-                    .await?;
+                let name = match view {
+                    Some(view) => view,
+                    None => {
+                        self.get_view(&statement, false, create_if_missing, None)
+                            .await?
+                    }
+                };
                 (
                     Cow::Owned(name),
                     Cow::Owned(processed_query_params),
@@ -1549,6 +2173,24 @@ impl NoriaConnector {
             }
         };
 
+        if let Some(experiments) = self.experiments.clone().filter(|_| !pinned) {
+            if let Some(candidate) = experiments.promoted(&qname) {
+                qname = Cow::Owned(candidate);
+            }
+            if let Some(experiment) = experiments.get(&qname) {
+                let processed_query_params = processed_query_params.into_owned();
+                return self
+                    .execute_experiment_select(
+                        &experiment,
+                        &processed_query_params,
+                        params,
+                        ticket,
+                        event,
+                    )
+                    .await;
+            }
+        }
+
         let view_failed = self.failed_views.take(qname.as_ref()).is_some();
         let getter = self
             .inner
@@ -1562,6 +2204,8 @@ impl NoriaConnector {
             params,
             ticket,
             self.read_behavior,
+            self.read_timeout,
+            self.result_limits,
             self.read_request_handler.as_mut(),
             event,
             self.dialect,
@@ -1569,7 +2213,7 @@ impl NoriaConnector {
         .await;
 
         if let Err(e) = res.as_ref() {
-            if e.is_networking_related() || e.caused_by_view_destroyed() {
+            if e.is_stale_reader_address() {
                 self.failed_views.insert(qname.into_owned());
             }
         }
@@ -1577,6 +2221,316 @@ impl NoriaConnector {
         res
     }
 
+    /// Execute a read of the control cache of the given experiment, routing it to the candidate
+    /// cache instead if chosen by the experiment. Failed reads of the candidate are retried against
+    /// the control cache.
+    async fn execute_experiment_select(
+        &mut self,
+        experiment: &Experiment,
+        processed_query_params: &ProcessedQueryParams,
+        params: &[DfValue],
+        ticket: Option<Timestamp>,
+        event: &mut readyset_client_metrics::QueryExecutionEvent,
+    ) -> ReadySetResult<QueryResult<'static>> {
+        if experiment.route_to_candidate() {
+            let start = Instant::now();
+            match self
+                .read_owned(
+                    &experiment.candidate,
+                    processed_query_params,
+                    params,
+                    ticket,
+                    event,
+                )
+                .await
+            {
+                Ok((schema, rows)) => {
+                    experiment.record_candidate_read(start.elapsed());
+                    if experiment.compare_results {
+                        self.compare_experiment_results(
+                            experiment,
+                            &rows,
+                            processed_query_params,
+                            params,
+                            ticket,
+                            event,
+                        )
+                        .await;
+                    }
+                    return Ok(QueryResult::from_owned(schema, vec![Results::new(rows)]));
+                }
+                Err(error) => {
+                    warn!(
+                        %error,
+                        cache = %experiment.control.display_unquoted(),
+                        candidate = %experiment.candidate.display_unquoted(),
+                        "Read from experiment candidate failed, retrying against existing cache"
+                    );
+                    experiment.record_candidate_error();
+                }
+            }
+        }
+
+        let start = Instant::now();
+        let (schema, rows) = self
+            .read_owned(
+                &experiment.control,
+                processed_query_params,
+                params,
+                ticket,
+                event,
+            )
+            .await?;
+        experiment.record_control_read(start.elapsed());
+        Ok(QueryResult::from_owned(schema, vec![Results::new(rows)]))
+    }
+
+    /// Read the control cache of the given experiment, and compare its results (ignoring row order)
+    /// to the given results of a read of the candidate cache, recording a mismatch if they differ
+    async fn compare_experiment_results(
+        &mut self,
+        experiment: &Experiment,
+        candidate_rows: &[Vec<DfValue>],
+        processed_query_params: &ProcessedQueryParams,
+        params: &[DfValue],
+        ticket: Option<Timestamp>,
+        event: &mut readyset_client_metrics::QueryExecutionEvent,
+    ) {
+        let start = Instant::now();
+        match self
+            .read_owned(
+                &experiment.control,
+                processed_query_params,
+                params,
+                ticket,
+                event,
+            )
+            .await
+        {
+            Ok((_, control_rows)) => {
+                experiment.record_control_read(start.elapsed());
+                if control_rows
+                    .iter()
+                    .sorted()
+                    .ne(candidate_rows.iter().sorted())
+                {
+                    warn!(
+                        cache = %experiment.control.display_unquoted(),
+                        candidate = %experiment.candidate.display_unquoted(),
+                        control_rows = control_rows.len(),
+                        candidate_rows = candidate_rows.len(),
+                        "Results from experiment candidate differ from existing cache"
+                    );
+                    experiment.record_mismatch();
+                }
+            }
+            Err(error) => warn!(
+                %error,
+                cache = %experiment.control.display_unquoted(),
+                "Failed to read existing cache to compare experiment results"
+            ),
+        }
+    }
+
+    /// Execute a read against the view with the given name, returning the schema and rows of the
+    /// result set as owned values
+    async fn read_owned(
+        &mut self,
+        name: &Relation,
+        processed_query_params: &ProcessedQueryParams,
+        params: &[DfValue],
+        ticket: Option<Timestamp>,
+        event: &mut readyset_client_metrics::QueryExecutionEvent,
+    ) -> ReadySetResult<(SelectSchema<'static>, Vec<Vec<DfValue>>)> {
+        let view_failed = self.failed_views.take(name).is_some();
+        let getter = self
+            .inner
+            .get_mut()?
+            .get_noria_view(name, view_failed)
+            .await?;
+
+        let res = do_read(
+            getter,
+            processed_query_params,
+            params,
+            ticket,
+            self.read_behavior,
+            self.read_timeout,
+            self.result_limits,
+            self.read_request_handler.as_mut(),
+            event,
+            self.dialect,
+        )
+        .await;
+
+        match res {
+            Ok(QueryResult::Select { schema, rows }) => Ok((schema.into_owned(), rows.into_vec())),
+            Ok(_) => internal!("Read returned a non-SELECT result"),
+            Err(e) => {
+                if e.is_stale_reader_address() {
+                    self.failed_views.insert(name.clone());
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns the cache experiments running in this adapter, or an error if experiments are not
+    /// enabled
+    fn cache_experiments(&self) -> ReadySetResult<Arc<CacheExperiments>> {
+        match &self.experiments {
+            Some(experiments) => Ok(Arc::clone(experiments)),
+            None => unsupported!("Cache experiments are not enabled"),
+        }
+    }
+
+    /// Start an experiment routing `percent` percent of the reads of the existing cache with the
+    /// given name to a new candidate cache for `statement`, which must already have been rewritten
+    /// with [`rewrite::process_query`]
+    pub(crate) async fn create_experiment(
+        &mut self,
+        cache: &Relation,
+        statement: &SelectStatement,
+        percent: u8,
+        compare_results: bool,
+    ) -> ReadySetResult<()> {
+        let experiments = self.cache_experiments()?;
+        let control = experiments.resolve(cache);
+        let views = noria_await!(self.inner.get_mut()?, self.inner.get_mut()?.noria.views())?;
+        if !views.contains_key(&control) {
+            return Err(ReadySetError::ViewNotFound(
+                cache.display_unquoted().to_string(),
+            ));
+        }
+
+        let candidate: Relation =
+            utils::generate_query_name(statement, self.schema_search_path()).into();
+        if candidate == control {
+            invalid!(
+                "The query for an experiment must differ from the query of the existing cache"
+            );
+        }
+        self.handle_create_cached_query(
+            Some(&candidate),
+            statement,
+            None,
+            false,
+            CacheOptions::default(),
+        )
+        .await?;
+        experiments.start(control.clone(), candidate.clone(), percent, compare_results)?;
+        info!(
+            cache = %control.display_unquoted(),
+            candidate = %candidate.display_unquoted(),
+            percent,
+            compare_results,
+            "Started cache experiment"
+        );
+        Ok(())
+    }
+
+    /// End the experiment running for the cache with the given name, replacing the existing cache
+    /// with the experiment's candidate for all future reads
+    pub(crate) async fn promote_experiment(&mut self, cache: &Relation) -> ReadySetResult<()> {
+        let experiments = self.cache_experiments()?;
+        let experiment = experiments.promote(&experiments.resolve(cache))?;
+        noria_await!(
+            self.inner.get_mut()?,
+            self.inner
+                .get_mut()?
+                .noria
+                .remove_query(&experiment.control)
+        )?;
+        info!(
+            cache = %experiment.control.display_unquoted(),
+            candidate = %experiment.candidate.display_unquoted(),
+            "Promoted cache experiment"
+        );
+        Ok(())
+    }
+
+    /// End the experiment running for the cache with the given name, dropping the experiment's
+    /// candidate cache
+    pub(crate) async fn drop_experiment(&mut self, cache: &Relation) -> ReadySetResult<()> {
+        let experiments = self.cache_experiments()?;
+        let experiment = match experiments.remove(&experiments.resolve(cache)) {
+            Some(experiment) => experiment,
+            None => invalid!(
+                "No experiment is running for cache {}",
+                cache.display_unquoted()
+            ),
+        };
+        self.drop_view(&experiment.candidate).await?;
+        info!(
+            cache = %experiment.control.display_unquoted(),
+            candidate = %experiment.candidate.display_unquoted(),
+            "Dropped cache experiment"
+        );
+        Ok(())
+    }
+
+    /// Returns the status of all running cache experiments, as a response to a `SHOW EXPERIMENTS`
+    /// query
+    pub(crate) fn experiment_statuses(&self) -> ReadySetResult<QueryResult<'static>> {
+        let experiments = self.cache_experiments()?;
+        let columns = [
+            "cache",
+            "candidate",
+            "percent",
+            "compare results",
+            "control reads",
+            "control mean latency (us)",
+            "candidate reads",
+            "candidate mean latency (us)",
+            "candidate errors",
+            "mismatches",
+        ];
+        let select_schema = SelectSchema {
+            use_bogo: false,
+            schema: Cow::Owned(
+                columns
+                    .iter()
+                    .map(|name| ColumnSchema {
+                        column: nom_sql::Column {
+                            name: (*name).into(),
+                            table: None,
+                        },
+                        column_type: DfType::DEFAULT_TEXT,
+                        base: None,
+                    })
+                    .collect(),
+            ),
+            columns: Cow::Owned(columns.iter().map(|name| (*name).into()).collect()),
+        };
+        let data = experiments
+            .list()
+            .into_iter()
+            .map(|experiment| {
+                let stats = experiment.stats();
+                vec![
+                    DfValue::from(experiment.control.display(self.parse_dialect).to_string()),
+                    DfValue::from(experiment.candidate.display(self.parse_dialect).to_string()),
+                    DfValue::from(experiment.percent.to_string()),
+                    DfValue::from(if experiment.compare_results {
+                        "yes"
+                    } else {
+                        "no"
+                    }),
+                    DfValue::from(stats.control_reads.to_string()),
+                    DfValue::from(stats.control_mean_latency.as_micros().to_string()),
+                    DfValue::from(stats.candidate_reads.to_string()),
+                    DfValue::from(stats.candidate_mean_latency.as_micros().to_string()),
+                    DfValue::from(stats.candidate_errors.to_string()),
+                    DfValue::from(stats.mismatches.to_string()),
+                ]
+            })
+            .collect::<Vec<_>>();
+        Ok(QueryResult::from_owned(
+            select_schema,
+            vec![Results::new(data)],
+        ))
+    }
+
     pub(crate) async fn handle_create_view<'a>(
         &'a mut self,
         q: &nom_sql::CreateViewStatement,
@@ -1664,6 +2618,12 @@ fn build_view_query<'a>(
     )
 }
 
+/// Errors from reads that exceeded the configured [`ResultLimits`] come back from remote readers
+/// wrapped in a view error; unwrap them so that they're reported to the client as-is.
+fn unwrap_result_limit_exceeded(e: ReadySetError) -> ReadySetError {
+    e.result_limit_exceeded_cause().cloned().unwrap_or(e)
+}
+
 /// Run the supplied [`SelectStatement`] on the supplied [`View`]
 /// Assumption: the [`View`] was created for that specific [`SelectStatement`]
 #[allow(clippy::needless_lifetimes)] // clippy erroneously thinks the timelife can be elided
@@ -1674,11 +2634,13 @@ async fn do_read<'a>(
     params: &[DfValue],
     ticket: Option<Timestamp>,
     read_behavior: ReadBehavior,
+    read_timeout: Option<Duration>,
+    result_limits: ResultLimits,
     read_request_handler: Option<&'a mut ReadRequestHandler>,
     event: &mut readyset_client_metrics::QueryExecutionEvent,
     dialect: Dialect,
 ) -> ReadySetResult<QueryResult<'a>> {
-    let (reader_handle, vq) = match build_view_query(
+    let (reader_handle, mut vq) = match build_view_query(
         getter,
         processed_query_params,
         params,
@@ -1690,6 +2652,8 @@ async fn do_read<'a>(
         None => return Err(ReadySetError::NoCacheForQuery),
     };
 
+    vq.timeout = read_timeout;
+    vq.result_limits = result_limits;
     event.num_keys = Some(vq.key_comparisons.len() as _);
 
     let data = if let Some(rh) = read_request_handler {
@@ -1723,10 +2687,16 @@ async fn do_read<'a>(
                 .into_unserialized()
                 .expect("Requested raw result")
         } else {
-            reader_handle.raw_lookup(vq).await?
+            reader_handle
+                .raw_lookup(vq)
+                .await
+                .map_err(unwrap_result_limit_exceeded)?
         }
     } else {
-        reader_handle.raw_lookup(vq).await?
+        reader_handle
+            .raw_lookup(vq)
+            .await
+            .map_err(unwrap_result_limit_exceeded)?
     };
 
     event.cache_misses = data.total_stats().map(|s| s.cache_misses);
@@ -0,0 +1,375 @@
+//! Blue/green experiments between an existing cache and a candidate cache for a rewritten version
+//! of its query.
+//!
+//! An experiment is started with `CREATE EXPERIMENT FOR <cache> ... FROM <select>`, which creates
+//! the candidate cache and registers the experiment in the adapter's [`CacheExperiments`]. From
+//! then on, the configured percentage of reads of the existing (control) cache are executed
+//! against the candidate instead, falling back to the control cache if the candidate read fails.
+//! Experiments created with `COMPARE RESULTS` additionally execute each of those reads against the
+//! control cache, and report any differences between the two result sets. Statistics for all
+//! running experiments are available with `SHOW EXPERIMENTS`.
+//!
+//! An experiment ends either with `PROMOTE EXPERIMENT`, which routes all reads of the control
+//! cache to the candidate and drops the control cache, or with `DROP EXPERIMENT`, which drops the
+//! candidate cache.
+//!
+//! Experiments are tracked per adapter process: with multiple adapters in a deployment, each must
+//! be sent its own experiment statements.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use nom_sql::Relation;
+use parking_lot::RwLock;
+use rand::Rng;
+use readyset_client_metrics::recorded;
+use readyset_errors::{invalid_err, ReadySetResult};
+
+/// A single running experiment between a control cache and a candidate cache
+#[derive(Debug)]
+pub struct Experiment {
+    /// The name of the existing cache
+    pub control: Relation,
+    /// The name of the candidate cache
+    pub candidate: Relation,
+    /// The percentage of reads of the control cache to route to the candidate, between 0 and 100
+    pub percent: u8,
+    /// Whether to compare the results of reads routed to the candidate with the results of the
+    /// control cache
+    pub compare_results: bool,
+    control_reads: AtomicU64,
+    control_read_micros: AtomicU64,
+    candidate_reads: AtomicU64,
+    candidate_read_micros: AtomicU64,
+    candidate_errors: AtomicU64,
+    mismatches: AtomicU64,
+}
+
+/// A point-in-time snapshot of the statistics collected for an [`Experiment`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExperimentStats {
+    /// The number of successful reads of the control cache
+    pub control_reads: u64,
+    /// The mean latency of reads of the control cache
+    pub control_mean_latency: Duration,
+    /// The number of successful reads of the candidate cache
+    pub candidate_reads: u64,
+    /// The mean latency of reads of the candidate cache
+    pub candidate_mean_latency: Duration,
+    /// The number of reads of the candidate cache which failed
+    pub candidate_errors: u64,
+    /// The number of reads of the candidate cache which returned different results than the
+    /// control cache
+    pub mismatches: u64,
+}
+
+fn mean(total_micros: u64, count: u64) -> Duration {
+    Duration::from_micros(total_micros.checked_div(count).unwrap_or(0))
+}
+
+impl Experiment {
+    fn new(control: Relation, candidate: Relation, percent: u8, compare_results: bool) -> Self {
+        Self {
+            control,
+            candidate,
+            percent,
+            compare_results,
+            control_reads: Default::default(),
+            control_read_micros: Default::default(),
+            candidate_reads: Default::default(),
+            candidate_read_micros: Default::default(),
+            candidate_errors: Default::default(),
+            mismatches: Default::default(),
+        }
+    }
+
+    /// Randomly decide whether the next read should be routed to the candidate cache, with a
+    /// probability of [`percent`](Self::percent) percent
+    pub fn route_to_candidate(&self) -> bool {
+        rand::thread_rng().gen_range(0..100) < self.percent
+    }
+
+    /// Record a successful read of the control cache which took the given amount of time
+    pub fn record_control_read(&self, duration: Duration) {
+        self.control_reads.fetch_add(1, Ordering::Relaxed);
+        self.control_read_micros
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+        metrics::histogram!(
+            recorded::EXPERIMENT_READ_TIME,
+            duration.as_secs_f64(),
+            "cache" => self.control.display_unquoted().to_string(),
+            "variant" => "control"
+        );
+    }
+
+    /// Record a successful read of the candidate cache which took the given amount of time
+    pub fn record_candidate_read(&self, duration: Duration) {
+        self.candidate_reads.fetch_add(1, Ordering::Relaxed);
+        self.candidate_read_micros
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+        metrics::histogram!(
+            recorded::EXPERIMENT_READ_TIME,
+            duration.as_secs_f64(),
+            "cache" => self.control.display_unquoted().to_string(),
+            "variant" => "candidate"
+        );
+    }
+
+    /// Record a failed read of the candidate cache
+    pub fn record_candidate_error(&self) {
+        self.candidate_errors.fetch_add(1, Ordering::Relaxed);
+        metrics::increment_counter!(
+            recorded::EXPERIMENT_CANDIDATE_ERRORS,
+            "cache" => self.control.display_unquoted().to_string()
+        );
+    }
+
+    /// Record a read of the candidate cache which returned different results than the control
+    /// cache
+    pub fn record_mismatch(&self) {
+        self.mismatches.fetch_add(1, Ordering::Relaxed);
+        metrics::increment_counter!(
+            recorded::EXPERIMENT_RESULT_MISMATCHES,
+            "cache" => self.control.display_unquoted().to_string()
+        );
+    }
+
+    /// Returns a snapshot of the statistics collected so far for this experiment
+    pub fn stats(&self) -> ExperimentStats {
+        let control_reads = self.control_reads.load(Ordering::Relaxed);
+        let candidate_reads = self.candidate_reads.load(Ordering::Relaxed);
+        ExperimentStats {
+            control_reads,
+            control_mean_latency: mean(
+                self.control_read_micros.load(Ordering::Relaxed),
+                control_reads,
+            ),
+            candidate_reads,
+            candidate_mean_latency: mean(
+                self.candidate_read_micros.load(Ordering::Relaxed),
+                candidate_reads,
+            ),
+            candidate_errors: self.candidate_errors.load(Ordering::Relaxed),
+            mismatches: self.mismatches.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// Running experiments, keyed by the name of their control cache
+    experiments: HashMap<Relation, Arc<Experiment>>,
+    /// Control caches which have been replaced by the candidate of a promoted experiment, mapped
+    /// to the name of that candidate
+    promotions: HashMap<Relation, Relation>,
+}
+
+/// The set of experiments currently running in an adapter, along with the caches which have been
+/// replaced by promoted experiments. Shared between all the connections to the adapter.
+#[derive(Debug, Default)]
+pub struct CacheExperiments {
+    inner: RwLock<Inner>,
+}
+
+impl CacheExperiments {
+    /// Construct a new, empty set of experiments
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new experiment for the given control cache. Returns an error if an experiment is
+    /// already running for the control cache, or if either cache is already part of another
+    /// experiment.
+    pub fn start(
+        &self,
+        control: Relation,
+        candidate: Relation,
+        percent: u8,
+        compare_results: bool,
+    ) -> ReadySetResult<Arc<Experiment>> {
+        let mut inner = self.inner.write();
+        for experiment in inner.experiments.values() {
+            for name in [&control, &candidate] {
+                if *name == experiment.control || *name == experiment.candidate {
+                    return Err(invalid_err!(
+                        "Cache {} is already part of an experiment",
+                        name.display_unquoted()
+                    ));
+                }
+            }
+        }
+        let experiment = Arc::new(Experiment::new(
+            control.clone(),
+            candidate,
+            percent,
+            compare_results,
+        ));
+        inner.experiments.insert(control, Arc::clone(&experiment));
+        Ok(experiment)
+    }
+
+    /// Returns the experiment running for the given control cache, if any
+    pub fn get(&self, control: &Relation) -> Option<Arc<Experiment>> {
+        self.inner.read().experiments.get(control).cloned()
+    }
+
+    /// End the experiment running for the given control cache, returning it if it existed
+    pub fn remove(&self, control: &Relation) -> Option<Arc<Experiment>> {
+        self.inner.write().experiments.remove(control)
+    }
+
+    /// End the experiment running for the given control cache, and redirect all future reads of
+    /// the control cache to the experiment's candidate. Returns an error if no experiment is
+    /// running for the control cache.
+    pub fn promote(&self, control: &Relation) -> ReadySetResult<Arc<Experiment>> {
+        let mut inner = self.inner.write();
+        let experiment = inner.experiments.remove(control).ok_or_else(|| {
+            invalid_err!(
+                "No experiment is running for cache {}",
+                control.display_unquoted()
+            )
+        })?;
+        // Caches that were previously replaced by the control cache are now replaced by the
+        // candidate
+        for target in inner.promotions.values_mut() {
+            if *target == experiment.control {
+                *target = experiment.candidate.clone();
+            }
+        }
+        inner
+            .promotions
+            .insert(experiment.control.clone(), experiment.candidate.clone());
+        Ok(experiment)
+    }
+
+    /// If the cache with the given name has been replaced by the candidate of a promoted
+    /// experiment, returns the name of that candidate
+    pub fn promoted(&self, name: &Relation) -> Option<Relation> {
+        self.inner.read().promotions.get(name).cloned()
+    }
+
+    /// Returns the name of the cache that currently serves reads for the cache with the given name,
+    /// taking into account any promoted experiments
+    pub fn resolve(&self, name: &Relation) -> Relation {
+        self.promoted(name).unwrap_or_else(|| name.clone())
+    }
+
+    /// Notify the set of experiments that a new cache has been created with the given name, which
+    /// will no longer be redirected to the candidate of any promoted experiment
+    pub fn cache_created(&self, name: &Relation) {
+        self.inner.write().promotions.remove(name);
+    }
+
+    /// Notify the set of experiments that the cache with the given name has been dropped, ending
+    /// any experiment it was part of
+    pub fn cache_dropped(&self, name: &Relation) {
+        let mut inner = self.inner.write();
+        inner
+            .experiments
+            .retain(|control, experiment| control != name && experiment.candidate != *name);
+        inner.promotions.retain(|_, target| target != name);
+    }
+
+    /// End all experiments and forget all promotions, eg because all caches have been dropped
+    pub fn clear(&self) {
+        *self.inner.write() = Default::default();
+    }
+
+    /// Returns all currently running experiments, ordered by the name of their control cache
+    pub fn list(&self) -> Vec<Arc<Experiment>> {
+        let mut experiments = self
+            .inner
+            .read()
+            .experiments
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        experiments.sort_by(|e1, e2| e1.control.cmp(&e2.control));
+        experiments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_and_remove() {
+        let experiments = CacheExperiments::new();
+        let experiment = experiments
+            .start("q1".into(), "q1_candidate".into(), 100, false)
+            .unwrap();
+        assert!(experiment.route_to_candidate());
+        assert!(experiments.get(&"q1".into()).is_some());
+        assert!(experiments.get(&"q1_candidate".into()).is_none());
+
+        // Neither cache can be part of a second experiment
+        assert!(experiments
+            .start("q1".into(), "q2".into(), 10, false)
+            .is_err());
+        assert!(experiments
+            .start("q2".into(), "q1_candidate".into(), 10, false)
+            .is_err());
+
+        assert!(experiments.remove(&"q1".into()).is_some());
+        assert!(experiments.list().is_empty());
+        assert!(!experiments
+            .start("q1".into(), "q2".into(), 0, false)
+            .unwrap()
+            .route_to_candidate());
+    }
+
+    #[test]
+    fn promote() {
+        let experiments = CacheExperiments::new();
+        experiments
+            .start("q1".into(), "q2".into(), 10, false)
+            .unwrap();
+        assert!(experiments.promote(&"q2".into()).is_err());
+        experiments.promote(&"q1".into()).unwrap();
+        assert!(experiments.get(&"q1".into()).is_none());
+        assert_eq!(experiments.resolve(&"q1".into()), Relation::from("q2"));
+
+        // Promoting an experiment against the replacement also redirects the original cache
+        experiments
+            .start("q2".into(), "q3".into(), 10, false)
+            .unwrap();
+        experiments.promote(&"q2".into()).unwrap();
+        assert_eq!(experiments.resolve(&"q1".into()), Relation::from("q3"));
+        assert_eq!(experiments.resolve(&"q2".into()), Relation::from("q3"));
+
+        experiments.cache_created(&"q1".into());
+        assert_eq!(experiments.resolve(&"q1".into()), Relation::from("q1"));
+        experiments.cache_dropped(&"q3".into());
+        assert_eq!(experiments.promoted(&"q2".into()), None);
+    }
+
+    #[test]
+    fn stats() {
+        let experiments = CacheExperiments::new();
+        let experiment = experiments
+            .start("q1".into(), "q1_candidate".into(), 50, true)
+            .unwrap();
+        assert_eq!(experiment.stats(), ExperimentStats::default());
+
+        experiment.record_control_read(Duration::from_micros(100));
+        experiment.record_control_read(Duration::from_micros(300));
+        experiment.record_candidate_read(Duration::from_micros(50));
+        experiment.record_candidate_error();
+        experiment.record_mismatch();
+
+        assert_eq!(
+            experiment.stats(),
+            ExperimentStats {
+                control_reads: 2,
+                control_mean_latency: Duration::from_micros(200),
+                candidate_reads: 1,
+                candidate_mean_latency: Duration::from_micros(50),
+                candidate_errors: 1,
+                mismatches: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! A registry of the client connections to an adapter, used to cancel a statement running on one
+//! connection from another connection.
+//!
+//! Each [`Backend`](crate::Backend) built with a [`ConnectionRegistry`] is assigned a unique
+//! connection id, which is reported to MySQL clients in the initial handshake and to Postgres
+//! clients (along with a randomly generated secret key) in the `BackendKeyData` message. A client
+//! can then cancel whatever statement is running on that connection with `KILL QUERY <id>` (MySQL)
+//! or by sending a cancel request with the id and secret key (Postgres). Cancellation is
+//! cooperative: the statement's future is dropped the next time it yields, which cancels any
+//! in-flight ReadySet reads, and the statement is then cancelled on the upstream database.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+
+struct Connection {
+    secret_key: u32,
+    cancel: Arc<Notify>,
+}
+
+/// The set of connections currently open to an adapter
+pub struct ConnectionRegistry {
+    next_id: AtomicU32,
+    connections: Mutex<HashMap<u32, Connection>>,
+}
+
+impl Default for ConnectionRegistry {
+    fn default() -> Self {
+        Self {
+            // Connection id 0 has a special meaning in some clients, so start at 1
+            next_id: AtomicU32::new(1),
+            connections: Default::default(),
+        }
+    }
+}
+
+impl ConnectionRegistry {
+    /// Construct a new, empty connection registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new connection, returning a handle which deregisters it when dropped
+    pub fn register(self: &Arc<Self>) -> ConnectionHandle {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let secret_key = rand::random();
+        let cancel = Arc::new(Notify::new());
+        self.connections.lock().insert(
+            id,
+            Connection {
+                secret_key,
+                cancel: Arc::clone(&cancel),
+            },
+        );
+        ConnectionHandle {
+            registry: Arc::clone(self),
+            id,
+            secret_key,
+            cancel,
+        }
+    }
+
+    /// Cancel the statement currently running on the connection with the given id, if any.
+    /// Returns false if no such connection exists.
+    pub fn cancel(&self, id: u32) -> bool {
+        match self.connections.lock().get(&id) {
+            Some(conn) => {
+                conn.cancel.notify_waiters();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancel the statement currently running on the connection with the given id, if any, and if
+    /// the given secret key matches the one generated for that connection. Returns false if no
+    /// such connection exists or the secret key doesn't match.
+    pub fn cancel_with_key(&self, id: u32, secret_key: u32) -> bool {
+        match self.connections.lock().get(&id) {
+            Some(conn) if conn.secret_key == secret_key => {
+                conn.cancel.notify_waiters();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the number of currently registered connections
+    pub fn len(&self) -> usize {
+        self.connections.lock().len()
+    }
+
+    /// Returns true if there are no currently registered connections
+    pub fn is_empty(&self) -> bool {
+        self.connections.lock().is_empty()
+    }
+}
+
+/// A connection registered with a [`ConnectionRegistry`], which is deregistered when dropped
+pub struct ConnectionHandle {
+    registry: Arc<ConnectionRegistry>,
+    id: u32,
+    secret_key: u32,
+    cancel: Arc<Notify>,
+}
+
+impl ConnectionHandle {
+    /// The registry this connection is registered with
+    pub fn registry(&self) -> &ConnectionRegistry {
+        &self.registry
+    }
+
+    /// The unique id of this connection
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The secret key which must accompany Postgres cancel requests for this connection
+    pub fn secret_key(&self) -> u32 {
+        self.secret_key
+    }
+
+    /// Returns the [`Notify`] which is notified whenever a request is made to cancel the statement
+    /// running on this connection. Requests made while no statement is waiting on the [`Notify`]
+    /// have no effect.
+    pub fn cancel_signal(&self) -> Arc<Notify> {
+        Arc::clone(&self.cancel)
+    }
+}
+
+impl Drop for ConnectionHandle {
+    fn drop(&mut self) {
+        self.registry.connections.lock().remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cancel_running_statement() {
+        let registry = Arc::new(ConnectionRegistry::new());
+        let conn = registry.register();
+        let other = registry.register();
+        assert_ne!(conn.id(), other.id());
+        assert_eq!(registry.len(), 2);
+
+        let signal = conn.cancel_signal();
+        let cancelled = signal.notified();
+        assert!(!registry.cancel_with_key(conn.id(), conn.secret_key().wrapping_add(1)));
+        assert!(registry.cancel(conn.id()));
+        cancelled.await;
+
+        let id = conn.id();
+        drop(conn);
+        assert!(!registry.cancel(id));
+        assert_eq!(registry.len(), 1);
+    }
+}
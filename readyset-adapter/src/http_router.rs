@@ -1,4 +1,8 @@
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::future::Future;
+use std::hash::{Hash, Hasher};
 use std::io;
 use std::net::SocketAddr;
 use std::pin::Pin;
@@ -8,20 +12,32 @@ use std::task::{Context, Poll};
 use anyhow::anyhow;
 use futures::TryFutureExt;
 use health_reporter::{HealthReporter as AdapterHealthReporter, State};
-use hyper::header::CONTENT_TYPE;
+use hyper::header::{CONTENT_TYPE, ETAG, IF_NONE_MATCH};
 use hyper::service::make_service_fn;
 use hyper::{self, Body, Method, Request, Response};
 use metrics_exporter_prometheus::PrometheusHandle;
+use nom_sql::Relation;
 use readyset_client::query::DeniedQuery;
+use readyset_client::{ReaderHandle, ReadySetHandle, SchemaType, ViewPlaceholder};
 use readyset_client_metrics::recorded;
+use readyset_data::{DfValue, Dialect};
+use readyset_errors::{ReadySetError, ReadySetResult};
+use readyset_server::profiling::{self, PPROF_PATH_PREFIX};
 use readyset_sql_passes::anonymize::Anonymizer;
 use readyset_util::shutdown::ShutdownReceiver;
+use serde::{Deserialize, Serialize};
 use tokio::net::TcpListener;
 use tokio::sync::mpsc::Sender;
 use tokio_stream::wrappers::TcpListenerStream;
 use tower::Service;
 
-use crate::query_status_cache::QueryStatusCache;
+use crate::query_pins::QueryPinLists;
+use crate::query_status_cache::{QueryPin, QueryStatusCache};
+use crate::schema_snapshots::SchemaSnapshots;
+
+/// Response header containing the [code](ReadySetError::code) of the underlying error when a
+/// request to the lookup endpoint fails
+pub const ERROR_CODE_HEADER: &str = "X-ReadySet-Error-Code";
 
 /// Routes requests from an HTTP server to expose metrics data from the adapter.
 /// To see the supported http requests and their respective routing, see
@@ -43,6 +59,213 @@ pub struct NoriaAdapterHttpRouter {
     /// Used to retrieve the prometheus scrape's render as a String when servicing
     /// HTTP requests on /metrics.
     pub prometheus_handle: Option<PrometheusHandle>,
+
+    /// Whether to serve the profiling endpoints in [`readyset_server::profiling`]
+    pub enable_pprof: bool,
+
+    /// The queries pinned to ReadySet or to the upstream database by the operator
+    pub query_pins: Arc<QueryPinLists>,
+
+    /// Handle to the ReadySet controller, used to forward cache invalidation requests
+    pub controller: ReadySetHandle,
+
+    /// Snapshots of the upstream database's schema, if `--schema-snapshot-interval` is set
+    pub schema_snapshots: Arc<SchemaSnapshots>,
+
+    /// Whether to anonymize identifiers in the allow and deny lists by hashing them, so they're
+    /// consistent with anonymized schema snapshots
+    pub anonymize_identifiers: bool,
+
+    /// Expression evaluation dialect to use when looking up keys in caches
+    pub expr_dialect: Dialect,
+}
+
+/// A request to invalidate the cached state derived from rows of a table which were written to
+/// out-of-band, eg by a batch job which bypasses the replicated upstream database
+#[derive(Debug, Deserialize)]
+struct InvalidationRequest {
+    /// The name of the table, optionally schema-qualified (`schema.table`)
+    table: String,
+    /// The primary keys of the rows which were written to
+    keys: Vec<Vec<serde_json::Value>>,
+}
+
+impl InvalidationRequest {
+    fn table(&self) -> Relation {
+        match self.table.split_once('.') {
+            Some((schema, name)) => Relation {
+                schema: Some(schema.into()),
+                name: name.into(),
+            },
+            None => self.table.as_str().into(),
+        }
+    }
+
+    fn keys(&self) -> Result<Vec<Vec<DfValue>>, String> {
+        self.keys
+            .iter()
+            .map(|key| key.iter().map(json_to_df_value).collect())
+            .collect()
+    }
+}
+
+/// Convert a scalar JSON value to a [`DfValue`], which is coerced to the type of the corresponding
+/// column once it reaches the controller
+fn json_to_df_value(value: &serde_json::Value) -> Result<DfValue, String> {
+    match value {
+        serde_json::Value::Null => Ok(DfValue::None),
+        serde_json::Value::Bool(b) => Ok((*b).into()),
+        serde_json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Ok(i.into())
+            } else if let Some(u) = n.as_u64() {
+                Ok(u.into())
+            } else {
+                DfValue::try_from(n.as_f64().unwrap_or_default()).map_err(|e| e.to_string())
+            }
+        }
+        serde_json::Value::String(s) => Ok(s.as_str().into()),
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+            Err(format!("Key values must be scalars, got {value}"))
+        }
+    }
+}
+
+/// A request to look up the results of a cache for a batch of keys
+#[derive(Debug, Deserialize)]
+struct LookupRequest {
+    /// The name of the cache, as listed by `SHOW CACHES`
+    cache: String,
+    /// The values for the parameters of the cache's query to look up, one set per key
+    keys: Vec<Vec<serde_json::Value>>,
+}
+
+impl LookupRequest {
+    fn keys(&self) -> Result<Vec<Vec<DfValue>>, String> {
+        self.keys
+            .iter()
+            .map(|key| key.iter().map(json_to_df_value).collect())
+            .collect()
+    }
+}
+
+/// The results of looking up a single key in a cache
+#[derive(Debug, Serialize)]
+struct KeyLookupResult {
+    /// The values for the parameters of the cache's query that were looked up
+    key: Vec<serde_json::Value>,
+    /// The version of the results for this key, which never decreases and changes whenever the
+    /// results change
+    version: u64,
+    /// The rows for this key, as objects mapping column names to values
+    rows: Vec<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// Convert a [`DfValue`] read from a cache to a JSON value. Values without an equivalent JSON type
+/// are converted to their string representation
+fn df_value_to_json(value: &DfValue) -> serde_json::Value {
+    match value {
+        DfValue::None => serde_json::Value::Null,
+        DfValue::Int(i) => (*i).into(),
+        DfValue::UnsignedInt(u) => (*u).into(),
+        DfValue::Float(f) => serde_json::Number::from_f64(f64::from(*f))
+            .map(serde_json::Value::Number)
+            .unwrap_or_default(),
+        DfValue::Double(f) => serde_json::Number::from_f64(*f)
+            .map(serde_json::Value::Number)
+            .unwrap_or_default(),
+        DfValue::Text(_) | DfValue::TinyText(_) => <&str>::try_from(value)
+            .map(|s| s.to_owned())
+            .unwrap_or_default()
+            .into(),
+        _ => value.to_string().into(),
+    }
+}
+
+/// Returns the number of parameters taken by the query for the cache read by the given reader
+fn num_parameters(reader: &ReaderHandle) -> usize {
+    reader
+        .key_map()
+        .iter()
+        .map(|(placeholder, _)| match placeholder {
+            ViewPlaceholder::Generated => 0,
+            ViewPlaceholder::OneToOne(idx, _) => *idx,
+            ViewPlaceholder::Between(lower, upper) => *lower.max(upper),
+            ViewPlaceholder::PageNumber {
+                offset_placeholder, ..
+            } => *offset_placeholder,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Look up the results for each of the given keys in the cache with the given name, returning
+/// the results and version for each key, along with an ETag for the whole batch which changes
+/// whenever the version of any of the keys changes
+async fn lookup_keys(
+    controller: &mut ReadySetHandle,
+    cache: &str,
+    keys: Vec<Vec<DfValue>>,
+    dialect: Dialect,
+) -> ReadySetResult<(Vec<KeyLookupResult>, String)> {
+    futures::future::poll_fn(|cx| controller.poll_ready(cx)).await?;
+    let mut view = controller.view(cache).await?;
+    let expected_len = view
+        .as_mut_reader_handle()
+        .map_or(0, |reader| num_parameters(reader));
+
+    let mut etag = DefaultHasher::new();
+    cache.hash(&mut etag);
+    let mut results = Vec::with_capacity(keys.len());
+    for key in keys {
+        if key.len() != expected_len {
+            return Err(ReadySetError::WrongKeyColumnCount(expected_len, key.len()));
+        }
+
+        let (reader, query) = view
+            .build_view_query(vec![Cow::Borrowed(&key)], None, None, None, true, dialect)?
+            .ok_or_else(|| ReadySetError::BadRequest(format!("No results for key {key:?}")))?;
+        // Read the version before the rows, so that the version never covers writes which the
+        // rows don't include
+        let version = reader
+            .key_versions(query.key_comparisons.clone())
+            .await?
+            .into_iter()
+            .max()
+            .unwrap_or_default();
+        let columns = reader
+            .schema()
+            .map(|schema| {
+                schema
+                    .schema(SchemaType::ReturnedSchema)
+                    .iter()
+                    .map(|col| col.column.name.to_string())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let rows = reader
+            .raw_lookup(query)
+            .await?
+            .into_iter()
+            .map(|row| {
+                columns
+                    .iter()
+                    .cloned()
+                    .zip(row.iter().map(df_value_to_json))
+                    .collect()
+            })
+            .collect();
+
+        key.hash(&mut etag);
+        version.hash(&mut etag);
+        results.push(KeyLookupResult {
+            key: key.iter().map(df_value_to_json).collect(),
+            version,
+            rows,
+        });
+    }
+
+    Ok((results, format!("\"{:016x}\"", etag.finish())))
 }
 
 impl NoriaAdapterHttpRouter {
@@ -117,6 +340,9 @@ impl Service<Request<Body>> for NoriaAdapterHttpRouter {
     /// List of SQL queries that will be handled by ReadySet as opposed to being passed through to
     /// the underlying database.
     ///
+    /// Identifiers in the listed queries are anonymized. If `--anonymize-identifiers` is set, they
+    /// are replaced with hashes consistent with those in anonymized schema snapshots.
+    ///
     /// * **URL**
     ///
     ///   `/allow-list`
@@ -145,6 +371,9 @@ impl Service<Request<Body>> for NoriaAdapterHttpRouter {
     /// List of SQL queries that will _not_ be handled by ReadySet and instead passed through to the
     /// underlying database.
     ///
+    /// Identifiers in the listed queries are anonymized. If `--anonymize-identifiers` is set, they
+    /// are replaced with hashes consistent with those in anonymized schema snapshots.
+    ///
     /// * **URL**
     ///
     ///   `/deny-list`
@@ -168,6 +397,208 @@ impl Service<Request<Body>> for NoriaAdapterHttpRouter {
     ///
     ///   `curl -X GET <adapter>:<adapter-port>/deny-list`
     ///
+    /// ## Pinned Queries
+    ///
+    /// List the queries pinned to ReadySet (`allow`) or to the upstream database (`deny`), either
+    /// by `--query-allow-list-file` and `--query-deny-list-file` or at runtime.
+    ///
+    /// * **URL**
+    ///
+    ///   `/pinned-queries`
+    ///
+    /// * **Method:**
+    ///
+    ///   `GET`
+    ///
+    /// * **Success Response:**
+    ///
+    ///     * **Code:** 200 <br /> **Content:** `{ "allow": [ ... ], "deny": [ ... ] }`
+    ///
+    /// * **Sample Call:**
+    ///
+    ///   `curl -X GET <adapter>:<adapter-port>/pinned-queries`
+    ///
+    /// ## Pin Query
+    ///
+    /// Pin the `SELECT` statement in the request body to ReadySet (`allow`) or to the upstream
+    /// database (`deny`), or remove any pin for it (`unpin`), overriding the allow and deny list
+    /// files.
+    ///
+    /// * **URL**
+    ///
+    ///   `/pinned-queries/allow`, `/pinned-queries/deny`, or `/pinned-queries/unpin`
+    ///
+    /// * **Method:**
+    ///
+    ///   `POST`
+    ///
+    /// * **Success Response:**
+    ///
+    ///     * **Code:** 200 <br />
+    ///
+    /// * **Error Response:**
+    ///
+    ///     * **Code:** 400 Bad Request <br /> **Content:** `"<error>"`
+    ///
+    /// * **Sample Call:**
+    ///
+    ///   `curl -X POST <adapter>:<adapter-port>/pinned-queries/deny -d 'SELECT * FROM t'`
+    ///
+    /// ## Reload Pinned Queries
+    ///
+    /// Re-read the allow and deny list files immediately, rather than waiting for them to be
+    /// reloaded periodically.
+    ///
+    /// * **URL**
+    ///
+    ///   `/pinned-queries/reload`
+    ///
+    /// * **Method:**
+    ///
+    ///   `POST`
+    ///
+    /// * **Success Response:**
+    ///
+    ///     * **Code:** 200 <br />
+    ///
+    /// * **Error Response:**
+    ///
+    ///     * **Code:** 400 Bad Request <br /> **Content:** `"<error>"`
+    ///
+    /// * **Sample Call:**
+    ///
+    ///   `curl -X POST <adapter>:<adapter-port>/pinned-queries/reload`
+    ///
+    /// ## Invalidate
+    ///
+    /// Evict all cached state derived from the rows of a table with the given primary keys, so
+    /// that subsequent reads of that state are replayed. Intended to be called by jobs which write
+    /// to tables out-of-band, such as batch ETL jobs which bypass the replicated upstream database.
+    ///
+    /// * **URL**
+    ///
+    ///   `/invalidate`
+    ///
+    /// * **Method:**
+    ///
+    ///   `POST`
+    ///
+    /// * **Data Params:**
+    ///
+    ///   `{ "table": "<schema>.<table>", "keys": [[<primary key value>, ...], ...] }`
+    ///
+    /// * **Success Response:**
+    ///
+    ///     * **Code:** 200 <br />
+    ///
+    /// * **Error Response:**
+    ///
+    ///     * **Code:** 400 Bad Request <br /> **Content:** `"<error>"`
+    ///
+    ///   OR
+    ///
+    ///     * **Code:** 500 Internal Server Error <br /> **Content:** `"<error>"`
+    ///
+    /// * **Sample Call:**
+    ///
+    ///   `curl -X POST <adapter>:<adapter-port>/invalidate -d '{"table": "public.users", "keys":
+    ///   [[1], [2]]}'`
+    ///
+    /// ## Lookup
+    ///
+    /// Look up the results of a cache for a batch of keys, each of which is a set of values for
+    /// the parameters of the cache's query. The results for each key include a version, which
+    /// never decreases and changes whenever the results for that key change, so clients can
+    /// cheaply detect which keys have changed. The response also has an `ETag` header covering
+    /// the versions of all the keys, and requests with a matching `If-None-Match` header get an
+    /// empty `304 Not Modified` response.
+    ///
+    /// * **URL**
+    ///
+    ///   `/lookup`
+    ///
+    /// * **Method:**
+    ///
+    ///   `POST`
+    ///
+    /// * **Data Params:**
+    ///
+    ///   `{ "cache": "<cache name>", "keys": [[<parameter value>, ...], ...] }`
+    ///
+    /// * **Success Response:**
+    ///
+    ///     * **Code:** 200 <br /> **Content:** `[{ "key": [...], "version": <version>, "rows": [{
+    ///       "<column>": <value>, ... }, ...] }, ...]`
+    ///
+    ///   OR
+    ///
+    ///     * **Code:** 304 Not Modified <br />
+    ///
+    /// * **Error Response:**
+    ///
+    ///     * **Code:** 400 Bad Request <br /> **Content:** `"<error>"`
+    ///
+    ///   OR
+    ///
+    ///     * **Code:** 404 Not Found <br /> **Content:** `"<error>"`
+    ///
+    ///   OR
+    ///
+    ///     * **Code:** 500 Internal Server Error <br /> **Content:** `"<error>"`
+    ///
+    /// * **Sample Call:**
+    ///
+    ///   `curl -X POST <adapter>:<adapter-port>/lookup -H 'If-None-Match: "<etag>"' -d '{"cache":
+    ///   "q_1a2b3c4d5e6f7a8b", "keys": [[1], [2]]}'`
+    ///
+    /// ## Schema Snapshots
+    ///
+    /// List the snapshots of the upstream database's schema taken while the adapter has been
+    /// running, if `--schema-snapshot-interval` is set. A new snapshot is only recorded when the
+    /// schema changes, and each snapshot after the first includes a diff against the previous one.
+    /// If `--anonymize-identifiers` is set, only the (anonymized) table and view definitions in the
+    /// schema are recorded.
+    ///
+    /// * **URL**
+    ///
+    ///   `/schema-snapshots`
+    ///
+    /// * **Method:**
+    ///
+    ///   `GET`
+    ///
+    /// * **Success Response:**
+    ///
+    ///     * **Code:** 200 <br /> **Content:** `[{ "taken_at": "<timestamp>", "schema": "<schema
+    ///       dump>", "diff": "<diff>" | null }, ...]`
+    ///
+    /// * **Sample Call:**
+    ///
+    ///   `curl -X GET <adapter>:<adapter-port>/schema-snapshots`
+    ///
+    /// ## Schema Changes
+    ///
+    /// List only the changes to the upstream database's schema between subsequent snapshots,
+    /// without the full schema dumps, for correlating with queries that became unsupported
+    /// partway through a run.
+    ///
+    /// * **URL**
+    ///
+    ///   `/schema-snapshots/changes`
+    ///
+    /// * **Method:**
+    ///
+    ///   `GET`
+    ///
+    /// * **Success Response:**
+    ///
+    ///     * **Code:** 200 <br /> **Content:** `[{ "taken_at": "<timestamp>", "diff": "<diff>" },
+    ///       ...]`
+    ///
+    /// * **Sample Call:**
+    ///
+    ///   `curl -X GET <adapter>:<adapter-port>/schema-snapshots/changes`
+    ///
     /// ## Prometheus
     ///
     /// Endpoint for Prometheus metric API calls.
@@ -245,9 +676,21 @@ impl Service<Request<Body>> for NoriaAdapterHttpRouter {
             }
             (&Method::GET, "/allow-list") => {
                 let query_cache = self.query_cache;
+                let anonymize_identifiers = self.anonymize_identifiers;
                 Box::pin(async move {
                     let allow_list = query_cache.allow_list();
-                    let res = match serde_json::to_string(&allow_list) {
+                    let json = if anonymize_identifiers {
+                        let mut anonymizer = Anonymizer::with_hashed_identifiers();
+                        serde_json::to_string(
+                            &allow_list
+                                .into_iter()
+                                .map(|(query, _)| query.to_anonymized_string(&mut anonymizer))
+                                .collect::<Vec<_>>(),
+                        )
+                    } else {
+                        serde_json::to_string(&allow_list)
+                    };
+                    let res = match json {
                         Ok(json) => res
                             .header(CONTENT_TYPE, "application/json")
                             .body(hyper::Body::from(json)),
@@ -262,8 +705,13 @@ impl Service<Request<Body>> for NoriaAdapterHttpRouter {
             }
             (&Method::GET, "/deny-list") => {
                 let query_cache = self.query_cache;
+                let anonymize_identifiers = self.anonymize_identifiers;
                 Box::pin(async move {
-                    let mut anonymizer = Anonymizer::new();
+                    let mut anonymizer = if anonymize_identifiers {
+                        Anonymizer::with_hashed_identifiers()
+                    } else {
+                        Anonymizer::new()
+                    };
                     let deny_list = query_cache
                         .deny_list()
                         .into_iter()
@@ -284,6 +732,176 @@ impl Service<Request<Body>> for NoriaAdapterHttpRouter {
                     Ok(res.unwrap())
                 })
             }
+            (&Method::GET, "/pinned-queries") => {
+                let mut pins: HashMap<&str, Vec<String>> =
+                    HashMap::from([("allow", vec![]), ("deny", vec![])]);
+                for (query, pin) in self.query_pins.pinned_queries() {
+                    let key = match pin {
+                        QueryPin::Allow => "allow",
+                        QueryPin::Deny => "deny",
+                    };
+                    pins.entry(key).or_default().push(query);
+                }
+                let res =
+                    match serde_json::to_string(&pins) {
+                        Ok(json) => res
+                            .header(CONTENT_TYPE, "application/json")
+                            .body(hyper::Body::from(json)),
+                        Err(_) => res.status(500).header(CONTENT_TYPE, "text/plain").body(
+                            hyper::Body::from(
+                                "pinned queries failed to be converted into a json string"
+                                    .to_string(),
+                            ),
+                        ),
+                    };
+                Box::pin(async move { Ok(res.unwrap()) })
+            }
+            (&Method::POST, "/pinned-queries/reload") => {
+                let query_pins = Arc::clone(&self.query_pins);
+                Box::pin(async move {
+                    let res = match query_pins.reload().await {
+                        Ok(_) => res.status(200).body(hyper::Body::empty()),
+                        Err(e) => res
+                            .status(400)
+                            .header(CONTENT_TYPE, "text/plain")
+                            .body(hyper::Body::from(e.to_string())),
+                    };
+                    Ok(res.unwrap())
+                })
+            }
+            (&Method::POST, path) if path.starts_with("/pinned-queries/") => {
+                let pin = match path.trim_start_matches("/pinned-queries/") {
+                    "allow" => Some(Some(QueryPin::Allow)),
+                    "deny" => Some(Some(QueryPin::Deny)),
+                    "unpin" => Some(None),
+                    _ => None,
+                };
+                let query_pins = Arc::clone(&self.query_pins);
+                Box::pin(async move {
+                    let pin = match pin {
+                        Some(pin) => pin,
+                        None => return Ok(res.status(404).body(hyper::Body::empty()).unwrap()),
+                    };
+                    let body = hyper::body::to_bytes(req.into_body()).await?;
+                    let res = match std::str::from_utf8(&body)
+                        .map_err(|e| e.to_string())
+                        .and_then(|query| query_pins.set_pin(query, pin).map_err(|e| e.to_string()))
+                    {
+                        Ok(()) => res.status(200).body(hyper::Body::empty()),
+                        Err(e) => res
+                            .status(400)
+                            .header(CONTENT_TYPE, "text/plain")
+                            .body(hyper::Body::from(e)),
+                    };
+                    Ok(res.unwrap())
+                })
+            }
+            (&Method::POST, "/invalidate") => {
+                let mut controller = self.controller.clone();
+                Box::pin(async move {
+                    let body = hyper::body::to_bytes(req.into_body()).await?;
+                    let request = serde_json::from_slice::<InvalidationRequest>(&body)
+                        .map_err(|e| e.to_string())
+                        .and_then(|request| Ok((request.table(), request.keys()?)));
+                    let (table, keys) = match request {
+                        Ok(request) => request,
+                        Err(e) => {
+                            return Ok(res
+                                .status(400)
+                                .header(CONTENT_TYPE, "text/plain")
+                                .body(hyper::Body::from(e))
+                                .unwrap())
+                        }
+                    };
+                    let res = match controller.invalidate_table_keys(table, keys).await {
+                        Ok(()) => res.status(200).body(hyper::Body::empty()),
+                        Err(e) => res
+                            .status(500)
+                            .header(CONTENT_TYPE, "text/plain")
+                            .body(hyper::Body::from(e.to_string())),
+                    };
+                    Ok(res.unwrap())
+                })
+            }
+            (&Method::POST, "/lookup") => {
+                let mut controller = self.controller.clone();
+                let dialect = self.expr_dialect;
+                Box::pin(async move {
+                    let if_none_match = req.headers().get(IF_NONE_MATCH).cloned();
+                    let body = hyper::body::to_bytes(req.into_body()).await?;
+                    let request = serde_json::from_slice::<LookupRequest>(&body)
+                        .map_err(|e| e.to_string())
+                        .and_then(|request| Ok((request.keys()?, request.cache)));
+                    let (keys, cache) = match request {
+                        Ok(request) => request,
+                        Err(e) => {
+                            return Ok(res
+                                .status(400)
+                                .header(CONTENT_TYPE, "text/plain")
+                                .body(hyper::Body::from(e))
+                                .unwrap())
+                        }
+                    };
+                    let (results, etag) =
+                        match lookup_keys(&mut controller, &cache, keys, dialect).await {
+                            Ok(results) => results,
+                            Err(e) => {
+                                let status = match e {
+                                    _ if e.caused_by_view_not_found() => 404,
+                                    ReadySetError::WrongKeyColumnCount(..)
+                                    | ReadySetError::BadRequest(_) => 400,
+                                    _ => 500,
+                                };
+                                return Ok(res
+                                    .status(status)
+                                    .header(CONTENT_TYPE, "text/plain")
+                                    .header(ERROR_CODE_HEADER, e.root_cause().code())
+                                    .body(hyper::Body::from(e.to_string()))
+                                    .unwrap());
+                            }
+                        };
+                    if if_none_match.map_or(false, |v| v.as_bytes() == etag.as_bytes()) {
+                        return Ok(res
+                            .status(304)
+                            .header(ETAG, etag)
+                            .body(hyper::Body::empty())
+                            .unwrap());
+                    }
+                    let res = match serde_json::to_string(&results) {
+                        Ok(json) => res
+                            .header(CONTENT_TYPE, "application/json")
+                            .header(ETAG, etag)
+                            .body(hyper::Body::from(json)),
+                        Err(_) => res.status(500).header(CONTENT_TYPE, "text/plain").body(
+                            hyper::Body::from(
+                                "lookup results failed to be converted into a json string"
+                                    .to_string(),
+                            ),
+                        ),
+                    };
+                    Ok(res.unwrap())
+                })
+            }
+            (&Method::GET, path @ ("/schema-snapshots" | "/schema-snapshots/changes")) => {
+                let json = if path == "/schema-snapshots" {
+                    serde_json::to_string(&self.schema_snapshots.snapshots())
+                } else {
+                    serde_json::to_string(&self.schema_snapshots.changes())
+                };
+                let res =
+                    match json {
+                        Ok(json) => res
+                            .header(CONTENT_TYPE, "application/json")
+                            .body(hyper::Body::from(json)),
+                        Err(_) => res.status(500).header(CONTENT_TYPE, "text/plain").body(
+                            hyper::Body::from(
+                                "schema snapshots failed to be converted into a json string"
+                                    .to_string(),
+                            ),
+                        ),
+                    };
+                Box::pin(async move { Ok(res.unwrap()) })
+            }
             (&Method::GET, "/health") => {
                 let state = self.health_reporter.health().state;
                 Box::pin(async move {
@@ -302,6 +920,11 @@ impl Service<Request<Body>> for NoriaAdapterHttpRouter {
                     Ok(res.unwrap())
                 })
             }
+            (&Method::GET, path) if self.enable_pprof && path.starts_with(PPROF_PATH_PREFIX) => {
+                let path = path.to_owned();
+                let query = req.uri().query().map(ToOwned::to_owned);
+                Box::pin(async move { Ok(profiling::serve(&path, query.as_deref()).await) })
+            }
             (&Method::GET, "/metrics") => {
                 let body = self.prometheus_handle.as_ref().map(|x| x.render());
                 let res = res.header(CONTENT_TYPE, "text/plain");
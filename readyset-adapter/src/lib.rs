@@ -9,19 +9,31 @@
 #![deny(unreachable_pub)]
 
 pub mod backend;
+pub mod cache_experiments;
+pub mod connection_registry;
 pub mod fallback_cache;
 pub mod http_router;
 pub mod migration_handler;
+pub mod migration_queue;
+pub mod privileges;
 pub mod proxied_queries_reporter;
 mod query_handler;
+pub mod query_hints;
+pub mod query_pins;
 pub mod query_status_cache;
+pub mod read_replicas;
+pub mod result_streaming;
 pub mod rewrite;
+pub mod schema_snapshots;
+mod transactions;
 pub mod upstream_database;
+pub mod upstream_retry;
 mod utils;
 pub mod views_synchronizer;
 
 pub use crate::backend::{Backend, BackendBuilder};
 pub use crate::query_handler::{QueryHandler, SetBehavior};
+pub use crate::result_streaming::ResultStreaming;
 pub use crate::upstream_database::{
     UpstreamConfig, UpstreamDatabase, UpstreamDestination, UpstreamPrepare,
 };
@@ -12,7 +12,7 @@ use dataflow_expression::Dialect;
 use metrics::{counter, register_counter, Counter};
 use nom_sql::Literal;
 use readyset_client::query::{MigrationState, Query};
-use readyset_client::recipe::changelist::{Change, ChangeList};
+use readyset_client::recipe::changelist::{CacheOptions, Change, ChangeList};
 use readyset_client::{PlaceholderIdx, ReadySetHandle, ViewCreateRequest};
 use readyset_client_metrics::recorded;
 use readyset_data::DfValue;
@@ -24,7 +24,7 @@ use tokio::select;
 use tracing::{debug, error, info, instrument};
 
 use crate::backend::NoriaConnector;
-use crate::query_status_cache::QueryStatusCache;
+use crate::query_status_cache::{QueryPin, QueryStatusCache};
 use crate::utils;
 
 pub struct MigrationHandler {
@@ -96,7 +96,11 @@ impl MigrationHandler {
         for q in to_process {
             match &q.0 {
                 Query::Parsed(req) => {
-                    if has_controller {
+                    // Queries pinned to ReadySet are always migrated, even if we'd otherwise only
+                    // perform a dry run
+                    if has_controller
+                        && self.query_status_cache.pin(&req.statement) != Some(QueryPin::Allow)
+                    {
                         self.perform_dry_run_migration(req).await
                     } else {
                         self.perform_migration(req).await
@@ -174,6 +178,7 @@ impl MigrationHandler {
                         &query.query().statement,
                         Some(query.query().schema_search_path.clone()),
                         false,
+                        CacheOptions::default(),
                     )
                     .await;
                 // Inform the query status cache of completed migrations
@@ -296,6 +301,7 @@ impl MigrationHandler {
                 &inlined_query,
                 Some(view_request.schema_search_path.clone()),
                 false,
+                CacheOptions::default(),
             )
             .await
     }
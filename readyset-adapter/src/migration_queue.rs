@@ -0,0 +1,111 @@
+//! A queue of migrations shared between all connections to the adapter.
+//!
+//! When the adapter migrates queries in the request path, every connection executing a query that
+//! isn't cached yet asks the controller to create a cache for it. Without coordination, many
+//! connections executing the same new query at once (for example right after a deploy) each send
+//! their own, identical, migration request, and the controller - which can only run one migration
+//! at a time - has to work through all of them while every one of those connections waits.
+//!
+//! Instead, connections submit migrations to a [`MigrationQueue`], which:
+//!
+//! - Deduplicates migrations for the same query (with the same schema search path), so that every
+//!   connection waiting on a query that's already being migrated waits for that migration rather
+//!   than starting a new one
+//! - Limits the number of migrations sent to the controller concurrently
+//! - Runs each migration in a background task, so that a migration still completes if every
+//!   connection waiting on it goes away
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures::future::{BoxFuture, FutureExt, Shared};
+use metrics::{decrement_gauge, increment_counter, increment_gauge};
+use parking_lot::Mutex;
+use readyset_client::recipe::changelist::ChangeList;
+use readyset_client::{ReadySetHandle, ViewCreateRequest};
+use readyset_client_metrics::recorded;
+use readyset_errors::{internal_err, ReadySetResult};
+use tokio::sync::Semaphore;
+
+type InFlight = HashMap<ViewCreateRequest, Shared<BoxFuture<'static, ReadySetResult<()>>>>;
+
+/// A queue of migrations, shared between connections. See the [module
+/// documentation](self) for more information.
+pub struct MigrationQueue {
+    /// Migrations which have been submitted but not yet completed
+    in_flight: Arc<Mutex<InFlight>>,
+    /// Limits the number of migrations which are sent to the controller concurrently
+    permits: Arc<Semaphore>,
+}
+
+impl MigrationQueue {
+    /// Create a new [`MigrationQueue`] which runs at most `max_concurrent_migrations` migrations at
+    /// once
+    pub fn new(max_concurrent_migrations: usize) -> Self {
+        Self {
+            in_flight: Default::default(),
+            permits: Arc::new(Semaphore::new(max_concurrent_migrations.max(1))),
+        }
+    }
+
+    /// Returns the number of migrations which have been submitted but not yet completed
+    pub fn len(&self) -> usize {
+        self.in_flight.lock().len()
+    }
+
+    /// Returns `true` if there are no migrations in progress
+    pub fn is_empty(&self) -> bool {
+        self.in_flight.lock().is_empty()
+    }
+
+    /// Migrate the query in the given `changelist`, which creates a cache for `request`, and wait
+    /// for the migration to complete.
+    ///
+    /// If a migration for the same `request` is already in progress, waits for that migration
+    /// instead of starting a new one.
+    pub async fn migrate(
+        &self,
+        noria: &ReadySetHandle,
+        request: ViewCreateRequest,
+        changelist: ChangeList,
+    ) -> ReadySetResult<()> {
+        let migration = match self.in_flight.lock().entry(request.clone()) {
+            Entry::Occupied(e) => {
+                increment_counter!(recorded::MIGRATION_QUEUE_DEDUPLICATED);
+                e.get().clone()
+            }
+            Entry::Vacant(e) => {
+                increment_gauge!(recorded::MIGRATION_QUEUE_IN_FLIGHT, 1.0);
+                let mut noria = noria.clone();
+                let permits = Arc::clone(&self.permits);
+                let in_flight = Arc::clone(&self.in_flight);
+                let task = tokio::spawn(async move {
+                    let res = async {
+                        let _permit = permits
+                            .acquire()
+                            .await
+                            .map_err(|_| internal_err!("Migration queue closed"))?;
+                        futures::future::poll_fn(|cx| noria.poll_ready(cx)).await?;
+                        noria.extend_recipe(changelist).await
+                    }
+                    .await;
+                    in_flight.lock().remove(&request);
+                    decrement_gauge!(recorded::MIGRATION_QUEUE_IN_FLIGHT, 1.0);
+                    res
+                });
+                e.insert(
+                    async move {
+                        task.await
+                            .map_err(|e| internal_err!("Migration task failed: {}", e))?
+                    }
+                    .boxed()
+                    .shared(),
+                )
+                .clone()
+            }
+        };
+
+        migration.await
+    }
+}
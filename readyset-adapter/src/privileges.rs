@@ -0,0 +1,371 @@
+//! Enforcement of the upstream database's `SELECT` privileges on cached queries.
+//!
+//! When enabled with `--enforce-upstream-privileges`, the adapter periodically reads the `SELECT`
+//! privileges granted to each user from the upstream database's grant tables (see
+//! [`UpstreamDatabase::select_privileges`]), and before serving the results of a query from
+//! ReadySet checks that the user the client authenticated as can select from every table the query
+//! reads from. Queries which fail that check are sent to the upstream database instead, which can
+//! enforce its own privileges, or fail with [`ReadySetError::SelectPrivilegeDenied`] if there is no
+//! upstream database (or the query must always be served by ReadySet).
+//!
+//! Until privileges have been read from the upstream database for the first time, all queries are
+//! denied.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use nom_sql::analysis::visit::{self, Visitor};
+use nom_sql::{
+    CommonTableExpr, Relation, SelectStatement, SqlIdentifier, TableExpr, TableExprInner,
+};
+use parking_lot::RwLock;
+use readyset_errors::{ReadySetError, ReadySetResult};
+use readyset_util::shutdown::ShutdownReceiver;
+use tokio::select;
+use tracing::{debug, info, warn};
+
+use crate::upstream_database::{UpstreamConfig, UpstreamDatabase};
+
+/// The `SELECT` privileges granted to a single upstream database user
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UserPrivileges {
+    /// Whether the user can select from every table, either because they're a superuser or
+    /// because they've been granted `SELECT` globally
+    pub all_tables: bool,
+    /// Schemas (databases, in MySQL) in which the user can select from every table. These may
+    /// contain the `%` and `_` wildcards supported by MySQL's database-level grants.
+    pub schemas: Vec<String>,
+    /// Individual tables the user can select from
+    pub tables: HashSet<Relation>,
+}
+
+impl UserPrivileges {
+    fn can_select(&self, table: &Relation) -> bool {
+        self.all_tables
+            || self.tables.contains(table)
+            || table.schema.as_ref().map_or(false, |schema| {
+                self.schemas
+                    .iter()
+                    .any(|pattern| matches_grant_pattern(pattern, schema))
+            })
+    }
+}
+
+/// Returns true if `name` matches a MySQL database-level grant pattern, in which `%` matches any
+/// sequence of characters, `_` matches any single character, and either can be escaped with `\`.
+fn matches_grant_pattern(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+
+    fn go(pattern: &[char], name: &[char]) -> bool {
+        match pattern {
+            [] => name.is_empty(),
+            ['%', rest @ ..] => (0..=name.len()).any(|skip| go(rest, &name[skip..])),
+            ['_', rest @ ..] => !name.is_empty() && go(rest, &name[1..]),
+            ['\\', c, rest @ ..] | [c, rest @ ..] => {
+                name.first() == Some(c) && go(rest, &name[1..])
+            }
+        }
+    }
+
+    go(&pattern, &name)
+}
+
+/// A snapshot of the `SELECT` privileges granted to all users of the upstream database
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SelectPrivileges {
+    /// Privileges of each user, keyed by username
+    pub users: HashMap<String, UserPrivileges>,
+    /// All the tables that exist in the upstream database, if known, used to determine which
+    /// schema in the schema search path an unqualified table name refers to. If a table isn't
+    /// found here, it's assumed to be in the first schema in the search path.
+    pub known_tables: HashSet<Relation>,
+}
+
+impl SelectPrivileges {
+    /// Qualify `table` with the schema it refers to given the `schema_search_path`
+    fn resolve(&self, table: &Relation, schema_search_path: &[SqlIdentifier]) -> Relation {
+        if table.schema.is_some() {
+            return table.clone();
+        }
+        let qualify = |schema: &SqlIdentifier| Relation {
+            schema: Some(schema.clone()),
+            name: table.name.clone(),
+        };
+        schema_search_path
+            .iter()
+            .map(qualify)
+            .find(|candidate| self.known_tables.contains(candidate))
+            .or_else(|| schema_search_path.first().map(qualify))
+            .unwrap_or_else(|| table.clone())
+    }
+
+    /// Check that `user` can select from all of the given `tables`, returning
+    /// [`ReadySetError::SelectPrivilegeDenied`] for the first table they can't select from
+    pub fn check(
+        &self,
+        user: &str,
+        tables: &[Relation],
+        schema_search_path: &[SqlIdentifier],
+    ) -> ReadySetResult<()> {
+        let privileges = self.users.get(user);
+        for table in tables {
+            let table = self.resolve(table, schema_search_path);
+            if !privileges.map_or(false, |p| p.can_select(&table)) {
+                return Err(ReadySetError::SelectPrivilegeDenied {
+                    user: user.to_owned(),
+                    table: table.display_unquoted().to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The most recently read [`SelectPrivileges`] of the upstream database, shared between all
+/// connections to the adapter. `None` until privileges have been read for the first time.
+pub type SharedSelectPrivileges = Arc<RwLock<Option<SelectPrivileges>>>;
+
+/// Enforces the upstream database's `SELECT` privileges for the user a single connection has
+/// authenticated as
+#[derive(Debug, Clone)]
+pub(crate) struct PrivilegeCheck {
+    privileges: SharedSelectPrivileges,
+    /// The user the connection has authenticated as, if it has authenticated yet
+    user: Option<String>,
+}
+
+impl PrivilegeCheck {
+    pub(crate) fn new(privileges: SharedSelectPrivileges) -> Self {
+        Self {
+            privileges,
+            user: None,
+        }
+    }
+
+    pub(crate) fn set_user(&mut self, user: &str) {
+        self.user = Some(user.to_owned());
+    }
+
+    /// Check that the connection's user can select from all of the given `tables`
+    pub(crate) fn check(
+        &self,
+        tables: &[Relation],
+        schema_search_path: &[SqlIdentifier],
+    ) -> ReadySetResult<()> {
+        let user = self.user.as_deref().unwrap_or_default();
+        match &*self.privileges.read() {
+            Some(privileges) => privileges.check(user, tables, schema_search_path),
+            None => match tables.first() {
+                Some(table) => Err(ReadySetError::SelectPrivilegeDenied {
+                    user: user.to_owned(),
+                    table: table.display_unquoted().to_string(),
+                }),
+                None => Ok(()),
+            },
+        }
+    }
+}
+
+#[derive(Default)]
+struct TablesVisitor<'ast> {
+    tables: Vec<&'ast Relation>,
+    cte_names: HashSet<&'ast SqlIdentifier>,
+}
+
+impl<'ast> Visitor<'ast> for TablesVisitor<'ast> {
+    type Error = !;
+
+    fn visit_common_table_expr(&mut self, cte: &'ast CommonTableExpr) -> Result<(), Self::Error> {
+        self.cte_names.insert(&cte.name);
+        visit::walk_common_table_expr(self, cte)
+    }
+
+    fn visit_table_expr(&mut self, table_expr: &'ast TableExpr) -> Result<(), Self::Error> {
+        if let TableExprInner::Table(table) = &table_expr.inner {
+            self.tables.push(table);
+        }
+        visit::walk_table_expr(self, table_expr)
+    }
+}
+
+/// Returns all the tables (or views) read by the given statement, including in subqueries, but not
+/// including references to common table expressions
+pub(crate) fn tables_read_by(statement: &SelectStatement) -> Vec<Relation> {
+    let mut visitor = TablesVisitor::default();
+    let _ = visitor.visit_select_statement(statement);
+    let mut tables = Vec::with_capacity(visitor.tables.len());
+    for table in visitor.tables {
+        if (table.schema.is_some() || !visitor.cte_names.contains(&table.name))
+            && !tables.contains(table)
+        {
+            tables.push(table.clone());
+        }
+    }
+    tables
+}
+
+/// Periodically reads the `SELECT` privileges of all users from the upstream database into a
+/// [`SharedSelectPrivileges`]
+pub struct PrivilegeSynchronizer<DB: UpstreamDatabase> {
+    upstream_config: UpstreamConfig,
+    /// Connection to the upstream database, re-established if an error is encountered
+    upstream: Option<DB>,
+    privileges: SharedSelectPrivileges,
+    /// The interval between subsequent reads of the upstream database's privileges
+    poll_interval: Duration,
+    /// Receiver to return the shutdown signal on
+    shutdown_recv: ShutdownReceiver,
+}
+
+impl<DB: UpstreamDatabase> PrivilegeSynchronizer<DB> {
+    pub fn new(
+        upstream_config: UpstreamConfig,
+        privileges: SharedSelectPrivileges,
+        poll_interval: Duration,
+        shutdown_recv: ShutdownReceiver,
+    ) -> Self {
+        Self {
+            upstream_config,
+            upstream: None,
+            privileges,
+            poll_interval,
+            shutdown_recv,
+        }
+    }
+
+    pub async fn run(&mut self) {
+        let mut interval = tokio::time::interval(self.poll_interval);
+        loop {
+            select! {
+                biased;
+                _ = self.shutdown_recv.recv() => {
+                    info!("Privilege synchronizer shutting down after shut down signal received");
+                    break;
+                }
+                _ = interval.tick() => self.poll().await,
+            }
+        }
+    }
+
+    async fn poll(&mut self) {
+        debug!("Reading SELECT privileges from upstream database");
+        let upstream = match &mut self.upstream {
+            Some(upstream) => upstream,
+            None => match DB::connect(self.upstream_config.clone(), None).await {
+                Ok(upstream) => self.upstream.insert(upstream),
+                Err(error) => {
+                    warn!(%error, "Could not connect to upstream database to read privileges");
+                    return;
+                }
+            },
+        };
+
+        match upstream.select_privileges().await {
+            Ok(privileges) => {
+                let mut current = self.privileges.write();
+                if current.as_ref() != Some(&privileges) {
+                    info!(
+                        num_users = privileges.users.len(),
+                        "Loaded SELECT privileges from upstream database"
+                    );
+                    *current = Some(privileges);
+                }
+            }
+            Err(error) => {
+                warn!(%error, "Could not read privileges from upstream database");
+                self.upstream = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::{parse_select_statement, Dialect};
+
+    use super::*;
+
+    fn tables(query: &str) -> Vec<Relation> {
+        tables_read_by(&parse_select_statement(Dialect::MySQL, query).unwrap())
+    }
+
+    #[test]
+    fn tables_in_joins_and_subqueries() {
+        assert_eq!(
+            tables(
+                "WITH recent AS (SELECT * FROM s.posts) SELECT * FROM users u \
+                 JOIN recent ON u.id = recent.author \
+                 WHERE u.id IN (SELECT user_id FROM follows)"
+            ),
+            vec![
+                Relation {
+                    schema: Some("s".into()),
+                    name: "posts".into()
+                },
+                "users".into(),
+                "follows".into(),
+            ]
+        );
+    }
+
+    #[test]
+    fn grant_patterns() {
+        assert!(matches_grant_pattern("app", "app"));
+        assert!(!matches_grant_pattern("app", "app2"));
+        assert!(matches_grant_pattern("app%", "app_staging"));
+        assert!(matches_grant_pattern("app_", "app2"));
+        assert!(!matches_grant_pattern("app\\_", "app2"));
+        assert!(matches_grant_pattern("app\\_", "app_"));
+    }
+
+    #[test]
+    fn check_privileges() {
+        let privileges = SelectPrivileges {
+            users: HashMap::from([
+                (
+                    "alice".to_owned(),
+                    UserPrivileges {
+                        schemas: vec!["app%".into()],
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "bob".to_owned(),
+                    UserPrivileges {
+                        tables: HashSet::from([Relation {
+                            schema: Some("public".into()),
+                            name: "posts".into(),
+                        }]),
+                        ..Default::default()
+                    },
+                ),
+            ]),
+            known_tables: HashSet::from([Relation {
+                schema: Some("public".into()),
+                name: "posts".into(),
+            }]),
+        };
+
+        privileges
+            .check("alice", &["users".into()], &["app".into()])
+            .unwrap();
+        privileges
+            .check("alice", &["users".into()], &["other".into()])
+            .unwrap_err();
+
+        // unqualified tables resolve to the first schema in the search path they exist in
+        let search_path = ["bob".into(), "public".into()];
+        privileges
+            .check("bob", &["posts".into()], &search_path)
+            .unwrap();
+        privileges
+            .check("bob", &["posts".into(), "users".into()], &search_path)
+            .unwrap_err();
+
+        privileges
+            .check("carol", &["posts".into()], &search_path)
+            .unwrap_err();
+    }
+}
@@ -0,0 +1,153 @@
+//! Hints embedded in individual statements, which override how the adapter handles that statement
+//! regardless of its query status, so that specific queries can be pinned to known-good behavior
+//! without changing the adapter's configuration.
+//!
+//! Two hints are supported:
+//!
+//! - `/*+ readyset_upstream */` always proxies the statement to the upstream database.
+//! - `/*+ readyset_view(name) */` always serves the statement from the cache with the given name,
+//!   without falling back to the upstream database if the read fails. The cache must have been
+//!   created for the same query (eg with a different name, or as the candidate of an experiment).
+//!   This hint is only supported for ad-hoc (not prepared) statements.
+//!
+//! Hints are written in a comment starting with `/*+`, either at the very beginning of the
+//! statement or directly after its first keyword (where MySQL expects optimizer hints), eg `SELECT
+//! /*+ readyset_upstream */ * FROM t`. Hint names are case-insensitive. Any other hints in the same
+//! comment, such as MySQL optimizer hints, are ignored; if a comment contains more than one
+//! ReadySet hint, the last one wins.
+
+use nom_sql::{Dialect, Relation};
+
+/// A hint overriding how the adapter handles a single statement. See the [module
+/// documentation](self) for more information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryHint {
+    /// Serve the statement from the cache with the given name
+    View(Relation),
+    /// Proxy the statement to the upstream database
+    Upstream,
+}
+
+impl QueryHint {
+    /// Returns the hint embedded in the given statement, if any
+    pub fn parse(dialect: Dialect, query: &str) -> Option<Self> {
+        let query = query.trim_start();
+        let after_keyword = query
+            .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+            .trim_start();
+        let comment = [query, after_keyword]
+            .into_iter()
+            .find_map(|s| s.strip_prefix("/*+"))?;
+        let comment = &comment[..comment.find("*/")?];
+
+        hints(comment)
+            .filter_map(|(name, args)| {
+                if name.eq_ignore_ascii_case("readyset_upstream") {
+                    Some(QueryHint::Upstream)
+                } else if name.eq_ignore_ascii_case("readyset_view") {
+                    nom_sql::parse_relation(dialect, args?)
+                        .ok()
+                        .map(QueryHint::View)
+                } else {
+                    None
+                }
+            })
+            .last()
+    }
+}
+
+/// Returns an iterator over the name and (if present) parenthesized arguments of each hint in the
+/// given hint comment
+fn hints(mut comment: &str) -> impl Iterator<Item = (&str, Option<&str>)> {
+    std::iter::from_fn(move || loop {
+        comment = comment.trim_start();
+        if comment.is_empty() {
+            return None;
+        }
+
+        let name_len = comment
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(comment.len());
+        let name = &comment[..name_len];
+        comment = comment[name_len..].trim_start();
+
+        let args = match comment.strip_prefix('(') {
+            Some(rest) => {
+                let end = rest.find(')').unwrap_or(rest.len());
+                comment = rest.get(end + 1..).unwrap_or_default();
+                Some(rest[..end].trim())
+            }
+            None => None,
+        };
+
+        if !name.is_empty() {
+            return Some((name, args));
+        }
+        if args.is_none() {
+            // Skip anything that isn't part of a hint
+            let mut chars = comment.chars();
+            chars.next();
+            comment = chars.as_str();
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(query: &str) -> Option<QueryHint> {
+        QueryHint::parse(Dialect::MySQL, query)
+    }
+
+    #[test]
+    fn no_hint() {
+        assert_eq!(parse("SELECT * FROM t"), None);
+        assert_eq!(parse("/* readyset_upstream */ SELECT * FROM t"), None);
+        assert_eq!(parse("SELECT * FROM t /*+ readyset_upstream */"), None);
+        assert_eq!(parse("SELECT /*+ BKA(t) */ * FROM t"), None);
+        assert_eq!(parse("SELECT /*+ readyset_upstream * FROM t"), None);
+    }
+
+    #[test]
+    fn upstream() {
+        assert_eq!(
+            parse("/*+ readyset_upstream */ SELECT * FROM t"),
+            Some(QueryHint::Upstream)
+        );
+        assert_eq!(
+            parse("  select /*+READYSET_UPSTREAM*/ * FROM t"),
+            Some(QueryHint::Upstream)
+        );
+    }
+
+    #[test]
+    fn view() {
+        assert_eq!(
+            parse("SELECT /*+ readyset_view(q1) */ * FROM t WHERE x = 1"),
+            Some(QueryHint::View("q1".into()))
+        );
+        assert_eq!(
+            parse("SELECT /*+ readyset_view( `s`.`q1` ) */ * FROM t WHERE x = 1"),
+            Some(QueryHint::View(Relation {
+                schema: Some("s".into()),
+                name: "q1".into(),
+            }))
+        );
+        assert_eq!(parse("SELECT /*+ readyset_view */ * FROM t"), None);
+    }
+
+    #[test]
+    fn alongside_other_hints() {
+        assert_eq!(
+            parse(
+                "SELECT /*+ NO_RANGE_OPTIMIZATION(t PRIMARY) readyset_view(q1) BKA(t) */ * FROM t"
+            ),
+            Some(QueryHint::View("q1".into()))
+        );
+        assert_eq!(
+            parse("SELECT /*+ readyset_view(q1), readyset_upstream */ * FROM t"),
+            Some(QueryHint::Upstream)
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! Operator-configured lists of queries which should always be served by ReadySet (the query allow
+//! list) or always be proxied to the upstream database (the query deny list), regardless of whether
+//! ReadySet supports them.
+//!
+//! Queries can be pinned by listing them in the files passed to `--query-allow-list-file` and
+//! `--query-deny-list-file`, or at runtime via the adapter's HTTP API. Each file contains one
+//! `SELECT` statement per line, optionally terminated by a semicolon; empty lines and lines
+//! starting with `--` or `#` are ignored. Queries are matched after being rewritten in the same way
+//! as queries received by the adapter, so literals which would be auto-parameterized match any
+//! value.
+//!
+//! The files are re-read periodically, and any changes are applied to the [`QueryStatusCache`]
+//! without restarting the adapter. If a query is pinned both at runtime and in a file, the runtime
+//! pin takes precedence; if a query is listed in both files, it is denied.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use nom_sql::{Dialect, SelectStatement};
+use parking_lot::Mutex;
+use readyset_errors::{invalid_err, ReadySetResult};
+use readyset_util::shutdown::ShutdownReceiver;
+use tokio::select;
+use tracing::{info, warn};
+
+use crate::query_status_cache::{QueryPin, QueryStatusCache};
+use crate::rewrite;
+
+/// How often to check the allow and deny list files for changes
+const RELOAD_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+struct PinState {
+    /// The contents of the allow and deny list files as of the last successful reload
+    file_contents: (Option<String>, Option<String>),
+    /// Pins loaded from the allow and deny list files
+    file_pins: HashMap<SelectStatement, QueryPin>,
+    /// Pins set at runtime, which take precedence over `file_pins`. A value of `None` unpins a
+    /// query which is pinned in one of the files.
+    runtime_pins: HashMap<SelectStatement, Option<QueryPin>>,
+}
+
+/// The set of queries pinned by the operator, which keeps the pins in the [`QueryStatusCache`] in
+/// sync with the allow and deny list files and any runtime changes
+pub struct QueryPinLists {
+    allow_list_file: Option<PathBuf>,
+    deny_list_file: Option<PathBuf>,
+    dialect: Dialect,
+    server_supports_pagination: bool,
+    query_status_cache: &'static QueryStatusCache,
+    state: Mutex<PinState>,
+}
+
+impl QueryPinLists {
+    /// Construct a new, empty set of pinned queries. Call [`QueryPinLists::reload`] to load the
+    /// initial contents of the allow and deny list files.
+    pub fn new(
+        allow_list_file: Option<PathBuf>,
+        deny_list_file: Option<PathBuf>,
+        dialect: Dialect,
+        server_supports_pagination: bool,
+        query_status_cache: &'static QueryStatusCache,
+    ) -> Self {
+        Self {
+            allow_list_file,
+            deny_list_file,
+            dialect,
+            server_supports_pagination,
+            query_status_cache,
+            state: Default::default(),
+        }
+    }
+
+    /// Parse and rewrite a single query, so that it can be matched against the queries in the
+    /// [`QueryStatusCache`]
+    pub fn normalize(&self, query: &str) -> ReadySetResult<SelectStatement> {
+        let query = query.trim();
+        let query = query.strip_suffix(';').unwrap_or(query);
+        let mut stmt = nom_sql::parse_select_statement(self.dialect, query)
+            .map_err(|_| invalid_err!("Could not parse pinned query as a SELECT: {query}"))?;
+        rewrite::process_query(&mut stmt, self.server_supports_pagination)?;
+        Ok(stmt)
+    }
+
+    fn parse_list(&self, contents: &str) -> ReadySetResult<Vec<SelectStatement>> {
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with("--") && !l.starts_with('#'))
+            .map(|l| self.normalize(l))
+            .collect()
+    }
+
+    async fn read_file(path: &Option<PathBuf>) -> ReadySetResult<Option<String>> {
+        match path {
+            Some(path) => tokio::fs::read_to_string(path)
+                .await
+                .map(Some)
+                .map_err(|e| invalid_err!("Could not read {}: {e}", path.display())),
+            None => Ok(None),
+        }
+    }
+
+    /// Push the current set of pins to the query status cache
+    fn apply(&self, state: &PinState) {
+        let mut pins = state.file_pins.clone();
+        for (stmt, pin) in &state.runtime_pins {
+            match pin {
+                Some(pin) => pins.insert(stmt.clone(), *pin),
+                None => pins.remove(stmt),
+            };
+        }
+        self.query_status_cache.set_pinned_queries(pins);
+    }
+
+    /// Re-read the allow and deny list files, applying any changes. Returns true if either file
+    /// changed.
+    ///
+    /// If either file can't be read or contains an invalid query, returns an error and leaves the
+    /// current pins unchanged.
+    pub async fn reload(&self) -> ReadySetResult<bool> {
+        let contents = (
+            Self::read_file(&self.allow_list_file).await?,
+            Self::read_file(&self.deny_list_file).await?,
+        );
+        if self.state.lock().file_contents == contents {
+            return Ok(false);
+        }
+
+        let mut file_pins = HashMap::new();
+        if let Some(allow) = &contents.0 {
+            file_pins.extend(
+                self.parse_list(allow)?
+                    .into_iter()
+                    .map(|stmt| (stmt, QueryPin::Allow)),
+            );
+        }
+        if let Some(deny) = &contents.1 {
+            file_pins.extend(
+                self.parse_list(deny)?
+                    .into_iter()
+                    .map(|stmt| (stmt, QueryPin::Deny)),
+            );
+        }
+
+        let mut state = self.state.lock();
+        info!(
+            allowed = file_pins
+                .values()
+                .filter(|p| **p == QueryPin::Allow)
+                .count(),
+            denied = file_pins.values().filter(|p| **p == QueryPin::Deny).count(),
+            "Loaded query allow and deny lists"
+        );
+        state.file_contents = contents;
+        state.file_pins = file_pins;
+        self.apply(&state);
+        Ok(true)
+    }
+
+    /// Pin (or, if `pin` is `None`, unpin) a query at runtime, overriding the allow and deny list
+    /// files
+    pub fn set_pin(&self, query: &str, pin: Option<QueryPin>) -> ReadySetResult<()> {
+        let stmt = self.normalize(query)?;
+        let mut state = self.state.lock();
+        state.runtime_pins.insert(stmt, pin);
+        self.apply(&state);
+        Ok(())
+    }
+
+    /// Returns the display strings of all currently pinned queries, along with their pins
+    pub fn pinned_queries(&self) -> Vec<(String, QueryPin)> {
+        self.query_status_cache
+            .pinned_queries()
+            .into_iter()
+            .map(|(stmt, pin)| (stmt.display(self.dialect).to_string(), pin))
+            .collect()
+    }
+
+    /// Periodically reload the allow and deny list files until a shutdown signal is received
+    pub async fn run(&self, mut shutdown_rx: ShutdownReceiver) {
+        if self.allow_list_file.is_none() && self.deny_list_file.is_none() {
+            return;
+        }
+
+        let mut interval = tokio::time::interval(RELOAD_INTERVAL);
+        loop {
+            select! {
+                _ = interval.tick() => {
+                    if let Err(error) = self.reload().await {
+                        warn!(%error, "Failed to reload query allow and deny lists");
+                    }
+                }
+                _ = shutdown_rx.recv() => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use readyset_client::query::MigrationState;
+    use readyset_client::ViewCreateRequest;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn pins_follow_files_and_runtime_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let allow_path = dir.path().join("allow.sql");
+        let deny_path = dir.path().join("deny.sql");
+        std::fs::write(&allow_path, "-- cached\nSELECT * FROM t1 WHERE id = 1;\n").unwrap();
+        std::fs::write(&deny_path, "SELECT * FROM t2\n").unwrap();
+
+        let cache: &'static _ = Box::leak(Box::new(QueryStatusCache::new()));
+        let pins = QueryPinLists::new(
+            Some(allow_path),
+            Some(deny_path.clone()),
+            Dialect::MySQL,
+            false,
+            cache,
+        );
+        assert!(pins.reload().await.unwrap());
+        assert!(!pins.reload().await.unwrap());
+
+        let allowed = pins.normalize("SELECT * FROM t1 WHERE id = 42").unwrap();
+        let denied = ViewCreateRequest::new(pins.normalize("SELECT * FROM t2").unwrap(), vec![]);
+        assert_eq!(cache.pin(&allowed), Some(QueryPin::Allow));
+        assert!(
+            cache
+                .query_status(&ViewCreateRequest::new(allowed, vec![]))
+                .always
+        );
+        assert_eq!(
+            cache.query_migration_state(&denied).1,
+            MigrationState::Unsupported
+        );
+
+        std::fs::write(&deny_path, "").unwrap();
+        assert!(pins.reload().await.unwrap());
+        assert_eq!(
+            cache.query_migration_state(&denied).1,
+            MigrationState::Pending
+        );
+
+        pins.set_pin("SELECT * FROM t2", Some(QueryPin::Deny))
+            .unwrap();
+        assert_eq!(
+            cache.query_migration_state(&denied).1,
+            MigrationState::Unsupported
+        );
+        assert!(pins.set_pin("DELETE FROM t2", None).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 //! The query status cache provides a thread-safe window into an adapter's
 //! knowledge about queries, currently the migration status of a query in
 //! ReadySet.
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -11,6 +11,7 @@ use anyhow::anyhow;
 use clap::ValueEnum;
 use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
+use nom_sql::SelectStatement;
 use readyset_client::query::*;
 use readyset_client::ViewCreateRequest;
 use readyset_data::DfValue;
@@ -46,6 +47,42 @@ pub struct QueryStatusCache {
     ///
     /// Currently unused.
     enable_experimental_placeholder_inlining: bool,
+
+    /// Queries which have been pinned by the operator to always be cached by ReadySet or always be
+    /// proxied to the upstream database, indexed by their rewritten statement. See
+    /// [`crate::query_pins`].
+    pins: DashMap<SelectStatement, QueryPin, ahash::RandomState>,
+}
+
+/// An operator-configured override of how a query is handled, regardless of the query's migration
+/// state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryPin {
+    /// Always serve the query from ReadySet, migrating it if necessary
+    Allow,
+    /// Never attempt to migrate the query, and always proxy it to the upstream database
+    Deny,
+}
+
+impl QueryPin {
+    /// Update `status` to reflect this pin
+    fn apply(self, status: &mut QueryStatus) {
+        match self {
+            QueryPin::Allow => status.always = true,
+            QueryPin::Deny => {
+                status.migration_state = MigrationState::Unsupported;
+                status.always = false;
+            }
+        }
+    }
+
+    /// Undo the effect of [`QueryPin::apply`] on `status`
+    fn unapply(self, status: &mut QueryStatus) {
+        match self {
+            QueryPin::Allow => status.always = false,
+            QueryPin::Deny => status.migration_state = MigrationState::Pending,
+        }
+    }
 }
 
 /// Keys into the queries stored in `QueryStatusCache`
@@ -134,6 +171,7 @@ impl QueryStatusCache {
             pending_inlined_migrations: DashMap::default(),
             style: MigrationStyle::InRequestPath,
             enable_experimental_placeholder_inlining: false,
+            pins: DashMap::default(),
         }
     }
 
@@ -177,8 +215,14 @@ impl QueryStatusCache {
         Q: Into<Query>,
     {
         let q: Query = q.into();
-        let status = match q {
-            Query::Parsed { .. } => status,
+        let status = match &q {
+            Query::Parsed(req) => {
+                let mut status = status;
+                if let Some(pin) = self.pin(&req.statement) {
+                    pin.apply(&mut status);
+                }
+                status
+            }
             Query::ParseFailed(_) => {
                 let mut status = status;
                 if status.migration_state != MigrationState::Unsupported {
@@ -473,10 +517,51 @@ impl QueryStatusCache {
             .filter(|v| v.is_successful())
             .for_each(|mut v| {
                 v.migration_state = MigrationState::Pending;
-                v.always = false;
+                v.always = self.pin(&v.key().statement) == Some(QueryPin::Allow);
             });
     }
 
+    /// Returns the pin for the given rewritten statement, if any
+    pub fn pin(&self, statement: &SelectStatement) -> Option<QueryPin> {
+        self.pins.get(statement).map(|p| *p)
+    }
+
+    /// Returns all currently pinned statements, along with their pins
+    pub fn pinned_queries(&self) -> Vec<(SelectStatement, QueryPin)> {
+        self.pins
+            .iter()
+            .map(|r| (r.key().clone(), *r.value()))
+            .collect()
+    }
+
+    /// Replace the set of pinned queries with `pins`, updating the status of all queries whose pin
+    /// has changed.
+    ///
+    /// Queries which are no longer denied are returned to the [`MigrationState::Pending`] state,
+    /// so that they can be migrated again (or re-marked as unsupported).
+    pub fn set_pinned_queries(&self, pins: HashMap<SelectStatement, QueryPin>) {
+        for mut entry in self.statuses.iter_mut() {
+            let statement = &entry.key().statement;
+            let old = self.pin(statement);
+            let new = pins.get(statement).copied();
+            if old == new {
+                continue;
+            }
+            let status = entry.value_mut();
+            if let Some(old) = old {
+                old.unapply(status);
+            }
+            if let Some(new) = new {
+                new.apply(status);
+            }
+        }
+
+        self.pins.retain(|k, _| pins.contains_key(k));
+        for (statement, pin) in pins {
+            self.pins.insert(statement, pin);
+        }
+    }
+
     /// This method is called when a query is executed with the given params, but no inlined cache
     /// exists for the params. Adding the query to `Self::pending_inlined_migrations` indicates that
     /// it should be migrated by the MigrationHandler.
@@ -0,0 +1,105 @@
+//! Routing of fallback reads to read replicas of the upstream database.
+//!
+//! By default, every query which can't be served by ReadySet - including reads of queries which
+//! aren't cached, or which miss in the cache - is proxied to the upstream database, adding load to
+//! the primary. If the adapter is configured with the URLs of one or more read replicas of the
+//! upstream database, each client connection additionally opens a connection to one of those
+//! replicas, and ad-hoc `SELECT` statements which would otherwise be proxied to the primary outside
+//! of a transaction are sent to the replica instead (see [`Backend::with_read_upstream`]).
+//!
+//! Replicas are assigned to new client connections round-robin, skipping replicas which can't be
+//! connected to. If no replica can be connected to, reads are proxied to the primary.
+//!
+//! [`Backend::with_read_upstream`]: crate::Backend::with_read_upstream
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use readyset_util::redacted::RedactedString;
+use tokio::time::timeout;
+use tracing::warn;
+
+use crate::fallback_cache::FallbackCache;
+use crate::{UpstreamConfig, UpstreamDatabase};
+
+/// A set of read replicas of the upstream database, shared between connections. See the [module
+/// documentation](self) for more information.
+pub struct ReadReplicas {
+    urls: Vec<RedactedString>,
+    /// The index into `urls` of the replica to try first for the next connection
+    next: AtomicUsize,
+}
+
+impl ReadReplicas {
+    /// Create a new set of read replicas from their URLs
+    pub fn new(urls: Vec<RedactedString>) -> Self {
+        Self {
+            urls,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns `true` if no read replicas are configured
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+
+    /// Returns the URLs of all replicas, in the order they should be tried by the next connection
+    fn next_urls(&self) -> impl Iterator<Item = &RedactedString> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let len = self.urls.len();
+        (0..len).map(move |i| &self.urls[start.wrapping_add(i) % len])
+    }
+
+    /// Connect to the next read replica, using the rest of the given upstream `config`. Returns
+    /// `None` if no replicas are configured, or none of them could be connected to within
+    /// `connect_timeout`.
+    pub async fn connect<DB: UpstreamDatabase>(
+        &self,
+        config: &UpstreamConfig,
+        fallback_cache: Option<FallbackCache<DB::CachedReadResult>>,
+        connect_timeout: Duration,
+    ) -> Option<DB> {
+        for url in self.next_urls() {
+            let config = UpstreamConfig {
+                upstream_db_url: Some(url.clone()),
+                ..config.clone()
+            };
+            match timeout(connect_timeout, DB::connect(config, fallback_cache.clone())).await {
+                Ok(Ok(upstream)) => return Some(upstream),
+                Ok(Err(error)) => warn!(%error, "Error connecting to read replica"),
+                Err(_) => warn!("Connection to read replica timed out"),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_replicas(urls: &[&str]) -> ReadReplicas {
+        ReadReplicas::new(urls.iter().map(|url| url.to_string().into()).collect())
+    }
+
+    fn next_urls(replicas: &ReadReplicas) -> Vec<String> {
+        replicas.next_urls().map(|url| url.0.clone()).collect()
+    }
+
+    #[test]
+    fn round_robin() {
+        let replicas = read_replicas(&["a", "b", "c"]);
+        assert_eq!(next_urls(&replicas), ["a", "b", "c"]);
+        assert_eq!(next_urls(&replicas), ["b", "c", "a"]);
+        assert_eq!(next_urls(&replicas), ["c", "a", "b"]);
+        assert_eq!(next_urls(&replicas), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn no_replicas() {
+        let replicas = read_replicas(&[]);
+        assert!(replicas.is_empty());
+        assert!(next_urls(&replicas).is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+//! Limits on how result sets are buffered while they're written to clients.
+//!
+//! Rows from both ReadySet and the upstream database are written to the client as they're produced,
+//! rather than being buffered in their entirety first. The protocol-specific backends use a
+//! [`StreamedResultSize`] to keep track of how many bytes of rows they've written for a result set,
+//! flushing buffered rows out to the client once more than
+//! [`max_buffered_bytes`](ResultStreaming::max_buffered_bytes) are pending, and failing the query
+//! partway through if a single row or the result set as a whole grows past the configured hard
+//! limits.
+
+use readyset_errors::{ReadySetError, ReadySetResult};
+
+/// The default value for [`ResultStreaming::max_buffered_bytes`]
+pub const DEFAULT_MAX_BUFFERED_BYTES: usize = 1024 * 1024;
+
+/// Configuration for streaming result sets to clients
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResultStreaming {
+    /// Once more than this many bytes of rows are buffered for a result set, the buffered rows are
+    /// flushed to the client before any more are written
+    pub max_buffered_bytes: usize,
+    /// The maximum size, in bytes, of a single row written to a client
+    pub max_row_bytes: Option<usize>,
+    /// The maximum total size, in bytes, of the rows in a single result set written to a client
+    pub max_result_bytes: Option<usize>,
+}
+
+impl Default for ResultStreaming {
+    fn default() -> Self {
+        Self {
+            max_buffered_bytes: DEFAULT_MAX_BUFFERED_BYTES,
+            max_row_bytes: None,
+            max_result_bytes: None,
+        }
+    }
+}
+
+/// Tracks the size of a single result set as its rows are written to a client
+#[derive(Debug)]
+pub struct StreamedResultSize {
+    limits: ResultStreaming,
+    buffered_bytes: usize,
+    total_bytes: usize,
+}
+
+impl StreamedResultSize {
+    pub fn new(limits: ResultStreaming) -> Self {
+        Self {
+            limits,
+            buffered_bytes: 0,
+            total_bytes: 0,
+        }
+    }
+
+    /// Record that a row of `row_bytes` bytes is about to be written to the client.
+    ///
+    /// Returns an error if the row, or the result set including the row, is larger than the
+    /// configured limits, in which case the row should not be written. Otherwise, returns `true` if
+    /// the rows buffered so far (including this one) should be flushed to the client once the row
+    /// has been written.
+    pub fn add_row(&mut self, row_bytes: usize) -> ReadySetResult<bool> {
+        if let Some(max_bytes) = self.limits.max_row_bytes {
+            if row_bytes > max_bytes {
+                return Err(ReadySetError::RowSizeLimitExceeded { max_bytes });
+            }
+        }
+
+        self.total_bytes += row_bytes;
+        if let Some(max_bytes) = self.limits.max_result_bytes {
+            if self.total_bytes > max_bytes {
+                return Err(ReadySetError::ResultSizeLimitExceeded { max_bytes });
+            }
+        }
+
+        self.buffered_bytes += row_bytes;
+        if self.buffered_bytes > self.limits.max_buffered_bytes {
+            self.buffered_bytes = 0;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Returns the total number of bytes of rows written so far
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flushes_after_max_buffered_bytes() {
+        let mut size = StreamedResultSize::new(ResultStreaming {
+            max_buffered_bytes: 10,
+            ..Default::default()
+        });
+        assert!(!size.add_row(6).unwrap());
+        assert!(size.add_row(6).unwrap());
+        assert!(!size.add_row(6).unwrap());
+        assert_eq!(size.total_bytes(), 18);
+    }
+
+    #[test]
+    fn row_size_limit() {
+        let mut size = StreamedResultSize::new(ResultStreaming {
+            max_row_bytes: Some(8),
+            ..Default::default()
+        });
+        size.add_row(8).unwrap();
+        assert_eq!(
+            size.add_row(9).unwrap_err(),
+            ReadySetError::RowSizeLimitExceeded { max_bytes: 8 }
+        );
+    }
+
+    #[test]
+    fn result_size_limit() {
+        let mut size = StreamedResultSize::new(ResultStreaming {
+            max_result_bytes: Some(20),
+            ..Default::default()
+        });
+        size.add_row(10).unwrap();
+        size.add_row(10).unwrap();
+        assert_eq!(
+            size.add_row(1).unwrap_err(),
+            ReadySetError::ResultSizeLimitExceeded { max_bytes: 20 }
+        );
+    }
+}
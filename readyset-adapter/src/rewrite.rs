@@ -4,13 +4,18 @@ use std::convert::{TryFrom, TryInto};
 use std::fmt::Debug;
 use std::{iter, mem};
 
+use chrono::{Local, NaiveDateTime};
 use itertools::{Either, Itertools};
+use nom_sql::analysis::is_aggregate;
+use nom_sql::analysis::visit::{self, Visitor};
 use nom_sql::analysis::visit_mut::{self, VisitorMut};
 use nom_sql::{
-    BinaryOperator, Expr, InValue, ItemPlaceholder, LimitClause, Literal, SelectStatement,
+    BinaryOperator, Expr, FunctionExpr, InValue, ItemPlaceholder, LimitClause, Literal,
+    SelectStatement,
 };
-use readyset_data::{DfType, DfValue};
+use readyset_data::{DfType, DfValue, Dialect};
 use readyset_errors::{internal_err, invalid_err, unsupported, ReadySetError, ReadySetResult};
+use readyset_sql_passes::expr::const_eval;
 use tracing::trace;
 
 /// Struct storing information about parameters processed from a raw user supplied query, which
@@ -24,14 +29,36 @@ use tracing::trace;
 pub struct ProcessedQueryParams {
     reordered_placeholders: Option<Vec<usize>>,
     rewritten_in_conditions: Vec<RewrittenIn>,
-    auto_parameters: Vec<(usize, Literal)>,
+    auto_parameters: Vec<(usize, AutoParameter)>,
     pagination_parameters: AdapterPaginationParams,
 }
 
+/// The value of a parameter which was extracted from the query by [`process_query`], rather than
+/// being supplied by the user
+#[derive(Debug, Clone)]
+enum AutoParameter {
+    /// A literal which appeared in the query
+    Literal(Literal),
+    /// An expression relative to the current time, such as `NOW() - INTERVAL 1 DAY`, which is
+    /// evaluated each time the query is executed
+    NowRelative(Expr),
+}
+
+impl AutoParameter {
+    fn value(&self) -> ReadySetResult<Literal> {
+        match self {
+            AutoParameter::Literal(lit) => Ok(lit.clone()),
+            AutoParameter::NowRelative(expr) => eval_now_relative(expr, Local::now().naive_local()),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct AdapterPaginationParams {
     /// The values of `LIMIT` and `OFFSET` in the original query
     limit_clause: LimitClause,
+    /// Whether the values of `LIMIT` and `OFFSET` are passed to the reader with each read, rather
+    /// than being handled by the dataflow graph
     force_paginate_in_adapter: bool,
 }
 
@@ -40,7 +67,7 @@ struct AdapterPaginationParams {
 /// post-processing path.
 fn use_fallback_pagination(server_supports_pagination: bool, limit_clause: &LimitClause) -> bool {
     if server_supports_pagination &&
-        // Can't handle parameterized LIMIT even if support is enabled
+        // Parameterized LIMIT can't be materialized, so is always applied per-read
         !matches!(limit_clause.limit(), Some(Literal::Placeholder(_))) &&
         // Can't handle bare OFFSET
         !(limit_clause.limit().is_none() && limit_clause.offset().is_some())
@@ -53,30 +80,105 @@ fn use_fallback_pagination(server_supports_pagination: bool, limit_clause: &Limi
     true
 }
 
+/// Returns true if a parameterized `LIMIT` (and `OFFSET`) should be kept in the query, so that
+/// readyset-server caches the full ordered result set for each key and the reader applies the
+/// limit and offset passed with each read.
+fn use_reader_pagination(server_supports_pagination: bool, limit_clause: &LimitClause) -> bool {
+    server_supports_pagination
+        && matches!(limit_clause.limit(), Some(Literal::Placeholder(_)))
+        && matches!(limit_clause.offset(), None | Some(Literal::Placeholder(_)))
+}
+
+/// The names of PostgreSQL's full-text search functions
+const FULL_TEXT_SEARCH_FUNCTIONS: &[&str] = &[
+    "to_tsvector",
+    "to_tsquery",
+    "plainto_tsquery",
+    "phraseto_tsquery",
+    "websearch_to_tsquery",
+    "ts_rank",
+    "ts_rank_cd",
+    "ts_headline",
+];
+
+struct FullTextSearchVisitor;
+
+impl<'ast> Visitor<'ast> for FullTextSearchVisitor {
+    type Error = ReadySetError;
+
+    fn visit_expr(&mut self, expr: &'ast Expr) -> Result<(), Self::Error> {
+        match expr {
+            Expr::Call(FunctionExpr::Match { .. }) => {
+                unsupported!("Full-text search with MATCH ... AGAINST is not supported")
+            }
+            Expr::BinaryOp {
+                op: BinaryOperator::AtAt,
+                ..
+            } => unsupported!("Full-text search with '@@' is not supported"),
+            Expr::Call(FunctionExpr::Call { name, .. })
+                if FULL_TEXT_SEARCH_FUNCTIONS.contains(&name.to_lowercase().as_str()) =>
+            {
+                unsupported!("Full-text search with {}() is not supported", name)
+            }
+            _ => visit::walk_expr(self, expr),
+        }
+    }
+}
+
+/// Returns an unsupported error if the query uses full-text search, so that it's proxied to the
+/// upstream database without ever being migrated.
+///
+/// Full-text search can't be evaluated in dataflow, but since every distinct query gets its own
+/// cache, rejecting a query which uses it only affects that query - variants of the same query
+/// without the full-text predicate are cached as usual.
+fn reject_full_text_search(query: &SelectStatement) -> ReadySetResult<()> {
+    FullTextSearchVisitor.visit_select_statement(query)
+}
+
 /// This rewrite pass accomplishes the following:
+/// - Rejects queries which use full-text search (see [`reject_full_text_search`])
 /// - Remaps dollar sign placeholders so that they appear in order
 /// - Replaces literals with placeholders when they can be used as lookup indices in the noria
 ///   dataflow representation of the query. Note that this pass may not replace all literals and is
 ///   therefore cannot guarantee that the rewritten query is free of user PII.
-/// - Collapses 'WHERE <expr> IN ?, ... ?' to 'WHERE <expr> = ?'
+/// - Collapses 'WHERE <expr> IN ?, ... ?' to 'WHERE <expr> = ?', after replacing any literals in
+///   the list with placeholders, so that queries with IN lists of any length share a single view
 /// - Removes `OFFSET ?` if there isn't a `LIMIT`
+/// - Removes `LIMIT` and `OFFSET` if they're to be applied in the adapter (but keeps a
+///   parameterized `LIMIT` if the server supports pagination, for the reader to apply per-read)
 pub fn process_query(
     query: &mut SelectStatement,
     server_supports_pagination: bool,
 ) -> ReadySetResult<ProcessedQueryParams> {
+    reject_full_text_search(query)?;
+
     let reordered_placeholders = reorder_numbered_placeholders(query);
 
     let limit_clause = mem::take(&mut query.limit_clause);
 
     let force_paginate_in_adapter =
-        use_fallback_pagination(server_supports_pagination, &query.limit_clause);
+        use_fallback_pagination(server_supports_pagination, &limit_clause);
 
-    if !force_paginate_in_adapter {
-        // If adapter pagination shouldn't be used reinstate the limit clause
+    if !force_paginate_in_adapter
+        || use_reader_pagination(server_supports_pagination, &limit_clause)
+    {
+        // If adapter pagination shouldn't be used, or the limit and offset will be applied by the
+        // reader, reinstate the limit clause
         query.limit_clause.clone_from(&limit_clause);
     }
 
-    let auto_parameters = auto_parametrize_query(query);
+    let mut auto_parameters = parametrize_now_relative_conditions(query)
+        .into_iter()
+        .map(|(i, expr)| (i, AutoParameter::NowRelative(expr)))
+        .collect::<Vec<_>>();
+    // `auto_parametrize_query` leaves queries with range parameters alone, so it never adds
+    // placeholders to a query which had conditions parametrized above, which would invalidate
+    // their positions
+    auto_parameters.extend(
+        auto_parametrize_query(query)
+            .into_iter()
+            .map(|(i, lit)| (i, AutoParameter::Literal(lit))),
+    );
     let rewritten_in_conditions = collapse_where_in(query)?;
     number_placeholders(query)?;
     Ok(ProcessedQueryParams {
@@ -200,9 +302,8 @@ impl ProcessedQueryParams {
 
         let auto_parameters = self
             .auto_parameters
-            .clone()
-            .into_iter()
-            .map(|(i, lit)| -> ReadySetResult<_> { Ok((i, lit.try_into()?)) })
+            .iter()
+            .map(|(i, param)| -> ReadySetResult<_> { Ok((*i, param.value()?.try_into()?)) })
             .collect::<Result<Vec<_>, _>>()?;
 
         let params = splice_auto_parameters(params, &auto_parameters);
@@ -591,33 +692,24 @@ impl<'ast> VisitorMut<'ast> for AutoParametrizeVisitor {
                     lhs: box Expr::Column(_),
                     rhs: InValue::List(exprs),
                     negated: false,
-                } if exprs.iter().all(|e| {
-                    matches!(
-                        e,
-                        Expr::Literal(lit) if !matches!(lit, Literal::Placeholder(_))
-                    )
-                }) && !self.has_aggregates =>
+                } if exprs.iter().all(|e| matches!(e, Expr::Literal(_)))
+                    && exprs
+                        .iter()
+                        .any(|e| !matches!(e, Expr::Literal(Literal::Placeholder(_))))
+                    && !self.has_aggregates =>
                 {
-                    let exprs = mem::replace(
-                        exprs,
-                        iter::repeat(Expr::Literal(Literal::Placeholder(
-                            ItemPlaceholder::QuestionMark,
-                        )))
-                        .take(exprs.len())
-                        .collect(),
-                    );
-                    let num_exprs = exprs.len();
-                    let start_index = self.param_index;
-                    self.out
-                        .extend(exprs.into_iter().enumerate().filter_map(
-                            move |(i, expr)| match expr {
-                                Expr::Literal(lit) => Some((i + start_index, lit)),
-                                // unreachable since we checked everything in the list is a literal
-                                // above, but best not to panic regardless
-                                _ => None,
-                            },
-                        ));
-                    self.param_index += num_exprs;
+                    // Replace every literal in the list with a placeholder, leaving any existing
+                    // placeholders where they are, so that the IN can be collapsed into a single
+                    // lookup regardless of how many of its values were given as literals
+                    for expr in exprs {
+                        match expr {
+                            Expr::Literal(Literal::Placeholder(_)) => self.param_index += 1,
+                            Expr::Literal(lit) => self.replace_literal(lit),
+                            // unreachable since we checked everything in the list is a literal
+                            // above, but best not to panic regardless
+                            _ => {}
+                        }
+                    }
                     return Ok(());
                 }
                 Expr::BinaryOp {
@@ -684,6 +776,162 @@ pub fn auto_parametrize_query(query: &mut SelectStatement) -> Vec<(usize, Litera
     visitor.out
 }
 
+/// Returns true if the given expression is a call to a function which returns the current date and
+/// time
+fn is_now_call(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Call(FunctionExpr::Call { name, arguments })
+            if arguments.is_empty()
+                && ["now", "current_timestamp", "localtimestamp", "localtime"]
+                    .iter()
+                    .any(|f| name.eq_ignore_ascii_case(f))
+    )
+}
+
+/// Returns true if the given expression can be evaluated given only the current time, and depends
+/// on it
+fn is_now_relative(expr: &Expr) -> bool {
+    let mut contains_now = false;
+    for subexpr in iter::once(expr).chain(expr.recursive_subexpressions()) {
+        match subexpr {
+            _ if is_now_call(subexpr) => contains_now = true,
+            Expr::Column(_)
+            | Expr::Literal(Literal::Placeholder(_))
+            | Expr::Exists(_)
+            | Expr::NestedSelect(_)
+            | Expr::In {
+                rhs: InValue::Subquery(_),
+                ..
+            }
+            | Expr::Variable(_) => return false,
+            Expr::Call(f) if is_aggregate(f) => return false,
+            _ => {}
+        }
+    }
+    contains_now
+}
+
+/// Evaluate the given [now-relative](is_now_relative) expression, as of the given time
+fn eval_now_relative(expr: &Expr, now: NaiveDateTime) -> ReadySetResult<Literal> {
+    struct ReplaceNowVisitor(Literal);
+
+    impl<'ast> VisitorMut<'ast> for ReplaceNowVisitor {
+        type Error = !;
+
+        fn visit_expr(&mut self, expr: &'ast mut Expr) -> Result<(), Self::Error> {
+            if is_now_call(expr) {
+                *expr = Expr::Literal(self.0.clone());
+                Ok(())
+            } else {
+                visit_mut::walk_expr(self, expr)
+            }
+        }
+    }
+
+    let mut expr = expr.clone();
+    let Ok(()) = ReplaceNowVisitor(Literal::String(
+        now.format("%Y-%m-%d %H:%M:%S%.6f").to_string(),
+    ))
+    .visit_expr(&mut expr);
+    const_eval(&expr, Dialect::DEFAULT_MYSQL)
+}
+
+#[derive(Default)]
+struct NowRelativeConditionsVisitor {
+    out: Vec<(usize, Expr)>,
+    in_supported_position: bool,
+    param_index: usize,
+    query_depth: u8,
+}
+
+impl<'ast> VisitorMut<'ast> for NowRelativeConditionsVisitor {
+    type Error = !;
+
+    fn visit_literal(&mut self, literal: &'ast mut Literal) -> Result<(), Self::Error> {
+        if matches!(literal, Literal::Placeholder(_)) {
+            self.param_index += 1;
+        }
+        Ok(())
+    }
+
+    fn visit_select_statement(
+        &mut self,
+        select_statement: &'ast mut SelectStatement,
+    ) -> Result<(), Self::Error> {
+        self.query_depth = self.query_depth.saturating_add(1);
+        visit_mut::walk_select_statement(self, select_statement)?;
+        self.query_depth = self.query_depth.saturating_sub(1);
+        Ok(())
+    }
+
+    fn visit_where_clause(&mut self, expression: &'ast mut Expr) -> Result<(), Self::Error> {
+        // As with auto-parametrization, we can only support parameters in the WHERE clause of the
+        // top-level query
+        self.in_supported_position = self.query_depth <= 1;
+        self.visit_expr(expression)?;
+        self.in_supported_position = false;
+        Ok(())
+    }
+
+    fn visit_expr(&mut self, expression: &'ast mut Expr) -> Result<(), Self::Error> {
+        let was_supported = self.in_supported_position;
+        if was_supported {
+            match expression {
+                Expr::BinaryOp {
+                    lhs: box Expr::Column(_),
+                    op,
+                    rhs,
+                } if op.is_ordering_comparison() && is_now_relative(rhs) => {
+                    let value = mem::replace(
+                        rhs.as_mut(),
+                        Expr::Literal(Literal::Placeholder(ItemPlaceholder::QuestionMark)),
+                    );
+                    self.out.push((self.param_index, value));
+                    self.param_index += 1;
+                    return Ok(());
+                }
+                Expr::BinaryOp {
+                    lhs,
+                    op,
+                    rhs: rhs @ box Expr::Column(_),
+                } if op.is_ordering_comparison() && is_now_relative(lhs) => {
+                    // for `NOW() > col`, flip the comparison first then revisit
+                    mem::swap(lhs, rhs);
+                    *op = op.flip_ordering_comparison().unwrap_or(*op);
+                    return self.visit_expr(expression);
+                }
+                Expr::BinaryOp {
+                    lhs,
+                    op: BinaryOperator::And,
+                    rhs,
+                } => {
+                    self.visit_expr(lhs.as_mut())?;
+                    self.in_supported_position = true;
+                    self.visit_expr(rhs.as_mut())?;
+                    self.in_supported_position = true;
+                    return Ok(());
+                }
+                _ => self.in_supported_position = false,
+            }
+        }
+
+        visit_mut::walk_expr(self, expression)?;
+        self.in_supported_position = was_supported;
+        Ok(())
+    }
+}
+
+/// Replace the right-hand side of all range conditions which compare a column to a value relative
+/// to the current time, in positions we support parameters in the given query, with parameters.
+/// Returns the replaced expressions, alongside the index in the parameter list where they appear,
+/// to be [evaluated](eval_now_relative) each time the query is executed.
+fn parametrize_now_relative_conditions(query: &mut SelectStatement) -> Vec<(usize, Expr)> {
+    let mut visitor = NowRelativeConditionsVisitor::default();
+    let Ok(()) = visitor.visit_select_statement(query);
+    visitor.out
+}
+
 /// Splice the given list of extracted parameters, which should be a tuple of (placeholder position,
 /// value) as returned by [`auto_parametrize_query`] into the given list of parameters supplied by
 /// the user, by interleaving them into the params based on the placeholder position.
@@ -1122,6 +1370,15 @@ mod tests {
             );
         }
 
+        #[test]
+        fn literals_and_placeholders_in_in_rhs() {
+            test_auto_parametrize(
+                "SELECT id FROM users WHERE id IN (1, ?, 3) AND name = ?",
+                "SELECT id FROM users WHERE id IN (?, ?, ?) AND name = ?",
+                vec![(0, 1_u32.into()), (2, 3_u32.into())],
+            );
+        }
+
         #[test]
         fn mixed_in_with_equality() {
             test_auto_parametrize(
@@ -1182,6 +1439,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn eval_now_relative_as_of() {
+        let now = chrono::NaiveDate::from_ymd(2022, 3, 1).and_hms(12, 0, 0);
+        let eval = |expr: &str| {
+            eval_now_relative(&nom_sql::parse_expr(Dialect::MySQL, expr).unwrap(), now).unwrap()
+        };
+
+        assert_eq!(
+            eval("NOW() - INTERVAL 1 DAY"),
+            Literal::from("2022-02-28 12:00:00")
+        );
+        assert_eq!(
+            eval("date_add(current_timestamp, INTERVAL 90 MINUTE)"),
+            Literal::from("2022-03-01 13:30:00")
+        );
+    }
+
     mod splice_auto_parameters {
         use super::*;
 
@@ -1255,6 +1529,32 @@ mod tests {
                 query,
             )
         }
+        #[test]
+        fn full_text_search_is_unsupported() {
+            for (dialect, query) in [
+                (
+                    nom_sql::Dialect::MySQL,
+                    "SELECT id FROM posts WHERE author_id = 1 AND MATCH (body) AGAINST ('x')",
+                ),
+                (
+                    nom_sql::Dialect::PostgreSQL,
+                    "SELECT id FROM posts WHERE to_tsvector(body) @@ to_tsquery('x')",
+                ),
+                (
+                    nom_sql::Dialect::PostgreSQL,
+                    "SELECT ts_rank(v, q) FROM posts WHERE author_id = $1",
+                ),
+            ] {
+                let mut query = nom_sql::parse_select_statement(dialect, query).unwrap();
+                let err = process_query(&mut query, false).unwrap_err();
+                assert!(err.caused_by_unsupported(), "{err}");
+            }
+
+            // The same query without the full-text predicate is still supported
+            let mut query = parse_select_statement("SELECT id FROM posts WHERE author_id = 1");
+            process_query(&mut query, false).unwrap();
+        }
+
         #[test]
         fn rewrite_literals() {
             let mut query = parse_select_statement(
@@ -1301,6 +1601,73 @@ mod tests {
             assert_eq!(keys, vec![vec![4.into()]]);
         }
 
+        #[test]
+        fn now_relative_condition() {
+            let (keys, query) = process_and_make_keys(
+                "SELECT x FROM test WHERE y = ? AND NOW() - INTERVAL 1 DAY < created_at",
+                vec![1.into()],
+            );
+
+            assert_eq!(
+                query,
+                parse_select_statement("SELECT x FROM test WHERE y = $1 AND created_at > $2")
+            );
+            let [key] = keys.as_slice() else {
+                panic!("Expected exactly one key, got {keys:?}");
+            };
+            assert_eq!(key[0], 1.into());
+            let yesterday = NaiveDateTime::parse_from_str(
+                <&str>::try_from(&key[1]).unwrap(),
+                "%Y-%m-%d %H:%M:%S%.f",
+            )
+            .unwrap();
+            let elapsed = Local::now().naive_local() - yesterday;
+            assert!(
+                (chrono::Duration::hours(23)..=chrono::Duration::hours(25)).contains(&elapsed),
+                "{elapsed}"
+            );
+        }
+
+        #[test]
+        fn now_in_equality_is_not_parametrized() {
+            let mut query = parse_select_statement("SELECT x FROM test WHERE created_at = NOW()");
+            let processed = process_query(&mut query, false).unwrap();
+            assert_eq!(
+                query,
+                parse_select_statement("SELECT x FROM test WHERE created_at = NOW()")
+            );
+            assert!(processed.auto_parameters.is_empty());
+        }
+
+        #[test]
+        fn in_with_literals_and_placeholders() {
+            let (keys, query) = process_and_make_keys(
+                "SELECT x FROM test WHERE x IN (?, 2, ?)",
+                vec![1.into(), 3.into()],
+            );
+
+            assert_eq!(
+                query,
+                parse_select_statement("SELECT x FROM test WHERE x = $1")
+            );
+            assert_eq!(keys, vec![vec![1.into()], vec![2.into()], vec![3.into()]]);
+        }
+
+        #[test]
+        fn in_arities_share_query() {
+            let (_, two) = process_and_make_keys(
+                "SELECT x FROM test WHERE x IN (?, ?) AND y = 1",
+                vec![1.into(), 2.into()],
+            );
+            let (keys, five) = process_and_make_keys(
+                "SELECT x FROM test WHERE x IN (?, 2, ?, 4, 5) AND y = 1",
+                vec![1.into(), 3.into()],
+            );
+
+            assert_eq!(two, five);
+            assert_eq!(keys.len(), 5);
+        }
+
         #[test]
         fn number_autoparam_number() {
             let (keys, query) = process_and_make_keys(
@@ -1524,6 +1891,38 @@ mod tests {
             assert_eq!(keys, vec![vec![1.into()]]);
         }
 
+        #[test]
+        fn parameterized_limit_with_server_pagination() {
+            let mut query =
+                parse_select_statement("SELECT * FROM t WHERE x = ? ORDER BY y LIMIT ? OFFSET ?");
+            let processed = process_query(&mut query, true).unwrap();
+            let params = vec![1.into(), 10.into(), 20.into()];
+
+            // The limit clause is kept, but its parameters are passed to the reader rather than
+            // being part of the key
+            assert_eq!(
+                query,
+                parse_select_statement(
+                    "SELECT * FROM t WHERE x = $1 ORDER BY y LIMIT $2 OFFSET $3"
+                ),
+                "{}",
+                query.display(nom_sql::Dialect::MySQL)
+            );
+            assert_eq!(
+                processed.limit_offset_params(&params).unwrap(),
+                (Some(10), Some(20))
+            );
+            assert_eq!(
+                processed
+                    .make_keys(&params)
+                    .unwrap()
+                    .into_iter()
+                    .map(|c| c.to_vec())
+                    .collect::<Vec<_>>(),
+                vec![vec![DfValue::from(1)]]
+            );
+        }
+
         #[test]
         fn correct_offset_limit() {
             let get_lim_off = |q: &str, p: &[DfValue]| -> (Option<usize>, Option<usize>) {
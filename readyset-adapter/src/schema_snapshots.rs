@@ -0,0 +1,274 @@
+//! Periodic snapshots of the upstream database's schema.
+//!
+//! When the adapter is left running for a long time to analyze which of an application's queries
+//! ReadySet supports, the schema of the upstream database may change partway through. To make it
+//! possible to correlate unsupported queries with those changes, the [`SchemaSnapshotter`]
+//! periodically dumps the upstream database's schema and records a new [`SchemaSnapshot`] in the
+//! shared [`SchemaSnapshots`] whenever the dump differs from the previous one. Every snapshot
+//! after the first is stored along with a line-based diff against its predecessor, so the history
+//! can be inspected as a sequence of incremental changes.
+//!
+//! If `--anonymize-identifiers` is set, each dump is anonymized before it's recorded: every table
+//! and view definition is re-rendered with its identifiers replaced by [hashes of those
+//! identifiers](Anonymizer::with_hashed_identifiers) and its literals removed, and every other
+//! statement in the dump is dropped. Since the same identifier always hashes to the same token,
+//! anonymized schemas can still be correlated with the (identically anonymized) queries listed by
+//! the adapter's HTTP API.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use nom_sql::{Dialect, SqlQuery};
+use parking_lot::RwLock;
+use readyset_sql_passes::anonymize::Anonymizer;
+use readyset_util::shutdown::ShutdownReceiver;
+use serde::Serialize;
+use tokio::select;
+use tracing::{debug, info, warn};
+
+use crate::{UpstreamConfig, UpstreamDatabase};
+
+/// A single snapshot of the upstream database's schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemaSnapshot {
+    /// When the schema was dumped
+    pub taken_at: DateTime<Utc>,
+    /// The full schema dump
+    pub schema: String,
+    /// The lines removed from (prefixed with `-`) and added to (prefixed with `+`) the previous
+    /// snapshot's schema, or `None` for the first snapshot
+    pub diff: Option<String>,
+}
+
+/// A change to the upstream database's schema, as observed between two subsequent
+/// [`SchemaSnapshot`]s
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemaChange {
+    /// When the snapshot including the change was taken
+    pub taken_at: DateTime<Utc>,
+    /// The lines removed from (prefixed with `-`) and added to (prefixed with `+`) the schema
+    pub diff: String,
+}
+
+/// The history of snapshots of the upstream database's schema, shared between the
+/// [`SchemaSnapshotter`] and the adapter's HTTP API
+#[derive(Debug, Default)]
+pub struct SchemaSnapshots {
+    snapshots: RwLock<Vec<SchemaSnapshot>>,
+}
+
+impl SchemaSnapshots {
+    /// Record a dump of the schema taken at `taken_at`, if it differs from the most recent
+    /// snapshot. Returns `true` if a new snapshot was recorded.
+    pub fn record(&self, taken_at: DateTime<Utc>, schema: String) -> bool {
+        let mut snapshots = self.snapshots.write();
+        let diff = match snapshots.last() {
+            Some(last) if last.schema == schema => return false,
+            Some(last) => Some(diff_schemas(&last.schema, &schema)),
+            None => None,
+        };
+        snapshots.push(SchemaSnapshot {
+            taken_at,
+            schema,
+            diff,
+        });
+        true
+    }
+
+    /// Returns all recorded snapshots, oldest first
+    pub fn snapshots(&self) -> Vec<SchemaSnapshot> {
+        self.snapshots.read().clone()
+    }
+
+    /// Returns the changes to the schema between each pair of subsequent snapshots, oldest first
+    pub fn changes(&self) -> Vec<SchemaChange> {
+        self.snapshots
+            .read()
+            .iter()
+            .filter_map(|snapshot| {
+                Some(SchemaChange {
+                    taken_at: snapshot.taken_at,
+                    diff: snapshot.diff.clone()?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Returns the lines removed from `old` and added in `new`, prefixed with `-` and `+`
+/// respectively
+fn diff_schemas(old: &str, new: &str) -> String {
+    let mut res = String::new();
+    for line in diff::lines(old, new) {
+        let (prefix, line) = match line {
+            diff::Result::Left(l) => ('-', l),
+            diff::Result::Right(r) => ('+', r),
+            diff::Result::Both(..) => continue,
+        };
+        res.push(prefix);
+        res.push_str(line);
+        res.push('\n');
+    }
+    res
+}
+
+/// Anonymize the identifiers in the table and view definitions in the given schema dump, dropping
+/// all other statements (which may also contain identifiers, but aren't needed to reconstruct the
+/// structure of the schema).
+pub fn anonymize_schema(dump: &str, dialect: Dialect) -> String {
+    let mut anonymizer = Anonymizer::with_hashed_identifiers();
+    let mut res = String::new();
+    for stmt in dump.split(";\n") {
+        // Strip out comments, which `pg_dump` emits between statements
+        let stmt = stmt
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("--"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if stmt.trim().is_empty() {
+            continue;
+        }
+
+        let anonymized = match nom_sql::parse_query(dialect, &stmt) {
+            Ok(SqlQuery::CreateTable(mut create_table)) => {
+                anonymizer.anonymize_create_table(&mut create_table);
+                create_table.display(dialect).to_string()
+            }
+            Ok(SqlQuery::CreateView(mut create_view)) => {
+                anonymizer.anonymize_create_view(&mut create_view);
+                create_view.display(dialect).to_string()
+            }
+            _ => continue,
+        };
+        res.push_str(&anonymized);
+        res.push_str(";\n");
+    }
+    res
+}
+
+/// Periodically dumps the schema of the upstream database, recording it in a [`SchemaSnapshots`]
+/// if it's changed
+pub struct SchemaSnapshotter<DB: UpstreamDatabase> {
+    upstream_config: UpstreamConfig,
+    /// Connection to the upstream database, re-established if an error is encountered
+    upstream: Option<DB>,
+    snapshots: Arc<SchemaSnapshots>,
+    /// The interval between subsequent dumps of the upstream database's schema
+    interval: Duration,
+    /// Whether to [anonymize](anonymize_schema) each dump before recording it
+    anonymize_identifiers: bool,
+    /// Receiver to return the shutdown signal on
+    shutdown_recv: ShutdownReceiver,
+}
+
+impl<DB: UpstreamDatabase> SchemaSnapshotter<DB> {
+    pub fn new(
+        upstream_config: UpstreamConfig,
+        snapshots: Arc<SchemaSnapshots>,
+        interval: Duration,
+        anonymize_identifiers: bool,
+        shutdown_recv: ShutdownReceiver,
+    ) -> Self {
+        Self {
+            upstream_config,
+            upstream: None,
+            snapshots,
+            interval,
+            anonymize_identifiers,
+            shutdown_recv,
+        }
+    }
+
+    pub async fn run(&mut self) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            select! {
+                biased;
+                _ = self.shutdown_recv.recv() => {
+                    info!("Schema snapshotter shutting down after shut down signal received");
+                    break;
+                }
+                _ = interval.tick() => self.snapshot().await,
+            }
+        }
+    }
+
+    async fn snapshot(&mut self) {
+        debug!("Dumping schema of upstream database");
+        let upstream = match &mut self.upstream {
+            Some(upstream) => upstream,
+            None => match DB::connect(self.upstream_config.clone(), None).await {
+                Ok(upstream) => self.upstream.insert(upstream),
+                Err(error) => {
+                    warn!(%error, "Could not connect to upstream database to dump schema");
+                    return;
+                }
+            },
+        };
+
+        match upstream.schema_dump().await {
+            Ok(dump) => {
+                let mut schema = String::from_utf8_lossy(&dump).into_owned();
+                if self.anonymize_identifiers {
+                    schema = anonymize_schema(&schema, DB::sql_dialect());
+                }
+                if self.snapshots.record(Utc::now(), schema) {
+                    info!("Recorded new snapshot of upstream database schema");
+                }
+            }
+            Err(error) => {
+                warn!(%error, "Could not dump schema of upstream database");
+                self.upstream = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_only_changes() {
+        let snapshots = SchemaSnapshots::default();
+        assert!(snapshots.record(Utc::now(), "CREATE TABLE t (x int);\n".into()));
+        assert!(!snapshots.record(Utc::now(), "CREATE TABLE t (x int);\n".into()));
+        assert!(snapshots.record(
+            Utc::now(),
+            "CREATE TABLE t (x int);\nCREATE TABLE u (y int);\n".into()
+        ));
+
+        let all = snapshots.snapshots();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].diff, None);
+        assert_eq!(all[1].diff.as_deref(), Some("+CREATE TABLE u (y int);\n"));
+
+        let changes = snapshots.changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(Some(&changes[0].diff), all[1].diff.as_ref());
+    }
+
+    #[test]
+    fn anonymize_schema_consistently() {
+        let dump = "-- a comment\n\
+                    CREATE TABLE users (id INT, name TEXT);\n\
+                    SET search_path = public;\n\
+                    CREATE TABLE posts (id INT, author INT);\n";
+        let anonymized = anonymize_schema(dump, Dialect::MySQL);
+        assert_eq!(anonymized.lines().count(), 2);
+        assert!(!anonymized.contains("users"));
+        assert!(!anonymized.contains("author"));
+        assert!(!anonymized.contains("search_path"));
+        assert_eq!(anonymized, anonymize_schema(dump, Dialect::MySQL));
+
+        let mut id = nom_sql::SqlIdentifier::from("id");
+        Anonymizer::with_hashed_identifiers().replace(&mut id);
+        assert_eq!(anonymized.matches(id.as_str()).count(), 2);
+    }
+
+    #[test]
+    fn diff_removed_and_added_lines() {
+        assert_eq!(diff_schemas("a\nb\nc", "a\nx\nc"), "-b\n+x\n".to_string());
+    }
+}
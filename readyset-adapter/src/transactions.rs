@@ -0,0 +1,222 @@
+//! Tracking of the explicit transactions open on each connection to the adapter.
+//!
+//! Statements run within an explicit transaction are always proxied to the upstream database, so
+//! a client which opens a transaction and then stops sending statements holds an upstream
+//! connection (along with any locks taken in the transaction) for as long as it remains connected.
+//! Each [`Backend`](crate::Backend) records how long its transactions stay open, and, mirroring
+//! Postgres's `idle_in_transaction_session_timeout`, can be configured to have connections which
+//! stay idle within a transaction for too long terminated, which closes their upstream connection
+//! and so rolls back the transaction.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{future, Future};
+use parking_lot::Mutex;
+use readyset_client_metrics::recorded;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tracing::warn;
+
+struct TransactionState {
+    /// When the currently open transaction started, if any
+    started_at: Mutex<Option<Instant>>,
+    /// When the connection last became idle within the currently open transaction, or `None` if
+    /// there is no open transaction or a statement is running
+    idle_since: watch::Sender<Option<Instant>>,
+    /// Whether the connection is idle outside of a transaction: no statement is running, and there
+    /// is no open transaction
+    idle: watch::Sender<bool>,
+}
+
+impl Drop for TransactionState {
+    fn drop(&mut self) {
+        if let Some(started_at) = self.started_at.get_mut().take() {
+            record_transaction_end(started_at);
+        }
+    }
+}
+
+fn record_transaction_end(started_at: Instant) {
+    metrics::histogram!(
+        recorded::TRANSACTION_DURATION,
+        started_at.elapsed().as_secs_f64()
+    );
+    metrics::decrement_gauge!(recorded::OPEN_TRANSACTIONS, 1.0);
+}
+
+/// Tracks the explicit transaction open on a single connection, if any
+pub(crate) struct TransactionTracker {
+    state: Arc<TransactionState>,
+}
+
+impl Default for TransactionTracker {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(TransactionState {
+                started_at: Mutex::new(None),
+                idle_since: watch::channel(None).0,
+                idle: watch::channel(true).0,
+            }),
+        }
+    }
+}
+
+impl TransactionTracker {
+    /// Record that a transaction was started on the connection
+    pub(crate) fn start_transaction(&self) {
+        let mut started_at = self.state.started_at.lock();
+        if started_at.is_none() {
+            *started_at = Some(Instant::now());
+            metrics::increment_gauge!(recorded::OPEN_TRANSACTIONS, 1.0);
+        }
+    }
+
+    /// Record that the transaction open on the connection, if any, was committed or rolled back
+    pub(crate) fn end_transaction(&self) {
+        if let Some(started_at) = self.state.started_at.lock().take() {
+            record_transaction_end(started_at);
+        }
+    }
+
+    /// Record that a statement started running on the connection. The connection is considered
+    /// busy until the returned guard is dropped, after which it's considered idle within the
+    /// transaction if one is open.
+    pub(crate) fn statement_started(&self) -> StatementGuard {
+        self.state.idle_since.send_replace(None);
+        self.state.idle.send_replace(false);
+        StatementGuard {
+            state: Arc::clone(&self.state),
+        }
+    }
+
+    /// Returns a future which resolves once the connection has been idle within an open
+    /// transaction for longer than `timeout`
+    pub(crate) fn idle_timeout(&self, timeout: Duration) -> impl Future<Output = ()> + 'static {
+        let mut idle = self.state.idle_since.subscribe();
+        async move {
+            loop {
+                let idle_since = *idle.borrow_and_update();
+                let deadline = async {
+                    match idle_since {
+                        Some(idle_since) => tokio::time::sleep_until(idle_since + timeout).await,
+                        None => future::pending().await,
+                    }
+                };
+                tokio::select! {
+                    _ = deadline => {
+                        warn!(
+                            ?timeout,
+                            "Connection exceeded maximum time idle in transaction, terminating"
+                        );
+                        metrics::increment_counter!(recorded::IDLE_IN_TRANSACTION_TIMEOUTS);
+                        return;
+                    }
+                    res = idle.changed() => {
+                        // The tracker was dropped along with the connection
+                        if res.is_err() {
+                            return future::pending().await;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns a future which resolves once the connection is idle outside of a transaction, so
+    /// that it can be closed without interrupting a statement or rolling back a transaction
+    pub(crate) fn idle(&self) -> impl Future<Output = ()> + 'static {
+        let mut idle = self.state.idle.subscribe();
+        async move {
+            while !*idle.borrow_and_update() {
+                // The tracker was dropped along with the connection
+                if idle.changed().await.is_err() {
+                    return future::pending().await;
+                }
+            }
+        }
+    }
+}
+
+/// Guard returned by [`TransactionTracker::statement_started`], which marks the connection as
+/// idle within its open transaction (if any) when dropped
+pub(crate) struct StatementGuard {
+    state: Arc<TransactionState>,
+}
+
+impl Drop for StatementGuard {
+    fn drop(&mut self) {
+        let in_transaction = self.state.started_at.lock().is_some();
+        if in_transaction {
+            self.state.idle_since.send_replace(Some(Instant::now()));
+        }
+        self.state.idle.send_replace(!in_transaction);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
+
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    #[tokio::test]
+    async fn idle_timeout_only_outside_statements_in_transactions() {
+        let tracker = TransactionTracker::default();
+        let mut timeout = tracker.idle_timeout(TIMEOUT).boxed();
+
+        // not in a transaction
+        drop(tracker.statement_started());
+        assert!(tokio::time::timeout(TIMEOUT * 2, &mut timeout)
+            .await
+            .is_err());
+
+        // running a statement in a transaction
+        let statement = tracker.statement_started();
+        tracker.start_transaction();
+        assert!(tokio::time::timeout(TIMEOUT * 2, &mut timeout)
+            .await
+            .is_err());
+
+        // committed before the timeout elapsed
+        drop(statement);
+        let statement = tracker.statement_started();
+        tracker.end_transaction();
+        drop(statement);
+        assert!(tokio::time::timeout(TIMEOUT * 2, &mut timeout)
+            .await
+            .is_err());
+
+        // idle in a transaction
+        let statement = tracker.statement_started();
+        tracker.start_transaction();
+        drop(statement);
+        tokio::time::timeout(TIMEOUT * 10, &mut timeout)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn idle_outside_statements_and_transactions() {
+        let tracker = TransactionTracker::default();
+        assert!(tracker.idle().now_or_never().is_some());
+
+        // running a statement
+        let statement = tracker.statement_started();
+        let mut idle = tracker.idle().boxed();
+        assert!((&mut idle).now_or_never().is_none());
+
+        // idle in a transaction
+        tracker.start_transaction();
+        drop(statement);
+        assert!((&mut idle).now_or_never().is_none());
+
+        // committed
+        let statement = tracker.statement_started();
+        tracker.end_transaction();
+        drop(statement);
+        tokio::time::timeout(TIMEOUT, idle).await.unwrap();
+    }
+}
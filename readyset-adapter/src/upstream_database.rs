@@ -3,12 +3,15 @@ use std::fmt::Debug;
 
 use async_trait::async_trait;
 pub use database_utils::UpstreamConfig;
+use futures::future::BoxFuture;
 use nom_sql::{SqlIdentifier, StartTransactionStatement};
 use readyset_client_metrics::QueryDestination;
 use readyset_data::DfValue;
 use readyset_errors::ReadySetError;
 
 use crate::fallback_cache::FallbackCache;
+use crate::privileges::SelectPrivileges;
+use crate::upstream_retry::ClassifyError;
 
 /// Information about a statement that has been prepared in an [`UpstreamDatabase`]
 pub struct UpstreamPrepare<DB: UpstreamDatabase> {
@@ -83,7 +86,7 @@ pub trait UpstreamDatabase: Sized + Send {
     /// This type, which must have at least one enum variant that includes a
     /// [`readyset_client::ReadySetError`], is used as the error type for all return values in the
     /// noria_client backend.
-    type Error: From<ReadySetError> + IsFatalError + Error + Send + Sync + 'static;
+    type Error: From<ReadySetError> + IsFatalError + ClassifyError + Error + Send + Sync + 'static;
 
     /// When there's no upstream DB to fetch the version from, default to this value. This features
     /// is only used for tests
@@ -100,6 +103,22 @@ pub trait UpstreamDatabase: Sized + Send {
     /// Resets the connection with the upstream database
     async fn reset(&mut self) -> Result<(), Self::Error>;
 
+    /// Replaces this connection with a new connection to the upstream database at the given URL,
+    /// keeping the rest of the configuration this database was originally constructed with.
+    ///
+    /// This is used to connect to the upstream database with a different set of credentials once
+    /// a client has authenticated as a user with its own upstream credentials.
+    async fn reconnect_to(&mut self, upstream_db_url: String) -> Result<(), Self::Error>;
+
+    /// Returns a future which, when awaited, cancels whatever query is currently executing on this
+    /// connection.
+    ///
+    /// This is used to cancel queries which exceed the maximum query execution time: the future is
+    /// created before the query is executed, and awaited after the future executing the query has
+    /// been dropped, so it must not borrow the connection itself. Awaiting the future when no query
+    /// is executing should have no effect.
+    fn query_canceller(&self) -> BoxFuture<'static, Result<(), Self::Error>>;
+
     /// Returns the SQL dialect for which to format queries.
     fn sql_dialect() -> nom_sql::Dialect;
 
@@ -178,4 +197,8 @@ pub trait UpstreamDatabase: Sized + Send {
     /// supports a multi-element schema search path, the concept of "currently connected database"
     /// in MySQL can be thought of as a schema search path that only has one element
     async fn schema_search_path(&mut self) -> Result<Vec<SqlIdentifier>, Self::Error>;
+
+    /// Query the upstream database's grant tables for the `SELECT` privileges of all of its users,
+    /// for enforcing them on queries served by ReadySet. See [`crate::privileges`].
+    async fn select_privileges(&mut self) -> Result<SelectPrivileges, Self::Error>;
 }
@@ -0,0 +1,147 @@
+//! Classification of errors returned by the upstream database, and retries of statements which
+//! failed with transient errors.
+//!
+//! Each upstream database flavor classifies its own errors (via [`ClassifyError`]) as either
+//! permanent - such as a syntax error or a constraint violation, which will fail again no matter
+//! how many times the statement is retried - or as one of a small set of [`TransientErrorKind`]s,
+//! such as a deadlock, which may well succeed if the statement is simply run again.
+//!
+//! When a statement executed outside of a transaction fails with a transient error, the
+//! protocol-specific backends ask the [`Backend`](crate::Backend) whether the statement should be
+//! retried, which it decides based on the configured [`UpstreamRetryPolicy`]. Statements executed
+//! within a transaction are never retried, since the error may have rolled back the entire
+//! transaction, not just the statement.
+//!
+//! Connection-level errors ([`TransientErrorKind::ConnectionLost`] and
+//! [`TransientErrorKind::ReadOnly`]) indicate that the upstream database may have failed over, so
+//! the connection to the upstream database is re-established before the next statement is executed
+//! (whether or not the failed statement is retried), resolving the upstream database's address
+//! afresh so that statements reach the new writer once its address has been repointed at it.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Duration;
+
+use clap::ValueEnum;
+
+/// A category of error returned by the upstream database which may not occur again if the
+/// statement which caused it is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum TransientErrorKind {
+    /// The statement was chosen as the victim of a deadlock, and was rolled back
+    Deadlock,
+    /// The statement timed out waiting for a lock
+    LockTimeout,
+    /// The statement could not be serialized with concurrent transactions
+    SerializationFailure,
+    /// The connection to the upstream database was lost while executing the statement. The
+    /// statement may or may not have been applied before the connection was lost, so this kind of
+    /// error should only be retried if all statements are idempotent
+    ConnectionLost,
+    /// The upstream database rejected a write because it is read-only, for example because it was
+    /// demoted to a replica during a failover
+    ReadOnly,
+}
+
+impl TransientErrorKind {
+    /// Returns the name of this kind of error, as used in metrics and configuration
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Deadlock => "deadlock",
+            Self::LockTimeout => "lock-timeout",
+            Self::SerializationFailure => "serialization-failure",
+            Self::ConnectionLost => "connection-lost",
+            Self::ReadOnly => "read-only",
+        }
+    }
+}
+
+impl fmt::Display for TransientErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Trait for errors returned by an upstream database which can classify themselves as transient
+/// or permanent
+pub trait ClassifyError {
+    /// Returns the kind of transient error this is, or `None` if this error is permanent
+    fn transient_kind(&self) -> Option<TransientErrorKind>;
+
+    /// Returns true if this error, returned by a statement executed within a transaction, leaves
+    /// the transaction failed in the upstream database, such that every statement other than a
+    /// rollback is rejected until the transaction is ended. By default, errors don't fail the
+    /// transaction they occur in.
+    fn fails_transaction(&self) -> bool {
+        false
+    }
+}
+
+/// Configuration for which statements that fail in the upstream database with a transient error
+/// are retried, and how
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpstreamRetryPolicy {
+    /// The maximum number of times to retry a single statement. A value of 0 disables retries
+    pub max_retries: u32,
+    /// The amount of time to wait before the first retry of a statement. Each subsequent retry of
+    /// the same statement waits twice as long as the previous one
+    pub backoff: Duration,
+    /// The kinds of transient error which should be retried
+    pub retry_kinds: HashSet<TransientErrorKind>,
+}
+
+impl Default for UpstreamRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            backoff: Duration::from_millis(10),
+            retry_kinds: HashSet::from([
+                TransientErrorKind::Deadlock,
+                TransientErrorKind::LockTimeout,
+                TransientErrorKind::SerializationFailure,
+            ]),
+        }
+    }
+}
+
+impl UpstreamRetryPolicy {
+    /// Returns how long to wait before retrying a statement which failed with an error of the
+    /// given `kind`, having already been retried `attempt` times, or `None` if the statement should
+    /// not be retried
+    pub fn backoff(&self, kind: TransientErrorKind, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_retries || !self.retry_kinds.contains(&kind) {
+            return None;
+        }
+        Some(self.backoff.saturating_mul(1 << attempt.min(16)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_policy_retries_statement_errors() {
+        let policy = UpstreamRetryPolicy::default();
+        assert_eq!(
+            policy.backoff(TransientErrorKind::Deadlock, 0),
+            Some(Duration::from_millis(10))
+        );
+        assert_eq!(
+            policy.backoff(TransientErrorKind::SerializationFailure, 2),
+            Some(Duration::from_millis(40))
+        );
+        assert_eq!(policy.backoff(TransientErrorKind::Deadlock, 3), None);
+        assert_eq!(policy.backoff(TransientErrorKind::ConnectionLost, 0), None);
+        assert_eq!(policy.backoff(TransientErrorKind::ReadOnly, 0), None);
+    }
+
+    #[test]
+    fn disabled_policy() {
+        let policy = UpstreamRetryPolicy {
+            max_retries: 0,
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(TransientErrorKind::Deadlock, 0), None);
+    }
+}
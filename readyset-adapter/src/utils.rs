@@ -3,14 +3,17 @@ use std::convert::{TryFrom, TryInto};
 use std::iter;
 
 use nom_sql::analysis::visit::{self, Visitor};
+use nom_sql::analysis::ReferredColumns;
 use nom_sql::{
     BinaryOperator, Column, ColumnConstraint, CreateTableBody, DeleteStatement, Expr,
-    InsertStatement, Literal, SelectStatement, SqlIdentifier, SqlQuery, TableKey, UpdateStatement,
+    FieldDefinitionExpr, FunctionExpr, InsertStatement, Literal, ReferentialAction, Relation,
+    SelectStatement, SqlIdentifier, SqlQuery, TableExpr, TableKey, UpdateStatement,
 };
 use readyset_client::{Modification, Operation};
 use readyset_data::{DfType, DfValue, Dialect};
 use readyset_errors::{
-    bad_request_err, invariant, invariant_eq, unsupported, unsupported_err, ReadySetResult,
+    bad_request_err, invariant, invariant_eq, unsupported, unsupported_err, ReadySetError,
+    ReadySetResult,
 };
 use readyset_util::hash::hash;
 
@@ -301,16 +304,25 @@ pub(crate) fn get_limit_parameters(query: &SelectStatement) -> Vec<Column> {
 
 pub(crate) fn insert_statement_parameter_columns(query: &InsertStatement) -> Vec<&Column> {
     // need to find for which fields we *actually* have a parameter
-    query
-        .data
-        .iter()
-        .flat_map(|d| {
-            d.iter().enumerate().filter_map(|(i, v)| match *v {
-                Expr::Literal(Literal::Placeholder(_)) => Some(&query.fields.as_ref().unwrap()[i]),
-                _ => None,
-            })
+    let data_params = query.data.iter().flat_map(|d| {
+        d.iter().enumerate().filter_map(|(i, v)| match *v {
+            Expr::Literal(Literal::Placeholder(_)) => Some(&query.fields.as_ref().unwrap()[i]),
+            _ => None,
         })
-        .collect()
+    });
+
+    // Parameters in the `ON DUPLICATE KEY UPDATE` clause come after all the parameters in the
+    // inserted rows
+    let on_duplicate_params = query
+        .on_duplicate
+        .iter()
+        .flatten()
+        .filter_map(|(c, v)| match v {
+            Expr::Literal(Literal::Placeholder(_)) => Some(c),
+            _ => None,
+        });
+
+    data_params.chain(on_duplicate_params).collect()
 }
 
 pub(crate) fn update_statement_parameter_columns(query: &UpdateStatement) -> Vec<&Column> {
@@ -391,9 +403,39 @@ where
     Ok(())
 }
 
+/// Extract the modifications to make to each column from the `SET` clause of an UPDATE statement,
+/// or from the `ON DUPLICATE KEY UPDATE` clause of an INSERT statement. In the latter case,
+/// `inserted_row` should be the row that would have been inserted, which is used to resolve
+/// references to `VALUES(<column>)`.
+/// Returns true if the given column refers to the row proposed for insertion by a Postgres
+/// `INSERT ... ON CONFLICT DO UPDATE`, eg `EXCLUDED.value`
+fn is_excluded_column(column: &Column) -> bool {
+    column
+        .table
+        .as_ref()
+        .map_or(false, |t| t.schema.is_none() && t.name == "excluded")
+}
+
+/// Returns the value of the given column in the row that an upsert would have inserted, referred to
+/// with `VALUES(<column>)` in MySQL and `EXCLUDED.<column>` in Postgres
+fn inserted_value(
+    row: &[DfValue],
+    column: &Column,
+    schema: &CreateTableBody,
+) -> ReadySetResult<DfValue> {
+    schema
+        .fields
+        .iter()
+        .position(|f| f.column.name == column.name)
+        .and_then(|idx| row.get(idx))
+        .cloned()
+        .ok_or_else(|| ReadySetError::NoSuchColumn(column.name.to_string()))
+}
+
 pub(crate) fn extract_update_params_and_fields<I>(
     q: &mut UpdateStatement,
     params: &mut Option<I>,
+    inserted_row: Option<&[DfValue]>,
     schema: &CreateTableBody,
     dialect: Dialect,
 ) -> ReadySetResult<Vec<(usize, Modification)>>
@@ -436,7 +478,7 @@ where
                 } => {
                     // we only support "column = column +/- literal"
                     // TODO(ENG-142): Handle nested arithmetic
-                    invariant_eq!(c, &field.column);
+                    invariant_eq!(c.name, field.column.name);
                     match op {
                         BinaryOperator::Add => {
                             updates.push((i, Modification::Apply(Operation::Add, l.try_into()?)))
@@ -447,6 +489,22 @@ where
                         _ => unsupported!(),
                     }
                 }
+                Expr::Call(FunctionExpr::Call {
+                    ref name,
+                    ref arguments,
+                }) if name.eq_ignore_ascii_case("values") => {
+                    let (Some(row), [Expr::Column(c)]) = (inserted_row, arguments.as_slice())
+                    else {
+                        unsupported!("VALUES() is only supported in ON DUPLICATE KEY UPDATE");
+                    };
+                    updates.push((i, Modification::Set(inserted_value(row, c, schema)?)));
+                }
+                Expr::Column(ref c) if is_excluded_column(c) => {
+                    let Some(row) = inserted_row else {
+                        unsupported!("EXCLUDED is only supported in ON CONFLICT DO UPDATE");
+                    };
+                    updates.push((i, Modification::Set(inserted_value(row, c, schema)?)));
+                }
                 _ => unsupported!(),
             }
         }
@@ -454,14 +512,164 @@ where
     Ok(updates)
 }
 
+/// The rows of a table targeted by an UPDATE or DELETE statement
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum MutationTarget {
+    /// The row with the given primary key
+    Key(Vec<DfValue>),
+    /// The rows matching a WHERE clause which refers to columns other than the primary key, whose
+    /// primary keys have to be looked up with [`pkey_lookup_query`] before they can be mutated
+    Lookup {
+        where_clause: Expr,
+        /// The values for the placeholders in the WHERE clause, in order
+        params: Vec<DfValue>,
+    },
+}
+
+/// Returns true if the given WHERE clause of an UPDATE or DELETE statement refers to any columns
+/// other than the primary key of the table with the given schema
+pub(crate) fn where_refers_to_non_pkey_columns(expr: &Expr, schema: &CreateTableBody) -> bool {
+    let pkey = get_primary_key(schema);
+    expr.referred_columns()
+        .any(|c| !pkey.iter().any(|(_, pk)| pk.name == c.name))
+}
+
+/// Build a query projecting the primary key columns of all the rows of `table` matching
+/// `where_clause`, which is used to find the rows targeted by an UPDATE or DELETE statement whose
+/// WHERE clause refers to columns other than the primary key
+pub(crate) fn pkey_lookup_query(
+    table: &Relation,
+    schema: &CreateTableBody,
+    where_clause: Expr,
+) -> SelectStatement {
+    SelectStatement {
+        tables: vec![TableExpr::from(table.clone())],
+        fields: get_primary_key(schema)
+            .into_iter()
+            .map(|(_, c)| FieldDefinitionExpr::Expr {
+                expr: Expr::Column(Column {
+                    name: c.name.clone(),
+                    table: Some(table.clone()),
+                }),
+                alias: None,
+            })
+            .collect(),
+        where_clause: Some(where_clause),
+        ..Default::default()
+    }
+}
+
+/// A foreign key declared with `ON DELETE CASCADE`, whose referencing rows have to be deleted along
+/// with the rows they reference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CascadingForeignKey {
+    /// The referencing table
+    pub(crate) table: Relation,
+    /// The referencing columns
+    columns: Vec<Column>,
+    /// For each referencing column, the position within the referenced table's primary key of the
+    /// column it references
+    pkey_positions: Vec<usize>,
+}
+
+impl CascadingForeignKey {
+    /// Returns a WHERE clause matching the rows of the referencing table which reference the row
+    /// with the given primary key, or `None` if no rows can reference it because part of the
+    /// referenced key is NULL
+    pub(crate) fn referencing_rows(&self, pkey: &[DfValue]) -> ReadySetResult<Option<Expr>> {
+        let mut conds = Vec::with_capacity(self.columns.len());
+        for (column, pos) in self.columns.iter().zip(&self.pkey_positions) {
+            let value = pkey
+                .get(*pos)
+                .ok_or_else(|| bad_request_err!("Primary key has too few columns"))?;
+            if value.is_none() {
+                return Ok(None);
+            }
+            conds.push(Expr::BinaryOp {
+                lhs: Box::new(Expr::Column(Column {
+                    name: column.name.clone(),
+                    table: Some(self.table.clone()),
+                })),
+                op: BinaryOperator::Equal,
+                rhs: Box::new(Expr::Literal(value.clone().try_into()?)),
+            });
+        }
+        Ok(conds.into_iter().reduce(|lhs, rhs| Expr::BinaryOp {
+            lhs: Box::new(lhs),
+            op: BinaryOperator::And,
+            rhs: Box::new(rhs),
+        }))
+    }
+}
+
+/// Returns the foreign keys of `table` which reference the primary key of `referenced` with `ON
+/// DELETE CASCADE`.
+///
+/// Cascading foreign keys which reference columns of `referenced` other than its primary key are
+/// unsupported, since the referenced values can't be determined from the primary keys of the
+/// deleted rows.
+pub(crate) fn cascading_foreign_keys(
+    referenced: &Relation,
+    referenced_schema: &CreateTableBody,
+    table: &Relation,
+    schema: &CreateTableBody,
+) -> ReadySetResult<Vec<CascadingForeignKey>> {
+    let pkey = get_primary_key(referenced_schema);
+    let mut res = vec![];
+    for key in schema.keys.iter().flatten() {
+        let TableKey::ForeignKey {
+            columns,
+            target_table,
+            target_columns,
+            on_delete: Some(ReferentialAction::Cascade),
+            ..
+        } = key
+        else {
+            continue;
+        };
+        // Unqualified references are to tables in the same schema as the referencing table
+        if target_table.name != referenced.name
+            || target_table.schema.as_ref().or(table.schema.as_ref()) != referenced.schema.as_ref()
+        {
+            continue;
+        }
+
+        let pkey_positions = target_columns
+            .iter()
+            .map(|target| pkey.iter().position(|(_, c)| c.name == target.name))
+            .collect::<Option<Vec<_>>>()
+            .filter(|positions| positions.len() == pkey.len() && columns.len() == pkey.len())
+            .ok_or_else(|| {
+                unsupported_err!(
+                    "ON DELETE CASCADE is only supported for foreign keys referencing the primary \
+                     key of {}",
+                    referenced.display_unquoted()
+                )
+            })?;
+        res.push(CascadingForeignKey {
+            table: table.clone(),
+            columns: columns.clone(),
+            pkey_positions,
+        });
+    }
+    Ok(res)
+}
+
 pub(crate) fn extract_pkey_where<I>(
     where_clause: Expr,
     mut params: Option<I>,
     schema: &CreateTableBody,
-) -> ReadySetResult<Vec<DfValue>>
+) -> ReadySetResult<MutationTarget>
 where
     I: Iterator<Item = DfValue>,
 {
+    if where_refers_to_non_pkey_columns(&where_clause, schema) {
+        return Ok(MutationTarget::Lookup {
+            where_clause,
+            params: params.map(Iterator::collect).unwrap_or_default(),
+        });
+    }
+
     let pkey = get_primary_key(schema);
     let mut col_to_val: HashMap<_, _> = HashMap::new();
     walk_pkey_where(&mut col_to_val, &mut params, where_clause)?;
@@ -469,14 +677,15 @@ where
         .map(|&(_, c)| {
             col_to_val.remove(c.name.as_str()).ok_or_else(|| {
                 unsupported_err!(
-                    "UPDATE or DELETE on columns other than the primary key are not supported",
+                    "UPDATE/DELETE requires all columns of a compound key to be present"
                 )
             })
         })
-        .collect()
+        .collect::<ReadySetResult<_>>()
+        .map(MutationTarget::Key)
 }
 
-type ExtractedUpdate = (Vec<DfValue>, Vec<(usize, Modification)>);
+type ExtractedUpdate = (MutationTarget, Vec<(usize, Modification)>);
 
 pub(crate) fn extract_update<I>(
     mut q: UpdateStatement,
@@ -487,19 +696,19 @@ pub(crate) fn extract_update<I>(
 where
     I: Iterator<Item = DfValue>,
 {
-    let updates = extract_update_params_and_fields(&mut q, &mut params, schema, dialect);
+    let updates = extract_update_params_and_fields(&mut q, &mut params, None, schema, dialect);
     let where_clause = q
         .where_clause
         .ok_or_else(|| unsupported_err!("UPDATE without WHERE is not supported"))?;
-    let key = extract_pkey_where(where_clause, params, schema)?;
-    Ok((key, updates?))
+    let target = extract_pkey_where(where_clause, params, schema)?;
+    Ok((target, updates?))
 }
 
 pub(crate) fn extract_delete<I>(
     q: DeleteStatement,
     params: Option<I>,
     schema: &CreateTableBody,
-) -> ReadySetResult<Vec<DfValue>>
+) -> ReadySetResult<MutationTarget>
 where
     I: Iterator<Item = DfValue>,
 {
@@ -867,4 +1076,163 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_insert_on_duplicate_parameter_columns() {
+        let insert = "INSERT INTO t (a, b) VALUES (?, ?) ON DUPLICATE KEY UPDATE b = ?";
+        let insert = nom_sql::parse_query(Dialect::MySQL, insert).unwrap();
+
+        let pc = get_parameter_columns(&insert);
+        assert_eq!(
+            pc.into_iter().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+            vec!["a", "b", "b"]
+        );
+    }
+
+    #[test]
+    fn test_extract_on_duplicate_values() {
+        let schema = get_schema("CREATE TABLE t (id INT PRIMARY KEY, a INT, b INT)");
+        let insert = "INSERT INTO t (id, a, b) VALUES (1, 2, 3) \
+                      ON DUPLICATE KEY UPDATE a = VALUES(b), b = b + 1";
+        let SqlQuery::Insert(insert) = nom_sql::parse_query(Dialect::MySQL, insert).unwrap() else {
+            unreachable!()
+        };
+        let mut update = UpdateStatement {
+            table: insert.table,
+            fields: insert.on_duplicate.unwrap(),
+            where_clause: None,
+        };
+        let row = vec![DfValue::from(1), DfValue::from(2), DfValue::from(3)];
+
+        let updates = extract_update_params_and_fields(
+            &mut update,
+            &mut None::<iter::Empty<DfValue>>,
+            Some(&row),
+            &schema,
+            readyset_data::Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap();
+        assert_eq!(
+            updates,
+            vec![
+                (1, Modification::Set(DfValue::from(3))),
+                (2, Modification::Apply(Operation::Add, DfValue::from(1))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_on_conflict_excluded() {
+        let schema = get_schema("CREATE TABLE t (id INT PRIMARY KEY, a INT, b INT)");
+        let insert = "INSERT INTO t (id, a, b) VALUES (1, 2, 3) \
+                      ON CONFLICT (id) DO UPDATE SET a = excluded.b, b = $1";
+        let SqlQuery::Insert(insert) = nom_sql::parse_query(Dialect::PostgreSQL, insert).unwrap()
+        else {
+            unreachable!()
+        };
+        let mut update = UpdateStatement {
+            table: insert.table,
+            fields: insert.on_duplicate.unwrap(),
+            where_clause: None,
+        };
+        let row = vec![DfValue::from(1), DfValue::from(2), DfValue::from(3)];
+
+        let updates = extract_update_params_and_fields(
+            &mut update,
+            &mut Some(vec![DfValue::from(4)].into_iter()),
+            Some(&row),
+            &schema,
+            readyset_data::Dialect::DEFAULT_POSTGRESQL,
+        )
+        .unwrap();
+        assert_eq!(
+            updates,
+            vec![
+                (1, Modification::Set(DfValue::from(3))),
+                (2, Modification::Set(DfValue::from(4))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_update_non_pkey_where() {
+        let schema = get_schema("CREATE TABLE t (id INT PRIMARY KEY, a INT, b INT)");
+        let SqlQuery::Update(update) =
+            nom_sql::parse_query(Dialect::MySQL, "UPDATE t SET a = ? WHERE b = ? AND id > 1")
+                .unwrap()
+        else {
+            unreachable!()
+        };
+        let where_clause = update.where_clause.clone().unwrap();
+
+        let (target, updates) = extract_update(
+            update,
+            Some(vec![DfValue::from(1), DfValue::from(2)].into_iter()),
+            &schema,
+            readyset_data::Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap();
+        assert_eq!(updates, vec![(1, Modification::Set(DfValue::from(1)))]);
+        assert_eq!(
+            target,
+            MutationTarget::Lookup {
+                where_clause: where_clause.clone(),
+                params: vec![DfValue::from(2)],
+            }
+        );
+
+        let lookup = pkey_lookup_query(&"t".into(), &schema, where_clause);
+        assert_eq!(
+            lookup.display(Dialect::MySQL).to_string(),
+            "SELECT `t`.`id` FROM `t` WHERE ((`b` = ?) AND (`id` > 1))"
+        );
+    }
+
+    #[test]
+    fn test_extract_delete_pkey_where() {
+        let schema = get_schema("CREATE TABLE t (id INT PRIMARY KEY, a INT, b INT)");
+        let SqlQuery::Delete(delete) =
+            nom_sql::parse_query(Dialect::MySQL, "DELETE FROM t WHERE id = 3").unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            extract_delete(delete, None::<iter::Empty<DfValue>>, &schema).unwrap(),
+            MutationTarget::Key(vec![DfValue::from(3)])
+        );
+    }
+
+    #[test]
+    fn test_cascading_foreign_keys() {
+        let parent = get_schema("CREATE TABLE p (a INT, b INT, PRIMARY KEY (a, b))");
+        let child = get_schema(
+            "CREATE TABLE c (id INT PRIMARY KEY, x INT, y INT,
+             FOREIGN KEY (y, x) REFERENCES p (b, a) ON DELETE CASCADE,
+             FOREIGN KEY (x) REFERENCES other (id) ON DELETE CASCADE,
+             FOREIGN KEY (x, y) REFERENCES p (a, b) ON DELETE RESTRICT)",
+        );
+        let fks = cascading_foreign_keys(&"p".into(), &parent, &"c".into(), &child).unwrap();
+        assert_eq!(fks.len(), 1);
+        assert_eq!(
+            fks[0]
+                .referencing_rows(&[DfValue::from(1), DfValue::from(2)])
+                .unwrap()
+                .unwrap()
+                .display(Dialect::MySQL)
+                .to_string(),
+            "((`c`.`y` = 2) AND (`c`.`x` = 1))"
+        );
+        assert_eq!(
+            fks[0]
+                .referencing_rows(&[DfValue::None, DfValue::from(2)])
+                .unwrap(),
+            None
+        );
+
+        let child = get_schema(
+            "CREATE TABLE c (id INT PRIMARY KEY, x INT,
+             FOREIGN KEY (x) REFERENCES p (b) ON DELETE CASCADE)",
+        );
+        cascading_foreign_keys(&"p".into(), &parent, &"c".into(), &child).unwrap_err();
+    }
 }
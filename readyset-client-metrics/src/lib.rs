@@ -44,6 +44,14 @@ pub struct QueryExecutionEvent {
 
     /// Number of cache misses which occurred as part of a query
     pub cache_misses: Option<u64>,
+
+    /// The user the connection the query was executed on is authenticated as, if any
+    pub user: Option<Arc<str>>,
+
+    /// The name of the client application which opened the connection the query was executed on,
+    /// if it was sent by the client (`application_name` in PostgreSQL, the `program_name`
+    /// connection attribute in MySQL)
+    pub application: Option<Arc<str>>,
 }
 
 #[derive(Debug, PartialEq, Eq, Serialize, Clone, Copy, Default)]
@@ -162,6 +170,8 @@ impl QueryExecutionEvent {
             destination: None,
             cache_misses: None,
             num_keys: None,
+            user: None,
+            application: None,
         }
     }
 
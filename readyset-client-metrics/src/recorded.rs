@@ -9,6 +9,8 @@
 /// | database_type | The database type being executed. Must be a [`DatabaseType`] |
 /// | query_type | SqlQueryType, whether the query was a read or write. |
 /// | event_type | EventType, whether the query was a prepare, execute, or query.  |
+/// | user | The authenticated user, if `--query-log-user-labels` is set. |
+/// | application | The connection's application, if `--query-log-application-labels` is set. |
 ///
 /// [`DatabaseType`]: crate::DatabaseType
 pub const QUERY_LOG_EXECUTION_TIME: &str = "readyset_query_log_execution_time";
@@ -21,6 +23,8 @@ pub const QUERY_LOG_EXECUTION_TIME: &str = "readyset_query_log_execution_time";
 /// | query | The query text being executed. |
 /// | query_type | SqlQueryType, whether the query was a read or write. |
 /// | event_type | EventType, whether the query was a prepare, execute, or query.  |
+/// | user | The authenticated user, if `--query-log-user-labels` is set. |
+/// | application | The connection's application, if `--query-log-application-labels` is set. |
 pub const QUERY_LOG_PARSE_TIME: &str = "readyset_query_log_parse_time";
 
 /// Counter: The number of individual keys read for a query. This will be greater than the number of
@@ -29,6 +33,8 @@ pub const QUERY_LOG_PARSE_TIME: &str = "readyset_query_log_parse_time";
 /// | Tag | Description |
 /// | --- | ----------- |
 /// | query | The query text being executed. |
+/// | user | The authenticated user, if `--query-log-user-labels` is set. |
+/// | application | The connection's application, if `--query-log-application-labels` is set. |
 pub const QUERY_LOG_TOTAL_KEYS_READ: &str = "readyset_query_log_total_keys_read";
 
 /// Counter: The number of cache misses which occurred, potentially multiple from a single query.
@@ -36,6 +42,8 @@ pub const QUERY_LOG_TOTAL_KEYS_READ: &str = "readyset_query_log_total_keys_read"
 /// | Tag | Description |
 /// | --- | ----------- |
 /// | query | The query text being executed. |
+/// | user | The authenticated user, if `--query-log-user-labels` is set. |
+/// | application | The connection's application, if `--query-log-application-labels` is set. |
 pub const QUERY_LOG_TOTAL_CACHE_MISSES: &str = "readyset_query_log_total_cache_misses";
 
 /// Counter: The number of queries which encountered at least one cache miss.
@@ -43,8 +51,17 @@ pub const QUERY_LOG_TOTAL_CACHE_MISSES: &str = "readyset_query_log_total_cache_m
 /// | Tag | Description |
 /// | --- | ----------- |
 /// | query | The query text being executed. |
+/// | user | The authenticated user, if `--query-log-user-labels` is set. |
+/// | application | The connection's application, if `--query-log-application-labels` is set. |
 pub const QUERY_LOG_QUERY_CACHE_MISSED: &str = "readyset_query_log_query_cache_missed";
 
+/// Counter: The number of query execution events which were not exported by the query logger,
+/// either because the export queue was full or because the events failed to be sent.
+pub const QUERY_LOG_EVENTS_DROPPED: &str = "readyset_query_log_events_dropped";
+
+/// Counter: The number of query execution events exported by the query logger.
+pub const QUERY_LOG_EVENTS_EXPORTED: &str = "readyset_query_log_events_exported";
+
 /// Counter: The number of successful queries (dry runs/real) processed by the migration handler.
 pub const MIGRATION_HANDLER_SUCCESSES: &str = "readyset_migration_handler_successes";
 
@@ -57,8 +74,93 @@ pub const MIGRATION_HANDLER_FAILURES: &str = "readyset_migration_handler_failure
 /// status in the query status cache. Requires optimization of locking.
 pub const MIGRATION_HANDLER_ALLOWED: &str = "readyset_migration_handler_allowed";
 
+/// Counter: The number of migrations submitted to the migration queue which were deduplicated with
+/// an identical migration that was already in progress.
+pub const MIGRATION_QUEUE_DEDUPLICATED: &str = "readyset_migration_queue_deduplicated";
+
+/// Gauge: The number of migrations submitted to the migration queue which have not yet completed,
+/// including those waiting for other migrations to complete before being run.
+pub const MIGRATION_QUEUE_IN_FLIGHT: &str = "readyset_migration_queue_in_flight";
+
 /// Counter: The number of HTTP requests received at the noria-client.
 pub const ADAPTER_EXTERNAL_REQUESTS: &str = "readyset_noria_client_external_requests";
 
 /// Gauge: The number of currently connected SQL clients
 pub const CONNECTED_CLIENTS: &str = "readyset_noria_client_connected_clients";
+
+/// Counter: The number of queries and prepared statement executions which were cancelled because
+/// they exceeded the configured maximum query execution time.
+pub const QUERY_TIMEOUTS: &str = "readyset_noria_client_query_timeouts";
+
+/// Counter: The number of queries and prepared statement executions which were cancelled by a
+/// request from another connection, such as `KILL QUERY` or a Postgres cancel request.
+pub const QUERIES_CANCELLED: &str = "readyset_noria_client_queries_cancelled";
+
+/// Gauge: The number of explicit transactions currently open on connections to the adapter.
+pub const OPEN_TRANSACTIONS: &str = "readyset_noria_client_open_transactions";
+
+/// Histogram: The time in seconds explicit transactions were open for, from the statement starting
+/// them until they were committed or rolled back, or the connection was closed.
+pub const TRANSACTION_DURATION: &str = "readyset_noria_client_transaction_duration";
+
+/// Counter: The number of connections which were terminated because they were idle within an open
+/// transaction for longer than the configured idle-in-transaction timeout.
+pub const IDLE_IN_TRANSACTION_TIMEOUTS: &str = "readyset_noria_client_idle_in_transaction_timeouts";
+
+/// Histogram: The time in seconds spent executing reads against a cache which is part of a cache
+/// experiment (see `CREATE EXPERIMENT`), or against the candidate cache of the experiment.
+///
+/// | Tag | Description |
+/// | --- | ----------- |
+/// | cache | The name of the existing cache the experiment is being run against. |
+/// | variant | `control` for reads of the existing cache, `candidate` for reads of the candidate. |
+pub const EXPERIMENT_READ_TIME: &str = "readyset_noria_client_experiment_read_time";
+
+/// Counter: The number of reads routed to the candidate cache of a cache experiment which failed,
+/// and were retried against the existing cache.
+///
+/// | Tag | Description |
+/// | --- | ----------- |
+/// | cache | The name of the existing cache the experiment is being run against. |
+pub const EXPERIMENT_CANDIDATE_ERRORS: &str = "readyset_noria_client_experiment_candidate_errors";
+
+/// Counter: The number of reads routed to the candidate cache of a cache experiment which returned
+/// different results than the existing cache. Only recorded for experiments created with
+/// `COMPARE RESULTS`.
+///
+/// | Tag | Description |
+/// | --- | ----------- |
+/// | cache | The name of the existing cache the experiment is being run against. |
+pub const EXPERIMENT_RESULT_MISMATCHES: &str = "readyset_noria_client_experiment_result_mismatches";
+
+/// Counter: The number of statements which failed in the upstream database with a transient error,
+/// and were retried.
+///
+/// | Tag | Description |
+/// | --- | ----------- |
+/// | kind | The kind of transient error the statement failed with, such as `deadlock`. |
+pub const UPSTREAM_RETRIES: &str = "readyset_noria_client_upstream_retries";
+
+/// Counter: The number of statements which failed in the upstream database with a transient error,
+/// and were not retried, either because they were executed within a transaction, because the
+/// retry policy doesn't retry that kind of error, or because they had already been retried the
+/// maximum number of times.
+///
+/// | Tag | Description |
+/// | --- | ----------- |
+/// | kind | The kind of transient error the statement failed with, such as `deadlock`. |
+pub const UPSTREAM_TRANSIENT_ERRORS_NOT_RETRIED: &str =
+    "readyset_noria_client_upstream_transient_errors_not_retried";
+
+/// Counter: The number of times a connection to the upstream database was re-established after a
+/// statement failed with an error indicating that the upstream database may have failed over.
+pub const UPSTREAM_RECONNECTS: &str = "readyset_noria_client_upstream_reconnects";
+
+/// Counter: The number of writes which succeeded in the upstream database and were also applied
+/// directly to ReadySet's base tables, with write-through enabled.
+pub const WRITES_WRITTEN_THROUGH: &str = "readyset_noria_client_writes_written_through";
+
+/// Counter: The number of writes which succeeded in the upstream database, with write-through
+/// enabled, but could not be applied directly to ReadySet's base tables, and so will only be
+/// visible once they arrive via replication.
+pub const WRITES_NOT_WRITTEN_THROUGH: &str = "readyset_noria_client_writes_not_written_through";
@@ -1,7 +1,14 @@
 //! Helpers for writing integration tests against adapters that use noria-client
+//!
+//! [`TestBuilder`] starts a full ReadySet deployment - a controller and worker backed by a
+//! [`LocalAuthority`], and an adapter - inside the test process, listening on ephemeral ports. As
+//! long as no fallback database is configured, writes go directly to ReadySet's base tables, so
+//! end-to-end tests (adapter protocol -> dataflow -> reader) can run with `cargo test`, without
+//! an upstream database or any other external services.
 
 use std::collections::HashMap;
 use std::env;
+use std::net::SocketAddr;
 use std::str::FromStr;
 use std::sync::atomic::AtomicUsize;
 use std::sync::{Arc, RwLock};
@@ -9,15 +16,22 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use database_utils::DatabaseURL;
+use health_reporter::{HealthReporter, State};
 use nom_sql::Relation;
 use readyset_adapter::backend::noria_connector::{NoriaConnector, ReadBehavior};
 use readyset_adapter::backend::{BackendBuilder, MigrationMode};
+use readyset_adapter::http_router::NoriaAdapterHttpRouter;
+use readyset_adapter::query_pins::QueryPinLists;
 use readyset_adapter::query_status_cache::QueryStatusCache;
+use readyset_adapter::schema_snapshots::SchemaSnapshots;
 use readyset_adapter::{Backend, QueryHandler, UpstreamConfig, UpstreamDatabase};
 use readyset_client::consensus::{Authority, LocalAuthorityStore};
 use readyset_client::ViewCreateRequest;
-use readyset_server::{Builder, Handle, LocalAuthority, ReadySetHandle};
+use readyset_server::{
+    Builder, DurabilityMode, Handle, LocalAuthority, PersistenceParameters, ReadySetHandle,
+};
 use readyset_util::shutdown::ShutdownSender;
+use tempfile::TempDir;
 use tokio::net::{TcpListener, TcpStream};
 
 #[cfg(feature = "mysql")]
@@ -78,6 +92,24 @@ pub struct TestBuilder {
     migration_mode: MigrationMode,
     recreate_database: bool,
     query_status_cache: Option<&'static QueryStatusCache>,
+    durable_base_tables: bool,
+    http_router: bool,
+}
+
+/// A full ReadySet deployment running inside the test process, started by
+/// [`TestBuilder::build_deployment`]
+pub struct TestDeployment<A: Adapter> {
+    /// Options for connecting to the adapter
+    pub connection_opts: A::ConnectionOpts,
+    /// The address the adapter is listening on for client connections
+    pub adapter_addr: SocketAddr,
+    /// The address the adapter is serving its HTTP endpoints on, if enabled with
+    /// [`TestBuilder::http_router`]
+    pub http_addr: Option<SocketAddr>,
+    /// A handle to the ReadySet server
+    pub handle: Handle,
+    /// Shuts down the whole deployment, and removes any temporary directories created for it
+    pub shutdown_tx: ShutdownSender,
 }
 
 impl Default for TestBuilder {
@@ -97,6 +129,8 @@ impl TestBuilder {
             migration_mode: MigrationMode::InRequestPath,
             recreate_database: true,
             query_status_cache: None,
+            durable_base_tables: false,
+            http_router: false,
         }
     }
 
@@ -144,7 +178,33 @@ impl TestBuilder {
         self
     }
 
+    /// Persist base tables to disk, in a temporary directory which is removed when the deployment
+    /// is shut down, rather than keeping them in memory
+    pub fn durable_base_tables(mut self, durable_base_tables: bool) -> Self {
+        self.durable_base_tables = durable_base_tables;
+        self
+    }
+
+    /// Serve the adapter's HTTP endpoints on an ephemeral port, which is returned in
+    /// [`TestDeployment::http_addr`]
+    pub fn http_router(mut self, http_router: bool) -> Self {
+        self.http_router = http_router;
+        self
+    }
+
     pub async fn build<A>(self) -> (A::ConnectionOpts, Handle, ShutdownSender)
+    where
+        A: Adapter + 'static,
+    {
+        let deployment = self.build_deployment::<A>().await;
+        (
+            deployment.connection_opts,
+            deployment.handle,
+            deployment.shutdown_tx,
+        )
+    }
+
+    pub async fn build_deployment<A>(self) -> TestDeployment<A>
     where
         A: Adapter + 'static,
     {
@@ -192,6 +252,18 @@ impl TestBuilder {
             builder.disable_partial();
         }
 
+        let storage_dir = if self.durable_base_tables {
+            let dir = TempDir::new().unwrap();
+            builder.set_persistence(PersistenceParameters {
+                mode: DurabilityMode::Permanent,
+                db_dir: Some(dir.path().into()),
+                ..Default::default()
+            });
+            Some(dir)
+        } else {
+            None
+        };
+
         if let Some((f, _)) = &fallback_url_and_db_name {
             builder.set_replication_url(f.clone());
         }
@@ -200,6 +272,42 @@ impl TestBuilder {
             handle.backend_ready().await;
         }
 
+        let http_addr = if self.http_router {
+            let mut rh = ReadySetHandle::new(authority.clone()).await;
+            let server_supports_pagination = rh.supports_pagination().await.unwrap();
+            let mut health_reporter = HealthReporter::new();
+            health_reporter.set_state(State::Healthy);
+            let router = NoriaAdapterHttpRouter {
+                listen_addr: "127.0.0.1:0".parse().unwrap(),
+                query_cache: query_status_cache,
+                health_reporter,
+                failpoint_channel: None,
+                prometheus_handle: None,
+                enable_pprof: false,
+                query_pins: Arc::new(QueryPinLists::new(
+                    None,
+                    None,
+                    A::DIALECT,
+                    server_supports_pagination,
+                    query_status_cache,
+                )),
+                controller: rh,
+                schema_snapshots: Arc::new(SchemaSnapshots::default()),
+                anonymize_identifiers: false,
+                expr_dialect: A::EXPR_DIALECT,
+            };
+            let http_listener = router.create_listener().await.unwrap();
+            let http_addr = http_listener.local_addr().unwrap();
+            tokio::spawn(NoriaAdapterHttpRouter::route_requests(
+                router,
+                http_listener,
+                shutdown_tx.subscribe(),
+            ));
+            Some(http_addr)
+        } else {
+            None
+        };
+
         let auto_increments: Arc<RwLock<HashMap<Relation, AtomicUsize>>> = Arc::default();
         let query_cache: Arc<RwLock<HashMap<ViewCreateRequest, Relation>>> = Arc::default();
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -208,6 +316,8 @@ impl TestBuilder {
         let mut backend_shutdown_rx = shutdown_tx.subscribe();
         let fallback_url = fallback_url_and_db_name.as_ref().map(|(f, _)| f.clone());
         tokio::spawn(async move {
+            // Keep the storage directory around until the deployment is shut down
+            let _storage_dir = storage_dir;
             let backend_shutdown_rx_connection = backend_shutdown_rx.clone();
             let connection_fut = async move {
                 loop {
@@ -265,13 +375,15 @@ impl TestBuilder {
             }
         });
 
-        (
-            A::connection_opts_with_port(
+        TestDeployment {
+            connection_opts: A::connection_opts_with_port(
                 fallback_url_and_db_name.as_ref().map(|(_, db)| db.as_str()),
                 addr.port(),
             ),
+            adapter_addr: addr,
+            http_addr,
             handle,
             shutdown_tx,
-        )
+        }
     }
 }
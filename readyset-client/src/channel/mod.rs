@@ -11,14 +11,16 @@ use std::pin::Pin;
 use std::sync::RwLock;
 use std::task::{Context, Poll};
 
-use async_bincode::{AsyncBincodeWriter, AsyncDestination};
+use async_bincode::AsyncBincodeWriter;
 use futures_util::sink::{Sink, SinkExt};
-use tokio::io::BufWriter;
+use futures_util::TryFutureExt;
+use tokio::io::{AsyncWriteExt, BufWriter};
 
 pub mod tcp;
 
 pub use self::tcp::{DualTcpStream, TcpSender};
 use crate::internal::ReplicaAddress;
+use crate::tls::MaybeTlsStream;
 use crate::{ReadySetError, ReadySetResult};
 
 pub const CONNECTION_FROM_BASE: u8 = 1;
@@ -86,35 +88,48 @@ impl<T> DomainConnectionBuilder<Remote, T>
 where
     T: serde::Serialize,
 {
+    fn connection_tag(&self) -> u8 {
+        if self.is_for_base {
+            CONNECTION_FROM_BASE
+        } else {
+            CONNECTION_FROM_DOMAIN
+        }
+    }
+
     /// Establishes a TCP sink for an asynchronous context. The function may block for a long
     /// time while the connection is being established, be careful not to call it on our main Tokio
     /// executer, but only from inside a Domain thread.
-    pub fn build_async(
-        self,
-    ) -> io::Result<AsyncBincodeWriter<BufWriter<tokio::net::TcpStream>, T, AsyncDestination>> {
-        // TODO: async
-        // we must currently write and call flush, because the remote end (currently) does a
-        // synchronous read upon accepting a connection.
-        let s = self.build_sync()?.into_inner().into_inner()?;
-
-        tokio::net::TcpStream::from_std(s)
-            .map(BufWriter::new)
-            .map(AsyncBincodeWriter::from)
-            .map(AsyncBincodeWriter::for_async)
+    ///
+    /// If internal TLS is enabled, the TLS handshake is performed when the sink is first used.
+    pub fn build_async(self) -> io::Result<Box<dyn Sink<T, Error = bincode::Error> + Send + Unpin>>
+    where
+        T: Send + 'static,
+    {
+        let tag = self.connection_tag();
+        let s = tcp::connect_socket(self.sport, &self.addr)?;
+        s.set_nonblocking(true)?;
+        let s = tokio::net::TcpStream::from_std(s)?;
+
+        let connect = async move {
+            let mut s = MaybeTlsStream::connect(s).await?;
+            s.write_all(&[tag]).await?;
+            s.flush().await?;
+            Ok::<_, bincode::Error>(
+                AsyncBincodeWriter::<_, T, _>::from(BufWriter::new(s)).for_async(),
+            )
+        };
+        Ok(Box::new(Box::pin(connect).flatten_sink()))
     }
 
     /// Establishes a TCP sink for a synchronous context. The function may block for a long
     /// time while the connection is being established, be careful not to call it on our main Tokio
     /// executer, but only from inside a Domain thread.
     pub fn build_sync(self) -> io::Result<TcpSender<T>> {
+        let tag = self.connection_tag();
         let mut s = TcpSender::connect_from(self.sport, &self.addr)?;
         {
             let s = s.get_mut();
-            s.write_all(&[if self.is_for_base {
-                CONNECTION_FROM_BASE
-            } else {
-                CONNECTION_FROM_DOMAIN
-            }])?;
+            s.write_all(&[tag])?;
             s.flush()?;
         }
 
@@ -162,7 +177,6 @@ where
                 _marker: Remote,
             }
             .build_async()
-            .map(|c| Box::new(c) as Box<_>)
         }
     }
 
@@ -290,6 +304,19 @@ impl<K: Eq + Hash + Clone, T> ChannelCoordinator<K, T> {
         }
     }
 
+    pub fn remove<Q>(&self, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        #[allow(clippy::expect_used)]
+        // This can only fail if the mutex is poisoned, in which case we can't recover,
+        // so we allow to panic if that happens.
+        let mut guard = self.inner.write().expect("poisoned mutex");
+        guard.addrs.remove(key);
+        guard.locals.remove(key);
+    }
+
     pub fn clear(&self) {
         let mut guard = self.inner.write().expect("poisoned mutex");
         guard.addrs.clear();
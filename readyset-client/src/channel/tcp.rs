@@ -20,6 +20,7 @@ use socket2::{Domain, Protocol, SockAddr, Socket, Type};
 use thiserror::Error;
 use tokio::io::{AsyncRead, AsyncWrite};
 
+use crate::tls::MaybeTlsSyncStream;
 use crate::Tagged;
 
 #[derive(Debug, Error)]
@@ -65,43 +66,36 @@ macro_rules! poisoning_try {
 }
 
 pub struct TcpSender<T> {
-    stream: BufStream<std::net::TcpStream>,
+    stream: BufStream<MaybeTlsSyncStream>,
     poisoned: bool,
 
     phantom: PhantomData<T>,
 }
 
 impl<T: Serialize> TcpSender<T> {
+    /// Create a new `TcpSender` over the given connection, which is wrapped in a TLS session if
+    /// internal TLS is enabled
     pub fn new(stream: std::net::TcpStream) -> Result<Self, io::Error> {
-        stream.set_nodelay(true).map(|_| Self {
-            stream: BufStream::new(stream),
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream: BufStream::new(MaybeTlsSyncStream::connect(stream)?),
             poisoned: false,
             phantom: PhantomData,
         })
     }
 
     pub(crate) fn connect_from(sport: Option<u16>, addr: &SocketAddr) -> Result<Self, io::Error> {
-        let bind_addr = std::net::SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, sport.unwrap_or(0));
-        let s = Socket::new(Domain::IPV4, Type::STREAM, Some(Protocol::TCP))?;
-        s.set_reuse_address(true)?;
-        s.bind(&SockAddr::from(bind_addr))?;
-        s.set_nodelay(true)?;
-        s.connect(&SockAddr::from(*addr))?;
-        Self::new(std::net::TcpStream::from(s))
+        Self::new(connect_socket(sport, addr)?)
     }
 
     pub fn connect(addr: &SocketAddr) -> Result<Self, io::Error> {
         Self::connect_from(None, addr)
     }
 
-    pub fn get_mut(&mut self) -> &mut BufStream<std::net::TcpStream> {
+    pub fn get_mut(&mut self) -> &mut BufStream<MaybeTlsSyncStream> {
         &mut self.stream
     }
 
-    pub(crate) fn into_inner(self) -> BufStream<std::net::TcpStream> {
-        self.stream
-    }
-
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
         self.stream.get_ref().local_addr()
     }
@@ -148,6 +142,20 @@ impl<T: Serialize> super::Sender for TcpSender<T> {
     }
 }
 
+/// Open a TCP connection to `addr`, optionally from the given source port
+pub(crate) fn connect_socket(
+    sport: Option<u16>,
+    addr: &SocketAddr,
+) -> Result<std::net::TcpStream, io::Error> {
+    let bind_addr = std::net::SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, sport.unwrap_or(0));
+    let s = Socket::new(Domain::IPV4, Type::STREAM, Some(Protocol::TCP))?;
+    s.set_reuse_address(true)?;
+    s.bind(&SockAddr::from(bind_addr))?;
+    s.set_nodelay(true)?;
+    s.connect(&SockAddr::from(*addr))?;
+    Ok(std::net::TcpStream::from(s))
+}
+
 #[derive(Debug)]
 pub enum TryRecvError {
     Empty,
@@ -161,11 +169,13 @@ pub enum RecvError {
     DeserializationError(bincode::Error),
 }
 
+/// A bincode stream receiving `T` (or `T2`, upgraded to `T`), which acknowledges the writes it
+/// receives from base table clients with the number of rows they affected
 #[pin_project(project = DualTcpStreamProj)]
 pub enum DualTcpStream<S, T, T2, D> {
-    Passthrough(#[pin] AsyncBincodeStream<S, T, Tagged<()>, D>),
+    Passthrough(#[pin] AsyncBincodeStream<S, T, Tagged<u64>, D>),
     Upgrade(
-        #[pin] AsyncBincodeStream<S, T2, Tagged<()>, D>,
+        #[pin] AsyncBincodeStream<S, T2, Tagged<u64>, D>,
         Box<dyn FnMut(T2) -> T + Send + Sync>,
     ),
 }
@@ -178,7 +188,7 @@ impl<S, T, T2> From<S> for DualTcpStream<S, T, T2, AsyncDestination> {
 
 impl<S, T, T2> DualTcpStream<S, T, T2, AsyncDestination> {
     pub fn upgrade<F: 'static + FnMut(T2) -> T + Send + Sync>(stream: S, f: F) -> Self {
-        let s: AsyncBincodeStream<S, T2, Tagged<()>, AsyncDestination> =
+        let s: AsyncBincodeStream<S, T2, Tagged<u64>, AsyncDestination> =
             AsyncBincodeStream::from(stream).for_async();
         DualTcpStream::Upgrade(s, Box::new(f))
     }
@@ -191,11 +201,11 @@ impl<S, T, T2> DualTcpStream<S, T, T2, AsyncDestination> {
     }
 }
 
-impl<S, T, T2, D> Sink<Tagged<()>> for DualTcpStream<S, T, T2, D>
+impl<S, T, T2, D> Sink<Tagged<u64>> for DualTcpStream<S, T, T2, D>
 where
     S: AsyncWrite,
-    AsyncBincodeStream<S, T, Tagged<()>, D>: Sink<Tagged<()>, Error = bincode::Error>,
-    AsyncBincodeStream<S, T2, Tagged<()>, D>: Sink<Tagged<()>, Error = bincode::Error>,
+    AsyncBincodeStream<S, T, Tagged<u64>, D>: Sink<Tagged<u64>, Error = bincode::Error>,
+    AsyncBincodeStream<S, T2, Tagged<u64>, D>: Sink<Tagged<u64>, Error = bincode::Error>,
 {
     type Error = bincode::Error;
 
@@ -206,7 +216,7 @@ where
         }
     }
 
-    fn start_send(self: Pin<&mut Self>, item: Tagged<()>) -> Result<(), Self::Error> {
+    fn start_send(self: Pin<&mut Self>, item: Tagged<u64>) -> Result<(), Self::Error> {
         match self.project() {
             DualTcpStreamProj::Passthrough(abs) => abs.start_send(item),
             DualTcpStreamProj::Upgrade(abs, _) => abs.start_send(item),
@@ -233,8 +243,8 @@ where
     T: DeserializeOwned,
     T2: DeserializeOwned,
     S: AsyncRead,
-    AsyncBincodeStream<S, T, Tagged<()>, D>: Stream<Item = Result<T, bincode::Error>>,
-    AsyncBincodeStream<S, T2, Tagged<()>, D>: Stream<Item = Result<T2, bincode::Error>>,
+    AsyncBincodeStream<S, T, Tagged<u64>, D>: Stream<Item = Result<T, bincode::Error>>,
+    AsyncBincodeStream<S, T2, Tagged<u64>, D>: Stream<Item = Result<T2, bincode::Error>>,
 {
     type Item = Result<T, bincode::Error>;
 
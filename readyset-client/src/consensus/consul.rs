@@ -136,13 +136,14 @@ use futures::stream::FuturesOrdered;
 use futures::TryStreamExt;
 use metrics::gauge;
 use readyset_errors::{internal, internal_err};
+use reqwest::header::{HeaderMap, HeaderValue};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use tracing::{error, warn};
 
 use super::{
-    AdapterId, AuthorityControl, AuthorityWorkerHeartbeatResponse, GetLeaderResult, LeaderPayload,
-    WorkerDescriptor, WorkerId,
+    AdapterId, AuthorityControl, AuthorityOptions, AuthorityWorkerHeartbeatResponse,
+    GetLeaderResult, LeaderPayload, WorkerDescriptor, WorkerId,
 };
 #[cfg(feature = "failure_injection")]
 use crate::failpoints;
@@ -167,11 +168,46 @@ const SESSION_TTL: &str = "20s";
 /// The size of each chunk stored in Consul. Consul converts the chunk's bytes to base64
 /// encoding, the encoded base64 bytes must be less than 512KB.
 const CHUNK_SIZE: usize = 256000;
+/// The maximum amount of time a blocking query waits for a change before returning. This should
+/// be shorter than the time callers of `watch_leader` and `watch_workers` wait before polling.
+const WATCH_WAIT: &str = "4s";
+
+/// The keys that can be watched with blocking queries
+#[derive(Clone, Copy)]
+enum WatchedKey {
+    /// The leader key, which changes whenever leadership changes or the leader's session dies
+    Leader,
+    /// All the keys under the worker prefix
+    Workers,
+}
+
+impl WatchedKey {
+    fn path(self) -> &'static str {
+        match self {
+            WatchedKey::Leader => CONTROLLER_KEY,
+            WatchedKey::Workers => WORKER_PREFIX,
+        }
+    }
+}
+
 struct ConsulAuthorityInner {
     session: Option<String>,
     /// The last index that the controller key was modified or
     /// created at.
     controller_index: Option<u64>,
+    /// The index of the leader key as of the last blocking query on it.
+    leader_watch_index: Option<u64>,
+    /// The index of the worker keys as of the last blocking query on them.
+    workers_watch_index: Option<u64>,
+}
+
+impl ConsulAuthorityInner {
+    fn watch_index(&mut self, key: WatchedKey) -> &mut Option<u64> {
+        match key {
+            WatchedKey::Leader => &mut self.leader_watch_index,
+            WatchedKey::Workers => &mut self.workers_watch_index,
+        }
+    }
 }
 
 /// Coordinator that shares connection information between workers and clients using Consul.
@@ -179,6 +215,11 @@ pub struct ConsulAuthority {
     /// The consul client.
     consul: ConsulClient,
 
+    /// The address of the Consul agent, and a client to issue blocking queries to it with, which
+    /// `consul` doesn't support.
+    address: String,
+    http: reqwest::Client,
+
     /// Deployment associated with this authority.
     deployment: String,
 
@@ -208,7 +249,7 @@ fn adapter_id_to_path(id: &str) -> String {
 
 /// Returns the next controller state version. Returns a version in the set { "0", "1" }
 /// since only two versions are required.
-fn next_state_version(current: &str) -> String {
+pub(super) fn next_state_version(current: &str) -> String {
     if current == "0" { "1" } else { "0" }.to_string()
 }
 
@@ -230,7 +271,7 @@ fn get_value_as_bytes(mut r: ApiResponse<Vec<KVPair>>) -> ReadySetResult<Vec<u8>
         .try_into()?)
 }
 
-struct ChunkedState(Vec<Vec<u8>>);
+pub(super) struct ChunkedState(pub(super) Vec<Vec<u8>>);
 
 impl From<Vec<u8>> for ChunkedState {
     fn from(v: Vec<u8>) -> ChunkedState {
@@ -252,12 +293,12 @@ impl From<ChunkedState> for Vec<u8> {
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-struct StateVersion {
+pub(super) struct StateVersion {
     // We must keep the number of chunks in the version as if the number of chunks
     // decreases we have no way of atomically deleting. We instead just keep track
     // of what chunks are actually active via `num_chunks`.
-    num_chunks: usize,
-    version: String,
+    pub(super) num_chunks: usize,
+    pub(super) version: String,
 }
 
 impl Default for StateVersion {
@@ -274,38 +315,107 @@ impl Default for StateVersion {
 /// state bytes directly, otherwise we hold the version which acts as a pointer to the prefix used
 /// for all the dataflow state chunks.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-enum StateValue {
+pub(super) enum StateValue {
     Data(Vec<u8>),
     Version(StateVersion),
 }
 
+/// The parts of a Consul connect string, in the format of
+/// `[http(s)://][<token>@]<address>:<port>/<deployment>`.
+#[derive(Debug, PartialEq, Eq)]
+struct ConnectString {
+    /// The address of the Consul agent, including the scheme
+    address: String,
+    /// The ACL token to authenticate with, if any
+    token: Option<String>,
+    deployment: String,
+}
+
+impl ConnectString {
+    /// Parse a connect string, falling back to `options` for the token and whether to use TLS if
+    /// the connect string doesn't specify a token or scheme.
+    fn parse(connect_string: &str, options: &AuthorityOptions) -> ReadySetResult<Self> {
+        // We artificially create a namespace for each deployment by prefixing the
+        // deployment to each keys path.
+        let (address, deployment) = connect_string.rsplit_once('/').ok_or_else(|| {
+            ReadySetError::Internal("Consul connect string missing deployment".to_owned())
+        })?;
+
+        let (scheme, address) = match address.split_once("://") {
+            Some((scheme @ ("http" | "https"), address)) => (scheme, address),
+            Some((scheme, _)) => {
+                return Err(internal_err!("Unsupported Consul URL scheme: {scheme}"));
+            }
+            None if options.tls() => ("https", address),
+            None => ("http", address),
+        };
+
+        let (token, address) = match address.rsplit_once('@') {
+            Some((token, address)) => (Some(token.to_owned()), address),
+            None => (
+                options.authority_token.as_ref().map(|t| t.0.clone()),
+                address,
+            ),
+        };
+
+        Ok(Self {
+            address: format!("{scheme}://{address}"),
+            token,
+            deployment: deployment.to_owned(),
+        })
+    }
+}
+
 impl ConsulAuthority {
     /// The connect string should be in the format of
-    /// http(s)://<address>:<port>/<deployment>.
+    /// `[http(s)://][<token>@]<address>:<port>/<deployment>`.
     fn new_with_inner(
         connect_string: &str,
+        options: &AuthorityOptions,
         inner: Option<RwLock<ConsulAuthorityInner>>,
     ) -> ReadySetResult<Self> {
-        // We artificially create a namespace for each deployment by prefixing the
-        // deployment to each keys path.
-        let split_idx = connect_string.rfind('/').ok_or_else(|| {
-            ReadySetError::Internal("Consul connect string missing deployment".to_owned())
-        })?;
-
-        let deployment = connect_string[(split_idx + 1)..].to_owned();
-        let address = connect_string[..split_idx].to_owned();
+        let ConnectString {
+            address,
+            token,
+            deployment,
+        } = ConnectString::parse(connect_string, options)?;
 
+        let mut settings = ConsulClientSettingsBuilder::default();
+        settings.address(address.clone());
+        if let Some(token) = &token {
+            settings.token(token.clone());
+        }
+        if let Some(ca) = &options.authority_tls_ca {
+            settings.ca_certs(vec![ca.to_string_lossy().into_owned()]);
+        }
+        if options.authority_tls_skip_verify {
+            settings.verify(false);
+        }
         // TODO(justin): Introduce PR to add timeouts.
         let client = ConsulClient::new(
-            ConsulClientSettingsBuilder::default()
-                .address(address)
+            settings
                 .build()
                 .map_err(|_| internal_err!("Invalid config for consul client"))?,
         )
         .map_err(|_| internal_err!("Failed to connect to consul client"))?;
 
+        let mut headers = HeaderMap::new();
+        if let Some(token) = &token {
+            let mut value = HeaderValue::from_str(token)
+                .map_err(|_| internal_err!("Invalid Consul ACL token"))?;
+            value.set_sensitive(true);
+            headers.insert("X-Consul-Token", value);
+        }
+        let http = options
+            .http_client_builder()?
+            .default_headers(headers)
+            .build()
+            .map_err(|e| internal_err!("Failed to build Consul HTTP client: {e}"))?;
+
         let authority = Self {
             consul: client,
+            address,
+            http,
             deployment,
             inner,
         };
@@ -315,11 +425,29 @@ impl ConsulAuthority {
 
     /// Create a new instance.
     pub fn new(connect_string: &str) -> ReadySetResult<Self> {
+        Self::new_with_options(connect_string, &AuthorityOptions::default())
+    }
+
+    /// Create a new instance, authenticating with Consul and connecting to it over TLS as
+    /// configured by `options`.
+    pub fn new_with_options(
+        connect_string: &str,
+        options: &AuthorityOptions,
+    ) -> ReadySetResult<Self> {
         let inner = Some(RwLock::new(ConsulAuthorityInner {
             controller_index: None,
             session: None,
+            leader_watch_index: None,
+            workers_watch_index: None,
         }));
-        Self::new_with_inner(connect_string, inner)
+        Self::new_with_inner(connect_string, options, inner)
+    }
+
+    /// Returns the `<address>:<port>` of the Consul agent, without the scheme
+    pub fn agent_address(&self) -> &str {
+        self.address
+            .split_once("://")
+            .map_or(&self.address, |(_, address)| address)
     }
 
     async fn create_session(&self) -> ReadySetResult<()> {
@@ -408,6 +536,54 @@ impl ConsulAuthority {
         Ok(())
     }
 
+    /// Performs a [blocking query] on `key`, which returns once the key's index has advanced past
+    /// `index` or after [`WATCH_WAIT`], whichever comes first. Returns the key's index at that
+    /// point.
+    ///
+    /// [blocking query]: https://developer.hashicorp.com/consul/api-docs/features/blocking
+    async fn blocking_query_index(&self, key: WatchedKey, index: u64) -> ReadySetResult<u64> {
+        let mut url = format!(
+            "{}/v1/kv/{}?index={}&wait={}",
+            self.address,
+            self.prefix_with_deployment(key.path()),
+            index,
+            WATCH_WAIT
+        );
+        if matches!(key, WatchedKey::Workers) {
+            url.push_str("&recurse");
+        }
+
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| internal_err!("Blocking query to Consul failed: {e}"))?;
+        // Consul returns the index even if the key doesn't exist
+        response
+            .headers()
+            .get("X-Consul-Index")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| internal_err!("Consul response is missing the X-Consul-Index header"))
+    }
+
+    /// Waits for `key` to change, or for [`WATCH_WAIT`] to elapse.
+    async fn watch(&self, key: WatchedKey) -> ReadySetResult<()> {
+        let last_index = *self.write_inner()?.watch_index(key);
+        let index = match last_index {
+            Some(index) => index,
+            // We haven't watched this key before, so find out which index to wait on
+            None => self.blocking_query_index(key, 0).await?,
+        };
+
+        let new_index = self.blocking_query_index(key, index).await?;
+        // If Consul's state is reset its indexes can go backwards, in which case we have to start
+        // over from the beginning
+        *self.write_inner()?.watch_index(key) = Some(if new_index < index { 0 } else { new_index });
+        Ok(())
+    }
+
     #[cfg(test)]
     async fn destroy_session(&self) -> ReadySetResult<()> {
         let inner_session = self.read_inner()?.session.clone();
@@ -734,15 +910,15 @@ impl AuthorityControl for ConsulAuthority {
     }
 
     fn can_watch(&self) -> bool {
-        false
+        true
     }
 
     async fn watch_leader(&self) -> ReadySetResult<()> {
-        Ok(())
+        self.watch(WatchedKey::Leader).await
     }
 
     async fn watch_workers(&self) -> ReadySetResult<()> {
-        Ok(())
+        self.watch(WatchedKey::Workers).await
     }
 
     async fn try_read<P: DeserializeOwned>(&self, path: &str) -> ReadySetResult<Option<P>> {
@@ -1042,6 +1218,61 @@ mod tests {
         )
     }
 
+    #[test]
+    fn parse_connect_string() {
+        let options = AuthorityOptions::default();
+        assert_eq!(
+            ConnectString::parse("http://127.0.0.1:8500/dep", &options).unwrap(),
+            ConnectString {
+                address: "http://127.0.0.1:8500".into(),
+                token: None,
+                deployment: "dep".into(),
+            }
+        );
+        assert_eq!(
+            ConnectString::parse("consul:8500/dep", &options).unwrap(),
+            ConnectString {
+                address: "http://consul:8500".into(),
+                token: None,
+                deployment: "dep".into(),
+            }
+        );
+        assert_eq!(
+            ConnectString::parse("https://secret@consul:8501/dep", &options).unwrap(),
+            ConnectString {
+                address: "https://consul:8501".into(),
+                token: Some("secret".into()),
+                deployment: "dep".into(),
+            }
+        );
+        assert!(ConnectString::parse("consul:8500", &options).is_err());
+        assert!(ConnectString::parse("zk://consul:8500/dep", &options).is_err());
+    }
+
+    #[test]
+    fn parse_connect_string_with_options() {
+        let options = AuthorityOptions {
+            authority_token: Some("from-flag".to_owned().into()),
+            authority_tls: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            ConnectString::parse("consul:8501/dep", &options).unwrap(),
+            ConnectString {
+                address: "https://consul:8501".into(),
+                token: Some("from-flag".into()),
+                deployment: "dep".into(),
+            }
+        );
+        // A token in the connect string takes precedence
+        assert_eq!(
+            ConnectString::parse("http://secret@consul:8501/dep", &options)
+                .unwrap()
+                .token,
+            Some("secret".into())
+        );
+    }
+
     #[tokio::test]
     #[serial]
     async fn read_write_operations() {
@@ -1109,6 +1340,38 @@ mod tests {
         assert_eq!(incr_state(&authority).await, 2);
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn watch_leader_returns_on_leader_change() {
+        let authority_address = test_authority_address("watch_leader");
+        let authority = Arc::new(ConsulAuthority::new(&authority_address).unwrap());
+        authority.init().await.unwrap();
+        authority.delete_all_keys().await;
+
+        let watch = tokio::spawn({
+            let authority = authority.clone();
+            async move { authority.watch_leader().await }
+        });
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(!watch.is_finished());
+
+        authority
+            .become_leader(LeaderPayload {
+                controller_uri: url::Url::parse("http://127.0.0.1:8500").unwrap(),
+                nonce: 1,
+            })
+            .await
+            .unwrap();
+
+        // The watch should return as soon as the leader changes, well before the blocking query
+        // would time out
+        tokio::time::timeout(Duration::from_secs(2), watch)
+            .await
+            .expect("watch_leader should return when the leader changes")
+            .unwrap()
+            .unwrap();
+    }
+
     #[tokio::test]
     #[serial]
     async fn leader_election_operations() {
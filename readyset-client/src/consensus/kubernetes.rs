@@ -0,0 +1,1122 @@
+//! # Kubernetes Authority
+//! *TL;DR: Leadership and worker registration use Leases, everything else lives in ConfigMaps.*
+//!
+//! The [`KubernetesAuthority`] talks directly to the Kubernetes API server, so deployments running
+//! in Kubernetes don't need to run Consul alongside ReadySet. By default it connects to the API
+//! server of the cluster it's running in, authenticating with the token of the pod's service
+//! account, and creates all of its objects in the service account's namespace. The service account
+//! needs permission to get, list, create, update and patch `leases` in the `coordination.k8s.io`
+//! API group, and `configmaps` in the core API group.
+//!
+//! ## Objects
+//! All objects are named after, and labeled with, the deployment they belong to.
+//!
+//! | Object | Description |
+//! | ------ | ----------- |
+//! | Lease `<deployment>-controller` | Held by the current leader, with the leader's payload in an annotation. |
+//! | Lease `<deployment>-worker-<id>` | Held by worker `<id>`, with its [`WorkerDescriptor`] in an annotation. |
+//! | Lease `<deployment>-adapter-<id>` | Held by adapter `<id>`, with its HTTP endpoint in an annotation. |
+//! | ConfigMap `<deployment>-state` | The controller state, or the version of the chunks holding it. |
+//! | ConfigMap `<deployment>-state-<version>-<n>` | Chunk `n` of version `<version>` of the controller state. |
+//! | ConfigMap `<deployment>-keys` | Every other key, written with [`AuthorityControl::read_modify_write`]. |
+//!
+//! ## Leases
+//! Each authority picks a unique identity when it's initialized, which it uses as the holder of
+//! every Lease it acquires. A Lease is only considered held if it has been renewed within the last
+//! [`LEASE_DURATION`]; [`AuthorityControl::worker_heartbeat`] renews every Lease held by the given
+//! identity, including the controller Lease, so a node that stops heartbeating loses both its
+//! leadership and its registration as a worker, as it would if its Consul session died.
+//!
+//! All updates to existing objects are conditional on the object's `resourceVersion` being the
+//! one we last read, so two nodes can't both acquire the controller Lease.
+//!
+//! ## Controller state
+//! ConfigMaps can hold at most 1 MiB of data, so the controller state is split into chunks in the
+//! same way as it is in Consul - see the documentation of the `consul` module for how updates to
+//! the chunks are kept atomic. Before writing any part of the controller state we check that we
+//! still hold the controller Lease.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use failpoint_macros::set_failpoint;
+use futures::stream::FuturesOrdered;
+use futures::TryStreamExt;
+use metrics::gauge;
+use parking_lot::RwLock;
+use readyset_errors::{internal, internal_err};
+use readyset_util::redacted::RedactedString;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use super::consul::{next_state_version, ChunkedState, StateValue, StateVersion};
+use super::{
+    AdapterId, AuthorityControl, AuthorityOptions, AuthorityWorkerHeartbeatResponse,
+    GetLeaderResult, LeaderPayload, WorkerDescriptor, WorkerId,
+};
+#[cfg(feature = "failure_injection")]
+use crate::failpoints;
+use crate::metrics::recorded;
+use crate::ReadySetResult;
+
+/// Path to the service account token mounted into every pod.
+const TOKEN_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount/token";
+/// Path to the certificate of the cluster's certificate authority, mounted into every pod.
+const CA_CERT_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount/ca.crt";
+/// Path to the namespace of the pod's service account, mounted into every pod.
+const NAMESPACE_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount/namespace";
+/// The namespace to use if we're not running in a pod.
+const DEFAULT_NAMESPACE: &str = "default";
+
+const LEASES_API: &str = "/apis/coordination.k8s.io/v1";
+const CONFIG_MAPS_API: &str = "/api/v1";
+
+/// Label holding the deployment an object belongs to.
+const DEPLOYMENT_LABEL: &str = "readyset.io/deployment";
+/// Label holding the role of the holder of a Lease: one of [`CONTROLLER_ROLE`], [`WORKER_ROLE`]
+/// or [`ADAPTER_ROLE`].
+const ROLE_LABEL: &str = "readyset.io/role";
+/// Label holding the identity of the holder of a Lease, so that heartbeats can find all the Leases
+/// held by an identity.
+const HOLDER_LABEL: &str = "readyset.io/holder";
+/// Annotation holding the JSON-serialized payload of a Lease's holder.
+const PAYLOAD_ANNOTATION: &str = "readyset.io/payload";
+
+const CONTROLLER_ROLE: &str = "controller";
+const WORKER_ROLE: &str = "worker";
+const ADAPTER_ROLE: &str = "adapter";
+
+/// Suffix of the name of the ConfigMap holding the controller state.
+const STATE_NAME: &str = "state";
+/// Suffix of the name of the ConfigMap holding all other keys.
+const KEYS_NAME: &str = "keys";
+/// Key that the data of a ConfigMap holding the controller state, or a chunk of it, is stored
+/// under.
+const VALUE_KEY: &str = "value";
+
+/// How long a Lease is held for after it was last renewed.
+const LEASE_DURATION: Duration = Duration::from_secs(20);
+
+/// (De)serialization of the `MicroTime`s used in Leases, which must have exactly six fractional
+/// digits.
+mod micro_time {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    const FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.6fZ";
+
+    pub(super) fn serialize<S>(
+        time: &Option<DateTime<Utc>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match time {
+            Some(time) => serializer.serialize_str(&time.format(FORMAT).to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<DateTime<Utc>>::deserialize(deserializer)
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ObjectMeta {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    resource_version: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    labels: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    annotations: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LeaseSpec {
+    #[serde(default)]
+    holder_identity: Option<String>,
+    #[serde(default)]
+    lease_duration_seconds: Option<u64>,
+    #[serde(default, with = "micro_time")]
+    acquire_time: Option<DateTime<Utc>>,
+    #[serde(default, with = "micro_time")]
+    renew_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    lease_transitions: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Lease {
+    // Objects in lists don't include their API version or kind
+    #[serde(default)]
+    api_version: String,
+    #[serde(default)]
+    kind: String,
+    metadata: ObjectMeta,
+    #[serde(default)]
+    spec: LeaseSpec,
+}
+
+impl Lease {
+    fn new(metadata: ObjectMeta) -> Self {
+        Self {
+            api_version: "coordination.k8s.io/v1".to_owned(),
+            kind: "Lease".to_owned(),
+            metadata,
+            spec: Default::default(),
+        }
+    }
+
+    /// Returns the identity of the holder of this Lease, if it's held as of `now`.
+    fn holder(&self, now: DateTime<Utc>) -> Option<&str> {
+        let holder = self
+            .spec
+            .holder_identity
+            .as_deref()
+            .filter(|h| !h.is_empty())?;
+        let renew_time = self.spec.renew_time.or(self.spec.acquire_time)?;
+        let duration = chrono::Duration::seconds(self.spec.lease_duration_seconds? as i64);
+        (renew_time + duration > now).then_some(holder)
+    }
+
+    /// Make `identity` the holder of this Lease as of `now`, with the given `payload`.
+    fn acquire(&mut self, identity: &str, payload: String, now: DateTime<Utc>) {
+        if self.spec.holder_identity.as_deref() != Some(identity) {
+            self.spec.lease_transitions = Some(self.spec.lease_transitions.unwrap_or(0) + 1);
+        }
+        self.spec.holder_identity = Some(identity.to_owned());
+        self.spec.lease_duration_seconds = Some(LEASE_DURATION.as_secs());
+        self.spec.acquire_time = Some(now);
+        self.spec.renew_time = Some(now);
+        self.metadata
+            .labels
+            .insert(HOLDER_LABEL.to_owned(), identity.to_owned());
+        self.metadata
+            .annotations
+            .insert(PAYLOAD_ANNOTATION.to_owned(), payload);
+    }
+
+    /// Deserialize the payload of the holder of this Lease, if it has one.
+    fn payload<P: DeserializeOwned>(&self) -> ReadySetResult<Option<P>> {
+        self.metadata
+            .annotations
+            .get(PAYLOAD_ANNOTATION)
+            .map(|p| serde_json::from_str(p))
+            .transpose()
+            .map_err(Into::into)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigMap {
+    // Objects in lists don't include their API version or kind
+    #[serde(default)]
+    api_version: String,
+    #[serde(default)]
+    kind: String,
+    metadata: ObjectMeta,
+    /// Base64-encoded values
+    #[serde(default)]
+    binary_data: BTreeMap<String, String>,
+}
+
+impl ConfigMap {
+    fn new(metadata: ObjectMeta) -> Self {
+        Self {
+            api_version: "v1".to_owned(),
+            kind: "ConfigMap".to_owned(),
+            metadata,
+            binary_data: Default::default(),
+        }
+    }
+
+    fn get(&self, key: &str) -> ReadySetResult<Option<Vec<u8>>> {
+        self.binary_data
+            .get(key)
+            .map(|v| base64::decode(v).map_err(|e| internal_err!("Invalid ConfigMap data: {e}")))
+            .transpose()
+    }
+
+    fn set(&mut self, key: String, value: &[u8]) {
+        self.binary_data.insert(key, base64::encode(value));
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct List<T> {
+    items: Vec<T>,
+}
+
+/// A request to the Kubernetes API that failed in a way the caller is expected to handle.
+enum ApiError {
+    /// The object doesn't exist.
+    NotFound,
+    /// The object already exists, or its `resourceVersion` didn't match the one we sent.
+    Conflict,
+}
+
+type ApiResult<T> = ReadySetResult<Result<T, ApiError>>;
+
+/// Converts `name` into a valid name (and label value) for a Kubernetes object, by lowercasing it
+/// and replacing any unsupported characters with `-`.
+fn object_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Converts an authority path into a valid ConfigMap key.
+fn path_to_key(path: &str) -> String {
+    path.chars()
+        .map(|c| match c {
+            '/' => '.',
+            c if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Coordinator that shares connection information between workers and clients using the
+/// Kubernetes API. See the [module documentation](self) for more information.
+pub struct KubernetesAuthority {
+    http: reqwest::Client,
+    /// A bearer token to use instead of the pod's service account token, if any.
+    token: Option<RedactedString>,
+    /// The base URL of the Kubernetes API server.
+    api_server: String,
+    /// The namespace all objects are created in.
+    namespace: String,
+    /// Deployment associated with this authority.
+    deployment: String,
+    /// The identity this authority holds leases with, set by `init`.
+    identity: RwLock<Option<String>>,
+    /// The holder and payload of the controller Lease the last time it was read by
+    /// `try_get_leader`.
+    last_leader: RwLock<Option<(String, String)>>,
+}
+
+impl KubernetesAuthority {
+    /// Create a new instance for the given deployment. `api_server` is the URL of the Kubernetes
+    /// API server; if it's empty, we connect to the API server of the cluster we're running in.
+    pub fn new(api_server: &str, deployment: &str) -> ReadySetResult<Self> {
+        Self::new_with_options(api_server, deployment, &AuthorityOptions::default())
+    }
+
+    /// Create a new instance, authenticating with the API server and verifying its certificate as
+    /// configured by `options`.
+    pub fn new_with_options(
+        api_server: &str,
+        deployment: &str,
+        options: &AuthorityOptions,
+    ) -> ReadySetResult<Self> {
+        let api_server = if api_server.is_empty() {
+            let host = std::env::var("KUBERNETES_SERVICE_HOST").map_err(|_| {
+                internal_err!("No Kubernetes API server given, and not running in a pod")
+            })?;
+            let port = std::env::var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|_| "443".into());
+            // IPv6 addresses need to be bracketed to be used in a URL
+            if host.contains(':') {
+                format!("https://[{host}]:{port}")
+            } else {
+                format!("https://{host}:{port}")
+            }
+        } else if api_server.contains("://") {
+            api_server.trim_end_matches('/').to_owned()
+        } else {
+            format!("https://{}", api_server.trim_end_matches('/'))
+        };
+
+        let namespace = std::fs::read_to_string(NAMESPACE_PATH)
+            .map(|ns| ns.trim().to_owned())
+            .unwrap_or_else(|_| DEFAULT_NAMESPACE.to_owned());
+
+        let mut builder = options.http_client_builder()?;
+        if let Ok(ca_cert) = std::fs::read(CA_CERT_PATH) {
+            builder = builder.add_root_certificate(
+                reqwest::Certificate::from_pem(&ca_cert)
+                    .map_err(|e| internal_err!("Invalid Kubernetes CA certificate: {e}"))?,
+            );
+        }
+        let http = builder
+            .build()
+            .map_err(|e| internal_err!("Failed to build Kubernetes API client: {e}"))?;
+
+        Ok(Self {
+            http,
+            token: options.authority_token.clone(),
+            api_server,
+            namespace,
+            deployment: object_name(deployment),
+            identity: RwLock::new(None),
+            last_leader: RwLock::new(None),
+        })
+    }
+
+    fn identity(&self) -> ReadySetResult<String> {
+        self.identity
+            .read()
+            .clone()
+            .ok_or_else(|| internal_err!("KubernetesAuthority used before being initialized"))
+    }
+
+    /// Returns the name of the object with the given suffix in our deployment.
+    fn name(&self, suffix: &str) -> String {
+        format!("{}-{}", self.deployment, suffix)
+    }
+
+    fn metadata(&self, name: String, role: Option<&str>) -> ObjectMeta {
+        let mut labels = BTreeMap::from([(DEPLOYMENT_LABEL.to_owned(), self.deployment.clone())]);
+        if let Some(role) = role {
+            labels.insert(ROLE_LABEL.to_owned(), role.to_owned());
+        }
+        ObjectMeta {
+            name,
+            labels,
+            ..Default::default()
+        }
+    }
+
+    fn url(&self, api: &str, resource: &str, name: Option<&str>) -> String {
+        let mut url = format!(
+            "{}{}/namespaces/{}/{}",
+            self.api_server, api, self.namespace, resource
+        );
+        if let Some(name) = name {
+            url.push('/');
+            url.push_str(name);
+        }
+        url
+    }
+
+    /// Issue a request to the Kubernetes API, deserializing the response.
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: String,
+        query: &[(&str, &str)],
+        body: Option<(&str, Vec<u8>)>,
+    ) -> ApiResult<T> {
+        let mut request = self.http.request(method, url).query(query);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token.as_str());
+        } else if let Ok(token) = tokio::fs::read_to_string(TOKEN_PATH).await {
+            // The token is rotated periodically, so we read it for every request
+            request = request.bearer_auth(token.trim());
+        }
+        if let Some((content_type, body)) = body {
+            request = request.header(CONTENT_TYPE, content_type).body(body);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| internal_err!("Request to the Kubernetes API failed: {e}"))?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(Err(ApiError::NotFound)),
+            StatusCode::CONFLICT => Ok(Err(ApiError::Conflict)),
+            status if status.is_success() => Ok(Ok(response
+                .json()
+                .await
+                .map_err(|e| internal_err!("Invalid response from the Kubernetes API: {e}"))?)),
+            status => {
+                let message = response.text().await.unwrap_or_default();
+                internal!("Request to the Kubernetes API failed with {status}: {message}")
+            }
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        api: &str,
+        resource: &str,
+        name: &str,
+    ) -> ApiResult<T> {
+        self.request(Method::GET, self.url(api, resource, Some(name)), &[], None)
+            .await
+    }
+
+    async fn create<T: Serialize + DeserializeOwned>(
+        &self,
+        api: &str,
+        resource: &str,
+        object: &T,
+    ) -> ApiResult<T> {
+        let body = serde_json::to_vec(object)?;
+        self.request(
+            Method::POST,
+            self.url(api, resource, None),
+            &[],
+            Some(("application/json", body)),
+        )
+        .await
+    }
+
+    /// Replace the object with the given name. If the object has a `resourceVersion`, this fails
+    /// with [`ApiError::Conflict`] if the object was modified since that version.
+    async fn replace<T: Serialize + DeserializeOwned>(
+        &self,
+        api: &str,
+        resource: &str,
+        name: &str,
+        object: &T,
+    ) -> ApiResult<T> {
+        let body = serde_json::to_vec(object)?;
+        self.request(
+            Method::PUT,
+            self.url(api, resource, Some(name)),
+            &[],
+            Some(("application/json", body)),
+        )
+        .await
+    }
+
+    async fn list_leases(&self, role: &str, holder: Option<&str>) -> ReadySetResult<Vec<Lease>> {
+        let mut selector = format!(
+            "{}={},{}={}",
+            DEPLOYMENT_LABEL, self.deployment, ROLE_LABEL, role
+        );
+        if let Some(holder) = holder {
+            selector.push_str(&format!(",{}={}", HOLDER_LABEL, holder));
+        }
+        match self
+            .request::<List<Lease>>(
+                Method::GET,
+                self.url(LEASES_API, "leases", None),
+                &[("labelSelector", &selector)],
+                None,
+            )
+            .await?
+        {
+            Ok(list) => Ok(list.items),
+            Err(_) => Ok(vec![]),
+        }
+    }
+
+    async fn get_lease(&self, name: &str) -> ReadySetResult<Option<Lease>> {
+        match self.get(LEASES_API, "leases", name).await? {
+            Ok(lease) => Ok(Some(lease)),
+            Err(ApiError::NotFound) => Ok(None),
+            Err(ApiError::Conflict) => internal!("Unexpected conflict reading Lease {name}"),
+        }
+    }
+
+    /// Acquire the Lease with the given name and role for our identity, holding `payload`, as long
+    /// as it isn't already held by someone else. Returns whether the Lease was acquired.
+    async fn acquire_lease<P: Serialize>(
+        &self,
+        name: String,
+        role: &str,
+        payload: &P,
+    ) -> ReadySetResult<bool> {
+        let identity = self.identity()?;
+        let payload = serde_json::to_string(payload)?;
+        let now = Utc::now();
+
+        let res = match self.get_lease(&name).await? {
+            Some(mut lease) => {
+                if matches!(lease.holder(now), Some(holder) if holder != identity) {
+                    return Ok(false);
+                }
+                lease.acquire(&identity, payload, now);
+                self.replace(LEASES_API, "leases", &name, &lease).await?
+            }
+            None => {
+                let mut lease = Lease::new(self.metadata(name, Some(role)));
+                lease.acquire(&identity, payload, now);
+                self.create(LEASES_API, "leases", &lease).await?
+            }
+        };
+        // A conflict means someone else acquired or created the lease between our read and write
+        Ok(res.is_ok())
+    }
+
+    /// Renew `lease`, as long as it's still held by `identity`. Returns whether the lease was
+    /// renewed.
+    async fn renew_lease(&self, mut lease: Lease, identity: &str) -> ReadySetResult<bool> {
+        loop {
+            let now = Utc::now();
+            if lease.holder(now) != Some(identity) {
+                return Ok(false);
+            }
+            lease.spec.renew_time = Some(now);
+            let name = lease.metadata.name.clone();
+            match self.replace(LEASES_API, "leases", &name, &lease).await? {
+                Ok(_) => return Ok(true),
+                // Someone else modified the lease, so check whether we still hold it
+                Err(ApiError::Conflict) => match self.get_lease(&name).await? {
+                    Some(l) => lease = l,
+                    None => return Ok(false),
+                },
+                Err(ApiError::NotFound) => return Ok(false),
+            }
+        }
+    }
+
+    /// Returns the payloads of all the leases with the given role that are currently held, keyed
+    /// by the identity of their holder.
+    async fn held_lease_payloads<P: DeserializeOwned>(
+        &self,
+        role: &str,
+    ) -> ReadySetResult<HashMap<String, P>> {
+        let now = Utc::now();
+        let mut payloads = HashMap::new();
+        for lease in self.list_leases(role, None).await? {
+            if let Some(holder) = lease.holder(now) {
+                if let Some(payload) = lease.payload()? {
+                    payloads.insert(holder.to_owned(), payload);
+                }
+            }
+        }
+        Ok(payloads)
+    }
+
+    async fn ensure_leader(&self) -> ReadySetResult<()> {
+        let identity = self.identity()?;
+        let lease = self.get_lease(&self.name(CONTROLLER_ROLE)).await?;
+        if lease.as_ref().and_then(|l| l.holder(Utc::now())) != Some(identity.as_str()) {
+            internal!("An authority that has lost leadership attempted to issue a write")
+        }
+        Ok(())
+    }
+
+    async fn get_config_map(&self, name: &str) -> ReadySetResult<Option<ConfigMap>> {
+        match self.get(CONFIG_MAPS_API, "configmaps", name).await? {
+            Ok(config_map) => Ok(Some(config_map)),
+            Err(ApiError::NotFound) => Ok(None),
+            Err(ApiError::Conflict) => internal!("Unexpected conflict reading ConfigMap {name}"),
+        }
+    }
+
+    /// Write `config_map`, creating it if it doesn't exist. If the ConfigMap has a
+    /// `resourceVersion`, the write only succeeds if the ConfigMap hasn't been modified since that
+    /// version; returns whether the write succeeded.
+    async fn write_config_map(&self, config_map: &ConfigMap) -> ReadySetResult<bool> {
+        let name = &config_map.metadata.name;
+        let res = match self
+            .replace(CONFIG_MAPS_API, "configmaps", name, config_map)
+            .await?
+        {
+            Err(ApiError::NotFound) if config_map.metadata.resource_version.is_none() => {
+                self.create(CONFIG_MAPS_API, "configmaps", config_map)
+                    .await?
+            }
+            res => res,
+        };
+        Ok(res.is_ok())
+    }
+
+    /// Unconditionally write a ConfigMap with the given name holding `value`.
+    async fn write_value(&self, name: String, value: &[u8]) -> ReadySetResult<()> {
+        let mut config_map = ConfigMap::new(self.metadata(name, None));
+        config_map.set(VALUE_KEY.to_owned(), value);
+        if !self.write_config_map(&config_map).await? {
+            internal!("Failed to write ConfigMap {}", config_map.metadata.name)
+        }
+        Ok(())
+    }
+
+    async fn read_value(&self, name: &str) -> ReadySetResult<Option<Vec<u8>>> {
+        match self.get_config_map(name).await? {
+            Some(config_map) => config_map.get(VALUE_KEY),
+            None => Ok(None),
+        }
+    }
+
+    /// Read the raw bytes stored at `path`, along with the ConfigMap holding all keys.
+    async fn read_key(&self, path: &str) -> ReadySetResult<(Option<ConfigMap>, Option<Vec<u8>>)> {
+        let config_map = self.get_config_map(&self.name(KEYS_NAME)).await?;
+        let value = match &config_map {
+            Some(config_map) => config_map.get(&path_to_key(path))?,
+            None => None,
+        };
+        Ok((config_map, value))
+    }
+
+    /// Retrieves the controller state value if it exists, otherwise returns None.
+    async fn get_controller_state_value(&self) -> ReadySetResult<Option<StateValue>> {
+        match self.read_value(&self.name(STATE_NAME)).await? {
+            Some(bytes) => {
+                let data = cloudflare_zlib::inflate(&bytes)
+                    .map_err(|e| internal_err!("Failure during decompress: {e}"))?;
+                Ok(Some(rmp_serde::from_slice(&data)?))
+            }
+            None => {
+                warn!("No controller state version in Kubernetes");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Writes the controller state value, as long as we're still the leader.
+    async fn write_controller_state_value(&self, input: StateValue) -> ReadySetResult<()> {
+        self.ensure_leader().await?;
+        let new_val = rmp_serde::to_vec(&input)?;
+        let compressed = super::Compressor::compress(&new_val);
+        self.write_value(self.name(STATE_NAME), &compressed).await
+    }
+
+    fn state_chunk_name(&self, version: &str, chunk: usize) -> String {
+        self.name(&format!("{STATE_NAME}-{version}-{chunk}"))
+    }
+
+    /// Retrieves the controller state referred to by `state_value`, reading its chunks if
+    /// necessary. Returns the state value to be used when calculating the next state value.
+    async fn get_controller_state<P: DeserializeOwned>(
+        &self,
+        state_value: StateValue,
+    ) -> ReadySetResult<(P, Option<StateValue>)> {
+        let (state_bytes, value) = match state_value {
+            StateValue::Version(ref v) => {
+                let chunk_futures: FuturesOrdered<_> = (0..v.num_chunks)
+                    .map(|c| {
+                        let name = self.state_chunk_name(&v.version, c);
+                        async move {
+                            self.read_value(&name).await?.ok_or_else(|| {
+                                internal_err!("Missing controller state chunk {name}")
+                            })
+                        }
+                    })
+                    .collect();
+
+                let t: ReadySetResult<Vec<Vec<u8>>> = chunk_futures.try_collect().await;
+                let chunks = ChunkedState(t?);
+                (chunks.into(), Some(state_value))
+            }
+            StateValue::Data(d) => (d, None),
+        };
+        let data = cloudflare_zlib::inflate(&state_bytes)
+            .map_err(|e| internal_err!("Compression failed: {e}"))?;
+        Ok((rmp_serde::from_slice(&data)?, value))
+    }
+
+    /// Write the chunks of `controller_state` to ConfigMaps, if it needs to be chunked. Returns the
+    /// state value to write to refer to the new state.
+    async fn write_controller_state<P: Serialize>(
+        &self,
+        version: Option<StateValue>,
+        controller_state: P,
+    ) -> ReadySetResult<(StateValue, P)> {
+        let new_val = rmp_serde::to_vec(&controller_state)?;
+        let compressed = super::Compressor::compress(&new_val);
+
+        gauge!(recorded::DATAFLOW_STATE_SERIALIZED, compressed.len() as f64);
+
+        let chunked = ChunkedState::from(compressed);
+        let num_chunks = chunked.0.len();
+        let state_value = if num_chunks > 1 {
+            let new_version = match version {
+                Some(StateValue::Version(v)) => next_state_version(&v.version),
+                Some(StateValue::Data(_)) | None => StateVersion::default().version,
+            };
+
+            self.ensure_leader().await?;
+            for (i, chunk) in chunked.0.iter().enumerate() {
+                self.write_value(self.state_chunk_name(&new_version, i), chunk)
+                    .await?;
+            }
+
+            StateValue::Version(StateVersion {
+                num_chunks,
+                version: new_version,
+            })
+        } else {
+            StateValue::Data(chunked.into())
+        };
+
+        Ok((state_value, controller_state))
+    }
+}
+
+#[async_trait]
+impl AuthorityControl for KubernetesAuthority {
+    async fn init(&self) -> ReadySetResult<()> {
+        let mut identity = self.identity.write();
+        if identity.is_none() {
+            *identity = Some(uuid::Uuid::new_v4().to_string());
+        }
+        Ok(())
+    }
+
+    async fn become_leader(&self, payload: LeaderPayload) -> ReadySetResult<Option<LeaderPayload>> {
+        Ok(self
+            .acquire_lease(self.name(CONTROLLER_ROLE), CONTROLLER_ROLE, &payload)
+            .await?
+            .then_some(payload))
+    }
+
+    async fn surrender_leadership(&self) -> ReadySetResult<()> {
+        let identity = self.identity()?;
+        if let Some(mut lease) = self.get_lease(&self.name(CONTROLLER_ROLE)).await? {
+            if lease.spec.holder_identity.as_deref() == Some(identity.as_str()) {
+                lease.spec.holder_identity = None;
+                lease.metadata.labels.remove(HOLDER_LABEL);
+                let name = lease.metadata.name.clone();
+                // If this conflicts, someone else has already taken over the lease
+                let _ = self.replace(LEASES_API, "leases", &name, &lease).await?;
+            }
+        }
+        Ok(())
+    }
+
+    // Block until there is any leader.
+    async fn get_leader(&self) -> ReadySetResult<LeaderPayload> {
+        loop {
+            if let Ok(Some(lease)) = self.get_lease(&self.name(CONTROLLER_ROLE)).await {
+                if lease.holder(Utc::now()).is_some() {
+                    if let Some(payload) = lease.payload()? {
+                        return Ok(payload);
+                    }
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    async fn try_get_leader(&self) -> ReadySetResult<GetLeaderResult> {
+        let lease = match self.get_lease(&self.name(CONTROLLER_ROLE)).await {
+            Ok(Some(lease)) => lease,
+            _ => return Ok(GetLeaderResult::NoLeader),
+        };
+        let (holder, payload) = match (
+            lease.holder(Utc::now()),
+            lease.metadata.annotations.get(PAYLOAD_ANNOTATION),
+        ) {
+            (Some(holder), Some(payload)) => (holder.to_owned(), payload.clone()),
+            // The leader may have changed but if no one holds the lease then that leader is dead.
+            _ => return Ok(GetLeaderResult::NoLeader),
+        };
+
+        let mut last_leader = self.last_leader.write();
+        if last_leader
+            .as_ref()
+            .map_or(false, |(h, p)| *h == holder && *p == payload)
+        {
+            return Ok(GetLeaderResult::Unchanged);
+        }
+        let leader = serde_json::from_str(&payload)?;
+        *last_leader = Some((holder, payload));
+        Ok(GetLeaderResult::NewLeader(leader))
+    }
+
+    fn can_watch(&self) -> bool {
+        false
+    }
+
+    async fn watch_leader(&self) -> ReadySetResult<()> {
+        internal!("KubernetesAuthority does not support `watch_leader`.");
+    }
+
+    async fn watch_workers(&self) -> ReadySetResult<()> {
+        internal!("KubernetesAuthority does not support `watch_workers`.");
+    }
+
+    async fn try_read<P: DeserializeOwned>(&self, path: &str) -> ReadySetResult<Option<P>> {
+        match self.read_key(path).await? {
+            (_, Some(bytes)) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            (_, None) => Ok(None),
+        }
+    }
+
+    async fn read_modify_write<F, P, E>(&self, path: &str, mut f: F) -> ReadySetResult<Result<P, E>>
+    where
+        F: Send + FnMut(Option<P>) -> Result<P, E>,
+        P: Send + Serialize + DeserializeOwned,
+        E: Send,
+    {
+        loop {
+            let (config_map, current_val) = self.read_key(path).await?;
+            let current_val = current_val
+                .map(|bytes| serde_json::from_slice(&bytes))
+                .transpose()?;
+
+            let modified = match f(current_val) {
+                Ok(modified) => modified,
+                Err(e) => return Ok(Err(e)),
+            };
+            let mut config_map = config_map
+                .unwrap_or_else(|| ConfigMap::new(self.metadata(self.name(KEYS_NAME), None)));
+            config_map.set(path_to_key(path), &serde_json::to_vec(&modified)?);
+
+            // Creating the ConfigMap fails if someone else created it first, and replacing it fails
+            // if someone else modified it since we read it; either way we need to try again
+            let written = match config_map.metadata.resource_version {
+                Some(_) => self.write_config_map(&config_map).await?,
+                None => self
+                    .create(CONFIG_MAPS_API, "configmaps", &config_map)
+                    .await?
+                    .is_ok(),
+            };
+            if written {
+                return Ok(Ok(modified));
+            }
+        }
+    }
+
+    async fn update_controller_state<F, U, P, E>(
+        &self,
+        mut f: F,
+        _: U,
+    ) -> ReadySetResult<Result<P, E>>
+    where
+        F: Send + FnMut(Option<P>) -> Result<P, E>,
+        U: Send,
+        P: Send + Serialize + DeserializeOwned,
+        E: Send,
+    {
+        self.ensure_leader().await?;
+
+        let current_value = self.get_controller_state_value().await?;
+        let (current_state, current_value) = match current_value {
+            Some(v) => self.get_controller_state(v).await?,
+            None => (None, None),
+        };
+
+        match f(current_state) {
+            Ok(r) => {
+                let (new_value, r) = self.write_controller_state(current_value, r).await?;
+                self.write_controller_state_value(new_value).await?;
+                Ok(Ok(r))
+            }
+            Err(e) => Ok(Err(e)),
+        }
+    }
+
+    async fn overwrite_controller_state<P>(&self, state: P) -> ReadySetResult<()>
+    where
+        P: Send + Serialize + 'static,
+    {
+        self.ensure_leader().await?;
+
+        let current_value = self.get_controller_state_value().await?;
+        let (new_value, _) = self.write_controller_state(current_value, state).await?;
+        self.write_controller_state_value(new_value).await?;
+        Ok(())
+    }
+
+    async fn try_read_raw(&self, path: &str) -> ReadySetResult<Option<Vec<u8>>> {
+        match self.read_key(path).await? {
+            (_, Some(bytes)) => Ok(Some(serde_json::from_slice::<Vec<u8>>(&bytes)?)),
+            (_, None) => Ok(None),
+        }
+    }
+
+    async fn register_worker(&self, payload: WorkerDescriptor) -> ReadySetResult<Option<WorkerId>>
+    where
+        WorkerDescriptor: Serialize,
+    {
+        let identity = self.identity()?;
+        let name = self.name(&format!("{WORKER_ROLE}-{identity}"));
+        if !self.acquire_lease(name, WORKER_ROLE, &payload).await? {
+            internal!("Failed to acquire the lease for worker {identity}")
+        }
+        Ok(Some(identity))
+    }
+
+    async fn worker_heartbeat(
+        &self,
+        id: WorkerId,
+    ) -> ReadySetResult<AuthorityWorkerHeartbeatResponse> {
+        set_failpoint!(failpoints::AUTHORITY, |_| {
+            Ok(AuthorityWorkerHeartbeatResponse::Failed)
+        });
+
+        // Renew every lease held by this identity, failing if we've lost any of them
+        let mut renewed = false;
+        for role in [CONTROLLER_ROLE, WORKER_ROLE, ADAPTER_ROLE] {
+            let leases = match self.list_leases(role, Some(&id)).await {
+                Ok(leases) => leases,
+                Err(e) => {
+                    error!("Authority failed to heartbeat: {}", e.to_string());
+                    return Ok(AuthorityWorkerHeartbeatResponse::Failed);
+                }
+            };
+            for lease in leases {
+                match self.renew_lease(lease, &id).await {
+                    Ok(true) => renewed = true,
+                    Ok(false) => {
+                        error!("Authority failed to heartbeat: lease expired");
+                        return Ok(AuthorityWorkerHeartbeatResponse::Failed);
+                    }
+                    Err(e) => {
+                        error!("Authority failed to heartbeat: {}", e.to_string());
+                        return Ok(AuthorityWorkerHeartbeatResponse::Failed);
+                    }
+                }
+            }
+        }
+
+        Ok(if renewed {
+            AuthorityWorkerHeartbeatResponse::Alive
+        } else {
+            AuthorityWorkerHeartbeatResponse::Failed
+        })
+    }
+
+    async fn get_workers(&self) -> ReadySetResult<HashSet<WorkerId>> {
+        set_failpoint!(failpoints::AUTHORITY, |_| internal!(
+            "authority->server failure injected"
+        ));
+
+        let now = Utc::now();
+        Ok(self
+            .list_leases(WORKER_ROLE, None)
+            .await?
+            .iter()
+            .filter_map(|lease| lease.holder(now).map(|h| h.to_owned()))
+            .collect())
+    }
+
+    async fn worker_data(
+        &self,
+        worker_ids: Vec<WorkerId>,
+    ) -> ReadySetResult<HashMap<WorkerId, WorkerDescriptor>> {
+        set_failpoint!(failpoints::AUTHORITY, |_| internal!(
+            "authority->server failure injected"
+        ));
+
+        let mut worker_descriptors: HashMap<WorkerId, WorkerDescriptor> = HashMap::new();
+
+        for w in worker_ids {
+            let name = self.name(&format!("{WORKER_ROLE}-{w}"));
+            let descriptor = self
+                .get_lease(&name)
+                .await?
+                .map(|lease| lease.payload())
+                .transpose()?
+                .flatten()
+                .ok_or_else(|| internal_err!("Missing worker descriptor for {w}"))?;
+            worker_descriptors.insert(w, descriptor);
+        }
+
+        Ok(worker_descriptors)
+    }
+
+    async fn register_adapter(&self, endpoint: SocketAddr) -> ReadySetResult<Option<AdapterId>> {
+        set_failpoint!(failpoints::AUTHORITY, |_| internal!(
+            "authority->server failure injected"
+        ));
+
+        let identity = self.identity()?;
+        let name = self.name(&format!("{ADAPTER_ROLE}-{identity}"));
+        if !self.acquire_lease(name, ADAPTER_ROLE, &endpoint).await? {
+            internal!("Failed to acquire the lease for adapter {identity}")
+        }
+        Ok(Some(identity))
+    }
+
+    async fn get_adapters(&self) -> ReadySetResult<HashSet<SocketAddr>> {
+        set_failpoint!(failpoints::AUTHORITY, |_| internal!(
+            "authority->server failure injected"
+        ));
+
+        Ok(self
+            .held_lease_payloads::<SocketAddr>(ADAPTER_ROLE)
+            .await?
+            .into_values()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lease(holder: Option<&str>, renewed_secs_ago: i64) -> Lease {
+        let mut lease = Lease::new(ObjectMeta {
+            name: "d-controller".to_owned(),
+            ..Default::default()
+        });
+        lease.spec = LeaseSpec {
+            holder_identity: holder.map(|h| h.to_owned()),
+            lease_duration_seconds: Some(LEASE_DURATION.as_secs()),
+            acquire_time: None,
+            renew_time: Some(Utc::now() - chrono::Duration::seconds(renewed_secs_ago)),
+            lease_transitions: None,
+        };
+        lease
+    }
+
+    #[test]
+    fn lease_holder() {
+        let now = Utc::now();
+        assert_eq!(lease(Some("a"), 1).holder(now), Some("a"));
+        assert_eq!(lease(Some("a"), 60).holder(now), None);
+        assert_eq!(lease(None, 1).holder(now), None);
+        assert_eq!(lease(Some(""), 1).holder(now), None);
+    }
+
+    #[test]
+    fn acquire_lease() {
+        let now = Utc::now();
+        let mut lease = lease(Some("a"), 60);
+        lease.acquire("b", "{}".to_owned(), now);
+        assert_eq!(lease.holder(now), Some("b"));
+        assert_eq!(lease.spec.lease_transitions, Some(1));
+        assert_eq!(lease.metadata.labels[HOLDER_LABEL], "b");
+        assert_eq!(
+            lease.payload::<serde_json::Value>().unwrap(),
+            Some(serde_json::json!({}))
+        );
+
+        // Reacquiring a lease we already hold isn't a transition
+        lease.acquire("b", "{}".to_owned(), now);
+        assert_eq!(lease.spec.lease_transitions, Some(1));
+    }
+
+    #[test]
+    fn lease_serialization() {
+        let mut lease = lease(Some("a"), 0);
+        lease.spec.renew_time = Some(
+            DateTime::parse_from_rfc3339("2023-01-02T03:04:05.123456789Z")
+                .unwrap()
+                .into(),
+        );
+        let json = serde_json::to_value(&lease).unwrap();
+        assert_eq!(json["apiVersion"], "coordination.k8s.io/v1");
+        assert_eq!(json["spec"]["holderIdentity"], "a");
+        assert_eq!(json["spec"]["renewTime"], "2023-01-02T03:04:05.123456Z");
+
+        let rt: Lease = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            rt.spec.renew_time.unwrap().timestamp_micros(),
+            lease.spec.renew_time.unwrap().timestamp_micros()
+        );
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(object_name("My_Deployment"), "my-deployment");
+        assert_eq!(path_to_key("persistent/stats"), "persistent.stats");
+        assert_eq!(path_to_key("a b"), "a_b");
+    }
+
+    #[test]
+    fn config_map_data() {
+        let mut config_map = ConfigMap::new(ObjectMeta::default());
+        assert_eq!(config_map.get(VALUE_KEY).unwrap(), None);
+        config_map.set(VALUE_KEY.to_owned(), &[0, 1, 255]);
+        assert_eq!(config_map.get(VALUE_KEY).unwrap(), Some(vec![0, 1, 255]));
+    }
+}
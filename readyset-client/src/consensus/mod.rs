@@ -1,26 +1,30 @@
-//! Trait for interacting with an conensus system (Consul, etcd) to determine
+//! Trait for interacting with an conensus system (Consul, Kubernetes) to determine
 //! which ReadySet worker acts as the controller, which ReadySet workers exist, detecting failed
 //! workers which necessitate changes, and storing cluster wide global state.
 
 use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Display};
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use anyhow::anyhow;
 use async_trait::async_trait;
-use clap::ValueEnum;
+use clap::{Args, ValueEnum};
 use enum_dispatch::enum_dispatch;
-use readyset_errors::ReadySetResult;
+use readyset_errors::{internal_err, ReadySetResult};
+use readyset_util::redacted::RedactedString;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
 mod consul;
+mod kubernetes;
 mod local;
 mod standalone;
 
 pub use self::consul::ConsulAuthority;
+pub use self::kubernetes::KubernetesAuthority;
 pub use self::local::{LocalAuthority, LocalAuthorityStore};
 pub use self::standalone::StandaloneAuthority;
 use crate::ControllerDescriptor;
@@ -82,6 +86,10 @@ pub struct WorkerSchedulingConfig {
     /// Configuration for how domains containing or not containing reader nodes may be scheduled
     /// onto this worker
     pub reader_nodes: NodeTypeSchedulingRestriction,
+    /// Label for the version of ReadySet running on this worker, if any. While a canary of a
+    /// version is in progress, workers with that version label only run the domains being
+    /// canaried, and those domains only run on workers with that version label.
+    pub version: Option<String>,
 }
 
 /// Initial registration request body, sent from workers to controllers.
@@ -237,14 +245,16 @@ pub enum Authority {
     ConsulAuthority,
     LocalAuthority,
     StandaloneAuthority,
+    KubernetesAuthority,
 }
 
 /// Enum that mirrors Authority that parses command line arguments.
-#[derive(Clone, Debug, Eq, PartialEq, ValueEnum)]
+#[derive(Clone, Debug, Eq, PartialEq, ValueEnum, Serialize, Deserialize)]
 pub enum AuthorityType {
     Consul,
     Local,
     Standalone,
+    Kubernetes,
 }
 
 impl FromStr for AuthorityType {
@@ -254,6 +264,7 @@ impl FromStr for AuthorityType {
             "consul" => Ok(AuthorityType::Consul),
             "local" => Ok(AuthorityType::Local),
             "standalone" => Ok(AuthorityType::Standalone),
+            "kubernetes" => Ok(AuthorityType::Kubernetes),
             other => Err(anyhow!("Invalid authority type: {}", other)),
         }
     }
@@ -265,21 +276,95 @@ impl Display for AuthorityType {
             AuthorityType::Consul => write!(f, "consul"),
             AuthorityType::Local => write!(f, "local"),
             AuthorityType::Standalone => write!(f, "standalone"),
+            AuthorityType::Kubernetes => write!(f, "kubernetes"),
         }
     }
 }
 
 impl AuthorityType {
     pub async fn to_authority(&self, addr: &str, deployment: &str) -> Authority {
+        self.to_authority_with_options(addr, deployment, &AuthorityOptions::default())
+            .await
+    }
+
+    /// Construct an authority of this type, authenticating with it and connecting to it over TLS
+    /// as configured by `options`.
+    pub async fn to_authority_with_options(
+        &self,
+        addr: &str,
+        deployment: &str,
+        options: &AuthorityOptions,
+    ) -> Authority {
         match self {
             AuthorityType::Consul => Authority::from(
-                ConsulAuthority::new(&format!("http://{}/{}", addr, deployment)).unwrap(),
+                ConsulAuthority::new_with_options(&format!("{}/{}", addr, deployment), options)
+                    .unwrap(),
             ),
             AuthorityType::Local => Authority::from(LocalAuthority::new()),
             AuthorityType::Standalone => {
                 Authority::from(StandaloneAuthority::new(addr, deployment).unwrap())
             }
+            AuthorityType::Kubernetes => Authority::from(
+                KubernetesAuthority::new_with_options(addr, deployment, options).unwrap(),
+            ),
+        }
+    }
+}
+
+/// Command-line options for authenticating with the authority, and connecting to it over TLS.
+///
+/// This option struct is intended to be embedded inside of a larger option struct using
+/// `#[clap(flatten)]`. A token or `https://` scheme given in the authority address itself (eg
+/// `https://<token>@consul:8501`) takes precedence over these options.
+#[derive(Args, Debug, Clone, Default)]
+#[group(skip)]
+pub struct AuthorityOptions {
+    /// Token to authenticate with the authority.
+    ///
+    /// With `--authority consul` this is a Consul ACL token. With `--authority kubernetes` this is
+    /// a bearer token used instead of the token of the pod's service account.
+    #[clap(long, env = "AUTHORITY_TOKEN")]
+    pub authority_token: Option<RedactedString>,
+
+    /// Connect to the authority over TLS.
+    #[clap(long, env = "AUTHORITY_TLS")]
+    pub authority_tls: bool,
+
+    /// Path to a PEM-encoded certificate authority to verify the authority's certificate against,
+    /// in addition to the system's root certificates. Implies `--authority-tls`.
+    #[clap(long, env = "AUTHORITY_TLS_CA")]
+    pub authority_tls_ca: Option<PathBuf>,
+
+    /// Don't verify the authority's TLS certificate. This is insecure, and should only be used
+    /// for testing.
+    #[clap(long, env = "AUTHORITY_TLS_SKIP_VERIFY")]
+    pub authority_tls_skip_verify: bool,
+}
+
+impl AuthorityOptions {
+    /// Returns true if connections to the authority should use TLS
+    pub fn tls(&self) -> bool {
+        self.authority_tls || self.authority_tls_ca.is_some()
+    }
+
+    /// Returns a builder for an HTTP client that verifies the authority's certificate as
+    /// configured by these options
+    pub(crate) fn http_client_builder(&self) -> ReadySetResult<reqwest::ClientBuilder> {
+        let mut builder =
+            reqwest::Client::builder().danger_accept_invalid_certs(self.authority_tls_skip_verify);
+        if let Some(path) = &self.authority_tls_ca {
+            let pem = std::fs::read(path).map_err(|e| {
+                internal_err!(
+                    "Failed to read authority CA certificate {}: {e}",
+                    path.display()
+                )
+            })?;
+            builder = builder.add_root_certificate(
+                reqwest::Certificate::from_pem(&pem)
+                    .map_err(|e| internal_err!("Invalid authority CA certificate: {e}"))?,
+            );
         }
+        Ok(builder)
     }
 }
 
@@ -36,8 +36,8 @@ const STATE_KEY: &str = "state";
 const LEADER_UPDATE_PERIOD: Duration = Duration::from_secs(1);
 
 type SharedStateHandle = Arc<SharedState>;
-/// A shared state store, that maps between a deployment name to its state
-type SharedStore = Arc<Mutex<HashMap<String, SharedStateHandle>>>;
+/// A shared state store, that maps from the path of a deployment's authority database to its state
+type SharedStore = Arc<Mutex<HashMap<PathBuf, SharedStateHandle>>>;
 /// A singleton shared state store for the process, since all readyset workers inside a given
 /// process are supposed to have access to the same shared state, it is implemented as a static.
 static SHARED_STORE: OnceLock<SharedStore> = OnceLock::new();
@@ -62,20 +62,23 @@ impl StandaloneAuthority {
         // Open or create a database at path, with the name ${deployment}.auth. This will ensure
         // that the database has the same prefix as base tables, so convenient to remove, while
         // avoiding accidental name collision.
-        let state = match db_store.entry(deployment.to_string()) {
+        let dir = PathBuf::from(path);
+        if !dir.is_dir() {
+            std::fs::create_dir_all(&dir)?;
+        }
+        let mut path = dir.canonicalize()?;
+        path.push(deployment);
+        path.set_extension("auth");
+
+        // Key the store by the database path rather than just the deployment name, so that
+        // deployments with the same name in different directories don't share state
+        let state = match db_store.entry(path) {
             Entry::Occupied(e) => e.get().clone(),
             Entry::Vacant(e) => {
-                let mut path: PathBuf = path.into();
-                if !path.is_dir() {
-                    std::fs::create_dir_all(&path)?;
-                }
-                path.push(deployment);
-                path.set_extension("auth");
-
                 let mut options = rocksdb::Options::default();
                 options.create_if_missing(true);
                 options.set_compression_type(rocksdb::DBCompressionType::Lz4);
-                let db = DB::open(&options, path)?;
+                let db = DB::open(&options, e.key())?;
 
                 let new_state = SharedState {
                     db: RwLock::new(db),
@@ -405,6 +408,32 @@ mod tests {
         assert_eq!(workers.len(), 0);
     }
 
+    #[tokio::test]
+    async fn separate_directories() {
+        let dir1 = tempdir().unwrap();
+        let dir2 = tempdir().unwrap();
+
+        let authority1 =
+            StandaloneAuthority::new(dir1.path().to_str().unwrap(), "separate_directories")
+                .unwrap();
+        let authority2 =
+            StandaloneAuthority::new(dir2.path().to_str().unwrap(), "separate_directories")
+                .unwrap();
+
+        authority1
+            .read_modify_write("/a", |_: Option<u32>| -> Result<u32, u32> { Ok(1) })
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(authority2.try_read::<u32>("/a").await.unwrap().is_none());
+
+        // Opening the same directory again shares state with the existing authority
+        let authority3 =
+            StandaloneAuthority::new(dir1.path().to_str().unwrap(), "separate_directories")
+                .unwrap();
+        assert_eq!(authority3.try_read::<u32>("/a").await.unwrap(), Some(1));
+    }
+
     #[tokio::test]
     async fn overwrite_controller_state() {
         let dir = tempdir().unwrap();
@@ -2,18 +2,23 @@ use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::future::Future;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 
 use futures_util::future;
 use hyper::client::HttpConnector;
+use hyper_rustls::HttpsConnector;
 use nom_sql::{Relation, SelectStatement};
 use parking_lot::RwLock;
 use petgraph::graph::NodeIndex;
+use readyset_data::DfValue;
 use readyset_errors::{
-    internal, internal_err, rpc_err, rpc_err_no_downcast, ReadySetError, ReadySetResult,
+    internal, internal_err, rpc_err, rpc_err_no_downcast, unsupported, ReadySetError,
+    ReadySetResult,
 };
+use readyset_util::redacted::RedactedString;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use tower::buffer::Buffer;
@@ -23,21 +28,30 @@ use tracing::{debug, trace};
 use url::Url;
 
 use crate::consensus::{Authority, AuthorityControl};
-use crate::debug::info::GraphInfo;
+use crate::debug::canary::CanaryReport;
+use crate::debug::info::{DomainPlacement, GraphInfo, ViewPlan};
+use crate::debug::skew::ReaderSkewReport;
 use crate::debug::stats;
+use crate::durability::{DurabilityMode, RocksDbTuning};
+use crate::internal::{DomainIndex, ReplicaAddress};
 use crate::metrics::MetricsDump;
 use crate::recipe::changelist::ChangeList;
 use crate::recipe::{ExtendRecipeResult, ExtendRecipeSpec, MigrationStatus};
 use crate::replication::ReplicationOffsets;
-use crate::status::ReadySetStatus;
+use crate::status::{ReadySetStatus, StandbyState};
 use crate::table::{Table, TableBuilder, TableRpc};
 use crate::view::{View, ViewBuilder, ViewRpc};
-use crate::{NodeSize, ReplicationOffset, TableStatus, ViewCreateRequest, ViewFilter, ViewRequest};
+use crate::{
+    KeyComparison, NodeSize, ReplicationOffset, TableStatus, ViewCreateRequest, ViewFilter,
+    ViewRequest,
+};
 
 mod rpc;
 
 const EXTEND_RECIPE_POLL_INTERVAL: Duration = Duration::from_secs(1);
 const WAIT_FOR_ALL_TABLES_TO_COMPACT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// The maximum number of keys to replay into a view at once in [`ReadySetHandle::warm_up_view`]
+const WARM_UP_BATCH_SIZE: usize = 1000;
 
 /// Describes a running controller instance.
 ///
@@ -51,10 +65,12 @@ pub struct ControllerDescriptor {
 
 struct Controller {
     authority: Arc<Authority>,
-    client: hyper::Client<hyper::client::HttpConnector>,
+    client: hyper::Client<HttpsConnector<HttpConnector>>,
     /// The last valid leader URL seen by this service. Used to circumvent requests to Consul in
     /// the happy-path.
     leader_url: Arc<RwLock<Option<Url>>>,
+    /// Token to authenticate requests to the controller with, if the controller requires one
+    auth_token: Option<RedactedString>,
 }
 
 #[derive(Debug)]
@@ -93,6 +109,7 @@ impl Service<ControllerRequest> for Controller {
         let client = self.client.clone();
         let auth = self.authority.clone();
         let leader_url = self.leader_url.clone();
+        let auth_token = self.auth_token.clone();
         let request_timeout = req.timeout.unwrap_or(Duration::MAX);
         let path = req.path;
         let body = req.request;
@@ -124,7 +141,14 @@ impl Service<ControllerRequest> for Controller {
                 //             (if you try and use the `url` directly instead of stringifying)
                 #[allow(clippy::unwrap_used)]
                 let string_url = url.as_ref().unwrap().join(path)?.to_string();
-                let r = hyper::Request::post(string_url)
+                let mut r = hyper::Request::post(string_url);
+                if let Some(auth_token) = &auth_token {
+                    r = r.header(
+                        hyper::header::AUTHORIZATION,
+                        format!("Bearer {}", auth_token.as_str()),
+                    );
+                }
+                let r = r
                     .body(hyper::Body::from(body.clone()))
                     .map_err(|e| internal_err!("http request failed: {}", e))?;
 
@@ -164,6 +188,9 @@ impl Service<ControllerRequest> for Controller {
                         let err: ReadySetError = bincode::deserialize(&body)?;
                         return Err(err);
                     }
+                    hyper::StatusCode::UNAUTHORIZED | hyper::StatusCode::FORBIDDEN => {
+                        return Err(ReadySetError::Unauthorized { path: path.into() });
+                    }
                     s => {
                         last_error_desc = Some(format!("got status {}", s));
                         if s == hyper::StatusCode::SERVICE_UNAVAILABLE {
@@ -223,15 +250,34 @@ impl Clone for ReadySetHandle {
 const CONTROLLER_BUFFER_SIZE: usize = 8;
 
 impl ReadySetHandle {
+    /// Create a [`ReadySetHandle`] that bootstraps a connection to ReadySet via the configuration
+    /// stored in the given `authority`, authenticating requests to the controller with
+    /// `auth_token` if given.
     pub fn make(
         authority: Arc<Authority>,
         request_timeout: Option<Duration>,
         migration_timeout: Option<Duration>,
+        auth_token: Option<RedactedString>,
     ) -> Self {
         // need to use lazy otherwise current executor won't be known
         let tracer = tracing::dispatcher::get_default(|d| d.clone());
         let mut http_connector = HttpConnector::new();
         http_connector.set_connect_timeout(request_timeout);
+        http_connector.enforce_http(false);
+        // The controller is only served over https if internal TLS is enabled, in which case its
+        // URL (as stored in the authority) will have an `https` scheme
+        let tls_config = match crate::tls::internal() {
+            Some(tls) => (*tls.client_config()).clone(),
+            None => rustls::ClientConfig::builder()
+                .with_safe_defaults()
+                .with_root_certificates(rustls::RootCertStore::empty())
+                .with_no_client_auth(),
+        };
+        let connector = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_tls_config(tls_config)
+            .https_or_http()
+            .enable_http2()
+            .wrap_connector(http_connector);
         ReadySetHandle {
             views: Default::default(),
             domains: Default::default(),
@@ -244,8 +290,9 @@ impl ReadySetHandle {
                         .http2_keep_alive_timeout(
                             request_timeout.unwrap_or(Duration::from_secs(20)),
                         )
-                        .build(http_connector),
+                        .build(connector),
                     leader_url: Arc::new(RwLock::new(None)),
+                    auth_token,
                 },
                 CONTROLLER_BUFFER_SIZE,
             ),
@@ -277,7 +324,7 @@ impl ReadySetHandle {
     /// stored in the given `authority`. Assigns the authority no timeouts for requests and
     /// migrations.
     pub async fn new<I: Into<Arc<Authority>>>(authority: I) -> Self {
-        Self::make(authority.into(), None, None)
+        Self::make(authority.into(), None, None, None)
     }
 
     /// Create a [`ReadySetHandle`] that bootstraps a connection to ReadySet via the configuration
@@ -287,7 +334,7 @@ impl ReadySetHandle {
         request_timeout: Option<Duration>,
         migration_timeout: Option<Duration>,
     ) -> Self {
-        Self::make(authority.into(), request_timeout, migration_timeout)
+        Self::make(authority.into(), request_timeout, migration_timeout, None)
     }
 
     async fn simple_get_request<R>(&mut self, path: &'static str) -> ReadySetResult<R>
@@ -364,6 +411,18 @@ impl ReadySetHandle {
             .await
     }
 
+    /// Returns the plan for reading from the view for the given query, including the chain of
+    /// dataflow nodes it reads from and how each of them is materialized, or `None` if the query
+    /// is not cached.
+    pub async fn view_plan(
+        &mut self,
+        query: ViewCreateRequest,
+        dialect: dataflow_expression::Dialect,
+    ) -> ReadySetResult<Option<ViewPlan>> {
+        self.rpc("view_plan", (query, dialect), self.request_timeout)
+            .await
+    }
+
     /// Obtain a `View` that allows you to query the given external view.
     ///
     /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
@@ -526,11 +585,69 @@ impl ReadySetHandle {
         self.rpc("get_statistics", (), self.request_timeout)
     }
 
-    /// Flush all partial state, evicting all rows present.
+    /// Flush all partial state, evicting all rows present, or only the partial state that the view
+    /// with the given name reads from if one is given. Returns the number of bytes evicted.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn flush_partial(
+        &mut self,
+        view: Option<Relation>,
+    ) -> impl Future<Output = ReadySetResult<u64>> + '_ {
+        self.rpc("flush_partial", view, self.request_timeout)
+    }
+
+    /// Evict the given keys from the reader for the view with the given name, so that subsequent
+    /// reads of those keys are replayed from the dataflow graph rather than served from the
+    /// reader. Keys are given in the same form as for lookups against the view, and keys which are
+    /// not present in the reader are ignored.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn invalidate_keys(
+        &mut self,
+        view: Relation,
+        keys: Vec<KeyComparison>,
+    ) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc("invalidate_keys", (view, keys), self.request_timeout)
+    }
+
+    /// Replay the given keys into the reader for the view with the given name, so that the first
+    /// reads of those keys are served from the reader rather than having to wait for upqueries.
+    /// Keys are given in the same form as for lookups against the view. Returns the number of keys
+    /// replayed.
+    ///
+    /// Keys are replayed in batches, each of which waits until all of its keys have been filled in
+    /// the reader before the next is sent, so warming up a view doesn't flood the dataflow graph
+    /// with upqueries.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub async fn warm_up_view(
+        &mut self,
+        view: Relation,
+        keys: Vec<KeyComparison>,
+    ) -> ReadySetResult<usize> {
+        let mut reader = match self.view(view).await? {
+            View::Single(reader) => reader,
+            View::MultipleReused(_) => {
+                unsupported!("Warming up a view which reuses other caches is not supported")
+            }
+        };
+        for batch in keys.chunks(WARM_UP_BATCH_SIZE) {
+            reader.multi_lookup(batch.to_vec(), true).await?;
+        }
+        Ok(keys.len())
+    }
+
+    /// Evict all partially materialized state derived from the rows of the given base table with
+    /// the given primary keys, so that subsequent reads of that state are replayed. This is
+    /// intended for invalidating cached state after rows are written to out-of-band.
     ///
     /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
-    pub fn flush_partial(&mut self) -> impl Future<Output = ReadySetResult<()>> + '_ {
-        self.rpc("flush_partial", (), self.request_timeout)
+    pub fn invalidate_table_keys(
+        &mut self,
+        table: Relation,
+        keys: Vec<Vec<DfValue>>,
+    ) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc("invalidate_table_keys", (table, keys), self.request_timeout)
     }
 
     /// Performs a dry-run migration with the given set of queries.
@@ -670,6 +787,79 @@ impl ReadySetHandle {
         self.rpc("get_info", (), self.request_timeout)
     }
 
+    /// Query the controller for the placement of all domains onto workers, including the shards
+    /// and replicas of each domain and whether they have been manually pinned.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn domain_placement(
+        &mut self,
+    ) -> impl Future<Output = ReadySetResult<Vec<DomainPlacement>>> + '_ {
+        self.rpc("domain_placement", (), self.request_timeout)
+    }
+
+    /// Pin the given replica of a domain shard to the given worker, moving the domain to that
+    /// worker if it is currently running elsewhere.
+    ///
+    /// Moving a domain re-creates it on the target worker and replays its materialized state from
+    /// upstream domains. Domains containing base tables cannot be moved.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn move_domain(
+        &mut self,
+        replica_address: ReplicaAddress,
+        worker: Url,
+    ) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc(
+            "move_domain",
+            (replica_address, worker),
+            self.migration_timeout,
+        )
+    }
+
+    /// Remove a pin previously placed on the given replica of a domain shard by
+    /// [`move_domain`](Self::move_domain). The domain is not moved, but will be placed by the
+    /// scheduler the next time it is (re-)created.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn unpin_domain(
+        &mut self,
+        replica_address: ReplicaAddress,
+    ) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc("unpin_domain", replica_address, self.request_timeout)
+    }
+
+    /// Start a canary of the version of ReadySet with the given version label on the given
+    /// domains, moving one replica of each of their shards onto workers with that version label,
+    /// and all other domains off of those workers. If a canary of the same version is already in
+    /// progress, the domains are added to it.
+    ///
+    /// Domains containing base tables cannot be canaried.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn start_canary(
+        &mut self,
+        version: String,
+        domains: Vec<DomainIndex>,
+    ) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc("start_canary", (version, domains), self.migration_timeout)
+    }
+
+    /// End the canary in progress, if any, lifting its placement restrictions. Domains are not
+    /// moved.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn end_canary(&mut self) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc("end_canary", (), self.request_timeout)
+    }
+
+    /// Return a report comparing the latency and reader key counts of the canary replica of every
+    /// domain shard in the canary in progress with the stable replicas of the same shard.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn canary_report(&mut self) -> impl Future<Output = ReadySetResult<CanaryReport>> + '_ {
+        self.rpc("canary_report", (), self.request_timeout)
+    }
+
     /// Remove the given external view from the graph.
     ///
     /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
@@ -726,6 +916,88 @@ impl ReadySetHandle {
         self.rpc("snapshotting_tables", (), self.request_timeout)
     }
 
+    /// Trigger a manual compaction of the persisted state of the given base table, to drop any
+    /// deletion tombstones that have accumulated in it. The compaction runs in the background.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn compact_table(
+        &mut self,
+        table: Relation,
+    ) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc("compact_table", table, self.request_timeout)
+    }
+
+    /// Schedule the persisted state of the given base table to be compacted every `interval`, or
+    /// disable scheduled compactions for the table if `interval` is `None`.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn set_table_compaction_interval(
+        &mut self,
+        table: Relation,
+        interval: Option<Duration>,
+    ) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc(
+            "set_table_compaction_interval",
+            (table, interval),
+            self.request_timeout,
+        )
+    }
+
+    /// Override the durability mode of the base table with the given name, which must not exist
+    /// yet, in place of the server's global durability mode.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn set_table_durability(
+        &mut self,
+        table: Relation,
+        mode: DurabilityMode,
+    ) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc("set_table_durability", (table, mode), self.request_timeout)
+    }
+
+    /// Returns the [`StandbyState`] of this deployment, or `None` if it is not running as a
+    /// standby.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn standby_state(
+        &mut self,
+    ) -> impl Future<Output = ReadySetResult<Option<StandbyState>>> + '_ {
+        self.rpc("standby_state", (), self.request_timeout)
+    }
+
+    /// Promote this deployment from a standby to a primary, which starts replicating from the
+    /// upstream database from the point the standby has caught up to. The previous primary should
+    /// be stopped (or have its standby configuration removed) first.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn promote(&mut self) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc("promote", (), self.request_timeout)
+    }
+
+    /// Apply RocksDB tuning parameters (block cache size, compression, and write buffer size) to
+    /// the persisted state of the given base table. Parameters left unset in `tuning` retain their
+    /// previous values.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn set_table_tuning(
+        &mut self,
+        table: Relation,
+        tuning: RocksDbTuning,
+    ) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc("set_table_tuning", (table, tuning), self.request_timeout)
+    }
+
+    /// Back up the deployment to the given directory, by creating a RocksDB checkpoint of the
+    /// persisted state of every base table and writing out the controller's dataflow state. The
+    /// directory is created on the servers running the base tables, and can be restored by
+    /// starting a new deployment with `--restore-from`. Returns the number of base tables backed
+    /// up.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn backup(&mut self, dir: PathBuf) -> impl Future<Output = ReadySetResult<usize>> + '_ {
+        self.rpc("backup", dir, self.request_timeout)
+    }
+
     /// Poll in a loop to wait for all tables to finish compacting
     pub async fn wait_for_all_tables_to_compact(&mut self) -> ReadySetResult<()> {
         while !self
@@ -746,6 +1018,14 @@ impl ReadySetHandle {
         self.rpc("node_sizes", (), self.request_timeout)
     }
 
+    /// Return a report of the distribution of keys and reads across the shards of every sharded
+    /// reader, flagging shards which hold or serve much more than their fair share.
+    pub fn reader_skew_report(
+        &mut self,
+    ) -> impl Future<Output = ReadySetResult<ReaderSkewReport>> + '_ {
+        self.rpc("reader_skew", (), self.request_timeout)
+    }
+
     /// Return whether the leader is ready or not.
     pub fn leader_ready(&mut self) -> impl Future<Output = ReadySetResult<bool>> + '_ {
         self.rpc("leader_ready", (), self.request_timeout)
@@ -780,6 +1060,32 @@ impl ReadySetHandle {
         self.rpc("set_memory_limit", (period, limit), self.request_timeout)
     }
 
+    /// Set (or, if `limit` is `None`, clear) the memory budget, in bytes, for each replica of each
+    /// shard of the given domain. Domains whose state grows past their budget are evicted from
+    /// until they're back under it, and domains with budgets are only evicted from to enforce the
+    /// global memory limit if there's nothing else to evict from.
+    pub fn set_domain_memory_limit(
+        &mut self,
+        domain: DomainIndex,
+        limit: Option<usize>,
+    ) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc(
+            "set_domain_memory_limit",
+            (domain, limit),
+            self.request_timeout,
+        )
+    }
+
+    /// Set (or, if `limit` is `None`, clear) the memory budget, in bytes, for the domain containing
+    /// the reader for the given view. See [`Self::set_domain_memory_limit`].
+    pub fn set_view_memory_limit(
+        &mut self,
+        view: Relation,
+        limit: Option<usize>,
+    ) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc("set_view_memory_limit", (view, limit), self.request_timeout)
+    }
+
     #[cfg(feature = "failure_injection")]
     /// Set a failpoint with provided name and action
     pub fn failpoint(
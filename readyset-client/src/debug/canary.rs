@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::internal::ReplicaAddress;
+
+/// A canary replica is considered divergent if it spends at least this many times as long
+/// processing as the stable replicas of the same domain shard
+pub const LATENCY_FACTOR: f64 = 1.5;
+
+/// A canary replica is considered divergent if the number of keys materialized in its readers
+/// differs from that of the stable replicas of the same domain shard by at least this fraction
+pub const KEY_DIVERGENCE_THRESHOLD: f64 = 0.1;
+
+/// Replicas are only compared on the basis of key counts if the stable replicas have at least
+/// this many keys, so that small readers aren't reported
+pub const MIN_KEYS: u64 = 1000;
+
+/// Statistics about a single replica of a domain shard, used to compare a canary replica with the
+/// stable replicas of the same shard
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanaryReplicaStats {
+    /// Total thread time spent processing in the replica since it was created, in nanoseconds
+    pub process_time: u64,
+    /// The number of keys materialized in the readers of the replica
+    pub reader_keys: u64,
+    /// The number of reads served by the readers of the replica since it was created
+    pub reads: u64,
+}
+
+/// A comparison of the canary replica of a single domain shard with its stable replicas
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CanaryShard {
+    /// The address of the canary replica
+    pub replica_address: ReplicaAddress,
+    /// The worker running the canary replica
+    pub worker: Url,
+    /// Statistics for the canary replica
+    pub canary: CanaryReplicaStats,
+    /// Statistics for each of the other, stable, replicas of the same domain shard
+    pub stable: Vec<CanaryReplicaStats>,
+}
+
+/// Returns the mean of the values, or `None` if there are no values
+fn mean(values: impl Iterator<Item = u64>) -> Option<f64> {
+    let (count, total) = values.fold((0u64, 0u64), |(count, total), v| (count + 1, total + v));
+    (count > 0).then(|| total as f64 / count as f64)
+}
+
+impl CanaryShard {
+    /// Returns the ratio of the time the canary replica spent processing to the mean time spent
+    /// processing by the stable replicas, or `None` if there are no stable replicas to compare
+    /// against. Every replica of a domain shard processes the same writes, so a canary which is
+    /// as fast as the stable version has a latency ratio of about 1
+    pub fn latency_ratio(&self) -> Option<f64> {
+        let stable = mean(self.stable.iter().map(|s| s.process_time))?;
+        Some(if stable == 0.0 {
+            1.0
+        } else {
+            self.canary.process_time as f64 / stable
+        })
+    }
+
+    /// Returns the difference between the number of keys in the readers of the canary replica and
+    /// the mean number in the readers of the stable replicas, as a fraction of the latter, or
+    /// `None` if there are no stable replicas to compare against.
+    ///
+    /// Partially materialized readers only hold the keys that have been read from them, so key
+    /// counts only diverge significantly for those if the replicas serve very different reads.
+    pub fn key_divergence(&self) -> Option<f64> {
+        let stable = mean(self.stable.iter().map(|s| s.reader_keys))?;
+        Some((self.canary.reader_keys as f64 - stable).abs() / stable.max(1.0))
+    }
+
+    /// Returns true if the canary replica is significantly slower than, or has materialized a
+    /// significantly different number of keys than, the stable replicas
+    pub fn is_divergent(&self) -> bool {
+        let enough_keys = self.stable.iter().any(|s| s.reader_keys >= MIN_KEYS);
+        self.latency_ratio()
+            .map_or(false, |ratio| ratio >= LATENCY_FACTOR)
+            || (enough_keys
+                && self
+                    .key_divergence()
+                    .map_or(false, |div| div >= KEY_DIVERGENCE_THRESHOLD))
+    }
+}
+
+/// A report comparing the canary replicas of every domain shard in a canary of a new ReadySet
+/// version with the stable replicas of the same shards
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CanaryReport {
+    /// The version label being canaried, or `None` if there is no canary in progress
+    pub version: Option<String>,
+    /// A comparison for each canaried domain shard, ordered by domain index and shard
+    pub shards: Vec<CanaryShard>,
+}
+
+impl CanaryReport {
+    /// Returns the canaried domain shards which diverge significantly from their stable replicas
+    pub fn divergent(&self) -> impl Iterator<Item = &CanaryShard> {
+        self.shards.iter().filter(|s| s.is_divergent())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::DomainIndex;
+
+    fn stats(process_time: u64, reader_keys: u64) -> CanaryReplicaStats {
+        CanaryReplicaStats {
+            process_time,
+            reader_keys,
+            reads: 0,
+        }
+    }
+
+    fn shard(canary: CanaryReplicaStats, stable: &[CanaryReplicaStats]) -> CanaryShard {
+        CanaryShard {
+            replica_address: ReplicaAddress {
+                domain_index: DomainIndex::from(1),
+                shard: 0,
+                replica: stable.len(),
+            },
+            worker: "http://127.0.0.1:6033".parse().unwrap(),
+            canary,
+            stable: stable.to_vec(),
+        }
+    }
+
+    #[test]
+    fn matching() {
+        let shard = shard(stats(1050, 5000), &[stats(1000, 5000), stats(1100, 5000)]);
+        assert_eq!(shard.latency_ratio(), Some(1.0));
+        assert_eq!(shard.key_divergence(), Some(0.0));
+        assert!(!shard.is_divergent());
+    }
+
+    #[test]
+    fn slow() {
+        let shard = shard(stats(3000, 5000), &[stats(1000, 5000)]);
+        assert_eq!(shard.latency_ratio(), Some(3.0));
+        assert!(shard.is_divergent());
+    }
+
+    #[test]
+    fn missing_keys() {
+        let shard = shard(stats(1000, 4000), &[stats(1000, 5000)]);
+        assert_eq!(shard.key_divergence(), Some(0.2));
+        assert!(shard.is_divergent());
+    }
+
+    #[test]
+    fn too_few_keys() {
+        let shard = shard(stats(1000, 5), &[stats(1000, 50)]);
+        assert!(!shard.is_divergent());
+    }
+
+    #[test]
+    fn no_stable_replicas() {
+        let shard = shard(stats(1000, 5000), &[]);
+        assert_eq!(shard.latency_ratio(), None);
+        assert_eq!(shard.key_divergence(), None);
+        assert!(!shard.is_divergent());
+    }
+}
@@ -26,3 +26,70 @@ impl Deref for GraphInfo {
         &self.workers
     }
 }
+
+/// Placement of a single replica of a single shard of a domain onto a worker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicaPlacement {
+    /// The worker that is running this replica of the domain shard
+    pub worker: Url,
+    /// Whether this replica has been manually pinned to its worker, rather than being placed by
+    /// the scheduler
+    pub pinned: bool,
+}
+
+/// Information about which workers are running the shards and replicas of a domain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainPlacement {
+    /// The index of the domain
+    pub domain_index: DomainIndex,
+    /// Placement of the domain, indexed by shard index first and replica index second
+    pub shards: Vec<Vec<ReplicaPlacement>>,
+    /// The dataflow nodes in the domain, along with their names
+    pub nodes: Vec<(NodeIndex, String)>,
+    /// Whether the domain contains a base table node.
+    ///
+    /// Domains with base tables own persistent state local to the worker, and so cannot be moved
+    /// to a different worker.
+    pub has_base_table: bool,
+    /// Whether the domain contains a reader node
+    pub has_reader: bool,
+}
+
+/// An index on the materialized state of a node in a [`ViewPlan`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewPlanIndex {
+    /// The type of the index
+    pub index_type: IndexType,
+    /// The names of the columns the index is keyed on
+    pub columns: Vec<String>,
+}
+
+/// A single dataflow node in a [`ViewPlan`]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ViewPlanNode {
+    /// The index of the node in the dataflow graph
+    pub index: NodeIndex,
+    /// The name of the node
+    pub name: String,
+    /// A description of the operation performed by the node
+    pub description: String,
+    /// The domain the node runs in
+    pub domain: DomainIndex,
+    /// The nodes this node reads from which are also part of the plan
+    pub parents: Vec<NodeIndex>,
+    /// How the node's state is materialized
+    pub materialization: MaterializationStatus,
+    /// The indices on the node's materialized state. For reader nodes, this is the index used to
+    /// look up the query's parameters.
+    pub indices: Vec<ViewPlanIndex>,
+}
+
+/// The plan for reading from a view, as returned by `ReadySetHandle::view_plan`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ViewPlan {
+    /// The name of the cache for the query
+    pub name: nom_sql::Relation,
+    /// The dataflow nodes the view reads from, in breadth-first order starting with the view's
+    /// reader node and ending with the base tables it reads from
+    pub nodes: Vec<ViewPlanNode>,
+}
@@ -1,4 +1,8 @@
+/// Types related to comparing canaries of new ReadySet versions with the stable version.
+pub mod canary;
 /// Types related to graph information.
 pub mod info;
+/// Types related to the distribution of keys and reads across the shards of readers.
+pub mod skew;
 /// Types related to graph statistics.
 pub mod stats;
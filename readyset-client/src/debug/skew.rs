@@ -0,0 +1,193 @@
+use nom_sql::Relation;
+use petgraph::graph::NodeIndex;
+use serde::{Deserialize, Serialize};
+
+/// A shard is considered hot if it holds, or serves, at least this many times its fair share of
+/// the keys or reads of its reader
+pub const HOT_SHARD_FACTOR: f64 = 2.0;
+
+/// Shards are only flagged as hot on the basis of key counts or reads if the reader has at least
+/// this many keys or reads in total, so that small or rarely-read readers aren't reported
+pub const MIN_SAMPLES: u64 = 1000;
+
+/// The number of keys in, and reads served by, a single shard of a reader
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReaderShardStats {
+    /// The number of keys materialized in the shard
+    pub key_count: usize,
+    /// The total number of reads served by the shard, across all its replicas, since the reader
+    /// was created
+    pub reads: u64,
+}
+
+/// The distribution of keys and reads across the shards of a single sharded reader
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReaderSkew {
+    /// The index of the reader node in the dataflow graph
+    pub node: NodeIndex,
+    /// The name of the reader
+    pub name: Relation,
+    /// Statistics for each shard of the reader, indexed by shard
+    pub shards: Vec<ReaderShardStats>,
+    /// The shards holding or serving at least [`HOT_SHARD_FACTOR`] times their fair share of keys
+    /// or reads
+    pub hot_shards: Vec<usize>,
+}
+
+/// Returns the number of values, and their sum
+fn count_and_total(values: impl Iterator<Item = u64>) -> (u64, u64) {
+    values.fold((0, 0), |(count, total), v| (count + 1, total + v))
+}
+
+/// Returns the ratio of the largest value to the mean, or 1 if there are no values
+fn skew(values: impl Iterator<Item = u64> + Clone) -> f64 {
+    let (count, total) = count_and_total(values.clone());
+    if total == 0 {
+        return 1.0;
+    }
+    let max = values.max().unwrap_or_default();
+    max as f64 / (total as f64 / count as f64)
+}
+
+/// Returns the indices of the values which are at least [`HOT_SHARD_FACTOR`] times the mean, if
+/// the values sum to at least [`MIN_SAMPLES`]
+fn hot(values: impl Iterator<Item = u64> + Clone) -> impl Iterator<Item = usize> {
+    let (count, total) = count_and_total(values.clone());
+    let threshold = HOT_SHARD_FACTOR * total as f64 / count.max(1) as f64;
+    values
+        .enumerate()
+        .filter(move |(_, v)| total >= MIN_SAMPLES && *v as f64 >= threshold)
+        .map(|(i, _)| i)
+}
+
+impl ReaderSkew {
+    /// Build the skew report for a reader from the statistics for each of its shards
+    pub fn new(node: NodeIndex, name: Relation, shards: Vec<ReaderShardStats>) -> Self {
+        let mut hot_shards = hot(shards.iter().map(|s| s.key_count as u64))
+            .chain(hot(shards.iter().map(|s| s.reads)))
+            .collect::<Vec<_>>();
+        hot_shards.sort_unstable();
+        hot_shards.dedup();
+        Self {
+            node,
+            name,
+            shards,
+            hot_shards,
+        }
+    }
+
+    /// Returns the ratio of the number of keys in the shard with the most keys to the mean number
+    /// of keys per shard. A perfectly balanced reader has a key skew of 1
+    pub fn key_skew(&self) -> f64 {
+        skew(self.shards.iter().map(|s| s.key_count as u64))
+    }
+
+    /// Returns the ratio of the number of reads served by the busiest shard to the mean number of
+    /// reads per shard. A perfectly balanced reader has a read skew of 1
+    pub fn read_skew(&self) -> f64 {
+        skew(self.shards.iter().map(|s| s.reads))
+    }
+
+    /// Returns true if any shard of this reader is hot
+    pub fn is_skewed(&self) -> bool {
+        !self.hot_shards.is_empty()
+    }
+
+    /// Returns a suggestion for how to rebalance this reader, if it's skewed.
+    ///
+    /// Readers aren't resharded automatically, since the shard a key lives in is determined by
+    /// the key itself.
+    pub fn suggestion(&self) -> Option<String> {
+        if !self.is_skewed() {
+            return None;
+        }
+        // A shard is only hot if it has at least `HOT_SHARD_FACTOR` times its fair share of either
+        // keys or reads, so if the keys aren't skewed the reads must be
+        Some(if self.key_skew() >= HOT_SHARD_FACTOR {
+            format!(
+                "The keys of {} are unevenly distributed across its shards. Consider recreating \
+                 the cache keyed on columns with more distinct values",
+                self.name.display_unquoted()
+            )
+        } else {
+            format!(
+                "A small number of keys of {} receive most of its reads. Consider adding \
+                 replicas of the cache to spread out reads of hot keys",
+                self.name.display_unquoted()
+            )
+        })
+    }
+}
+
+/// A report of the distribution of keys and reads across the shards of every sharded reader
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReaderSkewReport {
+    /// The skew of each sharded reader, most read-skewed first
+    pub readers: Vec<ReaderSkew>,
+}
+
+impl ReaderSkewReport {
+    /// Build a report from the skew of each sharded reader
+    pub fn new(mut readers: Vec<ReaderSkew>) -> Self {
+        readers.sort_by(|a, b| b.read_skew().total_cmp(&a.read_skew()));
+        Self { readers }
+    }
+
+    /// Returns the readers with at least one hot shard
+    pub fn skewed(&self) -> impl Iterator<Item = &ReaderSkew> {
+        self.readers.iter().filter(|r| r.is_skewed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reader(shards: &[(usize, u64)]) -> ReaderSkew {
+        ReaderSkew::new(
+            NodeIndex::new(1),
+            "q".into(),
+            shards
+                .iter()
+                .map(|&(key_count, reads)| ReaderShardStats { key_count, reads })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn balanced() {
+        let reader = reader(&[(1000, 5000), (1100, 4800), (900, 5200)]);
+        assert!(!reader.is_skewed());
+        assert!(reader.key_skew() < 1.2);
+        assert_eq!(reader.suggestion(), None);
+    }
+
+    #[test]
+    fn hot_keys() {
+        let reader = reader(&[(1000, 1000), (1000, 1000), (1000, 10000), (1000, 1000)]);
+        assert_eq!(reader.hot_shards, [2]);
+        assert!(reader.read_skew() > 3.0);
+        assert!(reader.suggestion().unwrap().contains("replicas"));
+    }
+
+    #[test]
+    fn skewed_keys() {
+        let reader = reader(&[(100, 0), (5000, 0), (100, 0), (100, 0)]);
+        assert_eq!(reader.hot_shards, [1]);
+        assert!(reader.suggestion().unwrap().contains("distinct values"));
+    }
+
+    #[test]
+    fn too_few_samples() {
+        let reader = reader(&[(1, 0), (50, 10), (1, 0)]);
+        assert!(!reader.is_skewed());
+        assert_eq!(reader.key_skew(), 50.0 / (52.0 / 3.0));
+    }
+
+    #[test]
+    fn empty() {
+        let reader = reader(&[(0, 0), (0, 0)]);
+        assert!(!reader.is_skewed());
+        assert_eq!(reader.key_skew(), 1.0);
+    }
+}
@@ -0,0 +1,93 @@
+//! Configuration for the durability and storage of base tables
+
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use nom_sql::CreateTableStatement;
+use readyset_errors::{unsupported, ReadySetResult};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Indicates to what degree updates should be persisted.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, ValueEnum)]
+pub enum DurabilityMode {
+    /// Don't do any durability
+    #[value(alias("memory"))]
+    MemoryOnly,
+    /// Delete any log files on exit. Useful mainly for tests.
+    #[value(alias("ephemeral"))]
+    DeleteOnExit,
+    /// Persist updates to disk, and don't delete them later.
+    #[value(alias("persistent"))]
+    Permanent,
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid durability mode; expected one of persistent, ephemeral, or memory")]
+pub struct InvalidDurabilityMode;
+
+impl FromStr for DurabilityMode {
+    type Err = InvalidDurabilityMode;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "persistent" => Ok(Self::Permanent),
+            "ephemeral" => Ok(Self::DeleteOnExit),
+            "memory" => Ok(Self::MemoryOnly),
+            _ => Err(InvalidDurabilityMode),
+        }
+    }
+}
+
+impl DurabilityMode {
+    /// Returns the durability mode given for the table created by `stmt` in its
+    /// `DURABILITY = '<durability>'` option, if any, overriding the server's global durability
+    /// mode for that table
+    pub fn from_create_table(stmt: &CreateTableStatement) -> ReadySetResult<Option<Self>> {
+        let Some(durability) = stmt.get_durability() else {
+            return Ok(None);
+        };
+        match durability.to_lowercase().parse() {
+            Ok(mode) => Ok(Some(mode)),
+            Err(InvalidDurabilityMode) => unsupported!(
+                "Invalid durability for table {}: {durability}",
+                stmt.table.display_unquoted()
+            ),
+        }
+    }
+}
+
+/// Compression algorithms which can be used for the persisted state of base tables
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, ValueEnum)]
+pub enum BaseTableCompression {
+    /// Don't compress
+    None,
+    /// Compress with LZ4. This is the default.
+    Lz4,
+}
+
+/// RocksDB tuning parameters for the persisted state of a base table. Any parameters left unset
+/// use the defaults.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RocksDbTuning {
+    /// The size in bytes of an LRU block cache dedicated to the table. Only used by indices which
+    /// support range queries, since hash indices are stored in plain tables which don't use the
+    /// block cache. If unset, the table shares RocksDB's default block cache.
+    pub block_cache_bytes: Option<usize>,
+    /// The compression algorithm to use for the table
+    pub compression: Option<BaseTableCompression>,
+    /// The size in bytes of each of the table's memtables
+    pub write_buffer_bytes: Option<usize>,
+}
+
+impl RocksDbTuning {
+    /// Returns a new set of tuning parameters, taking each parameter from `self` if it's set and
+    /// from `defaults` otherwise
+    pub fn or(self, defaults: Self) -> Self {
+        Self {
+            block_cache_bytes: self.block_cache_bytes.or(defaults.block_cache_bytes),
+            compression: self.compression.or(defaults.compression),
+            write_buffer_bytes: self.write_buffer_bytes.or(defaults.write_buffer_bytes),
+        }
+    }
+}
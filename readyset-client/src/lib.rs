@@ -249,10 +249,12 @@ pub mod failpoints;
 
 pub mod consistency;
 mod controller;
+pub mod durability;
 pub mod metrics;
 pub mod query;
 pub mod status;
 mod table;
+pub mod tls;
 mod view;
 use std::convert::TryFrom;
 use std::default::Default;
@@ -355,13 +357,14 @@ use url::Url;
 
 pub use crate::consensus::WorkerDescriptor;
 pub use crate::controller::{ControllerDescriptor, ReadySetHandle};
+pub use crate::durability::{BaseTableCompression, DurabilityMode, RocksDbTuning};
 pub use crate::table::{
     Modification, Operation, PacketData, PacketPayload, PacketTrace, Table, TableOperation,
     TableReplicationStatus, TableRequest, TableStatus,
 };
 pub use crate::view::{
-    KeyComparison, LookupResult, ReadQuery, ReadReply, ReadReplyBatch, ReadReplyStats, SchemaType,
-    View, ViewCreateRequest, ViewQuery,
+    KeyComparison, LookupResult, ReadQuery, ReadReply, ReadReplyBatch, ReadReplyStats,
+    ResultLimits, SchemaType, View, ViewCreateRequest, ViewQuery,
 };
 
 pub mod builders {
@@ -524,6 +527,8 @@ pub fn shard_by(dt: &DfValue, shards: usize) -> usize {
         | DfValue::Numeric(_)
         | DfValue::BitVector(_)
         | DfValue::Array(_)
+        | DfValue::Uuid(_)
+        | DfValue::Interval(_)
         | DfValue::PassThrough(_) => {
             use std::hash::{Hash, Hasher};
             let mut hasher = ahash::AHasher::new_with_keys(0x3306, 0x6033);
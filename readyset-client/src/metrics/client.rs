@@ -1,7 +1,8 @@
+use futures::{Stream, StreamExt};
 use readyset_errors::{rpc_err, ReadySetError, ReadySetResult};
 use url::Url;
 
-use crate::metrics::MetricsDump;
+use crate::metrics::{MetricsDump, MetricsStreamEvent, MetricsStreamOptions};
 use crate::ReadySetHandle;
 
 /// A metrics dump tagged with the address it was received from.
@@ -57,6 +58,62 @@ impl MetricsClient {
         })
     }
 
+    /// Subscribes to a stream of updates to the metrics of a single readyset-server in a
+    /// deployment. Each item in the returned stream is the current value of all metrics matching
+    /// the filter in `options`, reconstructed from the deltas sent by the server.
+    pub async fn stream_metrics_for_server(
+        &mut self,
+        url: Url,
+        options: &MetricsStreamOptions,
+    ) -> ReadySetResult<impl Stream<Item = ReadySetResult<MetricsDump>>> {
+        let mut metrics_endpoint = url.join("metrics_stream")?;
+        metrics_endpoint.set_query(Some(&options.to_query()));
+        let res = self
+            .client
+            .get(metrics_endpoint.as_str())
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .map_err(|e| e.into())
+            .map_err(rpc_err!("MetricsClient::stream_metrics_for_server"))?;
+
+        let mut buf = String::new();
+        let mut current: Option<MetricsDump> = None;
+        Ok(res
+            .bytes_stream()
+            .map(move |chunk| -> ReadySetResult<Vec<MetricsDump>> {
+                let chunk = chunk
+                    .map_err(|e| e.into())
+                    .map_err(rpc_err!("MetricsClient::stream_metrics_for_server"))?;
+                buf.push_str(&String::from_utf8_lossy(&chunk));
+
+                let mut dumps = vec![];
+                while let Some(end) = buf.find("\n\n") {
+                    let event = buf.drain(..end + 2).collect::<String>();
+                    match (MetricsStreamEvent::from_sse(&event)?, &mut current) {
+                        (None, _) => continue,
+                        (Some(MetricsStreamEvent::Snapshot(dump)), _) => current = Some(dump),
+                        (Some(MetricsStreamEvent::Delta(delta)), Some(current)) => {
+                            current.apply_delta(delta)
+                        }
+                        (Some(MetricsStreamEvent::Delta(_)), None) => {
+                            return Err(ReadySetError::SerializationFailed(
+                                "Received metrics delta before snapshot".to_owned(),
+                            ))
+                        }
+                    }
+                    dumps.extend(current.clone());
+                }
+                Ok(dumps)
+            })
+            .flat_map(|res| {
+                futures::stream::iter(match res {
+                    Ok(dumps) => dumps.into_iter().map(Ok).collect::<Vec<_>>(),
+                    Err(e) => vec![Err(e)],
+                })
+            }))
+    }
+
     /// Retrieves metrics from each readyset-server in a deployment and aggregates the results
     /// into a single json string.
     pub async fn get_metrics(&mut self) -> ReadySetResult<Vec<TaggedMetricsDump>> {
@@ -10,6 +10,9 @@ use serde::{Deserialize, Serialize};
 
 /// A client for accessing readyset metrics for a deployment.
 pub mod client;
+pub mod stream;
+
+pub use crate::metrics::stream::{MetricsFilter, MetricsStreamEvent, MetricsStreamOptions};
 
 /// Documents the set of metrics that are currently being recorded within
 /// a ReadySet instance.
@@ -123,6 +126,28 @@ pub mod recorded {
     /// | tag | The client tag of the request that the replay is required for. |
     pub const DOMAIN_TOTAL_SEED_REPLAY_TIME: &str = "readyset_domain.total_seed_replay_time_us";
 
+    /// Counter: The number of upqueries (reader replay requests and partial replay requests)
+    /// handled by a domain on behalf of a tenant. Recorded at the domain following
+    /// RequestReaderReplay or RequestPartialReplay packet handling.
+    ///
+    /// | Tag | Description |
+    /// | --- | ----------- |
+    /// | domain | The index of the domain the upquery is recorded in. |
+    /// | shard | The shard the upquery is recorded in. |
+    /// | tenant | The schema of the base tables the upquery reads from, if any. |
+    pub const DOMAIN_TENANT_UPQUERIES: &str = "readyset_domain.tenant_upqueries";
+
+    /// Histogram: The time in microseconds spent handling an upquery on behalf of a tenant.
+    /// Recorded at the domain following RequestReaderReplay or RequestPartialReplay packet
+    /// handling.
+    ///
+    /// | Tag | Description |
+    /// | --- | ----------- |
+    /// | domain | The index of the domain the upquery is recorded in. |
+    /// | shard | The shard the upquery is recorded in. |
+    /// | tenant | The schema of the base tables the upquery reads from, if any. |
+    pub const DOMAIN_TENANT_UPQUERY_TIME: &str = "readyset_domain.tenant_upquery_time_us";
+
     /// Histogram: The time in microseconds that a domain spawning a state
     /// chunker at a node during the processing of a StartReplay packet.
     /// Recorded at the domain when the state chunker thread is finished
@@ -255,6 +280,31 @@ pub mod recorded {
     /// | node | The LocalNodeIndex of the dataflow node. |
     pub const NODE_STATE_SIZE_BYTES: &str = "readyset_node_state_size_bytes";
 
+    /// Gauge: The number of bytes used to store the compressed keys in a dataflow node's partial
+    /// state, when state compression is enabled.
+    ///
+    /// | Tag | Description |
+    /// | --- | ----------- |
+    /// | node | The LocalNodeIndex of the dataflow node. |
+    pub const NODE_STATE_COMPRESSED_BYTES: &str = "readyset_node_state_compressed_bytes";
+
+    /// Counter: The number of lookups of compressed keys in a dataflow node's partial state which
+    /// were served from the node's cache of decompressed keys.
+    ///
+    /// | Tag | Description |
+    /// | --- | ----------- |
+    /// | node | The LocalNodeIndex of the dataflow node. |
+    pub const NODE_STATE_COMPRESSED_CACHE_HITS: &str = "readyset_node_state_compressed_cache_hits";
+
+    /// Counter: The number of lookups of compressed keys in a dataflow node's partial state which
+    /// required decompressing the key.
+    ///
+    /// | Tag | Description |
+    /// | --- | ----------- |
+    /// | node | The LocalNodeIndex of the dataflow node. |
+    pub const NODE_STATE_COMPRESSED_CACHE_MISSES: &str =
+        "readyset_node_state_compressed_cache_misses";
+
     /// Gauge: The sum of the amount of bytes used to store the dataflow node's
     /// partial state within a domain.
     ///
@@ -268,6 +318,23 @@ pub mod recorded {
     /// within a domain.
     pub const READER_STATE_SIZE_BYTES: &str = "readyset_reader_state_size_bytes";
 
+    /// Gauge: The number of keys materialized in a single shard of a reader.
+    ///
+    /// | Tag | Description |
+    /// | --- | ----------- |
+    /// | node | The index of the reader node in the dataflow graph. |
+    /// | shard | The shard of the reader. |
+    pub const READER_SHARD_KEY_COUNT: &str = "readyset_reader_shard_key_count";
+
+    /// Counter: The number of reads served by a single shard of a reader. The rate of this counter
+    /// is the QPS of the shard.
+    ///
+    /// | Tag | Description |
+    /// | --- | ----------- |
+    /// | node | The index of the reader node in the dataflow graph. |
+    /// | shard | The shard of the reader. |
+    pub const READER_SHARD_READS: &str = "readyset_reader_shard_reads";
+
     /// Gauge: The sum of the amount of bytes used to store a node's base tables
     /// on disk.
     pub const ESTIMATED_BASE_TABLE_SIZE_BYTES: &str = "readyset_base_tables_estimated_size_bytes";
@@ -301,6 +368,26 @@ pub mod recorded {
     /// | node | The LocalNodeIndex of the base table node handling the packet. |
     pub const BASE_TABLE_LOOKUP_REQUESTS: &str = "readyset_base_table.lookup_requests";
 
+    /// Gauge: The estimated fraction of entries in a base table's primary index which are
+    /// deletion tombstones that have not yet been compacted away.
+    ///
+    /// | Tag | Description |
+    /// | --- | ----------- |
+    /// | table_name | The name of the base table. |
+    /// | domain | The index of the domain containing the base table. |
+    /// | shard | The shard of the base table. |
+    pub const BASE_TABLE_TOMBSTONE_RATIO: &str = "readyset_base_table.tombstone_ratio";
+
+    /// Counter: The number of manual compactions started for a base table, either because they
+    /// were requested via the controller API or because the table's compaction interval elapsed.
+    ///
+    /// | Tag | Description |
+    /// | --- | ----------- |
+    /// | table_name | The name of the base table. |
+    /// | domain | The index of the domain containing the base table. |
+    /// | shard | The shard of the base table. |
+    pub const BASE_TABLE_COMPACTIONS: &str = "readyset_base_table.compactions";
+
     /// Counter: The number of packets dropped by an egress node.
     ///
     ///
@@ -351,6 +438,26 @@ pub mod recorded {
     /// | packet_type | The type of packet |
     pub const DOMAIN_PACKET_SENT: &str = "readyset_domain.packet_sent";
 
+    /// Gauge: Whether a domain replica is currently stalled, ie has been processing the same
+    /// packet or request for longer than the configured stall threshold. 1 if stalled, 0
+    /// otherwise.
+    ///
+    /// | Tag | Description |
+    /// | --- | ----------- |
+    /// | domain | The index of the domain. |
+    /// | shard | The shard of the domain. |
+    /// | replica | The replica of the domain. |
+    pub const DOMAIN_STALLED: &str = "readyset_domain.stalled";
+
+    /// Counter: The number of times a domain replica has been detected as stalled.
+    ///
+    /// | Tag | Description |
+    /// | --- | ----------- |
+    /// | domain | The index of the domain. |
+    /// | shard | The shard of the domain. |
+    /// | replica | The replica of the domain. |
+    pub const DOMAIN_STALLS: &str = "readyset_domain.stalls";
+
     /// Histogram: The time a snapshot takes to be performed.
     pub const REPLICATOR_SNAPSHOT_DURATION: &str = "readyset_replicator.snapshot_duration_us";
 
@@ -392,6 +499,17 @@ pub mod recorded {
     /// log.
     pub const REPLICATOR_FAILURE: &str = "readyset_replicator.update_failure";
 
+    /// Counter: Number of failures encountered when shipping changes to a standby deployment,
+    /// each of which detaches the standby.
+    pub const REPLICATOR_STANDBY_FAILURE: &str = "readyset_replicator.standby_failure";
+
+    /// Counter: Number of failovers of the upstream database detected by the replicator, each of
+    /// which restarts replication against the new writer.
+    ///
+    /// | Tag | Description |
+    /// | reason | Why the failover was detected: `server-changed` or `read-only` |
+    pub const REPLICATOR_UPSTREAM_FAILOVER: &str = "readyset_replicator.upstream_failover";
+
     /// Counter: Number of tables that failed to replicate and are ignored
     pub const TABLE_FAILED_TO_REPLICATE: &str = "readyset_replicator.table_failed";
 
@@ -437,6 +555,26 @@ pub mod recorded {
     /// Gauge: A stub gague used to report the version information for the server.
     /// Labels are used to convey the version information.
     pub const READYSET_SERVER_VERSION: &str = "readyset_server_version";
+
+    /// Gauge: The ratio of the time spent processing by the canary replica of a domain shard to
+    /// the mean time spent processing by its stable replicas, recorded each time a canary report
+    /// is requested.
+    ///
+    /// | Tag | Description |
+    /// | --- | ----------- |
+    /// | domain | The index of the domain. |
+    /// | shard | The shard of the domain. |
+    pub const CANARY_LATENCY_RATIO: &str = "readyset_canary.latency_ratio";
+
+    /// Gauge: The difference between the number of keys materialized in the readers of the canary
+    /// replica of a domain shard and the mean number in the readers of its stable replicas, as a
+    /// fraction of the latter, recorded each time a canary report is requested.
+    ///
+    /// | Tag | Description |
+    /// | --- | ----------- |
+    /// | domain | The index of the domain. |
+    /// | shard | The shard of the domain. |
+    pub const CANARY_KEY_DIVERGENCE: &str = "readyset_canary.key_divergence";
 }
 
 /// A dumped metric's kind.
@@ -608,6 +746,65 @@ impl MetricsDump {
 
         dumped_metrics
     }
+
+    /// Returns a new [`MetricsDump`] containing only the metrics in this dump which match
+    /// `filter`
+    pub fn filtered(&self, filter: &MetricsFilter) -> MetricsDump {
+        let metrics = self
+            .metrics
+            .iter()
+            .filter_map(|(name, dm)| {
+                let dm = dm
+                    .iter()
+                    .filter(|m| filter.matches(name, &m.labels))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                (!dm.is_empty()).then(|| (name.clone(), dm))
+            })
+            .collect();
+        MetricsDump { metrics }
+    }
+
+    /// Returns a new [`MetricsDump`] containing only the metrics in this dump whose values differ
+    /// from (or are not present in) `prev`.
+    ///
+    /// Applying the result to `prev` with [`MetricsDump::apply_delta`] yields a dump equivalent to
+    /// this one.
+    pub fn delta(&self, prev: &MetricsDump) -> MetricsDump {
+        let metrics = self
+            .metrics
+            .iter()
+            .filter_map(|(name, dm)| {
+                let prev = prev.metrics.get(name);
+                let dm = dm
+                    .iter()
+                    .filter(|m| {
+                        !prev
+                            .iter()
+                            .flat_map(|p| p.iter())
+                            .any(|p| p.labels == m.labels && p.value == m.value)
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+                (!dm.is_empty()).then(|| (name.clone(), dm))
+            })
+            .collect();
+        MetricsDump { metrics }
+    }
+
+    /// Update the values of the metrics in this dump with the values in `delta`, as returned by
+    /// [`MetricsDump::delta`]
+    pub fn apply_delta(&mut self, delta: MetricsDump) {
+        for (name, dm) in delta.metrics {
+            let ent = self.metrics.entry(name).or_default();
+            for m in dm {
+                match ent.iter_mut().find(|e| e.labels == m.labels) {
+                    Some(e) => e.value = m.value,
+                    None => ent.push(m),
+                }
+            }
+        }
+    }
 }
 
 impl DumpedMetricValue {
@@ -702,4 +899,65 @@ mod test {
 
         assert_eq!(get_metric!(md, metrics_name), None);
     }
+
+    fn counter(labels: &[(&str, &str)], value: f64) -> DumpedMetric {
+        DumpedMetric {
+            labels: labels
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            value: DumpedMetricValue::Counter(value),
+        }
+    }
+
+    #[test]
+    fn delta_and_apply() {
+        let prev = MetricsDump {
+            metrics: HashMap::from([
+                (
+                    "a".to_owned(),
+                    vec![counter(&[("x", "1")], 1.0), counter(&[("x", "2")], 2.0)],
+                ),
+                ("b".to_owned(), vec![counter(&[], 3.0)]),
+            ]),
+        };
+        let mut next = prev.clone();
+        next.metrics.get_mut("a").unwrap()[1].value = DumpedMetricValue::Counter(5.0);
+        next.metrics.insert("c".to_owned(), vec![counter(&[], 1.0)]);
+
+        let delta = next.delta(&prev);
+        let mut keys = delta.keys().collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, vec!["a", "c"]);
+        assert_eq!(delta.metrics["a"].len(), 1);
+        assert_eq!(
+            get_metric!(delta, "a", "x" => "2"),
+            Some(DumpedMetricValue::Counter(5.0))
+        );
+
+        let mut applied = prev.clone();
+        applied.apply_delta(delta);
+        assert_eq!(applied.delta(&next).metrics, HashMap::new());
+        assert_eq!(next.delta(&applied).metrics, HashMap::new());
+    }
+
+    #[test]
+    fn filtered() {
+        let md = MetricsDump {
+            metrics: HashMap::from([
+                (
+                    "readyset_a".to_owned(),
+                    vec![counter(&[("x", "1")], 1.0), counter(&[("x", "2")], 2.0)],
+                ),
+                ("other".to_owned(), vec![counter(&[("x", "1")], 3.0)]),
+            ]),
+        };
+        let filter = MetricsFilter {
+            names: vec!["readyset_".to_owned()],
+            labels: vec![("x".to_owned(), "1".to_owned())],
+        };
+        let res = md.filtered(&filter);
+        assert_eq!(res.keys().collect::<Vec<_>>(), vec!["readyset_a"]);
+        assert_eq!(res.metrics["readyset_a"].len(), 1);
+    }
 }
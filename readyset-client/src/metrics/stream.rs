@@ -0,0 +1,204 @@
+//! Rather than repeatedly requesting a full [`MetricsDump`] from the `/metrics_dump` endpoint,
+//! clients can subscribe to the `/metrics_stream` endpoint of a readyset-server, which streams
+//! [server-sent events][sse] containing an initial snapshot of all metrics matching a
+//! [`MetricsFilter`], followed by a delta of all metrics that have changed since the previous event
+//! at a configurable interval.
+//!
+//! [sse]: https://html.spec.whatwg.org/multipage/server-sent-events.html
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use readyset_errors::{ReadySetError, ReadySetResult};
+use serde::{Deserialize, Serialize};
+use url::form_urlencoded;
+
+use crate::metrics::MetricsDump;
+
+/// The default interval between events in a metrics stream
+pub const DEFAULT_STREAM_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The minimum interval between events in a metrics stream
+pub const MIN_STREAM_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A filter on the metrics to include in a metrics stream
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsFilter {
+    /// Only include metrics whose name starts with one of these prefixes. If empty, metrics with
+    /// any name are included.
+    pub names: Vec<String>,
+    /// Only include metrics which have all of these labels
+    pub labels: Vec<(String, String)>,
+}
+
+impl MetricsFilter {
+    /// Returns true if a metric with the given name and labels matches this filter
+    pub fn matches(&self, name: &str, labels: &HashMap<String, String>) -> bool {
+        (self.names.is_empty() || self.names.iter().any(|n| name.starts_with(n.as_str())))
+            && self
+                .labels
+                .iter()
+                .all(|(k, v)| labels.get(k).map_or(false, |l| l == v))
+    }
+}
+
+/// Options for subscribing to a metrics stream, passed as query parameters to the
+/// `/metrics_stream` endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricsStreamOptions {
+    /// The interval between events in the stream
+    pub interval: Duration,
+    /// The filter on the metrics to include in the stream
+    pub filter: MetricsFilter,
+}
+
+impl Default for MetricsStreamOptions {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_STREAM_INTERVAL,
+            filter: Default::default(),
+        }
+    }
+}
+
+impl MetricsStreamOptions {
+    /// Parse options from a query string of the form
+    /// `interval_ms=500&name=readyset_domain&label=domain:0`, where `name` and `label` may each be
+    /// repeated
+    pub fn from_query(query: &str) -> ReadySetResult<Self> {
+        let mut res = Self::default();
+        for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+            match key.as_ref() {
+                "interval_ms" => {
+                    let ms = value.parse::<u64>().map_err(|_| {
+                        ReadySetError::BadRequest(format!("Invalid interval_ms: {value}"))
+                    })?;
+                    res.interval = Duration::from_millis(ms).max(MIN_STREAM_INTERVAL);
+                }
+                "name" => res.filter.names.push(value.into_owned()),
+                "label" => {
+                    let (k, v) = value.split_once(':').ok_or_else(|| {
+                        ReadySetError::BadRequest(format!(
+                            "Invalid label filter {value}; expected <key>:<value>"
+                        ))
+                    })?;
+                    res.filter.labels.push((k.to_owned(), v.to_owned()));
+                }
+                _ => {
+                    return Err(ReadySetError::BadRequest(format!(
+                        "Unknown metrics stream option: {key}"
+                    )))
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    /// Format these options as a query string that can be parsed by
+    /// [`MetricsStreamOptions::from_query`]
+    pub fn to_query(&self) -> String {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        query.append_pair("interval_ms", &self.interval.as_millis().to_string());
+        for name in &self.filter.names {
+            query.append_pair("name", name);
+        }
+        for (k, v) in &self.filter.labels {
+            query.append_pair("label", &format!("{k}:{v}"));
+        }
+        query.finish()
+    }
+}
+
+/// A single event in a metrics stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MetricsStreamEvent {
+    /// The values of all matching metrics. Always the first event in a stream.
+    Snapshot(MetricsDump),
+    /// The values of all matching metrics which have changed since the previous event
+    Delta(MetricsDump),
+}
+
+impl MetricsStreamEvent {
+    /// Encode this event as a server-sent event
+    pub fn to_sse(&self) -> ReadySetResult<String> {
+        let (event, dump) = match self {
+            MetricsStreamEvent::Snapshot(dump) => ("snapshot", dump),
+            MetricsStreamEvent::Delta(dump) => ("delta", dump),
+        };
+        let data = serde_json::to_string(dump)
+            .map_err(|e| ReadySetError::SerializationFailed(e.to_string()))?;
+        Ok(format!("event: {event}\ndata: {data}\n\n"))
+    }
+
+    /// Decode a single server-sent event, not including the trailing blank line, as encoded by
+    /// [`MetricsStreamEvent::to_sse`]. Returns `None` for events without any data, such as
+    /// comments.
+    pub fn from_sse(event: &str) -> ReadySetResult<Option<Self>> {
+        let mut kind = None;
+        let mut data = String::new();
+        for line in event.lines() {
+            if let Some(v) = line.strip_prefix("event:") {
+                kind = Some(v.trim());
+            } else if let Some(v) = line.strip_prefix("data:") {
+                data.push_str(v.trim_start());
+            }
+        }
+        if data.is_empty() {
+            return Ok(None);
+        }
+
+        let dump = serde_json::from_str(&data)
+            .map_err(|e| ReadySetError::SerializationFailed(e.to_string()))?;
+        match kind {
+            Some("snapshot") => Ok(Some(MetricsStreamEvent::Snapshot(dump))),
+            Some("delta") => Ok(Some(MetricsStreamEvent::Delta(dump))),
+            _ => Err(ReadySetError::SerializationFailed(format!(
+                "Unknown metrics stream event type: {kind:?}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options_round_trip() {
+        let options = MetricsStreamOptions {
+            interval: Duration::from_millis(500),
+            filter: MetricsFilter {
+                names: vec!["readyset_domain".to_owned(), "readyset_server".to_owned()],
+                labels: vec![("domain".to_owned(), "0".to_owned())],
+            },
+        };
+        assert_eq!(
+            MetricsStreamOptions::from_query(&options.to_query()).unwrap(),
+            options
+        );
+    }
+
+    #[test]
+    fn options_interval_clamped() {
+        let options = MetricsStreamOptions::from_query("interval_ms=1").unwrap();
+        assert_eq!(options.interval, MIN_STREAM_INTERVAL);
+        assert!(MetricsStreamOptions::from_query("label=nocolon").is_err());
+        assert!(MetricsStreamOptions::from_query("unknown=1").is_err());
+    }
+
+    #[test]
+    fn sse_round_trip() {
+        let event = MetricsStreamEvent::Delta(MetricsDump {
+            metrics: HashMap::new(),
+        });
+        let sse = event.to_sse().unwrap();
+        assert!(sse.ends_with("\n\n"));
+        assert!(matches!(
+            MetricsStreamEvent::from_sse(sse.trim_end()).unwrap(),
+            Some(MetricsStreamEvent::Delta(_))
+        ));
+        assert!(MetricsStreamEvent::from_sse(": keepalive")
+            .unwrap()
+            .is_none());
+    }
+}
@@ -34,15 +34,17 @@
 use dataflow_expression::Dialect;
 use nom_locate::LocatedSpan;
 use nom_sql::{
-    AlterTableStatement, CacheInner, CreateCacheStatement, CreateTableStatement,
-    CreateViewStatement, DropTableStatement, DropViewStatement, Relation, SelectStatement,
-    SqlIdentifier, SqlQuery,
+    AlterTableStatement, CacheInner, ColumnConstraint, ColumnSpecification, CreateCacheStatement,
+    CreateTableStatement, CreateViewStatement, DropTableStatement, DropViewStatement, Expr,
+    Literal, Relation, SelectStatement, SqlIdentifier, SqlQuery, SqlType,
 };
 use readyset_data::DfType;
 use readyset_errors::{internal, unsupported, ReadySetError, ReadySetResult};
 use serde::{Deserialize, Serialize};
 use tracing::error;
 
+use crate::internal::IndexType;
+
 /// The specification for a list of changes that must be made
 /// to the MIR and dataflow graphs.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -185,8 +187,10 @@ impl ChangeList {
                             SqlQuery::CreateView(cvs) => changes.push(Change::CreateView(cvs)),
                             SqlQuery::CreateCache(CreateCacheStatement {
                                 name,
+                                options,
                                 inner,
                                 always,
+                                ..
                             }) => {
                                 let statement = match inner {
                                     Ok(CacheInner::Statement(stmt)) => stmt,
@@ -208,6 +212,7 @@ impl ChangeList {
                                     name,
                                     statement,
                                     always,
+                                    options: CacheOptions::from_sql(&options)?,
                                 })
                             }
                             SqlQuery::AlterTable(ats) => changes.push(Change::AlterTable(ats)),
@@ -227,7 +232,7 @@ impl ChangeList {
                             }
                             SqlQuery::DropCache(dcs) => changes.push(Change::Drop {
                                 name: dcs.name,
-                                if_exists: false,
+                                if_exists: dcs.if_exists,
                             }),
                             _ => unsupported!(
                                 "Only DDL statements supported in ChangeList (got {})",
@@ -302,6 +307,76 @@ pub enum AlterTypeChange {
     },
 }
 
+/// Options controlling how a cached query is created, which can be given in the
+/// `WITH (<option> = <value>, ...)` clause of a `CREATE CACHE` statement.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CacheOptions {
+    /// Fully materialize the reader for the cache (and the nodes it reads from), even if it could
+    /// be partially materialized.
+    ///
+    /// Given as `full_materialization = true`.
+    pub full_materialization: bool,
+    /// The type of index to use for the reader for the cache, if not the one chosen based on the
+    /// query. Hash indexes can't be used for queries that require range lookups.
+    ///
+    /// Given as `index_type = 'hash'` or `index_type = 'btree'`.
+    pub index_type: Option<IndexType>,
+    /// The number of times to replicate the domain containing the reader for the cache,
+    /// overriding the cluster-wide replication strategy.
+    ///
+    /// Given as `replicas = <n>`.
+    pub replicas: Option<usize>,
+}
+
+impl CacheOptions {
+    /// Build a set of cache options from the options given in a `CREATE CACHE` statement,
+    /// returning an error for unknown options or invalid values
+    pub fn from_sql(options: &[(SqlIdentifier, Literal)]) -> ReadySetResult<Self> {
+        let mut res = Self::default();
+        for (name, value) in options {
+            match (name.to_lowercase().as_str(), value) {
+                ("full_materialization", Literal::Boolean(full)) => {
+                    res.full_materialization = *full
+                }
+                ("index_type", Literal::String(index_type)) => {
+                    res.index_type = Some(match index_type.to_lowercase().as_str() {
+                        "hash" => IndexType::HashMap,
+                        "btree" => IndexType::BTreeMap,
+                        _ => unsupported!("Unknown index type for cache: {index_type}"),
+                    })
+                }
+                ("replicas", Literal::UnsignedInteger(replicas)) if *replicas > 0 => {
+                    res.replicas = Some(*replicas as usize)
+                }
+                ("full_materialization" | "index_type" | "replicas", _) => {
+                    unsupported!("Invalid value for cache option {name}: {value}")
+                }
+                _ => unsupported!("Unknown cache option: {name}"),
+            }
+        }
+        Ok(res)
+    }
+
+    /// Convert these options back into the form they're given in a `CREATE CACHE` statement
+    pub fn to_sql(&self) -> Vec<(SqlIdentifier, Literal)> {
+        let mut res = vec![];
+        if self.full_materialization {
+            res.push(("full_materialization".into(), Literal::Boolean(true)));
+        }
+        if let Some(index_type) = self.index_type {
+            let index_type = match index_type {
+                IndexType::HashMap => "hash",
+                IndexType::BTreeMap => "btree",
+            };
+            res.push(("index_type".into(), index_type.into()));
+        }
+        if let Some(replicas) = self.replicas {
+            res.push(("replicas".into(), (replicas as u64).into()));
+        }
+        res
+    }
+}
+
 /// Describes a singe change to be made to the MIR and dataflow graphs.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Change {
@@ -325,6 +400,8 @@ pub enum Change {
         /// If set to `true`, execution of this cache will bypass transaction handling in the
         /// adapter
         always: bool,
+        /// Options for how the cache should be created
+        options: CacheOptions,
     },
     /// Alter an existing table in the graph, making changes according to the given `ALTER TABLE`
     /// statement
@@ -380,19 +457,25 @@ impl Change {
             name: Some(name.into()),
             statement: Box::new(statement),
             always,
+            options: CacheOptions::default(),
         }
     }
 
     /// Return true if this change requires noria to resnapshot the database in order to properly
     /// update the schema
+    ///
+    /// Columns added to the end of a table and dropped from a table can (usually) be applied to
+    /// the table's existing base node in place, so don't require a resnapshot.
     pub fn requires_resnapshot(&self) -> bool {
         match self {
             Change::AlterTable(alter_table) => {
                 if let Ok(definitions) = &alter_table.definitions {
                     definitions.iter().any(|def| match def {
-                        nom_sql::AlterTableDefinition::AddColumn(_)
-                        | nom_sql::AlterTableDefinition::AlterColumn { .. }
-                        | nom_sql::AlterTableDefinition::DropColumn { .. }
+                        nom_sql::AlterTableDefinition::AddColumn { spec, position } => {
+                            position.is_some() || !can_backfill_column(spec)
+                        }
+                        nom_sql::AlterTableDefinition::DropColumn { .. } => false,
+                        nom_sql::AlterTableDefinition::AlterColumn { .. }
                         | nom_sql::AlterTableDefinition::ChangeColumn { .. }
                         | nom_sql::AlterTableDefinition::RenameColumn { .. }
                         | nom_sql::AlterTableDefinition::AddKey(_)
@@ -437,6 +520,33 @@ impl Change {
     }
 }
 
+/// Returns true if the value of the column with the given spec in the rows that are already in its
+/// table, once it's added by `ALTER TABLE`, is known without having to ask the upstream database
+fn can_backfill_column(spec: &ColumnSpecification) -> bool {
+    if matches!(spec.sql_type, SqlType::Serial | SqlType::BigSerial) {
+        return false;
+    }
+
+    let has_default = spec
+        .constraints
+        .iter()
+        .any(|c| matches!(c, ColumnConstraint::DefaultValue(_)));
+    spec.constraints.iter().all(|c| match c {
+        ColumnConstraint::DefaultValue(expr) => matches!(expr, Expr::Literal(_)),
+        // MySQL fills in NOT NULL columns without a default with an implicit default value that
+        // depends on the column's type
+        ColumnConstraint::NotNull => has_default,
+        ColumnConstraint::AutoIncrement
+        | ColumnConstraint::PrimaryKey
+        | ColumnConstraint::Unique => false,
+        ColumnConstraint::Null
+        | ColumnConstraint::CharacterSet(_)
+        | ColumnConstraint::Collation(_)
+        | ColumnConstraint::OnUpdateCurrentTimestamp
+        | ColumnConstraint::Srid(_) => true,
+    })
+}
+
 mod parse {
     use nom::bytes::complete::{tag, take_until};
     use nom::combinator::recognize;
@@ -491,6 +601,40 @@ mod parse {
 mod tests {
     use super::*;
 
+    #[test]
+    fn create_cache_with_options() {
+        let changelist = ChangeList::from_str(
+            "CREATE CACHE foo WITH (full_materialization = true, index_type = 'btree', replicas = 3) \
+             FROM SELECT a FROM b WHERE c = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap();
+        let options = match changelist.changes.as_slice() {
+            [Change::CreateCache { options, .. }] => options.clone(),
+            changes => panic!("Unexpected changes: {changes:?}"),
+        };
+        assert_eq!(
+            options,
+            CacheOptions {
+                full_materialization: true,
+                index_type: Some(IndexType::BTreeMap),
+                replicas: Some(3),
+            }
+        );
+        assert_eq!(CacheOptions::from_sql(&options.to_sql()).unwrap(), options);
+
+        ChangeList::from_str(
+            "CREATE CACHE foo WITH (memory_budget = 100) FROM SELECT a FROM b;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap_err();
+        ChangeList::from_str(
+            "CREATE CACHE foo WITH (replicas = 'many') FROM SELECT a FROM b;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap_err();
+    }
+
     #[test]
     fn it_handles_multiple_statements_per_line() {
         let queries =
@@ -604,5 +748,37 @@ mod tests {
             };
             assert!(change.requires_resnapshot())
         }
+
+        fn alter_table(query: &str) -> Change {
+            ChangeList::from_str(query, Dialect::DEFAULT_MYSQL)
+                .unwrap()
+                .changes
+                .remove(0)
+        }
+
+        #[test]
+        fn add_and_drop_columns() {
+            assert!(!alter_table("ALTER TABLE t ADD COLUMN x INT").requires_resnapshot());
+            assert!(!alter_table(
+                "ALTER TABLE t ADD COLUMN x INT NOT NULL DEFAULT 1, DROP COLUMN y"
+            )
+            .requires_resnapshot());
+            assert!(!alter_table("ALTER TABLE t DROP COLUMN y").requires_resnapshot());
+        }
+
+        #[test]
+        fn add_columns_that_cant_be_backfilled() {
+            assert!(alter_table("ALTER TABLE t ADD COLUMN x INT FIRST").requires_resnapshot());
+            assert!(alter_table("ALTER TABLE t ADD COLUMN x INT NOT NULL").requires_resnapshot());
+            assert!(
+                alter_table("ALTER TABLE t ADD COLUMN x INT AUTO_INCREMENT PRIMARY KEY")
+                    .requires_resnapshot()
+            );
+            assert!(
+                alter_table("ALTER TABLE t ADD COLUMN x TIMESTAMP DEFAULT CURRENT_TIMESTAMP")
+                    .requires_resnapshot()
+            );
+            assert!(alter_table("ALTER TABLE t MODIFY COLUMN x BIGINT").requires_resnapshot());
+        }
     }
 }
@@ -70,6 +70,18 @@ impl PartialOrd for ReplicationOffset {
 }
 
 impl ReplicationOffset {
+    /// Returns this offset in the format used by the upstream database itself - a binlog file name
+    /// and position (`binlog.000003:154`) for MySQL, and an LSN (`16/B374D848`) for PostgreSQL -
+    /// so that it can be compared against the positions returned by the upstream database.
+    pub fn upstream_position(&self) -> String {
+        if self.replication_log_name.is_empty() {
+            let lsn = self.offset as i64;
+            format!("{:X}/{:X}", lsn >> 32, lsn & 0xffffffff)
+        } else {
+            self.to_string()
+        }
+    }
+
     /// Try to mutate `other` to take the maximum of its offset and the offset of
     /// `self`. If `other` is `None`, will assign it to `Some(self.clone)`.
     ///
@@ -353,4 +365,19 @@ mod tests {
             assert!(res.is_none());
         }
     }
+
+    #[test]
+    fn upstream_position() {
+        let binlog = ReplicationOffset {
+            offset: (6 << 123) | (3 << 64) | 154,
+            replication_log_name: "binlog".to_owned(),
+        };
+        assert_eq!(binlog.upstream_position(), "binlog.000003:154");
+
+        let lsn = ReplicationOffset {
+            offset: 0x16_B374_D848,
+            replication_log_name: String::new(),
+        };
+        assert_eq!(lsn.upstream_position(), "16/B374D848");
+    }
 }
@@ -76,3 +76,28 @@ impl Display for SnapshotStatus {
         write!(f, "{}", s)
     }
 }
+
+/// The state of a deployment running as a warm standby of another deployment, which is sent
+/// writes to its base tables by the primary deployment's replicator rather than replicating from
+/// the upstream database itself.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub enum StandbyState {
+    /// The standby is taking an initial snapshot of the upstream database, and is not yet ready
+    /// to receive writes from the primary.
+    Seeding,
+    /// The standby is ready to receive writes from the primary.
+    Standby,
+    /// The standby has been promoted, and now replicates from the upstream database itself.
+    Promoted,
+}
+
+impl Display for StandbyState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            StandbyState::Seeding => "Seeding",
+            StandbyState::Standby => "Standby",
+            StandbyState::Promoted => "Promoted",
+        };
+        write!(f, "{}", s)
+    }
+}
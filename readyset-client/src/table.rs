@@ -6,7 +6,7 @@ use std::net::SocketAddr;
 use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
 use std::time::{Duration, Instant, SystemTime};
-use std::{fmt, iter};
+use std::{fmt, iter, mem};
 
 use async_bincode::{AsyncBincodeStream, AsyncDestination};
 use derive_more::TryInto;
@@ -15,11 +15,12 @@ use futures_util::stream::futures_unordered::FuturesUnordered;
 use futures_util::stream::TryStreamExt;
 use futures_util::{future, ready};
 use itertools::Either;
-use nom_sql::{CreateTableBody, Relation, SqlIdentifier};
+use nom_sql::{CreateTableBody, Relation, SqlIdentifier, SqlType};
 use petgraph::graph::NodeIndex;
 use readyset_data::DfValue;
 use readyset_errors::{
-    internal, internal_err, rpc_err, table_err, unsupported, ReadySetError, ReadySetResult,
+    bad_request_err, internal, internal_err, rpc_err, table_err, unsupported, ReadySetError,
+    ReadySetResult,
 };
 use serde::{Deserialize, Serialize};
 use tokio::io::AsyncWriteExt;
@@ -34,11 +35,16 @@ use vec_map::VecMap;
 use crate::channel::CONNECTION_FROM_BASE;
 use crate::internal::*;
 use crate::replication::ReplicationOffset;
+use crate::tls::MaybeTlsStream;
 use crate::{consistency, Tagged, Tagger};
 
 // TODO(justin): Make write propagation sample rate configurable.
 const TRACE_SAMPLE_RATE: Duration = Duration::from_secs(1);
 
+/// The maximum number of bytes of values sent to a base table in a single packet by
+/// [`Table::perform_all`] and [`Table::insert_many`], unless a single operation is larger
+const MAX_BATCH_BYTES: usize = 16 * 1024 * 1024;
+
 /// A modification to make to an existing value.
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Operation {
@@ -159,8 +165,12 @@ impl From<Vec<DfValue>> for TableOperation {
     }
 }
 
-type Transport =
-    AsyncBincodeStream<tokio::net::TcpStream, Tagged<()>, Tagged<PacketData>, AsyncDestination>;
+type Transport = AsyncBincodeStream<
+    MaybeTlsStream<tokio::net::TcpStream>,
+    Tagged<u64>,
+    Tagged<PacketData>,
+    AsyncDestination,
+>;
 
 #[derive(Debug)]
 struct Endpoint {
@@ -188,8 +198,9 @@ impl Service<()> for Endpoint {
         let f = tokio::net::TcpStream::connect(self.addr);
         let timeout = self.timeout;
         async move {
-            let mut s = tokio::time::timeout(timeout, f).await??;
+            let s = tokio::time::timeout(timeout, f).await??;
             s.set_nodelay(true)?;
+            let mut s = MaybeTlsStream::connect(s).await?;
             s.write_all(&[CONNECTION_FROM_BASE]).await?;
             s.flush().await?;
             let s = AsyncBincodeStream::from(s).for_async();
@@ -314,6 +325,11 @@ pub struct TableBuilder {
 
     /// The amount of time before a table request RPC is terminated.
     pub table_request_timeout: Duration,
+
+    /// The maximum size, in bytes, of a single text or binary value written to the table, or
+    /// `None` for no limit.
+    #[serde(default)]
+    pub max_value_size: Option<usize>,
 }
 
 impl TableBuilder {
@@ -356,12 +372,19 @@ impl TableBuilder {
             conns.push(s);
         }
 
+        let binary_columns = self
+            .schema
+            .as_ref()
+            .map(|schema| binary_columns(schema, &self.columns, &self.dropped))
+            .unwrap_or_default();
+
         Table {
             ni: self.ni,
             node: self.addr,
             key: self.key,
             key_is_primary: self.key_is_primary,
             columns: self.columns,
+            binary_columns,
             dropped: self.dropped,
             table_name: self.table_name,
             schema: self.schema,
@@ -369,8 +392,102 @@ impl TableBuilder {
             shards: conns,
             last_trace_sample: Instant::now(),
             request_timeout: self.table_request_timeout,
+            max_value_size: self.max_value_size,
+        }
+    }
+}
+
+/// Returns true if values of the given type are byte strings, which are stored as
+/// [`DfValue::ByteArray`]s rather than as text
+fn is_binary_type(ty: &SqlType) -> bool {
+    matches!(
+        ty,
+        SqlType::Blob
+            | SqlType::TinyBlob
+            | SqlType::MediumBlob
+            | SqlType::LongBlob
+            | SqlType::ByteArray
+            | SqlType::Binary(_)
+            | SqlType::VarBinary(_)
+            | SqlType::Spatial(_)
+    )
+}
+
+/// Returns the indices, in the rows of the base table node (including dropped columns), of the
+/// columns of the table with a binary type
+fn binary_columns(
+    schema: &CreateTableBody,
+    columns: &[SqlIdentifier],
+    dropped: &VecMap<DfValue>,
+) -> Vec<usize> {
+    (0..)
+        .filter(|i| !dropped.contains_key(*i))
+        .zip(columns)
+        .filter(|(_, name)| {
+            schema
+                .fields
+                .iter()
+                .any(|f| f.column.name == **name && is_binary_type(&f.sql_type))
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Returns the approximate number of bytes taken up by `value` when written to a base table
+fn value_size(value: &DfValue) -> usize {
+    mem::size_of::<DfValue>() + value.as_bytes().map_or(0, |b| b.len())
+}
+
+/// Returns the approximate number of bytes taken up by the values in `op`
+fn operation_size(op: &TableOperation) -> usize {
+    let row_size = |row: &[DfValue]| row.iter().map(value_size).sum::<usize>();
+    let update_size = |update: &[Modification]| {
+        update
+            .iter()
+            .map(|m| match m {
+                Modification::Set(v) | Modification::Apply(_, v) => value_size(v),
+                Modification::None => 0,
+            })
+            .sum::<usize>()
+    };
+    match op {
+        TableOperation::Insert(row) | TableOperation::DeleteRow { row } => row_size(row),
+        TableOperation::DeleteByKey { key } => row_size(key),
+        TableOperation::InsertOrUpdate { row, update } => row_size(row) + update_size(update),
+        TableOperation::Update { update, key } => update_size(update) + row_size(key),
+        TableOperation::Truncate
+        | TableOperation::SetReplicationOffset(_)
+        | TableOperation::SetSnapshotMode(_) => 0,
+    }
+}
+
+/// Splits `ops` into batches of operations whose values take up at most [`MAX_BATCH_BYTES`], so
+/// that writes of many large values aren't all sent to the base table in a single packet.
+///
+/// Operations which aren't applied in order within a batch (such as setting the replication
+/// offset) are sent with the last batch, so that they only take effect once all the rows have
+/// been written.
+fn batches(ops: Vec<TableOperation>) -> Vec<Vec<TableOperation>> {
+    let mut batches = vec![];
+    let mut current = vec![];
+    let mut current_bytes = 0;
+    let mut unordered = vec![];
+    for op in ops {
+        let size = operation_size(&op);
+        if size == 0 {
+            unordered.push(op);
+            continue;
+        }
+        if !current.is_empty() && current_bytes + size > MAX_BATCH_BYTES {
+            batches.push(mem::take(&mut current));
+            current_bytes = 0;
         }
+        current_bytes += size;
+        current.push(op);
     }
+    current.extend(unordered);
+    batches.push(current);
+    batches
 }
 
 /// A `Table` is used to perform writes, deletes, and other operations to data in base tables.
@@ -387,6 +504,8 @@ pub struct Table {
     key_is_primary: bool,
     key: Vec<usize>,
     columns: Vec<SqlIdentifier>,
+    /// The indices of the columns with a binary type, in rows including dropped columns.
+    binary_columns: Vec<usize>,
     dropped: VecMap<DfValue>,
     table_name: Relation,
     schema: Option<CreateTableBody>,
@@ -394,6 +513,7 @@ pub struct Table {
     shard_addrs: Vec<SocketAddr>,
     last_trace_sample: Instant,
     request_timeout: Duration,
+    max_value_size: Option<usize>,
 }
 
 impl fmt::Debug for Table {
@@ -417,7 +537,7 @@ impl Table {
     fn input(
         &mut self,
         mut i: PacketData,
-    ) -> impl Future<Output = Result<Tagged<()>, ReadySetError>> + Send {
+    ) -> impl Future<Output = Result<Tagged<u64>, ReadySetError>> + Send {
         let span = if crate::trace_next_op() {
             Some(trace_span!("table-request", base = self.ni.index()))
         } else {
@@ -580,9 +700,13 @@ impl Table {
                     }
                 }
 
+                // Each row operation is only sent to one shard, so the rows affected by the write
+                // are the sum of those affected in each shard
                 future::Either::Right(
                     wait_for
-                        .try_for_each(|_| async { Ok(()) })
+                        .try_fold(0, |affected_rows, Tagged { v, .. }| async move {
+                            Ok(affected_rows + v)
+                        })
                         .map_err(rpc_err!("Table::input"))
                         .map_ok(Tagged::from),
                 )
@@ -595,7 +719,7 @@ impl Table {
     fn timestamp(
         &mut self,
         t: PacketData,
-    ) -> impl Future<Output = Result<Tagged<()>, ReadySetError>> + Send {
+    ) -> impl Future<Output = Result<Tagged<u64>, ReadySetError>> + Send {
         let nshards = self.shards.len();
         match self.shards.first_mut() {
             Some(table_rpc) if nshards == 1 => {
@@ -629,7 +753,7 @@ impl Table {
                     wait_for
                         .try_for_each(|_| async { Ok(()) })
                         .map_err(rpc_err!("Table::timestamp"))
-                        .map_ok(Tagged::from),
+                        .map_ok(|()| Tagged::from(0)),
                 ))
             }
         }
@@ -648,7 +772,7 @@ impl Service<TableRequest> for Table {
     type Error = ReadySetError;
     type Response = <TableRpc as Service<Tagged<PacketData>>>::Response;
 
-    type Future = impl Future<Output = Result<Tagged<()>, ReadySetError>> + Send;
+    type Future = impl Future<Output = Result<Tagged<u64>, ReadySetError>> + Send;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         for s in &mut self.shards {
@@ -702,8 +826,13 @@ impl Table {
         self.schema.as_ref()
     }
 
+    /// Get the indices of the key columns of this base table, in the order the key passed to
+    /// [`Table::upsert`] must be given in.
+    pub fn key(&self) -> &[usize] {
+        &self.key
+    }
+
     fn inject_dropped_cols(&self, r: &mut TableOperation) -> ReadySetResult<()> {
-        use std::mem;
         let ndropped = self.dropped.len();
         if ndropped != 0 {
             // inject defaults for dropped columns
@@ -810,9 +939,74 @@ impl Table {
         })
     }
 
+    /// Prepare a single value written to the column at index `col` (if known), converting text
+    /// written to binary columns to a byte array and checking it's within the maximum value size
+    fn prep_value(&self, col: Option<usize>, value: &mut DfValue) -> ReadySetResult<()> {
+        let Ok(bytes) = value.as_bytes() else {
+            return Ok(());
+        };
+        if let Some(max_bytes) = self.max_value_size {
+            if bytes.len() > max_bytes {
+                let column = col
+                    .and_then(|col| {
+                        let ndropped = self.dropped.keys().filter(|&d| d < col).count();
+                        self.columns.get(col - ndropped)
+                    })
+                    .map_or_else(|| "<unknown>".to_owned(), |c| c.to_string());
+                return Err(ReadySetError::ValueSizeLimitExceeded { column, max_bytes });
+            }
+        }
+        if matches!(value, DfValue::Text(_) | DfValue::TinyText(_))
+            && col.map_or(false, |col| self.binary_columns.contains(&col))
+        {
+            *value = DfValue::ByteArray(Arc::new(bytes.to_vec()));
+        }
+        Ok(())
+    }
+
+    /// Prepare all the values in the given operation with [`Self::prep_value`]. Must be called
+    /// after dropped columns have been injected into the operation.
+    fn prep_values(&self, op: &mut TableOperation) -> ReadySetResult<()> {
+        let prep_row = |row: &mut [DfValue]| {
+            row.iter_mut()
+                .enumerate()
+                .try_for_each(|(i, v)| self.prep_value(Some(i), v))
+        };
+        let prep_key = |key: &mut [DfValue]| {
+            key.iter_mut()
+                .enumerate()
+                .try_for_each(|(i, v)| self.prep_value(self.key.get(i).copied(), v))
+        };
+        let prep_update = |update: &mut [Modification]| {
+            update
+                .iter_mut()
+                .enumerate()
+                .try_for_each(|(i, m)| match m {
+                    Modification::Set(v) | Modification::Apply(_, v) => self.prep_value(Some(i), v),
+                    Modification::None => Ok(()),
+                })
+        };
+        match op {
+            TableOperation::Insert(row) | TableOperation::DeleteRow { row } => prep_row(row),
+            TableOperation::DeleteByKey { key } => prep_key(key),
+            TableOperation::InsertOrUpdate { row, update } => {
+                prep_row(row)?;
+                prep_update(update)
+            }
+            TableOperation::Update { update, key } => {
+                prep_update(update)?;
+                prep_key(key)
+            }
+            TableOperation::Truncate
+            | TableOperation::SetReplicationOffset(_)
+            | TableOperation::SetSnapshotMode(_) => Ok(()),
+        }
+    }
+
     fn prep_records(&mut self, mut ops: Vec<TableOperation>) -> ReadySetResult<PacketData> {
         for r in &mut ops {
             self.inject_dropped_cols(r)?;
+            self.prep_values(r)?;
         }
 
         Ok(PacketData {
@@ -822,19 +1016,34 @@ impl Table {
         })
     }
 
-    async fn request(&mut self, r: TableRequest) -> ReadySetResult<()> {
+    /// Perform the given request, returning the number of rows affected by it
+    async fn request(&mut self, r: TableRequest) -> ReadySetResult<u64> {
         future::poll_fn(|cx| self.poll_ready(cx)).await?;
-        self.call(r).await?;
+        let Tagged {
+            v: affected_rows, ..
+        } = self.call(r).await?;
 
-        Ok(())
+        Ok(affected_rows)
     }
 
-    async fn request_with_timeout(&mut self, r: TableRequest) -> ReadySetResult<()> {
+    async fn request_with_timeout(&mut self, r: TableRequest) -> ReadySetResult<u64> {
         tokio::time::timeout(self.request_timeout, self.request(r))
             .await
             .map_err(|_| internal_err!("Timeout during table request"))?
     }
 
+    /// Perform the given operations, split into [`batches`] of bounded size, returning the number
+    /// of rows affected by them
+    async fn perform_batched(&mut self, ops: Vec<TableOperation>) -> ReadySetResult<u64> {
+        let mut affected_rows = 0;
+        for batch in batches(ops) {
+            affected_rows += self
+                .request_with_timeout(TableRequest::TableOperations(batch))
+                .await?;
+        }
+        Ok(affected_rows)
+    }
+
     /// Insert a single row of data into this base table.
     pub async fn insert<V>(&mut self, u: V) -> ReadySetResult<()>
     where
@@ -844,6 +1053,7 @@ impl Table {
             u.into(),
         )]))
         .await
+        .map(|_| ())
     }
 
     /// Insert multiple rows of data into this base table.
@@ -852,28 +1062,29 @@ impl Table {
         I: IntoIterator<Item = V>,
         V: Into<Vec<DfValue>>,
     {
-        self.request_with_timeout(TableRequest::TableOperations(
+        self.perform_batched(
             rows.into_iter()
                 .map(|row| TableOperation::Insert(row.into()))
                 .collect::<Vec<_>>(),
-        ))
+        )
         .await
+        .map(|_| ())
     }
 
-    /// Perform multiple operation on this base table.
-    pub async fn perform_all<I, V>(&mut self, i: I) -> ReadySetResult<()>
+    /// Perform multiple operation on this base table, returning the number of rows affected by
+    /// them.
+    pub async fn perform_all<I, V>(&mut self, i: I) -> ReadySetResult<u64>
     where
         I: IntoIterator<Item = V>,
         V: Into<TableOperation>,
     {
-        self.request_with_timeout(TableRequest::TableOperations(
-            i.into_iter().map(Into::into).collect::<Vec<_>>(),
-        ))
-        .await
+        self.perform_batched(i.into_iter().map(Into::into).collect::<Vec<_>>())
+            .await
     }
 
-    /// Delete the row with the given key from this base table.
-    pub async fn delete<I>(&mut self, key: I) -> ReadySetResult<()>
+    /// Delete the row with the given key from this base table, returning the number of rows
+    /// deleted.
+    pub async fn delete<I>(&mut self, key: I) -> ReadySetResult<u64>
     where
         I: Into<Vec<DfValue>>,
     {
@@ -893,13 +1104,16 @@ impl Table {
             TableOperation::DeleteRow { row: row.into() },
         ]))
         .await
+        .map(|_| ())
     }
 
     /// Update the row with the given key in this base table.
     ///
     /// `u` is a set of column-modification pairs, where for each pair `(i, m)`, the modification
     /// `m` will be applied to column `i` of the record with key `key`.
-    pub async fn update<V>(&mut self, key: Vec<DfValue>, u: V) -> ReadySetResult<()>
+    ///
+    /// Returns the number of rows updated, which is 0 if no row exists with the given key.
+    pub async fn update<V>(&mut self, key: Vec<DfValue>, u: V) -> ReadySetResult<u64>
     where
         V: IntoIterator<Item = (usize, Modification)>,
     {
@@ -935,6 +1149,62 @@ impl Table {
         insert: Vec<DfValue>,
         update: V,
     ) -> ReadySetResult<()>
+    where
+        V: IntoIterator<Item = (usize, Modification)>,
+    {
+        let op = self.insert_or_update_operation(insert, update)?;
+        self.request_with_timeout(TableRequest::TableOperations(vec![op]))
+            .await
+            .map(|_| ())
+    }
+
+    /// Atomically insert `insert` into this base table, or if a row already exists with the given
+    /// `key`, update that row with the modifications in `update` (as documented in
+    /// [`Table::update`]) instead.
+    ///
+    /// `key` must match the values of the key columns of `insert`. Returns the number of rows
+    /// affected, counted the way MySQL does: 1 if the row was inserted, 2 if an existing row was
+    /// changed, and 0 if an existing row was left unchanged.
+    pub async fn upsert<V>(
+        &mut self,
+        key: Vec<DfValue>,
+        insert: Vec<DfValue>,
+        update: V,
+    ) -> ReadySetResult<u64>
+    where
+        V: IntoIterator<Item = (usize, Modification)>,
+    {
+        if key.len() != self.key.len() {
+            return Err(table_err(
+                self.table_name().clone(),
+                ReadySetError::WrongKeyColumnCount(self.key.len(), key.len()),
+            ));
+        }
+        if self
+            .key
+            .iter()
+            .zip(&key)
+            .any(|(col, k)| insert.get(*col) != Some(k))
+        {
+            return Err(table_err(
+                self.table_name().clone(),
+                bad_request_err("upsert key does not match the key columns of the inserted row"),
+            ));
+        }
+
+        let op = self.insert_or_update_operation(insert, update)?;
+        self.request_with_timeout(TableRequest::TableOperations(vec![op]))
+            .await
+    }
+
+    /// Build (but don't perform) an insert-or-update operation on this base table, as documented
+    /// in [`Table::insert_or_update`]. Used to perform several insert-or-update operations at once
+    /// with [`Table::perform_all`].
+    pub fn insert_or_update_operation<V>(
+        &self,
+        insert: Vec<DfValue>,
+        update: V,
+    ) -> ReadySetResult<TableOperation>
     where
         V: IntoIterator<Item = (usize, Modification)>,
     {
@@ -955,13 +1225,10 @@ impl Table {
             }
         }
 
-        self.request_with_timeout(TableRequest::TableOperations(vec![
-            TableOperation::InsertOrUpdate {
-                row: insert,
-                update: set,
-            },
-        ]))
-        .await
+        Ok(TableOperation::InsertOrUpdate {
+            row: insert,
+            update: set,
+        })
     }
 
     /// Delete all rows from this base table
@@ -970,11 +1237,14 @@ impl Table {
             TableOperation::Truncate,
         ]))
         .await
+        .map(|_| ())
     }
 
     /// Updates the timestamp of the base table in the data flow graph.
     pub async fn update_timestamp(&mut self, t: consistency::Timestamp) -> ReadySetResult<()> {
-        self.request_with_timeout(TableRequest::Timestamp(t)).await
+        self.request_with_timeout(TableRequest::Timestamp(t))
+            .await
+            .map(|_| ())
     }
 
     /// Set the replication offset for this table to the given value.
@@ -993,6 +1263,7 @@ impl Table {
             TableOperation::SetReplicationOffset(offset),
         ]))
         .await
+        .map(|_| ())
     }
 
     /// Enable or disable snapshot mode for this table. In snapshot mode compactions are disabled
@@ -1002,5 +1273,63 @@ impl Table {
             TableOperation::SetSnapshotMode(snapshot),
         ]))
         .await
+        .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::{parse_create_table, Dialect};
+
+    use super::*;
+
+    #[test]
+    fn binary_columns_skip_dropped() {
+        let schema = parse_create_table(
+            Dialect::MySQL,
+            "CREATE TABLE t (id INT, data BLOB, name TEXT, hash VARBINARY(32))",
+        )
+        .unwrap()
+        .body
+        .unwrap();
+        let columns = ["id", "data", "name", "hash"].map(SqlIdentifier::from);
+        assert_eq!(
+            binary_columns(&schema, &columns, &VecMap::new()),
+            vec![1, 3]
+        );
+
+        let mut dropped = VecMap::new();
+        dropped.insert(1, DfValue::None);
+        assert_eq!(binary_columns(&schema, &columns, &dropped), vec![2, 4]);
+    }
+
+    #[test]
+    fn batches_split_large_values() {
+        let big = DfValue::ByteArray(Arc::new(vec![0; MAX_BATCH_BYTES / 3]));
+        let ops = vec![
+            TableOperation::Insert(vec![1.into(), big.clone()]),
+            TableOperation::SetReplicationOffset(ReplicationOffset {
+                offset: 1,
+                replication_log_name: "binlog".into(),
+            }),
+            TableOperation::Insert(vec![2.into(), big.clone()]),
+            TableOperation::Insert(vec![3.into(), big]),
+        ];
+        let batches = batches(ops);
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].len(), 2);
+        assert_eq!(batches[1].len(), 2);
+        assert!(matches!(
+            batches[1].last(),
+            Some(TableOperation::SetReplicationOffset(_))
+        ));
+    }
+
+    #[test]
+    fn small_writes_are_one_batch() {
+        let ops = (0..100)
+            .map(|i| TableOperation::Insert(vec![i.into(), "x".into()]))
+            .collect::<Vec<_>>();
+        assert_eq!(batches(ops.clone()), vec![ops]);
     }
 }
@@ -0,0 +1,534 @@
+//! Optional mutual TLS for the internal transports between ReadySet processes.
+//!
+//! When configured (see [`InternalTlsOptions`]), every connection between ReadySet servers and
+//! adapters - HTTP requests to the controller and workers, channels between domains, and table and
+//! reader RPCs - is encrypted, and *both* ends of each connection must present a certificate signed
+//! by the configured certificate authority.
+//!
+//! Since the addresses of domains, readers and workers are assigned dynamically, certificates are
+//! not verified against the address being connected to, but against a single server name shared
+//! by all ReadySet processes in the deployment (`--internal-tls-server-name`), which must be
+//! included as a DNS subject alternative name in each process's certificate.
+//!
+//! The certificate and private key are re-read from disk whenever their modification times
+//! change, so they can be rotated without restarting the process - new connections will use the
+//! new certificate, and existing connections are unaffected. To rotate the certificate authority
+//! itself, first deploy a bundle containing both the old and the new authority to all processes.
+//!
+//! The TLS configuration is global to the process, and is installed once on startup with
+//! [`InternalTlsOptions::install`].
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::task::{Context as TaskContext, Poll};
+use std::time::SystemTime;
+
+use anyhow::{anyhow, bail, Context};
+use clap::Args;
+use rustls::client::{ResolvesClientCert, ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+use rustls::server::{AllowAnyAuthenticatedClient, ClientHello, ResolvesServerCert};
+use rustls::sign::{self, CertifiedKey};
+use rustls::{
+    Certificate, ClientConfig, ClientConnection, PrivateKey, RootCertStore, ServerConfig,
+    ServerName, SignatureScheme, StreamOwned,
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tracing::{info, warn};
+
+/// The TLS configuration installed for this process, if any
+static INTERNAL_TLS: OnceLock<InternalTls> = OnceLock::new();
+
+/// Returns the TLS configuration to use for internal connections, or [`None`] if internal
+/// connections are in plaintext.
+pub fn internal() -> Option<&'static InternalTls> {
+    INTERNAL_TLS.get()
+}
+
+/// Command-line options for configuring mutual TLS between ReadySet processes.
+///
+/// This option struct is intended to be embedded inside of a larger option struct using
+/// `#[clap(flatten)]`. All ReadySet servers and adapters in a deployment must be configured the
+/// same way.
+#[derive(Args, Debug, Clone)]
+#[group(skip)]
+pub struct InternalTlsOptions {
+    /// Path to a PEM-encoded bundle of the certificate authorities to verify the certificates of
+    /// other ReadySet processes against. If set, all connections between ReadySet servers and
+    /// adapters use mutual TLS.
+    #[clap(long, env = "INTERNAL_TLS_CA", requires = "internal_tls_cert")]
+    pub internal_tls_ca: Option<PathBuf>,
+
+    /// Path to the PEM-encoded certificate (chain) this process presents to other ReadySet
+    /// processes. Re-read whenever the file changes.
+    #[clap(long, env = "INTERNAL_TLS_CERT", requires = "internal_tls_key")]
+    pub internal_tls_cert: Option<PathBuf>,
+
+    /// Path to the PEM-encoded private key for `--internal-tls-cert`. Re-read whenever the file
+    /// changes.
+    #[clap(long, env = "INTERNAL_TLS_KEY", requires = "internal_tls_ca")]
+    pub internal_tls_key: Option<PathBuf>,
+
+    /// Name that the certificates of all ReadySet processes are verified against
+    #[clap(long, env = "INTERNAL_TLS_SERVER_NAME", default_value = "readyset")]
+    pub internal_tls_server_name: String,
+}
+
+impl InternalTlsOptions {
+    /// If internal TLS is enabled, load the configured certificates and install them as the TLS
+    /// configuration for all internal connections made and accepted by this process.
+    pub fn install(&self) -> anyhow::Result<()> {
+        let (ca, cert, key) = match (
+            &self.internal_tls_ca,
+            &self.internal_tls_cert,
+            &self.internal_tls_key,
+        ) {
+            (Some(ca), Some(cert), Some(key)) => (ca, cert, key),
+            (None, None, None) => return Ok(()),
+            _ => bail!(
+                "--internal-tls-ca, --internal-tls-cert and --internal-tls-key must all be \
+                 specified together"
+            ),
+        };
+
+        let tls = InternalTls::load(
+            ca,
+            cert.clone(),
+            key.clone(),
+            &self.internal_tls_server_name,
+        )?;
+        INTERNAL_TLS
+            .set(tls)
+            .map_err(|_| anyhow!("internal TLS configuration was already installed"))?;
+        info!(
+            server_name = %self.internal_tls_server_name,
+            "Enabled mutual TLS for internal connections"
+        );
+        Ok(())
+    }
+}
+
+/// Client and server TLS configurations for connections between ReadySet processes
+pub struct InternalTls {
+    client: Arc<ClientConfig>,
+    server: Arc<ServerConfig>,
+    server_name: ServerName,
+}
+
+impl std::fmt::Debug for InternalTls {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InternalTls")
+            .field("server_name", &self.server_name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl InternalTls {
+    fn load(ca: &Path, cert: PathBuf, key: PathBuf, server_name: &str) -> anyhow::Result<Self> {
+        let mut roots = RootCertStore::empty();
+        for ca_cert in load_certs(ca)? {
+            roots
+                .add(&ca_cert)
+                .with_context(|| format!("invalid CA certificate in {}", ca.display()))?;
+        }
+        let server_name = ServerName::try_from(server_name)
+            .map_err(|_| anyhow!("invalid internal TLS server name: {}", server_name))?;
+        let identity = Arc::new(ReloadingIdentity::load(cert, key)?);
+
+        let mut server = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots.clone()))
+            .with_cert_resolver(identity.clone());
+        // The controller and worker HTTP servers speak both HTTP/2 (to `ReadySetHandle`s) and
+        // HTTP/1.1 (to other servers)
+        server.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+        let client = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(SharedNameVerifier {
+                inner: WebPkiVerifier::new(roots, None),
+                server_name: server_name.clone(),
+            }))
+            .with_client_cert_resolver(identity);
+
+        Ok(Self {
+            client: Arc::new(client),
+            server: Arc::new(server),
+            server_name,
+        })
+    }
+
+    /// Returns the TLS configuration to use when connecting to other ReadySet processes
+    pub fn client_config(&self) -> Arc<ClientConfig> {
+        Arc::clone(&self.client)
+    }
+
+    /// Returns the TLS configuration to use when accepting connections from other ReadySet
+    /// processes
+    pub fn server_config(&self) -> Arc<ServerConfig> {
+        Arc::clone(&self.server)
+    }
+
+    /// Perform a TLS handshake as the client side of the given connection to another ReadySet
+    /// process
+    pub async fn connect<S>(&self, stream: S) -> io::Result<tokio_rustls::client::TlsStream<S>>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        tokio_rustls::TlsConnector::from(self.client_config())
+            .connect(self.server_name.clone(), stream)
+            .await
+    }
+
+    /// Perform a TLS handshake as the server side of the given connection from another ReadySet
+    /// process
+    pub async fn accept<S>(&self, stream: S) -> io::Result<tokio_rustls::server::TlsStream<S>>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        tokio_rustls::TlsAcceptor::from(self.server_config())
+            .accept(stream)
+            .await
+    }
+
+    /// Wrap the given blocking connection to another ReadySet process in a TLS client session.
+    /// The handshake is performed on the first read from or write to the returned stream.
+    pub fn connect_sync<S>(&self, stream: S) -> io::Result<StreamOwned<ClientConnection, S>>
+    where
+        S: Read + Write,
+    {
+        let conn = ClientConnection::new(self.client_config(), self.server_name.clone())
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        Ok(StreamOwned::new(conn, stream))
+    }
+}
+
+/// A connection between ReadySet processes, which is encrypted if internal TLS is enabled
+pub enum MaybeTlsStream<S> {
+    /// A plaintext connection
+    Plain(S),
+    /// The client side of a TLS connection
+    Client(Box<tokio_rustls::client::TlsStream<S>>),
+    /// The server side of a TLS connection
+    Server(Box<tokio_rustls::server::TlsStream<S>>),
+}
+
+impl<S> MaybeTlsStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Establish the client side of a connection to another ReadySet process over `stream`,
+    /// performing a TLS handshake if internal TLS is enabled
+    pub async fn connect(stream: S) -> io::Result<Self> {
+        match internal() {
+            Some(tls) => Ok(Self::Client(Box::new(tls.connect(stream).await?))),
+            None => Ok(Self::Plain(stream)),
+        }
+    }
+
+    /// Establish the server side of a connection from another ReadySet process over `stream`,
+    /// performing a TLS handshake if internal TLS is enabled
+    pub async fn accept(stream: S) -> io::Result<Self> {
+        match internal() {
+            Some(tls) => Ok(Self::Server(Box::new(tls.accept(stream).await?))),
+            None => Ok(Self::Plain(stream)),
+        }
+    }
+
+    /// Returns a reference to the underlying connection
+    pub fn get_ref(&self) -> &S {
+        match self {
+            Self::Plain(s) => s,
+            Self::Client(s) => s.get_ref().0,
+            Self::Server(s) => s.get_ref().0,
+        }
+    }
+}
+
+impl<S> AsyncRead for MaybeTlsStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(s) => Pin::new(s).poll_read(cx, buf),
+            Self::Client(s) => Pin::new(s).poll_read(cx, buf),
+            Self::Server(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl<S> AsyncWrite for MaybeTlsStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Plain(s) => Pin::new(s).poll_write(cx, buf),
+            Self::Client(s) => Pin::new(s).poll_write(cx, buf),
+            Self::Server(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(s) => Pin::new(s).poll_flush(cx),
+            Self::Client(s) => Pin::new(s).poll_flush(cx),
+            Self::Server(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(s) => Pin::new(s).poll_shutdown(cx),
+            Self::Client(s) => Pin::new(s).poll_shutdown(cx),
+            Self::Server(s) => Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
+
+/// A blocking connection to another ReadySet process, which is encrypted if internal TLS is enabled
+pub enum MaybeTlsSyncStream {
+    /// A plaintext connection
+    Plain(TcpStream),
+    /// A TLS connection
+    Tls(Box<StreamOwned<ClientConnection, TcpStream>>),
+}
+
+impl MaybeTlsSyncStream {
+    /// Establish a connection to another ReadySet process over `stream`, wrapping it in a TLS
+    /// session if internal TLS is enabled
+    pub fn connect(stream: TcpStream) -> io::Result<Self> {
+        match internal() {
+            Some(tls) => Ok(Self::Tls(Box::new(tls.connect_sync(stream)?))),
+            None => Ok(Self::Plain(stream)),
+        }
+    }
+
+    /// Returns a reference to the underlying connection
+    pub fn get_ref(&self) -> &TcpStream {
+        match self {
+            Self::Plain(s) => s,
+            Self::Tls(s) => &s.sock,
+        }
+    }
+
+    /// Returns the local address of the underlying connection
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.get_ref().local_addr()
+    }
+
+    /// Returns the remote address of the underlying connection
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.get_ref().peer_addr()
+    }
+}
+
+impl Read for MaybeTlsSyncStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(s) => s.read(buf),
+            Self::Tls(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for MaybeTlsSyncStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(s) => s.write(buf),
+            Self::Tls(s) => s.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(s) => s.flush(),
+            Self::Tls(s) => s.flush(),
+        }
+    }
+}
+
+/// Verifies server certificates against the shared server name of the deployment, rather than the
+/// name (or address) that was connected to.
+struct SharedNameVerifier {
+    inner: WebPkiVerifier,
+    server_name: ServerName,
+}
+
+impl ServerCertVerifier for SharedNameVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        _server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            &self.server_name,
+            scts,
+            ocsp_response,
+            now,
+        )
+    }
+}
+
+/// A certificate and private key loaded from disk, which are reloaded the next time they're used
+/// after either of the files change.
+struct ReloadingIdentity {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    current: RwLock<(SystemTime, Arc<CertifiedKey>)>,
+}
+
+impl ReloadingIdentity {
+    fn load(cert_path: PathBuf, key_path: PathBuf) -> anyhow::Result<Self> {
+        let modified = last_modified(&cert_path, &key_path)?;
+        let key = load_certified_key(&cert_path, &key_path)?;
+        Ok(Self {
+            cert_path,
+            key_path,
+            current: RwLock::new((modified, key)),
+        })
+    }
+
+    /// Returns the current certificate and key, first reloading them if either of the files have
+    /// changed since they were last loaded. If reloading fails, keeps using the previous
+    /// certificate and key.
+    fn current(&self) -> Arc<CertifiedKey> {
+        #[allow(clippy::unwrap_used)] // Only fails if the lock is poisoned
+        let (loaded_at, key) = self.current.read().unwrap().clone();
+        let modified = match last_modified(&self.cert_path, &self.key_path) {
+            Ok(modified) if modified > loaded_at => modified,
+            Ok(_) => return key,
+            Err(error) => {
+                warn!(error = %error, "Could not check internal TLS certificate for changes");
+                return key;
+            }
+        };
+
+        match load_certified_key(&self.cert_path, &self.key_path) {
+            Ok(new_key) => {
+                info!(cert = %self.cert_path.display(), "Reloaded internal TLS certificate");
+                #[allow(clippy::unwrap_used)] // Only fails if the lock is poisoned
+                let mut current = self.current.write().unwrap();
+                *current = (modified, Arc::clone(&new_key));
+                new_key
+            }
+            Err(error) => {
+                warn!(%error, "Could not reload internal TLS certificate, using previous one");
+                key
+            }
+        }
+    }
+}
+
+impl ResolvesServerCert for ReloadingIdentity {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current())
+    }
+}
+
+impl ResolvesClientCert for ReloadingIdentity {
+    fn resolve(
+        &self,
+        _acceptable_issuers: &[&[u8]],
+        _sigschemes: &[SignatureScheme],
+    ) -> Option<Arc<CertifiedKey>> {
+        Some(self.current())
+    }
+
+    fn has_certs(&self) -> bool {
+        true
+    }
+}
+
+fn last_modified(cert_path: &Path, key_path: &Path) -> anyhow::Result<SystemTime> {
+    let modified = |path: &Path| {
+        std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .with_context(|| format!("could not stat {}", path.display()))
+    };
+    Ok(modified(cert_path)?.max(modified(key_path)?))
+}
+
+fn open(path: &Path) -> anyhow::Result<BufReader<File>> {
+    File::open(path)
+        .map(BufReader::new)
+        .with_context(|| format!("could not open {}", path.display()))
+}
+
+fn load_certs(path: &Path) -> anyhow::Result<Vec<Certificate>> {
+    let certs = rustls_pemfile::certs(&mut open(path)?)
+        .with_context(|| format!("could not parse certificates in {}", path.display()))?;
+    if certs.is_empty() {
+        bail!("no certificates found in {}", path.display());
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn load_private_key(path: &Path) -> anyhow::Result<PrivateKey> {
+    let mut reader = open(path)?;
+    loop {
+        match rustls_pemfile::read_one(&mut reader)
+            .with_context(|| format!("could not parse private key in {}", path.display()))?
+        {
+            Some(rustls_pemfile::Item::PKCS8Key(key))
+            | Some(rustls_pemfile::Item::RSAKey(key))
+            | Some(rustls_pemfile::Item::ECKey(key)) => return Ok(PrivateKey(key)),
+            Some(_) => continue,
+            None => bail!("no private key found in {}", path.display()),
+        }
+    }
+}
+
+fn load_certified_key(cert_path: &Path, key_path: &Path) -> anyhow::Result<Arc<CertifiedKey>> {
+    let certs = load_certs(cert_path)?;
+    let key = sign::any_supported_type(&load_private_key(key_path)?)
+        .map_err(|_| anyhow!("unsupported private key type in {}", key_path.display()))?;
+    Ok(Arc::new(CertifiedKey::new(certs, key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn install_requires_all_paths() {
+        let opts = InternalTlsOptions {
+            internal_tls_ca: Some("ca.pem".into()),
+            internal_tls_cert: Some("cert.pem".into()),
+            internal_tls_key: None,
+            internal_tls_server_name: "readyset".into(),
+        };
+        assert!(opts.install().is_err());
+        assert!(internal().is_none());
+    }
+
+    #[test]
+    fn install_without_paths_is_plaintext() {
+        let opts = InternalTlsOptions {
+            internal_tls_ca: None,
+            internal_tls_cert: None,
+            internal_tls_key: None,
+            internal_tls_server_name: "readyset".into(),
+        };
+        opts.install().unwrap();
+        assert!(internal().is_none());
+    }
+}
@@ -20,7 +20,7 @@ use futures_util::stream::{StreamExt, TryStreamExt};
 use futures_util::{future, ready};
 use nom_sql::{
     BinaryOperator, Column, ColumnConstraint, ColumnSpecification, ItemPlaceholder, Literal,
-    Relation, SelectStatement, SqlIdentifier,
+    Relation, SelectStatement, SqlIdentifier, SqlType,
 };
 use petgraph::graph::NodeIndex;
 use proptest::arbitrary::Arbitrary;
@@ -51,10 +51,11 @@ pub(crate) mod results;
 
 use self::results::{ResultIterator, Results};
 use crate::consistency::Timestamp;
+use crate::tls::MaybeTlsStream;
 use crate::{ReaderAddress, Tagged, Tagger};
 
 type Transport = AsyncBincodeStream<
-    tokio::net::TcpStream,
+    MaybeTlsStream<tokio::net::TcpStream>,
     Tagged<ReadReply>,
     Instrumented<Tagged<ReadQuery>>,
     AsyncDestination,
@@ -146,6 +147,8 @@ pub struct ColumnBase {
     pub column: SqlIdentifier,
     /// The name of the base table for this column
     pub table: Relation,
+    /// The type of the column in the base table
+    pub sql_type: SqlType,
     /// A list of constraints on the column
     pub constraints: Vec<ColumnConstraint>,
 }
@@ -173,6 +176,7 @@ impl ColumnSchema {
             base: Some(ColumnBase {
                 column: spec.column.name.clone(),
                 table,
+                sql_type: spec.sql_type.clone(),
                 constraints: spec.constraints,
             }),
             column: spec.column,
@@ -231,6 +235,7 @@ impl Service<()> for Endpoint {
         async move {
             let s = tokio::time::timeout(timeout, f).await??;
             s.set_nodelay(true)?;
+            let s = MaybeTlsStream::connect(s).await?;
             let s = AsyncBincodeStream::from(s).for_async();
             let t = multiplex::MultiplexTransport::new(s, Tagger::default());
             Ok(multiplex::Client::with_error_handler(
@@ -800,6 +805,13 @@ pub enum ReadQuery {
         /// Where to read from
         target: ReaderAddress,
     },
+    /// Read the versions of the rows for the given keys in a leaf view
+    KeyVersions {
+        /// Where to read from
+        target: ReaderAddress,
+        /// The keys to read the versions of
+        keys: Vec<KeyComparison>,
+    },
 }
 
 /// The result of a lookup to a view.
@@ -859,6 +871,8 @@ pub enum ReadReply<D = ReadReplyBatch> {
     Size(usize),
     // Read keys of view
     Keys(Vec<Vec<DfValue>>),
+    /// Read the versions of the rows for a set of keys in a view
+    KeyVersions(Vec<u64>),
 }
 
 impl<D> ReadReply<D> {
@@ -1093,6 +1107,37 @@ pub enum View {
     MultipleReused(Vec1<ReusedReaderHandle>),
 }
 
+/// Limits on the size of the result set returned by a single read from a view. Reads whose results
+/// exceed either limit fail with [`ReadySetError::ResultRowLimitExceeded`] or
+/// [`ReadySetError::ResultSizeLimitExceeded`], rather than returning any results.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResultLimits {
+    /// The maximum number of rows a read may return
+    pub max_rows: Option<usize>,
+    /// The maximum size, in bytes, of the serialized rows a read may return
+    pub max_bytes: Option<usize>,
+}
+
+impl ResultLimits {
+    /// Returns true if neither limit is set
+    pub fn is_unlimited(&self) -> bool {
+        self.max_rows.is_none() && self.max_bytes.is_none()
+    }
+
+    /// Check the given number of rows and serialized bytes read so far against these limits
+    pub fn check(&self, rows: usize, bytes: usize) -> ReadySetResult<()> {
+        match (self.max_rows, self.max_bytes) {
+            (Some(max_rows), _) if rows > max_rows => {
+                Err(ReadySetError::ResultRowLimitExceeded { max_rows })
+            }
+            (_, Some(max_bytes)) if bytes > max_bytes => {
+                Err(ReadySetError::ResultSizeLimitExceeded { max_bytes })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 /// A read query to be run against a [`View`].
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ViewQuery {
@@ -1117,6 +1162,14 @@ pub struct ViewQuery {
     // TODO(justin): Verify reads block on timestamps once timestamps have a definition
     // with Ord.
     pub timestamp: Option<Timestamp>,
+    /// The maximum amount of time to wait for the results of a blocking read, including any
+    /// upqueries it triggers. If `None`, or longer than the server's configured upquery timeout,
+    /// the server's upquery timeout is used instead.
+    #[serde(default)]
+    pub timeout: Option<Duration>,
+    /// Limits on the number of rows and bytes the read may return
+    #[serde(default)]
+    pub result_limits: ResultLimits,
 }
 
 // TODO(andrew): consolidate From impls once RYW fully adopted
@@ -1131,6 +1184,8 @@ impl From<(Vec<KeyComparison>, bool, Option<Timestamp>)> for ViewQuery {
             offset: None,
             filter: None,
             timestamp: ticket,
+            timeout: None,
+            result_limits: ResultLimits::default(),
         }
     }
 }
@@ -1144,6 +1199,8 @@ impl From<(Vec<KeyComparison>, bool)> for ViewQuery {
             limit: None,
             offset: None,
             timestamp: None,
+            timeout: None,
+            result_limits: ResultLimits::default(),
         }
     }
 }
@@ -1264,6 +1321,8 @@ impl Service<ViewQuery> for ReaderHandle {
                             limit: query.limit,
                             offset: query.offset,
                             timestamp: query.timestamp.clone(),
+                            timeout: query.timeout,
+                            result_limits: query.result_limits,
                         },
                     }));
 
@@ -1432,6 +1491,71 @@ impl ReaderHandle {
         Ok(vec)
     }
 
+    /// Get the versions of the rows for each of the given keys in this view, derived from the write
+    /// epoch of the readers for this view.
+    ///
+    /// The version of a key never decreases, and changes whenever the rows for that key change, so
+    /// clients can use versions to cheaply detect whether the results for a key have changed. To
+    /// make sure a version covers the results read for a key, the version must be read *before*
+    /// the results.
+    #[instrument(level = "info", skip(self))]
+    pub async fn key_versions(&mut self, keys: Vec<KeyComparison>) -> ReadySetResult<Vec<u64>> {
+        future::poll_fn(|cx| self.poll_ready(cx)).await?;
+
+        let mut shard_keys = vec![Vec::new(); self.shards.len()];
+        for (i, key) in keys.iter().enumerate() {
+            for shard in key.shard_keys(self.shards.len()) {
+                #[allow(clippy::indexing_slicing)]
+                // We built `shard_keys` to be the correct length, so it's safe to access it by
+                // index in this case.
+                shard_keys[shard].push((i, key.clone()));
+            }
+        }
+
+        let node = self.node;
+        let name = self.name.clone();
+        let mut rsps = self
+            .shards
+            .iter_mut()
+            .enumerate()
+            .zip(shard_keys)
+            .map(|((shardi, shard), shard_keys)| {
+                let (idxs, keys): (Vec<_>, Vec<_>) = shard_keys.into_iter().unzip();
+                shard
+                    .call(Instrumented::from(Tagged::from(ReadQuery::KeyVersions {
+                        target: ReaderAddress {
+                            node,
+                            name: name.clone(),
+                            shard: shardi,
+                        },
+                        keys,
+                    })))
+                    .map_ok(move |reply| (idxs, reply))
+            })
+            .collect::<FuturesUnordered<_>>();
+
+        // Range keys are read from every shard, so take the highest version of all of them
+        let mut versions = vec![0; keys.len()];
+        while let Some((idxs, reply)) = rsps
+            .next()
+            .await
+            .transpose()
+            .map_err(rpc_err!("View::key_versions"))?
+        {
+            if let ReadReply::KeyVersions(shard_versions) = reply.v {
+                for (i, version) in idxs.into_iter().zip(shard_versions) {
+                    if let Some(v) = versions.get_mut(i) {
+                        *v = version.max(*v);
+                    }
+                }
+            } else {
+                unreachable!();
+            }
+        }
+
+        Ok(versions)
+    }
+
     // TODO(andrew): consolidate RYW and normal reads into cohesive API once API design is settled.
     // RYW functionality currently added as duplicate methods so as not to disrupt current
     // reader usage until RYW is fully adopted
@@ -1761,6 +1885,8 @@ impl ReaderHandle {
             limit,
             offset,
             timestamp: ticket,
+            timeout: None,
+            result_limits: ResultLimits::default(),
         }))
     }
 }
@@ -2047,6 +2173,7 @@ mod tests {
                         base: Some(ColumnBase {
                             table: "t".into(),
                             column: "x".into(),
+                            sql_type: SqlType::Int(None),
                             constraints: vec![],
                         }),
                     },
@@ -2059,6 +2186,7 @@ mod tests {
                         base: Some(ColumnBase {
                             table: "t".into(),
                             column: "y".into(),
+                            sql_type: SqlType::Text,
                             constraints: vec![],
                         }),
                     },
@@ -2073,6 +2201,7 @@ mod tests {
                         base: Some(ColumnBase {
                             table: "t".into(),
                             column: "x".into(),
+                            sql_type: SqlType::Int(None),
                             constraints: vec![],
                         }),
                     },
@@ -2085,6 +2214,7 @@ mod tests {
                         base: Some(ColumnBase {
                             table: "t".into(),
                             column: "y".into(),
+                            sql_type: SqlType::Text,
                             constraints: vec![],
                         }),
                     },
@@ -126,6 +126,29 @@ struct RowComparator {
     order_by: Arc<[(usize, OrderType)]>,
 }
 
+impl RowComparator {
+    /// Returns true if every row in each of the given (individually sorted) result sets sorts no
+    /// later than every row in the next non-empty result set, meaning that concatenating the
+    /// result sets yields a sorted result without needing to merge them.
+    fn is_ordered_across_sets(&self, data: &SharedResults) -> bool {
+        let mut sets = data.iter().filter(|rows| !rows.is_empty());
+        let mut prev = match sets.next() {
+            Some(rows) => rows,
+            None => return true,
+        };
+        for next in sets {
+            match (prev.last(), next.first()) {
+                (Some(last), Some(first)) if self.cmp(last, first) == Ordering::Greater => {
+                    return false
+                }
+                _ => {}
+            }
+            prev = next;
+        }
+        true
+    }
+}
+
 impl<T> Comparator<[T]> for RowComparator
 where
     T: Ord,
@@ -184,7 +207,16 @@ impl ResultIterator {
                     .iter()
                     .all(|s| { s.is_sorted_by(|a, b| Some(comparator.cmp(a, b))) }));
 
-                ResultIteratorInner::MultiKeyMerge(MergeIterator::new(data, comparator))
+                if comparator.is_ordered_across_sets(&data) {
+                    // Keyset fast path: when the results for each key sort entirely before the
+                    // results for the next key (such as when the ORDER BY columns are the key
+                    // columns of the reader, and the keys were looked up in order), the results
+                    // don't need to be merged, and an OFFSET can skip over whole result sets at
+                    // once rather than visiting every row
+                    ResultIteratorInner::MultiKey(MultiKeyIterator::new(data))
+                } else {
+                    ResultIteratorInner::MultiKeyMerge(MergeIterator::new(data, comparator))
+                }
             }
             (None, Some(aggregates)) => {
                 if aggregates.group_by.is_empty() {
@@ -245,7 +277,7 @@ impl ResultIterator {
                         aggregate: aggregates.clone(),
                         filter: filter.take(),
                     }),
-                    limit,
+                    limit: None,
                     offset: None,
                     default_row: default_row.clone(),
                     non_empty: false,
@@ -262,11 +294,10 @@ impl ResultIterator {
                 });
 
                 if let Some(offset) = offset {
-                    if offset >= results.len() {
-                        results.clear();
-                    } else {
-                        results.drain(offset..);
-                    }
+                    results.drain(..offset.min(results.len()));
+                }
+                if let Some(limit) = limit {
+                    results.truncate(limit);
                 }
 
                 return ResultIterator::owned(vec![Results {
@@ -405,6 +436,43 @@ impl MultiKeyIterator {
     }
 }
 
+impl MultiKeyIterator {
+    /// Advance the iterator by `n` rows, skipping over whole result sets at a time
+    fn skip(&mut self, mut n: usize) {
+        if n == 0 {
+            return;
+        }
+        if self.row.is_none() {
+            // Position the iterator on the first row
+            self.advance();
+            n -= 1;
+        }
+
+        while n > 0 {
+            let (rows, row) = match (self.data.get(self.set), self.row) {
+                (Some(rows), Some(row)) => (rows, *row),
+                _ => return,
+            };
+            if row + n < rows.len() {
+                self.row = NonMaxUsize::new(row + n);
+                return;
+            }
+
+            // Move to the first row of the next non-empty set
+            n -= rows.len() - row;
+            self.set += 1;
+            self.row = Some(NonMaxUsize::zero());
+            while self
+                .data
+                .get(self.set)
+                .map_or(false, |rows| rows.is_empty())
+            {
+                self.set += 1;
+            }
+        }
+    }
+}
+
 impl StreamingIterator for MultiKeyIterator {
     type Item = [DfValue];
 
@@ -560,10 +628,20 @@ impl StreamingIterator for ResultIterator {
     #[inline(always)]
     fn advance(&mut self) {
         if let Some(offset) = self.offset.take() {
-            for _ in 0..offset {
-                self.advance_filtered();
-                if self.inner.get().is_none() {
-                    break;
+            if offset > 0 {
+                // The default row only ever takes the place of the first row
+                self.default_row = None;
+            }
+            match &mut self.inner {
+                // Without a filter, there's no need to visit each of the skipped rows
+                ResultIteratorInner::MultiKey(inner) if self.filter.is_none() => inner.skip(offset),
+                _ => {
+                    for _ in 0..offset {
+                        self.advance_filtered();
+                        if self.inner.get().is_none() {
+                            break;
+                        }
+                    }
                 }
             }
         }
@@ -625,3 +703,90 @@ impl From<ResultIterator> for Vec<Vec<DfValue>> {
         iter.into_vec()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results(sets: &[&[i32]]) -> SharedResults {
+        sets.iter()
+            .map(|set| {
+                triomphe::Arc::new(
+                    set.iter()
+                        .map(|v| vec![DfValue::from(*v)].into_boxed_slice())
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn ordered_post_lookup() -> PostLookup {
+        PostLookup {
+            order_by: Some(vec![(0, OrderType::OrderAscending)]),
+            ..Default::default()
+        }
+    }
+
+    fn values(iter: ResultIterator) -> Vec<i32> {
+        iter.into_iter()
+            .map(|row| i32::try_from(&row[0]).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn offset_across_ordered_sets() {
+        let data = results(&[&[1, 2], &[], &[3, 4, 5], &[6]]);
+        assert!(matches!(
+            ResultIterator::new(data.clone(), &ordered_post_lookup(), None, None, None).inner,
+            ResultIteratorInner::MultiKey(_)
+        ));
+
+        for offset in 0..8 {
+            assert_eq!(
+                values(ResultIterator::new(
+                    data.clone(),
+                    &ordered_post_lookup(),
+                    Some(2),
+                    Some(offset),
+                    None
+                )),
+                (1..=6).skip(offset).take(2).collect::<Vec<_>>(),
+                "offset {offset}"
+            );
+        }
+    }
+
+    #[test]
+    fn offset_across_overlapping_sets() {
+        let data = results(&[&[1, 4], &[2, 3, 5]]);
+        let iter = ResultIterator::new(data, &ordered_post_lookup(), Some(3), Some(1), None);
+        assert!(matches!(iter.inner, ResultIteratorInner::MultiKeyMerge(_)));
+        assert_eq!(values(iter), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn offset_skips_default_row() {
+        let post_lookup = PostLookup {
+            default_row: Some(Arc::new(vec![DfValue::from(0)].into_boxed_slice())),
+            ..Default::default()
+        };
+        assert_eq!(
+            values(ResultIterator::new(
+                results(&[&[]]),
+                &post_lookup,
+                None,
+                None,
+                None
+            )),
+            vec![0]
+        );
+        assert!(values(ResultIterator::new(
+            results(&[&[]]),
+            &post_lookup,
+            None,
+            Some(1),
+            None
+        ))
+        .is_empty());
+    }
+}
@@ -367,6 +367,8 @@ pub struct ServerStartParams {
     replicator_restart_timeout_secs: Option<u64>,
     /// Number of times to replicate reader domains.
     reader_replicas: Option<usize>,
+    /// Whether to move domains between workers as workers join and leave the cluster.
+    rebalance_domains: bool,
     /// Whether or not to auto restart the server process.
     auto_restart: bool,
     /// Whether the server should wait to receive a failpoint request before proceeding with the
@@ -429,6 +431,8 @@ pub struct DeploymentBuilder {
     replicator_restart_timeout_secs: Option<u64>,
     /// Number of times to replicate reader domains
     reader_replicas: Option<usize>,
+    /// Whether to move domains between workers as workers join and leave the cluster
+    rebalance_domains: bool,
     /// If true, will automatically restart the server/adapter processes
     auto_restart: bool,
     /// Sets whether the adapter, server, both, or neither should wait to receive a failpoint
@@ -514,6 +518,7 @@ impl DeploymentBuilder {
             pass: Some(pass),
             replicator_restart_timeout_secs: None,
             reader_replicas: None,
+            rebalance_domains: false,
             auto_restart: false,
             wait_for_failpoint: FailpointDestination::None,
             database_type,
@@ -644,6 +649,13 @@ impl DeploymentBuilder {
         self
     }
 
+    /// Sets whether to move domains between workers as workers join and leave the cluster, via
+    /// `--rebalance-policy balance`
+    pub fn rebalance_domains(mut self, rebalance_domains: bool) -> Self {
+        self.rebalance_domains = rebalance_domains;
+        self
+    }
+
     /// Sets whether or not to restart the adapter/server processes
     pub fn auto_restart(mut self, auto_restart: bool) -> Self {
         self.auto_restart = auto_restart;
@@ -714,6 +726,7 @@ impl DeploymentBuilder {
             authority_type: self.authority.to_string(),
             replicator_restart_timeout_secs: self.replicator_restart_timeout_secs,
             reader_replicas: self.reader_replicas,
+            rebalance_domains: self.rebalance_domains,
             auto_restart: self.auto_restart,
             wait_for_failpoint,
         }
@@ -1547,6 +1560,9 @@ async fn start_server(
     if let Some(rs) = server_start_params.reader_replicas {
         builder = builder.reader_replicas(rs);
     }
+    if server_start_params.rebalance_domains {
+        builder = builder.rebalance_policy("balance");
+    }
     if server_start_params.wait_for_failpoint {
         builder = builder.wait_for_failpoint();
     }
@@ -1613,6 +1629,9 @@ async fn start_adapter(
         if let Some(rs) = server_start_params.reader_replicas {
             builder = builder.reader_replicas(rs);
         }
+        if server_start_params.rebalance_domains {
+            builder = builder.rebalance_policy("balance");
+        }
     }
 
     if let Some(interval) = params.async_migration_interval {
@@ -127,6 +127,60 @@ async fn balance_base_table_domains() {
     deployment.teardown().await.unwrap();
 }
 
+#[clustertest]
+async fn rebalance_domains_onto_new_worker() {
+    let mut deployment = DeploymentBuilder::new("ct_rebalance_domains_onto_new_worker")
+        .with_servers(1, ServerParams::default())
+        .rebalance_domains(true)
+        .start()
+        .await
+        .unwrap();
+
+    deployment
+        .leader_handle()
+        .extend_recipe(
+            ChangeList::from_str(
+                "CREATE TABLE t (id int, val int);
+                 CREATE CACHE q1 FROM SELECT val FROM t WHERE id = ?;
+                 CREATE CACHE q2 FROM SELECT id FROM t WHERE val = ?;
+                 CREATE CACHE q3 FROM SELECT id, val FROM t WHERE id = ? AND val = ?;",
+                Dialect::DEFAULT_MYSQL,
+            )
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    deployment
+        .start_server(ServerParams::default(), true)
+        .await
+        .unwrap();
+
+    // The new worker should take over some of the reader domains from the first worker
+    eventually! {
+        let info = deployment.leader_handle().get_info().await.unwrap();
+        info.len() == 2 && info.values().all(|domains| !domains.is_empty())
+    }
+
+    let mut t = deployment.leader_handle().table("t").await.unwrap();
+    t.insert(vec![DfValue::from(1), DfValue::from(2)])
+        .await
+        .unwrap();
+    let mut q1 = deployment
+        .leader_handle()
+        .view("q1")
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+    eventually! {
+        let res = q1.lookup(&[1.into()], true).await.unwrap();
+        res.into_vec() == vec![vec![DfValue::from(2)]]
+    }
+
+    deployment.teardown().await.unwrap();
+}
+
 async fn get_metric(
     deployment: &mut DeploymentHandle,
     address: Url,
@@ -7,6 +7,8 @@ use serde::{Deserialize, Serialize};
 use strum_macros::{EnumCount, FromRepr};
 use test_strategy::Arbitrary;
 
+use crate::dialect::SqlEngine;
+
 /// Description for how string values should be compared against each other for ordering and
 /// equality.
 ///
@@ -90,6 +92,36 @@ impl Collation {
         }
     }
 
+    /// Returns the collation corresponding to the collation with the given name in the given SQL
+    /// engine, or `None` if that collation isn't supported, in which case the default collation
+    /// should be used.
+    ///
+    /// In MySQL, case-insensitive collations (such as `utf8mb4_general_ci`) map to [`Citext`], and
+    /// case-sensitive and binary collations (such as `utf8mb4_bin` or `binary`) map to [`Utf8`].
+    /// In PostgreSQL, only the byte-order collations (`C`, `POSIX`, and `ucs_basic`) are
+    /// supported, and map to [`Utf8`].
+    ///
+    /// [`Citext`]: Collation::Citext
+    /// [`Utf8`]: Collation::Utf8
+    pub fn from_name(engine: SqlEngine, name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        match engine {
+            SqlEngine::MySQL => {
+                if name == "binary" || name.ends_with("_bin") || name.ends_with("_cs") {
+                    Some(Self::Utf8)
+                } else if name.ends_with("_ci") {
+                    Some(Self::Citext)
+                } else {
+                    None
+                }
+            }
+            SqlEngine::PostgreSQL => match name.as_str() {
+                "c" | "posix" | "ucs_basic" | "default" => Some(Self::Utf8),
+                _ => None,
+            },
+        }
+    }
+
     /// Returns `true` if the collation is [`Utf8`].
     ///
     /// [`Utf8`]: Collation::Utf8
@@ -159,6 +191,32 @@ mod tests {
         citext_strings_equal("Į", "į");
     }
 
+    #[test]
+    fn from_name() {
+        assert_eq!(
+            Collation::from_name(SqlEngine::MySQL, "utf8mb4_general_ci"),
+            Some(Collation::Citext)
+        );
+        assert_eq!(
+            Collation::from_name(SqlEngine::MySQL, "UTF8MB4_0900_AI_CI"),
+            Some(Collation::Citext)
+        );
+        assert_eq!(
+            Collation::from_name(SqlEngine::MySQL, "utf8mb4_bin"),
+            Some(Collation::Utf8)
+        );
+        assert_eq!(
+            Collation::from_name(SqlEngine::MySQL, "binary"),
+            Some(Collation::Utf8)
+        );
+        assert_eq!(
+            Collation::from_name(SqlEngine::PostgreSQL, "C"),
+            Some(Collation::Utf8)
+        );
+        assert_eq!(Collation::from_name(SqlEngine::PostgreSQL, "en_US"), None);
+        assert_eq!(Collation::from_name(SqlEngine::MySQL, "utf8mb4"), None);
+    }
+
     #[test]
     fn citext_ordering() {
         #[track_caller]
@@ -0,0 +1,493 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use bytes::{BufMut, BytesMut};
+use mysql_time::MySqlTime;
+use proptest::arbitrary::{any, Arbitrary};
+use proptest::strategy::{BoxedStrategy, Strategy};
+use readyset_errors::{invalid_err, ReadySetError, ReadySetResult};
+use readyset_util::redacted::Sensitive;
+use serde::{Deserialize, Serialize};
+use tokio_postgres::types::{accepts, to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const MICROS_PER_MINUTE: i64 = 60 * MICROS_PER_SECOND;
+const MICROS_PER_HOUR: i64 = 60 * MICROS_PER_MINUTE;
+const MICROS_PER_DAY: i64 = 24 * MICROS_PER_HOUR;
+const DAYS_PER_MONTH: i64 = 30;
+
+/// A [PostgreSQL `interval`](https://www.postgresql.org/docs/current/datatype-datetime.html#DATATYPE-INTERVAL-INPUT).
+///
+/// As in PostgreSQL, intervals are stored as separate numbers of months, days, and microseconds,
+/// since the number of days in a month and the number of hours in a day vary. When comparing and
+/// hashing intervals a month is treated as 30 days and a day as 24 hours, so eg `'1 mon'` and
+/// `'30 days'` are equal.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Interval {
+    months: i32,
+    days: i32,
+    microseconds: i64,
+}
+
+impl Interval {
+    /// Construct a new interval from its component numbers of months, days, and microseconds
+    pub fn new(months: i32, days: i32, microseconds: i64) -> Self {
+        Self {
+            months,
+            days,
+            microseconds,
+        }
+    }
+
+    /// Returns the number of months in this interval
+    pub fn months(&self) -> i32 {
+        self.months
+    }
+
+    /// Returns the number of days in this interval, not including those in [`Self::months`]
+    pub fn days(&self) -> i32 {
+        self.days
+    }
+
+    /// Returns the number of microseconds in this interval, not including those in
+    /// [`Self::months`] or [`Self::days`]
+    pub fn microseconds(&self) -> i64 {
+        self.microseconds
+    }
+
+    /// Returns the smallest representable interval
+    pub fn min_value() -> Self {
+        Self::new(i32::MIN, i32::MIN, i64::MIN)
+    }
+
+    /// Returns the largest representable interval
+    pub fn max_value() -> Self {
+        Self::new(i32::MAX, i32::MAX, i64::MAX)
+    }
+
+    /// Returns the total length of this interval in microseconds, treating each month as 30 days
+    /// and each day as 24 hours
+    fn span(&self) -> i128 {
+        (i128::from(self.months) * i128::from(DAYS_PER_MONTH) + i128::from(self.days))
+            * i128::from(MICROS_PER_DAY)
+            + i128::from(self.microseconds)
+    }
+
+    /// Returns true if this interval has a length of zero
+    pub fn is_zero(&self) -> bool {
+        self.span() == 0
+    }
+}
+
+impl PartialEq for Interval {
+    fn eq(&self, other: &Self) -> bool {
+        self.span() == other.span()
+    }
+}
+
+impl Eq for Interval {}
+
+impl PartialOrd for Interval {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Interval {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.span().cmp(&other.span())
+    }
+}
+
+impl Hash for Interval {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.span().hash(state)
+    }
+}
+
+impl From<MySqlTime> for Interval {
+    fn from(t: MySqlTime) -> Self {
+        // MySqlTime is limited to 838:59:59, so this can't overflow
+        #[allow(clippy::unwrap_used)]
+        let microseconds = chrono::Duration::from(t).num_microseconds().unwrap();
+        Self::new(0, 0, microseconds)
+    }
+}
+
+/// As in PostgreSQL, converting an interval to a time discards everything but the time of day
+impl From<Interval> for MySqlTime {
+    fn from(i: Interval) -> Self {
+        MySqlTime::from_microseconds(i.microseconds.rem_euclid(MICROS_PER_DAY))
+    }
+}
+
+/// Formats intervals in PostgreSQL's default (`postgres`) interval output style, eg
+/// `1 year 2 mons -3 days +04:05:06.789`
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = [
+            (self.months / 12, "year", "years"),
+            (self.months % 12, "mon", "mons"),
+            (self.days, "day", "days"),
+        ];
+
+        // As in PostgreSQL, once a negative field has been written, positive fields are written
+        // with an explicit sign
+        let mut after_negative = false;
+        let mut empty = true;
+        for (value, singular, plural) in fields {
+            if value == 0 {
+                continue;
+            }
+            if !empty {
+                f.write_str(" ")?;
+            }
+            let sign = if after_negative && value > 0 { "+" } else { "" };
+            let unit = if value == 1 { singular } else { plural };
+            write!(f, "{sign}{value} {unit}")?;
+            after_negative |= value < 0;
+            empty = false;
+        }
+
+        if self.microseconds != 0 || empty {
+            if !empty {
+                f.write_str(" ")?;
+            }
+            if self.microseconds < 0 {
+                f.write_str("-")?;
+            } else if after_negative {
+                f.write_str("+")?;
+            }
+            let micros = self.microseconds.unsigned_abs();
+            let (hours, micros) = (
+                micros / MICROS_PER_HOUR as u64,
+                micros % MICROS_PER_HOUR as u64,
+            );
+            let (minutes, micros) = (
+                micros / MICROS_PER_MINUTE as u64,
+                micros % MICROS_PER_MINUTE as u64,
+            );
+            let (seconds, micros) = (
+                micros / MICROS_PER_SECOND as u64,
+                micros % MICROS_PER_SECOND as u64,
+            );
+            write!(f, "{hours:02}:{minutes:02}:{seconds:02}")?;
+            if micros != 0 {
+                let fraction = format!("{micros:06}");
+                write!(f, ".{}", fraction.trim_end_matches('0'))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The unit of a field in an interval literal, along with the number of months, days, or
+/// microseconds it represents
+#[derive(Clone, Copy)]
+enum Unit {
+    Months(i64),
+    Days(i64),
+    Microseconds(i64),
+}
+
+impl Unit {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s.to_ascii_lowercase().as_str() {
+            "microsecond" | "microseconds" | "us" | "usec" | "usecs" => Unit::Microseconds(1),
+            "millisecond" | "milliseconds" | "ms" | "msec" | "msecs" => Unit::Microseconds(1000),
+            "second" | "seconds" | "sec" | "secs" | "s" => Unit::Microseconds(MICROS_PER_SECOND),
+            "minute" | "minutes" | "min" | "mins" | "m" => Unit::Microseconds(MICROS_PER_MINUTE),
+            "hour" | "hours" | "hr" | "hrs" | "h" => Unit::Microseconds(MICROS_PER_HOUR),
+            "day" | "days" | "d" => Unit::Days(1),
+            "week" | "weeks" | "w" => Unit::Days(7),
+            "month" | "months" | "mon" | "mons" => Unit::Months(1),
+            "year" | "years" | "yr" | "yrs" | "y" => Unit::Months(12),
+            "decade" | "decades" => Unit::Months(120),
+            "century" | "centuries" => Unit::Months(1200),
+            "millennium" | "millennia" => Unit::Months(12000),
+            _ => return None,
+        })
+    }
+}
+
+/// Parses a (possibly signed, possibly fractional) number into its integral and fractional parts,
+/// both of which have the sign of the number
+fn parse_number(s: &str) -> Option<(i128, f64)> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (integral, fractional) = digits.split_once('.').unwrap_or((digits, ""));
+    if (integral.is_empty() && fractional.is_empty())
+        || !integral
+            .bytes()
+            .chain(fractional.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let integral = if integral.is_empty() {
+        0
+    } else {
+        integral.parse::<i128>().ok()?
+    };
+    let fractional = if fractional.is_empty() {
+        0.0
+    } else {
+        format!("0.{fractional}").parse::<f64>().ok()?
+    };
+
+    Some(if negative {
+        (-integral, -fractional)
+    } else {
+        (integral, fractional)
+    })
+}
+
+/// Parses a time field of an interval literal, of the form `[+-]HH:MM[:SS[.ffffff]]`, into a number
+/// of microseconds
+fn parse_time(s: &str) -> Option<i128> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let mut parts = s.split(':');
+    let hours = parts.next()?.parse::<u64>().ok()?;
+    let minutes = parts.next()?.parse::<u64>().ok()?;
+    let (seconds, fraction) = match parts.next() {
+        Some(seconds) if !seconds.starts_with(['+', '-']) => parse_number(seconds)?,
+        Some(_) => return None,
+        None => (0, 0.0),
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let micros = i128::from(hours) * i128::from(MICROS_PER_HOUR)
+        + i128::from(minutes) * i128::from(MICROS_PER_MINUTE)
+        + seconds * i128::from(MICROS_PER_SECOND)
+        + (fraction * MICROS_PER_SECOND as f64).round() as i128;
+    Some(if negative { -micros } else { micros })
+}
+
+/// Parses intervals written in PostgreSQL's `postgres` or `postgres_verbose` styles, eg
+/// `1 year 2 mons -3 days +04:05:06.789` or `@ 1 hour 30 mins ago`
+impl FromStr for Interval {
+    type Err = ReadySetError;
+
+    fn from_str(s: &str) -> ReadySetResult<Self> {
+        let mk_err = || invalid_err!("Invalid input syntax for type interval: {}", Sensitive(&s));
+
+        let trimmed = s.trim();
+        let mut tokens = trimmed
+            .strip_prefix('@')
+            .unwrap_or(trimmed)
+            .split_whitespace()
+            .peekable();
+        if tokens.peek().is_none() {
+            return Err(mk_err());
+        }
+
+        let (mut months, mut days, mut micros) = (0i128, 0i128, 0i128);
+        let mut ago = false;
+        while let Some(token) = tokens.next() {
+            if ago {
+                // `ago` is only allowed at the very end
+                return Err(mk_err());
+            }
+            if token.eq_ignore_ascii_case("ago") {
+                ago = true;
+                continue;
+            }
+            if token.contains(':') {
+                micros += parse_time(token).ok_or_else(mk_err)?;
+                continue;
+            }
+
+            let (integral, fractional) = parse_number(token).ok_or_else(mk_err)?;
+            // A number with no unit is a number of seconds
+            let unit = match tokens.peek().and_then(|unit| Unit::parse(unit)) {
+                Some(unit) => {
+                    tokens.next();
+                    unit
+                }
+                None => Unit::Microseconds(MICROS_PER_SECOND),
+            };
+
+            // Fractional months spill over into days, and fractional days into microseconds
+            match unit {
+                Unit::Months(n) => {
+                    months += integral * i128::from(n);
+                    let fractional_days = fractional * (n * DAYS_PER_MONTH) as f64;
+                    days += fractional_days.trunc() as i128;
+                    micros += (fractional_days.fract() * MICROS_PER_DAY as f64).round() as i128;
+                }
+                Unit::Days(n) => {
+                    days += integral * i128::from(n);
+                    micros += (fractional * (n * MICROS_PER_DAY) as f64).round() as i128;
+                }
+                Unit::Microseconds(n) => {
+                    micros += integral * i128::from(n) + (fractional * n as f64).round() as i128;
+                }
+            }
+        }
+
+        if ago {
+            (months, days, micros) = (-months, -days, -micros);
+        }
+
+        Ok(Self::new(
+            months.try_into().map_err(|_| mk_err())?,
+            days.try_into().map_err(|_| mk_err())?,
+            micros.try_into().map_err(|_| mk_err())?,
+        ))
+    }
+}
+
+impl ToSql for Interval {
+    fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.put_i64(self.microseconds);
+        out.put_i32(self.days);
+        out.put_i32(self.months);
+        Ok(IsNull::No)
+    }
+
+    accepts!(INTERVAL);
+
+    to_sql_checked!();
+}
+
+impl<'a> FromSql<'a> for Interval {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        if raw.len() != 16 {
+            return Err(format!("Invalid interval length: {}", raw.len()).into());
+        }
+        let (microseconds, rest) = raw.split_at(8);
+        let (days, months) = rest.split_at(4);
+        Ok(Self::new(
+            i32::from_be_bytes(months.try_into()?),
+            i32::from_be_bytes(days.try_into()?),
+            i64::from_be_bytes(microseconds.try_into()?),
+        ))
+    }
+
+    accepts!(INTERVAL);
+}
+
+impl Arbitrary for Interval {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Interval>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        any::<(i32, i32, i64)>()
+            .prop_map(|(months, days, microseconds)| Interval::new(months, days, microseconds))
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use readyset_util::{eq_laws, hash_laws, ord_laws};
+    use test_strategy::proptest;
+
+    use super::*;
+
+    fn parse(s: &str) -> Interval {
+        s.parse().unwrap()
+    }
+
+    fn fields(i: Interval) -> (i32, i32, i64) {
+        (i.months(), i.days(), i.microseconds())
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Interval::default().to_string(), "00:00:00");
+        assert_eq!(
+            Interval::new(
+                14,
+                3,
+                4 * MICROS_PER_HOUR + 5 * MICROS_PER_MINUTE + 6_789_000
+            )
+            .to_string(),
+            "1 year 2 mons 3 days 04:05:06.789"
+        );
+        assert_eq!(
+            Interval::new(0, -1, MICROS_PER_HOUR).to_string(),
+            "-1 days +01:00:00"
+        );
+        assert_eq!(Interval::new(-14, 0, 0).to_string(), "-1 years -2 mons");
+        assert_eq!(
+            Interval::new(0, 0, -90 * MICROS_PER_HOUR).to_string(),
+            "-90:00:00"
+        );
+    }
+
+    #[test]
+    fn parse_postgres_style() {
+        assert_eq!(
+            fields(parse("1 year 2 mons -3 days +04:05:06.789")),
+            (
+                14,
+                -3,
+                4 * MICROS_PER_HOUR + 5 * MICROS_PER_MINUTE + 6_789_000
+            )
+        );
+        assert_eq!(fields(parse("-00:00:01")), (0, 0, -MICROS_PER_SECOND));
+        assert_eq!(fields(parse("1:30")), (0, 0, 90 * MICROS_PER_MINUTE));
+    }
+
+    #[test]
+    fn parse_verbose_style() {
+        assert_eq!(
+            fields(parse("@ 1 hour 30 mins ago")),
+            (0, 0, -90 * MICROS_PER_MINUTE)
+        );
+        assert_eq!(fields(parse("2 weeks")), (0, 14, 0));
+        assert_eq!(fields(parse("1.5 months")), (1, 15, 0));
+        assert_eq!(fields(parse("10")), (0, 0, 10 * MICROS_PER_SECOND));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for s in [
+            "",
+            "ago",
+            "1 day ago 2 hours",
+            "1 fortnight",
+            "1:2:3:4",
+            "abc",
+        ] {
+            assert!(s.parse::<Interval>().is_err(), "{s:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn month_is_30_days() {
+        assert_eq!(parse("1 mon"), parse("30 days"));
+        assert_eq!(parse("1 day"), parse("24 hours"));
+        assert!(parse("1 mon") < parse("31 days"));
+    }
+
+    #[proptest]
+    fn display_parse_round_trip(interval: Interval) {
+        assert_eq!(fields(parse(&interval.to_string())), fields(interval));
+    }
+
+    #[proptest]
+    fn to_from_sql_round_trip(interval: Interval) {
+        let mut bytes = BytesMut::new();
+        interval.to_sql(&Type::INTERVAL, &mut bytes).unwrap();
+        let result = Interval::from_sql(&Type::INTERVAL, &bytes).unwrap();
+        assert_eq!(fields(result), fields(interval));
+    }
+
+    eq_laws!(Interval);
+    hash_laws!(Interval);
+    ord_laws!(Interval);
+}
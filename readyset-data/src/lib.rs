@@ -36,6 +36,7 @@ pub mod dialect;
 mod r#enum;
 mod float;
 mod integer;
+mod interval;
 mod serde;
 mod text;
 mod timestamp;
@@ -46,6 +47,7 @@ pub use ndarray::{ArrayD, IxDyn};
 pub use crate::array::Array;
 pub use crate::collation::Collation;
 pub use crate::dialect::Dialect;
+pub use crate::interval::Interval;
 pub use crate::r#type::{DfType, PgEnumMetadata, PgTypeCategory};
 pub use crate::serde::TextRef;
 pub use crate::text::{Text, TinyText};
@@ -111,6 +113,10 @@ pub enum DfValue {
     Array(Arc<Array>),
     /// Container type for arbitrary unserialized, unsupported types
     PassThrough(Arc<PassThrough>),
+    /// A UUID, stored natively rather than as text so that it can be compared and hashed cheaply
+    Uuid(Arc<Uuid>),
+    /// A PostgreSQL interval
+    Interval(Arc<Interval>),
     /// A sentinel maximal value.
     ///
     /// This value is always greater than all other [`DfValue`]s, except itself.
@@ -162,6 +168,8 @@ impl fmt::Display for DfValue {
             DfValue::PassThrough(ref p) => {
                 write!(f, "[{}:{:x?}]", p.ty.name(), p.data)
             }
+            DfValue::Uuid(ref u) => write!(f, "{}", u),
+            DfValue::Interval(ref i) => write!(f, "{}", i),
             DfValue::Max => f.write_str("MAX"),
         }
     }
@@ -222,6 +230,8 @@ impl DfValue {
                 ty: p.ty.clone(),
                 data: [].into(),
             })),
+            DfValue::Uuid(_) => DfValue::from(Uuid::nil()),
+            DfValue::Interval(_) => DfValue::from(Interval::min_value()),
             DfValue::Max => DfValue::None,
         }
     }
@@ -241,6 +251,8 @@ impl DfValue {
             DfValue::UnsignedInt(_) => DfValue::UnsignedInt(u64::max_value()),
             DfValue::Time(_) => DfValue::Time(MySqlTime::max_value()),
             DfValue::Numeric(_) => DfValue::from(Decimal::MAX),
+            DfValue::Uuid(_) => DfValue::from(Uuid::from_u128(u128::MAX)),
+            DfValue::Interval(_) => DfValue::from(Interval::max_value()),
             DfValue::TinyText(_)
             | DfValue::Text(_)
             | DfValue::ByteArray(_)
@@ -320,6 +332,8 @@ impl DfValue {
             DfValue::ByteArray(ref array) => !array.is_empty(),
             DfValue::Numeric(ref d) => !d.is_zero(),
             DfValue::BitVector(ref bits) => !bits.is_empty(),
+            DfValue::Uuid(ref u) => !u.is_nil(),
+            DfValue::Interval(ref i) => !i.is_zero(),
             // Truthiness only matters for mysql, and mysql doesn't have arrays, so we can kind of
             // pick whatever we want here - but it makes the most sense to try to limit falsiness to
             // only the things that mysql considers falsey
@@ -386,6 +400,8 @@ impl DfValue {
             Self::ByteArray(_) => Some(ByteArray),
             Self::Numeric(_) => Some(Numeric(None)),
             Self::BitVector(_) => Some(VarBit(None)),
+            Self::Uuid(_) => Some(SqlType::Uuid),
+            Self::Interval(_) => Some(SqlType::Interval),
             // TODO: Once this returns DfType instead of SqlType, an empty array and an array of
             // null should be Array(Unknown) not Unknown.
             Self::Array(vs) => Some(SqlType::Array(Box::new(
@@ -421,6 +437,8 @@ impl DfValue {
             Self::ByteArray(_) => Blob,
             Self::Numeric(_) => DfType::DEFAULT_NUMERIC,
             Self::BitVector(_) => VarBit(None),
+            Self::Uuid(_) => DfType::Uuid,
+            Self::Interval(_) => DfType::Interval,
             Self::Array(array) => Array(Box::new(
                 array
                     .values()
@@ -539,13 +557,13 @@ impl DfValue {
             DfValue::Float(f) => float::coerce_f64(f64::from(*f), to_ty, from_ty),
             DfValue::Double(f) => float::coerce_f64(*f, to_ty, from_ty),
             DfValue::Numeric(d) => float::coerce_decimal(d.as_ref(), to_ty, from_ty),
-            DfValue::Time(ts) => {
-                if let DfType::Text(collation) = to_ty {
+            DfValue::Time(ts) => match to_ty {
+                DfType::Text(collation) => {
                     Ok(DfValue::from_str_and_collation(&ts.to_string(), *collation))
-                } else {
-                    Err(mk_err())
                 }
-            }
+                DfType::Interval => Ok(DfValue::from(Interval::from(*ts))),
+                _ => Err(mk_err()),
+            },
             DfValue::BitVector(vec) => match to_ty {
                 DfType::VarBit(None) => Ok(self.clone()),
                 DfType::VarBit(max_size_opt) => match max_size_opt {
@@ -554,7 +572,18 @@ impl DfValue {
                 },
                 _ => Err(mk_err()),
             },
-            DfValue::ByteArray(_) | DfValue::Max => Err(mk_err()),
+            DfValue::Uuid(u) if to_ty.is_any_text() => {
+                DfValue::from(u.to_string()).coerce_to(to_ty, &DfType::DEFAULT_TEXT)
+            }
+            DfValue::Interval(i) if to_ty.is_any_text() => {
+                DfValue::from(i.to_string()).coerce_to(to_ty, &DfType::DEFAULT_TEXT)
+            }
+            DfValue::Interval(i) if matches!(to_ty, DfType::Time { .. }) => {
+                Ok(DfValue::Time(MySqlTime::from(**i)))
+            }
+            DfValue::Uuid(_) | DfValue::Interval(_) | DfValue::ByteArray(_) | DfValue::Max => {
+                Err(mk_err())
+            }
             DfValue::PassThrough(ref p) => Err(ReadySetError::DfValueConversionError {
                 src_type: format!("PassThrough[{}]", p.ty),
                 target_type: to_ty.to_string(),
@@ -575,6 +604,14 @@ impl DfValue {
                 .unwrap_or(DfValue::Int(0));
         } else if col_ty.is_array() && col_ty.innermost_array_type().is_enum() {
             *self = self.coerce_to(col_ty, &DfType::Unknown)?;
+        } else if let Some(collation) = col_ty.collation() {
+            // Tag strings with the collation of their column, so that they're compared according
+            // to that collation everywhere in the graph
+            if let Some((s, value_collation)) = self.as_str_and_collation() {
+                if value_collation != collation {
+                    *self = Self::from_str_and_collation(s, collation);
+                }
+            }
         }
 
         Ok(())
@@ -841,6 +878,8 @@ impl PartialEq for DfValue {
                 bits_a.as_ref() == bits_b.as_ref()
             }
             (DfValue::Array(vs_a), DfValue::Array(vs_b)) => vs_a == vs_b,
+            (DfValue::Uuid(a), DfValue::Uuid(b)) => a == b,
+            (DfValue::Interval(a), DfValue::Interval(b)) => a == b,
             (&DfValue::None, &DfValue::None) => true,
             (&DfValue::Max, &DfValue::Max) => true,
             _ => false,
@@ -993,6 +1032,8 @@ impl Ord for DfValue {
             (DfValue::ByteArray(array_a), DfValue::ByteArray(array_b)) => array_a.cmp(array_b),
             (DfValue::BitVector(bits_a), DfValue::BitVector(bits_b)) => bits_a.cmp(bits_b),
             (DfValue::Array(vs_a), DfValue::Array(vs_b)) => vs_a.cmp(vs_b),
+            (DfValue::Uuid(a), DfValue::Uuid(b)) => a.cmp(b),
+            (DfValue::Interval(a), DfValue::Interval(b)) => a.cmp(b),
 
             // for all other kinds of data types, just compare the variants in order
             (_, _) => DfValueKind::from(self).cmp(&DfValueKind::from(other)),
@@ -1028,6 +1069,8 @@ impl Hash for DfValue {
             DfValue::BitVector(ref bits) => bits.hash(state),
             DfValue::Array(ref vs) => vs.hash(state),
             DfValue::PassThrough(ref p) => p.hash(state),
+            DfValue::Uuid(ref u) => u.as_u128().hash(state),
+            DfValue::Interval(ref i) => i.hash(state),
         }
     }
 }
@@ -1162,6 +1205,48 @@ impl<'a> TryFrom<&'a DfValue> for Decimal {
     }
 }
 
+impl From<Uuid> for DfValue {
+    fn from(u: Uuid) -> Self {
+        DfValue::Uuid(Arc::new(u))
+    }
+}
+
+impl<'a> TryFrom<&'a DfValue> for Uuid {
+    type Error = ReadySetError;
+
+    fn try_from(dt: &'a DfValue) -> Result<Self, Self::Error> {
+        match dt {
+            DfValue::Uuid(u) => Ok(**u),
+            _ => Err(Self::Error::DfValueConversionError {
+                src_type: "DfValue".to_string(),
+                target_type: "Uuid".to_string(),
+                details: "".to_string(),
+            }),
+        }
+    }
+}
+
+impl From<Interval> for DfValue {
+    fn from(i: Interval) -> Self {
+        DfValue::Interval(Arc::new(i))
+    }
+}
+
+impl<'a> TryFrom<&'a DfValue> for Interval {
+    type Error = ReadySetError;
+
+    fn try_from(dt: &'a DfValue) -> Result<Self, Self::Error> {
+        match dt {
+            DfValue::Interval(i) => Ok(**i),
+            _ => Err(Self::Error::DfValueConversionError {
+                src_type: "DfValue".to_string(),
+                target_type: "Interval".to_string(),
+                details: "".to_string(),
+            }),
+        }
+    }
+}
+
 /// Bit vectors are represented as [`BitVec`].
 impl From<BitVec> for DfValue {
     fn from(b: BitVec) -> Self {
@@ -1261,6 +1346,8 @@ impl TryFrom<DfValue> for Literal {
             DfValue::ByteArray(ref array) => Ok(Literal::ByteArray(array.as_ref().clone())),
             DfValue::Numeric(ref d) => Ok(Literal::Numeric(d.mantissa(), d.scale())),
             DfValue::BitVector(ref bits) => Ok(Literal::BitVector(bits.as_ref().to_bytes())),
+            DfValue::Uuid(ref u) => Ok(Literal::String(u.to_string())),
+            DfValue::Interval(ref i) => Ok(Literal::String(i.to_string())),
             DfValue::Array(_) => unsupported!("Arrays not implemented yet"),
             DfValue::PassThrough(_) => internal!("PassThrough has no representation as a literal"),
             DfValue::Max => internal!("MAX has no representation as a literal"),
@@ -1774,6 +1861,10 @@ impl ToSql for DfValue {
             (Self::BitVector(ref bits), _) => bits.as_ref().to_sql(ty, out),
             (Self::Array(ref array), _) => array.as_ref().to_sql(ty, out),
             (Self::PassThrough(p), _) => p.data.as_ref().to_sql(&p.ty, out),
+            (Self::Uuid(u), &Type::UUID) => u.to_sql(ty, out),
+            (Self::Uuid(u), _) => u.to_string().to_sql(ty, out),
+            (Self::Interval(i), &Type::INTERVAL) => i.to_sql(ty, out),
+            (Self::Interval(i), _) => i.to_string().to_sql(ty, out),
         }
     }
 
@@ -1852,7 +1943,8 @@ impl<'a> FromSql<'a> for DfValue {
                     MacAddress::from_sql(ty, raw)?.to_string(MacAddressFormat::HexString),
                 )),
                 Type::INET => Ok(DfValue::from(IpInet::from_sql(ty, raw)?.to_string())),
-                Type::UUID => Ok(DfValue::from(Uuid::from_sql(ty, raw)?.to_string())),
+                Type::UUID => Ok(DfValue::from(Uuid::from_sql(ty, raw)?)),
+                Type::INTERVAL => Ok(DfValue::from(Interval::from_sql(ty, raw)?)),
                 Type::JSON | Type::JSONB => {
                     let raw = match (ty, raw) {
                         (&Type::JSONB, []) => {
@@ -1962,6 +2054,8 @@ impl TryFrom<&DfValue> for mysql_common::value::Value {
             }
             DfValue::BitVector(_) => internal!("MySQL does not support bit vector types"),
             DfValue::Array(_) => internal!("MySQL does not support array types"),
+            DfValue::Uuid(u) => Ok(Value::Bytes(u.to_string().into_bytes())),
+            DfValue::Interval(_) => internal!("MySQL does not support interval types"),
         }
     }
 }
@@ -2131,6 +2225,10 @@ impl Arbitrary for DfValue {
                 .prop_map(|bs| DfValue::BitVector(Arc::new(BitVec::from_bytes(&bs))))
                 .boxed(),
             Some(DfValueKind::Array) => any::<Array>().prop_map(DfValue::from).boxed(),
+            Some(DfValueKind::Uuid) => any::<u128>()
+                .prop_map(|u| DfValue::from(Uuid::from_u128(u)))
+                .boxed(),
+            Some(DfValueKind::Interval) => any::<Interval>().prop_map(DfValue::from).boxed(),
             Some(DfValueKind::PassThrough) => any::<(u32, Vec<u8>)>()
                 .prop_map(|(oid, data)| {
                     DfValue::PassThrough(Arc::new(PassThrough {
@@ -2288,8 +2386,12 @@ mod tests {
                 if t.to_chrono().naive_local().date().year() < 1000
                     || t.to_chrono().naive_local().date().year() > 9999 =>
                 false,
-            DfValue::ByteArray(_) | DfValue::BitVector(_) | DfValue::Array(_) | DfValue::Max =>
-                false,
+            DfValue::ByteArray(_)
+            | DfValue::BitVector(_)
+            | DfValue::Array(_)
+            | DfValue::Uuid(_)
+            | DfValue::Interval(_)
+            | DfValue::Max => false,
             _ => true,
         });
 
@@ -2419,6 +2521,21 @@ mod tests {
         )
     }
 
+    #[test]
+    fn coerce_for_table_op_sets_collation() {
+        let mut val = DfValue::from("AbC");
+        val.maybe_coerce_for_table_op(&DfType::VarChar(10, Collation::Citext))
+            .unwrap();
+        assert_eq!(val.as_str_and_collation(), Some(("AbC", Collation::Citext)));
+        assert_eq!(val, DfValue::from("abc"));
+
+        // Non-text values are left alone
+        let mut val = DfValue::from(1);
+        val.maybe_coerce_for_table_op(&DfType::Text(Collation::Citext))
+            .unwrap();
+        assert_eq!(val, DfValue::from(1));
+    }
+
     #[test]
     fn real_to_string() {
         let a_float: DfValue = DfValue::try_from(8.99_f32).unwrap();
@@ -3564,9 +3681,54 @@ mod tests {
 
         #[test]
         fn text_to_uuid() {
-            let input = DfValue::from(uuid::Uuid::new_v4().to_string());
+            let uuid = uuid::Uuid::new_v4();
+            let input = DfValue::from(uuid.to_string());
             let result = input.coerce_to(&DfType::Uuid, &DfType::Unknown).unwrap();
-            assert_eq!(input, result);
+            assert_eq!(result, DfValue::from(uuid));
+        }
+
+        #[test]
+        fn uuid_to_text() {
+            let uuid = uuid::Uuid::new_v4();
+            let result = DfValue::from(uuid)
+                .coerce_to(&DfType::DEFAULT_TEXT, &DfType::Uuid)
+                .unwrap();
+            assert_eq!(result, DfValue::from(uuid.to_string()));
+        }
+
+        #[test]
+        fn time_to_interval() {
+            let time = DfValue::Time(MySqlTime::from_hmsus(false, 25, 30, 0, 0));
+            let interval = time.coerce_to(&DfType::Interval, &DfType::Unknown).unwrap();
+            assert_eq!(interval.to_string(), "-25:30:00");
+        }
+
+        #[test]
+        fn interval_to_time() {
+            let interval = DfValue::from("1 day 02:03:04".parse::<Interval>().unwrap());
+            let time = interval
+                .coerce_to(
+                    &DfType::Time {
+                        subsecond_digits: 0,
+                    },
+                    &DfType::Unknown,
+                )
+                .unwrap();
+            assert_eq!(time, DfValue::Time(MySqlTime::from_hmsus(true, 2, 3, 4, 0)));
+        }
+
+        #[test]
+        fn text_to_interval() {
+            let interval = DfValue::from("1 mon")
+                .coerce_to(&DfType::Interval, &DfType::DEFAULT_TEXT)
+                .unwrap();
+            assert_eq!(interval, DfValue::from(Interval::new(0, 30, 0)));
+            assert_eq!(
+                interval
+                    .coerce_to(&DfType::DEFAULT_TEXT, &DfType::Interval)
+                    .unwrap(),
+                DfValue::from("1 mon")
+            );
         }
 
         macro_rules! bool_conversion {
@@ -3775,6 +3937,31 @@ mod tests {
             }
         }
 
+        #[test]
+        fn enum_ordering() {
+            // Enum values compare by the position of their variant, not alphabetically
+            let variants = ["small", "medium", "large"];
+            let enum_ty = DfType::from_enum_variants(variants.into_iter().map(Into::into), None);
+            let mut values = ["large", "small", "medium"]
+                .into_iter()
+                .map(|v| {
+                    DfValue::from(v)
+                        .coerce_to(&enum_ty, &DfType::DEFAULT_TEXT)
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
+            values.sort();
+            let labels = values
+                .iter()
+                .map(|v| {
+                    v.coerce_to(&DfType::DEFAULT_TEXT, &enum_ty)
+                        .unwrap()
+                        .to_string()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(labels, variants);
+        }
+
         #[test]
         fn int_to_unknown() {
             assert_eq!(
@@ -11,8 +11,9 @@ use serde::{Deserialize, Deserializer, Serialize};
 use serde_bytes::{ByteBuf, Bytes};
 use strum::VariantNames;
 use strum_macros::{EnumString, EnumVariantNames, FromRepr};
+use uuid::Uuid;
 
-use crate::{Array, Collation, DfValue, Text, TimestampTz, TinyText};
+use crate::{Array, Collation, DfValue, Interval, Text, TimestampTz, TinyText};
 
 impl DfValue {
     /// Version number for the current implementations of [`serde::Deserialize`] and
@@ -55,6 +56,10 @@ impl DfValue {
             DfValue::Numeric(Arc::new(Decimal::MAX)),
             DfValue::BitVector(Arc::new(BitVec::from_bytes(b"aaaaaaaaa"))),
             DfValue::Array(Arc::new(Array::from(vec![DfValue::from("aaaaaaaaa")]))),
+            DfValue::Uuid(Arc::new(Uuid::from_u128(
+                0xa0eebc99_9c0b_4ef8_bb6d_6bb9bd380a11,
+            ))),
+            DfValue::Interval(Arc::new(Interval::new(14, -3, 14_706_789_000))),
             DfValue::Max,
         ]
     }
@@ -74,6 +79,8 @@ enum Variant {
     TimestampTz,
     Array,
     Max,
+    Uuid,
+    Interval,
 }
 
 enum TextOrTinyText {
@@ -148,6 +155,12 @@ impl serde::ser::Serialize for DfValue {
                 serialize_variant(serializer, Variant::TimestampTz, &(ts, extra))
             }
             DfValue::Array(vs) => serialize_variant(serializer, Variant::Array, &vs),
+            DfValue::Uuid(u) => serialize_variant(serializer, Variant::Uuid, &u.as_u128()),
+            DfValue::Interval(i) => serialize_variant(
+                serializer,
+                Variant::Interval,
+                &(i.months(), i.days(), i.microseconds()),
+            ),
             DfValue::PassThrough(v) => Err(serde::ser::Error::custom(format_args!(
                 "PassThrough value of type {} not supported in dataflow graph",
                 v.ty
@@ -285,6 +298,15 @@ impl<'de> Deserialize<'de> for DfValue {
                     (Variant::Max, variant) => {
                         VariantAccess::unit_variant(variant).map(|_| DfValue::Max)
                     }
+                    (Variant::Uuid, variant) => VariantAccess::newtype_variant::<u128>(variant)
+                        .map(|u| DfValue::from(Uuid::from_u128(u))),
+                    (Variant::Interval, variant) => {
+                        VariantAccess::newtype_variant::<(i32, i32, i64)>(variant).map(
+                            |(months, days, microseconds)| {
+                                DfValue::from(Interval::new(months, days, microseconds))
+                            },
+                        )
+                    }
                 }
             }
         }
@@ -9,7 +9,7 @@ use std::sync::atomic::Ordering::Relaxed;
 use cidr::IpInet;
 use readyset_errors::{ReadySetError, ReadySetResult};
 
-use crate::{Array, Collation, DfType, DfValue};
+use crate::{Array, Collation, DfType, DfValue, Interval};
 
 pub(crate) const TINYTEXT_WIDTH: usize = 14;
 
@@ -492,24 +492,17 @@ pub(crate) trait TextCoerce: Sized + Clone + Into<DfValue> {
             }
 
             DfType::Uuid => {
-                // Since UUIDs can be represented in many ways, if we want to store them as a
-                // string, we have to at least normalize to the same representation.
-                // I.e. we want to make sure that
-                //'123e4567-e89b-12d3-a456-426614174000',
-                //'123E4567-E89b-12D3-A456-426614174000',
-                // and '123e4567e89b12d3a456426614174000' are equal.
                 let uuid = str
                     .parse::<uuid::Uuid>()
-                    .map_err(|e| Self::coerce_err(to_ty, e))?
-                    .to_string();
-
-                if uuid.as_str() == str {
-                    Ok(self.clone().into())
-                } else {
-                    Ok(uuid.into())
-                }
+                    .map_err(|e| Self::coerce_err(to_ty, e))?;
+                Ok(DfValue::from(uuid))
             }
 
+            DfType::Interval => str
+                .parse::<Interval>()
+                .map(DfValue::from)
+                .map_err(|e| Self::coerce_err(to_ty, e)),
+
             DfType::Time { .. } => match str.parse::<mysql_time::MySqlTime>() {
                 Ok(t) => Ok(DfValue::Time(t)),
                 Err(mysql_time::ConvertError::ParseError) => Ok(DfValue::Time(Default::default())),
@@ -728,23 +721,26 @@ mod tests {
         );
 
         // TEXT to UUID
+        let uuid = DfValue::from(uuid::Uuid::from_u128(
+            0xa0eebc99_9c0b_4ef8_bb6d_6bb9bd380a11,
+        ));
         assert_eq!(
             DfValue::from("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11")
                 .coerce_to(&DfType::Uuid, &DfType::Unknown)
                 .unwrap(),
-            DfValue::from("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11"),
+            uuid,
         );
         assert_eq!(
             DfValue::from("A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11")
                 .coerce_to(&DfType::Uuid, &DfType::Unknown)
                 .unwrap(),
-            DfValue::from("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11"),
+            uuid,
         );
         assert_eq!(
             DfValue::from("a0eebc999c0b4ef8bb6d6bb9bd380a11")
                 .coerce_to(&DfType::Uuid, &DfType::Unknown)
                 .unwrap(),
-            DfValue::from("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11"),
+            uuid,
         );
         /* TODO: fix the following UUID conversions one day
         assert_eq!(
@@ -422,6 +422,7 @@ impl TimestampTz {
             | DfType::MacAddr
             | DfType::Inet
             | DfType::Uuid
+            | DfType::Interval
             | DfType::Bit(_)
             | DfType::VarBit(_)
             | DfType::Array(_) => Err(ReadySetError::DfValueConversionError {
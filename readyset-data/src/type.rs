@@ -184,6 +184,9 @@ pub enum DfType {
 
     /// [PostgreSQL `jsonb`](https://www.postgresql.org/docs/current/datatype-json.html).
     Jsonb,
+
+    /// [PostgreSQL `interval`](https://www.postgresql.org/docs/current/datatype-datetime.html).
+    Interval,
 }
 
 /// Defaults.
@@ -274,6 +277,9 @@ impl DfType {
             QuotedChar => Self::TinyInt,
 
             Blob | TinyBlob | MediumBlob | LongBlob | ByteArray => Self::Blob,
+            // Spatial values are passed through opaquely, as the raw bytes of their internal
+            // representation
+            Spatial(_) => Self::Blob,
             VarBinary(len) => Self::VarBinary(len),
             Binary(len) => Self::Binary(len.unwrap_or(1)),
 
@@ -297,6 +303,7 @@ impl DfType {
             TimestampTz => Self::TimestampTz {
                 subsecond_digits: dialect.default_subsecond_digits(),
             },
+            Interval => Self::Interval,
 
             Uuid => Self::Uuid,
             MacAddr => Self::MacAddr,
@@ -351,6 +358,7 @@ impl DfType {
             | DfType::Time { .. }
             | DfType::Timestamp { .. }
             | DfType::TimestampTz { .. } => PgTypeCategory::DateTime,
+            DfType::Interval => PgTypeCategory::Timespan,
             DfType::MacAddr | DfType::Inet => PgTypeCategory::NetworkAddress,
             DfType::Uuid | DfType::Enum { .. } | DfType::Json | DfType::Jsonb => {
                 PgTypeCategory::UserDefined
@@ -463,6 +471,29 @@ impl DfType {
         matches!(self, Self::Text(..) | Self::VarChar(..) | Self::Char(..))
     }
 
+    /// Returns the collation of this type, if it's any `text` type
+    #[inline]
+    pub fn collation(&self) -> Option<Collation> {
+        match self {
+            Self::Text(collation) | Self::VarChar(_, collation) | Self::Char(_, collation) => {
+                Some(*collation)
+            }
+            _ => None,
+        }
+    }
+
+    /// If this is any `text` type, returns the same type with the given collation. Otherwise,
+    /// returns this type unchanged.
+    #[must_use]
+    pub fn with_collation(self, collation: Collation) -> Self {
+        match self {
+            Self::Text(_) => Self::Text(collation),
+            Self::VarChar(len, _) => Self::VarChar(len, collation),
+            Self::Char(len, _) => Self::Char(len, collation),
+            ty => ty,
+        }
+    }
+
     /// Returns `true` if this is any IEEE 754 floating-point type.
     #[inline]
     pub fn is_any_float(&self) -> bool {
@@ -475,10 +506,10 @@ impl DfType {
         matches!(self, Self::Array { .. })
     }
 
-    /// Returns `true` if this is any MySQL binary type.
+    /// Returns `true` if this is any binary (byte string) type.
     #[inline]
     pub fn is_binary(&self) -> bool {
-        matches!(self, Self::Binary(_) | Self::VarBinary(_))
+        matches!(self, Self::Blob | Self::Binary(_) | Self::VarBinary(_))
     }
 
     /// Returns the deepest nested type in [`DfType::Array`], otherwise returns `self`.
@@ -612,7 +643,8 @@ impl fmt::Display for DfType {
             | Self::MacAddr
             | Self::Uuid
             | Self::Json
-            | Self::Jsonb => write!(f, "{kind:?}"),
+            | Self::Jsonb
+            | Self::Interval => write!(f, "{kind:?}"),
 
             Self::Text(collation) => {
                 write!(f, "Text")?;
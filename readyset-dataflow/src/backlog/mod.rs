@@ -1,7 +1,10 @@
 use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::ops::Bound;
-use std::sync::Arc;
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use ahash::RandomState;
 use common::SizeOf;
@@ -25,6 +28,40 @@ pub(crate) type ReaderUpdatedSender = tokio::sync::broadcast::Sender<ReaderNotif
 pub(crate) trait Trigger =
     Fn(&mut dyn Iterator<Item = KeyComparison>) -> bool + 'static + Send + Sync;
 
+/// The minimum number of key versions to keep before pruning the versions of keys which are no
+/// longer present in the reader
+const MIN_KEY_VERSIONS_TO_PRUNE: usize = 1024;
+
+/// The versions of the keys in a reader, shared between its [`WriteHandle`] and its
+/// [`SingleReadHandle`]s.
+///
+/// Every time the [`WriteHandle`] is swapped, it advances the reader's write epoch and records
+/// that epoch as the version of every key it wrote to since the last swap. Keys without a recorded
+/// version (such as keys which were filled with no rows, or which were evicted) have the current
+/// write epoch as their version. This means that the version of a key never decreases, and always
+/// changes when the rows for that key change.
+///
+/// The write epoch starts at the time the reader was created (in microseconds since the UNIX
+/// epoch), so that versions keep increasing if the reader is recreated, eg after a restart.
+#[derive(Debug)]
+struct KeyVersions {
+    epoch: AtomicU64,
+    versions: RwLock<HashMap<Vec<DfValue>, u64, RandomState>>,
+}
+
+impl KeyVersions {
+    fn new() -> Self {
+        let epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .unwrap_or_default();
+        Self {
+            epoch: AtomicU64::new(epoch),
+            versions: Default::default(),
+        }
+    }
+}
+
 /// Allocate a new end-user facing result table.
 ///
 /// # Invariants:
@@ -137,6 +174,8 @@ fn new_inner(
     };
 
     let (notifier, receiver) = tokio::sync::broadcast::channel(1);
+    let key_versions = Arc::new(KeyVersions::new());
+    let reads = Arc::new(AtomicU64::new(0));
 
     let w = WriteHandle {
         partial: trigger.is_some(),
@@ -147,6 +186,9 @@ fn new_inner(
         mem_size: 0,
         notifier,
         eviction_epoch: 0,
+        key_versions: Arc::clone(&key_versions),
+        written_keys: Default::default(),
+        reads: Arc::clone(&reads),
     };
 
     let r = SingleReadHandle {
@@ -156,6 +198,8 @@ fn new_inner(
         post_lookup: post_processing,
         receiver,
         eviction_epoch: 0,
+        key_versions,
+        reads,
     };
 
     (r, w)
@@ -183,6 +227,12 @@ pub(crate) struct WriteHandle {
     notifier: ReaderUpdatedSender,
     /// How many eviction rounds this handle had
     eviction_epoch: usize,
+    /// The versions of the keys in this reader
+    key_versions: Arc<KeyVersions>,
+    /// The keys which have been written to since the last call to `swap()`
+    written_keys: HashSet<Vec<DfValue>, RandomState>,
+    /// The number of reads served by this reader, shared with its [`SingleReadHandle`]s
+    reads: Arc<AtomicU64>,
 }
 
 type Key<'a> = Cow<'a, [DfValue]>;
@@ -283,12 +333,31 @@ impl WriteHandle {
 
     pub(crate) fn swap(&mut self) {
         self.handle.refresh();
+
+        // Only record the new versions once the writes are visible to readers, so that a version
+        // read before the rows for a key never covers writes which those rows don't include
+        let epoch = self.key_versions.epoch.load(atomic::Ordering::Acquire) + 1;
+        #[allow(clippy::unwrap_used)] // lock poisoning is unrecoverable
+        let mut versions = self.key_versions.versions.write().unwrap();
+        versions.extend(self.written_keys.drain().map(|key| (key, epoch)));
+        if versions.len() > MIN_KEY_VERSIONS_TO_PRUNE.max(self.len() * 2) {
+            versions.retain(|key, _| matches!(self.contains_key(key), Ok(true)));
+        }
+        self.key_versions
+            .epoch
+            .store(epoch, atomic::Ordering::Release);
     }
 
     pub(crate) fn len(&self) -> usize {
         self.handle.read().len()
     }
 
+    /// Returns the total number of reads served by this reader, as recorded by
+    /// [`SingleReadHandle::record_read`]
+    pub(crate) fn reads(&self) -> u64 {
+        self.reads.load(atomic::Ordering::Relaxed)
+    }
+
     /// Add a new set of records to the backlog.
     ///
     /// These will be made visible to readers after the next call to `swap()`.
@@ -296,7 +365,12 @@ impl WriteHandle {
     where
         I: IntoIterator<Item = Record>,
     {
-        let mem_delta = self.handle.add(&self.index.columns, self.cols, rs);
+        let key_cols = &self.index.columns;
+        let written_keys = &mut self.written_keys;
+        let rs = rs.into_iter().inspect(|r| {
+            written_keys.insert(key_cols.iter().map(|&c| r[c].clone()).collect());
+        });
+        let mem_delta = self.handle.add(key_cols, self.cols, rs);
         match mem_delta.cmp(&0) {
             Ordering::Greater => {
                 self.mem_size += mem_delta as usize;
@@ -451,6 +525,10 @@ pub struct SingleReadHandle {
     receiver: ReaderUpdatedNotifier,
     /// Caches the eviction epoch of the associated [`WriteHandle`]
     eviction_epoch: usize,
+    /// The versions of the keys in this reader
+    key_versions: Arc<KeyVersions>,
+    /// The number of reads served by this reader, shared with its [`WriteHandle`]
+    reads: Arc<AtomicU64>,
 }
 
 impl Clone for SingleReadHandle {
@@ -462,6 +540,8 @@ impl Clone for SingleReadHandle {
             post_lookup: self.post_lookup.clone(),
             receiver: self.receiver.resubscribe(),
             eviction_epoch: self.eviction_epoch,
+            key_versions: self.key_versions.clone(),
+            reads: self.reads.clone(),
         }
     }
 }
@@ -539,6 +619,18 @@ impl SingleReadHandle {
         self.handle.len() == 0
     }
 
+    /// Record that a read was served by this reader, for reporting the distribution of reads
+    /// across the shards of a reader
+    pub fn record_read(&self) {
+        self.reads.fetch_add(1, atomic::Ordering::Relaxed);
+    }
+
+    /// Returns true if this reader is partially materialized, meaning that misses can be filled by
+    /// calling [`Self::trigger`]
+    pub fn is_partial(&self) -> bool {
+        self.trigger.is_some()
+    }
+
     pub fn keys(&self) -> Vec<Vec<DfValue>> {
         self.handle.keys()
     }
@@ -547,6 +639,24 @@ impl SingleReadHandle {
         self.handle.timestamp()
     }
 
+    /// Returns the versions of the rows for each of the given keys, derived from the write epoch of
+    /// this reader. The version of a key never decreases, and changes whenever the rows for that
+    /// key change.
+    ///
+    /// Versions aren't tracked for ranges of keys, so the version of a range is always the
+    /// current write epoch.
+    pub fn key_versions(&self, keys: &[KeyComparison]) -> Vec<u64> {
+        let epoch = self.key_versions.epoch.load(atomic::Ordering::Acquire);
+        #[allow(clippy::unwrap_used)] // lock poisoning is unrecoverable
+        let versions = self.key_versions.versions.read().unwrap();
+        keys.iter()
+            .map(|key| match key {
+                KeyComparison::Equal(key) => versions.get(key.as_slice()).copied().unwrap_or(epoch),
+                KeyComparison::Range(_) => epoch,
+            })
+            .collect()
+    }
+
     /// Returns true if the corresponding write handle to our read handle has been dropped
     pub fn was_dropped(&self) -> bool {
         self.handle.was_dropped()
@@ -604,6 +714,35 @@ mod tests {
         assert_eq!(r.get(&a[0..1]).unwrap()[0], a);
     }
 
+    #[test]
+    fn key_versions() {
+        let key = |k: i32| KeyComparison::Equal(vec1![k.into()]);
+        let (r, mut w) = new(2, Index::hash_map(vec![0]), ReaderProcessing::default());
+        w.add(vec![
+            Record::Positive(vec![1.into(), "a".into()]),
+            Record::Positive(vec![2.into(), "b".into()]),
+        ]);
+        w.swap();
+        let v1 = r.key_versions(&[key(1), key(2)]);
+        assert_eq!(v1[0], v1[1]);
+
+        // writing to one key only changes the version of that key
+        w.add(vec![Record::Positive(vec![1.into(), "c".into()])]);
+        let unswapped = r.key_versions(&[key(1), key(2)]);
+        assert_eq!(unswapped, v1);
+        w.swap();
+        let v2 = r.key_versions(&[key(1), key(2)]);
+        assert!(v2[0] > v1[0]);
+        assert_eq!(v2[1], v1[1]);
+
+        // keys without any rows have the current write epoch as their version, which never
+        // decreases
+        let missing = r.key_versions(&[key(3)]);
+        assert!(missing[0] >= v2[0]);
+        w.swap();
+        assert!(r.key_versions(&[key(3)])[0] > missing[0]);
+    }
+
     #[test]
     fn busybusybusy() {
         use std::thread;
@@ -3,14 +3,18 @@
 //! To make the metrics performant, it holds handles to all the required metrics for
 //! fast operations, wherever possible.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryInto;
 use std::time::Duration;
 
+use dataflow_state::CompressionStats;
 use metrics::{
     register_counter, register_gauge, register_histogram, Counter, Gauge, Histogram, Label,
     SharedString,
 };
+use nom_sql::{Relation, SqlIdentifier};
+use petgraph::graph::NodeIndex;
+use readyset_client::debug::skew::ReaderShardStats;
 use readyset_client::internal::ReplicaAddress;
 use readyset_client::metrics::recorded;
 use strum::{EnumCount, IntoEnumIterator};
@@ -45,9 +49,14 @@ pub(super) struct DomainMetrics {
     replay_misses: BTreeMap<(LocalNodeIndex, Tag), Counter>,
 
     reader_replay_request_time: NodeMap<(Counter, Histogram)>,
+    tenant_upqueries: HashMap<Option<SqlIdentifier>, (Counter, Histogram)>,
     chuncked_replay_time: NodeMap<(Counter, Histogram)>,
     base_table_lookups: NodeMap<Counter>,
     node_state_size: NodeMap<Gauge>,
+    reader_shard_stats: HashMap<NodeIndex, (Gauge, Counter)>,
+    node_state_compression: NodeMap<(Gauge, Counter, Counter)>,
+    base_table_tombstone_ratio: NodeMap<Gauge>,
+    base_table_compactions: NodeMap<Counter>,
 }
 
 impl DomainMetrics {
@@ -95,8 +104,13 @@ impl DomainMetrics {
             replay_misses: Default::default(),
             packets_sent: packets_sent.try_into().ok().unwrap(),
             reader_replay_request_time: Default::default(),
+            tenant_upqueries: Default::default(),
             base_table_lookups: Default::default(),
             node_state_size: Default::default(),
+            reader_shard_stats: Default::default(),
+            node_state_compression: Default::default(),
+            base_table_tombstone_ratio: Default::default(),
+            base_table_compactions: Default::default(),
             shard,
             index,
         }
@@ -298,6 +312,32 @@ impl DomainMetrics {
         }
     }
 
+    pub(super) fn rec_tenant_upquery(&mut self, tenant: Option<SqlIdentifier>, time: Duration) {
+        let (ctr, histo) = self
+            .tenant_upqueries
+            .entry(tenant)
+            .or_insert_with_key(|tenant| {
+                let tenant = tenant.as_ref().map_or("", |t| t.as_str()).to_owned();
+                (
+                    register_counter!(
+                        recorded::DOMAIN_TENANT_UPQUERIES,
+                        "domain" => self.index.clone(),
+                        "shard" => self.shard.clone(),
+                        "tenant" => tenant.clone(),
+                    ),
+                    register_histogram!(
+                        recorded::DOMAIN_TENANT_UPQUERY_TIME,
+                        "domain" => self.index.clone(),
+                        "shard" => self.shard.clone(),
+                        "tenant" => tenant,
+                    ),
+                )
+            });
+
+        ctr.increment(1);
+        histo.record(time.as_micros() as f64);
+    }
+
     pub(super) fn inc_replay_misses(&mut self, miss_in: LocalNodeIndex, needed_for: Tag, n: usize) {
         if let Some(ctr) = self.replay_misses.get(&(miss_in, needed_for)) {
             ctr.increment(n as u64);
@@ -335,6 +375,45 @@ impl DomainMetrics {
         }
     }
 
+    pub(super) fn set_base_table_tombstone_ratio(
+        &mut self,
+        node: LocalNodeIndex,
+        table_name: &Relation,
+        ratio: f64,
+    ) {
+        if let Some(gauge) = self.base_table_tombstone_ratio.get(node) {
+            gauge.set(ratio);
+        } else {
+            let gauge = register_gauge!(
+                recorded::BASE_TABLE_TOMBSTONE_RATIO,
+                "table_name" => table_name.display_unquoted().to_string(),
+                "domain" => self.index.clone(),
+                "shard" => self.shard.clone(),
+            );
+            gauge.set(ratio);
+            self.base_table_tombstone_ratio.insert(node, gauge);
+        }
+    }
+
+    pub(super) fn inc_base_table_compactions(
+        &mut self,
+        node: LocalNodeIndex,
+        table_name: &Relation,
+    ) {
+        if let Some(ctr) = self.base_table_compactions.get(node) {
+            ctr.increment(1);
+        } else {
+            let ctr = register_counter!(
+                recorded::BASE_TABLE_COMPACTIONS,
+                "table_name" => table_name.display_unquoted().to_string(),
+                "domain" => self.index.clone(),
+                "shard" => self.shard.clone(),
+            );
+            ctr.increment(1);
+            self.base_table_compactions.insert(node, ctr);
+        }
+    }
+
     pub(super) fn set_state_sizes(&self, partial: u64, reader: u64, base: u64, node: u64) {
         self.partial_state_size.set(partial as f64);
         self.reader_state_size.set(reader as f64);
@@ -354,4 +433,57 @@ impl DomainMetrics {
             self.node_state_size.insert(node, gauge);
         }
     }
+
+    pub(super) fn set_reader_shard_stats(&mut self, node: NodeIndex, stats: ReaderShardStats) {
+        let (key_count, reads) = self.reader_shard_stats.entry(node).or_insert_with(|| {
+            let node = node.index().to_string();
+            (
+                register_gauge!(
+                    recorded::READER_SHARD_KEY_COUNT,
+                    "node" => node.clone(),
+                    "shard" => self.shard.clone(),
+                ),
+                register_counter!(
+                    recorded::READER_SHARD_READS,
+                    "node" => node,
+                    "shard" => self.shard.clone(),
+                ),
+            )
+        });
+        key_count.set(stats.key_count as f64);
+        reads.absolute(stats.reads);
+    }
+
+    pub(super) fn set_node_state_compression_stats(
+        &mut self,
+        node: LocalNodeIndex,
+        stats: CompressionStats,
+    ) {
+        if self.node_state_compression.get(node).is_none() {
+            let node_label = node.to_string();
+            self.node_state_compression.insert(
+                node,
+                (
+                    register_gauge!(
+                        recorded::NODE_STATE_COMPRESSED_BYTES,
+                        "node" => node_label.clone(),
+                    ),
+                    register_counter!(
+                        recorded::NODE_STATE_COMPRESSED_CACHE_HITS,
+                        "node" => node_label.clone(),
+                    ),
+                    register_counter!(
+                        recorded::NODE_STATE_COMPRESSED_CACHE_MISSES,
+                        "node" => node_label,
+                    ),
+                ),
+            );
+        }
+
+        if let Some((bytes, hits, misses)) = self.node_state_compression.get(node) {
+            bytes.set(stats.compressed_bytes as f64);
+            hits.absolute(stats.cache_hits);
+            misses.absolute(stats.cache_misses);
+        }
+    }
 }
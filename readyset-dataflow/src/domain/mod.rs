@@ -6,6 +6,7 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use std::convert::{TryFrom, TryInto};
 use std::fmt::Debug;
 use std::ops::Bound;
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -22,11 +23,13 @@ use futures_util::stream::StreamExt;
 use futures_util::TryFutureExt;
 pub use internal::{DomainIndex, ReplicaAddress};
 use merging_interval_tree::IntervalTreeSet;
+use nom_sql::SqlIdentifier;
 use petgraph::graph::NodeIndex;
+use readyset_client::debug::skew::ReaderShardStats;
 use readyset_client::internal::Index;
 use readyset_client::replication::ReplicationOffsetState;
 use readyset_client::{channel, internal, KeyComparison, KeyCount, ReaderAddress};
-use readyset_errors::{internal, internal_err, ReadySetError, ReadySetResult};
+use readyset_errors::{internal, internal_err, invalid_err, ReadySetError, ReadySetResult};
 use readyset_util::futures::abort_on_panic;
 use readyset_util::progress::report_progress_with;
 use readyset_util::redacted::Sensitive;
@@ -66,6 +69,17 @@ pub struct Config {
 
     #[serde(default)]
     pub eviction_kind: crate::EvictionKind,
+
+    /// The relative share of each domain's upquery processing given to each tenant (the schema of
+    /// the base tables a query reads from) when multiple tenants are waiting on upqueries in the
+    /// same domain. Tenants not listed here have a weight of 1.
+    #[serde(default)]
+    pub tenant_weights: HashMap<SqlIdentifier, u32>,
+
+    /// The maximum size, in bytes, of a single text or binary value written to a base table, or
+    /// `None` for no limit.
+    #[serde(default)]
+    pub max_value_size: Option<usize>,
 }
 
 const BATCH_SIZE: usize = 256;
@@ -449,11 +463,14 @@ impl DomainBuilder {
 
             aggressively_update_state_sizes: self.config.aggressively_update_state_sizes,
             replay_completed: false,
+            affected_rows: 0,
 
             metrics: domain_metrics::DomainMetrics::new(address),
 
             eviction_kind: self.config.eviction_kind,
+            tenant_weights: self.config.tenant_weights,
             remapped_keys: Default::default(),
+            last_base_table_compactions: Default::default(),
 
             init_state_tx,
         }
@@ -661,9 +678,20 @@ pub struct Domain {
 
     replay_completed: bool,
 
+    /// The number of rows affected by the writes to base tables processed since the last call to
+    /// [`Domain::take_affected_rows`]
+    affected_rows: u64,
+
     metrics: domain_metrics::DomainMetrics,
     eviction_kind: crate::EvictionKind,
 
+    /// See [`Config::tenant_weights`]
+    tenant_weights: HashMap<SqlIdentifier, u32>,
+
+    /// The time at which each base table in this domain with a compaction interval was last
+    /// compacted (or when we first saw it, if it hasn't been compacted yet)
+    last_base_table_compactions: NodeMap<time::Instant>,
+
     /// This channel is used to notify the replica that a base node has its persistent state
     /// initialized.
     /// This allow us to asynchronously run that process, and avoid any bottlenecks on the
@@ -752,6 +780,34 @@ impl Domain {
         self.replica
     }
 
+    /// Create a RocksDB checkpoint of the persisted state of every base table in this domain in
+    /// `dir`, returning the number of tables checkpointed. Returns an error if the state of any
+    /// base table is still being initialized.
+    fn checkpoint_base_tables(&self, dir: &Path) -> ReadySetResult<usize> {
+        let mut checkpointed = 0;
+        for (idx, n) in self.nodes.iter() {
+            let node = n.borrow();
+            if !node.is_base() || node.is_dropped() {
+                continue;
+            }
+            match self.state.get(idx) {
+                Some(state) => {
+                    if let Some(persistent) = state.as_persistent() {
+                        persistent.checkpoint(dir)?;
+                        checkpointed += 1;
+                    }
+                }
+                None => {
+                    return Err(internal_err!(
+                        "Cannot checkpoint base table {} while its state is being initialized",
+                        node.name().display_unquoted()
+                    ))
+                }
+            }
+        }
+        Ok(checkpointed)
+    }
+
     fn snapshotting_base_nodes(&self) -> Vec<LocalNodeIndex> {
         self.state
             .iter()
@@ -1166,7 +1222,10 @@ impl Domain {
             self.process_ptimes.start(me);
             let mut m = Some(m);
             let NodeProcessingResult {
-                misses, captured, ..
+                misses,
+                captured,
+                affected_rows,
+                ..
             } = n.process(
                 &mut m,
                 None,
@@ -1183,6 +1242,7 @@ impl Domain {
                 },
             )?;
             assert_eq!(captured.len(), 0);
+            self.affected_rows += affected_rows;
             self.process_ptimes.stop();
             self.process_times.stop();
 
@@ -1578,10 +1638,8 @@ impl Domain {
                         weak_indices,
                     } => {
                         if !self.state.contains_key(node) {
-                            self.state.insert(
-                                node,
-                                MaterializedNodeState::Memory(MemoryState::default()),
-                            );
+                            let state = self.new_partial_state();
+                            self.state.insert(node, state);
                         }
                         let state = self.state.get_mut(node).unwrap();
                         for (index, tags) in strict_indices {
@@ -2074,12 +2132,13 @@ impl Domain {
                 node_ref.borrow_mut().purge = purge;
 
                 let is_ready = if !index.is_empty() {
-                    match (
-                        node_ref.borrow().get_base(),
-                        &self.persistence_parameters.mode,
-                    ) {
-                        (Some(base), &DurabilityMode::DeleteOnExit)
-                        | (Some(base), &DurabilityMode::Permanent) => {
+                    let mode = self
+                        .persistence_parameters
+                        .mode_for_table(node_ref.borrow().name())
+                        .clone();
+                    match (node_ref.borrow().get_base(), mode) {
+                        (Some(base), mode @ DurabilityMode::DeleteOnExit)
+                        | (Some(base), mode @ DurabilityMode::Permanent) => {
                             let node = node_ref.borrow();
                             let node_name = node.name();
                             let base_name = format!(
@@ -2096,7 +2155,14 @@ impl Domain {
                                 self.shard.unwrap_or(0),
                             );
 
-                            let persistence_params = self.persistence_parameters.clone();
+                            let tuning = self.persistence_parameters.tuning_for_table(node_name);
+                            let persistence_params = PersistenceParameters {
+                                mode,
+                                tuning: base
+                                    .rocksdb_tuning()
+                                    .map_or(tuning, |overrides| overrides.or(tuning)),
+                                ..self.persistence_parameters.clone()
+                            };
                             let init_state_tx = self.init_state_tx.clone();
                             let unique_keys = base.all_unique_keys();
 
@@ -2267,6 +2333,23 @@ impl Domain {
                 }
                 Ok(Some(bincode::serialize(&res)?))
             }
+            DomainRequest::RequestReaderShardStats => {
+                let res = self
+                    .reader_write_handles
+                    .iter()
+                    .filter_map(|(local_index, wh)| {
+                        let node = self.nodes.get(local_index)?.borrow().global_addr();
+                        Some((
+                            node,
+                            ReaderShardStats {
+                                key_count: wh.len(),
+                                reads: wh.reads(),
+                            },
+                        ))
+                    })
+                    .collect::<Vec<_>>();
+                Ok(Some(bincode::serialize(&res)?))
+            }
             DomainRequest::Packet(pkt) => {
                 self.handle_packet(Box::new(pkt), executor)?;
                 Ok(None)
@@ -2282,9 +2365,14 @@ impl Domain {
                     .insert_generated_columns(node, index.columns.clone(), tag);
                 // ...and also make sure we use that tag to index those columns in this node, so we
                 // know what hole to fill when we've satisfied replays to those columns
+                if !self.state.contains_key(node) {
+                    let state = self.new_partial_state();
+                    self.state.insert(node, state);
+                }
+                #[allow(clippy::unwrap_used)] // inserted above if missing
                 self.state
-                    .entry(node)
-                    .or_insert_with(|| MaterializedNodeState::Memory(MemoryState::default()))
+                    .get_mut(node)
+                    .unwrap()
                     .add_key(index, Some(vec![tag]));
                 Ok(None)
             }
@@ -2299,6 +2387,50 @@ impl Domain {
                     .all(|state| state.compaction_finished());
                 Ok(Some(bincode::serialize(&finished)?))
             }
+            DomainRequest::CompactBaseTable { node } => {
+                self.compact_base_table(node)?;
+                Ok(None)
+            }
+            DomainRequest::SetBaseTableCompactionInterval { node, interval } => {
+                self.nodes
+                    .get(node)
+                    .ok_or_else(|| ReadySetError::NoSuchNode(node.id()))?
+                    .borrow_mut()
+                    .get_base_mut()
+                    .ok_or_else(|| {
+                        internal_err!("told to set compaction interval on non-base node")
+                    })?
+                    .set_compaction_interval(interval);
+                Ok(None)
+            }
+            DomainRequest::SetBaseTableTuning { node, tuning } => {
+                let n = self
+                    .nodes
+                    .get(node)
+                    .ok_or_else(|| ReadySetError::NoSuchNode(node.id()))?;
+                n.borrow_mut()
+                    .get_base_mut()
+                    .ok_or_else(|| internal_err!("told to set tuning on non-base node"))?
+                    .set_rocksdb_tuning(tuning);
+                // If the table's persistent state hasn't been initialized yet, the new tuning will
+                // be picked up when it is
+                if let Some(persistent) = self
+                    .state
+                    .get_mut(node)
+                    .and_then(|state| state.as_persistent_mut())
+                {
+                    persistent.set_tuning(tuning)?;
+                }
+                Ok(None)
+            }
+            DomainRequest::CheckpointBaseTables { dir } => {
+                let checkpointed = self.checkpoint_base_tables(&dir)?;
+                Ok(Some(bincode::serialize(&checkpointed)?))
+            }
+            DomainRequest::EvictReaderKeys { node, keys } => {
+                self.evict_reader_keys(node, &keys)?;
+                Ok(None)
+            }
         };
         // What we just did might have done things like insert into `self.delayed_for_self`, so
         // run the event loop before returning to make sure that gets processed.
@@ -4097,6 +4229,74 @@ impl Domain {
                     TriggerEndpoint::None | TriggerEndpoint::Start(..) => {}
                 }
             }
+            EvictRequest::BaseTableKeys { node, keys } => {
+                #[allow(clippy::indexing_slicing)] // node must exist
+                let primary_key = self.nodes[node]
+                    .borrow()
+                    .get_base()
+                    .and_then(|base| base.primary_key().map(|pk| pk.to_vec()))
+                    .ok_or_else(|| {
+                        internal_err!("Received base table eviction for node without primary key")
+                    })?;
+                let state = self.state.get(node).ok_or_else(|| {
+                    internal_err!("Received base table eviction for stateless node")
+                })?;
+
+                let mut rows = vec![];
+                for key in keys {
+                    if key.len() != primary_key.len() {
+                        return Err(ReadySetError::WrongKeyColumnCount(
+                            primary_key.len(),
+                            key.len(),
+                        ));
+                    }
+                    if let LookupResult::Some(rs) =
+                        state.lookup(&primary_key, &PointKey::from(key.into_iter()))
+                    {
+                        rows.extend(rs.into_iter().map(|r| r.into_owned()));
+                    }
+                }
+                if rows.is_empty() {
+                    // None of the rows live in this shard
+                    return Ok(());
+                }
+
+                // Evict the keys the rows map to along every partial replay path out of the base
+                // table, as though the rows had just been evicted from the base table itself
+                let mut evictions = vec![];
+                for (tag, path) in &self.replay_paths {
+                    if path.source != Some(node) {
+                        continue;
+                    }
+                    let index = match &path.trigger {
+                        TriggerEndpoint::Local(index) | TriggerEndpoint::Start(index) => index,
+                        _ => continue,
+                    };
+                    let keys = rows
+                        .iter()
+                        .filter_map(|row| {
+                            index
+                                .columns
+                                .iter()
+                                .map(|c| row.get(*c).cloned())
+                                .collect::<Option<Vec<_>>>()
+                                .and_then(|key| Vec1::try_from_vec(key).ok())
+                                .map(KeyComparison::Equal)
+                        })
+                        .collect::<HashSet<_>>();
+                    evictions.push((*tag, keys.into_iter().collect::<Vec<_>>()));
+                }
+                for (tag, keys) in evictions {
+                    self.handle_eviction(
+                        EvictRequest::Keys {
+                            link: Link::new(node, node),
+                            tag,
+                            keys,
+                        },
+                        ex,
+                    )?;
+                }
+            }
         };
 
         Ok(())
@@ -4110,8 +4310,118 @@ impl Domain {
         }
     }
 
+    /// Evict the given keys from the reader node `node`, skipping any keys which are not present
+    fn evict_reader_keys(
+        &mut self,
+        node: LocalNodeIndex,
+        keys: &[KeyComparison],
+    ) -> ReadySetResult<()> {
+        let handle = self
+            .reader_write_handles
+            .get_mut(node)
+            .ok_or_else(|| internal_err!("told to evict keys from non-reader node"))?;
+        if !handle.is_partial() {
+            // Fully materialized readers can't have holes, and are always up to date anyway
+            return Ok(());
+        }
+
+        let mut evicted = false;
+        for key in keys {
+            if handle.contains(key).unwrap_or(false) {
+                handle.mark_hole(key)?;
+                evicted = true;
+            }
+        }
+        if evicted {
+            trace!(local = %node, ?keys, "Evicted keys from reader");
+            handle.swap();
+            handle.notify_readers_of_eviction()?;
+        }
+        Ok(())
+    }
+
+    /// Trigger a manual compaction of the persisted state of the given base table node
+    fn compact_base_table(&mut self, node: LocalNodeIndex) -> ReadySetResult<()> {
+        let name = self
+            .nodes
+            .get(node)
+            .ok_or_else(|| ReadySetError::NoSuchNode(node.id()))?
+            .borrow()
+            .name()
+            .clone();
+        self.state
+            .get_mut(node)
+            .and_then(|state| state.as_persistent_mut())
+            .ok_or_else(|| {
+                invalid_err!(
+                    "Table {} does not have (initialized) persistent state",
+                    name.display_unquoted()
+                )
+            })?
+            .compact();
+        self.metrics.inc_base_table_compactions(node, &name);
+        self.last_base_table_compactions
+            .insert(node, time::Instant::now());
+        Ok(())
+    }
+
+    /// Perform periodic maintenance of the base tables in this domain: update the metric for the
+    /// ratio of deletion tombstones in each table, and start a compaction for any table whose
+    /// compaction interval has elapsed since it was last compacted.
+    pub fn maintain_base_tables(&mut self) {
+        let now = time::Instant::now();
+        let Domain {
+            state,
+            nodes,
+            metrics,
+            last_base_table_compactions,
+            ..
+        } = self;
+
+        let mut due = vec![];
+        for (node, state) in state.iter() {
+            let (persistent, n) = match (state.as_persistent(), nodes.get(node)) {
+                (Some(persistent), Some(n)) => (persistent, n.borrow()),
+                _ => continue,
+            };
+            metrics.set_base_table_tombstone_ratio(
+                node,
+                n.name(),
+                persistent.tombstone_stats().ratio(),
+            );
+
+            if let Some(interval) = n.get_base().and_then(|base| base.compaction_interval()) {
+                if !last_base_table_compactions.contains_key(node) {
+                    last_base_table_compactions.insert(node, now);
+                }
+                if last_base_table_compactions
+                    .get(node)
+                    .map_or(false, |last| now.duration_since(*last) >= interval)
+                {
+                    due.push(node);
+                }
+            }
+        }
+
+        for node in due {
+            if let Err(error) = self.compact_base_table(node) {
+                warn!(%error, "Could not start scheduled base table compaction");
+            }
+        }
+    }
+
+    /// Construct a new, empty state for a partially materialized node, compressing cold keys if
+    /// configured to do so in our [`PersistenceParameters`]
+    fn new_partial_state(&self) -> MaterializedNodeState {
+        MaterializedNodeState::Memory(match self.persistence_parameters.state_compression {
+            Some(params) => MemoryState::with_compression(params),
+            None => MemoryState::default(),
+        })
+    }
+
     pub fn update_state_sizes(&mut self) {
         let mut reader_size: u64 = 0;
+        let mut reader_shard_stats = vec![];
         let total: u64 = self
             .nodes
             .values()
@@ -4123,6 +4433,13 @@ impl Domain {
                     // We are a reader, which has its own kind of state
                     let mut size = 0;
                     if let Some(wh) = self.reader_write_handles.get(local_index) {
+                        reader_shard_stats.push((
+                            n.global_addr(),
+                            ReaderShardStats {
+                                key_count: wh.len(),
+                                reads: wh.reads(),
+                            },
+                        ));
                         if wh.is_partial() {
                             size = wh.deep_size_of();
                             reader_size += size;
@@ -4146,10 +4463,19 @@ impl Domain {
             .map(|(ni, state)| {
                 let ret = state.deep_size_of();
                 metrics.set_node_state_size(ni, ret);
+                if let MaterializedNodeState::Memory(ms) = state {
+                    if let Some(stats) = ms.compression_stats() {
+                        metrics.set_node_state_compression_stats(ni, stats);
+                    }
+                }
                 ret
             })
             .sum();
 
+        for (node, stats) in reader_shard_stats {
+            self.metrics.set_reader_shard_stats(node, stats);
+        }
+
         self.metrics.set_state_sizes(
             total,
             reader_size,
@@ -4215,7 +4541,13 @@ impl Domain {
             self.wait_time.stop();
         }
 
+        let tenant = self.upquery_tenant(&packet);
+        let start = time::Instant::now();
         self.handle(packet, executor)?;
+        if let Some(tenant) = tenant {
+            self.metrics.rec_tenant_upquery(tenant, start.elapsed());
+        }
+
         // After we handle an external packet, the domain may have accumulated a bunch of packets to
         // itself we need to process them all next;
         while let Some(message) = self.delayed_for_self.pop_front() {
@@ -4234,6 +4566,44 @@ impl Domain {
         Ok(())
     }
 
+    /// If the given packet is an upquery (a request for a replay on behalf of either a reader or a
+    /// downstream domain), returns the tenant the upquery is being made on behalf of, or `None` if
+    /// the packet isn't an upquery. Upqueries for nodes without a tenant belong to the default
+    /// tenant, `Some(None)`.
+    pub fn upquery_tenant(&self, packet: &Packet) -> Option<Option<SqlIdentifier>> {
+        let node = match packet {
+            Packet::RequestReaderReplay { node, .. } => Some(*node),
+            Packet::RequestPartialReplay { tag, .. } => self
+                .replay_paths
+                .get(*tag)
+                .and_then(|path| path.target_node()),
+            _ => return None,
+        };
+        Some(
+            node.and_then(|node| self.nodes.get(node))
+                .and_then(|n| n.borrow().tenant().cloned()),
+        )
+    }
+
+    /// Returns the relative share of upquery processing in this domain configured for the given
+    /// tenant
+    pub fn tenant_weight(&self, tenant: Option<&SqlIdentifier>) -> u32 {
+        tenant
+            .and_then(|tenant| self.tenant_weights.get(tenant))
+            .copied()
+            .unwrap_or(1)
+            .max(1)
+    }
+
+    /// Returns the number of rows affected by the writes to base tables processed since this was
+    /// last called, which is used to acknowledge each write to the client that sent it.
+    ///
+    /// Writes buffered while the domain is replaying are counted when they are eventually
+    /// processed, rather than when the packet containing them is handled.
+    pub fn take_affected_rows(&mut self) -> u64 {
+        mem::take(&mut self.affected_rows)
+    }
+
     /// Handle an expired timeout from `next_poll_duration`
     pub fn handle_timeout(&mut self) -> ReadySetResult<()> {
         if self.wait_time.is_running() {
@@ -60,7 +60,8 @@ pub use dataflow_expression::{
     PostLookupAggregateFunction, PostLookupAggregates, ReaderProcessing,
 };
 pub use dataflow_state::{
-    DurabilityMode, MaterializedNodeState, PersistenceParameters, PersistentState,
+    BaseTableCompression, DurabilityMode, MaterializedNodeState, PersistenceParameters,
+    PersistentState, RemoteStorage, RocksDbTuning, StateCompression,
 };
 
 pub use crate::domain::{Domain, DomainBuilder, DomainIndex};
@@ -1,12 +1,14 @@
 use std::collections::{HashMap, HashSet};
 
-use nom_sql::{ColumnSpecification, Relation, SqlIdentifier};
+use nom_sql::{ColumnConstraint, ColumnSpecification, Relation, SqlIdentifier};
 use readyset_client::consistency::Timestamp;
-use readyset_data::{DfType, Dialect};
+use readyset_data::{Collation, DfType, Dialect};
 use serde::{Deserialize, Serialize};
 
 use crate::ops::grouped::aggregate::AggregatorState;
+use crate::ops::grouped::approx::ApproxState;
 use crate::ops::grouped::concat::GroupConcatState;
+use crate::ops::grouped::percentile::PercentileState;
 use crate::ops::{self};
 use crate::prelude::*;
 use crate::processing::LookupIndex;
@@ -50,6 +52,9 @@ impl Column {
     }
 
     /// Creates a dataflow column from the [`nom_sql`] specification.
+    ///
+    /// If the specification has a `COLLATE` constraint naming a supported collation, text
+    /// columns are given that collation.
     #[inline]
     pub fn from_spec<F>(
         spec: ColumnSpecification,
@@ -59,11 +64,15 @@ impl Column {
     where
         F: Fn(Relation) -> Option<DfType>,
     {
-        Ok(Self::new(
-            spec.column.name,
-            DfType::from_sql_type(&spec.sql_type, dialect, resolve_type)?,
-            spec.column.table,
-        ))
+        let mut ty = DfType::from_sql_type(&spec.sql_type, dialect, resolve_type)?;
+        if let Some(collation) = spec.constraints.iter().find_map(|c| match c {
+            ColumnConstraint::Collation(name) => Collation::from_name(dialect.engine(), name),
+            _ => None,
+        }) {
+            ty = ty.with_collation(collation);
+        }
+
+        Ok(Self::new(spec.column.name, ty, spec.column.table))
     }
 
     /// Column name
@@ -108,6 +117,11 @@ pub struct Node {
 
     sharded_by: Sharding,
 
+    /// The tenant (the schema of the base tables it was derived from) this node does work on
+    /// behalf of, used to schedule upqueries fairly between tenants sharing a domain
+    #[serde(default)]
+    tenant: Option<SqlIdentifier>,
+
     // Tracks each up stream nodes timestamp.
     // Used to maintain read-your-write consistency when reading data
     // in the data flow graph.
@@ -140,16 +154,19 @@ impl Node {
             purge: false,
 
             sharded_by: Sharding::None,
+            tenant: None,
             timestamps: HashMap::new(),
         }
     }
 
     pub fn mirror<NT: Into<NodeType>>(&self, n: NT) -> Node {
-        Self::new(self.name.clone(), self.columns.clone(), n)
+        self.named_mirror(n, self.name.clone())
     }
 
     pub fn named_mirror<NT: Into<NodeType>>(&self, n: NT, name: Relation) -> Node {
-        Self::new(name, self.columns.clone(), n)
+        let mut mirror = Self::new(name, self.columns.clone(), n);
+        mirror.tenant = self.tenant.clone();
+        mirror
     }
 
     /// Duplicates the existing node, clearing the index, taken flag, and timestamps
@@ -191,6 +208,8 @@ impl DanglingDomainNode {
 pub enum AuxiliaryNodeState {
     Aggregation(AggregatorState),
     Concat(GroupConcatState),
+    Percentile(PercentileState),
+    Approx(ApproxState),
 }
 
 // external parts of Ingredient
@@ -370,6 +389,10 @@ impl Node {
                     Some(AuxiliaryNodeState::Aggregation(Default::default()))
                 }
                 NodeOperator::Concat(_) => Some(AuxiliaryNodeState::Concat(Default::default())),
+                NodeOperator::Percentile(_) => {
+                    Some(AuxiliaryNodeState::Percentile(Default::default()))
+                }
+                NodeOperator::Approx(_) => Some(AuxiliaryNodeState::Approx(Default::default())),
                 NodeOperator::Extremum(_)
                 | NodeOperator::Join(_)
                 | NodeOperator::Paginate(_)
@@ -377,7 +400,8 @@ impl Node {
                 | NodeOperator::Union(_)
                 | NodeOperator::Identity(_)
                 | NodeOperator::Filter(_)
-                | NodeOperator::TopK(_) => None,
+                | NodeOperator::TopK(_)
+                | NodeOperator::Window(_) => None,
             },
             NodeType::Ingress
             | NodeType::Base(_)
@@ -409,6 +433,16 @@ impl Node {
         self.sharded_by = s;
     }
 
+    /// Returns the tenant this node does work on behalf of, if any
+    pub fn tenant(&self) -> Option<&SqlIdentifier> {
+        self.tenant.as_ref()
+    }
+
+    /// Set the tenant this node does work on behalf of
+    pub fn set_tenant(&mut self, tenant: Option<SqlIdentifier>) {
+        self.tenant = tenant;
+    }
+
     /// Returns the node's inner NodeType as a String.
     pub fn node_type_string(&self) -> String {
         self.inner.to_string()
@@ -27,6 +27,9 @@ pub(crate) struct NodeProcessingResult {
 
     /// Keys for replays captured during processing
     pub(crate) captured: HashSet<KeyComparison>,
+
+    /// The number of rows affected by the writes processed by a base node
+    pub(crate) affected_rows: u64,
 }
 
 /// A helper struct that combines unique misses for the same columns in the same node
@@ -156,6 +159,7 @@ impl Node {
                             records: mut rs,
                             replication_offset,
                             set_snapshot_mode,
+                            affected_rows,
                         } = b.process_ops(
                             addr,
                             &self.columns,
@@ -201,6 +205,11 @@ impl Node {
                             data: rs,
                             trace,
                         }));
+
+                        return Ok(NodeProcessingResult {
+                            affected_rows,
+                            ..Default::default()
+                        });
                     }
                     Some(ref p) => {
                         // TODO: replays?
@@ -436,6 +445,7 @@ impl Node {
                     misses,
                     lookups,
                     captured,
+                    affected_rows: 0,
                 });
             }
             NodeType::Dropped => {
@@ -876,6 +886,7 @@ pub mod bench {
             ],
             lookups: vec![],
             captured: HashSet::new(),
+            affected_rows: 0,
         };
 
         c.bench_function("unique_misses", |b| {
@@ -2,8 +2,9 @@ use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::time::Duration;
 
-use dataflow_state::{MaterializedNodeState, PointKey, SnapshotMode};
+use dataflow_state::{MaterializedNodeState, PointKey, RocksDbTuning, SnapshotMode};
 use itertools::Itertools;
 use nom_sql::Relation;
 use readyset_client::replication::ReplicationOffset;
@@ -43,6 +44,11 @@ pub struct BaseWrite {
 
     /// Optionally enter or exit the snapshot mode for this table
     pub set_snapshot_mode: Option<SetSnapshotMode>,
+
+    /// The number of rows affected by the operations in this batch, counted the way MySQL does:
+    /// one for each row inserted, deleted or changed by an update, and two for each row changed
+    /// by an [`InsertOrUpdate`](TableOperation::InsertOrUpdate).
+    pub affected_rows: u64,
 }
 
 impl From<Records> for BaseWrite {
@@ -51,6 +57,7 @@ impl From<Records> for BaseWrite {
             records,
             replication_offset: None,
             set_snapshot_mode: None,
+            affected_rows: 0,
         }
     }
 }
@@ -70,6 +77,13 @@ pub struct Base {
     dropped: Vec<usize>,
     unmodified: bool,
     permissive_writes: bool,
+    /// If set, how often the persisted state of this base table should be manually compacted, to
+    /// drop accumulated deletion tombstones
+    compaction_interval: Option<Duration>,
+    /// RocksDB tuning parameters set for the persisted state of this base table at runtime, which
+    /// take precedence over those configured in the domain's persistence parameters
+    #[serde(default)]
+    rocksdb_tuning: Option<RocksDbTuning>,
 }
 
 impl Base {
@@ -118,6 +132,33 @@ impl Base {
             .collect()
     }
 
+    /// Returns how often the persisted state of this base table should be manually compacted, if
+    /// scheduled compactions are enabled for it
+    pub fn compaction_interval(&self) -> Option<Duration> {
+        self.compaction_interval
+    }
+
+    /// Set (or clear, if `None`) the interval at which the persisted state of this base table
+    /// should be manually compacted
+    pub fn set_compaction_interval(&mut self, interval: Option<Duration>) {
+        self.compaction_interval = interval;
+    }
+
+    /// Returns the RocksDB tuning parameters set for the persisted state of this base table at
+    /// runtime, if any
+    pub fn rocksdb_tuning(&self) -> Option<RocksDbTuning> {
+        self.rocksdb_tuning
+    }
+
+    /// Set the RocksDB tuning parameters for the persisted state of this base table. Parameters
+    /// left unset in `tuning` retain their previous values.
+    pub fn set_rocksdb_tuning(&mut self, tuning: RocksDbTuning) {
+        self.rocksdb_tuning = Some(match self.rocksdb_tuning {
+            Some(old) => tuning.or(old),
+            None => tuning,
+        });
+    }
+
     /// Add a new column to this base node.
     pub fn add_column(&mut self, default: DfValue) -> ReadySetResult<usize> {
         invariant!(
@@ -166,6 +207,17 @@ impl Base {
         }
     }
 
+    /// Like [`Base::fix`], but for a row read out of the base's state, which is only copied if it
+    /// needs to be extended
+    fn fix_stored<'a>(&self, row: Cow<'a, [DfValue]>) -> Cow<'a, [DfValue]> {
+        if self.unmodified || row.len() == self.defaults.len() {
+            return row;
+        }
+        let mut row = row.into_owned();
+        self.fix(&mut row);
+        Cow::Owned(row)
+    }
+
     /// Process table operations for a base table that doesn't have a key, such tables can
     /// have multiple copies of the same row, and delete operations are free to remove any of them
     fn process_unkeyed(
@@ -176,6 +228,7 @@ impl Base {
         // Keep track of the maximal replication offset in the list, if any
         let mut replication_offset: Option<ReplicationOffset> = None;
         let mut set_snapshot_mode: Option<SetSnapshotMode> = None;
+        let mut affected_rows = 0;
 
         // This is a non keyed table, can only apply non-keyed operations
         let mut records = Vec::with_capacity(operations.len());
@@ -184,10 +237,12 @@ impl Base {
                 TableOperation::Insert(mut row) => {
                     self.fix(&mut row);
                     records.push(Record::Positive(row));
+                    affected_rows += 1;
                 }
                 TableOperation::DeleteRow { mut row } => {
                     self.fix(&mut row);
                     records.push(Record::Negative(row));
+                    affected_rows += 1;
                 }
                 TableOperation::SetReplicationOffset(offset) => {
                     offset.try_max_into(&mut replication_offset)?;
@@ -201,7 +256,10 @@ impl Base {
                 }
                 TableOperation::Truncate => {
                     records.clear();
-                    records.extend(db.cloned_records().into_iter().map(|r| Record::Negative(r)))
+                    records.extend(db.cloned_records().into_iter().map(|mut r| {
+                        self.fix(&mut r);
+                        Record::Negative(r)
+                    }))
                 }
                 TableOperation::DeleteByKey { .. }
                 | TableOperation::InsertOrUpdate { .. }
@@ -215,6 +273,7 @@ impl Base {
             records: records.into(),
             replication_offset,
             set_snapshot_mode,
+            affected_rows,
         })
     }
 
@@ -244,6 +303,16 @@ impl Base {
             _ => return self.process_unkeyed(db, ops),
         };
 
+        // Since truncations are processed before any keyed operations, drop the keyed operations
+        // which precede the last truncation - their effects would be truncated anyway
+        if let Some(last_truncate) = ops.iter().rposition(|op| *op == TableOperation::Truncate) {
+            let mut i = 0;
+            ops.retain(|op| {
+                i += 1;
+                i > last_truncate || key_of(key_cols, op).next().is_none()
+            });
+        }
+
         let mut n_ops = ops.len();
         // Sort all of the operations lexicographically by key types, all unkeyed operations will
         // move to the front of the vector (which can only be `SetReplicationOffset`), for
@@ -297,7 +366,10 @@ impl Base {
             if !truncated {
                 debug!("Truncating base");
                 truncated = true;
-                results.extend(db.cloned_records().into_iter().map(|r| Record::Negative(r)));
+                results.extend(db.cloned_records().into_iter().map(|mut r| {
+                    self.fix(&mut r);
+                    Record::Negative(r)
+                }));
             }
         }
 
@@ -313,14 +385,16 @@ impl Base {
         }
         let mut touched_keys: HashMap<Vec<DfValue>, TouchedKey> = HashMap::new();
         let mut failed_log = FailedOpLogger::new(name);
+        let mut affected_rows = 0;
 
         for (key, ops) in &ops {
             // It is not enough to check the persisted value for the key, as it may have been
             // changed in previous iteration, therefore we have to check it was not
             // changed in one of the outstanding records
-            let stored_value = if snapshot_mode.is_enabled() {
+            let stored_value = if snapshot_mode.is_enabled() || truncated {
                 // In snapshot mode don't check the currently store values as it doesn't matter for
-                // correctness but imposes a heavy toll on batched writes
+                // correctness but imposes a heavy toll on batched writes. If the base was truncated
+                // the stored values have all been deleted already.
                 None
             } else {
                 match touched_keys.get(&key) {
@@ -330,7 +404,11 @@ impl Base {
                     None => match db.lookup(key_cols, &PointKey::from(key.clone())) {
                         LookupResult::Missing => internal!(),
                         LookupResult::Some(rows) if rows.is_empty() => None,
-                        LookupResult::Some(rows) if rows.len() == 1 => rows.into_iter().next(),
+                        // Rows written before columns were added to the base are stored without
+                        // those columns, so fill them in before comparing against the new row
+                        LookupResult::Some(rows) if rows.len() == 1 => {
+                            rows.into_iter().next().map(|row| self.fix_stored(row))
+                        }
                         LookupResult::Some(rows) => {
                             internal!(
                                 "key {:?} not unique; num_rows={}",
@@ -347,44 +425,62 @@ impl Base {
             let mut value = stored_value.clone();
 
             for op in ops {
+                let is_upsert = matches!(op, TableOperation::InsertOrUpdate { .. });
                 match op {
-                    TableOperation::Insert(row) if value.is_none() => value = Some(Cow::Owned(row)),
+                    TableOperation::Insert(row) if value.is_none() => {
+                        value = Some(Cow::Owned(row));
+                        affected_rows += 1;
+                    }
                     TableOperation::Insert(_) => {
                         failed_log.failed_insert();
                     }
                     TableOperation::DeleteRow { row } if value == Some(Cow::Borrowed(&row)) => {
                         // Delete the row, but only if it fully matches the current row
                         value = None;
+                        affected_rows += 1;
                     }
                     TableOperation::DeleteRow { row } => {
                         failed_log.failed_delete(row, value.as_deref());
                     }
-                    TableOperation::DeleteByKey { .. } => value = None,
+                    TableOperation::DeleteByKey { .. } => {
+                        if value.take().is_some() {
+                            affected_rows += 1;
+                        }
+                    }
 
                     TableOperation::InsertOrUpdate { row, .. } if value.is_none() => {
-                        value = Some(Cow::Owned(row))
+                        value = Some(Cow::Owned(row));
+                        affected_rows += 1;
                     }
                     TableOperation::InsertOrUpdate { update, .. }
                     | TableOperation::Update { update, .. }
                         if value.is_some() =>
                     {
+                        let mut changed = false;
                         if let Some(updated) = value.as_mut().map(Cow::to_mut) {
                             for (col, op) in update.into_iter().enumerate() {
                                 // XXX: make sure user doesn't update primary key?
-                                match op {
-                                    Modification::Set(v) => updated[col] = v,
+                                let new = match op {
+                                    Modification::Set(v) => v,
                                     Modification::Apply(op, v) => {
                                         let old: i128 = <i128>::try_from(updated[col].clone())?;
                                         let delta: i128 = <i128>::try_from(v)?;
-                                        updated[col] = match op {
+                                        match op {
                                             Operation::Add => DfValue::try_from(old + delta)?,
                                             Operation::Sub => DfValue::try_from(old - delta)?,
-                                        };
+                                        }
                                     }
-                                    Modification::None => {}
-                                }
+                                    Modification::None => continue,
+                                };
+                                changed |= updated[col] != new;
+                                updated[col] = new;
                             }
                         }
+                        // Like MySQL, only count rows whose values actually changed, and count
+                        // upserts that update an existing row twice
+                        if changed {
+                            affected_rows += if is_upsert { 2 } else { 1 };
+                        }
                     }
                     TableOperation::Update { .. } => {
                         failed_log.failed_update();
@@ -436,6 +532,7 @@ impl Base {
             records: results.into(),
             replication_offset,
             set_snapshot_mode,
+            affected_rows,
         })
     }
 
@@ -460,6 +557,8 @@ impl Default for Base {
             dropped: Vec::new(),
             unmodified: true,
             permissive_writes: false,
+            compaction_interval: None,
+            rocksdb_tuning: None,
         }
     }
 }
@@ -894,6 +993,7 @@ mod tests {
                     .into(),
                     replication_offset: None,
                     set_snapshot_mode: None,
+                    affected_rows: 2,
                 }
             )
         }
@@ -948,6 +1048,59 @@ mod tests {
                     .into(),
                     replication_offset: None,
                     set_snapshot_mode: None,
+                    affected_rows: 2,
+                }
+            )
+        }
+
+        #[test]
+        fn delete_row_after_added_column_keyed() {
+            let mut b = Base::new()
+                .with_primary_key([0])
+                .with_default_values(vec![DfValue::None, DfValue::None]);
+            b.add_column(4.into()).unwrap();
+
+            let ni = LocalNodeIndex::make(0u32);
+
+            let mut state = MaterializedNodeState::Persistent(
+                PersistentState::new(
+                    String::from("delete_row_after_added_column_keyed"),
+                    Vec::<Box<[usize]>>::new(),
+                    &PersistenceParameters::default(),
+                )
+                .unwrap(),
+            );
+
+            state.add_key(Index::hash_map(vec![0]), None);
+
+            // Written before the column was added
+            let mut recs = vec![Record::Positive(vec![2.into(), 3.into()])].into();
+            state.process_records(&mut recs, None, None).unwrap();
+
+            let mut state_map = NodeMap::new();
+            state_map.insert(ni, state);
+
+            let table = Relation {
+                name: "test".into(),
+                schema: None,
+            };
+            assert_eq!(
+                b.process_ops(
+                    ni,
+                    &[],
+                    vec![TableOperation::DeleteRow {
+                        row: vec![2.into(), 3.into(), 4.into()]
+                    }],
+                    &state_map,
+                    SnapshotMode::SnapshotModeDisabled,
+                    table,
+                )
+                .unwrap(),
+                BaseWrite {
+                    records: vec![Record::Negative(vec![2.into(), 3.into(), 4.into()])].into(),
+                    replication_offset: None,
+                    set_snapshot_mode: None,
+                    affected_rows: 1,
                 }
             )
         }
@@ -1010,6 +1163,7 @@ mod tests {
                     .into(),
                     replication_offset: None,
                     set_snapshot_mode: None,
+                    affected_rows: 2,
                 }
             )
         }
@@ -1066,7 +1220,8 @@ mod tests {
                     ]
                     .into(),
                     replication_offset: None,
-                    set_snapshot_mode: None
+                    set_snapshot_mode: None,
+                    affected_rows: 0
                 }
             );
         }
@@ -1122,9 +1277,147 @@ mod tests {
                     ]
                     .into(),
                     replication_offset: None,
-                    set_snapshot_mode: None
+                    set_snapshot_mode: None,
+                    affected_rows: 0
+                }
+            );
+        }
+
+        #[test]
+        fn truncate_with_keyed_ops() {
+            let mut b = Base::new().with_primary_key([0]);
+            let ni = LocalNodeIndex::make(0u32);
+            let mut state = MaterializedNodeState::Persistent(
+                PersistentState::new(
+                    "truncate_with_keyed_ops".into(),
+                    Vec::<Box<[usize]>>::new(),
+                    &PersistenceParameters::default(),
+                )
+                .unwrap(),
+            );
+
+            state.add_key(Index::hash_map(vec![0]), None);
+            let mut recs = vec![
+                Record::Positive(vec![1.into(), "a".into()]),
+                Record::Positive(vec![2.into(), "b".into()]),
+            ]
+            .into();
+            state.process_records(&mut recs, None, None).unwrap();
+
+            let mut state_map = NodeMap::new();
+            state_map.insert(ni, state);
+
+            let table = Relation {
+                name: "test".into(),
+                schema: None,
+            };
+            let res = b
+                .process_ops(
+                    ni,
+                    &[],
+                    vec![
+                        TableOperation::Insert(vec![3.into(), "c".into()]),
+                        TableOperation::Truncate,
+                        TableOperation::Insert(vec![1.into(), "z".into()]),
+                    ],
+                    &state_map,
+                    SnapshotMode::SnapshotModeDisabled,
+                    table,
+                )
+                .unwrap();
+            assert_eq!(
+                res,
+                BaseWrite {
+                    records: vec![
+                        Record::Negative(vec![1.into(), "a".into()]),
+                        Record::Negative(vec![2.into(), "b".into()]),
+                        Record::Positive(vec![1.into(), "z".into()]),
+                    ]
+                    .into(),
+                    replication_offset: None,
+                    set_snapshot_mode: None,
+                    affected_rows: 1
                 }
             );
         }
+
+        #[test]
+        fn affected_rows() {
+            let mut b = Base::new().with_primary_key([0]);
+            let ni = LocalNodeIndex::make(0u32);
+            let mut state = MaterializedNodeState::Persistent(
+                PersistentState::new(
+                    "affected_rows".into(),
+                    Vec::<Box<[usize]>>::new(),
+                    &PersistenceParameters::default(),
+                )
+                .unwrap(),
+            );
+
+            state.add_key(Index::hash_map(vec![0]), None);
+            let mut recs = vec![
+                Record::Positive(vec![1.into(), 1.into()]),
+                Record::Positive(vec![2.into(), 2.into()]),
+            ]
+            .into();
+            state.process_records(&mut recs, None, None).unwrap();
+
+            let mut state_map = NodeMap::new();
+            state_map.insert(ni, state);
+
+            let table = Relation {
+                name: "test".into(),
+                schema: None,
+            };
+            let res = b
+                .process_ops(
+                    ni,
+                    &[],
+                    vec![
+                        // inserted: 1
+                        TableOperation::Insert(vec![3.into(), 3.into()]),
+                        // duplicate key: 0
+                        TableOperation::Insert(vec![1.into(), 3.into()]),
+                        // updated by an upsert: 2
+                        TableOperation::InsertOrUpdate {
+                            row: vec![2.into(), 3.into()],
+                            update: vec![Modification::None, Modification::Set(4.into())],
+                        },
+                        // inserted by an upsert: 1
+                        TableOperation::InsertOrUpdate {
+                            row: vec![4.into(), 4.into()],
+                            update: vec![Modification::None, Modification::Set(5.into())],
+                        },
+                        // updated: 1
+                        TableOperation::Update {
+                            key: vec![1.into()],
+                            update: vec![Modification::None, Modification::Set(6.into())],
+                        },
+                        // unchanged: 0
+                        TableOperation::InsertOrUpdate {
+                            row: vec![4.into(), 4.into()],
+                            update: vec![Modification::None, Modification::None],
+                        },
+                        // missing key: 0
+                        TableOperation::Update {
+                            key: vec![5.into()],
+                            update: vec![Modification::None, Modification::Set(6.into())],
+                        },
+                        // deleted: 1
+                        TableOperation::DeleteByKey {
+                            key: vec![3.into()],
+                        },
+                        // missing key: 0
+                        TableOperation::DeleteByKey {
+                            key: vec![6.into()],
+                        },
+                    ],
+                    &state_map,
+                    SnapshotMode::SnapshotModeDisabled,
+                    table,
+                )
+                .unwrap();
+            assert_eq!(res.affected_rows, 6);
+        }
     }
 }
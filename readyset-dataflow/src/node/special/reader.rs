@@ -25,6 +25,14 @@ pub struct Reader {
     ///
     /// The data is stored in this manner instead of in a Hashmap to support ordered iteration.
     placeholder_map: Vec<(ViewPlaceholder, KeyColumnIdx)>,
+
+    /// If true, this reader (and the nodes it reads from) will always be fully materialized, even
+    /// if it could be partially materialized
+    force_full_materialization: bool,
+
+    /// If set, the number of times to replicate the domain containing this reader, overriding the
+    /// cluster-wide replication strategy
+    replicas: Option<usize>,
 }
 
 impl Clone for Reader {
@@ -34,6 +42,8 @@ impl Clone for Reader {
             reader_processing: self.reader_processing.clone(),
             index: self.index.clone(),
             placeholder_map: self.placeholder_map.clone(),
+            force_full_materialization: self.force_full_materialization,
+            replicas: self.replicas,
         }
     }
 }
@@ -45,6 +55,8 @@ impl Reader {
             reader_processing,
             index: None,
             placeholder_map: Default::default(),
+            force_full_materialization: false,
+            replicas: None,
         }
     }
 
@@ -60,6 +72,8 @@ impl Reader {
             reader_processing: self.reader_processing.clone(),
             index: self.index.clone(),
             placeholder_map: self.placeholder_map.clone(),
+            force_full_materialization: self.force_full_materialization,
+            replicas: self.replicas,
         }
     }
 
@@ -115,6 +129,26 @@ impl Reader {
         self.placeholder_map.as_ref()
     }
 
+    /// Returns true if this reader must be fully materialized, even if it could be partially
+    /// materialized
+    pub fn force_full_materialization(&self) -> bool {
+        self.force_full_materialization
+    }
+
+    pub fn set_force_full_materialization(&mut self, force_full_materialization: bool) {
+        self.force_full_materialization = force_full_materialization;
+    }
+
+    /// Returns the number of times the domain containing this reader should be replicated, if
+    /// that's been configured for this reader specifically
+    pub fn replicas(&self) -> Option<usize> {
+        self.replicas
+    }
+
+    pub fn set_replicas(&mut self, replicas: Option<usize>) {
+        self.replicas = replicas;
+    }
+
     #[allow(clippy::unreachable)]
     #[failpoint("reader-handle-packet")]
     pub(in crate::node) fn process(
@@ -12,7 +12,7 @@ use crate::ops::grouped::{GroupedOperation, GroupedOperator};
 use crate::prelude::*;
 
 /// Supported aggregation operators.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Aggregation {
     /// Count the number of non-null values.
     Count,
@@ -22,8 +22,20 @@ pub enum Aggregation {
     Avg,
     /// Concatenates using the given separator between values.
     GroupConcat { separator: String },
+    /// Computes the given percentile (as a fraction between 0 and 1) of the values, interpolating
+    /// between adjacent values if necessary.
+    Percentile { percentile: f64 },
+    /// Estimates the number of distinct values with a HyperLogLog sketch.
+    ApproxCountDistinct,
+    /// Estimates the given percentile (as a fraction between 0 and 1) of the values with a
+    /// t-digest sketch.
+    ApproxPercentile { percentile: f64 },
 }
 
+// The percentiles of `Aggregation::Percentile` and `Aggregation::ApproxPercentile` are always
+// between 0 and 1, so never NaN
+impl Eq for Aggregation {}
+
 impl Aggregation {
     /// Construct a new `Aggregator` that performs this operation.
     ///
@@ -50,6 +62,8 @@ impl Aggregation {
                 }
             }
             Aggregation::GroupConcat { .. } => DfType::Text(/* TODO */ Collation::default()),
+            Aggregation::ApproxCountDistinct => DfType::BigInt,
+            Aggregation::Percentile { .. } | Aggregation::ApproxPercentile { .. } => DfType::Double,
         };
 
         Ok(GroupedOperator::new(
@@ -60,9 +74,33 @@ impl Aggregation {
                 group: group_by.into(),
                 over_else: None,
                 out_ty,
+                distinct: false,
             },
         ))
     }
+
+    /// Construct a new `Aggregator` that performs this operation over only the distinct values of
+    /// the `over` column within each group, as in `COUNT(DISTINCT x)`.
+    ///
+    /// See [`Aggregation::over`] for the meaning of the arguments.
+    pub fn over_distinct(
+        self,
+        src: NodeIndex,
+        over: usize,
+        group_by: &[usize],
+        over_col_ty: &DfType,
+    ) -> ReadySetResult<GroupedOperator<Aggregator>> {
+        invariant!(
+            matches!(
+                self,
+                Aggregation::Count | Aggregation::Sum | Aggregation::Avg
+            ),
+            "only COUNT, SUM, and AVG can be computed over distinct values"
+        );
+        let mut op = self.over(src, over, group_by, over_col_ty)?;
+        op.inner.distinct = true;
+        Ok(op)
+    }
 }
 
 /// Aggregator implements a Soup node that performs common aggregation operations such as counts
@@ -85,6 +123,8 @@ pub struct Aggregator {
     over_else: Option<Literal>,
     // Output type of this column
     out_ty: DfType,
+    /// If true, only aggregate over the distinct values of the `over` column within each group
+    distinct: bool,
 }
 
 /// Diff type for numerical aggregations.
@@ -129,6 +169,44 @@ impl AverageDataPair {
 /// Auxiliary State for an Aggregator node, which is owned by a Domain
 pub struct AggregatorState {
     count_sum_map: HashMap<GroupHash, AverageDataPair>,
+    /// For distinct aggregates, the number of times each value occurs within each group
+    distinct_counts: HashMap<GroupHash, HashMap<DfValue, usize>>,
+}
+
+/// Record the given diff in the counts of the values within its group, returning whether it
+/// changes the set of distinct values in that group (and so should be applied to the aggregate).
+fn update_distinct_counts(
+    distinct_counts: &mut HashMap<GroupHash, HashMap<DfValue, usize>>,
+    diff: &NumericalDiff,
+) -> bool {
+    if diff.value.is_none() {
+        return true;
+    }
+
+    let group = distinct_counts.entry(diff.group_hash).or_default();
+    let changed = if diff.positive {
+        let count = group.entry(diff.value.clone()).or_default();
+        *count += 1;
+        *count == 1
+    } else {
+        match group.get_mut(&diff.value) {
+            Some(count) if *count > 1 => {
+                *count -= 1;
+                false
+            }
+            Some(_) => {
+                group.remove(&diff.value);
+                true
+            }
+            // We never saw this value, so it was never part of the aggregate
+            None => false,
+        }
+    };
+
+    if group.is_empty() {
+        distinct_counts.remove(&diff.group_hash);
+    }
+    changed
 }
 
 impl Aggregator {
@@ -206,10 +284,11 @@ impl GroupedOperation for Aggregator {
             }
         };
 
-        let count_sum_map = match auxiliary_node_state {
-            Some(AuxiliaryNodeState::Aggregation(ref mut aggregator_state)) => {
-                &mut aggregator_state.count_sum_map
-            }
+        let (count_sum_map, distinct_counts) = match auxiliary_node_state {
+            Some(AuxiliaryNodeState::Aggregation(ref mut aggregator_state)) => (
+                &mut aggregator_state.count_sum_map,
+                &mut aggregator_state.distinct_counts,
+            ),
             Some(_) => internal!("Incorrect auxiliary state for Aggregation node"),
             None => internal!("Missing auxiliary state for Aggregation node"),
         };
@@ -237,9 +316,35 @@ impl GroupedOperation for Aggregator {
                     Aggregation::GroupConcat { separator: _ } => internal!(
                         "GroupConcats are separate from the other aggregations in the dataflow."
                     ),
+                    Aggregation::Percentile { .. } => internal!(
+                        "Percentiles are separate from the other aggregations in the dataflow."
+                    ),
+                    Aggregation::ApproxCountDistinct | Aggregation::ApproxPercentile { .. } => {
+                        internal!(
+                            "Approximate aggregates are separate from the other aggregations in \
+                             the dataflow."
+                        )
+                    }
                 }
             };
 
+        let mut distinct_diffs;
+        let diffs: &mut dyn Iterator<Item = Self::Diff> = if self.distinct {
+            // If we don't have a current value for the group, we're starting it from scratch (eg
+            // during a replay), so forget any values we previously counted for it
+            let mut reset_group = current.is_none();
+            distinct_diffs = diffs.filter(|diff| {
+                if reset_group {
+                    distinct_counts.remove(&diff.group_hash);
+                    reset_group = false;
+                }
+                update_distinct_counts(distinct_counts, diff)
+            });
+            &mut distinct_diffs
+        } else {
+            diffs
+        };
+
         diffs
             .fold(Ok(current.cloned().unwrap_or(self.new_data()?)), apply_diff)
             .map(Some)
@@ -254,14 +359,25 @@ impl GroupedOperation for Aggregator {
                 Aggregation::GroupConcat { separator: ref s } => {
                     format!("||({})", s)
                 }
+                Aggregation::Percentile { percentile } => format!("PCT({})", percentile),
+                Aggregation::ApproxCountDistinct => "~COUNT(DISTINCT)".to_owned(),
+                Aggregation::ApproxPercentile { percentile } => format!("~PCT({})", percentile),
             };
         }
 
         let op_string = match self.op {
+            Aggregation::Count { .. } if self.distinct => format!("|*|(DISTINCT {})", self.over),
+            Aggregation::Sum if self.distinct => format!("𝛴(DISTINCT {})", self.over),
+            Aggregation::Avg if self.distinct => format!("Avg(DISTINCT {})", self.over),
             Aggregation::Count { .. } => "|*|".to_owned(),
             Aggregation::Sum => format!("𝛴({})", self.over),
             Aggregation::Avg => format!("Avg({})", self.over),
             Aggregation::GroupConcat { separator: ref s } => format!("||({}, {})", s, self.over),
+            Aggregation::Percentile { percentile } => format!("PCT({}, {})", self.over, percentile),
+            Aggregation::ApproxCountDistinct => format!("~COUNT(DISTINCT {})", self.over),
+            Aggregation::ApproxPercentile { percentile } => {
+                format!("~PCT({}, {})", self.over, percentile)
+            }
         };
         let group_cols = self
             .group
@@ -1129,4 +1245,143 @@ mod tests {
             .into()
         );
     }
+
+    fn setup_distinct(aggregation: Aggregation) -> ops::test::MockGraph {
+        let mut g = ops::test::MockGraph::new();
+        let s = g.add_base("source", &["x", "y"]);
+        g.set_op(
+            "identity",
+            &["x", "ys"],
+            aggregation
+                .over_distinct(s.as_global(), 1, &[0], &DfType::Double)
+                .unwrap(),
+            true,
+        );
+        g
+    }
+
+    #[test]
+    fn describes_distinct() {
+        let c = Aggregation::Count
+            .over_distinct(0.into(), 1, &[0], &DfType::Unknown)
+            .unwrap();
+        assert_eq!(c.description(true), "|*|(DISTINCT 1) γ[0]");
+    }
+
+    #[test]
+    fn count_distinct_forwards() {
+        let mut c = setup_distinct(Aggregation::Count);
+
+        let out = c.narrow_one_row(vec![1.into(), 1.into()], true);
+        assert_eq!(
+            out,
+            vec![vec![DfValue::from(1), DfValue::from(1), DfValue::from(1)]].into()
+        );
+
+        // A duplicate value only changes the row count (the last column)
+        let out = c.narrow_one_row(vec![1.into(), 1.into()], true);
+        assert_eq!(
+            out,
+            vec![
+                (
+                    vec![DfValue::from(1), DfValue::from(1), DfValue::from(1)],
+                    false
+                ),
+                (
+                    vec![DfValue::from(1), DfValue::from(1), DfValue::from(2)],
+                    true
+                ),
+            ]
+            .into()
+        );
+
+        // A new value changes the count
+        let out = c.narrow_one_row(vec![1.into(), 2.into()], true);
+        assert_eq!(
+            out,
+            vec![
+                (
+                    vec![DfValue::from(1), DfValue::from(1), DfValue::from(2)],
+                    false
+                ),
+                (
+                    vec![DfValue::from(1), DfValue::from(2), DfValue::from(3)],
+                    true
+                ),
+            ]
+            .into()
+        );
+
+        // Removing one of the duplicates doesn't remove the value
+        let out = c.narrow_one_row((vec![1.into(), 1.into()], false), true);
+        assert_eq!(
+            out,
+            vec![
+                (
+                    vec![DfValue::from(1), DfValue::from(2), DfValue::from(3)],
+                    false
+                ),
+                (
+                    vec![DfValue::from(1), DfValue::from(2), DfValue::from(2)],
+                    true
+                ),
+            ]
+            .into()
+        );
+
+        // But removing the last one does
+        let out = c.narrow_one_row((vec![1.into(), 1.into()], false), true);
+        assert_eq!(
+            out,
+            vec![
+                (
+                    vec![DfValue::from(1), DfValue::from(2), DfValue::from(2)],
+                    false
+                ),
+                (
+                    vec![DfValue::from(1), DfValue::from(1), DfValue::from(1)],
+                    true
+                ),
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn sum_distinct_forwards() {
+        let mut c = setup_distinct(Aggregation::Sum);
+
+        let out = c.narrow_one(
+            vec![
+                (vec![1.into(), 5.into()], true),
+                (vec![1.into(), 5.into()], true),
+                (vec![1.into(), 7.into()], true),
+                (vec![2.into(), 5.into()], true),
+            ],
+            true,
+        );
+        assert!(out.has_positive(&vec![
+            DfValue::from(1),
+            DfValue::try_from(12.0f64).unwrap(),
+            DfValue::from(3)
+        ]));
+        assert!(out.has_positive(&vec![
+            DfValue::from(2),
+            DfValue::try_from(5.0f64).unwrap(),
+            DfValue::from(1)
+        ]));
+
+        let out = c.narrow_one(
+            vec![
+                (vec![1.into(), 7.into()], false),
+                (vec![1.into(), 5.into()], false),
+            ],
+            true,
+        );
+        assert!(out.has_positive(&vec![
+            DfValue::from(1),
+            DfValue::try_from(5.0f64).unwrap(),
+            DfValue::from(1)
+        ]));
+    }
 }
@@ -0,0 +1,342 @@
+//! Approximate aggregates, maintained with probabilistic sketches: `APPROX_COUNT_DISTINCT()`
+//! (backed by a HyperLogLog) and `APPROX_PERCENTILE()` (backed by a t-digest)
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use common::DfValue;
+use readyset_data::DfType;
+use readyset_errors::{invariant, invariant_eq};
+use readyset_util::Indices;
+use serde::{Deserialize, Serialize};
+
+use crate::node::{AuxiliaryNodeState, Node};
+use crate::ops::grouped::sketch::{HyperLogLog, TDigest};
+use crate::ops::grouped::{GroupedOperation, GroupedOperator};
+use crate::prelude::*;
+
+/// The approximate aggregates supported by [`ApproxAggregator`]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Approximation {
+    /// Estimate the number of distinct non-null values
+    CountDistinct,
+    /// Estimate the given percentile (as a fraction between 0 and 1) of the values
+    Percentile { percentile: f64 },
+}
+
+/// The sketch summarizing the values of a single group
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum Sketch {
+    HyperLogLog(HyperLogLog),
+    TDigest(TDigest),
+}
+
+/// The sketch of a single group, along with the value we last emitted for it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct GroupSketch {
+    sketch: Sketch,
+    /// The value we last emitted for this group.
+    last_output: DfValue,
+}
+
+impl GroupSketch {
+    fn new(op: Approximation) -> Self {
+        Self {
+            sketch: match op {
+                Approximation::CountDistinct => Sketch::HyperLogLog(Default::default()),
+                Approximation::Percentile { .. } => Sketch::TDigest(Default::default()),
+            },
+            last_output: DfValue::None,
+        }
+    }
+
+    fn insert(&mut self, value: &DfValue) -> ReadySetResult<()> {
+        match &mut self.sketch {
+            Sketch::HyperLogLog(hll) => hll.insert(value),
+            Sketch::TDigest(digest) => digest.insert(f64::try_from(value)?),
+        }
+        Ok(())
+    }
+
+    fn output(&mut self, op: Approximation) -> ReadySetResult<DfValue> {
+        match (&mut self.sketch, op) {
+            (Sketch::HyperLogLog(hll), Approximation::CountDistinct) => {
+                Ok(DfValue::from(hll.estimate() as i64))
+            }
+            (Sketch::TDigest(digest), Approximation::Percentile { percentile }) => digest
+                .quantile(percentile)
+                .map_or(Ok(DfValue::None), DfValue::try_from),
+            _ => internal!("Sketch does not match approximate aggregate {:?}", op),
+        }
+    }
+}
+
+/// `ApproxAggregator` implements the approximate aggregate functions `APPROX_COUNT_DISTINCT` and
+/// `APPROX_PERCENTILE`, which trade exactness for a bounded amount of state per group.
+///
+/// The values of each group are summarized in a sketch kept in the node's auxiliary state, so
+/// inserts are cheap regardless of the size of the group. Sketches can't have values removed from
+/// them though, so removing a value from a group (or losing that group's sketch) causes it to be
+/// rebuilt from the parent.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApproxAggregator {
+    /// Which column to aggregate.
+    source_col: usize,
+    /// The columns to group by.
+    group_by: Vec<usize>,
+    /// The aggregate to compute.
+    op: Approximation,
+}
+
+impl ApproxAggregator {
+    /// Construct a new `ApproxAggregator`, computing `op` over the values in the provided
+    /// `source_col`.
+    pub fn new(
+        src: NodeIndex,
+        source_col: usize,
+        group_by: Vec<usize>,
+        op: Approximation,
+    ) -> ReadySetResult<GroupedOperator<ApproxAggregator>> {
+        if let Approximation::Percentile { percentile } = op {
+            invariant!(
+                (0.0..=1.0).contains(&percentile),
+                "percentile must be between 0 and 1"
+            );
+        }
+        Ok(GroupedOperator::new(
+            src,
+            ApproxAggregator {
+                source_col,
+                group_by,
+                op,
+            },
+        ))
+    }
+}
+
+pub struct ApproxDiff {
+    value: DfValue,
+    is_positive: bool,
+    group_by: Vec<DfValue>,
+}
+
+impl GroupedOperation for ApproxAggregator {
+    type Diff = ApproxDiff;
+
+    fn setup(&mut self, parent: &Node) -> ReadySetResult<()> {
+        invariant!(
+            self.source_col < parent.columns().len(),
+            "cannot aggregate over non-existing column"
+        );
+        Ok(())
+    }
+
+    fn group_by(&self) -> &[usize] {
+        &self.group_by
+    }
+
+    fn to_diff(&self, record: &[DfValue], is_positive: bool) -> ReadySetResult<Self::Diff> {
+        let value = record
+            .get(self.source_col)
+            .ok_or(ReadySetError::InvalidRecordLength)?
+            .clone();
+        // We need this to figure out which state to use.
+        let group_by = record
+            .cloned_indices(self.group_by.iter().cloned())
+            .map_err(|_| ReadySetError::InvalidRecordLength)?;
+        Ok(ApproxDiff {
+            value,
+            is_positive,
+            group_by,
+        })
+    }
+
+    fn apply(
+        &self,
+        current: Option<&DfValue>,
+        diffs: &mut dyn Iterator<Item = Self::Diff>,
+        auxiliary_node_state: Option<&mut AuxiliaryNodeState>,
+    ) -> ReadySetResult<Option<DfValue>> {
+        let mut diffs = diffs.peekable();
+
+        let first_diff = diffs
+            .peek()
+            .ok_or_else(|| internal_err!("approximate aggregate got no diffs"))?;
+        let group = first_diff.group_by.clone();
+
+        let groups = match auxiliary_node_state {
+            Some(AuxiliaryNodeState::Approx(ref mut s)) => &mut s.groups,
+            Some(_) => internal!("Incorrect auxiliary state for ApproxAggregator node"),
+            None => internal!("Missing auxiliary state for ApproxAggregator node"),
+        };
+
+        let mut sketch = match (current, groups.remove(&group)) {
+            // if state matches what we last emitted, use it
+            (Some(current), Some(sketch)) if *current == sketch.last_output => sketch,
+            // if state doesn't match, need to recreate it
+            (Some(_), _) => return Ok(None),
+            // if we're recreating or this is the first record for the group, start afresh
+            (None, _) => GroupSketch::new(self.op),
+        };
+
+        for ApproxDiff {
+            value,
+            is_positive,
+            group_by,
+        } in diffs
+        {
+            invariant_eq!(group_by, group);
+            if !is_positive {
+                // The sketch can't forget values, so rebuild it from the rest of the group
+                return Ok(None);
+            }
+            if value.is_none() {
+                continue;
+            }
+            sketch.insert(&value)?;
+        }
+
+        let out = sketch.output(self.op)?;
+        sketch.last_output = out.clone();
+        groups.insert(group, sketch);
+        Ok(Some(out))
+    }
+
+    fn description(&self, detailed: bool) -> String {
+        match (self.op, detailed) {
+            (Approximation::CountDistinct, false) => "~COUNT(DISTINCT)".to_owned(),
+            (Approximation::Percentile { .. }, false) => "~PCT".to_owned(),
+            (Approximation::CountDistinct, true) => {
+                format!("~COUNT(DISTINCT {}) γ{:?}", self.source_col, self.group_by)
+            }
+            (Approximation::Percentile { percentile }, true) => format!(
+                "~PCT({}, {}) γ{:?}",
+                self.source_col, percentile, self.group_by
+            ),
+        }
+    }
+
+    fn over_column(&self) -> usize {
+        self.source_col
+    }
+
+    fn output_col_type(&self) -> DfType {
+        match self.op {
+            Approximation::CountDistinct => DfType::BigInt,
+            Approximation::Percentile { .. } => DfType::Double,
+        }
+    }
+
+    fn can_lose_state(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+/// Auxiliary State for a single ApproxAggregator Node, which is owned by a Domain.
+///
+/// The sketches are serializable, so that they can be persisted or moved along with the node.
+pub struct ApproxState {
+    groups: HashMap<Vec<DfValue>, GroupSketch>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ops, LookupIndex};
+
+    fn setup(op: Approximation, mat: bool) -> ops::test::MockGraph {
+        let mut g = ops::test::MockGraph::new();
+        let s = g.add_base("source", &["x", "y"]);
+
+        let a = ApproxAggregator::new(s.as_global(), 1, vec![0], op).unwrap();
+
+        g.set_op("approx", &["x", "ys"], a, mat);
+        g
+    }
+
+    fn positive_value(rs: &Records, group: i32) -> Option<DfValue> {
+        rs.iter().find_map(|r| match r {
+            Record::Positive(r) if r[0] == group.into() => Some(r[1].clone()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn it_describes() {
+        let c = setup(Approximation::CountDistinct, true);
+        assert_eq!(c.node().description(true), "~COUNT(DISTINCT 1) γ[0]");
+        let c = setup(Approximation::Percentile { percentile: 0.9 }, true);
+        assert_eq!(c.node().description(true), "~PCT(1, 0.9) γ[0]");
+    }
+
+    #[test]
+    fn it_forwards_count_distinct() {
+        let mut c = setup(Approximation::CountDistinct, true);
+
+        let rs = c.narrow_one_row(vec![1.into(), 3.into()], true);
+        assert_eq!(positive_value(&rs, 1), Some(1i64.into()));
+
+        let rs = c.narrow_one(
+            vec![
+                (vec![1.into(), 3.into()], true),
+                (vec![1.into(), 4.into()], true),
+                (vec![1.into(), 5.into()], true),
+                // nulls are ignored
+                (vec![1.into(), DfValue::None], true),
+                // new group
+                (vec![2.into(), 7.into()], true),
+            ],
+            true,
+        );
+        assert_eq!(positive_value(&rs, 1), Some(3i64.into()));
+        assert_eq!(positive_value(&rs, 2), Some(1i64.into()));
+
+        // removing a value rebuilds the sketch from the parent
+        let base = c.narrow_base_id();
+        c.seed(base, vec![1.into(), 3.into()]);
+        c.seed(base, vec![1.into(), 5.into()]);
+        let rs = c.narrow_one_row((vec![1.into(), 3.into()], false), true);
+        assert_eq!(positive_value(&rs, 1), Some(2i64.into()));
+    }
+
+    #[test]
+    fn it_forwards_percentile() {
+        let mut c = setup(Approximation::Percentile { percentile: 0.5 }, true);
+
+        let rs = c.narrow_one(
+            (1..=10)
+                .map(|v| (vec![DfValue::from(1), DfValue::from(v)], true))
+                .collect::<Vec<_>>(),
+            true,
+        );
+        // small groups are computed exactly
+        assert_eq!(positive_value(&rs, 1), Some(DfValue::Double(5.5)));
+
+        let rs = c.narrow_one_row(vec![1.into(), 11.into()], true);
+        assert_eq!(positive_value(&rs, 1), Some(DfValue::Double(6.0)));
+    }
+
+    #[test]
+    fn it_suggests_indices() {
+        let me = 1.into();
+        let c = setup(Approximation::CountDistinct, false);
+        let idx = c.node().suggest_indexes(me);
+
+        // should add an index on our own columns, and on the parent to rebuild lost state
+        assert_eq!(idx.len(), 2);
+
+        // should only index on the group-by column
+        assert_eq!(idx[&me], LookupIndex::Strict(Index::hash_map(vec![0])));
+    }
+
+    #[test]
+    fn it_resolves() {
+        let c = setup(Approximation::CountDistinct, false);
+        assert_eq!(
+            c.node().resolve(0),
+            Some(vec![(c.narrow_base_id().as_global(), 0)])
+        );
+        assert_eq!(c.node().resolve(1), None);
+    }
+}
@@ -14,8 +14,11 @@ use crate::processing::{ColumnSource, IngredientLookupResult, LookupIndex, Looku
 
 // pub mod latest;
 pub mod aggregate;
+pub mod approx;
 pub mod concat;
 pub mod extremum;
+pub mod percentile;
+pub mod sketch;
 
 /// Trait for implementing operations that collapse a group of records into a single record.
 ///
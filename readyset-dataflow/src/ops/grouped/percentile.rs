@@ -0,0 +1,362 @@
+//! Incrementally maintained `PERCENTILE_CONT()` (and `MEDIAN()`) implementation
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+
+use common::DfValue;
+use readyset_data::DfType;
+use readyset_errors::{invariant, invariant_eq};
+use readyset_util::Indices;
+use serde::{Deserialize, Serialize};
+
+use crate::node::{AuxiliaryNodeState, Node};
+use crate::ops::grouped::{GroupedOperation, GroupedOperator};
+use crate::prelude::*;
+
+/// All the (non-null) values in a single group, along with the value we last emitted for it.
+#[derive(Clone, Debug, Default)]
+struct GroupValues {
+    /// The number of times each value occurs in the group, ordered by value.
+    counts: BTreeMap<DfValue, usize>,
+    /// The total number of values in the group.
+    len: usize,
+    /// The percentile we last emitted for this group.
+    last_output: DfValue,
+}
+
+impl GroupValues {
+    fn insert(&mut self, value: DfValue) {
+        *self.counts.entry(value).or_default() += 1;
+        self.len += 1;
+    }
+
+    fn remove(&mut self, value: &DfValue) -> ReadySetResult<()> {
+        let count = self
+            .counts
+            .get_mut(value)
+            .ok_or_else(|| internal_err!("percentile couldn't remove value from group"))?;
+        *count -= 1;
+        if *count == 0 {
+            self.counts.remove(value);
+        }
+        self.len -= 1;
+        Ok(())
+    }
+
+    /// Returns the value at the given zero-based position in the sorted values of the group.
+    fn nth(&self, n: usize) -> ReadySetResult<f64> {
+        let mut seen = 0;
+        for (value, count) in &self.counts {
+            seen += count;
+            if seen > n {
+                return f64::try_from(value);
+            }
+        }
+        internal!(
+            "percentile position {} out of bounds for group of {}",
+            n,
+            self.len
+        )
+    }
+
+    /// Compute the given percentile of the values in the group, linearly interpolating between
+    /// the two closest values if the percentile falls between them, or NULL if the group has no
+    /// values.
+    fn percentile(&self, percentile: f64) -> ReadySetResult<DfValue> {
+        if self.len == 0 {
+            return Ok(DfValue::None);
+        }
+
+        let position = percentile * (self.len - 1) as f64;
+        let lower_pos = position.floor() as usize;
+        let lower = self.nth(lower_pos)?;
+        let fraction = position - lower_pos as f64;
+        let res = if fraction > 0.0 {
+            let upper = self.nth(lower_pos + 1)?;
+            lower + fraction * (upper - lower)
+        } else {
+            lower
+        };
+        DfValue::try_from(res)
+    }
+}
+
+/// `Percentile` implements the `PERCENTILE_CONT` SQL aggregate function (of which `MEDIAN` is the
+/// special case of the 0.5th percentile), computing the given percentile of the values of a column
+/// within each group.
+///
+/// Since values can be removed from a group as well as added to it, all the values of each group
+/// are kept in the node's auxiliary state rather than in an approximate sketch, which would not be
+/// able to support removals. If that state is lost (or doesn't match the value we have
+/// materialized for a group), it is rebuilt from the parent.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Percentile {
+    /// Which column to aggregate.
+    source_col: usize,
+    /// The columns to group by.
+    group_by: Vec<usize>,
+    /// The percentile to compute, as a fraction between 0 and 1.
+    percentile: f64,
+}
+
+impl Percentile {
+    /// Construct a new `Percentile`, computing the `percentile` (given as a fraction between 0 and
+    /// 1) of the values in the provided `source_col`.
+    pub fn new(
+        src: NodeIndex,
+        source_col: usize,
+        group_by: Vec<usize>,
+        percentile: f64,
+    ) -> ReadySetResult<GroupedOperator<Percentile>> {
+        invariant!(
+            (0.0..=1.0).contains(&percentile),
+            "percentile must be between 0 and 1"
+        );
+        Ok(GroupedOperator::new(
+            src,
+            Percentile {
+                source_col,
+                group_by,
+                percentile,
+            },
+        ))
+    }
+}
+
+pub struct PercentileDiff {
+    value: DfValue,
+    is_positive: bool,
+    group_by: Vec<DfValue>,
+}
+
+impl GroupedOperation for Percentile {
+    type Diff = PercentileDiff;
+
+    fn setup(&mut self, parent: &Node) -> ReadySetResult<()> {
+        invariant!(
+            self.source_col < parent.columns().len(),
+            "cannot aggregate over non-existing column"
+        );
+        Ok(())
+    }
+
+    fn group_by(&self) -> &[usize] {
+        &self.group_by
+    }
+
+    fn to_diff(&self, record: &[DfValue], is_positive: bool) -> ReadySetResult<Self::Diff> {
+        let value = match record
+            .get(self.source_col)
+            .ok_or(ReadySetError::InvalidRecordLength)?
+        {
+            DfValue::None => DfValue::None,
+            v => DfValue::try_from(f64::try_from(v)?)?,
+        };
+        // We need this to figure out which state to use.
+        let group_by = record
+            .cloned_indices(self.group_by.iter().cloned())
+            .map_err(|_| ReadySetError::InvalidRecordLength)?;
+        Ok(PercentileDiff {
+            value,
+            is_positive,
+            group_by,
+        })
+    }
+
+    fn apply(
+        &self,
+        current: Option<&DfValue>,
+        diffs: &mut dyn Iterator<Item = Self::Diff>,
+        auxiliary_node_state: Option<&mut AuxiliaryNodeState>,
+    ) -> ReadySetResult<Option<DfValue>> {
+        let mut diffs = diffs.peekable();
+
+        let first_diff = diffs
+            .peek()
+            .ok_or_else(|| internal_err!("percentile got no diffs"))?;
+        let group = first_diff.group_by.clone();
+
+        let groups = match auxiliary_node_state {
+            Some(AuxiliaryNodeState::Percentile(ref mut ps)) => &mut ps.groups,
+            Some(_) => internal!("Incorrect auxiliary state for Percentile node"),
+            None => internal!("Missing auxiliary state for Percentile node"),
+        };
+
+        let mut values = match (current, groups.remove(&group)) {
+            // if state matches what we last emitted, use it
+            (Some(current), Some(values)) if *current == values.last_output => values,
+            // if state doesn't match, need to recreate it
+            (Some(_), _) => return Ok(None),
+            // if we're recreating or this is the first record for the group, start afresh
+            (None, _) => GroupValues::default(),
+        };
+
+        for PercentileDiff {
+            value,
+            is_positive,
+            group_by,
+        } in diffs
+        {
+            invariant_eq!(group_by, group);
+            if value.is_none() {
+                continue;
+            }
+            if is_positive {
+                values.insert(value);
+            } else {
+                values.remove(&value)?;
+            }
+        }
+
+        let out = values.percentile(self.percentile)?;
+        values.last_output = out.clone();
+        groups.insert(group, values);
+        Ok(Some(out))
+    }
+
+    fn description(&self, detailed: bool) -> String {
+        if !detailed {
+            return "PCT".to_owned();
+        }
+
+        format!(
+            "PCT({}, {}) γ{:?}",
+            self.source_col, self.percentile, self.group_by
+        )
+    }
+
+    fn over_column(&self) -> usize {
+        self.source_col
+    }
+
+    fn output_col_type(&self) -> DfType {
+        DfType::Double
+    }
+
+    fn can_lose_state(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Default)]
+/// Auxiliary State for a single Percentile Node, which is owned by a Domain.
+pub struct PercentileState {
+    groups: HashMap<Vec<DfValue>, GroupValues>,
+}
+
+#[cfg(test)]
+#[allow(clippy::unreachable)]
+mod tests {
+    use super::*;
+    use crate::{ops, LookupIndex};
+
+    fn setup(percentile: f64, mat: bool) -> ops::test::MockGraph {
+        let mut g = ops::test::MockGraph::new();
+        let s = g.add_base("source", &["x", "y"]);
+
+        let p = Percentile::new(s.as_global(), 1, vec![0], percentile).unwrap();
+
+        g.set_op("percentile", &["x", "ys"], p, mat);
+        g
+    }
+
+    fn positive_value(rs: &Records, group: i32) -> Option<DfValue> {
+        rs.iter().find_map(|r| match r {
+            Record::Positive(r) if r[0] == group.into() => Some(r[1].clone()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn it_describes() {
+        let c = setup(0.5, true);
+        assert_eq!(c.node().description(true), "PCT(1, 0.5) γ[0]");
+    }
+
+    #[test]
+    fn it_forwards_median() {
+        let mut c = setup(0.5, true);
+
+        // first row for a group should emit that row's value
+        let rs = c.narrow_one_row(vec![1.into(), 3.into()], true);
+        assert_eq!(rs.len(), 1);
+        assert_eq!(positive_value(&rs, 1), Some(DfValue::Double(3.0)));
+
+        // an even number of values should interpolate between the middle two
+        let rs = c.narrow_one_row(vec![1.into(), 4.into()], true);
+        assert_eq!(rs.len(), 2);
+        match rs.iter().next().unwrap() {
+            Record::Negative(r) => assert_eq!(r[1], DfValue::Double(3.0)),
+            _ => unreachable!(),
+        }
+        assert_eq!(positive_value(&rs, 1), Some(DfValue::Double(3.5)));
+
+        let rs = c.narrow_one(
+            vec![
+                (vec![1.into(), 10.into()], true),
+                (vec![1.into(), 1.into()], true),
+                (vec![1.into(), 4.into()], true),
+                // nulls are ignored
+                (vec![1.into(), DfValue::None], true),
+                // new group
+                (vec![2.into(), 7.into()], true),
+            ],
+            true,
+        );
+        // group 1 is now [1, 3, 4, 4, 10]
+        assert_eq!(positive_value(&rs, 1), Some(DfValue::Double(4.0)));
+        assert_eq!(positive_value(&rs, 2), Some(DfValue::Double(7.0)));
+
+        // removing values should update the median
+        let rs = c.narrow_one(
+            vec![
+                (vec![1.into(), 4.into()], false),
+                (vec![1.into(), 4.into()], false),
+            ],
+            true,
+        );
+        // group 1 is now [1, 3, 10]
+        assert_eq!(rs.len(), 2);
+        assert_eq!(positive_value(&rs, 1), Some(DfValue::Double(3.0)));
+    }
+
+    #[test]
+    fn it_interpolates_percentiles() {
+        let mut c = setup(0.9, true);
+
+        let rs = c.narrow_one(
+            (1..=10)
+                .map(|v| (vec![DfValue::from(1), DfValue::from(v)], true))
+                .collect::<Vec<_>>(),
+            true,
+        );
+        // position 0.9 * 9 = 8.1, between 9 and 10
+        match positive_value(&rs, 1) {
+            Some(DfValue::Double(v)) => assert!((v - 9.1).abs() < 1e-9),
+            v => panic!("unexpected percentile {:?}", v),
+        }
+    }
+
+    #[test]
+    fn it_suggests_indices() {
+        let me = 1.into();
+        let c = setup(0.5, false);
+        let idx = c.node().suggest_indexes(me);
+
+        // should add an index on our own columns, and on the parent to rebuild lost state
+        assert_eq!(idx.len(), 2);
+
+        // should only index on the group-by column
+        assert_eq!(idx[&me], LookupIndex::Strict(Index::hash_map(vec![0])));
+    }
+
+    #[test]
+    fn it_resolves() {
+        let c = setup(0.5, false);
+        assert_eq!(
+            c.node().resolve(0),
+            Some(vec![(c.narrow_base_id().as_global(), 0)])
+        );
+        assert_eq!(c.node().resolve(1), None);
+    }
+}
@@ -0,0 +1,365 @@
+//! Probabilistic sketches used to maintain approximate aggregates in a bounded amount of memory
+//! per group.
+//!
+//! Neither sketch supports removing values, so operators built on them must rebuild a group's
+//! sketch from scratch when a value is removed from it.
+
+use std::collections::hash_map::DefaultHasher;
+use std::f64::consts::PI;
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+
+/// The number of bits of each hash used to pick a register of a [`HyperLogLog`]
+const HLL_PRECISION: u32 = 12;
+
+/// The number of registers in a [`HyperLogLog`]
+const HLL_REGISTERS: usize = 1 << HLL_PRECISION;
+
+/// A HyperLogLog sketch, which estimates the number of distinct values inserted into it.
+///
+/// The sketch uses 2^12 one-byte registers, giving a standard error of around 1.6% regardless of
+/// the number of distinct values.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self {
+            registers: vec![0; HLL_REGISTERS],
+        }
+    }
+}
+
+impl HyperLogLog {
+    /// Insert a value into the sketch
+    pub fn insert<T: Hash + ?Sized>(&mut self, value: &T) {
+        // `DefaultHasher::new()` always uses the same keys, so the same value hashes the same way
+        // in every process, which lets us merge serialized sketches
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let register = (hash >> (64 - HLL_PRECISION)) as usize;
+        let rank = ((hash << HLL_PRECISION)
+            .leading_zeros()
+            .min(64 - HLL_PRECISION)
+            + 1) as u8;
+        self.registers[register] = self.registers[register].max(rank);
+    }
+
+    /// Merge all the values inserted into `other` into this sketch
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        for (r, o) in self.registers.iter_mut().zip(&other.registers) {
+            *r = (*r).max(*o);
+        }
+    }
+
+    /// Estimate the number of distinct values inserted into the sketch
+    pub fn estimate(&self) -> u64 {
+        let m = HLL_REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = alpha * m * m / sum;
+
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        // Small cardinalities are estimated much more accurately by linear counting. Since we use
+        // 64-bit hashes, there's no need for a correction for large cardinalities.
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
+    }
+}
+
+/// A single cluster of values in a [`TDigest`]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// A merging t-digest, which estimates quantiles of the values inserted into it.
+///
+/// Values are clustered into centroids which are small near the tails of the distribution and
+/// larger near the median, so extreme quantiles are estimated more accurately than central ones.
+/// The number of centroids is bounded by the compression factor, independently of the number of
+/// values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TDigest {
+    compression: f64,
+    /// The compressed centroids, ordered by mean
+    centroids: Vec<Centroid>,
+    /// Values inserted since the centroids were last compressed
+    buffer: Vec<f64>,
+    count: u64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_COMPRESSION)
+    }
+}
+
+impl TDigest {
+    /// The compression factor used by [`TDigest::default`]
+    pub const DEFAULT_COMPRESSION: f64 = 100.0;
+
+    /// Create a new, empty t-digest with the given compression factor. Higher compression factors
+    /// give more accurate estimates at the cost of more memory.
+    pub fn new(compression: f64) -> Self {
+        Self {
+            compression,
+            centroids: vec![],
+            buffer: vec![],
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Returns the number of values inserted into the digest
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Insert a value into the digest
+    pub fn insert(&mut self, value: f64) {
+        self.buffer.push(value);
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        if self.buffer.len() >= 5 * self.compression as usize {
+            self.compress();
+        }
+    }
+
+    /// The scale function, mapping a quantile to the index of the centroid that should hold it.
+    /// Each centroid can span at most one unit of `k`.
+    fn k(&self, q: f64) -> f64 {
+        self.compression / (2.0 * PI) * (2.0 * q - 1.0).clamp(-1.0, 1.0).asin()
+    }
+
+    /// Merge all buffered values into the centroids
+    pub fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+
+        let mut all = std::mem::take(&mut self.centroids);
+        all.extend(
+            self.buffer
+                .drain(..)
+                .map(|mean| Centroid { mean, weight: 1.0 }),
+        );
+        all.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+
+        let total = self.count as f64;
+        let mut all = all.into_iter();
+        let mut current = match all.next() {
+            Some(c) => c,
+            None => return,
+        };
+        let mut weight_so_far = 0.0;
+        let mut k_lower = self.k(0.0);
+        for c in all {
+            let q_upper = (weight_so_far + current.weight + c.weight) / total;
+            if self.k(q_upper) - k_lower <= 1.0 {
+                let weight = current.weight + c.weight;
+                current.mean += (c.mean - current.mean) * c.weight / weight;
+                current.weight = weight;
+            } else {
+                weight_so_far += current.weight;
+                self.centroids.push(current);
+                k_lower = self.k(weight_so_far / total);
+                current = c;
+            }
+        }
+        self.centroids.push(current);
+    }
+
+    /// Estimate the given quantile (as a fraction between 0 and 1) of the values inserted into the
+    /// digest, or `None` if the digest is empty.
+    ///
+    /// As long as no two values have been merged into the same centroid, this interpolates between
+    /// adjacent values in the same way as `PERCENTILE_CONT`.
+    pub fn quantile(&mut self, q: f64) -> Option<f64> {
+        self.compress();
+        let first = self.centroids.first()?;
+        // Values are clustered by their means, so the first and last centroids don't necessarily
+        // hold the smallest and largest values
+        if q <= 0.0 {
+            return Some(self.min);
+        }
+        if q >= 1.0 {
+            return Some(self.max);
+        }
+
+        // The position of the quantile, measured such that the center of each centroid falls at
+        // its cumulative weight minus half its own weight, and the smallest and largest values
+        // fall half a value in from either end
+        let index = q * (self.count - 1) as f64 + 0.5;
+
+        if index < first.weight / 2.0 {
+            // Since `index` is at least 0.5, the first centroid must hold more than one value
+            let fraction = (index - 0.5) / (first.weight / 2.0 - 0.5);
+            return Some(self.min + (first.mean - self.min) * fraction);
+        }
+
+        let mut weight_so_far = first.weight / 2.0;
+        for (left, right) in self.centroids.iter().zip(self.centroids.iter().skip(1)) {
+            let delta = (left.weight + right.weight) / 2.0;
+            if weight_so_far + delta > index {
+                let fraction = (index - weight_so_far) / delta;
+                return Some(left.mean + (right.mean - left.mean) * fraction);
+            }
+            weight_so_far += delta;
+        }
+
+        #[allow(clippy::unwrap_used)] // We know we have at least one centroid
+        let last = self.centroids.last().unwrap();
+        if last.weight <= 1.0 {
+            return Some(last.mean);
+        }
+        let fraction = ((index - weight_so_far) / (last.weight / 2.0 - 0.5)).min(1.0);
+        Some(last.mean + (self.max - last.mean) * fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod hyperloglog {
+        use super::*;
+
+        #[test]
+        fn empty() {
+            assert_eq!(HyperLogLog::default().estimate(), 0);
+        }
+
+        #[test]
+        fn small_counts_are_exact() {
+            let mut hll = HyperLogLog::default();
+            for i in 0..10 {
+                hll.insert(&i);
+                hll.insert(&i);
+            }
+            assert_eq!(hll.estimate(), 10);
+        }
+
+        #[test]
+        fn large_counts_are_close() {
+            let mut hll = HyperLogLog::default();
+            for i in 0..100_000u64 {
+                hll.insert(&i);
+            }
+            let estimate = hll.estimate() as f64;
+            assert!(
+                (estimate - 100_000.0).abs() / 100_000.0 < 0.05,
+                "estimate {} too far from 100000",
+                estimate
+            );
+        }
+
+        #[test]
+        fn merge() {
+            let mut a = HyperLogLog::default();
+            let mut b = HyperLogLog::default();
+            for i in 0..1000u64 {
+                a.insert(&i);
+                b.insert(&(i + 500));
+            }
+            a.merge(&b);
+
+            let mut both = HyperLogLog::default();
+            for i in 0..1500u64 {
+                both.insert(&i);
+            }
+            assert_eq!(a, both);
+        }
+
+        #[test]
+        fn serialize_round_trip() {
+            let mut hll = HyperLogLog::default();
+            for i in 0..1000u64 {
+                hll.insert(&i);
+            }
+            let rt: HyperLogLog = bincode::deserialize(&bincode::serialize(&hll).unwrap()).unwrap();
+            assert_eq!(rt, hll);
+            assert_eq!(rt.estimate(), hll.estimate());
+        }
+    }
+
+    mod tdigest {
+        use super::*;
+
+        #[test]
+        fn empty() {
+            assert_eq!(TDigest::default().quantile(0.5), None);
+        }
+
+        #[test]
+        fn single_value() {
+            let mut digest = TDigest::default();
+            digest.insert(7.0);
+            assert_eq!(digest.quantile(0.0), Some(7.0));
+            assert_eq!(digest.quantile(0.5), Some(7.0));
+            assert_eq!(digest.quantile(1.0), Some(7.0));
+        }
+
+        #[test]
+        fn small_inputs_match_percentile_cont() {
+            let mut digest = TDigest::default();
+            for v in 1..=10 {
+                digest.insert(v as f64);
+            }
+            assert_eq!(digest.quantile(0.0), Some(1.0));
+            assert_eq!(digest.quantile(0.5), Some(5.5));
+            assert!((digest.quantile(0.9).unwrap() - 9.1).abs() < 1e-9);
+            assert_eq!(digest.quantile(1.0), Some(10.0));
+        }
+
+        #[test]
+        fn large_inputs_are_close() {
+            let mut digest = TDigest::default();
+            // Insert out of order, to exercise merging buffers into existing centroids
+            for i in 0..100_000u64 {
+                digest.insert(((i * 7919) % 100_000) as f64);
+            }
+            assert_eq!(digest.count(), 100_000);
+            for q in [0.01, 0.25, 0.5, 0.75, 0.99] {
+                let estimate = digest.quantile(q).unwrap();
+                let expected = q * 99_999.0;
+                assert!(
+                    (estimate - expected).abs() < 500.0,
+                    "estimate {} of quantile {} too far from {}",
+                    estimate,
+                    q,
+                    expected
+                );
+            }
+            assert_eq!(digest.quantile(0.0), Some(0.0));
+            assert_eq!(digest.quantile(1.0), Some(99_999.0));
+            assert!(digest.centroids.len() < 2 * TDigest::DEFAULT_COMPRESSION as usize);
+        }
+
+        #[test]
+        fn serialize_round_trip() {
+            let mut digest = TDigest::default();
+            for v in 0..1000 {
+                digest.insert(v as f64);
+            }
+            let mut rt: TDigest =
+                bincode::deserialize(&bincode::serialize(&digest).unwrap()).unwrap();
+            assert_eq!(rt, digest);
+            assert_eq!(rt.quantile(0.5), digest.quantile(0.5));
+        }
+    }
+}
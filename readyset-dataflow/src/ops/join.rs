@@ -23,6 +23,8 @@ pub enum JoinType {
     Left,
     /// Inner join between two views
     Inner,
+    /// Full outer join between two views
+    Full,
 }
 
 /// Join rows between two nodes based on a (compound) equal join key
@@ -173,6 +175,29 @@ impl Join {
             .collect()
     }
 
+    /// Like [`generate_null`](Self::generate_null), but for a row from the right parent with no
+    /// matching rows in the left parent
+    fn generate_null_left(&self, right: &[DfValue]) -> Vec<DfValue> {
+        self.emit
+            .iter()
+            .map(|&(side, col)| {
+                if side == Side::Right {
+                    right[col].clone()
+                } else {
+                    DfValue::None
+                }
+            })
+            .collect()
+    }
+
+    /// Generate a null-padded row for a row from the parent on the given `side`
+    fn generate_null_for(&self, side: Side, row: &[DfValue]) -> Vec<DfValue> {
+        match side {
+            Side::Left => self.generate_null(row),
+            Side::Right => self.generate_null_left(row),
+        }
+    }
+
     fn resolve_col(&self, col: usize) -> (Option<usize>, Option<usize>) {
         let (side, pcol) = self.emit[col];
 
@@ -214,7 +239,12 @@ impl Ingredient for Join {
     }
 
     fn must_replay_among(&self) -> Option<HashSet<NodeIndex>> {
-        Some(Some(self.left.as_global()).into_iter().collect())
+        if self.kind == JoinType::Full {
+            // Rows from the right with no match in the left only show up in replays from the right
+            None
+        } else {
+            Some(Some(self.left.as_global()).into_iter().collect())
+        }
     }
 
     fn on_connected(&mut self, _g: &Graph) {}
@@ -240,6 +270,7 @@ impl Ingredient for Join {
         let mut lookups = Vec::new();
 
         let from_left = from == *self.left;
+        let from_side = if from_left { Side::Left } else { Side::Right };
 
         let other = if from_left { *self.right } else { *self.left };
 
@@ -339,6 +370,11 @@ impl Ingredient for Join {
             .group_by(|rec| from_key.iter().map(|i| rec[*i].clone()).collect::<Vec<_>>());
 
         let is_replay = replay_key_cols.is_some();
+        let is_full_replay = matches!(replay, ReplayContext::Full { .. });
+
+        // Full joins are fully replayed through both parents. The left replay emits all the joined
+        // rows, so the right replay only needs to emit the rows with no match in the left.
+        let emit_matches = !(self.kind == JoinType::Full && is_full_replay && !from_left);
 
         // Only do a lookup into a weak index if we're processing regular updates,
         // not if we're processing a replay, since regular updates should represent
@@ -362,14 +398,17 @@ impl Ingredient for Join {
             let nulls = join_key.iter().any(|v| v.is_none());
 
             // The difference between a left join and an inner join, is that for the former we must
-            // emit rows with nulls even if we later get no match in the other side.
+            // emit rows with nulls even if we later get no match in the other side. A full join
+            // does the same for both sides.
 
-            let mut new_right_count = None;
+            let mut new_from_count = None;
 
-            if self.kind == JoinType::Left && !from_left {
+            if !is_full_replay
+                && (self.kind == JoinType::Full || (self.kind == JoinType::Left && !from_left))
+            {
                 let rc = self.lookup(
-                    *self.right,
-                    &self.on_right(),
+                    from,
+                    &from_key,
                     &PointKey::from(join_key.iter().cloned()),
                     nodes,
                     state,
@@ -380,8 +419,8 @@ impl Ingredient for Join {
                     IngredientLookupResult::Records(rc) => {
                         if replay_key_cols.is_some() && !nulls {
                             lookups.push(Lookup {
-                                on: *self.right,
-                                cols: self.on_right(),
+                                on: from,
+                                cols: from_key.clone(),
                                 key: join_key
                                     .clone()
                                     .try_into()
@@ -390,7 +429,7 @@ impl Ingredient for Join {
                         }
 
                         let rc = rc.count();
-                        new_right_count = Some(rc);
+                        new_from_count = Some(rc);
                     }
                     IngredientLookupResult::Miss => {
                         // we got something from right, but that row's key is not in right??
@@ -455,11 +494,12 @@ impl Ingredient for Join {
                 rc_diff += if positive { 1 } else { -1 };
 
                 if other_rows.is_empty() {
-                    if self.kind == JoinType::Left && from_left {
-                        // left join, got a thing from left, no rows in right == NULL
-                        ret.push((self.generate_null(&row), positive).into());
+                    if (self.kind == JoinType::Left && from_left) || self.kind == JoinType::Full {
+                        // left join, got a thing from left, no rows in right == NULL (and the
+                        // same from either side for full joins)
+                        ret.push((self.generate_null_for(from_side, &row), positive).into());
                     }
-                } else {
+                } else if emit_matches {
                     for other in other_rows.iter() {
                         if from == *self.left {
                             ret.push((self.generate_row(&row, other), positive).into());
@@ -470,17 +510,21 @@ impl Ingredient for Join {
                 }
             }
 
-            // For a left join with updates from the right side, we also have to emit/delete NULL
-            // rows if row count changed to/from zero
-            if let Some(new_rc) = new_right_count {
+            // For a left join with updates from the right side (or a full join with updates from
+            // either side), we also have to emit/delete NULL rows if row count changed to/from zero
+            if let Some(new_rc) = new_from_count {
                 let old_rc = new_rc as isize - rc_diff;
                 if new_rc == 0 && old_rc != 0 {
                     for other in other_rows.iter() {
-                        ret.push((self.generate_null(other), true).into());
+                        ret.push(
+                            (self.generate_null_for(from_side.other_side(), other), true).into(),
+                        );
                     }
                 } else if new_rc != 0 && old_rc == 0 {
                     for other in other_rows.iter() {
-                        ret.push((self.generate_null(other), false).into());
+                        ret.push(
+                            (self.generate_null_for(from_side.other_side(), other), false).into(),
+                        );
                     }
                 }
             }
@@ -515,6 +559,7 @@ impl Ingredient for Join {
             return String::from(match self.kind {
                 JoinType::Left => "⋉",
                 JoinType::Inner => "⋈",
+                JoinType::Full => "⟗",
             });
         }
 
@@ -534,6 +579,7 @@ impl Ingredient for Join {
         let op = match self.kind {
             JoinType::Left => "⋉",
             JoinType::Inner => "⋈",
+            JoinType::Full => "⟗",
         };
 
         format!(
@@ -668,6 +714,15 @@ impl Ingredient for Join {
     }
 
     fn column_source(&self, cols: &[usize]) -> ColumnSource {
+        // Rows with no match in the left parent can't be found by upquerying the left parent for a
+        // key (and vice versa), so full joins can only be fully materialized
+        if self.kind == JoinType::Full {
+            return ColumnSource::RequiresFullReplay(vec1![
+                self.left.as_global(),
+                self.right.as_global()
+            ]);
+        }
+
         // NOTE: This function relies pretty heavily on the fact that upqueries for NULLs are not
         // possible. If they were possible, you could return incorrect results, because
         //   SELECT * FROM a LEFT JOIN b where b.col IS NULL;
@@ -880,6 +935,96 @@ mod tests {
         assert_eq!(rs.len(), 0);
     }
 
+    #[test]
+    fn full_join() {
+        let mut j = ops::test::MockGraph::new();
+        let l = j.add_base("left", &["l0", "l1"]);
+        let r = j.add_base("right", &["r0", "r1"]);
+        j.set_op(
+            "join",
+            &["j0", "j1", "j2", "j3"],
+            Join::new(
+                l.as_global(),
+                r.as_global(),
+                JoinType::Full,
+                vec![(0, 0)],
+                vec![
+                    (Side::Left, 0),
+                    (Side::Left, 1),
+                    (Side::Right, 0),
+                    (Side::Right, 1),
+                ],
+            ),
+            false,
+        );
+
+        let l_b2 = vec![2.into(), "b".try_into().unwrap()];
+        let l_c3 = vec![3.into(), "c".try_into().unwrap()];
+        let r_z2 = vec![2.into(), "z".try_into().unwrap()];
+
+        // unmatched forward from right should produce [None + z2]
+        j.seed(r, r_z2.clone());
+        let rs = j.one_row(r, r_z2, false);
+        assert_eq!(
+            rs,
+            vec![(
+                vec![
+                    DfValue::None,
+                    DfValue::None,
+                    2.into(),
+                    "z".try_into().unwrap()
+                ],
+                true
+            )]
+            .into()
+        );
+
+        // a match from the left should revoke the nulls and replace them with a full row
+        j.seed(l, l_b2.clone());
+        let rs = j.one_row(l, l_b2, false);
+        assert_eq!(
+            rs,
+            vec![
+                (
+                    vec![
+                        2.into(),
+                        "b".try_into().unwrap(),
+                        2.into(),
+                        "z".try_into().unwrap()
+                    ],
+                    true
+                ),
+                (
+                    vec![
+                        DfValue::None,
+                        DfValue::None,
+                        2.into(),
+                        "z".try_into().unwrap()
+                    ],
+                    false
+                ),
+            ]
+            .into()
+        );
+
+        // unmatched forward from left should produce [c3 + None]
+        j.seed(l, l_c3.clone());
+        let rs = j.one_row(l, l_c3, false);
+        assert_eq!(
+            rs,
+            vec![(
+                vec![
+                    3.into(),
+                    "c".try_into().unwrap(),
+                    DfValue::None,
+                    DfValue::None
+                ],
+                true
+            )]
+            .into()
+        );
+    }
+
     #[test]
     fn nulls_from_left() {
         let (mut j, l, r) = setup();
@@ -17,8 +17,11 @@ pub mod project;
 pub mod topk;
 pub mod union;
 pub(crate) mod utils;
+pub mod window;
 
+use crate::ops::grouped::approx::ApproxAggregator;
 use crate::ops::grouped::concat::GroupConcat;
+use crate::ops::grouped::percentile::Percentile;
 use crate::processing::{
     ColumnMiss, ColumnSource, IngredientLookupResult, LookupIndex, LookupMode,
 };
@@ -47,6 +50,7 @@ pub enum NodeOperator {
     Aggregation(grouped::GroupedOperator<grouped::aggregate::Aggregator>),
     Extremum(grouped::GroupedOperator<grouped::extremum::ExtremumOperator>),
     Concat(grouped::GroupedOperator<GroupConcat>),
+    Percentile(grouped::GroupedOperator<Percentile>),
     Join(join::Join),
     Paginate(paginate::Paginate),
     Project(project::Project),
@@ -54,6 +58,8 @@ pub enum NodeOperator {
     Identity(identity::Identity),
     Filter(filter::Filter),
     TopK(topk::TopK),
+    Window(window::Window),
+    Approx(grouped::GroupedOperator<ApproxAggregator>),
 }
 
 impl ToString for NodeOperator {
@@ -62,6 +68,7 @@ impl ToString for NodeOperator {
             NodeOperator::Aggregation(_) => "Aggregation",
             NodeOperator::Extremum(_) => "Extremum",
             NodeOperator::Concat(_) => "Concat",
+            NodeOperator::Percentile(_) => "Percentile",
             NodeOperator::Join(_) => "Join",
             NodeOperator::Paginate(_) => "Paginate",
             NodeOperator::Project(_) => "Project",
@@ -69,6 +76,8 @@ impl ToString for NodeOperator {
             NodeOperator::Identity(_) => "Identity",
             NodeOperator::Filter(_) => "Filter",
             NodeOperator::TopK(_) => "TopK",
+            NodeOperator::Window(_) => "Window",
+            NodeOperator::Approx(_) => "Approx",
         }
         .to_string()
     }
@@ -80,6 +89,7 @@ macro_rules! impl_ingredient_fn_mut {
             NodeOperator::Aggregation(ref mut i) => i.$fn($($arg),*),
             NodeOperator::Extremum(ref mut i) => i.$fn($($arg),*),
             NodeOperator::Concat(ref mut i) => i.$fn($($arg),*),
+            NodeOperator::Percentile(ref mut i) => i.$fn($($arg),*),
             NodeOperator::Join(ref mut i) => i.$fn($($arg),*),
             NodeOperator::Paginate(ref mut i) => i.$fn($($arg),*),
             NodeOperator::Project(ref mut i) => i.$fn($($arg),*),
@@ -87,6 +97,8 @@ macro_rules! impl_ingredient_fn_mut {
             NodeOperator::Identity(ref mut i) => i.$fn($($arg),*),
             NodeOperator::Filter(ref mut i) => i.$fn($($arg),*),
             NodeOperator::TopK(ref mut i) => i.$fn($($arg),*),
+            NodeOperator::Window(ref mut i) => i.$fn($($arg),*),
+            NodeOperator::Approx(ref mut i) => i.$fn($($arg),*),
         }
     }
 }
@@ -97,6 +109,7 @@ macro_rules! impl_ingredient_fn_ref {
             NodeOperator::Aggregation(ref i) => i.$fn($($arg),*),
             NodeOperator::Extremum(ref i) => i.$fn($($arg),*),
             NodeOperator::Concat(ref i) => i.$fn($($arg),*),
+            NodeOperator::Percentile(ref i) => i.$fn($($arg),*),
             NodeOperator::Join(ref i) => i.$fn($($arg),*),
             NodeOperator::Paginate(ref i) => i.$fn($($arg),*),
             NodeOperator::Project(ref i) => i.$fn($($arg),*),
@@ -104,6 +117,8 @@ macro_rules! impl_ingredient_fn_ref {
             NodeOperator::Identity(ref i) => i.$fn($($arg),*),
             NodeOperator::Filter(ref i) => i.$fn($($arg),*),
             NodeOperator::TopK(ref i) => i.$fn($($arg),*),
+            NodeOperator::Window(ref i) => i.$fn($($arg),*),
+            NodeOperator::Approx(ref i) => i.$fn($($arg),*),
         }
     }
 }
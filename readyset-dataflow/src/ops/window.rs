@@ -0,0 +1,593 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::{fmt, mem};
+
+use dataflow_state::PointKey;
+use itertools::Itertools;
+use nom_sql::OrderType;
+use readyset_util::Indices;
+use serde::{Deserialize, Serialize};
+
+use crate::ops::utils::Order;
+use crate::prelude::*;
+use crate::processing::{ColumnMiss, LookupIndex};
+
+/// The function computed by a [`Window`] operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowFunction {
+    /// `ROW_NUMBER()`: the 1-based position of the row within its partition
+    RowNumber,
+    /// `RANK()`: the 1-based rank of the row within its partition, with gaps after ties
+    Rank,
+    /// `DENSE_RANK()`: the 1-based rank of the row within its partition, without gaps after ties
+    DenseRank,
+    /// `COUNT(*)`: the number of rows in the row's window frame, which is the whole partition if
+    /// the window has no `ORDER BY`, and otherwise all the rows up to and including the last of
+    /// the row's peers
+    CountStar,
+}
+
+impl fmt::Display for WindowFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowFunction::RowNumber => write!(f, "row_number"),
+            WindowFunction::Rank => write!(f, "rank"),
+            WindowFunction::DenseRank => write!(f, "dense_rank"),
+            WindowFunction::CountStar => write!(f, "count"),
+        }
+    }
+}
+
+/// Window provides an operator that computes a [`WindowFunction`] for every row within its
+/// partition (the `PARTITION BY` columns of the window), according to the `ORDER BY` of the window.
+///
+/// The result of the window function is emitted as an extra column at the end of each row. Like
+/// [`Paginate`](crate::ops::paginate::Paginate), this operator keeps its own output materialized,
+/// keyed by the partition columns, so that it can incrementally maintain the rankings for each
+/// partition. When a row is added to or removed from a partition, only the rows whose ranking has
+/// changed are re-emitted.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Window {
+    /// The direct Ingredient or Base ancestor of this node
+    src: IndexPair,
+    /// The index of this node. Used to look up into our own state
+    our_index: Option<IndexPair>,
+    /// The column index of the column containing the result of the window function
+    ///
+    /// This is always equal to the number of columns in the parent node (there is one more output
+    /// column than input columns)
+    ///
+    /// Set during [`Ingredient::on_connected`]
+    result_col: Option<usize>,
+    /// The list of column indices that we're partitioning by
+    partition_by: Vec<usize>,
+    /// The ordering of the records within each partition
+    order: Order,
+    /// The window function to compute
+    function: WindowFunction,
+}
+
+impl Window {
+    /// Construct a new Window operator.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - this operator's ancestor
+    /// * `function` - the ranking function to compute for each row
+    /// * `partition_by` - the columns that this operator is keyed on
+    /// * `order` - the ordering of the rows within each partition
+    pub fn new(
+        src: NodeIndex,
+        function: WindowFunction,
+        partition_by: Vec<usize>,
+        order: Vec<(usize, OrderType)>,
+    ) -> Self {
+        Window {
+            src: src.into(),
+            our_index: None,
+            result_col: None,
+            partition_by,
+            order: order.into(),
+            function,
+        }
+    }
+
+    /// Project the columns we are partitioning by out of the given record
+    fn project_partition<'rec, R>(&self, rec: &'rec R) -> ReadySetResult<Vec<&'rec DfValue>>
+    where
+        R: Indices<'static, usize, Output = DfValue> + ?Sized,
+    {
+        rec.indices(self.partition_by.clone())
+            .map_err(|_| ReadySetError::InvalidRecordLength)
+    }
+
+    /// Return the column index of the result column output by this node (which will always be the
+    /// last column)
+    ///
+    /// # Panics
+    ///
+    /// Panics if called before [`Ingredient::on_connected`]
+    fn result_column(&self) -> usize {
+        self.result_col
+            .expect("result_column called before Ingredient::on_connected")
+    }
+
+    /// Compute the window function for all the rows in the given partition, emitting records for
+    /// any rows whose result has changed
+    fn post_partition(&self, out: &mut Vec<Record>, current_partition: &mut Vec<Cow<[DfValue]>>) {
+        let result_column = self.result_column();
+        let mut current_partition = mem::take(current_partition);
+        current_partition.sort_by(|a, b| {
+            self.order
+                .cmp(a, b)
+                // Break ties deterministically, so that ROW_NUMBER doesn't arbitrarily reorder
+                // rows that compare equal
+                .then_with(|| a[..result_column].cmp(&b[..result_column]))
+        });
+
+        let mut result = 0u64;
+        for i in 0..current_partition.len() {
+            let tied = i > 0
+                && self
+                    .order
+                    .cmp(&current_partition[i - 1], &current_partition[i])
+                    == Ordering::Equal;
+            result = match self.function {
+                WindowFunction::RowNumber => i as u64 + 1,
+                WindowFunction::Rank if tied => result,
+                WindowFunction::Rank => i as u64 + 1,
+                WindowFunction::DenseRank if tied => result,
+                WindowFunction::DenseRank => result + 1,
+                WindowFunction::CountStar if tied => result,
+                WindowFunction::CountStar => {
+                    let peers = current_partition[i + 1..]
+                        .iter()
+                        .take_while(|r| self.order.cmp(&current_partition[i], r) == Ordering::Equal)
+                        .count();
+                    (i + 1 + peers) as u64
+                }
+            };
+
+            let row = &mut current_partition[i];
+            if let Some(existing) = row.get(result_column) {
+                // if the row already has a result, that means it started out in the partition
+                if *existing != result.into() {
+                    // if the result is different, we need to emit a negative for the old result
+                    // and a positive for the new one
+                    out.push(Record::Negative(row.clone().into_owned()));
+                    row.to_mut()[result_column] = result.into();
+                    out.push(Record::Positive(row.clone().into_owned()));
+                }
+            } else {
+                row.to_mut().push(result.into());
+                out.push(Record::Positive(row.clone().into_owned()));
+            }
+        }
+    }
+}
+
+impl Ingredient for Window {
+    fn take(&mut self) -> NodeOperator {
+        self.clone().into()
+    }
+
+    fn ancestors(&self) -> Vec<NodeIndex> {
+        vec![self.src.as_global()]
+    }
+
+    impl_replace_sibling!(src);
+
+    fn on_connected(&mut self, graph: &Graph) {
+        self.result_col = Some(graph[self.src.as_global()].columns().len());
+    }
+
+    fn on_commit(&mut self, us: NodeIndex, remap: &HashMap<NodeIndex, IndexPair>) {
+        self.src.remap(remap);
+        self.our_index = Some(remap[&us]);
+    }
+
+    fn on_input(
+        &mut self,
+        from: LocalNodeIndex,
+        rs: Records,
+        replay: &ReplayContext,
+        _nodes: &DomainNodes,
+        state: &StateMap,
+        _auxiliary_node_states: &mut AuxiliaryNodeStateMap,
+    ) -> ReadySetResult<ProcessingResult> {
+        debug_assert_eq!(from, *self.src);
+
+        if rs.is_empty() {
+            return Ok(ProcessingResult {
+                results: rs,
+                ..Default::default()
+            });
+        }
+
+        let mut rs = Vec::from(rs);
+        rs.sort_by(|a: &Record, b: &Record| {
+            self.project_partition(&***a)
+                .unwrap_or_default()
+                .cmp(&self.project_partition(&***b).unwrap_or_default())
+        });
+
+        let us = self.our_index.unwrap();
+        let db = state
+            .get(*us)
+            .ok_or_else(|| internal_err!("window must have its own state materialized"))?;
+
+        let result_column = self.result_column();
+        // The key of the partition currently being processed, or None if we haven't started on
+        // the first partition yet. Note that the key itself may be empty if the window has no
+        // `PARTITION BY`, in which case all rows are in the same partition
+        let mut current_partition_key: Option<Vec<DfValue>> = None;
+        let mut current_partition: Vec<Cow<[DfValue]>> = vec![];
+        let mut partition_missed = false;
+
+        let mut out = vec![];
+        let mut lookups = vec![];
+        let mut misses = vec![];
+
+        for r in rs {
+            let record_partition = self.project_partition(r.rec())?;
+            if current_partition_key.as_ref().map_or(true, |key| {
+                key.iter().cmp(record_partition.iter().copied()) != Ordering::Equal
+            }) {
+                // New partition!
+                if current_partition_key.is_some() {
+                    self.post_partition(&mut out, &mut current_partition);
+                }
+
+                let key =
+                    current_partition_key.insert(record_partition.into_iter().cloned().collect());
+
+                // Load all rows for the partition into memory
+                match db.lookup(&self.partition_by, &PointKey::from(key.iter().cloned())) {
+                    LookupResult::Some(local_records) => {
+                        if replay.is_partial() {
+                            lookups.push(Lookup {
+                                on: *us,
+                                cols: self.partition_by.clone(),
+                                key: key
+                                    .clone()
+                                    .try_into()
+                                    .map_err(|_| internal_err!("Empty partition"))?,
+                            });
+                        }
+
+                        partition_missed = false;
+                        current_partition.extend(local_records.into_iter());
+                    }
+                    LookupResult::Missing => {
+                        partition_missed = true;
+                    }
+                }
+            }
+
+            if partition_missed {
+                misses.push(
+                    Miss::builder()
+                        .on(*us)
+                        .lookup_idx(self.partition_by.clone())
+                        .lookup_key(self.partition_by.clone())
+                        .replay(replay)
+                        .record(r.into_row())
+                        .build(),
+                );
+                continue;
+            }
+
+            match r {
+                Record::Positive(r) => current_partition.push(Cow::Owned(r)),
+                Record::Negative(r) => {
+                    if let Some(pos) = current_partition
+                        .iter()
+                        .position(|row| row.len() > result_column && row[..result_column] == *r)
+                    {
+                        let row = current_partition.remove(pos);
+                        out.push(Record::Negative(row.into_owned()));
+                    } else if let Some(pos) = current_partition.iter().position(|row| **row == *r) {
+                        // The row was added earlier in this same batch, so hasn't been emitted yet
+                        current_partition.remove(pos);
+                    }
+                }
+            }
+        }
+
+        if current_partition_key.is_some() {
+            self.post_partition(&mut out, &mut current_partition);
+        }
+
+        Ok(ProcessingResult {
+            results: out.into(),
+            lookups,
+            misses,
+        })
+    }
+
+    fn suggest_indexes(&self, this: NodeIndex) -> HashMap<NodeIndex, LookupIndex> {
+        HashMap::from([(
+            this,
+            LookupIndex::Strict(Index::hash_map(self.partition_by.clone())),
+        )])
+    }
+
+    fn column_source(&self, cols: &[usize]) -> ColumnSource {
+        if cols.contains(&self.result_column()) {
+            if cols.len() == 1 {
+                // Lookups on only the result of the window function require a full replay
+                return ColumnSource::RequiresFullReplay(vec1![self.src.as_global()]);
+            }
+
+            let columns = cols
+                .iter()
+                .copied()
+                .filter(|c| *c != self.result_column())
+                .collect::<Vec<_>>();
+            ColumnSource::GeneratedFromColumns(vec1![ColumnRef {
+                node: self.our_index.unwrap().as_global(),
+                columns,
+            }])
+        } else {
+            ColumnSource::ExactCopy(ColumnRef {
+                node: self.src.as_global(),
+                columns: cols.to_vec(),
+            })
+        }
+    }
+
+    fn handle_upquery(&mut self, miss: ColumnMiss) -> ReadySetResult<Vec<ColumnMiss>> {
+        let result_column = miss
+            .column_indices
+            .iter()
+            .position(|ci| *ci == self.result_column())
+            .expect("handle_upquery invariant");
+
+        Ok(vec![ColumnMiss {
+            node: *self.our_index.unwrap(),
+            column_indices: self.partition_by.clone(),
+            missed_keys: miss.missed_keys.mapped(|k| {
+                k.map_endpoints(|mut r| {
+                    r.remove(result_column).expect("handle_upquery invariant");
+                    r
+                })
+            }),
+        }])
+    }
+
+    fn description(&self, detailed: bool) -> String {
+        if !detailed {
+            return "Window".into();
+        }
+
+        format!(
+            "Window {}() γ[{}] o[{}]",
+            self.function,
+            self.partition_by.iter().join(", "),
+            self.order
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::test::MockGraph;
+
+    fn setup(function: WindowFunction) -> (MockGraph, IndexPair) {
+        let mut g = MockGraph::new();
+        let s = g.add_base("source", &["x", "y"]);
+
+        // function() OVER (PARTITION BY y ORDER BY x)
+        g.set_op(
+            "window",
+            &["x", "y", "rank"],
+            Window::new(
+                s.as_global(),
+                function,
+                vec![1],
+                vec![(0, OrderType::OrderAscending)],
+            ),
+            true,
+        );
+        (g, s)
+    }
+
+    fn with_rank(row: &[DfValue], rank: u64) -> Vec<DfValue> {
+        let mut res = row.to_vec();
+        res.push(rank.into());
+        res
+    }
+
+    #[test]
+    fn suggest_indexes() {
+        let (g, _) = setup(WindowFunction::RowNumber);
+        let res = g.node().suggest_indexes(g.node_index().as_global());
+        assert_eq!(res.len(), 1);
+        assert_eq!(
+            res[&g.node_index().as_global()],
+            LookupIndex::Strict(Index::hash_map(vec![1]))
+        );
+    }
+
+    #[test]
+    fn column_source_for_partitioned_result_lookup() {
+        let (g, _) = setup(WindowFunction::RowNumber);
+        let src = g.node().column_source(&[1, 2]);
+        assert_eq!(
+            src,
+            ColumnSource::GeneratedFromColumns(vec1![ColumnRef {
+                node: g.node_index().as_global(),
+                columns: vec![1],
+            }])
+        );
+    }
+
+    #[test]
+    fn row_number_multiple_partitions() {
+        let (mut g, _) = setup(WindowFunction::RowNumber);
+
+        let r1a = vec![1.into(), "a".into()];
+        let r2a = vec![2.into(), "a".into()];
+        let r1b = vec![1.into(), "b".into()];
+
+        let res = g.narrow_one(vec![r2a.clone(), r1a.clone(), r1b.clone()], true);
+        assert_eq!(
+            res,
+            vec![with_rank(&r1a, 1), with_rank(&r2a, 2), with_rank(&r1b, 1)].into()
+        );
+    }
+
+    #[test]
+    fn row_number_shifts_on_insert() {
+        let (mut g, _) = setup(WindowFunction::RowNumber);
+
+        let r1a = vec![1.into(), "a".into()];
+        let r2a = vec![2.into(), "a".into()];
+        let r3a = vec![3.into(), "a".into()];
+
+        g.narrow_one(vec![r1a, r3a.clone()], true);
+
+        let res = g.narrow_one_row(r2a.clone(), true);
+        assert_eq!(
+            res,
+            vec![
+                (with_rank(&r2a, 2), true),
+                (with_rank(&r3a, 2), false),
+                (with_rank(&r3a, 3), true),
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn row_number_shifts_on_delete() {
+        let (mut g, _) = setup(WindowFunction::RowNumber);
+
+        let r1a = vec![1.into(), "a".into()];
+        let r2a = vec![2.into(), "a".into()];
+        let r3a = vec![3.into(), "a".into()];
+
+        g.narrow_one(vec![r1a.clone(), r2a, r3a.clone()], true);
+
+        let res = g.narrow_one_row((vec![2.into(), "a".into()], false), true);
+        assert_eq!(
+            res,
+            vec![
+                (with_rank(&[2.into(), "a".into()], 2), false),
+                (with_rank(&r3a, 3), false),
+                (with_rank(&r3a, 2), true),
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn row_number_without_partition() {
+        let mut g = MockGraph::new();
+        let s = g.add_base("source", &["x", "y"]);
+
+        // ROW_NUMBER() OVER (ORDER BY x)
+        g.set_op(
+            "window",
+            &["x", "y", "rn"],
+            Window::new(
+                s.as_global(),
+                WindowFunction::RowNumber,
+                vec![],
+                vec![(0, OrderType::OrderAscending)],
+            ),
+            true,
+        );
+
+        let r1a = vec![1.into(), "a".into()];
+        let r2b = vec![2.into(), "b".into()];
+        let r3a = vec![3.into(), "a".into()];
+
+        let res = g.narrow_one(vec![r3a.clone(), r1a.clone()], true);
+        assert_eq!(res, vec![with_rank(&r1a, 1), with_rank(&r3a, 2)].into());
+
+        let res = g.narrow_one_row(r2b.clone(), true);
+        assert_eq!(
+            res,
+            vec![
+                (with_rank(&r2b, 2), true),
+                (with_rank(&r3a, 2), false),
+                (with_rank(&r3a, 3), true),
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn rank_and_dense_rank_with_ties() {
+        let r1a = vec![1.into(), "a".into()];
+        let r1a_dup = vec![1.into(), "a".into()];
+        let r2a = vec![2.into(), "a".into()];
+        let input = vec![r1a.clone(), r1a_dup.clone(), r2a.clone()];
+
+        let (mut g, _) = setup(WindowFunction::Rank);
+        let res = g.narrow_one(input.clone(), true);
+        assert_eq!(
+            res,
+            vec![
+                with_rank(&r1a, 1),
+                with_rank(&r1a_dup, 1),
+                with_rank(&r2a, 3)
+            ]
+            .into()
+        );
+
+        let (mut g, _) = setup(WindowFunction::DenseRank);
+        let res = g.narrow_one(input, true);
+        assert_eq!(
+            res,
+            vec![
+                with_rank(&r1a, 1),
+                with_rank(&r1a_dup, 1),
+                with_rank(&r2a, 2)
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn count_star_counts_peers() {
+        let (mut g, _) = setup(WindowFunction::CountStar);
+
+        let r1a = vec![1.into(), "a".into()];
+        let r1a_dup = vec![1.into(), "a".into()];
+        let r2a = vec![2.into(), "a".into()];
+        let r1b = vec![1.into(), "b".into()];
+
+        let res = g.narrow_one(
+            vec![r1a.clone(), r1a_dup.clone(), r2a.clone(), r1b.clone()],
+            true,
+        );
+        assert_eq!(
+            res,
+            vec![
+                with_rank(&r1a, 2),
+                with_rank(&r1a_dup, 2),
+                with_rank(&r2a, 3),
+                with_rank(&r1b, 1)
+            ]
+            .into()
+        );
+
+        let res = g.narrow_one_row((r1a.clone(), false), true);
+        assert_eq!(
+            res,
+            vec![
+                (with_rank(&r1a, 2), false),
+                (with_rank(&r1a_dup, 2), false),
+                (with_rank(&r1a_dup, 1), true),
+                (with_rank(&r2a, 3), false),
+                (with_rank(&r2a, 2), true),
+            ]
+            .into()
+        );
+    }
+}
@@ -1,7 +1,9 @@
 use std::collections::HashSet;
 use std::fmt::{self, Display};
+use std::path::PathBuf;
+use std::time::Duration;
 
-use dataflow_state::MaterializedNodeState;
+use dataflow_state::{MaterializedNodeState, RocksDbTuning};
 use itertools::Itertools;
 use readyset_client::{self, KeyComparison, PacketData, PacketTrace};
 use readyset_data::DfType;
@@ -233,6 +235,12 @@ pub enum EvictRequest {
         tag: Tag,
         keys: Vec<KeyComparison>,
     },
+    /// Evict all partially materialized state derived from the rows of the base table node `node`
+    /// with the indicated primary keys, from all materializations below it.
+    BaseTableKeys {
+        node: LocalNodeIndex,
+        keys: Vec<Vec<DfValue>>,
+    },
 }
 
 /// A request issued to a domain through the worker RPC interface.
@@ -389,6 +397,9 @@ pub enum DomainRequest {
     /// bytes
     RequestNodeSizes,
 
+    /// Request the number of keys in, and reads served by, each reader in the domain
+    RequestReaderShardStats,
+
     /// Process the packet, as per usual
     Packet(Packet),
 
@@ -410,6 +421,38 @@ pub enum DomainRequest {
     },
 
     AllTablesCompacted,
+
+    /// Trigger a manual compaction of the persisted state of a `Base` node in the background.
+    CompactBaseTable {
+        node: LocalNodeIndex,
+    },
+
+    /// Set (or clear, if `None`) the interval at which the persisted state of a `Base` node should
+    /// be manually compacted
+    SetBaseTableCompactionInterval {
+        node: LocalNodeIndex,
+        interval: Option<Duration>,
+    },
+
+    /// Apply RocksDB tuning parameters to the persisted state of a `Base` node. Parameters left
+    /// unset retain their previous values.
+    SetBaseTableTuning {
+        node: LocalNodeIndex,
+        tuning: RocksDbTuning,
+    },
+    /// Create a RocksDB checkpoint of the persisted state of every `Base` node in the domain, in
+    /// the given directory on the worker running the domain.
+    ///
+    /// Replies with the number of base tables checkpointed.
+    CheckpointBaseTables {
+        dir: PathBuf,
+    },
+    /// Evict the given keys from the state of a reader node, if present, so that subsequent
+    /// lookups of those keys miss and trigger a fresh replay
+    EvictReaderKeys {
+        node: LocalNodeIndex,
+        keys: Vec<KeyComparison>,
+    },
 }
 
 /// The primary unit of communication between nodes in the dataflow graph.
@@ -2,6 +2,7 @@
 
 use std::error::Error;
 use std::io;
+use std::time::Duration;
 
 use derive_more::Display;
 use nom_sql::Relation;
@@ -286,6 +287,49 @@ pub enum ReadySetError {
     #[error("Upquery timeout")]
     UpqueryTimeout,
 
+    /// A query took longer than the configured maximum query execution time, and was cancelled.
+    #[error(
+        "Query execution was interrupted, maximum execution time of {}ms exceeded",
+        timeout.as_millis()
+    )]
+    QueryTimeout {
+        /// The maximum query execution time that was exceeded
+        timeout: Duration,
+    },
+
+    /// A read from a view returned more rows than the configured maximum for a single query.
+    #[error("Query result exceeded the maximum of {max_rows} rows")]
+    ResultRowLimitExceeded {
+        /// The maximum number of rows that was exceeded
+        max_rows: usize,
+    },
+
+    /// A read from a view returned more data than the configured maximum for a single query.
+    #[error("Query result exceeded the maximum size of {max_bytes} bytes")]
+    ResultSizeLimitExceeded {
+        /// The maximum result size, in bytes, that was exceeded
+        max_bytes: usize,
+    },
+
+    /// A single row written to a client was larger than the configured maximum row size.
+    #[error("Query result contained a row larger than the maximum size of {max_bytes} bytes")]
+    RowSizeLimitExceeded {
+        /// The maximum row size, in bytes, that was exceeded
+        max_bytes: usize,
+    },
+
+    /// A query was cancelled by a request from another connection, such as a `KILL QUERY`
+    /// statement or a Postgres cancel request.
+    #[error("Query execution was interrupted")]
+    QueryCancelled,
+
+    /// A request was made to cancel a query on a connection that doesn't exist.
+    #[error("Unknown thread id: {id}")]
+    UnknownConnection {
+        /// The id of the connection
+        id: u32,
+    },
+
     /// The query specified an empty lookup key.
     #[error("the query specified an empty lookup key")]
     EmptyKey,
@@ -425,6 +469,23 @@ pub enum ReadySetError {
     #[error("API endpoint not found")]
     UnknownEndpoint,
 
+    /// A request was made to an API endpoint without a token granting access to that endpoint.
+    #[error("Not authorized to access API endpoint {path}")]
+    Unauthorized {
+        /// The path of the endpoint.
+        path: String,
+    },
+
+    /// The user a client authenticated as hasn't been granted `SELECT` on a table read by a query
+    /// in the upstream database, so the query's results can't be served from ReadySet.
+    #[error("SELECT privilege denied to user '{user}' for table {table}")]
+    SelectPrivilegeDenied {
+        /// The user the client authenticated as
+        user: String,
+        /// The table the user can't select from
+        table: String,
+    },
+
     /// A node index passed to the controller was invalid.
     #[error("Node {index} not found in controller")]
     NodeNotFound {
@@ -672,6 +733,15 @@ pub enum ReadySetError {
     /// Incorrect Auxiliary node state was found
     #[error("Incorrect Auxiliary Node State for {0} Node")]
     IncorrectNodeState(String),
+
+    /// A value written to a base table was larger than the configured maximum value size.
+    #[error("Value for column {column} is larger than the maximum size of {max_bytes} bytes")]
+    ValueSizeLimitExceeded {
+        /// The column the value was written to
+        column: String,
+        /// The maximum value size, in bytes, that was exceeded
+        max_bytes: usize,
+    },
 }
 
 impl ReadySetError {
@@ -686,6 +756,134 @@ impl ReadySetError {
         }
     }
 
+    /// Returns the stable numeric code identifying the kind of this error, for consumers outside
+    /// of ReadySet which need to handle particular errors programmatically without matching on
+    /// error messages.
+    ///
+    /// Codes are never changed or reused once assigned - new variants must be given the next
+    /// unused code. Note that this is the code of this error itself, even if it wraps another
+    /// error; use [`root_cause`](Self::root_cause) to get the code of the underlying error.
+    pub fn code(&self) -> u16 {
+        match self {
+            Self::Context { .. } => 1,
+            Self::InvalidQuery(..) => 2,
+            Self::NoQueryForId { .. } => 3,
+            Self::SetDisallowed { .. } => 4,
+            Self::InvalidUpstreamDatabase => 5,
+            Self::RpcFailed { .. } => 6,
+            Self::SelectQueryCreationFailed { .. } => 7,
+            Self::MigrationPlanFailed { .. } => 8,
+            Self::MigrationApplyFailed { .. } => 9,
+            Self::RecipeInvariantViolated(..) => 10,
+            Self::DomainCreationFailed { .. } => 11,
+            Self::MirNodeToDataflowFailed { .. } => 12,
+            Self::TcpSendError(..) => 13,
+            Self::SerializationFailed(..) => 14,
+            Self::WrongColumnCount(..) => 15,
+            Self::WrongColumnType(..) => 16,
+            Self::WrongKeyColumnCount(..) => 17,
+            Self::WrongPacketDataType => 18,
+            Self::NonNullable { .. } => 19,
+            Self::ColumnRequired { .. } => 20,
+            Self::ExprNotInGroupBy { .. } => 21,
+            Self::TableNotFound { .. } => 22,
+            Self::TableNotReplicated { .. } => 23,
+            Self::ViewNotYetAvailable => 24,
+            Self::ViewDestroyed => 25,
+            Self::ViewNotFound(..) => 26,
+            Self::ViewNotFoundInWorkers { .. } => 27,
+            Self::ViewAlreadyExists(..) => 28,
+            Self::NoCacheForQuery => 29,
+            Self::ReaderNotFound => 30,
+            Self::ServerShuttingDown => 31,
+            Self::UpqueryTimeout => 32,
+            Self::QueryTimeout { .. } => 33,
+            Self::ResultRowLimitExceeded { .. } => 34,
+            Self::ResultSizeLimitExceeded { .. } => 35,
+            Self::RowSizeLimitExceeded { .. } => 36,
+            Self::QueryCancelled => 37,
+            Self::UnknownConnection { .. } => 38,
+            Self::EmptyKey => 39,
+            Self::ReaderMissingKey => 40,
+            Self::PreparedStatementMissing { .. } => 41,
+            Self::Internal(..) => 42,
+            Self::BadRequest(..) => 43,
+            Self::Unsupported(..) => 44,
+            Self::UnparseableQuery { .. } => 45,
+            Self::TableError { .. } => 46,
+            Self::ViewError { .. } => 47,
+            Self::NoSuchFunction(..) => 48,
+            Self::ArityError(..) => 49,
+            Self::MultipleAutoIncrement => 50,
+            Self::NoSuchColumn(..) => 51,
+            Self::DfValueConversionError { .. } => 52,
+            Self::ProjectExprInvalidColumnIndex(..) => 53,
+            Self::ProjectExprBuiltInFunctionError { .. } => 54,
+            Self::NaiveDateTimeParseError(..) => 55,
+            Self::InvalidPrimaryKeyField => 56,
+            Self::LostController => 57,
+            Self::NotLeader => 58,
+            Self::LeaderNotReady => 59,
+            Self::NoQuorum => 60,
+            Self::UnknownEndpoint => 61,
+            Self::Unauthorized { .. } => 62,
+            Self::SelectPrivilegeDenied { .. } => 63,
+            Self::NodeNotFound { .. } => 64,
+            Self::IndexNotFound { .. } => 65,
+            Self::UnknownWorker { .. } => 66,
+            Self::ReplicationUnknownWorker { .. } => 67,
+            Self::WorkerFailed { .. } => 68,
+            Self::HttpRequestFailed { .. } => 69,
+            Self::ShardIndexOutOfBounds { .. } => 70,
+            Self::ViewReplicaOutOfBounds { .. } => 71,
+            Self::NoSuchReplica { .. } => 72,
+            Self::NoSuchNode(..) => 73,
+            Self::InvalidNodeType { .. } => 74,
+            Self::NoSuchReplayPath(..) => 75,
+            Self::UnmappableDomain { .. } => 76,
+            Self::UnknownDomain { .. } => 77,
+            Self::ServiceUnavailable => 78,
+            Self::UrlParseFailed(..) => 79,
+            Self::SqlModeParseFailed(..) => 80,
+            Self::ReplicationOffsetLogDifferent(..) => 81,
+            Self::ReplicationFailed(..) => 82,
+            Self::NoAvailableWorkers { .. } => 83,
+            Self::InvalidRecordLength => 84,
+            Self::IOError(..) => 85,
+            Self::Size0Error => 86,
+            Self::NonInternalNode => 87,
+            Self::NodeAlreadyTaken => 88,
+            Self::KeyAlreadyFilled => 89,
+            Self::RangeAlreadyFilled => 90,
+            Self::NonExistentColumn { .. } => 91,
+            Self::JemallocCtlError(..) => 92,
+            Self::ArrayParseError { .. } => 93,
+            Self::ResnapshotNeeded => 94,
+            Self::InvalidRootCertificate => 95,
+            Self::MirNodeNotFound { .. } => 96,
+            Self::RelationNotFound { .. } => 97,
+            Self::FailedBaseOps { .. } => 98,
+            Self::MirNodeMustHaveDfNodeAssigned { .. } => 99,
+            Self::UnsupportedServerVersion { .. } => 100,
+            Self::UnparseableServerVersion => 101,
+            Self::UpstreamConnectionLost(..) => 102,
+            Self::UnknownMigration(..) => 103,
+            Self::ConsulError(..) => 104,
+            Self::UnsupportedPlaceholders { .. } => 105,
+            Self::NativeTlsError(..) => 106,
+            Self::IncorrectNodeState(..) => 107,
+            Self::ValueSizeLimitExceeded { .. } => 108,
+        }
+    }
+
+    /// Returns the innermost error wrapped by this error, or this error itself if it doesn't wrap
+    /// another error
+    pub fn root_cause(&self) -> &Self {
+        self.source()
+            .and_then(|e| e.downcast_ref::<Box<ReadySetError>>())
+            .map_or(self, |e| e.root_cause())
+    }
+
     fn any_cause<F>(&self, f: F) -> bool
     where
         F: Fn(&Self) -> bool + Clone,
@@ -799,12 +997,45 @@ impl ReadySetError {
         self.any_cause(|e| matches!(e, Self::ViewDestroyed))
     }
 
+    /// Returns true if the error either *is* [`ResnapshotNeeded`], or was *caused by*
+    /// [`ResnapshotNeeded`]
+    pub fn caused_by_resnapshot_needed(&self) -> bool {
+        self.any_cause(|e| matches!(e, Self::ResnapshotNeeded))
+    }
+
+    /// Returns true if the error either *is* [`ReaderNotFound`], or was *caused by*
+    /// [`ReaderNotFound`]
+    pub fn caused_by_reader_not_found(&self) -> bool {
+        self.any_cause(|e| matches!(e, Self::ReaderNotFound))
+    }
+
+    /// Returns true if the error indicates that the addresses of the reader shards we sent a read
+    /// to are out of date, for example because the domain containing the reader was moved to a
+    /// different worker, and that the view should be re-requested from the controller.
+    pub fn is_stale_reader_address(&self) -> bool {
+        self.is_networking_related()
+            || self.caused_by_view_destroyed()
+            || self.caused_by_reader_not_found()
+    }
+
     /// Returns true if the error either *is* [`SerializationFailed`], or was *caused by*
     /// [`SerializationFailed`]
     pub fn caused_by_serialization_failed(&self) -> bool {
         self.any_cause(|e| matches!(e, Self::SerializationFailed(_)))
     }
 
+    /// If `self` either *is* [`ResultRowLimitExceeded`], [`ResultSizeLimitExceeded`] or
+    /// [`RowSizeLimitExceeded`], or was *caused by* one of them, returns that error. Otherwise,
+    /// returns `None`
+    pub fn result_limit_exceeded_cause(&self) -> Option<&Self> {
+        self.find_map_cause(|e| match e {
+            Self::ResultRowLimitExceeded { .. }
+            | Self::ResultSizeLimitExceeded { .. }
+            | Self::RowSizeLimitExceeded { .. } => Some(e),
+            _ => None,
+        })
+    }
+
     /// Returns `true` if the error is [`InvalidQuery`].
     pub fn is_invalid_query(&self) -> bool {
         matches!(self, Self::InvalidQuery(..))
@@ -1081,14 +1312,18 @@ pub fn rpc_err_no_downcast<T: Into<String>>(during: T, err: ReadySetError) -> Re
 /// Make a new [`ReadySetError::RpcFailed`] with the provided string-able `during` value
 /// and the provided `err` as cause.
 ///
-/// This attempts to downcast the `err` into a `Box<ReadySetError>`. If the downcasting
-/// fails, the error is formatted as a [`ReadySetError::Internal`].
+/// This attempts to downcast the `err`, or failing that any of its sources (since errors returned
+/// by services are often wrapped by the middleware in front of them), into a [`ReadySetError`], so
+/// that the original error survives the RPC boundary. If none of them are a [`ReadySetError`], the
+/// error is formatted as a [`ReadySetError::Internal`].
 pub fn rpc_err<T: Into<String>>(during: T, err: Box<dyn std::error::Error>) -> ReadySetError {
-    // TODO(eta): this downcast WILL always fail, because I haven't really had a chance to
-    // unravel the complete madness that is `tokio_tower` yet.
-    let rse: Box<ReadySetError> = err
-        .downcast()
-        .unwrap_or_else(|e| Box::new(internal_err!("failed to downcast: {}", e)));
+    let rse: Box<ReadySetError> = err.downcast().unwrap_or_else(|err| {
+        std::iter::successors(err.source(), |e| e.source())
+            .find_map(|e| e.downcast_ref::<ReadySetError>())
+            .cloned()
+            .map(Box::new)
+            .unwrap_or_else(|| Box::new(internal_err!("failed to downcast: {}", err)))
+    });
     ReadySetError::RpcFailed {
         during: during.into(),
         source: rse,
@@ -1189,7 +1424,7 @@ macro_rules! set_failpoint_return_err {
 
 #[cfg(test)]
 mod test {
-    use crate::{internal, ReadySetError, ReadySetResult};
+    use crate::{internal, rpc_err, ReadySetError, ReadySetResult};
 
     #[test]
     #[should_panic(expected = "errors/src/lib.rs")]
@@ -1250,4 +1485,36 @@ mod test {
             ("t2_view", Some("public"))
         )
     }
+
+    #[test]
+    fn codes_are_stable() {
+        assert_eq!(ReadySetError::InvalidQuery("q".into()).code(), 2);
+        assert_eq!(ReadySetError::ViewNotFound("v".into()).code(), 26);
+        assert_eq!(ReadySetError::ServerShuttingDown.code(), 31);
+        assert_eq!(ReadySetError::IncorrectNodeState("n".into()).code(), 107);
+    }
+
+    #[test]
+    fn root_cause_code() {
+        let err = ReadySetError::RpcFailed {
+            during: "test".to_owned(),
+            source: Box::new(ReadySetError::ViewNotFound("v".into()).context("some context")),
+        };
+        assert_eq!(err.code(), 6);
+        assert_eq!(err.root_cause(), &ReadySetError::ViewNotFound("v".into()));
+        assert_eq!(err.root_cause().code(), 26);
+    }
+
+    #[test]
+    fn rpc_err_preserves_wrapped_errors() {
+        #[derive(Debug, thiserror::Error)]
+        #[error("service failed")]
+        struct ServiceError(#[source] ReadySetError);
+
+        let err = rpc_err!("test")(Box::new(ServiceError(ReadySetError::ServerShuttingDown)));
+        assert_eq!(err.root_cause(), &ReadySetError::ServerShuttingDown);
+
+        let err = rpc_err!("test")(Box::new(ReadySetError::ViewDestroyed));
+        assert_eq!(err.root_cause(), &ReadySetError::ViewDestroyed);
+    }
 }
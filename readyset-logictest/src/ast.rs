@@ -428,6 +428,8 @@ impl TryFrom<DfValue> for Value {
             DfValue::Numeric(ref d) => Ok(Value::Numeric(*d.as_ref())),
             DfValue::BitVector(ref b) => Ok(Value::BitVector(b.as_ref().clone())),
             DfValue::Array(_) => bail!("Arrays not supported"),
+            DfValue::Uuid(u) => Ok(Value::Text(u.to_string())),
+            DfValue::Interval(i) => Ok(Value::Text(i.to_string())),
             DfValue::PassThrough(_) => unimplemented!(),
         }
     }
@@ -99,17 +99,21 @@ fn is_ddl(query: &SqlQuery) -> bool {
         | SqlQuery::Commit(_)
         | SqlQuery::Rollback(_)
         | SqlQuery::Show(_)
-        | SqlQuery::Explain(_) => false,
+        | SqlQuery::Explain(_)
+        | SqlQuery::Kill(_)
+        | SqlQuery::FlushCache(_) => false,
         SqlQuery::CreateTable(_)
         | SqlQuery::CreateView(_)
         | SqlQuery::DropTable(_)
         | SqlQuery::DropView(_)
         | SqlQuery::AlterTable(_)
         | SqlQuery::RenameTable(_)
+        | SqlQuery::Truncate(_)
         | SqlQuery::Use(_)
         | SqlQuery::CreateCache(_)
         | SqlQuery::DropCache(_)
-        | SqlQuery::DropAllCaches(_) => true,
+        | SqlQuery::DropAllCaches(_)
+        | SqlQuery::Experiment(_) => true,
     }
 }
 
@@ -269,6 +269,7 @@ impl Seed {
                         })
                         .collect(),
                     ignore: false,
+                    on_conflict: None,
                     on_duplicate: None,
                 }
             })
@@ -157,6 +157,23 @@ impl MirGraph {
                 columns
             }
             MirNodeInner::Distinct { group_by } => group_by.clone(),
+            MirNodeInner::Window {
+                partition_by,
+                order,
+                output_column,
+                ..
+            } => {
+                // Window nodes need all the columns they pass through, as well as the columns
+                // they partition and order by
+                let mut columns = self.columns(node);
+                columns.retain(|c| c != output_column);
+                for c in partition_by.iter().chain(order.iter().map(|(c, _)| c)) {
+                    if !columns.contains(c) {
+                        columns.push(c.clone());
+                    }
+                }
+                columns
+            }
             MirNodeInner::Project { emit } => {
                 let mut columns = vec![];
                 for expr in emit {
@@ -254,6 +271,7 @@ impl MirGraph {
                 .collect(),
             MirNodeInner::Join { project, .. }
             | MirNodeInner::LeftJoin { project, .. }
+            | MirNodeInner::FullJoin { project, .. }
             | MirNodeInner::DependentJoin { project, .. } => project.clone(),
             MirNodeInner::JoinAggregates => {
                 let cols = self
@@ -285,6 +303,10 @@ impl MirGraph {
                 .into_iter()
                 .chain(iter::once(MirColumn::named(&*PAGE_NUMBER_COL)))
                 .collect(),
+            MirNodeInner::Window { output_column, .. } => parent_columns()
+                .into_iter()
+                .chain(iter::once(output_column.clone()))
+                .collect(),
             MirNodeInner::Distinct { group_by } => group_by
                 .iter()
                 .cloned()
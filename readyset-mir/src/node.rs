@@ -122,6 +122,7 @@ mod tests {
         use dataflow::ops::grouped::aggregate::Aggregation;
         use dataflow::ops::grouped::extremum::Extremum;
         use dataflow::ops::union::DuplicateMode;
+        use dataflow::ops::window::WindowFunction;
         use nom_sql::{BinaryOperator, ColumnSpecification, Expr, OrderType, SqlType};
         use readyset_client::ViewPlaceholder;
 
@@ -212,6 +213,7 @@ mod tests {
                     group_by: vec![Column::new(Some("base"), "b")],
                     output_column: Column::named("agg"),
                     kind: Aggregation::Sum,
+                    distinct: false,
                 },
                 vec![Column::new(Some("base"), "b"), Column::named("agg")],
             );
@@ -392,6 +394,23 @@ mod tests {
             )
         }
 
+        #[test]
+        fn window() {
+            has_columns_single_parent(
+                MirNodeInner::Window {
+                    function: WindowFunction::RowNumber,
+                    partition_by: vec![Column::new(Some("base"), "b")],
+                    order: vec![(Column::new(Some("base"), "a"), OrderType::OrderAscending)],
+                    output_column: Column::named("rn"),
+                },
+                vec![
+                    Column::new(Some("base"), "a"),
+                    Column::new(Some("base"), "b"),
+                    Column::named("rn"),
+                ],
+            )
+        }
+
         #[test]
         fn join_aggregates() {
             let mut graph = MirGraph::new();
@@ -403,6 +422,7 @@ mod tests {
                     group_by: vec![Column::new(Some("base"), "b")],
                     output_column: Column::named("count"),
                     kind: Aggregation::Count,
+                    distinct: false,
                 },
             ));
             graph.add_edge(base, count, 0);
@@ -414,6 +434,7 @@ mod tests {
                     group_by: vec![Column::new(Some("base"), "b")],
                     output_column: Column::named("sum"),
                     kind: Aggregation::Sum,
+                    distinct: false,
                 },
             ));
             graph.add_edge(base, sum, 0);
@@ -446,6 +467,7 @@ mod tests {
                     group_by: vec![Column::named("a"), Column::named("b")],
                     output_column: Column::named("sum(c)"),
                     kind: Aggregation::Sum,
+                    distinct: false,
                 },
             ));
 
@@ -456,6 +478,7 @@ mod tests {
                     group_by: vec![Column::named("a"), Column::named("b")],
                     output_column: Column::named("sum(c)"),
                     kind: Aggregation::Sum,
+                    distinct: false,
                 },
             ));
 
@@ -532,6 +555,7 @@ mod tests {
                     group_by: vec![Column::named("gb_a"), Column::named("gb_b")],
                     output_column: Column::named("output"),
                     kind: Aggregation::Count,
+                    distinct: false,
                 },
             ));
             let mut referenced = graph.referenced_columns(node);
@@ -4,6 +4,7 @@ use common::{DfValue, IndexType};
 use dataflow::ops::grouped::aggregate::Aggregation;
 use dataflow::ops::grouped::extremum::Extremum;
 use dataflow::ops::union;
+use dataflow::ops::window::WindowFunction;
 use dataflow::PostLookupAggregates;
 use derive_more::From;
 use itertools::Itertools;
@@ -83,6 +84,8 @@ pub enum MirNodeInner {
         output_column: Column,
         /// Which aggregate function we are computing
         kind: Aggregation,
+        /// If true, only aggregate over the distinct values of `on` within each group
+        distinct: bool,
     },
     /// Base node in the graph, corresponding to a snapshot of a full table in the upstream
     /// database.
@@ -170,6 +173,20 @@ pub enum MirNodeInner {
         /// Columns (from both parents) to project in the output.
         project: Vec<Column>,
     },
+    /// Node which computes a *full outer* join on its two parents, emitting rows from either
+    /// parent that have no match in the other padded with nulls
+    ///
+    /// Converted to [`Join`] with [`JoinType::Full`] when lowering to dataflow.
+    ///
+    /// [`Join`]: dataflow::ops::join::Join
+    /// [`JoinType::Full`]: dataflow::ops::join::JoinType::Full
+    FullJoin {
+        /// Columns to use as the join keys. Each tuple corresponds to a column in the left parent
+        /// and column in the right parent.
+        on: Vec<(Column, Column)>,
+        /// Columns (from both parents) to project in the output.
+        project: Vec<Column>,
+    },
     /// Join where nodes in the right-hand side depend on columns in the left-hand side
     /// (referencing tables in `dependent_tables`). These are created during compilation for
     /// correlated subqueries, and must be removed entirely by rewrite passes before lowering
@@ -255,6 +272,23 @@ pub enum MirNodeInner {
         /// LIMIT clause
         limit: usize,
     },
+    /// Node which computes a window function for each of its input rows within that row's
+    /// partition, outputting its result as an additional column.
+    ///
+    /// Converted to [`Window`] when lowering to dataflow.
+    ///
+    /// [`Window`]: dataflow::ops::window::Window
+    Window {
+        /// Which window function we are computing
+        function: WindowFunction,
+        /// Set of columns that rows are partitioned by
+        partition_by: Vec<Column>,
+        /// Set of columns used for ordering the rows within each partition
+        order: Vec<(Column, OrderType)>,
+        /// The column name to use for the result of the window function, which will always be the
+        /// last column
+        output_column: Column,
+    },
     /// Node which emits only distinct rows per some group.
     ///
     /// Converted to [`Aggregator`] with [`Aggregation::Count`] when lowering to dataflow.
@@ -333,6 +367,7 @@ impl MirNodeInner {
             }
             MirNodeInner::Join { project, .. }
             | MirNodeInner::LeftJoin { project, .. }
+            | MirNodeInner::FullJoin { project, .. }
             | MirNodeInner::DependentJoin { project, .. } => {
                 if !project.contains(&c) {
                     project.push(c);
@@ -385,15 +420,28 @@ impl MirNodeInner {
                 ref on,
                 ref group_by,
                 ref kind,
+                distinct,
                 ..
             } => {
+                let distinct = if *distinct { "DISTINCT " } else { "" };
                 let op_string = match *kind {
-                    Aggregation::Count { .. } => format!("|*|({})", on.name.as_str()),
-                    Aggregation::Sum => format!("𝛴({})", on.name.as_str()),
-                    Aggregation::Avg => format!("AVG({})", on.name.as_str()),
+                    Aggregation::Count { .. } => {
+                        format!("|*|({}{})", distinct, on.name.as_str())
+                    }
+                    Aggregation::Sum => format!("𝛴({}{})", distinct, on.name.as_str()),
+                    Aggregation::Avg => format!("AVG({}{})", distinct, on.name.as_str()),
                     Aggregation::GroupConcat { separator: ref s } => {
                         format!("||([{}], \"{}\")", on.name.as_str(), s.as_str())
                     }
+                    Aggregation::Percentile { percentile } => {
+                        format!("PCT({}, {})", on.name.as_str(), percentile)
+                    }
+                    Aggregation::ApproxCountDistinct => {
+                        format!("~|*|(DISTINCT {})", on.name.as_str())
+                    }
+                    Aggregation::ApproxPercentile { percentile } => {
+                        format!("~PCT({}, {})", on.name.as_str(), percentile)
+                    }
                 };
                 let group_cols = group_by
                     .iter()
@@ -495,6 +543,26 @@ impl MirNodeInner {
                     jc
                 )
             }
+            MirNodeInner::FullJoin {
+                ref on,
+                ref project,
+                ..
+            } => {
+                let jc = on
+                    .iter()
+                    .map(|(l, r)| format!("{}:{}", l.name, r.name))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(
+                    "⟗ [{} on {}]",
+                    project
+                        .iter()
+                        .map(|c| c.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    jc
+                )
+            }
             MirNodeInner::DependentJoin {
                 ref on,
                 ref project,
@@ -531,6 +599,19 @@ impl MirNodeInner {
             } => {
                 format!("TopK [k: {}, {:?}]", limit, order)
             }
+            MirNodeInner::Window {
+                ref function,
+                ref partition_by,
+                ref order,
+                ..
+            } => {
+                let partition_cols = partition_by
+                    .iter()
+                    .map(|c| c.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{}() γ[{}] {:?}", function, partition_cols, order)
+            }
             MirNodeInner::Union {
                 ref emit,
                 ref duplicate_mode,
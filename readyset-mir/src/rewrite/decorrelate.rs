@@ -367,6 +367,7 @@ mod tests {
                 group_by: vec![Column::named("__count_grp")],
                 output_column: Column::named("__exists_count"),
                 kind: Aggregation::Count,
+                distinct: false,
             },
         ));
         graph[exists_count].add_owner(query_name.clone());
@@ -551,6 +552,7 @@ mod tests {
                 group_by: vec![Column::new(Some("t2"), "b")],
                 output_column: Column::named("COUNT(t2.b)"),
                 kind: Aggregation::Count,
+                distinct: false,
             },
         ));
         graph[t2_count].add_owner(query_name.clone());
@@ -618,6 +620,7 @@ mod tests {
                 group_by: vec![Column::named("__count_grp")],
                 output_column: Column::named("__exists_count"),
                 kind: Aggregation::Count,
+                distinct: false,
             },
         ));
         graph[exists_count].add_owner(query_name.clone());
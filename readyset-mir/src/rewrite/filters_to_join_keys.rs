@@ -62,7 +62,11 @@ pub(crate) fn convert_filters_to_join_keys(query: &mut MirQuery<'_>) -> ReadySet
                 | MirNodeInner::Extremum { group_by, .. }
                 | MirNodeInner::Distinct { group_by, .. }
                 | MirNodeInner::Paginate { group_by, .. }
-                | MirNodeInner::TopK { group_by, .. } => {
+                | MirNodeInner::TopK { group_by, .. }
+                | MirNodeInner::Window {
+                    partition_by: group_by,
+                    ..
+                } => {
                     if !(group_by.contains(&c1) && group_by.contains(&c2)) {
                         trace!(
                             "Columns in filter not in group_by of ancestor grouped node; can't \
@@ -104,6 +108,11 @@ pub(crate) fn convert_filters_to_join_keys(query: &mut MirQuery<'_>) -> ReadySet
                     // TODO: figure out what to do about left joins
                     continue 'filter;
                 }
+                MirNodeInner::FullJoin { .. } => {
+                    // Filters above a full join can't become join keys, since that would stop the
+                    // join from emitting null-padded rows for either side
+                    continue 'filter;
+                }
                 MirNodeInner::Union { .. } => {
                     // TODO: figure out what to do about unions
                     continue 'filter;
@@ -118,6 +118,7 @@ mod tests {
                 group_by: vec![],
                 output_column: Column::named("agg"),
                 kind: Aggregation::Sum,
+                distinct: false,
             },
         ));
         mir_graph[grp].add_owner(query_name.clone());
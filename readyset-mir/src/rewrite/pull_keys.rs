@@ -39,7 +39,11 @@ fn push_view_key(query: &mut MirQuery<'_>, node_idx: NodeIndex) -> ReadySetResul
         | MirNodeInner::Extremum { group_by, .. }
         | MirNodeInner::Distinct { group_by }
         | MirNodeInner::Paginate { group_by, .. }
-        | MirNodeInner::TopK { group_by, .. } => {
+        | MirNodeInner::TopK { group_by, .. }
+        | MirNodeInner::Window {
+            partition_by: group_by,
+            ..
+        } => {
             for ViewKeyColumn { column, op, .. } in &key {
                 invariant_eq!(
                     *op,
@@ -55,6 +59,9 @@ fn push_view_key(query: &mut MirQuery<'_>, node_idx: NodeIndex) -> ReadySetResul
         MirNodeInner::LeftJoin { .. } => unsupported!(
             "Parameters in subqueries on the right-hand side of LEFT JOIN not supported"
         ),
+        MirNodeInner::FullJoin { .. } => {
+            unsupported!("Parameters in subqueries on either side of FULL JOIN not supported")
+        }
         // TODO: we might support this already? Will have to see
         MirNodeInner::Union { .. } => {
             unsupported!("Parameters on one side of a UNION not yet supported")
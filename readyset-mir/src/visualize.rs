@@ -154,15 +154,26 @@ impl GraphViz for MirNodeInner {
                 ref on,
                 ref group_by,
                 ref kind,
+                distinct,
                 ..
             } => {
+                let distinct = if *distinct { "DISTINCT " } else { "" };
                 let op_string = match kind {
-                    AggregationKind::Count { .. } => format!("\\|*\\|({})", on),
-                    AggregationKind::Sum => format!("𝛴({})", on),
-                    AggregationKind::Avg => format!("AVG({})", on),
+                    AggregationKind::Count { .. } => format!("\\|*\\|({}{})", distinct, on),
+                    AggregationKind::Sum => format!("𝛴({}{})", distinct, on),
+                    AggregationKind::Avg => format!("AVG({}{})", distinct, on),
                     AggregationKind::GroupConcat { separator: s } => {
                         format!("\\|\\|({}, \\\"{}\\\")", on, s)
                     }
+                    AggregationKind::Percentile { percentile } => {
+                        format!("PCT({}, {})", on, percentile)
+                    }
+                    AggregationKind::ApproxCountDistinct => {
+                        format!("~\\|*\\|(DISTINCT {})", on)
+                    }
+                    AggregationKind::ApproxPercentile { percentile } => {
+                        format!("~PCT({}, {})", on, percentile)
+                    }
                 };
                 let group_cols = group_by.iter().join(", ");
                 write!(f, "{} | γ: {}", op_string, group_cols)
@@ -291,6 +302,10 @@ impl GraphViz for MirNodeInner {
                 let jc = on.iter().map(|(l, r)| format!("{}:{}", l, r)).join(", ");
                 write!(f, "⋉  | on: {}", jc)
             }
+            MirNodeInner::FullJoin { ref on, .. } => {
+                let jc = on.iter().map(|(l, r)| format!("{}:{}", l, r)).join(", ");
+                write!(f, "⟗  | on: {}", jc)
+            }
             MirNodeInner::DependentJoin { ref on, .. } => {
                 write!(
                     f,
@@ -337,6 +352,19 @@ impl GraphViz for MirNodeInner {
                     .unwrap_or_else(|| "".into());
                 write!(f, "TopK [k: {}; {}]", limit, order)
             }
+            MirNodeInner::Window {
+                ref function,
+                ref partition_by,
+                ref order,
+                ..
+            } => {
+                let order = order
+                    .iter()
+                    .map(|(c, o)| format!("{}: {}", c.name.as_str(), o))
+                    .join(", ");
+                let partition_cols = partition_by.iter().join(", ");
+                write!(f, "{}() | γ: {} | {}", function, partition_cols, order)
+            }
             MirNodeInner::Union {
                 ref emit,
                 ref duplicate_mode,
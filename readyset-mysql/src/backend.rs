@@ -1,13 +1,11 @@
-use core::fmt;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::convert::TryFrom;
-use std::fmt::Formatter;
 use std::ops::{Deref, DerefMut};
 
 use async_trait::async_trait;
 use futures_util::StreamExt;
-use itertools::{izip, Itertools};
+use itertools::izip;
 use mysql_async::consts::StatusFlags;
 use mysql_common::bigdecimal03::ToPrimitive;
 use mysql_srv::{
@@ -20,9 +18,10 @@ use readyset_adapter::backend::noria_connector::{
 use readyset_adapter::backend::{
     noria_connector, QueryResult, SinglePrepareResult, UpstreamPrepare,
 };
+use readyset_adapter::result_streaming::StreamedResultSize;
 use readyset_data::{DfType, DfValue, DfValueKind};
 use readyset_errors::{internal, ReadySetError};
-use readyset_util::redacted::Sensitive;
+use readyset_util::redacted::{log_parameters, Sensitive};
 use streaming_iterator::StreamingIterator;
 use tokio::io::{self, AsyncWrite};
 use tracing::{error, info, trace};
@@ -36,22 +35,6 @@ use crate::{Error, MySqlQueryHandler};
 
 /// Helper struct to correctly transform a binary type value into its correct [`String`]
 /// representation.
-// TODO(fran): We can't keep using the `Display` impl of `DfValue`, since types such as binary or
-//  byte array have different display formats depending on the database (Postgres even has two
-//  possible display formats based on configuration).
-//  This is a temporary workaround, since the actual fix might involve a bigger effort.
-struct BinaryDisplay<'a>(&'a [u8]);
-
-impl<'a> fmt::Display for BinaryDisplay<'a> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "0x{}",
-            self.0.iter().map(|byte| format!("{:02x}", byte)).join("")
-        )
-    }
-}
-
 async fn write_column<W: AsyncWrite + Unpin>(
     rw: &mut RowWriter<'_, W>,
     c: &DfValue,
@@ -87,20 +70,17 @@ async fn write_column<W: AsyncWrite + Unpin>(
                 rw.write_col(i as isize)
             }
         }
-        DfValue::Text(ref t) => {
-            if ty.is_binary() {
-                rw.write_col(BinaryDisplay(c.as_bytes()?).to_string())
-            } else {
-                rw.write_col(t.as_str())
-            }
-        }
-        DfValue::TinyText(ref t) => {
-            if ty.is_binary() {
-                rw.write_col(BinaryDisplay(c.as_bytes()?).to_string())
-            } else {
-                rw.write_col(t.as_str())
-            }
+        // Geometries are sent in MySQL's internal format, exactly as we received them from the
+        // upstream database
+        DfValue::Text(_) | DfValue::TinyText(_) | DfValue::ByteArray(_)
+            if cs.coltype == ColumnType::MYSQL_TYPE_GEOMETRY =>
+        {
+            rw.write_col(c.as_bytes()?)
         }
+        // Binary values are sent as raw bytes, in both the text and binary protocols
+        DfValue::Text(_) | DfValue::TinyText(_) if ty.is_binary() => rw.write_col(c.as_bytes()?),
+        DfValue::Text(ref t) => rw.write_col(t.as_str()),
+        DfValue::TinyText(ref t) => rw.write_col(t.as_str()),
         ref dt @ (DfValue::Float(..) | DfValue::Double(..)) => match cs.coltype {
             mysql_srv::ColumnType::MYSQL_TYPE_DECIMAL
             | mysql_srv::ColumnType::MYSQL_TYPE_NEWDECIMAL => {
@@ -145,7 +125,8 @@ async fn write_column<W: AsyncWrite + Unpin>(
             _ => return Err(conv_error())?,
         },
         DfValue::Time(ref t) => rw.write_col(t),
-        DfValue::ByteArray(ref bytes) => rw.write_col(BinaryDisplay(bytes.as_ref()).to_string()),
+        DfValue::ByteArray(ref bytes) => rw.write_col(bytes.as_slice()),
+        DfValue::Uuid(ref u) => rw.write_col(u.to_string()),
         // These types are PostgreSQL specific
         DfValue::Array(_) => {
             internal!("Cannot write MySQL column: MySQL does not support arrays")
@@ -153,6 +134,9 @@ async fn write_column<W: AsyncWrite + Unpin>(
         DfValue::BitVector(_) => {
             internal!("Cannot write MySQL column: MySQL does not support bit vectors")
         }
+        DfValue::Interval(_) => {
+            internal!("Cannot write MySQL column: MySQL does not support intervals")
+        }
         DfValue::PassThrough(_) => {
             internal!("Cannot write MySQL column: PassThrough types aren't supported for MySQL")
         }
@@ -357,9 +341,53 @@ macro_rules! handle_error {
     };
 }
 
+/// Ends the current row of the given [`RowWriter`], enforcing the row and result set size limits
+/// tracked by the given [`StreamedResultSize`] and flushing buffered rows to the client once enough
+/// of them have accumulated. If a limit is exceeded, the row is discarded and an error is written
+/// to the client in its place.
+/// Evaluate `$statement`, which executes a statement via the given backend, retrying it (after
+/// waiting for the backoff) as long as it fails with an upstream error which the backend says
+/// should be retried
+macro_rules! with_upstream_retries {
+    ($backend: expr, $statement: expr) => {{
+        let mut attempt = 0;
+        loop {
+            let error = match $statement {
+                Err(e) => e,
+                res => break res,
+            };
+            match $backend.upstream_retry_backoff(&error, attempt) {
+                Some(backoff) => {
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                None => break Err(error),
+            }
+        }
+    }};
+}
+
+macro_rules! end_row {
+    ($rw: expr, $size: expr) => {{
+        match $size.add_row($rw.row_len()) {
+            Ok(flush) => {
+                $rw.end_row().await?;
+                if flush {
+                    $rw.flush().await?;
+                }
+            }
+            Err(e) => {
+                let e = Error::from(e);
+                return $rw.error(e.error_kind(), e.to_string().as_bytes()).await;
+            }
+        }
+    }};
+}
+
 async fn handle_readyset_result<'a, W>(
     result: noria_connector::QueryResult<'a>,
     writer: QueryResultWriter<'_, W>,
+    mut size: StreamedResultSize,
 ) -> io::Result<()>
 where
     W: AsyncWrite + Unpin,
@@ -399,7 +427,7 @@ where
                         return handle_column_write_err(e, rw).await;
                     }
                 }
-                rw.end_row().await?;
+                end_row!(rw, size);
             }
             rw.finish().await
         }
@@ -409,6 +437,7 @@ where
 async fn handle_upstream_result<'a, W>(
     result: upstream::QueryResult<'a>,
     writer: QueryResultWriter<'_, W>,
+    mut size: StreamedResultSize,
 ) -> io::Result<()>
 where
     W: AsyncWrite + Unpin,
@@ -445,7 +474,7 @@ where
                 for (i, _) in row.columns_ref().iter().enumerate() {
                     rw.write_col(row.as_ref(i).expect("Must match column number"))?;
                 }
-                rw.end_row().await?;
+                end_row!(rw, size);
             }
 
             if let Some(status_flags) = stream.status_flags() {
@@ -465,7 +494,7 @@ where
                 for (i, _) in row.columns_ref().iter().enumerate() {
                     rw.write_col(row.as_ref(i).expect("Must match column number"))?;
                 }
-                rw.end_row().await?;
+                end_row!(rw, size);
             }
 
             if let Some(status_flags) = status_flags {
@@ -480,13 +509,14 @@ where
 async fn handle_query_result<'a, W>(
     result: Result<QueryResult<'a, MySqlUpstream>, Error>,
     writer: QueryResultWriter<'_, W>,
+    size: StreamedResultSize,
 ) -> io::Result<()>
 where
     W: AsyncWrite + Unpin,
 {
     match result {
-        Ok(QueryResult::Noria(result)) => handle_readyset_result(result, writer).await,
-        Ok(QueryResult::Upstream(result)) => handle_upstream_result(result, writer).await,
+        Ok(QueryResult::Noria(result)) => handle_readyset_result(result, writer, size).await,
+        Ok(QueryResult::Upstream(result)) => handle_upstream_result(result, writer, size).await,
         Err(error) => handle_error!(error, writer),
     }
 }
@@ -611,10 +641,20 @@ where
         };
 
         if self.enable_statement_logging {
-            info!(target: "client_statement", "Execute: {{id: {id}, params: {:?}}}", value_params)
+            // The values of parameters are only logged if explicitly enabled
+            if log_parameters() {
+                info!(target: "client_statement", "Execute: {{id: {id}, params: {:?}}}", value_params)
+            } else {
+                info!(
+                    target: "client_statement",
+                    "Execute: {{id: {id}, num_params: {}}}",
+                    value_params.len()
+                )
+            }
         }
 
-        match self.execute(id, &value_params).await {
+        let mut size = self.streamed_result_size();
+        match with_upstream_retries!(self, self.execute(id, &value_params).await) {
             Ok(QueryResult::Noria(noria_connector::QueryResult::Select { mut rows, schema })) => {
                 let CachedSchema {
                     mysql_schema,
@@ -652,11 +692,11 @@ where
                             return handle_column_write_err(e, rw).await;
                         };
                     }
-                    rw.end_row().await?;
+                    end_row!(rw, size);
                 }
                 rw.finish().await
             }
-            execute_result => handle_query_result(execute_result, results).await,
+            execute_result => handle_query_result(execute_result, results, size).await,
         }
     }
 
@@ -692,8 +732,9 @@ where
         if self.enable_statement_logging {
             info!(target: "client_statement", "Query: {query}");
         }
-        let query_result = self.query(query).await;
-        handle_query_result(query_result, results).await
+        let size = self.streamed_result_size();
+        let query_result = with_upstream_retries!(self, self.query(query).await);
+        handle_query_result(query_result, results, size).await
     }
 
     fn password_for_username(&self, username: &str) -> Option<Vec<u8>> {
@@ -704,6 +745,25 @@ where
         self.does_require_authentication()
     }
 
+    fn connection_id(&self) -> u32 {
+        // Connection ids are only used to cancel queries, so if this connection isn't registered
+        // any id will do
+        self.connection().map_or(0, |conn| conn.id())
+    }
+
+    fn on_connection_attributes(&mut self, attributes: &[(&str, &str)]) {
+        if let Some((_, program_name)) = attributes.iter().find(|(k, _)| *k == "program_name") {
+            self.noria.set_application_name(program_name);
+        }
+    }
+
+    async fn on_authenticated(&mut self, username: &str) -> io::Result<()> {
+        self.noria
+            .on_authenticated(username)
+            .await
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))
+    }
+
     fn version(&self) -> String {
         self.noria.version()
     }
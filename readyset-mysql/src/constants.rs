@@ -1,2 +1,6 @@
 /// The default character set to use when writing out column packets.
 pub static DEFAULT_CHARACTER_SET: u16 = mysql_async::consts::UTF8_GENERAL_CI;
+
+/// The character set of columns containing binary (byte string) values, which tells clients not
+/// to decode them as text.
+pub static BINARY_CHARACTER_SET: u16 = 63;
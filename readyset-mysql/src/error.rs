@@ -2,6 +2,7 @@ use std::io;
 
 use mysql_srv::MsqlSrvError;
 use readyset_adapter::upstream_database::IsFatalError;
+use readyset_adapter::upstream_retry::{ClassifyError, TransientErrorKind};
 use readyset_errors::ReadySetError;
 use thiserror::Error;
 
@@ -42,6 +43,23 @@ impl Error {
          */
         match self {
             Self::MySql(mysql_async::Error::Server(e)) => e.code.into(),
+            Self::ReadySet(ReadySetError::QueryTimeout { .. } | ReadySetError::QueryCancelled) => {
+                mysql_srv::ErrorKind::ER_QUERY_INTERRUPTED
+            }
+            Self::ReadySet(ReadySetError::UnknownConnection { .. }) => {
+                mysql_srv::ErrorKind::ER_NO_SUCH_THREAD
+            }
+            Self::ReadySet(ReadySetError::SelectPrivilegeDenied { .. }) => {
+                mysql_srv::ErrorKind::ER_TABLEACCESS_DENIED_ERROR
+            }
+            Self::ReadySet(
+                ReadySetError::ResultRowLimitExceeded { .. }
+                | ReadySetError::ResultSizeLimitExceeded { .. }
+                | ReadySetError::RowSizeLimitExceeded { .. },
+            ) => mysql_srv::ErrorKind::ER_TOO_BIG_SELECT,
+            Self::ReadySet(ReadySetError::ValueSizeLimitExceeded { .. }) => {
+                mysql_srv::ErrorKind::ER_DATA_TOO_LONG
+            }
             Self::MySql(_) => {
                 // TODO(peter): We need to translate these to appropriate
                 // mysql error codes. Currently mysql_async is only used by fallback.
@@ -57,3 +75,29 @@ impl IsFatalError for Error {
         matches!(self, Self::MySql(e) if e.is_fatal())
     }
 }
+
+impl ClassifyError for Error {
+    fn transient_kind(&self) -> Option<TransientErrorKind> {
+        match self {
+            Self::MySql(mysql_async::Error::Server(e)) => {
+                match mysql_srv::ErrorKind::from(e.code) {
+                    mysql_srv::ErrorKind::ER_LOCK_DEADLOCK => Some(TransientErrorKind::Deadlock),
+                    mysql_srv::ErrorKind::ER_LOCK_WAIT_TIMEOUT => {
+                        Some(TransientErrorKind::LockTimeout)
+                    }
+                    // Returned for writes when the server is running with --read-only or
+                    // --super-read-only
+                    mysql_srv::ErrorKind::ER_OPTION_PREVENTS_STATEMENT => {
+                        Some(TransientErrorKind::ReadOnly)
+                    }
+                    _ => None,
+                }
+            }
+            Self::MySql(
+                mysql_async::Error::Io(_)
+                | mysql_async::Error::Driver(mysql_async::DriverError::ConnectionClosed),
+            ) => Some(TransientErrorKind::ConnectionLost),
+            _ => None,
+        }
+    }
+}
@@ -3,14 +3,17 @@ use std::collections::HashSet;
 use std::str::FromStr;
 
 use lazy_static::lazy_static;
-use nom_sql::{Column, Expr, FieldDefinitionExpr, Literal, SqlIdentifier, SqlQuery, VariableScope};
+use nom_sql::{
+    Column, Expr, FieldDefinitionExpr, FunctionExpr, Literal, SelectStatement, SqlIdentifier,
+    SqlQuery, VariableScope,
+};
 use readyset_adapter::backend::noria_connector::QueryResult;
 use readyset_adapter::backend::SelectSchema;
 use readyset_adapter::{QueryHandler, SetBehavior};
 use readyset_client::results::Results;
 use readyset_client::ColumnSchema;
 use readyset_data::{DfType, DfValue};
-use readyset_errors::{ReadySetError, ReadySetResult};
+use readyset_errors::{unsupported, ReadySetError, ReadySetResult};
 use tracing::warn;
 
 const MAX_ALLOWED_PACKET_VARIABLE_NAME: &str = "max_allowed_packet";
@@ -813,17 +816,37 @@ lazy_static! {
     ]);
 }
 
+/// Returns true if the given statement either uses the `SQL_CALC_FOUND_ROWS` modifier or selects
+/// `FOUND_ROWS()`.
+///
+/// `FOUND_ROWS()` returns the number of rows matched by the previous `SQL_CALC_FOUND_ROWS` query on
+/// the same connection, which ReadySet doesn't compute, so both halves of the pattern are sent to
+/// the upstream database (which shares a connection with the client).
+fn uses_found_rows(stmt: &SelectStatement) -> bool {
+    stmt.calc_found_rows
+        || stmt.fields.iter().any(|field| {
+            matches!(field, FieldDefinitionExpr::Expr {
+                expr: Expr::Call(FunctionExpr::Call { name, arguments }),
+                ..
+            } if arguments.is_empty() && name.eq_ignore_ascii_case("found_rows"))
+        })
+}
+
 /// MySQL flavor of [`QueryHandler`].
 pub struct MySqlQueryHandler;
 
 impl QueryHandler for MySqlQueryHandler {
     fn requires_fallback(query: &SqlQuery) -> bool {
-        // Currently any query with variables requires a fallback
+        // Currently any query with variables, or which relies on `FOUND_ROWS()`, requires a
+        // fallback
         match query {
-            SqlQuery::Select(stmt) => stmt.fields.iter().any(|field| match field {
-                FieldDefinitionExpr::Expr { expr, .. } => expr.contains_vars(),
-                _ => false,
-            }),
+            SqlQuery::Select(stmt) => {
+                uses_found_rows(stmt)
+                    || stmt.fields.iter().any(|field| match field {
+                        FieldDefinitionExpr::Expr { expr, .. } => expr.contains_vars(),
+                        _ => false,
+                    })
+            }
             _ => false,
         }
     }
@@ -838,6 +861,9 @@ impl QueryHandler for MySqlQueryHandler {
         // If `@@max_allowed_packet` was not present in the fields, we return an empty set
         // of rows.
         match query {
+            SqlQuery::Select(stmt) if uses_found_rows(stmt) => {
+                unsupported!("SQL_CALC_FOUND_ROWS and FOUND_ROWS() require an upstream database")
+            }
             SqlQuery::Select(stmt)
                 if stmt.fields.iter().any(|field| {
                     matches!(field, FieldDefinitionExpr::Expr {
@@ -977,6 +1003,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn found_rows_requires_fallback() {
+        let requires_fallback = |q: &str| {
+            MySqlQueryHandler::requires_fallback(
+                &nom_sql::parse_query(nom_sql::Dialect::MySQL, q).unwrap(),
+            )
+        };
+        assert!(requires_fallback(
+            "SELECT SQL_CALC_FOUND_ROWS * FROM t WHERE x = 1 LIMIT 10"
+        ));
+        assert!(requires_fallback("SELECT FOUND_ROWS()"));
+        assert!(requires_fallback("SELECT found_rows() AS total"));
+        assert!(!requires_fallback("SELECT * FROM t WHERE x = 1 LIMIT 10"));
+    }
+
     #[test]
     fn all_required_sql_modes_are_allowed() {
         for mode in REQUIRED_SQL_MODES {
@@ -1,11 +1,11 @@
 #![warn(clippy::panic)]
 
-use nom_sql::{self, ColumnConstraint, Relation};
+use nom_sql::{self, ColumnConstraint, Relation, SqlType};
 use readyset_client::ColumnSchema;
 use readyset_data::DfType;
 use readyset_errors::{unsupported, ReadySetResult};
 
-use crate::constants::DEFAULT_CHARACTER_SET;
+use crate::constants::{BINARY_CHARACTER_SET, DEFAULT_CHARACTER_SET};
 
 pub(crate) fn convert_column(col: &ColumnSchema) -> ReadySetResult<mysql_srv::Column> {
     let mut colflags = mysql_srv::ColumnFlags::empty();
@@ -37,7 +37,21 @@ pub(crate) fn convert_column(col: &ColumnSchema) -> ReadySetResult<mysql_srv::Co
         }
         DfType::Bool => MYSQL_TYPE_BIT,
         DfType::DateTime { .. } => MYSQL_TYPE_DATETIME,
-        DfType::Blob => MYSQL_TYPE_BLOB,
+        // Spatial columns are stored as blobs, but clients need to know they're geometries to
+        // decode them
+        DfType::Blob
+            if col
+                .base
+                .as_ref()
+                .map_or(false, |b| matches!(b.sql_type, SqlType::Spatial(_))) =>
+        {
+            colflags |= mysql_srv::ColumnFlags::BINARY_FLAG;
+            MYSQL_TYPE_GEOMETRY
+        }
+        DfType::Blob => {
+            colflags |= mysql_srv::ColumnFlags::BINARY_FLAG;
+            MYSQL_TYPE_BLOB
+        }
         DfType::Char(..) => {
             // TODO(grfn): I'm not sure if this is right
             MYSQL_TYPE_STRING
@@ -60,9 +74,9 @@ pub(crate) fn convert_column(col: &ColumnSchema) -> ReadySetResult<mysql_srv::Co
             MYSQL_TYPE_VAR_STRING
         }
         DfType::Enum { .. } => {
-            // TODO(grfn): I don't know if this is right
+            // MySQL itself sends enum columns as strings, with the enum flag set
             colflags |= mysql_srv::ColumnFlags::ENUM_FLAG;
-            MYSQL_TYPE_VAR_STRING
+            MYSQL_TYPE_STRING
         }
         DfType::Time { .. } => MYSQL_TYPE_TIME,
         DfType::Json => MYSQL_TYPE_JSON,
@@ -70,6 +84,7 @@ pub(crate) fn convert_column(col: &ColumnSchema) -> ReadySetResult<mysql_srv::Co
         DfType::MacAddr => unsupported!("MySQL does not support the MACADDR type"),
         DfType::Inet => unsupported!("MySQL does not support the INET type"),
         DfType::Uuid => unsupported!("MySQL does not support the UUID type"),
+        DfType::Interval => unsupported!("MySQL does not support the INTERVAL type"),
         DfType::Jsonb => unsupported!("MySQL does not support the JSONB type"),
         DfType::Bit(size) => {
             if size < 64 {
@@ -125,6 +140,10 @@ pub(crate) fn convert_column(col: &ColumnSchema) -> ReadySetResult<mysql_srv::Co
         coltype,
         column_length,
         colflags,
-        character_set: DEFAULT_CHARACTER_SET,
+        character_set: if col.column_type.is_binary() {
+            BINARY_CHARACTER_SET
+        } else {
+            DEFAULT_CHARACTER_SET
+        },
     })
 }
@@ -7,6 +7,7 @@ use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use futures_util::future::BoxFuture;
 use futures_util::Stream;
 #[cfg(feature = "fallback_cache")]
 use futures_util::StreamExt;
@@ -15,11 +16,12 @@ use mysql_async::prelude::Queryable;
 use mysql_async::{
     Column, Conn, Opts, OptsBuilder, ResultSetStream, Row, SslOpts, TxOpts, UrlError,
 };
-use nom_sql::{SqlIdentifier, StartTransactionStatement};
+use nom_sql::{Relation, SqlIdentifier, StartTransactionStatement};
 use pin_project::pin_project;
 use readyset_adapter::fallback_cache::FallbackCache;
 #[cfg(feature = "fallback_cache")]
 use readyset_adapter::fallback_cache::FallbackCacheApi;
+use readyset_adapter::privileges::SelectPrivileges;
 use readyset_adapter::upstream_database::UpstreamDestination;
 use readyset_adapter::{UpstreamConfig, UpstreamDatabase, UpstreamPrepare};
 use readyset_client_metrics::QueryDestination;
@@ -388,6 +390,31 @@ impl UpstreamDatabase for MySqlUpstream {
         Ok(())
     }
 
+    async fn reconnect_to(&mut self, upstream_db_url: String) -> Result<(), Error> {
+        let mut upstream_config = self.upstream_config.clone();
+        upstream_config.upstream_db_url = Some(upstream_db_url.into());
+        #[cfg(feature = "fallback_cache")]
+        let new_self = Self::connect(upstream_config, self.fallback_cache.clone()).await?;
+        #[cfg(not(feature = "fallback_cache"))]
+        let new_self = Self::connect(upstream_config, None).await?;
+        let old_self = std::mem::replace(self, new_self);
+        let _ = old_self.conn.disconnect().await as Result<(), _>;
+        Ok(())
+    }
+
+    fn query_canceller(&self) -> BoxFuture<'static, Result<(), Error>> {
+        let opts = self.conn.opts().clone();
+        let connection_id = self.conn.id();
+        Box::pin(async move {
+            // A running query can only be killed from a different connection
+            let mut conn = Conn::new(opts).await?;
+            conn.query_drop(format!("KILL QUERY {connection_id}"))
+                .await?;
+            conn.disconnect().await?;
+            Ok(())
+        })
+    }
+
     /// Prepares the given query using the mysql connection. Note, queries are prepared on a
     /// per connection basis. They are not universal.
     async fn prepare<'a, 'b, S>(
@@ -578,7 +605,7 @@ impl UpstreamDatabase for MySqlUpstream {
                 .map(|row: (String, String)| row.1)
             {
                 dump.push_str(&create);
-                dump.push('\n');
+                dump.push_str(";\n");
             }
         }
         Ok(dump.into_bytes())
@@ -587,4 +614,48 @@ impl UpstreamDatabase for MySqlUpstream {
     async fn schema_search_path(&mut self) -> Result<Vec<SqlIdentifier>, Self::Error> {
         Ok(self.database().into_iter().map(|s| s.into()).collect())
     }
+
+    async fn select_privileges(&mut self) -> Result<SelectPrivileges, Self::Error> {
+        // Privileges are granted to `user`@`host` pairs, but clients authenticate to the adapter
+        // by username alone, so the privileges granted to a user from every host are merged.
+        // Privileges granted via roles aren't read, so queries relying on them are always proxied
+        let mut privileges = SelectPrivileges::default();
+
+        let global: Vec<String> = self
+            .conn
+            .query("SELECT User FROM mysql.user WHERE Select_priv = 'Y'")
+            .await?;
+        for user in global {
+            privileges.users.entry(user).or_default().all_tables = true;
+        }
+
+        let databases: Vec<(String, String)> = self
+            .conn
+            .query("SELECT User, Db FROM mysql.db WHERE Select_priv = 'Y'")
+            .await?;
+        for (user, db) in databases {
+            privileges.users.entry(user).or_default().schemas.push(db);
+        }
+
+        let tables: Vec<(String, String, String)> = self
+            .conn
+            .query(
+                "SELECT User, Db, Table_name FROM mysql.tables_priv \
+                 WHERE FIND_IN_SET('Select', Table_priv) > 0",
+            )
+            .await?;
+        for (user, db, table) in tables {
+            privileges
+                .users
+                .entry(user)
+                .or_default()
+                .tables
+                .insert(Relation {
+                    schema: Some(db.into()),
+                    name: table.into(),
+                });
+        }
+
+        Ok(privileges)
+    }
 }
@@ -1,5 +1,9 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use mysql_async::prelude::*;
 use readyset_adapter::backend::UnsupportedSetMode;
+use readyset_adapter::connection_registry::ConnectionRegistry;
 use readyset_adapter::BackendBuilder;
 use readyset_client::query::QueryId;
 use readyset_client_metrics::QueryDestination;
@@ -902,6 +906,80 @@ async fn replication_failure_ignores_table() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+async fn query_timeout_cancels_upstream_query() {
+    let (opts, _handle, shutdown_tx) = setup_with(
+        BackendBuilder::new()
+            .require_authentication(false)
+            .query_timeout(Some(Duration::from_millis(500))),
+    )
+    .await;
+    let mut conn = mysql_async::Conn::new(opts).await.unwrap();
+
+    let start = Instant::now();
+    let err = conn.query_drop("SELECT SLEEP(30)").await.unwrap_err();
+    assert!(start.elapsed() < Duration::from_secs(10));
+    match err {
+        mysql_async::Error::Server(e) => {
+            assert_eq!(e.code, mysql_srv::ErrorKind::ER_QUERY_INTERRUPTED as u16)
+        }
+        e => panic!("Expected a server error, got {e}"),
+    }
+
+    // The connection should still be usable after the query is cancelled
+    let row: Option<(i32,)> = conn.query_first("SELECT 1").await.unwrap();
+    assert_eq!(row, Some((1,)));
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+async fn kill_query_cancels_query_on_other_connection() {
+    let (opts, _handle, shutdown_tx) = setup_with(
+        BackendBuilder::new()
+            .require_authentication(false)
+            .connection_registry(Arc::new(ConnectionRegistry::new())),
+    )
+    .await;
+    let mut conn = mysql_async::Conn::new(opts.clone()).await.unwrap();
+    let mut killer = mysql_async::Conn::new(opts).await.unwrap();
+    let id = conn.id();
+    assert_ne!(id, killer.id());
+
+    let start = Instant::now();
+    let query = tokio::spawn(async move {
+        let res = conn.query_drop("SELECT SLEEP(30)").await;
+        (conn, res)
+    });
+    sleep().await;
+    killer.query_drop(format!("KILL QUERY {id}")).await.unwrap();
+
+    let (mut conn, res) = query.await.unwrap();
+    assert!(start.elapsed() < Duration::from_secs(10));
+    match res.unwrap_err() {
+        mysql_async::Error::Server(e) => {
+            assert_eq!(e.code, mysql_srv::ErrorKind::ER_QUERY_INTERRUPTED as u16)
+        }
+        e => panic!("Expected a server error, got {e}"),
+    }
+
+    // The connection should still be usable after the query is cancelled
+    let row: Option<(i32,)> = conn.query_first("SELECT 1").await.unwrap();
+    assert_eq!(row, Some((1,)));
+
+    // Killing a connection that doesn't exist is an error
+    match killer.query_drop("KILL QUERY 999999").await.unwrap_err() {
+        mysql_async::Error::Server(e) => {
+            assert_eq!(e.code, mysql_srv::ErrorKind::ER_NO_SUCH_THREAD as u16)
+        }
+        e => panic!("Expected a server error, got {e}"),
+    }
+
+    shutdown_tx.shutdown().await;
+}
+
 #[allow(dead_code)]
 async fn last_statement_matches(dest: &str, status: &str, client: &mut mysql_async::Conn) -> bool {
     let rows: Vec<(String, String)> = client
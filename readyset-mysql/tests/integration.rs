@@ -201,6 +201,41 @@ async fn delete_bogus() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn affected_rows_for_noop_writes() {
+    let (opts, _handle, shutdown_tx) = setup().await;
+    let mut conn = mysql_async::Conn::new(opts).await.unwrap();
+    conn.query_drop("CREATE TABLE Cats (id int PRIMARY KEY, name VARCHAR(255))")
+        .await
+        .unwrap();
+    sleep().await;
+
+    conn.query_drop("INSERT INTO Cats (id, name) VALUES (1, \"Bob\")")
+        .await
+        .unwrap();
+    sleep().await;
+
+    {
+        // Like MySQL, setting a column to its current value doesn't count as affecting the row
+        let updated = conn
+            .query_iter("UPDATE Cats SET Cats.name = \"Bob\" WHERE Cats.id = 1")
+            .await
+            .unwrap();
+        assert_eq!(updated.affected_rows(), 0);
+    }
+
+    {
+        let deleted = conn
+            .query_iter("DELETE FROM Cats WHERE Cats.id = 1 OR Cats.id = 2")
+            .await
+            .unwrap();
+        // There's no row with id 2 to delete
+        assert_eq!(deleted.affected_rows(), 1);
+    }
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn delete_bogus_valid_and() {
     let (opts, _handle, shutdown_tx) = setup().await;
@@ -288,9 +323,25 @@ async fn delete_other_column() {
         .unwrap();
     sleep().await;
 
-    conn.query_drop("DELETE FROM Cats WHERE Cats.id = 1 OR Cats.name = \"Bob\"")
+    for (id, name) in [(1, "Alice"), (2, "Bob"), (3, "Bob"), (4, "Carol")] {
+        conn.query_drop(format!(
+            "INSERT INTO Cats (id, name) VALUES ({id}, \"{name}\")"
+        ))
         .await
-        .unwrap_err();
+        .unwrap();
+    }
+    sleep().await;
+
+    let deleted = conn
+        .query_iter("DELETE FROM Cats WHERE Cats.id = 1 OR Cats.name = \"Bob\"")
+        .await
+        .unwrap();
+    assert_eq!(deleted.affected_rows(), 3);
+    drop(deleted);
+    sleep().await;
+
+    let remaining: Vec<i32> = conn.query("SELECT Cats.id FROM Cats").await.unwrap();
+    assert_eq!(remaining, vec![4]);
 
     shutdown_tx.shutdown().await;
 }
@@ -645,8 +696,39 @@ async fn update_other_column() {
         .unwrap();
     sleep().await;
 
-    let query = "UPDATE Cats SET Cats.name = \"Rusty\" WHERE Cats.name = \"Bob\"";
-    conn.query_drop(query).await.unwrap_err();
+    for (id, name) in [(1, "Bob"), (2, "Bob"), (3, "Carol")] {
+        conn.query_drop(format!(
+            "INSERT INTO Cats (id, name) VALUES ({id}, \"{name}\")"
+        ))
+        .await
+        .unwrap();
+    }
+    sleep().await;
+
+    let updated = conn
+        .exec_iter(
+            "UPDATE Cats SET Cats.name = ? WHERE Cats.name = ?",
+            ("Rusty", "Bob"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.affected_rows(), 2);
+    drop(updated);
+    sleep().await;
+
+    let mut rows: Vec<(i32, String)> = conn
+        .query("SELECT Cats.id, Cats.name FROM Cats")
+        .await
+        .unwrap();
+    rows.sort();
+    assert_eq!(
+        rows,
+        vec![
+            (1, "Rusty".to_owned()),
+            (2, "Rusty".to_owned()),
+            (3, "Carol".to_owned())
+        ]
+    );
 
     shutdown_tx.shutdown().await;
 }
@@ -1833,6 +1915,59 @@ async fn simple_nonblocking_select() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn create_cache_with_warmup() {
+    let (opts, _handle, shutdown_tx) = TestBuilder::default()
+        .read_behavior(ReadBehavior::NonBlocking)
+        .build::<MySQLAdapter>()
+        .await;
+    let mut conn = mysql_async::Conn::new(opts).await.unwrap();
+    conn.query_drop("CREATE TABLE test (x int, y int)")
+        .await
+        .unwrap();
+    conn.query_drop("INSERT INTO test (x, y) VALUES (4, 2), (5, 3)")
+        .await
+        .unwrap();
+    sleep().await;
+
+    conn.query_drop("CREATE CACHE FROM SELECT y FROM test WHERE x = ? WITH WARMUP (4, 5)")
+        .await
+        .unwrap();
+
+    // Non-blocking reads of the warmed up keys hit immediately, without waiting for an upquery
+    let rows: Vec<i32> = conn
+        .exec("SELECT y FROM test WHERE x = ?", (5,))
+        .await
+        .unwrap();
+    assert_eq!(rows, vec![3]);
+    assert_eq!(
+        last_query_info(&mut conn).await.destination,
+        QueryDestination::Readyset
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn drop_cache_if_exists() {
+    let (opts, _handle, shutdown_tx) = setup().await;
+    let mut conn = mysql_async::Conn::new(opts).await.unwrap();
+    conn.query_drop("CREATE TABLE test (x int, y int)")
+        .await
+        .unwrap();
+    sleep().await;
+
+    conn.query_drop("CREATE CACHE q FROM SELECT y FROM test WHERE x = ?")
+        .await
+        .unwrap();
+    conn.query_drop("DROP CACHE q").await.unwrap();
+
+    conn.query_drop("DROP CACHE q").await.unwrap_err();
+    conn.query_drop("DROP CACHE IF EXISTS q").await.unwrap();
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn switch_database_with_use() {
     let (opts, _handle, shutdown_tx) = setup().await;
@@ -1993,3 +2128,101 @@ async fn test_proxied_queries_telemetry() {
 
     shutdown_tx.shutdown().await;
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn durable_deployment_http_lookup() {
+    readyset_tracing::init_test_logging();
+    let deployment = TestBuilder::default()
+        .durable_base_tables(true)
+        .http_router(true)
+        .build_deployment::<MySQLAdapter>()
+        .await;
+    let mut conn = mysql_async::Conn::new(deployment.connection_opts)
+        .await
+        .unwrap();
+
+    conn.query_drop("CREATE TABLE t (id int primary key, val int)")
+        .await
+        .unwrap();
+    conn.query_drop("INSERT INTO t (id, val) VALUES (1, 10), (2, 20)")
+        .await
+        .unwrap();
+    sleep().await;
+    conn.query_drop("CREATE CACHE q FROM SELECT val FROM t WHERE id = ?")
+        .await
+        .unwrap();
+
+    let rows: Vec<i32> = conn
+        .exec("SELECT val FROM t WHERE id = ?", (1,))
+        .await
+        .unwrap();
+    assert_eq!(rows, vec![10]);
+
+    let http_addr = deployment.http_addr.unwrap();
+    let lookup = |etag: Option<reqwest::header::HeaderValue>| async move {
+        let mut request = reqwest::Client::new()
+            .post(format!("http://{http_addr}/lookup"))
+            .json(&serde_json::json!({ "cache": "q", "keys": [[1], [2]] }));
+        if let Some(etag) = etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        request.send().await.unwrap()
+    };
+
+    let res = lookup(None).await;
+    assert_eq!(res.status(), 200);
+    let etag = res.headers()[reqwest::header::ETAG].clone();
+    let before: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(before[0]["rows"], serde_json::json!([{ "val": 10 }]));
+    assert_eq!(before[1]["rows"], serde_json::json!([{ "val": 20 }]));
+
+    // Nothing has changed, so the results don't need to be sent again
+    assert_eq!(lookup(Some(etag.clone())).await.status(), 304);
+
+    conn.query_drop("UPDATE t SET val = 30 WHERE id = 2")
+        .await
+        .unwrap();
+    sleep().await;
+
+    let res = lookup(Some(etag)).await;
+    assert_eq!(res.status(), 200);
+    let after: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(after[0]["version"], before[0]["version"]);
+    assert_ne!(after[1]["version"], before[1]["version"]);
+    assert_eq!(after[1]["rows"], serde_json::json!([{ "val": 30 }]));
+
+    deployment.shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn enum_column() {
+    let (opts, _handle, shutdown_tx) = setup().await;
+    let mut conn = mysql_async::Conn::new(opts).await.unwrap();
+
+    conn.query_drop(
+        "CREATE TABLE shirts (id INT PRIMARY KEY, size ENUM('small', 'medium', 'large'))",
+    )
+    .await
+    .unwrap();
+    conn.query_drop(
+        "INSERT INTO shirts (id, size) VALUES (1, 'large'), (2, 'small'), (3, 'medium')",
+    )
+    .await
+    .unwrap();
+    sleep().await;
+
+    let rows: Vec<(i32, String)> = conn
+        .exec("SELECT id, size FROM shirts WHERE size = ?", ("medium",))
+        .await
+        .unwrap();
+    assert_eq!(rows, vec![(3, "medium".to_owned())]);
+
+    // Enums are ordered by the position of their variants, not alphabetically
+    let rows: Vec<String> = conn
+        .query("SELECT size FROM shirts ORDER BY size")
+        .await
+        .unwrap();
+    assert_eq!(rows, vec!["small", "medium", "large"]);
+
+    shutdown_tx.shutdown().await;
+}
@@ -1,6 +1,10 @@
+use std::sync::Arc;
+
+use futures_util::future::try_join_all;
 use mysql_async::prelude::*;
 use mysql_async::{Conn, Result, Row, Statement};
 use readyset_adapter::backend::{MigrationMode, QueryInfo, UnsupportedSetMode};
+use readyset_adapter::migration_queue::MigrationQueue;
 use readyset_adapter::query_status_cache::QueryStatusCache;
 use readyset_adapter::BackendBuilder;
 use readyset_client_metrics::QueryDestination;
@@ -609,6 +613,48 @@ async fn in_request_path_rewritten_query_without_fallback() {
     shutdown_tx.shutdown().await;
 }
 
+// With in_request_path migration, many connections executing the same new query at once should
+// share a single migration through the migration queue, rather than each migrating it.
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+async fn in_request_path_concurrent_migrations() {
+    let query_status_cache: &'static _ = Box::leak(Box::new(QueryStatusCache::new()));
+    let migration_queue = Arc::new(MigrationQueue::new(1));
+    let (opts, _handle, shutdown_tx) = TestBuilder::new(
+        BackendBuilder::default()
+            .require_authentication(false)
+            .migration_queue(Arc::clone(&migration_queue)),
+    )
+    .fallback(false)
+    .query_status_cache(query_status_cache)
+    .migration_mode(MigrationMode::InRequestPath)
+    .build::<MySQLAdapter>()
+    .await;
+
+    let mut conn = Conn::new(opts.clone()).await.unwrap();
+    conn.query_drop("CREATE TABLE t (a INT, b INT)")
+        .await
+        .unwrap();
+    sleep().await;
+
+    let results = try_join_all((0..8).map(|_| {
+        let opts = opts.clone();
+        async move {
+            let mut conn = Conn::new(opts).await?;
+            conn.query::<Row, _>("SELECT * FROM t WHERE a = 1").await
+        }
+    }))
+    .await;
+    results.unwrap();
+    assert!(migration_queue.is_empty());
+    assert_eq!(query_status_cache.allow_list().len(), 1);
+
+    let caches: Vec<Row> = conn.query("SHOW CACHES").await.unwrap();
+    assert_eq!(caches.len(), 1);
+
+    shutdown_tx.shutdown().await;
+}
+
 // With the out_of_band query mode without fallback, queries that are not
 // explicitely migrated are denied. We verify that two queries end up being
 // cached as the same query in the query status cache. Otherwise, the second
@@ -6,11 +6,14 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use clap::ValueEnum;
 use eui48::MacAddressFormat;
+use futures::future;
 use postgres_types::Type;
 use psql_srv as ps;
 use readyset_adapter::backend as cl;
+use readyset_adapter::result_streaming::StreamedResultSize;
 use readyset_data::DfValue;
 use thiserror::Error;
+use tracing::debug;
 
 use crate::error::Error;
 use crate::query_handler::PostgreSqlQueryHandler;
@@ -114,6 +117,30 @@ impl ps::Backend for Backend {
             .map(|pw| ps::Credentials::CleartextPassword(pw))
     }
 
+    fn on_application_name(&mut self, application_name: &str) {
+        self.inner.set_application_name(application_name)
+    }
+
+    async fn on_authenticated(&mut self, user: &str) -> Result<(), ps::Error> {
+        Ok(self.inner.on_authenticated(user).await?)
+    }
+
+    fn cancel_key(&self) -> Option<(i32, i32)> {
+        self.inner
+            .connection()
+            .map(|conn| (conn.id() as i32, conn.secret_key() as i32))
+    }
+
+    async fn on_cancel_request(&mut self, process_id: i32, secret_key: i32) {
+        let cancelled = self.inner.connection().map_or(false, |conn| {
+            conn.registry()
+                .cancel_with_key(process_id as u32, secret_key as u32)
+        });
+        if !cancelled {
+            debug!(%process_id, "Ignoring cancel request for unknown connection");
+        }
+    }
+
     async fn on_init(&mut self, _database: &str) -> Result<ps::CredentialsNeeded, ps::Error> {
         if self.does_require_authentication() {
             match self.authentication_method {
@@ -126,7 +153,21 @@ impl ps::Backend for Backend {
     }
 
     async fn on_query(&mut self, query: &str) -> Result<ps::QueryResponse<Resultset>, ps::Error> {
-        self.query(query).await?.try_into()
+        let size = self.streamed_result_size();
+        let mut attempt = 0;
+        loop {
+            let error = match self.query(query).await {
+                Ok(res) => return Ok(with_size_limits(res.try_into()?, size)),
+                Err(e) => e,
+            };
+            match self.inner.upstream_retry_backoff(&error, attempt) {
+                Some(backoff) => {
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                None => return Err(error.into()),
+            }
+        }
     }
 
     async fn on_prepare(
@@ -149,12 +190,56 @@ impl ps::Backend for Backend {
             .iter()
             .map(|p| ParamRef(p).try_into())
             .collect::<Result<Vec<DfValue>, ps::Error>>()?;
-        self.execute(statement_id, &params).await?.try_into()
+        let size = self.streamed_result_size();
+        let mut attempt = 0;
+        loop {
+            let error = match self.execute(statement_id, &params).await {
+                Ok(res) => return Ok(with_size_limits(res.try_into()?, size)),
+                Err(e) => e,
+            };
+            match self.inner.upstream_retry_backoff(&error, attempt) {
+                Some(backoff) => {
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                None => return Err(error.into()),
+            }
+        }
     }
 
     async fn on_close(&mut self, _statement_id: u32) -> Result<(), ps::Error> {
         Ok(())
     }
+
+    fn transaction_status(&self) -> ps::TransactionStatus {
+        match self.inner.transaction_status() {
+            cl::TransactionStatus::Idle => ps::TransactionStatus::Idle,
+            cl::TransactionStatus::InTransaction => ps::TransactionStatus::InTransaction,
+            cl::TransactionStatus::Failed => ps::TransactionStatus::Failed,
+        }
+    }
+
+    async fn next_notification(&mut self) -> ps::Notification {
+        match self.inner.upstream_mut() {
+            Some(upstream) => upstream.next_notification().await,
+            None => future::pending().await,
+        }
+    }
+}
+
+/// Enforce the configured row and result set size limits on the rows of `response`, if it's a
+/// select response
+fn with_size_limits(
+    response: ps::QueryResponse<Resultset>,
+    size: StreamedResultSize,
+) -> ps::QueryResponse<Resultset> {
+    match response {
+        ps::QueryResponse::Select { schema, resultset } => ps::QueryResponse::Select {
+            schema,
+            resultset: resultset.with_size_limits(size),
+        },
+        response => response,
+    }
 }
 
 /// A simple wrapper around a request parameter `psql_srv::Value` reference, facilitiating
@@ -189,7 +274,8 @@ impl TryFrom<ParamRef<'_>> for DfValue {
             ps::Value::ByteArray(b) => Ok(DfValue::ByteArray(Arc::new(b.clone()))),
             ps::Value::MacAddress(m) => Ok(DfValue::from(m.to_string(MacAddressFormat::HexString))),
             ps::Value::Inet(ip) => Ok(DfValue::from(ip.to_string())),
-            ps::Value::Uuid(uuid) => Ok(DfValue::from(uuid.to_string())),
+            ps::Value::Uuid(uuid) => Ok(DfValue::from(*uuid)),
+            ps::Value::Interval(i) => Ok(DfValue::from(*i)),
             ps::Value::Json(v) | ps::Value::Jsonb(v) => Ok(DfValue::from(v.to_string())),
             ps::Value::Bit(bits) | ps::Value::VarBit(bits) => Ok(DfValue::from(bits.clone())),
             ps::Value::Array(arr, _) => Ok(DfValue::from(arr.clone())),
@@ -2,8 +2,10 @@ use std::io;
 
 use psql_srv as ps;
 use readyset_adapter::upstream_database::IsFatalError;
+use readyset_adapter::upstream_retry::{ClassifyError, TransientErrorKind};
 use readyset_errors::ReadySetError;
 use thiserror::Error;
+use tokio_postgres::error::SqlState;
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -27,6 +29,18 @@ impl From<Error> for ps::Error {
                 ps::Error::MissingPreparedStatement(statement_id.to_string())
             }
             ReadySet(ReadySetError::Unsupported(s)) => ps::Error::Unsupported(s),
+            ReadySet(e @ ReadySetError::QueryTimeout { .. }) => {
+                ps::Error::QueryTimeout(e.to_string())
+            }
+            ReadySet(e @ ReadySetError::QueryCancelled) => ps::Error::QueryCancelled(e.to_string()),
+            ReadySet(e @ ReadySetError::SelectPrivilegeDenied { .. }) => {
+                ps::Error::InsufficientPrivilege(e.to_string())
+            }
+            ReadySet(
+                e @ (ReadySetError::ResultRowLimitExceeded { .. }
+                | ReadySetError::ResultSizeLimitExceeded { .. }
+                | ReadySetError::RowSizeLimitExceeded { .. }),
+            ) => ps::Error::ResultLimitExceeded(e.to_string()),
             ReadySet(e) => ps::Error::Unknown(e.to_string()),
             PostgreSql(e) => e.into(),
         }
@@ -40,3 +54,47 @@ impl IsFatalError for Error {
         matches!(self, Self::PostgreSql(e) if e.is_closed())
     }
 }
+
+impl ClassifyError for Error {
+    fn transient_kind(&self) -> Option<TransientErrorKind> {
+        let Self::PostgreSql(e) = self else {
+            return None;
+        };
+        if e.is_closed() {
+            return Some(TransientErrorKind::ConnectionLost);
+        }
+        match e.code()? {
+            c if *c == SqlState::T_R_DEADLOCK_DETECTED => Some(TransientErrorKind::Deadlock),
+            c if *c == SqlState::LOCK_NOT_AVAILABLE => Some(TransientErrorKind::LockTimeout),
+            c if *c == SqlState::T_R_SERIALIZATION_FAILURE => {
+                Some(TransientErrorKind::SerializationFailure)
+            }
+            c if *c == SqlState::READ_ONLY_SQL_TRANSACTION => Some(TransientErrorKind::ReadOnly),
+            _ => None,
+        }
+    }
+
+    fn fails_transaction(&self) -> bool {
+        // Postgres aborts a transaction as soon as any statement in it fails, including statements
+        // which are cancelled
+        matches!(
+            self,
+            Self::PostgreSql(_)
+                | Self::ReadySet(
+                    ReadySetError::QueryTimeout { .. } | ReadySetError::QueryCancelled
+                )
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fails_transaction() {
+        assert!(Error::ReadySet(ReadySetError::QueryCancelled).fails_transaction());
+        assert!(!Error::ReadySet(ReadySetError::Unsupported("x".into())).fails_transaction());
+        assert!(!Error::Io(io::Error::from(io::ErrorKind::Other)).fails_transaction());
+    }
+}
@@ -1,10 +1,13 @@
+use std::mem;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use futures::{ready, Stream};
 use psql_srv as ps;
+use readyset_adapter::result_streaming::StreamedResultSize;
 use readyset_client::results::ResultIterator;
+use readyset_data::DfValue;
 use tokio_postgres::types::Type;
 use tokio_postgres::{GenericResult, ResultStream};
 
@@ -29,6 +32,11 @@ pub struct Resultset {
 
     /// The data types of the projected fields for each row.
     project_field_types: Arc<Vec<Type>>,
+
+    /// If set, used to enforce limits on the size of the rows in the resultset as they're
+    /// written to the client. Rows are flushed to the client as they're written by `psql_srv`, so
+    /// the flush signal from this is ignored.
+    size: Option<StreamedResultSize>,
 }
 
 impl Resultset {
@@ -36,6 +44,7 @@ impl Resultset {
         Self {
             results: ResultsetInner::Empty,
             project_field_types: Arc::new(vec![]),
+            size: None,
         }
     }
 
@@ -55,6 +64,7 @@ impl Resultset {
         Ok(Resultset {
             results: ResultsetInner::ReadySet(Box::new(results.into_iter())),
             project_field_types,
+            size: None,
         })
     }
 
@@ -69,16 +79,39 @@ impl Resultset {
                 stream,
             },
             project_field_types: Arc::new(schema),
+            size: None,
+        }
+    }
+
+    /// Enforce the row and result set size limits tracked by `size` on the rows in this
+    /// resultset. Once a limit is exceeded, the resultset yields an error and ends.
+    pub fn with_size_limits(self, size: StreamedResultSize) -> Self {
+        Self {
+            size: Some(size),
+            ..self
         }
     }
 }
 
+/// Returns an estimate of the number of bytes needed to send the given row to the client
+fn row_size(values: &[DfValue]) -> usize {
+    values
+        .iter()
+        .map(|v| match v {
+            DfValue::Text(_) | DfValue::TinyText(_) => v.as_str().map_or(0, str::len),
+            DfValue::ByteArray(bytes) => bytes.len(),
+            _ => mem::size_of::<DfValue>(),
+        })
+        .sum()
+}
+
 impl Stream for Resultset {
     type Item = Result<Row, psql_srv::Error>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let project_field_types = self.project_field_types.clone();
-        let next = match &mut self.get_mut().results {
+        let this = self.get_mut();
+        let next = match &mut this.results {
             ResultsetInner::Empty => None,
             ResultsetInner::ReadySet(i) => i.next().map(Ok),
             ResultsetInner::Stream { first_row, stream } => {
@@ -112,12 +145,22 @@ impl Stream for Resultset {
             }
         };
 
-        Poll::Ready(next.map(|values| {
+        let next = next.map(|values: Result<Vec<DfValue>, psql_srv::Error>| {
+            let values = values?;
+            if let Some(size) = &mut this.size {
+                if let Err(e) = size.add_row(row_size(&values)) {
+                    // Don't send any more rows once a limit has been exceeded
+                    this.results = ResultsetInner::Empty;
+                    return Err(crate::Error::from(e).into());
+                }
+            }
             Ok(Row {
-                values: values?,
+                values,
                 project_field_types,
             })
-        }))
+        });
+
+        Poll::Ready(next)
     }
 }
 
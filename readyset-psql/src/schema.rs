@@ -113,6 +113,7 @@ pub fn type_to_pgsql(col_type: &DfType) -> Result<pgsql::types::Type, Error> {
         DfType::MacAddr => Ok(Type::MACADDR),
         DfType::Inet => Ok(Type::INET),
         DfType::Uuid => Ok(Type::UUID),
+        DfType::Interval => Ok(Type::INTERVAL),
         DfType::Bit(_) => Ok(Type::BIT),
         DfType::VarBit(_) => Ok(Type::VARBIT),
         DfType::Array(box DfType::Unknown) => {
@@ -177,6 +178,7 @@ pub fn type_to_pgsql(col_type: &DfType) -> Result<pgsql::types::Type, Error> {
         DfType::Array(box DfType::MacAddr) => Ok(Type::MACADDR_ARRAY),
         DfType::Array(box DfType::Inet) => Ok(Type::INET_ARRAY),
         DfType::Array(box DfType::Uuid) => Ok(Type::UUID_ARRAY),
+        DfType::Array(box DfType::Interval) => Ok(Type::INTERVAL_ARRAY),
         DfType::Array(box DfType::Bit(_)) => Ok(Type::BIT_ARRAY),
         DfType::Array(box DfType::VarBit(_)) => Ok(Type::VARBIT_ARRAY),
         DfType::Array(box DfType::Array(_)) => unsupported_type!(),
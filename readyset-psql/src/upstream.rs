@@ -7,19 +7,23 @@ use std::str::FromStr;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use futures::StreamExt;
-use nom_sql::{SqlIdentifier, StartTransactionStatement};
+use futures::future::{self, BoxFuture};
+use futures::{stream, StreamExt};
+use nom_sql::{Relation, SqlIdentifier, StartTransactionStatement};
 use pgsql::config::Host;
 use pgsql::types::Type;
-use pgsql::{GenericResult, ResultStream, Row, SimpleQueryMessage};
+use pgsql::{AsyncMessage, GenericResult, ResultStream, Row, SimpleQueryMessage};
 use postgres_types::Kind;
-use psql_srv::Column;
+use psql_srv::{Column, Notification};
 use readyset_adapter::fallback_cache::FallbackCache;
+use readyset_adapter::privileges::SelectPrivileges;
 use readyset_adapter::upstream_database::UpstreamDestination;
 use readyset_adapter::{UpstreamConfig, UpstreamDatabase, UpstreamPrepare};
 use readyset_data::DfValue;
 use readyset_errors::{internal_err, invariant_eq, unsupported, ReadySetError, ReadySetResult};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::process::Command;
+use tokio::sync::mpsc;
 use tokio_postgres as pgsql;
 use tracing::{debug, info, info_span};
 use tracing_futures::Instrument;
@@ -36,6 +40,12 @@ pub struct PostgreSqlUpstream {
     client: pgsql::Client,
     /// A tokio task that handles the connection, required by `tokio_postgres` to operate
     _connection_handle: tokio::task::JoinHandle<Result<(), pgsql::Error>>,
+    /// Asynchronous notifications received on the connection, as a result of `LISTEN` statements
+    /// sent to the upstream database
+    notifications: mpsc::UnboundedReceiver<pgsql::Notification>,
+    /// The TLS connector used to connect to the upstream, which is also needed to send requests to
+    /// cancel running queries
+    tls: postgres_native_tls::MakeTlsConnector,
     /// Map from prepared statement IDs to prepared statements
     prepared_statements: HashMap<u32, pgsql::Statement>,
     /// ID for the next prepared statement
@@ -169,6 +179,49 @@ fn convert_params_for_upstream(params: &[DfValue], types: &[Type]) -> ReadySetRe
         .collect()
 }
 
+/// Drive the given connection to the upstream database until it closes, forwarding any
+/// asynchronous notifications received on it to `notifications`
+async fn drive_connection<S, T>(
+    mut connection: pgsql::Connection<S, T>,
+    notifications: mpsc::UnboundedSender<pgsql::Notification>,
+) -> Result<(), pgsql::Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut messages = stream::poll_fn(|cx| connection.poll_message(cx));
+    while let Some(message) = messages.next().await {
+        match message? {
+            AsyncMessage::Notification(notification) => {
+                // If the receiver has been dropped, so has the client, and the connection will
+                // close shortly
+                let _ = notifications.send(notification);
+            }
+            AsyncMessage::Notice(notice) => {
+                info!("{}: {}", notice.severity(), notice.message());
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+impl PostgreSqlUpstream {
+    /// Wait for the next asynchronous notification received on the connection to the upstream
+    /// database as a result of a `LISTEN` statement. This is cancellation safe. If the connection
+    /// has closed, never returns.
+    pub async fn next_notification(&mut self) -> Notification {
+        match self.notifications.recv().await {
+            Some(notification) => Notification {
+                process_id: notification.process_id(),
+                channel: notification.channel().to_owned(),
+                payload: notification.payload().to_owned(),
+            },
+            None => future::pending().await,
+        }
+    }
+}
+
 #[async_trait]
 impl UpstreamDatabase for PostgreSqlUpstream {
     type StatementMeta = StatementMeta;
@@ -207,7 +260,10 @@ impl UpstreamDatabase for PostgreSqlUpstream {
             port = ?pg_config.get_ports()
         );
         span.in_scope(|| info!("Establishing connection"));
-        let (client, connection) = pg_config.connect(tls).instrument(span.clone()).await?;
+        let (client, connection) = pg_config
+            .connect(tls.clone())
+            .instrument(span.clone())
+            .await?;
         let version = connection.parameter("server_version").ok_or_else(|| {
             ReadySetError::Internal("Upstream database failed to send server version".to_string())
         })?;
@@ -225,12 +281,15 @@ impl UpstreamDatabase for PostgreSqlUpstream {
             }));
         }
         let version = format!("{version} ReadySet");
-        let _connection_handle = tokio::spawn(connection);
+        let (notifications_tx, notifications) = mpsc::unbounded_channel();
+        let _connection_handle = tokio::spawn(drive_connection(connection, notifications_tx));
         span.in_scope(|| info!("Established connection to upstream"));
 
         Ok(Self {
             client,
             _connection_handle,
+            notifications,
+            tls,
             prepared_statements: Default::default(),
             statement_id_counter: 0,
             user,
@@ -255,6 +314,20 @@ impl UpstreamDatabase for PostgreSqlUpstream {
         drop(old_self);
         Ok(())
     }
+
+    async fn reconnect_to(&mut self, upstream_db_url: String) -> Result<(), Error> {
+        let mut upstream_config = self.upstream_config.clone();
+        upstream_config.upstream_db_url = Some(upstream_db_url.into());
+        let old_self = std::mem::replace(self, Self::connect(upstream_config, None).await?);
+        drop(old_self);
+        Ok(())
+    }
+
+    fn query_canceller(&self) -> BoxFuture<'static, Result<(), Error>> {
+        let cancel_token = self.client.cancel_token();
+        let tls = self.tls.clone();
+        Box::pin(async move { Ok(cancel_token.cancel_query(tls).await?) })
+    }
     // Returns the upstream server's version, with ReadySet's info appended, to indicate to clients
     // that they're going via ReadySet
     fn version(&self) -> String {
@@ -415,4 +488,65 @@ impl UpstreamDatabase for PostgreSqlUpstream {
             })
             .collect())
     }
+
+    async fn select_privileges(&mut self) -> Result<SelectPrivileges, Self::Error> {
+        const RELATIONS: &str = "pg_catalog.pg_class c \
+             JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace";
+        const USER_RELATIONS: &str = "c.relkind IN ('r', 'p', 'v', 'm', 'f') \
+             AND n.nspname NOT IN ('pg_catalog', 'information_schema')";
+        let relation = |row: &Row, idx: usize| Relation {
+            schema: Some(row.get::<_, String>(idx).into()),
+            name: row.get::<_, String>(idx + 1).into(),
+        };
+
+        let mut privileges = SelectPrivileges::default();
+
+        for row in self
+            .client
+            .query(
+                &*format!("SELECT n.nspname, c.relname FROM {RELATIONS} WHERE {USER_RELATIONS}"),
+                &[],
+            )
+            .await?
+        {
+            privileges.known_tables.insert(relation(&row, 0));
+        }
+
+        for row in self
+            .client
+            .query(
+                "SELECT rolname FROM pg_catalog.pg_roles WHERE rolsuper",
+                &[],
+            )
+            .await?
+        {
+            privileges.users.entry(row.get(0)).or_default().all_tables = true;
+        }
+
+        // `has_table_privilege` takes into account table ownership, and privileges granted to
+        // PUBLIC or inherited from other roles
+        for row in self
+            .client
+            .query(
+                &*format!(
+                    "SELECT r.rolname, n.nspname, c.relname \
+                     FROM {RELATIONS} CROSS JOIN pg_catalog.pg_roles r \
+                     WHERE {USER_RELATIONS} AND r.rolcanlogin AND NOT r.rolsuper \
+                     AND has_schema_privilege(r.oid, n.oid, 'USAGE') \
+                     AND has_table_privilege(r.oid, c.oid, 'SELECT')"
+                ),
+                &[],
+            )
+            .await?
+        {
+            privileges
+                .users
+                .entry(row.get(0))
+                .or_default()
+                .tables
+                .insert(relation(&row, 1));
+        }
+
+        Ok(privileges)
+    }
 }
@@ -88,6 +88,15 @@ impl TryFrom<Value> for ps::Value {
             (Type::BYTEA, DfValue::ByteArray(b)) => Ok(ps::Value::ByteArray(
                 std::sync::Arc::try_unwrap(b).unwrap_or_else(|v| v.as_ref().to_vec()),
             )),
+            // Byte strings which were stored as text are still sent as bytes, so that they're
+            // encoded correctly
+            (Type::BYTEA, ref dt @ (DfValue::Text(_) | DfValue::TinyText(_))) => {
+                Ok(ps::Value::ByteArray(
+                    dt.as_bytes()
+                        .map_err(|e| ps::Error::InternalError(e.to_string()))?
+                        .to_vec(),
+                ))
+            }
             (Type::MACADDR, DfValue::Text(m)) => Ok(ps::Value::MacAddress(
                 MacAddress::parse_str(m.as_str())
                     .map_err(|e| ps::Error::ParseError(e.to_string()))?,
@@ -98,6 +107,12 @@ impl TryFrom<Value> for ps::Value {
                     .parse::<IpInet>()
                     .map_err(|e| ps::Error::ParseError(e.to_string()))?,
             )),
+            (Type::UUID, DfValue::Uuid(u)) => Ok(ps::Value::Uuid(*u)),
+            (Type::TEXT, DfValue::Uuid(u)) => Ok(ps::Value::Text(u.to_string().as_str().into())),
+            (Type::INTERVAL, DfValue::Interval(i)) => Ok(ps::Value::Interval(*i)),
+            (Type::TEXT, DfValue::Interval(i)) => {
+                Ok(ps::Value::Text(i.to_string().as_str().into()))
+            }
             (Type::UUID, DfValue::Text(u)) => Ok(ps::Value::Uuid(
                 Uuid::parse_str(u.as_str()).map_err(|e| ps::Error::ParseError(e.to_string()))?,
             )),
@@ -172,6 +187,18 @@ mod tests {
         assert_eq!(ps::Value::try_from(val).unwrap(), ps::Value::Char(8));
     }
 
+    #[test]
+    fn text_bytea() {
+        let val = Value {
+            col_type: Type::BYTEA,
+            value: DfValue::from("\u{0}\u{1}abc"),
+        };
+        assert_eq!(
+            ps::Value::try_from(val).unwrap(),
+            ps::Value::ByteArray(vec![0, 1, b'a', b'b', b'c'])
+        );
+    }
+
     #[test]
     fn tiny_text_varchar() {
         let val = Value {
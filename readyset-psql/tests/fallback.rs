@@ -183,6 +183,45 @@ async fn unsupported_query_ad_hoc() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+async fn listen_notify() {
+    let (config, _handle, shutdown_tx) = setup().await;
+
+    let (listener, mut connection) = config.connect(tokio_postgres::NoTls).await.unwrap();
+    let (notifications_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut messages = futures::stream::poll_fn(|cx| connection.poll_message(cx));
+        while let Some(message) = messages.next().await {
+            if let tokio_postgres::AsyncMessage::Notification(n) = message.unwrap() {
+                notifications_tx.send(n).unwrap();
+            }
+        }
+    });
+    listener.simple_query("LISTEN readyset_test").await.unwrap();
+
+    let notifier = connect(config).await;
+    notifier
+        .simple_query("NOTIFY readyset_test, 'hello'")
+        .await
+        .unwrap();
+
+    let notification =
+        tokio::time::timeout(std::time::Duration::from_secs(5), notifications.recv())
+            .await
+            .unwrap()
+            .unwrap();
+    assert_eq!(notification.channel(), "readyset_test");
+    assert_eq!(notification.payload(), "hello");
+
+    listener
+        .simple_query("UNLISTEN readyset_test")
+        .await
+        .unwrap();
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 #[serial]
 async fn prepare_execute_fallback() {
@@ -761,6 +761,48 @@ mod types {
         shutdown_tx.shutdown().await;
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial_test::serial]
+    async fn uuid_as_pk() {
+        let (config, _handle, shutdown_tx) = setup().await;
+        let client = connect(config).await;
+
+        client
+            .simple_query("CREATE TABLE t (id uuid primary key, x int);")
+            .await
+            .unwrap();
+
+        client
+            .simple_query(
+                "INSERT INTO t (id, x) VALUES \
+                 ('a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11', 1), \
+                 ('A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A12', 2)",
+            )
+            .await
+            .unwrap();
+
+        sleep().await;
+
+        let id = Uuid::parse_str("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a12").unwrap();
+        let res = client
+            .query_one("SELECT id, x FROM t WHERE id = $1", &[&id])
+            .await
+            .unwrap();
+        assert_eq!(res.get::<_, Uuid>(0), id);
+        assert_eq!(res.get::<_, i32>(1), 2);
+
+        let res = client
+            .query_one(
+                "SELECT x FROM t WHERE id = 'a0eebc999c0b4ef8bb6d6bb9bd380a11'",
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.get::<_, i32>(0), 1);
+
+        shutdown_tx.shutdown().await;
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     #[serial_test::serial]
     async fn alter_enum_complex_variant_changes() {
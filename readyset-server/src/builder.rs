@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::future::Future;
 use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
@@ -5,17 +6,26 @@ use std::sync::Arc;
 use std::time::{self, Duration};
 
 use database_utils::UpstreamConfig;
-use dataflow::PersistenceParameters;
+use dataflow::{PersistenceParameters, RemoteStorage, StateCompression};
+use nom_sql::SqlIdentifier;
 use readyset_client::consensus::{
     Authority, LocalAuthority, LocalAuthorityStore, NodeTypeSchedulingRestriction,
     WorkerSchedulingConfig,
 };
 use readyset_telemetry_reporter::TelemetrySender;
+use readyset_util::cgroup::CgroupLimits;
 use readyset_util::shutdown::{self, ShutdownSender};
+use tracing::info;
 
+use crate::controller::rebalance::RebalancePolicy;
 use crate::controller::replication::ReplicationStrategy;
 use crate::handle::Handle;
-use crate::{Config, FrontierStrategy, ReuseConfigType, VolumeId};
+use crate::worker::watchdog::WatchdogConfig;
+use crate::{Config, FrontierStrategy, HttpAuthConfig, ReuseConfigType, VolumeId};
+
+/// The maximum number of RocksDB background threads to use if `--persistence-threads` is not
+/// specified
+const DEFAULT_MAX_PERSISTENCE_THREADS: usize = 6;
 
 /// Used to construct a worker.
 #[derive(Clone)]
@@ -23,6 +33,7 @@ pub struct Builder {
     config: Config,
     memory_limit: Option<usize>,
     memory_check_frequency: Option<time::Duration>,
+    reader_checkpoint_interval: Option<time::Duration>,
     listen_addr: IpAddr,
     external_addr: SocketAddr,
     leader_eligible: bool,
@@ -41,6 +52,7 @@ impl Default for Builder {
             external_addr: "127.0.0.1:6033".parse().unwrap(),
             memory_limit: None,
             memory_check_frequency: None,
+            reader_checkpoint_interval: None,
             leader_eligible: true,
             domain_scheduling_config: Default::default(),
             telemetry: TelemetrySender::new_no_op(),
@@ -57,10 +69,36 @@ impl Builder {
         deployment_dir: PathBuf,
     ) -> Self {
         let mut builder = Self::default();
-        if opts.memory > 0 {
-            builder.set_memory_limit(opts.memory, Duration::from_secs(opts.memory_check_freq));
+
+        let cgroup_limits = CgroupLimits::detect();
+        let memory = opts.memory.unwrap_or_else(|| {
+            cgroup_limits
+                .memory_bytes
+                .map_or(0, |limit| (limit / 4 * 3) as usize)
+        });
+        let persistence_threads = opts.persistence_threads.unwrap_or_else(|| {
+            cgroup_limits
+                .available_cpus()
+                .min(DEFAULT_MAX_PERSISTENCE_THREADS) as i32
+        });
+        info!(
+            ?cgroup_limits,
+            memory_limit_bytes = memory,
+            persistence_threads,
+            "Effective worker resource configuration"
+        );
+
+        if memory > 0 {
+            builder.set_memory_limit(memory, Duration::from_secs(opts.memory_check_freq));
         }
         builder.set_eviction_kind(opts.eviction_kind);
+        builder.set_tenant_weights(opts.tenant_weights.into_iter().collect());
+        builder.set_max_value_size(opts.max_value_size);
+        if opts.reader_checkpoint_interval_secs > 0 {
+            builder.set_reader_checkpoint_interval(Some(Duration::from_secs(
+                opts.reader_checkpoint_interval_secs,
+            )));
+        }
 
         builder.set_sharding(match opts.shards {
             0 | 1 => None,
@@ -89,17 +127,52 @@ impl Builder {
         ));
 
         builder.set_replication_strategy(opts.domain_replication_options.into());
+        builder.set_rebalance_policy(opts.rebalance_policy);
+        builder.set_enable_pprof(opts.enable_pprof);
+        builder.set_http_auth(HttpAuthConfig {
+            admin_token: opts.http_auth_token,
+            read_only_token: opts.http_read_only_auth_token,
+        });
+        if opts.domain_stall_threshold_secs > 0 {
+            builder.set_domain_watchdog(Some(WatchdogConfig {
+                stall_threshold: Duration::from_secs(opts.domain_stall_threshold_secs),
+                dump_on_stall: opts.dump_stalled_domains,
+            }));
+        }
+
+        if let Some(dir) = opts.restore_from {
+            builder.set_restore_from(dir);
+        }
 
         if let Some(volume_id) = opts.volume_id {
             builder.set_volume_id(volume_id);
         }
 
-        let persistence_params = PersistenceParameters::new(
+        builder.set_version_label(
+            opts.version_label
+                .unwrap_or_else(|| readyset_version::RELEASE_VERSION.to_owned()),
+        );
+
+        let mut persistence_params = PersistenceParameters::new(
             opts.durability,
             Some(deployment.into()),
-            opts.persistence_threads,
+            persistence_threads,
             Some(deployment_dir),
         );
+        for (table, mode) in opts.table_durability {
+            persistence_params.set_table_mode(table, mode);
+        }
+        persistence_params.set_state_compression(opts.state_compression_threshold_bytes.map(
+            |max_uncompressed_bytes| StateCompression {
+                max_uncompressed_bytes,
+                hot_keys: opts.state_compression_hot_keys,
+                ..Default::default()
+            },
+        ));
+        persistence_params.set_remote_storage(opts.remote_storage_url.map(|url| RemoteStorage {
+            url,
+            upload_interval: Duration::from_secs(opts.remote_storage_upload_interval_secs),
+        }));
         builder.set_persistence(persistence_params);
 
         builder.set_replicator_config(opts.replicator_config);
@@ -166,6 +239,18 @@ impl Builder {
         self.memory_check_frequency = Some(check_freq);
     }
 
+    /// Set how often to checkpoint the keys in all partially materialized readers on this worker to
+    /// disk, to be restored when the worker restarts. `None` (the default) disables reader
+    /// checkpointing.
+    ///
+    /// Reader checkpoints are only written if base tables are stored durably (with
+    /// [`DurabilityMode::Permanent`][0]).
+    ///
+    /// [0]: dataflow::DurabilityMode::Permanent
+    pub fn set_reader_checkpoint_interval(&mut self, interval: Option<time::Duration>) {
+        self.reader_checkpoint_interval = interval;
+    }
+
     /// Set the IP address that the worker should use for listening.
     pub fn set_listen_addr(&mut self, listen_addr: IpAddr) {
         self.listen_addr = listen_addr;
@@ -237,6 +322,12 @@ impl Builder {
         self.config.replication_strategy = replication_strategy
     }
 
+    /// Sets the policy for redistributing domains between workers as workers join and leave the
+    /// cluster
+    pub fn set_rebalance_policy(&mut self, rebalance_policy: RebalancePolicy) {
+        self.config.rebalance_policy = rebalance_policy
+    }
+
     /// Configures this ReadySet server to accept only domains that contain reader nodes.
     ///
     /// Overwrites any previous call to [`no_readers`]
@@ -263,6 +354,12 @@ impl Builder {
         self.domain_scheduling_config.volume_id = Some(volume_id);
     }
 
+    /// Configures the label for the version of ReadySet this server is running, which canaries of
+    /// new versions are scheduled by.
+    pub fn set_version_label(&mut self, version: String) {
+        self.domain_scheduling_config.version = Some(version);
+    }
+
     /// Set the value of [`Config::abort_on_task_failure`]. See the documentation of that field for
     /// more information.
     pub fn set_abort_on_task_failure(&mut self, abort_on_task_failure: bool) {
@@ -299,6 +396,18 @@ impl Builder {
         self.config.domain_config.eviction_kind = value;
     }
 
+    /// Sets the value of [`Config::domain_config::tenant_weights`]. See documentation of
+    /// that field for more information.
+    pub fn set_tenant_weights(&mut self, value: HashMap<SqlIdentifier, u32>) {
+        self.config.domain_config.tenant_weights = value;
+    }
+
+    /// Sets the value of [`Config::domain_config::max_value_size`]. See documentation of
+    /// that field for more information.
+    pub fn set_max_value_size(&mut self, value: Option<usize>) {
+        self.config.domain_config.max_value_size = value;
+    }
+
     /// Assigns a telemetry reporter to this ReadySet server
     pub fn set_telemetry_sender(&mut self, value: TelemetrySender) {
         self.telemetry = value;
@@ -310,6 +419,28 @@ impl Builder {
         self.wait_for_failpoint = value;
     }
 
+    /// Sets whether to serve the CPU profiling and task dump endpoints in [`crate::profiling`] from
+    /// the HTTP router
+    pub fn set_enable_pprof(&mut self, value: bool) {
+        self.config.enable_pprof = value;
+    }
+
+    /// Set the tokens required to access the HTTP endpoints of the server
+    pub fn set_http_auth(&mut self, value: HttpAuthConfig) {
+        self.config.http_auth = value;
+    }
+
+    /// Sets the configuration for detecting stalled domains, or disables stall detection if `None`
+    pub fn set_domain_watchdog(&mut self, value: Option<WatchdogConfig>) {
+        self.config.domain_watchdog = value;
+    }
+
+    /// Sets the directory containing a backup to restore the deployment from, if it has no
+    /// existing state
+    pub fn set_restore_from(&mut self, dir: PathBuf) {
+        self.config.restore_from = Some(dir);
+    }
+
     /// Sets whether to log statements in the replicator
     pub fn set_replicator_statement_logging(&mut self, value: bool) {
         self.config.replicator_statement_logging = value;
@@ -327,6 +458,7 @@ impl Builder {
             ref config,
             memory_limit,
             memory_check_frequency,
+            reader_checkpoint_interval,
             domain_scheduling_config,
             leader_eligible,
             telemetry,
@@ -342,6 +474,7 @@ impl Builder {
             config,
             memory_limit,
             memory_check_frequency,
+            reader_checkpoint_interval,
             domain_scheduling_config,
             leader_eligible,
             telemetry,
@@ -364,6 +497,7 @@ impl Builder {
             ref config,
             memory_limit,
             memory_check_frequency,
+            reader_checkpoint_interval,
             domain_scheduling_config,
             leader_eligible,
             telemetry,
@@ -380,6 +514,7 @@ impl Builder {
             config,
             memory_limit,
             memory_check_frequency,
+            reader_checkpoint_interval,
             domain_scheduling_config,
             leader_eligible,
             readers,
@@ -0,0 +1,94 @@
+//! Backups of the state of a ReadySet deployment, and restoring deployments from those backups.
+//!
+//! A backup, created via the `/backup` controller RPC, is a directory containing:
+//!
+//! - A [RocksDB checkpoint][checkpoint] of the persisted state of every base table, each in a
+//!   subdirectory named after the table's database directory (eg `readyset-public-posts-0.db`)
+//! - The serialized [`DfState`] of the controller, in [`DATAFLOW_STATE_FILE`]
+//!
+//! Since the replication offset of each base table is stored in its RocksDB database, a deployment
+//! restored from a backup resumes replication from the point at which each table was checkpointed,
+//! rather than re-snapshotting from the upstream database.
+//!
+//! Backups are restored when starting a server with `--restore-from` pointed at the backup
+//! directory. The restore only takes effect if the authority has no existing controller state
+//! (i.e. for a brand new deployment) - otherwise it's ignored, so that restarting a server with
+//! the same options doesn't overwrite the state of the running deployment. Backups are written to
+//! and restored from the local filesystem of the server(s) running the base table domains, so
+//! multi-server deployments need a shared (or synchronized) backup directory.
+//!
+//! [checkpoint]: https://github.com/facebook/rocksdb/wiki/Checkpoints
+
+use std::fs;
+use std::path::Path;
+
+use readyset_errors::ReadySetResult;
+use tracing::{info, warn};
+
+use crate::controller::state::DfState;
+use crate::controller::ControllerState;
+use crate::Config;
+
+/// The name of the file within a backup directory containing the serialized [`DfState`]
+pub(super) const DATAFLOW_STATE_FILE: &str = "dataflow_state.bin";
+
+/// Write the given dataflow state to [`DATAFLOW_STATE_FILE`] in `dir`
+pub(super) fn write_dataflow_state(dir: &Path, state: &DfState) -> ReadySetResult<()> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(DATAFLOW_STATE_FILE);
+    fs::write(&path, bincode::serialize(state)?)?;
+    info!(path = %path.display(), "Wrote dataflow state to backup");
+    Ok(())
+}
+
+/// Restore the deployment backed up in `dir`, by copying the checkpoints of all base tables into
+/// the configured `db_dir` and returning a [`ControllerState`] built from the backed-up dataflow
+/// state and the given config.
+///
+/// Any existing databases in the `db_dir` with the same names as the tables in the backup are
+/// replaced, so that this can safely be retried if the controller state fails to be written to the
+/// authority.
+pub(super) fn restore(dir: &Path, config: &Config) -> ReadySetResult<ControllerState> {
+    info!(dir = %dir.display(), "Restoring deployment from backup");
+
+    let state_path = dir.join(DATAFLOW_STATE_FILE);
+    let mut dataflow_state: DfState = bincode::deserialize(&fs::read(&state_path)?)?;
+
+    let db_dir = config
+        .persistence
+        .db_dir
+        .clone()
+        .unwrap_or_else(|| ".".into());
+    fs::create_dir_all(&db_dir)?;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if !entry.file_type()?.is_dir() || !name.to_string_lossy().ends_with(".db") {
+            continue;
+        }
+
+        let target = db_dir.join(&name);
+        if target.exists() {
+            warn!(
+                path = %target.display(),
+                "Replacing existing base table database with the one from the backup"
+            );
+            fs::remove_dir_all(&target)?;
+        }
+        fs::create_dir(&target)?;
+        for file in fs::read_dir(entry.path())? {
+            let file = file?;
+            fs::copy(file.path(), target.join(file.file_name()))?;
+        }
+        info!(path = %target.display(), "Restored base table database");
+    }
+
+    dataflow_state.domain_config = config.domain_config.clone();
+    dataflow_state.replication_strategy = config.replication_strategy;
+    dataflow_state.with_persistence_options(config.persistence.clone());
+
+    Ok(ControllerState {
+        config: config.clone(),
+        dataflow_state,
+    })
+}
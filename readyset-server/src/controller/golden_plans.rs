@@ -0,0 +1,154 @@
+//! Golden-file tests for the dataflow graphs produced by lowering SQL queries.
+//!
+//! Each `.sql` file in `tests/golden_plans` contains a recipe - a schema and a set of `CREATE
+//! CACHE` statements - which is migrated into a fresh, unsharded ReadySet instance. The resulting
+//! dataflow graph is then rendered to a stable textual description, and compared against the
+//! corresponding `.plan` file. This catches unintended changes to query planning, such as an
+//! explosion in the number of nodes or the loss of reuse between queries, which otherwise only
+//! show up as memory growth in production.
+//!
+//! Missing `.plan` files are recorded the first time the tests are run. To accept intentional
+//! changes to existing plans, rerun the tests with `UPDATE_GOLDEN_PLANS=1` set, and review the
+//! changes to the `.plan` files.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use dataflow::prelude::Graph;
+use petgraph::graph::NodeIndex;
+use petgraph::EdgeDirection;
+use readyset_client::internal::MaterializationStatus;
+use readyset_client::recipe::changelist::ChangeList;
+use readyset_data::Dialect;
+
+use super::state::DfState;
+use crate::integration_utils::start_simple_unsharded;
+
+/// Returns true if the given node is an implementation detail of how the graph is divided into
+/// domains (or the root of the graph), rather than part of the plan for any query
+fn is_plumbing(graph: &Graph, index: NodeIndex) -> bool {
+    let node = &graph[index];
+    node.is_source()
+        || node.is_ingress()
+        || node.is_egress()
+        || node.is_sharder()
+        || node.is_dropped()
+}
+
+/// Returns the parents of the given node, looking through any plumbing nodes
+fn logical_parents(graph: &Graph, index: NodeIndex) -> Vec<NodeIndex> {
+    let mut parents = vec![];
+    let mut stack = graph
+        .neighbors_directed(index, EdgeDirection::Incoming)
+        .collect::<Vec<_>>();
+    while let Some(parent) = stack.pop() {
+        if graph[parent].is_source() {
+            continue;
+        }
+        if is_plumbing(graph, parent) {
+            stack.extend(graph.neighbors_directed(parent, EdgeDirection::Incoming));
+        } else {
+            parents.push(parent);
+        }
+    }
+    parents
+}
+
+/// Render a description of the dataflow graph which only changes when the plan does, omitting
+/// the nodes added to divide the graph into domains and renumbering the remaining nodes
+/// sequentially.
+fn describe_plan(state: &DfState) -> String {
+    let graph = &state.ingredients;
+    let mut ids = HashMap::new();
+    let mut nodes = String::new();
+    let mut materialized = 0;
+    for index in graph.node_indices() {
+        if is_plumbing(graph, index) {
+            continue;
+        }
+        let id = ids.len();
+        ids.insert(index, id);
+
+        let node = &graph[index];
+        let mut parents = logical_parents(graph, index)
+            .into_iter()
+            .filter_map(|parent| ids.get(&parent).copied())
+            .collect::<Vec<_>>();
+        parents.sort_unstable();
+        let materialization = match state.materializations.get_status(index, node) {
+            MaterializationStatus::Not => "",
+            MaterializationStatus::Full => " [full]",
+            MaterializationStatus::Partial { .. } => " [partial]",
+        };
+        if !materialization.is_empty() {
+            materialized += 1;
+        }
+
+        #[allow(clippy::unwrap_used)] // writing to a String can't fail
+        writeln!(
+            nodes,
+            "{id}: {} {}{materialization} <- {parents:?}",
+            node.name().display_unquoted(),
+            node.description(true),
+        )
+        .unwrap();
+    }
+
+    format!(
+        "# {} nodes, {materialized} materialized\n{nodes}",
+        ids.len()
+    )
+}
+
+fn golden_plans_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden_plans")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn golden_plans() {
+    let update = env::var("UPDATE_GOLDEN_PLANS").is_ok();
+    let mut recipes = fs::read_dir(golden_plans_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "sql"))
+        .collect::<Vec<_>>();
+    recipes.sort();
+    assert!(
+        !recipes.is_empty(),
+        "No recipes found in tests/golden_plans"
+    );
+
+    let mut mismatches = vec![];
+    for recipe in recipes {
+        let name = recipe.file_stem().unwrap().to_string_lossy().into_owned();
+        let (mut noria, shutdown_tx) =
+            start_simple_unsharded(&format!("golden_plans_{name}")).await;
+        noria
+            .extend_recipe(
+                ChangeList::from_str(fs::read_to_string(&recipe).unwrap(), Dialect::DEFAULT_MYSQL)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let plan = noria.migrate(|mig| describe_plan(mig.dataflow_state)).await;
+        shutdown_tx.shutdown().await;
+
+        let golden = recipe.with_extension("plan");
+        match fs::read_to_string(&golden) {
+            Ok(expected) if expected == plan => {}
+            Ok(expected) if !update => {
+                eprintln!("Plan for {name} changed.\nExpected:\n{expected}\nActual:\n{plan}");
+                mismatches.push(name)
+            }
+            _ => fs::write(&golden, plan).unwrap(),
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "The plans for {mismatches:?} don't match the golden files in tests/golden_plans. If the \
+         changes are intentional, rerun with UPDATE_GOLDEN_PLANS=1 and review the diff"
+    );
+}
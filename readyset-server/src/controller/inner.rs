@@ -8,6 +8,7 @@
 )]
 
 use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -16,15 +17,18 @@ use failpoint_macros::failpoint;
 use futures::future::Fuse;
 use futures::FutureExt;
 use hyper::Method;
+use nom_sql::Relation;
 use readyset_client::consensus::Authority;
 use readyset_client::internal::ReplicaAddress;
 use readyset_client::recipe::{ExtendRecipeResult, ExtendRecipeSpec, MigrationStatus};
 use readyset_client::replication::ReplicationOffset;
-use readyset_client::status::{ReadySetStatus, SnapshotStatus};
-use readyset_client::WorkerDescriptor;
-use readyset_errors::{internal_err, ReadySetError, ReadySetResult};
+use readyset_client::status::{ReadySetStatus, SnapshotStatus, StandbyState};
+use readyset_client::{KeyComparison, RocksDbTuning, WorkerDescriptor};
+use readyset_data::DfValue;
+use readyset_errors::{internal_err, invalid_err, ReadySetError, ReadySetResult};
 use readyset_telemetry_reporter::TelemetrySender;
 use readyset_util::futures::abort_on_panic;
+use readyset_util::redacted::RedactedString;
 use readyset_util::shutdown::ShutdownReceiver;
 use readyset_version::RELEASE_VERSION;
 use reqwest::Url;
@@ -36,8 +40,10 @@ use tokio::task::JoinHandle;
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
 
+use crate::controller::rebalance::RebalancePolicy;
+use crate::controller::standby::Standby;
 use crate::controller::state::{DfState, DfStateHandle};
-use crate::controller::{ControllerState, Worker, WorkerIdentifier};
+use crate::controller::{backup, ControllerState, Worker, WorkerIdentifier};
 use crate::coordination::DomainDescriptor;
 use crate::worker::WorkerRequestKind;
 
@@ -66,6 +72,9 @@ pub struct Leader {
     quorum: usize,
     controller_uri: Url,
 
+    /// How to redistribute domains between workers as workers join and leave the cluster
+    rebalance_policy: RebalancePolicy,
+
     /// The amount of time to wait for a worker request to complete.
     worker_request_timeout: Duration,
     /// Whether to log statements received by the replicators
@@ -74,6 +83,8 @@ pub struct Leader {
     pub(super) replicator_config: UpstreamConfig,
     /// A client to the current authority.
     pub(super) authority: Arc<Authority>,
+    /// Token used to authenticate requests to the HTTP endpoints of the controller and workers
+    http_auth_token: Option<RedactedString>,
 
     /// A map of currently running migrations.
     ///
@@ -83,6 +94,10 @@ pub struct Leader {
     running_migrations: Mutex<SlotMap<DefaultKey, RunningMigration>>,
 
     pub(super) running_recovery: Option<watch::Receiver<ReadySetResult<()>>>,
+
+    /// If this deployment is running as a warm standby of another deployment, the state of the
+    /// standby
+    standby: Option<Arc<Standby>>,
 }
 
 impl Leader {
@@ -130,6 +145,9 @@ impl Leader {
         let replicator_restart_timeout = self.replicator_config.replicator_restart_timeout;
         let config = self.replicator_config.clone();
         let replicator_statement_logging = self.replicator_statement_logging;
+        let http_auth_token = self.http_auth_token.clone();
+
+        let standby = self.standby.clone();
 
         // The replication task ideally won't panic, but if it does and we arent replicating, that
         // will mean the data we return, will be more and more stale, and the transaction logs on
@@ -140,13 +158,79 @@ impl Leader {
                 // The replicator wants to know if we're restarting the server so that it can
                 // resnapshot to capture changes made to replication-tables.
                 let mut server_startup = true;
+
+                if let Some(standby) = &standby {
+                    // A standby only replicates from the upstream database until it has finished
+                    // seeding its base tables, after which it receives writes from the primary
+                    // deployment until it's promoted.
+                    let mut noria = readyset_client::ReadySetHandle::make(
+                        Arc::clone(&authority),
+                        None,
+                        None,
+                        http_auth_token.clone(),
+                    );
+                    match noria
+                        .replication_offsets()
+                        .await
+                        .and_then(|o| o.max_offset().map(|o| o.is_some()))
+                    {
+                        Ok(true) => standby.finish_seeding(),
+                        Ok(false) => {}
+                        Err(error) => {
+                            warn!(%error, "Could not determine whether standby has been seeded")
+                        }
+                    }
+                    while standby.state() == StandbyState::Seeding {
+                        let noria = readyset_client::ReadySetHandle::make(
+                            Arc::clone(&authority),
+                            None,
+                            None,
+                            http_auth_token.clone(),
+                        );
+                        let seeded = standby.seeded();
+                        select! {
+                            res = replicators::NoriaAdapter::start(
+                                noria,
+                                config.clone(),
+                                http_auth_token.clone(),
+                                Some(seeded.clone()),
+                                telemetry_sender.clone(),
+                                true,
+                                replicator_statement_logging,
+                            ) => {
+                                if let Err(error) = res {
+                                    error!(
+                                        target: "replicators",
+                                        %error,
+                                        timeout_sec=replicator_restart_timeout.as_secs(),
+                                        "Error seeding standby, will retry after timeout"
+                                    );
+                                    tokio::time::sleep(replicator_restart_timeout).await;
+                                }
+                            }
+                            _ = seeded.notified() => standby.finish_seeding(),
+                        }
+                    }
+                    ready_notification.notify_one();
+                    standby.wait_for_promotion().await;
+                    // The standby's base tables already contain everything up to the replication
+                    // offsets shipped by the primary, so resume from there without resnapshotting
+                    server_startup = false;
+                }
+
                 loop {
                     let noria: readyset_client::ReadySetHandle =
-                        readyset_client::ReadySetHandle::new(Arc::clone(&authority)).await;
+                        readyset_client::ReadySetHandle::make(
+                            Arc::clone(&authority),
+                            None,
+                            None,
+                            http_auth_token.clone(),
+                        );
 
                     match replicators::NoriaAdapter::start(
                         noria,
                         config.clone(),
+                        http_auth_token.clone(),
                         Some(ready_notification.clone()),
                         telemetry_sender.clone(),
                         server_startup,
@@ -288,6 +372,19 @@ impl Leader {
                     };
                     return_serialized!(res);
                 }
+                (&Method::POST, "/set_domain_memory_limit") => {
+                    let (domain, limit) = bincode::deserialize(&body)?;
+                    let ds = self.dataflow_state_handle.read().await;
+                    ds.set_domain_memory_limit(domain, limit).await?;
+                    return_serialized!(());
+                }
+                (&Method::POST, "/set_view_memory_limit") => {
+                    let (view, limit): (Relation, _) = bincode::deserialize(&body)?;
+                    let ds = self.dataflow_state_handle.read().await;
+                    ds.set_domain_memory_limit(ds.reader_domain(&view)?, limit)
+                        .await?;
+                    return_serialized!(());
+                }
                 (&Method::GET | &Method::POST, "/version") => {
                     return_serialized!(RELEASE_VERSION);
                 }
@@ -333,6 +430,12 @@ impl Leader {
                     check_quorum!(ds);
                     return_serialized!(ds.view_statuses(queries, dialect))
                 }
+                (&Method::POST, "/view_plan") => {
+                    let (query, dialect) = bincode::deserialize(&body)?;
+                    let ds = self.dataflow_state_handle.read().await;
+                    check_quorum!(ds);
+                    return_serialized!(ds.view_plan(query, dialect)?)
+                }
                 (&Method::GET | &Method::POST, "/instances") => {
                     let ds = self.dataflow_state_handle.read().await;
                     check_quorum!(ds);
@@ -353,6 +456,11 @@ impl Leader {
                         .map(|w| w.0)
                         .collect::<Vec<_>>());
                 }
+                (&Method::GET | &Method::POST, "/domain_placement") => {
+                    let ds = self.dataflow_state_handle.read().await;
+                    check_quorum!(ds);
+                    return_serialized!(ds.domain_placement())
+                }
                 (&Method::GET, "/nodes") => {
                     let ds = self.dataflow_state_handle.read().await;
                     check_quorum!(ds);
@@ -433,6 +541,20 @@ impl Leader {
                     }?;
                     return_serialized!(res);
                 }
+                (&Method::POST, "/standby_state") => {
+                    return_serialized!(self.standby.as_ref().map(|s| s.state()));
+                }
+                (&Method::POST, "/promote") => {
+                    self.standby
+                        .as_ref()
+                        .ok_or_else(|| {
+                            invalid_err!(
+                                "Cannot promote a deployment which is not running as a standby"
+                            )
+                        })?
+                        .promote()?;
+                    return_serialized!(());
+                }
                 (&Method::POST, "/snapshotting_tables") => {
                     let res = {
                         let ds = self.dataflow_state_handle.read().await;
@@ -441,6 +563,17 @@ impl Leader {
                     }?;
                     return_serialized!(res);
                 }
+                (&Method::POST, "/backup") => {
+                    let dir: PathBuf = bincode::deserialize(&body)?;
+                    let res = {
+                        let ds = self.dataflow_state_handle.read().await;
+                        check_quorum!(ds);
+                        let checkpointed = ds.checkpoint_base_tables(&dir).await?;
+                        backup::write_dataflow_state(&dir, &ds)?;
+                        checkpointed
+                    };
+                    return_serialized!(res);
+                }
                 (&Method::POST, "/all_tables_compacted") => {
                     let res = {
                         let ds = self.dataflow_state_handle.read().await;
@@ -456,6 +589,20 @@ impl Leader {
                     }?;
                     return_serialized!(res);
                 }
+                (&Method::GET | &Method::POST, "/reader_skew") => {
+                    let res = {
+                        let ds = self.dataflow_state_handle.read().await;
+                        ds.reader_skew_report().await
+                    }?;
+                    return_serialized!(res);
+                }
+                (&Method::GET | &Method::POST, "/canary_report") => {
+                    let res = {
+                        let ds = self.dataflow_state_handle.read().await;
+                        ds.canary_report().await
+                    }?;
+                    return_serialized!(res);
+                }
                 (&Method::POST, "/leader_ready") => {
                     return_serialized!(leader_ready);
                 }
@@ -512,16 +659,24 @@ impl Leader {
         // *** Write methods (all of them require quorum) ***
 
         match (&method, path) {
-            (&Method::GET, "/flush_partial") => {
+            (&Method::POST, "/flush_partial") => {
+                let view: Option<Relation> = bincode::deserialize(&body)?;
                 let ret = {
                     let mut writer = self.dataflow_state_handle.write().await;
                     check_quorum!(writer.as_ref());
-                    let r = writer.as_mut().flush_partial().await?;
+                    let r = writer.as_mut().flush_partial(view).await?;
                     self.dataflow_state_handle.commit(writer, authority).await?;
                     r
                 };
                 return_serialized!(ret);
             }
+            (&Method::POST, "/invalidate_keys") => {
+                let (view, keys): (Relation, Vec<KeyComparison>) = bincode::deserialize(&body)?;
+                let ds = self.dataflow_state_handle.read().await;
+                check_quorum!(ds);
+                ds.invalidate_keys(&view, keys).await?;
+                return_serialized!(());
+            }
             (&Method::POST, "/extend_recipe") => {
                 let body: ExtendRecipeSpec = bincode::deserialize(&body)?;
                 if body.require_leader_ready {
@@ -605,6 +760,82 @@ impl Leader {
                 self.dataflow_state_handle.commit(writer, authority).await?;
                 return_serialized!(ReadySetResult::Ok(()));
             }
+            (&Method::POST, "/move_domain") => {
+                require_leader_ready()?;
+                let (replica_address, worker) = bincode::deserialize(&body)?;
+                let mut writer = self.dataflow_state_handle.write().await;
+                check_quorum!(writer.as_ref());
+                writer.as_mut().move_domain(replica_address, worker).await?;
+                self.dataflow_state_handle.commit(writer, authority).await?;
+                return_serialized!(());
+            }
+            (&Method::POST, "/compact_table") => {
+                let table: Relation = bincode::deserialize(&body)?;
+                let ds = self.dataflow_state_handle.read().await;
+                check_quorum!(ds);
+                ds.compact_table(&table).await?;
+                return_serialized!(());
+            }
+            (&Method::POST, "/invalidate_table_keys") => {
+                let (table, keys): (Relation, Vec<Vec<DfValue>>) = bincode::deserialize(&body)?;
+                let ds = self.dataflow_state_handle.read().await;
+                check_quorum!(ds);
+                ds.invalidate_table_keys(&table, keys).await?;
+                return_serialized!(());
+            }
+            (&Method::POST, "/set_table_compaction_interval") => {
+                let (table, interval): (Relation, Option<Duration>) = bincode::deserialize(&body)?;
+                let mut writer = self.dataflow_state_handle.write().await;
+                check_quorum!(writer.as_ref());
+                writer
+                    .as_mut()
+                    .set_table_compaction_interval(&table, interval)
+                    .await?;
+                self.dataflow_state_handle.commit(writer, authority).await?;
+                return_serialized!(());
+            }
+            (&Method::POST, "/set_table_durability") => {
+                let (table, mode) = bincode::deserialize(&body)?;
+                let mut writer = self.dataflow_state_handle.write().await;
+                check_quorum!(writer.as_ref());
+                writer.as_mut().set_table_durability(table, mode)?;
+                self.dataflow_state_handle.commit(writer, authority).await?;
+                return_serialized!(());
+            }
+            (&Method::POST, "/set_table_tuning") => {
+                let (table, tuning): (Relation, RocksDbTuning) = bincode::deserialize(&body)?;
+                let mut writer = self.dataflow_state_handle.write().await;
+                check_quorum!(writer.as_ref());
+                writer.as_mut().set_table_tuning(&table, tuning).await?;
+                self.dataflow_state_handle.commit(writer, authority).await?;
+                return_serialized!(());
+            }
+            (&Method::POST, "/unpin_domain") => {
+                require_leader_ready()?;
+                let replica_address = bincode::deserialize(&body)?;
+                let mut writer = self.dataflow_state_handle.write().await;
+                check_quorum!(writer.as_ref());
+                writer.as_mut().unpin_domain(&replica_address);
+                self.dataflow_state_handle.commit(writer, authority).await?;
+                return_serialized!(());
+            }
+            (&Method::POST, "/start_canary") => {
+                require_leader_ready()?;
+                let (version, domains) = bincode::deserialize(&body)?;
+                let mut writer = self.dataflow_state_handle.write().await;
+                check_quorum!(writer.as_ref());
+                writer.as_mut().start_canary(version, domains).await?;
+                self.dataflow_state_handle.commit(writer, authority).await?;
+                return_serialized!(());
+            }
+            (&Method::POST, "/end_canary") => {
+                require_leader_ready()?;
+                let mut writer = self.dataflow_state_handle.write().await;
+                check_quorum!(writer.as_ref());
+                writer.as_mut().end_canary();
+                self.dataflow_state_handle.commit(writer, authority).await?;
+                return_serialized!(());
+            }
             (&Method::POST, "/remove_node") => {
                 require_leader_ready()?;
                 let body = bincode::deserialize(&body)?;
@@ -639,7 +870,8 @@ impl Leader {
                 worker_uri.clone(),
                 domain_scheduling_config,
                 self.worker_request_timeout,
-            );
+                self.http_auth_token.clone(),
+            )?;
 
             let mut domain_addresses = Vec::new();
             for (domain_index, handle) in &ds.domains {
@@ -710,6 +942,17 @@ impl Leader {
             None
         };
 
+        // Domains can only be moved once any recovery of the graph has finished, since until then
+        // they might not be running anywhere
+        if self.rebalance_policy == RebalancePolicy::Balance
+            && ds.workers.len() >= self.quorum
+            && dmp.is_none()
+            && !self.pending_recovery
+            && self.running_recovery.is_none()
+        {
+            ds.rebalance_domains().await?;
+        }
+
         self.dataflow_state_handle
             .commit(writer, &self.authority)
             .await?;
@@ -752,22 +995,61 @@ impl Leader {
 
         // first, translate from the affected workers to affected data-flow nodes
         let mut affected_nodes = HashMap::new();
-        for wi in failed {
+        for wi in &failed {
             warn!(worker = %wi, "handling failure of worker");
-            let mut domain_nodes_on_worker = ds.nodes_on_worker(Some(&wi));
+            let mut domain_nodes_on_worker = ds.nodes_on_worker(Some(wi));
             for (domain_index, node_indices) in domain_nodes_on_worker.drain() {
-                ds.domains.remove(&domain_index);
                 ds.materializations.remove_nodes(&node_indices);
                 affected_nodes
                     .entry(domain_index)
                     .or_insert_with(|| HashSet::new())
                     .extend(node_indices);
             }
-            ds.workers.remove(&wi);
+        }
+
+        // The affected domains are re-created from scratch, so any of their replicas still
+        // running on other workers have to be removed first
+        for domain_index in affected_nodes.keys() {
+            let Some(dh) = ds.domains.remove(domain_index) else {
+                continue;
+            };
+            for (shard, replicas) in dh.shards().enumerate() {
+                for (replica, wi) in replicas.iter().enumerate() {
+                    let replica_address = ReplicaAddress {
+                        domain_index: *domain_index,
+                        shard,
+                        replica,
+                    };
+                    ds.channel_coordinator.remove(&replica_address);
+                    if failed.contains(wi) {
+                        continue;
+                    }
+                    if let Some(w) = ds.workers.get(wi) {
+                        if let Err(error) = w
+                            .rpc::<()>(WorkerRequestKind::RemoveDomain(replica_address))
+                            .await
+                        {
+                            warn!(
+                                %replica_address,
+                                worker = %wi,
+                                %error,
+                                "Could not remove replica of domain on failed worker"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        for wi in &failed {
+            ds.workers.remove(wi);
         }
 
         ds.plan_recovery(&affected_nodes).await?.apply(ds).await?;
 
+        if self.rebalance_policy == RebalancePolicy::Balance {
+            ds.rebalance_domains().await?;
+        }
+
         self.dataflow_state_handle
             .commit(writer, &self.authority)
             .await
@@ -781,6 +1063,7 @@ impl Leader {
         replicator_statement_logging: bool,
         replicator_config: UpstreamConfig,
         worker_request_timeout: Duration,
+        http_auth_token: Option<RedactedString>,
     ) -> Self {
         assert_ne!(state.config.quorum, 0);
 
@@ -792,6 +1075,9 @@ impl Leader {
         let pending_recovery = state.dataflow_state.ingredients.node_indices().count() > 1;
 
         let dataflow_state_handle = Arc::new(DfStateHandle::new(state.dataflow_state));
+        let standby = replicator_config
+            .standby
+            .then(|| Arc::new(Standby::new(StandbyState::Seeding)));
 
         Leader {
             dataflow_state_handle,
@@ -801,12 +1087,16 @@ impl Leader {
 
             controller_uri,
 
+            rebalance_policy: state.config.rebalance_policy,
+
             replicator_statement_logging,
             replicator_config,
             authority,
+            http_auth_token,
             worker_request_timeout,
             running_migrations: Default::default(),
             running_recovery: None,
+            standby,
         }
     }
 }
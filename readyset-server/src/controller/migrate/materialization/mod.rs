@@ -476,6 +476,15 @@ impl Materializations {
                 able = false;
             }
 
+            #[allow(clippy::indexing_slicing)] // ordered is built from graph
+            if graph[ni]
+                .as_reader()
+                .map_or(false, |r| r.force_full_materialization())
+            {
+                debug!(node = %ni.index(), "full because requested for reader");
+                able = false;
+            }
+
             // we are already fully materialized, so can't be made partial
             if !new.contains(&ni)
                 && self.added.get(&ni).map(|i| i.len()).unwrap_or(0)
@@ -700,6 +709,12 @@ impl Materializations {
         Ok(())
     }
 
+    /// Retrieves the set of indices that are maintained for the given node, or None if the node
+    /// isn't materialized.
+    pub(in crate::controller) fn indices_for(&self, index: NodeIndex) -> Option<&Indices> {
+        self.have.get(&index)
+    }
+
     /// Retrieves the materialization status of a given node, or None
     /// if the node isn't materialized.
     pub(in crate::controller) fn get_status(
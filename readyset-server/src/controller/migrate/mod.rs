@@ -547,6 +547,13 @@ impl<'df> Migration<'df> {
         #[allow(clippy::unwrap_used)]
         let parents = i.ancestors().unwrap();
         assert!(!parents.is_empty());
+        // Nodes do work on behalf of the tenant of the first of their ancestors that has one
+        #[allow(clippy::indexing_slicing)] // parents must exist in ingredients
+        i.set_tenant(
+            parents
+                .iter()
+                .find_map(|&p| self.dataflow_state.ingredients[p].tenant().cloned()),
+        );
 
         // add to the graph
         let ni = self.dataflow_state.ingredients.add_node(i);
@@ -579,11 +586,12 @@ impl<'df> Migration<'df> {
         C: Into<Column>,
         CS: IntoIterator<Item = C>,
     {
+        let mut base = node::Node::new(name, columns, b);
+        // Base tables belong to the tenant of their schema
+        base.set_tenant(base.name().schema.clone());
+
         // add to the graph
-        let ni = self
-            .dataflow_state
-            .ingredients
-            .add_node(node::Node::new(name, columns, b));
+        let ni = self.dataflow_state.ingredients.add_node(base);
         debug!(node = ni.index(), "adding new base");
 
         // keep track of the fact that it's new
@@ -786,6 +794,32 @@ impl<'df> Migration<'df> {
         r.set_mapping(placeholder_map);
     }
 
+    /// Configure the reader for the given node, which must have been [maintained](Self::maintain)
+    /// within this migration, to always be fully materialized and/or to have its domain replicated
+    /// the given number of times.
+    pub fn set_reader_options(
+        &mut self,
+        n: NodeIndex,
+        full_materialization: bool,
+        replicas: Option<usize>,
+    ) -> ReadySetResult<()> {
+        if !full_materialization && replicas.is_none() {
+            return Ok(());
+        }
+
+        let ri = *self
+            .readers
+            .get(&n)
+            .ok_or_else(|| internal_err!("No reader added for node {}", n.index()))?;
+        #[allow(clippy::indexing_slicing)] // readers only contains valid indices
+        let r = self.dataflow_state.ingredients[ri]
+            .as_mut_reader()
+            .ok_or_else(|| internal_err!("Node {} is not a reader", ri.index()))?;
+        r.set_force_full_materialization(full_materialization);
+        r.set_replicas(replicas);
+        Ok(())
+    }
+
     /// Build a `MigrationPlan` for this migration, and apply it if the planning stage succeeds.
     pub(super) async fn commit(self, dry_run: bool) -> ReadySetResult<()> {
         let start = self.start;
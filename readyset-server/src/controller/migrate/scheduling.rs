@@ -10,28 +10,35 @@
 //!    replicas of all shards of all domains within the migration will be scheduled to that worker,
 //!    *if* it's valid
 //! 3. Otherwise, for each replica of each shard in the domain, we first filter the set of workers
-//!    down to only workers that aren't running a different replica of the same domain shard, then
-//!    either:
-//!    a. Run the domain shard on the worker matching its [placement restrictions][], if it has any,
+//!    down to only workers that aren't running a different replica of the same domain shard, and,
+//!    if a [canary][] of a new version is in progress, to only workers with the canary's version if
+//!    the replica is being canaried or without it otherwise, then either:
+//!    a. Run the domain shard replica on the worker it has been [pinned][] to, if any, and if that
+//!       worker is available, or
+//!    b. Run the domain shard on the worker matching its [placement restrictions][], if it has any,
 //!       or
-//!    b. If the domain contains base tables, run it on the worker running the smallest number of
+//!    c. If the domain contains base tables, run it on the worker running the smallest number of
 //!       other base tables, or otherwise
-//!    c. Run it on the worker that has the smallest number of domain shards scheduled onto it
+//!    d. Run it on the worker that has the smallest number of domain shards scheduled onto it
 //!
 //! [reader_only]: Worker::reader_only
 //! [worker]: Migration::worker
 //! [placement restrictions]: DomainPlacementRestriction
+//! [pinned]: DfState::move_domain
+//! [canary]: DfState::start_canary
 
 use std::collections::{HashMap, HashSet};
 
 use array2::Array2;
 use dataflow::prelude::*;
 use readyset_client::consensus::NodeTypeSchedulingRestriction;
-use readyset_client::internal::DomainIndex;
-use tracing::{instrument, trace};
+use readyset_client::internal::{DomainIndex, ReplicaAddress};
+use tracing::{instrument, trace, warn};
 
 use crate::controller::state::DfState;
-use crate::controller::{DomainPlacementRestriction, NodeRestrictionKey, Worker, WorkerIdentifier};
+use crate::controller::{
+    DomainCanary, DomainPlacementRestriction, NodeRestrictionKey, Worker, WorkerIdentifier,
+};
 
 /// Verifies that the worker `worker` meets the domain placement restrictions of all dataflow nodes
 /// that will be placed in a new domain on the worker.  If the set of restrictions in this domain
@@ -45,6 +52,20 @@ fn worker_meets_restrictions(
         .all(|r| r.worker_volume == worker.domain_scheduling_config.volume_id)
 }
 
+/// Verifies that the given replica of a domain shard may run on the worker `worker` given the
+/// canary in progress, if any: replicas being canaried may only run on workers with the canary's
+/// version label, and all other replicas may only run on workers without it.
+fn worker_meets_canary(
+    worker: &Worker,
+    canary: Option<&DomainCanary>,
+    replica_address: &ReplicaAddress,
+) -> bool {
+    canary.map_or(true, |canary| {
+        canary.replicas.contains(replica_address)
+            == (worker.domain_scheduling_config.version.as_ref() == Some(&canary.version))
+    })
+}
+
 /// Statistics about the domains scheduled onto a worker
 #[derive(Default, Clone, Copy)]
 struct WorkerStats {
@@ -184,10 +205,45 @@ impl<'state> Scheduler<'state> {
                     })
                     .collect::<Vec<_>>();
 
-                let worker_id = if dataflow_node_restrictions.is_empty() {
+                let replica_address = ReplicaAddress {
+                    domain_index,
+                    shard,
+                    replica,
+                };
+                let replica_workers = available_workers
+                    .iter()
+                    .copied()
+                    .filter(|(_, worker)| {
+                        worker_meets_canary(
+                            worker,
+                            self.dataflow_state.canary.as_ref(),
+                            &replica_address,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                let pinned_worker = self
+                    .dataflow_state
+                    .domain_pins
+                    .get(&replica_address)
+                    .and_then(|pinned| {
+                        let worker = replica_workers.iter().find(|(wi, _)| *wi == pinned);
+                        if worker.is_none() {
+                            warn!(
+                                %replica_address,
+                                %pinned,
+                                "Domain is pinned to an unavailable worker; ignoring pin"
+                            );
+                        }
+                        worker
+                    });
+
+                let worker_id = if pinned_worker.is_some() {
+                    // If the domain shard replica has been pinned to a worker, always use that
+                    pinned_worker
+                } else if dataflow_node_restrictions.is_empty() {
                     // If there are no placement restrictions, pick the node based on load-balancing
                     // heuristics
-                    available_workers.iter().min_by_key(|(wi, _)| {
+                    replica_workers.iter().min_by_key(|(wi, _)| {
                         let stats = self.worker_stats.get(wi).copied().unwrap_or_default();
 
                         if is_base_table_domain {
@@ -205,7 +261,7 @@ impl<'state> Scheduler<'state> {
                     // that meets the placement restrictions. This can lead to
                     // imbalance in the number of dataflow nodes placed on each
                     // server.
-                    available_workers.iter().find(|(_, worker)| {
+                    replica_workers.iter().find(|(_, worker)| {
                         worker_meets_restrictions(worker, &dataflow_node_restrictions)
                     })
                 }
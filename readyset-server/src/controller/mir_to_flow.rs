@@ -12,9 +12,12 @@ use std::iter;
 
 use common::DfValue;
 use dataflow::node::Column as DfColumn;
+use dataflow::ops::grouped::approx::{ApproxAggregator, Approximation};
 use dataflow::ops::grouped::concat::GroupConcat;
+use dataflow::ops::grouped::percentile::Percentile;
 use dataflow::ops::join::{Join, JoinType};
 use dataflow::ops::project::Project;
+use dataflow::ops::window::WindowFunction;
 use dataflow::ops::Side;
 use dataflow::{node, ops, Expr as DfExpr, PostLookupAggregates, ReaderProcessing};
 use itertools::Itertools;
@@ -89,6 +92,7 @@ pub(super) fn mir_node_to_flow_parts(
                     ref on,
                     ref group_by,
                     ref kind,
+                    distinct,
                     ..
                 } => {
                     invariant_eq!(ancestors.len(), 1);
@@ -101,6 +105,7 @@ pub(super) fn mir_node_to_flow_parts(
                         on,
                         group_by,
                         GroupedNodeType::Aggregation(kind.clone()),
+                        distinct,
                         mig,
                     )?)
                 }
@@ -133,6 +138,7 @@ pub(super) fn mir_node_to_flow_parts(
                         on,
                         group_by,
                         GroupedNodeType::Extremum(kind.clone()),
+                        false,
                         mig,
                     )?)
                 }
@@ -263,6 +269,27 @@ pub(super) fn mir_node_to_flow_parts(
                         mig,
                     )?)
                 }
+                MirNodeInner::FullJoin {
+                    ref on,
+                    ref project,
+                    ..
+                } => {
+                    invariant_eq!(ancestors.len(), 2);
+                    let left = ancestors[0];
+                    let right = ancestors[1];
+                    Some(make_join_node(
+                        graph,
+                        name,
+                        left,
+                        right,
+                        &graph.columns(mir_node),
+                        on,
+                        project,
+                        JoinType::Full,
+                        custom_types,
+                        mig,
+                    )?)
+                }
                 MirNodeInner::Project { ref emit } => {
                     invariant_eq!(ancestors.len(), 1);
                     let parent = ancestors[0];
@@ -330,6 +357,25 @@ pub(super) fn mir_node_to_flow_parts(
                         mig,
                     )?)
                 }
+                MirNodeInner::Window {
+                    function,
+                    ref partition_by,
+                    ref order,
+                    ..
+                } => {
+                    invariant_eq!(ancestors.len(), 1);
+                    let parent = ancestors[0];
+                    Some(make_window_node(
+                        graph,
+                        name,
+                        parent,
+                        &graph.columns(mir_node),
+                        function,
+                        partition_by,
+                        order,
+                        mig,
+                    )?)
+                }
                 MirNodeInner::AliasTable { .. } => None,
             };
 
@@ -370,18 +416,9 @@ fn make_base_node(
         .map(|cs| DfColumn::from_spec(cs.clone(), mig.dialect, |ty| custom_types.get(&ty).cloned()))
         .collect::<Result<Vec<_>, _>>()?;
 
-    // note that this defaults to a "None" (= NULL) default value for columns that do not have one
-    // specified; we don't currently handle a "NOT NULL" SQL constraint for defaults
     let default_values = column_specs
         .iter()
-        .map(|cs| {
-            for c in &cs.constraints {
-                if let ColumnConstraint::DefaultValue(Expr::Literal(ref dv)) = *c {
-                    return dv.try_into();
-                }
-            }
-            Ok(DfValue::None)
-        })
+        .map(column_default_value)
         .collect::<Result<Vec<DfValue>, _>>()?;
 
     let cols_from_spec = |cols: &[Column]| -> ReadySetResult<Vec<usize>> {
@@ -417,6 +454,33 @@ fn make_base_node(
     Ok(DfNodeIndex::new(mig.add_base(name, columns, base)))
 }
 
+/// Returns the default value of the column with the given spec, which is used to fill in the column
+/// in rows that don't have a value for it.
+///
+/// note that this defaults to a "None" (= NULL) default value for columns that do not have one
+/// specified; we don't currently handle a "NOT NULL" SQL constraint for defaults
+fn column_default_value(spec: &ColumnSpecification) -> ReadySetResult<DfValue> {
+    for c in &spec.constraints {
+        if let ColumnConstraint::DefaultValue(Expr::Literal(ref dv)) = *c {
+            return dv.try_into();
+        }
+    }
+    Ok(DfValue::None)
+}
+
+/// Add the column with the given spec to the end of the existing base table node `node`, filling
+/// it in with the column's default value in all the rows that are already in the table.
+pub(super) fn add_base_column(
+    node: NodeIndex,
+    spec: ColumnSpecification,
+    custom_types: &HashMap<Relation, DfType>,
+    mig: &mut Migration<'_>,
+) -> ReadySetResult<usize> {
+    let default = column_default_value(&spec)?;
+    let column = DfColumn::from_spec(spec, mig.dialect, |ty| custom_types.get(&ty).cloned())?;
+    mig.add_column(node, column, default)
+}
+
 fn make_union_node(
     graph: &MirGraph,
     name: Relation,
@@ -522,6 +586,7 @@ fn make_grouped_node(
     on: &Column,
     group_by: &[Column],
     kind: GroupedNodeType,
+    distinct: bool,
     mig: &mut Migration<'_>,
 ) -> ReadySetResult<DfNodeIndex> {
     let parent_na = graph.resolve_dataflow_node(parent).ok_or_else(|| {
@@ -573,13 +638,60 @@ fn make_grouped_node(
             set_names(&column_names(columns), &mut cols)?;
             mig.add_ingredient(name, cols, gc)
         }
-        GroupedNodeType::Aggregation(agg) => {
-            let grouped = agg.over(
+        // Like GroupConcat, percentiles need their own operator in dataflow
+        GroupedNodeType::Aggregation(Aggregation::Percentile { percentile }) => {
+            let pct = Percentile::new(
                 parent_na.address(),
                 over_col_indx,
-                group_col_indx.as_slice(),
-                over_col_ty,
+                group_col_indx,
+                percentile,
+            )?;
+            let agg_col = make_agg_col(DfType::Double);
+            cols.push(agg_col);
+            set_names(&column_names(columns), &mut cols)?;
+            mig.add_ingredient(name, cols, pct)
+        }
+        // Approximate aggregates are maintained with sketches by their own operator
+        GroupedNodeType::Aggregation(Aggregation::ApproxCountDistinct) => {
+            let approx = ApproxAggregator::new(
+                parent_na.address(),
+                over_col_indx,
+                group_col_indx,
+                Approximation::CountDistinct,
+            )?;
+            let agg_col = make_agg_col(DfType::BigInt);
+            cols.push(agg_col);
+            set_names(&column_names(columns), &mut cols)?;
+            mig.add_ingredient(name, cols, approx)
+        }
+        GroupedNodeType::Aggregation(Aggregation::ApproxPercentile { percentile }) => {
+            let approx = ApproxAggregator::new(
+                parent_na.address(),
+                over_col_indx,
+                group_col_indx,
+                Approximation::Percentile { percentile },
             )?;
+            let agg_col = make_agg_col(DfType::Double);
+            cols.push(agg_col);
+            set_names(&column_names(columns), &mut cols)?;
+            mig.add_ingredient(name, cols, approx)
+        }
+        GroupedNodeType::Aggregation(agg) => {
+            let grouped = if distinct {
+                agg.over_distinct(
+                    parent_na.address(),
+                    over_col_indx,
+                    group_col_indx.as_slice(),
+                    over_col_ty,
+                )?
+            } else {
+                agg.over(
+                    parent_na.address(),
+                    over_col_indx,
+                    group_col_indx.as_slice(),
+                    over_col_ty,
+                )?
+            };
             let agg_col = make_agg_col(grouped.output_col_type().or_ref(over_col_ty).clone());
             cols.push(agg_col);
             set_names(&column_names(columns), &mut cols)?;
@@ -1105,6 +1217,60 @@ fn make_paginate_or_topk_node(
     Ok(DfNodeIndex::new(na))
 }
 
+fn make_window_node(
+    graph: &MirGraph,
+    name: Relation,
+    parent: MirNodeIndex,
+    columns: &[Column],
+    function: WindowFunction,
+    partition_by: &[Column],
+    order: &[(Column, OrderType)],
+    mig: &mut Migration<'_>,
+) -> ReadySetResult<DfNodeIndex> {
+    let parent_na = graph.resolve_dataflow_node(parent).ok_or_else(|| {
+        ReadySetError::MirNodeMustHaveDfNodeAssigned {
+            mir_node_index: parent.index(),
+        }
+    })?;
+    let mut parent_cols = mig.dataflow_state.ingredients[parent_na.address()]
+        .columns()
+        .to_vec();
+
+    // the result of the window function is projected last
+    let result_col_name = columns
+        .last()
+        .ok_or_else(|| internal_err!("No projected columns for window"))?
+        .name
+        .clone();
+    parent_cols.push(DfColumn::new(
+        result_col_name,
+        DfType::UnsignedBigInt,
+        Some(name.clone()),
+    ));
+    set_names(&column_names(columns), &mut parent_cols)?;
+
+    let partition_by_indx = partition_by
+        .iter()
+        .map(|c| graph.column_id_for_column(parent, c))
+        .collect::<ReadySetResult<Vec<_>>>()?;
+
+    let order_indx = order
+        .iter()
+        .map(|(c, order_type)| {
+            graph
+                .column_id_for_column(parent, c)
+                .map(|id| (id, *order_type))
+        })
+        .collect::<ReadySetResult<Vec<_>>>()?;
+
+    let na = mig.add_ingredient(
+        name,
+        parent_cols,
+        ops::window::Window::new(parent_na.address(), function, partition_by_indx, order_indx),
+    );
+    Ok(DfNodeIndex::new(na))
+}
+
 fn make_reader_processing(
     graph: &MirGraph,
     parent: &MirNodeIndex,
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Formatter};
 use std::future;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -19,11 +19,13 @@ use readyset_client::consensus::{
 };
 #[cfg(feature = "failure_injection")]
 use readyset_client::failpoints;
+use readyset_client::internal::ReplicaAddress;
 use readyset_client::metrics::recorded;
 use readyset_client::ControllerDescriptor;
 use readyset_data::Dialect;
 use readyset_errors::{internal, internal_err, ReadySetError, ReadySetResult};
 use readyset_telemetry_reporter::TelemetrySender;
+use readyset_util::redacted::RedactedString;
 use readyset_util::select;
 use readyset_util::shutdown::ShutdownReceiver;
 use serde::de::DeserializeOwned;
@@ -42,14 +44,19 @@ use crate::materialization::Materializations;
 use crate::worker::{WorkerRequest, WorkerRequestKind, WorkerRequestType};
 use crate::{Config, VolumeId};
 
+mod backup;
 mod domain_handle;
+#[cfg(test)]
+mod golden_plans;
 mod inner;
 mod keys;
 pub(crate) mod migrate; // crate viz for tests
 mod mir_to_flow;
+pub(crate) mod rebalance;
 pub(crate) mod replication;
 pub(crate) mod schema;
 pub(crate) mod sql;
+mod standby;
 mod state;
 
 /// Time between leader state change checks without thread parking.
@@ -75,6 +82,20 @@ pub struct NodeRestrictionKey {
     shard: usize,
 }
 
+/// A canary of a new version of ReadySet on a subset of domains, started with
+/// [`DfState::start_canary`].
+///
+/// While a canary is in progress, the canaried replicas of domain shards only run on workers whose
+/// version label matches the canary's, and all other replicas only run on workers whose version
+/// label doesn't.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct DomainCanary {
+    /// The version label of the workers the canaried replicas run on
+    version: String,
+    /// The replicas of domain shards being canaried
+    replicas: HashSet<ReplicaAddress>,
+}
+
 /// The full (metadata) state of a running ReadySet cluster.
 ///
 /// This struct is the root data structure that is serialized atomically and written to the
@@ -166,6 +187,8 @@ pub struct Worker {
     /// Configuration for how domains should be scheduled onto this worker
     domain_scheduling_config: WorkerSchedulingConfig,
     request_timeout: Duration,
+    /// Token to authenticate requests to the worker with
+    auth_token: Option<RedactedString>,
 }
 
 impl Worker {
@@ -173,18 +196,32 @@ impl Worker {
         instance_uri: Url,
         domain_scheduling_config: WorkerSchedulingConfig,
         request_timeout: Duration,
-    ) -> Self {
-        Worker {
+        auth_token: Option<RedactedString>,
+    ) -> ReadySetResult<Self> {
+        let http = match readyset_client::tls::internal() {
+            Some(tls) => {
+                reqwest::Client::builder().use_preconfigured_tls((*tls.client_config()).clone())
+            }
+            None => reqwest::Client::builder(),
+        }
+        .build()
+        .map_err(|e| internal_err!("could not build HTTP client for worker: {}", e))?;
+
+        Ok(Worker {
             healthy: true,
             uri: instance_uri,
-            http: reqwest::Client::new(),
+            http,
             domain_scheduling_config,
             request_timeout,
-        }
+            auth_token,
+        })
     }
     pub async fn rpc<T: DeserializeOwned>(&self, req: WorkerRequestKind) -> ReadySetResult<T> {
         let body = hyper::Body::from(bincode::serialize(&req)?);
-        let http_req = self.http.post(self.uri.join("worker_request")?).body(body);
+        let mut http_req = self.http.post(self.uri.join("worker_request")?).body(body);
+        if let Some(auth_token) = &self.auth_token {
+            http_req = http_req.bearer_auth(auth_token.as_str());
+        }
         let resp = http_req
             .timeout(self.request_timeout)
             .send()
@@ -204,6 +241,12 @@ impl Worker {
         if !status.is_success() {
             if status == reqwest::StatusCode::SERVICE_UNAVAILABLE {
                 return Err(ReadySetError::ServiceUnavailable);
+            } else if status == reqwest::StatusCode::UNAUTHORIZED
+                || status == reqwest::StatusCode::FORBIDDEN
+            {
+                return Err(ReadySetError::Unauthorized {
+                    path: "/worker_request".into(),
+                });
             } else if status == reqwest::StatusCode::BAD_REQUEST {
                 return Err(ReadySetError::SerializationFailed(
                     "remote server returned 400".into(),
@@ -504,6 +547,7 @@ impl Controller {
                     self.config.replicator_statement_logging,
                     self.config.replicator_config.clone(),
                     self.config.worker_request_timeout,
+                    self.config.http_auth.admin_token.clone(),
                 );
                 self.leader_ready.store(false, Ordering::Release);
 
@@ -773,10 +817,22 @@ impl AuthorityLeaderElectionState {
                 .update_controller_state(
                     |state: Option<ControllerState>| -> Result<ControllerState, ()> {
                         match state {
-                            None => {
-                                Ok(ControllerState::new(self.config.clone(), self.permissive_writes))
+                            None => match &self.config.restore_from {
+                                Some(dir) => backup::restore(dir, &self.config).map_err(|error| {
+                                    error!(%error, "Failed to restore deployment from backup");
+                                }),
+                                None => Ok(ControllerState::new(
+                                    self.config.clone(),
+                                    self.permissive_writes,
+                                )),
                             },
                             Some(mut state) => {
+                                if let Some(dir) = &self.config.restore_from {
+                                    warn!(
+                                        dir = %dir.display(),
+                                        "Deployment already has controller state, not restoring from backup"
+                                    );
+                                }
                                 // check that running config is compatible with the new
                                 // configuration.
                                 if state.config != self.config {
@@ -1111,13 +1167,14 @@ mod tests {
     use std::collections::{BTreeMap, HashSet};
 
     use nom_sql::{parse_create_table, parse_select_statement, Dialect, Relation};
+    use readyset_client::internal::{IndexType, MaterializationStatus};
     use readyset_client::recipe::changelist::{Change, ChangeList};
     use readyset_client::replication::ReplicationOffset;
     use readyset_client::{KeyCount, TableReplicationStatus, TableStatus, ViewCreateRequest};
     use readyset_data::Dialect as DataDialect;
     use readyset_util::eventually;
 
-    use crate::integration_utils::start_simple;
+    use crate::integration_utils::{start_simple, DEFAULT_SHARDING};
 
     #[tokio::test(flavor = "multi_thread")]
     async fn remove_query() {
@@ -1142,6 +1199,45 @@ mod tests {
         let queries = noria.views().await.unwrap();
         assert!(!queries.contains_key(&"test_query".into()));
 
+        let err = noria.remove_query(&"test_query".into()).await.unwrap_err();
+        assert!(err.caused_by_view_not_found());
+
+        shutdown_tx.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn create_cache_with_options() {
+        let (mut noria, shutdown_tx) = start_simple("create_cache_with_options").await;
+        noria
+            .extend_recipe(
+                ChangeList::from_str(
+                    "CREATE TABLE users (id INT PRIMARY KEY, name TEXT);
+                 CREATE CACHE q WITH (full_materialization = true, index_type = 'btree')
+                 FROM SELECT id FROM users WHERE name = ?;",
+                    DataDialect::DEFAULT_MYSQL,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let query =
+            parse_select_statement(Dialect::MySQL, "SELECT id FROM users WHERE name = ?").unwrap();
+        let plan = noria
+            .view_plan(
+                ViewCreateRequest::new(query, vec![]),
+                DataDialect::DEFAULT_MYSQL,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let reader = &plan.nodes[0];
+        assert!(matches!(
+            reader.materialization,
+            MaterializationStatus::Full
+        ));
+        assert_eq!(reader.indices[0].index_type, IndexType::BTreeMap);
+
         shutdown_tx.shutdown().await;
     }
 
@@ -1275,6 +1371,51 @@ mod tests {
         shutdown_tx.shutdown().await;
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reader_skew_report() {
+        let (mut noria, shutdown_tx) = start_simple("reader_skew_report").await;
+
+        noria
+            .extend_recipe(
+                ChangeList::from_str(
+                    "CREATE TABLE skew_test (id INT PRIMARY KEY, stuff TEXT);
+                 CREATE CACHE q1 FROM SELECT * FROM skew_test WHERE id = ?;",
+                    DataDialect::DEFAULT_MYSQL,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let mut table = noria.table("skew_test").await.unwrap();
+        table.insert(vec![1.into(), "abc".into()]).await.unwrap();
+        let mut view = noria
+            .view("q1")
+            .await
+            .unwrap()
+            .into_reader_handle()
+            .unwrap();
+        let view_idx = *view.node();
+        for _ in 0..10 {
+            view.lookup(&[1.into()], true).await.unwrap();
+        }
+
+        eventually!(run_test: {
+            noria.reader_skew_report().await
+        },
+        then_assert: |report| {
+            let report = report.unwrap();
+            let reader = report.readers.iter().find(|r| r.node == view_idx).unwrap();
+            assert_eq!(reader.shards.len(), DEFAULT_SHARDING);
+            assert_eq!(reader.shards.iter().map(|s| s.key_count).sum::<usize>(), 1);
+            assert_eq!(reader.shards.iter().map(|s| s.reads).sum::<u64>(), 10);
+            // Too few reads to flag the shard serving them as hot
+            assert!(!reader.is_skewed());
+        });
+
+        shutdown_tx.shutdown().await;
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn view_statuses() {
         let (mut noria, shutdown_tx) = start_simple("view_statuses").await;
@@ -0,0 +1,21 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// How the controller should redistribute domains between workers as workers join and leave the
+/// cluster.
+///
+/// Regardless of the policy, the domains running on a worker that leaves the cluster are always
+/// re-scheduled onto the remaining workers, and have their state re-populated via replays.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum RebalancePolicy {
+    /// Only move domains off of workers that have left the cluster
+    #[default]
+    Recover,
+    /// Additionally, whenever a worker joins or leaves the cluster, move domains from the workers
+    /// running the most domain shard replicas onto the workers running the fewest, until all
+    /// workers are running roughly the same number.
+    ///
+    /// Domains containing base tables, and domains with a replica pinned to a worker, are never
+    /// moved.
+    Balance,
+}
@@ -52,7 +52,10 @@ impl From<ReplicationOptions> for ReplicationStrategy {
 }
 
 impl ReplicationStrategy {
-    /// Determine the number of times a domain with the given nodes should be replicated
+    /// Determine the number of times a domain with the given nodes should be replicated.
+    ///
+    /// If any of the readers in the domain has been configured with its own number of replicas,
+    /// the largest of those takes precedence over the replication strategy.
     ///
     /// # Invariants
     ///
@@ -62,6 +65,18 @@ impl ReplicationStrategy {
         let has_reader = || domain_nodes.iter().any(|n| ingredients[*n].is_reader());
         let has_base = || domain_nodes.iter().any(|n| ingredients[*n].is_base());
 
+        if let Some(reader_replicas) = domain_nodes
+            .iter()
+            .filter_map(|n| ingredients[*n].as_reader()?.replicas())
+            .max()
+        {
+            if has_base() {
+                warn!("Found domain with both reader and base, not replicating");
+                return 1;
+            }
+            return reader_replicas;
+        }
+
         match *self {
             ReplicationStrategy::Never => 1,
             ReplicationStrategy::ReaderDomains(num_reader_replicas) => {
@@ -59,16 +59,23 @@ fn get_base_for_column(
         if source_node.is_base() {
             if let Some(Schema::Table(ref schema)) = recipe.schema_for(source_node.name()) {
                 let col_index = cols.first().unwrap().unwrap();
+                // Columns that were dropped from the table in place are still part of its base
+                // node, so the index of a column in the node doesn't necessarily match its index in
+                // the table's schema
+                let name = source_node.columns()[col_index].name();
+                let Some(field) = schema
+                    .fields
+                    .iter()
+                    .find(|f| f.column.name.as_str() == name)
+                else {
+                    return Ok(None);
+                };
                 #[allow(clippy::unwrap_used)] // occurs after implied table rewrite
                 return Ok(Some(ColumnBase {
-                    column: schema.fields[col_index].column.name.clone(),
-                    table: schema.fields[col_index]
-                        .column
-                        .table
-                        .as_ref()
-                        .unwrap()
-                        .clone(),
-                    constraints: schema.fields[col_index].constraints.clone(),
+                    column: field.column.name.clone(),
+                    table: field.column.table.as_ref().unwrap().clone(),
+                    sql_type: field.sql_type.clone(),
+                    constraints: field.constraints.clone(),
                 }));
             }
         }
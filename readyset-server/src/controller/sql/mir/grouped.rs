@@ -273,6 +273,11 @@ pub(super) fn post_lookup_aggregates(
                 Avg { .. } => {
                     unsupported!("Average is not supported as a post-lookup aggregate")
                 }
+                // The same value can occur in the groups of multiple keys, so distinct aggregates
+                // can't be combined by summing them
+                Count { distinct: true, .. } | Sum { distinct: true, .. } => {
+                    unsupported!("DISTINCT aggregates are not supported as post-lookup aggregates")
+                }
                 // Count and sum are handled the same way, as re-aggregating counts is
                 // done by just summing the numbers together
                 Count { .. } | CountStar | Sum { .. } => PostLookupAggregateFunction::Sum,
@@ -281,7 +286,22 @@ pub(super) fn post_lookup_aggregates(
                 GroupConcat { separator, .. } => PostLookupAggregateFunction::GroupConcat {
                     separator: separator.clone().unwrap_or_else(|| ",".to_owned()),
                 },
-                Call { .. } | Substring { .. } => continue,
+                // Percentiles of each key's group can't be combined into the percentile of the
+                // union of those groups
+                Median(_) | PercentileCont { .. } => {
+                    unsupported!("Percentiles are not supported as post-lookup aggregates")
+                }
+                ApproxCountDistinct(_) | ApproxPercentile { .. } => {
+                    unsupported!(
+                        "Approximate aggregates are not supported as post-lookup aggregates"
+                    )
+                }
+                Call { .. }
+                | Substring { .. }
+                | Extract { .. }
+                | Trim { .. }
+                | Window { .. }
+                | Match { .. } => continue,
             },
         });
     }
@@ -48,6 +48,7 @@ pub(super) fn make_joins(
         let (mut join_kind, jps) = match &qg.edges[&(jref.src.clone(), jref.dst.clone())] {
             QueryGraphEdge::Join { on } => (JoinKind::Inner, on),
             QueryGraphEdge::LeftJoin { on } => (JoinKind::Left, on),
+            QueryGraphEdge::FullJoin { on } => (JoinKind::Full, on),
         };
 
         let (left_chain, right_chain) =
@@ -60,6 +61,10 @@ pub(super) fn make_joins(
                     "Dependent left join not yet supported (when joining to {})",
                     jref.dst.display_unquoted()
                 ),
+                JoinKind::Full => internal!(
+                    "Dependent full join not yet supported (when joining to {})",
+                    jref.dst.display_unquoted()
+                ),
                 JoinKind::Inner => {
                     join_kind = JoinKind::Dependent;
                 }
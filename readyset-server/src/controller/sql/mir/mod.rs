@@ -5,9 +5,10 @@ use std::vec::Vec;
 
 use ::serde::{Deserialize, Serialize};
 use catalog_tables::is_catalog_table;
-use common::IndexType;
+use common::{DfValue, IndexType};
 use dataflow::ops::grouped::aggregate::Aggregation;
 use dataflow::ops::union;
+use dataflow::ops::window::WindowFunction;
 use lazy_static::lazy_static;
 use mir::graph::MirGraph;
 use mir::node::node_inner::MirNodeInner;
@@ -38,7 +39,9 @@ use crate::controller::sql::mir::grouped::{
     post_lookup_aggregates,
 };
 use crate::controller::sql::mir::join::{make_cross_joins, make_joins};
-use crate::controller::sql::query_graph::{to_query_graph, OutputColumn, Pagination, QueryGraph};
+use crate::controller::sql::query_graph::{
+    to_query_graph, OutputColumn, Pagination, QueryGraph, ViewKey,
+};
 use crate::controller::sql::query_signature::Signature;
 
 mod grouped;
@@ -83,6 +86,25 @@ fn value_columns_needed_for_predicates(
         .collect()
 }
 
+/// Returns the fraction given as the percentile argument to the percentile aggregate `function`
+/// (`PERCENTILE_CONT` or `APPROX_PERCENTILE`), which must be a constant number between 0 and 1
+fn percentile_fraction(function: &str, percentile: &Literal) -> ReadySetResult<f64> {
+    if matches!(percentile, Literal::Placeholder(_)) {
+        unsupported!("{} does not support parameters as the percentile", function);
+    }
+    let fraction = DfValue::try_from(percentile)
+        .and_then(|v| f64::try_from(&v))
+        .map_err(|_| invalid_err!("{} percentile must be a number", function))?;
+    if !(0.0..=1.0).contains(&fraction) {
+        return Err(invalid_err!(
+            "{} percentile {} is not between 0 and 1",
+            function,
+            fraction
+        ));
+    }
+    Ok(fraction)
+}
+
 /// The result of removing a relation from MIR.
 #[derive(Default)]
 pub struct MirRemovalResult {
@@ -101,6 +123,8 @@ pub enum JoinKind {
     Inner,
     /// Left joins - see [`MirNodeInner::LeftJoin`]
     Left,
+    /// Full outer joins - see [`MirNodeInner::FullJoin`]
+    Full,
     /// Dependent joins - see [`MirNodeInner::DependentJoin`]
     Dependent,
 }
@@ -365,6 +389,43 @@ impl SqlToMirConverter {
         })
     }
 
+    /// Returns the column specifications of the base table with the given name.
+    ///
+    /// Columns that have been dropped from the table in place are still included, since they're
+    /// never removed from the table's dataflow node, so the index of each column here is its index
+    /// in the rows of that node.
+    pub(super) fn base_column_specs(
+        &self,
+        table: &Relation,
+    ) -> ReadySetResult<&[ColumnSpecification]> {
+        let ni = self
+            .get_relation(table)
+            .ok_or_else(|| self.table_not_found_err(table))?;
+        match &self.mir_graph[ni].inner {
+            MirNodeInner::Base { column_specs, .. } => Ok(column_specs),
+            _ => internal!("{} is not a base table", table.display_unquoted()),
+        }
+    }
+
+    /// Adds a column with the given spec to the end of the columns of the base table with the
+    /// given name.
+    pub(super) fn add_base_column(
+        &mut self,
+        table: &Relation,
+        spec: ColumnSpecification,
+    ) -> ReadySetResult<()> {
+        let ni = self
+            .get_relation(table)
+            .ok_or_else(|| self.table_not_found_err(table))?;
+        match &mut self.mir_graph[ni].inner {
+            MirNodeInner::Base { column_specs, .. } => {
+                column_specs.push(spec);
+                Ok(())
+            }
+            _ => internal!("{} is not a base table", table.display_unquoted()),
+        }
+    }
+
     /// Removes a cached query/view from MIR, along with all views/cached queries that depend on
     /// it.
     pub(super) fn remove_query(&mut self, name: &Relation) -> ReadySetResult<MirRemovalResult> {
@@ -450,6 +511,32 @@ impl SqlToMirConverter {
         self.mir_graph.node_weight(node)
     }
 
+    /// Override the type of index used by the reader for the given leaf node. The new index type
+    /// must be able to support all the lookups the query requires.
+    pub(super) fn set_leaf_index_type(
+        &mut self,
+        leaf: NodeIndex,
+        index_type: IndexType,
+    ) -> ReadySetResult<()> {
+        match self
+            .mir_graph
+            .node_weight_mut(leaf)
+            .map(|node| &mut node.inner)
+        {
+            Some(MirNodeInner::Leaf {
+                index_type: leaf_index_type,
+                ..
+            }) => {
+                if index_type < *leaf_index_type {
+                    unsupported!("Query requires a {leaf_index_type:?} index, not {index_type:?}");
+                }
+                *leaf_index_type = index_type;
+                Ok(())
+            }
+            _ => internal!("Node {} is not a leaf", leaf.index()),
+        }
+    }
+
     fn add_query_node(
         &mut self,
         query_name: Relation,
@@ -744,34 +831,16 @@ impl SqlToMirConverter {
             };
         }
 
-        let mut out_nodes = Vec::new();
-
         let mknode = |over: Column, t: GroupedNodeType, distinct: bool| {
-            if distinct {
-                let new_name = format!("{}_d{}", name.display_unquoted(), out_nodes.len()).into();
-                let mut dist_col = vec![over.clone()];
-                dist_col.extend(group_cols.clone());
-                let node = self.make_distinct_node(query_name, new_name, parent, dist_col.clone());
-                out_nodes.push(node);
-                out_nodes.push(self.make_grouped_node(
-                    query_name,
-                    name,
-                    func_col,
-                    (node, over),
-                    group_cols,
-                    t,
-                ));
-            } else {
-                out_nodes.push(self.make_grouped_node(
-                    query_name,
-                    name,
-                    func_col,
-                    (parent, over),
-                    group_cols,
-                    t,
-                ));
-            }
-            out_nodes
+            vec![self.make_grouped_node(
+                query_name,
+                name,
+                func_col,
+                (parent, over),
+                group_cols,
+                t,
+                distinct,
+            )]
         };
 
         Ok(match function {
@@ -879,6 +948,90 @@ impl SqlToMirConverter {
                 }),
                 false,
             ),
+            Median(box Expr::Column(col)) => mknode(
+                Column::from(col),
+                GroupedNodeType::Aggregation(Aggregation::Percentile { percentile: 0.5 }),
+                false,
+            ),
+            Median(ref expr) => mknode(
+                // TODO(celine): replace with ParentRef
+                Column::named(
+                    projected_exprs
+                        .get(expr)
+                        .cloned()
+                        .ok_or_else(|| mk_error!(expr))?,
+                ),
+                GroupedNodeType::Aggregation(Aggregation::Percentile { percentile: 0.5 }),
+                false,
+            ),
+            PercentileCont {
+                expr: box Expr::Column(col),
+                ref percentile,
+            } => mknode(
+                Column::from(col),
+                GroupedNodeType::Aggregation(Aggregation::Percentile {
+                    percentile: percentile_fraction("PERCENTILE_CONT", percentile)?,
+                }),
+                false,
+            ),
+            PercentileCont {
+                ref expr,
+                ref percentile,
+            } => mknode(
+                // TODO(celine): replace with ParentRef
+                Column::named(
+                    projected_exprs
+                        .get(expr)
+                        .cloned()
+                        .ok_or_else(|| mk_error!(expr))?,
+                ),
+                GroupedNodeType::Aggregation(Aggregation::Percentile {
+                    percentile: percentile_fraction("PERCENTILE_CONT", percentile)?,
+                }),
+                false,
+            ),
+            ApproxCountDistinct(box Expr::Column(col)) => mknode(
+                Column::from(col),
+                GroupedNodeType::Aggregation(Aggregation::ApproxCountDistinct),
+                false,
+            ),
+            ApproxCountDistinct(ref expr) => mknode(
+                // TODO(celine): replace with ParentRef
+                Column::named(
+                    projected_exprs
+                        .get(expr)
+                        .cloned()
+                        .ok_or_else(|| mk_error!(expr))?,
+                ),
+                GroupedNodeType::Aggregation(Aggregation::ApproxCountDistinct),
+                false,
+            ),
+            ApproxPercentile {
+                expr: box Expr::Column(col),
+                ref percentile,
+            } => mknode(
+                Column::from(col),
+                GroupedNodeType::Aggregation(Aggregation::ApproxPercentile {
+                    percentile: percentile_fraction("APPROX_PERCENTILE", percentile)?,
+                }),
+                false,
+            ),
+            ApproxPercentile {
+                ref expr,
+                ref percentile,
+            } => mknode(
+                // TODO(celine): replace with ParentRef
+                Column::named(
+                    projected_exprs
+                        .get(expr)
+                        .cloned()
+                        .ok_or_else(|| mk_error!(expr))?,
+                ),
+                GroupedNodeType::Aggregation(Aggregation::ApproxPercentile {
+                    percentile: percentile_fraction("APPROX_PERCENTILE", percentile)?,
+                }),
+                false,
+            ),
             _ => {
                 internal!("not an aggregate: {:?}", Sensitive(&function));
             }
@@ -893,6 +1046,7 @@ impl SqlToMirConverter {
         (parent_node, on): (NodeIndex, Column),
         group_by: Vec<Column>,
         node_type: GroupedNodeType,
+        distinct: bool,
     ) -> NodeIndex {
         self.add_query_node(
             query_name.clone(),
@@ -904,6 +1058,7 @@ impl SqlToMirConverter {
                         group_by,
                         output_column,
                         kind,
+                        distinct,
                     },
                 ),
                 GroupedNodeType::Extremum(kind) => MirNode::new(
@@ -987,6 +1142,7 @@ impl SqlToMirConverter {
         let inner = match kind {
             JoinKind::Inner => MirNodeInner::Join { on, project },
             JoinKind::Left => MirNodeInner::LeftJoin { on, project },
+            JoinKind::Full => MirNodeInner::FullJoin { on, project },
             JoinKind::Dependent => MirNodeInner::DependentJoin { on, project },
         };
         trace!(?inner, "Added join node");
@@ -1142,6 +1298,87 @@ impl SqlToMirConverter {
         Ok(nodes)
     }
 
+    /// Make a window node computing the given window function call, with its result in a column
+    /// named `alias`
+    fn make_window_node(
+        &mut self,
+        query_name: &Relation,
+        name: Relation,
+        parent: NodeIndex,
+        function: &FunctionExpr,
+        alias: &SqlIdentifier,
+        view_key: &ViewKey,
+    ) -> ReadySetResult<NodeIndex> {
+        let FunctionExpr::Window {
+            function,
+            partition_by,
+            order,
+        } = function
+        else {
+            internal!("make_window_node called with a non-window function");
+        };
+
+        let column = |expr: &Expr| -> ReadySetResult<Column> {
+            match expr {
+                Expr::Column(col) => Ok(Column::from(col)),
+                _ => unsupported!("Only column references are currently supported in OVER clauses"),
+            }
+        };
+
+        // The query's parameters filter rows *before* the window function is computed, but are
+        // only applied by the reader, so we also partition by the parameter columns to compute the
+        // window function separately for each key. This only gives the right results for
+        // equality parameters
+        if view_key.index_type != IndexType::HashMap {
+            unsupported!("Range parameters are not supported in queries with window functions");
+        }
+        let mut partition_by = partition_by
+            .iter()
+            .map(column)
+            .collect::<ReadySetResult<Vec<_>>>()?;
+        for (col, _) in &view_key.columns {
+            if col.name != *PAGE_NUMBER_COL && !partition_by.contains(col) {
+                partition_by.push(col.clone());
+            }
+        }
+
+        let order = order
+            .iter()
+            .flat_map(|order| &order.order_by)
+            .map(|(field, ot)| -> ReadySetResult<_> {
+                match field {
+                    FieldReference::Expr(expr) => {
+                        Ok((column(expr)?, ot.unwrap_or(OrderType::OrderAscending)))
+                    }
+                    FieldReference::Numeric(_) => {
+                        unsupported!("Numeric field references are not supported in OVER clauses")
+                    }
+                }
+            })
+            .collect::<ReadySetResult<Vec<_>>>()?;
+
+        let function = match function {
+            nom_sql::WindowFunction::RowNumber => WindowFunction::RowNumber,
+            nom_sql::WindowFunction::Rank => WindowFunction::Rank,
+            nom_sql::WindowFunction::DenseRank => WindowFunction::DenseRank,
+            nom_sql::WindowFunction::CountStar => WindowFunction::CountStar,
+        };
+
+        Ok(self.add_query_node(
+            query_name.clone(),
+            MirNode::new(
+                name,
+                MirNodeInner::Window {
+                    function,
+                    partition_by,
+                    order,
+                    output_column: Column::named(alias.clone()),
+                },
+            ),
+            &[parent],
+        ))
+    }
+
     fn make_predicate_nodes(
         &mut self,
         query_name: &Relation,
@@ -1240,6 +1477,7 @@ impl SqlToMirConverter {
                     (group_proj, Column::named("__count_val")),
                     vec![Column::named("__count_grp")],
                     GroupedNodeType::Aggregation(Aggregation::Count),
+                    false,
                 );
                 // -> [0, <count>] for each row
 
@@ -1405,6 +1643,11 @@ impl SqlToMirConverter {
         //  Reuse should just require that we add the query name to the "owners" hashset in the
         //  reused nodes if the node properties are identical.
 
+        // A parametrized LIMIT is applied by the reader, so only the outermost query can have one
+        if query_graph.parametrized_limit && !leaf_behavior.should_make_leaf() {
+            unsupported!("Parametrized LIMIT is not supported in subqueries or views");
+        }
+
         // Canonical operator order: B-J-F-G-P-R
         // (Base, Join, Filter, GroupBy, Project, Reader)
         let leaf = {
@@ -1638,6 +1881,18 @@ impl SqlToMirConverter {
                 prev_node = subquery_leaf;
             }
 
+            // 9a. Add window nodes for window functions, which are computed after all filters
+            for (function, alias) in &query_graph.window_functions {
+                let name = format!(
+                    "q_{:x}_n{}",
+                    query_graph.signature().hash,
+                    self.mir_graph.node_count()
+                )
+                .into();
+                prev_node =
+                    self.make_window_node(query_name, name, prev_node, function, alias, &view_key)?;
+            }
+
             // 10. Get the final node
             let mut final_node = prev_node;
 
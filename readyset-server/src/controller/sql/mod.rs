@@ -6,11 +6,12 @@ use ::mir::visualize::GraphViz;
 use ::mir::DfNodeIndex;
 use ::serde::{Deserialize, Serialize};
 use nom_sql::{
-    CompoundSelectOperator, CompoundSelectStatement, CreateTableBody, FieldDefinitionExpr,
-    Relation, SelectSpecification, SelectStatement, SqlIdentifier, SqlType, TableExpr,
+    AlterTableDefinition, CompoundSelectOperator, CompoundSelectStatement, CreateTableBody,
+    CreateTableStatement, FieldDefinitionExpr, Relation, SelectSpecification, SelectStatement,
+    SqlIdentifier, SqlType, TableExpr, TableKey,
 };
 use petgraph::graph::NodeIndex;
-use readyset_client::recipe::changelist::{AlterTypeChange, Change};
+use readyset_client::recipe::changelist::{AlterTypeChange, CacheOptions, Change};
 use readyset_client::recipe::ChangeList;
 use readyset_data::{DfType, Dialect, PgEnumMetadata};
 use readyset_errors::{
@@ -26,7 +27,9 @@ use self::mir::{LeafBehavior, NodeIndex as MirNodeIndex, SqlToMirConverter};
 use self::query_graph::to_query_graph;
 pub(crate) use self::recipe::{QueryID, Recipe, Schema};
 use self::registry::ExprRegistry;
-use crate::controller::mir_to_flow::{mir_node_to_flow_parts, mir_query_to_flow_parts};
+use crate::controller::mir_to_flow::{
+    add_base_column, mir_node_to_flow_parts, mir_query_to_flow_parts,
+};
 use crate::controller::sql::registry::RecipeExpr;
 use crate::controller::Migration;
 use crate::sql::mir::MirRemovalResult;
@@ -119,6 +122,12 @@ pub(crate) struct SqlIncorporator {
 
     /// Whether or to treat failed writes to base tables as no-ops
     permissive_writes: bool,
+
+    /// The views and caches that have been compiled to dataflow, as they were defined *before*
+    /// being rewritten, along with the schema search path they were created with. Used to re-plan
+    /// them against the new schema of the tables they depend on when those tables change.
+    #[serde(default)]
+    original_expressions: HashMap<Relation, (RecipeExpr, Vec<SqlIdentifier>)>,
 }
 
 impl SqlIncorporator {
@@ -288,12 +297,38 @@ impl SqlIncorporator {
                     name,
                     statement,
                     always,
+                    options,
                 } => {
-                    self.add_query(name, *statement, always, &schema_search_path, mig)?;
+                    self.add_query(name, *statement, always, options, &schema_search_path, mig)?;
                 }
-                Change::AlterTable(_) => {
-                    // The only ALTER TABLE changes that can end up here (currently) are ones that
-                    // aren't relevant to ReadySet, so we can just ignore them.
+                change @ Change::AlterTable(_) if change.requires_resnapshot() => {
+                    return Err(ReadySetError::ResnapshotNeeded);
+                }
+                Change::AlterTable(mut stmt) => {
+                    if let Some(first_schema) = schema_search_path.first() {
+                        if stmt.table.schema.is_none() {
+                            stmt.table.schema = Some(first_schema.clone())
+                        }
+                    }
+                    if self
+                        .mir_converter
+                        .non_replicated_relations
+                        .contains(&stmt.table)
+                    {
+                        continue;
+                    }
+
+                    // Unparsed definitions require a resnapshot, so were handled above
+                    let Ok(definitions) = stmt.definitions else {
+                        internal!("ALTER TABLE definitions failed to parse");
+                    };
+                    self.alter_table(&stmt.table, definitions, &schema_search_path, dialect, mig)
+                        .map_err(|e| {
+                            e.context(format!(
+                                "while altering table {}",
+                                stmt.table.display_unquoted()
+                            ))
+                        })?;
                 }
                 Change::CreateType { mut name, ty } => {
                     if let Some(first_schema) = schema_search_path.first() {
@@ -524,10 +559,12 @@ impl SqlIncorporator {
         name: Option<Relation>,
         mut stmt: SelectStatement,
         always: bool,
+        options: CacheOptions,
         schema_search_path: &[SqlIdentifier],
         mig: &mut Migration<'_>,
     ) -> ReadySetResult<Relation> {
         let name = name.unwrap_or_else(|| format!("q_{}", self.num_queries).into());
+        let original_statement = stmt.clone();
 
         let mut invalidating_tables = vec![];
         let detect_placeholders_config =
@@ -583,10 +620,16 @@ impl SqlIncorporator {
             Err(err) => Err(err),
         }?;
 
+        if let (Some(mir_query), Some(index_type)) = (mir_query, options.index_type) {
+            self.mir_converter
+                .set_leaf_index_type(mir_query, index_type)?;
+        }
+
         let aliased = !self.registry.add_query(RecipeExpr::Cache {
             name: name.clone(),
             statement: stmt,
             always,
+            options: options.clone(),
         })?;
         self.registry
             .insert_invalidating_tables(name.clone(), invalidating_tables)?;
@@ -595,9 +638,23 @@ impl SqlIncorporator {
             return Ok(name);
         }
 
+        self.original_expressions.insert(
+            name.clone(),
+            (
+                RecipeExpr::Cache {
+                    name: name.clone(),
+                    statement: original_statement,
+                    always,
+                    options: options.clone(),
+                },
+                schema_search_path.to_vec(),
+            ),
+        );
+
         // We don't add a leaf if we're reusing a query
         if let Some(mir_query) = mir_query {
             let leaf = self.mir_to_dataflow(name.clone(), mir_query, mig)?;
+            mig.set_reader_options(leaf, options.full_materialization, options.replicas)?;
             self.leaf_addresses.insert(name.clone(), leaf);
         }
 
@@ -727,12 +784,13 @@ impl SqlIncorporator {
         let not_found_err = || self.mir_converter.table_not_found_err(table);
 
         let addr = self.leaf_addresses.get(table).ok_or_else(not_found_err)?;
+        // Use the column's position in MIR rather than in the table's schema, since the former
+        // also includes any columns that were dropped from the table in place
         let idx = self
-            .get_base_schema(table)
-            .ok_or_else(not_found_err)?
-            .fields
+            .mir_converter
+            .base_column_specs(table)?
             .iter()
-            .position(|f| f.column == *column)
+            .position(|cs| cs.column == *column)
             .ok_or_else(|| ReadySetError::NoSuchColumn(column.name.clone().into()))?;
         mig.set_column_type(*addr, idx, new_ty)?;
 
@@ -790,12 +848,22 @@ impl SqlIncorporator {
         Ok(Some(removal_result.dataflow_nodes_to_remove))
     }
 
+    /// Drop the given table and recreate it with the given `body`, then re-plan all the views and
+    /// caches that depended on it against the new schema.
+    ///
+    /// Since this all happens within the same migration, the dataflow nodes for the re-planned
+    /// caches replace the old ones (which are torn down) when the migration is committed, so
+    /// clients looking up a cache by name see either the old or the new version, but never no
+    /// cache at all. Any views or caches that are no longer valid against the new schema (eg
+    /// because they reference a column that was dropped) are dropped.
     fn drop_and_recreate_table(
         &mut self,
         table: &Relation,
         body: CreateTableBody,
         mig: &mut Migration,
     ) -> ReadySetResult<()> {
+        let dependents = self.dependent_expressions(table);
+
         let removed_node_indices = self.remove_expression(table, mig)?;
         if removed_node_indices.is_none() {
             error!(
@@ -812,6 +880,211 @@ impl SqlIncorporator {
             name: table.clone(),
             body,
         })?;
+
+        self.recreate_dependent_expressions(table, dependents, mig)
+    }
+
+    /// Apply the given `ALTER TABLE` definitions to the existing base table `table` in place.
+    ///
+    /// Columns added to the end of the table are added to its base node, which fills them in with
+    /// their default value in all the rows already in the table. Dropped columns are removed from
+    /// the table's schema, but are kept in its base node, which fills them in with their default
+    /// value in new writes. All the views and caches that depend on the table are then re-planned
+    /// against its new schema.
+    ///
+    /// If any of the definitions can't be applied in place, returns
+    /// [`ReadySetError::ResnapshotNeeded`] without making any changes.
+    fn alter_table(
+        &mut self,
+        table: &Relation,
+        definitions: Vec<AlterTableDefinition>,
+        schema_search_path: &[SqlIdentifier],
+        dialect: Dialect,
+        mig: &mut Migration<'_>,
+    ) -> ReadySetResult<()> {
+        if definitions
+            .iter()
+            .all(|def| matches!(def, AlterTableDefinition::ReplicaIdentity(_)))
+        {
+            // Nothing that's relevant to ReadySet
+            return Ok(());
+        }
+
+        let not_found_err = || self.mir_converter.table_not_found_err(table);
+        let mut body = self.get_base_schema(table).ok_or_else(not_found_err)?;
+        let addr = *self.leaf_addresses.get(table).ok_or_else(not_found_err)?;
+        // Rows are stored in the base node's state with the columns they were written with, which
+        // deletes can only be matched up with by the table's primary key
+        let has_primary_key = body
+            .keys
+            .iter()
+            .flatten()
+            .any(|key| matches!(key, TableKey::PrimaryKey { .. }));
+        let column_specs = self.mir_converter.base_column_specs(table)?;
+
+        let mut num_added = 0;
+        let mut dropped = vec![];
+        for definition in definitions {
+            match definition {
+                AlterTableDefinition::AddColumn {
+                    spec,
+                    position: None,
+                } if has_primary_key => {
+                    // Columns that were previously dropped in place are still part of the base
+                    // node, so can't be added again
+                    if column_specs
+                        .iter()
+                        .chain(&body.fields)
+                        .any(|cs| cs.column.name == spec.column.name)
+                    {
+                        return Err(ReadySetError::ResnapshotNeeded);
+                    }
+                    body.fields.push(spec);
+                    num_added += 1;
+                }
+                AlterTableDefinition::DropColumn { name, .. } if has_primary_key => {
+                    let pos = body
+                        .fields
+                        .iter()
+                        .position(|f| f.column.name == name)
+                        .ok_or_else(|| ReadySetError::NoSuchColumn(name.to_string()))?;
+                    let in_key = body.keys.iter().flatten().any(|key| match key {
+                        TableKey::PrimaryKey { columns, .. }
+                        | TableKey::UniqueKey { columns, .. }
+                        | TableKey::FulltextKey { columns, .. }
+                        | TableKey::Key { columns, .. }
+                        | TableKey::ForeignKey { columns, .. }
+                        | TableKey::SpatialKey { columns, .. } => {
+                            columns.iter().any(|c| c.name == name)
+                        }
+                        TableKey::CheckConstraint { .. } => false,
+                    });
+                    let Some(idx) = column_specs.iter().position(|cs| cs.column.name == name)
+                    else {
+                        // The column was added by this same statement
+                        return Err(ReadySetError::ResnapshotNeeded);
+                    };
+                    if in_key {
+                        return Err(ReadySetError::ResnapshotNeeded);
+                    }
+                    body.fields.remove(pos);
+                    dropped.push(idx);
+                }
+                AlterTableDefinition::ReplicaIdentity(_) => {}
+                _ => return Err(ReadySetError::ResnapshotNeeded),
+            }
+        }
+
+        // Qualify and normalize the added columns the same way as the columns in a CREATE TABLE
+        let body = self
+            .rewrite(
+                CreateTableStatement {
+                    if_not_exists: false,
+                    table: table.clone(),
+                    body: Ok(body),
+                    options: Ok(vec![]),
+                },
+                schema_search_path,
+                dialect,
+                None,
+            )?
+            .body
+            .map_err(|_| internal_err!("rewriting a CREATE TABLE can't fail to parse its body"))?;
+
+        if mig.changes.contains_new(&addr) {
+            // The table was created in this same migration, so has no rows to keep around
+            return self.drop_and_recreate_table(table, body, mig);
+        }
+
+        let dependents = self.dependent_expressions(table);
+        for (expr, _) in &dependents {
+            self.remove_expression(expr.name(), mig)?;
+        }
+
+        for spec in &body.fields[body.fields.len() - num_added..] {
+            add_base_column(addr, spec.clone(), &self.custom_types, mig)?;
+            self.mir_converter.add_base_column(table, spec.clone())?;
+        }
+        for idx in dropped {
+            mig.drop_column(addr, idx)?;
+        }
+
+        self.register_query(
+            table.clone(),
+            body.fields.iter().map(|f| f.column.name.clone()).collect(),
+        );
+        self.base_schemas.insert(table.clone(), body.clone());
+        invariant!(self.registry.replace_table_body(table, body));
+
+        self.recreate_dependent_expressions(table, dependents, mig)
+    }
+
+    /// Returns the views and caches that depend on the given table, along with the schema search
+    /// path each was created with, so that they can be re-created with
+    /// [`recreate_dependent_expressions`] after the table's schema changes.
+    ///
+    /// [`recreate_dependent_expressions`]: Self::recreate_dependent_expressions
+    fn dependent_expressions(&self, table: &Relation) -> Vec<(RecipeExpr, Vec<SqlIdentifier>)> {
+        self.registry
+            .dependent_expressions(table)
+            .into_iter()
+            .map(|expr| {
+                self.original_expressions
+                    .get(expr.name())
+                    .cloned()
+                    .unwrap_or_else(|| (expr.clone(), vec![]))
+            })
+            .collect()
+    }
+
+    /// Re-plan the given views and caches, which depended on `table` before its schema changed,
+    /// against the table's new schema. Any caches that are no longer valid against the new schema
+    /// are dropped.
+    fn recreate_dependent_expressions(
+        &mut self,
+        table: &Relation,
+        dependents: Vec<(RecipeExpr, Vec<SqlIdentifier>)>,
+        mig: &mut Migration<'_>,
+    ) -> ReadySetResult<()> {
+        // Views are only compiled once a cache selects from them, so re-add them all before any of
+        // the caches
+        let (views, caches): (Vec<_>, Vec<_>) = dependents
+            .into_iter()
+            .partition(|(expr, _)| matches!(expr, RecipeExpr::View { .. }));
+        for (expr, schema_search_path) in views.into_iter().chain(caches) {
+            match expr {
+                RecipeExpr::View { name, definition } => {
+                    self.add_view(name, definition, schema_search_path)?;
+                }
+                RecipeExpr::Cache {
+                    name,
+                    statement,
+                    always,
+                    options,
+                } => match self.add_query(
+                    Some(name.clone()),
+                    statement,
+                    always,
+                    options,
+                    &schema_search_path,
+                    mig,
+                ) {
+                    Ok(_) => debug!(
+                        cache = %name.display_unquoted(),
+                        table = %table.display_unquoted(),
+                        "Re-created cache after table schema changed"
+                    ),
+                    Err(error) => warn!(
+                        cache = %name.display_unquoted(),
+                        table = %table.display_unquoted(),
+                        %error,
+                        "Could not re-create cache after table schema changed; dropping it"
+                    ),
+                },
+                RecipeExpr::Table { .. } => {}
+            }
+        }
+
         Ok(())
     }
 
@@ -1049,6 +1322,10 @@ impl SqlIncorporator {
         uncompiled_view: UncompiledView,
         mig: &mut Migration<'_>,
     ) -> ReadySetResult<()> {
+        let original_expression = RecipeExpr::View {
+            name: uncompiled_view.name.clone(),
+            definition: uncompiled_view.definition.clone(),
+        };
         let UncompiledView {
             name,
             mut definition,
@@ -1083,6 +1360,9 @@ impl SqlIncorporator {
             return Ok(());
         }
 
+        self.original_expressions
+            .insert(name.clone(), (original_expression, schema_search_path));
+
         self.mir_to_dataflow(name, mir_leaf, mig)?;
 
         Ok(())
@@ -1156,6 +1436,7 @@ impl SqlIncorporator {
         for query in removal_result.relations_removed.iter() {
             self.leaf_addresses.remove(query);
             self.registry.remove_expression(query);
+            self.original_expressions.remove(query);
         }
         // Sadly, we don't use `DfNodeIndex` for migrations/df state, so we need to map them
         // to `NodeIndex`.
@@ -196,6 +196,7 @@ pub struct QueryGraphNode {
 pub enum QueryGraphEdge {
     Join { on: Vec<JoinPredicate> },
     LeftJoin { on: Vec<JoinPredicate> },
+    FullJoin { on: Vec<JoinPredicate> },
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
@@ -234,6 +235,12 @@ pub struct QueryGraph {
     /// If a single aggregate is projected as multiple aliases, only one will appear in this map,
     /// but both will appear in `self.columns` as [`OutputColumn::Data`] referencing that alias
     pub aggregates: HashMap<FunctionExpr, SqlIdentifier>,
+    /// Window functions in the query, in the order they appear in the projected fields, along with
+    /// the alias for each window function
+    ///
+    /// Like for [`aggregates`](Self::aggregates), window functions appear in `self.columns` as
+    /// [`OutputColumn::Data`] referencing their alias
+    pub window_functions: Vec<(FunctionExpr, SqlIdentifier)>,
     /// Set of columns that appear in the GROUP BY clause
     pub group_by: HashSet<Column>,
     /// Final set of projected columns in this query; may include literals in addition to the
@@ -256,6 +263,10 @@ pub struct QueryGraph {
     pub order: Option<Vec<(Column, OrderType)>>,
     /// The pagination (order, limit, offset) for the query, if any
     pub pagination: Option<Pagination>,
+    /// True if the query has a parametrized `LIMIT` (and optionally `OFFSET`). Rather than being
+    /// materialized in a TopK or Paginate node, the values of these are provided with each read
+    /// of the query's reader, which applies them after ordering the results of the lookup.
+    pub parametrized_limit: bool,
     /// True if the query is correlated (is a subquery that refers to columns in an outer query)
     pub is_correlated: bool,
 }
@@ -428,6 +439,7 @@ impl Hash for QueryGraph {
         aggregates.hash(state);
 
         // these fields are Vecs, so already ordered
+        self.window_functions.hash(state);
         self.columns.hash(state);
         self.fields.hash(state);
         self.default_row.hash(state);
@@ -436,6 +448,7 @@ impl Hash for QueryGraph {
         self.having_predicates.hash(state);
         self.order.hash(state);
         self.pagination.hash(state);
+        self.parametrized_limit.hash(state);
         self.is_correlated.hash(state);
     }
 }
@@ -673,6 +686,7 @@ fn classify_conditionals(
                     | Expr::Between { .. }
                     | Expr::Cast { .. }
                     | Expr::In { .. }
+                    | Expr::Interval { .. }
                     | Expr::Variable(_) => {
                         unsupported!(
                             "Unsupported right-hand side of condition expression: {}",
@@ -734,6 +748,7 @@ fn classify_conditionals(
         | Expr::NestedSelect(_)
         | Expr::Cast { .. }
         | Expr::Array(_)
+        | Expr::Interval { .. }
         | Expr::Variable(_) => global.push(ce.clone()),
     }
     Ok(())
@@ -769,6 +784,54 @@ fn collect_join_predicates(cond: Expr, out: &mut Vec<JoinPredicate>) -> ReadySet
     }
 }
 
+/// Replace any (unqualified) references in the provided HAVING expression to the aliases of
+/// fields in the SELECT list with the aliased expressions themselves, so that eg
+/// `SELECT count(*) AS c ... HAVING c > 1` filters on the value of the aggregate.
+///
+/// Columns in the tables of the query take precedence over aliases, so any column references that
+/// have already been qualified with a table are left untouched.
+fn expand_having_aliases(having_expr: &mut Expr, fields: &[FieldDefinitionExpr]) {
+    struct AliasExpander<'a> {
+        aliases: HashMap<&'a SqlIdentifier, &'a Expr>,
+    }
+
+    impl<'ast, 'a> VisitorMut<'ast> for AliasExpander<'a> {
+        type Error = !;
+
+        fn visit_expr(&mut self, expr: &'ast mut Expr) -> Result<(), Self::Error> {
+            if let Expr::Column(Column { name, table: None }) = expr {
+                if let Some(aliased) = self.aliases.get(name) {
+                    *expr = (*aliased).clone();
+                }
+                Ok(())
+            } else {
+                walk_expr(self, expr)
+            }
+        }
+
+        fn visit_select_statement(
+            &mut self,
+            _: &'ast mut SelectStatement,
+        ) -> Result<(), Self::Error> {
+            // Don't walk into subqueries
+            Ok(())
+        }
+    }
+
+    let aliases = fields
+        .iter()
+        .filter_map(|field| match field {
+            FieldDefinitionExpr::Expr {
+                expr,
+                alias: Some(alias),
+            } => Some((alias, expr)),
+            _ => None,
+        })
+        .collect();
+
+    let _ = AliasExpander { aliases }.visit_expr(having_expr);
+}
+
 /// Processes the provided HAVING expression by extracting aggregates, splitting predicates, and
 /// replacing aggregates in predicates with column references.
 ///
@@ -777,9 +840,12 @@ fn collect_join_predicates(cond: Expr, out: &mut Vec<JoinPredicate>) -> ReadySet
 /// map.
 fn extract_having_aggregates(
     having_expr: &Expr,
+    fields: &[FieldDefinitionExpr],
     aggregates: &mut HashMap<FunctionExpr, SqlIdentifier>,
 ) -> Vec<Expr> {
-    let mut having_predicates = split_conjunctions(iter::once(having_expr));
+    let mut having_expr = having_expr.clone();
+    expand_having_aliases(&mut having_expr, fields);
+    let mut having_predicates = split_conjunctions(iter::once(&having_expr));
 
     #[derive(Default)]
     struct AggregateFinder {
@@ -904,7 +970,16 @@ fn default_row_for_select(st: &SelectStatement) -> Option<Vec<DfValue>> {
                     FunctionExpr::Max(..) => DfValue::None,
                     FunctionExpr::Min(..) => DfValue::None,
                     FunctionExpr::GroupConcat { .. } => DfValue::None,
-                    FunctionExpr::Call { .. } | FunctionExpr::Substring { .. } => DfValue::None,
+                    FunctionExpr::Median(..) => DfValue::None,
+                    FunctionExpr::PercentileCont { .. } => DfValue::None,
+                    FunctionExpr::ApproxCountDistinct(..) => DfValue::Int(0),
+                    FunctionExpr::ApproxPercentile { .. } => DfValue::None,
+                    FunctionExpr::Call { .. }
+                    | FunctionExpr::Substring { .. }
+                    | FunctionExpr::Extract { .. }
+                    | FunctionExpr::Trim { .. }
+                    | FunctionExpr::Window { .. }
+                    | FunctionExpr::Match { .. } => DfValue::None,
                 },
                 _ => DfValue::None,
             })
@@ -914,6 +989,11 @@ fn default_row_for_select(st: &SelectStatement) -> Option<Vec<DfValue>> {
 
 #[allow(clippy::cognitive_complexity)]
 pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
+    if stmt.calc_found_rows {
+        // We'd have no way of returning the count to a subsequent `FOUND_ROWS()`
+        unsupported!("SQL_CALC_FOUND_ROWS is not supported");
+    }
+
     // a handy closure for making new relation nodes
     let new_node =
         |rel: Relation, preds: Vec<Expr>, st: &SelectStatement| -> ReadySetResult<QueryGraphNode> {
@@ -1128,14 +1208,33 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
             }
         };
 
+        // A right join is a left join with the sides swapped, so plan it as one, with the tables
+        // and the sides of the join predicates flipped
+        let (left_table, right_table, join_preds) = if jc.operator.is_right_join() {
+            let join_preds = join_preds
+                .into_iter()
+                .map(|JoinPredicate { left, right }| JoinPredicate {
+                    left: right,
+                    right: left,
+                })
+                .collect();
+            (right_table, left_table, join_preds)
+        } else {
+            (left_table, right_table, join_preds)
+        };
+
         // add edge for join
         // FIXME(eta): inefficient cloning!
         if let std::collections::hash_map::Entry::Vacant(e) =
             edges.entry((left_table.clone(), right_table.clone()))
         {
             e.insert(match jc.operator {
-                JoinOperator::LeftJoin | JoinOperator::LeftOuterJoin => {
-                    QueryGraphEdge::LeftJoin { on: join_preds }
+                JoinOperator::LeftJoin
+                | JoinOperator::LeftOuterJoin
+                | JoinOperator::RightJoin
+                | JoinOperator::RightOuterJoin => QueryGraphEdge::LeftJoin { on: join_preds },
+                JoinOperator::FullJoin | JoinOperator::FullOuterJoin => {
+                    QueryGraphEdge::FullJoin { on: join_preds }
                 }
                 JoinOperator::Join | JoinOperator::InnerJoin => {
                     QueryGraphEdge::Join { on: join_preds }
@@ -1241,11 +1340,12 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
     // necessarily return these in the query results.
     let mut aggregates = HashMap::new();
     let having_predicates = if let Some(having_expr) = stmt.having.as_ref() {
-        extract_having_aggregates(having_expr, &mut aggregates)
+        extract_having_aggregates(having_expr, &stmt.fields, &mut aggregates)
     } else {
         vec![]
     };
 
+    let mut window_functions: Vec<(FunctionExpr, SqlIdentifier)> = vec![];
     let mut columns = Vec::with_capacity(stmt.fields.len());
     for field in stmt.fields.iter() {
         match field {
@@ -1269,6 +1369,26 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
                             column: c.clone(),
                         });
                     }
+                    Expr::Call(function @ FunctionExpr::Window { .. }) => {
+                        let window_name = match window_functions.iter().find(|(f, _)| f == function)
+                        {
+                            Some((_, window_name)) => window_name.clone(),
+                            None => {
+                                window_functions.push((function.clone(), name.clone()));
+                                name.clone()
+                            }
+                        };
+                        // As with aggregates, the values for window functions will have already
+                        // been projected by the window nodes by the time we're projecting the
+                        // result set columns
+                        columns.push(OutputColumn::Data {
+                            alias: alias.clone().unwrap_or(name),
+                            column: Column {
+                                name: window_name,
+                                table: None,
+                            },
+                        })
+                    }
                     Expr::Call(function) if is_aggregate(function) => {
                         let agg_name = aggregates
                             .entry(function.clone())
@@ -1323,6 +1443,10 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
         Default::default()
     };
 
+    if !window_functions.is_empty() && (!aggregates.is_empty() || !group_by.is_empty()) {
+        unsupported!("Window functions are not supported in queries with aggregates");
+    }
+
     if let Some(ref order) = stmt.order {
         // For each column in the `ORDER BY` clause, check if it needs to be projected
         order
@@ -1401,8 +1525,22 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
         })
         .transpose()?;
 
+    // A parametrized LIMIT can't be materialized, so it's applied by the reader per-read instead
+    let parametrized_limit = matches!(stmt.limit_clause.limit(), Some(Literal::Placeholder(_)));
+    if parametrized_limit
+        && !matches!(
+            stmt.limit_clause.offset(),
+            None | Some(Literal::Placeholder(_)) | Some(Literal::UnsignedInteger(0))
+        )
+    {
+        unsupported!("Numeric OFFSETs must be parametrized");
+    }
+
     // Extract pagination parameters
-    let pagination = extract_limit_offset(&stmt.limit_clause)?
+    let pagination = (!parametrized_limit)
+        .then(|| extract_limit_offset(&stmt.limit_clause))
+        .transpose()?
+        .flatten()
         .map(|(limit, offset)| -> ReadySetResult<Pagination> {
             Ok(Pagination {
                 order: stmt
@@ -1455,6 +1593,7 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
         relations,
         edges,
         aggregates,
+        window_functions,
         group_by,
         columns,
         fields: stmt.fields.clone(),
@@ -1463,6 +1602,7 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
         global_predicates,
         having_predicates,
         pagination,
+        parametrized_limit,
         order,
         is_correlated: is_correlated(&stmt),
     })
@@ -1473,7 +1613,9 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
 #[cfg(test)]
 mod tests {
     use assert_unordered::assert_eq_unordered;
-    use nom_sql::{parse_query, parse_select_statement, Dialect, FunctionExpr, SqlQuery};
+    use nom_sql::{
+        parse_query, parse_select_statement, Dialect, FunctionExpr, SqlQuery, WindowFunction,
+    };
 
     use super::*;
 
@@ -1551,6 +1693,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn window_functions() {
+        let qg = make_query_graph(
+            "SELECT t.x, row_number() OVER (PARTITION BY t.y ORDER BY t.x) AS rn FROM t",
+        );
+        assert_eq!(qg.window_functions.len(), 1);
+        assert!(matches!(
+            qg.window_functions[0].0,
+            FunctionExpr::Window {
+                function: WindowFunction::RowNumber,
+                ..
+            }
+        ));
+        assert_eq!(qg.window_functions[0].1, "rn");
+        assert_eq!(
+            qg.columns,
+            vec![
+                OutputColumn::Data {
+                    alias: "x".into(),
+                    column: "t.x".into()
+                },
+                OutputColumn::Data {
+                    alias: "rn".into(),
+                    column: Column::from("rn")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn window_functions_with_aggregates() {
+        let query = parse_select_statement(
+            Dialect::MySQL,
+            "SELECT count(*), rank() OVER (ORDER BY t.x) FROM t GROUP BY t.x",
+        )
+        .unwrap();
+        to_query_graph(query).unwrap_err();
+    }
+
     #[test]
     fn having_predicates_and_aggregates() {
         let qg = make_query_graph("select t.x from t having t.x > 2;");
@@ -1611,6 +1792,35 @@ mod tests {
         assert_eq!(qg.aggregates, HashMap::from(expected_aggs));
     }
 
+    #[test]
+    fn having_references_aliases() {
+        let qg = make_query_graph(
+            "select t.x, count(t.y) as c from t group by t.x having c > 1 and count(t.y) < 5;",
+        );
+        let agg_column = || {
+            Box::new(Expr::Column(Column {
+                name: "count(`t`.`y`)".into(),
+                table: None,
+            }))
+        };
+        assert_eq_unordered!(
+            qg.having_predicates,
+            vec![
+                Expr::BinaryOp {
+                    lhs: agg_column(),
+                    op: BinaryOperator::Greater,
+                    rhs: Box::new(Expr::Literal(Literal::UnsignedInteger(1)))
+                },
+                Expr::BinaryOp {
+                    lhs: agg_column(),
+                    op: BinaryOperator::Less,
+                    rhs: Box::new(Expr::Literal(Literal::UnsignedInteger(5)))
+                },
+            ]
+        );
+        assert_eq!(qg.aggregates.len(), 1);
+    }
+
     #[test]
     fn with_subquery() {
         let qg = make_query_graph(
@@ -1636,6 +1846,41 @@ mod tests {
         to_query_graph(query).unwrap_err();
     }
 
+    #[test]
+    fn right_join_is_flipped_left_join() {
+        let qg = make_query_graph("SELECT t1.x, t2.y FROM t1 RIGHT JOIN t2 ON t1.id = t2.t1_id");
+        assert_eq!(
+            qg.edges,
+            HashMap::from([(
+                ("t2".into(), "t1".into()),
+                QueryGraphEdge::LeftJoin {
+                    on: vec![JoinPredicate {
+                        left: Expr::Column("t2.t1_id".into()),
+                        right: Expr::Column("t1.id".into()),
+                    }]
+                }
+            )])
+        );
+    }
+
+    #[test]
+    fn full_join() {
+        let qg =
+            make_query_graph("SELECT t1.x, t2.y FROM t1 FULL OUTER JOIN t2 ON t1.id = t2.t1_id");
+        assert_eq!(
+            qg.edges,
+            HashMap::from([(
+                ("t1".into(), "t2".into()),
+                QueryGraphEdge::FullJoin {
+                    on: vec![JoinPredicate {
+                        left: Expr::Column("t1.id".into()),
+                        right: Expr::Column("t2.t1_id".into()),
+                    }]
+                }
+            )])
+        );
+    }
+
     #[test]
     fn order_by_aggregate() {
         let qg = make_query_graph(
@@ -1956,5 +2201,25 @@ mod tests {
                 ]
             );
         }
+
+        #[test]
+        fn parametrized_limit() {
+            let qg = make_query_graph(
+                "SELECT t.x FROM t WHERE t.x = $1 ORDER BY t.y ASC LIMIT $2 OFFSET $3",
+            );
+            assert!(qg.parametrized_limit);
+            assert_eq!(qg.pagination, None);
+            assert!(qg.order.is_some());
+
+            // The limit and offset are provided with each read, so aren't part of the key
+            let key = qg.view_key(&mir::Config::default()).unwrap();
+            assert_eq!(
+                key.columns,
+                vec![(
+                    mir::Column::new(Some("t"), "x"),
+                    ViewPlaceholder::OneToOne(1, BinaryOperator::Equal)
+                )]
+            );
+        }
     }
 }
@@ -75,7 +75,9 @@ impl Signature for QueryGraph {
 
         for e in self.edges.values() {
             match e {
-                QueryGraphEdge::Join { on } | QueryGraphEdge::LeftJoin { on } => {
+                QueryGraphEdge::Join { on }
+                | QueryGraphEdge::LeftJoin { on }
+                | QueryGraphEdge::FullJoin { on } => {
                     on.iter()
                         .flat_map(|p| vec![&p.left, &p.right])
                         .flat_map(|p| p.referred_columns())
@@ -61,10 +61,13 @@ impl Recipe {
                 name,
                 statement,
                 always,
+                options,
             } => SqlQuery::CreateCache(CreateCacheStatement {
                 name: Some(name.clone()),
+                options: options.to_sql(),
                 inner: Ok(CacheInner::Statement(Box::new(statement.clone()))),
                 always: *always,
+                warmup: None,
             }),
         });
         if expr.is_none() {
@@ -188,6 +191,21 @@ impl Recipe {
         Ok(self.inc.registry.contains(&statement))
     }
 
+    /// Returns the name of the cache in `self` that, after rewriting according to `dialect`, is
+    /// semantically equivalent to the given `query`, if any.
+    ///
+    /// Returns an error if rewriting fails for any reason
+    pub(crate) fn cache_name_for(
+        &self,
+        query: ViewCreateRequest,
+        dialect: Dialect,
+    ) -> ReadySetResult<Option<Relation>> {
+        let statement =
+            self.inc
+                .rewrite(query.statement, &query.schema_search_path, dialect, None)?;
+        Ok(self.inc.registry.name_for(&statement).cloned())
+    }
+
     /// Returns the MatchedCaches for the query if they exists.
     pub fn reused_caches(&self, name: &Relation) -> Option<&Vec1<MatchedCache>> {
         self.inc.registry.reused_caches(name)
@@ -1,11 +1,12 @@
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use nom_sql::analysis::visit::{self, Visitor};
 use nom_sql::{
     CreateTableBody, CreateTableStatement, CreateViewStatement, ItemPlaceholder, Literal, Relation,
     SelectSpecification, SelectStatement, SqlType,
 };
+use readyset_client::recipe::changelist::CacheOptions;
 use readyset_client::PlaceholderIdx;
 use readyset_errors::{internal_err, unsupported_err, ReadySetError, ReadySetResult};
 use readyset_sql_passes::SelectStatementSkeleton;
@@ -36,6 +37,7 @@ pub(crate) enum RecipeExpr {
         name: Relation,
         statement: SelectStatement,
         always: bool,
+        options: CacheOptions,
     },
 }
 
@@ -437,6 +439,43 @@ impl ExprRegistry {
         Ok(true)
     }
 
+    /// Replaces the body of the table with the given name, keeping all of the [`RecipeExpr`]s that
+    /// depend on it. Returns `false` if there is no table with the given name.
+    pub(super) fn replace_table_body(&mut self, name: &Relation, body: CreateTableBody) -> bool {
+        let Some(old_id) = self.aliases.get(name).copied() else {
+            return false;
+        };
+        let name = match self.expressions.get(&old_id) {
+            Some(RecipeExpr::Table { name, .. }) => name.clone(),
+            _ => return false,
+        };
+        self.expressions.remove(&old_id);
+
+        let expression = RecipeExpr::Table { name, body };
+        let new_id = expression.calculate_hash();
+        debug!(?expression, %new_id, "Replacing table in the registry");
+
+        for query_id in self.aliases.values_mut() {
+            if *query_id == old_id {
+                *query_id = new_id;
+            }
+        }
+        if let Some(deps) = self.dependencies.remove(&old_id) {
+            self.dependencies.insert(new_id, deps);
+        }
+        for deps in self.custom_type_dependencies.values_mut() {
+            deps.remove(&old_id);
+        }
+        for ty in expression.custom_type_references() {
+            if let Some(deps) = self.custom_type_dependencies.get_mut(ty) {
+                deps.insert(new_id);
+            }
+        }
+
+        self.expressions.insert(new_id, expression);
+        true
+    }
+
     /// Retrieves the [`RecipeExpr`] associated with the given name or alias.
     /// If no query is found, returns `None`.
     pub(super) fn get(&self, alias: &Relation) -> Option<&RecipeExpr> {
@@ -452,6 +491,16 @@ impl ExprRegistry {
         self.expressions.contains_key(&expression.query_id())
     }
 
+    /// Retrieves the name of the [`RecipeExpr`] that is equivalent to the given expression, if any
+    pub(super) fn name_for<E>(&self, expression: &E) -> Option<&Relation>
+    where
+        E: RegistryExpr,
+    {
+        self.expressions
+            .get(&expression.query_id())
+            .map(RecipeExpr::name)
+    }
+
     /// Retrieves the original name for the query with the given `alias` (which might already be the
     /// original name). Returns `None` is there no [`RecipeExpr`] associated with the
     /// given `alias`.
@@ -582,6 +631,28 @@ impl ExprRegistry {
             .flatten()
     }
 
+    /// Returns all the views and caches that depend on the expression with the given name, either
+    /// directly or via other views
+    pub(super) fn dependent_expressions(&self, name: &Relation) -> Vec<&RecipeExpr> {
+        let mut res = vec![];
+        let mut seen = HashSet::new();
+        let mut queue = self
+            .aliases
+            .get(name)
+            .copied()
+            .into_iter()
+            .collect::<VecDeque<_>>();
+        while let Some(query_id) = queue.pop_front() {
+            for dependency_id in self.dependencies.get(&query_id).into_iter().flatten() {
+                if seen.insert(*dependency_id) {
+                    res.extend(self.expressions.get(dependency_id));
+                    queue.push_back(*dependency_id);
+                }
+            }
+        }
+        res
+    }
+
     /// Returns an iterator over a list of expressions that contain columns referencing the given
     /// custom type
     pub(super) fn expressions_referencing_custom_type(
@@ -732,6 +803,7 @@ mod tests {
                 statement: parse_select_statement(Dialect::MySQL, "SELECT * FROM test_table;")
                     .unwrap(),
                 always: false,
+                options: Default::default(),
             };
 
             assert_eq!(cached_query.name(), &query_name);
@@ -762,6 +834,7 @@ mod tests {
                 statement: parse_select_statement(Dialect::MySQL, "SELECT * FROM test_table;")
                     .unwrap(),
                 always: false,
+                options: Default::default(),
             };
 
             let cached_query_table_refs = cached_query.table_references();
@@ -806,6 +879,7 @@ mod tests {
                     name: "test_query".into(),
                     statement: statement.clone(),
                     always: false,
+                    options: Default::default(),
                 })
                 .unwrap();
             registry
@@ -813,6 +887,7 @@ mod tests {
                     name: "test_query_alias".into(),
                     statement,
                     always: false,
+                    options: Default::default(),
                 })
                 .unwrap();
 
@@ -823,6 +898,7 @@ mod tests {
                     name: "test_query".into(),
                     statement: statement.clone(),
                     always: false,
+                    options: Default::default(),
                 })
                 .unwrap();
             registry
@@ -830,6 +906,7 @@ mod tests {
                     name: "test_query_alias".into(),
                     statement,
                     always: false,
+                    options: Default::default(),
                 })
                 .unwrap();
 
@@ -866,6 +943,7 @@ mod tests {
                 )
                 .unwrap(),
                 always: false,
+                options: Default::default(),
             };
 
             assert!(registry.add_query(expr.clone()).unwrap());
@@ -883,6 +961,7 @@ mod tests {
                 statement: parse_select_statement(Dialect::MySQL, "SELECT * FROM test_table;")
                     .unwrap(),
                 always: false,
+                options: Default::default(),
             };
             assert!(!registry.add_query(expr).unwrap());
 
@@ -893,7 +972,8 @@ mod tests {
                     name: "test_query".into(),
                     statement: parse_select_statement(Dialect::MySQL, "SELECT * FROM test_table;")
                         .unwrap(),
-                    always: false
+                    always: false,
+                    options: Default::default()
                 }
             );
         }
@@ -1032,7 +1112,8 @@ mod tests {
                     name: "test_query".into(),
                     statement: parse_select_statement(Dialect::MySQL, "SELECT * FROM test_table")
                         .unwrap(),
-                    always: false
+                    always: false,
+                    options: Default::default()
                 }
             );
             assert!(registry.get(&"test_query_alias".into()).is_none())
@@ -1057,6 +1138,33 @@ mod tests {
             assert!(registry.aliases.is_empty());
         }
 
+        #[test]
+        fn replace_table_body() {
+            let mut registry = setup();
+            let name: Relation = "test_table".into();
+            let dependents = registry.dependent_expressions(&name).len();
+            let body = parse_create_table(
+                Dialect::MySQL,
+                "CREATE TABLE test_table (col1 INT, col2 TEXT);",
+            )
+            .unwrap()
+            .body
+            .unwrap();
+
+            assert!(registry.replace_table_body(&name, body.clone()));
+            assert_eq!(
+                registry.get(&name).unwrap(),
+                &RecipeExpr::Table {
+                    name: name.clone(),
+                    body: body.clone()
+                }
+            );
+            assert_eq!(registry.dependent_expressions(&name).len(), dependents);
+
+            assert!(!registry.replace_table_body(&"test_query".into(), body.clone()));
+            assert!(!registry.replace_table_body(&"nonexistent".into(), body));
+        }
+
         #[test]
         fn len() {
             let registry = setup();
@@ -1078,6 +1186,7 @@ mod tests {
                     name: "test".into(),
                     statement: stmt.clone(),
                     always: false,
+                    options: Default::default(),
                 })
                 .unwrap();
             assert!(registry.contains(&stmt))
@@ -1108,6 +1217,7 @@ mod tests {
                     statement: parse_select_statement(Dialect::MySQL, "SELECT * FROM test_table")
                         .unwrap(),
                     always: false,
+                    options: Default::default(),
                 })
                 .unwrap();
 
@@ -1194,7 +1304,8 @@ mod tests {
                 .add_query(RecipeExpr::Cache {
                     name: "foo".into(),
                     statement: query.clone(),
-                    always: false
+                    always: false,
+                    options: Default::default(),
                 })
                 .unwrap());
 
@@ -1350,6 +1461,7 @@ mod tests {
                     name: "test_query".into(),
                     statement: statement.clone(),
                     always: false,
+                    options: Default::default(),
                 })
                 .unwrap();
 
@@ -1372,6 +1484,7 @@ mod tests {
                     name: "alias".into(),
                     statement,
                     always: false,
+                    options: Default::default(),
                 })
                 .unwrap();
 
@@ -1405,6 +1518,7 @@ mod tests {
                     name: "query1".into(),
                     statement: statement1.clone(),
                     always: false,
+                    options: Default::default(),
                 })
                 .unwrap();
 
@@ -1413,6 +1527,7 @@ mod tests {
                     name: "query1_alias".into(),
                     statement: statement1,
                     always: false,
+                    options: Default::default(),
                 })
                 .unwrap();
 
@@ -1421,6 +1536,7 @@ mod tests {
                     name: "query2".into(),
                     statement: statement2,
                     always: false,
+                    options: Default::default(),
                 })
                 .unwrap();
 
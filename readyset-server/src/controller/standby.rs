@@ -0,0 +1,98 @@
+//! State for a ReadySet deployment running as a warm standby of another deployment (see
+//! `--standby`).
+//!
+//! A standby deployment starts out [seeding](StandbyState::Seeding) its base tables with a snapshot
+//! of the upstream database. Once the snapshot is complete it stops replicating from the upstream
+//! database, and enters the [`Standby`](StandbyState::Standby) state, in which it receives writes
+//! shipped to it by the primary deployment's replicator. When the standby is
+//! [promoted](StandbyState::Promoted) it resumes replicating from the upstream database, starting
+//! at the replication offsets of the last writes shipped to it.
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use readyset_client::status::StandbyState;
+use readyset_errors::{invalid_err, ReadySetResult};
+use tokio::sync::Notify;
+use tracing::info;
+
+pub(super) struct Standby {
+    state: Mutex<StandbyState>,
+    /// Notified by the replicator when it has finished seeding the standby
+    seeded: Arc<Notify>,
+    /// Notified when the standby is promoted
+    promoted: Notify,
+}
+
+impl Standby {
+    pub(super) fn new(state: StandbyState) -> Self {
+        Self {
+            state: Mutex::new(state),
+            seeded: Arc::new(Notify::new()),
+            promoted: Notify::new(),
+        }
+    }
+
+    /// Returns the current state of the standby
+    pub(super) fn state(&self) -> StandbyState {
+        *self.state.lock()
+    }
+
+    /// Returns the notification that the replicator should use to signal that it has finished
+    /// seeding the standby
+    pub(super) fn seeded(&self) -> Arc<Notify> {
+        Arc::clone(&self.seeded)
+    }
+
+    /// Mark the standby as having finished seeding, so that it can start receiving writes from the
+    /// primary deployment
+    pub(super) fn finish_seeding(&self) {
+        let mut state = self.state.lock();
+        if *state == StandbyState::Seeding {
+            info!("Finished seeding standby; now waiting for writes from the primary deployment");
+            *state = StandbyState::Standby;
+        }
+    }
+
+    /// Promote the standby, so that it resumes replicating from the upstream database.
+    ///
+    /// Returns an error if the standby has not finished seeding, or has already been promoted.
+    pub(super) fn promote(&self) -> ReadySetResult<()> {
+        let mut state = self.state.lock();
+        if *state != StandbyState::Standby {
+            return Err(invalid_err!(
+                "Cannot promote a standby in the {} state",
+                *state
+            ));
+        }
+        info!("Promoting standby deployment");
+        *state = StandbyState::Promoted;
+        self.promoted.notify_one();
+        Ok(())
+    }
+
+    /// Wait until the standby has been promoted
+    pub(super) async fn wait_for_promotion(&self) {
+        if self.state() != StandbyState::Promoted {
+            self.promoted.notified().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn promote_requires_seeding() {
+        let standby = Standby::new(StandbyState::Seeding);
+        assert!(standby.promote().is_err());
+        standby.finish_seeding();
+        assert_eq!(standby.state(), StandbyState::Standby);
+        standby.promote().unwrap();
+        assert_eq!(standby.state(), StandbyState::Promoted);
+        assert!(standby.promote().is_err());
+        standby.finish_seeding();
+        assert_eq!(standby.state(), StandbyState::Promoted);
+    }
+}
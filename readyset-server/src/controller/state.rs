@@ -13,19 +13,21 @@
 //! to manipulate it in a thread-safe way.
 
 use std::borrow::Cow;
-use std::cell;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::net::SocketAddr;
 use std::ops::Deref;
+use std::path::Path;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use std::{cell, iter};
 
 use array2::Array2;
 use common::IndexPair;
 use dataflow::payload::EvictRequest;
 use dataflow::prelude::{ChannelCoordinator, DomainIndex, DomainNodes, Graph, NodeIndex};
 use dataflow::{
-    DomainBuilder, DomainConfig, DomainRequest, NodeMap, Packet, PersistenceParameters, Sharding,
+    DomainBuilder, DomainConfig, DomainRequest, DurabilityMode, NodeMap, Packet,
+    PersistenceParameters, Sharding,
 };
 use futures::stream::{self, StreamExt, TryStreamExt};
 use futures::{FutureExt, TryStream};
@@ -39,26 +41,31 @@ use readyset_client::builders::{
     ReaderHandleBuilder, ReusedReaderHandleBuilder, TableBuilder, ViewBuilder,
 };
 use readyset_client::consensus::{Authority, AuthorityControl};
-use readyset_client::debug::info::GraphInfo;
+use readyset_client::debug::canary::{CanaryReplicaStats, CanaryReport, CanaryShard};
+use readyset_client::debug::info::{
+    DomainPlacement, GraphInfo, ReplicaPlacement, ViewPlan, ViewPlanIndex, ViewPlanNode,
+};
+use readyset_client::debug::skew::{ReaderShardStats, ReaderSkew, ReaderSkewReport};
 use readyset_client::debug::stats::{DomainStats, GraphStats, NodeStats};
-use readyset_client::internal::{MaterializationStatus, ReplicaAddress};
+use readyset_client::internal::{Index, MaterializationStatus, ReplicaAddress};
 use readyset_client::metrics::recorded;
 use readyset_client::recipe::changelist::{Change, ChangeList};
 use readyset_client::recipe::ExtendRecipeSpec;
 use readyset_client::replication::{ReplicationOffset, ReplicationOffsetState, ReplicationOffsets};
 use readyset_client::{
-    NodeSize, TableReplicationStatus, TableStatus, ViewCreateRequest, ViewFilter, ViewRequest,
-    ViewSchema,
+    KeyComparison, NodeSize, RocksDbTuning, TableReplicationStatus, TableStatus, ViewCreateRequest,
+    ViewFilter, ViewRequest, ViewSchema,
 };
-use readyset_data::Dialect;
+use readyset_data::{DfType, DfValue, Dialect};
 use readyset_errors::{
-    internal, internal_err, invariant_eq, NodeType, ReadySetError, ReadySetResult,
+    bad_request_err, internal, internal_err, invariant_eq, unsupported, NodeType, ReadySetError,
+    ReadySetResult,
 };
 use regex::Regex;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use tokio::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard};
-use tracing::{debug, error, instrument, trace, warn};
+use tracing::{debug, error, info, instrument, trace, warn};
 use vec1::Vec1;
 
 use super::migrate::DomainSettings;
@@ -70,7 +77,7 @@ use crate::controller::migrate::scheduling::Scheduler;
 use crate::controller::migrate::{routing, DomainMigrationPlan, Migration};
 use crate::controller::sql::Schema;
 use crate::controller::{
-    schema, ControllerState, DomainPlacementRestriction, NodeRestrictionKey, Worker,
+    schema, ControllerState, DomainCanary, DomainPlacementRestriction, NodeRestrictionKey, Worker,
     WorkerIdentifier,
 };
 use crate::coordination::{DomainDescriptor, RunDomainResponse};
@@ -123,6 +130,14 @@ pub struct DfState {
     /// Map from global node index to index pair for each domain
     #[serde(with = "serde_with::rust::hashmap_as_tuple_list")]
     pub(super) domain_node_index_pairs: HashMap<DomainIndex, HashMap<NodeIndex, IndexPair>>,
+    /// Replicas of domain shards which have been manually pinned to a particular worker via
+    /// [`DfState::move_domain`]. These take precedence over the scheduler's own heuristics.
+    #[serde(default, with = "serde_with::rust::hashmap_as_tuple_list")]
+    pub(super) domain_pins: HashMap<ReplicaAddress, WorkerIdentifier>,
+    /// The canary of a new ReadySet version on a subset of domains currently in progress, if any.
+    /// See [`DfState::start_canary`].
+    #[serde(default)]
+    pub(super) canary: Option<DomainCanary>,
 
     #[serde(skip)]
     pub(super) domains: HashMap<DomainIndex, DomainHandle>,
@@ -170,6 +185,8 @@ impl DfState {
             read_addrs: Default::default(),
             workers: Default::default(),
             domain_node_index_pairs: Default::default(),
+            domain_pins: Default::default(),
+            canary: None,
             replication_strategy,
         }
     }
@@ -299,6 +316,105 @@ impl DfState {
             .collect()
     }
 
+    /// Returns the plan for reading from the cache for the given query, or `None` if the query
+    /// is not cached.
+    ///
+    /// The plan consists of the query's reader node and all of its ancestors up to (and including)
+    /// the base tables it reads from, along with how each of those nodes is materialized.
+    pub(super) fn view_plan(
+        &self,
+        query: ViewCreateRequest,
+        dialect: Dialect,
+    ) -> ReadySetResult<Option<ViewPlan>> {
+        let name = match self.recipe.cache_name_for(query, dialect)? {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+        let node = match self.recipe.node_addr_for(&name) {
+            Ok(ni) => ni,
+            // If the cache reuses another cache, plan the read from the first cache it reuses
+            Err(_) => match self
+                .recipe
+                .reused_caches(&name)
+                .and_then(|caches| self.recipe.node_addr_for(caches.first().name()).ok())
+            {
+                Some(ni) => ni,
+                None => return Ok(None),
+            },
+        };
+        #[allow(clippy::indexing_slicing)] // just came from the recipe
+        let reader_name = self.ingredients[node].name().clone();
+        let reader = match self.find_reader_for(node, &reader_name, &None) {
+            Some(reader) => reader,
+            None => return Ok(None),
+        };
+
+        let mut nodes = Vec::new();
+        let mut seen = HashSet::from([reader]);
+        let mut queue = VecDeque::from([reader]);
+        while let Some(ni) = queue.pop_front() {
+            #[allow(clippy::indexing_slicing)] // came from self.ingredients
+            let n = &self.ingredients[ni];
+            let column_names = |index: &Index| ViewPlanIndex {
+                index_type: index.index_type,
+                columns: index
+                    .columns
+                    .iter()
+                    .map(|c| {
+                        n.columns()
+                            .get(*c)
+                            .map(|col| col.name().to_owned())
+                            .unwrap_or_else(|| c.to_string())
+                    })
+                    .collect(),
+            };
+
+            let parents = if n.is_base() {
+                vec![]
+            } else {
+                self.ingredients
+                    .neighbors_directed(ni, petgraph::EdgeDirection::Incoming)
+                    .filter(|p| *p != self.source)
+                    .collect::<Vec<_>>()
+            };
+            for parent in &parents {
+                if seen.insert(*parent) {
+                    queue.push_back(*parent);
+                }
+            }
+
+            let indices = match n.as_reader() {
+                Some(r) => r.index().into_iter().map(column_names).collect(),
+                None => self
+                    .materializations
+                    .indices_for(ni)
+                    .into_iter()
+                    .flatten()
+                    .map(column_names)
+                    .collect(),
+            };
+            let description = if n.is_base() {
+                "Base table".to_owned()
+            } else if n.is_reader() {
+                "Leaf view".to_owned()
+            } else {
+                n.description(true)
+            };
+
+            nodes.push(ViewPlanNode {
+                index: ni,
+                name: n.name().display_unquoted().to_string(),
+                description,
+                domain: n.domain(),
+                parents,
+                materialization: self.materializations.get_status(ni, n),
+                indices,
+            });
+        }
+
+        Ok(Some(ViewPlan { name, nodes }))
+    }
+
     pub(super) fn find_reader_for(
         &self,
         node: NodeIndex,
@@ -338,6 +454,22 @@ impl DfState {
         None
     }
 
+    /// Returns the index of the domain containing the reader node for the view with the given
+    /// name
+    pub(super) fn reader_domain(&self, name: &Relation) -> ReadySetResult<DomainIndex> {
+        let node = match self.recipe.node_addr_for(name) {
+            Ok(ni) => Some(ni),
+            Err(_) => self.views().get(name).copied(),
+        };
+        let name = self.recipe.resolve_alias(name).unwrap_or(name);
+        let reader = node
+            .and_then(|ni| self.find_reader_for(ni, name, &None))
+            .ok_or_else(|| ReadySetError::ViewNotFound(name.display_unquoted().to_string()))?;
+        #[allow(clippy::indexing_slicing)] // `find_reader_for` returns valid indices
+        let domain = self.ingredients[reader].domain();
+        Ok(domain)
+    }
+
     /// Create a ViewBuilder given the node, the ViewRequest, and an optional name if this view
     /// request is for a reused cache.
     fn view_builder_inner(
@@ -654,6 +786,7 @@ impl DfState {
             columns,
             schema,
             table_request_timeout: self.domain_config.table_request_timeout,
+            max_value_size: self.domain_config.max_value_size,
         }))
     }
 
@@ -697,6 +830,64 @@ impl DfState {
             .collect()
     }
 
+    /// Returns the placement of the shards and replicas of every domain onto workers, ordered by
+    /// domain index.
+    pub(super) fn domain_placement(&self) -> Vec<DomainPlacement> {
+        let mut res = self
+            .domains
+            .iter()
+            .map(|(di, dh)| {
+                let nodes = self
+                    .domain_nodes
+                    .get(di)
+                    .map(|nm| nm.values().copied().collect::<Vec<_>>())
+                    .unwrap_or_default();
+                #[allow(clippy::indexing_slicing)] // domain_nodes only contains valid indices
+                let has_base_table = nodes.iter().any(|ni| self.ingredients[*ni].is_base());
+                #[allow(clippy::indexing_slicing)] // domain_nodes only contains valid indices
+                let has_reader = nodes.iter().any(|ni| self.ingredients[*ni].is_reader());
+                let shards = dh
+                    .shards()
+                    .enumerate()
+                    .map(|(shard, replicas)| {
+                        replicas
+                            .iter()
+                            .enumerate()
+                            .map(|(replica, worker)| ReplicaPlacement {
+                                worker: worker.clone(),
+                                pinned: self.domain_pins.contains_key(&ReplicaAddress {
+                                    domain_index: *di,
+                                    shard,
+                                    replica,
+                                }),
+                            })
+                            .collect()
+                    })
+                    .collect();
+                #[allow(clippy::indexing_slicing)] // domain_nodes only contains valid indices
+                let nodes = nodes
+                    .into_iter()
+                    .map(|ni| {
+                        (
+                            ni,
+                            self.ingredients[ni].name().display_unquoted().to_string(),
+                        )
+                    })
+                    .collect();
+
+                DomainPlacement {
+                    domain_index: *di,
+                    shards,
+                    nodes,
+                    has_base_table,
+                    has_reader,
+                }
+            })
+            .collect::<Vec<_>>();
+        res.sort_by_key(|placement| placement.domain_index);
+        res
+    }
+
     pub(super) fn graphviz(
         &self,
         detailed: bool,
@@ -905,6 +1096,47 @@ impl DfState {
             .collect()
     }
 
+    /// Create a RocksDB checkpoint of the persisted state of every base table in `dir`, on the
+    /// workers running the base tables' domains. Returns the number of tables checkpointed.
+    pub(super) async fn checkpoint_base_tables(&self, dir: &Path) -> ReadySetResult<usize> {
+        let domains = self.domains_with_base_tables().await?;
+        self.query_domains::<_, usize>(domains.into_iter().map(|domain| {
+            (
+                domain,
+                DomainRequest::CheckpointBaseTables {
+                    dir: dir.to_owned(),
+                },
+            )
+        }))
+        .try_fold(0, |acc, (_, checkpointed)| async move {
+            Ok(acc + checkpointed.into_iter().flatten().sum::<usize>())
+        })
+        .await
+    }
+
+    /// Set (or, if `limit` is `None`, clear) the memory budget for each replica of each shard of
+    /// the given domain, on all healthy workers.
+    ///
+    /// Budgets are kept in memory on the workers, and aren't persisted, so they need to be set
+    /// again if workers restart.
+    pub(super) async fn set_domain_memory_limit(
+        &self,
+        domain: DomainIndex,
+        limit: Option<usize>,
+    ) -> ReadySetResult<()> {
+        if !self.domains.contains_key(&domain) {
+            return Err(ReadySetError::UnknownDomain {
+                domain_index: domain.index(),
+            });
+        }
+        for worker in self.workers.values().filter(|w| w.healthy) {
+            worker
+                .rpc::<()>(WorkerRequestKind::SetDomainMemoryLimit { domain, limit })
+                .await?;
+        }
+        Ok(())
+    }
+
     pub(super) async fn all_tables_compacted(&self) -> ReadySetResult<bool> {
         let domains = self.domains_with_base_tables().await?;
         let mut stream = self
@@ -952,6 +1184,47 @@ impl DfState {
         Ok(res)
     }
 
+    /// Return a report of the distribution of keys and reads across the shards of every sharded
+    /// reader, flagging hot shards
+    pub(super) async fn reader_skew_report(&self) -> ReadySetResult<ReaderSkewReport> {
+        let domains: Vec<DomainIndex> = self.domains.keys().copied().collect();
+        let stats_per_domain: Vec<(DomainIndex, Vec<Vec<Vec<(NodeIndex, ReaderShardStats)>>>)> =
+            self.query_domains::<_, Vec<(NodeIndex, ReaderShardStats)>>(
+                domains
+                    .into_iter()
+                    .map(|di| (di, DomainRequest::RequestReaderShardStats)),
+            )
+            .try_collect()
+            .await?;
+
+        let mut shards_per_reader: HashMap<NodeIndex, Vec<ReaderShardStats>> = HashMap::new();
+        for (_domain, per_shard_stats) in stats_per_domain {
+            let num_shards = per_shard_stats.len();
+            if num_shards < 2 {
+                continue;
+            }
+            for (shard, per_replica_stats) in per_shard_stats.into_iter().enumerate() {
+                for (node, stats) in per_replica_stats.into_iter().flatten() {
+                    let shards = shards_per_reader
+                        .entry(node)
+                        .or_insert_with(|| vec![Default::default(); num_shards]);
+                    // Every replica of a shard holds the same keys, but serves its own reads
+                    shards[shard].key_count = shards[shard].key_count.max(stats.key_count);
+                    shards[shard].reads += stats.reads;
+                }
+            }
+        }
+
+        Ok(ReaderSkewReport::new(
+            shards_per_reader
+                .into_iter()
+                .map(|(node, shards)| {
+                    ReaderSkew::new(node, self.ingredients[node].name().clone(), shards)
+                })
+                .collect(),
+        ))
+    }
+
     // ** Modify operations **
 
     /// Perform a new query schema migration.
@@ -1199,14 +1472,658 @@ impl DfState {
             .insert(NodeRestrictionKey { node_name, shard }, node_restriction);
     }
 
+    /// Pin the given replica of a domain shard to `worker`. If the replica is currently running on
+    /// a different worker, the domain is moved there by removing it from all the workers it's
+    /// running on and re-creating it, using the same process we use to recover domains from failed
+    /// workers. The domain's materialized state is then re-populated via replays from upstream.
+    ///
+    /// Domains containing base tables own persistent state that is local to the worker they're
+    /// running on, so they cannot be moved.
+    pub(super) async fn move_domain(
+        &mut self,
+        replica_address: ReplicaAddress,
+        worker: WorkerIdentifier,
+    ) -> ReadySetResult<()> {
+        let domain_index = replica_address.domain_index;
+        if !self.workers.get(&worker).iter().any(|w| w.healthy) {
+            return Err(bad_request_err(format!(
+                "Cannot move domain {replica_address} to unknown or unhealthy worker {worker}"
+            )));
+        }
+
+        let current_worker = self
+            .domains
+            .get(&domain_index)
+            .ok_or_else(|| ReadySetError::UnknownDomain {
+                domain_index: domain_index.index(),
+            })?
+            .assignment(replica_address.shard, replica_address.replica)?
+            .clone();
+
+        let nodes = self
+            .domain_nodes
+            .get(&domain_index)
+            .map(|nm| nm.values().copied().collect::<HashSet<_>>())
+            .unwrap_or_default();
+        #[allow(clippy::indexing_slicing)] // domain_nodes only contains valid indices
+        let has_base_table = nodes.iter().any(|ni| self.ingredients[*ni].is_base());
+        if has_base_table {
+            unsupported!("Cannot move domain {domain_index}, since it contains a base table");
+        }
+
+        self.domain_pins.insert(replica_address, worker.clone());
+        if current_worker == worker {
+            debug!(%replica_address, %worker, "Domain already running on worker; not moving");
+            return Ok(());
+        }
+
+        warn!(%replica_address, from = %current_worker, to = %worker, "Moving domain");
+        self.relocate_domain(domain_index).await
+    }
+
+    /// Remove all replicas of all shards of the given domain from the workers they're running on,
+    /// and re-create them on the workers chosen by the [`Scheduler`], re-populating their state via
+    /// replays from upstream.
+    async fn relocate_domain(&mut self, domain_index: DomainIndex) -> ReadySetResult<()> {
+        let nodes = self
+            .domain_nodes
+            .get(&domain_index)
+            .map(|nm| nm.values().copied().collect::<HashSet<_>>())
+            .unwrap_or_default();
+        let replicas = self
+            .domains
+            .get(&domain_index)
+            .ok_or_else(|| ReadySetError::UnknownDomain {
+                domain_index: domain_index.index(),
+            })?
+            .shards()
+            .enumerate()
+            .flat_map(|(shard, replicas)| {
+                replicas.iter().enumerate().map(move |(replica, wi)| {
+                    let replica_address = ReplicaAddress {
+                        domain_index,
+                        shard,
+                        replica,
+                    };
+                    (replica_address, wi.clone())
+                })
+            })
+            .collect::<Vec<_>>();
+        for (replica_address, wi) in replicas {
+            if let Some(w) = self.workers.get(&wi) {
+                w.rpc::<()>(WorkerRequestKind::RemoveDomain(replica_address))
+                    .await?;
+            }
+            self.channel_coordinator.remove(&replica_address);
+        }
+        // Only forget the domain once every replica has been removed, so that if any of the RPCs
+        // above fail we still know where the domain is running and the move can be retried
+        self.domains.remove(&domain_index);
+        self.materializations.remove_nodes(&nodes);
+
+        let affected_nodes = HashMap::from([(domain_index, nodes)]);
+        self.plan_recovery(&affected_nodes).await?.apply(self).await
+    }
+
+    /// Move domains from the healthy workers running the most domain shard replicas onto the
+    /// healthy workers running the fewest, until the number of domain shard replicas running on
+    /// each worker differs by at most one, or there are no more domains that can be moved.
+    ///
+    /// Domains containing base tables can't be moved (see [`Self::move_domain`]), and domains with
+    /// a replica that has been pinned to a worker are left where they are. Each domain is moved at
+    /// most once.
+    pub(super) async fn rebalance_domains(&mut self) -> ReadySetResult<()> {
+        let mut moved = HashSet::new();
+        loop {
+            let mut load = self
+                .workers
+                .iter()
+                .filter(|(_, w)| w.healthy)
+                .map(|(wi, _)| (wi, 0usize))
+                .collect::<HashMap<_, _>>();
+            for wi in self.domains.values().flat_map(|dh| dh.shards().flatten()) {
+                if let Some(n) = load.get_mut(wi) {
+                    *n += 1;
+                }
+            }
+
+            let (Some((most_loaded, max)), Some((_, min))) = (
+                load.iter().max_by_key(|(_, n)| **n),
+                load.iter().min_by_key(|(_, n)| **n),
+            ) else {
+                return Ok(());
+            };
+            if max - min <= 1 {
+                return Ok(());
+            }
+
+            #[allow(clippy::indexing_slicing)] // domain_nodes only contains valid indices
+            let candidate = self
+                .domains
+                .iter()
+                .filter(|(di, dh)| {
+                    !moved.contains(*di)
+                        && dh.shards().flatten().any(|wi| wi == *most_loaded)
+                        && !self.domain_pins.keys().any(|ra| ra.domain_index == **di)
+                        && !self
+                            .domain_nodes
+                            .get(*di)
+                            .iter()
+                            .flat_map(|nm| nm.values())
+                            .any(|ni| self.ingredients[*ni].is_base())
+                })
+                .map(|(di, _)| *di)
+                .min();
+            let Some(domain_index) = candidate else {
+                return Ok(());
+            };
+
+            info!(
+                domain_index = %domain_index.index(),
+                from = %most_loaded,
+                "Rebalancing domain"
+            );
+            moved.insert(domain_index);
+            self.relocate_domain(domain_index).await?;
+        }
+    }
+
+    /// Remove any pin placed on the given replica of a domain shard by [`Self::move_domain`].
+    ///
+    /// This doesn't move the domain - it'll be placed by the scheduler the next time it's created.
+    pub(super) fn unpin_domain(&mut self, replica_address: &ReplicaAddress) {
+        self.domain_pins.remove(replica_address);
+    }
+
+    /// Start a canary of the version of ReadySet with the given `version` label on the given
+    /// `domains`, moving one replica of each of their shards onto workers with that version label,
+    /// and the replicas of all other domains off of those workers. If a canary of the same version
+    /// is already in progress, the domains are added to it.
+    ///
+    /// The last replica of each domain shard is canaried, so that domains with more than one
+    /// replica keep running on the stable version, which the output and latency of the canary can
+    /// then be compared with (see [`Self::canary_report`]). Domains containing base tables can't
+    /// be moved (see [`Self::move_domain`]), so they can't be canaried.
+    pub(super) async fn start_canary(
+        &mut self,
+        version: String,
+        domains: Vec<DomainIndex>,
+    ) -> ReadySetResult<()> {
+        if let Some(canary) = &self.canary {
+            if canary.version != version {
+                return Err(bad_request_err(format!(
+                    "A canary of version {} is already in progress",
+                    canary.version
+                )));
+            }
+        }
+        if !self
+            .workers
+            .values()
+            .any(|w| w.healthy && w.domain_scheduling_config.version.as_ref() == Some(&version))
+        {
+            return Err(bad_request_err(format!(
+                "Cannot start canary of version {version} with no healthy workers running it"
+            )));
+        }
+
+        let mut replicas = self
+            .canary
+            .as_ref()
+            .map(|canary| canary.replicas.clone())
+            .unwrap_or_default();
+        for domain_index in domains {
+            let dh =
+                self.domains
+                    .get(&domain_index)
+                    .ok_or_else(|| ReadySetError::UnknownDomain {
+                        domain_index: domain_index.index(),
+                    })?;
+            #[allow(clippy::indexing_slicing)] // domain_nodes only contains valid indices
+            let has_base_table = self
+                .domain_nodes
+                .get(&domain_index)
+                .iter()
+                .flat_map(|nm| nm.values())
+                .any(|ni| self.ingredients[*ni].is_base());
+            if has_base_table {
+                unsupported!("Cannot canary domain {domain_index}, since it contains a base table");
+            }
+
+            let replica = dh.num_replicas() - 1;
+            replicas.extend((0..dh.num_shards()).map(|shard| ReplicaAddress {
+                domain_index,
+                shard,
+                replica,
+            }));
+        }
+
+        info!(%version, num_replicas = replicas.len(), "Starting canary");
+        self.canary = Some(DomainCanary { version, replicas });
+        self.enforce_canary().await
+    }
+
+    /// Relocate every domain with a replica running on a worker it isn't allowed to run on given
+    /// the canary in progress, if any. Domains containing base tables can't be moved, and are left
+    /// where they are.
+    async fn enforce_canary(&mut self) -> ReadySetResult<()> {
+        let Some(canary) = &self.canary else {
+            return Ok(());
+        };
+
+        let misplaced = self
+            .domains
+            .iter()
+            .filter(|(di, dh)| {
+                dh.shards().enumerate().any(|(shard, replicas)| {
+                    replicas.iter().enumerate().any(|(replica, wi)| {
+                        let canaried = canary.replicas.contains(&ReplicaAddress {
+                            domain_index: **di,
+                            shard,
+                            replica,
+                        });
+                        let on_canary_worker = self.workers.get(wi).map_or(false, |w| {
+                            w.domain_scheduling_config.version.as_ref() == Some(&canary.version)
+                        });
+                        canaried != on_canary_worker
+                    })
+                })
+            })
+            .map(|(di, _)| *di)
+            .collect::<Vec<_>>();
+
+        for domain_index in misplaced {
+            #[allow(clippy::indexing_slicing)] // domain_nodes only contains valid indices
+            let has_base_table = self
+                .domain_nodes
+                .get(&domain_index)
+                .iter()
+                .flat_map(|nm| nm.values())
+                .any(|ni| self.ingredients[*ni].is_base());
+            if has_base_table {
+                warn!(
+                    domain_index = %domain_index.index(),
+                    "Domain containing a base table is running on a worker with the canary \
+                     version, and cannot be moved"
+                );
+                continue;
+            }
+
+            info!(domain_index = %domain_index.index(), "Moving domain for canary");
+            self.relocate_domain(domain_index).await?;
+        }
+
+        Ok(())
+    }
+
+    /// End the canary in progress, if any. Domains aren't moved: to complete the canary, upgrade
+    /// the remaining workers to the canary version, and to roll it back, stop the workers running
+    /// the canary version so that their domains are recovered onto the remaining workers.
+    pub(super) fn end_canary(&mut self) {
+        if let Some(canary) = self.canary.take() {
+            info!(version = %canary.version, "Ending canary");
+        }
+    }
+
+    /// Return a report comparing the canary replica of every domain shard in the canary in
+    /// progress, if any, with the stable replicas of the same shard, recording the comparisons in
+    /// metrics.
+    pub(super) async fn canary_report(&self) -> ReadySetResult<CanaryReport> {
+        let Some(canary) = &self.canary else {
+            return Ok(CanaryReport::default());
+        };
+
+        let mut replicas = canary
+            .replicas
+            .iter()
+            .filter(|ra| self.domains.contains_key(&ra.domain_index))
+            .collect::<Vec<_>>();
+        replicas.sort_by_key(|ra| (ra.domain_index, ra.shard));
+        let mut domains = replicas
+            .iter()
+            .map(|ra| ra.domain_index)
+            .collect::<Vec<_>>();
+        domains.dedup();
+
+        let domain_stats: HashMap<
+            DomainIndex,
+            Vec<Vec<(DomainStats, HashMap<NodeIndex, NodeStats>)>>,
+        > = self
+            .query_domains(
+                domains
+                    .clone()
+                    .into_iter()
+                    .map(|di| (di, DomainRequest::GetStatistics)),
+            )
+            .try_collect()
+            .await?;
+        let reader_stats: HashMap<DomainIndex, Vec<Vec<Vec<(NodeIndex, ReaderShardStats)>>>> = self
+            .query_domains(
+                domains
+                    .into_iter()
+                    .map(|di| (di, DomainRequest::RequestReaderShardStats)),
+            )
+            .try_collect()
+            .await?;
+
+        let mut shards = Vec::with_capacity(replicas.len());
+        for ra in replicas {
+            let stats = |replica: usize| {
+                let (totals, _) = domain_stats
+                    .get(&ra.domain_index)?
+                    .get(ra.shard)?
+                    .get(replica)?;
+                let readers = reader_stats
+                    .get(&ra.domain_index)?
+                    .get(ra.shard)?
+                    .get(replica)?;
+                Some(CanaryReplicaStats {
+                    process_time: totals.total_ptime,
+                    reader_keys: readers.iter().map(|(_, s)| s.key_count as u64).sum(),
+                    reads: readers.iter().map(|(_, s)| s.reads).sum(),
+                })
+            };
+            // The number of replicas may have changed since the canary was started
+            let Some(canary_stats) = stats(ra.replica) else {
+                continue;
+            };
+            #[allow(clippy::indexing_slicing)] // filtered to domains in self.domains above
+            let dh = &self.domains[&ra.domain_index];
+            let shard = CanaryShard {
+                replica_address: *ra,
+                worker: dh.assignment(ra.shard, ra.replica)?.clone(),
+                canary: canary_stats,
+                stable: (0..dh.num_replicas())
+                    .filter(|replica| *replica != ra.replica)
+                    .filter_map(stats)
+                    .collect(),
+            };
+
+            let labels = [
+                ("domain", ra.domain_index.index().to_string()),
+                ("shard", ra.shard.to_string()),
+            ];
+            if let Some(ratio) = shard.latency_ratio() {
+                gauge!(recorded::CANARY_LATENCY_RATIO, ratio, &labels);
+            }
+            if let Some(divergence) = shard.key_divergence() {
+                gauge!(recorded::CANARY_KEY_DIVERGENCE, divergence, &labels);
+            }
+            shards.push(shard);
+        }
+
+        Ok(CanaryReport {
+            version: Some(canary.version.clone()),
+            shards,
+        })
+    }
+
+    /// Returns the global index, domain, and local index of the base table node with the given
+    /// name
+    fn base_table_node(
+        &self,
+        table: &Relation,
+    ) -> ReadySetResult<(NodeIndex, DomainIndex, LocalNodeIndex)> {
+        let ni = self
+            .recipe
+            .node_addr_for(table)
+            .map_err(|_| ReadySetError::TableNotFound {
+                name: table.name.clone().into(),
+                schema: table.schema.clone().map(Into::into),
+            })?;
+        let node = self
+            .ingredients
+            .node_weight(ni)
+            .ok_or_else(|| ReadySetError::NodeNotFound { index: ni.index() })?;
+        if !node.is_base() {
+            return Err(ReadySetError::InvalidNodeType {
+                node_index: node.local_addr().id(),
+                expected_type: NodeType::Base,
+            });
+        }
+        if !self.domains.contains_key(&node.domain()) {
+            return Err(ReadySetError::UnknownDomain {
+                domain_index: node.domain().index(),
+            });
+        }
+        Ok((ni, node.domain(), node.local_addr()))
+    }
+
+    /// Trigger a manual compaction of the persisted state of all shards of the given base table,
+    /// to drop any deletion tombstones that have accumulated in it. The compaction runs in the
+    /// background; this returns as soon as it has been started.
+    pub(super) async fn compact_table(&self, table: &Relation) -> ReadySetResult<()> {
+        let (_, domain, node) = self.base_table_node(table)?;
+        self.query_domains::<_, ()>(iter::once((
+            domain,
+            DomainRequest::CompactBaseTable { node },
+        )))
+        .try_collect::<Vec<_>>()
+        .await?;
+        Ok(())
+    }
+
+    /// Evict all partially materialized state derived from the rows of the given base table with
+    /// the given primary keys, so that subsequent reads of that state are replayed. This is used to
+    /// invalidate the caches which depend on rows that were written to out-of-band.
+    pub(super) async fn invalidate_table_keys(
+        &self,
+        table: &Relation,
+        keys: Vec<Vec<DfValue>>,
+    ) -> ReadySetResult<()> {
+        let (ni, domain, node) = self.base_table_node(table)?;
+        #[allow(clippy::indexing_slicing)] // checked by base_table_node
+        let base_node = &self.ingredients[ni];
+        let primary_key = match base_node.get_base().and_then(|b| b.primary_key()) {
+            Some(pk) => pk,
+            None => unsupported!(
+                "Cannot invalidate keys of table {} without a primary key",
+                table.display_unquoted()
+            ),
+        };
+        let columns = base_node.columns();
+        let keys = keys
+            .into_iter()
+            .map(|key| {
+                if key.len() != primary_key.len() {
+                    return Err(ReadySetError::WrongKeyColumnCount(
+                        primary_key.len(),
+                        key.len(),
+                    ));
+                }
+                key.into_iter()
+                    .zip(primary_key)
+                    .map(|(value, col)| {
+                        let ty = columns
+                            .get(*col)
+                            .ok_or_else(|| internal_err!("primary key column out of bounds"))?
+                            .ty();
+                        value.coerce_to(ty, &DfType::Unknown)
+                    })
+                    .collect::<ReadySetResult<Vec<_>>>()
+            })
+            .collect::<ReadySetResult<Vec<_>>>()?;
+
+        // The rows may live in any shard of the table, so send the keys to all of them
+        self.query_domains::<_, ()>(iter::once((
+            domain,
+            DomainRequest::Packet(Packet::Evict(EvictRequest::BaseTableKeys { node, keys })),
+        )))
+        .try_collect::<Vec<_>>()
+        .await?;
+        Ok(())
+    }
+
+    /// Override the durability mode of the base table with the given name, which must not yet
+    /// exist. The override takes effect when the table is created.
+    pub(super) fn set_table_durability(
+        &mut self,
+        table: Relation,
+        mode: DurabilityMode,
+    ) -> ReadySetResult<()> {
+        if self.recipe.node_addr_for(&table).is_ok() {
+            unsupported!(
+                "Cannot change the durability mode of existing table {}",
+                table.display_unquoted()
+            );
+        }
+        self.persistence.set_table_mode(table, mode);
+        Ok(())
+    }
+
+    /// Override the durability mode of each table created in `changelist` with a
+    /// `DURABILITY = '<durability>'` option
+    fn set_recipe_table_durability(&mut self, changelist: &ChangeList) -> ReadySetResult<()> {
+        for change in changelist.changes() {
+            let Change::CreateTable(cts) = change else {
+                continue;
+            };
+            let Some(mode) = DurabilityMode::from_create_table(cts)? else {
+                continue;
+            };
+            // Resolve the table's schema the same way the recipe does when creating it
+            let mut table = cts.table.clone();
+            if table.schema.is_none() {
+                table.schema = changelist.schema_search_path().first().cloned();
+            }
+            // Re-issuing the statement for an existing table is fine, as long as it doesn't change
+            // the table's durability mode
+            if *self.persistence.mode_for_table(&table) == mode {
+                continue;
+            }
+            self.set_table_durability(table, mode)?;
+        }
+        Ok(())
+    }
+
+    /// Set (or clear, if `None`) the interval at which the persisted state of the given base table
+    /// is compacted.
+    pub(super) async fn set_table_compaction_interval(
+        &mut self,
+        table: &Relation,
+        interval: Option<Duration>,
+    ) -> ReadySetResult<()> {
+        let (ni, domain, node) = self.base_table_node(table)?;
+        self.query_domains::<_, ()>(iter::once((
+            domain,
+            DomainRequest::SetBaseTableCompactionInterval { node, interval },
+        )))
+        .try_collect::<Vec<_>>()
+        .await?;
+
+        // Also update our copy of the node, so the interval is retained if the domain is ever
+        // re-created
+        #[allow(clippy::indexing_slicing)] // checked by base_table_node
+        self.ingredients[ni]
+            .get_base_mut()
+            .ok_or_else(|| internal_err!("base_table_node returned non-base node"))?
+            .set_compaction_interval(interval);
+        Ok(())
+    }
+
+    /// Apply RocksDB tuning parameters to the persisted state of all shards of the given base
+    /// table. Parameters left unset in `tuning` retain their previous values.
+    pub(super) async fn set_table_tuning(
+        &mut self,
+        table: &Relation,
+        tuning: RocksDbTuning,
+    ) -> ReadySetResult<()> {
+        let (ni, domain, node) = self.base_table_node(table)?;
+        self.query_domains::<_, ()>(iter::once((
+            domain,
+            DomainRequest::SetBaseTableTuning { node, tuning },
+        )))
+        .try_collect::<Vec<_>>()
+        .await?;
+
+        // Also update our copy of the node, so the tuning is retained if the domain is ever
+        // re-created
+        #[allow(clippy::indexing_slicing)] // checked by base_table_node
+        self.ingredients[ni]
+            .get_base_mut()
+            .ok_or_else(|| internal_err!("base_table_node returned non-base node"))?
+            .set_rocksdb_tuning(tuning);
+        Ok(())
+    }
+
     pub(super) fn set_schema_replication_offset(&mut self, offset: Option<ReplicationOffset>) {
         self.schema_replication_offset = offset;
     }
 
-    pub(super) async fn flush_partial(&mut self) -> ReadySetResult<u64> {
+    /// Returns the index of the reader node for the view with the given name
+    fn reader_node(&self, view: &Relation) -> ReadySetResult<NodeIndex> {
+        self.ingredients
+            .externals(petgraph::EdgeDirection::Outgoing)
+            .find(|n| {
+                #[allow(clippy::indexing_slicing)] // just came from self.ingredients
+                let node = &self.ingredients[*n];
+                node.is_reader() && node.name() == view
+            })
+            .ok_or_else(|| ReadySetError::ViewNotFound(view.display_unquoted().to_string()))
+    }
+
+    /// Evict the given keys from the reader for the view with the given name, so that subsequent
+    /// reads of those keys are replayed from the nodes above the reader. Keys which are not
+    /// present in the reader are ignored.
+    pub(super) async fn invalidate_keys(
+        &self,
+        view: &Relation,
+        keys: Vec<KeyComparison>,
+    ) -> ReadySetResult<()> {
+        let ni = self.reader_node(view)?;
+        #[allow(clippy::indexing_slicing)] // came from reader_node
+        let node = &self.ingredients[ni];
+        let key_len = match node.as_reader().and_then(|r| r.key()) {
+            Some(key) => key.len(),
+            None => unsupported!("View {} has no key to invalidate", view.display_unquoted()),
+        };
+        for key in &keys {
+            if let Some(len) = key.len() {
+                if len != key_len {
+                    return Err(ReadySetError::WrongKeyColumnCount(key_len, len));
+                }
+            }
+        }
+
+        // Readers may be sharded, but keys which aren't present in a shard are skipped, so we can
+        // just send the full set of keys to all shards
+        self.query_domains::<_, ()>(iter::once((
+            node.domain(),
+            DomainRequest::EvictReaderKeys {
+                node: node.local_addr(),
+                keys,
+            },
+        )))
+        .try_collect::<Vec<_>>()
+        .await?;
+
+        Ok(())
+    }
+
+    /// Evict all state from partially materialized nodes, returning the number of bytes evicted.
+    ///
+    /// If `view` is given, only the state of the nodes the reader for that view reads from is
+    /// evicted. Note that those nodes may be shared with other views, whose state is then also
+    /// (partially) evicted.
+    pub(super) async fn flush_partial(&mut self, view: Option<Relation>) -> ReadySetResult<u64> {
+        let only_nodes = match view {
+            Some(view) => {
+                let reader = self.reader_node(&view)?;
+                let graph = petgraph::visit::Reversed(&self.ingredients);
+                let mut bfs = Bfs::new(graph, reader);
+                let mut ancestors = HashSet::new();
+                while let Some(ni) = bfs.next(graph) {
+                    ancestors.insert(ni);
+                }
+                Some(ancestors)
+            }
+            None => None,
+        };
+
         // get statistics for current domain sizes
         // and evict all state from partial nodes
         let workers = &self.workers;
+        let only_nodes = &only_nodes;
         let mut to_evict = Vec::new();
         for (di, s) in self.domains.iter_mut() {
             let domain_to_evict: Vec<(NodeIndex, u64)> = s
@@ -1220,6 +2137,9 @@ impl DfState {
                 .flat_map(move |(_, node_stats)| {
                     node_stats
                         .into_iter()
+                        .filter(move |(ni, _)| {
+                            only_nodes.as_ref().map_or(true, |nodes| nodes.contains(ni))
+                        })
                         .filter_map(|(ni, ns)| match ns.materialized {
                             MaterializationStatus::Partial { .. } => Some((ni, ns.mem_size)),
                             _ => None,
@@ -1263,6 +2183,15 @@ impl DfState {
         changelist: ChangeList,
         dry_run: bool,
     ) -> Result<(), ReadySetError> {
+        // Durability overrides given in the recipe have to be in place before the migration
+        // creates the domains for the tables they apply to. They're only kept if the migration is
+        // actually committed.
+        let table_modes = self.persistence.table_modes.clone();
+        if let Err(error) = self.set_recipe_table_durability(&changelist) {
+            self.persistence.table_modes = table_modes;
+            return Err(error);
+        }
+
         // I hate this, but there's no way around for now, as migrations
         // are super entangled with the recipe and the graph.
         let mut new = self.recipe.clone();
@@ -1273,6 +2202,10 @@ impl DfState {
             })
             .await;
 
+        if dry_run || r.is_err() {
+            self.persistence.table_modes = table_modes;
+        }
+
         match &r {
             Ok(_) => self.recipe = new,
             Err(e) => {
@@ -1315,9 +2248,15 @@ impl DfState {
         }
     }
 
+    /// Remove the query with the given name (or alias), returning [`ReadySetError::ViewNotFound`]
+    /// if no such query exists
     pub(super) async fn remove_query(&mut self, query_name: &Relation) -> ReadySetResult<()> {
         let name = match self.recipe.resolve_alias(query_name) {
-            None => return Ok(()),
+            None => {
+                return Err(ReadySetError::ViewNotFound(
+                    query_name.display_unquoted().to_string(),
+                ))
+            }
             Some(name) => name,
         };
 
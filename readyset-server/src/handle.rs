@@ -5,6 +5,7 @@ use dataflow::prelude::*;
 use readyset_client::consensus::Authority;
 use readyset_client::prelude::*;
 use readyset_data::Dialect;
+use readyset_util::redacted::RedactedString;
 use reqwest::Url;
 use tokio::sync::mpsc::Sender;
 
@@ -40,8 +41,9 @@ impl Handle {
         authority: Arc<Authority>,
         event_tx: Sender<HandleRequest>,
         descriptor: ControllerDescriptor,
+        http_auth_token: Option<RedactedString>,
     ) -> Self {
-        let c = ReadySetHandle::make(authority, None, None);
+        let c = ReadySetHandle::make(authority, None, None, http_auth_token);
         Handle {
             c: Some(c),
             event_tx: Some(event_tx),
@@ -6,15 +6,18 @@ use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use anyhow::anyhow;
-use futures::TryFutureExt;
+use futures::{future, stream, Stream, StreamExt, TryFutureExt};
 use health_reporter::{HealthReporter, State};
-use hyper::header::CONTENT_TYPE;
+use hyper::header::{AUTHORIZATION, CACHE_CONTROL, CONTENT_TYPE, WWW_AUTHENTICATE};
 use hyper::service::make_service_fn;
 use hyper::{self, Body, Method, Request, Response, StatusCode};
 use readyset_client::consensus::Authority;
-use readyset_client::metrics::recorded;
-use readyset_errors::ReadySetError;
+use readyset_client::metrics::{recorded, MetricsDump, MetricsStreamEvent, MetricsStreamOptions};
+use readyset_client::tls::MaybeTlsStream;
+use readyset_errors::{ReadySetError, ReadySetResult};
+use readyset_util::redacted::RedactedString;
 use readyset_util::shutdown::ShutdownReceiver;
+use serde::{Deserialize, Serialize};
 use tokio::net::TcpListener;
 use tokio::sync::mpsc::Sender;
 use tokio_stream::wrappers::TcpListenerStream;
@@ -22,9 +25,13 @@ use tower::Service;
 use tracing::warn;
 
 use crate::controller::ControllerRequest;
-use crate::metrics::{get_global_recorder, Clear, RecorderType};
+use crate::metrics::{get_global_recorder, Clear, CompositeMetricsRecorder, RecorderType};
+use crate::profiling::{self, PPROF_PATH_PREFIX};
 use crate::worker::WorkerRequest;
 
+/// Maximum number of TLS handshakes for incoming HTTP connections to perform concurrently
+const MAX_CONCURRENT_TLS_HANDSHAKES: usize = 64;
+
 /// Routes requests from an HTTP server to noria server workers and controllers.
 /// The NoriaServerHttpRouter takes several channels (`worker_tx`, `controller_tx`)
 /// used to pass messages from this context to the worker and controller threads.
@@ -48,6 +55,122 @@ pub struct NoriaServerHttpRouter {
     /// handled.
     /// Most commonly used to block on further startup action if --wait-for-failpoint is supplied.
     pub failpoint_channel: Option<Arc<Sender<()>>>,
+    /// Whether to serve the profiling endpoints in [`crate::profiling`]
+    pub enable_pprof: bool,
+    /// Tokens required to access the HTTP endpoints
+    pub http_auth: HttpAuthConfig,
+}
+
+/// Configuration for shared-token authentication of requests to the HTTP endpoints of the server.
+///
+/// Requests authenticate by passing one of the tokens in an `Authorization: Bearer <token>` header.
+/// If no admin token is configured, all requests are allowed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpAuthConfig {
+    /// Token granting access to all endpoints, including ones that modify the deployment such as
+    /// `/extend_recipe`. Also used by the server itself for requests between the controller and
+    /// workers.
+    pub admin_token: Option<RedactedString>,
+    /// Token granting access only to read-only introspection endpoints
+    pub read_only_token: Option<RedactedString>,
+}
+
+/// Level of access to the HTTP endpoints, as granted by a token or required by an endpoint
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum HttpAccess {
+    ReadOnly,
+    Admin,
+}
+
+/// Controller endpoints requested via POST which only read the state of the deployment, and can
+/// therefore be accessed with the read-only token. All GET endpoints other than `/failpoint` are
+/// also read-only.
+const READ_ONLY_POST_ENDPOINTS: &[&str] = &[
+    "/all_tables_compacted",
+    "/canary_report",
+    "/controller_uri",
+    "/domain_placement",
+    "/dry_run",
+    "/get_info",
+    "/get_statistics",
+    "/graphviz",
+    "/healthy_workers",
+    "/instances",
+    "/leader_ready",
+    "/metrics_dump",
+    "/migration_status",
+    "/node_sizes",
+    "/non_replicated_relations",
+    "/reader_skew",
+    "/replication_offsets",
+    "/simple_graphviz",
+    "/snapshotting_tables",
+    "/standby_state",
+    "/status",
+    "/supports_pagination",
+    "/table_builder",
+    "/table_builder_by_index",
+    "/table_statuses",
+    "/tables",
+    "/verbose_views",
+    "/version",
+    "/view_builder",
+    "/view_plan",
+    "/view_statuses",
+    "/views",
+    "/workers",
+];
+
+/// Returns the level of access required to make a request to the given endpoint, or `None` if the
+/// endpoint doesn't require authentication
+fn required_access(method: &Method, path: &str) -> Option<HttpAccess> {
+    match (method, path) {
+        // Health checks are made by orchestrators and load balancers, which can't be expected to
+        // have a token
+        (&Method::GET, "/health") => None,
+        (&Method::GET, "/failpoint") => Some(HttpAccess::Admin),
+        (&Method::GET, _) => Some(HttpAccess::ReadOnly),
+        (&Method::POST, path) if READ_ONLY_POST_ENDPOINTS.contains(&path) => {
+            Some(HttpAccess::ReadOnly)
+        }
+        _ => Some(HttpAccess::Admin),
+    }
+}
+
+/// Compare two tokens in time independent of the position of the first mismatching byte
+fn tokens_match(expected: &str, actual: &str) -> bool {
+    expected.len() == actual.len()
+        && expected
+            .bytes()
+            .zip(actual.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+impl HttpAuthConfig {
+    /// Returns the level of access granted to a request carrying the given bearer `token`, or
+    /// `None` if the request should be rejected
+    fn access(&self, token: Option<&str>) -> Option<HttpAccess> {
+        let admin_token = match &self.admin_token {
+            Some(admin_token) => admin_token,
+            None => return Some(HttpAccess::Admin),
+        };
+        let token = token?;
+
+        if tokens_match(admin_token, token) {
+            Some(HttpAccess::Admin)
+        } else if self
+            .read_only_token
+            .as_ref()
+            .map_or(false, |read_only_token| {
+                tokens_match(read_only_token, token)
+            })
+        {
+            Some(HttpAccess::ReadOnly)
+        } else {
+            None
+        }
+    }
 }
 
 impl NoriaServerHttpRouter {
@@ -71,18 +194,61 @@ impl NoriaServerHttpRouter {
         http_listener: TcpListener,
         shutdown_rx: ShutdownReceiver,
     ) -> anyhow::Result<()> {
-        hyper::server::Server::builder(hyper::server::accept::from_stream(
-            shutdown_rx.wrap_stream(TcpListenerStream::new(http_listener)),
-        ))
-        .serve(make_service_fn(move |_| {
-            let s = router.clone();
-            async move { io::Result::Ok(s) }
-        }))
-        .map_err(move |e| anyhow!("HTTP server failed, {}", e))
-        .await
+        // If internal TLS is enabled, perform the handshake for each connection before handing it
+        // off to hyper, dropping (rather than failing the whole server on) connections whose
+        // handshake fails
+        let connections = shutdown_rx
+            .wrap_stream(TcpListenerStream::new(http_listener))
+            .map(|conn| async move {
+                match conn {
+                    Ok(conn) => match MaybeTlsStream::accept(conn).await {
+                        Ok(conn) => Some(Ok(conn)),
+                        Err(error) => {
+                            warn!(%error, "TLS handshake for HTTP connection failed");
+                            None
+                        }
+                    },
+                    Err(e) => Some(Err(e)),
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_TLS_HANDSHAKES)
+            .filter_map(future::ready);
+
+        hyper::server::Server::builder(hyper::server::accept::from_stream(connections))
+            .serve(make_service_fn(move |_| {
+                let s = router.clone();
+                async move { io::Result::Ok(s) }
+            }))
+            .map_err(move |e| anyhow!("HTTP server failed, {}", e))
+            .await
     }
 }
 
+/// Returns a stream of server-sent events containing a snapshot of all metrics in `recorder`
+/// matching the filter in `options`, followed by deltas of the metrics that have changed every
+/// [`interval`](MetricsStreamOptions::interval). See [`readyset_client::metrics::stream`].
+fn metrics_stream(
+    recorder: &'static CompositeMetricsRecorder,
+    options: MetricsStreamOptions,
+) -> impl Stream<Item = ReadySetResult<String>> {
+    let interval = tokio::time::interval(options.interval);
+    stream::unfold(
+        (interval, None::<MetricsDump>),
+        move |(mut interval, prev)| {
+            let filter = options.filter.clone();
+            async move {
+                interval.tick().await;
+                let dump = recorder.dump()?.filtered(&filter);
+                let event = match &prev {
+                    None => MetricsStreamEvent::Snapshot(dump.clone()),
+                    Some(prev) => MetricsStreamEvent::Delta(dump.delta(prev)),
+                };
+                Some((event.to_sse(), (interval, Some(dump))))
+            }
+        },
+    )
+}
+
 /// Tower service definition to route http requests `Request<Body>` to their
 /// responses. Requests on the endpoint `/worker_request` are routed to the
 /// worker along the `worker_tx` channel, while any request that is not specifically
@@ -104,6 +270,35 @@ impl Service<Request<Body>> for NoriaServerHttpRouter {
 
         metrics::increment_counter!(recorded::SERVER_EXTERNAL_REQUESTS);
 
+        if let Some(required) = required_access(req.method(), req.uri().path()) {
+            let token = req
+                .headers()
+                .get(AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "));
+            match self.http_auth.access(token) {
+                Some(access) if access >= required => {}
+                access => {
+                    let res = match access {
+                        // The token is valid, but doesn't grant access to this endpoint
+                        Some(_) => res.status(StatusCode::FORBIDDEN),
+                        None => res
+                            .status(StatusCode::UNAUTHORIZED)
+                            .header(WWW_AUTHENTICATE, "Bearer"),
+                    };
+                    let res = res
+                        .header(CONTENT_TYPE, "text/plain")
+                        .body(hyper::Body::from(
+                            ReadySetError::Unauthorized {
+                                path: req.uri().path().to_owned(),
+                            }
+                            .to_string(),
+                        ));
+                    return Box::pin(async move { Ok(res.unwrap()) });
+                }
+            }
+        }
+
         match (req.method(), req.uri().path()) {
             #[cfg(feature = "failure_injection")]
             (&Method::GET, "/failpoint") => {
@@ -153,6 +348,11 @@ impl Service<Request<Body>> for NoriaServerHttpRouter {
                 };
                 Box::pin(async move { Ok(res.unwrap()) })
             }
+            (&Method::GET, path) if self.enable_pprof && path.starts_with(PPROF_PATH_PREFIX) => {
+                let path = path.to_owned();
+                let query = req.uri().query().map(ToOwned::to_owned);
+                Box::pin(async move { Ok(profiling::serve(&path, query.as_deref()).await) })
+            }
             (&Method::GET, "/health") => {
                 let state = self.health_reporter.health().state;
                 Box::pin(async move {
@@ -184,6 +384,25 @@ impl Service<Request<Body>> for NoriaServerHttpRouter {
                 };
                 Box::pin(async move { Ok(res.unwrap()) })
             }
+            (&Method::GET, "/metrics_stream") => {
+                let options =
+                    MetricsStreamOptions::from_query(req.uri().query().unwrap_or_default());
+                let res = match (options, get_global_recorder()) {
+                    (Ok(options), Some(recorder)) if recorder.dump().is_some() => res
+                        .header(CONTENT_TYPE, "text/event-stream")
+                        .header(CACHE_CONTROL, "no-cache")
+                        .body(hyper::Body::wrap_stream(metrics_stream(recorder, options))),
+                    (Err(e), _) => res
+                        .status(400)
+                        .header(CONTENT_TYPE, "text/plain")
+                        .body(hyper::Body::from(e.to_string())),
+                    _ => res
+                        .status(404)
+                        .header(CONTENT_TYPE, "text/plain")
+                        .body(hyper::Body::from("Noria metrics were not enabled. To fix this, run Noria with --noria-metrics".to_string())),
+                };
+                Box::pin(async move { Ok(res.unwrap()) })
+            }
             (&Method::POST, "/reset_metrics") => {
                 if let Some(r) = get_global_recorder() {
                     r.clear();
@@ -33,9 +33,12 @@ use nom_sql::{
 };
 use readyset_client::consensus::{Authority, LocalAuthority, LocalAuthorityStore};
 use readyset_client::consistency::Timestamp;
-use readyset_client::internal::LocalNodeIndex;
+use readyset_client::internal::{LocalNodeIndex, ReplicaAddress};
 use readyset_client::recipe::changelist::{Change, ChangeList};
-use readyset_client::{KeyComparison, Modification, SchemaType, ViewPlaceholder, ViewQuery};
+use readyset_client::{
+    BaseTableCompression, KeyComparison, Modification, RocksDbTuning, SchemaType, ViewPlaceholder,
+    ViewQuery,
+};
 use readyset_data::{DfType, DfValue, Dialect};
 use readyset_errors::ReadySetError::{
     self, MigrationPlanFailed, RpcFailed, SelectQueryCreationFailed,
@@ -551,6 +554,71 @@ async fn base_mutation() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn base_upsert() {
+    use readyset_client::{Modification, Operation};
+
+    let (mut g, shutdown_tx) = start_simple_unsharded("base_upsert").await;
+    let a = g
+        .migrate(|mig| {
+            let a = mig.add_base(
+                "a",
+                make_columns(&["a", "b"]),
+                Base::new().with_primary_key([0]),
+            );
+            mig.maintain_anonymous(a, &Index::hash_map(vec![0]));
+            a
+        })
+        .await;
+
+    let mut read = g.view("a").await.unwrap().into_reader_handle().unwrap();
+    let mut write = g.table_by_index(a).await.unwrap();
+    let add_one = || vec![(1, Modification::Apply(Operation::Add, 1.into()))];
+
+    // upsert with no existing row inserts
+    let affected = write
+        .upsert(vec![1.into()], vec![1.into(), 2.into()], add_one())
+        .await
+        .unwrap();
+    assert_eq!(affected, 1);
+    sleep().await;
+    assert_eq!(
+        read.lookup(&[1.into()], true).await.unwrap().into_vec(),
+        vec![vec![1.into(), 2.into()]]
+    );
+
+    // upsert with an existing row updates it
+    let affected = write
+        .upsert(vec![1.into()], vec![1.into(), 2.into()], add_one())
+        .await
+        .unwrap();
+    assert_eq!(affected, 2);
+    sleep().await;
+    assert_eq!(
+        read.lookup(&[1.into()], true).await.unwrap().into_vec(),
+        vec![vec![1.into(), 3.into()]]
+    );
+
+    // upsert that doesn't change the existing row affects nothing
+    let affected = write
+        .upsert(
+            vec![1.into()],
+            vec![1.into(), 2.into()],
+            vec![(1, Modification::Set(3.into()))],
+        )
+        .await
+        .unwrap();
+    assert_eq!(affected, 0);
+
+    // the key has to match the inserted row
+    write
+        .upsert(vec![2.into()], vec![1.into(), 2.into()], add_one())
+        .await
+        .unwrap_err();
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn shared_interdomain_ancestor() {
     // set up graph
@@ -926,6 +994,453 @@ async fn it_works_with_sql_recipe() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn key_versions() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("key_versions").await;
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id int, val int, PRIMARY KEY(id));
+             CREATE CACHE q FROM SELECT id, val FROM t WHERE id = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t").await.unwrap();
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+    let keys = || {
+        vec![
+            KeyComparison::Equal(vec1![1.into()]),
+            KeyComparison::Equal(vec1![2.into()]),
+        ]
+    };
+
+    t.insert_many(vec![vec![1.into(), 1.into()], vec![2.into(), 2.into()]])
+        .await
+        .unwrap();
+    sleep().await;
+    q.multi_lookup(keys(), true).await.unwrap();
+    let before = q.key_versions(keys()).await.unwrap();
+
+    t.update(vec![1.into()], vec![(1, Modification::Set(3.into()))])
+        .await
+        .unwrap();
+    sleep().await;
+    let after = q.key_versions(keys()).await.unwrap();
+
+    assert!(after[0] > before[0]);
+    assert_eq!(after[1], before[1]);
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn domain_placement_and_pinning() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("domain_placement_and_pinning").await;
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id int, val int, PRIMARY KEY(id));
+             CREATE CACHE q FROM SELECT val FROM t WHERE id = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let worker = g.workers().await.unwrap().remove(0);
+    let placement = g.domain_placement().await.unwrap();
+    assert!(placement
+        .iter()
+        .flat_map(|dp| dp.shards.iter().flatten())
+        .all(|rp| rp.worker == worker && !rp.pinned));
+
+    let base_domain = placement.iter().find(|dp| dp.has_base_table).unwrap();
+    let base_replica = ReplicaAddress {
+        domain_index: base_domain.domain_index,
+        shard: 0,
+        replica: 0,
+    };
+    let err = g
+        .move_domain(base_replica, worker.clone())
+        .await
+        .unwrap_err();
+    assert!(err.caused_by_unsupported(), "{err}");
+
+    let reader_domain = placement.iter().find(|dp| dp.has_reader).unwrap();
+    let reader_replica = ReplicaAddress {
+        domain_index: reader_domain.domain_index,
+        shard: 0,
+        replica: 0,
+    };
+    g.move_domain(reader_replica, worker.clone()).await.unwrap();
+    let placement = g.domain_placement().await.unwrap();
+    let reader_domain = placement
+        .iter()
+        .find(|dp| dp.domain_index == reader_replica.domain_index)
+        .unwrap();
+    assert!(reader_domain.shards[0][0].pinned);
+
+    g.unpin_domain(reader_replica).await.unwrap();
+    let placement = g.domain_placement().await.unwrap();
+    assert!(placement
+        .iter()
+        .flat_map(|dp| dp.shards.iter().flatten())
+        .all(|rp| !rp.pinned));
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn canary_domain_on_new_version() {
+    let authority_store = Arc::new(LocalAuthorityStore::new());
+    let mut builder = Builder::for_tests();
+    builder.set_sharding(None);
+    builder.set_persistence(get_persistence_params("canary_domain_on_new_version"));
+    let (mut g, shutdown_tx_1) = builder
+        .start_local_custom(Arc::new(Authority::from(LocalAuthority::new_with_store(
+            authority_store.clone(),
+        ))))
+        .await
+        .unwrap();
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id int, val int, PRIMARY KEY(id));
+             CREATE CACHE q FROM SELECT val FROM t WHERE id = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+    let stable_worker = g.workers().await.unwrap().remove(0);
+
+    let mut builder = Builder::for_tests();
+    builder.set_sharding(None);
+    builder.set_persistence(get_persistence_params("canary_domain_on_new_version"));
+    builder.set_version_label("canary".into());
+    let (_w2, shutdown_tx_2) = builder
+        .start(Arc::new(Authority::from(LocalAuthority::new_with_store(
+            authority_store,
+        ))))
+        .await
+        .unwrap();
+    sleep().await;
+
+    let placement = g.domain_placement().await.unwrap();
+    let base_domain = placement.iter().find(|dp| dp.has_base_table).unwrap();
+    let reader_domain = placement.iter().find(|dp| dp.has_reader).unwrap();
+
+    let err = g
+        .start_canary("nonexistent".into(), vec![reader_domain.domain_index])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("no healthy workers"), "{err}");
+    let err = g
+        .start_canary("canary".into(), vec![base_domain.domain_index])
+        .await
+        .unwrap_err();
+    assert!(err.caused_by_unsupported(), "{err}");
+
+    g.start_canary("canary".into(), vec![reader_domain.domain_index])
+        .await
+        .unwrap();
+    let placement = g.domain_placement().await.unwrap();
+    for dp in &placement {
+        let on_stable_worker = dp.shards[0][0].worker == stable_worker;
+        assert_eq!(
+            on_stable_worker,
+            dp.domain_index != reader_domain.domain_index,
+            "{dp:?}"
+        );
+    }
+
+    let mut t = g.table("t").await.unwrap();
+    t.insert(vec![1.into(), 2.into()]).await.unwrap();
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+    eventually!(q.lookup(&[1.into()], true).await.unwrap().into_vec() == vec![vec![2.into()]]);
+
+    let report = g.canary_report().await.unwrap();
+    assert_eq!(report.version.as_deref(), Some("canary"));
+    assert_eq!(report.shards.len(), 1);
+    assert_eq!(
+        report.shards[0].replica_address.domain_index,
+        reader_domain.domain_index
+    );
+    assert_ne!(report.shards[0].worker, stable_worker);
+    // The domain only has one replica, so there's nothing to compare the canary with
+    assert!(report.shards[0].stable.is_empty());
+
+    g.end_canary().await.unwrap();
+    assert_eq!(g.canary_report().await.unwrap().version, None);
+
+    tokio::join!(shutdown_tx_1.shutdown(), shutdown_tx_2.shutdown());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn view_refreshes_after_domain_moves() {
+    let authority_store = Arc::new(LocalAuthorityStore::new());
+    let cluster_name = "view_refreshes_after_domain_moves";
+    let (mut g, shutdown_tx_1) = build_custom(
+        cluster_name,
+        None,
+        true,
+        Arc::new(Authority::from(LocalAuthority::new_with_store(
+            authority_store.clone(),
+        ))),
+        false,
+        None,
+    )
+    .await;
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id int, val int, PRIMARY KEY(id));
+             CREATE CACHE q FROM SELECT val FROM t WHERE id = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t").await.unwrap();
+    t.insert(vec![1.into(), 2.into()]).await.unwrap();
+    sleep().await;
+
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+    let res = q
+        .lookup(&[DfValue::from(1)], true)
+        .await
+        .unwrap()
+        .into_vec();
+    assert_eq!(res, vec![vec![DfValue::from(2)]]);
+
+    let (_w2, shutdown_tx_2) = build_custom(
+        cluster_name,
+        None,
+        false,
+        Arc::new(Authority::from(LocalAuthority::new_with_store(
+            authority_store,
+        ))),
+        false,
+        None,
+    )
+    .await;
+    sleep().await;
+
+    let placement = g.domain_placement().await.unwrap();
+    let reader_domain = placement.iter().find(|dp| dp.has_reader).unwrap();
+    let reader_replica = ReplicaAddress {
+        domain_index: reader_domain.domain_index,
+        shard: 0,
+        replica: 0,
+    };
+    let old_worker = reader_domain.shards[0][0].worker.clone();
+    let new_worker = g
+        .workers()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|w| *w != old_worker)
+        .unwrap();
+    g.move_domain(reader_replica, new_worker).await.unwrap();
+    sleep().await;
+
+    // The handle we already have still points at the reader on the old worker, which no longer
+    // exists
+    let err = q.lookup(&[DfValue::from(1)], true).await.unwrap_err();
+    assert!(err.is_stale_reader_address(), "{err}");
+
+    // Re-requesting the view from the controller picks up the reader's new address
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+    let res = q
+        .lookup(&[DfValue::from(1)], true)
+        .await
+        .unwrap()
+        .into_vec();
+    assert_eq!(res, vec![vec![DfValue::from(2)]]);
+
+    tokio::join!(shutdown_tx_1.shutdown(), shutdown_tx_2.shutdown());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn table_durability_override() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut builder = Builder::for_tests();
+    builder.set_persistence(PersistenceParameters::new(
+        DurabilityMode::Permanent,
+        Some("table_durability_override".into()),
+        1,
+        Some(dir.path().into()),
+    ));
+    builder.set_sharding(None);
+    let (mut g, shutdown_tx) = builder.start_local().await.unwrap();
+
+    g.set_table_durability("scratch_rpc".into(), DurabilityMode::MemoryOnly)
+        .await
+        .unwrap();
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE scratch (id int, PRIMARY KEY(id)) DURABILITY='memory';
+             CREATE TABLE scratch_rpc (id int, PRIMARY KEY(id));
+             CREATE TABLE t (id int, PRIMARY KEY(id));
+             CREATE CACHE q FROM SELECT id FROM scratch WHERE id = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    for table in ["scratch", "scratch_rpc", "t"] {
+        let mut table = g.table(table).await.unwrap();
+        table.insert(vec![1.into()]).await.unwrap();
+    }
+    sleep().await;
+
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+    assert_eq!(
+        q.lookup(&[1.into()], true).await.unwrap().into_vec(),
+        vec![vec![DfValue::from(1)]]
+    );
+
+    // Only `t` is persisted, so it's the only table with a RocksDB database in the deployment's
+    // directory
+    let dbs = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    assert!(dbs.iter().any(|db| db.ends_with("-t-0.db")), "{dbs:?}");
+    assert!(!dbs.iter().any(|db| db.contains("scratch")), "{dbs:?}");
+
+    // Re-issuing the same statement for an existing table is fine, but changing its durability
+    // mode isn't
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE scratch (id int, PRIMARY KEY(id)) DURABILITY='memory';",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+    let err = g
+        .extend_recipe(
+            ChangeList::from_str(
+                "CREATE TABLE t (id int, PRIMARY KEY(id)) DURABILITY='memory';",
+                Dialect::DEFAULT_MYSQL,
+            )
+            .unwrap(),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.caused_by_unsupported(), "{err}");
+    let err = g
+        .set_table_durability("t".into(), DurabilityMode::MemoryOnly)
+        .await
+        .unwrap_err();
+    assert!(err.caused_by_unsupported(), "{err}");
+
+    let err = g
+        .extend_recipe(
+            ChangeList::from_str(
+                "CREATE TABLE t2 (id int, PRIMARY KEY(id)) DURABILITY='sometimes';",
+                Dialect::DEFAULT_MYSQL,
+            )
+            .unwrap(),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.caused_by_unsupported(), "{err}");
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn compact_table() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("compact_table").await;
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id int, val int, PRIMARY KEY(id));",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t").await.unwrap();
+    for i in 0..10 {
+        t.insert(vec![i.into(), i.into()]).await.unwrap();
+    }
+    for i in 0..5 {
+        t.delete(vec![i.into()]).await.unwrap();
+    }
+    sleep().await;
+
+    g.compact_table("t".into()).await.unwrap();
+    g.wait_for_all_tables_to_compact().await.unwrap();
+
+    g.set_table_compaction_interval("t".into(), Some(Duration::from_secs(60)))
+        .await
+        .unwrap();
+    g.set_table_compaction_interval("t".into(), None)
+        .await
+        .unwrap();
+
+    let err = g.compact_table("nonexistent".into()).await.unwrap_err();
+    assert!(
+        err.caused_by_table_not_found(),
+        "expected table not found error, got {err}"
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn set_table_tuning() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("set_table_tuning").await;
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id int, val int, PRIMARY KEY(id));",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t").await.unwrap();
+    t.insert(vec![1.into(), 1.into()]).await.unwrap();
+
+    g.set_table_tuning(
+        "t".into(),
+        RocksDbTuning {
+            compression: Some(BaseTableCompression::None),
+            write_buffer_bytes: Some(4 << 20),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    t.insert(vec![2.into(), 2.into()]).await.unwrap();
+    sleep().await;
+
+    let err = g
+        .set_table_tuning("nonexistent".into(), RocksDbTuning::default())
+        .await
+        .unwrap_err();
+    assert!(
+        err.caused_by_table_not_found(),
+        "expected table not found error, got {err}"
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn it_works_with_vote() {
     let (mut g, shutdown_tx) = start_simple_unsharded("it_works_with_vote").await;
@@ -1388,6 +1903,68 @@ async fn it_recovers_persisted_bases() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn it_restores_from_backup() {
+    let dir = tempfile::tempdir().unwrap();
+    let backup_dir = dir.path().join("backup");
+
+    {
+        let mut g = Builder::for_tests();
+        g.set_persistence(PersistenceParameters::new(
+            DurabilityMode::Permanent,
+            Some(dir.path().join("original").to_string_lossy().into()),
+            1,
+            None,
+        ));
+        let (mut g, shutdown_tx) = g.start_local().await.unwrap();
+        g.backend_ready().await;
+
+        let sql = "
+            CREATE TABLE Car (id int, price int, PRIMARY KEY(id));
+            CREATE CACHE CarPrice FROM SELECT price FROM Car WHERE id = ?;
+        ";
+        g.extend_recipe(ChangeList::from_str(sql, Dialect::DEFAULT_MYSQL).unwrap())
+            .await
+            .unwrap();
+
+        let mut mutator = g.table("Car").await.unwrap();
+        for i in 1..10 {
+            let price = i * 10;
+            mutator.insert(vec![i.into(), price.into()]).await.unwrap();
+        }
+        sleep().await;
+
+        assert_eq!(g.backup(backup_dir.clone()).await.unwrap(), 1);
+        shutdown_tx.shutdown().await;
+    }
+
+    let mut g = Builder::for_tests();
+    g.set_persistence(PersistenceParameters::new(
+        DurabilityMode::Permanent,
+        Some(dir.path().join("restored").to_string_lossy().into()),
+        1,
+        None,
+    ));
+    g.set_restore_from(backup_dir);
+    let (mut g, shutdown_tx) = g.start_local().await.unwrap();
+    g.backend_ready().await;
+
+    let mut getter = g
+        .view("CarPrice")
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+    for i in 1..10 {
+        let price = i * 10;
+        let result = getter.lookup(&[i.into()], true).await.unwrap().into_vec();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0][0], price.into());
+    }
+
+    shutdown_tx.shutdown().await;
+}
+
 // TODO(ENG-860): Flaky test.
 #[tokio::test(flavor = "multi_thread")]
 async fn it_recovers_persisted_bases_with_volume_id() {
@@ -1886,10 +2463,58 @@ async fn it_works_with_simple_arithmetic() {
     // Let writes propagate:
     sleep().await;
 
-    // Retrieve the result of the count query:
-    let result = getter.lookup(&[id.clone()], true).await.unwrap().into_vec();
-    assert_eq!(result.len(), 1);
-    assert_eq!(result[0][0], 246.into());
+    // Retrieve the result of the count query:
+    let result = getter.lookup(&[id.clone()], true).await.unwrap().into_vec();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0][0], 246.into());
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn it_works_with_case_in_projection() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("it_works_with_case_in_projection").await;
+
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE Car (id int, price int, PRIMARY KEY(id));
+         CREATE CACHE CarPrice FROM SELECT id,
+             CASE WHEN price > 100 THEN 'expensive' WHEN price > 10 THEN 'fair' END,
+             CASE price WHEN 0 THEN 'free' ELSE 'paid' END
+             FROM Car WHERE id = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut mutator = g.table("Car").await.unwrap();
+    let mut getter = g
+        .view("CarPrice")
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+    mutator
+        .insert_many(vec![
+            vec![1.into(), 123.into()],
+            vec![2.into(), 50.into()],
+            vec![3.into(), 0.into()],
+        ])
+        .await
+        .unwrap();
+
+    sleep().await;
+
+    for (id, price_range, paid) in [
+        (1, DfValue::from("expensive"), "paid"),
+        (2, DfValue::from("fair"), "paid"),
+        (3, DfValue::None, "free"),
+    ] {
+        let result = getter.lookup(&[id.into()], true).await.unwrap().into_vec();
+        assert_eq!(result, vec![vec![id.into(), price_range, paid.into()]]);
+    }
 
     shutdown_tx.shutdown().await;
 }
@@ -3809,7 +4434,8 @@ async fn finkelstein1982_queries() {
                     inc.add_table(stmt.table, stmt.body.unwrap(), mig).unwrap();
                 }
                 SqlQuery::Select(stmt) => {
-                    inc.add_query(None, stmt, false, &[], mig).unwrap();
+                    inc.add_query(None, stmt, false, Default::default(), &[], mig)
+                        .unwrap();
                 }
                 _ => panic!("unexpected query type"),
             }
@@ -4458,6 +5084,153 @@ async fn simple_pagination() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn window_functions() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("window_functions").await;
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (x INT, y TEXT);
+         CREATE CACHE row_numbers FROM
+         SELECT x, y, ROW_NUMBER() OVER (PARTITION BY y ORDER BY x) AS rn FROM t;
+         CREATE CACHE ranks FROM
+         SELECT x, RANK() OVER (ORDER BY x DESC) AS r FROM t WHERE y = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t").await.unwrap();
+    let mut row_numbers = g
+        .view("row_numbers")
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+    let mut ranks = g.view("ranks").await.unwrap().into_reader_handle().unwrap();
+
+    t.insert_many(vec![
+        vec![DfValue::from(3), DfValue::from("a")],
+        vec![DfValue::from(1), DfValue::from("a")],
+        vec![DfValue::from(2), DfValue::from("b")],
+    ])
+    .await
+    .unwrap();
+
+    sleep().await;
+
+    let mut rows: Vec<Vec<DfValue>> = row_numbers.lookup(&[0.into()], true).await.unwrap().into();
+    rows.sort();
+    assert_eq!(
+        rows,
+        vec![
+            vec![DfValue::from(1), DfValue::from("a"), DfValue::from(1)],
+            vec![DfValue::from(2), DfValue::from("b"), DfValue::from(1)],
+            vec![DfValue::from(3), DfValue::from("a"), DfValue::from(2)],
+        ]
+    );
+
+    let mut rows: Vec<Vec<DfValue>> = ranks.lookup(&["a".into()], true).await.unwrap().into();
+    rows.sort();
+    assert_eq!(
+        rows,
+        vec![
+            vec![DfValue::from(1), DfValue::from(2)],
+            vec![DfValue::from(3), DfValue::from(1)],
+        ]
+    );
+
+    t.insert(vec![DfValue::from(2), DfValue::from("a")])
+        .await
+        .unwrap();
+
+    sleep().await;
+
+    let mut rows: Vec<Vec<DfValue>> = row_numbers.lookup(&[0.into()], true).await.unwrap().into();
+    rows.sort();
+    assert_eq!(
+        rows,
+        vec![
+            vec![DfValue::from(1), DfValue::from("a"), DfValue::from(1)],
+            vec![DfValue::from(2), DfValue::from("a"), DfValue::from(2)],
+            vec![DfValue::from(2), DfValue::from("b"), DfValue::from(1)],
+            vec![DfValue::from(3), DfValue::from("a"), DfValue::from(3)],
+        ]
+    );
+
+    let mut rows: Vec<Vec<DfValue>> = ranks.lookup(&["a".into()], true).await.unwrap().into();
+    rows.sort();
+    assert_eq!(
+        rows,
+        vec![
+            vec![DfValue::from(1), DfValue::from(3)],
+            vec![DfValue::from(2), DfValue::from(2)],
+            vec![DfValue::from(3), DfValue::from(1)],
+        ]
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn count_star_over_window() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("count_star_over_window").await;
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (x INT, y TEXT);
+         CREATE CACHE q FROM
+         SELECT x, COUNT(*) OVER (PARTITION BY y) AS c FROM t WHERE y = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t").await.unwrap();
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+
+    t.insert_many(vec![
+        vec![DfValue::from(1), DfValue::from("a")],
+        vec![DfValue::from(2), DfValue::from("a")],
+        vec![DfValue::from(3), DfValue::from("b")],
+    ])
+    .await
+    .unwrap();
+
+    sleep().await;
+
+    let mut rows: Vec<Vec<DfValue>> = q.lookup(&["a".into()], true).await.unwrap().into();
+    rows.sort();
+    assert_eq!(
+        rows,
+        vec![
+            vec![DfValue::from(1), DfValue::from(2)],
+            vec![DfValue::from(2), DfValue::from(2)],
+        ]
+    );
+
+    t.insert(vec![DfValue::from(3), DfValue::from("a")])
+        .await
+        .unwrap();
+
+    sleep().await;
+
+    let mut rows: Vec<Vec<DfValue>> = q.lookup(&["a".into()], true).await.unwrap().into();
+    rows.sort();
+    assert_eq!(
+        rows,
+        vec![
+            vec![DfValue::from(1), DfValue::from(3)],
+            vec![DfValue::from(2), DfValue::from(3)],
+            vec![DfValue::from(3), DfValue::from(3)],
+        ]
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn correct_nested_view_schema() {
     let r_txt = "CREATE TABLE votes (story int, user int);
@@ -5389,6 +6162,8 @@ async fn post_read_ilike() {
             timestamp: None,
             limit: None,
             offset: None,
+            timeout: None,
+            result_limits: Default::default(),
         })
         .await
         .unwrap()
@@ -8026,11 +8801,11 @@ async fn partial_join_on_one_parent() {
 
 const LIMIT: usize = 10;
 
-async fn aggressive_eviction_setup() -> (crate::Handle, ShutdownSender) {
+async fn aggressive_eviction_setup(memory_limit: usize) -> (crate::Handle, ShutdownSender) {
     let (mut g, shutdown_tx) = build(
         "aggressive_eviction",
         None,
-        Some((15000, Duration::from_millis(4))),
+        Some((memory_limit, Duration::from_millis(4))),
     )
     .await;
 
@@ -8095,7 +8870,7 @@ async fn aggressive_eviction_setup() -> (crate::Handle, ShutdownSender) {
 }
 
 async fn aggressive_eviction_impl() {
-    let (mut g, shutdown_tx) = aggressive_eviction_setup().await;
+    let (mut g, shutdown_tx) = aggressive_eviction_setup(15000).await;
     let mut view = g.view("w").await.unwrap().into_reader_handle().unwrap();
 
     for i in 0..500 {
@@ -8114,7 +8889,7 @@ async fn aggressive_eviction_impl() {
 }
 
 async fn aggressive_eviction_range_impl() {
-    let (mut g, shutdown_tx) = aggressive_eviction_setup().await;
+    let (mut g, shutdown_tx) = aggressive_eviction_setup(15000).await;
 
     let mut view = g.view("v").await.unwrap().into_reader_handle().unwrap();
 
@@ -8166,6 +8941,38 @@ rusty_fork_test! {
     }
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn view_memory_limit() {
+    // Never hit the global memory limit, so that only the view's budget causes evictions
+    let (mut g, shutdown_tx) = aggressive_eviction_setup(usize::MAX).await;
+
+    g.set_view_memory_limit("w".into(), Some(1)).await.unwrap();
+    let err = g
+        .set_view_memory_limit("nonexistent".into(), Some(1))
+        .await
+        .unwrap_err();
+    assert!(err.caused_by_view_not_found(), "{err}");
+
+    let mut view = g.view("w").await.unwrap().into_reader_handle().unwrap();
+    for i in 0..100 {
+        let offset = i % 10;
+        let keys: Vec<_> = (offset..offset + 20)
+            .map(|k| KeyComparison::Equal(vec1::Vec1::new(DfValue::Int(k))))
+            .collect();
+
+        let r = view
+            .raw_lookup(ViewQuery::from((keys, true)))
+            .await
+            .unwrap()
+            .into_vec();
+        assert_eq!(r.len(), LIMIT);
+    }
+
+    g.set_view_memory_limit("w".into(), None).await.unwrap();
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn partial_ingress_above_full_reader() {
     readyset_tracing::init_test_logging();
@@ -9085,7 +9892,8 @@ async fn multiple_simultaneous_migrations() {
                     parse_select_statement(nom_sql::Dialect::MySQL, "SELECT * FROM t WHERE x = ?")
                         .unwrap()
                 ),
-                always: false
+                always: false,
+                options: Default::default()
             },
             Dialect::DEFAULT_MYSQL
         )),
@@ -9096,7 +9904,8 @@ async fn multiple_simultaneous_migrations() {
                     parse_select_statement(nom_sql::Dialect::MySQL, "SELECT * FROM t WHERE y = ?")
                         .unwrap()
                 ),
-                always: false
+                always: false,
+                options: Default::default()
             },
             Dialect::DEFAULT_MYSQL
         ))
@@ -9258,14 +10067,8 @@ async fn double_create_table_with_multiple_modifications() {
         .await
         .unwrap();
 
-    // Altering a table currently means we delete and recreate the whole thing.
-    g.view("t1").await.unwrap_err();
-
-    let recreate_view = "CREATE CACHE t1 FROM SELECT * FROM table_1;";
-    g.extend_recipe(ChangeList::from_str(recreate_view, Dialect::DEFAULT_MYSQL).unwrap())
-        .await
-        .unwrap();
-
+    // Altering a table means we delete and recreate the whole thing, but the cache is re-planned
+    // against the new schema
     let mut view = g.view("t1").await.unwrap().into_reader_handle().unwrap();
     let results = view.lookup(&[0.into()], true).await.unwrap().into_vec();
     assert!(results.is_empty());
@@ -9291,6 +10094,129 @@ async fn double_create_table_with_multiple_modifications() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn changing_table_schema_recreates_dependent_caches() {
+    let (mut g, shutdown_tx) =
+        start_simple_unsharded("changing_table_schema_recreates_dependent_caches").await;
+
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id INT, a INT, b INT);
+             CREATE VIEW v AS SELECT id, a FROM t;
+             CREATE CACHE from_view FROM SELECT id, a FROM v WHERE id = ?;
+             CREATE CACHE uses_b FROM SELECT id, b FROM t WHERE id = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id INT, a INT, c INT);",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    // The cache on the view is still valid, and should have been re-created
+    let mut from_view = g
+        .view("from_view")
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+    // The cache referencing the dropped column isn't, and should have been dropped
+    g.view("uses_b").await.unwrap_err();
+
+    let mut table = g.table("t").await.unwrap();
+    table
+        .insert(vec![1.into(), 2.into(), 3.into()])
+        .await
+        .unwrap();
+
+    sleep().await;
+
+    assert_eq!(
+        from_view
+            .lookup(&[1.into()], true)
+            .await
+            .unwrap()
+            .into_vec(),
+        vec![vec![DfValue::from(1), DfValue::from(2)]]
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn alter_table_adds_and_drops_columns_in_place() {
+    let (mut g, shutdown_tx) =
+        start_simple_unsharded("alter_table_adds_and_drops_columns_in_place").await;
+
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id INT PRIMARY KEY, a INT, b INT);
+             CREATE CACHE q FROM SELECT * FROM t WHERE id = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut table = g.table("t").await.unwrap();
+    table
+        .insert(vec![1.into(), 2.into(), 3.into()])
+        .await
+        .unwrap();
+
+    g.extend_recipe(
+        ChangeList::from_str(
+            "ALTER TABLE t ADD COLUMN c INT DEFAULT 4, DROP COLUMN b;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    // The existing row should have been kept, with the new column backfilled with its default
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+    assert_eq!(
+        q.lookup(&[1.into()], true).await.unwrap().into_vec(),
+        vec![vec![DfValue::from(1), DfValue::from(2), DfValue::from(4)]]
+    );
+
+    let mut table = g.table("t").await.unwrap();
+    table
+        .insert(vec![5.into(), 6.into(), 7.into()])
+        .await
+        .unwrap();
+
+    sleep().await;
+
+    assert_eq!(
+        q.lookup(&[5.into()], true).await.unwrap().into_vec(),
+        vec![vec![DfValue::from(5), DfValue::from(6), DfValue::from(7)]]
+    );
+
+    // The dropped column is still part of the table's base node, so can't be added back in place
+    let err = g
+        .extend_recipe(
+            ChangeList::from_str("ALTER TABLE t ADD COLUMN b INT;", Dialect::DEFAULT_MYSQL)
+                .unwrap(),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.caused_by_resnapshot_needed(), "{err}");
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn double_identical_create_table() {
     let (mut g, shutdown_tx) = start_simple_unsharded("double_create_table_add_column").await;
@@ -9414,6 +10340,39 @@ async fn multiple_aggregates_and_predicates() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn having_aggregate_alias() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("having_aggregate_alias").await;
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (a int, b int);
+            CREATE CACHE q FROM
+                SELECT t.a, count(t.b) AS c FROM t GROUP BY t.a HAVING c > 1 AND sum(t.b) < 10",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t").await.unwrap();
+    t.insert_many(vec![
+        vec![DfValue::from(1), DfValue::from(1)],
+        vec![DfValue::from(1), DfValue::from(2)],
+        vec![DfValue::from(2), DfValue::from(3)],
+        vec![DfValue::from(3), DfValue::from(5)],
+        vec![DfValue::from(3), DfValue::from(6)],
+    ])
+    .await
+    .unwrap();
+
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+    let res = q.lookup(&[0.into()], true).await.unwrap().into_vec();
+    assert_eq!(res, vec![vec![DfValue::from(1), DfValue::from(2)]]);
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn cascade_drop_view() {
     let (mut g, shutdown_tx) = start_simple_unsharded("cascade_drop_view").await;
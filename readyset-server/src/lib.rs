@@ -419,6 +419,7 @@ mod integration_serial;
 mod integration_utils;
 
 pub mod metrics;
+pub mod profiling;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ReuseConfigType {
@@ -429,16 +430,18 @@ pub enum ReuseConfigType {
 
 use controller::migrate::materialization;
 pub use controller::migrate::materialization::FrontierStrategy;
+pub use controller::rebalance::RebalancePolicy;
 pub use controller::replication::{ReplicationOptions, ReplicationStrategy};
 use controller::sql;
 use database_utils::UpstreamConfig;
-pub use dataflow::{DurabilityMode, PersistenceParameters};
+pub use dataflow::{DurabilityMode, PersistenceParameters, RemoteStorage, StateCompression};
 pub use petgraph::graph::NodeIndex;
 pub use readyset_client::consensus::{Authority, LocalAuthority};
 pub use readyset_client::*;
 
 pub use crate::builder::Builder;
 pub use crate::handle::Handle;
+pub use crate::http_router::HttpAuthConfig;
 pub use crate::metrics::NoriaMetricsRecorder;
 
 pub mod manual {
@@ -452,11 +455,14 @@ use std::net::{IpAddr, ToSocketAddrs};
 use std::path::PathBuf;
 use std::time::Duration;
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
 use clap::Args;
 use dataflow::DomainConfig;
+use readyset_util::redacted::RedactedString;
 use serde::{Deserialize, Serialize};
 
+use crate::worker::watchdog::WatchdogConfig;
+
 /// Configuration for a running ReadySet cluster
 // WARNING: if you change this structure or any of the structures used in its fields, make sure to
 // write a serialized instance of the previous version to tests/config_versions by running the
@@ -484,11 +490,27 @@ pub struct Config {
     pub(crate) replicator_statement_logging: bool,
     #[serde(default)]
     pub(crate) replication_strategy: ReplicationStrategy,
+    /// How to redistribute domains between workers as workers join and leave the cluster
+    #[serde(default)]
+    pub(crate) rebalance_policy: RebalancePolicy,
     /// The duration to wait before canceling the task waiting on an upquery.
     pub(crate) upquery_timeout: Duration,
     /// The duration to wait before canceling a task waiting on a worker request. Worker requests
     /// are typically issued as part of migrations.
     pub(crate) worker_request_timeout: Duration,
+    /// Whether to serve the profiling endpoints in [`profiling`] from the HTTP router
+    #[serde(default)]
+    pub(crate) enable_pprof: bool,
+    /// Configuration for detecting stalled domains, or `None` to disable stall detection
+    #[serde(default)]
+    pub(crate) domain_watchdog: Option<WatchdogConfig>,
+    /// Tokens required to access the HTTP endpoints of the server
+    #[serde(default)]
+    pub(crate) http_auth: HttpAuthConfig,
+    /// If set, a directory containing a backup (created via the `/backup` controller RPC) to
+    /// restore the deployment from, if the deployment has no existing state
+    #[serde(default)]
+    pub(crate) restore_from: Option<PathBuf>,
 }
 
 impl Default for Config {
@@ -507,6 +529,8 @@ impl Default for Config {
                 // now.
                 table_request_timeout: Duration::from_millis(1800000),
                 eviction_kind: dataflow::EvictionKind::Random,
+                tenant_weights: Default::default(),
+                max_value_size: None,
             },
             persistence: Default::default(),
             quorum: 1,
@@ -516,8 +540,13 @@ impl Default for Config {
             replicator_statement_logging: false,
             replicator_config: Default::default(),
             replication_strategy: Default::default(),
+            rebalance_policy: Default::default(),
             upquery_timeout: Duration::from_millis(5000),
             worker_request_timeout: Duration::from_millis(1800000),
+            enable_pprof: false,
+            domain_watchdog: None,
+            http_auth: Default::default(),
+            restore_from: None,
         }
     }
 }
@@ -532,6 +561,33 @@ pub fn resolve_addr(addr: &str) -> anyhow::Result<IpAddr> {
         .ip())
 }
 
+/// Parse a per-table durability override of the form `[<schema>.]<table>=<durability>`
+pub fn parse_table_durability(s: &str) -> anyhow::Result<(nom_sql::Relation, DurabilityMode)> {
+    let (table, mode) = s
+        .split_once('=')
+        .ok_or_else(|| anyhow!("Expected <table>=<durability>, got: {}", s))?;
+    let table = match table.split_once('.') {
+        Some((schema, name)) => nom_sql::Relation {
+            schema: Some(schema.into()),
+            name: name.into(),
+        },
+        None => table.into(),
+    };
+    Ok((table, mode.parse()?))
+}
+
+/// Parse a tenant scheduling weight of the form `<schema>=<weight>`
+pub fn parse_tenant_weight(s: &str) -> anyhow::Result<(nom_sql::SqlIdentifier, u32)> {
+    let (schema, weight) = s
+        .split_once('=')
+        .ok_or_else(|| anyhow!("Expected <schema>=<weight>, got: {}", s))?;
+    let weight: u32 = weight.parse()?;
+    if weight == 0 {
+        bail!("Tenant weights must be greater than 0");
+    }
+    Ok((schema.into(), weight))
+}
+
 // Command-line options for running a `readyset-server` worker.
 //
 // This option struct is intended to be embedded inside of a larger option struct using
@@ -546,13 +602,37 @@ pub struct WorkerOptions {
     #[clap(long, default_value = "persistent")]
     pub durability: DurabilityMode,
 
-    /// Number of background threads used by RocksDB
-    #[clap(long, default_value = "6")]
-    pub persistence_threads: i32,
+    /// Override the durability of an individual base table, as `<table>=<durability>`, eg
+    /// `--table-durability scratch=memory`. Can be specified multiple times. Tables can be
+    /// schema-qualified (`schema.table`); unqualified table names apply to tables with that name
+    /// in any schema. Tables created by a recipe can also be given a durability with a
+    /// `DURABILITY = '<durability>'` option in their `CREATE TABLE` statement.
+    #[clap(long = "table-durability", value_parser = parse_table_durability)]
+    pub table_durability: Vec<(nom_sql::Relation, DurabilityMode)>,
+
+    /// Number of background threads used by RocksDB.
+    ///
+    /// Defaults to the number of CPUs available to the process (taking any cgroup CPU limit into
+    /// account), up to a maximum of 6.
+    #[clap(long)]
+    pub persistence_threads: Option<i32>,
+
+    /// Memory, in bytes, available for partially materialized state (0 = unlimited).
+    ///
+    /// If not specified and the process is running with a cgroup memory limit (eg inside of a
+    /// container), defaults to 75% of that limit. Otherwise, defaults to unlimited.
+    #[clap(long, short = 'm', env = "NORIA_MEMORY_BYTES")]
+    pub memory: Option<usize>,
 
-    /// Memory, in bytes, available for partially materialized state (0 = unlimited)
-    #[clap(long, short = 'm', default_value = "0", env = "NORIA_MEMORY_BYTES")]
-    pub memory: usize,
+    /// If set, once the uncompressed rows in a node's partially materialized state grow past this
+    /// many bytes, cold keys in that state are stored zstd-compressed until the uncompressed rows
+    /// are back under the limit. If unset, partial state is never compressed.
+    #[clap(long, env = "STATE_COMPRESSION_THRESHOLD_BYTES")]
+    pub state_compression_threshold_bytes: Option<u64>,
+
+    /// The number of decompressed keys to cache per node for reads of compressed partial state.
+    #[clap(long, env = "STATE_COMPRESSION_HOT_KEYS", default_value = "1024")]
+    pub state_compression_hot_keys: usize,
 
     /// Frequency at which to check the state size against the memory limit (in seconds)
     #[clap(
@@ -566,6 +646,16 @@ pub struct WorkerOptions {
     #[clap(long = "eviction-policy", default_value_t = dataflow::EvictionKind::LRU)]
     pub eviction_kind: dataflow::EvictionKind,
 
+    /// Frequency at which to checkpoint the keys in partially materialized readers to disk, so
+    /// that they can be re-populated when the server restarts (in seconds, 0 = disabled). Only
+    /// takes effect with `--durability persistent`.
+    #[clap(
+        long = "reader-checkpoint-every",
+        default_value = "0",
+        env = "READER_CHECKPOINT_EVERY"
+    )]
+    pub reader_checkpoint_interval_secs: u64,
+
     /// Disable partial
     #[clap(long = "nopartial", hide = true)]
     pub no_partial: bool,
@@ -590,6 +680,11 @@ pub struct WorkerOptions {
     #[clap(long, env = "VOLUME_ID")]
     pub volume_id: Option<VolumeId>,
 
+    /// Label for the version of ReadySet this server is running, used to canary new versions on
+    /// a subset of domains before upgrading the whole deployment. Defaults to the release version.
+    #[clap(long, env = "VERSION_LABEL")]
+    pub version_label: Option<String>,
+
     /// Enable experimental support for TopK in dataflow.
     ///
     /// NOTE If enabled, this must be set for all ReadySet processes (both servers and adapters).
@@ -612,15 +707,86 @@ pub struct WorkerOptions {
     #[clap(long, env = "DB_DIR")]
     pub db_dir: Option<PathBuf>,
 
+    /// URL of an object store to periodically upload the persisted state of base tables to, so
+    /// that it can be recovered if the local disk is lost. Supports `s3://bucket/prefix`,
+    /// `gs://bucket/prefix`, and `file:///path`. Credentials are read from the standard
+    /// environment variables for each object store.
+    #[clap(long, env = "REMOTE_STORAGE_URL")]
+    pub remote_storage_url: Option<String>,
+
+    /// Interval in seconds between uploads of the persisted state of each base table to
+    /// `--remote-storage-url`
+    #[clap(
+        long,
+        env = "REMOTE_STORAGE_UPLOAD_INTERVAL",
+        default_value = "300",
+        requires = "remote_storage_url"
+    )]
+    pub remote_storage_upload_interval_secs: u64,
+
+    /// Directory containing a backup of a deployment, created via the `/backup` controller RPC, to
+    /// restore this deployment from when it starts up. Ignored if the deployment already exists.
+    #[clap(long, env = "RESTORE_FROM")]
+    pub restore_from: Option<PathBuf>,
+
     #[clap(flatten)]
     pub domain_replication_options: ReplicationOptions,
 
+    /// How to redistribute domains between workers as workers join and leave the cluster.
+    /// `recover` only re-schedules the domains that were running on workers that leave, while
+    /// `balance` additionally moves domains onto the least loaded workers whenever a worker joins
+    /// or leaves, so that all workers run a similar number of domains.
+    #[clap(long, env = "REBALANCE_POLICY", value_enum, default_value = "recover")]
+    pub rebalance_policy: RebalancePolicy,
+
     #[clap(flatten)]
     pub replicator_config: UpstreamConfig,
 
     /// Timeout in seconds for all requests made from the controller to workers
     #[clap(long, env = "WORKER_REQUEST_TIMEOUT_SECONDS", default_value = "1800")]
     pub worker_request_timeout_seconds: u64,
+
+    /// Serve CPU profiling (`/debug/pprof/profile`) and async task dump (`/debug/pprof/tasks`)
+    /// endpoints from the HTTP server of the ReadySet server and adapter
+    #[clap(long, env = "ENABLE_PPROF")]
+    pub enable_pprof: bool,
+
+    /// Report a domain as stalled (via a log message and the `readyset_domain.stalled` metric) if
+    /// it spends longer than this many seconds handling a single batch of packets or request (0 =
+    /// disabled)
+    #[clap(long, env = "DOMAIN_STALL_THRESHOLD", default_value = "30")]
+    pub domain_stall_threshold_secs: u64,
+
+    /// When a domain stalls, log the type of the packet it is handling and the depths of its
+    /// incoming and outgoing packet queues
+    #[clap(long, env = "DUMP_STALLED_DOMAINS")]
+    pub dump_stalled_domains: bool,
+
+    /// Give the tenant owning the given schema a larger share of each domain's time for handling
+    /// upqueries, as `<schema>=<weight>`, eg `--tenant-weight important=4`. Can be specified
+    /// multiple times. When multiple tenants are waiting on upqueries in the same domain, each is
+    /// given time proportional to its weight; schemas not listed have a weight of 1.
+    #[clap(long = "tenant-weight", value_parser = parse_tenant_weight)]
+    pub tenant_weights: Vec<(nom_sql::SqlIdentifier, u32)>,
+
+    /// The maximum size, in bytes, of a single text or binary value (such as a BLOB) that can be
+    /// written to a base table. Writes and replicated changes containing larger values fail. If
+    /// unset, values of any size are accepted.
+    #[clap(long, env = "MAX_VALUE_SIZE")]
+    pub max_value_size: Option<usize>,
+
+    /// Require requests to the HTTP endpoints of the ReadySet server to authenticate with this
+    /// token, passed as `Authorization: Bearer <token>`. Grants access to all endpoints, and must
+    /// be set to the same value for all servers and adapters in the deployment.
+    #[clap(long, env = "HTTP_AUTH_TOKEN")]
+    pub http_auth_token: Option<RedactedString>,
+
+    /// Token granting access to only the read-only introspection endpoints of the ReadySet server
+    #[clap(long, env = "HTTP_READ_ONLY_AUTH_TOKEN", requires = "http_auth_token")]
+    pub http_read_only_auth_token: Option<RedactedString>,
+
+    #[clap(flatten)]
+    pub internal_tls: readyset_client::tls::InternalTlsOptions,
 }
 
 use std::pin::Pin;
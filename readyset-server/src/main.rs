@@ -9,12 +9,13 @@ use clap::Parser;
 use futures_util::future::{self, Either};
 use metrics_exporter_prometheus::PrometheusBuilder;
 use readyset_client::metrics::recorded;
-use readyset_server::consensus::AuthorityType;
+use readyset_server::consensus::{AuthorityOptions, AuthorityType};
 use readyset_server::metrics::{
     install_global_recorder, CompositeMetricsRecorder, MetricsRecorder,
 };
 use readyset_server::{resolve_addr, Builder, NoriaMetricsRecorder, WorkerOptions};
 use readyset_telemetry_reporter::{TelemetryEvent, TelemetryInitializer};
+use readyset_util::cgroup::CgroupLimits;
 use readyset_version::*;
 use tracing::{error, info};
 
@@ -93,6 +94,12 @@ struct Options {
     authority: AuthorityType,
 
     /// Authority uri
+    ///
+    /// With `--authority consul`, this may include a scheme and a Consul ACL token, as
+    /// `https://<token>@<address>:<port>`.
+    ///
+    /// With `--authority kubernetes`, this is the URL of the Kubernetes API server, and defaults
+    /// to the API server of the cluster ReadySet is running in.
     // NOTE: `authority_address` should come after `authority` for clap to set default values
     // properly
     #[clap(
@@ -100,10 +107,14 @@ struct Options {
         env = "AUTHORITY_ADDRESS",
         default_value_if("authority", "standalone", Some(".")),
         default_value_if("authority", "consul", Some("127.0.0.1:8500")),
+        default_value_if("authority", "kubernetes", Some("")),
         required = false
     )]
     authority_address: String,
 
+    #[clap(flatten)]
+    authority_options: AuthorityOptions,
+
     /// Whether this server should only run reader domains
     #[clap(long, conflicts_with = "no_readers")]
     reader_only: bool,
@@ -141,9 +152,11 @@ struct Options {
 }
 
 fn main() -> anyhow::Result<()> {
-    let opts: Options = Options::parse();
+    let mut opts: Options = Options::parse();
+    let worker_threads = CgroupLimits::detect().available_cpus();
     let rt = tokio::runtime::Builder::new_multi_thread()
         .enable_all()
+        .worker_threads(worker_threads)
         .thread_name("worker")
         .build()?;
 
@@ -154,6 +167,7 @@ fn main() -> anyhow::Result<()> {
         }
     });
     info!(?opts, "Starting ReadySet server");
+    info!(worker_threads, "Started runtime");
 
     info!(version = %VERSION_STR_ONELINE);
 
@@ -204,6 +218,16 @@ fn main() -> anyhow::Result<()> {
             .as_millis() as u64
     );
 
+    if let Err(error) = opts.worker_options.internal_tls.install() {
+        error!(%error, "Error configuring internal TLS");
+        process::exit(1)
+    }
+
+    if let Err(error) = rt.block_on(opts.worker_options.replicator_config.resolve_secrets()) {
+        error!(%error, "Error reading --upstream-db-url-secret");
+        process::exit(1)
+    }
+
     if let Some(volume_id) = &opts.worker_options.volume_id {
         info!(%volume_id);
     }
@@ -245,8 +269,11 @@ fn main() -> anyhow::Result<()> {
 
     let deployment = opts.deployment;
     let external_port = opts.external_port;
+    let authority_options = opts.authority_options;
     let (_handle, shutdown_tx) = rt.block_on(async move {
-        let authority = authority.to_authority(&authority_addr, &deployment).await;
+        let authority = authority
+            .to_authority_with_options(&authority_addr, &deployment, &authority_options)
+            .await;
 
         let external_addr = external_addr.await.unwrap_or_else(|error| {
             error!(%error, "Error obtaining external IP address");
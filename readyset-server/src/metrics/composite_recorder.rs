@@ -3,7 +3,7 @@ use std::sync::Arc;
 
 use metrics::{Counter, Gauge, Histogram, KeyName, Recorder, SharedString, Unit};
 use metrics_exporter_prometheus::PrometheusRecorder;
-use readyset_client::metrics::Key;
+use readyset_client::metrics::{Key, MetricsDump};
 
 use crate::metrics::recorders::MetricsRecorder;
 use crate::metrics::{Clear, Render};
@@ -98,6 +98,12 @@ impl CompositeMetricsRecorder {
             RecorderType::Prometheus => self.prom_recorder.as_ref().map(|x| x.render()),
         }
     }
+
+    /// Returns a [`MetricsDump`] of the current values of all metrics, if the ReadySet
+    /// sub-recorder exists
+    pub fn dump(&self) -> Option<MetricsDump> {
+        self.noria_recorder.as_ref().map(|x| x.dump())
+    }
 }
 
 impl Clear for CompositeMetricsRecorder {
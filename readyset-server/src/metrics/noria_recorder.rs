@@ -22,6 +22,27 @@ impl NoriaMetricsRecorder {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// Returns a [`MetricsDump`] of the current values of all metrics in this recorder
+    pub fn dump(&self) -> MetricsDump {
+        MetricsDump::from_metrics(
+            self.counters
+                .lock()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.load(Relaxed)))
+                .collect(),
+            self.gauges
+                .lock()
+                .iter()
+                .map(|(k, v)| (k.clone(), f64::from_bits(v.load(Relaxed))))
+                .collect(),
+            self.histograms
+                .lock()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.0.lock().clone()))
+                .collect(),
+        )
+    }
 }
 
 struct NoriaHistogram(Mutex<metrics_util::Histogram>);
@@ -95,24 +116,7 @@ impl Recorder for NoriaMetricsRecorder {
 
 impl Render for NoriaMetricsRecorder {
     fn render(&self) -> String {
-        let md = MetricsDump::from_metrics(
-            self.counters
-                .lock()
-                .iter()
-                .map(|(k, v)| (k.clone(), v.load(Relaxed)))
-                .collect(),
-            self.gauges
-                .lock()
-                .iter()
-                .map(|(k, v)| (k.clone(), f64::from_bits(v.load(Relaxed))))
-                .collect(),
-            self.histograms
-                .lock()
-                .iter()
-                .map(|(k, v)| (k.clone(), v.0.lock().clone()))
-                .collect(),
-        );
-        serde_json::to_string(&md).unwrap()
+        serde_json::to_string(&self.dump()).unwrap()
     }
 }
 
@@ -0,0 +1,226 @@
+//! Built-in profiling endpoints, for diagnosing production performance problems and stalls without
+//! having to re-deploy an instrumented build.
+//!
+//! When enabled with `--enable-pprof`, the HTTP routers of both readyset-server and the adapter
+//! serve the following endpoints:
+//!
+//! * `GET /debug/pprof/profile?seconds=<n>&frequency=<hz>&format=<flamegraph|pprof>` samples the
+//!   CPU usage of the whole process for `seconds` (default 30), and returns either an SVG
+//!   flamegraph (the default) or an uncompressed protobuf profile that can be read by `go tool
+//!   pprof`.
+//! * `GET /debug/pprof/tasks` returns a dump of the backtraces of all tokio tasks in the runtime
+//!   serving the request. This requires building with `RUSTFLAGS="--cfg tokio_unstable --cfg
+//!   tokio_taskdump"`, and returns 501 Not Implemented otherwise.
+//!
+//! Only one CPU profile can be captured at a time.
+
+use std::time::Duration;
+
+use hyper::header::CONTENT_TYPE;
+use hyper::{Body, Response, StatusCode};
+use readyset_errors::{internal_err, ReadySetError, ReadySetResult};
+use tracing::info;
+
+/// The prefix of the paths of all profiling endpoints
+pub const PPROF_PATH_PREFIX: &str = "/debug/pprof/";
+
+/// The default duration to sample CPU usage for
+const DEFAULT_PROFILE_DURATION: Duration = Duration::from_secs(30);
+
+/// The maximum duration to sample CPU usage for
+const MAX_PROFILE_DURATION: Duration = Duration::from_secs(300);
+
+/// The default frequency to sample CPU usage at, in Hz. Slightly off of 100 to avoid sampling in
+/// lockstep with other periodic activity.
+const DEFAULT_PROFILE_FREQUENCY: i32 = 99;
+
+/// Functions to exclude from CPU profile stack traces, as unwinding through them can deadlock
+const PROFILE_BLOCKLIST: &[&str] = &["libc", "libgcc", "pthread", "vdso"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProfileFormat {
+    Flamegraph,
+    Pprof,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ProfileRequest {
+    duration: Duration,
+    frequency: i32,
+    format: ProfileFormat,
+}
+
+impl ProfileRequest {
+    fn from_query(query: Option<&str>) -> ReadySetResult<Self> {
+        let mut res = Self {
+            duration: DEFAULT_PROFILE_DURATION,
+            frequency: DEFAULT_PROFILE_FREQUENCY,
+            format: ProfileFormat::Flamegraph,
+        };
+        for (key, value) in querystring::querify(query.unwrap_or_default()) {
+            let invalid = || ReadySetError::BadRequest(format!("Invalid value for {key}: {value}"));
+            match key {
+                "seconds" => {
+                    res.duration = Duration::from_secs(value.parse().map_err(|_| invalid())?)
+                        .min(MAX_PROFILE_DURATION)
+                }
+                "frequency" => {
+                    res.frequency = value.parse().map_err(|_| invalid())?;
+                    if res.frequency <= 0 {
+                        return Err(invalid());
+                    }
+                }
+                "format" => {
+                    res.format = match value {
+                        "flamegraph" => ProfileFormat::Flamegraph,
+                        "pprof" => ProfileFormat::Pprof,
+                        _ => return Err(invalid()),
+                    }
+                }
+                _ => {
+                    return Err(ReadySetError::BadRequest(format!(
+                        "Unknown profile option: {key}"
+                    )))
+                }
+            }
+        }
+        Ok(res)
+    }
+}
+
+/// Sample the CPU usage of the process as described by `req`, returning the content type and body
+/// of the resulting profile.
+///
+/// This blocks the current thread for the duration of the profile.
+fn cpu_profile(req: ProfileRequest) -> ReadySetResult<(&'static str, Vec<u8>)> {
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(req.frequency)
+        .blocklist(PROFILE_BLOCKLIST)
+        .build()
+        .map_err(|e| internal_err!("Could not start CPU profiler: {e}"))?;
+    info!(duration = ?req.duration, frequency = req.frequency, "Capturing CPU profile");
+    std::thread::sleep(req.duration);
+
+    let report = guard
+        .report()
+        .build()
+        .map_err(|e| internal_err!("Could not build CPU profile: {e}"))?;
+    drop(guard);
+
+    let mut body = Vec::new();
+    match req.format {
+        ProfileFormat::Flamegraph => {
+            report
+                .flamegraph(&mut body)
+                .map_err(|e| internal_err!("Could not render flamegraph: {e}"))?;
+            Ok(("image/svg+xml", body))
+        }
+        ProfileFormat::Pprof => {
+            use pprof::protos::Message;
+            report
+                .pprof()
+                .map_err(|e| internal_err!("Could not build pprof profile: {e}"))?
+                .encode(&mut body)
+                .map_err(|e| internal_err!("Could not encode pprof profile: {e}"))?;
+            Ok(("application/octet-stream", body))
+        }
+    }
+}
+
+/// Dump the backtraces of all tasks in the current tokio runtime
+#[cfg(all(tokio_unstable, tokio_taskdump))]
+async fn task_dump() -> Option<String> {
+    use std::fmt::Write;
+
+    let dump = tokio::runtime::Handle::current().dump().await;
+    let mut res = String::new();
+    for (i, task) in dump.tasks().iter().enumerate() {
+        #[allow(clippy::unwrap_used)] // writing to a String can't fail
+        writeln!(res, "task {i}:\n{}\n", task.trace()).unwrap();
+    }
+    Some(res)
+}
+
+#[cfg(not(all(tokio_unstable, tokio_taskdump)))]
+async fn task_dump() -> Option<String> {
+    None
+}
+
+fn text_response(status: StatusCode, body: String) -> Response<Body> {
+    #[allow(clippy::unwrap_used)] // hardcoded header
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "text/plain")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+/// Serve a request to one of the profiling endpoints, with the given path (which should start with
+/// [`PPROF_PATH_PREFIX`]) and query string
+pub async fn serve(path: &str, query: Option<&str>) -> Response<Body> {
+    match path.strip_prefix(PPROF_PATH_PREFIX) {
+        Some("profile") => {
+            let req = match ProfileRequest::from_query(query) {
+                Ok(req) => req,
+                Err(e) => return text_response(StatusCode::BAD_REQUEST, e.to_string()),
+            };
+            let res = tokio::task::spawn_blocking(move || cpu_profile(req))
+                .await
+                .unwrap_or_else(|e| Err(internal_err!("CPU profiler panicked: {e}")));
+            match res {
+                #[allow(clippy::unwrap_used)] // hardcoded header
+                Ok((content_type, body)) => Response::builder()
+                    .header(CONTENT_TYPE, content_type)
+                    .body(Body::from(body))
+                    .unwrap(),
+                Err(e) => text_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+            }
+        }
+        Some("tasks") => match task_dump().await {
+            Some(dump) => text_response(StatusCode::OK, dump),
+            None => text_response(
+                StatusCode::NOT_IMPLEMENTED,
+                "Task dumps require building with RUSTFLAGS=\"--cfg tokio_unstable --cfg \
+                 tokio_taskdump\""
+                    .to_owned(),
+            ),
+        },
+        _ => text_response(
+            StatusCode::NOT_FOUND,
+            "Unknown profiling endpoint".to_owned(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_profile_request() {
+        assert_eq!(
+            ProfileRequest::from_query(None).unwrap(),
+            ProfileRequest {
+                duration: DEFAULT_PROFILE_DURATION,
+                frequency: DEFAULT_PROFILE_FREQUENCY,
+                format: ProfileFormat::Flamegraph,
+            }
+        );
+        assert_eq!(
+            ProfileRequest::from_query(Some("seconds=5&frequency=1000&format=pprof")).unwrap(),
+            ProfileRequest {
+                duration: Duration::from_secs(5),
+                frequency: 1000,
+                format: ProfileFormat::Pprof,
+            }
+        );
+        assert_eq!(
+            ProfileRequest::from_query(Some("seconds=100000"))
+                .unwrap()
+                .duration,
+            MAX_PROFILE_DURATION
+        );
+        assert!(ProfileRequest::from_query(Some("format=svg")).is_err());
+        assert!(ProfileRequest::from_query(Some("frequency=0")).is_err());
+    }
+}
@@ -51,7 +51,7 @@ use std::net::{IpAddr, SocketAddr};
 use std::sync::{Arc, Mutex};
 use std::{process, time};
 
-use dataflow::Readers;
+use dataflow::{DurabilityMode, Readers};
 use failpoint_macros::set_failpoint;
 use futures_util::future::{Either, TryFutureExt};
 use health_reporter::{HealthReporter, State as ServerState};
@@ -67,7 +67,9 @@ use url::Url;
 
 use crate::controller::{Controller, ControllerRequest, HandleRequest};
 use crate::handle::Handle;
-use crate::http_router::NoriaServerHttpRouter;
+use crate::http_router::{HttpAuthConfig, NoriaServerHttpRouter};
+use crate::worker::reader_checkpoint::ReaderCheckpointConfig;
+use crate::worker::watchdog::WatchdogConfig;
 use crate::worker::{MemoryTracker, Worker, WorkerRequest};
 use crate::Config;
 
@@ -115,14 +117,28 @@ async fn start_worker(
     readers: Readers,
     memory_limit: Option<usize>,
     memory_check_frequency: Option<time::Duration>,
+    reader_checkpoint_config: Option<ReaderCheckpointConfig>,
+    domain_watchdog: Option<WatchdogConfig>,
     shutdown_rx: ShutdownReceiver,
 ) -> Result<(), anyhow::Error> {
     set_failpoint!("start-worker");
+    if let Some(reader_checkpoint_config) = reader_checkpoint_config {
+        tokio::spawn(maybe_abort_on_panic!(
+            abort_on_task_failure,
+            crate::worker::reader_checkpoint::run(
+                reader_checkpoint_config,
+                readers.clone(),
+                shutdown_rx.clone(),
+            )
+        ));
+    }
+
     let worker = Worker {
         election_state: None,
         // this initial duration doesn't matter; it gets set upon worker registration
         evict_interval: memory_check_frequency.map(|f| tokio::time::interval(f)),
         memory_limit,
+        domain_memory_limits: Default::default(),
         rx: worker_rx,
         coord: Arc::new(Default::default()),
         domain_bind: listen_addr,
@@ -134,6 +150,8 @@ async fn start_worker(
         is_evicting: Default::default(),
         domain_wait_queue: Default::default(),
         shutdown_rx,
+        watchdog: domain_watchdog
+            .map(|config| (config, tokio::time::interval(config.check_interval()))),
     };
 
     tokio::spawn(maybe_abort_on_panic!(abort_on_task_failure, worker.run()));
@@ -201,6 +219,8 @@ async fn start_request_router(
     abort_on_task_failure: bool,
     health_reporter: HealthReporter,
     failpoint_channel: Option<Arc<Sender<()>>>,
+    enable_pprof: bool,
+    http_auth: HttpAuthConfig,
     shutdown_rx: ShutdownReceiver,
 ) -> Result<Url, anyhow::Error> {
     let http_server = NoriaServerHttpRouter {
@@ -211,13 +231,20 @@ async fn start_request_router(
         authority: authority.clone(),
         health_reporter: health_reporter.clone(),
         failpoint_channel,
+        enable_pprof,
+        http_auth,
     };
 
     let http_listener = http_server.create_listener().await?;
     let real_external_addr =
         SocketAddr::new(external_addr.ip(), http_listener.local_addr()?.port());
+    let scheme = if readyset_client::tls::internal().is_some() {
+        "https"
+    } else {
+        "http"
+    };
     // FIXME(eta): this won't work for IPv6
-    let http_uri = Url::parse(&format!("http://{}", real_external_addr))?;
+    let http_uri = Url::parse(&format!("{}://{}", scheme, real_external_addr))?;
     tokio::spawn(maybe_abort_on_panic!(
         abort_on_task_failure,
         NoriaServerHttpRouter::route_requests(http_server, http_listener, shutdown_rx)
@@ -262,6 +289,7 @@ pub(crate) async fn start_instance_inner(
     config: Config,
     memory_limit: Option<usize>,
     memory_check_frequency: Option<time::Duration>,
+    reader_checkpoint_interval: Option<time::Duration>,
     domain_scheduling_config: WorkerSchedulingConfig,
     leader_eligible: bool,
     readers: Readers,
@@ -276,9 +304,26 @@ pub(crate) async fn start_instance_inner(
 
     let Config {
         abort_on_task_failure,
+        ref persistence,
+        enable_pprof,
+        domain_watchdog,
+        ref http_auth,
         ..
     } = config;
 
+    // Checkpointing reader keys is only useful if the base tables we'd be replaying them from are
+    // durable
+    let reader_checkpoint_config = reader_checkpoint_interval
+        .filter(|_| persistence.mode == DurabilityMode::Permanent)
+        .map(|interval| ReaderCheckpointConfig {
+            path: persistence
+                .db_dir
+                .clone()
+                .unwrap_or_else(|| ".".into())
+                .join(format!("{}-readers.ckpt", persistence.db_filename_prefix)),
+            interval,
+        });
+
     let (tx, rx) = maybe_create_failpoint_chann(wait_for_failpoint);
     let mut health_reporter = HealthReporter::new();
     let http_uri = start_request_router(
@@ -290,9 +335,12 @@ pub(crate) async fn start_instance_inner(
         abort_on_task_failure,
         health_reporter.clone(),
         tx,
+        enable_pprof,
+        http_auth.clone(),
         shutdown_rx.clone(),
     )
     .await?;
+    let http_auth_token = http_auth.admin_token.clone();
 
     // If we previously setup a failpoint channel because wait_for_failpoint was enabled,
     // then we should wait to hear from the http router that a failpoint request was
@@ -307,6 +355,8 @@ pub(crate) async fn start_instance_inner(
         readers,
         memory_limit,
         memory_check_frequency,
+        reader_checkpoint_config,
+        domain_watchdog,
         shutdown_rx.clone(),
     )
     .await?;
@@ -336,7 +386,12 @@ pub(crate) async fn start_instance_inner(
 
     health_reporter.set_state(ServerState::Healthy);
 
-    Ok(Handle::new(authority, handle_tx, our_descriptor))
+    Ok(Handle::new(
+        authority,
+        handle_tx,
+        our_descriptor,
+        http_auth_token,
+    ))
 }
 
 /// Start up a new instance and return a handle to it. Dropping the handle will stop the
@@ -348,6 +403,7 @@ pub(super) async fn start_instance(
     config: Config,
     memory_limit: Option<usize>,
     memory_check_frequency: Option<time::Duration>,
+    reader_checkpoint_interval: Option<time::Duration>,
     domain_scheduling_config: WorkerSchedulingConfig,
     leader_eligible: bool,
     telemetry_sender: TelemetrySender,
@@ -379,6 +435,7 @@ pub(super) async fn start_instance(
         config,
         memory_limit,
         memory_check_frequency,
+        reader_checkpoint_interval,
         domain_scheduling_config,
         leader_eligible,
         readers,
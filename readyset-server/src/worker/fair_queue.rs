@@ -0,0 +1,157 @@
+//! Fair scheduling of upqueries between tenants sharing a domain.
+//!
+//! Every dataflow node belongs to a tenant - the schema of the base tables it was derived from -
+//! and every upquery a domain receives (a replay request from either a reader or a downstream
+//! domain) is made on behalf of the tenant of the node that requested it. Without any scheduling,
+//! a domain handles upqueries in the order it receives them, so a burst of upqueries for one
+//! tenant (for example after a large eviction) delays every other tenant's reads behind it.
+//!
+//! To prevent that, each domain replica queues the upqueries it receives in a [`FairQueue`], which
+//! hands them back out using self-clocked weighted fair queuing: every queued upquery is tagged
+//! with a virtual finish time, which advances more slowly for tenants with a higher weight
+//! (configured with `--tenant-weight`), and upqueries are handled in order of their finish time.
+//! Each tenant with upqueries waiting is thus given a share of the domain's time proportional to
+//! its weight, regardless of how many upqueries the other tenants have queued.
+
+use std::collections::{HashMap, VecDeque};
+
+use nom_sql::SqlIdentifier;
+
+/// The virtual time taken to handle one upquery for a tenant with a weight of 1
+const UPQUERY_COST: u64 = 1 << 16;
+
+/// The queued upqueries for a single tenant
+struct TenantQueue<T> {
+    /// The queued upqueries, along with their virtual finish time
+    items: VecDeque<(u64, T)>,
+    /// The virtual finish time of the most recently queued upquery
+    last_finish: u64,
+}
+
+/// A queue of upqueries which returns them in weighted fair order between tenants. See the
+/// [module documentation](self) for more information.
+pub(super) struct FairQueue<T> {
+    tenants: HashMap<Option<SqlIdentifier>, TenantQueue<T>>,
+    /// The virtual finish time of the most recently dequeued upquery
+    virtual_time: u64,
+    len: usize,
+}
+
+impl<T> Default for FairQueue<T> {
+    fn default() -> Self {
+        Self {
+            tenants: HashMap::new(),
+            virtual_time: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<T> FairQueue<T> {
+    /// Returns the total number of upqueries queued for all tenants
+    pub(super) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Queue an upquery on behalf of the given tenant, which has the given weight
+    pub(super) fn push(&mut self, tenant: Option<SqlIdentifier>, weight: u32, item: T) {
+        let virtual_time = self.virtual_time;
+        let queue = self.tenants.entry(tenant).or_insert_with(|| TenantQueue {
+            items: VecDeque::new(),
+            last_finish: virtual_time,
+        });
+        let finish = queue.last_finish.max(virtual_time) + UPQUERY_COST / u64::from(weight.max(1));
+        queue.last_finish = finish;
+        queue.items.push_back((finish, item));
+        self.len += 1;
+    }
+
+    /// Dequeue the upquery with the earliest virtual finish time, if any
+    pub(super) fn pop(&mut self) -> Option<T> {
+        let (tenant, queue) = self
+            .tenants
+            .iter_mut()
+            .filter_map(|(tenant, queue)| Some((queue.items.front()?.0, tenant, queue)))
+            .min_by_key(|(finish, _, _)| *finish)
+            .map(|(_, tenant, queue)| (tenant.clone(), queue))?;
+        let (finish, item) = queue.items.pop_front()?;
+        if queue.items.is_empty() {
+            self.tenants.remove(&tenant);
+        }
+
+        self.virtual_time = finish;
+        self.len -= 1;
+        Some(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(queue: &mut FairQueue<(&'static str, usize)>) -> Vec<(&'static str, usize)> {
+        std::iter::from_fn(|| queue.pop()).collect()
+    }
+
+    #[test]
+    fn tenant_order_is_preserved() {
+        let mut queue = FairQueue::default();
+        for i in 0..3 {
+            queue.push(Some("a".into()), 1, ("a", i));
+        }
+        assert_eq!(queue.len(), 3);
+        assert_eq!(drain(&mut queue), vec![("a", 0), ("a", 1), ("a", 2)]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn storm_does_not_starve_other_tenants() {
+        let mut queue = FairQueue::default();
+        for i in 0..100 {
+            queue.push(Some("noisy".into()), 1, ("noisy", i));
+        }
+        queue.push(Some("quiet".into()), 1, ("quiet", 0));
+        queue.push(None, 1, ("default", 0));
+
+        let order = drain(&mut queue);
+        let position = |tenant| order.iter().position(|(t, _)| *t == tenant).unwrap();
+        assert!(position("quiet") <= 2);
+        assert!(position("default") <= 2);
+    }
+
+    #[test]
+    fn weights_are_respected() {
+        let mut queue = FairQueue::default();
+        for i in 0..30 {
+            queue.push(Some("heavy".into()), 3, ("heavy", i));
+            queue.push(Some("light".into()), 1, ("light", i));
+        }
+
+        let first = drain(&mut queue).into_iter().take(20).collect::<Vec<_>>();
+        let heavy = first.iter().filter(|(t, _)| *t == "heavy").count();
+        assert_eq!(heavy, 15);
+    }
+
+    #[test]
+    fn idle_tenants_do_not_accumulate_credit() {
+        let mut queue = FairQueue::default();
+        for i in 0..10 {
+            queue.push(Some("a".into()), 1, ("a", i));
+        }
+        for _ in 0..5 {
+            queue.pop();
+        }
+
+        // "b" was idle while "a" was being served, so it starts at the current virtual time rather
+        // than getting all of its upqueries handled before any more of "a"'s
+        for i in 0..10 {
+            queue.push(Some("b".into()), 1, ("b", i));
+        }
+        let next = drain(&mut queue).into_iter().take(4).collect::<Vec<_>>();
+        assert_eq!(next.iter().filter(|(t, _)| *t == "b").count(), 2);
+    }
+}
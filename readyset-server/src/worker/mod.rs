@@ -13,11 +13,11 @@ use enum_kinds::EnumKind;
 use futures::stream::FuturesUnordered;
 use futures::FutureExt;
 use futures_util::future::TryFutureExt;
-use futures_util::sink::SinkExt;
+use futures_util::sink::{Sink, SinkExt};
 use futures_util::stream::StreamExt;
 use metrics::{counter, gauge, histogram};
 use readyset_client::channel;
-use readyset_client::internal::ReplicaAddress;
+use readyset_client::internal::{DomainIndex, ReplicaAddress};
 use readyset_client::metrics::recorded;
 use readyset_errors::{internal_err, ReadySetError, ReadySetResult};
 use readyset_util::select;
@@ -29,20 +29,29 @@ use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::{oneshot, Mutex};
 use tokio::task::JoinError;
 use tokio::time::Interval;
-use tracing::{debug, error, info, info_span, trace, warn};
+use tracing::{debug, error, info, info_span, trace, warn, Instrument};
 use url::Url;
 
 use self::replica::Replica;
+use self::watchdog::{DomainActivity, WatchdogConfig};
 use crate::coordination::{DomainDescriptor, RunDomainResponse};
 use crate::worker::replica::WrappedDomainRequest;
 
+mod fair_queue;
+pub(crate) mod reader_checkpoint;
 /// Request handlers and utilities for reading from the ReadHandle of a
 /// left-right map associated with a reader node.
 pub mod readers;
 mod replica;
+/// Detection of stalled domains
+pub mod watchdog;
 
 type ChannelCoordinator = channel::ChannelCoordinator<ReplicaAddress, Box<Packet>>;
 
+/// How often to check the state sizes of domains against their memory budgets, if a budget has
+/// been set for a domain on a worker which isn't otherwise checking its memory usage
+const DEFAULT_DOMAIN_MEMORY_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Some kind of request for a running ReadySet worker.
 ///
 /// Most of these requests return `()`, apart from `DomainRequest`.
@@ -61,6 +70,11 @@ pub enum WorkerRequestKind {
     /// Clear domains.
     ClearDomains,
 
+    /// Stop running the given domain replica on this worker, dropping all of its state.
+    ///
+    /// Sent by the controller when a domain is moved to a different worker.
+    RemoveDomain(ReplicaAddress),
+
     /// A set of domains has been started elsewhere in the distributed system.
     ///
     /// The message contains information on how the domain can be reached, in order that
@@ -88,6 +102,15 @@ pub enum WorkerRequestKind {
         /// The limit in bytes
         limit: Option<usize>,
     },
+
+    /// Set (or clear, if `limit` is `None`) the memory budget for each replica of each shard of a
+    /// domain running on this worker
+    SetDomainMemoryLimit {
+        /// The domain to set the budget for
+        domain: DomainIndex,
+        /// The budget, in bytes of reported state size
+        limit: Option<usize>,
+    },
 }
 
 /// A request to a running ReadySet worker, containing a request kind and a completion channel.
@@ -115,6 +138,8 @@ pub struct DomainHandle {
     /// Can be used to send an abort signal to the domain
     /// aborts automatically when dropped
     _domain_abort: oneshot::Sender<()>,
+    /// Record of what the domain is currently doing, for stall detection
+    activity: Arc<DomainActivity>,
 }
 
 /// Long-lived struct for tracking the currently allocated heap memory used by the current process
@@ -186,6 +211,12 @@ pub struct Worker {
     pub(crate) evict_interval: Option<Interval>,
     /// A memory limit for state, in bytes.
     pub(crate) memory_limit: Option<usize>,
+    /// Memory budgets for the state of individual domains, in bytes of reported state size.
+    ///
+    /// Each replica of each shard of a domain is evicted from whenever its state grows past its
+    /// domain's budget, and domains with budgets are only evicted from to bring the worker under
+    /// its overall `memory_limit` if no domains without budgets are available to evict from.
+    pub(crate) domain_memory_limits: Arc<HashMap<DomainIndex, usize>>,
     /// Channel through which worker requests are received.
     pub(crate) rx: Receiver<WorkerRequest>,
     /// Channel coordinator (used by domains to figure out where other domains are).
@@ -207,12 +238,16 @@ pub struct Worker {
     pub(crate) is_evicting: Arc<AtomicBool>,
     pub(crate) domain_wait_queue: FuturesUnordered<FinishedDomainFuture>,
     pub(crate) shutdown_rx: ShutdownReceiver,
+    /// Configuration for detecting stalled domains, along with a timer for checking them, if
+    /// enabled
+    pub(crate) watchdog: Option<(WatchdogConfig, Interval)>,
 }
 
 impl Worker {
     fn process_eviction(&mut self) {
         tokio::spawn(do_eviction(
             self.memory_limit,
+            Arc::clone(&self.domain_memory_limits),
             self.coord.clone(),
             self.memory,
             Arc::clone(&self.state_sizes),
@@ -249,6 +284,16 @@ impl Worker {
 
                 Ok(None)
             }
+            WorkerRequestKind::RemoveDomain(replica_address) => {
+                info!(%replica_address, "controller requested that this worker removes a domain");
+                self.coord.remove(&replica_address);
+                self.state_sizes.lock().await.remove(&replica_address);
+                // Dropping the handle aborts the domain's runtime
+                if self.domains.remove(&replica_address).is_none() {
+                    warn!(%replica_address, "asked to remove domain that is not running");
+                }
+                Ok(None)
+            }
             WorkerRequestKind::RunDomain(builder) => {
                 let replica_addr = builder.address();
                 let span = info_span!("domain", address = %replica_addr);
@@ -302,6 +347,7 @@ impl Worker {
                     .await
                     .insert(replica_addr, state_size);
 
+                let activity = Arc::new(DomainActivity::default());
                 let replica = Replica::new(
                     domain,
                     listener,
@@ -309,6 +355,7 @@ impl Worker {
                     req_rx,
                     init_state_rx,
                     self.coord.clone(),
+                    Arc::clone(&activity),
                 );
                 // Each domain is single threaded in nature, so we spawn each one in a separate
                 // thread, so we can avoid running blocking operations on the multi
@@ -344,6 +391,7 @@ impl Worker {
                     DomainHandle {
                         req_tx,
                         _domain_abort,
+                        activity,
                     },
                 );
 
@@ -389,10 +437,44 @@ impl Worker {
             }
             WorkerRequestKind::Ping => Ok(None),
             WorkerRequestKind::SetMemoryLimit { period, limit } => {
+                // Keep checking domains' state sizes against their budgets, if any are set
+                let period = period.or_else(|| {
+                    (!self.domain_memory_limits.is_empty())
+                        .then_some(DEFAULT_DOMAIN_MEMORY_CHECK_INTERVAL)
+                });
                 self.evict_interval = period.map(tokio::time::interval);
                 self.memory_limit = limit;
                 Ok(None)
             }
+            WorkerRequestKind::SetDomainMemoryLimit { domain, limit } => {
+                info!(%domain, ?limit, "setting domain memory limit");
+                let limits = Arc::make_mut(&mut self.domain_memory_limits);
+                match limit {
+                    Some(limit) => {
+                        limits.insert(domain, limit);
+                    }
+                    None => {
+                        limits.remove(&domain);
+                    }
+                }
+                if self.evict_interval.is_none() && !limits.is_empty() {
+                    self.evict_interval =
+                        Some(tokio::time::interval(DEFAULT_DOMAIN_MEMORY_CHECK_INTERVAL));
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    fn check_domains(&self) {
+        if let Some((config, _)) = &self.watchdog {
+            watchdog::check_domains(
+                config,
+                self.domains
+                    .iter()
+                    .map(|(addr, handle)| (addr, &*handle.activity)),
+                std::time::Instant::now(),
+            );
         }
     }
 
@@ -409,6 +491,14 @@ impl Worker {
                     futures_util::future::pending().await
                 }
             };
+            let wd = &mut self.watchdog;
+            let watchdog = async {
+                if let Some((_, ref mut interval)) = wd {
+                    interval.tick().await
+                } else {
+                    futures_util::future::pending().await
+                }
+            };
 
             select! {
                 req = self.rx.recv() => {
@@ -427,6 +517,9 @@ impl Worker {
                 _ = eviction => {
                     self.process_eviction();
                 }
+                _ = watchdog => {
+                    self.check_domains();
+                }
                 Some(res) = self.domain_wait_queue.next() => {
                     handle_domain_future_completion(res);
                 }
@@ -435,8 +528,56 @@ impl Worker {
     }
 }
 
+type DomainSender = Box<dyn Sink<Box<Packet>, Error = bincode::Error> + Send + Unpin>;
+
+/// Ask the given domain replica to evict `num_bytes` bytes of state, reusing (or creating) a
+/// connection to it in `domain_senders`
+async fn request_eviction(
+    coord: &ChannelCoordinator,
+    domain_senders: &mut HashMap<ReplicaAddress, DomainSender>,
+    target: ReplicaAddress,
+    num_bytes: usize,
+) -> ReadySetResult<()> {
+    counter!(
+        recorded::EVICTION_WORKER_EVICTIONS_REQUESTED,
+        1,
+        "domain" => target.domain_index.index().to_string(),
+    );
+
+    let tx = match domain_senders.entry(target) {
+        Occupied(entry) => entry.into_mut(),
+        Vacant(entry) => entry.insert(tokio::task::block_in_place(|| {
+            coord.builder_for(&target)?.build_async().map_err(|e| {
+                internal_err!(
+                    "an error occurred while trying to create a domain connection: '{}'",
+                    e
+                )
+            })
+        })?),
+    };
+    let r = tx
+        .send(Box::new(Packet::Evict(EvictRequest::Bytes {
+            node: None,
+            num_bytes,
+        })))
+        .await;
+
+    if let Err(e) = r {
+        // probably exiting?
+        warn!(
+            "failed to evict from {}: {}",
+            target.domain_index.index(),
+            e
+        );
+        // remove sender so we don't try to use it again
+        domain_senders.remove(&target);
+    }
+    Ok(())
+}
+
 /// Calculate the total memory used by the process (by querying [`jemalloc_ctl`]), then perform an
-/// eviction if that's over the configured `memory_limit`.
+/// eviction if that's over the configured `memory_limit`. Also evict from any domain replicas
+/// whose reported state size is over their domain's budget in `domain_memory_limits`.
 ///
 /// There is a *significant* proportional discrepancy - about 8x - between the memory size reported
 /// by individual node states and the actual number of bytes allocated by the application - rather
@@ -444,10 +585,13 @@ impl Worker {
 /// the global allocator directly for the amount of memory we use and use that to decide *when* to
 /// evict, but use the state sizes of individual nodes to decide *where* to evict. This is
 /// imperfect, and should likely be improved in the future, but is a good way to avoid running fully
-/// out of memory and getting OOM-killed before we ever realise it's time to evict.
+/// out of memory and getting OOM-killed before we ever realise it's time to evict. Per-domain
+/// budgets, on the other hand, are set explicitly by the user, so they're compared directly against
+/// the reported state sizes.
 #[allow(clippy::type_complexity)]
 async fn do_eviction(
     memory_limit: Option<usize>,
+    domain_memory_limits: Arc<HashMap<DomainIndex, usize>>,
     coord: Arc<ChannelCoordinator>,
     memory_tracker: MemoryTracker,
     state_sizes: Arc<Mutex<HashMap<ReplicaAddress, Arc<AtomicUsize>>>>,
@@ -468,127 +612,123 @@ async fn do_eviction(
     let used: usize = memory_tracker.allocated_bytes()?;
     gauge!(recorded::EVICTION_WORKER_HEAP_ALLOCATED_BYTES, used as f64);
     // Are we over the limit?
-    match memory_limit {
-        None => Ok(()),
-        Some(limit) => {
-            if used >= limit {
-                // we are! time to evict.
-                // add current state sizes (could be out of date, as packet sent below is not
-                // necessarily received immediately)
-                let (mut sizes, total_reported) = {
-                    let state_sizes = state_sizes.lock().await;
-                    let mut total_reported = 0;
-                    let sizes = state_sizes
-                        .iter()
-                        .map(|(replica_addr, size_atom)| {
-                            let size = size_atom.load(Ordering::Acquire);
-                            span.in_scope(|| {
-                                trace!("domain {} state size is {} bytes", replica_addr, size)
-                            });
-                            total_reported += size;
-                            (*replica_addr, size)
-                        })
-                        .collect::<Vec<_>>();
-                    (sizes, total_reported)
-                };
+    let over_limit = memory_limit.filter(|&limit| used >= limit);
+    if over_limit.is_none() && domain_memory_limits.is_empty() {
+        return Ok(());
+    }
 
-                // state sizes are under actual memory usage, but roughly proportional to actual
-                // memory usage - let's figure out proportionally how much *reported* memory we
-                // should evict
-                let actual_over = used - limit;
-                let mut proportional_over =
-                    ((total_reported as f64 / used as f64) * actual_over as f64).round() as usize;
-
-                // here's how we're going to proceed.
-                // we don't want to _empty_ any views if we can avoid it.
-                // and we also need to be aware that evicting something from one place may cause a
-                // number of downstream evictions.
-
-                // we want to spread the eviction impact across multiple nodes where possible,
-                // so we distribute how much we're over the limit across the 3 largest nodes.
-                // -1* so we sort in descending order
-                // TODO: be smarter than 3 here
-                sizes.sort_unstable_by_key(|&(_, s)| -(s as i64));
-                sizes.truncate(3);
-
-                // don't evict from tiny things (< 10% of max)
-                if let Some(too_small_i) = sizes.iter().position(|&(_, s)| s < sizes[0].1 / 10) {
-                    // everything beyond this is smaller, so also too small
-                    sizes.truncate(too_small_i);
-                }
+    // add current state sizes (could be out of date, as packet sent below is not
+    // necessarily received immediately)
+    let (sizes, total_reported) = {
+        let state_sizes = state_sizes.lock().await;
+        let mut total_reported = 0;
+        let sizes = state_sizes
+            .iter()
+            .map(|(replica_addr, size_atom)| {
+                let size = size_atom.load(Ordering::Acquire);
+                span.in_scope(|| trace!("domain {} state size is {} bytes", replica_addr, size));
+                total_reported += size;
+                (*replica_addr, size)
+            })
+            .collect::<Vec<_>>();
+        (sizes, total_reported)
+    };
+
+    let mut domain_senders = HashMap::new();
+
+    // First, bring every domain replica with a budget back under that budget
+    for &(target, size) in &sizes {
+        let budget = match domain_memory_limits.get(&target.domain_index) {
+            Some(&budget) if size > budget => budget,
+            _ => continue,
+        };
+        span.in_scope(|| {
+            debug!(
+                %target,
+                size,
+                budget,
+                "state size of domain replica exceeds its budget; evicting"
+            )
+        });
+        request_eviction(&coord, &mut domain_senders, target, size - budget)
+            .instrument(span.clone())
+            .await?;
+    }
 
-                // starting with the smallest of the n domains
-                let mut n = sizes.len();
-                let mut domain_senders = HashMap::new();
-                for &(target, size) in sizes.iter().rev() {
-                    // TODO: should this be evenly divided, or weighted by the size of the domains?
-                    let share = (proportional_over + n - 1) / n;
-                    // we're only willing to evict at most half the state in each domain
-                    // unless this is the only domain left to evict from
-                    let evict = if n > 1 {
-                        cmp::min(size / 2, share)
-                    } else {
-                        assert_eq!(share, proportional_over);
-                        share
-                    };
-                    proportional_over -= evict;
-                    n -= 1;
-
-                    span.in_scope(|| {
-                        debug!(
-                            "memory footprint ({} bytes) exceeds limit ({} bytes); evicting from largest domain {}",
-                            used,
-                            limit,
-                            target.domain_index,
-                        )
-                    });
+    if let Some(limit) = over_limit {
+        // we are! time to evict.
+        // state sizes are under actual memory usage, but roughly proportional to actual
+        // memory usage - let's figure out proportionally how much *reported* memory we
+        // should evict
+        let actual_over = used - limit;
+        let mut proportional_over =
+            ((total_reported as f64 / used as f64) * actual_over as f64).round() as usize;
+
+        // domains with budgets are kept under them above, so (so that one query can't cause
+        // everything else to be evicted) only evict from them if there's nothing else to evict
+        // from
+        let (budgeted, mut sizes): (Vec<_>, Vec<_>) = sizes
+            .into_iter()
+            .partition(|(target, _)| domain_memory_limits.contains_key(&target.domain_index));
+        if sizes.iter().all(|&(_, s)| s == 0) {
+            sizes = budgeted;
+        }
 
-                    counter!(
-                        recorded::EVICTION_WORKER_EVICTIONS_REQUESTED,
-                        1,
-                        "domain" => target.domain_index.index().to_string(),
-                    );
+        // here's how we're going to proceed.
+        // we don't want to _empty_ any views if we can avoid it.
+        // and we also need to be aware that evicting something from one place may cause a
+        // number of downstream evictions.
+
+        // we want to spread the eviction impact across multiple nodes where possible,
+        // so we distribute how much we're over the limit across the 3 largest nodes.
+        // -1* so we sort in descending order
+        // TODO: be smarter than 3 here
+        sizes.sort_unstable_by_key(|&(_, s)| -(s as i64));
+        sizes.truncate(3);
+
+        // don't evict from tiny things (< 10% of max)
+        if let Some(too_small_i) = sizes.iter().position(|&(_, s)| s < sizes[0].1 / 10) {
+            // everything beyond this is smaller, so also too small
+            sizes.truncate(too_small_i);
+        }
 
-                    let tx = match domain_senders.entry(target) {
-                        Occupied(entry) => entry.into_mut(),
-                        Vacant(entry) => entry.insert(tokio::task::block_in_place(|| {
-                            coord.builder_for(&target)?.build_async().map_err(|e| {
-                                internal_err!(
-                                    "an error occurred while trying to create a domain connection: '{}'",
-                                    e
-                                )
-                            })
-                        })?),
-                    };
-                    let r = tx
-                        .send(Box::new(Packet::Evict(EvictRequest::Bytes {
-                            node: None,
-                            num_bytes: evict,
-                        })))
-                        .await;
-
-                    if let Err(e) = r {
-                        // probably exiting?
-                        span.in_scope(|| {
-                            warn!(
-                                "failed to evict from {}: {}",
-                                target.domain_index.index(),
-                                e
-                            )
-                        });
-                        // remove sender so we don't try to use it again
-                        domain_senders.remove(&target);
-                    }
-                }
-            }
-            histogram!(
-                recorded::EVICTION_WORKER_EVICTION_TIME,
-                start.elapsed().as_micros() as f64,
-            );
+        // starting with the smallest of the n domains
+        let mut n = sizes.len();
+        for &(target, size) in sizes.iter().rev() {
+            // TODO: should this be evenly divided, or weighted by the size of the domains?
+            let share = (proportional_over + n - 1) / n;
+            // we're only willing to evict at most half the state in each domain
+            // unless this is the only domain left to evict from
+            let evict = if n > 1 {
+                cmp::min(size / 2, share)
+            } else {
+                assert_eq!(share, proportional_over);
+                share
+            };
+            proportional_over -= evict;
+            n -= 1;
+
+            span.in_scope(|| {
+                debug!(
+                    "memory footprint ({} bytes) exceeds limit ({} bytes); evicting from largest domain {}",
+                    used,
+                    limit,
+                    target.domain_index,
+                )
+            });
 
-            Ok(())
+            request_eviction(&coord, &mut domain_senders, target, evict)
+                .instrument(span.clone())
+                .await?;
         }
     }
+
+    histogram!(
+        recorded::EVICTION_WORKER_EVICTION_TIME,
+        start.elapsed().as_micros() as f64,
+    );
+
+    Ok(())
 }
 
 impl Drop for Worker {
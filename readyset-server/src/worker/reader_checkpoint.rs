@@ -0,0 +1,255 @@
+//! Periodic checkpointing of the contents of partially materialized readers to local disk, to allow
+//! warm restarts of a worker.
+//!
+//! Rather than writing out the rows in each reader, we only write out the set of keys that are
+//! currently filled in each partially materialized reader. When the worker restarts and the reader
+//! is re-created, we trigger replays for all of those keys, which re-populates the reader from the
+//! (durable) base tables. This means that a restored reader can never contain results that are
+//! stale with respect to the base tables it's derived from, at the cost of having to perform
+//! upqueries for the restored keys - which is still dramatically faster than waiting for every key
+//! to be requested by clients again.
+//!
+//! Restoring is best-effort: checkpointed keys for readers that don't exist (or have changed) after
+//! the restart are kept around in case the reader shows up later, but are otherwise ignored. Keys
+//! for readers which still haven't been re-created after [`MAX_PENDING_CHECKPOINTS`] checkpoints,
+//! such as the readers of caches which were dropped, are discarded so that they aren't carried
+//! forward into every checkpoint.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use dataflow::Readers;
+use readyset_client::{KeyComparison, ReaderAddress};
+use readyset_data::DfValue;
+use readyset_errors::{internal_err, ReadySetResult};
+use readyset_util::select;
+use readyset_util::shutdown::ShutdownReceiver;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+use vec1::Vec1;
+
+/// Maximum number of keys to trigger replays for in a single call to the reader's trigger function
+/// when restoring a checkpoint
+const RESTORE_BATCH_SIZE: usize = 1024;
+
+/// Number of checkpoints after startup for which the keys of checkpointed readers that haven't
+/// been re-created yet are kept, before being discarded
+const MAX_PENDING_CHECKPOINTS: usize = 30;
+
+/// Configuration for reader checkpointing
+#[derive(Debug, Clone)]
+pub struct ReaderCheckpointConfig {
+    /// The path to the file to write reader checkpoints to, and read them from at startup
+    pub path: PathBuf,
+    /// How often to checkpoint the keys in all readers on this worker
+    pub interval: Duration,
+}
+
+/// The keys that were filled in a single partially materialized reader at the time of a
+/// checkpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ReaderCheckpoint {
+    address: ReaderAddress,
+    keys: Vec<Vec<DfValue>>,
+}
+
+async fn read_checkpoint(path: &Path) -> ReadySetResult<HashMap<ReaderAddress, Vec<Vec<DfValue>>>> {
+    let bytes = match tokio::fs::read(path).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let checkpoints: Vec<ReaderCheckpoint> = bincode::deserialize(&bytes)
+        .map_err(|e| internal_err!("Could not deserialize reader checkpoint: {e}"))?;
+    Ok(checkpoints
+        .into_iter()
+        .map(|ReaderCheckpoint { address, keys }| (address, keys))
+        .collect())
+}
+
+/// Atomically overwrite the checkpoint file at `path` with the given checkpoints
+async fn write_checkpoint(path: &Path, checkpoints: &[ReaderCheckpoint]) -> ReadySetResult<()> {
+    let bytes = bincode::serialize(checkpoints)
+        .map_err(|e| internal_err!("Could not serialize reader checkpoint: {e}"))?;
+    let tmp_path = path.with_extension("tmp");
+    tokio::fs::write(&tmp_path, bytes).await?;
+    tokio::fs::rename(&tmp_path, path).await?;
+    Ok(())
+}
+
+/// Trigger replays for all the checkpointed keys for readers which now exist on this worker,
+/// removing them from `pending`.
+fn restore(readers: &Readers, pending: &mut HashMap<ReaderAddress, Vec<Vec<DfValue>>>) {
+    #[allow(clippy::unwrap_used)] // lock poisoning is unrecoverable
+    let readers = readers.lock().unwrap();
+    pending.retain(|address, keys| {
+        let reader = match readers.get(address) {
+            Some(reader) => reader,
+            None => return true,
+        };
+        if !reader.is_partial() || reader.was_dropped() {
+            return false;
+        }
+
+        let misses = keys
+            .drain(..)
+            .filter_map(|key| Vec1::try_from_vec(key).ok())
+            .map(KeyComparison::Equal)
+            .filter(|key| !matches!(reader.contains(key), Ok(true)))
+            .collect::<Vec<_>>();
+        info!(
+            reader = %address.name.display_unquoted(),
+            shard = address.shard,
+            num_keys = misses.len(),
+            "Restoring reader keys from checkpoint"
+        );
+        for batch in misses.chunks(RESTORE_BATCH_SIZE) {
+            reader.trigger(batch.iter().cloned());
+        }
+
+        false
+    });
+}
+
+/// Collect the keys in all partially materialized readers on this worker, along with all keys that
+/// are still pending restoration
+fn collect(
+    readers: &Readers,
+    pending: &HashMap<ReaderAddress, Vec<Vec<DfValue>>>,
+) -> Vec<ReaderCheckpoint> {
+    #[allow(clippy::unwrap_used)] // lock poisoning is unrecoverable
+    let readers = readers.lock().unwrap();
+    readers
+        .iter()
+        .filter(|(_, reader)| reader.is_partial() && !reader.was_dropped())
+        .map(|(address, reader)| ReaderCheckpoint {
+            address: address.clone(),
+            keys: reader.keys(),
+        })
+        .chain(
+            pending
+                .iter()
+                .filter(|(address, _)| !readers.contains_key(address))
+                .map(|(address, keys)| ReaderCheckpoint {
+                    address: address.clone(),
+                    keys: keys.clone(),
+                }),
+        )
+        .collect()
+}
+
+/// Discard all keys still pending restoration if `checkpoints` checkpoints have been written since
+/// startup, since by then the readers they belong to are most likely never coming back
+fn expire_pending(pending: &mut HashMap<ReaderAddress, Vec<Vec<DfValue>>>, checkpoints: usize) {
+    if checkpoints >= MAX_PENDING_CHECKPOINTS && !pending.is_empty() {
+        info!(
+            num_readers = pending.len(),
+            "Discarding checkpointed keys for readers which were not re-created"
+        );
+        pending.clear();
+    }
+}
+
+/// Run the reader checkpointing task until `shutdown_rx` receives a shutdown signal.
+///
+/// On startup, this loads any existing checkpoint from disk, then every
+/// [`interval`](ReaderCheckpointConfig::interval) restores the keys for any checkpointed readers
+/// which have been created since the last tick, and writes out a new checkpoint.
+pub(crate) async fn run(
+    config: ReaderCheckpointConfig,
+    readers: Readers,
+    mut shutdown_rx: ShutdownReceiver,
+) {
+    let mut pending = match read_checkpoint(&config.path).await {
+        Ok(pending) => pending,
+        Err(error) => {
+            warn!(%error, path = %config.path.display(), "Could not load reader checkpoint");
+            HashMap::new()
+        }
+    };
+    if !pending.is_empty() {
+        info!(
+            num_readers = pending.len(),
+            "Loaded reader checkpoint; keys will be restored as readers are created"
+        );
+    }
+
+    let mut checkpoints = 0;
+    let mut interval = tokio::time::interval(config.interval);
+    loop {
+        select! {
+            _ = shutdown_rx.recv() => {
+                debug!("Reader checkpointing shutting down after shutdown signal received");
+                return;
+            }
+            _ = interval.tick() => {
+                restore(&readers, &mut pending);
+                expire_pending(&mut pending, checkpoints);
+                checkpoints += 1;
+                let checkpoint = collect(&readers, &pending);
+                if let Err(error) = write_checkpoint(&config.path, &checkpoint).await {
+                    warn!(%error, path = %config.path.display(), "Could not write reader checkpoint");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::Relation;
+    use petgraph::graph::NodeIndex;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn checkpoint_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("readers.ckpt");
+        assert!(read_checkpoint(&path).await.unwrap().is_empty());
+
+        let address = ReaderAddress {
+            node: NodeIndex::new(3),
+            name: Relation::from("q"),
+            shard: 0,
+        };
+        let keys = vec![vec![DfValue::from(1)], vec![DfValue::from("a")]];
+        write_checkpoint(
+            &path,
+            &[ReaderCheckpoint {
+                address: address.clone(),
+                keys: keys.clone(),
+            }],
+        )
+        .await
+        .unwrap();
+
+        let res = read_checkpoint(&path).await.unwrap();
+        assert_eq!(res, HashMap::from([(address, keys)]));
+    }
+
+    #[test]
+    fn pending_readers_expire() {
+        let readers = Readers::default();
+        let address = ReaderAddress {
+            node: NodeIndex::new(3),
+            name: Relation::from("dropped"),
+            shard: 0,
+        };
+        let mut pending = HashMap::from([(address.clone(), vec![vec![DfValue::from(1)]])]);
+
+        for checkpoints in 0..MAX_PENDING_CHECKPOINTS {
+            restore(&readers, &mut pending);
+            expire_pending(&mut pending, checkpoints);
+            let checkpoint = collect(&readers, &pending);
+            assert_eq!(checkpoint.len(), 1);
+            assert_eq!(checkpoint[0].address, address);
+        }
+
+        restore(&readers, &mut pending);
+        expire_pending(&mut pending, MAX_PENDING_CHECKPOINTS);
+        assert!(pending.is_empty());
+        assert!(collect(&readers, &pending).is_empty());
+    }
+}
@@ -11,7 +11,8 @@ use async_bincode::AsyncBincodeStream;
 use bincode::Options;
 use dataflow::prelude::*;
 use dataflow::{
-    Expr as DfExpr, LookupError, ReaderMap, ReaderUpdatedNotifier, Readers, SingleReadHandle,
+    Expr as DfExpr, LookupError, PostLookup, ReaderMap, ReaderUpdatedNotifier, Readers,
+    SingleReadHandle,
 };
 use failpoint_macros::set_failpoint;
 use futures::pin_mut;
@@ -21,10 +22,11 @@ use readyset_client::consistency::Timestamp;
 #[cfg(feature = "failure_injection")]
 use readyset_client::failpoints;
 use readyset_client::metrics::recorded;
-use readyset_client::results::ResultIterator;
+use readyset_client::results::{ResultIterator, SharedResults};
+use readyset_client::tls::MaybeTlsStream;
 use readyset_client::{
-    KeyComparison, LookupResult, ReadQuery, ReadReply, ReadReplyStats, ReaderAddress, Tagged,
-    ViewQuery,
+    KeyComparison, LookupResult, ReadQuery, ReadReply, ReadReplyStats, ReaderAddress, ResultLimits,
+    Tagged, ViewQuery,
 };
 use readyset_errors::internal_err;
 use readyset_util::shutdown::ShutdownReceiver;
@@ -60,24 +62,79 @@ pub enum ServerReadReplyBatch {
 }
 
 impl ServerReadReplyBatch {
+    /// Construct a [`ServerReadReplyBatch`] from the results of a lookup into a reader, which is
+    /// left [`Unserialized`] if `raw_result` is set and serialized otherwise. Returns an error if
+    /// the results exceed the given `limits`.
+    fn from_lookup(
+        data: SharedResults,
+        post_lookup: &PostLookup,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        filter: Option<DfExpr>,
+        raw_result: bool,
+        limits: ResultLimits,
+    ) -> ReadySetResult<Self> {
+        if !raw_result {
+            return Self::serialize(
+                ResultIterator::new(data, post_lookup, limit, offset, filter),
+                limits,
+            );
+        }
+
+        if !limits.is_unlimited() {
+            // Check the limits in a separate pass over the results, so that the results returned
+            // to the (local) caller can still be iterated lazily without being copied
+            Self::check_limits(
+                ResultIterator::new(data.clone(), post_lookup, limit, offset, filter.clone()),
+                limits,
+            )?;
+        }
+        Ok(Self::Unserialized(ResultIterator::new(
+            data,
+            post_lookup,
+            limit,
+            offset,
+            filter,
+        )))
+    }
+
+    /// Check that the number of rows in a result set, and their serialized size, are within the
+    /// given `limits`.
+    fn check_limits(mut rs: ResultIterator, limits: ResultLimits) -> ReadySetResult<()> {
+        let options = bincode::DefaultOptions::default();
+        let mut n = 0usize;
+        let mut bytes = 0usize;
+        while let Some(row) = rs.next() {
+            n += 1;
+            bytes += options
+                .serialized_size(row)
+                .map_err(|e| internal_err!("{e}"))? as usize;
+            limits.check(n, bytes)?;
+        }
+        Ok(())
+    }
+
     /// Construct a [`ServerReadReplyBatch`] by serializing a result set, and storing the serialized
-    /// bytes.
-    fn serialize(mut rs: ResultIterator) -> Self {
+    /// bytes. Returns an error as soon as the result set is found to exceed the given `limits`.
+    fn serialize(mut rs: ResultIterator, limits: ResultLimits) -> ReadySetResult<Self> {
         let mut v = Vec::with_capacity(16 * 1024);
 
         let options = bincode::DefaultOptions::default();
 
-        let mut ser = bincode::Serializer::new(&mut v, options);
-
-        usize::MAX.serialize(&mut ser).unwrap(); // Prepend the maximum possible room for length encoding
+        // Prepend the maximum possible room for length encoding
+        usize::MAX
+            .serialize(&mut bincode::Serializer::new(&mut v, options))
+            .unwrap();
+        let max_len_enc = options.serialized_size(&usize::MAX).unwrap();
 
         let mut n = 0usize;
         while let Some(row) = rs.next() {
-            row.serialize(&mut ser).unwrap();
+            row.serialize(&mut bincode::Serializer::new(&mut v, options))
+                .unwrap();
             n += 1;
+            limits.check(n, v.len() - max_len_enc as usize)?;
         }
 
-        let max_len_enc = options.serialized_size(&usize::MAX).unwrap();
         let len_enc = options.serialized_size(&n).unwrap();
         let skip_bytes = (max_len_enc - len_enc) as usize;
 
@@ -85,10 +142,10 @@ impl ServerReadReplyBatch {
         // Now encode the proper length
         n.serialize(&mut ser).unwrap();
 
-        Self::Serialized {
+        Ok(Self::Serialized {
             serialized_data: v.into(),
             skip_bytes,
-        }
+        })
     }
 
     /// Return this [`ServerReadReplyBatch`] as its unserialized [`ResultIterator`] if it is
@@ -179,6 +236,8 @@ impl ReadRequestHandler {
             filter,
             limit,
             offset,
+            timeout,
+            result_limits,
         } = query;
 
         macro_rules! reply_with_ok {
@@ -204,6 +263,7 @@ impl ReadRequestHandler {
             Ok(r) => r,
             Err(e) => reply_with_error!(e),
         };
+        reader.record_read();
 
         let consistency_miss = !has_sufficient_timestamp(reader, &timestamp);
 
@@ -222,12 +282,17 @@ impl ReadRequestHandler {
                 // immediately
                 self.hit_ctr.increment(1);
 
-                let results = ResultIterator::new(hit, &reader.post_lookup, limit, offset, filter);
-
-                let results = if raw_result {
-                    ServerReadReplyBatch::Unserialized(results)
-                } else {
-                    ServerReadReplyBatch::serialize(results)
+                let results = match ServerReadReplyBatch::from_lookup(
+                    hit,
+                    &reader.post_lookup,
+                    limit,
+                    offset,
+                    filter,
+                    raw_result,
+                    result_limits,
+                ) {
+                    Ok(results) => results,
+                    Err(e) => reply_with_error!(e),
                 };
 
                 reply_with_ok!(LookupResult::Results(
@@ -261,8 +326,10 @@ impl ReadRequestHandler {
                     offset,
                     filter,
                     timestamp,
-                    upquery_timeout: self.upquery_timeout,
+                    upquery_timeout: timeout
+                        .map_or(self.upquery_timeout, |t| t.min(self.upquery_timeout)),
                     raw_result,
+                    result_limits,
                     receiver,
                     eviction_epoch: reader.eviction_epoch(),
                 },
@@ -295,6 +362,20 @@ impl ReadRequestHandler {
             v: ReadReply::Keys(reader.keys()),
         })
     }
+
+    fn handle_key_versions_query(
+        &mut self,
+        tag: u32,
+        target: &ReaderAddress,
+        keys: &[KeyComparison],
+    ) -> Reply {
+        let reader = get_reader_from_cache(target, &mut self.readers_cache, &self.global_readers)?;
+
+        Ok(Tagged {
+            tag,
+            v: ReadReply::KeyVersions(reader.key_versions(keys)),
+        })
+    }
 }
 
 impl Service<Tagged<ReadQuery>> for ReadRequestHandler {
@@ -326,6 +407,14 @@ impl Service<Tagged<ReadQuery>> for ReadRequestHandler {
                 let _g = span.enter();
                 CallResult::Immediate(self.handle_keys_query(tag, target))
             }
+            ReadQuery::KeyVersions {
+                ref target,
+                ref keys,
+            } => {
+                let span = readyset_tracing::child_span!(INFO, "key_versions_query");
+                let _g = span.enter();
+                CallResult::Immediate(self.handle_key_versions_query(tag, target, keys))
+            }
         };
 
         async {
@@ -343,20 +432,33 @@ pub async fn retry_misses(mut rx: UnboundedReceiver<(BlockingRead, Ack)>) {
     let upquery_hist = metrics::register_histogram!(recorded::SERVER_VIEW_UPQUERY_DURATION);
     let mut reader_cache: ReaderMap = Default::default();
 
-    while let Some((mut pending, ack)) = rx.recv().await {
+    while let Some((mut pending, mut ack)) = rx.recv().await {
+        let deadline = tokio::time::Instant::from_std(pending.first + pending.upquery_timeout);
         loop {
-            if let Some(recv) = &mut pending.receiver {
-                // If a receiever is available (on miss) then we simply wait for a notification that
-                // a hole has been filled, then recheck
-                let _ = recv.recv().await;
-                while !recv.is_empty() {
-                    // This drains all the messages from the notifier so we don't get woken right up
-                    // again
-                    let _ = recv.try_recv();
+            let wait = async {
+                if let Some(recv) = &mut pending.receiver {
+                    // If a receiever is available (on miss) then we simply wait for a notification
+                    // that a hole has been filled, then recheck
+                    let _ = recv.recv().await;
+                    while !recv.is_empty() {
+                        // This drains all the messages from the notifier so we don't get woken
+                        // right up again
+                        let _ = recv.try_recv();
+                    }
+                } else {
+                    // For consistency misses we don't get notifications, so check periodically
+                    tokio::time::sleep(RETRY_TIMEOUT).await;
                 }
-            } else {
-                // For consistency misses we don't get notifications, so check periodically
-                tokio::time::sleep(RETRY_TIMEOUT).await;
+            };
+
+            tokio::select! {
+                _ = wait => {}
+                // Recheck once the read times out, so that we reply with the timeout error
+                // promptly even if no holes are filled
+                _ = tokio::time::sleep_until(deadline) => {}
+                // The client has given up on this read (eg because the query it's part of was
+                // cancelled), so there's no point in waiting for it any longer
+                _ = ack.closed() => break,
             }
 
             if let Poll::Ready(res) = pending.check(&mut reader_cache) {
@@ -401,28 +503,36 @@ pub(crate) async fn listen(
 
         let r = ReadRequestHandler::new(readers, tx, upquery_timeout);
 
-        let server =
-            server::Server::new(AsyncBincodeStream::from(stream).for_async(), r).map_err(|e| {
-                match e {
-                    // server is shutting down -- no need to report this error
-                    server::Error::Service(ReadySetError::ServerShuttingDown) => {}
-                    server::Error::BrokenTransportRecv(ref e)
-                    | server::Error::BrokenTransportSend(ref e) => {
-                        if let bincode::ErrorKind::Io(ref e) = **e {
-                            if e.kind() == std::io::ErrorKind::BrokenPipe
-                                || e.kind() == std::io::ErrorKind::ConnectionReset
-                            {
-                                // client went away
+        tokio::spawn(async move {
+            let stream = match MaybeTlsStream::accept(stream).await {
+                Ok(stream) => stream,
+                Err(error) => {
+                    warn!(%error, "TLS handshake with reader client failed");
+                    return;
+                }
+            };
+
+            let server = server::Server::new(AsyncBincodeStream::from(stream).for_async(), r)
+                .map_err(|e| {
+                    match e {
+                        // server is shutting down -- no need to report this error
+                        server::Error::Service(ReadySetError::ServerShuttingDown) => {}
+                        server::Error::BrokenTransportRecv(ref e)
+                        | server::Error::BrokenTransportSend(ref e) => {
+                            if let bincode::ErrorKind::Io(ref e) = **e {
+                                if e.kind() == std::io::ErrorKind::BrokenPipe
+                                    || e.kind() == std::io::ErrorKind::ConnectionReset
+                                {
+                                    // client went away
+                                }
+                            } else {
+                                error!(error = %e, "client transport error");
                             }
-                        } else {
-                            error!(error = %e, "client transport error");
                         }
+                        e => error!(error = %e, "reader service error"),
                     }
-                    e => error!(error = %e, "reader service error"),
-                }
-            });
+                });
 
-        tokio::spawn(async move {
             tokio::select! {
                 _ = server => {},
                 _ = shutdown_rx.recv() => {},
@@ -465,6 +575,7 @@ pub struct BlockingRead {
     timestamp: Option<Timestamp>,
     upquery_timeout: Duration,
     raw_result: bool,
+    result_limits: ResultLimits,
     receiver: Option<ReaderUpdatedNotifier>,
     eviction_epoch: usize,
 }
@@ -502,26 +613,21 @@ impl BlockingRead {
             Err(_) => return Poll::Ready(Err(ReadySetError::ServerShuttingDown)),
             Ok(hit) => {
                 // We hit on all keys, and there is no consistency miss, can return results
-                let results = ResultIterator::new(
+                let results = ServerReadReplyBatch::from_lookup(
                     hit,
                     &reader.post_lookup,
                     self.limit,
                     self.offset,
                     self.filter.take(),
+                    self.raw_result,
+                    self.result_limits,
                 );
 
-                let results = if self.raw_result {
-                    ServerReadReplyBatch::Unserialized(results)
-                } else {
-                    ServerReadReplyBatch::serialize(results)
-                };
-
                 return Poll::Ready(Ok(Tagged {
                     tag: self.tag,
-                    v: ReadReply::Normal(Ok(LookupResult::Results(
-                        vec![results],
-                        ReadReplyStats::default(),
-                    ))),
+                    v: ReadReply::Normal(results.map(|results| {
+                        LookupResult::Results(vec![results], ReadReplyStats::default())
+                    })),
                 }));
             }
         };
@@ -544,7 +650,7 @@ impl BlockingRead {
             }
         }
 
-        if self.first.elapsed() > self.upquery_timeout {
+        if self.first.elapsed() >= self.upquery_timeout {
             Poll::Ready(Err(ReadySetError::UpqueryTimeout))
         } else {
             Poll::Pending
@@ -596,13 +702,17 @@ mod readreply {
                     data.iter()
                         .cloned()
                         .map(|d| {
-                            ServerReadReplyBatch::serialize(ResultIterator::new(
-                                [d].into(),
-                                &Default::default(),
-                                None,
-                                None,
-                                None,
-                            ))
+                            ServerReadReplyBatch::serialize(
+                                ResultIterator::new(
+                                    [d].into(),
+                                    &Default::default(),
+                                    None,
+                                    None,
+                                    None,
+                                ),
+                                ResultLimits::default(),
+                            )
+                            .unwrap()
                         })
                         .collect(),
                     ReadReplyStats::default(),
@@ -728,6 +838,61 @@ mod readreply {
         ));
     }
 
+    #[test]
+    fn serialize_enforces_limits() {
+        let data = rows_vec([[
+            [DfValue::from(1), DfValue::from("a")],
+            [DfValue::from(2), DfValue::from("b")],
+            [DfValue::from(3), DfValue::from("c")],
+        ]]);
+        let serialize = |limits| {
+            ServerReadReplyBatch::serialize(
+                ResultIterator::new(data.clone(), &Default::default(), None, None, None),
+                limits,
+            )
+        };
+
+        serialize(ResultLimits {
+            max_rows: Some(3),
+            max_bytes: None,
+        })
+        .unwrap();
+        assert_eq!(
+            serialize(ResultLimits {
+                max_rows: Some(2),
+                max_bytes: None,
+            })
+            .unwrap_err(),
+            ReadySetError::ResultRowLimitExceeded { max_rows: 2 }
+        );
+        assert_eq!(
+            serialize(ResultLimits {
+                max_rows: None,
+                max_bytes: Some(8),
+            })
+            .unwrap_err(),
+            ReadySetError::ResultSizeLimitExceeded { max_bytes: 8 }
+        );
+
+        // Local (unserialized) reads are checked against the same limits
+        assert_eq!(
+            ServerReadReplyBatch::from_lookup(
+                data,
+                &Default::default(),
+                None,
+                None,
+                None,
+                true,
+                ResultLimits {
+                    max_rows: Some(2),
+                    max_bytes: None,
+                },
+            )
+            .unwrap_err(),
+            ReadySetError::ResultRowLimitExceeded { max_rows: 2 }
+        );
+    }
+
     #[test]
     fn rtt_size() {
         let got: Tagged<ReadReply> = bincode::deserialize(
@@ -760,13 +925,17 @@ mod readreply {
                     data.iter()
                         .cloned()
                         .map(|d| {
-                            ServerReadReplyBatch::serialize(ResultIterator::new(
-                                [d].into(),
-                                &Default::default(),
-                                None,
-                                None,
-                                None,
-                            ))
+                            ServerReadReplyBatch::serialize(
+                                ResultIterator::new(
+                                    [d].into(),
+                                    &Default::default(),
+                                    None,
+                                    None,
+                                    None,
+                                ),
+                                ResultLimits::default(),
+                            )
+                            .unwrap()
                         })
                         .collect(),
                     ReadReplyStats::default(),
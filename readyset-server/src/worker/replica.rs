@@ -9,12 +9,13 @@ use anyhow::{self, Context as AnyhowContext};
 use async_bincode::AsyncDestination;
 use dataflow::payload::{MaterializedState, SourceChannelIdentifier};
 use dataflow::prelude::Executor;
-use dataflow::{Domain, DomainRequest, Packet};
+use dataflow::{Domain, DomainRequest, Packet, PacketDiscriminants};
 use futures_util::sink::{Sink, SinkExt};
 use futures_util::stream::StreamExt;
 use futures_util::FutureExt;
 use readyset_client::channel::{self, CONNECTION_FROM_BASE};
 use readyset_client::internal::ReplicaAddress;
+use readyset_client::tls::MaybeTlsStream;
 use readyset_client::{KeyComparison, PacketData, PacketPayload, Tagged};
 use readyset_errors::ReadySetResult;
 use strawpoll::Strawpoll;
@@ -25,10 +26,22 @@ use tokio::sync::{mpsc, oneshot, Mutex};
 use tokio_stream::wrappers::IntervalStream;
 use tracing::{debug, error, info_span, instrument, warn, Span};
 
+use super::fair_queue::FairQueue;
+use super::watchdog::DomainActivity;
 use super::ChannelCoordinator;
 
-type DualTcpStream =
-    channel::DualTcpStream<BufStream<TcpStream>, Box<Packet>, Tagged<PacketData>, AsyncDestination>;
+/// How often to perform periodic maintenance of the base tables in a domain
+const BASE_TABLE_MAINTENANCE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The maximum number of queued upqueries to handle before checking for new packets
+const MAX_UPQUERIES_PER_ITERATION: usize = 16;
+
+type DualTcpStream = channel::DualTcpStream<
+    BufStream<MaybeTlsStream<TcpStream>>,
+    Box<Packet>,
+    Tagged<PacketData>,
+    AsyncDestination,
+>;
 
 type Outputs =
     AHashMap<ReplicaAddress, Box<dyn Sink<Box<Packet>, Error = bincode::Error> + Send + Unpin>>;
@@ -56,6 +69,10 @@ pub struct Replica {
     /// NOTE: if `aggressively_update_state_sizes` updates will happen every packet
     refresh_sizes: IntervalStream,
 
+    /// How often to perform periodic maintenance of base tables (updating tombstone metrics and
+    /// running scheduled compactions)
+    maintain_base_tables: IntervalStream,
+
     /// Incoming TCP connections, usually from other Domains
     incoming: Strawpoll<TcpListener>,
 
@@ -69,6 +86,12 @@ pub struct Replica {
 
     /// Stores pending outgoing messages
     out: Outboxes,
+
+    /// Upqueries received but not yet handled, scheduled fairly between tenants
+    upqueries: FairQueue<Box<Packet>>,
+
+    /// Record of what the domain is currently doing, for stall detection
+    activity: Arc<DomainActivity>,
 }
 
 impl Replica {
@@ -79,6 +102,7 @@ impl Replica {
         requests: mpsc::Receiver<WrappedDomainRequest>,
         init_state_reqs: mpsc::Receiver<MaterializedState>,
         cc: Arc<ChannelCoordinator>,
+        activity: Arc<DomainActivity>,
     ) -> Self {
         Replica {
             coord: cc,
@@ -86,9 +110,14 @@ impl Replica {
             incoming: Strawpoll::from(on),
             locals,
             out: Outboxes::new(),
+            upqueries: FairQueue::default(),
             refresh_sizes: IntervalStream::new(tokio::time::interval(Duration::from_millis(500))),
+            maintain_base_tables: IntervalStream::new(tokio::time::interval(
+                BASE_TABLE_MAINTENANCE_INTERVAL,
+            )),
             requests,
             init_state_reqs,
+            activity,
         }
     }
 }
@@ -145,12 +174,15 @@ impl Replica {
         )
     }
 
-    /// Read the first byte of a connection to determine if it is from a base node, and convert
-    /// it to a DualTcpStream, returning a unique token for the connection together with the
-    /// upgraded connection
+    /// Perform the TLS handshake for a connection if internal TLS is enabled, then read its first
+    /// byte to determine if it is from a base node, and convert it to a DualTcpStream, returning a
+    /// unique token for the connection together with the upgraded connection
     async fn handle_new_connection(
-        mut stream: TcpStream,
+        stream: TcpStream,
     ) -> Result<(u64, DualTcpStream), anyhow::Error> {
+        let _ = stream.set_nodelay(true);
+        let mut stream = MaybeTlsStream::accept(stream).await?;
+
         let mut tag: u8 = 0;
         stream.read_exact(std::slice::from_mut(&mut tag)).await?;
         let is_base = tag == CONNECTION_FROM_BASE;
@@ -158,7 +190,6 @@ impl Replica {
         debug!(base = is_base, "established new connection");
 
         let token = next_token();
-        let _ = stream.set_nodelay(true);
 
         let tcp = if is_base {
             DualTcpStream::upgrade(BufStream::new(stream), move |Tagged { v, tag }| {
@@ -340,11 +371,14 @@ impl Replica {
             domain,
             coord,
             refresh_sizes,
+            maintain_base_tables,
             incoming,
             locals,
             requests,
             out,
+            upqueries,
             init_state_reqs,
+            activity,
         } = &mut self;
 
         loop {
@@ -375,6 +409,7 @@ impl Replica {
                 domain_req = requests.recv() => match domain_req {
                     Some(req) => {
                         let _guard = span.enter();
+                        let _busy = activity.start("DomainRequest");
                         if req.done_tx.send(domain.domain_request(req.req, out)).is_err() {
                             span.in_scope(|| warn!("domain request sender hung up"));
                         }
@@ -386,7 +421,10 @@ impl Replica {
                 },
 
                 init_state = init_state_reqs.recv() => match init_state {
-                    Some(MaterializedState{ node, state }) => domain.process_state_for_node(node, *state)?,
+                    Some(MaterializedState{ node, state }) => {
+                        let _busy = activity.start("InitializeState");
+                        domain.process_state_for_node(node, *state)?
+                    }
                     None => {
                         span.in_scope(|| warn!("domain state initialization stream ended"));
                         return Ok(())
@@ -400,6 +438,7 @@ impl Replica {
                         return Ok(())
                     },
                     Some(mut packets) => {
+                        let busy = activity.start("Packets");
                         while let Some(mut packet) = packets.pop_front() {
                             let ack = match &mut *packet {
                                 Packet::Timestamp { src: SourceChannelIdentifier { token, tag }, .. } |
@@ -418,10 +457,23 @@ impl Replica {
                                 _ => None,
                             };
 
+                            // Upqueries are handled later, in fair order between tenants
+                            if let Some(tenant) = domain.upquery_tenant(&packet) {
+                                let weight = domain.tenant_weight(tenant.as_ref());
+                                upqueries.push(tenant, weight, packet);
+                                continue;
+                            }
+
+                            busy.handling(PacketDiscriminants::from(&*packet).into());
+                            activity.set_queue_depths(
+                                packets.len() + upqueries.len(),
+                                out.domains.values().map(VecDeque::len).sum(),
+                            );
                             span.in_scope(|| domain.handle_packet(packet, out))?;
 
+                            let affected_rows = domain.take_affected_rows();
                             if let Some((tag, conn)) = ack {
-                                conn.send(Tagged { tag, v: () }).await?;
+                                conn.send(Tagged { tag, v: affected_rows }).await?;
                             }
                         }
                     },
@@ -433,8 +485,33 @@ impl Replica {
                 // Update domain sizes when `refresh_sizes` expires
                 Some(_) = refresh_sizes.next() => domain.update_state_sizes(),
 
+                // Update base table tombstone metrics and run any scheduled compactions
+                Some(_) = maintain_base_tables.next() => domain.maintain_base_tables(),
+
                 // Wait for a possible sleep
-                _ = tokio::time::sleep(domain.next_poll_duration().unwrap_or_else(|| Duration::from_secs(3600))) => domain.handle_timeout()?,
+                _ = tokio::time::sleep(domain.next_poll_duration().unwrap_or_else(|| Duration::from_secs(3600))) => {
+                    let _busy = activity.start("Timeout");
+                    domain.handle_timeout()?
+                }
+
+                // Don't wait for new packets if there are upqueries left to handle
+                _ = std::future::ready(()), if !upqueries.is_empty() => {}
+            }
+
+            // Handle some of the queued upqueries, in fair order between tenants
+            if !upqueries.is_empty() {
+                let busy = activity.start("Upqueries");
+                for _ in 0..MAX_UPQUERIES_PER_ITERATION {
+                    let Some(packet) = upqueries.pop() else {
+                        break;
+                    };
+                    busy.handling(PacketDiscriminants::from(&*packet).into());
+                    activity.set_queue_depths(
+                        upqueries.len(),
+                        out.domains.values().map(VecDeque::len).sum(),
+                    );
+                    span.in_scope(|| domain.handle_packet(packet, out))?;
+                }
             }
 
             // Check if the previous batch of send packets is done, and issue a new batch if needed
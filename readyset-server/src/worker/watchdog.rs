@@ -0,0 +1,222 @@
+//! Detection of stalled domains.
+//!
+//! Each domain replica run by a worker records what it is currently doing in a shared
+//! [`DomainActivity`]: when it started handling its current batch of packets (or domain request, or
+//! timeout), which packet it is handling, and how many packets are queued up behind it. The worker
+//! periodically [checks](check_domains) the activity of all its domains, and reports any domain
+//! which has been busy with the same piece of work for longer than the configured
+//! [stall threshold](WatchdogConfig::stall_threshold) via a log message and the
+//! [`DOMAIN_STALLED`](recorded::DOMAIN_STALLED) and [`DOMAIN_STALLS`](recorded::DOMAIN_STALLS)
+//! metrics. Domains are single-threaded, so a domain that has stalled (due to a deadlock, a
+//! pathologically slow replay, or a blocked network connection) stops processing any packets at
+//! all until it recovers.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use metrics::{gauge, increment_counter};
+use parking_lot::Mutex;
+use readyset_client::internal::ReplicaAddress;
+use readyset_client::metrics::recorded;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Sentinel value for [`DomainActivity::busy_since`] indicating that the domain is idle
+const IDLE: u64 = u64::MAX;
+
+/// Configuration for the domain stall watchdog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    /// A domain is considered stalled once it has been handling the same piece of work for longer
+    /// than this duration
+    pub stall_threshold: Duration,
+    /// If true, include the domain's queue depths and the type of the packet it is handling in the
+    /// log message emitted when a domain stalls
+    pub dump_on_stall: bool,
+}
+
+impl WatchdogConfig {
+    /// The interval at which the watchdog should check for stalled domains
+    pub(crate) fn check_interval(&self) -> Duration {
+        (self.stall_threshold / 4).max(Duration::from_millis(100))
+    }
+}
+
+/// The activity of a single domain replica, updated by the domain and read by the watchdog
+pub(crate) struct DomainActivity {
+    epoch: Instant,
+    /// Milliseconds since `epoch` at which the domain started handling its current piece of work,
+    /// or [`IDLE`] if the domain is not currently handling anything
+    busy_since: AtomicU64,
+    /// Milliseconds since `epoch` at which the domain last finished handling a piece of work
+    last_processed: AtomicU64,
+    /// The kind of work (packet type, domain request, or timeout) the domain is currently handling
+    current: Mutex<Option<&'static str>>,
+    /// The number of packets received by the domain which are waiting to be handled
+    queued_packets: AtomicUsize,
+    /// The number of packets the domain has produced which are waiting to be sent to other domains
+    outgoing_packets: AtomicUsize,
+    /// Whether the domain has been reported as stalled and has not yet recovered
+    stalled: AtomicBool,
+}
+
+/// Marks the domain as busy with a piece of work until dropped
+pub(crate) struct BusyGuard<'a>(&'a DomainActivity);
+
+impl<'a> BusyGuard<'a> {
+    /// Record that the domain has moved on to handling a new piece of work of the given kind
+    pub(crate) fn handling(&self, kind: &'static str) {
+        *self.0.current.lock() = Some(kind);
+    }
+}
+
+impl<'a> Drop for BusyGuard<'a> {
+    fn drop(&mut self) {
+        let activity = self.0;
+        activity
+            .last_processed
+            .store(activity.now_ms(), Ordering::Relaxed);
+        activity.busy_since.store(IDLE, Ordering::Release);
+        *activity.current.lock() = None;
+    }
+}
+
+impl Default for DomainActivity {
+    fn default() -> Self {
+        Self {
+            epoch: Instant::now(),
+            busy_since: AtomicU64::new(IDLE),
+            last_processed: AtomicU64::new(0),
+            current: Mutex::new(None),
+            queued_packets: AtomicUsize::new(0),
+            outgoing_packets: AtomicUsize::new(0),
+            stalled: AtomicBool::new(false),
+        }
+    }
+}
+
+impl DomainActivity {
+    fn now_ms(&self) -> u64 {
+        self.epoch.elapsed().as_millis() as u64
+    }
+
+    /// Record that the domain has started handling a piece of work of the given kind, which lasts
+    /// until the returned guard is dropped
+    pub(crate) fn start(&self, kind: &'static str) -> BusyGuard<'_> {
+        *self.current.lock() = Some(kind);
+        self.busy_since.store(self.now_ms(), Ordering::Release);
+        BusyGuard(self)
+    }
+
+    /// Record the current depths of the domain's incoming and outgoing packet queues
+    pub(crate) fn set_queue_depths(&self, queued: usize, outgoing: usize) {
+        self.queued_packets.store(queued, Ordering::Relaxed);
+        self.outgoing_packets.store(outgoing, Ordering::Relaxed);
+    }
+
+    /// Returns how long the domain has been handling its current piece of work as of `now`, or
+    /// `None` if the domain is idle
+    fn busy_for(&self, now: Instant) -> Option<Duration> {
+        match self.busy_since.load(Ordering::Acquire) {
+            IDLE => None,
+            since => Some(
+                now.saturating_duration_since(self.epoch)
+                    .saturating_sub(Duration::from_millis(since)),
+            ),
+        }
+    }
+}
+
+/// Check the activity of each of the given domains as of `now`, reporting any domains which have
+/// newly stalled or recovered. Returns the number of domains which are currently stalled.
+pub(crate) fn check_domains<'a, I>(config: &WatchdogConfig, domains: I, now: Instant) -> usize
+where
+    I: IntoIterator<Item = (&'a ReplicaAddress, &'a DomainActivity)>,
+{
+    let mut num_stalled = 0;
+    for (address, activity) in domains {
+        let busy_for = activity.busy_for(now);
+        let stalled = matches!(busy_for, Some(busy_for) if busy_for >= config.stall_threshold);
+        let was_stalled = activity.stalled.swap(stalled, Ordering::Relaxed);
+        if stalled {
+            num_stalled += 1;
+        }
+        if stalled == was_stalled {
+            continue;
+        }
+
+        let labels = [
+            ("domain", address.domain_index.index().to_string()),
+            ("shard", address.shard.to_string()),
+            ("replica", address.replica.to_string()),
+        ];
+        gauge!(
+            recorded::DOMAIN_STALLED,
+            if stalled { 1.0 } else { 0.0 },
+            &labels
+        );
+
+        if !stalled {
+            info!(domain = %address, "Domain has recovered from stall");
+            continue;
+        }
+
+        increment_counter!(recorded::DOMAIN_STALLS, &labels);
+        let busy_for = busy_for.unwrap_or_default();
+        if config.dump_on_stall {
+            let last_processed =
+                Duration::from_millis(activity.last_processed.load(Ordering::Relaxed));
+            warn!(
+                domain = %address,
+                ?busy_for,
+                current = activity.current.lock().unwrap_or("unknown"),
+                queued_packets = activity.queued_packets.load(Ordering::Relaxed),
+                outgoing_packets = activity.outgoing_packets.load(Ordering::Relaxed),
+                since_last_processed = ?now
+                    .saturating_duration_since(activity.epoch)
+                    .saturating_sub(last_processed),
+                "Domain appears to be stalled"
+            );
+        } else {
+            warn!(domain = %address, ?busy_for, "Domain appears to be stalled");
+        }
+    }
+    num_stalled
+}
+
+#[cfg(test)]
+mod tests {
+    use readyset_client::internal::DomainIndex;
+
+    use super::*;
+
+    #[test]
+    fn detects_stall_and_recovery() {
+        let config = WatchdogConfig {
+            stall_threshold: Duration::from_secs(10),
+            dump_on_stall: true,
+        };
+        let address = ReplicaAddress {
+            domain_index: DomainIndex::from(0),
+            shard: 0,
+            replica: 0,
+        };
+        let activity = DomainActivity::default();
+        let check = |now| check_domains(&config, [(&address, &activity)], now);
+
+        let start = Instant::now();
+        assert_eq!(check(start + Duration::from_secs(60)), 0);
+
+        let guard = activity.start("Input");
+        assert_eq!(check(Instant::now()), 0);
+        assert_eq!(check(Instant::now() + Duration::from_secs(11)), 1);
+        assert!(activity.stalled.load(Ordering::Relaxed));
+
+        guard.handling("Timestamp");
+        assert_eq!(*activity.current.lock(), Some("Timestamp"));
+        drop(guard);
+        assert_eq!(*activity.current.lock(), None);
+        assert_eq!(check(Instant::now() + Duration::from_secs(11)), 0);
+        assert!(!activity.stalled.load(Ordering::Relaxed));
+    }
+}
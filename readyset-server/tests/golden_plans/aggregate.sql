@@ -0,0 +1,4 @@
+CREATE TABLE votes (post_id INT, user_id INT);
+
+CREATE CACHE vote_count FROM
+SELECT post_id, count(*) FROM votes WHERE post_id = ? GROUP BY post_id;
@@ -0,0 +1,7 @@
+CREATE TABLE users (id INT PRIMARY KEY, name TEXT);
+CREATE TABLE posts (id INT PRIMARY KEY, author_id INT, title TEXT);
+
+CREATE CACHE posts_with_author FROM
+SELECT posts.id, posts.title, users.name
+FROM posts JOIN users ON posts.author_id = users.id
+WHERE users.id = ?;
@@ -0,0 +1,3 @@
+CREATE TABLE users (id INT PRIMARY KEY, name TEXT, email TEXT);
+
+CREATE CACHE user_by_id FROM SELECT id, name FROM users WHERE id = ?;
@@ -0,0 +1,12 @@
+CREATE TABLE users (id INT PRIMARY KEY, name TEXT);
+CREATE TABLE posts (id INT PRIMARY KEY, author_id INT, title TEXT);
+
+CREATE CACHE posts_by_author FROM
+SELECT posts.id, posts.title, users.name
+FROM posts JOIN users ON posts.author_id = users.id
+WHERE users.id = ?;
+
+CREATE CACHE posts_by_title FROM
+SELECT posts.id, posts.title, users.name
+FROM posts JOIN users ON posts.author_id = users.id
+WHERE posts.title = ?;
@@ -0,0 +1,4 @@
+CREATE TABLE posts (id INT PRIMARY KEY, author_id INT, score INT);
+
+CREATE CACHE top_posts FROM
+SELECT id, score FROM posts WHERE author_id = ? ORDER BY score DESC LIMIT 10;
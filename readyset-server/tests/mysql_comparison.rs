@@ -324,6 +324,8 @@ async fn check_query(
                         // These types are PostgreSQL specific
                         | DfValue::BitVector(_)
                         | DfValue::PassThrough(_)
+                        | DfValue::Uuid(_)
+                        | DfValue::Interval(_)
                         | DfValue::Array(_) => {
                             unimplemented!()
                         }
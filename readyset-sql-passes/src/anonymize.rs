@@ -4,10 +4,12 @@
 
 use std::collections::HashMap;
 
+use blake2::digest::{Update, VariableOutput};
+use blake2::Blake2bVar;
 use nom_sql::analysis::visit_mut::VisitorMut;
 use nom_sql::{
     CreateTableOption, CreateTableStatement, CreateViewStatement, Literal, SelectStatement,
-    SqlIdentifier,
+    SqlIdentifier, SqlQuery,
 };
 
 pub trait Anonymize {
@@ -77,10 +79,19 @@ pub fn anonymize_literals(query: &mut SelectStatement) {
         .unwrap();
 }
 
+/// Replaces every instance of `Literal` in the AST of any kind of query with
+/// `Literal::String("<anonymized>")`
+pub fn anonymize_query_literals(query: &mut SqlQuery) {
+    #[allow(clippy::unwrap_used)] // error is !, which can never be returned
+    AnonymizeLiteralsVisitor.visit_sql_query(query).unwrap();
+}
+
 pub struct Anonymizer {
     /// A map of symbols to anonymized symbols
     anonymizations: HashMap<SqlIdentifier, SqlIdentifier>,
     next_id: u32,
+    /// If true, symbols are replaced with a hash of the symbol rather than a sequential token
+    hash_identifiers: bool,
 }
 
 impl Anonymizer {
@@ -88,10 +99,33 @@ impl Anonymizer {
         Self {
             anonymizations: HashMap::new(),
             next_id: 0,
+            hash_identifiers: false,
         }
     }
 
-    fn next_token(&mut self) -> SqlIdentifier {
+    /// Construct a new [`Anonymizer`] which replaces every symbol with a token derived from a hash
+    /// of that symbol, rather than a sequential token. Unlike the tokens produced by
+    /// [`Anonymizer::new`], these are the same no matter which order symbols are anonymized in,
+    /// so separately anonymized statements (eg a schema and the queries run against it) can still
+    /// be correlated with each other.
+    pub fn with_hashed_identifiers() -> Self {
+        Self {
+            hash_identifiers: true,
+            ..Self::new()
+        }
+    }
+
+    fn next_token(&mut self, s: &SqlIdentifier) -> SqlIdentifier {
+        if self.hash_identifiers {
+            let mut hasher = Blake2bVar::new(8).expect("8 is a valid output size for Blake2bVar");
+            hasher.update(s.as_bytes());
+            let mut buf = [0u8; 8];
+            hasher
+                .finalize_variable(&mut buf)
+                .expect("8 is a valid output size for Blake2bVar");
+            return format!("anon_{}", hex::encode(buf)).into();
+        }
+
         let ret = format!("anon_id_{}", self.next_id);
         self.next_id += 1;
         ret.into()
@@ -102,7 +136,7 @@ impl Anonymizer {
         match self.anonymizations.get(s) {
             Some(anon_s) => *s = anon_s.clone(),
             None => {
-                let anon_s = self.next_token();
+                let anon_s = self.next_token(s);
                 self.anonymizations.insert(s.clone(), anon_s.clone());
                 *s = anon_s;
             }
@@ -179,6 +213,7 @@ impl<'ast> VisitorMut<'ast> for AnonymizeVisitor<'_> {
             | CreateTableOption::AutoIncrement(_)
             | CreateTableOption::Engine(_)
             | CreateTableOption::Charset(_)
+            | CreateTableOption::Durability(_)
             | CreateTableOption::Other => {}
         }
         Ok(())
@@ -200,7 +235,9 @@ impl<'ast> VisitorMut<'ast> for AnonymizeVisitor<'_> {
             | nom_sql::ShowStatement::ProxiedQueries(..)
             | nom_sql::ShowStatement::ReadySetStatus
             | nom_sql::ShowStatement::ReadySetVersion
-            | nom_sql::ShowStatement::ReadySetTables => {}
+            | nom_sql::ShowStatement::ReadySetTables
+            | nom_sql::ShowStatement::ReadySetReplicationPosition
+            | nom_sql::ShowStatement::Experiments => {}
         }
         Ok(())
     }
@@ -249,6 +286,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn insert_query() {
+        let mut query = nom_sql::parse_query(
+            Dialect::MySQL,
+            "INSERT INTO users (id, credit_card_number) VALUES (1, \"look at this PII\")",
+        )
+        .unwrap();
+        let expected = nom_sql::parse_query(
+            Dialect::MySQL,
+            "INSERT INTO users (id, credit_card_number) VALUES (\"<anonymized>\", \"<anonymized>\")",
+        )
+        .unwrap();
+        anonymize_query_literals(&mut query);
+        assert_eq!(query, expected);
+    }
+
     #[test]
     fn parameterized_single_literal() {
         let mut query =
@@ -313,4 +366,19 @@ mod tests {
 
         assert_eq!(create_view, expected);
     }
+
+    #[test]
+    fn hashed_identifiers_consistent_across_anonymizers() {
+        let mut table = parse_create_table_statement(
+            "CREATE TABLE users (id INT, name TEXT, PRIMARY KEY (id))",
+        );
+        table.anonymize(&mut Anonymizer::with_hashed_identifiers());
+
+        let mut query = parse_select_statement("SELECT name FROM users WHERE id = ?");
+        query.anonymize(&mut Anonymizer::with_hashed_identifiers());
+
+        assert_eq!(query.tables, vec![table.table.clone().into()]);
+        assert!(table.table.name.starts_with("anon_"));
+        assert_ne!(table.table.name, "users");
+    }
 }
@@ -1,9 +1,15 @@
 use nom_sql::analysis::visit_mut::{self, VisitorMut};
-use nom_sql::{Column, CreateTableStatement, Relation};
+use nom_sql::{
+    Column, ColumnConstraint, ColumnSpecification, CreateTableOption, CreateTableStatement,
+    Relation,
+};
 
 #[derive(Debug, Default)]
 struct CreateTableColumnsVisitor {
     table: Option<Relation>,
+    /// The default collation for text columns in the table, if one was given in the table's
+    /// options
+    default_collation: Option<String>,
 }
 
 impl<'ast> VisitorMut<'ast> for CreateTableColumnsVisitor {
@@ -14,9 +20,38 @@ impl<'ast> VisitorMut<'ast> for CreateTableColumnsVisitor {
         create_table_statement: &'ast mut CreateTableStatement,
     ) -> Result<(), Self::Error> {
         self.table = Some(create_table_statement.table.clone());
+        self.default_collation = create_table_statement
+            .options
+            .iter()
+            .flatten()
+            .find_map(|opt| match opt {
+                CreateTableOption::Collate(collation) => Some(collation.to_string()),
+                _ => None,
+            });
         visit_mut::walk_create_table_statement(self, create_table_statement)
     }
 
+    fn visit_column_specification(
+        &mut self,
+        column_specification: &'ast mut ColumnSpecification,
+    ) -> Result<(), Self::Error> {
+        // Make the table's default collation explicit on every text column which doesn't specify
+        // its own, so that it's not lost along with the table's options
+        if let Some(collation) = &self.default_collation {
+            if column_specification.sql_type.is_any_text()
+                && !column_specification
+                    .constraints
+                    .iter()
+                    .any(|c| matches!(c, ColumnConstraint::Collation(_)))
+            {
+                column_specification
+                    .constraints
+                    .push(ColumnConstraint::Collation(collation.clone()));
+            }
+        }
+        visit_mut::walk_column_specification(self, column_specification)
+    }
+
     fn visit_column(&mut self, column: &'ast mut Column) -> Result<(), Self::Error> {
         column.table.get_or_insert_with(|| {
             self.table
@@ -59,4 +94,20 @@ mod tests {
         .unwrap();
         assert_eq!(orig.normalize_create_table_columns(), expected);
     }
+
+    #[test]
+    fn table_default_collation() {
+        let orig = parse_create_table(
+            Dialect::MySQL,
+            "CREATE TABLE t (a int, b text, c text COLLATE utf8mb4_bin) COLLATE=utf8mb4_general_ci",
+        )
+        .unwrap();
+        let expected = parse_create_table(
+            Dialect::MySQL,
+            "CREATE TABLE t (t.a int, t.b text COLLATE utf8mb4_general_ci, \
+             t.c text COLLATE utf8mb4_bin) COLLATE=utf8mb4_general_ci",
+        )
+        .unwrap();
+        assert_eq!(orig.normalize_create_table_columns(), expected);
+    }
 }
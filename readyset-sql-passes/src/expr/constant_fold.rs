@@ -7,7 +7,7 @@ use readyset_errors::{internal, ReadySetResult};
 /// Statically evaluate the given expression, returning a literal value representing the result.
 ///
 /// Returns an error if the expression evaluation failed, or if the expression is not constant
-fn const_eval(expr: &Expr, dialect: Dialect) -> ReadySetResult<Literal> {
+pub fn const_eval(expr: &Expr, dialect: Dialect) -> ReadySetResult<Literal> {
     #[derive(Clone)]
     struct ConstEvalLowerContext;
     impl LowerContext for ConstEvalLowerContext {
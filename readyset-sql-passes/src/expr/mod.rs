@@ -2,6 +2,7 @@ use dataflow_expression::Dialect;
 use nom_sql::analysis::visit_mut::VisitorMut;
 use nom_sql::{Expr, SelectStatement};
 
+pub use self::constant_fold::const_eval;
 use self::constant_fold::constant_fold_expr;
 use self::normalize_negation::normalize_negation;
 
@@ -48,7 +48,8 @@ fn negate_expr(expr: &mut Expr) -> bool {
                 | BinaryOperator::QuestionMarkPipe
                 | BinaryOperator::QuestionMarkAnd
                 | BinaryOperator::AtArrowRight
-                | BinaryOperator::AtArrowLeft => {
+                | BinaryOperator::AtArrowLeft
+                | BinaryOperator::AtAt => {
                     // Note we return true in this case to bypass the *op = ... above
                     *expr = Expr::UnaryOp {
                         op: UnaryOperator::Not,
@@ -0,0 +1,386 @@
+#![warn(clippy::panic)]
+//! Command-line tool for administering a ReadySet deployment, built on top of [`ReadySetHandle`].
+//!
+//! Each subcommand issues one or more requests to the current leader of the deployment and prints
+//! the result to stdout, as human-readable text by default or as JSON with `--json`.
+
+use std::collections::HashSet;
+
+use anyhow::{anyhow, bail};
+use clap::builder::NonEmptyStringValueParser;
+use clap::{Parser, Subcommand, ValueEnum};
+use nom_sql::Relation;
+use readyset_client::consensus::AuthorityType;
+use readyset_client::internal::{DomainIndex, ReplicaAddress};
+use readyset_client::recipe::ChangeList;
+use readyset_client::ReadySetHandle;
+use readyset_data::Dialect;
+use serde::Serialize;
+use url::Url;
+
+#[derive(Parser)]
+#[clap(name = "readyset-admin")]
+struct ReadySetAdmin {
+    #[clap(short, long, env("AUTHORITY_ADDRESS"), default_value("127.0.0.1:8500"))]
+    authority_address: String,
+
+    #[clap(long, env("AUTHORITY"), default_value("consul"), value_parser = ["consul"])]
+    authority: AuthorityType,
+
+    #[clap(short, long, env("DEPLOYMENT"), value_parser = NonEmptyStringValueParser::new())]
+    deployment: String,
+
+    /// Print results as JSON rather than as human-readable text
+    #[clap(long, global = true)]
+    json: bool,
+
+    /// The SQL dialect to parse and display queries with
+    #[clap(long, global = true, value_enum, default_value = "mysql")]
+    dialect: SqlDialect,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum SqlDialect {
+    Mysql,
+    Postgresql,
+}
+
+impl From<SqlDialect> for Dialect {
+    fn from(dialect: SqlDialect) -> Self {
+        match dialect {
+            SqlDialect::Mysql => Dialect::DEFAULT_MYSQL,
+            SqlDialect::Postgresql => Dialect::DEFAULT_POSTGRESQL,
+        }
+    }
+}
+
+impl From<SqlDialect> for nom_sql::Dialect {
+    fn from(dialect: SqlDialect) -> Self {
+        match dialect {
+            SqlDialect::Mysql => nom_sql::Dialect::MySQL,
+            SqlDialect::Postgresql => nom_sql::Dialect::PostgreSQL,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List all registered workers, and whether they are healthy
+    Workers,
+    /// List all domains, along with the workers running each of their shards and replicas
+    Domains,
+    /// List all cached queries
+    Views,
+    /// Cache a query
+    CreateCache {
+        /// The name to give the cache
+        #[clap(long)]
+        name: Option<String>,
+
+        /// Keep the cache from falling back to the upstream database if a read fails
+        #[clap(long)]
+        always: bool,
+
+        /// The SELECT statement to cache
+        query: String,
+    },
+    /// Remove a cached query
+    DropCache {
+        /// The name of the cache to drop, optionally schema-qualified (`schema.name`)
+        name: String,
+    },
+    /// Move all domains off of the given worker and onto the other healthy workers in the
+    /// deployment, so it can be taken out of service. Domains containing base tables can't be
+    /// moved, and are reported instead.
+    DrainWorker {
+        /// The URI of the worker to drain, as reported by the `workers` subcommand
+        worker: Url,
+    },
+    /// Start a canary of a new version of ReadySet, moving one replica of each shard of the given
+    /// domains onto the workers running that version, and all other domains off of those workers.
+    /// Domains containing base tables can't be canaried.
+    StartCanary {
+        /// The version label of the workers to canary the domains on, as passed to their
+        /// `--version-label`
+        version: String,
+
+        /// The indices of the domains to canary, as reported by the `domains` subcommand
+        #[clap(required = true)]
+        domains: Vec<usize>,
+    },
+    /// End the canary in progress, without moving any domains
+    EndCanary,
+    /// Compare the latency and reader key counts of the canaried replica of each domain shard
+    /// with the stable replicas of the same shard
+    CanaryReport,
+    /// Print a dump of the leader's metrics
+    Metrics,
+    /// Print the replication offsets of the schema and of each base table
+    ReplicationOffsets,
+}
+
+#[derive(Serialize)]
+struct WorkerStatus {
+    worker: Url,
+    healthy: bool,
+}
+
+fn print<T>(json: bool, value: &T, text: impl FnOnce(&T)) -> anyhow::Result<()>
+where
+    T: Serialize,
+{
+    if json {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else {
+        text(value);
+    }
+    Ok(())
+}
+
+fn parse_relation(name: &str) -> Relation {
+    match name.split_once('.') {
+        Some((schema, name)) => Relation {
+            schema: Some(schema.into()),
+            name: name.into(),
+        },
+        None => name.into(),
+    }
+}
+
+impl ReadySetAdmin {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let authority = self
+            .authority
+            .to_authority(&self.authority_address, &self.deployment)
+            .await;
+
+        let mut handle: ReadySetHandle = ReadySetHandle::new(authority).await;
+        handle.ready().await?;
+
+        let json = self.json;
+        let dialect = self.dialect;
+        match self.command {
+            Command::Workers => {
+                let healthy = handle
+                    .healthy_workers()
+                    .await?
+                    .into_iter()
+                    .collect::<HashSet<_>>();
+                let workers = handle
+                    .workers()
+                    .await?
+                    .into_iter()
+                    .map(|worker| WorkerStatus {
+                        healthy: healthy.contains(&worker),
+                        worker,
+                    })
+                    .collect::<Vec<_>>();
+                print(json, &workers, |workers| {
+                    for w in workers {
+                        let status = if w.healthy { "healthy" } else { "unhealthy" };
+                        println!("{}\t{}", w.worker, status);
+                    }
+                })?;
+            }
+            Command::Domains => {
+                let domains = handle.domain_placement().await?;
+                print(json, &domains, |domains| {
+                    for domain in domains {
+                        let mut kind = vec![];
+                        if domain.has_base_table {
+                            kind.push("base table");
+                        }
+                        if domain.has_reader {
+                            kind.push("reader");
+                        }
+                        println!(
+                            "domain {} ({} nodes{}{})",
+                            domain.domain_index.index(),
+                            domain.nodes.len(),
+                            if kind.is_empty() { "" } else { ", " },
+                            kind.join(", ")
+                        );
+                        for (shard, replicas) in domain.shards.iter().enumerate() {
+                            for (replica, placement) in replicas.iter().enumerate() {
+                                println!(
+                                    "  {}.{}\t{}{}",
+                                    shard,
+                                    replica,
+                                    placement.worker,
+                                    if placement.pinned { " (pinned)" } else { "" }
+                                );
+                            }
+                        }
+                    }
+                })?;
+            }
+            Command::Views => {
+                let views = handle
+                    .verbose_views()
+                    .await?
+                    .into_iter()
+                    .map(|(name, (query, always))| {
+                        (
+                            name.display(dialect.into()).to_string(),
+                            query.display(dialect.into()).to_string(),
+                            always,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                print(json, &views, |views| {
+                    for (name, query, always) in views {
+                        let always = if *always { " (always)" } else { "" };
+                        println!("{name}{always}\t{query}");
+                    }
+                })?;
+            }
+            Command::CreateCache {
+                name,
+                always,
+                query,
+            } => {
+                let name = name.map(|n| format!("{n} ")).unwrap_or_default();
+                let always = if always { "ALWAYS " } else { "" };
+                let changes = ChangeList::from_str(
+                    format!("CREATE CACHE {always}{name}FROM {query}"),
+                    dialect.into(),
+                )?;
+                handle.extend_recipe(changes).await?;
+                println!("Cache created");
+            }
+            Command::DropCache { name } => {
+                let name = parse_relation(&name);
+                if !handle.views().await?.contains_key(&name) {
+                    bail!("Cache {} does not exist", name.display_unquoted());
+                }
+                handle.remove_query(&name).await?;
+                println!("Cache {} dropped", name.display_unquoted());
+            }
+            Command::DrainWorker { worker } => {
+                let targets = handle
+                    .healthy_workers()
+                    .await?
+                    .into_iter()
+                    .filter(|w| *w != worker)
+                    .collect::<Vec<_>>();
+                if targets.is_empty() {
+                    return Err(anyhow!("No other healthy workers to move domains to"));
+                }
+
+                let mut moved = 0;
+                let mut unmovable = vec![];
+                for domain in handle.domain_placement().await? {
+                    for (shard, replicas) in domain.shards.iter().enumerate() {
+                        for (replica, placement) in replicas.iter().enumerate() {
+                            if placement.worker != worker {
+                                continue;
+                            }
+                            let address = ReplicaAddress {
+                                domain_index: domain.domain_index,
+                                shard,
+                                replica,
+                            };
+                            if domain.has_base_table {
+                                unmovable.push(address);
+                                continue;
+                            }
+                            // Spread the domains evenly across the remaining workers
+                            let target = &targets[moved % targets.len()];
+                            moved += 1;
+                            handle.move_domain(address, target.clone()).await?;
+                            println!("Moved {address} to {target}");
+                        }
+                    }
+                }
+
+                for address in &unmovable {
+                    eprintln!("Domain {address} contains a base table and cannot be moved");
+                }
+                if !unmovable.is_empty() {
+                    bail!("Worker {} could not be fully drained", worker);
+                }
+            }
+            Command::StartCanary { version, domains } => {
+                let num_domains = domains.len();
+                handle
+                    .start_canary(
+                        version.clone(),
+                        domains.into_iter().map(DomainIndex::from).collect(),
+                    )
+                    .await?;
+                println!("Canarying {num_domains} domains on version {version}");
+            }
+            Command::EndCanary => {
+                handle.end_canary().await?;
+                println!("Canary ended");
+            }
+            Command::CanaryReport => {
+                let report = handle.canary_report().await?;
+                print(json, &report, |report| {
+                    let Some(version) = &report.version else {
+                        println!("No canary in progress");
+                        return;
+                    };
+                    println!("canary of version {version}");
+                    let ratio =
+                        |r: Option<f64>| r.map_or_else(|| "-".to_owned(), |r| format!("{r:.2}"));
+                    for shard in &report.shards {
+                        println!(
+                            "  {}\t{}\tlatency ratio {}\tkey divergence {}{}",
+                            shard.replica_address,
+                            shard.worker,
+                            ratio(shard.latency_ratio()),
+                            ratio(shard.key_divergence()),
+                            if shard.is_divergent() {
+                                " (divergent)"
+                            } else {
+                                ""
+                            }
+                        );
+                    }
+                })?;
+            }
+            Command::Metrics => {
+                let metrics = handle.metrics_dump().await?;
+                print(json, &metrics, |metrics| {
+                    let mut names = metrics.metrics.keys().collect::<Vec<_>>();
+                    names.sort();
+                    for name in names {
+                        for metric in &metrics.metrics[name] {
+                            println!("{name}{:?}\t{:?}", metric.labels, metric.value);
+                        }
+                    }
+                })?;
+            }
+            Command::ReplicationOffsets => {
+                let offsets = handle.replication_offsets().await?;
+                print(json, &offsets, |offsets| {
+                    match &offsets.schema {
+                        Some(offset) => println!("schema\t{offset}"),
+                        None => println!("schema\t-"),
+                    }
+                    let mut tables = offsets.tables.iter().collect::<Vec<_>>();
+                    tables.sort_by(|(t1, _), (t2, _)| t1.cmp(t2));
+                    for (table, offset) in tables {
+                        let table = table.display_unquoted();
+                        match offset {
+                            Some(offset) => println!("{table}\t{offset}"),
+                            None => println!("{table}\t- (not yet snapshotted)"),
+                        }
+                    }
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let admin = ReadySetAdmin::parse();
+    admin.run().await
+}
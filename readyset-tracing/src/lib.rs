@@ -24,6 +24,7 @@ use opentelemetry::sdk::trace::{Sampler, Tracer};
 use opentelemetry::sdk::Resource;
 use opentelemetry::KeyValue;
 use opentelemetry_otlp::WithExportConfig;
+use readyset_util::redacted::{set_redaction_mode, RedactionMode};
 use tracing::Subscriber;
 use tracing_opentelemetry::OpenTelemetryLayer;
 use tracing_subscriber::filter::{self, ParseError};
@@ -87,6 +88,22 @@ pub struct Options {
     /// <deployment-name>_statements.log.
     #[clap(long, env = "STATEMENT_LOG_PATH", requires = "statement_logging")]
     pub statement_log_path: Option<String>,
+
+    /// Never include potentially sensitive data, such as the values of literals in queries or of
+    /// the parameters of prepared statements, in any logs, metrics, or error messages. Can't be
+    /// combined with statement logging, since statement logs always contain queries in full.
+    #[clap(
+        long,
+        env = "STRICT_REDACTION",
+        conflicts_with_all = ["statement_logging", "log_parameters"]
+    )]
+    pub strict_redaction: bool,
+
+    /// Include the values of literals in queries and of the parameters of prepared statements in
+    /// logs (including the slow query log and the statement log), for debugging. Never enable
+    /// this in production.
+    #[clap(long, env = "LOG_PARAMETERS", hide = true)]
+    pub log_parameters: bool,
 }
 
 impl Default for Options {
@@ -98,6 +115,8 @@ impl Default for Options {
             tracing_sample_percent: Percent(0.01),
             statement_logging: false,
             statement_log_path: None,
+            strict_redaction: false,
+            log_parameters: false,
         }
     }
 }
@@ -260,6 +279,7 @@ impl Options {
     /// }
     /// ```
     pub fn init(&self, service_name: &str, deployment: &str) -> Result<(), Error> {
+        set_redaction_mode(self.redaction_mode());
         if self.tracing_host.is_some() {
             self.init_logging_and_tracing(service_name, deployment)
         } else {
@@ -267,6 +287,17 @@ impl Options {
         }
     }
 
+    /// Returns the [`RedactionMode`] configured by these options
+    pub fn redaction_mode(&self) -> RedactionMode {
+        if self.strict_redaction {
+            RedactionMode::Strict
+        } else if self.log_parameters {
+            RedactionMode::LogParameters
+        } else {
+            RedactionMode::Default
+        }
+    }
+
     // Returns the provided `statement_log_path` or a default filename.
     fn statement_log_path_or_default(&self, deployment: &str) -> String {
         match self.statement_log_path {
@@ -0,0 +1,153 @@
+//! Detection of the CPU and memory limits imposed on the current process by Linux control groups
+//! (cgroups), eg when running inside of a container.
+//!
+//! Both cgroup v2 (the unified hierarchy, mounted at `/sys/fs/cgroup`) and cgroup v1 (with the
+//! `memory` and `cpu` controllers mounted at `/sys/fs/cgroup/memory` and `/sys/fs/cgroup/cpu`
+//! respectively) are supported. On platforms other than Linux, or if no cgroup limits are set,
+//! all limits are reported as [`None`].
+
+use std::fs;
+use std::path::Path;
+
+/// Any cgroup v1 memory limit at or above this value is treated as unlimited. The kernel reports
+/// "no limit" as `PAGE_COUNTER_MAX` rounded down to the page size, which is some very large number
+/// that differs across architectures and page sizes.
+const CGROUP_V1_UNLIMITED_MEMORY: u64 = 1 << 62;
+
+/// Resource limits imposed on the current process by cgroups
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CgroupLimits {
+    /// The maximum amount of memory, in bytes, that the process may use
+    pub memory_bytes: Option<u64>,
+    /// The maximum number of CPUs worth of time the process may use, which may be fractional (eg
+    /// `1.5` for a quota of 150ms per 100ms period)
+    pub cpus: Option<f64>,
+}
+
+impl CgroupLimits {
+    /// Detect the cgroup limits for the current process, falling back to no limits if the cgroup
+    /// filesystem is not available
+    pub fn detect() -> Self {
+        Self::detect_at(Path::new("/sys/fs/cgroup"))
+    }
+
+    /// Detect the cgroup limits from a cgroup filesystem mounted at the given root
+    fn detect_at(root: &Path) -> Self {
+        let read = |path: &str| fs::read_to_string(root.join(path)).ok();
+
+        if let Some(memory_max) = read("memory.max") {
+            // cgroup v2
+            return Self {
+                memory_bytes: parse_v2_memory_max(&memory_max),
+                cpus: read("cpu.max").and_then(|s| parse_v2_cpu_max(&s)),
+            };
+        }
+
+        // cgroup v1
+        Self {
+            memory_bytes: read("memory/memory.limit_in_bytes")
+                .and_then(|s| parse_v1_memory_limit(&s)),
+            cpus: read("cpu/cpu.cfs_quota_us")
+                .zip(read("cpu/cpu.cfs_period_us"))
+                .and_then(|(quota, period)| parse_v1_cpu_quota(&quota, &period)),
+        }
+    }
+
+    /// Returns the number of CPUs available to the current process, taking into account both the
+    /// number of CPUs on the host and the cgroup CPU limit (rounded up to the next whole CPU)
+    pub fn available_cpus(&self) -> usize {
+        let host_cpus = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        match self.cpus {
+            Some(cpus) => host_cpus.min(cpus.ceil() as usize).max(1),
+            None => host_cpus,
+        }
+    }
+}
+
+/// Parse the contents of a cgroup v2 `memory.max` file, which is either a number of bytes or the
+/// string `max`
+fn parse_v2_memory_max(s: &str) -> Option<u64> {
+    match s.trim() {
+        "max" => None,
+        limit => limit.parse().ok(),
+    }
+}
+
+/// Parse the contents of a cgroup v2 `cpu.max` file, which has the format `<quota> <period>`,
+/// where the quota is either a number of microseconds or the string `max`
+fn parse_v2_cpu_max(s: &str) -> Option<f64> {
+    let (quota, period) = s.trim().split_once(' ')?;
+    if quota == "max" {
+        return None;
+    }
+    cpus_from_quota(quota.parse().ok()?, period.parse().ok()?)
+}
+
+/// Parse the contents of a cgroup v1 `memory.limit_in_bytes` file
+fn parse_v1_memory_limit(s: &str) -> Option<u64> {
+    s.trim()
+        .parse()
+        .ok()
+        .filter(|limit| *limit < CGROUP_V1_UNLIMITED_MEMORY)
+}
+
+/// Parse the contents of the cgroup v1 `cpu.cfs_quota_us` and `cpu.cfs_period_us` files. A quota
+/// of `-1` means no limit.
+fn parse_v1_cpu_quota(quota: &str, period: &str) -> Option<f64> {
+    let quota: i64 = quota.trim().parse().ok()?;
+    if quota < 0 {
+        return None;
+    }
+    cpus_from_quota(quota as u64, period.trim().parse().ok()?)
+}
+
+fn cpus_from_quota(quota: u64, period: u64) -> Option<f64> {
+    if quota == 0 || period == 0 {
+        return None;
+    }
+    Some(quota as f64 / period as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v2_limits() {
+        assert_eq!(parse_v2_memory_max("max\n"), None);
+        assert_eq!(parse_v2_memory_max("1073741824\n"), Some(1 << 30));
+        assert_eq!(parse_v2_cpu_max("max 100000\n"), None);
+        assert_eq!(parse_v2_cpu_max("150000 100000\n"), Some(1.5));
+    }
+
+    #[test]
+    fn v1_limits() {
+        assert_eq!(parse_v1_memory_limit("9223372036854771712\n"), None);
+        assert_eq!(parse_v1_memory_limit("536870912\n"), Some(1 << 29));
+        assert_eq!(parse_v1_cpu_quota("-1\n", "100000\n"), None);
+        assert_eq!(parse_v1_cpu_quota("200000\n", "100000\n"), Some(2.0));
+    }
+
+    #[test]
+    fn available_cpus_respects_limit() {
+        let limits = CgroupLimits {
+            memory_bytes: None,
+            cpus: Some(0.5),
+        };
+        assert_eq!(limits.available_cpus(), 1);
+        assert_eq!(
+            CgroupLimits::default().available_cpus(),
+            std::thread::available_parallelism().unwrap().get()
+        );
+    }
+
+    #[test]
+    fn detect_missing_cgroup_fs() {
+        assert_eq!(
+            CgroupLimits::detect_at(Path::new("/nonexistent/cgroup")),
+            CgroupLimits::default()
+        );
+    }
+}
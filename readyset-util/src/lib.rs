@@ -8,6 +8,7 @@ use std::collections::{BTreeMap, HashMap};
 use std::hash::Hash;
 
 pub mod arbitrary;
+pub mod cgroup;
 pub mod display;
 pub mod fmt;
 pub mod futures;
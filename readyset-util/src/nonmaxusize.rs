@@ -25,6 +25,16 @@ impl NonMaxUsize {
         // Safe, since in valid range
         unsafe { NonMaxUsize(0) }
     }
+
+    /// Create a new [`NonMaxUsize`] with the given value, or [`None`] if it is [`usize::MAX`]
+    pub fn new(val: usize) -> Option<Self> {
+        if val == usize::MAX {
+            None
+        } else {
+            // Safe, since we tested it is not the invalid value
+            Some(unsafe { NonMaxUsize(val) })
+        }
+    }
 }
 
 impl Deref for NonMaxUsize {
@@ -5,6 +5,7 @@ use std::convert::Infallible;
 use std::fmt::{Debug, Display};
 use std::ops::Deref;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
 
 use serde::{Deserialize, Serialize};
 
@@ -14,23 +15,72 @@ pub const REDACT_SENSITIVE: bool = false;
 
 /// Constant which is set to `true` if the `redact_sensitive` feature is enabled
 #[cfg(feature = "redact_sensitive")]
-pub const REDACT_SENSITIVE: bool = false;
+pub const REDACT_SENSITIVE: bool = true;
+
+/// How much potentially sensitive data, such as the values of literals in queries and of the
+/// parameters of prepared statements, may be included in logs, metrics, and error messages.
+///
+/// This is set once for the whole process on startup with [`set_redaction_mode`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RedactionMode {
+    /// Values wrapped in [`Sensitive`] are displayed (unless the `redact_sensitive` feature is
+    /// enabled), but literals are anonymized in queries recorded in logs and metrics, and the
+    /// parameters of prepared statements are never logged
+    #[default]
+    Default = 0,
+    /// Sensitive data is never included in any logs, metrics, or error messages: values wrapped
+    /// in [`Sensitive`] are always redacted, and literals are always anonymized. This is always
+    /// the mode if the `redact_sensitive` feature is enabled
+    Strict = 1,
+    /// The values of literals and parameters are included in logs, for debugging. This should
+    /// only ever be enabled during development
+    LogParameters = 2,
+}
+
+static REDACTION_MODE: AtomicU8 = AtomicU8::new(RedactionMode::Default as u8);
+
+/// Set the [`RedactionMode`] for the whole process
+pub fn set_redaction_mode(mode: RedactionMode) {
+    REDACTION_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// Returns the current [`RedactionMode`] for the process
+pub fn redaction_mode() -> RedactionMode {
+    if REDACT_SENSITIVE {
+        return RedactionMode::Strict;
+    }
+    match REDACTION_MODE.load(Ordering::Relaxed) {
+        1 => RedactionMode::Strict,
+        2 => RedactionMode::LogParameters,
+        _ => RedactionMode::Default,
+    }
+}
+
+/// Returns `true` if values wrapped in [`Sensitive`] should be redacted
+pub fn redact_sensitive() -> bool {
+    redaction_mode() == RedactionMode::Strict
+}
+
+/// Returns `true` if the values of literals and parameters may be included in logs
+pub fn log_parameters() -> bool {
+    redaction_mode() == RedactionMode::LogParameters
+}
 
-/// Wraps a type that implements Display and Debug, overriding both implementations if the
-/// `redact_literals` feature is enabled
+/// Wraps a type that implements Display and Debug, overriding both implementations if
+/// [`redact_sensitive`] returns `true`
 pub struct Sensitive<'a, T: ?Sized>(pub &'a T);
 
 impl<'a, T> Display for Sensitive<'a, T>
 where
     T: ?Sized + Display,
 {
-    #[cfg(not(feature = "redact_sensitive"))]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
-    }
-    #[cfg(feature = "redact_sensitive")]
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "<redacted>")
+        if redact_sensitive() {
+            write!(f, "<redacted>")
+        } else {
+            write!(f, "{}", self.0)
+        }
     }
 }
 
@@ -38,13 +88,12 @@ impl<'a, T> Debug for Sensitive<'a, T>
 where
     T: ?Sized + Debug,
 {
-    #[cfg(not(feature = "redact_sensitive"))]
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.0)
-    }
-    #[cfg(feature = "redact_sensitive")]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "<redacted>")
+        if redact_sensitive() {
+            write!(f, "<redacted>")
+        } else {
+            write!(f, "{:?}", self.0)
+        }
     }
 }
 
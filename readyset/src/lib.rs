@@ -1,13 +1,15 @@
 #![deny(macro_use_extern_crate)]
 #![feature(let_chains)]
 
+mod listeners;
 pub mod mysql;
 pub mod psql;
+mod query_event_export;
 mod query_logger;
+mod users;
 
 use std::collections::HashMap;
 use std::fs::remove_dir_all;
-use std::io;
 use std::marker::Send;
 use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
@@ -15,39 +17,53 @@ use std::str::FromStr;
 use std::sync::atomic::AtomicUsize;
 use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{io, iter};
 
-use anyhow::{anyhow, bail, ensure};
+use anyhow::{anyhow, bail, ensure, Context};
 use async_trait::async_trait;
 use clap::builder::NonEmptyStringValueParser;
 use clap::{ArgGroup, Parser, ValueEnum};
+use database_utils::secrets::SecretSource;
 use database_utils::{DatabaseType, DatabaseURL, UpstreamConfig};
 use failpoint_macros::set_failpoint;
-use futures_util::future::FutureExt;
+use futures_util::future::{self, FutureExt};
 use futures_util::stream::StreamExt;
 use health_reporter::{HealthReporter as AdapterHealthReporter, State as AdapterState};
 use metrics_exporter_prometheus::PrometheusBuilder;
 use nom_sql::Relation;
 use readyset_adapter::backend::noria_connector::{NoriaConnector, ReadBehavior};
 use readyset_adapter::backend::MigrationMode;
+use readyset_adapter::cache_experiments::CacheExperiments;
+use readyset_adapter::connection_registry::ConnectionRegistry;
 use readyset_adapter::fallback_cache::{
     DiskModeledCache, EvictionModeledCache, FallbackCache, SimpleFallbackCache,
 };
 use readyset_adapter::http_router::NoriaAdapterHttpRouter;
 use readyset_adapter::migration_handler::MigrationHandler;
+use readyset_adapter::migration_queue::MigrationQueue;
+use readyset_adapter::privileges::{PrivilegeSynchronizer, SharedSelectPrivileges};
 use readyset_adapter::proxied_queries_reporter::ProxiedQueriesReporter;
+use readyset_adapter::query_pins::QueryPinLists;
 use readyset_adapter::query_status_cache::{MigrationStyle, QueryStatusCache};
+use readyset_adapter::read_replicas::ReadReplicas;
+use readyset_adapter::schema_snapshots::{SchemaSnapshots, SchemaSnapshotter};
+use readyset_adapter::upstream_retry::{TransientErrorKind, UpstreamRetryPolicy};
 use readyset_adapter::views_synchronizer::ViewsSynchronizer;
-use readyset_adapter::{Backend, BackendBuilder, QueryHandler, UpstreamDatabase};
-use readyset_client::consensus::{AuthorityControl, AuthorityType, ConsulAuthority};
+use readyset_adapter::{Backend, BackendBuilder, QueryHandler, ResultStreaming, UpstreamDatabase};
+use readyset_client::consensus::{
+    Authority, AuthorityControl, AuthorityOptions, AuthorityType, ConsulAuthority, LocalAuthority,
+    LocalAuthorityStore,
+};
 #[cfg(feature = "failure_injection")]
 use readyset_client::failpoints;
 use readyset_client::metrics::recorded;
-use readyset_client::{ReadySetHandle, ViewCreateRequest};
+use readyset_client::{ReadySetHandle, ResultLimits, ViewCreateRequest};
 use readyset_dataflow::Readers;
 use readyset_errors::ReadySetError;
 use readyset_server::metrics::{CompositeMetricsRecorder, MetricsRecorder};
 use readyset_server::worker::readers::{retry_misses, Ack, BlockingRead, ReadRequestHandler};
 use readyset_telemetry_reporter::{TelemetryBuilder, TelemetryEvent, TelemetryInitializer};
+use readyset_util::cgroup::CgroupLimits;
 use readyset_util::futures::abort_on_panic;
 use readyset_util::redacted::RedactedString;
 use readyset_util::shutdown;
@@ -59,6 +75,9 @@ use tokio_stream::wrappers::TcpListenerStream;
 use tracing::{debug, debug_span, error, info, info_span, span, warn, Level};
 use tracing_futures::Instrument;
 
+use crate::listeners::ListenerProfile;
+use crate::query_event_export::QueryEventSink;
+
 // How frequently to try to establish an http registration for the first time or if the last tick
 // failed and we need to establish a new one
 const REGISTER_HTTP_INIT_INTERVAL: Duration = Duration::from_secs(2);
@@ -164,12 +183,36 @@ pub struct Options {
     #[clap(long, short = 'a', env = "LISTEN_ADDRESS")]
     address: Option<SocketAddr>,
 
+    /// Listen with `SO_REUSEPORT` set, so that another adapter can listen on the same address
+    /// while this one is still running. To replace an adapter without resetting any client
+    /// connections, start the new adapter with this option set, then send the old one SIGTERM with
+    /// `--connection-drain-timeout-secs` set.
+    ///
+    /// Connections which the kernel has queued for the old adapter but which it hasn't yet
+    /// accepted when it stops listening are reset, unless the `net.ipv4.tcp_migrate_req` sysctl
+    /// (Linux 5.14 and later) is enabled, in which case they're handed off to the new adapter.
+    #[clap(long, env = "REUSE_PORT")]
+    reuse_port: bool,
+
+    /// An additional address to listen for client connections on, optionally followed by
+    /// comma-separated options which override the adapter's for connections accepted on it:
+    /// `upstream=true|false`, `users=NAME[:NAME...]`, and `reads=blocking|non-blocking`. For
+    /// example, `0.0.0.0:3308,upstream=false` listens on port 3308 for connections which are only
+    /// ever served from caches. Can be passed multiple times, or separated by `;` in the
+    /// environment variable.
+    #[clap(
+        long = "extra-listener",
+        env = "EXTRA_LISTENERS",
+        value_delimiter = ';'
+    )]
+    extra_listeners: Vec<ListenerProfile>,
+
     /// ReadySet deployment ID to attach to
     #[clap(long, env = "DEPLOYMENT", value_parser = NonEmptyStringValueParser::new())]
     deployment: String,
 
     /// Database engine protocol to emulate. If omitted, will be inferred from the
-    /// `upstream-db-url`
+    /// `upstream-db-url`. Required if the upstream URL is passed with `--upstream-db-url-secret`
     #[clap(
         long,
         env = "DATABASE_TYPE",
@@ -179,6 +222,9 @@ pub struct Options {
     pub database_type: Option<DatabaseType>,
 
     /// Run ReadySet in standalone mode, running a readyset-server instance within this adapter.
+    ///
+    /// Combine with `--authority local` to coordinate the adapter and server entirely in memory,
+    /// without persisting any authority state.
     #[clap(long, env = "STANDALONE", conflicts_with = "embedded_readers")]
     standalone: bool,
 
@@ -192,6 +238,12 @@ pub struct Options {
     authority: AuthorityType,
 
     /// Authority uri
+    ///
+    /// With `--authority consul`, this may include a scheme and a Consul ACL token, as
+    /// `https://<token>@<address>:<port>`.
+    ///
+    /// With `--authority kubernetes`, this is the URL of the Kubernetes API server, and defaults
+    /// to the API server of the cluster ReadySet is running in.
     // NOTE: `authority_address` should come after `authority` for clap to set default values
     // properly
     #[clap(
@@ -199,10 +251,15 @@ pub struct Options {
         env = "AUTHORITY_ADDRESS",
         default_value_if("authority", "standalone", Some(".")),
         default_value_if("authority", "consul", Some("127.0.0.1:8500")),
+        default_value_if("authority", "local", Some("")),
+        default_value_if("authority", "kubernetes", Some("")),
         required = false
     )]
     authority_address: String,
 
+    #[clap(flatten)]
+    authority_options: AuthorityOptions,
+
     /// Log slow queries (> 5ms)
     #[clap(long, hide = true)]
     log_slow: bool,
@@ -211,6 +268,39 @@ pub struct Options {
     #[clap(long, env = "ALLOW_UNAUTHENTICATED_CONNECTIONS")]
     allow_unauthenticated_connections: bool,
 
+    /// Enforce the upstream database's SELECT privileges on queries served by ReadySet, by
+    /// periodically reading the upstream database's grant tables. Queries which read from a table
+    /// the authenticated user can't select from in the upstream database are proxied to the
+    /// upstream database instead. The user in --upstream-db-url must be able to read the grant
+    /// tables (the `mysql` database, for MySQL)
+    #[clap(
+        long,
+        env = "ENFORCE_UPSTREAM_PRIVILEGES",
+        conflicts_with = "allow_unauthenticated_connections"
+    )]
+    enforce_upstream_privileges: bool,
+
+    /// How often (in seconds) to re-read the upstream database's privileges, if
+    /// --enforce-upstream-privileges is passed
+    #[clap(long, env = "PRIVILEGES_REFRESH_INTERVAL", default_value = "30")]
+    privileges_refresh_interval: u64,
+
+    /// If set, how often (in seconds) to take a snapshot of the upstream database's schema.
+    /// Snapshots are only recorded when the schema has changed, and can be retrieved along with a
+    /// diff between each pair of subsequent snapshots from the `/schema-snapshots` endpoint on
+    /// --metrics-address, to correlate unsupported queries with schema changes that happened while
+    /// the adapter was running.
+    #[clap(long, env = "SCHEMA_SNAPSHOT_INTERVAL")]
+    schema_snapshot_interval: Option<u64>,
+
+    /// Anonymize table and column names in the schema snapshots and the lists of allowed and
+    /// denied queries served from --metrics-address, by replacing each identifier with a hash
+    /// of that identifier. Since the hashes are consistent across all of these, the anonymized
+    /// queries can still be matched up with the anonymized schema, eg when sharing them with
+    /// ReadySet support.
+    #[clap(long, env = "ANONYMIZE_IDENTIFIERS")]
+    anonymize_identifiers: bool,
+
     /// Specify the migration mode for ReadySet to use
     #[clap(long, env = "QUERY_CACHING", default_value = "explicit")]
     query_caching: MigrationStyle,
@@ -232,7 +322,15 @@ pub struct Options {
     #[clap(long, env = "MIGRATION_TASK_INTERVAL", default_value = "20000")]
     migration_task_interval: u64,
 
-    /// IP:PORT to host endpoint for scraping metrics from the adapter.
+    /// The maximum number of queries migrated in the request path at once. Connections executing
+    /// a query which is already being migrated by another connection wait for that migration
+    /// rather than starting their own.
+    #[clap(long, env = "MAX_CONCURRENT_MIGRATIONS", default_value = "4")]
+    max_concurrent_migrations: usize,
+
+    /// IP:PORT to listen on for HTTP requests to the adapter, separately from the SQL listen
+    /// address. Serves Prometheus metrics at `/metrics` (if `--prometheus-metrics` is enabled),
+    /// and the health of the adapter at `/health`.
     #[clap(long, env = "METRICS_ADDRESS", default_value = "0.0.0.0:6034")]
     metrics_address: SocketAddr,
 
@@ -246,6 +344,20 @@ pub struct Options {
     #[clap(long, env = "ALLOWED_PASSWORD", short = 'p')]
     password: Option<RedactedString>,
 
+    /// Read the password to authenticate database connections with from a secret, rather than
+    /// passing it with --password. See --upstream-db-url-secret for the supported secret sources.
+    /// The secret is only read once, at startup
+    #[clap(long, env = "ALLOWED_PASSWORD_SECRET", conflicts_with = "password")]
+    password_secret: Option<SecretSource>,
+
+    /// Path to a TOML (if the file name ends in `.toml`) or JSON file mapping the usernames of all
+    /// users allowed to connect to the adapter to their password, and optionally to an
+    /// `upstream_db_url` with which to connect to the upstream database once a client has
+    /// authenticated as that user. If passed, --username and --password are only used to allow one
+    /// additional user. Ignored if --allow-unauthenticated-connections is passed
+    #[clap(long, env = "USERS_FILE")]
+    users_file: Option<PathBuf>,
+
     /// Enable recording and exposing Prometheus metrics
     #[clap(long, env = "PROMETHEUS_METRICS")]
     prometheus_metrics: bool,
@@ -261,6 +373,65 @@ pub struct Options {
     #[clap(long, hide = true, env = "QUERY_LOG_AD_HOC", requires = "query_log")]
     query_log_ad_hoc: bool,
 
+    /// The maximum number of distinct queries to record per-query metrics for in the query log.
+    /// Metrics for queries beyond this limit are recorded with a `query` label of `<overflow>`,
+    /// unless a query becomes more frequent than the least frequent query being recorded, in which
+    /// case it replaces that query. Metrics already recorded for a replaced query are not removed.
+    #[clap(long, env = "QUERY_LOG_MAX_QUERIES", default_value = "1000")]
+    query_log_max_queries: usize,
+
+    /// The fraction of query executions, between 0 and 1, to record in the per-query histograms of
+    /// the query log. Per-query counters are always recorded for every execution.
+    #[clap(
+        long,
+        env = "QUERY_LOG_SAMPLE_RATE",
+        default_value = "1.0",
+        value_parser = parse_sample_rate
+    )]
+    query_log_sample_rate: f64,
+
+    /// Label the per-query metrics recorded in the query log with the user each query's connection
+    /// is authenticated as
+    #[clap(long, env = "QUERY_LOG_USER_LABELS", requires = "query_log")]
+    query_log_user_labels: bool,
+
+    /// Label the per-query metrics recorded in the query log with the name of the application
+    /// which opened each query's connection, as reported in the `application_name` startup
+    /// parameter (PostgreSQL) or the `program_name` connection attribute (MySQL)
+    #[clap(long, env = "QUERY_LOG_APPLICATION_LABELS", requires = "query_log")]
+    query_log_application_labels: bool,
+
+    /// The maximum number of distinct users and applications to label the per-query metrics in the
+    /// query log with. Metrics for any other users or applications are labeled `<overflow>`.
+    #[clap(long, env = "QUERY_LOG_MAX_LABEL_VALUES", default_value = "100")]
+    query_log_max_label_values: usize,
+
+    /// Export every query execution recorded in the query log, with the literals in each query
+    /// anonymized, as a JSON log record to this OTLP/HTTP logs endpoint (eg
+    /// `http://localhost:4318/v1/logs`)
+    #[clap(long, env = "QUERY_LOG_EXPORT_OTLP_ENDPOINT", requires = "query_log")]
+    query_log_export_otlp_endpoint: Option<reqwest::Url>,
+
+    /// Export every query execution recorded in the query log, with the literals in each query
+    /// anonymized, as a JSON message to a Kafka topic on these (comma-separated) brokers
+    #[cfg(feature = "kafka")]
+    #[clap(
+        long,
+        env = "QUERY_LOG_EXPORT_KAFKA_BROKERS",
+        requires_all = ["query_log", "query_log_export_kafka_topic"],
+        conflicts_with = "query_log_export_otlp_endpoint"
+    )]
+    query_log_export_kafka_brokers: Option<String>,
+
+    /// The Kafka topic to export query executions to. See `--query-log-export-kafka-brokers`.
+    #[cfg(feature = "kafka")]
+    #[clap(
+        long,
+        env = "QUERY_LOG_EXPORT_KAFKA_TOPIC",
+        default_value = "readyset-query-log"
+    )]
+    query_log_export_kafka_topic: String,
+
     /// Use the AWS EC2 metadata service to determine the external address of this noria adapter's
     /// http endpoint.
     #[clap(long)]
@@ -290,6 +461,18 @@ pub struct Options {
     #[clap(long, env = "UNSUPPORTED_SET_MODE", default_value = "error")]
     unsupported_set_mode: UnsupportedSetMode,
 
+    /// Path to a file containing queries, one per line, which should always be served by ReadySet
+    /// (migrating them if necessary) regardless of `--query-caching`. The file is reloaded
+    /// automatically when it changes.
+    #[clap(long, env = "QUERY_ALLOW_LIST_FILE")]
+    query_allow_list_file: Option<PathBuf>,
+
+    /// Path to a file containing queries, one per line, which should never be migrated and always
+    /// be proxied to the upstream database. Takes precedence over `--query-allow-list-file`. The
+    /// file is reloaded automatically when it changes.
+    #[clap(long, env = "QUERY_DENY_LIST_FILE")]
+    query_deny_list_file: Option<PathBuf>,
+
     // TODO(DAN): require explicit migrations
     /// Specifies the polling interval in seconds for requesting views from the Leader.
     #[clap(long, env = "OUTPUTS_POLLING_INTERVAL", default_value = "300")]
@@ -328,6 +511,110 @@ pub struct Options {
     )]
     fallback_recovery_seconds: u64,
 
+    /// The maximum amount of time, in milliseconds, that any single query or prepared statement
+    /// execution may take, including both reads from the cache and queries proxied to the upstream
+    /// database. Queries which exceed this time are cancelled, and return a timeout error to the
+    /// client. A value of 0 disables the timeout.
+    #[clap(long, env = "QUERY_TIMEOUT_MS", default_value = "0")]
+    query_timeout_ms: u64,
+
+    /// The maximum amount of time, in milliseconds, that a connection may remain idle within an
+    /// open transaction. Since statements within transactions are proxied to the upstream
+    /// database, idle transactions hold upstream connections open. Connections which exceed this
+    /// time are terminated, rolling back their transaction. A value of 0 disables the timeout.
+    #[clap(long, env = "IDLE_IN_TRANSACTION_TIMEOUT_MS", default_value = "0")]
+    idle_in_transaction_timeout_ms: u64,
+
+    /// The maximum amount of time, in seconds, to wait for client connections to close when
+    /// shutting down. Once the adapter stops accepting new connections, each open connection is
+    /// closed as soon as it's idle outside of a transaction, so that clients can reconnect (eg to
+    /// another adapter listening on the same address with `--reuse-port`) without losing an
+    /// in-flight statement. Connections which are still open after this time are terminated. A
+    /// value of 0 terminates all connections immediately.
+    #[clap(long, env = "CONNECTION_DRAIN_TIMEOUT_SECS", default_value = "0")]
+    connection_drain_timeout_secs: u64,
+
+    /// The maximum number of rows that a single read from a cached query may return. Reads which
+    /// exceed this limit fail with an error, rather than being proxied to the upstream database.
+    /// If unset, reads may return any number of rows.
+    #[clap(long, env = "MAX_RESULT_ROWS")]
+    max_result_rows: Option<usize>,
+
+    /// The maximum size, in bytes, of the rows that a single read from a cached query may return.
+    /// Reads which exceed this limit fail with an error, rather than being proxied to the upstream
+    /// database. If unset, reads may return results of any size.
+    #[clap(long, env = "MAX_RESULT_BYTES")]
+    max_result_bytes: Option<usize>,
+
+    /// The number of bytes of rows which may be buffered for a single result set before they're
+    /// flushed to the client. Result sets from both ReadySet and the upstream database are
+    /// streamed to the client as they're written, rather than being buffered in their entirety.
+    #[clap(long, env = "RESULT_BUFFER_BYTES", default_value = "1048576")]
+    result_buffer_bytes: usize,
+
+    /// The maximum size, in bytes, of a single row written to a client. Queries returning larger
+    /// rows fail with an error partway through their results. If unset, rows may be any size.
+    #[clap(long, env = "MAX_ROW_BYTES")]
+    max_row_bytes: Option<usize>,
+
+    /// The maximum total size, in bytes, of the rows in a single result set written to a client,
+    /// whether it was read from ReadySet or the upstream database. Queries returning larger
+    /// results fail with an error partway through their results. If unset, results may be any
+    /// size.
+    #[clap(long, env = "MAX_STREAMED_RESULT_BYTES")]
+    max_streamed_result_bytes: Option<usize>,
+
+    /// The maximum number of times to retry a statement which fails in the upstream database with
+    /// a transient error, such as a deadlock. Statements executed within a transaction are never
+    /// retried. A value of 0 disables retries.
+    #[clap(long, env = "UPSTREAM_MAX_RETRIES", default_value = "3")]
+    upstream_max_retries: u32,
+
+    /// The amount of time, in milliseconds, to wait before the first retry of a statement which
+    /// failed in the upstream database with a transient error. Each subsequent retry of the same
+    /// statement waits twice as long as the previous one.
+    #[clap(long, env = "UPSTREAM_RETRY_BACKOFF_MS", default_value = "10")]
+    upstream_retry_backoff_ms: u64,
+
+    /// The kinds of transient upstream error which statements are retried after. Statements which
+    /// fail with `connection-lost` or `read-only` errors, which indicate that the upstream
+    /// database may have failed over, are retried on a new upstream connection. Retrying
+    /// `connection-lost` may re-execute writes which were applied before the connection was
+    /// lost.
+    #[clap(
+        long = "upstream-retry-kind",
+        env = "UPSTREAM_RETRY_KINDS",
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [
+            TransientErrorKind::Deadlock,
+            TransientErrorKind::LockTimeout,
+            TransientErrorKind::SerializationFailure,
+        ]
+    )]
+    upstream_retry_kinds: Vec<TransientErrorKind>,
+
+    /// Also apply INSERT and DELETE statements which succeed in the upstream database directly
+    /// to ReadySet, so that they can be read from caches immediately rather than after they
+    /// arrive via replication. Only applies to writes executed outside of a transaction, to
+    /// tables with a primary key, which don't rely on auto-incremented or computed default values.
+    /// Tables written to with this enabled should not have triggers or generated columns in the
+    /// upstream database.
+    #[clap(long, env = "WRITE_THROUGH", conflicts_with = "standalone")]
+    write_through: bool,
+
+    /// URL of a read replica of the upstream database. Ad-hoc reads which would otherwise be
+    /// proxied to the upstream database outside of a transaction, such as reads of queries which
+    /// aren't cached, are proxied to the read replica instead. Can be passed multiple times to
+    /// spread reads across several replicas, in which case each new client connection is assigned
+    /// a replica round-robin.
+    #[clap(
+        long = "upstream-read-url",
+        env = "UPSTREAM_READ_URLS",
+        value_delimiter = ','
+    )]
+    upstream_read_urls: Vec<RedactedString>,
+
     /// Whether to use non-blocking or blocking reads against the cache.
     #[clap(long, env = "NON_BLOCKING_READS")]
     non_blocking_reads: bool,
@@ -383,6 +670,30 @@ pub struct Options {
     controller_address: Option<IpAddr>,
 }
 
+/// Bind a listener for client connections to the given address, optionally with `SO_REUSEPORT` set
+/// (see `--reuse-port`). Must be called within the context of a tokio runtime.
+fn bind_listener(address: SocketAddr, reuse_port: bool) -> io::Result<net::TcpListener> {
+    let socket = match address {
+        SocketAddr::V4(_) => net::TcpSocket::new_v4()?,
+        SocketAddr::V6(_) => net::TcpSocket::new_v6()?,
+    };
+    // Matches `TcpListener::bind`
+    socket.set_reuseaddr(true)?;
+    if reuse_port {
+        socket.set_reuseport(true)?;
+    }
+    socket.bind(address)?;
+    socket.listen(1024)
+}
+
+fn parse_sample_rate(s: &str) -> anyhow::Result<f64> {
+    let rate = s.parse::<f64>()?;
+    if !(rate > 0.0 && rate <= 1.0) {
+        bail!("Sample rate must be greater than 0 and at most 1");
+    }
+    Ok(rate)
+}
+
 impl Options {
     /// Return the configured database type, either explicitly set by the user or inferred from the
     /// upstream DB URL
@@ -466,10 +777,29 @@ impl<H> NoriaAdapter<H>
 where
     H: ConnectionHandler + Clone + Send + Sync + 'static,
 {
-    pub fn run(&mut self, options: Options) -> anyhow::Result<()> {
-        let rt = tokio::runtime::Runtime::new()?;
+    pub fn run(&mut self, mut options: Options) -> anyhow::Result<()> {
+        let worker_threads = CgroupLimits::detect().available_cpus();
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .worker_threads(worker_threads)
+            .build()?;
         rt.block_on(async { options.tracing.init("adapter", options.deployment.as_ref()) })?;
         info!(?options, "Starting ReadySet adapter");
+        info!(worker_threads, "Started runtime");
+
+        // Read any credentials passed as secrets. The upstream database URL keeps being re-read in
+        // the background, so that new upstream connections pick up rotated credentials
+        let upstream_db_url_secret = rt
+            .block_on(
+                options
+                    .server_worker_options
+                    .replicator_config
+                    .watch_upstream_db_url(),
+            )?
+            .map(Arc::new);
+        if let Some(secret) = &options.password_secret {
+            options.password = Some(rt.block_on(secret.fetch())?);
+        }
 
         let deployment_dir = options
             .server_worker_options
@@ -488,9 +818,20 @@ where
 
         let mut parsed_upstream_url = None;
 
-        let users: &'static HashMap<String, String> =
-            Box::leak(Box::new(if !options.allow_unauthenticated_connections {
-                HashMap::from([(
+        let mut users = HashMap::new();
+        let mut upstream_urls = HashMap::new();
+        if !options.allow_unauthenticated_connections {
+            if let Some(path) = &options.users_file {
+                for (username, credentials) in users::load_users_file(path)? {
+                    if let Some(url) = credentials.upstream_db_url {
+                        upstream_urls.insert(username.clone(), url.0);
+                    }
+                    users.insert(username, credentials.password.0);
+                }
+            }
+
+            if options.users_file.is_none() || options.username.is_some() {
+                users.insert(
                     options
                         .username
                         .or_else(|| {
@@ -540,10 +881,11 @@ where
                                  passed"
                             )
                         })?,
-                )])
-            } else {
-                HashMap::new()
-            }));
+                );
+            }
+        }
+        let users: &'static HashMap<String, String> = Box::leak(Box::new(users));
+        let upstream_urls: &'static HashMap<String, String> = Box::leak(Box::new(upstream_urls));
         info!(version = %VERSION_STR_ONELINE);
 
         if options.allow_unsupported_set {
@@ -554,10 +896,41 @@ where
         }
 
         let listen_address = options.address.unwrap_or(self.default_address);
-        let listener = rt.block_on(tokio::net::TcpListener::bind(&listen_address))?;
+        ensure!(
+            listen_address != options.metrics_address,
+            "--metrics-address must be different from the SQL listen address {listen_address}"
+        );
+        let listener = {
+            let _guard = rt.enter();
+            bind_listener(listen_address, options.reuse_port)?
+        };
 
         info!(%listen_address, "Listening for new connections");
 
+        // The users allowed to connect through each listener, starting with the one on
+        // `listen_address`
+        let mut listener_users = vec![users.clone()];
+        let mut extra_listeners = Vec::with_capacity(options.extra_listeners.len());
+        for profile in &options.extra_listeners {
+            ensure!(
+                profile.address != listen_address && profile.address != options.metrics_address,
+                "--extra-listener address {} must be different from the SQL listen address and \
+                 --metrics-address",
+                profile.address
+            );
+            ensure!(
+                profile.users.is_none() || !options.allow_unauthenticated_connections,
+                "--extra-listener users can't be restricted with \
+                 --allow-unauthenticated-connections"
+            );
+            listener_users.push(profile.allowed_users(users)?);
+            extra_listeners.push({
+                let _guard = rt.enter();
+                bind_listener(profile.address, options.reuse_port)?
+            });
+            info!(listen_address = %profile.address, "Listening for new connections");
+        }
+
         let auto_increments: Arc<RwLock<HashMap<Relation, AtomicUsize>>> = Arc::default();
         let query_cache: Arc<RwLock<HashMap<ViewCreateRequest, Relation>>> = Arc::default();
         let mut health_reporter = AdapterHealthReporter::new();
@@ -574,7 +947,17 @@ where
                 .unwrap_or_else(|_| options.authority_address.clone()),
             _ => options.authority_address.clone(),
         };
+        let authority_options = options.authority_options.clone();
         let deployment = options.deployment.clone();
+        // In standalone mode the adapter and the embedded server run in the same process, so they
+        // can coordinate through a single in-memory authority store
+        let local_authority_store = match authority {
+            AuthorityType::Local if options.standalone => {
+                Some(Arc::new(LocalAuthorityStore::new()))
+            }
+            AuthorityType::Local => bail!("--authority local can only be used with --standalone"),
+            _ => None,
+        };
         let migration_request_timeout = options.migration_request_timeout_ms;
         let controller_request_timeout = options.controller_request_timeout_ms;
         let server_supports_pagination = options
@@ -584,21 +967,27 @@ where
                 .server_worker_options
                 .enable_experimental_paginate_support;
         let no_upstream_connections = options.no_upstream_connections;
+        let http_auth_token = options.server_worker_options.http_auth_token.clone();
+        options.server_worker_options.internal_tls.install()?;
 
         let rh = rt.block_on(async {
-            let authority = authority
-                .to_authority(&authority_address, &deployment)
-                .await;
+            let authority = make_authority(
+                &authority,
+                &authority_address,
+                &deployment,
+                &authority_options,
+                local_authority_store.as_ref(),
+            )
+            .await;
 
-            Ok::<ReadySetHandle, ReadySetError>(
-                ReadySetHandle::with_timeouts(
+            Ok::<ReadySetHandle, ReadySetError>(rs_connect.in_scope(|| {
+                ReadySetHandle::make(
                     authority,
                     Some(Duration::from_millis(controller_request_timeout)),
                     Some(Duration::from_millis(migration_request_timeout)),
+                    http_auth_token,
                 )
-                .instrument(rs_connect.clone())
-                .await,
-            )
+            }))
         })?;
 
         rs_connect.in_scope(|| info!("ReadySetHandle created"));
@@ -608,8 +997,16 @@ where
             let _guard = rt.enter();
             tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()).unwrap()
         };
+        // Yields each accepted connection along with the index of the listener it was accepted on
         let mut listener = Box::pin(futures_util::stream::select(
-            TcpListenerStream::new(listener),
+            futures_util::stream::select_all(
+                iter::once(listener)
+                    .chain(extra_listeners)
+                    .enumerate()
+                    .map(|(idx, listener)| {
+                        TcpListenerStream::new(listener).map(move |s| s.map(|s| (s, idx)))
+                    }),
+            ),
             futures_util::stream::select(
                 ctrlc
                     .map(|r| {
@@ -696,12 +1093,38 @@ where
                 .unwrap();
 
             let shutdown_rx = shutdown_rx.clone();
+            let export = options
+                .query_log_export_otlp_endpoint
+                .clone()
+                .map(|endpoint| QueryEventSink::Otlp { endpoint });
+            #[cfg(feature = "kafka")]
+            let export = export.or_else(|| {
+                options
+                    .query_log_export_kafka_brokers
+                    .clone()
+                    .map(|brokers| QueryEventSink::Kafka {
+                        brokers,
+                        topic: options.query_log_export_kafka_topic.clone(),
+                    })
+            });
+            let qlog_config = query_logger::QueryLogConfig {
+                max_queries: Some(options.query_log_max_queries),
+                sample_rate: options.query_log_sample_rate,
+                export,
+                user_labels: options.query_log_user_labels,
+                application_labels: options.query_log_application_labels,
+                max_label_values: options.query_log_max_label_values,
+            };
             // Spawn the actual thread to run the logger
             std::thread::Builder::new()
                 .name("Query logger".to_string())
                 .stack_size(2 * 1024 * 1024) // Use the same value tokio is using
                 .spawn(move || {
-                    runtime.block_on(query_logger::QueryLogger::run(qlog_receiver, shutdown_rx));
+                    runtime.block_on(query_logger::QueryLogger::run(
+                        qlog_config,
+                        qlog_receiver,
+                        shutdown_rx,
+                    ));
                     runtime.shutdown_background();
                 })?;
 
@@ -731,6 +1154,22 @@ where
                 ),
         ));
 
+        let query_pins = Arc::new(QueryPinLists::new(
+            options.query_allow_list_file.clone(),
+            options.query_deny_list_file.clone(),
+            self.parse_dialect,
+            server_supports_pagination,
+            query_status_cache,
+        ));
+        rt.block_on(query_pins.reload())
+            .context("loading query allow and deny lists")?;
+        {
+            let query_pins = Arc::clone(&query_pins);
+            let shutdown_rx = shutdown_rx.clone();
+            rt.handle()
+                .spawn(async move { query_pins.run(shutdown_rx).await });
+        }
+
         let telemetry_sender = rt.block_on(async {
             let proxied_queries_reporter =
                 Arc::new(ProxiedQueriesReporter::new(query_status_cache));
@@ -770,17 +1209,28 @@ where
         } else {
             (None, None)
         };
+        let schema_snapshots = Arc::new(SchemaSnapshots::default());
         let http_server = NoriaAdapterHttpRouter {
             listen_addr: options.metrics_address,
             query_cache: query_status_cache,
             prometheus_handle,
             health_reporter: health_reporter.clone(),
             failpoint_channel: tx,
+            enable_pprof: options.server_worker_options.enable_pprof,
+            query_pins,
+            controller: rh.clone(),
+            schema_snapshots: Arc::clone(&schema_snapshots),
+            anonymize_identifiers: options.anonymize_identifiers,
+            expr_dialect: self.expr_dialect,
         };
 
+        let http_listener = rt
+            .block_on(http_server.create_listener())
+            .with_context(|| format!("binding --metrics-address {}", options.metrics_address))?;
+        info!(metrics_address = %options.metrics_address, "Serving metrics and health checks");
+
         let router_shutdown_rx = shutdown_rx.clone();
         let fut = async move {
-            let http_listener = http_server.create_listener().await.unwrap();
             NoriaAdapterHttpRouter::route_requests(http_server, http_listener, router_shutdown_rx)
                 .await
         };
@@ -926,6 +1376,43 @@ where
             rt.handle().spawn(abort_on_panic(fut));
         }
 
+        if let Some(interval) = options.schema_snapshot_interval {
+            ensure!(
+                upstream_config.upstream_db_url.is_some(),
+                "--schema-snapshot-interval requires an upstream database"
+            );
+            rs_connect.in_scope(|| info!("Spawning upstream schema snapshot task"));
+            let mut snapshotter = SchemaSnapshotter::<H::UpstreamDatabase>::new(
+                upstream_config.clone(),
+                schema_snapshots,
+                Duration::from_secs(interval),
+                options.anonymize_identifiers,
+                shutdown_rx.clone(),
+            );
+            rt.handle()
+                .spawn(abort_on_panic(async move { snapshotter.run().await }));
+        }
+
+        let select_privileges = if options.enforce_upstream_privileges {
+            ensure!(
+                upstream_config.upstream_db_url.is_some(),
+                "--enforce-upstream-privileges requires an upstream database"
+            );
+            rs_connect.in_scope(|| info!("Spawning upstream privileges synchronizer task"));
+            let select_privileges = SharedSelectPrivileges::default();
+            let mut synchronizer = PrivilegeSynchronizer::<H::UpstreamDatabase>::new(
+                upstream_config.clone(),
+                Arc::clone(&select_privileges),
+                Duration::from_secs(options.privileges_refresh_interval),
+                shutdown_rx.clone(),
+            );
+            rt.handle()
+                .spawn(abort_on_panic(async move { synchronizer.run().await }));
+            Some(select_privileges)
+        } else {
+            None
+        };
+
         if matches!(migration_style, MigrationStyle::Explicit) {
             rs_connect.in_scope(|| info!("Spawning explicit migrations task"));
             let rh = rh.clone();
@@ -956,6 +1443,7 @@ where
             let fut = reconcile_endpoint_registration(
                 authority_address.clone(),
                 deployment,
+                authority_options.clone(),
                 options.metrics_address.port(),
                 options.use_aws_external_address,
             )
@@ -993,9 +1481,14 @@ where
 
             let server_handle = rt.block_on(async move {
                 let authority = Arc::new(
-                    authority
-                        .to_authority(&authority_address, &deployment)
-                        .await,
+                    make_authority(
+                        &authority,
+                        &authority_address,
+                        &deployment,
+                        &authority_options,
+                        local_authority_store.as_ref(),
+                    )
+                    .await,
                 );
 
                 builder
@@ -1026,18 +1519,41 @@ where
 
         let expr_dialect = self.expr_dialect;
         let parse_dialect = self.parse_dialect;
-        while let Some(Ok(s)) = rt.block_on(listener.next()) {
+        let connection_registry = Arc::new(ConnectionRegistry::new());
+        let (drain_tx, drain_rx) = tokio::sync::watch::channel(false);
+        let cache_experiments = Arc::new(CacheExperiments::new());
+        let migration_queue = Arc::new(MigrationQueue::new(options.max_concurrent_migrations));
+        let read_replicas = Arc::new(ReadReplicas::new(options.upstream_read_urls.clone()));
+        while let Some(Ok((s, listener_idx))) = rt.block_on(listener.next()) {
             let connection = info_span!("connection", addr = %s.peer_addr()?);
             connection.in_scope(|| info!("Accepted new connection"));
             s.set_nodelay(true)?;
 
+            // Apply the overrides for the listener the connection was accepted on, if any
+            let profile = listener_idx
+                .checked_sub(1)
+                .map(|idx| &options.extra_listeners[idx]);
+            let no_upstream_connections = profile
+                .and_then(|p| p.upstream)
+                .map_or(no_upstream_connections, |upstream| !upstream);
+            let noria_read_behavior = match profile.and_then(|p| p.non_blocking_reads) {
+                Some(true) => ReadBehavior::NonBlocking,
+                Some(false) => ReadBehavior::Blocking,
+                None => noria_read_behavior,
+            };
+
             // bunch of stuff to move into the async block below
             let rh = rh.clone();
             let (auto_increments, query_cache) = (auto_increments.clone(), query_cache.clone());
             let mut connection_handler = self.connection_handler.clone();
-            let backend_builder = BackendBuilder::new()
+            let mut drain_rx = drain_rx.clone();
+            let mut backend_builder = BackendBuilder::new()
                 .slowlog(options.log_slow)
-                .users(users.clone())
+                .users(listener_users[listener_idx].clone())
+                .upstream_urls(upstream_urls.clone())
+                .connection_registry(Arc::clone(&connection_registry))
+                .cache_experiments(Arc::clone(&cache_experiments))
+                .migration_queue(Arc::clone(&migration_queue))
                 .require_authentication(!options.allow_unauthenticated_connections)
                 .dialect(self.parse_dialect)
                 .query_log(qlog_sender.clone(), options.query_log_ad_hoc)
@@ -1050,9 +1566,35 @@ where
                 .query_max_failure_seconds(options.query_max_failure_seconds)
                 .telemetry_sender(telemetry_sender.clone())
                 .fallback_recovery_seconds(options.fallback_recovery_seconds)
+                .query_timeout(
+                    (options.query_timeout_ms > 0)
+                        .then(|| Duration::from_millis(options.query_timeout_ms)),
+                )
+                .idle_in_transaction_timeout(
+                    (options.idle_in_transaction_timeout_ms > 0)
+                        .then(|| Duration::from_millis(options.idle_in_transaction_timeout_ms)),
+                )
+                .result_limits(ResultLimits {
+                    max_rows: options.max_result_rows,
+                    max_bytes: options.max_result_bytes,
+                })
+                .result_streaming(ResultStreaming {
+                    max_buffered_bytes: options.result_buffer_bytes,
+                    max_row_bytes: options.max_row_bytes,
+                    max_result_bytes: options.max_streamed_result_bytes,
+                })
+                .upstream_retry_policy(UpstreamRetryPolicy {
+                    max_retries: options.upstream_max_retries,
+                    backoff: Duration::from_millis(options.upstream_retry_backoff_ms),
+                    retry_kinds: options.upstream_retry_kinds.iter().copied().collect(),
+                })
+                .write_through(options.write_through)
                 .enable_experimental_placeholder_inlining(
                     options.experimental_placeholder_inlining,
                 );
+            if let Some(select_privileges) = &select_privileges {
+                backend_builder = backend_builder.select_privileges(Arc::clone(select_privileges));
+            }
             let telemetry_sender = telemetry_sender.clone();
 
             // Initialize the reader layer for the adapter.
@@ -1065,15 +1607,22 @@ where
             });
 
             let query_status_cache = query_status_cache;
-            let upstream_config = upstream_config.clone();
+            let mut upstream_config = upstream_config.clone();
+            if let Some(secret) = &upstream_db_url_secret {
+                upstream_config.upstream_db_url = Some(secret.current());
+            }
             let fallback_cache = fallback_cache.clone();
+            let read_replicas = Arc::clone(&read_replicas);
             let fut = async move {
                 let upstream_res =
                     if upstream_config.upstream_db_url.is_some() && !no_upstream_connections {
                         set_failpoint!(failpoints::UPSTREAM);
                         timeout(
                             UPSTREAM_CONNECTION_TIMEOUT,
-                            H::UpstreamDatabase::connect(upstream_config, fallback_cache),
+                            H::UpstreamDatabase::connect(
+                                upstream_config.clone(),
+                                fallback_cache.clone(),
+                            ),
                         )
                         .instrument(debug_span!("Connecting to upstream database"))
                         .await
@@ -1125,12 +1674,46 @@ where
                                 .instrument(debug_span!("Building noria connector"))
                                 .await;
 
-                                let backend = backend_builder.clone().build(
-                                    noria,
-                                    upstream,
-                                    query_status_cache,
-                                );
-                                connection_handler.process_connection(s, backend).await;
+                                let read_upstream =
+                                    if upstream.is_some() && !read_replicas.is_empty() {
+                                        read_replicas
+                                            .connect(
+                                                &upstream_config,
+                                                fallback_cache,
+                                                UPSTREAM_CONNECTION_TIMEOUT,
+                                            )
+                                            .instrument(debug_span!("Connecting to read replica"))
+                                            .await
+                                    } else {
+                                        None
+                                    };
+                                let backend = backend_builder
+                                    .clone()
+                                    .build(noria, upstream, query_status_cache)
+                                    .with_read_upstream(read_upstream);
+                                let idle_timeout = backend.idle_in_transaction_timeout();
+                                let idle = backend.idle();
+                                let drained = async move {
+                                    while !*drain_rx.borrow_and_update() {
+                                        if drain_rx.changed().await.is_err() {
+                                            future::pending::<()>().await;
+                                        }
+                                    }
+                                    idle.await;
+                                    debug!("Closing idle connection while draining");
+                                };
+                                // Dropping the connection closes its upstream connection, which
+                                // rolls back any open transaction
+                                tokio::select! {
+                                    _ = connection_handler.process_connection(s, backend) => {}
+                                    _ = async move {
+                                        match idle_timeout {
+                                            Some(idle_timeout) => idle_timeout.await,
+                                            None => future::pending().await,
+                                        }
+                                    } => {}
+                                    _ = drained => {}
+                                }
                             }
                             Err(error) => {
                                 error!(
@@ -1165,6 +1748,34 @@ where
         let rs_shutdown = span!(Level::INFO, "RS server Shutting down");
         health_reporter.set_state(AdapterState::ShuttingDown);
 
+        // Stop listening, so that new connections go to any other adapter listening on the same
+        // address (see `--reuse-port`)
+        drop(listener);
+        if options.connection_drain_timeout_secs > 0 {
+            let drain_timeout = Duration::from_secs(options.connection_drain_timeout_secs);
+            rs_shutdown.in_scope(|| {
+                info!(
+                    connections = connection_registry.len(),
+                    ?drain_timeout,
+                    "Draining client connections"
+                )
+            });
+            let _ = drain_tx.send(true);
+            let drained = rt.block_on(timeout(drain_timeout, async {
+                while !connection_registry.is_empty() {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }));
+            if drained.is_err() {
+                rs_shutdown.in_scope(|| {
+                    warn!(
+                        connections = connection_registry.len(),
+                        "Timed out draining client connections"
+                    )
+                });
+            }
+        }
+
         // We need to drop the last remaining `ShutdownReceiver` before sending the shutdown
         // signal. If we didn't, `ShutdownSender::shutdown` would hang forever, since it
         // specifically waits for every associated `ShutdownReceiver` to be dropped.
@@ -1226,6 +1837,26 @@ where
     }
 }
 
+/// Construct the authority for the given [`AuthorityType`]. If a [`LocalAuthorityStore`] is given,
+/// a [`LocalAuthority`] sharing that store is returned, so that the adapter and an embedded server
+/// see the same state.
+async fn make_authority(
+    authority: &AuthorityType,
+    authority_address: &str,
+    deployment: &str,
+    authority_options: &AuthorityOptions,
+    local_authority_store: Option<&Arc<LocalAuthorityStore>>,
+) -> Authority {
+    match local_authority_store {
+        Some(store) => Authority::from(LocalAuthority::new_with_store(Arc::clone(store))),
+        None => {
+            authority
+                .to_authority_with_options(authority_address, deployment, authority_options)
+                .await
+        }
+    }
+}
+
 async fn check_server_version_compatibility(rh: &mut ReadySetHandle) -> anyhow::Result<()> {
     let server_version = rh.version().await?;
     debug!(server_version);
@@ -1286,12 +1917,13 @@ async fn my_aws_ip() -> anyhow::Result<IpAddr> {
 async fn reconcile_endpoint_registration(
     authority_address: String,
     deployment: String,
+    authority_options: AuthorityOptions,
     port: u16,
     use_aws_external: bool,
 ) {
-    let connect_string = format!("http://{}/{}", &authority_address, &deployment);
-    debug!("{}", connect_string);
-    let authority = ConsulAuthority::new(&connect_string).unwrap();
+    let connect_string = format!("{}/{}", &authority_address, &deployment);
+    let authority = ConsulAuthority::new_with_options(&connect_string, &authority_options).unwrap();
+    debug!(address = authority.agent_address(), %deployment);
 
     let mut initializing = true;
     let mut interval = tokio::time::interval(REGISTER_HTTP_INIT_INTERVAL);
@@ -1322,7 +1954,7 @@ async fn reconcile_endpoint_registration(
 
         // We try to update our http endpoint every iteration regardless because it may
         // have changed.
-        let ip = match my_ip(&authority_address, use_aws_external).await {
+        let ip = match my_ip(authority.agent_address(), use_aws_external).await {
             Some(ip) => ip,
             None => {
                 info!("Failed to retrieve IP. Will try again on next tick");
@@ -1373,6 +2005,25 @@ mod tests {
         assert_eq!(opts.deployment, "test");
     }
 
+    #[test]
+    fn arg_parsing_standalone_local_authority() {
+        let opts = Options::parse_from(vec![
+            "readyset",
+            "--database-type",
+            "mysql",
+            "--deployment",
+            "test",
+            "--standalone",
+            "--authority",
+            "local",
+            "--allow-unauthenticated-connections",
+        ]);
+
+        assert!(opts.standalone);
+        assert_eq!(opts.authority, AuthorityType::Local);
+        assert_eq!(opts.authority_address, "");
+    }
+
     #[test]
     fn arg_parsing_with_upstream() {
         let opts = Options::parse_from(vec![
@@ -1393,6 +2044,24 @@ mod tests {
         assert_eq!(opts.deployment, "test");
     }
 
+    #[test]
+    fn arg_parsing_metrics_address() {
+        let opts = Options::parse_from(vec![
+            "readyset",
+            "--database-type",
+            "mysql",
+            "--deployment",
+            "test",
+            "--address",
+            "0.0.0.0:3306",
+            "--metrics-address",
+            "127.0.0.1:9090",
+            "--allow-unauthenticated-connections",
+        ]);
+
+        assert_eq!(opts.metrics_address, "127.0.0.1:9090".parse().unwrap());
+    }
+
     #[test]
     fn async_migrations_param_defaults() {
         let opts = Options::parse_from(vec![
@@ -0,0 +1,178 @@
+//! Additional listeners for client connections, each with its own profile of options.
+//!
+//! By default the adapter listens on a single address (`--address`), and handles every connection
+//! the same way. Each `--extra-listener` adds another address to listen on, along with overrides
+//! for how connections accepted on that address are handled, so that a single deployment can, for
+//! example, only serve cached reads on one port while being fully featured on another. An extra
+//! listener is given as its address, followed by any number of comma-separated options:
+//!
+//! ```text
+//! --extra-listener 0.0.0.0:3308,upstream=false,users=reporting:metrics,reads=non-blocking
+//! ```
+//!
+//! - `upstream=false` disables connecting to the upstream database for connections accepted on this
+//!   listener, so that queries are only ever served from caches (like `--no-upstream-connections`),
+//!   and `upstream=true` enables it even if `--no-upstream-connections` is set.
+//! - `users=NAME[:NAME...]` only allows the given users, which must also be allowed to connect by
+//!   `--username` or `--users-file`, to connect through this listener.
+//! - `reads=blocking` or `reads=non-blocking` overrides `--non-blocking-reads`.
+//!
+//! Any option which isn't given is the same as for connections accepted on `--address`.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context};
+
+/// An additional address to listen for client connections on, along with the options which
+/// override the adapter's for connections accepted on it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ListenerProfile {
+    pub(crate) address: SocketAddr,
+    /// Whether to connect to the upstream database, if set
+    pub(crate) upstream: Option<bool>,
+    /// The users allowed to connect, if restricted
+    pub(crate) users: Option<Vec<String>>,
+    /// Whether to use non-blocking reads, if set
+    pub(crate) non_blocking_reads: Option<bool>,
+}
+
+impl FromStr for ListenerProfile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(',').map(str::trim);
+        let address = parts.next().unwrap_or_default();
+        let mut profile = ListenerProfile {
+            address: address
+                .parse()
+                .with_context(|| format!("Invalid listener address {address:?}"))?,
+            upstream: None,
+            users: None,
+            non_blocking_reads: None,
+        };
+
+        for option in parts {
+            let (key, value) = option
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Expected KEY=VALUE for listener option {option:?}"))?;
+            match key.trim() {
+                "upstream" => {
+                    profile.upstream = Some(
+                        value
+                            .trim()
+                            .parse()
+                            .with_context(|| format!("Invalid value {value:?} for upstream"))?,
+                    )
+                }
+                "users" => {
+                    profile.users = Some(value.split(':').map(|u| u.trim().to_owned()).collect())
+                }
+                "reads" => {
+                    profile.non_blocking_reads = Some(match value.trim() {
+                        "blocking" => false,
+                        "non-blocking" => true,
+                        _ => bail!(
+                            "Invalid value {value:?} for reads; expected one of \"blocking\" or \
+                             \"non-blocking\""
+                        ),
+                    })
+                }
+                _ => bail!(
+                    "Unknown listener option {key:?}; expected one of \"upstream\", \"users\", or \
+                     \"reads\""
+                ),
+            }
+        }
+
+        Ok(profile)
+    }
+}
+
+impl ListenerProfile {
+    /// Returns the subset of `users` (a map from username to password) allowed to connect through
+    /// this listener
+    pub(crate) fn allowed_users(
+        &self,
+        users: &HashMap<String, String>,
+    ) -> anyhow::Result<HashMap<String, String>> {
+        let Some(allowed) = &self.users else {
+            return Ok(users.clone());
+        };
+
+        allowed
+            .iter()
+            .map(|user| {
+                let password = users.get(user).ok_or_else(|| {
+                    anyhow!(
+                        "User {user:?} allowed to connect to the listener on {} is not allowed to \
+                         connect to the adapter",
+                        self.address
+                    )
+                })?;
+                Ok((user.clone(), password.clone()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_profile() {
+        assert_eq!(
+            "127.0.0.1:3308".parse::<ListenerProfile>().unwrap(),
+            ListenerProfile {
+                address: "127.0.0.1:3308".parse().unwrap(),
+                upstream: None,
+                users: None,
+                non_blocking_reads: None,
+            }
+        );
+        assert_eq!(
+            "[::]:3308, upstream=false, users=alice:bob, reads=non-blocking"
+                .parse::<ListenerProfile>()
+                .unwrap(),
+            ListenerProfile {
+                address: "[::]:3308".parse().unwrap(),
+                upstream: Some(false),
+                users: Some(vec!["alice".to_owned(), "bob".to_owned()]),
+                non_blocking_reads: Some(true),
+            }
+        );
+
+        "localhost:3308".parse::<ListenerProfile>().unwrap_err();
+        "0.0.0.0:3308,upstream"
+            .parse::<ListenerProfile>()
+            .unwrap_err();
+        "0.0.0.0:3308,reads=eventually"
+            .parse::<ListenerProfile>()
+            .unwrap_err();
+        "0.0.0.0:3308,fallback=false"
+            .parse::<ListenerProfile>()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn allowed_users() {
+        let users = HashMap::from([
+            ("alice".to_owned(), "alicepw".to_owned()),
+            ("bob".to_owned(), "bobpw".to_owned()),
+        ]);
+
+        let all: ListenerProfile = "0.0.0.0:3308".parse().unwrap();
+        assert_eq!(all.allowed_users(&users).unwrap(), users);
+
+        let alice: ListenerProfile = "0.0.0.0:3308,users=alice".parse().unwrap();
+        assert_eq!(
+            alice.allowed_users(&users).unwrap(),
+            HashMap::from([("alice".to_owned(), "alicepw".to_owned())])
+        );
+
+        let unknown: ListenerProfile = "0.0.0.0:3308,users=alice:carol".parse().unwrap();
+        unknown.allowed_users(&users).unwrap_err();
+    }
+}
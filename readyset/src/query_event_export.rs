@@ -0,0 +1,270 @@
+//! Export of individual query execution events from the query logger, for offline analysis.
+//!
+//! The query logger aggregates [`QueryExecutionEvent`]s into per-query Prometheus metrics, which
+//! loses the detail of each individual execution. If an export sink is configured, the query
+//! logger additionally converts each event into an [`ExportedQueryEvent`] - with the literals in
+//! the query anonymized - and hands it to a [`QueryEventExporter`], which sends the events in
+//! batches, as JSON, to either:
+//!
+//! - An [OTLP/HTTP] logs endpoint (eg an OpenTelemetry collector), as one log record per event with
+//!   the event as the record's body
+//! - A Kafka topic, as one message per event keyed by the query ID (only if the adapter was built
+//!   with the `kafka` feature)
+//!
+//! Exporting never blocks the query logger: if the exporter falls behind, events are dropped and
+//! counted in the [`QUERY_LOG_EVENTS_DROPPED`] metric.
+//!
+//! [OTLP/HTTP]: https://opentelemetry.io/docs/specs/otlp/#otlphttp
+//! [`QUERY_LOG_EVENTS_DROPPED`]: readyset_client_metrics::recorded::QUERY_LOG_EVENTS_DROPPED
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use metrics::counter;
+use readyset_client_metrics::{
+    recorded, EventType, QueryDestination, QueryExecutionEvent, SqlQueryType,
+};
+use reqwest::Url;
+use serde::Serialize;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+use tracing::{info, warn};
+
+/// The maximum number of events waiting to be exported before new events are dropped
+const EXPORT_QUEUE_SIZE: usize = 10_000;
+
+/// The maximum number of events sent to the sink at once
+const MAX_BATCH_SIZE: usize = 500;
+
+/// Where to export query execution events to
+#[derive(Debug, Clone)]
+pub(crate) enum QueryEventSink {
+    /// POST the events to an OTLP/HTTP logs endpoint, eg `http://localhost:4318/v1/logs`
+    Otlp { endpoint: Url },
+    /// Produce the events to a Kafka topic
+    #[cfg(feature = "kafka")]
+    Kafka {
+        /// Comma-separated list of `host:port` pairs of the Kafka brokers to bootstrap from
+        brokers: String,
+        topic: String,
+    },
+}
+
+/// A single [`QueryExecutionEvent`], as exported by the query logger
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ExportedQueryEvent {
+    /// When the query logger received the event
+    pub(crate) timestamp: DateTime<Utc>,
+    pub(crate) event_type: EventType,
+    pub(crate) query_type: SqlQueryType,
+    /// The text of the query, with all literals anonymized
+    pub(crate) query: String,
+    pub(crate) query_id: Option<String>,
+    pub(crate) destination: Option<QueryDestination>,
+    pub(crate) num_keys: Option<u64>,
+    pub(crate) cache_misses: Option<u64>,
+    pub(crate) parse_duration_us: Option<u64>,
+    pub(crate) upstream_duration_us: Option<u64>,
+    pub(crate) readyset_duration_us: Option<u64>,
+    /// Whether ReadySet returned an error for the query. The error itself isn't exported, since
+    /// error messages can include values from the query.
+    pub(crate) readyset_error: bool,
+}
+
+impl ExportedQueryEvent {
+    /// Build an exported event from the given event, whose query has already been anonymized to
+    /// `query`
+    pub(crate) fn new(event: &QueryExecutionEvent, query: String) -> Self {
+        let micros = |d: Option<Duration>| d.map(|d| d.as_micros() as u64);
+        Self {
+            timestamp: Utc::now(),
+            event_type: event.event,
+            query_type: event.sql_type,
+            query,
+            query_id: event.query_id.map(|id| id.to_string()),
+            destination: event.destination,
+            num_keys: event.num_keys,
+            cache_misses: event.cache_misses,
+            parse_duration_us: micros(event.parse_duration),
+            upstream_duration_us: micros(event.upstream_duration),
+            readyset_duration_us: micros(event.readyset_duration),
+            readyset_error: event.noria_error.is_some(),
+        }
+    }
+
+    /// Convert the event to an OTLP log record, in the JSON encoding of the OTLP protobuf schema
+    fn to_otlp_log_record(&self) -> serde_json::Value {
+        json!({
+            "timeUnixNano": self.timestamp.timestamp_nanos().to_string(),
+            "severityText": "INFO",
+            "body": { "stringValue": serde_json::to_string(self).unwrap_or_default() },
+            "attributes": [
+                { "key": "event.name", "value": { "stringValue": "readyset.query_execution" } },
+            ],
+        })
+    }
+}
+
+/// A connection to a [`QueryEventSink`]
+enum SinkClient {
+    Otlp {
+        client: reqwest::Client,
+        endpoint: Url,
+    },
+    #[cfg(feature = "kafka")]
+    Kafka {
+        producer: rdkafka::producer::FutureProducer,
+        topic: String,
+    },
+}
+
+impl SinkClient {
+    fn new(sink: QueryEventSink) -> anyhow::Result<Self> {
+        Ok(match sink {
+            QueryEventSink::Otlp { endpoint } => SinkClient::Otlp {
+                client: reqwest::Client::builder()
+                    .timeout(Duration::from_secs(10))
+                    .build()?,
+                endpoint,
+            },
+            #[cfg(feature = "kafka")]
+            QueryEventSink::Kafka { brokers, topic } => SinkClient::Kafka {
+                producer: rdkafka::ClientConfig::new()
+                    .set("bootstrap.servers", brokers)
+                    .create()?,
+                topic,
+            },
+        })
+    }
+
+    async fn send(&self, events: &[ExportedQueryEvent]) -> anyhow::Result<()> {
+        match self {
+            SinkClient::Otlp { client, endpoint } => {
+                let body = json!({
+                    "resourceLogs": [{
+                        "resource": {
+                            "attributes": [
+                                { "key": "service.name", "value": { "stringValue": "readyset" } },
+                            ],
+                        },
+                        "scopeLogs": [{
+                            "scope": { "name": "readyset.query_log" },
+                            "logRecords": events
+                                .iter()
+                                .map(ExportedQueryEvent::to_otlp_log_record)
+                                .collect::<Vec<_>>(),
+                        }],
+                    }],
+                });
+                client
+                    .post(endpoint.clone())
+                    .json(&body)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            #[cfg(feature = "kafka")]
+            SinkClient::Kafka { producer, topic } => {
+                use rdkafka::producer::FutureRecord;
+
+                for event in events {
+                    let payload = serde_json::to_string(event)?;
+                    producer
+                        .send(
+                            FutureRecord::to(topic)
+                                .key(event.query_id.as_deref().unwrap_or_default())
+                                .payload(&payload),
+                            Duration::from_secs(0),
+                        )
+                        .await
+                        .map_err(|(error, _)| error)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A handle to a background task exporting query execution events to a [`QueryEventSink`]
+pub(crate) struct QueryEventExporter {
+    tx: mpsc::Sender<ExportedQueryEvent>,
+}
+
+impl QueryEventExporter {
+    /// Spawn a task on the current runtime to export events to the given sink. The task exits
+    /// once the returned exporter is dropped.
+    pub(crate) fn spawn(sink: QueryEventSink) -> anyhow::Result<Self> {
+        info!(?sink, "Exporting query execution events");
+        let client = SinkClient::new(sink)?;
+        let (tx, rx) = mpsc::channel(EXPORT_QUEUE_SIZE);
+        tokio::spawn(run(client, rx));
+        Ok(Self { tx })
+    }
+
+    /// Queue the given event to be exported, dropping it if the export queue is full
+    pub(crate) fn export(&self, event: ExportedQueryEvent) {
+        match self.tx.try_send(event) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) | Err(TrySendError::Closed(_)) => {
+                counter!(recorded::QUERY_LOG_EVENTS_DROPPED, 1);
+            }
+        }
+    }
+}
+
+async fn run(client: SinkClient, mut rx: mpsc::Receiver<ExportedQueryEvent>) {
+    let mut batch = Vec::with_capacity(MAX_BATCH_SIZE);
+    while let Some(event) = rx.recv().await {
+        batch.push(event);
+        while batch.len() < MAX_BATCH_SIZE {
+            match rx.try_recv() {
+                Ok(event) => batch.push(event),
+                Err(_) => break,
+            }
+        }
+
+        let num_events = batch.len() as u64;
+        match client.send(&batch).await {
+            Ok(()) => counter!(recorded::QUERY_LOG_EVENTS_EXPORTED, num_events),
+            Err(error) => {
+                warn!(%error, num_events, "Failed to export query execution events");
+                counter!(recorded::QUERY_LOG_EVENTS_DROPPED, num_events);
+            }
+        }
+        batch.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn otlp_log_record_body_is_event_json() {
+        let event = ExportedQueryEvent {
+            timestamp: Utc::now(),
+            event_type: EventType::Execute,
+            query_type: SqlQueryType::Read,
+            query: "SELECT * FROM `t` WHERE (`x` = $1)".into(),
+            query_id: Some("q_1".into()),
+            destination: Some(QueryDestination::Readyset),
+            num_keys: Some(1),
+            cache_misses: Some(0),
+            parse_duration_us: None,
+            upstream_duration_us: None,
+            readyset_duration_us: Some(150),
+            readyset_error: false,
+        };
+        let record = event.to_otlp_log_record();
+        let body: serde_json::Value =
+            serde_json::from_str(record["body"]["stringValue"].as_str().unwrap()).unwrap();
+        assert_eq!(body["query_id"], "q_1");
+        assert_eq!(body["destination"], "Readyset");
+        assert_eq!(body["readyset_duration_us"], 150);
+        assert_eq!(
+            record["timeUnixNano"],
+            event.timestamp.timestamp_nanos().to_string()
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 
 use metrics::{register_counter, register_histogram, Counter, Histogram, SharedString};
@@ -11,16 +11,117 @@ use readyset_sql_passes::anonymize::anonymize_literals;
 use readyset_util::shutdown::ShutdownReceiver;
 use tokio::select;
 use tokio::sync::mpsc::UnboundedReceiver;
-use tracing::{info, info_span};
+use tracing::{info, info_span, warn};
+
+use crate::query_event_export::{ExportedQueryEvent, QueryEventExporter, QueryEventSink};
+
+/// The value of the `query` label on the metrics recorded for all queries which aren't retained by
+/// the query logger, because the number of distinct queries exceeded
+/// [`QueryLogConfig::max_queries`].
+const OVERFLOW_QUERY: &str = "<overflow>";
+
+/// The value of the `user` or `application` label on the metrics recorded for all queries executed
+/// by users or applications which aren't retained by the query logger, because the number of
+/// distinct values of the label exceeded [`QueryLogConfig::max_label_values`].
+const OVERFLOW_LABEL: &str = "<overflow>";
+
+/// Configuration for the cardinality of the per-query metrics recorded by the query logger, and
+/// for exporting individual query execution events
+#[derive(Debug, Clone)]
+pub(crate) struct QueryLogConfig {
+    /// The maximum number of distinct queries to record metrics for. Once this many queries are
+    /// retained, metrics for any other queries are recorded under a single overflow query label,
+    /// unless they have been executed more often than the least frequently executed retained
+    /// query, in which case they replace it. If `None`, metrics are recorded for every query.
+    pub(crate) max_queries: Option<usize>,
+    /// The fraction of query executions to record in the per-query histograms, between 0 and 1.
+    /// Counters are always incremented for every execution.
+    pub(crate) sample_rate: f64,
+    /// Where to export every query execution event to, if anywhere
+    pub(crate) export: Option<QueryEventSink>,
+    /// Whether to label the per-query metrics with the user the query's connection is
+    /// authenticated as
+    pub(crate) user_labels: bool,
+    /// Whether to label the per-query metrics with the name of the application which opened the
+    /// query's connection
+    pub(crate) application_labels: bool,
+    /// The maximum number of distinct values to record for each of the `user` and `application`
+    /// labels. Metrics for queries with any other value are recorded with a value of
+    /// `<overflow>`.
+    pub(crate) max_label_values: usize,
+}
+
+impl Default for QueryLogConfig {
+    fn default() -> Self {
+        Self {
+            max_queries: None,
+            sample_rate: 1.0,
+            export: None,
+            user_labels: false,
+            application_labels: false,
+            max_label_values: 100,
+        }
+    }
+}
+
+/// The identity of a query for the purposes of the query log
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum QueryKey {
+    Id(QueryId),
+    Query(Arc<SqlQuery>),
+}
+
+/// The labels identifying the connection a query was executed on, recorded on the metrics for the
+/// query if enabled in the [`QueryLogConfig`]
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+struct ConnectionLabels {
+    user: Option<Arc<str>>,
+    application: Option<Arc<str>>,
+}
+
+/// The key of the metrics recorded for a query, which are recorded separately for each distinct
+/// set of [`ConnectionLabels`] the query is executed with
+type MetricsKey = (QueryKey, ConnectionLabels);
+
+/// Returns the value to record for a label with the given value, if any, bounding the number of
+/// distinct values of the label (tracked in `seen`) to `max_values`
+fn bounded_label(
+    seen: &mut HashSet<Arc<str>>,
+    value: Option<Arc<str>>,
+    max_values: usize,
+) -> Option<Arc<str>> {
+    let value = value?;
+    if seen.contains(&value) {
+        Some(value)
+    } else if seen.len() < max_values {
+        seen.insert(Arc::clone(&value));
+        Some(value)
+    } else {
+        Some(OVERFLOW_LABEL.into())
+    }
+}
 
 pub(crate) struct QueryLogger {
-    per_id_metrics: BTreeMap<QueryId, QueryMetrics>,
-    per_query_metrics: HashMap<Arc<SqlQuery>, QueryMetrics>,
+    config: QueryLogConfig,
+    /// Metrics for each retained query, along with the number of events seen for that query
+    per_query_metrics: HashMap<MetricsKey, (QueryMetrics, u64)>,
+    /// The number of events seen for queries which aren't retained, used to decide when one of
+    /// those queries should replace a retained query. Bounded to the same size as
+    /// `per_query_metrics`.
+    candidates: HashMap<MetricsKey, u64>,
+    /// Metrics for all queries which aren't retained, for each set of connection labels
+    overflow_metrics: HashMap<ConnectionLabels, QueryMetrics>,
+    /// The distinct values of the `user` label seen so far
+    users: HashSet<Arc<str>>,
+    /// The distinct values of the `application` label seen so far
+    applications: HashSet<Arc<str>>,
+    /// Exporter for individual query execution events, if configured
+    exporter: Option<QueryEventExporter>,
 }
 
 struct QueryMetrics {
-    query: SharedString,
-    query_id: Option<SharedString>,
+    /// The labels shared by all the metrics for the query
+    labels: Vec<(&'static str, SharedString)>,
     num_keys: Counter,
     cache_misses: Counter,
     cache_keys_missed: Counter,
@@ -35,21 +136,40 @@ struct QueryHistograms {
 }
 
 impl QueryMetrics {
+    fn new(
+        query: SharedString,
+        query_id: Option<SharedString>,
+        connection_labels: &ConnectionLabels,
+    ) -> Self {
+        let mut labels = vec![("query", query)];
+        if let Some(id) = query_id {
+            labels.push(("query_id", id));
+        }
+        if let Some(user) = &connection_labels.user {
+            labels.push(("user", SharedString::from(user.to_string())));
+        }
+        if let Some(application) = &connection_labels.application {
+            labels.push(("application", SharedString::from(application.to_string())));
+        }
+
+        QueryMetrics {
+            num_keys: register_counter!(recorded::QUERY_LOG_TOTAL_KEYS_READ, &labels),
+            cache_misses: register_counter!(recorded::QUERY_LOG_QUERY_CACHE_MISSED, &labels),
+            cache_keys_missed: register_counter!(recorded::QUERY_LOG_TOTAL_CACHE_MISSES, &labels),
+            labels,
+            histograms: BTreeMap::new(),
+        }
+    }
+
     fn parse_histogram(&mut self, kind: (EventType, SqlQueryType)) -> &mut Histogram {
         self.histograms
             .entry(kind)
             .or_default()
             .parse_time
             .get_or_insert_with(|| {
-                let mut labels = vec![
-                    ("query", self.query.clone()),
-                    ("event_type", SharedString::from(kind.0)),
-                    ("query_type", SharedString::from(kind.1)),
-                ];
-
-                if let Some(id) = &self.query_id {
-                    labels.push(("query_id", id.clone()));
-                }
+                let mut labels = self.labels.clone();
+                labels.push(("event_type", SharedString::from(kind.0)));
+                labels.push(("query_type", SharedString::from(kind.1)));
 
                 register_histogram!(recorded::QUERY_LOG_PARSE_TIME, &labels)
             })
@@ -61,16 +181,10 @@ impl QueryMetrics {
             .or_default()
             .readyset_exe_time
             .get_or_insert_with(|| {
-                let mut labels = vec![
-                    ("query", self.query.clone()),
-                    ("event_type", SharedString::from(kind.0)),
-                    ("query_type", SharedString::from(kind.1)),
-                    ("database_type", SharedString::from(DatabaseType::ReadySet)),
-                ];
-
-                if let Some(id) = &self.query_id {
-                    labels.push(("query_id", id.clone()));
-                }
+                let mut labels = self.labels.clone();
+                labels.push(("event_type", SharedString::from(kind.0)));
+                labels.push(("query_type", SharedString::from(kind.1)));
+                labels.push(("database_type", SharedString::from(DatabaseType::ReadySet)));
 
                 register_histogram!(recorded::QUERY_LOG_EXECUTION_TIME, &labels)
             })
@@ -82,16 +196,10 @@ impl QueryMetrics {
             .or_default()
             .upstream_exe_time
             .get_or_insert_with(|| {
-                let mut labels = vec![
-                    ("query", self.query.clone()),
-                    ("event_type", SharedString::from(kind.0)),
-                    ("query_type", SharedString::from(kind.1)),
-                    ("database_type", SharedString::from(DatabaseType::MySql)),
-                ];
-
-                if let Some(id) = &self.query_id {
-                    labels.push(("query_id", id.clone()));
-                }
+                let mut labels = self.labels.clone();
+                labels.push(("event_type", SharedString::from(kind.0)));
+                labels.push(("query_type", SharedString::from(kind.1)));
+                labels.push(("database_type", SharedString::from(DatabaseType::MySql)));
 
                 register_histogram!(recorded::QUERY_LOG_EXECUTION_TIME, &labels)
             })
@@ -99,8 +207,26 @@ impl QueryMetrics {
 }
 
 impl QueryLogger {
+    fn new(config: QueryLogConfig) -> Self {
+        QueryLogger {
+            config,
+            per_query_metrics: HashMap::new(),
+            candidates: HashMap::new(),
+            overflow_metrics: HashMap::new(),
+            users: HashSet::new(),
+            applications: HashSet::new(),
+            exporter: None,
+        }
+    }
+
     fn query_string(query: &SqlQuery) -> SharedString {
-        SharedString::from(match query {
+        SharedString::from(Self::anonymized_query(query))
+    }
+
+    /// Returns the text of the given query with all literals anonymized, or an empty string if
+    /// the query isn't a `SELECT`
+    fn anonymized_query(query: &SqlQuery) -> String {
+        match query {
             SqlQuery::Select(stmt) => {
                 let mut stmt = stmt.clone();
                 if readyset_adapter::rewrite::process_query(&mut stmt, true).is_ok() {
@@ -112,74 +238,170 @@ impl QueryLogger {
                 }
             }
             _ => "".to_string(),
-        })
-    }
-
-    fn metrics_for_id(&mut self, query_id: QueryId, query: Arc<SqlQuery>) -> &mut QueryMetrics {
-        self.per_id_metrics.entry(query_id).or_insert_with(|| {
-            let query_string = Self::query_string(&query);
-            let query_id = SharedString::from(query_id.to_string());
-
-            QueryMetrics {
-                num_keys: register_counter!(
-                    recorded::QUERY_LOG_TOTAL_KEYS_READ,
-                    "query" => query_string.clone(),
-                    "query_id" => query_id.clone(),
-                ),
-                cache_misses: register_counter!(
-                    recorded::QUERY_LOG_QUERY_CACHE_MISSED,
-                    "query" => query_string.clone(),
-                    "query_id" => query_id.clone(),
-                ),
-                cache_keys_missed: register_counter!(
-                    recorded::QUERY_LOG_TOTAL_CACHE_MISSES,
-                    "query" => query_string.clone(),
-                    "query_id" => query_id.clone(),
-                ),
-                query: query_string,
-                query_id: Some(query_id),
-                histograms: BTreeMap::new(),
+        }
+    }
+
+    /// Returns true if the given query should be retained (either because it already is, or
+    /// because there's room for it or it has become more frequent than the least frequent retained
+    /// query), evicting a retained query to make room for it if necessary.
+    fn retain(&mut self, key: &MetricsKey) -> bool {
+        let max_queries = match self.config.max_queries {
+            Some(max_queries) => max_queries,
+            None => return true,
+        };
+        if self.per_query_metrics.contains_key(key) || self.per_query_metrics.len() < max_queries {
+            return true;
+        }
+
+        let count = self.candidates.get(key).copied().unwrap_or(0) + 1;
+        let min = self
+            .per_query_metrics
+            .iter()
+            .min_by_key(|(_, (_, events))| *events)
+            .map(|(k, (_, events))| (k.clone(), *events));
+        match min {
+            Some((min_key, min_events)) if count > min_events => {
+                self.per_query_metrics.remove(&min_key);
+                self.candidates.insert(min_key, min_events);
+                true
             }
-        })
-    }
-
-    fn metrics_for_query(&mut self, query: Arc<SqlQuery>) -> &mut QueryMetrics {
-        self.per_query_metrics
-            .entry(query)
-            .or_insert_with_key(|query| {
-                let query_string = Self::query_string(query);
-
-                QueryMetrics {
-                    num_keys: register_counter!(
-                        readyset_client_metrics::recorded::QUERY_LOG_TOTAL_KEYS_READ,
-                        "query" => query_string.clone(),
-                    ),
-                    cache_misses: register_counter!(
-                        readyset_client_metrics::recorded::QUERY_LOG_QUERY_CACHE_MISSED,
-                        "query" => query_string.clone(),
-                    ),
-                    cache_keys_missed: register_counter!(
-                        readyset_client_metrics::recorded::QUERY_LOG_TOTAL_CACHE_MISSES,
-                        "query" => query_string.clone(),
-                    ),
-                    query: query_string,
-                    query_id: None,
-                    histograms: BTreeMap::new(),
+            _ => {
+                self.candidates.insert(key.clone(), count);
+                if self.candidates.len() > max_queries {
+                    if let Some(min_key) = self
+                        .candidates
+                        .iter()
+                        .min_by_key(|(_, count)| **count)
+                        .map(|(k, _)| k.clone())
+                    {
+                        self.candidates.remove(&min_key);
+                    }
                 }
-            })
+                false
+            }
+        }
+    }
+
+    /// Returns the connection labels to record for the given event, according to the config
+    fn connection_labels(&mut self, event: &QueryExecutionEvent) -> ConnectionLabels {
+        let max_values = self.config.max_label_values;
+        ConnectionLabels {
+            user: if self.config.user_labels {
+                bounded_label(&mut self.users, event.user.clone(), max_values)
+            } else {
+                None
+            },
+            application: if self.config.application_labels {
+                bounded_label(
+                    &mut self.applications,
+                    event.application.clone(),
+                    max_values,
+                )
+            } else {
+                None
+            },
+        }
+    }
+
+    fn metrics_for(&mut self, key: MetricsKey, query: &SqlQuery) -> &mut QueryMetrics {
+        if !self.retain(&key) {
+            return self
+                .overflow_metrics
+                .entry(key.1)
+                .or_insert_with_key(|labels| {
+                    QueryMetrics::new(SharedString::from(OVERFLOW_QUERY), None, labels)
+                });
+        }
+
+        let candidate_events = self.candidates.remove(&key).unwrap_or(0);
+        let (metrics, events) =
+            self.per_query_metrics
+                .entry(key)
+                .or_insert_with_key(|(key, labels)| {
+                    let query_id = match key {
+                        QueryKey::Id(id) => Some(SharedString::from(id.to_string())),
+                        QueryKey::Query(_) => None,
+                    };
+                    (
+                        QueryMetrics::new(Self::query_string(query), query_id, labels),
+                        candidate_events,
+                    )
+                });
+        *events += 1;
+        metrics
+    }
+
+    fn record(&mut self, event: QueryExecutionEvent) {
+        if let (Some(exporter), Some(query)) = (&self.exporter, &event.query) {
+            exporter.export(ExportedQueryEvent::new(
+                &event,
+                Self::anonymized_query(query),
+            ));
+        }
+
+        let query = match &event.query {
+            Some(query) => Arc::clone(query),
+            None => return,
+        };
+
+        let key = match event.query_id {
+            Some(id) => QueryKey::Id(id),
+            None => QueryKey::Query(Arc::clone(&query)),
+        };
+        let key = (key, self.connection_labels(&event));
+        let sample =
+            self.config.sample_rate >= 1.0 || rand::random::<f64>() < self.config.sample_rate;
+        let metrics = self.metrics_for(key, &query);
+
+        if let Some(num_keys) = event.num_keys {
+            metrics.num_keys.increment(num_keys);
+        }
+
+        if let Some(cache_misses) = event.cache_misses {
+            metrics.cache_keys_missed.increment(cache_misses);
+            if cache_misses != 0 {
+                metrics.cache_misses.increment(1);
+            }
+        }
+
+        if !sample {
+            return;
+        }
+
+        if let Some(duration) = event.parse_duration {
+            metrics
+                .parse_histogram((event.event, event.sql_type))
+                .record(duration);
+        }
+
+        if let Some(duration) = event.readyset_duration {
+            metrics
+                .readyset_histogram((event.event, event.sql_type))
+                .record(duration);
+        }
+
+        if let Some(duration) = event.upstream_duration {
+            metrics
+                .upstream_histogram((event.event, event.sql_type))
+                .record(duration);
+        }
     }
 
     /// Async task that logs query stats.
     pub(crate) async fn run(
+        config: QueryLogConfig,
         mut receiver: UnboundedReceiver<QueryExecutionEvent>,
         mut shutdown_recv: ShutdownReceiver,
     ) {
         let _span = info_span!("query-logger");
 
-        let mut logger = QueryLogger {
-            per_query_metrics: HashMap::new(),
-            per_id_metrics: BTreeMap::new(),
-        };
+        let mut logger = QueryLogger::new(config.clone());
+        if let Some(sink) = config.export {
+            match QueryEventExporter::spawn(sink) {
+                Ok(exporter) => logger.exporter = Some(exporter),
+                Err(error) => warn!(%error, "Failed to start exporting query execution events"),
+            }
+        }
 
         loop {
             select! {
@@ -202,47 +424,58 @@ impl QueryLogger {
                         }
                     };
 
-                    let query = match event.query {
-                        Some(query) => query,
-                        None => continue,
-                    };
+                    logger.record(event);
+                }
+            }
+        }
+    }
+}
 
-                    let metrics = if let Some(id) = event.query_id {
-                        logger.metrics_for_id(id, query)
-                    } else {
-                        logger.metrics_for_query(query)
-                    };
+#[cfg(test)]
+mod tests {
+    use nom_sql::{parse_query, Dialect};
 
-                    if let Some(num_keys) = event.num_keys {
-                        metrics.num_keys.increment(num_keys);
-                    }
+    use super::*;
 
-                    if let Some(cache_misses) = event.cache_misses {
-                        metrics.cache_keys_missed.increment(cache_misses);
-                        if cache_misses != 0 {
-                            metrics.cache_misses.increment(1);
-                        }
-                    }
+    #[test]
+    fn retains_most_frequent_queries() {
+        let query = parse_query(Dialect::MySQL, "SELECT * FROM t").unwrap();
+        let mut logger = QueryLogger::new(QueryLogConfig {
+            max_queries: Some(2),
+            ..Default::default()
+        });
+        let key = |id| (QueryKey::Id(QueryId::new(id)), ConnectionLabels::default());
+        let mut execute = |id| {
+            logger.metrics_for(key(id), &query);
+        };
 
-                    if let Some(duration) = event.parse_duration {
-                        metrics
-                            .parse_histogram((event.event, event.sql_type))
-                            .record(duration);
-                    }
+        execute(1);
+        execute(1);
+        execute(1);
+        execute(2);
+        // Over the limit, and not more frequent than 2 yet
+        execute(3);
+        // Now more frequent than 2, so replaces it
+        execute(3);
+        execute(4);
 
-                    if let Some(duration) = event.readyset_duration {
-                        metrics
-                            .readyset_histogram((event.event, event.sql_type))
-                            .record(duration);
-                    }
+        assert!(logger.per_query_metrics.contains_key(&key(1)));
+        assert!(!logger.per_query_metrics.contains_key(&key(2)));
+        assert_eq!(logger.per_query_metrics[&key(3)].1, 2);
+        assert!(!logger.overflow_metrics.is_empty());
+        assert_eq!(logger.candidates.get(&key(2)), Some(&1));
+        assert_eq!(logger.candidates.get(&key(4)), Some(&1));
+    }
 
-                    if let Some(duration) = event.upstream_duration {
-                        metrics
-                            .upstream_histogram((event.event, event.sql_type))
-                            .record(duration);
-                    }
-                }
-            }
-        }
+    #[test]
+    fn bounds_label_values() {
+        let mut seen = HashSet::new();
+        let mut label = |value: &str| bounded_label(&mut seen, Some(value.into()), 2);
+
+        assert_eq!(label("a").as_deref(), Some("a"));
+        assert_eq!(label("b").as_deref(), Some("b"));
+        assert_eq!(label("c").as_deref(), Some(OVERFLOW_LABEL));
+        assert_eq!(label("a").as_deref(), Some("a"));
+        assert_eq!(bounded_label(&mut seen, None, 2), None);
     }
 }
@@ -0,0 +1,270 @@
+//! Detection of failovers of the upstream database.
+//!
+//! Managed databases such as Amazon RDS and Aurora fail over by promoting a replica to be the new
+//! writer and repointing the DNS name of the database's endpoint at it, while the old writer is
+//! either shut down or demoted to a read-only replica. The replicator's existing connection to the
+//! upstream database doesn't necessarily notice: a binlog or WAL stream from a demoted writer can
+//! simply stop receiving changes.
+//!
+//! To detect failovers, the [`FailoverMonitor`] periodically opens a new connection to the upstream
+//! database (resolving its address afresh) and asks the server for its identity and whether it's
+//! read-only. If the upstream database URL points at a different server than the one replication
+//! was started against, or a server which was writable has become read-only, replication is
+//! restarted once the URL points at a writable server again.
+//!
+//! MySQL binlog positions are specific to a single server, so after failing over to a different
+//! MySQL server the replicator resnapshots. PostgreSQL WAL positions are preserved when a replica
+//! is promoted, so replication resumes from where it left off as long as the replication slot was
+//! carried over to the new writer, and resnapshots otherwise.
+
+use std::fmt;
+use std::time::Duration;
+
+use database_utils::{DatabaseURL, UpstreamConfig};
+use mysql::prelude::Queryable;
+use mysql::{OptsBuilder, SslOpts};
+use mysql_async as mysql;
+use postgres_native_tls::MakeTlsConnector;
+use readyset_errors::{internal_err, ReadySetResult};
+use tokio_postgres as pgsql;
+use tracing::{debug, info};
+
+use crate::noria_adapter::pg_tls_connector;
+
+/// The identity of the server the upstream database URL points at, and whether it's read-only
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ServerStatus {
+    /// Identifies the server, changing whenever the URL points at a different server
+    server_id: String,
+    read_only: bool,
+}
+
+/// Why a failover of the upstream database was detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FailoverReason {
+    /// The upstream database URL points at a different server than the one replication was
+    /// started against
+    ServerChanged,
+    /// The server replication was started against was writable, but has become read-only, for
+    /// example because it was demoted to a replica
+    ReadOnly,
+}
+
+impl FailoverReason {
+    /// Returns the name of this reason, as used in metrics
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::ServerChanged => "server-changed",
+            Self::ReadOnly => "read-only",
+        }
+    }
+
+    /// Returns the reason the upstream database is considered to have failed over, given the
+    /// status of the server replication was started against and its current status, if it has
+    fn detect(baseline: &ServerStatus, current: &ServerStatus) -> Option<Self> {
+        if current.server_id != baseline.server_id {
+            Some(Self::ServerChanged)
+        } else if current.read_only && !baseline.read_only {
+            Some(Self::ReadOnly)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for FailoverReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How to connect to the upstream database to probe it
+enum Upstream {
+    MySQL(mysql::Opts),
+    PostgreSQL(pgsql::Config, MakeTlsConnector),
+}
+
+/// Periodically probes the upstream database to detect failovers. See the [module
+/// documentation](self) for more information.
+pub(crate) struct FailoverMonitor {
+    /// How often to probe the upstream database. Zero disables probing
+    interval: Duration,
+    upstream: Option<Upstream>,
+    /// The status of the server replication was last started against
+    baseline: Option<ServerStatus>,
+}
+
+impl FailoverMonitor {
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            upstream: None,
+            baseline: None,
+        }
+    }
+
+    /// Set the URL used to connect to the upstream database, such as after it was rotated
+    pub(crate) async fn set_upstream(
+        &mut self,
+        url: &DatabaseURL,
+        config: &UpstreamConfig,
+    ) -> ReadySetResult<()> {
+        self.upstream = Some(match url {
+            DatabaseURL::MySQL(opts) => {
+                let mut opts = opts.clone();
+                if let Some(cert_path) = config.ssl_root_cert.clone() {
+                    let ssl_opts = SslOpts::default().with_root_cert_path(Some(cert_path));
+                    opts = OptsBuilder::from_opts(opts).ssl_opts(ssl_opts).into();
+                }
+                Upstream::MySQL(opts)
+            }
+            DatabaseURL::PostgreSQL(pg_config) => {
+                Upstream::PostgreSQL(pg_config.clone(), pg_tls_connector(config).await?)
+            }
+        });
+        Ok(())
+    }
+
+    /// Open a new connection to the upstream database, and ask the server for its status
+    async fn probe(&self) -> ReadySetResult<ServerStatus> {
+        match &self.upstream {
+            None => Err(internal_err!(
+                "Upstream database to probe for failover not set"
+            )),
+            Some(Upstream::MySQL(opts)) => {
+                let mut conn = mysql::Conn::new(opts.clone()).await?;
+                // Aurora MySQL readers have `innodb_read_only` set rather than `read_only`
+                let status = conn
+                    .query_first::<(String, bool), _>(
+                        "SELECT @@GLOBAL.server_uuid, \
+                         @@GLOBAL.read_only OR @@GLOBAL.innodb_read_only",
+                    )
+                    .await;
+                let _ = conn.disconnect().await;
+                let (server_id, read_only) =
+                    status?.ok_or_else(|| internal_err!("Server status query returned no rows"))?;
+                Ok(ServerStatus {
+                    server_id,
+                    read_only,
+                })
+            }
+            Some(Upstream::PostgreSQL(pg_config, tls_connector)) => {
+                let (client, connection) = pg_config.connect(tls_connector.clone()).await?;
+                let connection_handle = tokio::spawn(connection);
+                // A promoted replica has the same system identifier as the server it replaced, so
+                // tell servers apart by their address and start time instead
+                let row = client
+                    .query_one(
+                        "SELECT concat(inet_server_addr(), '@', pg_postmaster_start_time()), \
+                         pg_is_in_recovery()",
+                        &[],
+                    )
+                    .await;
+                connection_handle.abort();
+                let row = row?;
+                Ok(ServerStatus {
+                    server_id: row.try_get(0)?,
+                    read_only: row.try_get(1)?,
+                })
+            }
+        }
+    }
+
+    /// Wait until the upstream database URL points at a writable server (unless replication was
+    /// last started against a read-only server, such as a replica of the upstream database), and
+    /// record it as the server replication is being started against.
+    ///
+    /// Returns true if the URL points at a different server than the one replication was last
+    /// started against. If the upstream database can't be probed, returns false immediately,
+    /// leaving replication to report the error.
+    pub(crate) async fn wait_for_writer(&mut self) -> bool {
+        if self.interval.is_zero() {
+            return false;
+        }
+
+        loop {
+            match self.probe().await {
+                Ok(status)
+                    if status.read_only
+                        && matches!(&self.baseline, Some(baseline) if !baseline.read_only) =>
+                {
+                    info!(
+                        server_id = %status.server_id,
+                        "Upstream database is read-only, waiting for failover to complete"
+                    );
+                    tokio::time::sleep(self.interval).await;
+                }
+                Ok(status) => {
+                    let changed = matches!(
+                        &self.baseline,
+                        Some(baseline) if baseline.server_id != status.server_id
+                    );
+                    self.baseline = Some(status);
+                    return changed;
+                }
+                Err(error) => {
+                    debug!(%error, "Could not probe upstream database for failover");
+                    return false;
+                }
+            }
+        }
+    }
+
+    /// Resolves once a failover of the upstream database is detected. Never resolves if probing is
+    /// disabled, or replication was started without a successful probe of the upstream database.
+    pub(crate) async fn wait_for_failover(&self) -> FailoverReason {
+        let baseline = match &self.baseline {
+            Some(baseline) if !self.interval.is_zero() => baseline,
+            _ => return futures::future::pending().await,
+        };
+
+        loop {
+            tokio::time::sleep(self.interval).await;
+            match self.probe().await {
+                Ok(status) => {
+                    if let Some(reason) = FailoverReason::detect(baseline, &status) {
+                        return reason;
+                    }
+                }
+                // Replication itself will fail if the upstream database is unreachable
+                Err(error) => debug!(%error, "Could not probe upstream database for failover"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(server_id: &str, read_only: bool) -> ServerStatus {
+        ServerStatus {
+            server_id: server_id.into(),
+            read_only,
+        }
+    }
+
+    #[test]
+    fn detect_failover() {
+        let writer = status("a", false);
+        assert_eq!(FailoverReason::detect(&writer, &status("a", false)), None);
+        assert_eq!(
+            FailoverReason::detect(&writer, &status("b", false)),
+            Some(FailoverReason::ServerChanged)
+        );
+        assert_eq!(
+            FailoverReason::detect(&writer, &status("a", true)),
+            Some(FailoverReason::ReadOnly)
+        );
+    }
+
+    #[test]
+    fn replicating_from_a_replica_is_not_a_failover() {
+        let replica = status("a", true);
+        assert_eq!(FailoverReason::detect(&replica, &status("a", true)), None);
+        assert_eq!(
+            FailoverReason::detect(&replica, &status("b", true)),
+            Some(FailoverReason::ServerChanged)
+        );
+    }
+}
@@ -7,9 +7,11 @@
     let_chains
 )]
 pub mod db_util;
+pub(crate) mod failover;
 pub(crate) mod mysql_connector;
 pub(crate) mod noria_adapter;
 pub(crate) mod postgres_connector;
+pub(crate) mod standby;
 pub(crate) mod table_filter;
 
 use std::time::Duration;
@@ -10,7 +10,7 @@ use mysql_async as mysql;
 use mysql_common::binlog;
 use mysql_common::binlog::row::BinlogRow;
 use mysql_common::binlog::value::BinlogValue;
-use nom_sql::Relation;
+use nom_sql::{Relation, SqlQuery, TruncateTable};
 use readyset_client::metrics::recorded;
 use readyset_client::recipe::ChangeList;
 use readyset_client::replication::ReplicationOffset;
@@ -18,11 +18,14 @@ use readyset_data::{DfValue, Dialect};
 use readyset_errors::{internal_err, ReadySetError, ReadySetResult};
 use tracing::{info, warn};
 
+use super::gtid::GtidSet;
 use super::BinlogPosition;
 use crate::noria_adapter::{Connector, ReplicationAction};
 
 const CHECKSUM_QUERY: &str = "SET @master_binlog_checksum='CRC32'";
 const DEFAULT_SERVER_ID: u32 = u32::MAX - 55;
+/// The position of the first event in every binlog file, after the magic number
+const BINLOG_START_POSITION: u32 = 4;
 
 /// A connector that connects to a MySQL server and starts reading binlogs from a given position.
 ///
@@ -38,7 +41,13 @@ const DEFAULT_SERVER_ID: u32 = u32::MAX - 55;
 /// * `REPLICATION CLIENT` - to use SHOW MASTER STATUS, SHOW SLAVE STATUS, and SHOW BINARY LOGS;
 ///
 /// The connector must also be assigned a unique `server_id` value
+///
+/// If the server has `gtid_mode` set to `ON`, the connector keeps track of the set of GTIDs of all
+/// transactions it has seen committed, and uses that set to resume the binlog if the connection to
+/// the server is lost.
 pub(crate) struct MySqlBinlogConnector {
+    /// The options used to connect to the MySQL server, kept around to be able to reconnect
+    mysql_opts: mysql::Opts,
     /// This is the underlying (regular) MySQL connection
     connection: mysql::Conn,
     /// Reader is a decoder for binlog events
@@ -51,6 +60,16 @@ pub(crate) struct MySqlBinlogConnector {
     /// The GTID of the current transaction. Table modification events will have
     /// the current GTID attached if enabled in mysql.
     current_gtid: Option<u64>,
+    /// The source identifier and transaction number of the GTID of the current transaction, which
+    /// is added to `gtid_set` once the transaction commits
+    pending_gtid: Option<([u8; 16], u64)>,
+    /// The set of GTIDs of all transactions we've seen committed in the binlog, if the server has
+    /// GTIDs enabled
+    gtid_set: Option<GtidSet>,
+    /// If set, we started reading the binlog from the beginning of the binlog file rather than
+    /// from the position we were asked to start at, in order to reconstruct `gtid_set`. Events up
+    /// to this position have already been applied, and are skipped.
+    skip_until: Option<u32>,
     /// Whether to log statements received by the connector
     enable_statement_logging: bool,
 }
@@ -182,6 +201,88 @@ impl MySqlBinlogConnector {
         Ok(())
     }
 
+    /// Request the binlog starting after the set of GTIDs we've already seen committed, rather than
+    /// at a file and position
+    async fn request_binlog_gtid(&mut self, gtid_set: &GtidSet) -> mysql::Result<()> {
+        info!(%gtid_set, "Resuming binlog replication from GTID set");
+        let cmd = mysql_common::packets::ComBinlogDumpGtid::new(self.server_id())
+            .with_sids(gtid_set.to_sids());
+
+        self.connection.write_command(&cmd).await?;
+        self.connection.read_packet().await?;
+        Ok(())
+    }
+
+    /// Returns true if the server has GTIDs enabled for all transactions
+    async fn gtid_mode_enabled(&mut self) -> bool {
+        // `gtid_mode` doesn't exist on servers that don't support GTIDs, so treat any error as
+        // GTIDs being disabled
+        matches!(
+            self.connection
+                .query_first::<String, _>("SELECT @@GLOBAL.gtid_mode")
+                .await,
+            Ok(Some(mode)) if mode.eq_ignore_ascii_case("ON")
+        )
+    }
+
+    /// Re-establish a lost connection to the server, and resume the binlog from the set of GTIDs
+    /// we've already seen committed.
+    ///
+    /// Any events for a transaction that was in progress when the connection was lost will be
+    /// sent again, but will be skipped by the adapter since their positions are not past the
+    /// replication offsets of the tables they modify.
+    async fn reconnect(&mut self) -> mysql::Result<()> {
+        let gtid_set = self.gtid_set.clone().unwrap_or_default();
+        self.connection = mysql::Conn::new(self.mysql_opts.clone()).await?;
+        self.reader = binlog::EventStreamReader::new(binlog::consts::BinlogVersion::Version4);
+        self.pending_gtid = None;
+        self.skip_until = None;
+        self.register_as_replica().await?;
+        self.request_binlog_gtid(&gtid_set).await
+    }
+
+    /// Keep track of the set of GTIDs of committed transactions, if the server has GTIDs enabled
+    fn track_gtids(&mut self, event: &binlog::events::Event) -> mysql::Result<()> {
+        use mysql_common::binlog::events;
+
+        let Some(gtid_set) = &mut self.gtid_set else {
+            return Ok(());
+        };
+
+        let committed = match event.header().event_type() {
+            Ok(EventType::PREVIOUS_GTIDS_EVENT) => {
+                // The set of GTIDs in all previous binlog files, which we might not have seen if
+                // we started reading from the middle of the binlog
+                gtid_set.extend(
+                    GtidSet::from_previous_gtids_event(event.data())
+                        .map_err(|e| mysql_async::Error::Other(Box::new(e)))?,
+                );
+                false
+            }
+            Ok(EventType::GTID_EVENT) => {
+                let ev: events::GtidEvent = event.read_event()?;
+                self.pending_gtid = Some((ev.sid(), ev.gno()));
+                false
+            }
+            // Transactions on transactional storage engines end with an XID_EVENT, DDL and
+            // transactions on non-transactional engines with a QUERY_EVENT
+            Ok(EventType::XID_EVENT) => true,
+            Ok(EventType::QUERY_EVENT) => {
+                let ev: events::QueryEvent = event.read_event()?;
+                ev.query() != "BEGIN"
+            }
+            _ => false,
+        };
+
+        if committed {
+            if let Some((sid, gno)) = self.pending_gtid.take() {
+                gtid_set.add(sid, gno);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Compute the checksum of the event and compare to the supplied checksum
     fn validate_event_checksum(event: &binlog::events::Event) -> bool {
         if let Ok(Some(BinlogChecksumAlg::BINLOG_CHECKSUM_ALG_CRC32)) =
@@ -204,15 +305,31 @@ impl MySqlBinlogConnector {
         server_id: Option<u32>,
         enable_statement_logging: bool,
     ) -> ReadySetResult<Self> {
+        let mysql_opts = mysql_opts.into();
         let mut connector = MySqlBinlogConnector {
-            connection: mysql::Conn::new(mysql_opts).await?,
+            connection: mysql::Conn::new(mysql_opts.clone()).await?,
+            mysql_opts,
             reader: binlog::EventStreamReader::new(binlog::consts::BinlogVersion::Version4),
             server_id,
             next_position,
             current_gtid: None,
+            pending_gtid: None,
+            gtid_set: None,
+            skip_until: None,
             enable_statement_logging,
         };
 
+        if connector.gtid_mode_enabled().await {
+            // To find out the set of GTIDs committed up to the position we were asked to start
+            // at, start reading from the beginning of the binlog file, which begins with the set
+            // of GTIDs in all previous files, and skip any events before the requested position
+            connector.gtid_set = Some(GtidSet::default());
+            if connector.next_position.position > BINLOG_START_POSITION {
+                connector.skip_until = Some(connector.next_position.position);
+                connector.next_position.position = BINLOG_START_POSITION;
+            }
+        }
+
         connector.register_as_replica().await?;
         connector.request_binlog().await?;
 
@@ -221,7 +338,15 @@ impl MySqlBinlogConnector {
 
     /// Get the next raw binlog event
     async fn next_event(&mut self) -> mysql::Result<binlog::events::Event> {
-        let packet = self.connection.read_packet().await?;
+        let packet = match self.connection.read_packet().await {
+            Ok(packet) => packet,
+            Err(mysql::Error::Io(error)) if self.gtid_set.is_some() => {
+                warn!(%error, "Lost connection to MySQL, reconnecting");
+                self.reconnect().await?;
+                self.connection.read_packet().await?
+            }
+            Err(e) => return Err(e),
+        };
         // TODO: byte 0 of packet should be zero, unless EOF is reached, however we should never get
         // one without the NON_BLOCKING SQL flag set
         assert_eq!(packet.first(), Some(&0));
@@ -244,6 +369,14 @@ impl MySqlBinlogConnector {
 
         loop {
             let binlog_event = self.next_event().await?;
+            self.track_gtids(&binlog_event)?;
+
+            if let Some(skip_until) = self.skip_until {
+                if binlog_event.header().log_pos() <= skip_until {
+                    continue;
+                }
+                self.skip_until = None;
+            }
 
             self.next_position.position = binlog_event.header().log_pos();
 
@@ -299,6 +432,31 @@ impl MySqlBinlogConnector {
                         _ => continue,
                     };
 
+                    // `TRUNCATE TABLE` is always logged as a statement, even with row-based
+                    // binlogging, so it arrives here rather than as a `DELETE_ROWS_EVENT`
+                    if let Ok(SqlQuery::Truncate(truncate)) =
+                        nom_sql::parse_query(nom_sql::Dialect::MySQL, ev.query())
+                    {
+                        // MySQL only allows truncating one table at a time
+                        let Some(TruncateTable { mut relation, .. }) =
+                            truncate.tables.into_iter().next()
+                        else {
+                            continue;
+                        };
+                        if relation.schema.is_none() {
+                            relation.schema = Some(schema.into());
+                        }
+
+                        return Ok((
+                            ReplicationAction::TableAction {
+                                table: relation,
+                                actions: vec![readyset_client::TableOperation::Truncate],
+                                txid: self.current_gtid,
+                            },
+                            &self.next_position,
+                        ));
+                    }
+
                     let changes = match ChangeList::from_str(&ev.query(), Dialect::DEFAULT_MYSQL) {
                         Ok(changelist) => changelist.changes,
                         Err(error) => {
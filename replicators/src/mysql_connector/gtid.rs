@@ -0,0 +1,229 @@
+//! Tracking of MySQL [GTID sets][gtids], used to resume binlog replication by the set of
+//! transactions that have already been applied rather than by binlog file and position.
+//!
+//! [gtids]: https://dev.mysql.com/doc/refman/8.0/en/replication-gtids-concepts.html
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::ops::Range;
+use std::str::FromStr;
+
+use mysql_common::packets::{GnoInterval, Sid};
+use readyset_errors::{internal_err, ReadySetError, ReadySetResult};
+
+/// The length in bytes of a source identifier (a UUID)
+const SID_LEN: usize = 16;
+
+/// A set of GTIDs, represented as a map from source identifier to a sorted list of disjoint,
+/// non-adjacent, half-open ranges of transaction numbers (GNOs) originating at that source.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct GtidSet {
+    intervals: BTreeMap<[u8; SID_LEN], Vec<Range<u64>>>,
+}
+
+impl GtidSet {
+    /// Parse a GTID set from the payload of a `PREVIOUS_GTIDS_EVENT`, which is written at the
+    /// beginning of every binlog file and contains the set of all GTIDs in previous binlog files.
+    pub(crate) fn from_previous_gtids_event(mut data: &[u8]) -> ReadySetResult<Self> {
+        fn read<const N: usize>(data: &mut &[u8]) -> ReadySetResult<[u8; N]> {
+            if data.len() < N {
+                return Err(internal_err!("Truncated PREVIOUS_GTIDS_EVENT"));
+            }
+            let (bytes, rest) = data.split_at(N);
+            *data = rest;
+            bytes
+                .try_into()
+                .map_err(|_| internal_err!("Truncated PREVIOUS_GTIDS_EVENT"))
+        }
+        fn read_u64(data: &mut &[u8]) -> ReadySetResult<u64> {
+            Ok(u64::from_le_bytes(read(data)?))
+        }
+
+        let mut res = Self::default();
+        for _ in 0..read_u64(&mut data)? {
+            let sid = read::<SID_LEN>(&mut data)?;
+            for _ in 0..read_u64(&mut data)? {
+                let start = read_u64(&mut data)?;
+                let end = read_u64(&mut data)?;
+                res.add_interval(sid, start..end);
+            }
+        }
+        Ok(res)
+    }
+
+    /// Add all the GTIDs in `other` to this set
+    pub(crate) fn extend(&mut self, other: GtidSet) {
+        for (sid, ranges) in other.intervals {
+            for r in ranges {
+                self.add_interval(sid, r);
+            }
+        }
+    }
+
+    /// Add the GTID with the given source identifier and transaction number to this set
+    pub(crate) fn add(&mut self, sid: [u8; SID_LEN], gno: u64) {
+        self.add_interval(sid, gno..(gno + 1))
+    }
+
+    fn add_interval(&mut self, sid: [u8; SID_LEN], interval: Range<u64>) {
+        if interval.is_empty() {
+            return;
+        }
+        let ranges = self.intervals.entry(sid).or_default();
+        ranges.push(interval);
+        ranges.sort_by_key(|r| r.start);
+        let mut merged: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+        for r in ranges.drain(..) {
+            match merged.last_mut() {
+                Some(last) if r.start <= last.end => last.end = last.end.max(r.end),
+                _ => merged.push(r),
+            }
+        }
+        *ranges = merged;
+    }
+
+    /// Convert this set into the representation used to request a binlog stream with
+    /// `COM_BINLOG_DUMP_GTID`
+    pub(crate) fn to_sids(&self) -> Vec<Sid<'static>> {
+        self.intervals
+            .iter()
+            .map(|(sid, ranges)| {
+                Sid::new(*sid).with_intervals(
+                    ranges
+                        .iter()
+                        .map(|r| GnoInterval::new(r.start, r.end))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+}
+
+fn fmt_sid(sid: &[u8; SID_LEN], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (i, b) in sid.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            write!(f, "-")?;
+        }
+        write!(f, "{b:02x}")?;
+    }
+    Ok(())
+}
+
+/// Formats the set in the same format used by MySQL, eg
+/// `3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5:11`
+impl Display for GtidSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (sid, ranges)) in self.intervals.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            fmt_sid(sid, f)?;
+            for r in ranges {
+                if r.end - r.start == 1 {
+                    write!(f, ":{}", r.start)?;
+                } else {
+                    write!(f, ":{}-{}", r.start, r.end - 1)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses a set in the format returned by MySQL for eg `@@GLOBAL.gtid_executed`
+impl FromStr for GtidSet {
+    type Err = ReadySetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ReadySetError::ReplicationFailed(format!("Invalid GTID set: {s}"));
+
+        let mut res = Self::default();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let mut fields = part.split(':');
+            let uuid = fields.next().ok_or_else(invalid)?.replace('-', "");
+            if uuid.len() != SID_LEN * 2 {
+                return Err(invalid());
+            }
+            let mut sid = [0u8; SID_LEN];
+            for (i, b) in sid.iter_mut().enumerate() {
+                *b = u8::from_str_radix(uuid.get(i * 2..i * 2 + 2).ok_or_else(invalid)?, 16)
+                    .map_err(|_| invalid())?;
+            }
+
+            for interval in fields {
+                let (start, end) = interval.split_once('-').unwrap_or((interval, interval));
+                let start = start.trim().parse::<u64>().map_err(|_| invalid())?;
+                let end = end.trim().parse::<u64>().map_err(|_| invalid())?;
+                res.add_interval(sid, start..(end + 1));
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SID: &str = "3e11fa47-71ca-11e1-9e33-c80aa9429562";
+
+    fn sid() -> [u8; SID_LEN] {
+        [
+            0x3e, 0x11, 0xfa, 0x47, 0x71, 0xca, 0x11, 0xe1, 0x9e, 0x33, 0xc8, 0x0a, 0xa9, 0x42,
+            0x95, 0x62,
+        ]
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let set: GtidSet = format!("{SID}:1-5:11").parse().unwrap();
+        assert_eq!(set.intervals, BTreeMap::from([(sid(), vec![1..6, 11..12])]));
+        assert_eq!(set.to_string(), format!("{SID}:1-5:11"));
+    }
+
+    #[test]
+    fn parse_empty() {
+        let set: GtidSet = "".parse().unwrap();
+        assert_eq!(set, GtidSet::default());
+        assert_eq!(set.to_string(), "");
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!("not-a-uuid:1-5".parse::<GtidSet>().is_err());
+        assert!(format!("{SID}:a-5").parse::<GtidSet>().is_err());
+    }
+
+    #[test]
+    fn add_merges_adjacent_intervals() {
+        let mut set: GtidSet = format!("{SID}:1-5:7").parse().unwrap();
+        set.add(sid(), 6);
+        assert_eq!(set.to_string(), format!("{SID}:1-7"));
+        set.add(sid(), 9);
+        assert_eq!(set.to_string(), format!("{SID}:1-7:9"));
+    }
+
+    #[test]
+    fn extend() {
+        let mut set: GtidSet = format!("{SID}:1-5").parse().unwrap();
+        set.extend(format!("{SID}:3-9:12").parse().unwrap());
+        assert_eq!(set.to_string(), format!("{SID}:1-9:12"));
+    }
+
+    #[test]
+    fn from_previous_gtids_event() {
+        let mut data = vec![];
+        data.extend(1u64.to_le_bytes());
+        data.extend(sid());
+        data.extend(2u64.to_le_bytes());
+        data.extend(1u64.to_le_bytes());
+        data.extend(6u64.to_le_bytes());
+        data.extend(11u64.to_le_bytes());
+        data.extend(12u64.to_le_bytes());
+
+        let set = GtidSet::from_previous_gtids_event(&data).unwrap();
+        assert_eq!(set.to_string(), format!("{SID}:1-5:11"));
+
+        assert!(GtidSet::from_previous_gtids_event(&data[..20]).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 mod connector;
+mod gtid;
 mod snapshot;
 
 use std::fmt::{self, Display};
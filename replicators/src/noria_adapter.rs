@@ -25,23 +25,30 @@ use readyset_errors::{
     internal_err, invalid_err, set_failpoint_return_err, ReadySetError, ReadySetResult,
 };
 use readyset_telemetry_reporter::{TelemetryBuilder, TelemetryEvent, TelemetrySender};
+use readyset_util::redacted::RedactedString;
 use readyset_util::select;
 use tokio::sync::Notify;
+use tokio::task::JoinHandle;
 use tracing::{debug, error, info, info_span, trace, warn, Instrument};
 use {mysql_async as mysql, tokio_postgres as pgsql};
 
 use crate::db_util::{CreateSchema, DatabaseSchemas};
+use crate::failover::FailoverMonitor;
 use crate::mysql_connector::{MySqlBinlogConnector, MySqlReplicator};
 use crate::postgres_connector::{
     drop_publication, drop_readyset_schema, drop_replication_slot, PostgresReplicator,
     PostgresWalConnector, PUBLICATION_NAME, REPLICATION_SLOT,
 };
+use crate::standby::{StandbyConfig, StandbyShipper};
 use crate::table_filter::TableFilter;
 
 /// Time to wait for requests to coalesce between snapshotting. Useful for preventing a series of
 /// DDL changes from thrashing snapshotting
 const WAIT_BEFORE_RESNAPSHOT: Duration = Duration::from_secs(3);
 
+/// How often to try to attach a configured standby deployment that isn't currently attached
+const STANDBY_ATTACH_INTERVAL: Duration = Duration::from_secs(10);
+
 const RESNAPSHOT_SLOT: &str = "readyset_resnapshot";
 
 #[derive(Debug)]
@@ -92,17 +99,7 @@ pub async fn cleanup(config: UpstreamConfig) -> ReadySetResult<()> {
         .parse()
         .map_err(|e| invalid_err!("Invalid URL supplied to --upstream-db-url: {e}"))?
     {
-        let connector = {
-            let mut builder = native_tls::TlsConnector::builder();
-            if config.disable_upstream_ssl_verification {
-                builder.danger_accept_invalid_certs(true);
-            }
-            if let Some(root_cert) = config.get_root_cert().await {
-                builder.add_root_certificate(root_cert?);
-            }
-            builder.build().unwrap() // Never returns an error
-        };
-        let tls_connector = postgres_native_tls::MakeTlsConnector::new(connector);
+        let tls_connector = pg_tls_connector(&config).await?;
 
         let repl_slot_name = match &config.replication_server_id {
             Some(server_id) => {
@@ -156,12 +153,21 @@ pub struct NoriaAdapter {
     table_filter: TableFilter,
     /// If the connector can partially resnapshot a database
     supports_resnapshot: bool,
+    /// How to connect to the standby deployment to ship writes to, if any
+    standby_config: Option<StandbyConfig>,
+    /// The standby deployment writes are currently being shipped to, if it's attached
+    standby: Option<StandbyShipper>,
+    /// A running attempt to connect to the standby deployment
+    standby_attach: Option<JoinHandle<ReadySetResult<Option<StandbyShipper>>>>,
+    /// When we last started an attempt to connect to the standby deployment
+    last_standby_attach: Option<Instant>,
 }
 
 impl NoriaAdapter {
     pub async fn start(
         noria: ReadySetHandle,
         mut config: UpstreamConfig,
+        http_auth_token: Option<RedactedString>,
         mut notify: Option<Arc<Notify>>,
         telemetry_sender: TelemetrySender,
         server_startup: bool,
@@ -170,83 +176,122 @@ impl NoriaAdapter {
         // Resnapshot when restarting the server to apply changes that may have been made to the
         // replication-tables config parameter.
         let mut resnapshot = server_startup;
-        let url: DatabaseURL = config
-            .upstream_db_url
-            .take()
-            .ok_or_else(|| internal_err!("Replication URL not supplied"))?
-            .parse()
-            .map_err(|e| invalid_err!("Invalid URL supplied to --upstream-db-url: {e}"))?;
-
-        while let Err(err) = match url.clone() {
-            DatabaseURL::MySQL(options) => {
-                let noria = noria.clone();
-                let config = config.clone();
-                NoriaAdapter::start_inner_mysql(
-                    options,
-                    noria,
-                    config,
-                    &mut notify,
-                    resnapshot,
-                    &telemetry_sender,
-                    enable_statement_logging,
-                )
-                .await
+        // If the upstream URL is read from a secret, keep watching it so that we can reconnect with
+        // the new credentials if they're rotated
+        let mut url_secret = config.watch_upstream_db_url().await?;
+        let parse_url = |url: RedactedString| -> ReadySetResult<DatabaseURL> {
+            url.parse()
+                .map_err(|e| invalid_err!("Invalid URL supplied to --upstream-db-url: {e}"))
+        };
+        let mut url = parse_url(
+            config
+                .upstream_db_url
+                .take()
+                .ok_or_else(|| internal_err!("Replication URL not supplied"))?,
+        )?;
+        let mut failover = FailoverMonitor::new(config.failover_probe_interval);
+        failover.set_upstream(&url, &config).await?;
+        let standby_config = StandbyConfig::new(&config, http_auth_token);
+
+        loop {
+            if failover.wait_for_writer().await && matches!(url, DatabaseURL::MySQL(_)) {
+                // Binlog positions on the old server mean nothing on the new one
+                info!("Upstream database failed over to a different server, resnapshotting");
+                resnapshot = true;
             }
-            DatabaseURL::PostgreSQL(options) => {
-                let noria = noria.clone();
-                let config = config.clone();
-                let connector = {
-                    let mut builder = native_tls::TlsConnector::builder();
-                    if config.disable_upstream_ssl_verification {
-                        builder.danger_accept_invalid_certs(true);
+
+            let current_url = url.clone();
+            let replication = async {
+                match current_url {
+                    DatabaseURL::MySQL(options) => {
+                        let noria = noria.clone();
+                        let config = config.clone();
+                        NoriaAdapter::start_inner_mysql(
+                            options,
+                            noria,
+                            config,
+                            standby_config.clone(),
+                            &mut notify,
+                            resnapshot,
+                            &telemetry_sender,
+                            enable_statement_logging,
+                        )
+                        .await
                     }
-                    if let Some(root_cert) = config.get_root_cert().await {
-                        builder.add_root_certificate(root_cert?);
+                    DatabaseURL::PostgreSQL(options) => {
+                        let noria = noria.clone();
+                        let config = config.clone();
+                        let tls_connector = pg_tls_connector(&config).await?;
+                        let pool = pg_pool(
+                            options.clone(),
+                            config.replication_pool_size,
+                            tls_connector.clone(),
+                        )
+                        .await?;
+
+                        let repl_slot_name = match &config.replication_server_id {
+                            Some(server_id) => {
+                                format!("{}_{}", REPLICATION_SLOT, server_id)
+                            }
+                            _ => REPLICATION_SLOT.to_string(),
+                        };
+
+                        NoriaAdapter::start_inner_postgres(
+                            options,
+                            noria,
+                            config,
+                            standby_config.clone(),
+                            &mut notify,
+                            resnapshot,
+                            &telemetry_sender,
+                            tls_connector,
+                            pool,
+                            repl_slot_name,
+                            enable_statement_logging,
+                        )
+                        .await
                     }
-                    builder.build().unwrap() // Never returns an error
-                };
-                let tls_connector = postgres_native_tls::MakeTlsConnector::new(connector);
-                let pool = pg_pool(
-                    options.clone(),
-                    config.replication_pool_size,
-                    tls_connector.clone(),
-                )
-                .await?;
+                }
+            };
 
-                let repl_slot_name = match &config.replication_server_id {
-                    Some(server_id) => {
-                        format!("{}_{}", REPLICATION_SLOT, server_id)
-                    }
-                    _ => REPLICATION_SLOT.to_string(),
-                };
+            let rotated_url = async {
+                match &mut url_secret {
+                    Some(secret) => secret.changed().await,
+                    None => futures::future::pending().await,
+                }
+            };
 
-                NoriaAdapter::start_inner_postgres(
-                    options,
-                    noria,
-                    config,
-                    &mut notify,
-                    resnapshot,
-                    &telemetry_sender,
-                    tls_connector,
-                    pool,
-                    repl_slot_name,
-                    enable_statement_logging,
-                )
-                .await
-            }
-        } {
-            match err {
-                ReadySetError::ResnapshotNeeded => {
+            let res = select! {
+                res = replication => res,
+                new_url = rotated_url => {
+                    info!("Upstream database URL secret was rotated, reconnecting");
+                    url = parse_url(new_url)?;
+                    failover.set_upstream(&url, &config).await?;
+                    continue;
+                }
+                reason = failover.wait_for_failover() => {
+                    warn!(%reason, "Upstream database failed over, reconnecting");
+                    counter!(
+                        recorded::REPLICATOR_UPSTREAM_FAILOVER,
+                        1u64,
+                        "reason" => reason.as_str()
+                    );
+                    continue;
+                }
+            };
+
+            match res {
+                Err(ReadySetError::ResnapshotNeeded) => {
                     tokio::time::sleep(WAIT_BEFORE_RESNAPSHOT).await;
                     resnapshot = true;
                 }
-                err => {
+                Err(err) => {
                     warn!(error=%err, "Restarting adapter after error encountered");
                     return Err(err);
                 }
+                Ok(never) => never,
             }
         }
-        unreachable!("inner loop will never stop with an Ok status");
     }
 
     /// Finish the build and begin monitoring the binlog for changes
@@ -259,10 +304,12 @@ impl NoriaAdapter {
     /// * Each table is individually replicated into ReadySet
     /// * READ LOCK is released
     /// * Adapter keeps reading binlog from the next position keeping ReadySet up to date
+    #[allow(clippy::too_many_arguments)]
     async fn start_inner_mysql(
         mut mysql_options: mysql::Opts,
         mut noria: ReadySetHandle,
         mut config: UpstreamConfig,
+        standby_config: Option<StandbyConfig>,
         ready_notify: &mut Option<Arc<Notify>>,
         resnapshot: bool,
         telemetry_sender: &TelemetrySender,
@@ -415,6 +462,10 @@ impl NoriaAdapter {
             table_filter,
             supports_resnapshot: true,
             dialect: Dialect::DEFAULT_MYSQL,
+            standby_config,
+            standby: None,
+            standby_attach: None,
+            last_standby_attach: None,
         };
 
         let mut current_pos: ReplicationOffset = pos.try_into()?;
@@ -453,6 +504,7 @@ impl NoriaAdapter {
         pgsql_opts: pgsql::Config,
         mut noria: ReadySetHandle,
         mut config: UpstreamConfig,
+        standby_config: Option<StandbyConfig>,
         ready_notify: &mut Option<Arc<Notify>>,
         resnapshot: bool,
         telemetry_sender: &TelemetrySender,
@@ -578,8 +630,9 @@ impl NoriaAdapter {
                     // because only *some* tables are missing a replication offset - in that case we
                     // need to resnapshot *all* tables, because we just dropped the replication slot
                     // above, which prevents us from replicating any writes to tables we do have a
-                    // replication offset for that happened while we weren't running.
-                    /* full_snapshot = */ pos.is_none()
+                    // replication offset for that happened while we weren't running. The same
+                    // goes for when our replication slot was lost, such as after a failover.
+                    /* full_snapshot = */ pos.is_none() || connector.replication_slot.is_some()
                 ).fuse() =>  {
                     let status = if snapshot_result.is_err() {
                         SnapshotStatusTag::Failed.value()
@@ -644,6 +697,10 @@ impl NoriaAdapter {
             table_filter,
             supports_resnapshot: true,
             dialect: Dialect::DEFAULT_POSTGRESQL,
+            standby_config,
+            standby: None,
+            standby_attach: None,
+            last_standby_attach: None,
         };
 
         if min_pos != max_pos {
@@ -704,12 +761,7 @@ impl NoriaAdapter {
         if self.supports_resnapshot && changelist.changes().any(Change::requires_resnapshot) {
             // In case we detect a DDL change that requires a full schema resnapshot exit the loop
             // with the proper status
-            if let Some(pos) = self.replication_offsets.max_offset()?.cloned() {
-                // Forward all positions to the maximum position (the one prior to this statement)
-                // to avoid needless replay later
-                self.handle_log_position(pos).await?;
-            }
-            return Err(ReadySetError::ResnapshotNeeded);
+            return self.resnapshot_needed().await;
         }
 
         changelist = changelist.with_schema_search_path(vec![schema.into()]);
@@ -730,6 +782,11 @@ impl NoriaAdapter {
         {
             // ReadySet likely entered an invalid state, fail the replicator.
             Err(e @ ReadySetError::RecipeInvariantViolated(_)) => return Err(e),
+            // The controller couldn't apply an ALTER TABLE to the table's existing base node in
+            // place (eg because it drops a column that's part of the table's primary key)
+            Err(e) if self.supports_resnapshot && e.caused_by_resnapshot_needed() => {
+                return self.resnapshot_needed().await;
+            }
             Err(error) => {
                 warn!(%error, "Error extending recipe, DDL statement will not be used");
                 counter!(recorded::REPLICATOR_FAILURE, 1u64,);
@@ -747,11 +804,16 @@ impl NoriaAdapter {
                             _ => return None,
                         }))
                     }));
-                self.noria.extend_recipe(changelist).await?;
+                self.noria.extend_recipe(changelist.clone()).await?;
             }
             Ok(_) => {}
         }
 
+        if let Some(standby) = &mut self.standby {
+            let res = standby.ship_ddl_change(changelist, &tables, &pos).await;
+            self.handle_standby_result(res);
+        }
+
         self.replication_offsets.schema = Some(pos.clone());
         self.clear_mutator_cache();
 
@@ -774,6 +836,15 @@ impl NoriaAdapter {
         Ok(())
     }
 
+    /// Forward all positions to the maximum position (the one prior to the current DDL statement),
+    /// to avoid needless replay later, then return [`ReadySetError::ResnapshotNeeded`]
+    async fn resnapshot_needed(&mut self) -> ReadySetResult<()> {
+        if let Some(pos) = self.replication_offsets.max_offset()?.cloned() {
+            self.handle_log_position(pos).await?;
+        }
+        Err(ReadySetError::ResnapshotNeeded)
+    }
+
     /// Update the log position of the schema and the tables
     async fn handle_log_position(&mut self, pos: ReplicationOffset) -> ReadySetResult<()> {
         // Update the log position for the schema
@@ -801,6 +872,11 @@ impl NoriaAdapter {
 
         self.replication_offsets.advance_offset(pos.clone())?;
 
+        if let Some(standby) = &mut self.standby {
+            let res = standby.ship_log_position(&pos).await;
+            self.handle_standby_result(res);
+        }
+
         Ok(())
     }
 
@@ -812,6 +888,9 @@ impl NoriaAdapter {
         txid: Option<u64>,
         pos: ReplicationOffset,
     ) -> ReadySetResult<()> {
+        actions.push(TableOperation::SetReplicationOffset(pos.clone()));
+        let standby_actions = self.standby.is_some().then(|| actions.clone());
+
         // Send the rows as are
         let table_mutator = if let Some(table) = self.mutator_for_table(&table).await? {
             table
@@ -831,7 +910,6 @@ impl NoriaAdapter {
             }
             return Ok(());
         };
-        actions.push(TableOperation::SetReplicationOffset(pos.clone()));
         table_mutator.perform_all(actions).await?;
 
         // If there was a transaction id associated, propagate the timestamp with that transaction
@@ -845,6 +923,11 @@ impl NoriaAdapter {
             table_mutator.update_timestamp(timestamp).await?;
         }
 
+        if let (Some(standby), Some(actions)) = (&mut self.standby, standby_actions) {
+            let res = standby.ship_table_actions(&table, actions, &pos).await;
+            self.handle_standby_result(res);
+        }
+
         self.replication_offsets.tables.insert(table, Some(pos));
 
         Ok(())
@@ -940,6 +1023,8 @@ impl NoriaAdapter {
                 return Ok(());
             }
 
+            self.maybe_attach_standby(position).await;
+
             let (action, pos) = match self.connector.next_action(position, until.as_ref()).await {
                 Ok(next_action) => next_action,
                 // In some cases, we may fail to replicate because of unsupported operations, stop
@@ -950,6 +1035,10 @@ impl NoriaAdapter {
                 }
                 Err(e) => return Err(e),
             };
+            // An attempt to connect to the standby may have completed while we were waiting for
+            // the action. If so, attach the standby before applying the action, so that the action
+            // is shipped to it
+            self.maybe_attach_standby(position).await;
             *position = pos.clone();
             debug!(%position, "Received replication action");
 
@@ -978,6 +1067,73 @@ impl NoriaAdapter {
         }
     }
 
+    /// If a standby deployment is configured but not attached, start an attempt to connect to it
+    /// (at most every [`STANDBY_ATTACH_INTERVAL`]), and attach it once an attempt has completed if
+    /// it has caught up to `position`, the position of the last action applied.
+    ///
+    /// Connecting happens in the background so that an unreachable standby never blocks
+    /// replication. Since nothing wakes up the replication loop when an attempt completes, this is
+    /// called both before waiting for the next action and before applying it - otherwise the
+    /// standby would always be one action behind by the time it's checked.
+    async fn maybe_attach_standby(&mut self, position: &ReplicationOffset) {
+        let Some(standby_config) = self.standby_config.clone() else {
+            return;
+        };
+        if self.standby.is_some() {
+            return;
+        }
+        let attempt_due = self
+            .last_standby_attach
+            .map_or(true, |t| t.elapsed() >= STANDBY_ATTACH_INTERVAL);
+
+        if let Some(handle) = &self.standby_attach {
+            if !handle.is_finished() {
+                if attempt_due {
+                    // The previous attempt is taking too long; give up on it and try again later
+                    handle.abort();
+                    self.standby_attach = None;
+                }
+                return;
+            }
+        }
+
+        match self.standby_attach.take() {
+            Some(handle) => match handle.await {
+                Ok(Ok(Some(standby))) => match standby.is_caught_up(position) {
+                    Ok(true) => {
+                        info!(%position, "Shipping writes to standby deployment");
+                        self.standby = Some(standby);
+                    }
+                    Ok(false) => warn!(
+                        %position,
+                        "Standby deployment is behind the primary, and must be re-seeded"
+                    ),
+                    Err(error) => warn!(%error, "Could not attach standby deployment"),
+                },
+                Ok(Ok(None)) => {}
+                Ok(Err(error)) => warn!(%error, "Could not connect to standby deployment"),
+                Err(error) => warn!(%error, "Could not connect to standby deployment"),
+            },
+            None if attempt_due => {
+                self.last_standby_attach = Some(Instant::now());
+                self.standby_attach = Some(tokio::spawn(async move {
+                    StandbyShipper::connect(&standby_config).await
+                }));
+            }
+            None => {}
+        }
+    }
+
+    /// Detach the standby deployment if shipping a change to it failed. Since the standby has now
+    /// missed a change, it can't be attached again until it has been re-seeded.
+    fn handle_standby_result(&mut self, res: ReadySetResult<()>) {
+        if let Err(error) = res {
+            error!(%error, "Failed to ship change to standby deployment; detaching standby");
+            counter!(recorded::REPLICATOR_STANDBY_FAILURE, 1u64);
+            self.standby = None;
+        }
+    }
+
     /// When schema changes there is a risk the cached mutators will no longer be in sync
     /// and we need to drop them all
     fn clear_mutator_cache(&mut self) {
@@ -1074,6 +1230,20 @@ impl NoriaAdapter {
     }
 }
 
+/// Build a TLS connector for connections to an upstream PostgreSQL database, using the SSL
+/// settings in the given config
+pub(crate) async fn pg_tls_connector(config: &UpstreamConfig) -> ReadySetResult<MakeTlsConnector> {
+    let mut builder = native_tls::TlsConnector::builder();
+    if config.disable_upstream_ssl_verification {
+        builder.danger_accept_invalid_certs(true);
+    }
+    if let Some(root_cert) = config.get_root_cert().await {
+        builder.add_root_certificate(root_cert?);
+    }
+    let connector = builder.build().unwrap(); // Never returns an error
+    Ok(MakeTlsConnector::new(connector))
+}
+
 pub async fn pg_pool(
     config: pgsql::Config,
     pool_size: usize,
@@ -125,11 +125,34 @@ impl PostgresWalConnector {
             connector
                 .create_publication_and_slot(repl_slot_name)
                 .await?;
+        } else if !connector.replication_slot_exists(repl_slot_name).await? {
+            // Replication slots aren't carried over to a replica promoted during a failover (unless
+            // failover slots are in use), so our slot can disappear out from under us. The WAL
+            // since our replication offset is gone along with it, so start over from scratch.
+            warn!(
+                slot = repl_slot_name,
+                "Replication slot no longer exists, recreating it and resnapshotting"
+            );
+            connector.next_position = None;
+            connector
+                .create_publication_and_slot(repl_slot_name)
+                .await?;
         }
 
         Ok(connector)
     }
 
+    /// Returns true if a replication slot with the given name exists
+    async fn replication_slot_exists(&mut self, slot: &str) -> ReadySetResult<bool> {
+        let rows = self
+            .simple_query(&format!(
+                "SELECT 1 FROM pg_replication_slots WHERE slot_name = {}",
+                escape_literal(slot)
+            ))
+            .await?;
+        Ok(rows.iter().any(|m| matches!(m, SimpleQueryMessage::Row(_))))
+    }
+
     async fn create_publication_and_slot(&mut self, repl_slot_name: &str) -> ReadySetResult<()> {
         let system = self.identify_system().await?;
         debug!(
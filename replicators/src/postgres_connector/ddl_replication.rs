@@ -643,9 +643,10 @@ mod tests {
                 assert_eq!(stmt.table.name, "t");
                 assert_eq!(
                     stmt.definitions.unwrap(),
-                    vec![nom_sql::AlterTableDefinition::AddColumn(
-                        ColumnSpecification::new("y".into(), SqlType::Int(None),)
-                    ),]
+                    vec![nom_sql::AlterTableDefinition::AddColumn {
+                        spec: ColumnSpecification::new("y".into(), SqlType::Int(None)),
+                        position: None,
+                    }]
                 );
             }
             _ => panic!("Unexpected DDL event data: {:?}", ddl.data),
@@ -518,6 +518,7 @@ impl wal::TupleData {
                                     PGType::MACADDR => DfType::MacAddr,
                                     PGType::INET => DfType::Inet,
                                     PGType::UUID => DfType::Uuid,
+                                    PGType::INTERVAL => DfType::Interval,
                                     PGType::BIT => DfType::DEFAULT_BIT,
                                     PGType::VARBIT => DfType::VarBit(None),
                                     ref ty => unsupported!("Unsupported type: {ty}"),
@@ -628,8 +629,11 @@ impl wal::TupleData {
                                 | PGType::BPCHAR
                                 | PGType::MACADDR
                                 | PGType::INET
-                                | PGType::UUID
                                 | PGType::NAME => DfValue::from(str.as_ref()),
+                                PGType::UUID => DfValue::from(str.as_ref())
+                                    .coerce_to(&DfType::Uuid, &DfType::Unknown)?,
+                                PGType::INTERVAL => DfValue::from(str.as_ref())
+                                    .coerce_to(&DfType::Interval, &DfType::Unknown)?,
                                 // JSONB might rearrange the json value (like the order of the keys
                                 // in an object for example), vs
                                 // JSON that keeps the text as-is.
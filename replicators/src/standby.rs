@@ -0,0 +1,405 @@
+//! Shipping of writes to base tables from a primary ReadySet deployment to a warm standby
+//! deployment, for disaster recovery.
+//!
+//! The standby deployment is seeded with its own snapshot of the upstream database, after which it
+//! stops replicating from the upstream database (see `--standby`). From then on, every change the
+//! primary's replicator applies to its own base tables (schema changes, table writes, and updates
+//! to replication offsets) is also applied to the standby's base tables, as long as the standby
+//! hasn't already seen the replication offset of the change. Since the standby's base tables
+//! carry the same replication offsets as the primary's, promoting the standby lets it resume
+//! replicating from the upstream database exactly where the primary left off.
+//!
+//! Shipping is best-effort: a failure to ship a change to the standby never fails replication on
+//! the primary. Instead, the standby is detached, and re-attached once it has caught up with the
+//! primary by some other means (eg being re-seeded).
+
+use std::collections::{hash_map, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use database_utils::UpstreamConfig;
+use nom_sql::Relation;
+use readyset_client::consensus::AuthorityType;
+use readyset_client::recipe::changelist::ChangeList;
+use readyset_client::replication::{ReplicationOffset, ReplicationOffsets};
+use readyset_client::status::StandbyState;
+use readyset_client::{ReadySetHandle, Table, TableOperation};
+use readyset_errors::{invalid_err, ReadySetResult};
+use readyset_util::redacted::RedactedString;
+use tracing::info;
+
+/// Timeout for requests made to the standby deployment
+const STANDBY_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How to connect to the standby deployment that writes are shipped to
+#[derive(Clone, Debug)]
+pub(crate) struct StandbyConfig {
+    /// The type of authority used by the standby deployment
+    authority: AuthorityType,
+    /// The address of the standby deployment's authority
+    authority_address: String,
+    /// The name of the standby deployment
+    deployment: String,
+    /// Token to authenticate requests to the standby's controller with. Since the standby is a
+    /// copy of this deployment, it's expected to be configured with the same token.
+    http_auth_token: Option<RedactedString>,
+}
+
+impl StandbyConfig {
+    /// Returns the configuration for connecting to the standby deployment given in `config`, if
+    /// one is configured, authenticating requests to its controller with `http_auth_token`.
+    pub(crate) fn new(
+        config: &UpstreamConfig,
+        http_auth_token: Option<RedactedString>,
+    ) -> Option<Self> {
+        Some(Self {
+            authority: config.standby_authority.clone(),
+            authority_address: config.standby_authority_address.clone()?,
+            deployment: config.standby_deployment.clone()?,
+            http_auth_token,
+        })
+    }
+}
+
+/// A connection to a standby ReadySet deployment that writes are being shipped to
+pub(crate) struct StandbyShipper {
+    noria: ReadySetHandle,
+    /// The replication offsets of the standby's schema and tables, maintained as changes are
+    /// shipped to it
+    replication_offsets: ReplicationOffsets,
+    /// A map of cached table mutators for the standby's tables
+    mutator_map: HashMap<Relation, Option<Table>>,
+}
+
+impl StandbyShipper {
+    /// Connect to the standby deployment described by `config`. Connections to the standby's
+    /// controller use the internal TLS configuration of this process, if any (see
+    /// [`readyset_client::tls`]).
+    ///
+    /// Returns `None` if the standby is not yet ready to receive writes.
+    pub(crate) async fn connect(config: &StandbyConfig) -> ReadySetResult<Option<Self>> {
+        if config.authority == AuthorityType::Local {
+            return Err(invalid_err!(
+                "A local authority can't be used to connect to a standby deployment"
+            ));
+        }
+        let authority = config
+            .authority
+            .to_authority(&config.authority_address, &config.deployment)
+            .await;
+        let mut noria = ReadySetHandle::make(
+            Arc::new(authority),
+            Some(STANDBY_REQUEST_TIMEOUT),
+            None,
+            config.http_auth_token.clone(),
+        );
+
+        match noria.standby_state().await? {
+            Some(StandbyState::Standby) => {}
+            state => {
+                info!(?state, "Standby deployment not ready to receive writes");
+                return Ok(None);
+            }
+        }
+
+        let replication_offsets = noria.replication_offsets().await?;
+        info!(deployment = %config.deployment, "Connected to standby deployment");
+        Ok(Some(Self {
+            noria,
+            replication_offsets,
+            mutator_map: HashMap::new(),
+        }))
+    }
+
+    /// Returns true if the standby has already seen every change up to `position`, the replication
+    /// offset of the last change the primary has applied, so that shipping it every subsequent
+    /// change will keep it in sync with the primary.
+    pub(crate) fn is_caught_up(&self, position: &ReplicationOffset) -> ReadySetResult<bool> {
+        Ok(matches!(
+            self.replication_offsets.min_present_offset()?,
+            Some(min) if min >= position
+        ))
+    }
+
+    /// Returns true if the standby has already seen the schema change at `pos`
+    fn has_schema_offset(&self, pos: &ReplicationOffset) -> bool {
+        matches!(&self.replication_offsets.schema, Some(cur) if pos <= cur)
+    }
+
+    /// Get a mutator for a table in the standby from the cache if available, or fetch a new one
+    /// from the standby's controller and cache it. Returns None if the table doesn't exist in the
+    /// standby.
+    async fn mutator_for_table(&mut self, name: &Relation) -> ReadySetResult<Option<&mut Table>> {
+        match self.mutator_map.raw_entry_mut().from_key(name) {
+            hash_map::RawEntryMut::Occupied(o) => Ok(o.into_mut().as_mut()),
+            hash_map::RawEntryMut::Vacant(v) => match self.noria.table(name.clone()).await {
+                Ok(table) => Ok(v.insert(name.clone(), Some(table)).1.as_mut()),
+                Err(e) if e.caused_by_table_not_found() => {
+                    Ok(v.insert(name.clone(), None).1.as_mut())
+                }
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    /// Ship a change to the schema, which created the given `tables`, to the standby
+    pub(crate) async fn ship_ddl_change(
+        &mut self,
+        changelist: ChangeList,
+        tables: &[Relation],
+        pos: &ReplicationOffset,
+    ) -> ReadySetResult<()> {
+        if self.has_schema_offset(pos) {
+            return Ok(());
+        }
+
+        self.noria
+            .extend_recipe_with_offset(changelist, pos, false)
+            .await?;
+        self.replication_offsets.schema = Some(pos.clone());
+        self.mutator_map.clear();
+
+        for table in tables {
+            self.replication_offsets
+                .tables
+                .insert(table.clone(), Some(pos.clone()));
+            if let Some(mutator) = self.mutator_for_table(table).await? {
+                mutator.set_replication_offset(pos.clone()).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Ship a batch of writes to a table, which have already had a
+    /// [`TableOperation::SetReplicationOffset`] for `pos` appended to them, to the standby
+    pub(crate) async fn ship_table_actions(
+        &mut self,
+        table: &Relation,
+        actions: Vec<TableOperation>,
+        pos: &ReplicationOffset,
+    ) -> ReadySetResult<()> {
+        if matches!(self.replication_offsets.tables.get(table), Some(Some(cur)) if pos <= cur) {
+            return Ok(());
+        }
+
+        if let Some(mutator) = self.mutator_for_table(table).await? {
+            mutator.perform_all(actions).await?;
+            self.replication_offsets
+                .tables
+                .insert(table.clone(), Some(pos.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// Ship an update to the replication offset of the schema and all tables to the standby
+    pub(crate) async fn ship_log_position(
+        &mut self,
+        pos: &ReplicationOffset,
+    ) -> ReadySetResult<()> {
+        if self.has_schema_offset(pos) {
+            return Ok(());
+        }
+
+        self.noria.set_schema_replication_offset(Some(pos)).await?;
+
+        let tables = self
+            .replication_offsets
+            .tables
+            .iter()
+            .filter(|(_, v)| v.as_ref().map_or(true, |cur| cur < pos))
+            .map(|(k, _)| k.clone())
+            .collect::<Vec<_>>();
+        for table in tables {
+            if let Some(mutator) = self.mutator_for_table(&table).await? {
+                mutator.set_replication_offset(pos.clone()).await?;
+            }
+        }
+
+        self.replication_offsets.advance_offset(pos.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use readyset_data::{DfValue, Dialect};
+    use readyset_server::{Builder, DurabilityMode, Handle, PersistenceParameters};
+    use readyset_util::eventually;
+    use readyset_util::shutdown::ShutdownSender;
+
+    use super::*;
+
+    async fn start_standby() -> (Handle, ShutdownSender) {
+        readyset_tracing::init_test_logging();
+        let mut builder = Builder::for_tests();
+        builder.set_persistence(PersistenceParameters {
+            mode: DurabilityMode::DeleteOnExit,
+            ..Default::default()
+        });
+        builder.start_local().await.unwrap()
+    }
+
+    /// Returns a shipper for `standby`, as if a replicator had just connected to it
+    async fn connect_shipper(standby: &Handle) -> StandbyShipper {
+        let mut noria = ReadySetHandle::clone(standby);
+        let replication_offsets = noria.replication_offsets().await.unwrap();
+        StandbyShipper {
+            noria,
+            replication_offsets,
+            mutator_map: HashMap::new(),
+        }
+    }
+
+    fn offset(offset: u128) -> ReplicationOffset {
+        ReplicationOffset {
+            offset,
+            replication_log_name: "binlog".to_owned(),
+        }
+    }
+
+    fn changelist(sql: &str) -> ChangeList {
+        ChangeList::from_str(sql, Dialect::DEFAULT_MYSQL).unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ship_ddl_change() {
+        let (mut standby, shutdown_tx) = start_standby().await;
+        let mut shipper = connect_shipper(&standby).await;
+
+        shipper
+            .ship_ddl_change(
+                changelist("CREATE TABLE t1 (id int);"),
+                &["t1".into()],
+                &offset(1),
+            )
+            .await
+            .unwrap();
+
+        standby.table("t1").await.unwrap();
+        let offsets = standby.replication_offsets().await.unwrap();
+        assert_eq!(offsets.schema, Some(offset(1)));
+        assert_eq!(offsets.tables[&"t1".into()], Some(offset(1)));
+
+        // The standby has already seen the schema change at this offset, both according to the
+        // shipper that shipped it and according to a newly connected one, so shipping it again
+        // does nothing
+        for mut shipper in [shipper, connect_shipper(&standby).await] {
+            shipper
+                .ship_ddl_change(
+                    changelist("CREATE TABLE t2 (id int);"),
+                    &["t2".into()],
+                    &offset(1),
+                )
+                .await
+                .unwrap();
+        }
+
+        standby.table("t2").await.unwrap_err();
+        assert_eq!(
+            standby.replication_offsets().await.unwrap().schema,
+            Some(offset(1))
+        );
+
+        shutdown_tx.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ship_table_actions() {
+        let (mut standby, shutdown_tx) = start_standby().await;
+        let mut shipper = connect_shipper(&standby).await;
+
+        // No primary key, so shipping the same insert twice would duplicate the row
+        shipper
+            .ship_ddl_change(
+                changelist("CREATE TABLE t1 (id int, val int);"),
+                &["t1".into()],
+                &offset(1),
+            )
+            .await
+            .unwrap();
+        standby
+            .extend_recipe(changelist(
+                "CREATE CACHE q FROM SELECT id, val FROM t1 WHERE id = ?;",
+            ))
+            .await
+            .unwrap();
+
+        let actions = vec![
+            TableOperation::Insert(vec![1.into(), 2.into()]),
+            TableOperation::SetReplicationOffset(offset(2)),
+        ];
+        shipper
+            .ship_table_actions(&"t1".into(), actions.clone(), &offset(2))
+            .await
+            .unwrap();
+        for mut shipper in [shipper, connect_shipper(&standby).await] {
+            shipper
+                .ship_table_actions(&"t1".into(), actions.clone(), &offset(2))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            standby.replication_offsets().await.unwrap().tables[&"t1".into()],
+            Some(offset(2))
+        );
+        let mut view = standby
+            .view("q")
+            .await
+            .unwrap()
+            .into_reader_handle()
+            .unwrap();
+        eventually!(
+            run_test: { view.lookup(&[1.into()], true).await.unwrap().into_vec() },
+            then_assert: |rows| assert_eq!(rows, vec![vec![DfValue::from(1), DfValue::from(2)]])
+        );
+
+        shutdown_tx.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ship_log_position() {
+        let (mut standby, shutdown_tx) = start_standby().await;
+        let mut shipper = connect_shipper(&standby).await;
+
+        shipper
+            .ship_ddl_change(
+                changelist("CREATE TABLE t1 (id int); CREATE TABLE t2 (id int);"),
+                &["t1".into(), "t2".into()],
+                &offset(1),
+            )
+            .await
+            .unwrap();
+        shipper
+            .ship_table_actions(
+                &"t1".into(),
+                vec![
+                    TableOperation::Insert(vec![1.into()]),
+                    TableOperation::SetReplicationOffset(offset(2)),
+                ],
+                &offset(2),
+            )
+            .await
+            .unwrap();
+
+        shipper.ship_log_position(&offset(3)).await.unwrap();
+        assert!(shipper.is_caught_up(&offset(3)).unwrap());
+
+        let offsets = standby.replication_offsets().await.unwrap();
+        assert_eq!(offsets.schema, Some(offset(3)));
+        assert_eq!(offsets.tables[&"t1".into()], Some(offset(3)));
+        assert_eq!(offsets.tables[&"t2".into()], Some(offset(3)));
+
+        // Shipping the same position, or one the standby has already passed, leaves the standby's
+        // offsets where they are
+        shipper.ship_log_position(&offset(3)).await.unwrap();
+        let mut shipper = connect_shipper(&standby).await;
+        shipper.ship_log_position(&offset(3)).await.unwrap();
+        shipper.ship_log_position(&offset(2)).await.unwrap();
+
+        assert_eq!(standby.replication_offsets().await.unwrap(), offsets);
+
+        shutdown_tx.shutdown().await;
+    }
+}
@@ -11,8 +11,11 @@ use mysql_time::MySqlTime;
 use nom_sql::{parse_select_statement, Relation};
 use rand::distributions::Alphanumeric;
 use rand::{Rng, SeedableRng};
-use readyset_client::consensus::{Authority, LocalAuthority, LocalAuthorityStore};
+use readyset_client::consensus::{
+    Authority, AuthorityType, LocalAuthority, LocalAuthorityStore, StandaloneAuthority,
+};
 use readyset_client::recipe::changelist::{Change, ChangeList};
+use readyset_client::status::StandbyState;
 use readyset_client::ReadySetHandle;
 use readyset_data::{Collation, DfValue, Dialect, TinyText};
 use readyset_errors::{ReadySetError, ReadySetResult};
@@ -314,6 +317,7 @@ impl TestHandle {
                     upstream_db_url: Some(url),
                     ..config.unwrap_or_default()
                 },
+                None,
                 ready_notify.clone(),
                 telemetry_sender,
                 server_startup,
@@ -391,6 +395,7 @@ impl TestHandle {
                             .unwrap(),
                         ),
                         always: false,
+                        options: Default::default(),
                     },
                 ],
                 self.dialect,
@@ -617,6 +622,18 @@ async fn mysql_skip_unparsable() -> ReadySetResult<()> {
     replication_skip_unparsable_inner(&mysql_url()).await
 }
 
+#[tokio::test(flavor = "multi_thread")]
+#[serial_test::serial]
+async fn pgsql_replication_truncate() -> ReadySetResult<()> {
+    replication_truncate_inner(&pgsql_url()).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial_test::serial]
+async fn mysql_replication_truncate() -> ReadySetResult<()> {
+    replication_truncate_inner(&mysql_url()).await
+}
+
 #[tokio::test(flavor = "multi_thread")]
 #[serial_test::serial]
 async fn pgsql_replication_filter() -> ReadySetResult<()> {
@@ -1073,6 +1090,49 @@ async fn replication_skip_unparsable_inner(url: &str) -> ReadySetResult<()> {
     Ok(())
 }
 
+async fn replication_truncate_inner(url: &str) -> ReadySetResult<()> {
+    readyset_tracing::init_test_logging();
+    let mut client = DbConnection::connect(url).await?;
+
+    client
+        .query(
+            "
+            DROP TABLE IF EXISTS trunc_t CASCADE; CREATE TABLE trunc_t (id int PRIMARY KEY);
+            DROP VIEW IF EXISTS trunc_view; CREATE VIEW trunc_view AS SELECT * FROM trunc_t;
+            INSERT INTO trunc_t VALUES (1),(2),(3);
+            ",
+        )
+        .await?;
+
+    let (mut ctx, shutdown_tx) = TestHandle::start_noria(url.to_string(), None).await?;
+    ctx.ready_notify.as_ref().unwrap().notified().await;
+
+    ctx.check_results(
+        "trunc_view",
+        "Snapshot",
+        &[&[DfValue::Int(1)], &[DfValue::Int(2)], &[DfValue::Int(3)]],
+    )
+    .await?;
+
+    client
+        .query("TRUNCATE TABLE trunc_t; INSERT INTO trunc_t VALUES (2),(4);")
+        .await?;
+
+    ctx.check_results(
+        "trunc_view",
+        "Truncate",
+        &[&[DfValue::Int(2)], &[DfValue::Int(4)]],
+    )
+    .await?;
+
+    ctx.stop().await;
+    client.stop().await;
+
+    shutdown_tx.shutdown().await;
+
+    Ok(())
+}
+
 async fn replication_filter_inner(url: &str) -> ReadySetResult<()> {
     readyset_tracing::init_test_logging();
 
@@ -1567,6 +1627,7 @@ async fn postgresql_ddl_replicate_drop_view_internal(url: &str) {
             .unwrap(),
         ),
         always: false,
+        options: Default::default(),
     };
     ctx.noria
         .extend_recipe(ChangeList::from_change(
@@ -1642,7 +1703,8 @@ async fn postgresql_ddl_replicate_create_view_internal(url: &str) {
                     )
                     .unwrap()
                 ),
-                always: true
+                always: true,
+                options: Default::default()
             },
             Dialect::DEFAULT_POSTGRESQL
         ))
@@ -2585,3 +2647,95 @@ async fn pgsql_dont_replicate_partitioned_table() {
 
     shutdown_tx.shutdown().await;
 }
+
+/// Tests that once a standby deployment has been seeded from the upstream database, the primary
+/// deployment attaches it and ships every write it replicates to it
+async fn standby_inner(url: &str) -> ReadySetResult<()> {
+    readyset_tracing::init_test_logging();
+    let mut client = DbConnection::connect(url).await?;
+    client.query(CREATE_SCHEMA).await?;
+    client.query(POPULATE_SCHEMA).await?;
+
+    // The primary connects to the standby through the standby's authority, so use one that can
+    // be shared by deployments within the same process
+    let authority_dir = tempfile::tempdir().unwrap();
+    let authority_address = authority_dir.path().to_str().unwrap().to_owned();
+    let standby_authority = Arc::new(Authority::from(
+        StandaloneAuthority::new(&authority_address, "standby").unwrap(),
+    ));
+
+    let mut builder = Builder::for_tests();
+    builder.set_persistence(readyset_server::PersistenceParameters {
+        mode: readyset_server::DurabilityMode::DeleteOnExit,
+        ..Default::default()
+    });
+    builder.set_replicator_config(Config {
+        upstream_db_url: Some(url.to_owned().into()),
+        replication_server_id: Some(2),
+        standby: true,
+        ..Default::default()
+    });
+    let (noria, standby_shutdown_tx) = builder.start(Arc::clone(&standby_authority)).await.unwrap();
+    let mut standby = TestHandle {
+        url: url.to_owned(),
+        dialect: Dialect::DEFAULT_MYSQL,
+        noria,
+        authority: standby_authority,
+        replication_rt: None,
+        ready_notify: None,
+    };
+    eventually! {
+        matches!(
+            standby.noria.standby_state().await,
+            Ok(Some(StandbyState::Standby))
+        )
+    }
+    standby
+        .check_results("noria_view", "Standby snapshot", SNAPSHOT_RESULT)
+        .await?;
+
+    let (mut primary, primary_shutdown_tx) = TestHandle::start_noria(
+        url.to_owned(),
+        Some(Config {
+            replication_server_id: Some(1),
+            standby_authority_address: Some(authority_address),
+            standby_deployment: Some("standby".into()),
+            standby_authority: AuthorityType::Standalone,
+            ..Default::default()
+        }),
+    )
+    .await?;
+    primary.ready_notify.as_ref().unwrap().notified().await;
+    primary
+        .check_results("noria_view", "Snapshot", SNAPSHOT_RESULT)
+        .await?;
+
+    // The primary starts connecting to the standby once it starts replicating. Give it time to
+    // connect, so that the standby is attached by the time the primary receives the first write.
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    for (test_name, test_query, test_results) in TESTS {
+        client.query(test_query).await?;
+        primary
+            .check_results("noria_view", test_name, test_results)
+            .await?;
+        standby
+            .check_results("noria_view", test_name, test_results)
+            .await?;
+    }
+
+    client.stop().await;
+    primary.stop().await;
+    tokio::join!(
+        primary_shutdown_tx.shutdown(),
+        standby_shutdown_tx.shutdown()
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial_test::serial]
+async fn mysql_standby() -> ReadySetResult<()> {
+    standby_inner(&mysql_url()).await
+}